@@ -232,6 +232,9 @@ impl<'a> Reader for ZBufReader<'a> {
         let slice = self.inner.slices.get(self.cursor.slice).ok_or(DidntRead)?;
         match (slice.len() - self.cursor.byte).cmp(&len) {
             cmp::Ordering::Less => {
+                if len > self.remaining() {
+                    return Err(DidntRead);
+                }
                 let mut buffer = crate::vec::uninit(len);
                 self.read_exact(&mut buffer)?;
                 Ok(buffer.into())
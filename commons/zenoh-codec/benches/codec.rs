@@ -78,6 +78,7 @@ fn criterion_benchmark(c: &mut Criterion) {
         reliability: Reliability::default(),
         sn: TransportSn::MIN,
         ext_qos: zenoh_protocol::transport::frame::ext::QoSType::default(),
+        compressed: false,
     };
 
     let data = Push {
@@ -91,6 +92,8 @@ fn criterion_benchmark(c: &mut Criterion) {
             ext_sinfo: None,
             #[cfg(feature = "shared-memory")]
             ext_shm: None,
+            ext_integrity: None,
+            ext_batch: None,
             ext_unknown: vec![],
             payload: ZBuf::from(vec![0u8; 8]),
         }),
@@ -123,6 +126,7 @@ fn criterion_benchmark(c: &mut Criterion) {
         reliability: Reliability::default(),
         sn: TransportSn::MIN,
         ext_qos: zenoh_protocol::transport::frame::ext::QoSType::default(),
+        compressed: false,
     };
 
     let data = Push {
@@ -136,6 +140,8 @@ fn criterion_benchmark(c: &mut Criterion) {
             ext_sinfo: None,
             #[cfg(feature = "shared-memory")]
             ext_shm: None,
+            ext_integrity: None,
+            ext_batch: None,
             ext_unknown: vec![],
             payload: ZBuf::from(vec![0u8; 8]),
         }),
@@ -163,6 +169,7 @@ fn criterion_benchmark(c: &mut Criterion) {
         reliability: Reliability::default(),
         sn: TransportSn::MIN,
         ext_qos: zenoh_protocol::transport::frame::ext::QoSType::default(),
+        compressed: false,
     };
 
     let data = Push {
@@ -176,6 +183,8 @@ fn criterion_benchmark(c: &mut Criterion) {
             ext_sinfo: None,
             #[cfg(feature = "shared-memory")]
             ext_shm: None,
+            ext_integrity: None,
+            ext_batch: None,
             ext_unknown: vec![],
             payload: ZBuf::from(vec![0u8; 8]),
         }),
@@ -216,6 +225,8 @@ fn criterion_benchmark(c: &mut Criterion) {
             ext_sinfo: None,
             #[cfg(feature = "shared-memory")]
             ext_shm: None,
+            ext_integrity: None,
+            ext_batch: None,
             ext_unknown: vec![],
             payload: ZBuf::from(vec![0u8; 1_000_000]),
         }),
@@ -243,6 +254,8 @@ fn criterion_benchmark(c: &mut Criterion) {
             ext_sinfo: None,
             #[cfg(feature = "shared-memory")]
             ext_shm: None,
+            ext_integrity: None,
+            ext_batch: None,
             ext_unknown: vec![],
             payload: ZBuf::from(vec![0u8; 1_000_000]),
         }),
@@ -281,6 +294,8 @@ fn criterion_benchmark(c: &mut Criterion) {
             ext_sinfo: None,
             #[cfg(feature = "shared-memory")]
             ext_shm: None,
+            ext_integrity: None,
+            ext_batch: None,
             ext_unknown: vec![],
             payload: ZBuf::from(vec![0u8; 1_000_000]),
         }),
@@ -0,0 +1,38 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! Regenerates the golden files checked by the `golden` test.
+//!
+//! Only run it for an intentional change of the wire format, and review the diff of the files.
+//! See `tests/golden/README.md`.
+#[path = "../tests/golden/vectors.rs"]
+mod vectors;
+
+use std::fs;
+
+fn main() {
+    let dir = vectors::dir();
+    for entry in fs::read_dir(&dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().map_or(false, |e| e == "hex") {
+            fs::remove_file(&path).unwrap();
+        }
+    }
+
+    for vector in vectors::vectors() {
+        let path = vector.path();
+        fs::write(&path, vector.to_file()).unwrap();
+        println!("{}", path.display());
+    }
+}
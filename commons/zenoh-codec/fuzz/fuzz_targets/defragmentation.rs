@@ -0,0 +1,31 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use zenoh_codec::fuzzing::BatchDecoder;
+
+// Keep the defragmentation buffer small so that the limit is actually hit
+const DEFRAG_BUFF_SIZE: usize = 64 * 1024;
+
+fuzz_target!(|data: &[u8]| {
+    // The first two bytes select how the input is split into batches
+    if data.len() < 2 {
+        return;
+    }
+    let batch_size = usize::from(u16::from_le_bytes([data[0], data[1]])).max(1);
+    let mut decoder = BatchDecoder::new(DEFRAG_BUFF_SIZE);
+    for batch in data[2..].chunks(batch_size) {
+        let _ = decoder.decode(batch);
+    }
+});
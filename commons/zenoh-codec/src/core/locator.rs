@@ -67,7 +67,11 @@ where
     type Error = DidntRead;
 
     fn read(self, reader: &mut R) -> Result<Vec<Locator>, Self::Error> {
-        let len = self.read(&mut *reader)?;
+        let len: usize = self.read(&mut *reader)?;
+        // Each locator takes at least one byte
+        if len > reader.remaining() {
+            return Err(DidntRead);
+        }
         let mut vec: Vec<Locator> = Vec::with_capacity(len);
         for _ in 0..len {
             vec.push(self.read(&mut *reader)?);
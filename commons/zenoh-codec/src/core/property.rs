@@ -70,6 +70,10 @@ where
 
     fn read(self, reader: &mut R) -> Result<Vec<Property>, Self::Error> {
         let num: usize = self.read(&mut *reader)?;
+        // Each property takes at least one byte
+        if num > reader.remaining() {
+            return Err(DidntRead);
+        }
 
         let mut ps = Vec::with_capacity(num);
         for _ in 0..num {
@@ -0,0 +1,108 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! Entry points running the rx decoding paths on raw bytes, without any link.
+//!
+//! They are meant to be used by fuzzers and never panic on malformed input.
+use crate::{RCodec, Zenoh080, Zenoh080Reliability};
+use alloc::vec::Vec;
+use zenoh_buffers::{
+    reader::{DidntRead, HasReader, Reader},
+    SplitBuffer, ZBuf,
+};
+use zenoh_protocol::{
+    core::Reliability,
+    network::NetworkMessage,
+    transport::{TransportBody, TransportMessage},
+};
+
+/// The default capacity of the defragmentation buffer, matching the default maximum message size.
+pub const DEFAULT_DEFRAG_BUFF_SIZE: usize = 1_073_741_824;
+
+/// Decodes all the [`TransportMessage`]s contained in a batch, as received on a link.
+pub fn decode_transport_batch(bytes: &[u8]) -> Result<Vec<TransportMessage>, DidntRead> {
+    let codec = Zenoh080::new();
+    let mut reader = bytes.reader();
+    let mut msgs = Vec::new();
+    while reader.can_read() {
+        let msg: TransportMessage = codec.read(&mut reader)?;
+        msgs.push(msg);
+    }
+    Ok(msgs)
+}
+
+/// Decodes a single [`NetworkMessage`], as contained in a frame or a defragmented buffer.
+pub fn decode_network_message(bytes: &[u8]) -> Result<NetworkMessage, DidntRead> {
+    let codec = Zenoh080::new();
+    let mut reader = bytes.reader();
+    codec.read(&mut reader)
+}
+
+/// Decodes a sequence of batches into [`NetworkMessage`]s, reassembling the fragments
+/// like the transport does.
+///
+/// The defragmented messages can't exceed `defrag_buff_size` bytes: bigger messages are
+/// dropped and reported as an error.
+#[derive(Debug)]
+pub struct BatchDecoder {
+    defrag_buff_size: usize,
+    reliable: ZBuf,
+    best_effort: ZBuf,
+}
+
+impl BatchDecoder {
+    pub fn new(defrag_buff_size: usize) -> Self {
+        Self {
+            defrag_buff_size,
+            reliable: ZBuf::empty(),
+            best_effort: ZBuf::empty(),
+        }
+    }
+
+    /// Decodes a batch, returning the complete [`NetworkMessage`]s it contains.
+    pub fn decode(&mut self, bytes: &[u8]) -> Result<Vec<NetworkMessage>, DidntRead> {
+        let mut msgs = Vec::new();
+        for tmsg in decode_transport_batch(bytes)? {
+            match tmsg.body {
+                TransportBody::Frame(frame) => msgs.extend(frame.payload),
+                TransportBody::Fragment(fragment) => {
+                    let buffer = match fragment.reliability {
+                        Reliability::Reliable => &mut self.reliable,
+                        Reliability::BestEffort => &mut self.best_effort,
+                    };
+                    if buffer.len() + fragment.payload.len() > self.defrag_buff_size {
+                        buffer.clear();
+                        return Err(DidntRead);
+                    }
+                    buffer.push_zslice(fragment.payload);
+                    if !fragment.more {
+                        let mut reader = buffer.reader();
+                        let codec = Zenoh080Reliability::new(fragment.reliability);
+                        let res: Result<NetworkMessage, DidntRead> = codec.read(&mut reader);
+                        buffer.clear();
+                        msgs.push(res?);
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(msgs)
+    }
+}
+
+impl Default for BatchDecoder {
+    fn default() -> Self {
+        Self::new(DEFAULT_DEFRAG_BUFF_SIZE)
+    }
+}
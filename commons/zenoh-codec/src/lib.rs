@@ -22,6 +22,8 @@ extern crate alloc;
 
 pub mod common;
 pub mod core;
+#[cfg(feature = "fuzzing")]
+pub mod fuzzing;
 pub mod network;
 pub mod scouting;
 pub mod transport;
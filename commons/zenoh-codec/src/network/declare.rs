@@ -11,25 +11,34 @@
 // Contributors:
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
-use crate::{common::extension, RCodec, WCodec, Zenoh080, Zenoh080Condition, Zenoh080Header};
-use alloc::string::String;
+use crate::{
+    common::extension, LCodec, RCodec, WCodec, Zenoh080, Zenoh080Bounded, Zenoh080Condition,
+    Zenoh080Header,
+};
+use alloc::{string::String, vec::Vec};
+use core::num::NonZeroUsize;
 use zenoh_buffers::{
-    reader::{DidntRead, Reader},
+    reader::{DidntRead, HasReader, Reader},
     writer::{DidntWrite, HasWriter, Writer},
-    ZBuf,
+    ZBuf, ZSlice,
 };
 use zenoh_protocol::{
-    common::{iext, imsg, ZExtZ64},
+    common::{iext, imsg, ZExtZ64, ZExtZBufHeader},
     core::{ExprId, ExprLen, WireExpr},
     network::{
         declare::{
-            self, common, interest, keyexpr, queryable, subscriber, token, Declare, DeclareBody,
+            self, common, interest, keyexpr, prefix, queryable, subscriber, token, Declare,
+            DeclareBody,
         },
-        id, Mapping,
+        id, Mapping, UnknownKind,
     },
 };
 
 // Declaration
+//
+// A declaration is framed by the length of its body, following its header, for the nodes not
+// knowing its kind to skip it. The declaration is encoded twice directly into writers, once to
+// count the length of its body and once to write it, not to buffer it.
 impl<W> WCodec<&DeclareBody, &mut W> for Zenoh080
 where
     W: Writer,
@@ -37,6 +46,24 @@ where
     type Output = Result<(), DidntWrite>;
 
     fn write(self, writer: &mut W, x: &DeclareBody) -> Self::Output {
+        // The header, then the length of the rest of the declaration
+        let mut counter = LengthCounter::default();
+        self.write_declaration(&mut counter, x)?;
+        let header = counter.header.ok_or(DidntWrite)?;
+        self.write(&mut *writer, header)?;
+        let bodec = Zenoh080Bounded::<u32>::new();
+        bodec.write(&mut *writer, counter.len - 1)?;
+
+        let mut writer = SkipWriter { writer, skip: 1 };
+        self.write_declaration(&mut writer, x)
+    }
+}
+
+impl Zenoh080 {
+    fn write_declaration<W>(self, writer: &mut W, x: &DeclareBody) -> Result<(), DidntWrite>
+    where
+        W: Writer,
+    {
         match x {
             DeclareBody::DeclareKeyExpr(r) => self.write(&mut *writer, r)?,
             DeclareBody::UndeclareKeyExpr(r) => self.write(&mut *writer, r)?,
@@ -49,10 +76,104 @@ where
             DeclareBody::DeclareInterest(r) => self.write(&mut *writer, r)?,
             DeclareBody::FinalInterest(r) => self.write(&mut *writer, r)?,
             DeclareBody::UndeclareInterest(r) => self.write(&mut *writer, r)?,
+            DeclareBody::DeclareKeyExprs(r) => self.write(&mut *writer, r)?,
+            DeclareBody::RejectKeyExprs(r) => self.write(&mut *writer, r)?,
         }
+        Ok(())
+    }
+}
+
+// Counts the bytes of an encoding, keeping its first byte, without writing it.
+#[derive(Default)]
+struct LengthCounter {
+    header: Option<u8>,
+    len: usize,
+}
+
+impl Writer for LengthCounter {
+    fn write(&mut self, bytes: &[u8]) -> Result<NonZeroUsize, DidntWrite> {
+        self.write_exact(bytes)?;
+        NonZeroUsize::new(bytes.len()).ok_or(DidntWrite)
+    }
 
+    fn write_exact(&mut self, bytes: &[u8]) -> Result<(), DidntWrite> {
+        if self.header.is_none() {
+            self.header = bytes.first().copied();
+        }
+        self.len += bytes.len();
         Ok(())
     }
+
+    fn remaining(&self) -> usize {
+        usize::MAX
+    }
+
+    fn with_slot<F>(&mut self, len: usize, f: F) -> Result<NonZeroUsize, DidntWrite>
+    where
+        F: FnOnce(&mut [u8]) -> usize,
+    {
+        with_scratch_slot(len, f, |bytes| self.write_exact(bytes))
+    }
+}
+
+// Forwards an encoding to a writer, skipping its first `skip` bytes.
+struct SkipWriter<'a, W> {
+    writer: &'a mut W,
+    skip: usize,
+}
+
+impl<W> Writer for SkipWriter<'_, W>
+where
+    W: Writer,
+{
+    fn write(&mut self, bytes: &[u8]) -> Result<NonZeroUsize, DidntWrite> {
+        self.write_exact(bytes)?;
+        NonZeroUsize::new(bytes.len()).ok_or(DidntWrite)
+    }
+
+    fn write_exact(&mut self, bytes: &[u8]) -> Result<(), DidntWrite> {
+        let skipped = self.skip.min(bytes.len());
+        self.skip -= skipped;
+        match &bytes[skipped..] {
+            [] => Ok(()),
+            bytes => self.writer.write_exact(bytes),
+        }
+    }
+
+    fn remaining(&self) -> usize {
+        self.writer.remaining().saturating_add(self.skip)
+    }
+
+    fn with_slot<F>(&mut self, len: usize, f: F) -> Result<NonZeroUsize, DidntWrite>
+    where
+        F: FnOnce(&mut [u8]) -> usize,
+    {
+        if self.skip == 0 {
+            return self.writer.with_slot(len, f);
+        }
+        with_scratch_slot(len, f, |bytes| self.write_exact(bytes))
+    }
+}
+
+// Provides the slot of `len` bytes to `f` on the stack if small enough, passing the bytes
+// written by `f` to `write`.
+fn with_scratch_slot<F, G>(len: usize, f: F, write: G) -> Result<NonZeroUsize, DidntWrite>
+where
+    F: FnOnce(&mut [u8]) -> usize,
+    G: FnOnce(&[u8]) -> Result<(), DidntWrite>,
+{
+    const SCRATCH_LEN: usize = 16;
+    let mut stack = [0u8; SCRATCH_LEN];
+    let mut heap;
+    let slot = if len <= SCRATCH_LEN {
+        &mut stack[..len]
+    } else {
+        heap = alloc::vec![0u8; len];
+        heap.as_mut_slice()
+    };
+    let written = f(slot);
+    write(&slot[..written])?;
+    NonZeroUsize::new(written).ok_or(DidntWrite)
 }
 
 impl<R> RCodec<DeclareBody, &mut R> for Zenoh080
@@ -62,26 +183,50 @@ where
     type Error = DidntRead;
 
     fn read(self, reader: &mut R) -> Result<DeclareBody, Self::Error> {
+        let d: Result<DeclareBody, UnknownKind> = self.read(&mut *reader)?;
+        d.map_err(|_| DidntRead)
+    }
+}
+
+impl<R> RCodec<Result<DeclareBody, UnknownKind>, &mut R> for Zenoh080
+where
+    R: Reader,
+{
+    type Error = DidntRead;
+
+    fn read(self, reader: &mut R) -> Result<Result<DeclareBody, UnknownKind>, Self::Error> {
         let header: u8 = self.read(&mut *reader)?;
+        let bodec = Zenoh080Bounded::<u32>::new();
+        let len: usize = bodec.read(&mut *reader)?;
+        let mut body = if len > 0 {
+            reader.read_zslice(len)?
+        } else {
+            ZSlice::from(Vec::<u8>::new())
+        };
+
+        // The declaration is decoded from its framed body only, the unknown kinds are skipped
         let codec = Zenoh080Header::new(header);
+        let mut reader = body.reader();
 
         use declare::id::*;
         let d = match imsg::mid(codec.header) {
-            D_KEYEXPR => DeclareBody::DeclareKeyExpr(codec.read(&mut *reader)?),
-            U_KEYEXPR => DeclareBody::UndeclareKeyExpr(codec.read(&mut *reader)?),
-            D_SUBSCRIBER => DeclareBody::DeclareSubscriber(codec.read(&mut *reader)?),
-            U_SUBSCRIBER => DeclareBody::UndeclareSubscriber(codec.read(&mut *reader)?),
-            D_QUERYABLE => DeclareBody::DeclareQueryable(codec.read(&mut *reader)?),
-            U_QUERYABLE => DeclareBody::UndeclareQueryable(codec.read(&mut *reader)?),
-            D_TOKEN => DeclareBody::DeclareToken(codec.read(&mut *reader)?),
-            U_TOKEN => DeclareBody::UndeclareToken(codec.read(&mut *reader)?),
-            D_INTEREST => DeclareBody::DeclareInterest(codec.read(&mut *reader)?),
-            F_INTEREST => DeclareBody::FinalInterest(codec.read(&mut *reader)?),
-            U_INTEREST => DeclareBody::UndeclareInterest(codec.read(&mut *reader)?),
-            _ => return Err(DidntRead),
+            D_KEYEXPR => DeclareBody::DeclareKeyExpr(codec.read(&mut reader)?),
+            U_KEYEXPR => DeclareBody::UndeclareKeyExpr(codec.read(&mut reader)?),
+            D_SUBSCRIBER => DeclareBody::DeclareSubscriber(codec.read(&mut reader)?),
+            U_SUBSCRIBER => DeclareBody::UndeclareSubscriber(codec.read(&mut reader)?),
+            D_QUERYABLE => DeclareBody::DeclareQueryable(codec.read(&mut reader)?),
+            U_QUERYABLE => DeclareBody::UndeclareQueryable(codec.read(&mut reader)?),
+            D_TOKEN => DeclareBody::DeclareToken(codec.read(&mut reader)?),
+            U_TOKEN => DeclareBody::UndeclareToken(codec.read(&mut reader)?),
+            D_INTEREST => DeclareBody::DeclareInterest(codec.read(&mut reader)?),
+            F_INTEREST => DeclareBody::FinalInterest(codec.read(&mut reader)?),
+            U_INTEREST => DeclareBody::UndeclareInterest(codec.read(&mut reader)?),
+            D_KEYEXPRS => DeclareBody::DeclareKeyExprs(codec.read(&mut reader)?),
+            R_KEYEXPRS => DeclareBody::RejectKeyExprs(codec.read(&mut reader)?),
+            mid => return Ok(Err(UnknownKind::Declaration(mid))),
         };
 
-        Ok(d)
+        Ok(Ok(d))
     }
 }
 
@@ -145,6 +290,18 @@ where
     type Error = DidntRead;
 
     fn read(self, reader: &mut R) -> Result<Declare, Self::Error> {
+        let d: Result<Declare, UnknownKind> = self.read(&mut *reader)?;
+        d.map_err(|_| DidntRead)
+    }
+}
+
+impl<R> RCodec<Result<Declare, UnknownKind>, &mut R> for Zenoh080Header
+where
+    R: Reader,
+{
+    type Error = DidntRead;
+
+    fn read(self, reader: &mut R) -> Result<Result<Declare, UnknownKind>, Self::Error> {
         if imsg::mid(self.header) != id::DECLARE {
             return Err(DidntRead);
         }
@@ -181,14 +338,14 @@ where
         }
 
         // Body
-        let body: DeclareBody = self.codec.read(&mut *reader)?;
+        let body: Result<DeclareBody, UnknownKind> = self.codec.read(&mut *reader)?;
 
-        Ok(Declare {
+        Ok(body.map(|body| Declare {
             body,
             ext_qos,
             ext_tstamp,
             ext_nodeid,
-        })
+        }))
     }
 }
 
@@ -310,9 +467,204 @@ where
     }
 }
 
+// DeclareKeyExprs
+impl<W> WCodec<&keyexpr::DeclareKeyExprs, &mut W> for Zenoh080
+where
+    W: Writer,
+{
+    type Output = Result<(), DidntWrite>;
+
+    fn write(self, writer: &mut W, x: &keyexpr::DeclareKeyExprs) -> Self::Output {
+        // Header
+        let mut header = declare::id::D_KEYEXPRS;
+        if x.compressed {
+            header |= keyexpr::flag::C;
+        }
+        self.write(&mut *writer, header)?;
+
+        // Body
+        self.write(&mut *writer, x.mappings.len())?;
+        let mut previous = "";
+        for m in x.mappings.iter() {
+            // The suffix is always present, so no flag is needed per mapping
+            self.write(&mut *writer, m.id)?;
+            Zenoh080Bounded::<ExprId>::new().write(&mut *writer, m.wire_expr.scope)?;
+            let suffix = m.wire_expr.suffix.as_ref();
+            if x.compressed {
+                let (chunks, len) = prefix::shared_chunks(previous, suffix, usize::MAX);
+                self.write(&mut *writer, chunks)?;
+                Zenoh080Bounded::<ExprLen>::new().write(&mut *writer, &suffix[len..])?;
+                previous = suffix;
+            } else {
+                Zenoh080Bounded::<ExprLen>::new().write(&mut *writer, suffix)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<R> RCodec<keyexpr::DeclareKeyExprs, &mut R> for Zenoh080
+where
+    R: Reader,
+{
+    type Error = DidntRead;
+
+    fn read(self, reader: &mut R) -> Result<keyexpr::DeclareKeyExprs, Self::Error> {
+        let header: u8 = self.read(&mut *reader)?;
+        let codec = Zenoh080Header::new(header);
+
+        codec.read(reader)
+    }
+}
+
+impl<R> RCodec<keyexpr::DeclareKeyExprs, &mut R> for Zenoh080Header
+where
+    R: Reader,
+{
+    type Error = DidntRead;
+
+    fn read(self, reader: &mut R) -> Result<keyexpr::DeclareKeyExprs, Self::Error> {
+        if imsg::mid(self.header) != declare::id::D_KEYEXPRS {
+            return Err(DidntRead);
+        }
+
+        let compressed = imsg::has_flag(self.header, keyexpr::flag::C);
+        let num: usize = self.codec.read(&mut *reader)?;
+        let mut mappings: Vec<keyexpr::DeclareKeyExpr> = Vec::new();
+        let ccond = Zenoh080Condition::new(true);
+        for _ in 0..num {
+            let id: ExprId = self.codec.read(&mut *reader)?;
+            let wire_expr: WireExpr<'static> = if compressed {
+                let scope: ExprId = Zenoh080Bounded::<ExprId>::new().read(&mut *reader)?;
+                let chunks: usize = self.codec.read(&mut *reader)?;
+                let rest: String = Zenoh080Bounded::<ExprLen>::new().read(&mut *reader)?;
+                let previous = mappings.last().map_or("", |m| m.wire_expr.suffix.as_ref());
+                let len = prefix::chunks_len(previous, chunks).ok_or(DidntRead)?;
+                let mut suffix = String::with_capacity(len + rest.len());
+                suffix.push_str(&previous[..len]);
+                suffix.push_str(&rest);
+                WireExpr {
+                    scope,
+                    suffix: suffix.into(),
+                    mapping: Mapping::default(),
+                }
+            } else {
+                ccond.read(&mut *reader)?
+            };
+            mappings.push(keyexpr::DeclareKeyExpr { id, wire_expr });
+        }
+
+        // Extensions
+        let has_ext = imsg::has_flag(self.header, keyexpr::flag::Z);
+        if has_ext {
+            extension::skip_all(reader, "DeclareKeyExprs")?;
+        }
+
+        Ok(keyexpr::DeclareKeyExprs {
+            mappings,
+            compressed,
+        })
+    }
+}
+
+// RejectKeyExprs
+impl<W> WCodec<&keyexpr::RejectKeyExprs, &mut W> for Zenoh080
+where
+    W: Writer,
+{
+    type Output = Result<(), DidntWrite>;
+
+    fn write(self, writer: &mut W, x: &keyexpr::RejectKeyExprs) -> Self::Output {
+        // Header
+        let header = declare::id::R_KEYEXPRS;
+        self.write(&mut *writer, header)?;
+
+        // Body
+        self.write(&mut *writer, x.ids.len())?;
+        for id in x.ids.iter() {
+            self.write(&mut *writer, *id)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<R> RCodec<keyexpr::RejectKeyExprs, &mut R> for Zenoh080
+where
+    R: Reader,
+{
+    type Error = DidntRead;
+
+    fn read(self, reader: &mut R) -> Result<keyexpr::RejectKeyExprs, Self::Error> {
+        let header: u8 = self.read(&mut *reader)?;
+        let codec = Zenoh080Header::new(header);
+
+        codec.read(reader)
+    }
+}
+
+impl<R> RCodec<keyexpr::RejectKeyExprs, &mut R> for Zenoh080Header
+where
+    R: Reader,
+{
+    type Error = DidntRead;
+
+    fn read(self, reader: &mut R) -> Result<keyexpr::RejectKeyExprs, Self::Error> {
+        if imsg::mid(self.header) != declare::id::R_KEYEXPRS {
+            return Err(DidntRead);
+        }
+
+        let num: usize = self.codec.read(&mut *reader)?;
+        let mut ids = Vec::new();
+        for _ in 0..num {
+            let id: ExprId = self.codec.read(&mut *reader)?;
+            ids.push(id);
+        }
+
+        // Extensions
+        let has_ext = imsg::has_flag(self.header, keyexpr::flag::Z);
+        if has_ext {
+            extension::skip_all(reader, "RejectKeyExprs")?;
+        }
+
+        Ok(keyexpr::RejectKeyExprs { ids })
+    }
+}
+
 // SubscriberInfo
 crate::impl_zextz64!(subscriber::ext::SubscriberInfo, subscriber::ext::Info::ID);
 
+// Filter
+impl<W> WCodec<(&subscriber::ext::FilterType, bool), &mut W> for Zenoh080
+where
+    W: Writer,
+{
+    type Output = Result<(), DidntWrite>;
+
+    fn write(self, writer: &mut W, x: (&subscriber::ext::FilterType, bool)) -> Self::Output {
+        let (filter, more) = x;
+        let header: ZExtZBufHeader<{ subscriber::ext::Filter::ID }> =
+            ZExtZBufHeader::new(self.w_len(&filter.expr));
+        self.write(&mut *writer, (&header, more))?;
+        self.write(&mut *writer, &filter.expr)
+    }
+}
+
+impl<R> RCodec<(subscriber::ext::FilterType, bool), &mut R> for Zenoh080Header
+where
+    R: Reader,
+{
+    type Error = DidntRead;
+
+    fn read(self, reader: &mut R) -> Result<(subscriber::ext::FilterType, bool), Self::Error> {
+        let (_, more): (ZExtZBufHeader<{ subscriber::ext::Filter::ID }>, bool) =
+            self.read(&mut *reader)?;
+        let expr: String = self.codec.read(&mut *reader)?;
+        Ok((subscriber::ext::FilterType { expr }, more))
+    }
+}
+
 // DeclareSubscriber
 impl<W> WCodec<&subscriber::DeclareSubscriber, &mut W> for Zenoh080
 where
@@ -323,7 +675,8 @@ where
     fn write(self, writer: &mut W, x: &subscriber::DeclareSubscriber) -> Self::Output {
         // Header
         let mut header = declare::id::D_SUBSCRIBER;
-        let mut n_exts = (x.ext_info != subscriber::ext::SubscriberInfo::default()) as u8;
+        let mut n_exts = ((x.ext_info != subscriber::ext::SubscriberInfo::default()) as u8)
+            + (x.ext_filter.is_some() as u8);
         if n_exts != 0 {
             header |= subscriber::flag::Z;
         }
@@ -344,6 +697,10 @@ where
             n_exts -= 1;
             self.write(&mut *writer, (x.ext_info, n_exts != 0))?;
         }
+        if let Some(filter) = x.ext_filter.as_ref() {
+            n_exts -= 1;
+            self.write(&mut *writer, (filter, n_exts != 0))?;
+        }
 
         Ok(())
     }
@@ -386,6 +743,7 @@ where
 
         // Extensions
         let mut ext_info = subscriber::ext::SubscriberInfo::default();
+        let mut ext_filter = None;
 
         let mut has_ext = imsg::has_flag(self.header, subscriber::flag::Z);
         while has_ext {
@@ -398,6 +756,11 @@ where
                     ext_info = i;
                     has_ext = ext;
                 }
+                subscriber::ext::Filter::ID => {
+                    let (f, ext): (subscriber::ext::FilterType, bool) = eodec.read(&mut *reader)?;
+                    ext_filter = Some(f);
+                    has_ext = ext;
+                }
                 _ => {
                     has_ext = extension::skip(reader, "DeclareSubscriber", ext)?;
                 }
@@ -408,6 +771,7 @@ where
             id,
             wire_expr,
             ext_info,
+            ext_filter,
         })
     }
 }
@@ -1053,8 +1417,6 @@ where
     type Error = DidntRead;
 
     fn read(self, reader: &mut R) -> Result<(common::ext::WireExprType, bool), Self::Error> {
-        use zenoh_buffers::reader::HasReader;
-
         let (ext, more): (common::ext::WireExprExt, bool) = self.read(&mut *reader)?;
 
         let mut zeader = ext.value.reader();
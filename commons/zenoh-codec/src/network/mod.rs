@@ -18,7 +18,8 @@ mod request;
 mod response;
 
 use crate::{
-    LCodec, RCodec, WCodec, Zenoh080, Zenoh080Header, Zenoh080Length, Zenoh080Reliability,
+    LCodec, RCodec, WCodec, Zenoh080, Zenoh080Bounded, Zenoh080Header, Zenoh080Length,
+    Zenoh080Reliability,
 };
 use zenoh_buffers::{
     reader::{DidntRead, Reader},
@@ -56,7 +57,7 @@ where
     type Error = DidntRead;
 
     fn read(self, reader: &mut R) -> Result<NetworkMessage, Self::Error> {
-        let codec = Zenoh080Reliability::new(Reliability::default());
+        let codec = Zenoh080Reliability::new(Reliability::Reliable);
         codec.read(reader)
     }
 }
@@ -68,10 +69,26 @@ where
     type Error = DidntRead;
 
     fn read(self, reader: &mut R) -> Result<NetworkMessage, Self::Error> {
+        let msg: Result<NetworkMessage, UnknownKind> = self.read(&mut *reader)?;
+        msg.map_err(|_| DidntRead)
+    }
+}
+
+impl<R> RCodec<Result<NetworkMessage, UnknownKind>, &mut R> for Zenoh080Reliability
+where
+    R: Reader,
+{
+    type Error = DidntRead;
+
+    fn read(self, reader: &mut R) -> Result<Result<NetworkMessage, UnknownKind>, Self::Error> {
         let header: u8 = self.codec.read(&mut *reader)?;
 
         let codec = Zenoh080Header::new(header);
-        codec.read(&mut *reader)
+        let msg: Result<NetworkMessage, UnknownKind> = codec.read(&mut *reader)?;
+        Ok(msg.map(|mut msg| {
+            msg.reliability = self.reliability;
+            msg
+        }))
     }
 }
 
@@ -82,17 +99,44 @@ where
     type Error = DidntRead;
 
     fn read(self, reader: &mut R) -> Result<NetworkMessage, Self::Error> {
+        let msg: Result<NetworkMessage, UnknownKind> = self.read(&mut *reader)?;
+        msg.map_err(|_| DidntRead)
+    }
+}
+
+impl<R> RCodec<Result<NetworkMessage, UnknownKind>, &mut R> for Zenoh080Header
+where
+    R: Reader,
+{
+    type Error = DidntRead;
+
+    fn read(self, reader: &mut R) -> Result<Result<NetworkMessage, UnknownKind>, Self::Error> {
         let body = match imsg::mid(self.header) {
             id::PUSH => NetworkBody::Push(self.read(&mut *reader)?),
             id::REQUEST => NetworkBody::Request(self.read(&mut *reader)?),
             id::RESPONSE => NetworkBody::Response(self.read(&mut *reader)?),
             id::RESPONSE_FINAL => NetworkBody::ResponseFinal(self.read(&mut *reader)?),
-            id::DECLARE => NetworkBody::Declare(self.read(&mut *reader)?),
+            id::DECLARE => {
+                let d: Result<Declare, UnknownKind> = self.read(&mut *reader)?;
+                match d {
+                    Ok(d) => NetworkBody::Declare(d),
+                    Err(kind) => return Ok(Err(kind)),
+                }
+            }
             id::OAM => NetworkBody::OAM(self.read(&mut *reader)?),
+            mid if mid >= id::UNKNOWN_MIN => {
+                // A message of a newer version, skipped with the length of its body
+                let bodec = Zenoh080Bounded::<u32>::new();
+                let len: usize = bodec.read(&mut *reader)?;
+                if len > 0 {
+                    let _ = reader.read_zslice(len)?;
+                }
+                return Ok(Err(UnknownKind::Message(mid)));
+            }
             _ => return Err(DidntRead),
         };
 
-        Ok(body.into())
+        Ok(Ok(body.into()))
     }
 }
 
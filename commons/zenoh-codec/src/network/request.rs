@@ -12,15 +12,17 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 use crate::{
-    common::extension, RCodec, WCodec, Zenoh080, Zenoh080Bounded, Zenoh080Condition, Zenoh080Header,
+    common::extension, LCodec, RCodec, WCodec, Zenoh080, Zenoh080Bounded, Zenoh080Condition,
+    Zenoh080Header, Zenoh080Length,
 };
+use alloc::vec::Vec;
 use zenoh_buffers::{
     reader::{DidntRead, Reader},
     writer::{DidntWrite, Writer},
 };
 use zenoh_protocol::{
-    common::{iext, imsg},
-    core::WireExpr,
+    common::{iext, imsg, ZExtZBufHeader},
+    core::{WireExpr, ZenohId},
     network::{
         id,
         request::{ext, flag},
@@ -71,6 +73,62 @@ where
     }
 }
 
+// Repliers
+impl LCodec<&ext::RepliersType> for Zenoh080 {
+    fn w_len(self, x: &ext::RepliersType) -> usize {
+        x.zids.iter().fold(self.w_len(x.zids.len()), |len, zid| {
+            len + 1 + self.w_len(zid)
+        })
+    }
+}
+
+impl<W> WCodec<(&ext::RepliersType, bool), &mut W> for Zenoh080
+where
+    W: Writer,
+{
+    type Output = Result<(), DidntWrite>;
+
+    fn write(self, writer: &mut W, x: (&ext::RepliersType, bool)) -> Self::Output {
+        let (x, more) = x;
+        let header: ZExtZBufHeader<{ ext::Repliers::ID }> = ZExtZBufHeader::new(self.w_len(x));
+        self.write(&mut *writer, (&header, more))?;
+
+        self.write(&mut *writer, x.zids.len())?;
+        for zid in x.zids.iter() {
+            let flags: u8 = (zid.size() as u8 - 1) << 4;
+            self.write(&mut *writer, flags)?;
+
+            let lodec = Zenoh080Length::new(zid.size());
+            lodec.write(&mut *writer, zid)?;
+        }
+        Ok(())
+    }
+}
+
+impl<R> RCodec<(ext::RepliersType, bool), &mut R> for Zenoh080Header
+where
+    R: Reader,
+{
+    type Error = DidntRead;
+
+    fn read(self, reader: &mut R) -> Result<(ext::RepliersType, bool), Self::Error> {
+        let (_, more): (ZExtZBufHeader<{ ext::Repliers::ID }>, bool) = self.read(&mut *reader)?;
+
+        let count: usize = self.codec.read(&mut *reader)?;
+        let mut zids = Vec::with_capacity(count.min(reader.remaining()));
+        for _ in 0..count {
+            let flags: u8 = self.codec.read(&mut *reader)?;
+            let length = 1 + ((flags >> 4) as usize);
+
+            let lodec = Zenoh080Length::new(length);
+            let zid: ZenohId = lodec.read(&mut *reader)?;
+            zids.push(zid);
+        }
+
+        Ok((ext::RepliersType { zids }, more))
+    }
+}
+
 impl<W> WCodec<&Request, &mut W> for Zenoh080
 where
     W: Writer,
@@ -85,6 +143,8 @@ where
             + ((x.ext_target != ext::TargetType::default()) as u8)
             + (x.ext_budget.is_some() as u8)
             + (x.ext_timeout.is_some() as u8)
+            + (x.ext_correlation.is_some() as u8)
+            + (x.ext_repliers.is_some() as u8)
             + ((x.ext_nodeid != ext::NodeIdType::default()) as u8);
         if n_exts != 0 {
             header |= flag::Z;
@@ -124,6 +184,14 @@ where
             let e = ext::Timeout::new(to.as_millis() as u64);
             self.write(&mut *writer, (&e, n_exts != 0))?;
         }
+        if let Some(c) = x.ext_correlation.as_ref() {
+            n_exts -= 1;
+            self.write(&mut *writer, (c, n_exts != 0))?;
+        }
+        if let Some(r) = x.ext_repliers.as_ref() {
+            n_exts -= 1;
+            self.write(&mut *writer, (r, n_exts != 0))?;
+        }
         if x.ext_nodeid != ext::NodeIdType::default() {
             n_exts -= 1;
             self.write(&mut *writer, (x.ext_nodeid, n_exts != 0))?;
@@ -178,6 +246,8 @@ where
         let mut ext_target = ext::TargetType::default();
         let mut ext_limit = None;
         let mut ext_timeout = None;
+        let mut ext_correlation = None;
+        let mut ext_repliers = None;
 
         let mut has_ext = imsg::has_flag(self.header, flag::Z);
         while has_ext {
@@ -214,6 +284,16 @@ where
                     ext_timeout = Some(ext::TimeoutType::from_millis(to.value));
                     has_ext = ext;
                 }
+                ext::Correlation::ID => {
+                    let (c, ext): (ext::CorrelationType, bool) = eodec.read(&mut *reader)?;
+                    ext_correlation = Some(c);
+                    has_ext = ext;
+                }
+                ext::Repliers::ID => {
+                    let (r, ext): (ext::RepliersType, bool) = eodec.read(&mut *reader)?;
+                    ext_repliers = Some(r);
+                    has_ext = ext;
+                }
                 _ => {
                     has_ext = extension::skip(reader, "Request", ext)?;
                 }
@@ -233,6 +313,8 @@ where
             ext_target,
             ext_budget: ext_limit,
             ext_timeout,
+            ext_correlation,
+            ext_repliers,
         })
     }
 }
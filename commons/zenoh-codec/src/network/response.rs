@@ -41,7 +41,8 @@ where
         let mut header = id::RESPONSE;
         let mut n_exts = ((x.ext_qos != ext::QoSType::default()) as u8)
             + (x.ext_tstamp.is_some() as u8)
-            + (x.ext_respid.is_some() as u8);
+            + (x.ext_respid.is_some() as u8)
+            + (x.ext_correlation.is_some() as u8);
         if n_exts != 0 {
             header |= flag::Z;
         }
@@ -70,6 +71,10 @@ where
             n_exts -= 1;
             self.write(&mut *writer, (ri, n_exts != 0))?;
         }
+        if let Some(c) = x.ext_correlation.as_ref() {
+            n_exts -= 1;
+            self.write(&mut *writer, (c, n_exts != 0))?;
+        }
 
         // Payload
         self.write(&mut *writer, &x.payload)?;
@@ -117,6 +122,7 @@ where
         let mut ext_qos = ext::QoSType::default();
         let mut ext_tstamp = None;
         let mut ext_respid = None;
+        let mut ext_correlation = None;
 
         let mut has_ext = imsg::has_flag(self.header, flag::Z);
         while has_ext {
@@ -138,6 +144,11 @@ where
                     ext_respid = Some(t);
                     has_ext = ext;
                 }
+                ext::Correlation::ID => {
+                    let (c, ext): (ext::CorrelationType, bool) = eodec.read(&mut *reader)?;
+                    ext_correlation = Some(c);
+                    has_ext = ext;
+                }
                 _ => {
                     has_ext = extension::skip(reader, "Response", ext)?;
                 }
@@ -154,6 +165,7 @@ where
             ext_qos,
             ext_tstamp,
             ext_respid,
+            ext_correlation,
         })
     }
 }
@@ -17,9 +17,9 @@ use zenoh_buffers::{
     writer::{DidntWrite, Writer},
 };
 use zenoh_protocol::{
-    common::imsg,
+    common::{iext, imsg},
     transport::{
-        close::{flag, Close},
+        close::{ext, flag, Close},
         id,
     },
 };
@@ -36,11 +36,21 @@ where
         if x.session {
             header |= flag::S;
         }
+        let mut n_exts = x.ext_detail.is_some() as u8;
+        if n_exts != 0 {
+            header |= flag::Z;
+        }
         self.write(&mut *writer, header)?;
 
         // Body
         self.write(&mut *writer, x.reason)?;
 
+        // Extensions
+        if let Some(detail) = x.ext_detail.as_ref() {
+            n_exts -= 1;
+            self.write(&mut *writer, (detail, n_exts != 0))?;
+        }
+
         Ok(())
     }
 }
@@ -75,11 +85,28 @@ where
         let reason: u8 = self.codec.read(&mut *reader)?;
 
         // Extensions
-        let has_ext = imsg::has_flag(self.header, flag::Z);
-        if has_ext {
-            extension::skip_all(reader, "Close")?;
+        let mut ext_detail = None;
+
+        let mut has_ext = imsg::has_flag(self.header, flag::Z);
+        while has_ext {
+            let ext: u8 = self.codec.read(&mut *reader)?;
+            let eodec = Zenoh080Header::new(ext);
+            match iext::eid(ext) {
+                ext::Detail::ID => {
+                    let (d, ext): (ext::Detail, bool) = eodec.read(&mut *reader)?;
+                    ext_detail = Some(d);
+                    has_ext = ext;
+                }
+                _ => {
+                    has_ext = extension::skip(reader, "Close", ext)?;
+                }
+            }
         }
 
-        Ok(Close { reason, session })
+        Ok(Close {
+            reason,
+            session,
+            ext_detail,
+        })
     }
 }
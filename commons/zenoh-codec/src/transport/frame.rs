@@ -12,7 +12,7 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 use crate::{common::extension, RCodec, WCodec, Zenoh080, Zenoh080Header, Zenoh080Reliability};
-use alloc::vec::Vec;
+use alloc::{string::String, vec::Vec};
 use zenoh_buffers::{
     reader::{BacktrackableReader, DidntRead, Reader},
     writer::{DidntWrite, Writer},
@@ -20,7 +20,7 @@ use zenoh_buffers::{
 use zenoh_protocol::{
     common::{iext, imsg},
     core::Reliability,
-    network::NetworkMessage,
+    network::{declare::prefix, NetworkBody, NetworkMessage, UnknownKind},
     transport::{
         frame::{ext, flag, Frame, FrameHeader},
         id, TransportSn,
@@ -40,6 +40,9 @@ where
         if let Reliability::Reliable = x.reliability {
             header |= flag::R;
         }
+        if x.compressed {
+            header |= flag::C;
+        }
         if x.ext_qos != ext::QoSType::default() {
             header |= flag::Z;
         }
@@ -85,6 +88,7 @@ where
             true => Reliability::Reliable,
             false => Reliability::BestEffort,
         };
+        let compressed = imsg::has_flag(self.header, flag::C);
         let sn: TransportSn = self.codec.read(&mut *reader)?;
 
         // Extensions
@@ -110,6 +114,7 @@ where
             reliability,
             sn,
             ext_qos,
+            compressed,
         })
     }
 }
@@ -127,6 +132,7 @@ where
             reliability: x.reliability,
             sn: x.sn,
             ext_qos: x.ext_qos,
+            compressed: false,
         };
         self.write(&mut *writer, &header)?;
 
@@ -163,11 +169,26 @@ where
 
         let rcode = Zenoh080Reliability::new(header.reliability);
         let mut payload = Vec::new();
+        let mut skipped = Vec::new();
+        // The suffix previously declared in the frame, if the declared ones are prefix-delta encoded
+        let mut previous = header.compressed.then(String::new);
         while reader.can_read() {
             let mark = reader.mark();
-            let res: Result<NetworkMessage, DidntRead> = rcode.read(&mut *reader);
+            let mut res: Result<Result<NetworkMessage, UnknownKind>, DidntRead> =
+                rcode.read(&mut *reader);
+            if let (Some(previous), Ok(Ok(m))) = (previous.as_mut(), res.as_mut()) {
+                if let NetworkBody::Declare(declare) = &mut m.body {
+                    if let Some(wire_expr) = declare.declared_wire_expr_mut() {
+                        if prefix::decompress(previous, wire_expr).is_none() {
+                            res = Err(DidntRead);
+                        }
+                    }
+                }
+            }
             match res {
-                Ok(m) => payload.push(m),
+                Ok(Ok(m)) => payload.push(m),
+                // The messages of unknown kinds are skipped, the ones following them are decoded
+                Ok(Err(kind)) => skipped.push(kind),
                 Err(_) => {
                     reader.rewind(mark);
                     break;
@@ -180,6 +201,7 @@ where
             sn: header.sn,
             ext_qos: header.ext_qos,
             payload,
+            skipped,
         })
     }
 }
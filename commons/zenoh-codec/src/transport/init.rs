@@ -46,7 +46,10 @@ where
             + (x.ext_shm.is_some() as u8)
             + (x.ext_auth.is_some() as u8)
             + (x.ext_mlink.is_some() as u8)
-            + (x.ext_lowlatency.is_some() as u8);
+            + (x.ext_lowlatency.is_some() as u8)
+            + (x.ext_resync.is_some() as u8)
+            + (x.ext_prefix.is_some() as u8)
+            + (x.ext_integrity.is_some() as u8);
         if n_exts != 0 {
             header |= flag::Z;
         }
@@ -92,6 +95,18 @@ where
             n_exts -= 1;
             self.write(&mut *writer, (lowlatency, n_exts != 0))?;
         }
+        if let Some(resync) = x.ext_resync.as_ref() {
+            n_exts -= 1;
+            self.write(&mut *writer, (resync, n_exts != 0))?;
+        }
+        if let Some(prefix) = x.ext_prefix.as_ref() {
+            n_exts -= 1;
+            self.write(&mut *writer, (prefix, n_exts != 0))?;
+        }
+        if let Some(integrity) = x.ext_integrity.as_ref() {
+            n_exts -= 1;
+            self.write(&mut *writer, (integrity, n_exts != 0))?;
+        }
 
         Ok(())
     }
@@ -150,6 +165,9 @@ where
         let mut ext_auth = None;
         let mut ext_mlink = None;
         let mut ext_lowlatency = None;
+        let mut ext_resync = None;
+        let mut ext_prefix = None;
+        let mut ext_integrity = None;
 
         let mut has_ext = imsg::has_flag(self.header, flag::Z);
         while has_ext {
@@ -181,6 +199,21 @@ where
                     ext_lowlatency = Some(q);
                     has_ext = ext;
                 }
+                ext::Resync::ID => {
+                    let (q, ext): (ext::Resync, bool) = eodec.read(&mut *reader)?;
+                    ext_resync = Some(q);
+                    has_ext = ext;
+                }
+                ext::PrefixCompression::ID => {
+                    let (q, ext): (ext::PrefixCompression, bool) = eodec.read(&mut *reader)?;
+                    ext_prefix = Some(q);
+                    has_ext = ext;
+                }
+                ext::Integrity::ID => {
+                    let (q, ext): (ext::Integrity, bool) = eodec.read(&mut *reader)?;
+                    ext_integrity = Some(q);
+                    has_ext = ext;
+                }
                 _ => {
                     has_ext = extension::skip(reader, "InitSyn", ext)?;
                 }
@@ -198,6 +231,9 @@ where
             ext_auth,
             ext_mlink,
             ext_lowlatency,
+            ext_resync,
+            ext_prefix,
+            ext_integrity,
         })
     }
 }
@@ -219,7 +255,10 @@ where
             + (x.ext_shm.is_some() as u8)
             + (x.ext_auth.is_some() as u8)
             + (x.ext_mlink.is_some() as u8)
-            + (x.ext_lowlatency.is_some() as u8);
+            + (x.ext_lowlatency.is_some() as u8)
+            + (x.ext_resync.is_some() as u8)
+            + (x.ext_prefix.is_some() as u8)
+            + (x.ext_integrity.is_some() as u8);
         if n_exts != 0 {
             header |= flag::Z;
         }
@@ -268,6 +307,18 @@ where
             n_exts -= 1;
             self.write(&mut *writer, (lowlatency, n_exts != 0))?;
         }
+        if let Some(resync) = x.ext_resync.as_ref() {
+            n_exts -= 1;
+            self.write(&mut *writer, (resync, n_exts != 0))?;
+        }
+        if let Some(prefix) = x.ext_prefix.as_ref() {
+            n_exts -= 1;
+            self.write(&mut *writer, (prefix, n_exts != 0))?;
+        }
+        if let Some(integrity) = x.ext_integrity.as_ref() {
+            n_exts -= 1;
+            self.write(&mut *writer, (integrity, n_exts != 0))?;
+        }
 
         Ok(())
     }
@@ -329,6 +380,9 @@ where
         let mut ext_auth = None;
         let mut ext_mlink = None;
         let mut ext_lowlatency = None;
+        let mut ext_resync = None;
+        let mut ext_prefix = None;
+        let mut ext_integrity = None;
 
         let mut has_ext = imsg::has_flag(self.header, flag::Z);
         while has_ext {
@@ -360,6 +414,21 @@ where
                     ext_lowlatency = Some(q);
                     has_ext = ext;
                 }
+                ext::Resync::ID => {
+                    let (q, ext): (ext::Resync, bool) = eodec.read(&mut *reader)?;
+                    ext_resync = Some(q);
+                    has_ext = ext;
+                }
+                ext::PrefixCompression::ID => {
+                    let (q, ext): (ext::PrefixCompression, bool) = eodec.read(&mut *reader)?;
+                    ext_prefix = Some(q);
+                    has_ext = ext;
+                }
+                ext::Integrity::ID => {
+                    let (q, ext): (ext::Integrity, bool) = eodec.read(&mut *reader)?;
+                    ext_integrity = Some(q);
+                    has_ext = ext;
+                }
                 _ => {
                     has_ext = extension::skip(reader, "InitAck", ext)?;
                 }
@@ -378,6 +447,9 @@ where
             ext_auth,
             ext_mlink,
             ext_lowlatency,
+            ext_resync,
+            ext_prefix,
+            ext_integrity,
         })
     }
 }
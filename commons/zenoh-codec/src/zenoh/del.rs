@@ -37,7 +37,9 @@ where
         if x.timestamp.is_some() {
             header |= flag::T;
         }
-        let mut n_exts = (x.ext_sinfo.is_some()) as u8 + (x.ext_unknown.len() as u8);
+        let mut n_exts = (x.ext_sinfo.is_some()) as u8
+            + (x.ext_batch.is_some()) as u8
+            + (x.ext_unknown.len() as u8);
         if n_exts != 0 {
             header |= flag::Z;
         }
@@ -53,6 +55,10 @@ where
             n_exts -= 1;
             self.write(&mut *writer, (sinfo, n_exts != 0))?;
         }
+        if let Some(batch) = x.ext_batch.as_ref() {
+            n_exts -= 1;
+            self.write(&mut *writer, (batch, n_exts != 0))?;
+        }
         for u in x.ext_unknown.iter() {
             n_exts -= 1;
             self.write(&mut *writer, (u, n_exts != 0))?;
@@ -94,6 +100,7 @@ where
 
         // Extensions
         let mut ext_sinfo: Option<ext::SourceInfoType> = None;
+        let mut ext_batch: Option<ext::BatchType> = None;
         let mut ext_unknown = Vec::new();
 
         let mut has_ext = imsg::has_flag(self.header, flag::Z);
@@ -106,6 +113,11 @@ where
                     ext_sinfo = Some(s);
                     has_ext = ext;
                 }
+                ext::Batch::ID => {
+                    let (b, ext): (ext::BatchType, bool) = eodec.read(&mut *reader)?;
+                    ext_batch = Some(b);
+                    has_ext = ext;
+                }
                 _ => {
                     let (u, ext) = extension::read(reader, "Del", ext)?;
                     ext_unknown.push(u);
@@ -117,6 +129,7 @@ where
         Ok(Del {
             timestamp,
             ext_sinfo,
+            ext_batch,
             ext_unknown,
         })
     }
@@ -19,21 +19,24 @@ pub mod put;
 pub mod query;
 pub mod reply;
 
-#[cfg(not(feature = "shared-memory"))]
-use crate::Zenoh080Bounded;
 #[cfg(feature = "shared-memory")]
 use crate::Zenoh080Sliced;
-use crate::{LCodec, RCodec, WCodec, Zenoh080, Zenoh080Header, Zenoh080Length};
+use crate::{
+    LCodec, RCodec, WCodec, Zenoh080, Zenoh080Bounded, Zenoh080Condition, Zenoh080Header,
+    Zenoh080Length,
+};
+use alloc::vec::Vec;
 use zenoh_buffers::{
     reader::{DidntRead, Reader},
-    writer::{DidntWrite, Writer},
+    writer::{DidntWrite, HasWriter, Writer},
     ZBuf,
 };
 #[cfg(feature = "shared-memory")]
 use zenoh_protocol::common::{iext, ZExtUnit};
 use zenoh_protocol::{
     common::{imsg, ZExtZBufHeader},
-    core::{Encoding, ZenohId},
+    core::{Encoding, WireExpr, ZenohId},
+    network::Mapping,
     zenoh::{ext, id, PushBody, RequestBody, ResponseBody},
 };
 
@@ -202,6 +205,146 @@ where
     }
 }
 
+// Extension: Integrity
+impl<const ID: u8> LCodec<&ext::IntegrityType<{ ID }>> for Zenoh080 {
+    fn w_len(self, x: &ext::IntegrityType<{ ID }>) -> usize {
+        1 + self.w_len(x.checksum)
+    }
+}
+
+impl<W, const ID: u8> WCodec<(&ext::IntegrityType<{ ID }>, bool), &mut W> for Zenoh080
+where
+    W: Writer,
+{
+    type Output = Result<(), DidntWrite>;
+
+    fn write(self, writer: &mut W, x: (&ext::IntegrityType<{ ID }>, bool)) -> Self::Output {
+        let (x, more) = x;
+        let header: ZExtZBufHeader<{ ID }> = ZExtZBufHeader::new(self.w_len(x));
+        self.write(&mut *writer, (&header, more))?;
+
+        self.write(&mut *writer, x.algorithm)?;
+        self.write(&mut *writer, x.checksum)?;
+        Ok(())
+    }
+}
+
+impl<R, const ID: u8> RCodec<(ext::IntegrityType<{ ID }>, bool), &mut R> for Zenoh080Header
+where
+    R: Reader,
+{
+    type Error = DidntRead;
+
+    fn read(self, reader: &mut R) -> Result<(ext::IntegrityType<{ ID }>, bool), Self::Error> {
+        let (_, more): (ZExtZBufHeader<{ ID }>, bool) = self.read(&mut *reader)?;
+
+        let algorithm: u8 = self.codec.read(&mut *reader)?;
+        let checksum: u64 = self.codec.read(&mut *reader)?;
+
+        Ok((
+            ext::IntegrityType {
+                algorithm,
+                checksum,
+            },
+            more,
+        ))
+    }
+}
+
+// Extension: Batch
+impl<W> WCodec<&ext::BatchEntry, &mut W> for Zenoh080
+where
+    W: Writer,
+{
+    type Output = Result<(), DidntWrite>;
+
+    fn write(self, writer: &mut W, x: &ext::BatchEntry) -> Self::Output {
+        let mut flags = 0;
+        if x.wire_expr.has_suffix() {
+            flags |= ext::BatchEntry::FLAG_N;
+        }
+        if x.wire_expr.mapping == Mapping::Sender {
+            flags |= ext::BatchEntry::FLAG_M;
+        }
+        self.write(&mut *writer, flags)?;
+        self.write(&mut *writer, &x.wire_expr)?;
+        self.write(&mut *writer, &x.payload)?;
+        Ok(())
+    }
+}
+
+impl<R> RCodec<ext::BatchEntry, &mut R> for Zenoh080
+where
+    R: Reader,
+{
+    type Error = DidntRead;
+
+    fn read(self, reader: &mut R) -> Result<ext::BatchEntry, Self::Error> {
+        let flags: u8 = self.read(&mut *reader)?;
+        let ccond = Zenoh080Condition::new(flags & ext::BatchEntry::FLAG_N != 0);
+        let mut wire_expr: WireExpr<'static> = ccond.read(&mut *reader)?;
+        wire_expr.mapping = if flags & ext::BatchEntry::FLAG_M != 0 {
+            Mapping::Sender
+        } else {
+            Mapping::Receiver
+        };
+        let payload: PushBody = self.read(&mut *reader)?;
+        // The batches don't nest
+        if payload.ext_batch().is_some() {
+            return Err(DidntRead);
+        }
+        Ok(ext::BatchEntry { wire_expr, payload })
+    }
+}
+
+impl<W, const ID: u8> WCodec<(&ext::BatchType<{ ID }>, bool), &mut W> for Zenoh080
+where
+    W: Writer,
+{
+    type Output = Result<(), DidntWrite>;
+
+    fn write(self, writer: &mut W, x: (&ext::BatchType<{ ID }>, bool)) -> Self::Output {
+        let (x, more) = x;
+
+        // The length of the entries is only known once they are encoded
+        let mut buffer = Vec::new();
+        let mut bwriter = buffer.writer();
+        self.write(&mut bwriter, x.id)?;
+        let bodec = Zenoh080Bounded::<u32>::new();
+        bodec.write(&mut bwriter, x.entries.len())?;
+        for entry in x.entries.iter() {
+            self.write(&mut bwriter, entry)?;
+        }
+
+        let header: ZExtZBufHeader<{ ID }> = ZExtZBufHeader::new(buffer.len());
+        self.write(&mut *writer, (&header, more))?;
+        writer.write_exact(&buffer)?;
+        Ok(())
+    }
+}
+
+impl<R, const ID: u8> RCodec<(ext::BatchType<{ ID }>, bool), &mut R> for Zenoh080Header
+where
+    R: Reader,
+{
+    type Error = DidntRead;
+
+    fn read(self, reader: &mut R) -> Result<(ext::BatchType<{ ID }>, bool), Self::Error> {
+        let (_, more): (ZExtZBufHeader<{ ID }>, bool) = self.read(&mut *reader)?;
+
+        let id: u64 = self.codec.read(&mut *reader)?;
+        let bodec = Zenoh080Bounded::<u32>::new();
+        let len: usize = bodec.read(&mut *reader)?;
+        let mut entries = Vec::with_capacity(len.min(reader.remaining()));
+        for _ in 0..len {
+            let entry: ext::BatchEntry = self.codec.read(&mut *reader)?;
+            entries.push(entry);
+        }
+
+        Ok((ext::BatchType { id, entries }, more))
+    }
+}
+
 // Extension: Shm
 #[cfg(feature = "shared-memory")]
 impl<W, const ID: u8> WCodec<(&ext::ShmType<{ ID }>, bool), &mut W> for Zenoh080
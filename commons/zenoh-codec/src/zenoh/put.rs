@@ -46,7 +46,10 @@ where
         if x.encoding != Encoding::default() {
             header |= flag::E;
         }
-        let mut n_exts = (x.ext_sinfo.is_some()) as u8 + (x.ext_unknown.len() as u8);
+        let mut n_exts = (x.ext_sinfo.is_some()) as u8
+            + (x.ext_integrity.is_some()) as u8
+            + (x.ext_batch.is_some()) as u8
+            + (x.ext_unknown.len() as u8);
         #[cfg(feature = "shared-memory")]
         {
             n_exts += x.ext_shm.is_some() as u8;
@@ -74,6 +77,14 @@ where
             n_exts -= 1;
             self.write(&mut *writer, (eshm, n_exts != 0))?;
         }
+        if let Some(integrity) = x.ext_integrity.as_ref() {
+            n_exts -= 1;
+            self.write(&mut *writer, (integrity, n_exts != 0))?;
+        }
+        if let Some(batch) = x.ext_batch.as_ref() {
+            n_exts -= 1;
+            self.write(&mut *writer, (batch, n_exts != 0))?;
+        }
         for u in x.ext_unknown.iter() {
             n_exts -= 1;
             self.write(&mut *writer, (u, n_exts != 0))?;
@@ -135,6 +146,8 @@ where
         let mut ext_sinfo: Option<ext::SourceInfoType> = None;
         #[cfg(feature = "shared-memory")]
         let mut ext_shm: Option<ext::ShmType> = None;
+        let mut ext_integrity: Option<ext::IntegrityType> = None;
+        let mut ext_batch: Option<ext::BatchType> = None;
         let mut ext_unknown = Vec::new();
 
         let mut has_ext = imsg::has_flag(self.header, flag::Z);
@@ -153,6 +166,16 @@ where
                     ext_shm = Some(s);
                     has_ext = ext;
                 }
+                ext::Integrity::ID => {
+                    let (i, ext): (ext::IntegrityType, bool) = eodec.read(&mut *reader)?;
+                    ext_integrity = Some(i);
+                    has_ext = ext;
+                }
+                ext::Batch::ID => {
+                    let (b, ext): (ext::BatchType, bool) = eodec.read(&mut *reader)?;
+                    ext_batch = Some(b);
+                    has_ext = ext;
+                }
                 _ => {
                     let (u, ext) = extension::read(reader, "Put", ext)?;
                     ext_unknown.push(u);
@@ -182,6 +205,8 @@ where
             ext_sinfo,
             #[cfg(feature = "shared-memory")]
             ext_shm,
+            ext_integrity,
+            ext_batch,
             ext_unknown,
             payload,
         })
@@ -496,6 +496,104 @@ fn codec_undeclare_keyexpr() {
     run!(UndeclareKeyExpr, UndeclareKeyExpr::rand());
 }
 
+#[test]
+fn codec_declare_keyexprs() {
+    run!(DeclareKeyExprs, DeclareKeyExprs::rand());
+}
+
+#[test]
+fn codec_declare_keyexprs_compressed() {
+    fn encode(x: &DeclareKeyExprs) -> Vec<u8> {
+        let mut buffer = vec![];
+        let mut writer = buffer.writer();
+        Zenoh080::new().write(&mut writer, x).unwrap();
+        buffer
+    }
+
+    fn decode(buffer: Vec<u8>) -> DeclareKeyExprs {
+        let mut zslice = ZSlice::from(buffer);
+        let mut reader = zslice.reader();
+        let x: DeclareKeyExprs = Zenoh080::new().read(&mut reader).unwrap();
+        assert!(!reader.can_read());
+        x
+    }
+
+    let mapping = |id, suffix: String| DeclareKeyExpr {
+        id,
+        wire_expr: WireExpr {
+            scope: 0,
+            suffix: suffix.into(),
+            mapping: Mapping::default(),
+        },
+    };
+
+    // The suffixes sharing no chunk, all their chunks, or empty chunks
+    let suffixes = [
+        "a/b/c", "a/b/c", "a/b", "a/b/", "a/b//c", "", "/a", "/a/b", "ab", "a/bc", "a",
+    ];
+    let mut x = DeclareKeyExprs {
+        mappings: suffixes
+            .iter()
+            .enumerate()
+            .map(|(i, s)| mapping(i as ExprId, s.to_string()))
+            .collect(),
+        compressed: true,
+    };
+    assert_eq!(decode(encode(&x)), x);
+    x.compress();
+    assert_eq!(decode(encode(&x)), x);
+
+    // A burst of 10k declarations of a deep hierarchy, in an order sharing no prefix
+    const NUM: usize = 10_000;
+    const MIN_REDUCTION: f64 = 0.6;
+    let mut suffixes = vec![];
+    for joint in 0..10 {
+        for robot in 0..10 {
+            for cell in 0..10 {
+                for line in 0..10 {
+                    suffixes.push(format!(
+                        "factory/line{line}/cell{cell}/robot{robot}/joint{joint}"
+                    ));
+                }
+            }
+        }
+    }
+    let plain = DeclareKeyExprs {
+        mappings: suffixes
+            .into_iter()
+            .enumerate()
+            .map(|(i, s)| mapping(i as ExprId, s))
+            .collect(),
+        compressed: false,
+    };
+    assert_eq!(plain.mappings.len(), NUM);
+    let mut compressed = plain.clone();
+    compressed.compress();
+
+    let plain_len = encode(&plain).len();
+    let buffer = encode(&compressed);
+    let compressed_len = buffer.len();
+    let reduction = 1.0 - compressed_len as f64 / plain_len as f64;
+    println!(
+        "{NUM} declarations: {plain_len} bytes, {compressed_len} bytes compressed ({:.1}% less)",
+        reduction * 100.0
+    );
+    assert!(reduction > MIN_REDUCTION);
+
+    let decoded = decode(buffer);
+    assert_eq!(decoded, compressed);
+    let mut expected = plain.mappings.clone();
+    let mut decoded = decoded.mappings;
+    expected.sort_by_key(|m| m.id);
+    decoded.sort_by_key(|m| m.id);
+    assert_eq!(decoded, expected);
+}
+
+#[test]
+fn codec_reject_keyexprs() {
+    run!(RejectKeyExprs, RejectKeyExprs::rand());
+}
+
 #[test]
 fn codec_declare_subscriber() {
     run!(DeclareSubscriber, DeclareSubscriber::rand());
@@ -591,3 +689,85 @@ fn codec_ack() {
 fn codec_pull() {
     run!(zenoh::Pull, zenoh::Pull::rand());
 }
+
+// Malformed input
+#[test]
+fn codec_unchecked_length() {
+    let codec = Zenoh080::new();
+
+    // A length announcing far more bytes than available must not be allocated
+    let mut bytes = vec![];
+    let mut writer = bytes.writer();
+    codec.write(&mut writer, u32::MAX as usize).unwrap();
+    codec.write(&mut writer, [0u8; 4]).unwrap();
+
+    let mut reader = bytes.reader();
+    let res: Result<Vec<u8>, _> = codec.read(&mut reader);
+    assert!(res.is_err());
+
+    let mut reader = bytes.reader();
+    let res: Result<ZSlice, _> = codec.read(&mut reader);
+    assert!(res.is_err());
+
+    let mut reader = bytes.reader();
+    let res: Result<Vec<Locator>, _> = codec.read(&mut reader);
+    assert!(res.is_err());
+
+    let mut reader = bytes.reader();
+    let res: Result<Vec<Property>, _> = codec.read(&mut reader);
+    assert!(res.is_err());
+
+    let mut zbuf = ZBuf::empty();
+    zbuf.push_zslice(bytes[..2].to_vec().into());
+    zbuf.push_zslice(bytes[2..].to_vec().into());
+    let mut reader = zbuf.reader();
+    let res: Result<ZSlice, _> = codec.read(&mut reader);
+    assert!(res.is_err());
+}
+
+#[cfg(feature = "fuzzing")]
+#[test]
+fn codec_fuzzing_entry_points() {
+    use zenoh_codec::fuzzing::*;
+
+    let codec = Zenoh080::new();
+    for _ in 0..NUM_ITER {
+        let x = TransportMessage::rand();
+        let mut bytes = vec![];
+        let mut writer = bytes.writer();
+        codec.write(&mut writer, &x).unwrap();
+        codec.write(&mut writer, &x).unwrap();
+        assert_eq!(decode_transport_batch(&bytes).unwrap(), vec![x.clone(), x]);
+
+        let x = NetworkMessage::rand();
+        let mut bytes = vec![];
+        let mut writer = bytes.writer();
+        codec.write(&mut writer, &x).unwrap();
+        assert_eq!(decode_network_message(&bytes).unwrap(), x);
+
+        // Fragment the network message in two and enforce the defragmentation limit
+        let mid = bytes.len() / 2;
+        let mut batch = vec![];
+        let mut writer = batch.writer();
+        for (i, payload) in [&bytes[..mid], &bytes[mid..]].into_iter().enumerate() {
+            let fragment = Fragment {
+                reliability: Reliability::Reliable,
+                more: i == 0,
+                sn: i as TransportSn,
+                payload: payload.to_vec().into(),
+                ext_qos: transport::ext::QoSType::default(),
+            };
+            codec
+                .write(&mut writer, &TransportMessage::from(fragment))
+                .unwrap();
+        }
+        assert_eq!(
+            BatchDecoder::new(bytes.len()).decode(&batch).unwrap(),
+            vec![x]
+        );
+        assert!(BatchDecoder::new(bytes.len() - 1).decode(&batch).is_err());
+    }
+
+    assert!(decode_transport_batch(&[0xff; 16]).is_err());
+    assert!(decode_network_message(&[]).is_err());
+}
@@ -0,0 +1,166 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+#[path = "golden/vectors.rs"]
+mod vectors;
+
+use std::fs;
+use vectors::{Message, Vector};
+use zenoh_buffers::{
+    reader::{HasReader, Reader},
+    ZSlice,
+};
+use zenoh_codec::{RCodec, Zenoh080};
+use zenoh_protocol::{
+    network::{NetworkMessage, UnknownKind},
+    transport::{TransportBody, TransportMessage},
+};
+
+const REGENERATE: &str = "If the wire format change is intentional, regenerate the golden files \
+    with `cargo run -p zenoh-codec --example golden`";
+
+fn golden(vector: &Vector) -> Vec<u8> {
+    let path = vector.path();
+    let content = fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("{}: {e}. {REGENERATE}", path.display()));
+    vectors::from_hex(&content)
+}
+
+#[test]
+fn golden_encode() {
+    for vector in vectors::vectors() {
+        println!("Golden encode: {}", vector.name);
+        assert_eq!(
+            vectors::to_hex(&vector.encode()),
+            vectors::to_hex(&golden(&vector)),
+            "{} is not encoded as its golden file. {REGENERATE}",
+            vector.name
+        );
+    }
+}
+
+#[test]
+fn golden_decode() {
+    for vector in vectors::vectors() {
+        println!("Golden decode: {}", vector.name);
+        let message = vector.decode(golden(&vector));
+        assert_eq!(
+            message, vector.message,
+            "{} golden file is not decoded as expected. {REGENERATE}",
+            vector.name
+        );
+    }
+}
+
+#[test]
+fn golden_fragments() {
+    let vectors = vectors::vectors();
+    let find = |name: &str| vectors.iter().find(|v| v.name == name).unwrap();
+
+    // Reassemble the fragments decoded from the golden files
+    let mut payload = vec![];
+    for name in ["fragment_first", "fragment_last"] {
+        let vector = find(name);
+        match vector.decode(golden(vector)) {
+            Message::Transport(TransportMessage {
+                body: TransportBody::Fragment(fragment),
+                ..
+            }) => payload.extend_from_slice(fragment.payload.as_slice()),
+            m => panic!("{name} is not a fragment: {m:?}"),
+        }
+    }
+
+    let codec = Zenoh080::new();
+    let mut zslice = ZSlice::from(payload);
+    let mut reader = zslice.reader();
+    let message: NetworkMessage = codec.read(&mut reader).unwrap();
+    assert!(!reader.can_read());
+    assert_eq!(Message::Network(message), find("push_put").message);
+}
+
+#[test]
+fn golden_unknown_kinds() {
+    let vectors = vectors::vectors();
+    let find = |name: &str| vectors.iter().find(|v| v.name == name).unwrap();
+    let network = |name: &str| match &find(name).message {
+        Message::Network(m) => m.clone(),
+        m => panic!("{name} is not a network message: {m:?}"),
+    };
+
+    // A frame mixing the golden declarations with the ones of a newer version:
+    // a declaration of unknown kind 0x1f and a network message of unknown id 0x10
+    let mut bytes = golden(find("frame_reliable"))[..2].to_vec();
+    bytes.extend(golden(find("declare_keyexpr")));
+    bytes.extend([0x1e, 0x1f, 0x03, 0xaa, 0xbb, 0xcc]);
+    bytes.extend([0x10, 0x02, 0xaa, 0xbb]);
+    bytes.extend(golden(find("declare_subscriber")));
+    // The transport messages following the frame in the batch are still decoded
+    bytes.extend(golden(find("keep_alive")));
+
+    let codec = Zenoh080::new();
+    let mut zslice = ZSlice::from(bytes);
+    let mut reader = zslice.reader();
+    let message: TransportMessage = codec.read(&mut reader).unwrap();
+    match message.body {
+        TransportBody::Frame(frame) => {
+            assert_eq!(
+                frame.payload,
+                [network("declare_keyexpr"), network("declare_subscriber")]
+            );
+            assert_eq!(
+                frame.skipped,
+                [UnknownKind::Declaration(0x1f), UnknownKind::Message(0x10)]
+            );
+        }
+        m => panic!("Not a frame: {m:?}"),
+    }
+    let message: TransportMessage = codec.read(&mut reader).unwrap();
+    assert_eq!(Message::Transport(message), find("keep_alive").message);
+    assert!(!reader.can_read());
+
+    // A network message of unknown id whose length exceeds the batch can't be skipped:
+    // it ends the frame and fails to be decoded as the next transport message
+    let mut bytes = golden(find("frame_reliable"))[..2].to_vec();
+    bytes.extend(golden(find("declare_keyexpr")));
+    bytes.extend([0x10, 0x09, 0xaa]);
+
+    let mut zslice = ZSlice::from(bytes);
+    let mut reader = zslice.reader();
+    let message: TransportMessage = codec.read(&mut reader).unwrap();
+    match message.body {
+        TransportBody::Frame(frame) => {
+            assert_eq!(frame.payload, [network("declare_keyexpr")]);
+            assert!(frame.skipped.is_empty());
+        }
+        m => panic!("Not a frame: {m:?}"),
+    }
+    let res: Result<TransportMessage, _> = codec.read(&mut reader);
+    assert!(res.is_err());
+}
+
+#[test]
+fn golden_files() {
+    // Every golden file must have a vector, so that no stale file is left behind
+    let vectors = vectors::vectors();
+    for entry in fs::read_dir(vectors::dir()).unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().map_or(false, |e| e == "hex") {
+            let name = path.file_stem().unwrap().to_str().unwrap();
+            assert!(
+                vectors.iter().any(|v| v.name == name),
+                "{} has no vector. {REGENERATE}",
+                path.display()
+            );
+        }
+    }
+}
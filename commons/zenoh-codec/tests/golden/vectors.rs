@@ -0,0 +1,541 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+// The messages of the golden files, shared by the `golden` test and the `golden` example
+// regenerating the files.
+#![allow(dead_code)]
+
+use std::convert::TryFrom;
+use std::path::PathBuf;
+use std::time::Duration;
+use zenoh_buffers::{
+    reader::{HasReader, Reader},
+    writer::HasWriter,
+    ZBuf, ZSlice,
+};
+use zenoh_codec::{RCodec, WCodec, Zenoh080};
+use zenoh_protocol::{
+    common::{ZExtBody, ZExtUnknown},
+    core::{
+        CongestionControl, Encoding, Priority, Reliability, Timestamp, WhatAmI, WireExpr, ZenohId,
+        NTP64,
+    },
+    network::{
+        declare::{self, Declare, DeclareBody, DeclareKeyExpr, DeclareKeyExprs, DeclareSubscriber},
+        push::{self, Push},
+        request::{self, Request},
+        Mapping, NetworkMessage,
+    },
+    transport::{
+        close, fragment, frame, init, keepalive, open, Close, Fragment, Frame, InitAck, InitSyn,
+        KeepAlive, OpenAck, OpenSyn, TransportMessage,
+    },
+    zenoh::{put, Put, Query},
+};
+
+pub const HEADER: &str =
+    "# Generated by `cargo run -p zenoh-codec --example golden`, do not edit by hand.";
+const BYTES_PER_LINE: usize = 16;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Message {
+    Transport(TransportMessage),
+    Network(NetworkMessage),
+}
+
+pub struct Vector {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub message: Message,
+}
+
+impl Vector {
+    fn transport(name: &'static str, description: &'static str, msg: TransportMessage) -> Self {
+        Self {
+            name,
+            description,
+            message: Message::Transport(msg),
+        }
+    }
+
+    fn network(name: &'static str, description: &'static str, msg: NetworkMessage) -> Self {
+        Self {
+            name,
+            description,
+            message: Message::Network(msg),
+        }
+    }
+
+    pub fn path(&self) -> PathBuf {
+        dir().join(format!("{}.hex", self.name))
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        encode(&self.message)
+    }
+
+    pub fn decode(&self, bytes: Vec<u8>) -> Message {
+        let codec = Zenoh080::new();
+        let mut zslice = ZSlice::from(bytes);
+        let mut reader = zslice.reader();
+        let message = match &self.message {
+            Message::Transport(_) => Message::Transport(codec.read(&mut reader).unwrap()),
+            Message::Network(_) => Message::Network(codec.read(&mut reader).unwrap()),
+        };
+        assert!(!reader.can_read(), "{}: trailing bytes", self.name);
+        message
+    }
+
+    /// The content of the golden file of this vector.
+    pub fn to_file(&self) -> String {
+        let mut s = format!("# {}\n{}\n", self.description, HEADER);
+        s.push_str(&to_hex(&self.encode()));
+        s
+    }
+}
+
+/// The directory of the golden files.
+pub fn dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("golden")
+}
+
+pub fn encode(message: &Message) -> Vec<u8> {
+    let codec = Zenoh080::new();
+    let mut buffer = vec![];
+    let mut writer = buffer.writer();
+    match message {
+        Message::Transport(m) => codec.write(&mut writer, m).unwrap(),
+        Message::Network(m) => codec.write(&mut writer, m).unwrap(),
+    }
+    buffer
+}
+
+pub fn to_hex(bytes: &[u8]) -> String {
+    let mut s = String::new();
+    for line in bytes.chunks(BYTES_PER_LINE) {
+        let line: Vec<String> = line.iter().map(|b| format!("{b:02x}")).collect();
+        s.push_str(&line.join(" "));
+        s.push('\n');
+    }
+    s
+}
+
+/// Parses the content of a golden file, skipping the `#` comments.
+pub fn from_hex(s: &str) -> Vec<u8> {
+    s.lines()
+        .filter(|l| !l.trim_start().starts_with('#'))
+        .flat_map(|l| l.split_whitespace())
+        .map(|b| u8::from_str_radix(b, 16).unwrap())
+        .collect()
+}
+
+fn zid() -> ZenohId {
+    ZenohId::try_from([0x01, 0x02]).unwrap()
+}
+
+fn timestamp() -> Timestamp {
+    let id = uhlc::ID::try_from(&[0x01, 0x02][..]).unwrap();
+    Timestamp::new(NTP64(1_000), id)
+}
+
+fn cookie() -> ZSlice {
+    vec![0xde, 0xad, 0xbe, 0xef].into()
+}
+
+fn put(payload: &[u8]) -> Put {
+    Put {
+        timestamp: None,
+        encoding: Encoding::default(),
+        ext_sinfo: None,
+        #[cfg(feature = "shared-memory")]
+        ext_shm: None,
+        ext_integrity: None,
+        ext_batch: None,
+        ext_unknown: vec![],
+        payload: ZBuf::from(payload.to_vec()),
+    }
+}
+
+/// A push with every optional field and extension set.
+fn push_put() -> NetworkMessage {
+    Push {
+        wire_expr: WireExpr {
+            scope: 2,
+            suffix: "demo".into(),
+            mapping: Mapping::Sender,
+        },
+        ext_qos: push::ext::QoSType::new(Priority::RealTime, CongestionControl::Block, true),
+        ext_tstamp: Some(push::ext::TimestampType {
+            timestamp: timestamp(),
+        }),
+        ext_nodeid: push::ext::NodeIdType { node_id: 3 },
+        payload: Put {
+            timestamp: Some(timestamp()),
+            encoding: Encoding::TEXT_PLAIN.with_suffix(";utf8").unwrap(),
+            ext_sinfo: Some(put::ext::SourceInfoType {
+                zid: zid(),
+                eid: 7,
+                sn: 42,
+            }),
+            #[cfg(feature = "shared-memory")]
+            ext_shm: None,
+            ext_integrity: None,
+            ext_batch: None,
+            ext_unknown: vec![ZExtUnknown {
+                id: 0x24,
+                body: ZExtBody::Z64(99),
+            }],
+            payload: ZBuf::from(b"hello".to_vec()),
+        }
+        .into(),
+    }
+    .into()
+}
+
+/// A query request carrying a correlation id.
+fn request_query() -> NetworkMessage {
+    Request {
+        id: 5,
+        wire_expr: WireExpr {
+            scope: 1,
+            suffix: "".into(),
+            mapping: Mapping::Receiver,
+        },
+        ext_qos: request::ext::QoSType::default(),
+        ext_tstamp: None,
+        ext_nodeid: request::ext::NodeIdType::default(),
+        ext_target: request::ext::TargetType::default(),
+        ext_budget: None,
+        ext_timeout: None,
+        ext_correlation: Some(request::ext::CorrelationType { zid: zid(), eid: 9 }),
+        ext_repliers: None,
+        payload: Query {
+            parameters: String::new(),
+            ext_sinfo: None,
+            ext_consolidation: Default::default(),
+            ext_body: None,
+            ext_unknown: vec![],
+        }
+        .into(),
+    }
+    .into()
+}
+
+/// A query request accepting the replies of a single zenoh instance.
+fn request_repliers() -> NetworkMessage {
+    Request {
+        id: 5,
+        wire_expr: WireExpr {
+            scope: 1,
+            suffix: "".into(),
+            mapping: Mapping::Receiver,
+        },
+        ext_qos: request::ext::QoSType::default(),
+        ext_tstamp: None,
+        ext_nodeid: request::ext::NodeIdType::default(),
+        ext_target: request::ext::TargetType::default(),
+        ext_budget: None,
+        ext_timeout: None,
+        ext_correlation: None,
+        ext_repliers: Some(request::ext::RepliersType { zids: vec![zid()] }),
+        payload: Query {
+            parameters: String::new(),
+            ext_sinfo: None,
+            ext_consolidation: Default::default(),
+            ext_body: None,
+            ext_unknown: vec![],
+        }
+        .into(),
+    }
+    .into()
+}
+
+/// A push with no optional field set.
+fn push_min(reliability: Reliability) -> NetworkMessage {
+    let mut msg: NetworkMessage = Push {
+        wire_expr: WireExpr {
+            scope: 1,
+            suffix: "".into(),
+            mapping: Mapping::Receiver,
+        },
+        ext_qos: push::ext::QoSType::default(),
+        ext_tstamp: None,
+        ext_nodeid: push::ext::NodeIdType::default(),
+        payload: put(b"hi").into(),
+    }
+    .into();
+    msg.reliability = reliability;
+    msg
+}
+
+fn declare_keyexpr() -> NetworkMessage {
+    Declare {
+        ext_qos: declare::ext::QoSType::default(),
+        ext_tstamp: None,
+        ext_nodeid: declare::ext::NodeIdType::default(),
+        body: DeclareBody::DeclareKeyExpr(DeclareKeyExpr {
+            id: 1,
+            wire_expr: WireExpr {
+                scope: 0,
+                suffix: "demo/example".into(),
+                mapping: Mapping::Receiver,
+            },
+        }),
+    }
+    .into()
+}
+
+/// Each suffix sharing chunks with the previous one.
+fn declare_keyexprs_compressed() -> NetworkMessage {
+    let mapping = |id, suffix: &str| DeclareKeyExpr {
+        id,
+        wire_expr: WireExpr {
+            scope: 0,
+            suffix: suffix.to_string().into(),
+            mapping: Mapping::default(),
+        },
+    };
+    Declare {
+        ext_qos: declare::ext::QoSType::default(),
+        ext_tstamp: None,
+        ext_nodeid: declare::ext::NodeIdType::default(),
+        body: DeclareBody::DeclareKeyExprs(DeclareKeyExprs {
+            mappings: vec![
+                mapping(1, "factory/line1/cell3"),
+                mapping(2, "factory/line1/cell3/robot7"),
+                mapping(3, "factory/line1/cell4"),
+            ],
+            compressed: true,
+        }),
+    }
+    .into()
+}
+
+fn declare_subscriber() -> NetworkMessage {
+    Declare {
+        ext_qos: declare::ext::QoSType::default(),
+        ext_tstamp: None,
+        ext_nodeid: declare::ext::NodeIdType { node_id: 2 },
+        body: DeclareBody::DeclareSubscriber(DeclareSubscriber {
+            id: 5,
+            wire_expr: WireExpr {
+                scope: 1,
+                suffix: "/**".into(),
+                mapping: Mapping::Sender,
+            },
+            ext_info: declare::subscriber::ext::SubscriberInfo {
+                reliability: Reliability::Reliable,
+                mode: declare::Mode::Push,
+            },
+            ext_filter: None,
+        }),
+    }
+    .into()
+}
+
+/// The push split in two fragments.
+fn fragments() -> (Fragment, Fragment) {
+    const SPLIT: usize = 24;
+
+    let bytes = encode(&Message::Network(push_put()));
+    let first = Fragment {
+        reliability: Reliability::Reliable,
+        more: true,
+        sn: 42,
+        payload: bytes[..SPLIT].to_vec().into(),
+        ext_qos: fragment::ext::QoSType::default(),
+    };
+    let last = Fragment {
+        reliability: Reliability::Reliable,
+        more: false,
+        sn: 43,
+        payload: bytes[SPLIT..].to_vec().into(),
+        ext_qos: fragment::ext::QoSType::default(),
+    };
+    (first, last)
+}
+
+pub fn vectors() -> Vec<Vector> {
+    let (fragment_first, fragment_last) = fragments();
+
+    vec![
+        Vector::transport(
+            "init_syn",
+            "InitSyn from a router, with size params and the QoS extension",
+            InitSyn {
+                version: 0x08,
+                whatami: WhatAmI::Router,
+                zid: zid(),
+                resolution: Default::default(),
+                batch_size: 2_048,
+                ext_qos: Some(init::ext::QoS::new()),
+                ext_shm: None,
+                ext_auth: None,
+                ext_mlink: None,
+                ext_lowlatency: None,
+                ext_resync: None,
+                ext_prefix: None,
+                ext_integrity: None,
+            }
+            .into(),
+        ),
+        Vector::transport(
+            "init_ack",
+            "InitAck from a peer, with a cookie",
+            InitAck {
+                version: 0x08,
+                whatami: WhatAmI::Peer,
+                zid: ZenohId::try_from([0x0a]).unwrap(),
+                resolution: Default::default(),
+                batch_size: zenoh_protocol::transport::batch_size::UNICAST,
+                cookie: cookie(),
+                ext_qos: None,
+                ext_shm: None,
+                ext_auth: None,
+                ext_mlink: None,
+                ext_lowlatency: None,
+                ext_resync: None,
+                ext_prefix: None,
+                ext_integrity: None,
+            }
+            .into(),
+        ),
+        Vector::transport(
+            "open_syn",
+            "OpenSyn with a lease in seconds, a cookie and two extensions",
+            OpenSyn {
+                lease: Duration::from_secs(10),
+                initial_sn: 300,
+                cookie: cookie(),
+                ext_qos: Some(open::ext::QoS::new()),
+                ext_shm: None,
+                ext_auth: None,
+                ext_mlink: None,
+                ext_lowlatency: Some(open::ext::LowLatency::new()),
+            }
+            .into(),
+        ),
+        Vector::transport(
+            "open_ack",
+            "OpenAck with a lease in milliseconds",
+            OpenAck {
+                lease: Duration::from_millis(2_500),
+                initial_sn: 0,
+                ext_qos: None,
+                ext_shm: None,
+                ext_auth: None,
+                ext_mlink: None,
+                ext_lowlatency: None,
+            }
+            .into(),
+        ),
+        Vector::transport(
+            "close",
+            "Close of the whole session",
+            Close {
+                reason: close::reason::EXPIRED,
+                session: true,
+                ext_detail: None,
+            }
+            .into(),
+        ),
+        Vector::transport(
+            "close_detail",
+            "Close of a link rejected for the max sessions with a detail",
+            Close {
+                reason: close::reason::MAX_SESSIONS,
+                session: false,
+                ext_detail: None,
+            }
+            .with_detail("busy".to_string())
+            .into(),
+        ),
+        Vector::transport("keep_alive", "KeepAlive", KeepAlive::default().into()),
+        Vector::transport(
+            "keep_alive_echo",
+            "KeepAlive requesting an echo and echoing a token",
+            KeepAlive {
+                ext_echo_request: Some(keepalive::ext::EchoRequest::new(1_000)),
+                ext_echo_reply: Some(keepalive::ext::EchoReply::new(42)),
+            }
+            .into(),
+        ),
+        Vector::transport(
+            "frame_reliable",
+            "Reliable frame carrying a push and a declaration",
+            Frame {
+                reliability: Reliability::Reliable,
+                sn: 7,
+                ext_qos: frame::ext::QoSType::default(),
+                payload: vec![push_min(Reliability::Reliable), declare_keyexpr()],
+                skipped: vec![],
+            }
+            .into(),
+        ),
+        Vector::transport(
+            "frame_best_effort",
+            "Best effort frame with the QoS extension",
+            Frame {
+                reliability: Reliability::BestEffort,
+                sn: 300,
+                ext_qos: frame::ext::QoSType::new(Priority::RealTime),
+                payload: vec![push_min(Reliability::BestEffort)],
+                skipped: vec![],
+            }
+            .into(),
+        ),
+        Vector::transport(
+            "fragment_first",
+            "First fragment of push_put",
+            fragment_first.into(),
+        ),
+        Vector::transport(
+            "fragment_last",
+            "Last fragment of push_put",
+            fragment_last.into(),
+        ),
+        Vector::network(
+            "push_put",
+            "Push of a put with every optional field and extension",
+            push_put(),
+        ),
+        Vector::network(
+            "request_query",
+            "Query request carrying a correlation id",
+            request_query(),
+        ),
+        Vector::network(
+            "request_repliers",
+            "Query request accepting the replies of a single zenoh instance",
+            request_repliers(),
+        ),
+        Vector::network(
+            "declare_keyexpr",
+            "Declaration of a key expression",
+            declare_keyexpr(),
+        ),
+        Vector::network(
+            "declare_subscriber",
+            "Declaration of a subscriber with extensions",
+            declare_subscriber(),
+        ),
+        Vector::network(
+            "declare_keyexprs_compressed",
+            "Bulk declaration of key expressions with prefix compression",
+            declare_keyexprs_compressed(),
+        ),
+    ]
+}
@@ -14,6 +14,10 @@
 use super::*;
 
 pub const ENV: &str = "ZENOH_CONFIG";
+/// The directories of the user-defined presets, see [`ConfigLoader`](crate::ConfigLoader).
+pub const PRESETS_ENV: &str = "ZENOH_CONFIG_PRESETS";
+/// The JSON5 object overriding the configuration files, see [`ConfigLoader`](crate::ConfigLoader).
+pub const OVERRIDE_ENV: &str = "ZENOH_CONFIG_OVERRIDE";
 
 macro_rules! mode_accessor {
     ($type:ty) => {
@@ -88,15 +92,116 @@ pub mod timestamping {
 #[allow(dead_code)]
 pub const queries_default_timeout: u64 = 10000;
 
+#[allow(non_upper_case_globals)]
+#[allow(dead_code)]
+pub const wildcard_updates: bool = false;
+
+#[allow(non_upper_case_globals)]
+#[allow(dead_code)]
+pub const local_routing: bool = true;
+
+#[allow(non_upper_case_globals)]
+#[allow(dead_code)]
+pub mod integrity {
+    pub const enabled: bool = false;
+    pub const algorithm: crate::IntegrityAlgorithm = crate::IntegrityAlgorithm::Crc32c;
+    pub const on_mismatch: crate::IntegrityMismatch = crate::IntegrityMismatch::Drop;
+}
+
+#[allow(non_upper_case_globals)]
+#[allow(dead_code)]
+pub mod connect {
+    pub const parallelism: usize = 4;
+    pub mod required {
+        pub const router: &usize = &0;
+        pub const peer: &usize = &0;
+        pub const client: &usize = &1;
+        mode_accessor!(usize);
+    }
+    pub mod retry {
+        pub const period_init: u64 = 1000;
+        pub const period_max: u64 = 4000;
+        pub const period_increase_factor: f64 = 2.0;
+    }
+}
+
+#[allow(non_upper_case_globals)]
+#[allow(dead_code)]
+pub mod listen {
+    pub mod address_watch {
+        pub const enabled: bool = false;
+        pub const period: u64 = 2000;
+    }
+}
+
+#[allow(non_upper_case_globals)]
+#[allow(dead_code)]
+pub mod health {
+    pub const accept_pressure_degraded: u8 = 50;
+    pub const accept_pressure_failing: u8 = 100;
+    pub const connect_failures_degraded: usize = 1;
+}
+
+#[allow(non_upper_case_globals)]
+#[allow(dead_code)]
+pub mod limits {
+    pub mod keyexpr_mappings {
+        pub const remote_alarm: usize = 10000;
+        pub const grace_period: u64 = 1000;
+    }
+}
+
 #[allow(non_upper_case_globals)]
 #[allow(dead_code)]
 pub mod routing {
     pub mod router {
         pub const peers_failover_brokering: bool = true;
     }
+    pub const full_recomputation: bool = false;
     pub mod peer {
         pub const mode: &str = "peer_to_peer";
     }
+    pub mod face {
+        pub mod declaration_rate {
+            pub const enabled: bool = true;
+            pub const rate: u64 = 1000;
+            pub const burst: u64 = 10000;
+            pub const queue: usize = 10000;
+        }
+    }
+    pub mod declaration_refresh {
+        pub const enabled: bool = true;
+        pub const period: u64 = 10000;
+        pub const buckets: usize = 16;
+    }
+    pub mod query {
+        pub mod limits {
+            pub const enabled: bool = true;
+            pub const per_face: usize = 1000;
+            pub const total: usize = 10000;
+            pub const policy: &str = "reject";
+        }
+        pub mod throttle {
+            pub const enabled: bool = false;
+            pub const max_subjects: usize = 10000;
+        }
+    }
+    pub mod query_tracing {
+        pub const enabled: bool = false;
+        pub const size: usize = 100;
+    }
+    pub mod dead_letter {
+        pub const enabled: bool = false;
+        pub const window: u64 = 1000;
+    }
+    pub mod audit {
+        pub const enabled: bool = false;
+        pub const sampling: f64 = 1.0;
+        pub mod file {
+            pub const max_size: u64 = 10 * 1024 * 1024;
+            pub const max_files: usize = 5;
+        }
+    }
 }
 
 impl Default for TransportUnicastConf {
@@ -104,9 +209,30 @@ impl Default for TransportUnicastConf {
         Self {
             accept_timeout: 10_000,
             accept_pending: 100,
+            accept_pending_per_source: 100,
+            accept_failures_per_source: None,
+            accept_ban_period: 60_000,
             max_sessions: 1_000,
+            close_timeout: 1_000,
+            shutdown_timeout: 5_000,
             max_links: 1,
             lowlatency: false,
+            resync: true,
+            prefix_compression: true,
+            open_retry: TransportUnicastOpenRetryConf::default(),
+            limits: TransportUnicastLimitsConf::default(),
+        }
+    }
+}
+
+impl Default for TransportUnicastOpenRetryConf {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            period_init: 1_000,
+            period_max: 4_000,
+            period_increase_factor: 2.,
+            max_attempts: None,
         }
     }
 }
@@ -134,13 +260,26 @@ impl Default for LinkTxConf {
             sequence_number_resolution: Bits::from(TransportSn::MAX),
             lease: 10_000,
             keep_alive: 4,
+            keep_alive_period_ms: None,
             batch_size: BatchSize::MAX,
             queue: QueueConf::default(),
+            stall_timeout: 30_000,
+            scheduling: TxSchedulingConf::default(),
             threads: num,
         }
     }
 }
 
+impl Default for TxSchedulingConf {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            quantum: BatchSize::MAX as usize,
+            weights: vec![],
+        }
+    }
+}
+
 impl Default for QueueConf {
     fn default() -> Self {
         Self {
@@ -175,6 +314,8 @@ impl Default for LinkRxConf {
         Self {
             buffer_size: BatchSize::MAX as usize,
             max_message_size: 2_usize.pow(30),
+            queue_size: 1024,
+            pool_size: 16 * 1024 * 1024,
         }
     }
 }
@@ -15,7 +15,10 @@
 //! Configuration to pass to `zenoh::open()` and `zenoh::scout()` functions and associated constants.
 pub mod defaults;
 mod include;
+mod loader;
+mod vars;
 use include::recursive_include;
+pub use loader::*;
 use serde::{
     de::{self, MapAccess, Visitor},
     Deserialize, Serialize,
@@ -27,7 +30,6 @@ use std::{
     any::Any,
     collections::{HashMap, HashSet},
     fmt,
-    io::Read,
     marker::PhantomData,
     net::SocketAddr,
     path::Path,
@@ -35,6 +37,7 @@ use std::{
 };
 use validated_struct::ValidatedMapAssociatedTypes;
 pub use validated_struct::{GetError, ValidatedMap};
+pub use vars::VARS_KEY;
 use zenoh_core::zlock;
 pub use zenoh_protocol::core::{
     whatami, EndPoint, Locator, Priority, WhatAmI, WhatAmIMatcher, WhatAmIMatcherVisitor, ZenohId,
@@ -122,11 +125,38 @@ validated_struct::validator! {
         pub connect: #[derive(Default)]
         ConnectConfig {
             pub endpoints: Vec<EndPoint>,
+            /// The number of endpoints to connect to before the session is open, the others being retried in the background
+            /// (default: 1 for a client, 0 for a peer or a router).
+            required: Option<ModeDependentValue<usize>>,
+            /// The maximum number of endpoints connected to concurrently when opening the session (default: 4).
+            parallelism: Option<usize>,
+            /// The retry of the connection to the endpoints that couldn't be connected to, or whose transport closed,
+            /// the period between two attempts growing exponentially. A client retries until it is reconnected to a router.
+            pub retry: #[derive(Default)]
+            ConnectRetryConf {
+                /// The period in milliseconds before the first retry (default: 1000).
+                period_init: Option<u64>,
+                /// The maximum period in milliseconds between two attempts (default: 4000).
+                period_max: Option<u64>,
+                /// The factor the period is multiplied by after each attempt (default: 2).
+                period_increase_factor: Option<f64>,
+            },
         },
         /// Which endpoints to listen on. `zenohd` will add `tcp/[::]:7447` to these locators if left empty.
         pub listen: #[derive(Default)]
         ListenConfig {
             pub endpoints: Vec<EndPoint>,
+            /// The watching of the addresses of the local network interfaces.
+            /// The listeners bound to a specific address are removed, with their advertised locators, when the address disappears,
+            /// and bound again when it reappears. The locators advertised for the listeners bound to a wildcard address are refreshed.
+            pub address_watch: #[derive(Default)]
+            AddressWatchConf {
+                /// Whether the addresses of the local network interfaces are watched (default: false).
+                enabled: Option<bool>,
+                /// The period in milliseconds at which the addresses are polled, when their changes
+                /// aren't notified by the system, e.g. through netlink on Linux (default: 2000).
+                period: Option<u64>,
+            },
         },
         pub scouting: #[derive(Default)]
         ScoutingConf {
@@ -177,9 +207,85 @@ validated_struct::validator! {
             drop_future_timestamp: Option<bool>,
         },
 
+        /// The end-to-end integrity checksums of the payloads of the publications, meant for the links not protected by TLS.
+        /// The routers forward the checksums untouched, the receiving sessions verify them.
+        pub integrity: #[derive(Default)]
+        IntegrityConf {
+            /// Whether the session attaches a checksum of the payload to its publications (default: false).
+            /// When disabled, the checksums are absent from the wire.
+            enabled: Option<bool>,
+            /// The checksum algorithm: "crc32c" (default) or "xxhash64".
+            algorithm: Option<IntegrityAlgorithm>,
+            /// What the session does with the received samples whose payload doesn't match their checksum:
+            /// "drop" (default) drops them, "flag" delivers them flagged as corrupted.
+            on_mismatch: Option<IntegrityMismatch>,
+        },
+
         /// The default timeout to apply to queries in milliseconds.
         queries_default_timeout: Option<u64>,
 
+        /// Whether puts and deletes on key expressions containing wildcards are accepted.
+        /// If set to false (default), such updates are rejected by the API.
+        /// If set to true, storages record them and apply them to all the matching keys,
+        /// including the keys created after the update with an older timestamp.
+        wildcard_updates: Option<bool>,
+
+        /// Whether the publications of a session are delivered to the matching subscribers of the same session.
+        /// If set to true (default), the session delivers them locally in addition to sending them to the network.
+        /// If set to false, the session only sends them to the network.
+        local_routing: Option<bool>,
+
+        /// The panics of the callbacks of the subscribers and queryables of the sessions, which are contained:
+        /// logged and counted in the admin space under `@/session/<zid>/subscriber/<id>` and `@/session/<zid>/queryable/<id>`.
+        pub callbacks: #[derive(Default)]
+        CallbacksConf {
+            /// The number of consecutive panics of the callback of a subscriber or a queryable after which it is
+            /// undeclared (default: null, never).
+            undeclare_after_panics: Option<usize>,
+        },
+
+        /// The namespace the sessions opened with this configuration are confined to.
+        /// The sessions prefix the key expressions they use with it and strip it from the key expressions they receive.
+        /// The admin space keys (starting with `@/`) are left untouched, except the liveliness keys which are namespaced after `@/liveliness`.
+        namespace: Option<OwnedKeyExpr>,
+
+        /// The namespaces the authenticated clients and peers are confined to.
+        /// The key expressions declared and used by a face authenticated as one of the users of a rule must be included by its namespace,
+        /// the messages using other key expressions are dropped.
+        namespaces: Vec<NamespaceConf>,
+
+        /// Thresholds used to classify the health of the runtime as reported by `Runtime::health()`.
+        pub health: #[derive(Default)]
+        HealthConf {
+            /// The percentage of `transport/unicast/accept_pending` incoming transports pending
+            /// from which the runtime is degraded (default: 50).
+            accept_pressure_degraded: Option<u8>,
+            /// The percentage of `transport/unicast/accept_pending` incoming transports pending
+            /// from which the runtime is failing (default: 100).
+            accept_pressure_failing: Option<u8>,
+            /// The number of configured connect endpoints not established
+            /// from which the runtime is degraded (default: 1).
+            connect_failures_degraded: Option<usize>,
+        },
+
+        /// The limits on the resources used by this zenoh instance.
+        pub limits: #[derive(Default)]
+        LimitsConf {
+            /// The key expression mappings (expression ids) declared on each session. Their counts and the
+            /// estimate of their memory are reported for each session in the admin space under `@/router/<zid>`.
+            pub keyexpr_mappings: #[derive(Default)]
+            KeyExprMappingsConf {
+                /// The maximum number of mappings this zenoh instance declares to each session (default: null, unlimited).
+                /// Beyond it, the least recently used mapping is undeclared, the key expression it mapped being sent in full.
+                max_local: Option<usize>,
+                /// The number of mappings declared by a session from which an alarm is logged (default: 10000).
+                remote_alarm: Option<usize>,
+                /// The time in milliseconds an evicted mapping stays declared before being undeclared,
+                /// for the messages already sent with it (default: 1000).
+                grace_period: Option<u64>,
+            },
+        },
+
         /// The routing strategy to use and it's configuration.
         pub routing: #[derive(Default)]
         RoutingConf {
@@ -192,12 +298,124 @@ validated_struct::validator! {
                 /// The failover brokering only works if gossip discovery is enabled.
                 peers_failover_brokering: Option<bool>,
             },
+            /// When set to true, all the routes are recomputed on every change of the faces
+            /// or of the network instead of only the ones it affects.
+            /// Meant to compare both strategies when debugging the routing.
+            full_recomputation: Option<bool>,
             /// The routing strategy to use in peers and it's configuration.
             pub peer: #[derive(Default)]
             PeerRoutingConf {
                 /// The routing strategy to use in peers. ("peer_to_peer" or "linkstate").
                 mode: Option<String>,
             },
+            /// The limits applied to the messages received on each face.
+            pub face: #[derive(Default)]
+            FaceRoutingConf {
+                /// The rate limiting of the declarations received on the faces of clients and peers.
+                /// Beyond the rate, the declarations are queued and processed at the rate.
+                /// Once the queue is full, the face is closed.
+                pub declaration_rate: #[derive(Default)]
+                DeclarationRateConf {
+                    /// Whether the declarations are rate limited (default: true).
+                    enabled: Option<bool>,
+                    /// The number of declarations processed per second (default: 1000).
+                    rate: Option<u64>,
+                    /// The number of declarations processed without delay after an idle period (default: 10000).
+                    burst: Option<u64>,
+                    /// The maximum number of declarations waiting to be processed (default: 10000).
+                    queue: Option<usize>,
+                },
+            },
+            /// The soft-state refresh of the declarations sent over the multicast transports, which are best effort.
+            /// Each node periodically sends a digest of its declarations to the multicast groups,
+            /// the nodes whose view of them doesn't match the digest requesting them all.
+            pub declaration_refresh: #[derive(Default)]
+            DeclarationRefreshConf {
+                /// Whether the declarations are refreshed (default: true).
+                enabled: Option<bool>,
+                /// The period of the digests in milliseconds (default: 10000).
+                period: Option<u64>,
+                /// The granularity of the digests, as the number of hashes they contain (default: 16).
+                buckets: Option<usize>,
+            },
+            /// The queries routed by this zenoh instance.
+            pub query: #[derive(Default)]
+            QueryRoutingConf {
+                /// The limits on the queries routed by this zenoh instance and waiting for their final replies.
+                /// Beyond the limits, the new queries are rejected with an error reply, or the oldest pending
+                /// queries are evicted, and an alarm is logged.
+                pub limits: #[derive(Default)]
+                QueryLimitsConf {
+                    /// Whether the pending queries are limited (default: true).
+                    enabled: Option<bool>,
+                    /// The maximum number of pending queries received from a single face (default: 1000).
+                    per_face: Option<usize>,
+                    /// The maximum number of pending queries (default: 10000).
+                    total: Option<usize>,
+                    /// What to do with a new query beyond the limits: "reject" it,
+                    /// or "evict_oldest" pending query under the reached limit (default: "reject").
+                    policy: Option<String>,
+                },
+                /// The rate limiting of the queries of each client, applied by the router it is connected to,
+                /// e.g. to protect the storages from an application flooding them with queries.
+                /// Beyond the rate, the queries are replied with a "throttled" error telling when they may be retried.
+                pub throttle: #[derive(Default)]
+                QueryThrottleConf {
+                    /// Whether the queries are rate limited (default: false).
+                    enabled: Option<bool>,
+                    /// The maximum number of clients whose rate is tracked, the least recently active
+                    /// one being forgotten beyond it (default: 10000).
+                    max_subjects: Option<usize>,
+                    /// The rate limits, the first rule matching a query applying to it.
+                    rules: Vec<QueryThrottleRuleConf>,
+                },
+            },
+            /// The tracing of the queries routed by this zenoh instance.
+            /// The traces of the last queries are exposed in the admin space under `@/router/<zid>/debug/queries`.
+            pub query_tracing: #[derive(Default)]
+            QueryTracingConf {
+                /// Whether the routed queries are traced (default: false).
+                enabled: Option<bool>,
+                /// The number of traces kept (default: 100).
+                size: Option<usize>,
+            },
+            /// The notification of the data messages dropped by this zenoh instance without reaching any destination:
+            /// no matching subscriber, key expression outside of the namespace of the face, or congestion.
+            /// The notifications are published under `@/router/<zid>/deadletter`, at most once per key expression and window.
+            /// They don't change the routing of the messages.
+            pub dead_letter: #[derive(Default)]
+            DeadLetterConf {
+                /// Whether the dropped messages are notified (default: false).
+                enabled: Option<bool>,
+                /// The minimum time in milliseconds between two notifications for the same key expression (default: 1000).
+                window: Option<u64>,
+            },
+            /// The audit log of the operations of the faces of the transports: their declarations of subscribers and queryables,
+            /// their first publication on each key expression and their queries, each recorded with the identity the face
+            /// authenticated with and whether the operation was admitted.
+            /// The events are recorded by the configured sinks and the ones added with `Runtime::add_audit_sink`.
+            pub audit: #[derive(Default)]
+            AuditConf {
+                /// Whether the operations are audited (default: false).
+                enabled: Option<bool>,
+                /// The ratio of the events recorded, between 0 and 1 (default: 1).
+                sampling: Option<f64>,
+                /// The maximum number of events recorded per second, after a burst of as many (default: unlimited).
+                rate: Option<u64>,
+                /// The file the events are written to as JSON lines, if any.
+                pub file: #[derive(Default)]
+                AuditFileConf {
+                    /// The path of the file.
+                    path: Option<String>,
+                    /// The size in bytes beyond which the file is rotated (default: 10485760).
+                    max_size: Option<u64>,
+                    /// The number of rotated files kept, named after the file with a `.1`, `.2`... suffix (default: 5).
+                    max_files: Option<usize>,
+                },
+                /// The key expression the events are published onto as JSON, if any, e.g. `@/audit/router-1` for a
+                /// central collection.
+                key_expr: Option<OwnedKeyExpr>,
+            },
         },
 
         /// The declarations aggregation strategy.
@@ -208,6 +426,23 @@ validated_struct::validator! {
             /// A list of key-expressions for which all included publishers will be aggregated into.
             publishers: Vec<OwnedKeyExpr>,
         },
+
+        /// The deduplication of the samples published by redundant publishers.
+        /// Each rule drops the samples on the keys it matches that are duplicates of a sample routed within its window.
+        deduplication: Vec<DeduplicationConf>,
+
+        /// The mutations of the samples sent to some faces, e.g. to redact their payloads.
+        /// A sample is mutated by the first rule matching both its key and the face it is sent to.
+        mutations: Vec<MutationConf>,
+
+        /// The locators advertised by this zenoh instance in the scouting Hellos, the gossip and the admin space.
+        /// They don't change the endpoints the zenoh instance actually listens on.
+        pub advertise: #[derive(Default)]
+        AdvertiseConf {
+            /// The rules rewriting the advertised locators: a locator is replaced by the first rule it matches.
+            rewrite: Vec<LocatorRewriteConf>,
+        },
+
         pub transport: #[derive(Default)]
         TransportConf {
             pub unicast: TransportUnicastConf {
@@ -215,14 +450,66 @@ validated_struct::validator! {
                 accept_timeout: u64,
                 /// Number of links that may stay pending during accept phase (default: 100).
                 accept_pending: usize,
+                /// Number of links from the same source address that may stay pending during accept phase,
+                /// within the limit of `accept_pending` (default: 100).
+                accept_pending_per_source: usize,
+                /// Number of failed accepts from the same source address within `accept_ban_period` after
+                /// which the new links of the source are closed right away during `accept_ban_period`
+                /// (default: unlimited).
+                accept_failures_per_source: Option<usize>,
+                /// The period in milliseconds during which the failed accepts of a source address are
+                /// counted, and the source banned (default: 60000).
+                accept_ban_period: u64,
                 /// Maximum number of unicast sessions (default: 1000)
                 max_sessions: usize,
+                /// Timeout in milliseconds waiting for the peer to acknowledge the close of a session (default: 1000).
+                close_timeout: u64,
+                /// Timeout in milliseconds for the close of each session when closing all of them on shutdown.
+                /// The links of the sessions not closed in time are dropped (default: 5000).
+                shutdown_timeout: u64,
                 /// Maximum number of unicast incoming links per transport session (default: 1)
                 max_links: usize,
                 /// Enables the LowLatency transport (default `false`).
                 /// This option does not make LowLatency transport mandatory, the actual implementation of transport
                 /// used will depend on Establish procedure and other party's settings
                 lowlatency: bool,
+                /// Enables the bulk resynchronization of the key expression mappings when
+                /// reconnecting to a router (default `true`). The declarations are sent one by
+                /// one to the peers not supporting it.
+                resync: bool,
+                /// Enables the prefix compression of the key expressions declared in bulk
+                /// (default `true`): each key expression is sent as the number of chunks it
+                /// shares with the previous one and the rest. The key expressions are sent in
+                /// full to the peers not supporting it.
+                prefix_compression: bool,
+                /// The retry of the opening of the transports, when the link can't be opened or
+                /// drops during the establishment, the period between two attempts growing
+                /// exponentially.
+                pub open_retry: TransportUnicastOpenRetryConf {
+                    /// Whether to retry to open the transports (default: false).
+                    enabled: bool,
+                    /// The period in milliseconds before the first retry (default: 1000).
+                    period_init: u64,
+                    /// The maximum period in milliseconds between two attempts (default: 4000).
+                    period_max: u64,
+                    /// The factor the period is multiplied by after each attempt, a finite number
+                    /// of at least 1 (default: 2).
+                    period_increase_factor: f64 where (period_increase_factor_validator),
+                    /// The maximum number of attempts, including the first one (default: unlimited).
+                    max_attempts: Option<usize>,
+                },
+                /// The limits overriding `max_sessions` and `max_links` for the sessions and
+                /// links of some protocols.
+                pub limits: #[derive(Default)]
+                TransportUnicastLimitsConf {
+                    /// Maximum number of unicast sessions per protocol, e.g. `{ tls: 5 }`.
+                    max_sessions: Option<HashMap<String, usize>>,
+                    /// Maximum number of unicast incoming links per transport session, per protocol.
+                    max_links: Option<HashMap<String, usize>>,
+                    /// Maximum number of unicast sessions with the nodes of some kinds, in addition
+                    /// to the other limits, e.g. `{ client: 100, "router|peer": 10 }`.
+                    max_sessions_by_whatami: Option<HashMap<String, usize>>,
+                },
             },
             pub multicast: TransportMulticastConf {
                 /// Link join interval duration in milliseconds (default: 2500)
@@ -240,6 +527,8 @@ validated_struct::validator! {
                 // An optional whitelist of protocols to be used for accepting and opening sessions.
                 // If not configured, all the supported protocols are automatically whitelisted.
                 pub protocols: Option<Vec<String>>,
+                /// Whether to request the echo of the keep-alives to estimate the round-trip time of the links (default: false).
+                rtt_probe: bool,
                 pub tx: LinkTxConf {
                     /// The resolution in bits to be used for the message sequence numbers.
                     /// When establishing a session with another Zenoh instance, the lowest value of the two instances will be used.
@@ -249,6 +538,9 @@ validated_struct::validator! {
                     lease: u64,
                     /// Number fo keep-alive messages in a link lease duration (default: 4)
                     keep_alive: usize,
+                    /// Period in milliseconds of the keep-alive messages, overriding `keep_alive` if set.
+                    /// It must be at most half the lease.
+                    keep_alive_period_ms: Option<u64>,
                     /// Zenoh's MTU equivalent (default: 2^16-1)
                     batch_size: BatchSize,
                     pub queue: QueueConf {
@@ -271,6 +563,19 @@ validated_struct::validator! {
                         /// Higher values lead to a more aggressive batching but it will introduce additional latency.
                         backoff: u64,
                     },
+                    /// Time in milliseconds after which a link whose writes don't progress is closed (default: 30000).
+                    /// It must be comfortably larger than the time publications with the Block congestion control
+                    /// may wait for the link. 0 disables the watchdog.
+                    stall_timeout: u64,
+                    /// The deficit round-robin scheduling of the transports sharing the tx threads.
+                    pub scheduling: TxSchedulingConf {
+                        /// Whether the transports write in turn on the tx threads (default: false).
+                        enabled: bool,
+                        /// Number of bytes a transport writes per round per unit of weight (default: 65535).
+                        quantum: usize,
+                        /// The weights of the transports with some peers, the others having a weight of 1.
+                        weights: Vec<TxWeightConf>,
+                    },
                     // Number of threads used for TX
                     threads: usize,
                 },
@@ -284,6 +589,15 @@ validated_struct::validator! {
                     /// Maximum size of the defragmentation buffer at receiver end (default: 1GiB).
                     /// Fragmented messages that are larger than the configured size will be dropped.
                     max_message_size: usize,
+                    /// Number of received messages that can wait to be handled, per transport (default: 1024).
+                    /// The links keep being read and the keep-alive and close messages handled while the
+                    /// messages wait. Once full, the messages that can be dropped are dropped and the others
+                    /// wait for some room, holding the reading of the link. The link is closed once the
+                    /// queue stayed full for a whole lease.
+                    queue_size: usize,
+                    /// Total size in bytes of the received batch buffers kept for recycling across
+                    /// all the links (default: 16MiB). 0 disables the recycling.
+                    pool_size: usize,
                 },
                 pub tls: #[derive(Default)]
                 TLSConf {
@@ -329,6 +643,8 @@ validated_struct::validator! {
                     password: Option<String>,
                     /// The path to a file containing the user password dictionary, a file containing `<user>:<password>`
                     dictionary_file: Option<String>,
+                    /// The user password dictionary, merged with the one of `dictionary_file`.
+                    dictionary: Option<HashMap<String, String>>,
                 } where (user_conf_validator),
                 pub pubkey: #[derive(Default)]
                 PubKeyConf {
@@ -339,6 +655,15 @@ validated_struct::validator! {
                     key_size: Option<usize>,
                     known_keys_file: Option<String>,
                 },
+                /// The peers admitted to establish unicast transports, by ZenohId,
+                /// checked for the transports accepted and opened by this zenoh instance.
+                pub peers: #[derive(Default)]
+                PeersAuthConf {
+                    /// If not empty, only these peers are admitted.
+                    allow: Vec<ZenohId>,
+                    /// These peers are never admitted, even if they are in `allow`.
+                    deny: Vec<ZenohId>,
+                },
             },
         },
         /// Configuration of the admin space.
@@ -388,6 +713,126 @@ fn set_false() -> bool {
     false
 }
 
+/// A deduplication rule.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DeduplicationConf {
+    /// The key-expressions whose included keys are deduplicated.
+    pub keyexprs: Vec<OwnedKeyExpr>,
+    /// The period in milliseconds during which a sample is considered a duplicate of a previous one.
+    pub window: u64,
+    /// How duplicates are identified (default: `content`).
+    #[serde(default)]
+    pub mode: DeduplicationMode,
+}
+
+/// A namespace rule, confining the faces authenticated as one of its users to its namespace.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NamespaceConf {
+    /// The users (as authenticated by `transport/auth/usrpwd`) the rule applies to.
+    pub users: Vec<String>,
+    /// The key-expression prefixing all the key expressions of the users.
+    pub namespace: OwnedKeyExpr,
+}
+
+/// A rule rewriting the advertised locators, e.g. to advertise the public address of a zenoh
+/// instance behind a NAT.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LocatorRewriteConf {
+    /// The pattern of the rewritten locators, where `*` matches any sequence of characters.
+    pub r#match: String,
+    /// The locator advertised instead of the matching ones.
+    pub replace: Locator,
+}
+
+/// How duplicated samples are identified.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeduplicationMode {
+    /// Samples with the same kind, encoding and payload are duplicates, whatever their source.
+    #[default]
+    Content,
+    /// Samples with the same source info (source id and sequence number) and timestamp are duplicates.
+    SourceInfo,
+}
+
+/// A rate limit on the queries of each client on some key expressions.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct QueryThrottleRuleConf {
+    /// The key-expressions intersecting the throttled queries.
+    pub keyexprs: Vec<OwnedKeyExpr>,
+    /// The zids of the clients the rule applies to (default: any zid).
+    #[serde(default)]
+    pub zids: Vec<ZenohId>,
+    /// The number of queries per second allowed to each client.
+    pub rate: u64,
+    /// The number of queries allowed without delay to a client after an idle period.
+    pub burst: u64,
+}
+
+/// A rule mutating the samples sent to some faces.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MutationConf {
+    /// The key-expressions whose included keys are mutated.
+    pub keyexprs: Vec<OwnedKeyExpr>,
+    /// The zids of the faces the rule applies to (default: any zid).
+    #[serde(default)]
+    pub zids: Vec<ZenohId>,
+    /// The kinds of the faces the rule applies to (default: any kind).
+    #[serde(default)]
+    pub whatami: Option<WhatAmIMatcher>,
+    /// The mutation of the samples.
+    pub mutation: MutationKind,
+}
+
+/// How the payload of a sample is mutated.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MutationKind {
+    /// The payload is truncated to the given number of bytes.
+    Truncate(usize),
+    /// The payload is replaced by the given value.
+    Replace(String),
+    /// The payload is mutated by the hook registered on the runtime under the given name.
+    Custom(String),
+}
+
+/// The weight of the transports with some peers in the tx scheduling.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TxWeightConf {
+    /// The zids of the peers.
+    pub zids: Vec<ZenohId>,
+    /// The number of quanta the transports write per round, at least 1.
+    pub weight: u32,
+}
+
+/// The algorithm of the integrity checksums of the payloads.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IntegrityAlgorithm {
+    /// The CRC-32C (Castagnoli) of the payload.
+    #[default]
+    Crc32c,
+    /// The 64 bits xxHash of the payload.
+    Xxhash64,
+}
+
+/// What a session does with the received samples whose payload doesn't match their checksum.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IntegrityMismatch {
+    /// The samples are dropped, counted and reported by an alarm.
+    #[default]
+    Drop,
+    /// The samples are delivered flagged as corrupted.
+    Flag,
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct PluginSearchDirs(Vec<String>);
 impl Default for PluginSearchDirs {
@@ -539,41 +984,10 @@ impl Config {
         Self::from_file(path.as_str())
     }
 
+    /// Loads a configuration file, layered on the preset it names in its `preset` key if any,
+    /// see [`ConfigLoader`].
     pub fn from_file<P: AsRef<Path>>(path: P) -> ZResult<Self> {
-        let path = path.as_ref();
-        let mut config = Self::_from_file(path)?;
-        config.plugins.load_external_configs()?;
-        Ok(config)
-    }
-
-    fn _from_file(path: &Path) -> ZResult<Config> {
-        match std::fs::File::open(path) {
-            Ok(mut f) => {
-                let mut content = String::new();
-                if let Err(e) = f.read_to_string(&mut content) {
-                    bail!(e)
-                }
-                match path
-                    .extension()
-                    .map(|s| s.to_str().unwrap())
-                {
-                    Some("json") | Some("json5") => match json5::Deserializer::from_str(&content) {
-                        Ok(mut d) => Config::from_deserializer(&mut d).map_err(|e| match e {
-                            Ok(c) => zerror!("Invalid configuration: {}", c).into(),
-                            Err(e) => zerror!("JSON error: {}", e).into(),
-                        }),
-                        Err(e) => bail!(e),
-                    },
-                    Some("yaml") => Config::from_deserializer(serde_yaml::Deserializer::from_str(&content)).map_err(|e| match e {
-                        Ok(c) => zerror!("Invalid configuration: {}", c).into(),
-                        Err(e) => zerror!("YAML error: {}", e).into(),
-                    }),
-                    Some(other) => bail!("Unsupported file type '.{}' (.json, .json5 and .yaml are supported)", other),
-                    None => bail!("Unsupported file type. Configuration files must have an extension (.json, .json5 and .yaml supported)")
-                }
-            }
-            Err(e) => bail!(e),
-        }
+        ConfigLoader::new().file(path)?.load()
     }
 
     pub fn libloader(&self) -> LibLoader {
@@ -599,6 +1013,17 @@ fn config_from_json() {
     config
         .insert("transport/link/tx/lease", &mut from_str("168"))
         .unwrap();
+    let factor = "transport/unicast/open_retry/period_increase_factor";
+    assert!(config.insert(factor, &mut from_str("0.5")).is_err());
+    config.insert(factor, &mut from_str("1.5")).unwrap();
+    assert_eq!(
+        *config
+            .transport()
+            .unicast()
+            .open_retry()
+            .period_increase_factor(),
+        1.5
+    );
     dbg!(std::mem::size_of_val(&config));
     println!("{}", serde_json::to_string_pretty(&config).unwrap());
 }
@@ -782,6 +1207,10 @@ fn sequence_number_resolution_validator(b: &Bits) -> bool {
     b <= &Bits::from(TransportSn::MAX)
 }
 
+fn period_increase_factor_validator(factor: &f64) -> bool {
+    factor.is_finite() && *factor >= 1.
+}
+
 fn queue_size_validator(q: &QueueSizeConf) -> bool {
     fn check(size: &usize) -> bool {
         (QueueSizeConf::MIN..=QueueSizeConf::MAX).contains(size)
@@ -1280,6 +1709,50 @@ impl<'a> serde::Deserialize<'a> for ModeDependentValue<bool> {
     }
 }
 
+impl<'a> serde::Deserialize<'a> for ModeDependentValue<usize> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'a>,
+    {
+        struct UniqueOrDependent<U>(PhantomData<fn() -> U>);
+
+        impl<'de> Visitor<'de> for UniqueOrDependent<ModeDependentValue<usize>> {
+            type Value = ModeDependentValue<usize>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("unsigned integer or mode dependent unsigned integer")
+            }
+
+            fn visit_u64<E>(self, value: u64) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                usize::try_from(value)
+                    .map(ModeDependentValue::Unique)
+                    .map_err(|_| E::invalid_value(de::Unexpected::Unsigned(value), &self))
+            }
+
+            fn visit_i64<E>(self, value: i64) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                usize::try_from(value)
+                    .map(ModeDependentValue::Unique)
+                    .map_err(|_| E::invalid_value(de::Unexpected::Signed(value), &self))
+            }
+
+            fn visit_map<M>(self, map: M) -> Result<Self::Value, M::Error>
+            where
+                M: MapAccess<'de>,
+            {
+                ModeValues::deserialize(de::value::MapAccessDeserializer::new(map))
+                    .map(ModeDependentValue::Dependent)
+            }
+        }
+        deserializer.deserialize_any(UniqueOrDependent(PhantomData))
+    }
+}
+
 impl<'a> serde::Deserialize<'a> for ModeDependentValue<WhatAmIMatcher> {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -0,0 +1,461 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use crate::{defaults, include::deserialize_from_file, vars::expand_vars, Config};
+use serde_json::{Map, Value};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+    path::{Path, PathBuf},
+};
+use zenoh_result::{bail, zerror, ZResult};
+
+/// The key of a configuration file naming the preset it is layered on.
+pub const PRESET_KEY: &str = "preset";
+
+const PRESET_EXTENSIONS: [&str; 3] = ["json5", "json", "yaml"];
+
+// The presets shipped with zenoh, as JSON5 configurations.
+const BUILTIN_PRESETS: [(&str, &str); 3] = [
+    (
+        // A client connecting to the configured endpoints, not listening nor answering the scouts
+        "client-minimal",
+        r#"{
+            mode: "client",
+            listen: { endpoints: [] },
+            scouting: { multicast: { listen: false }, gossip: { enabled: false } },
+        }"#,
+    ),
+    (
+        // A router listening on the default port and answering the scouts
+        "router-default",
+        r#"{
+            mode: "router",
+            listen: { endpoints: ["tcp/[::]:7447"] },
+            scouting: { multicast: { enabled: true, listen: true }, gossip: { enabled: true } },
+            routing: { router: { peers_failover_brokering: true } },
+        }"#,
+    ),
+    (
+        // A peer discovering and connecting to the routers and peers of its local network
+        "peer-lan",
+        r#"{
+            mode: "peer",
+            scouting: {
+                multicast: { enabled: true, listen: true, autoconnect: { peer: "router|peer" } },
+                gossip: { enabled: true, multihop: false },
+            },
+        }"#,
+    ),
+];
+
+/// The layer of a [`ConfigLoader`] a configuration value comes from, by increasing precedence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ConfigSource {
+    Preset,
+    File,
+    Env,
+    Programmatic,
+}
+
+impl fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigSource::Preset => write!(f, "preset"),
+            ConfigSource::File => write!(f, "file"),
+            ConfigSource::Env => write!(f, "env"),
+            ConfigSource::Programmatic => write!(f, "programmatic"),
+        }
+    }
+}
+
+/// Builds a [`Config`] out of layers merged by precedence, whatever the order they are added in:
+/// a preset, then configuration files, then the `ZENOH_CONFIG_OVERRIDE` environment variable,
+/// then the values inserted programmatically.
+///
+/// The presets are either built in (`client-minimal`, `router-default` and `peer-lan`) or read from
+/// the `<name>.json5`, `<name>.json` or `<name>.yaml` files of the preset directories: the ones of the
+/// `ZENOH_CONFIG_PRESETS` environment variable and the ones added with [`ConfigLoader::preset_dir`].
+/// The user-defined presets take precedence over the built-in ones of the same name.
+#[derive(Debug, Clone)]
+pub struct ConfigLoader {
+    preset_dirs: Vec<PathBuf>,
+    preset: Option<String>,
+    layers: BTreeMap<ConfigSource, Value>,
+}
+
+impl Default for ConfigLoader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ConfigLoader {
+    pub fn new() -> Self {
+        let preset_dirs = std::env::var_os(defaults::PRESETS_ENV)
+            .map(|dirs| std::env::split_paths(&dirs).collect())
+            .unwrap_or_default();
+        ConfigLoader {
+            preset_dirs,
+            preset: None,
+            layers: BTreeMap::new(),
+        }
+    }
+
+    /// Adds a directory to look the user-defined presets up in.
+    ///
+    /// The directories must be added before the presets they define are used.
+    pub fn preset_dir<P: AsRef<Path>>(mut self, dir: P) -> Self {
+        self.preset_dirs.push(dir.as_ref().to_path_buf());
+        self
+    }
+
+    /// Sets the preset the other layers are merged over, replacing the previous one.
+    pub fn preset(mut self, name: &str) -> ZResult<Self> {
+        let value = self.resolve_preset(name)?;
+        self.preset = Some(name.to_string());
+        self.layers.insert(ConfigSource::Preset, value);
+        Ok(self)
+    }
+
+    /// Merges a configuration file. The preset it names in its `preset` key replaces the previous one.
+    pub fn file<P: AsRef<Path>>(mut self, path: P) -> ZResult<Self> {
+        let path = path.as_ref();
+        let mut value: Value = deserialize_from_file(path)
+            .map_err(|e| zerror!("Invalid configuration file {}: {}", path.display(), e))?;
+        if let Some(preset) = take_preset(&mut value)? {
+            self = self.preset(&preset)?;
+        }
+        self.merge(ConfigSource::File, value)?;
+        Ok(self)
+    }
+
+    /// Merges the JSON5 object of the `ZENOH_CONFIG_OVERRIDE` environment variable, if set.
+    pub fn env(mut self) -> ZResult<Self> {
+        if let Ok(overrides) = std::env::var(defaults::OVERRIDE_ENV) {
+            let value: Value = json5::from_str(&overrides)
+                .map_err(|e| zerror!("Invalid {}: {}", defaults::OVERRIDE_ENV, e))?;
+            self.merge(ConfigSource::Env, value)?;
+        }
+        Ok(self)
+    }
+
+    /// Merges a JSON5 `value` at `key`, e.g. `"scouting/multicast/enabled"`.
+    pub fn insert_json5(mut self, key: &str, value: &str) -> ZResult<Self> {
+        let mut value: Value =
+            json5::from_str(value).map_err(|e| zerror!("Invalid value for {}: {}", key, e))?;
+        for segment in key.split('/').filter(|s| !s.is_empty()).rev() {
+            let mut object = Map::new();
+            object.insert(segment.to_string(), value);
+            value = Value::Object(object);
+        }
+        self.merge(ConfigSource::Programmatic, value)?;
+        Ok(self)
+    }
+
+    /// The name of the preset the other layers are merged over, if any.
+    pub fn preset_name(&self) -> Option<&str> {
+        self.preset.as_deref()
+    }
+
+    /// The layer the value at `key` comes from, e.g. `"listen/endpoints"`.
+    pub fn provenance(&self, key: &str) -> Option<ConfigSource> {
+        self.layers
+            .iter()
+            .rev()
+            .find(|(_, value)| lookup(value, key).is_some())
+            .map(|(source, _)| *source)
+    }
+
+    /// The value at `key` before the expansion of the variables, e.g. `"tcp/${serial}:7447"`,
+    /// to display how a value of the loaded configuration was written.
+    pub fn template(&self, key: &str) -> Option<Value> {
+        lookup(&self.merged(), key).cloned()
+    }
+
+    /// Merges the layers, expands the variables and validates the resulting configuration.
+    ///
+    /// The variables are expanded before the validation, so that the errors show the expanded
+    /// values, see [`VARS_KEY`](crate::VARS_KEY).
+    pub fn load(&self) -> ZResult<Config> {
+        let mut value = self.merged();
+        expand_vars(&mut value)?;
+        let mut config = Config::from_deserializer(value).map_err(|e| match e {
+            Ok(c) => zerror!("Invalid configuration: {}", c),
+            Err(e) => zerror!("JSON error: {}", e),
+        })?;
+        config.plugins.load_external_configs()?;
+        Ok(config)
+    }
+
+    /// The names of the built-in and user-defined presets.
+    pub fn available_presets(&self) -> Vec<String> {
+        let mut names = BUILTIN_PRESETS
+            .iter()
+            .map(|(name, _)| name.to_string())
+            .collect::<BTreeSet<String>>();
+        for dir in self.preset_dirs.iter() {
+            let entries = match std::fs::read_dir(dir) {
+                Ok(entries) => entries,
+                Err(_) => continue,
+            };
+            for path in entries.filter_map(|e| e.ok()).map(|e| e.path()) {
+                let is_preset = path
+                    .extension()
+                    .and_then(|e| e.to_str())
+                    .map_or(false, |e| PRESET_EXTENSIONS.contains(&e));
+                if !is_preset {
+                    continue;
+                }
+                if let Some(name) = path.file_stem().and_then(|s| s.to_str()) {
+                    names.insert(name.to_string());
+                }
+            }
+        }
+        names.into_iter().collect()
+    }
+
+    fn resolve_preset(&self, name: &str) -> ZResult<Value> {
+        for dir in self.preset_dirs.iter() {
+            for extension in PRESET_EXTENSIONS {
+                let path = dir.join(format!("{name}.{extension}"));
+                if !path.is_file() {
+                    continue;
+                }
+                let mut value: Value = deserialize_from_file(&path)
+                    .map_err(|e| zerror!("Invalid preset {}: {}", path.display(), e))?;
+                if take_preset(&mut value)?.is_some() {
+                    bail!(
+                        "Invalid preset {}: presets can't name another preset",
+                        path.display()
+                    );
+                }
+                return Ok(value);
+            }
+        }
+        match BUILTIN_PRESETS.iter().find(|(n, _)| *n == name) {
+            Some((_, preset)) => Ok(json5::from_str(preset).unwrap()),
+            None => bail!(
+                "Unknown preset '{}'. Available presets: {}",
+                name,
+                self.available_presets().join(", ")
+            ),
+        }
+    }
+
+    fn merged(&self) -> Value {
+        let mut value = Value::Object(Map::new());
+        for layer in self.layers.values() {
+            merge(&mut value, layer);
+        }
+        value
+    }
+
+    fn merge(&mut self, source: ConfigSource, value: Value) -> ZResult<()> {
+        if !value.is_object() {
+            bail!("The {} configuration layer must be an object", source);
+        }
+        merge(
+            self.layers
+                .entry(source)
+                .or_insert_with(|| Value::Object(Map::new())),
+            &value,
+        );
+        Ok(())
+    }
+}
+
+impl Config {
+    /// Creates the configuration of the preset named `name`, see [`ConfigLoader`].
+    pub fn from_preset(name: &str) -> ZResult<Config> {
+        ConfigLoader::new().preset(name)?.load()
+    }
+}
+
+fn take_preset(value: &mut Value) -> ZResult<Option<String>> {
+    match value.as_object_mut().and_then(|o| o.remove(PRESET_KEY)) {
+        Some(Value::String(preset)) => Ok(Some(preset)),
+        Some(Value::Null) | None => Ok(None),
+        Some(other) => bail!("Invalid preset {}: expected a preset name", other),
+    }
+}
+
+// Merges the objects recursively, the other values of `layer` replace the ones of `base`
+fn merge(base: &mut Value, layer: &Value) {
+    match (base, layer) {
+        (Value::Object(base), Value::Object(layer)) => {
+            for (key, value) in layer.iter() {
+                match base.get_mut(key) {
+                    Some(base) => merge(base, value),
+                    None => {
+                        base.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+        }
+        (base, layer) => *base = layer.clone(),
+    }
+}
+
+fn lookup<'a>(value: &'a Value, key: &str) -> Option<&'a Value> {
+    key.split('/')
+        .filter(|s| !s.is_empty())
+        .try_fold(value, |value, segment| value.as_object()?.get(segment))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::WhatAmI;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("zenoh-config-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn config_layers_merge_order() {
+        let dir = temp_dir("layers");
+        let file = dir.join("config.json5");
+        std::fs::write(
+            &file,
+            r#"{
+                preset: "client-minimal",
+                connect: { endpoints: ["tcp/127.0.0.1:7447"] },
+                queries_default_timeout: 1000,
+                local_routing: false,
+            }"#,
+        )
+        .unwrap();
+        std::env::set_var(
+            defaults::OVERRIDE_ENV,
+            "{ queries_default_timeout: 2000, local_routing: true }",
+        );
+
+        let loader = ConfigLoader::new()
+            .insert_json5("local_routing", "false")
+            .unwrap()
+            .env()
+            .unwrap()
+            .file(&file)
+            .unwrap();
+        std::env::remove_var(defaults::OVERRIDE_ENV);
+        let config = loader.load().unwrap();
+
+        // preset < file < env < programmatic
+        assert_eq!(loader.preset_name(), Some("client-minimal"));
+        assert_eq!(*config.mode(), Some(WhatAmI::Client));
+        assert!(config.listen().endpoints().is_empty());
+        assert_eq!(config.connect().endpoints().len(), 1);
+        assert_eq!(*config.queries_default_timeout(), Some(2000));
+        assert_eq!(*config.local_routing(), Some(false));
+
+        let provenance = |key| loader.provenance(key).unwrap().to_string();
+        assert_eq!(provenance("mode"), "preset");
+        assert_eq!(provenance("scouting/gossip/enabled"), "preset");
+        assert_eq!(provenance("connect/endpoints"), "file");
+        assert_eq!(provenance("queries_default_timeout"), "env");
+        assert_eq!(provenance("local_routing"), "programmatic");
+        assert_eq!(loader.provenance("timestamping"), None);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn config_vars_expansion() {
+        let dir = temp_dir("vars");
+        let file = dir.join("config.json5");
+        std::fs::write(
+            &file,
+            r#"{
+                vars: { serial: "a1b2c3", mode: "client" },
+                mode: "${mode}",
+                connect: { endpoints: ["tcp/${serial}.fleet.example.com:7447"] },
+                metadata: { name: "device-${serial}" },
+            }"#,
+        )
+        .unwrap();
+
+        // The variables of the file can be overridden by the other layers
+        let loader = ConfigLoader::new().file(&file).unwrap();
+        let config = loader.load().unwrap();
+        assert_eq!(*config.mode(), Some(WhatAmI::Client));
+        assert_eq!(
+            config.connect().endpoints()[0].to_string(),
+            "tcp/a1b2c3.fleet.example.com:7447"
+        );
+        let config = loader
+            .clone()
+            .insert_json5("vars/serial", r#""d4e5f6""#)
+            .unwrap()
+            .load()
+            .unwrap();
+        assert_eq!(config.metadata()["name"], "device-d4e5f6");
+
+        // The template is kept for display
+        assert_eq!(
+            loader.template("connect/endpoints").unwrap()[0],
+            "tcp/${serial}.fleet.example.com:7447"
+        );
+        assert_eq!(
+            loader.provenance("connect/endpoints"),
+            Some(ConfigSource::File)
+        );
+
+        // The validation errors show the expanded values
+        let e = loader
+            .insert_json5("vars/mode", r#""gateway""#)
+            .unwrap()
+            .load()
+            .unwrap_err()
+            .to_string();
+        assert!(e.contains("gateway"), "{e}");
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn config_presets() {
+        for (name, _) in BUILTIN_PRESETS {
+            Config::from_preset(name).unwrap();
+        }
+        assert_eq!(
+            *Config::from_preset("router-default").unwrap().mode(),
+            Some(WhatAmI::Router)
+        );
+
+        // The unknown presets fail with the available ones
+        let e = Config::from_preset("unknown").unwrap_err().to_string();
+        assert!(
+            e.contains("client-minimal, peer-lan, router-default"),
+            "{e}"
+        );
+
+        // The user-defined presets are read from the preset directories
+        let dir = temp_dir("presets");
+        std::fs::write(
+            dir.join("edge.json5"),
+            r#"{ mode: "peer", queries_default_timeout: 500 }"#,
+        )
+        .unwrap();
+        let loader = ConfigLoader::new().preset_dir(&dir);
+        let config = loader.clone().preset("edge").unwrap().load().unwrap();
+        assert_eq!(*config.queries_default_timeout(), Some(500));
+        let e = loader.preset("unknown").unwrap_err().to_string();
+        assert!(e.contains("client-minimal, edge, peer-lan"), "{e}");
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
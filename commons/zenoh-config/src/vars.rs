@@ -0,0 +1,196 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use zenoh_result::{bail, ZResult};
+
+/// The key of the variables of a configuration, expanded in its strings.
+pub const VARS_KEY: &str = "vars";
+
+/// Removes the `vars` object of a configuration and expands the variables in all its strings.
+///
+/// `${NAME}` is replaced by the `NAME` variable of the `vars` object, or else by the `NAME`
+/// environment variable. `$${` is replaced by a literal `${`. The variables aren't expanded in
+/// the keys of the objects, nor in the values of the `vars` object.
+pub(crate) fn expand_vars(config: &mut Value) -> ZResult<()> {
+    let vars = match config.as_object_mut().and_then(|o| o.remove(VARS_KEY)) {
+        Some(Value::Object(vars)) => vars,
+        Some(Value::Null) | None => Map::new(),
+        Some(other) => bail!("Invalid {}: expected an object, got {}", VARS_KEY, other),
+    };
+    let mut values = HashMap::new();
+    for (name, value) in vars {
+        let value = match value {
+            Value::String(value) => value,
+            Value::Number(_) | Value::Bool(_) => value.to_string(),
+            other => bail!(
+                "Invalid {}/{}: expected a string, a number or a boolean, got {}",
+                VARS_KEY,
+                name,
+                other
+            ),
+        };
+        values.insert(name, value);
+    }
+    expand_value(config, &values, &mut String::new())
+}
+
+fn expand_value(
+    value: &mut Value,
+    vars: &HashMap<String, String>,
+    path: &mut String,
+) -> ZResult<()> {
+    match value {
+        Value::String(s) => {
+            if s.contains('$') {
+                *s = expand_str(s, vars, path)?;
+            }
+        }
+        Value::Array(values) => {
+            for (i, value) in values.iter_mut().enumerate() {
+                let len = path.len();
+                path.push_str(&format!("/{i}"));
+                expand_value(value, vars, path)?;
+                path.truncate(len);
+            }
+        }
+        Value::Object(values) => {
+            for (key, value) in values.iter_mut() {
+                let len = path.len();
+                path.push('/');
+                path.push_str(key);
+                expand_value(value, vars, path)?;
+                path.truncate(len);
+            }
+        }
+        Value::Null | Value::Bool(_) | Value::Number(_) => {}
+    }
+    Ok(())
+}
+
+fn expand_str(s: &str, vars: &HashMap<String, String>, path: &str) -> ZResult<String> {
+    let path = path.strip_prefix('/').unwrap_or(path);
+    let mut expanded = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(i) = rest.find('$') {
+        expanded.push_str(&rest[..i]);
+        rest = &rest[i..];
+        if let Some(escaped) = rest.strip_prefix("$${") {
+            expanded.push_str("${");
+            rest = escaped;
+        } else if let Some(var) = rest.strip_prefix("${") {
+            let end = match var.find('}') {
+                Some(end) => end,
+                None => bail!("Invalid {}: unterminated variable in `{}`", path, s),
+            };
+            let name = &var[..end];
+            if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                bail!(
+                    "Invalid {}: invalid variable name `{}` in `{}`",
+                    path,
+                    name,
+                    s
+                );
+            }
+            match vars.get(name) {
+                Some(value) => expanded.push_str(value),
+                None => match std::env::var(name) {
+                    Ok(value) => expanded.push_str(&value),
+                    Err(_) => bail!(
+                        "Invalid {}: undefined variable `{}` in `{}` (neither in {} nor in the environment)",
+                        path,
+                        name,
+                        s,
+                        VARS_KEY
+                    ),
+                },
+            }
+            rest = &var[end + 1..];
+        } else {
+            expanded.push('$');
+            rest = &rest[1..];
+        }
+    }
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expand(config: &str) -> ZResult<Value> {
+        let mut config: Value = json5::from_str(config).unwrap();
+        expand_vars(&mut config)?;
+        Ok(config)
+    }
+
+    #[test]
+    fn config_vars() {
+        std::env::set_var("ZENOH_CONFIG_TEST_SERIAL", "env-serial");
+        let config = expand(
+            r#"{
+                vars: { site: "lyon", port: 7447 },
+                listen: { endpoints: ["tcp/${site}.example.com:${port}"] },
+                plugins: {
+                    storage_manager: {
+                        storages: {
+                            device: { key_expr: "fleet/${ZENOH_CONFIG_TEST_SERIAL}/**", costs: "$5" },
+                        },
+                    },
+                },
+                transport: { link: { tls: { root_ca_certificate: "/etc/${site}/ca.pem" } } },
+                metadata: { literal: "$${site} and ${site}", tags: [{ site: ["${site}"] }] },
+            }"#,
+        )
+        .unwrap();
+        std::env::remove_var("ZENOH_CONFIG_TEST_SERIAL");
+
+        // The vars are removed, and expanded in the strings of lists inside objects
+        assert!(config.get(VARS_KEY).is_none());
+        assert_eq!(
+            config["listen"]["endpoints"][0],
+            "tcp/lyon.example.com:7447"
+        );
+        let device = &config["plugins"]["storage_manager"]["storages"]["device"];
+        assert_eq!(device["key_expr"], "fleet/env-serial/**");
+        assert_eq!(device["costs"], "$5");
+        assert_eq!(
+            config["transport"]["link"]["tls"]["root_ca_certificate"],
+            "/etc/lyon/ca.pem"
+        );
+        // The escape gives a literal `${`
+        assert_eq!(config["metadata"]["literal"], "${site} and lyon");
+        assert_eq!(config["metadata"]["tags"][0]["site"][0], "lyon");
+    }
+
+    #[test]
+    fn config_vars_errors() {
+        // The undefined variables fail with where they are used
+        let e = expand(r#"{ vars: { a: "1" }, connect: { endpoints: ["tcp/${serial}:7447"] } }"#)
+            .unwrap_err()
+            .to_string();
+        assert!(e.contains("connect/endpoints/0"), "{e}");
+        assert!(e.contains("undefined variable `serial`"), "{e}");
+
+        let e = expand(r#"{ id: "${unterminated" }"#)
+            .unwrap_err()
+            .to_string();
+        assert!(e.contains("unterminated variable"), "{e}");
+        let e = expand(r#"{ id: "${}" }"#).unwrap_err().to_string();
+        assert!(e.contains("invalid variable name"), "{e}");
+        assert!(expand(r#"{ vars: ["a"] }"#).is_err());
+        assert!(expand(r#"{ vars: { a: { b: "c" } } }"#).is_err());
+    }
+}
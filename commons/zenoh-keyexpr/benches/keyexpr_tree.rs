@@ -23,7 +23,7 @@ use zenoh_keyexpr::keyexpr_tree::{
     traits::*,
     KeArcTree, KeBoxTree,
 };
-use zenoh_keyexpr::{fuzzer::KeyExprFuzzer, OwnedKeyExpr};
+use zenoh_keyexpr::{fuzzer::KeyExprFuzzer, KeMap, OwnedKeyExpr};
 
 #[derive(Clone, Copy, Debug, Default)]
 pub struct Averager {
@@ -130,6 +130,31 @@ fn main() {
             println!();
         }
     }
+
+    // The local routing of the publications of a session with 5k subscribers: a scan of all the
+    // subscribers versus a lookup in a KeMap.
+    let subscribers = KeySet::generate(5000, 0.1, false);
+    let publications = KeySet::generate(1000, 0., true);
+    let results = Benchmarker::benchmark(|b| {
+        let mut kemap = KeMap::new();
+        let mut scan = Vec::new();
+        for (i, key) in subscribers.iter().enumerate() {
+            kemap.insert(key.clone(), i);
+            scan.push((key.clone(), i));
+        }
+        for key in publications.iter() {
+            b.run_once("kemap_publication", || {
+                kemap.values_intersecting(key).count()
+            });
+            b.run_once("scan_publication", || {
+                scan.iter().filter(|(k, _)| key.intersects(k)).count()
+            });
+        }
+    });
+    for name in ["kemap_publication", "scan_publication"] {
+        let stats = results.benches.get(name).unwrap().full_stats();
+        println!("{name}_{subscribers}\n\t{stats:.2e}")
+    }
 }
 
 pub struct Benchmarker {
@@ -77,12 +77,17 @@ impl keyexpr {
     }
 
     /// Returns `true` if the `keyexpr`s intersect, i.e. there exists at least one key which is contained in both of the sets defined by `self` and `other`.
+    ///
+    /// Both key expressions are assumed canon, as guaranteed by the safe constructors: the result is unspecified
+    /// for the strings passed to [`keyexpr::from_str_unchecked`] without being canonized, see [`canon::Canonizable`](super::canon::Canonizable).
     pub fn intersects(&self, other: &Self) -> bool {
         use super::intersect::Intersector;
         super::intersect::DEFAULT_INTERSECTOR.intersect(self, other)
     }
 
     /// Returns `true` if `self` includes `other`, i.e. the set defined by `self` contains every key belonging to the set defined by `other`.
+    ///
+    /// Both key expressions are assumed canon, see [`keyexpr::intersects`].
     pub fn includes(&self, other: &Self) -> bool {
         use super::include::Includer;
         super::include::DEFAULT_INCLUDER.includes(self, other)
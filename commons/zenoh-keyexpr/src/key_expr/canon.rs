@@ -12,16 +12,32 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 use crate::key_expr::{
+    keyexpr,
     utils::{Split, Writer},
     DELIMITER, DOUBLE_WILD, SINGLE_WILD,
 };
 use alloc::string::String;
-use core::{slice, str};
+use core::{convert::TryFrom, slice, str};
+use zenoh_result::ZResult;
 
+/// The values that can be put in canon-form in place, see [`keyexpr`](super::keyexpr).
+///
+/// Canonization makes the strings denoting the same set of keys equal: contiguous `$*`s and `**`
+/// chunks are collapsed, `$*` chunks are replaced by `*`, `**/*` is replaced by `*/**`, and the
+/// empty chunks of `//` are dropped. It doesn't make an invalid key expression valid, e.g. with a
+/// leading or trailing `/`, or a `#` or a `?`.
 pub trait Canonizable {
     fn canonize(&mut self);
 }
 
+/// Puts `s` in canon-form, see [`Canonizable`], then checks that it is a valid key expression,
+/// returning the reason why it isn't otherwise.
+pub fn canonize(s: &mut String) -> ZResult<()> {
+    s.canonize();
+    <&keyexpr>::try_from(s.as_str())?;
+    Ok(())
+}
+
 const DOLLAR_STAR: &[u8; 2] = b"$*";
 
 impl Canonizable for &mut str {
@@ -50,11 +66,19 @@ impl Canonizable for &mut str {
         writer.len = 0;
         let mut ke = self.as_bytes().splitter(&b'/');
         let mut in_big_wild = false;
+        // The empty chunks of `//` are dropped, but a leading or trailing `/` is kept for the
+        // key expression to be rejected
+        let mut trailing_empty = false;
 
         for chunk in ke.by_ref() {
             if chunk.is_empty() {
+                if in_big_wild {
+                    trailing_empty = true;
+                    continue;
+                }
                 break;
             }
+            trailing_empty = false;
             if in_big_wild {
                 match chunk {
                     [SINGLE_WILD] | b"$*" => {
@@ -79,9 +103,10 @@ impl Canonizable for &mut str {
         }
         for chunk in ke {
             if chunk.is_empty() {
-                writer.write_byte(b'/');
+                trailing_empty = true;
                 continue;
             }
+            trailing_empty = false;
             if in_big_wild {
                 match chunk {
                     [SINGLE_WILD] | b"$*" => {
@@ -107,6 +132,9 @@ impl Canonizable for &mut str {
             }
             writer.write(DOUBLE_WILD)
         }
+        if trailing_empty {
+            writer.write_byte(DELIMITER);
+        }
         *self = unsafe {
             str::from_utf8_unchecked_mut(slice::from_raw_parts_mut(writer.ptr, writer.len))
         }
@@ -171,4 +199,31 @@ fn canonizer() {
     let mut s = String::from("hello/**/*");
     s.canonize();
     assert_eq!(s, "hello/*/**");
+
+    // The empty chunks are dropped, but the leading and trailing ones
+    let mut s = String::from("hello//bye");
+    s.canonize();
+    assert_eq!(s, "hello/bye");
+    let mut s = String::from("**//hello/**//**/bye");
+    s.canonize();
+    assert_eq!(s, "**/hello/**/bye");
+    for s in ["/hello", "hello/", "hello//", "**/", "hello/**//"] {
+        let mut s = String::from(s);
+        assert!(canonize(&mut s).is_err(), "{s}");
+    }
+
+    // The invalid key expressions are rejected with the reason
+    for (s, reason) in [
+        (
+            "hello/***",
+            "`*` and `**` may only be preceded an followed by `/`",
+        ),
+        ("hello/a#b", "`#` and `?` are forbidden characters"),
+        ("hello/a?b", "`#` and `?` are forbidden characters"),
+        ("hello/a$b", "`$` is only allowed in `$*`"),
+    ] {
+        let mut s = String::from(s);
+        let e = canonize(&mut s).unwrap_err();
+        assert!(e.to_string().contains(reason), "{e}");
+    }
 }
@@ -11,7 +11,8 @@
 // Contributors:
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
-use super::{keyexpr, utils::Split, DELIMITER, DOUBLE_WILD, STAR_DSL};
+use super::{keyexpr, utils::Split, DELIMITER, DOUBLE_WILD, REACHED_INLINE, STAR_DSL};
+use alloc::vec;
 
 pub const DEFAULT_INCLUDER: LTRIncluder = LTRIncluder;
 
@@ -32,36 +33,98 @@ impl<T: for<'a> Includer<&'a [u8], &'a [u8]>> Includer<&keyexpr, &keyexpr> for T
 }
 
 pub struct LTRIncluder;
+
+fn chunks(s: &[u8]) -> impl Iterator<Item = &[u8]> {
+    s.split(|c| *c == DELIMITER)
+}
 impl Includer<&[u8], &[u8]> for LTRIncluder {
     fn includes(&self, mut left: &[u8], mut right: &[u8]) -> bool {
         loop {
             let (lchunk, lrest) = left.split_once(&DELIMITER);
             let lempty = lrest.is_empty();
             if lchunk == DOUBLE_WILD {
-                if lempty || self.includes(lrest, right) {
-                    return true;
-                }
-                right = right.split_once(&DELIMITER).1;
-                if right.is_empty() {
-                    return false;
-                }
-            } else {
-                let (rchunk, rrest) = right.split_once(&DELIMITER);
-                if rchunk.is_empty() || !self.non_double_wild_chunk_includes(lchunk, rchunk) {
-                    return false;
-                }
-                let rempty = rrest.is_empty();
-                if lempty {
-                    return rempty;
-                }
-                left = lrest;
-                right = rrest;
+                return lempty || self.double_wild_includes(left, right);
+            }
+            let (rchunk, rrest) = right.split_once(&DELIMITER);
+            if rchunk.is_empty() || !self.non_double_wild_chunk_includes(lchunk, rchunk) {
+                return false;
+            }
+            let rempty = rrest.is_empty();
+            if lempty {
+                return rempty;
             }
+            left = lrest;
+            right = rrest;
         }
     }
 }
 
 impl LTRIncluder {
+    /// Returns `true` if `left`, starting with a `**`, includes `right`.
+    ///
+    /// If the `**` is the only one of `left`, the rest of `left` includes the last chunks of
+    /// `right`. Otherwise, goes through the pairs of chunks `(i, j)` of `left` and `right` reached by matching them
+    /// from their start, a `**` of `left` either matching one more chunk of `right` or being done.
+    /// Each pair is considered once: `O(n1 * n2)` chunk inclusions at worst for `n1` and `n2`
+    /// chunks, with the reached pairs of 2 rows of `left` kept, i.e. `O(n2)` memory, whatever the
+    /// number of `**`.
+    fn double_wild_includes(&self, mut left: &[u8], right: &[u8]) -> bool {
+        let lrest = left.split_once(&DELIMITER).1;
+        if !chunks(lrest).any(|c| c == DOUBLE_WILD) {
+            let (n1, n2) = (chunks(lrest).count(), chunks(right).count());
+            return n1 <= n2
+                && chunks(lrest)
+                    .zip(chunks(right).skip(n2 - n1))
+                    .all(|(l, r)| !r.is_empty() && self.non_double_wild_chunk_includes(l, r));
+        }
+        // The chunks of `right` and its end
+        let w = right.iter().filter(|c| **c == DELIMITER).count() + 2;
+        let at = |i: usize, j: usize| (i % 2) * w + j;
+        let mut inline = [false; REACHED_INLINE];
+        let mut allocated;
+        let reached = if 2 * w <= REACHED_INLINE {
+            &mut inline[..2 * w]
+        } else {
+            allocated = vec![false; 2 * w];
+            &mut allocated[..]
+        };
+        reached[0] = true;
+        let mut i = 0;
+        while !left.is_empty() {
+            let (lchunk, lrest) = left.split_once(&DELIMITER);
+            let lempty = lrest.is_empty();
+            let mut rest = right;
+            for j in 0..w {
+                let (rchunk, rrest) = rest.split_once(&DELIMITER);
+                rest = rrest;
+                if !reached[at(i, j)] {
+                    continue;
+                }
+                if lchunk == DOUBLE_WILD {
+                    if lempty {
+                        return true;
+                    }
+                    reached[at(i + 1, j)] = true;
+                    if !rrest.is_empty() {
+                        reached[at(i, j + 1)] = true;
+                    }
+                } else if !rchunk.is_empty() && self.non_double_wild_chunk_includes(lchunk, rchunk)
+                {
+                    if !lempty {
+                        reached[at(i + 1, j + 1)] = true;
+                    } else if rrest.is_empty() {
+                        return true;
+                    }
+                }
+            }
+            // The row of `i` is reused for `i + 2`
+            reached[at(i, 0)..at(i, 0) + w].fill(false);
+            left = lrest;
+            i += 1;
+        }
+        false
+    }
+
     fn non_double_wild_chunk_includes(&self, lchunk: &[u8], rchunk: &[u8]) -> bool {
         if lchunk == b"*" || lchunk == rchunk {
             true
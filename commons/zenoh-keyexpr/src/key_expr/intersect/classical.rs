@@ -11,51 +11,63 @@
 // Contributors:
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
+use crate::key_expr::REACHED_INLINE;
+use alloc::vec;
+
+/// Returns `true` if the chunks `c1` and `c2` intersect, their `$*` matching any bytes.
+///
+/// Goes through the pairs of positions `(i, j)` in `c1` and `c2` reached by matching them from
+/// their start, a `$*` either matching one more byte of the other chunk or being done. Each pair
+/// is considered once: `O(|c1| * |c2|)` steps at worst, with the reached pairs of 3 rows of `c1`
+/// kept, i.e. `O(|c2|)` memory, whatever the number of `$*`.
 #[cold]
-fn star_dsl_intersect(mut it1: &[u8], mut it2: &[u8]) -> bool {
-    fn next(s: &[u8]) -> (u8, &[u8]) {
-        (s[0], &s[1..])
-    }
-    while !it1.is_empty() && !it2.is_empty() {
-        let (current1, advanced1) = next(it1);
-        let (current2, advanced2) = next(it2);
-        match (current1, current2) {
-            (b'$', b'$') => {
-                if advanced1.len() == 1 || advanced2.len() == 1 {
-                    return true;
-                }
-                if star_dsl_intersect(&advanced1[1..], it2) {
-                    return true;
-                } else {
-                    return star_dsl_intersect(it1, &advanced2[1..]);
-                };
+fn star_dsl_intersect(c1: &[u8], c2: &[u8]) -> bool {
+    let (l1, l2) = (c1.len(), c2.len());
+    let w = l2 + 1;
+    let at = |i: usize, j: usize| (i % 3) * w + j;
+    let mut reached = vec![false; 3 * w];
+    reached[0] = true;
+    for i in 0..=l1 {
+        for j in 0..=l2 {
+            if !reached[at(i, j)] {
+                continue;
             }
-            (b'$', _) => {
-                if advanced1.len() == 1 {
+            if i == l1 || j == l2 {
+                if i == l1 && j == l2 || &c1[i..] == b"$*" || &c2[j..] == b"$*" {
                     return true;
                 }
-                if star_dsl_intersect(&advanced1[1..], it2) {
-                    return true;
-                }
-                it2 = advanced2;
+                continue;
             }
-            (_, b'$') => {
-                if advanced2.len() == 1 {
-                    return true;
+            match (c1[i], c2[j]) {
+                (b'$', b'$') => {
+                    if i + 2 == l1 || j + 2 == l2 {
+                        return true;
+                    }
+                    reached[at(i + 2, j)] = true;
+                    reached[at(i, j + 2)] = true;
                 }
-                if star_dsl_intersect(it1, &advanced2[1..]) {
-                    return true;
+                (b'$', _) => {
+                    if i + 2 == l1 {
+                        return true;
+                    }
+                    reached[at(i + 2, j)] = true;
+                    reached[at(i, j + 1)] = true;
                 }
-                it1 = advanced1;
-            }
-            (sub1, sub2) if sub1 == sub2 => {
-                it1 = advanced1;
-                it2 = advanced2;
+                (_, b'$') => {
+                    if j + 2 == l2 {
+                        return true;
+                    }
+                    reached[at(i, j + 2)] = true;
+                    reached[at(i + 1, j)] = true;
+                }
+                (b1, b2) if b1 == b2 => reached[at(i + 1, j + 1)] = true,
+                (_, _) => {}
             }
-            (_, _) => return false,
         }
+        // The row of `i` is reused for `i + 3`
+        reached[at(i, 0)..at(i, 0) + w].fill(false);
     }
-    it1.is_empty() && it2.is_empty() || it1 == b"$*" || it2 == b"$*"
+    false
 }
 
 fn chunk_it_intersect<const STAR_DSL: bool>(it1: &[u8], it2: &[u8]) -> bool {
@@ -77,21 +89,18 @@ fn next(s: &[u8]) -> (&[u8], &[u8]) {
     }
 }
 
+fn chunks(s: &[u8]) -> impl Iterator<Item = &[u8]> {
+    s.split(|c| *c == b'/')
+}
+
 fn it_intersect<const STAR_DSL: bool>(mut it1: &[u8], mut it2: &[u8]) -> bool {
     while !it1.is_empty() && !it2.is_empty() {
         let (current1, advanced1) = next(it1);
         let (current2, advanced2) = next(it2);
         match (current1, current2) {
-            (b"**", _) => {
-                return advanced1.is_empty()
-                    || it_intersect::<STAR_DSL>(advanced1, it2)
-                    || it_intersect::<STAR_DSL>(it1, advanced2);
-            }
-            (_, b"**") => {
-                return advanced2.is_empty()
-                    || it_intersect::<STAR_DSL>(it1, advanced2)
-                    || it_intersect::<STAR_DSL>(advanced1, it2);
-            }
+            (b"**", _) if advanced1.is_empty() => return true,
+            (_, b"**") if advanced2.is_empty() => return true,
+            (b"**", _) | (_, b"**") => return double_wild_intersect::<STAR_DSL>(it1, it2),
             (sub1, sub2) if chunk_intersect::<STAR_DSL>(sub1, sub2) => {
                 it1 = advanced1;
                 it2 = advanced2;
@@ -101,6 +110,87 @@ fn it_intersect<const STAR_DSL: bool>(mut it1: &[u8], mut it2: &[u8]) -> bool {
     }
     (it1.is_empty() || it1 == b"**") && (it2.is_empty() || it2 == b"**")
 }
+
+/// Returns `true` if `it1` and `it2` intersect, their `**` matching any chunks.
+///
+/// If the `**` starting one of them is the only one of both, the rest of it intersects the last
+/// chunks of the other one. Otherwise, goes through the pairs of chunks `(i, j)` of `it1` and `it2` reached by matching them from
+/// their start, a `**` either matching one more chunk of the other expression or being done.
+/// Each pair is considered once: `O(n1 * n2)` chunk intersections at worst for `n1` and `n2`
+/// chunks, with the reached pairs of 2 rows of `it1` kept, i.e. `O(n2)` memory, whatever the
+/// number of `**`.
+fn double_wild_intersect<const STAR_DSL: bool>(it1: &[u8], it2: &[u8]) -> bool {
+    let (wild, other) = if next(it1).0 == b"**" {
+        (next(it1).1, it2)
+    } else {
+        (next(it2).1, it1)
+    };
+    if !chunks(wild).chain(chunks(other)).any(|c| c == b"**") {
+        let (n1, n2) = (chunks(wild).count(), chunks(other).count());
+        return n1 <= n2
+            && chunks(wild)
+                .zip(chunks(other).skip(n2 - n1))
+                .all(|(c1, c2)| chunk_intersect::<STAR_DSL>(c1, c2));
+    }
+    let w = it2.iter().filter(|c| **c == b'/').count() + 2;
+    let at = |i: usize, j: usize| (i % 2) * w + j;
+    let mut inline = [false; REACHED_INLINE];
+    let mut allocated;
+    let reached = if 2 * w <= REACHED_INLINE {
+        &mut inline[..2 * w]
+    } else {
+        allocated = vec![false; 2 * w];
+        &mut allocated[..]
+    };
+    reached[0] = true;
+    let mut rest1 = it1;
+    let mut i = 0;
+    loop {
+        let (current1, advanced1) = next(rest1);
+        let mut rest2 = it2;
+        for j in 0..w {
+            let (current2, advanced2) = next(rest2);
+            if !reached[at(i, j)] {
+                rest2 = advanced2;
+                continue;
+            }
+            if rest1.is_empty() || rest2.is_empty() {
+                if (rest1.is_empty() || rest1 == b"**") && (rest2.is_empty() || rest2 == b"**") {
+                    return true;
+                }
+            } else {
+                match (current1, current2) {
+                    (b"**", _) => {
+                        if advanced1.is_empty() {
+                            return true;
+                        }
+                        reached[at(i + 1, j)] = true;
+                        reached[at(i, j + 1)] = true;
+                    }
+                    (_, b"**") => {
+                        if advanced2.is_empty() {
+                            return true;
+                        }
+                        reached[at(i, j + 1)] = true;
+                        reached[at(i + 1, j)] = true;
+                    }
+                    (sub1, sub2) if chunk_intersect::<STAR_DSL>(sub1, sub2) => {
+                        reached[at(i + 1, j + 1)] = true
+                    }
+                    (_, _) => {}
+                }
+            }
+            rest2 = advanced2;
+        }
+        if rest1.is_empty() {
+            return false;
+        }
+        // The row of `i` is reused for `i + 2`
+        reached[at(i, 0)..at(i, 0) + w].fill(false);
+        rest1 = advanced1;
+        i += 1;
+    }
+}
 /// Retruns `true` if the given key expressions intersect.
 ///
 /// I.e. if it exists a resource key (with no wildcards) that matches
@@ -0,0 +1,181 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use super::{keyexpr, OwnedKeyExpr};
+use crate::keyexpr_tree::{
+    IKeyExprTree, IKeyExprTreeExt, IKeyExprTreeExtMut, IKeyExprTreeMut, IKeyExprTreeNode, KeBoxTree,
+};
+use core::fmt;
+
+/// A map of values addressed by key expressions, backed by a [`KeBoxTree`].
+///
+/// Unlike a `HashMap<OwnedKeyExpr, V>`, finding the values whose key expression intersects with
+/// or includes a given key expression doesn't require scanning all the entries of the map.
+pub struct KeMap<V: 'static> {
+    tree: KeBoxTree<V>,
+    len: usize,
+    // Number of removals since the last pruning of the tree
+    removed: usize,
+}
+
+impl<V: 'static> KeMap<V> {
+    pub fn new() -> Self {
+        KeMap {
+            tree: KeBoxTree::new(),
+            len: 0,
+            removed: 0,
+        }
+    }
+
+    /// Returns the number of values in the map.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Inserts a value at `key`, returning the previous value at `key` if it existed.
+    pub fn insert(&mut self, key: OwnedKeyExpr, value: V) -> Option<V> {
+        let previous = self.tree.insert(&key, value);
+        if previous.is_none() {
+            self.len += 1;
+        }
+        previous
+    }
+
+    /// Removes the value at `key`, treating `key` as a litteral key.
+    pub fn remove(&mut self, key: &keyexpr) -> Option<V> {
+        let value = self.tree.remove(key)?;
+        self.len -= 1;
+        // Removing a value may leave empty nodes behind: prune them once they may outnumber the
+        // values, keeping the cost of the pruning amortized over the removals.
+        self.removed += 1;
+        if self.removed > self.len {
+            self.tree.prune();
+            self.removed = 0;
+        }
+        Some(value)
+    }
+
+    /// Returns a reference to the value at `key`, treating `key` as a litteral key.
+    pub fn get(&self, key: &keyexpr) -> Option<&V> {
+        self.tree.weight_at(key)
+    }
+
+    /// Returns a mutable reference to the value at `key`, treating `key` as a litteral key.
+    pub fn get_mut(&mut self, key: &keyexpr) -> Option<&mut V> {
+        self.tree.weight_at_mut(key)
+    }
+
+    /// Iterates over the values whose key expression intersects with `key`.
+    pub fn values_intersecting<'a>(&'a self, key: &'a keyexpr) -> impl Iterator<Item = &'a V> {
+        self.tree
+            .intersecting_nodes(key)
+            .filter_map(|node| node.weight())
+    }
+
+    /// Iterates over the values whose key expression includes `key`.
+    pub fn values_including<'a>(&'a self, key: &'a keyexpr) -> impl Iterator<Item = &'a V> {
+        // The key expressions including `key` are a subset of the ones intersecting with it
+        self.tree
+            .intersecting_nodes(key)
+            .filter_map(move |node| match node.weight() {
+                Some(value) if node.keyexpr().includes(key) => Some(value),
+                _ => None,
+            })
+    }
+
+    /// Iterates over the key expressions and values of the map.
+    pub fn iter(&self) -> impl Iterator<Item = (OwnedKeyExpr, &V)> {
+        self.tree.key_value_pairs()
+    }
+}
+
+impl<V: 'static> Default for KeMap<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V: fmt::Debug + 'static> fmt::Debug for KeMap<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::KeMap;
+    use crate::fuzzer::KeyExprFuzzer;
+    use crate::OwnedKeyExpr;
+    use rand::Rng;
+    use std::collections::HashMap;
+
+    // Checks the map against a brute-force reference scanning all its entries
+    fn check(map: &KeMap<usize>, reference: &HashMap<OwnedKeyExpr, usize>, target: &OwnedKeyExpr) {
+        assert_eq!(map.len(), reference.len());
+
+        let mut expected = reference
+            .iter()
+            .filter_map(|(k, v)| target.intersects(k).then_some(*v))
+            .collect::<Vec<_>>();
+        let mut values = map.values_intersecting(target).copied().collect::<Vec<_>>();
+        expected.sort_unstable();
+        values.sort_unstable();
+        assert_eq!(values, expected, "values intersecting {target}");
+
+        let mut expected = reference
+            .iter()
+            .filter_map(|(k, v)| k.includes(target).then_some(*v))
+            .collect::<Vec<_>>();
+        let mut values = map.values_including(target).copied().collect::<Vec<_>>();
+        expected.sort_unstable();
+        values.sort_unstable();
+        assert_eq!(values, expected, "values including {target}");
+    }
+
+    #[test]
+    fn kemap_fuzz() {
+        let mut rng = rand::thread_rng();
+        let mut fuzzer = KeyExprFuzzer(rand::thread_rng());
+        for _ in 0..100 {
+            let keys = (&mut fuzzer).take(100).collect::<Vec<_>>();
+            let mut map = KeMap::new();
+            let mut reference = HashMap::new();
+            for (v, k) in keys.iter().enumerate() {
+                assert_eq!(map.insert(k.clone(), v), reference.insert(k.clone(), v));
+            }
+            for target in &keys {
+                assert_eq!(map.get(target), reference.get(target));
+                check(&map, &reference, target);
+            }
+
+            // Remove half of the keys, checking the map after each removal
+            for k in &keys {
+                if rng.gen_bool(0.5) {
+                    assert_eq!(map.remove(k), reference.remove(k));
+                    let target = &keys[rng.gen_range(0..keys.len())];
+                    check(&map, &reference, target);
+                }
+            }
+            let mut entries = map.iter().map(|(k, v)| (k, *v)).collect::<Vec<_>>();
+            let mut expected = reference.into_iter().collect::<Vec<_>>();
+            entries.sort_unstable_by(|(a, _), (b, _)| a.as_str().cmp(b.as_str()));
+            expected.sort_unstable_by(|(a, _), (b, _)| a.as_str().cmp(b.as_str()));
+            assert_eq!(entries, expected);
+        }
+    }
+}
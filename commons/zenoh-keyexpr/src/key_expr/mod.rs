@@ -19,6 +19,9 @@ pub(crate) const SINGLE_WILD: u8 = b'*';
 pub(crate) const DOUBLE_WILD: &[u8] = b"**";
 pub(crate) const STAR_DSL: &[u8] = b"$*";
 pub(crate) const FORBIDDEN_CHARS: [u8; 3] = [b'#', b'?', b'$'];
+// The reached pairs of the iterative matchings of the `**` kept on the stack, the larger ones
+// being allocated
+pub(crate) const REACHED_INLINE: usize = 128;
 
 pub(crate) mod owned;
 pub use owned::OwnedKeyExpr;
@@ -26,6 +29,9 @@ pub use owned::OwnedKeyExpr;
 pub(crate) mod borrowed;
 pub use borrowed::*;
 
+mod map;
+pub use map::KeMap;
+
 /// Used to implement and expose the tools to implement canonization of Key Expressions for string-like types.
 /// The average user doesn't need to bother with it.
 pub mod canon;
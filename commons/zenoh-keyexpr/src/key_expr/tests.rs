@@ -161,3 +161,312 @@ fn fuzz() {
         ke1 = ke2;
     }
 }
+
+// A key expression which may not be canon: contiguous `**`s and `$*`s, `$*` chunks, `**/*`, and
+// empty chunks between the others
+fn random_raw_expr(rng: &mut impl rand::Rng) -> String {
+    const CHUNKS: [&str; 10] = ["a", "b", "ab", "*", "**", "$*", "a$*", "$*b", "a$*$*b", ""];
+    let n = rng.gen_range(1..=5);
+    let mut chunks: Vec<&str> = (0..n)
+        .map(|_| CHUNKS[rng.gen_range(0..CHUNKS.len())])
+        .collect();
+    chunks.retain(|c| !c.is_empty() || rng.gen_bool(0.5));
+    if chunks.first().map_or(true, |c| c.is_empty()) {
+        chunks.insert(0, "a");
+    }
+    if chunks.last().map_or(true, |c| c.is_empty()) {
+        chunks.push("b");
+    }
+    chunks.join("/")
+}
+
+// Whether the chunk of a key matches the chunk of a key expression, by brute force
+fn chunk_matches(pattern: &str, chunk: &str) -> bool {
+    if pattern == "*" {
+        return true;
+    }
+    match pattern.split_once("$*") {
+        Some((head, tail)) => {
+            chunk.starts_with(head)
+                && (head.len()..=chunk.len())
+                    .any(|i| chunk.is_char_boundary(i) && chunk_matches(tail, &chunk[i..]))
+        }
+        None => pattern == chunk,
+    }
+}
+
+// Whether a key belongs to the set denoted by a key expression, canon or not, by brute force
+fn brute_matches(expr: &[&str], key: &[&str]) -> bool {
+    match expr.split_first() {
+        None => key.is_empty(),
+        Some((&"", expr)) => brute_matches(expr, key),
+        Some((&"**", expr)) => (0..=key.len()).any(|i| brute_matches(expr, &key[i..])),
+        Some((chunk, expr)) => match key.split_first() {
+            Some((first, key)) => chunk_matches(chunk, first) && brute_matches(expr, key),
+            None => false,
+        },
+    }
+}
+
+// All the keys of up to 4 chunks
+fn all_keys() -> Vec<String> {
+    const CHUNKS: [&str; 4] = ["a", "b", "ab", "ba"];
+    let mut keys: Vec<String> = CHUNKS.iter().map(|c| c.to_string()).collect();
+    let mut last = keys.clone();
+    for _ in 1..4 {
+        last = last
+            .iter()
+            .flat_map(|k| CHUNKS.iter().map(move |c| format!("{k}/{c}")))
+            .collect();
+        keys.extend(last.iter().cloned());
+    }
+    keys
+}
+
+#[test]
+fn canonize_idempotent() {
+    use crate::key_expr::canon::{canonize, Canonizable};
+    const ROUNDS: usize = 10_000;
+    let mut rng = rand::thread_rng();
+    for _ in 0..ROUNDS {
+        let raw = random_raw_expr(&mut rng);
+        let mut once = raw.clone();
+        canonize(&mut once).unwrap_or_else(|e| panic!("`{raw}` not canonized: {e}"));
+        let mut twice = once.clone();
+        twice.canonize();
+        assert_eq!(once, twice, "canonizing `{raw}` isn't idempotent");
+    }
+}
+
+#[test]
+fn canonize_preserves_matches() {
+    use crate::key_expr::canon::canonize;
+    const ROUNDS: usize = 500;
+    let keys = all_keys();
+    let mut rng = rand::thread_rng();
+    for _ in 0..ROUNDS {
+        let raw = random_raw_expr(&mut rng);
+        let mut canon = raw.clone();
+        canonize(&mut canon).unwrap();
+        let ke = keyexpr::new(canon.as_str()).unwrap();
+        let raw_chunks: Vec<&str> = raw.split('/').collect();
+        let canon_chunks: Vec<&str> = canon.split('/').collect();
+        for key in &keys {
+            let key_chunks: Vec<&str> = key.split('/').collect();
+            let expected = brute_matches(&raw_chunks, &key_chunks);
+            assert_eq!(
+                brute_matches(&canon_chunks, &key_chunks),
+                expected,
+                "`{raw}` canonized into `{canon}` changed the match of `{key}`"
+            );
+            let key = keyexpr::new(key.as_str()).unwrap();
+            assert_eq!(ke.intersects(key), expected, "`{canon}` and `{key}`");
+            assert_eq!(ke.includes(key), expected, "`{canon}` includes `{key}`");
+        }
+    }
+}
+
+// The recursive implementations of the intersection and inclusion replaced by the iterative
+// ones, as references of their semantics
+mod recursive {
+    fn next(s: &[u8]) -> (&[u8], &[u8]) {
+        match s.iter().position(|c| *c == b'/') {
+            Some(i) => (&s[..i], &s[(i + 1)..]),
+            None => (s, b""),
+        }
+    }
+
+    fn star_dsl_intersect(mut it1: &[u8], mut it2: &[u8]) -> bool {
+        while !it1.is_empty() && !it2.is_empty() {
+            let (current1, advanced1) = (it1[0], &it1[1..]);
+            let (current2, advanced2) = (it2[0], &it2[1..]);
+            match (current1, current2) {
+                (b'$', b'$') => {
+                    if advanced1.len() == 1 || advanced2.len() == 1 {
+                        return true;
+                    }
+                    return star_dsl_intersect(&advanced1[1..], it2)
+                        || star_dsl_intersect(it1, &advanced2[1..]);
+                }
+                (b'$', _) => {
+                    if advanced1.len() == 1 || star_dsl_intersect(&advanced1[1..], it2) {
+                        return true;
+                    }
+                    it2 = advanced2;
+                }
+                (_, b'$') => {
+                    if advanced2.len() == 1 || star_dsl_intersect(it1, &advanced2[1..]) {
+                        return true;
+                    }
+                    it1 = advanced1;
+                }
+                (sub1, sub2) if sub1 == sub2 => {
+                    it1 = advanced1;
+                    it2 = advanced2;
+                }
+                (_, _) => return false,
+            }
+        }
+        it1.is_empty() && it2.is_empty() || it1 == b"$*" || it2 == b"$*"
+    }
+
+    fn chunk_intersect(c1: &[u8], c2: &[u8]) -> bool {
+        c1 == c2 || c1 == b"*" || c2 == b"*" || star_dsl_intersect(c1, c2)
+    }
+
+    pub(super) fn intersect(mut it1: &[u8], mut it2: &[u8]) -> bool {
+        while !it1.is_empty() && !it2.is_empty() {
+            let (current1, advanced1) = next(it1);
+            let (current2, advanced2) = next(it2);
+            match (current1, current2) {
+                (b"**", _) => {
+                    return advanced1.is_empty()
+                        || intersect(advanced1, it2)
+                        || intersect(it1, advanced2);
+                }
+                (_, b"**") => {
+                    return advanced2.is_empty()
+                        || intersect(it1, advanced2)
+                        || intersect(advanced1, it2);
+                }
+                (sub1, sub2) if chunk_intersect(sub1, sub2) => {
+                    it1 = advanced1;
+                    it2 = advanced2;
+                }
+                (_, _) => return false,
+            }
+        }
+        (it1.is_empty() || it1 == b"**") && (it2.is_empty() || it2 == b"**")
+    }
+
+    fn chunk_includes(lchunk: &[u8], rchunk: &[u8]) -> bool {
+        use crate::key_expr::include::{Includer, DEFAULT_INCLUDER};
+        // Single chunks, with no `**`
+        DEFAULT_INCLUDER.includes(lchunk, rchunk)
+    }
+
+    pub(super) fn includes(mut left: &[u8], mut right: &[u8]) -> bool {
+        if left == right || left == b"**" {
+            return true;
+        }
+        loop {
+            let (lchunk, lrest) = next(left);
+            let lempty = lrest.is_empty();
+            if lchunk == b"**" {
+                if lempty || includes(lrest, right) {
+                    return true;
+                }
+                right = next(right).1;
+                if right.is_empty() {
+                    return false;
+                }
+            } else {
+                let (rchunk, rrest) = next(right);
+                if rchunk.is_empty() || !chunk_includes(lchunk, rchunk) {
+                    return false;
+                }
+                let rempty = rrest.is_empty();
+                if lempty {
+                    return rempty;
+                }
+                left = lrest;
+                right = rrest;
+            }
+        }
+    }
+}
+
+#[test]
+fn iterative_matches_recursive() {
+    const FUZZ_ROUNDS: usize = 20_000;
+    let mut fuzzer = fuzzer::KeyExprFuzzer(rand::thread_rng());
+    let mut ke1 = fuzzer.next().unwrap();
+    for ke2 in fuzzer.take(FUZZ_ROUNDS) {
+        let (l, r) = (ke1.as_bytes(), ke2.as_bytes());
+        assert_eq!(
+            ke1.intersects(&ke2),
+            recursive::intersect(l, r),
+            "{ke1} intersects {ke2}"
+        );
+        assert_eq!(
+            ke1.includes(&ke2),
+            recursive::includes(l, r),
+            "{ke1} includes {ke2}"
+        );
+        assert_eq!(
+            ke2.includes(&ke1),
+            recursive::includes(r, l),
+            "{ke2} includes {ke1}"
+        );
+        ke1 = ke2;
+    }
+}
+
+#[test]
+fn pathological_stack_safe() {
+    // Some 10k characters of wildcards, arriving from the network
+    let double_wilds = format!("{}b", "**/a/".repeat(2000));
+    let star_dsls = format!("a{}", "$*a".repeat(3333));
+    let cases = [
+        (
+            double_wilds.clone(),
+            format!("{}c", "a/".repeat(2000)),
+            false,
+        ),
+        (double_wilds, format!("{}b", "a/".repeat(2000)), true),
+        (star_dsls.clone(), format!("{}b", "a".repeat(1000)), false),
+        (star_dsls, "a".repeat(4000), true),
+    ];
+    // Well below the stack of the threads of a router
+    let tester = std::thread::Builder::new()
+        .stack_size(128 * 1024)
+        .spawn(move || {
+            for (left, right, expected) in cases {
+                let left = keyexpr::new(left.as_str()).unwrap();
+                let right = keyexpr::new(right.as_str()).unwrap();
+                assert_eq!(
+                    left.intersects(right),
+                    expected,
+                    "{left} intersects {right}"
+                );
+                assert_eq!(
+                    right.intersects(left),
+                    expected,
+                    "{right} intersects {left}"
+                );
+                assert_eq!(left.includes(right), expected, "{left} includes {right}");
+            }
+        })
+        .unwrap();
+    tester.join().unwrap();
+}
+
+#[test]
+fn single_double_wild() {
+    // A single `**` matches the last chunks of the other key expression
+    let cases = [
+        ("a/**/b/c", "a/x/y/b/c", true, true),
+        ("a/**/b/c", "a/b/c", true, true),
+        ("a/**/b/c", "a/b", false, false),
+        ("**/b", "b", true, true),
+        ("*/**/c", "a/b/c", true, true),
+        ("**/b", "a/*", false, true),
+        ("a/**/b$*", "a/x/bc", true, true),
+        ("a/**/b$*", "a/x/c", false, false),
+    ];
+    for (left, right, includes, intersects) in cases {
+        let left = keyexpr::new(left).unwrap();
+        let right = keyexpr::new(right).unwrap();
+        assert_eq!(left.includes(right), includes, "{left} includes {right}");
+        assert_eq!(
+            left.intersects(right),
+            intersects,
+            "{left} intersects {right}"
+        );
+        assert_eq!(
+            right.intersects(left),
+            intersects,
+            "{right} intersects {left}"
+        );
+    }
+}
@@ -13,7 +13,13 @@
 //
 use super::locator::*;
 use alloc::{borrow::ToOwned, format, string::String, vec::Vec};
-use core::{convert::TryFrom, fmt, str::FromStr};
+use core::{
+    convert::TryFrom,
+    fmt,
+    hash::{Hash, Hasher},
+    str::FromStr,
+    time::Duration,
+};
 use zenoh_result::{bail, zerror, Error as ZError, ZResult};
 
 // Parsing chars
@@ -24,6 +30,12 @@ pub const FIELD_SEPARATOR: char = '=';
 pub const CONFIG_SEPARATOR: char = '#';
 pub const VALUE_SEPARATOR: char = '|';
 
+// Metadata of the connect endpoints
+/// The metadata key of the priority of a connect endpoint, see [`EndPoint::connect_priority`].
+pub const PRIORITY_METADATA: &str = "priority";
+/// The metadata key of the exclusivity of a connect endpoint, see [`EndPoint::is_connect_exclusive`].
+pub const EXCLUSIVE_METADATA: &str = "exclusive";
+
 fn split_once(s: &str, c: char) -> (&str, &str) {
     match s.find(c) {
         Some(index) => {
@@ -381,13 +393,17 @@ impl fmt::Debug for MetadataMut<'_> {
 }
 
 // Config
-#[repr(transparent)]
-#[derive(Copy, Clone, PartialEq, Eq, Hash)]
-pub struct Config<'a>(pub(super) &'a str);
+/// The configuration of an [`EndPoint`], with typed accessors failing on the values that don't
+/// parse with an error naming the key, the endpoint and the value.
+#[derive(Copy, Clone)]
+pub struct Config<'a> {
+    inner: &'a str,
+    endpoint: &'a str,
+}
 
 impl<'a> Config<'a> {
     pub fn as_str(&'a self) -> &'a str {
-        self.0
+        self.inner
     }
 
     pub fn is_empty(&'a self) -> bool {
@@ -395,15 +411,74 @@ impl<'a> Config<'a> {
     }
 
     pub fn iter(&'a self) -> impl Iterator<Item = (&'a str, &'a str)> + DoubleEndedIterator {
-        Parameters::iter(self.0)
+        Parameters::iter(self.inner)
     }
 
     pub fn get(&'a self, k: &str) -> Option<&'a str> {
-        Parameters::get(self.0, k)
+        Parameters::get(self.inner, k)
     }
 
     pub fn values(&'a self, k: &str) -> impl Iterator<Item = &'a str> + DoubleEndedIterator {
-        Parameters::values(self.0, k)
+        Parameters::values(self.inner, k)
+    }
+
+    fn parse<T>(
+        &self,
+        k: &str,
+        expected: &str,
+        parse: impl FnOnce(&str) -> Option<T>,
+    ) -> ZResult<Option<T>> {
+        match Parameters::get(self.inner, k) {
+            None => Ok(None),
+            Some(v) => match parse(v) {
+                Some(t) => Ok(Some(t)),
+                None => bail!(
+                    "Invalid {} config of endpoint {}: expected {}, got {}",
+                    k,
+                    self.endpoint,
+                    expected,
+                    v
+                ),
+            },
+        }
+    }
+
+    /// The boolean value of `k`, spelled `true` or `false`.
+    pub fn get_bool(&self, k: &str) -> ZResult<Option<bool>> {
+        self.parse(k, "true or false", |v| v.parse().ok())
+    }
+
+    pub fn get_u64(&self, k: &str) -> ZResult<Option<u64>> {
+        self.parse(k, "an unsigned integer", |v| v.parse().ok())
+    }
+
+    /// The duration value of `k`, spelled as an unsigned integer of milliseconds.
+    pub fn get_duration_ms(&self, k: &str) -> ZResult<Option<Duration>> {
+        self.parse(k, "an unsigned integer of milliseconds", |v| {
+            v.parse().ok().map(Duration::from_millis)
+        })
+    }
+
+    /// The values of `k`, separated by `|`, each parsed as a `T`.
+    pub fn get_list<T: FromStr>(&self, k: &str) -> ZResult<Vec<T>> {
+        self.parse(k, "a list of values separated by |", |v| {
+            v.split(VALUE_SEPARATOR).map(|v| v.parse().ok()).collect()
+        })
+        .map(Option::unwrap_or_default)
+    }
+}
+
+impl PartialEq for Config<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.inner == other.inner
+    }
+}
+
+impl Eq for Config<'_> {}
+
+impl Hash for Config<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.inner.hash(state)
     }
 }
 
@@ -573,7 +648,10 @@ impl EndPoint {
     }
 
     pub fn config(&self) -> Config {
-        Config(config(self.inner.as_str()))
+        Config {
+            inner: config(self.inner.as_str()),
+            endpoint: self.inner.as_str(),
+        }
     }
 
     pub fn config_mut(&mut self) -> ConfigMut {
@@ -583,6 +661,45 @@ impl EndPoint {
     pub fn to_locator(&self) -> Locator {
         self.clone().into()
     }
+
+    /// The priority of this endpoint when connecting to it, from 1 (the most preferred) to 255,
+    /// given by its `priority` metadata (default: 1).
+    ///
+    /// The endpoints of a priority are connected to only if none of the endpoints of the
+    /// preferred priorities could be connected to.
+    pub fn connect_priority(&self) -> ZResult<u8> {
+        match self.metadata().get(PRIORITY_METADATA) {
+            None => Ok(1),
+            Some(priority) => match priority.parse::<u8>() {
+                Ok(priority) if priority > 0 => Ok(priority),
+                _ => bail!(
+                    "Invalid {} metadata of endpoint {}: expected an integer from 1 to 255, got {}",
+                    PRIORITY_METADATA,
+                    self,
+                    priority
+                ),
+            },
+        }
+    }
+
+    /// Whether this endpoint is exclusive when connecting to it, given by its `exclusive`
+    /// metadata (default: false).
+    ///
+    /// At most one transport is kept on the exclusive endpoints, the one on the most preferred one.
+    pub fn is_connect_exclusive(&self) -> ZResult<bool> {
+        match self.metadata().get(EXCLUSIVE_METADATA) {
+            None => Ok(false),
+            Some(exclusive) => exclusive.parse::<bool>().map_err(|_| {
+                zerror!(
+                    "Invalid {} metadata of endpoint {}: expected true or false, got {}",
+                    EXCLUSIVE_METADATA,
+                    self,
+                    exclusive
+                )
+                .into()
+            }),
+        }
+    }
 }
 
 impl From<Locator> for EndPoint {
@@ -937,3 +1054,69 @@ fn endpoints() {
     assert_eq!(i.next(), Some("224.0.0.3"));
     assert_eq!(i.next(), None);
 }
+
+#[test]
+fn endpoints_connect_preferences() {
+    let endpoint = EndPoint::from_str("tcp/127.0.0.1:7447").unwrap();
+    assert_eq!(endpoint.connect_priority().unwrap(), 1);
+    assert!(!endpoint.is_connect_exclusive().unwrap());
+
+    let endpoint = EndPoint::from_str("tcp/127.0.0.1:7447?priority=2;exclusive=true").unwrap();
+    assert_eq!(endpoint.connect_priority().unwrap(), 2);
+    assert!(endpoint.is_connect_exclusive().unwrap());
+
+    for invalid in [
+        "priority=0",
+        "priority=256",
+        "priority=high",
+        "exclusive=yes",
+    ] {
+        let endpoint = EndPoint::from_str(&format!("tcp/127.0.0.1:7447?{invalid}")).unwrap();
+        assert!(endpoint.connect_priority().is_err() || endpoint.is_connect_exclusive().is_err());
+    }
+}
+
+#[test]
+fn endpoints_config_typed() {
+    let endpoint = EndPoint::from_str(
+        "tcp/127.0.0.1:7447#auth=true;verify=false;count=42;lease=1500;ports=7447|7448",
+    )
+    .unwrap();
+    let c = endpoint.config();
+    assert_eq!(c.get_bool("auth").unwrap(), Some(true));
+    assert_eq!(c.get_bool("verify").unwrap(), Some(false));
+    assert_eq!(c.get_bool("missing").unwrap(), None);
+    assert_eq!(c.get_u64("count").unwrap(), Some(42));
+    assert_eq!(
+        c.get_duration_ms("lease").unwrap(),
+        Some(Duration::from_millis(1500))
+    );
+    assert_eq!(c.get_list::<u16>("ports").unwrap(), [7447, 7448]);
+    assert!(c.get_list::<u64>("missing").unwrap().is_empty());
+
+    // The booleans are spelled true or false only
+    for invalid in ["1", "0", "yes", "no", "True", "on", ""] {
+        let endpoint = EndPoint::from_str(&format!("tcp/127.0.0.1:7447#auth={invalid}")).unwrap();
+        let e = endpoint.config().get_bool("auth").unwrap_err().to_string();
+        assert!(
+            e.contains("auth") && e.contains("tcp/127.0.0.1:7447"),
+            "{e}"
+        );
+    }
+    // The durations are spelled as unsigned integers of milliseconds
+    for valid in ["0", "1500", "18446744073709551615"] {
+        let endpoint = EndPoint::from_str(&format!("tcp/127.0.0.1:7447#lease={valid}")).unwrap();
+        assert!(endpoint.config().get_duration_ms("lease").is_ok());
+    }
+    for invalid in ["-1", "1.5", "1s", "1500ms", " 1500", ""] {
+        let endpoint = EndPoint::from_str(&format!("tcp/127.0.0.1:7447#lease={invalid}")).unwrap();
+        let e = endpoint
+            .config()
+            .get_duration_ms("lease")
+            .unwrap_err()
+            .to_string();
+        assert!(e.contains(&format!("got {invalid}")), "{e}");
+    }
+    let endpoint = EndPoint::from_str("tcp/127.0.0.1:7447#ports=7447|nowhere").unwrap();
+    assert!(endpoint.config().get_list::<u16>("ports").is_err());
+}
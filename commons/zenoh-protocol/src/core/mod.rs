@@ -299,6 +299,23 @@ impl<'de> serde::Deserialize<'de> for ZenohId {
     }
 }
 
+/// The id of an entity (subscriber, queryable, publisher, liveliness token) declared by a zenoh instance.
+/// It is unique among the entities declared by the zenoh instance.
+pub type EntityId = u32;
+
+/// The global unique id of an entity: the [`ZenohId`] of the zenoh instance that declared it and its [`EntityId`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Serialize)]
+pub struct EntityGlobalId {
+    pub zid: ZenohId,
+    pub eid: EntityId,
+}
+
+impl fmt::Display for EntityGlobalId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.zid, self.eid)
+    }
+}
+
 #[repr(u8)]
 #[derive(Debug, Default, Copy, Clone, Eq, Hash, PartialEq)]
 pub enum Priority {
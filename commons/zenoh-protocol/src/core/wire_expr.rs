@@ -17,7 +17,11 @@ use alloc::{
     borrow::Cow,
     string::{String, ToString},
 };
-use core::{convert::TryInto, fmt, sync::atomic::AtomicU16};
+use core::{
+    convert::{TryFrom, TryInto},
+    fmt,
+    sync::atomic::AtomicU16,
+};
 use zenoh_keyexpr::{keyexpr, OwnedKeyExpr};
 use zenoh_result::{bail, ZResult};
 
@@ -173,6 +177,35 @@ impl<'a> From<&'a keyexpr> for WireExpr<'a> {
     }
 }
 
+impl From<OwnedKeyExpr> for WireExpr<'_> {
+    fn from(val: OwnedKeyExpr) -> Self {
+        WireExpr {
+            scope: 0,
+            suffix: Cow::Owned(val.into()),
+            mapping: Mapping::Sender,
+        }
+    }
+}
+
+/// Fails on the scoped wire expressions, their key expression being known only to the face
+/// that declared their scope, and on the invalid key expressions.
+impl<'a> TryFrom<&'a WireExpr<'a>> for &'a keyexpr {
+    type Error = zenoh_result::Error;
+    fn try_from(val: &'a WireExpr<'a>) -> Result<Self, Self::Error> {
+        <&keyexpr>::try_from(val.try_as_str()?)
+    }
+}
+
+/// Fails on the scoped wire expressions, their key expression being known only to the face
+/// that declared their scope, and on the invalid key expressions.
+impl TryFrom<WireExpr<'_>> for OwnedKeyExpr {
+    type Error = zenoh_result::Error;
+    fn try_from(val: WireExpr<'_>) -> Result<Self, Self::Error> {
+        let suffix: String = val.try_into()?;
+        OwnedKeyExpr::try_from(suffix)
+    }
+}
+
 impl fmt::Debug for WireExpr<'_> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         if self.scope == 0 {
@@ -261,3 +294,19 @@ impl WireExpr<'_> {
         }
     }
 }
+
+#[test]
+fn wire_expr_key_expr() {
+    let ke = OwnedKeyExpr::new("a/*/c").unwrap();
+    let wire = WireExpr::from(ke.clone());
+    assert_eq!(<&keyexpr>::try_from(&wire).unwrap(), &*ke);
+    assert_eq!(OwnedKeyExpr::try_from(wire).unwrap(), ke);
+
+    // Neither the scoped nor the invalid wire expressions are key expressions
+    let scoped = WireExpr::from(1 as ExprId).with_suffix("/c");
+    assert!(<&keyexpr>::try_from(&scoped).is_err());
+    assert!(OwnedKeyExpr::try_from(scoped).is_err());
+    let invalid = WireExpr::from("a/***");
+    assert!(<&keyexpr>::try_from(&invalid).is_err());
+    assert!(OwnedKeyExpr::try_from(invalid).is_err());
+}
@@ -28,7 +28,8 @@ pub mod transport;
 pub mod zenoh;
 
 // Zenoh version
-pub const VERSION: u8 = 0x08;
+// 0x09: the declarations are framed with their length, so that the unknown ones can be skipped
+pub const VERSION: u8 = 0x09;
 
 // Zenoh protocol uses the following conventions for message definition and representation.
 //
@@ -17,7 +17,7 @@ use crate::{
     network::Mapping,
     zextz64, zextzbuf,
 };
-use alloc::borrow::Cow;
+use alloc::{borrow::Cow, string::String};
 use core::ops::BitOr;
 pub use interest::*;
 pub use keyexpr::*;
@@ -42,9 +42,15 @@ pub mod flag {
 /// +-+-+-+---------+
 /// ~  [decl_exts]  ~  if Z==1
 /// +---------------+
+/// |  decl header  |
+/// +---------------+
+/// %    length     %  -- length of the rest of the declaration
+/// +---------------+
 /// ~  declaration  ~
 /// +---------------+
 ///
+/// The length prefix lets the nodes skip the declarations of the kinds they don't know.
+///
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Declare {
     pub ext_qos: ext::QoSType,
@@ -85,6 +91,9 @@ pub mod id {
     pub const D_INTEREST: u8 = 0x08;
     pub const F_INTEREST: u8 = 0x09;
     pub const U_INTEREST: u8 = 0x0A;
+
+    pub const D_KEYEXPRS: u8 = 0x0B;
+    pub const R_KEYEXPRS: u8 = 0x0C;
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -100,6 +109,8 @@ pub enum DeclareBody {
     DeclareInterest(DeclareInterest),
     FinalInterest(FinalInterest),
     UndeclareInterest(UndeclareInterest),
+    DeclareKeyExprs(DeclareKeyExprs),
+    RejectKeyExprs(RejectKeyExprs),
 }
 
 impl DeclareBody {
@@ -109,7 +120,7 @@ impl DeclareBody {
 
         let mut rng = rand::thread_rng();
 
-        match rng.gen_range(0..11) {
+        match rng.gen_range(0..13) {
             0 => DeclareBody::DeclareKeyExpr(DeclareKeyExpr::rand()),
             1 => DeclareBody::UndeclareKeyExpr(UndeclareKeyExpr::rand()),
             2 => DeclareBody::DeclareSubscriber(DeclareSubscriber::rand()),
@@ -121,12 +132,35 @@ impl DeclareBody {
             8 => DeclareBody::DeclareInterest(DeclareInterest::rand()),
             9 => DeclareBody::FinalInterest(FinalInterest::rand()),
             10 => DeclareBody::UndeclareInterest(UndeclareInterest::rand()),
+            11 => DeclareBody::DeclareKeyExprs(DeclareKeyExprs::rand()),
+            12 => DeclareBody::RejectKeyExprs(RejectKeyExprs::rand()),
             _ => unreachable!(),
         }
     }
 }
 
 impl Declare {
+    /// The key expression declared by a D_KEYEXPR, D_SUBSCRIBER or D_QUERYABLE declaration, which
+    /// is prefix-delta encoded in the frames with the C flag.
+    pub fn declared_wire_expr(&self) -> Option<&WireExpr<'static>> {
+        match &self.body {
+            DeclareBody::DeclareKeyExpr(m) => Some(&m.wire_expr),
+            DeclareBody::DeclareSubscriber(m) => Some(&m.wire_expr),
+            DeclareBody::DeclareQueryable(m) => Some(&m.wire_expr),
+            _ => None,
+        }
+    }
+
+    /// See [`Declare::declared_wire_expr`].
+    pub fn declared_wire_expr_mut(&mut self) -> Option<&mut WireExpr<'static>> {
+        match &mut self.body {
+            DeclareBody::DeclareKeyExpr(m) => Some(&mut m.wire_expr),
+            DeclareBody::DeclareSubscriber(m) => Some(&mut m.wire_expr),
+            DeclareBody::DeclareQueryable(m) => Some(&mut m.wire_expr),
+            _ => None,
+        }
+    }
+
     #[cfg(feature = "test")]
     pub fn rand() -> Self {
         use rand::Rng;
@@ -204,12 +238,84 @@ pub mod common {
     }
 }
 
+/// The prefix-delta encoding of the key expression suffixes, used by [`DeclareKeyExprs`] and by
+/// the frames with the C flag.
+pub mod prefix {
+    use crate::core::WireExpr;
+    use alloc::string::String;
+
+    /// The maximum number of chunks a suffix declared in a frame with the C flag shares with the
+    /// suffix previously declared in the frame.
+    pub const MAX_SHARED: u8 = 0x7f;
+
+    /// The number of leading chunks, at most `max`, `suffix` shares with `previous`, and their
+    /// length in `suffix`.
+    pub fn shared_chunks(previous: &str, suffix: &str, max: usize) -> (usize, usize) {
+        let mut chunks = 0;
+        let mut len = 0;
+        for (p, s) in previous.split('/').zip(suffix.split('/')).take(max) {
+            if p != s {
+                break;
+            }
+            // The separator before the chunk, but the first one
+            len += s.len() + usize::from(chunks != 0);
+            chunks += 1;
+        }
+        (chunks, len)
+    }
+
+    /// The length of the leading `chunks` chunks of `previous`, if it has that many.
+    pub fn chunks_len(previous: &str, chunks: usize) -> Option<usize> {
+        let mut split = previous.split('/');
+        let mut len = 0;
+        for i in 0..chunks {
+            len += split.next()?.len() + usize::from(i != 0);
+        }
+        Some(len)
+    }
+
+    /// Encodes the suffix of `wire_expr` declared in a frame with the C flag as the number of
+    /// chunks it shares with `previous`, the suffix previously declared in the frame, followed by
+    /// the rest of the suffix. The empty suffixes are left as is.
+    pub fn compress(previous: &mut String, wire_expr: &mut WireExpr<'_>) {
+        if wire_expr.suffix.is_empty() {
+            return;
+        }
+        let (chunks, len) = shared_chunks(previous, &wire_expr.suffix, MAX_SHARED as usize);
+        let mut suffix = String::with_capacity(1 + wire_expr.suffix.len() - len);
+        // A single byte, the number of chunks being at most 127
+        suffix.push(char::from(chunks as u8));
+        suffix.push_str(&wire_expr.suffix[len..]);
+        *previous = core::mem::replace(&mut wire_expr.suffix, suffix.into()).into_owned();
+    }
+
+    /// Reverts [`compress`], or returns `None` if the suffix of `wire_expr` is not a valid
+    /// encoding against `previous`.
+    pub fn decompress(previous: &mut String, wire_expr: &mut WireExpr<'_>) -> Option<()> {
+        let Some(&chunks) = wire_expr.suffix.as_bytes().first() else {
+            return Some(());
+        };
+        if chunks > MAX_SHARED {
+            return None;
+        }
+        let len = chunks_len(previous, chunks as usize)?;
+        let rest = &wire_expr.suffix[1..];
+        let mut suffix = String::with_capacity(len + rest.len());
+        suffix.push_str(&previous[..len]);
+        suffix.push_str(rest);
+        previous.clone_from(&suffix);
+        wire_expr.suffix = suffix.into();
+        Some(())
+    }
+}
+
 pub mod keyexpr {
     use super::*;
+    use alloc::vec::Vec;
 
     pub mod flag {
         pub const N: u8 = 1 << 5; // 0x20 Named         if N==1 then the key expr has name/suffix
-                                  // pub const X: u8 = 1 << 6; // 0x40 Reserved
+        pub const C: u8 = 1 << 6; // 0x40 Compressed    if C==1 then the suffixes of a D_KEXPRS are prefix-delta encoded
         pub const Z: u8 = 1 << 7; // 0x80 Extensions    if Z==1 then an extension will follow
     }
 
@@ -282,6 +388,114 @@ pub mod keyexpr {
             Self { id }
         }
     }
+
+    /// The key expression mappings declared in bulk, e.g. to resynchronize them at once after
+    /// a reconnection. Only sent to the peers which negotiated it at the transport establishment.
+    ///
+    /// With the C flag, each suffix is encoded as the number of chunks it shares with the suffix
+    /// of the previous mapping, followed by the rest of the suffix, e.g. `a/b/d` following
+    /// `a/b/c` is encoded as 2 and `/d`. Only sent to the peers which negotiated the prefix
+    /// compression at the transport establishment.
+    ///
+    /// ```text
+    /// Flags:
+    /// - X: Reserved
+    /// - C: Compressed     If C==1 then the suffixes are prefix-delta encoded
+    /// - Z: Extension      If Z==1 then at least one extension is present
+    ///
+    ///  7 6 5 4 3 2 1 0
+    /// +-+-+-+-+-+-+-+-+
+    /// |Z|C|X|D_KEXPRS |
+    /// +---------------+
+    /// %   num:z32     %
+    /// +---------------+
+    /// ~  expr_id:z16  ~  \
+    /// +---------------+   |
+    /// ~ key_scope:z16 ~   |
+    /// +---------------+   | num times
+    /// %  shared:z16   %   |  if C==1
+    /// +---------------+   |
+    /// ~  key_suffix   ~  /   <u8;z16>
+    /// +---------------+
+    /// ~  [decl_exts]  ~  if Z==1
+    /// +---------------+
+    /// ```
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct DeclareKeyExprs {
+        pub mappings: Vec<DeclareKeyExpr>,
+        /// Whether the suffixes are prefix-delta encoded, see [`DeclareKeyExprs::compress`].
+        pub compressed: bool,
+    }
+
+    impl DeclareKeyExprs {
+        /// Sorts the mappings by suffix, chunk by chunk, for the consecutive suffixes to share
+        /// the longest prefixes, and prefix-delta encodes them.
+        pub fn compress(&mut self) {
+            self.mappings.sort_by(|a, b| {
+                a.wire_expr
+                    .suffix
+                    .split('/')
+                    .cmp(b.wire_expr.suffix.split('/'))
+            });
+            self.compressed = true;
+        }
+
+        #[cfg(feature = "test")]
+        pub fn rand() -> Self {
+            use rand::Rng;
+            let mut rng = rand::thread_rng();
+
+            let num = rng.gen_range(0..16);
+            let mappings = (0..num).map(|_| DeclareKeyExpr::rand()).collect();
+            let mut declare = Self {
+                mappings,
+                compressed: false,
+            };
+            if rng.gen_bool(0.5) {
+                declare.compress();
+            }
+
+            declare
+        }
+    }
+
+    /// The ids of the key expression mappings of a [`DeclareKeyExprs`] the receiver rejected,
+    /// e.g. because they were already mapped to another key expression.
+    ///
+    /// ```text
+    /// Flags:
+    /// - X: Reserved
+    /// - X: Reserved
+    /// - Z: Extension      If Z==1 then at least one extension is present
+    ///
+    ///  7 6 5 4 3 2 1 0
+    /// +-+-+-+-+-+-+-+-+
+    /// |Z|X|X|R_KEXPRS |
+    /// +---------------+
+    /// %   num:z32     %
+    /// +---------------+
+    /// ~  expr_id:z16  ~  num times
+    /// +---------------+
+    /// ~  [decl_exts]  ~  if Z==1
+    /// +---------------+
+    /// ```
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct RejectKeyExprs {
+        pub ids: Vec<ExprId>,
+    }
+
+    impl RejectKeyExprs {
+        #[cfg(feature = "test")]
+        pub fn rand() -> Self {
+            use rand::Rng;
+            let mut rng = rand::thread_rng();
+
+            let num = rng.gen_range(0..16);
+            let ids = (0..num).map(|_| rng.gen()).collect();
+
+            Self { ids }
+        }
+    }
 }
 
 pub mod subscriber {
@@ -323,12 +537,14 @@ pub mod subscriber {
         pub id: SubscriberId,
         pub wire_expr: WireExpr<'static>,
         pub ext_info: ext::SubscriberInfo,
+        pub ext_filter: Option<ext::FilterType>,
     }
 
     pub mod ext {
         use super::*;
 
         pub type Info = zextz64!(0x01, false);
+        pub type Filter = zextzbuf!(0x02, false);
 
         /// # The subscription mode.
         ///
@@ -391,6 +607,44 @@ pub mod subscriber {
                 Info::new(v)
             }
         }
+
+        /// # The subscription filter.
+        ///
+        /// A predicate over the values of the samples the subscriber is interested in, the
+        /// samples it doesn't match being dropped before being forwarded to the subscriber.
+        ///
+        /// ```text
+        ///  7 6 5 4 3 2 1 0
+        /// +-+-+-+-+-+-+-+-+
+        /// |Z|1_0|    ID   |
+        /// +-+-+-+---------+
+        /// %   length:z32  %
+        /// +---------------+
+        /// ~ expr:<utf8;z32> ~
+        /// +---------------+
+        /// ```
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        pub struct FilterType {
+            pub expr: String,
+        }
+
+        impl FilterType {
+            #[cfg(feature = "test")]
+            pub fn rand() -> Self {
+                use rand::{
+                    distributions::{Alphanumeric, DistString},
+                    Rng,
+                };
+                let mut rng = rand::thread_rng();
+
+                const MIN: usize = 1;
+                const MAX: usize = 16;
+
+                let len = rng.gen_range(MIN..MAX);
+                let expr = Alphanumeric.sample_string(&mut rng, len);
+                Self { expr }
+            }
+        }
     }
 
     impl DeclareSubscriber {
@@ -402,11 +656,13 @@ pub mod subscriber {
             let id: SubscriberId = rng.gen();
             let wire_expr = WireExpr::rand();
             let ext_info = ext::SubscriberInfo::rand();
+            let ext_filter = rng.gen_bool(0.5).then(ext::FilterType::rand);
 
             Self {
                 id,
                 wire_expr,
                 ext_info,
+                ext_filter,
             }
         }
     }
@@ -20,16 +20,16 @@ pub mod response;
 use core::fmt;
 
 pub use declare::{
-    Declare, DeclareBody, DeclareInterest, DeclareKeyExpr, DeclareQueryable, DeclareSubscriber,
-    DeclareToken, UndeclareInterest, UndeclareKeyExpr, UndeclareQueryable, UndeclareSubscriber,
-    UndeclareToken,
+    Declare, DeclareBody, DeclareInterest, DeclareKeyExpr, DeclareKeyExprs, DeclareQueryable,
+    DeclareSubscriber, DeclareToken, RejectKeyExprs, UndeclareInterest, UndeclareKeyExpr,
+    UndeclareQueryable, UndeclareSubscriber, UndeclareToken,
 };
 pub use oam::Oam;
 pub use push::Push;
 pub use request::{AtomicRequestId, Request, RequestId};
 pub use response::{Response, ResponseFinal};
 
-use crate::core::{CongestionControl, Priority};
+use crate::core::{CongestionControl, Priority, Reliability};
 
 pub mod id {
     // WARNING: it's crucial that these IDs do NOT collide with the IDs
@@ -40,6 +40,11 @@ pub mod id {
     pub const REQUEST: u8 = 0x1c;
     pub const RESPONSE: u8 = 0x1b;
     pub const RESPONSE_FINAL: u8 = 0x1a;
+
+    // The network message ids are allocated downwards from 0x1f and the transport message ids
+    // upwards from 0x00: an unknown id from this one is a network message of a newer version,
+    // framed as its header, followed by the length of its body, followed by its body.
+    pub const UNKNOWN_MIN: u8 = 0x10;
 }
 
 #[repr(u8)]
@@ -78,6 +83,9 @@ pub enum NetworkBody {
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct NetworkMessage {
     pub body: NetworkBody,
+    /// The reliability of the channel this message is sent or was received on.
+    /// It is not encoded in the message itself but in the frame carrying it.
+    pub reliability: Reliability,
     #[cfg(feature = "stats")]
     pub size: Option<core::num::NonZeroUsize>,
 }
@@ -104,16 +112,13 @@ impl NetworkMessage {
 
     #[inline]
     pub fn is_reliable(&self) -> bool {
-        // TODO
-        true
+        self.reliability == Reliability::Reliable
     }
 
     #[inline]
     pub fn is_droppable(&self) -> bool {
-        if !self.is_reliable() {
-            return true;
-        }
-
+        // Droppability is only driven by the congestion control: a best-effort message
+        // is not retransmitted but it is not dropped on congestion unless asked for.
         let cc = match &self.body {
             NetworkBody::Declare(msg) => msg.ext_qos.get_congestion_control(),
             NetworkBody::Push(msg) => msg.ext_qos.get_congestion_control(),
@@ -139,6 +144,27 @@ impl NetworkMessage {
     }
 }
 
+/// A network message or a declaration of a kind unknown to this node, sent by a newer node.
+///
+/// Being framed with their length, they are skipped without interrupting the decoding of the
+/// messages following them.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum UnknownKind {
+    /// A network message of an unknown id.
+    Message(u8),
+    /// A declaration of an unknown id, skipped with the Declare message carrying it.
+    Declaration(u8),
+}
+
+impl fmt::Display for UnknownKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            UnknownKind::Message(id) => write!(f, "network message {id:#04x}"),
+            UnknownKind::Declaration(id) => write!(f, "declaration {id:#04x}"),
+        }
+    }
+}
+
 impl fmt::Display for NetworkMessage {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         fmt::Debug::fmt(self, f)
@@ -150,6 +176,7 @@ impl From<NetworkBody> for NetworkMessage {
     fn from(body: NetworkBody) -> Self {
         Self {
             body,
+            reliability: Reliability::Reliable,
             #[cfg(feature = "stats")]
             size: None,
         }
@@ -60,15 +60,18 @@ pub struct Request {
     pub ext_target: ext::TargetType,
     pub ext_budget: Option<ext::BudgetType>,
     pub ext_timeout: Option<ext::TimeoutType>,
+    pub ext_correlation: Option<ext::CorrelationType>,
+    pub ext_repliers: Option<ext::RepliersType>,
     pub payload: RequestBody,
 }
 
 pub mod ext {
     use crate::{
         common::{ZExtZ64, ZExtZBuf},
-        core::QueryTarget,
+        core::{QueryTarget, ZenohId},
         zextz64, zextzbuf,
     };
+    use alloc::vec::Vec;
     use core::{num::NonZeroU32, time::Duration};
 
     pub type QoS = zextz64!(0x1, false);
@@ -115,6 +118,43 @@ pub mod ext {
     // The timeout of the request
     pub type Timeout = zextz64!(0x6, false);
     pub type TimeoutType = Duration;
+
+    // The id correlating the request across the hops: the zid of its origin and a counter of that origin
+    pub type Correlation = zextzbuf!(0x7, false);
+    pub type CorrelationType = crate::network::ext::EntityIdType<{ Correlation::ID }>;
+
+    /// - Repliers (0x08)
+    ///  7 6 5 4 3 2 1 0
+    /// +-+-+-+-+-+-+-+-+
+    /// %     count     %
+    /// +---------------+
+    /// |zid_len|X|X|X|X|  -- repeated count times
+    /// +-------+-+-+---+
+    /// ~      zid      ~
+    /// +---------------+
+    ///
+    /// The zenoh instances whose replies are accepted by the querier: the routers may skip the
+    /// queryables of the other ones.
+    pub type Repliers = zextzbuf!(0x8, false);
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct RepliersType {
+        pub zids: Vec<ZenohId>,
+    }
+
+    impl RepliersType {
+        pub fn contains(&self, zid: &ZenohId) -> bool {
+            self.zids.contains(zid)
+        }
+
+        #[cfg(feature = "test")]
+        pub fn rand() -> Self {
+            use rand::Rng;
+            let mut rng = rand::thread_rng();
+
+            let zids = (0..rng.gen_range(0..4)).map(|_| ZenohId::rand()).collect();
+            Self { zids }
+        }
+    }
 }
 
 impl Request {
@@ -142,6 +182,8 @@ impl Request {
         } else {
             None
         };
+        let ext_correlation = rng.gen_bool(0.5).then(ext::CorrelationType::rand);
+        let ext_repliers = rng.gen_bool(0.5).then(ext::RepliersType::rand);
 
         Self {
             wire_expr,
@@ -153,6 +195,8 @@ impl Request {
             ext_target,
             ext_budget,
             ext_timeout,
+            ext_correlation,
+            ext_repliers,
         }
     }
 }
@@ -53,6 +53,7 @@ pub struct Response {
     pub ext_qos: ext::QoSType,
     pub ext_tstamp: Option<ext::TimestampType>,
     pub ext_respid: Option<ext::ResponderIdType>,
+    pub ext_correlation: Option<ext::CorrelationType>,
 }
 
 pub mod ext {
@@ -68,6 +69,10 @@ pub mod ext {
 
     pub type ResponderId = zextzbuf!(0x3, false);
     pub type ResponderIdType = crate::network::ext::EntityIdType<{ ResponderId::ID }>;
+
+    // The correlation id of the request the response replies to
+    pub type Correlation = zextzbuf!(0x4, false);
+    pub type CorrelationType = crate::network::ext::EntityIdType<{ Correlation::ID }>;
 }
 
 impl Response {
@@ -82,6 +87,7 @@ impl Response {
         let ext_qos = ext::QoSType::rand();
         let ext_tstamp = rng.gen_bool(0.5).then(ext::TimestampType::rand);
         let ext_respid = rng.gen_bool(0.5).then(ext::ResponderIdType::rand);
+        let ext_correlation = rng.gen_bool(0.5).then(ext::CorrelationType::rand);
 
         Self {
             rid,
@@ -90,6 +96,7 @@ impl Response {
             ext_qos,
             ext_tstamp,
             ext_respid,
+            ext_correlation,
         }
     }
 }
@@ -44,6 +44,10 @@
 /// ~  [CloseExts]  ~ if Flag(Z)==1
 /// +---------------+
 /// ```
+///
+/// NOTE: A [`Close`] MAY carry a human-readable detail of the reason, for the remote node to
+///       report it to the application. A node not supporting the extension skips it.
+///
 /// NOTE: 16 bits (2 bytes) may be prepended to the serialized message indicating the total length
 ///       in bytes of the message, resulting in the maximum length of a message being 65535 bytes.
 ///       This is necessary in those stream-oriented transports (e.g., TCP) that do not preserve
@@ -65,6 +69,8 @@ pub mod reason {
     pub const MAX_SESSIONS: u8 = 0x03;
     pub const MAX_LINKS: u8 = 0x04;
     pub const EXPIRED: u8 = 0x05;
+    pub const ABUSE: u8 = 0x06;
+    pub const UNAUTHORIZED: u8 = 0x07;
 }
 
 pub fn reason_to_str(reason: u8) -> &'static str {
@@ -75,14 +81,110 @@ pub fn reason_to_str(reason: u8) -> &'static str {
         reason::MAX_SESSIONS => "MAX_SESSIONS",
         reason::MAX_LINKS => "MAX_LINKS",
         reason::EXPIRED => "EXPIRED",
+        reason::ABUSE => "ABUSE",
+        reason::UNAUTHORIZED => "UNAUTHORIZED",
         _ => "UNKNOWN",
     }
 }
 
+/// The typed [`reason`] of a [`Close`] message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CloseReason {
+    Generic,
+    Unsupported,
+    Invalid,
+    MaxSessions,
+    MaxLinks,
+    Expired,
+    Abuse,
+    Unauthorized,
+    Unknown(u8),
+}
+
+impl CloseReason {
+    /// The numeric code of the reason on the wire.
+    pub const fn code(&self) -> u8 {
+        match self {
+            CloseReason::Generic => reason::GENERIC,
+            CloseReason::Unsupported => reason::UNSUPPORTED,
+            CloseReason::Invalid => reason::INVALID,
+            CloseReason::MaxSessions => reason::MAX_SESSIONS,
+            CloseReason::MaxLinks => reason::MAX_LINKS,
+            CloseReason::Expired => reason::EXPIRED,
+            CloseReason::Abuse => reason::ABUSE,
+            CloseReason::Unauthorized => reason::UNAUTHORIZED,
+            CloseReason::Unknown(code) => *code,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        reason_to_str(self.code())
+    }
+}
+
+impl From<u8> for CloseReason {
+    fn from(code: u8) -> Self {
+        match code {
+            reason::GENERIC => CloseReason::Generic,
+            reason::UNSUPPORTED => CloseReason::Unsupported,
+            reason::INVALID => CloseReason::Invalid,
+            reason::MAX_SESSIONS => CloseReason::MaxSessions,
+            reason::MAX_LINKS => CloseReason::MaxLinks,
+            reason::EXPIRED => CloseReason::Expired,
+            reason::ABUSE => CloseReason::Abuse,
+            reason::UNAUTHORIZED => CloseReason::Unauthorized,
+            code => CloseReason::Unknown(code),
+        }
+    }
+}
+
+impl From<CloseReason> for u8 {
+    fn from(reason: CloseReason) -> Self {
+        reason.code()
+    }
+}
+
+impl core::fmt::Display for CloseReason {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            CloseReason::Unknown(code) => write!(f, "UNKNOWN({code})"),
+            _ => f.write_str(self.as_str()),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Close {
     pub reason: u8,
     pub session: bool,
+    pub ext_detail: Option<ext::Detail>,
+}
+
+// Extensions
+pub mod ext {
+    use crate::{common::ZExtZBuf, zextzbuf};
+
+    /// # Detail extension
+    /// A human-readable UTF-8 detail of the reason of the close.
+    pub type Detail = zextzbuf!(0x1, false);
+}
+
+impl Close {
+    /// Sets the human-readable detail of the reason, carried by the [`ext::Detail`] extension.
+    pub fn with_detail(mut self, detail: alloc::string::String) -> Self {
+        self.ext_detail = Some(ext::Detail::new(detail.into_bytes().into()));
+        self
+    }
+
+    /// Returns the detail of the reason carried by the [`ext::Detail`] extension, if any.
+    pub fn detail(&self) -> Option<alloc::string::String> {
+        use zenoh_buffers::SplitBuffer;
+
+        self.ext_detail.as_ref().map(|d| {
+            let bytes = d.value.contiguous();
+            alloc::string::String::from_utf8_lossy(&bytes).into_owned()
+        })
+    }
 }
 
 impl Close {
@@ -94,7 +196,12 @@ impl Close {
 
         let reason: u8 = rng.gen();
         let session = rng.gen_bool(0.5);
+        let ext_detail = rng.gen_bool(0.5).then(ext::Detail::rand);
 
-        Self { reason, session }
+        Self {
+            reason,
+            session,
+            ext_detail,
+        }
     }
 }
@@ -11,7 +11,11 @@
 // Contributors:
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
-use crate::{core::Reliability, network::NetworkMessage, transport::TransportSn};
+use crate::{
+    core::Reliability,
+    network::{NetworkMessage, UnknownKind},
+    transport::TransportSn,
+};
 use alloc::vec::Vec;
 
 /// # Frame message
@@ -39,12 +43,12 @@ use alloc::vec::Vec;
 /// ```text
 /// Flags:
 /// - R: Reliable       If R==1 it concerns the reliable channel, else the best-effort channel
-/// - X: Reserved
+/// - C: Compressed     If C==1 then the declared key expressions are prefix-delta encoded
 /// - Z: Extensions     If Z==1 then zenoh extensions will follow.
 ///
 ///  7 6 5 4 3 2 1 0
 /// +-+-+-+-+-+-+-+-+
-/// |Z|X|R|  FRAME  |
+/// |Z|C|R|  FRAME  |
 /// +-+-+-+---------+
 /// %    seq num    %
 /// +---------------+
@@ -60,9 +64,15 @@ use alloc::vec::Vec;
 ///       the boundary of the serialized messages. The length is encoded as little-endian.
 ///       In any case, the length of a message must not exceed 65535 bytes.
 ///
+/// With the C flag, the key_suffix of the D_KEYEXPR, D_SUBSCRIBER and D_QUERYABLE declarations
+/// of the frame starts with a byte counting the chunks, at most 127, it shares with the suffix
+/// previously declared in the frame, followed by the rest of the suffix, see
+/// [`crate::network::declare::prefix`]. Only sent to the peers which negotiated the prefix
+/// compression at the transport establishment.
+///
 pub mod flag {
     pub const R: u8 = 1 << 5; // 0x20 Reliable      if R==1 then the frame is reliable
-                              // pub const X: u8 = 1 << 6; // 0x40       Reserved
+    pub const C: u8 = 1 << 6; // 0x40 Compressed    if C==1 then the declared key expressions are prefix-delta encoded
     pub const Z: u8 = 1 << 7; // 0x80 Extensions    if Z==1 then an extension will follow
 }
 
@@ -72,6 +82,8 @@ pub struct Frame {
     pub sn: TransportSn,
     pub payload: Vec<NetworkMessage>,
     pub ext_qos: ext::QoSType,
+    /// The messages of unknown kinds skipped when decoding the payload, never encoded.
+    pub skipped: Vec<UnknownKind>,
 }
 
 // Extensions
@@ -94,7 +106,8 @@ impl Frame {
         let ext_qos = ext::QoSType::rand();
         let mut payload = vec![];
         for _ in 0..rng.gen_range(1..4) {
-            let m = NetworkMessage::rand();
+            let mut m = NetworkMessage::rand();
+            m.reliability = reliability;
             payload.push(m);
         }
 
@@ -103,6 +116,7 @@ impl Frame {
             sn,
             ext_qos,
             payload,
+            skipped: vec![],
         }
     }
 }
@@ -113,6 +127,8 @@ pub struct FrameHeader {
     pub reliability: Reliability,
     pub sn: TransportSn,
     pub ext_qos: ext::QoSType,
+    /// Whether the key expressions declared in the frame are prefix-delta encoded.
+    pub compressed: bool,
 }
 
 impl FrameHeader {
@@ -125,11 +141,13 @@ impl FrameHeader {
         let reliability = Reliability::rand();
         let sn: TransportSn = rng.gen();
         let ext_qos = ext::QoSType::rand();
+        let compressed = rng.gen_bool(0.5);
 
         FrameHeader {
             reliability,
             sn,
             ext_qos,
+            compressed,
         }
     }
 }
@@ -118,6 +118,9 @@ pub struct InitSyn {
     pub ext_auth: Option<ext::Auth>,
     pub ext_mlink: Option<ext::MultiLink>,
     pub ext_lowlatency: Option<ext::LowLatency>,
+    pub ext_resync: Option<ext::Resync>,
+    pub ext_prefix: Option<ext::PrefixCompression>,
+    pub ext_integrity: Option<ext::Integrity>,
 }
 
 // Extensions
@@ -146,6 +149,18 @@ pub mod ext {
     /// # LowLatency extension
     /// Used to negotiate the use of lowlatency transport
     pub type LowLatency = zextunit!(0x5, false);
+
+    /// # Resync extension
+    /// Used to negotiate the bulk resynchronization of the key expression mappings on reconnect
+    pub type Resync = zextunit!(0x6, false);
+
+    /// # PrefixCompression extension
+    /// Used to negotiate the prefix compression of the key expressions declared in bulk
+    pub type PrefixCompression = zextunit!(0x7, false);
+
+    /// # Integrity extension
+    /// Used to negotiate the integrity checksums of the payloads of the publications
+    pub type Integrity = zextunit!(0x8, false);
 }
 
 impl InitSyn {
@@ -166,6 +181,9 @@ impl InitSyn {
         let ext_auth = rng.gen_bool(0.5).then_some(ZExtZBuf::rand());
         let ext_mlink = rng.gen_bool(0.5).then_some(ZExtZBuf::rand());
         let ext_lowlatency = rng.gen_bool(0.5).then_some(ZExtUnit::rand());
+        let ext_resync = rng.gen_bool(0.5).then_some(ZExtUnit::rand());
+        let ext_prefix = rng.gen_bool(0.5).then_some(ZExtUnit::rand());
+        let ext_integrity = rng.gen_bool(0.5).then_some(ZExtUnit::rand());
 
         Self {
             version,
@@ -178,6 +196,9 @@ impl InitSyn {
             ext_auth,
             ext_mlink,
             ext_lowlatency,
+            ext_resync,
+            ext_prefix,
+            ext_integrity,
         }
     }
 }
@@ -195,6 +216,9 @@ pub struct InitAck {
     pub ext_auth: Option<ext::Auth>,
     pub ext_mlink: Option<ext::MultiLink>,
     pub ext_lowlatency: Option<ext::LowLatency>,
+    pub ext_resync: Option<ext::Resync>,
+    pub ext_prefix: Option<ext::PrefixCompression>,
+    pub ext_integrity: Option<ext::Integrity>,
 }
 
 impl InitAck {
@@ -220,6 +244,9 @@ impl InitAck {
         let ext_auth = rng.gen_bool(0.5).then_some(ZExtZBuf::rand());
         let ext_mlink = rng.gen_bool(0.5).then_some(ZExtZBuf::rand());
         let ext_lowlatency = rng.gen_bool(0.5).then_some(ZExtUnit::rand());
+        let ext_resync = rng.gen_bool(0.5).then_some(ZExtUnit::rand());
+        let ext_prefix = rng.gen_bool(0.5).then_some(ZExtUnit::rand());
+        let ext_integrity = rng.gen_bool(0.5).then_some(ZExtUnit::rand());
 
         Self {
             version,
@@ -233,6 +260,9 @@ impl InitAck {
             ext_auth,
             ext_mlink,
             ext_lowlatency,
+            ext_resync,
+            ext_prefix,
+            ext_integrity,
         }
     }
 }
@@ -69,6 +69,11 @@
 /// +---------------+
 /// ```
 ///
+/// NOTE: A [`KeepAlive`] MAY carry an echo request with an opaque token, that the receiver
+///       SHOULD send back in an echo reply on the same link as soon as possible. This allows
+///       the sender to estimate the round-trip time of the link. A node not supporting the
+///       extensions skips them and never replies.
+///
 /// NOTE: 16 bits (2 bytes) may be prepended to the serialized message indicating the total length
 ///       in bytes of the message, resulting in the maximum length of a message being 65535 bytes.
 ///       This is necessary in those stream-oriented transports (e.g., TCP) that do not preserve
@@ -81,12 +86,37 @@ pub mod flag {
     pub const Z: u8 = 1 << 7; // 0x80 Extensions    if Z==1 then an extension will follow
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub struct KeepAlive;
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct KeepAlive {
+    pub ext_echo_request: Option<ext::EchoRequest>,
+    pub ext_echo_reply: Option<ext::EchoReply>,
+}
+
+// Extensions
+pub mod ext {
+    use crate::{common::ZExtZ64, zextz64};
+
+    /// # EchoRequest extension
+    /// Requests the receiver to echo the token in an [`EchoReply`].
+    pub type EchoRequest = zextz64!(0x1, false);
+
+    /// # EchoReply extension
+    /// Echoes the token of a received [`EchoRequest`].
+    pub type EchoReply = zextz64!(0x2, false);
+}
 
 impl KeepAlive {
     #[cfg(feature = "test")]
     pub fn rand() -> Self {
-        Self
+        use rand::Rng;
+        let mut rng = rand::thread_rng();
+
+        let ext_echo_request = rng.gen_bool(0.5).then(ext::EchoRequest::rand);
+        let ext_echo_reply = rng.gen_bool(0.5).then(ext::EchoReply::rand);
+
+        Self {
+            ext_echo_request,
+            ext_echo_reply,
+        }
     }
 }
@@ -201,6 +201,12 @@ impl From<Fragment> for TransportMessage {
     }
 }
 
+impl From<Oam> for TransportMessage {
+    fn from(oam: Oam) -> Self {
+        TransportBody::OAM(oam).into()
+    }
+}
+
 impl From<Join> for TransportMessage {
     fn from(join: Join) -> Self {
         TransportBody::Join(join).into()
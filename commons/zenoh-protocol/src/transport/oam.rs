@@ -21,6 +21,20 @@ pub mod flag {
     pub const Z: u8 = 1 << 7; // 0x80 Extensions    if Z==1 then an extension will follow
 }
 
+pub mod id {
+    use super::OamId;
+
+    /// Closes the link the message is received on, the body being the reason as u64.
+    pub const OAM_CLOSE_LINK: OamId = 0x0001;
+    /// Acknowledges an [`OAM_CLOSE_LINK`] on the link the message is received on.
+    pub const OAM_CLOSE_LINK_ACK: OamId = 0x0002;
+    /// Requests an [`OAM_ACK`] once the messages received before it are received, the body
+    /// being the id of the request as u64.
+    pub const OAM_ACK_REQUEST: OamId = 0x0003;
+    /// Acknowledges an [`OAM_ACK_REQUEST`], the body being the id of the request as u64.
+    pub const OAM_ACK: OamId = 0x0004;
+}
+
 /// ```text
 /// Flags:
 /// - E |: Encoding     The encoding of the extension
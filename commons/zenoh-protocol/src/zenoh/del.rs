@@ -42,6 +42,7 @@ pub mod flag {
 pub struct Del {
     pub timestamp: Option<Timestamp>,
     pub ext_sinfo: Option<ext::SourceInfoType>,
+    pub ext_batch: Option<ext::BatchType>,
     pub ext_unknown: Vec<ZExtUnknown>,
 }
 
@@ -52,11 +53,28 @@ pub mod ext {
     /// Used to carry additional information about the source of data
     pub type SourceInfo = zextzbuf!(0x1, false);
     pub type SourceInfoType = crate::zenoh::ext::SourceInfoType<{ SourceInfo::ID }>;
+
+    /// # Batch extension
+    /// Used to carry the other messages of a batch published atomically, delivered together to
+    /// the subscribers opting in
+    pub type Batch = zextzbuf!(0x4, false);
+    pub type BatchType = crate::zenoh::ext::BatchType<{ Batch::ID }>;
 }
 
 impl Del {
     #[cfg(feature = "test")]
     pub fn rand() -> Self {
+        use rand::Rng;
+        let mut rng = rand::thread_rng();
+
+        let mut x = Self::rand_unbatched();
+        x.ext_batch = rng.gen_bool(0.5).then_some(ext::BatchType::rand());
+        x
+    }
+
+    /// A random message without a batch extension, the batches not nesting.
+    #[cfg(feature = "test")]
+    pub fn rand_unbatched() -> Self {
         use crate::{common::iext, core::ZenohId};
         use rand::Rng;
         let mut rng = rand::thread_rng();
@@ -69,15 +87,13 @@ impl Del {
         let ext_sinfo = rng.gen_bool(0.5).then_some(ext::SourceInfoType::rand());
         let mut ext_unknown = Vec::new();
         for _ in 0..rng.gen_range(0..4) {
-            ext_unknown.push(ZExtUnknown::rand2(
-                iext::mid(ext::SourceInfo::ID) + 1,
-                false,
-            ));
+            ext_unknown.push(ZExtUnknown::rand2(iext::mid(ext::Batch::ID) + 1, false));
         }
 
         Self {
             timestamp,
             ext_sinfo,
+            ext_batch: None,
             ext_unknown,
         }
     }
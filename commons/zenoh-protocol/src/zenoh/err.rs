@@ -40,6 +40,13 @@ pub mod flag {
     pub const Z: u8 = 1 << 7; // 0x80 Extensions        if Z==1 then an extension will follow
 }
 
+/// The codes of the infrastructure errors, with `I==1`.
+pub mod code {
+    /// The query was throttled by the rate limit of its querier. The body is the properties
+    /// `retry_after_ms=<ms>`, the time after which the query may be retried.
+    pub const THROTTLED: u16 = 429;
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Err {
     pub code: u16,
@@ -53,6 +53,22 @@ pub enum PushBody {
 }
 
 impl PushBody {
+    /// The batch extension of the message, carrying the other messages of its batch.
+    pub fn ext_batch(&self) -> Option<&put::ext::BatchType> {
+        match self {
+            PushBody::Put(put) => put.ext_batch.as_ref(),
+            PushBody::Del(del) => del.ext_batch.as_ref(),
+        }
+    }
+
+    /// The mutable batch extension of the message.
+    pub fn ext_batch_mut(&mut self) -> &mut Option<put::ext::BatchType> {
+        match self {
+            PushBody::Put(put) => &mut put.ext_batch,
+            PushBody::Del(del) => &mut del.ext_batch,
+        }
+    }
+
     #[cfg(feature = "test")]
     pub fn rand() -> Self {
         use rand::Rng;
@@ -65,6 +81,20 @@ impl PushBody {
             _ => unreachable!(),
         }
     }
+
+    /// A random message without a batch extension, the batches not nesting.
+    #[cfg(feature = "test")]
+    pub fn rand_unbatched() -> Self {
+        use rand::Rng;
+
+        let mut rng = rand::thread_rng();
+
+        match rng.gen_range(0..2) {
+            0 => PushBody::Put(Put::rand_unbatched()),
+            1 => PushBody::Del(Del::rand_unbatched()),
+            _ => unreachable!(),
+        }
+    }
 }
 
 impl From<Put> for PushBody {
@@ -167,9 +197,11 @@ impl From<Ack> for ResponseBody {
 }
 
 pub mod ext {
+    use alloc::vec::Vec;
     use zenoh_buffers::ZBuf;
 
-    use crate::core::{Encoding, ZenohId};
+    use super::PushBody;
+    use crate::core::{Encoding, WireExpr, ZenohId};
 
     ///  7 6 5 4 3 2 1 0
     /// +-+-+-+-+-+-+-+-+
@@ -201,6 +233,106 @@ pub mod ext {
         }
     }
 
+    ///  7 6 5 4 3 2 1 0
+    /// +-+-+-+-+-+-+-+-+
+    /// |   algorithm   |
+    /// +---------------+
+    /// %   checksum    %  -- Computed over the payload
+    /// +---------------+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct IntegrityType<const ID: u8> {
+        pub algorithm: u8,
+        pub checksum: u64,
+    }
+
+    impl<const ID: u8> IntegrityType<{ ID }> {
+        pub const CRC32C: u8 = 0x00;
+        pub const XXHASH64: u8 = 0x01;
+
+        #[cfg(feature = "test")]
+        pub fn rand() -> Self {
+            use rand::Rng;
+            let mut rng = rand::thread_rng();
+
+            let algorithm: u8 = rng.gen_range(Self::CRC32C..=Self::XXHASH64);
+            let checksum: u64 = rng.gen();
+            Self {
+                algorithm,
+                checksum,
+            }
+        }
+    }
+
+    ///  7 6 5 4 3 2 1 0
+    /// +-+-+-+-+-+-+-+-+
+    /// %      id       %  -- Chosen by the publisher, unique among its batches
+    /// +---------------+
+    /// %   n_entries   %
+    /// +---------------+
+    /// ~    [entry]    ~  -- The other messages of the batch, in publication order
+    /// +---------------+
+    ///
+    /// entry:
+    /// +-+-+-+-+-+-+-+-+
+    /// |X|X|X|X|X|X|M|N|  -- M: the mapping of the sender, N: the key expression has a suffix
+    /// +---------------+
+    /// ~ key_scope:z16 ~
+    /// +---------------+
+    /// ~  key_suffix   ~  if N==1 -- <u8;z16>
+    /// +---------------+
+    /// ~   PushBody    ~
+    /// +---------------+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct BatchType<const ID: u8> {
+        pub id: u64,
+        pub entries: Vec<BatchEntry>,
+    }
+
+    impl<const ID: u8> BatchType<{ ID }> {
+        #[cfg(feature = "test")]
+        pub fn rand() -> Self {
+            use rand::Rng;
+            let mut rng = rand::thread_rng();
+
+            let id: u64 = rng.gen();
+            let entries = (0..rng.gen_range(0..3))
+                .map(|_| BatchEntry::rand())
+                .collect();
+            Self { id, entries }
+        }
+    }
+
+    /// A message of a batch other than the one carrying the batch extension.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct BatchEntry {
+        pub wire_expr: WireExpr<'static>,
+        pub payload: PushBody,
+    }
+
+    impl BatchEntry {
+        /// The key expression has a suffix.
+        pub const FLAG_N: u8 = 1;
+        /// The key expression uses the mapping of the sender.
+        pub const FLAG_M: u8 = 1 << 1;
+
+        #[cfg(feature = "test")]
+        pub fn rand() -> Self {
+            use crate::network::Mapping;
+            use rand::Rng;
+            let mut rng = rand::thread_rng();
+
+            let mut wire_expr = WireExpr::rand();
+            wire_expr.mapping = if rng.gen_bool(0.5) {
+                Mapping::Sender
+            } else {
+                Mapping::Receiver
+            };
+            // The entries don't nest batches
+            let payload = PushBody::rand_unbatched();
+            Self { wire_expr, payload }
+        }
+    }
+
     ///  7 6 5 4 3 2 1 0
     /// +-+-+-+-+-+-+-+-+
     /// +-+-+-+-+-+-+-+-+
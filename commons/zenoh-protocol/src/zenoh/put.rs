@@ -50,6 +50,8 @@ pub struct Put {
     pub ext_sinfo: Option<ext::SourceInfoType>,
     #[cfg(feature = "shared-memory")]
     pub ext_shm: Option<ext::ShmType>,
+    pub ext_integrity: Option<ext::IntegrityType>,
+    pub ext_batch: Option<ext::BatchType>,
     pub ext_unknown: Vec<ZExtUnknown>,
     pub payload: ZBuf,
 }
@@ -70,11 +72,33 @@ pub mod ext {
     pub type Shm = zextunit!(0x2, true);
     #[cfg(feature = "shared-memory")]
     pub type ShmType = crate::zenoh::ext::ShmType<{ Shm::ID }>;
+
+    /// # Integrity extension
+    /// Used to carry a checksum of the payload, verified end-to-end by the receiving sessions
+    pub type Integrity = zextzbuf!(0x3, false);
+    pub type IntegrityType = crate::zenoh::ext::IntegrityType<{ Integrity::ID }>;
+
+    /// # Batch extension
+    /// Used to carry the other messages of a batch published atomically, delivered together to
+    /// the subscribers opting in
+    pub type Batch = zextzbuf!(0x4, false);
+    pub type BatchType = crate::zenoh::ext::BatchType<{ Batch::ID }>;
 }
 
 impl Put {
     #[cfg(feature = "test")]
     pub fn rand() -> Self {
+        use rand::Rng;
+        let mut rng = rand::thread_rng();
+
+        let mut x = Self::rand_unbatched();
+        x.ext_batch = rng.gen_bool(0.5).then_some(ext::BatchType::rand());
+        x
+    }
+
+    /// A random message without a batch extension, the batches not nesting.
+    #[cfg(feature = "test")]
+    pub fn rand_unbatched() -> Self {
         use crate::{common::iext, core::ZenohId};
         use rand::Rng;
         let mut rng = rand::thread_rng();
@@ -88,12 +112,10 @@ impl Put {
         let ext_sinfo = rng.gen_bool(0.5).then_some(ext::SourceInfoType::rand());
         #[cfg(feature = "shared-memory")]
         let ext_shm = rng.gen_bool(0.5).then_some(ext::ShmType::rand());
+        let ext_integrity = rng.gen_bool(0.5).then_some(ext::IntegrityType::rand());
         let mut ext_unknown = Vec::new();
         for _ in 0..rng.gen_range(0..4) {
-            ext_unknown.push(ZExtUnknown::rand2(
-                iext::mid(ext::SourceInfo::ID) + 1,
-                false,
-            ));
+            ext_unknown.push(ZExtUnknown::rand2(iext::mid(ext::Batch::ID) + 1, false));
         }
         let payload = ZBuf::rand(rng.gen_range(1..=64));
 
@@ -103,6 +125,8 @@ impl Put {
             ext_sinfo,
             #[cfg(feature = "shared-memory")]
             ext_shm,
+            ext_integrity,
+            ext_batch: None,
             ext_unknown,
             payload,
         }
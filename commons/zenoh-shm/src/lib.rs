@@ -24,6 +24,9 @@ use zenoh_result::{zerror, ShmError, ZResult};
 
 const MIN_FREE_CHUNK_SIZE: usize = 1_024;
 const ACCOUNTED_OVERHEAD: usize = 4_096;
+// The shm segments are mapped at page boundaries in every process: an alignment up to the size
+// of a page is preserved across processes
+pub const MAX_ALIGNMENT: usize = 4_096;
 const ZENOH_SHM_PREFIX: &str = "zenoh_shm_zid";
 
 // Chunk header
@@ -42,6 +45,10 @@ struct Chunk {
     base_addr: *mut u8,
     offset: usize,
     size: usize,
+    // The padding before the header of an allocated chunk, to align its buffer
+    pad: usize,
+    // The left-over after the buffer of an allocated chunk, too small to be allocated
+    tail: usize,
 }
 
 impl Ord for Chunk {
@@ -76,6 +83,8 @@ pub struct SharedMemoryBufInfo {
     pub shm_manager: String,
     /// The kind of buffer.
     pub kind: u8,
+    /// The alignment of the beginning of the buffer, guaranteed by the shm manager.
+    pub alignment: usize,
 }
 
 impl SharedMemoryBufInfo {
@@ -85,8 +94,15 @@ impl SharedMemoryBufInfo {
             length,
             shm_manager: manager,
             kind,
+            alignment: 1,
         }
     }
+
+    /// Sets the alignment guaranteed for the beginning of the buffer.
+    pub fn with_alignment(mut self, alignment: usize) -> SharedMemoryBufInfo {
+        self.alignment = alignment;
+        self
+    }
 }
 
 /// A zenoh buffer in shared memory.
@@ -132,6 +148,11 @@ impl SharedMemoryBuf {
         self.info.shm_manager.clone()
     }
 
+    /// The alignment of the beginning of the buffer, see [`SharedMemoryManager::alloc_aligned`].
+    pub fn alignment(&self) -> usize {
+        self.info.alignment
+    }
+
     pub fn ref_count(&self) -> usize {
         let rc = self.rc_ptr.load(Ordering::SeqCst);
         unsafe { (*rc).load(Ordering::SeqCst) }
@@ -234,6 +255,15 @@ impl SharedMemoryReader {
                 let rc = unsafe { base_ptr.add(info.offset) as *mut ChunkHeaderType };
                 let rc_ptr = AtomicPtr::<ChunkHeaderType>::new(rc);
                 let buf = unsafe { base_ptr.add(info.offset + CHUNK_HEADER_SIZE) };
+                if info.alignment > 1 && buf as usize % info.alignment != 0 {
+                    let e = zerror!(
+                        "Shared memory buffer in {} is not aligned at {} bytes",
+                        info.shm_manager,
+                        info.alignment
+                    );
+                    log::trace!("{}", e);
+                    return Err(ShmError(e).into());
+                }
                 let shmb = SharedMemoryBuf {
                     rc_ptr,
                     buf: AtomicPtr::new(buf),
@@ -283,6 +313,7 @@ pub struct SharedMemoryManager {
     own_segment: Shmem,
     free_list: BinaryHeap<Chunk>,
     busy_list: Vec<Chunk>,
+    // The default alignment of the allocated buffers
     alignment: usize,
 }
 
@@ -325,6 +356,8 @@ impl SharedMemoryManager {
             base_addr: base_ptr,
             offset: 0,
             size: real_size,
+            pad: 0,
+            tail: 0,
         };
         free_list.push(chunk);
         let busy_list = vec![];
@@ -344,28 +377,70 @@ impl SharedMemoryManager {
         Ok(shm)
     }
 
-    fn free_chunk_map_to_shmbuf(&self, chunk: &Chunk) -> SharedMemoryBuf {
+    /// Returns the default alignment of the buffers allocated with [`alloc`](Self::alloc).
+    pub fn alignment(&self) -> usize {
+        self.alignment
+    }
+
+    /// Sets the default alignment of the buffers allocated with [`alloc`](Self::alloc).
+    ///
+    /// The alignment must be a power of two, and at most [`MAX_ALIGNMENT`].
+    pub fn set_alignment(&mut self, alignment: usize) -> ZResult<()> {
+        self.alignment = Self::check_alignment(alignment)?;
+        Ok(())
+    }
+
+    fn check_alignment(alignment: usize) -> ZResult<usize> {
+        if !alignment.is_power_of_two() || alignment > MAX_ALIGNMENT {
+            let e = zerror!(
+                "Invalid shared memory alignment: {} (must be a power of two up to {})",
+                alignment,
+                MAX_ALIGNMENT
+            );
+            return Err(ShmError(e).into());
+        }
+        // The chunk headers are always aligned
+        Ok(alignment.max(mem::align_of::<ChunkHeaderType>()))
+    }
+
+    fn free_chunk_map_to_shmbuf(&self, chunk: &Chunk, alignment: usize) -> SharedMemoryBuf {
         let info = SharedMemoryBufInfo {
-            offset: chunk.offset,
-            length: chunk.size,
+            offset: chunk.offset + chunk.pad,
+            length: chunk.size - chunk.pad - chunk.tail,
             shm_manager: self.segment_path.clone(),
             kind: 0,
+            alignment,
         };
-        let rc = chunk.base_addr as *mut ChunkHeaderType;
+        let rc = unsafe { chunk.base_addr.add(chunk.pad) } as *mut ChunkHeaderType;
         unsafe { (*rc).store(1, Ordering::SeqCst) };
         let rc_ptr = AtomicPtr::<ChunkHeaderType>::new(rc);
         SharedMemoryBuf {
             rc_ptr,
-            buf: AtomicPtr::<u8>::new(unsafe { chunk.base_addr.add(CHUNK_HEADER_SIZE) }),
-            len: chunk.size - CHUNK_HEADER_SIZE,
+            buf: AtomicPtr::<u8>::new(unsafe {
+                chunk.base_addr.add(chunk.pad + CHUNK_HEADER_SIZE)
+            }),
+            len: chunk.size - chunk.pad - chunk.tail - CHUNK_HEADER_SIZE,
             info,
         }
     }
 
+    /// Allocates a buffer of `len` bytes aligned with the default alignment of the manager,
+    /// see [`set_alignment`](Self::set_alignment).
     pub fn alloc(&mut self, len: usize) -> ZResult<SharedMemoryBuf> {
-        log::trace!("SharedMemoryManager::alloc({})", len);
-        // Always allocate a size that will keep the proper alignment requirements
-        let required_len = align_addr_at(len + CHUNK_HEADER_SIZE, self.alignment);
+        self.alloc_aligned(len, self.alignment)
+    }
+
+    /// Allocates a buffer of `len` bytes whose beginning is aligned at `alignment` bytes.
+    ///
+    /// The alignment must be a power of two, and at most [`MAX_ALIGNMENT`]. It is carried in the
+    /// [`SharedMemoryBufInfo`] of the buffer, and checked by the [`SharedMemoryReader`] when the
+    /// transport negotiated to send it along with the buffer.
+    pub fn alloc_aligned(&mut self, len: usize, alignment: usize) -> ZResult<SharedMemoryBuf> {
+        log::trace!("SharedMemoryManager::alloc_aligned({}, {})", len, alignment);
+        let alignment = Self::check_alignment(alignment)?;
+        // The size of the aligned allocations is a multiple of their alignment: the next
+        // allocations of the same alignment in the left-over don't need any padding
+        let required_len = align_addr_at(len + CHUNK_HEADER_SIZE, alignment);
         if self.available < required_len {
             self.garbage_collect();
         }
@@ -374,20 +449,46 @@ impl SharedMemoryManager {
             // famous Bach's book --  in essence keep an ordered list of free slot and always look for the
             // biggest as that will give the biggest left-over.
             match self.free_list.pop() {
-                Some(mut chunk) if chunk.size >= required_len => {
-                    self.available -= required_len;
+                Some(mut chunk)
+                    if chunk.size >= Self::padding(&chunk, alignment) + required_len =>
+                {
                     log::trace!("Allocator selected Chunk ({:?})", &chunk);
-                    if chunk.size - required_len >= MIN_FREE_CHUNK_SIZE {
+                    let pad = Self::padding(&chunk, alignment);
+                    if pad >= MIN_FREE_CHUNK_SIZE {
+                        // The padding is large enough to be allocated later on
                         let free_chunk = Chunk {
-                            base_addr: unsafe { chunk.base_addr.add(required_len) },
-                            offset: chunk.offset + required_len,
-                            size: chunk.size - required_len,
+                            base_addr: chunk.base_addr,
+                            offset: chunk.offset,
+                            size: pad,
+                            pad: 0,
+                            tail: 0,
+                        };
+                        log::trace!("The alignment will leave a Free Chunk: {:?}", &free_chunk);
+                        self.free_list.push(free_chunk);
+                        chunk.base_addr = unsafe { chunk.base_addr.add(pad) };
+                        chunk.offset += pad;
+                        chunk.size -= pad;
+                    } else {
+                        chunk.pad = pad;
+                    }
+                    let used_len = chunk.pad + required_len;
+                    if chunk.size - used_len >= MIN_FREE_CHUNK_SIZE {
+                        let free_chunk = Chunk {
+                            base_addr: unsafe { chunk.base_addr.add(used_len) },
+                            offset: chunk.offset + used_len,
+                            size: chunk.size - used_len,
+                            pad: 0,
+                            tail: 0,
                         };
                         log::trace!("The allocation will leave a Free Chunk: {:?}", &free_chunk);
                         self.free_list.push(free_chunk);
+                        chunk.size = used_len;
+                    } else {
+                        // Keep the left-over in the chunk, to get it back once the chunk is freed
+                        chunk.tail = chunk.size - used_len;
                     }
-                    chunk.size = required_len;
-                    let shm_buf = self.free_chunk_map_to_shmbuf(&chunk);
+                    self.available -= chunk.size;
+                    let shm_buf = self.free_chunk_map_to_shmbuf(&chunk, alignment);
                     log::trace!("The allocated Chunk is ({:?})", &chunk);
                     log::trace!("Allocated Shared Memory Buffer: {:?}", &shm_buf);
                     self.busy_list.push(chunk);
@@ -411,8 +512,14 @@ impl SharedMemoryManager {
         }
     }
 
+    // The padding to insert before the header of the chunk to align its buffer
+    fn padding(chunk: &Chunk, alignment: usize) -> usize {
+        let buf_addr = chunk.base_addr as usize + CHUNK_HEADER_SIZE;
+        align_addr_at(buf_addr, alignment) - buf_addr
+    }
+
     fn is_free_chunk(chunk: &Chunk) -> bool {
-        let rc_ptr = chunk.base_addr as *mut ChunkHeaderType;
+        let rc_ptr = unsafe { chunk.base_addr.add(chunk.pad) } as *mut ChunkHeaderType;
         let rc = unsafe { (*rc_ptr).load(Ordering::SeqCst) };
         rc == 0
     }
@@ -424,6 +531,8 @@ impl SharedMemoryManager {
                 base_addr: a.base_addr,
                 size: a.size + b.size,
                 offset: a.offset,
+                pad: 0,
+                tail: 0,
             })
         } else {
             None
@@ -476,9 +585,11 @@ impl SharedMemoryManager {
             .partition(|&c| SharedMemoryManager::is_free_chunk(c));
         self.busy_list = busy;
 
-        for f in free {
+        for mut f in free {
             freed += f.size;
             log::trace!("Garbage Collecting Chunk: {:?}", f);
+            f.pad = 0;
+            f.tail = 0;
             self.free_list.push(f)
         }
         self.available += freed;
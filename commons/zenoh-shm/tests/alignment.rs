@@ -0,0 +1,141 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use zenoh_buffers::{SplitBuffer, ZBuf};
+use zenoh_shm::{SharedMemoryBuf, SharedMemoryManager, SharedMemoryReader, MAX_ALIGNMENT};
+
+const SHM_SIZE: usize = 1 << 20;
+const ITERATIONS: usize = 10_000;
+const ALIGNMENTS: [Option<usize>; 5] = [None, Some(1), Some(64), Some(256), Some(MAX_ALIGNMENT)];
+
+// A small deterministic generator, to replay the same churn on every run
+struct XorShift(u64);
+
+impl XorShift {
+    fn next(&mut self, bound: usize) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 % bound as u64) as usize
+    }
+}
+
+fn shm_manager(name: &str) -> SharedMemoryManager {
+    let id = format!("{}_{}", name, std::process::id());
+    SharedMemoryManager::make(id, SHM_SIZE).unwrap()
+}
+
+fn check_disjoint(live: &[(SharedMemoryBuf, u8)], buf: &SharedMemoryBuf) {
+    let start = buf.as_slice().as_ptr() as usize;
+    let end = start + buf.len();
+    for (other, _) in live {
+        let other_start = other.as_slice().as_ptr() as usize;
+        let other_end = other_start + other.len();
+        assert!(
+            end <= other_start || other_end <= start,
+            "Overlapping buffers: {buf:?} {other:?}"
+        );
+    }
+}
+
+#[test]
+fn shm_alignment_churn() {
+    let mut shm = shm_manager("alignment_churn");
+    let default_alignment = shm.alignment();
+    let mut rng = XorShift(0x2545_f491_4f6c_dd1d);
+    let mut live: Vec<(SharedMemoryBuf, u8)> = vec![];
+    let mut allocated = 0;
+
+    for i in 0..ITERATIONS {
+        if !live.is_empty() && rng.next(3) == 0 {
+            let (buf, tag) = live.swap_remove(rng.next(live.len()));
+            assert!(buf.as_slice().iter().all(|b| *b == tag));
+            continue;
+        }
+
+        let len = 1 + rng.next(8_192);
+        let alignment = ALIGNMENTS[rng.next(ALIGNMENTS.len())];
+        let alloc = |shm: &mut SharedMemoryManager| match alignment {
+            Some(alignment) => shm.alloc_aligned(len, alignment),
+            None => shm.alloc(len),
+        };
+        let mut buf = match alloc(&mut shm) {
+            Ok(buf) => buf,
+            Err(_) => {
+                // Release half of the buffers and make some room
+                live.truncate(live.len() / 2);
+                shm.garbage_collect();
+                shm.defragment();
+                match alloc(&mut shm) {
+                    Ok(buf) => buf,
+                    Err(_) => continue,
+                }
+            }
+        };
+        allocated += 1;
+
+        let expected = alignment
+            .unwrap_or(default_alignment)
+            .max(default_alignment);
+        assert_eq!(buf.alignment(), expected);
+        assert_eq!(buf.as_slice().as_ptr() as usize % expected, 0);
+        assert!(buf.len() >= len);
+        check_disjoint(&live, &buf);
+
+        let tag = i as u8;
+        buf.as_mut().fill(tag);
+        live.push((buf, tag));
+    }
+    assert!(allocated > ITERATIONS / 2);
+
+    // The buffers were never overwritten by the other allocations
+    for (buf, tag) in &live {
+        assert!(buf.as_slice().iter().all(|b| b == tag));
+    }
+}
+
+#[test]
+fn shm_alignment_preserved() {
+    let mut shm = shm_manager("alignment_preserved");
+
+    // Invalid alignments are refused
+    assert!(shm.alloc_aligned(64, 3).is_err());
+    assert!(shm.alloc_aligned(64, 2 * MAX_ALIGNMENT).is_err());
+    assert!(shm.set_alignment(100).is_err());
+
+    // The default alignment of the manager applies to the unaligned allocations
+    shm.set_alignment(MAX_ALIGNMENT).unwrap();
+    let _unaligned = shm.alloc_aligned(10, 1).unwrap();
+    let mut buf = shm.alloc(100).unwrap();
+    assert_eq!(buf.alignment(), MAX_ALIGNMENT);
+    buf.as_mut().fill(42);
+
+    // The alignment is carried in the descriptor, and preserved by the receivers
+    buf.inc_ref_count();
+    let mut reader = SharedMemoryReader::new();
+    let received = reader.read_shmbuf(&buf.info).unwrap();
+    assert_eq!(received.alignment(), MAX_ALIGNMENT);
+    assert_eq!(received.as_slice().as_ptr() as usize % MAX_ALIGNMENT, 0);
+    assert_eq!(received.as_slice(), buf.as_slice());
+
+    // A descriptor whose buffer isn't aligned is refused
+    let mut info = buf.info.clone();
+    info.offset += 8;
+    assert!(reader.read_shmbuf(&info).is_err());
+
+    // The contiguous view of a payload points to the aligned buffer
+    let zbuf = ZBuf::from(received);
+    let contiguous = zbuf.contiguous();
+    assert_eq!(contiguous.as_ptr() as usize % MAX_ALIGNMENT, 0);
+    assert_eq!(&contiguous[..], buf.as_slice());
+}
@@ -0,0 +1,305 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use std::{
+    any::Any,
+    fmt,
+    ops::{Deref, DerefMut},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex, Weak,
+    },
+};
+use zenoh_buffers::ZSliceBuffer;
+use zenoh_core::zlock;
+
+struct SizeClass {
+    size: usize,
+    buffers: Mutex<Vec<Box<[u8]>>>,
+}
+
+struct BufferPoolInner {
+    // Sorted by increasing size
+    classes: Vec<SizeClass>,
+    capacity: usize,
+    // The total size of the buffers waiting in the pool
+    pooled: AtomicUsize,
+    hits: AtomicUsize,
+    misses: AtomicUsize,
+}
+
+impl BufferPoolInner {
+    fn class(&self, size: usize) -> Option<&SizeClass> {
+        self.classes.iter().find(|class| class.size >= size)
+    }
+
+    fn recycle(&self, buffer: Box<[u8]>) {
+        let size = buffer.len();
+        let class = match self.classes.iter().find(|class| class.size == size) {
+            Some(class) => class,
+            None => return,
+        };
+        // The buffers beyond the capacity of the pool are freed
+        if self.pooled.fetch_add(size, Ordering::Relaxed) + size > self.capacity {
+            self.pooled.fetch_sub(size, Ordering::Relaxed);
+            return;
+        }
+        zlock!(class.buffers).push(buffer);
+    }
+}
+
+/// A pool of byte buffers recycled by size class, bounded by the total size of the buffers
+/// it keeps.
+///
+/// A buffer taken from the pool returns to it once dropped, i.e. once all the [`ZSlice`]s
+/// viewing it are dropped when it backs a [`ZSlice`]. The pool falls back to allocating
+/// a new buffer when no buffer of the requested class is available.
+///
+/// [`ZSlice`]: zenoh_buffers::ZSlice
+#[derive(Clone)]
+pub struct BufferPool {
+    inner: Arc<BufferPoolInner>,
+}
+
+impl BufferPool {
+    /// Creates a pool recycling the buffers of the given `sizes`, keeping at most `capacity`
+    /// bytes of buffers. A `capacity` of 0 disables the recycling.
+    pub fn new(sizes: &[usize], capacity: usize) -> BufferPool {
+        let mut sizes = sizes.to_vec();
+        sizes.sort_unstable();
+        sizes.dedup();
+        BufferPool {
+            inner: Arc::new(BufferPoolInner {
+                classes: sizes
+                    .into_iter()
+                    .map(|size| SizeClass {
+                        size,
+                        buffers: Mutex::new(vec![]),
+                    })
+                    .collect(),
+                capacity,
+                pooled: AtomicUsize::new(0),
+                hits: AtomicUsize::new(0),
+                misses: AtomicUsize::new(0),
+            }),
+        }
+    }
+
+    /// Takes a buffer of at least `size` bytes: the one of the smallest class that fits.
+    ///
+    /// The buffers larger than the largest class are allocated and never recycled.
+    pub fn take(&self, size: usize) -> PooledBuffer {
+        let class = match self.inner.class(size) {
+            Some(class) => class,
+            None => {
+                self.inner.misses.fetch_add(1, Ordering::Relaxed);
+                return PooledBuffer::from(vec![0u8; size].into_boxed_slice());
+            }
+        };
+        let buffer = zlock!(class.buffers).pop();
+        let recycled = buffer.is_some();
+        let buffer = match buffer {
+            Some(buffer) => {
+                self.inner.pooled.fetch_sub(class.size, Ordering::Relaxed);
+                self.inner.hits.fetch_add(1, Ordering::Relaxed);
+                buffer
+            }
+            None => {
+                self.inner.misses.fetch_add(1, Ordering::Relaxed);
+                vec![0u8; class.size].into_boxed_slice()
+            }
+        };
+        PooledBuffer {
+            buffer,
+            recycled,
+            pool: Arc::downgrade(&self.inner),
+        }
+    }
+
+    /// Allocates up to `count` buffers of the class of `size` in advance, within the capacity
+    /// of the pool.
+    pub fn prefill(&self, size: usize, count: usize) {
+        if let Some(class) = self.inner.class(size) {
+            for _ in 0..count {
+                if self.inner.pooled.load(Ordering::Relaxed) + class.size > self.inner.capacity {
+                    break;
+                }
+                self.inner.recycle(vec![0u8; class.size].into_boxed_slice());
+            }
+        }
+    }
+
+    /// The number of buffers taken from the pool.
+    pub fn hits(&self) -> usize {
+        self.inner.hits.load(Ordering::Relaxed)
+    }
+
+    /// The number of buffers allocated because none was available in the pool.
+    pub fn misses(&self) -> usize {
+        self.inner.misses.load(Ordering::Relaxed)
+    }
+
+    /// The total size in bytes of the buffers waiting in the pool.
+    pub fn pooled(&self) -> usize {
+        self.inner.pooled.load(Ordering::Relaxed)
+    }
+}
+
+impl fmt::Debug for BufferPool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BufferPool")
+            .field(
+                "classes",
+                &self
+                    .inner
+                    .classes
+                    .iter()
+                    .map(|c| c.size)
+                    .collect::<Vec<_>>(),
+            )
+            .field("capacity", &self.inner.capacity)
+            .field("pooled", &self.pooled())
+            .field("hits", &self.hits())
+            .field("misses", &self.misses())
+            .finish()
+    }
+}
+
+/// A buffer taken from a [`BufferPool`], returning to it when dropped.
+pub struct PooledBuffer {
+    buffer: Box<[u8]>,
+    recycled: bool,
+    pool: Weak<BufferPoolInner>,
+}
+
+impl PooledBuffer {
+    /// Whether the buffer was taken from the pool rather than allocated.
+    pub fn recycled(&self) -> bool {
+        self.recycled
+    }
+}
+
+impl From<Box<[u8]>> for PooledBuffer {
+    fn from(buffer: Box<[u8]>) -> PooledBuffer {
+        PooledBuffer {
+            buffer,
+            recycled: false,
+            pool: Weak::new(),
+        }
+    }
+}
+
+impl Deref for PooledBuffer {
+    type Target = [u8];
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.buffer
+    }
+}
+
+impl DerefMut for PooledBuffer {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.buffer
+    }
+}
+
+impl Drop for PooledBuffer {
+    fn drop(&mut self) {
+        if let Some(pool) = self.pool.upgrade() {
+            pool.recycle(std::mem::take(&mut self.buffer));
+        }
+    }
+}
+
+impl fmt::Debug for PooledBuffer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PooledBuffer")
+            .field("len", &self.buffer.len())
+            .finish()
+    }
+}
+
+impl AsRef<[u8]> for PooledBuffer {
+    fn as_ref(&self) -> &[u8] {
+        self
+    }
+}
+
+impl AsMut<[u8]> for PooledBuffer {
+    fn as_mut(&mut self) -> &mut [u8] {
+        self
+    }
+}
+
+impl ZSliceBuffer for PooledBuffer {
+    fn as_slice(&self) -> &[u8] {
+        self
+    }
+    fn as_mut_slice(&mut self) -> &mut [u8] {
+        self
+    }
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zenoh_buffers::ZSlice;
+
+    #[test]
+    fn buffer_pool_size_classes() {
+        let pool = BufferPool::new(&[4_096, 1_024], 8_192);
+
+        // The smallest class that fits is used
+        let buffer = pool.take(1_000);
+        assert_eq!(buffer.len(), 1_024);
+        assert_eq!(pool.misses(), 1);
+        drop(buffer);
+        assert_eq!(pool.pooled(), 1_024);
+        let buffer = pool.take(512);
+        assert_eq!(buffer.len(), 1_024);
+        assert!(buffer.recycled());
+        assert_eq!(pool.hits(), 1);
+        drop(buffer);
+
+        // The buffers larger than all the classes are not recycled
+        let buffer = pool.take(5_000);
+        assert_eq!(buffer.len(), 5_000);
+        drop(buffer);
+        assert_eq!(pool.pooled(), 1_024);
+
+        // The pool keeps its capacity
+        let buffers = (0..4).map(|_| pool.take(4_096)).collect::<Vec<_>>();
+        drop(buffers);
+        assert_eq!(pool.pooled(), 1_024 + 4_096);
+    }
+
+    #[test]
+    fn buffer_pool_zslice_views() {
+        let pool = BufferPool::new(&[1_024], 1_024);
+        let slice = ZSlice::make(Arc::new(pool.take(1_024)), 0, 16).unwrap();
+        let view = slice.subslice(0, 8).unwrap();
+
+        // The buffer only returns to the pool when all its views are dropped
+        drop(slice);
+        assert_eq!(pool.pooled(), 0);
+        drop(view);
+        assert_eq!(pool.pooled(), 1_024);
+
+        pool.prefill(1_024, 4);
+        assert_eq!(pool.pooled(), 1_024);
+    }
+}
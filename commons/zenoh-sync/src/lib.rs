@@ -31,6 +31,9 @@ pub use lifo_queue::*;
 pub mod object_pool;
 pub use object_pool::*;
 
+pub mod buffer_pool;
+pub use buffer_pool::*;
+
 pub mod mvar;
 pub use mvar::*;
 
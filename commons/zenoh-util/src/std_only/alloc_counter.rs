@@ -0,0 +1,118 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! A global allocator counting the allocations, for the tests asserting the allocation budgets
+//! of the hot paths.
+//!
+//! The test binary installs it with:
+//! ```ignore
+//! #[global_allocator]
+//! static ALLOCATOR: zenoh_util::alloc_counter::CountingAllocator =
+//!     zenoh_util::alloc_counter::CountingAllocator;
+//! ```
+//! and measures the allocations of a closure with [`measure`]. The counters are global: the
+//! allocations of all the threads are counted, including the tasks of the runtime.
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+static INSTALLED: AtomicBool = AtomicBool::new(false);
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static BYTES: AtomicUsize = AtomicUsize::new(0);
+// The measures are serialized, the tests of a binary running in parallel
+static MEASURE: Mutex<()> = Mutex::new(());
+
+/// A global allocator counting the allocations and reallocations before forwarding them to the
+/// [`System`] allocator.
+pub struct CountingAllocator;
+
+impl CountingAllocator {
+    #[inline]
+    fn count(size: usize) {
+        INSTALLED.store(true, Ordering::Relaxed);
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        BYTES.fetch_add(size, Ordering::Relaxed);
+    }
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        Self::count(layout.size());
+        System.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        Self::count(layout.size());
+        System.alloc_zeroed(layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        Self::count(new_size);
+        System.realloc(ptr, layout, new_size)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+/// The allocations counted by [`measure`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Allocations {
+    /// The number of allocations and reallocations.
+    pub count: usize,
+    /// The number of bytes allocated and reallocated.
+    pub bytes: usize,
+}
+
+impl Allocations {
+    /// The allocations per message when `messages` messages were measured, rounded up.
+    pub fn per_message(&self, messages: usize) -> Allocations {
+        let messages = messages.max(1);
+        Allocations {
+            count: (self.count + messages - 1) / messages,
+            bytes: (self.bytes + messages - 1) / messages,
+        }
+    }
+}
+
+/// Whether [`CountingAllocator`] is the global allocator of the process.
+pub fn is_installed() -> bool {
+    // Allocates at least once through the global allocator
+    drop(std::hint::black_box(Box::new(0u8)));
+    INSTALLED.load(Ordering::Relaxed)
+}
+
+/// Runs `f` and returns its result with the allocations counted while it ran.
+///
+/// # Panics
+/// If [`CountingAllocator`] isn't the global allocator, nothing would be counted.
+pub fn measure<F, R>(f: F) -> (R, Allocations)
+where
+    F: FnOnce() -> R,
+{
+    assert!(
+        is_installed(),
+        "CountingAllocator must be the global allocator to measure the allocations"
+    );
+    let _guard = MEASURE.lock().unwrap_or_else(|e| e.into_inner());
+    let count = ALLOCATIONS.load(Ordering::SeqCst);
+    let bytes = BYTES.load(Ordering::SeqCst);
+    let res = f();
+    let allocations = Allocations {
+        count: ALLOCATIONS.load(Ordering::SeqCst) - count,
+        bytes: BYTES.load(Ordering::SeqCst) - bytes,
+    };
+    (res, allocations)
+}
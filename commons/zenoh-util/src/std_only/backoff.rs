@@ -0,0 +1,416 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+//! Exponential backoff between the attempts of an operation.
+//!
+//! A [`Backoff`] yields the delays to wait between the attempts of an operation: starting at an
+//! initial period, multiplied after each attempt up to a maximum period, optionally shortened by
+//! a random jitter and limited to a number of attempts. [`retry`] runs an operation until it
+//! succeeds, fails with an error that is not retryable, runs out of attempts or is cancelled
+//! through a [`CancellationToken`].
+use crate::clock::{Clock, SystemClock};
+use async_std::prelude::FutureExt;
+use event_listener::Event;
+use rand::Rng;
+use std::fmt;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+struct Cancellation {
+    cancelled: AtomicBool,
+    event: Event,
+}
+
+/// A token cancelling the retries of the [`Backoff`] policies it is given to.
+///
+/// All the clones of a token are cancelled together.
+#[derive(Clone)]
+pub struct CancellationToken(Arc<Cancellation>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self(Arc::new(Cancellation {
+            cancelled: AtomicBool::new(false),
+            event: Event::new(),
+        }))
+    }
+
+    /// Cancels the token, interrupting the sleeps of the retries waiting on it.
+    pub fn cancel(&self) {
+        self.0.cancelled.store(true, Ordering::Release);
+        self.0.event.notify(usize::MAX);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.cancelled.load(Ordering::Acquire)
+    }
+
+    /// Completes once the token is cancelled.
+    pub async fn cancelled(&self) {
+        while !self.is_cancelled() {
+            let listener = self.0.event.listen();
+            // The token may have been cancelled before listening
+            if self.is_cancelled() {
+                break;
+            }
+            listener.await;
+        }
+    }
+}
+
+impl Default for CancellationToken {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The delays between the attempts of an operation.
+///
+/// Iterating a [`Backoff`] yields the delay to wait before each new attempt, the first attempt
+/// being immediate: a policy limited to `n` attempts yields `n - 1` delays. The iteration also
+/// ends once the cancellation token of the policy is cancelled.
+#[derive(Clone)]
+pub struct Backoff {
+    period: Duration,
+    max_period: Duration,
+    multiplier: f64,
+    jitter: f64,
+    max_attempts: Option<usize>,
+    attempts: usize,
+    clock: Arc<dyn Clock>,
+    cancellation: Option<CancellationToken>,
+}
+
+impl Backoff {
+    /// Creates an unlimited policy waiting `period` before the second attempt, doubling the
+    /// delay at each attempt up to `max_period`, without jitter.
+    pub fn new(period: Duration, max_period: Duration) -> Self {
+        Self {
+            period: period.min(max_period),
+            max_period,
+            multiplier: 2.0,
+            jitter: 0.0,
+            max_attempts: None,
+            attempts: 1,
+            clock: Arc::new(SystemClock),
+            cancellation: None,
+        }
+    }
+
+    /// Sets the factor applied to the delay after each attempt, at least 1.
+    pub fn multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier.max(1.0);
+        self
+    }
+
+    /// Sets the fraction, between 0 and 1, by which each delay is randomly shortened.
+    ///
+    /// With a jitter `j`, a delay `d` becomes a random delay between `d * (1 - j)` and `d`,
+    /// so that the nodes retrying at the same time spread their attempts.
+    pub fn jitter(mut self, jitter: f64) -> Self {
+        self.jitter = if jitter > 0.0 { jitter.min(1.0) } else { 0.0 };
+        self
+    }
+
+    /// Limits the number of attempts of the operation, including the first one.
+    pub fn max_attempts(mut self, max_attempts: usize) -> Self {
+        self.max_attempts = Some(max_attempts);
+        self
+    }
+
+    /// Sets the clock the retries sleep on, the system clock by default.
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Sets the token cancelling the retries.
+    pub fn cancellation(mut self, cancellation: CancellationToken) -> Self {
+        self.cancellation = Some(cancellation);
+        self
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancellation
+            .as_ref()
+            .map_or(false, CancellationToken::is_cancelled)
+    }
+
+    // Sleeps for `delay` on the clock of the policy, returning `false` if cancelled meanwhile.
+    async fn sleep(&self, delay: Duration) -> bool {
+        let sleep = self.clock.sleep(delay);
+        let cancelled = async {
+            match &self.cancellation {
+                Some(cancellation) => cancellation.cancelled().await,
+                None => futures::future::pending().await,
+            }
+        };
+        async {
+            sleep.await;
+            true
+        }
+        .race(async {
+            cancelled.await;
+            false
+        })
+        .await
+    }
+}
+
+impl Iterator for Backoff {
+    type Item = Duration;
+
+    fn next(&mut self) -> Option<Duration> {
+        if self.is_cancelled()
+            || self
+                .max_attempts
+                .map_or(false, |max_attempts| self.attempts >= max_attempts)
+        {
+            return None;
+        }
+        self.attempts += 1;
+
+        let period = self.period;
+        // The next period is bounded before being converted back, as it overflows a Duration
+        // after enough attempts
+        self.period = Duration::try_from_secs_f64(period.as_secs_f64() * self.multiplier)
+            .map_or(self.max_period, |next| next.min(self.max_period));
+
+        let jitter = period.as_secs_f64() * self.jitter * rand::thread_rng().gen::<f64>();
+        let jitter = Duration::try_from_secs_f64(jitter).unwrap_or(period);
+        Some(period.saturating_sub(jitter))
+    }
+}
+
+/// The reason why [`retry`] gave up on an operation.
+#[derive(Debug)]
+pub enum RetryError<E> {
+    /// The operation failed with an error that is not retryable.
+    Fatal(E),
+    /// The operation failed on its last allowed attempt.
+    Exhausted(E),
+    /// The retries were cancelled, after the given error of the last attempt if any.
+    Cancelled(Option<E>),
+}
+
+impl<E> RetryError<E> {
+    /// Returns the error of the last attempt, if any.
+    pub fn into_inner(self) -> Option<E> {
+        match self {
+            RetryError::Fatal(e) | RetryError::Exhausted(e) => Some(e),
+            RetryError::Cancelled(e) => e,
+        }
+    }
+}
+
+impl<E: fmt::Display> fmt::Display for RetryError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RetryError::Fatal(e) => write!(f, "{e}"),
+            RetryError::Exhausted(e) => write!(f, "{e} (no attempt left)"),
+            RetryError::Cancelled(Some(e)) => write!(f, "{e} (retries cancelled)"),
+            RetryError::Cancelled(None) => write!(f, "retries cancelled"),
+        }
+    }
+}
+
+impl<E: std::error::Error> std::error::Error for RetryError<E> {}
+
+/// Runs `op` until it succeeds, waiting between its attempts the delays of `policy`.
+///
+/// The errors for which `is_retryable` returns `false` are returned right away, as well as the
+/// error of the last attempt allowed by `policy`. A cancellation of the policy interrupts the
+/// sleep in progress.
+pub async fn retry<T, E, Op, Fut, P>(
+    mut policy: Backoff,
+    mut is_retryable: P,
+    mut op: Op,
+) -> Result<T, RetryError<E>>
+where
+    Op: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+    P: FnMut(&E) -> bool,
+{
+    if policy.is_cancelled() {
+        return Err(RetryError::Cancelled(None));
+    }
+    loop {
+        let e = match op().await {
+            Ok(output) => return Ok(output),
+            Err(e) => e,
+        };
+        if !is_retryable(&e) {
+            return Err(RetryError::Fatal(e));
+        }
+        match policy.next() {
+            Some(delay) => {
+                if !policy.sleep(delay).await {
+                    return Err(RetryError::Cancelled(Some(e)));
+                }
+            }
+            None if policy.is_cancelled() => return Err(RetryError::Cancelled(Some(e))),
+            None => return Err(RetryError::Exhausted(e)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::TestClock;
+    use std::sync::atomic::AtomicUsize;
+
+    const MS: Duration = Duration::from_millis(1);
+
+    #[test]
+    fn backoff_periods() {
+        let delays: Vec<_> = Backoff::new(100 * MS, 1_000 * MS).take(6).collect();
+        assert_eq!(
+            delays,
+            [
+                100 * MS,
+                200 * MS,
+                400 * MS,
+                800 * MS,
+                1_000 * MS,
+                1_000 * MS
+            ]
+        );
+
+        let delays: Vec<_> = Backoff::new(100 * MS, 1_000 * MS)
+            .multiplier(3.0)
+            .max_attempts(4)
+            .collect();
+        assert_eq!(delays, [100 * MS, 300 * MS, 900 * MS]);
+    }
+
+    #[test]
+    fn backoff_jitter_bounds() {
+        let policy = Backoff::new(100 * MS, 10_000 * MS).max_attempts(1_000);
+        let jittered = policy.clone().jitter(0.25);
+        let mut spread = false;
+        for (delay, jittered) in policy.zip(jittered) {
+            assert!(jittered <= delay);
+            assert!(jittered + Duration::from_nanos(1) >= delay.mul_f64(0.75));
+            spread |= jittered != delay;
+        }
+        assert!(spread);
+
+        // Out of range jitters are bounded
+        let delays = Backoff::new(100 * MS, 100 * MS).jitter(2.0).take(1_000);
+        assert!(delays.into_iter().all(|delay| delay <= 100 * MS));
+        let delays = Backoff::new(100 * MS, 100 * MS).jitter(-1.0).take(10);
+        assert!(delays.into_iter().all(|delay| delay == 100 * MS));
+    }
+
+    #[test]
+    fn backoff_overflow() {
+        // The period reaches the maximum without overflowing, whatever the number of attempts
+        let last = Backoff::new(MS, Duration::MAX)
+            .multiplier(10.0)
+            .take(10_000)
+            .last();
+        assert_eq!(last, Some(Duration::MAX));
+
+        let mut policy = Backoff::new(MS, 5_000 * MS).multiplier(f64::MAX);
+        assert_eq!(policy.next(), Some(MS));
+        assert!(policy.take(10_000).all(|delay| delay == 5_000 * MS));
+    }
+
+    #[test]
+    fn retry_attempts() {
+        async_std::task::block_on(async {
+            // Retryable errors are retried until the last attempt
+            let calls = AtomicUsize::new(0);
+            let res: Result<(), _> = retry(
+                Backoff::new(MS, MS).max_attempts(3),
+                |_| true,
+                || async {
+                    calls.fetch_add(1, Ordering::SeqCst);
+                    Err("failure")
+                },
+            )
+            .await;
+            assert!(matches!(res, Err(RetryError::Exhausted("failure"))));
+            assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+            // Fatal errors are returned right away
+            let calls = AtomicUsize::new(0);
+            let res: Result<(), _> = retry(
+                Backoff::new(MS, MS),
+                |e| *e != "fatal",
+                || async {
+                    calls.fetch_add(1, Ordering::SeqCst);
+                    Err("fatal")
+                },
+            )
+            .await;
+            assert!(matches!(res, Err(RetryError::Fatal("fatal"))));
+            assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+            // The operation is retried until it succeeds
+            let calls = AtomicUsize::new(0);
+            let res = retry(
+                Backoff::new(MS, MS),
+                |_: &&str| true,
+                || async {
+                    match calls.fetch_add(1, Ordering::SeqCst) {
+                        0..=2 => Err("failure"),
+                        n => Ok(n),
+                    }
+                },
+            )
+            .await;
+            assert_eq!(res.unwrap(), 3);
+        });
+    }
+
+    #[test]
+    fn retry_cancellation() {
+        let clock = TestClock::new();
+        let cancellation = CancellationToken::new();
+        let calls = Arc::new(AtomicUsize::new(0));
+
+        // The clock never advances: the retry sleeps until cancelled
+        let policy = Backoff::new(Duration::from_secs(10), Duration::from_secs(10))
+            .clock(Arc::new(clock.clone()))
+            .cancellation(cancellation.clone());
+        let c_calls = calls.clone();
+        let handle = async_std::task::spawn(retry(
+            policy.clone(),
+            |_| true,
+            move || {
+                c_calls.fetch_add(1, Ordering::SeqCst);
+                async { Err::<(), _>("failure") }
+            },
+        ));
+        async_std::task::block_on(async {
+            while calls.load(Ordering::SeqCst) == 0 {
+                async_std::task::sleep(MS).await;
+            }
+            async_std::task::sleep(10 * MS).await;
+            cancellation.cancel();
+            let res = handle.timeout(Duration::from_secs(10)).await.unwrap();
+            assert!(matches!(res, Err(RetryError::Cancelled(Some("failure")))));
+        });
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // A cancelled policy neither yields delays nor attempts the operation
+        assert_eq!(policy.clone().next(), None);
+        let res: Result<(), _> =
+            async_std::task::block_on(retry(policy, |_: &()| true, || async { Ok(()) }));
+        assert!(matches!(res, Err(RetryError::Cancelled(None))));
+    }
+}
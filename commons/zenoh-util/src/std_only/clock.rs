@@ -0,0 +1,310 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! Clocks driving the timers and the timestamping.
+//!
+//! Components read the time through a [`Clock`] instead of calling `Instant::now()` or
+//! `SystemTime::now()` directly. [`SystemClock`] reads the real clocks and is the default.
+//! [`TestClock`] only moves forward when [`TestClock::advance`] is called and can be skewed
+//! per component, so that tests can check leases and HLC drift handling without sleeping.
+use async_std::prelude::FutureExt;
+use event_listener::Event;
+use futures::future::BoxFuture;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use zenoh_core::zlock;
+use zenoh_protocol::core::NTP64;
+
+/// A source of monotonic and wall-clock time.
+pub trait Clock: Send + Sync {
+    /// Returns the current monotonic time.
+    fn now(&self) -> Instant;
+
+    /// Returns the current wall-clock time.
+    fn system_time(&self) -> SystemTime;
+
+    /// Returns a future completing once `duration` elapsed on this clock.
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()>;
+
+    /// Returns the physical clock to be given to an HLC timestamping with this clock.
+    fn hlc_clock(&self) -> fn() -> NTP64;
+}
+
+/// Runs `future` until it completes or `duration` elapsed on `clock` since the call, whichever
+/// comes first.
+///
+/// Returns `None` if `duration` elapsed first.
+pub fn timeout<F: Future>(
+    clock: &dyn Clock,
+    duration: Duration,
+    future: F,
+) -> impl Future<Output = Option<F::Output>> {
+    let sleep = clock.sleep(duration);
+    async { Some(future.await) }.race(async {
+        sleep.await;
+        None
+    })
+}
+
+fn to_ntp64(time: SystemTime) -> NTP64 {
+    NTP64::from(time.duration_since(UNIX_EPOCH).unwrap_or_default())
+}
+
+fn system_ntp64() -> NTP64 {
+    to_ntp64(SystemTime::now())
+}
+
+/// The [`Clock`] reading the real monotonic and wall clocks.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn system_time(&self) -> SystemTime {
+        SystemTime::now()
+    }
+
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+        Box::pin(async_std::task::sleep(duration))
+    }
+
+    fn hlc_clock(&self) -> fn() -> NTP64 {
+        system_ntp64
+    }
+}
+
+struct Timeline {
+    instant: Instant,
+    system_time: SystemTime,
+    elapsed: Mutex<Duration>,
+    event: Event,
+}
+
+/// A manually advanced [`Clock`], for tests.
+///
+/// A [`TestClock`] starts at the real time of its creation and then only moves when
+/// [`TestClock::advance`] is called. The clocks derived from it with [`TestClock::skew_ahead`]
+/// and [`TestClock::skew_behind`] share its timeline but report a shifted wall-clock time,
+/// simulating components whose clocks drifted. Skews don't apply to the monotonic time.
+#[derive(Clone)]
+pub struct TestClock {
+    timeline: Arc<Timeline>,
+    ahead: bool,
+    skew: Duration,
+}
+
+impl TestClock {
+    pub fn new() -> Self {
+        Self {
+            timeline: Arc::new(Timeline {
+                instant: Instant::now(),
+                system_time: SystemTime::now(),
+                elapsed: Mutex::new(Duration::ZERO),
+                event: Event::new(),
+            }),
+            ahead: true,
+            skew: Duration::ZERO,
+        }
+    }
+
+    /// Moves the time forward by `duration`, waking up the sleeps it completes.
+    ///
+    /// The time moves for all the clocks sharing this clock's timeline.
+    pub fn advance(&self, duration: Duration) {
+        *zlock!(self.timeline.elapsed) += duration;
+        self.timeline.event.notify(usize::MAX);
+    }
+
+    /// Returns the time elapsed since this clock's timeline was created.
+    pub fn elapsed(&self) -> Duration {
+        *zlock!(self.timeline.elapsed)
+    }
+
+    /// Returns a clock sharing this clock's timeline whose wall-clock time is `skew` ahead of it.
+    pub fn skew_ahead(&self, skew: Duration) -> Self {
+        self.skewed(true, skew)
+    }
+
+    /// Returns a clock sharing this clock's timeline whose wall-clock time is `skew` behind it.
+    pub fn skew_behind(&self, skew: Duration) -> Self {
+        self.skewed(false, skew)
+    }
+
+    fn skewed(&self, ahead: bool, skew: Duration) -> Self {
+        let (ahead, skew) = match (self.ahead == ahead, self.skew.checked_sub(skew)) {
+            (true, _) => (ahead, self.skew + skew),
+            (false, Some(rest)) => (self.ahead, rest),
+            (false, None) => (ahead, skew - self.skew),
+        };
+        Self {
+            timeline: self.timeline.clone(),
+            ahead,
+            skew,
+        }
+    }
+
+    fn is_same(&self, other: &TestClock) -> bool {
+        Arc::ptr_eq(&self.timeline, &other.timeline)
+            && self.ahead == other.ahead
+            && self.skew == other.skew
+    }
+}
+
+impl Default for TestClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for TestClock {
+    fn now(&self) -> Instant {
+        self.timeline.instant + self.elapsed()
+    }
+
+    fn system_time(&self) -> SystemTime {
+        let time = self.timeline.system_time + self.elapsed();
+        if self.ahead {
+            time + self.skew
+        } else {
+            time - self.skew
+        }
+    }
+
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+        let timeline = self.timeline.clone();
+        let deadline = self.elapsed() + duration;
+        Box::pin(async move {
+            loop {
+                // Listen before checking, not to miss an advance in between
+                let listener = timeline.event.listen();
+                if *zlock!(timeline.elapsed) >= deadline {
+                    break;
+                }
+                listener.await;
+            }
+        })
+    }
+
+    fn hlc_clock(&self) -> fn() -> NTP64 {
+        let mut clocks = zlock!(HLC_CLOCKS);
+        let slot = match clocks.iter().position(|c| c.is_same(self)) {
+            Some(slot) => slot,
+            None => {
+                assert!(
+                    clocks.len() < HLC_SLOTS,
+                    "At most {HLC_SLOTS} distinct TestClocks can drive an HLC"
+                );
+                clocks.push(self.clone());
+                clocks.len() - 1
+            }
+        };
+        HLC_SLOT_CLOCKS[slot]
+    }
+}
+
+// An HLC only accepts a plain `fn() -> NTP64` as physical clock, which can't capture a TestClock:
+// the TestClocks driving an HLC are registered in a fixed set of slots, each read by its own function.
+const HLC_SLOTS: usize = 8;
+
+lazy_static! {
+    static ref HLC_CLOCKS: Mutex<Vec<TestClock>> = Mutex::new(Vec::with_capacity(HLC_SLOTS));
+}
+
+fn hlc_slot<const N: usize>() -> NTP64 {
+    let clock = zlock!(HLC_CLOCKS).get(N).cloned();
+    match clock {
+        Some(clock) => to_ntp64(clock.system_time()),
+        None => system_ntp64(),
+    }
+}
+
+const HLC_SLOT_CLOCKS: [fn() -> NTP64; HLC_SLOTS] = [
+    hlc_slot::<0>,
+    hlc_slot::<1>,
+    hlc_slot::<2>,
+    hlc_slot::<3>,
+    hlc_slot::<4>,
+    hlc_slot::<5>,
+    hlc_slot::<6>,
+    hlc_slot::<7>,
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clock() {
+        let clock = TestClock::new();
+        let instant = clock.now();
+        let time = clock.system_time();
+
+        // A sleep only completes once the clock advanced enough
+        let mut sleep = async_std::task::spawn(clock.sleep(Duration::from_secs(10)));
+        clock.advance(Duration::from_secs(5));
+        async_std::task::block_on(async {
+            let res = async_std::future::timeout(Duration::from_millis(100), &mut sleep).await;
+            assert!(res.is_err());
+            clock.advance(Duration::from_secs(5));
+            sleep.await;
+        });
+        assert_eq!(clock.now() - instant, Duration::from_secs(10));
+        assert_eq!(
+            clock.system_time().duration_since(time).unwrap(),
+            Duration::from_secs(10)
+        );
+
+        // A timeout expires once the clock advanced enough
+        let pending = async_std::task::spawn(timeout(
+            &clock,
+            Duration::from_secs(1),
+            futures::future::pending::<()>(),
+        ));
+        clock.advance(Duration::from_secs(1));
+        assert!(async_std::task::block_on(pending).is_none());
+        let ready = timeout(&clock, Duration::from_secs(1), async { 42 });
+        assert_eq!(async_std::task::block_on(ready), Some(42));
+
+        // Skewed clocks share the timeline
+        let ahead = clock.skew_ahead(Duration::from_secs(3));
+        let behind = ahead.skew_behind(Duration::from_secs(5));
+        assert_eq!(ahead.now(), clock.now());
+        assert_eq!(
+            ahead
+                .system_time()
+                .duration_since(clock.system_time())
+                .unwrap(),
+            Duration::from_secs(3)
+        );
+        assert_eq!(
+            clock
+                .system_time()
+                .duration_since(behind.system_time())
+                .unwrap(),
+            Duration::from_secs(2)
+        );
+        ahead.advance(Duration::from_secs(1));
+        assert_eq!(clock.elapsed(), Duration::from_secs(12));
+
+        // HLC clocks follow the test clocks
+        let hlc_clock = ahead.hlc_clock();
+        assert_eq!(ahead.hlc_clock() as usize, hlc_clock as usize);
+        assert_eq!(hlc_clock(), to_ntp64(ahead.system_time()));
+        assert_ne!(behind.hlc_clock() as usize, hlc_clock as usize);
+    }
+}
@@ -0,0 +1,204 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+//! Cooperative scheduling of the long-running async loops.
+//!
+//! A loop whose futures are always ready never gives the executor thread back to the other
+//! tasks. A [`Cooperate`] budget makes such a loop yield after a number of work units processed
+//! in a row, the loops processing less work than the budget never yielding. A
+//! [`StallWatchdog`] measures how late a periodic timer is woken up, detecting the loops that
+//! starve the executor.
+use async_std::task;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// A budget of work units after which a loop yields to the executor.
+#[derive(Clone, Debug)]
+pub struct Cooperate {
+    budget: usize,
+    remaining: usize,
+}
+
+impl Cooperate {
+    /// Creates a budget of `budget` work units, at least 1.
+    pub fn new(budget: usize) -> Self {
+        let budget = budget.max(1);
+        Self {
+            budget,
+            remaining: budget,
+        }
+    }
+
+    /// Consumes a work unit, returning `true` once the budget is exhausted.
+    ///
+    /// The budget is then replenished: the caller is expected to yield, after releasing the
+    /// locks it can't hold across an await point.
+    pub fn tick(&mut self) -> bool {
+        self.remaining -= 1;
+        if self.remaining == 0 {
+            self.remaining = self.budget;
+            return true;
+        }
+        false
+    }
+
+    /// Consumes a work unit, yielding to the executor once the budget is exhausted.
+    pub async fn step(&mut self) {
+        if self.tick() {
+            task::yield_now().await;
+        }
+    }
+
+    /// Replenishes the budget, when the loop has been suspended by a pending future.
+    pub fn reset(&mut self) {
+        self.remaining = self.budget;
+    }
+}
+
+struct WatchdogState {
+    stopped: AtomicBool,
+    stalls: AtomicUsize,
+    max_latency: AtomicU64,
+}
+
+/// A task waking up periodically and measuring how late it is woken up, i.e. the scheduling
+/// latency of the executor.
+///
+/// The latencies above the threshold are logged as stalls. The task stops when the watchdog is
+/// dropped.
+pub struct StallWatchdog {
+    state: Arc<WatchdogState>,
+}
+
+impl StallWatchdog {
+    /// Spawns a task waking up every `period`, reporting the wake-ups delayed by more than
+    /// `threshold`.
+    pub fn spawn(period: Duration, threshold: Duration) -> Self {
+        let state = Arc::new(WatchdogState {
+            stopped: AtomicBool::new(false),
+            stalls: AtomicUsize::new(0),
+            max_latency: AtomicU64::new(0),
+        });
+        let c_state = state.clone();
+        task::spawn(async move {
+            while !c_state.stopped.load(Ordering::Relaxed) {
+                let start = Instant::now();
+                task::sleep(period).await;
+                let latency = start.elapsed().saturating_sub(period);
+                let nanos = u64::try_from(latency.as_nanos()).unwrap_or(u64::MAX);
+                c_state.max_latency.fetch_max(nanos, Ordering::Relaxed);
+                if latency > threshold {
+                    c_state.stalls.fetch_add(1, Ordering::Relaxed);
+                    log::warn!(
+                        "Executor stalled: a timer of {} ms was woken up {} ms late",
+                        period.as_millis(),
+                        latency.as_millis()
+                    );
+                }
+            }
+        });
+        Self { state }
+    }
+
+    /// The number of wake-ups delayed by more than the threshold.
+    pub fn stalls(&self) -> usize {
+        self.state.stalls.load(Ordering::Relaxed)
+    }
+
+    /// The largest delay of a wake-up so far.
+    pub fn max_latency(&self) -> Duration {
+        Duration::from_nanos(self.state.max_latency.load(Ordering::Relaxed))
+    }
+}
+
+impl Drop for StallWatchdog {
+    fn drop(&mut self) {
+        self.state.stopped.store(true, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cooperate_budget() {
+        let mut cooperate = Cooperate::new(3);
+        let ticks: Vec<bool> = (0..7).map(|_| cooperate.tick()).collect();
+        assert_eq!(ticks, [false, false, true, false, false, true, false]);
+
+        // A suspended loop starts over with the full budget
+        cooperate.reset();
+        assert!(!cooperate.tick());
+        assert!(!cooperate.tick());
+        assert!(cooperate.tick());
+
+        // A zero budget yields at each work unit
+        let mut cooperate = Cooperate::new(0);
+        assert!(cooperate.tick());
+        assert!(cooperate.tick());
+    }
+
+    // Runs a loop of always ready work units next to a concurrent task on a single thread,
+    // recording the order in which they progress
+    fn interleave(units: usize, budget: usize) -> Vec<&'static str> {
+        use std::cell::RefCell;
+
+        let order = RefCell::new(vec![]);
+        let done = RefCell::new(false);
+        let work = async {
+            let mut cooperate = Cooperate::new(budget);
+            for _ in 0..units {
+                order.borrow_mut().push("work");
+                cooperate.step().await;
+            }
+            *done.borrow_mut() = true;
+        };
+        let concurrent = async {
+            while !*done.borrow() {
+                order.borrow_mut().push("concurrent");
+                task::yield_now().await;
+            }
+        };
+        futures::executor::block_on(async { futures::join!(work, concurrent) });
+        order.into_inner()
+    }
+
+    #[test]
+    fn cooperate_interleaving() {
+        // The concurrent task progresses once every budget of work units
+        let order = interleave(10, 4);
+        assert_eq!(
+            order,
+            [
+                "work",
+                "work",
+                "work",
+                "work",
+                "concurrent",
+                "work",
+                "work",
+                "work",
+                "work",
+                "concurrent",
+                "work",
+                "work",
+            ]
+        );
+
+        // A loop processing less work than its budget never yields
+        let order = interleave(3, 4);
+        assert_eq!(order, ["work", "work", "work"]);
+    }
+}
@@ -0,0 +1,386 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! Runtime control of the log filter.
+//!
+//! The filter uses the `RUST_LOG` syntax: a comma-separated list of `target=level`, `target`
+//! or `level` directives (e.g. `zenoh_transport=trace,zenoh=info`).
+//!
+//! The filter is applied by whatever logging backend the embedder installed, through a
+//! [`LogReloadHandle`]:
+//! - [`init_reloadable_logger`] installs a `log` logger wrapping any [`log::Log`] and registers
+//!   its handle, `init_log_from_env` wrapping `env_logger` with the `env_logger` feature;
+//! - embedders using `tracing-subscriber` register their own handle with
+//!   [`set_log_reload_handle`], typically a closure calling `reload::Handle::reload`.
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use zenoh_core::zlock;
+use zenoh_result::{bail, zerror, ZError, ZResult};
+
+/// The environment variable holding the initial log filter.
+pub const LOG_ENV_VAR: &str = "RUST_LOG";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Directive {
+    target: Option<String>,
+    level: LevelFilter,
+}
+
+/// A log filter, as parsed from a `RUST_LOG`-like string.
+///
+/// A record is enabled if its level is at most the level of the directive with the longest target
+/// prefixing the record's target. Records matching no directive are only enabled at the `error` level.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogFilter {
+    // Sorted by increasing target length, so that the most specific directive comes last
+    directives: Vec<Directive>,
+}
+
+impl LogFilter {
+    /// Returns `true` if a record with the given `target` and `level` passes this filter.
+    pub fn enabled(&self, target: &str, level: Level) -> bool {
+        let directive = self.directives.iter().rev().find(|d| match &d.target {
+            Some(t) => target.starts_with(t.as_str()),
+            None => true,
+        });
+        match directive {
+            Some(d) => level <= d.level,
+            None => level <= LevelFilter::Error,
+        }
+    }
+
+    /// Returns the most verbose level enabled by this filter.
+    pub fn max_level(&self) -> LevelFilter {
+        // Without a directive on all the targets, the others are enabled at the `error` level
+        let others = match self.directives.first() {
+            Some(Directive { target: None, .. }) => LevelFilter::Off,
+            _ => LevelFilter::Error,
+        };
+        self.directives
+            .iter()
+            .map(|d| d.level)
+            .fold(others, Ord::max)
+    }
+}
+
+impl FromStr for LogFilter {
+    type Err = ZError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut directives = vec![];
+        for directive in s.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+            let mut parts = directive.split('=');
+            let directive = match (parts.next(), parts.next(), parts.next()) {
+                (Some(part), None, None) => match LevelFilter::from_str(part) {
+                    Ok(level) => Directive {
+                        target: None,
+                        level,
+                    },
+                    Err(_) => Directive {
+                        target: Some(check_target(part)?.to_string()),
+                        level: LevelFilter::Trace,
+                    },
+                },
+                (Some(target), Some(level), None) => Directive {
+                    target: Some(check_target(target)?.to_string()),
+                    level: LevelFilter::from_str(level).map_err(|_| {
+                        zerror!("Invalid log level '{}' in directive '{}'", level, directive)
+                    })?,
+                },
+                _ => bail!("Invalid log directive '{}'", directive),
+            };
+            // A later directive overrides a previous one on the same target
+            directives.retain(|d: &Directive| d.target != directive.target);
+            directives.push(directive);
+        }
+        directives.sort_by_key(|d| d.target.as_ref().map_or(0, |t| t.len()));
+        Ok(LogFilter { directives })
+    }
+}
+
+fn check_target(target: &str) -> Result<&str, ZError> {
+    if target.is_empty()
+        || !target
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == ':' || c == '-')
+    {
+        bail!("Invalid log target '{}'", target);
+    }
+    Ok(target)
+}
+
+impl fmt::Display for LogFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, d) in self.directives.iter().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            let level = d.level.as_str().to_lowercase();
+            match &d.target {
+                Some(target) => write!(f, "{target}={level}")?,
+                None => write!(f, "{level}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A handle applying a new [`LogFilter`] to the installed logging backend.
+pub trait LogReloadHandle: Send + Sync {
+    fn reload(&self, filter: &LogFilter) -> ZResult<()>;
+}
+
+impl<F> LogReloadHandle for F
+where
+    F: Fn(&LogFilter) -> ZResult<()> + Send + Sync,
+{
+    fn reload(&self, filter: &LogFilter) -> ZResult<()> {
+        self(filter)
+    }
+}
+
+struct LogControl {
+    handle: Box<dyn LogReloadHandle>,
+    filter: LogFilter,
+}
+
+lazy_static! {
+    static ref LOG_CONTROL: Mutex<Option<LogControl>> = Mutex::new(None);
+}
+
+static SUPPRESSED: [AtomicU64; 5] = [
+    AtomicU64::new(0),
+    AtomicU64::new(0),
+    AtomicU64::new(0),
+    AtomicU64::new(0),
+    AtomicU64::new(0),
+];
+
+/// Registers the handle used by [`set_log_filter`], along with the filter currently applied.
+///
+/// It replaces any previously registered handle.
+pub fn set_log_reload_handle<H: LogReloadHandle + 'static>(handle: H, filter: LogFilter) {
+    *zlock!(LOG_CONTROL) = Some(LogControl {
+        handle: Box::new(handle),
+        filter,
+    });
+}
+
+/// Returns the current log filter, if a [`LogReloadHandle`] was registered.
+pub fn log_filter() -> Option<LogFilter> {
+    zlock!(LOG_CONTROL).as_ref().map(|c| c.filter.clone())
+}
+
+/// Parses `filter` and applies it through the registered [`LogReloadHandle`].
+///
+/// The current filter is left untouched if `filter` is invalid or if the handle fails.
+pub fn set_log_filter(filter: &str) -> ZResult<LogFilter> {
+    let filter: LogFilter = filter.parse()?;
+    let mut guard = zlock!(LOG_CONTROL);
+    match guard.as_mut() {
+        Some(control) => {
+            control.handle.reload(&filter)?;
+            control.filter = filter.clone();
+            Ok(filter)
+        }
+        None => bail!("No reloadable logger installed"),
+    }
+}
+
+/// The number of records suppressed by the filter, per level.
+///
+/// Only the logger installed by [`init_reloadable_logger`] counts the suppressed records, among
+/// the ones at most as verbose as the [`LogFilter::max_level`]: the more verbose ones are
+/// discarded by the `log` macros before reaching it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SuppressedRecords {
+    pub error: u64,
+    pub warn: u64,
+    pub info: u64,
+    pub debug: u64,
+    pub trace: u64,
+}
+
+/// Returns the number of records suppressed by the filter since the logger was installed.
+pub fn suppressed_records() -> SuppressedRecords {
+    let count = |level: Level| SUPPRESSED[level as usize - 1].load(Ordering::Relaxed);
+    SuppressedRecords {
+        error: count(Level::Error),
+        warn: count(Level::Warn),
+        info: count(Level::Info),
+        debug: count(Level::Debug),
+        trace: count(Level::Trace),
+    }
+}
+
+struct FilterState {
+    filter: RwLock<LogFilter>,
+    // Cached `filter.max_level()` to cheaply discard the most verbose records
+    max_level: AtomicUsize,
+}
+
+impl FilterState {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() as usize <= self.max_level.load(Ordering::Relaxed)
+            && self
+                .filter
+                .read()
+                .unwrap()
+                .enabled(metadata.target(), metadata.level())
+    }
+}
+
+struct FilterHandle(Arc<FilterState>);
+
+impl LogReloadHandle for FilterHandle {
+    fn reload(&self, filter: &LogFilter) -> ZResult<()> {
+        let mut guard = self.0.filter.write().unwrap();
+        *guard = filter.clone();
+        self.0
+            .max_level
+            .store(filter.max_level() as usize, Ordering::Relaxed);
+        log::set_max_level(filter.max_level());
+        Ok(())
+    }
+}
+
+/// A [`log::Log`] applying a reloadable [`LogFilter`] before forwarding the records to an inner logger.
+struct ReloadableLogger {
+    inner: Box<dyn Log>,
+    state: Arc<FilterState>,
+}
+
+impl Log for ReloadableLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.state.enabled(metadata) && self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        if self.state.enabled(record.metadata()) {
+            self.inner.log(record);
+        } else {
+            SUPPRESSED[record.level() as usize - 1].fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+}
+
+/// Installs `inner` as the global logger behind the reloadable `filter`, and registers its
+/// [`LogReloadHandle`].
+///
+/// `inner` should not filter the records itself, as its own filter can't be changed at runtime.
+pub fn init_reloadable_logger(inner: Box<dyn Log>, filter: &str) -> ZResult<()> {
+    let filter: LogFilter = filter.parse()?;
+    let state = Arc::new(FilterState {
+        max_level: AtomicUsize::new(filter.max_level() as usize),
+        filter: RwLock::new(filter.clone()),
+    });
+    log::set_boxed_logger(Box::new(ReloadableLogger {
+        inner,
+        state: state.clone(),
+    }))
+    .map_err(|e| zerror!("Unable to install the reloadable logger: {}", e))?;
+    log::set_max_level(filter.max_level());
+    set_log_reload_handle(FilterHandle(state), filter);
+    Ok(())
+}
+
+/// Installs an `env_logger` behind a reloadable filter initialized from the [`LOG_ENV_VAR`]
+/// environment variable, or from `default` if it's not set.
+#[cfg(feature = "env_logger")]
+pub fn init_log_from_env(default: &str) -> ZResult<()> {
+    init_reloadable_logger(Box::new(env_logger_builder().build()), &env_filter(default))
+}
+
+/// Returns the filter set in the [`LOG_ENV_VAR`] environment variable, or `default` if it's not set.
+pub fn env_filter(default: &str) -> String {
+    std::env::var(LOG_ENV_VAR).unwrap_or_else(|_| default.to_string())
+}
+
+/// Returns an `env_logger` builder letting all the records through, to be wrapped with
+/// [`init_reloadable_logger`].
+#[cfg(feature = "env_logger")]
+pub fn env_logger_builder() -> env_logger::Builder {
+    let mut builder = env_logger::Builder::new();
+    builder.filter_level(LevelFilter::Trace);
+    builder
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn log_filter_parse() {
+        let filter: LogFilter = "zenoh_transport=trace,zenoh=info".parse().unwrap();
+        assert!(filter.enabled("zenoh_transport::unicast", Level::Trace));
+        assert!(filter.enabled("zenoh::session", Level::Info));
+        assert!(!filter.enabled("zenoh::session", Level::Debug));
+        assert!(filter.enabled("async_std", Level::Error));
+        assert!(!filter.enabled("async_std", Level::Warn));
+        assert_eq!(filter.max_level(), LevelFilter::Trace);
+        assert_eq!(filter.to_string(), "zenoh=info,zenoh_transport=trace");
+        assert_eq!(filter.to_string().parse::<LogFilter>().unwrap(), filter);
+
+        let filter: LogFilter = "warn, zenoh_link ,zenoh=off".parse().unwrap();
+        assert!(filter.enabled("async_std", Level::Warn));
+        assert!(filter.enabled("zenoh_link_tcp", Level::Trace));
+        assert!(!filter.enabled("zenoh::net", Level::Error));
+
+        // The targets without directive are enabled at the `error` level
+        let filter: LogFilter = "zenoh=off".parse().unwrap();
+        assert_eq!(filter.max_level(), LevelFilter::Error);
+        let filter: LogFilter = "off,zenoh=warn".parse().unwrap();
+        assert_eq!(filter.max_level(), LevelFilter::Warn);
+        let filter: LogFilter = "off".parse().unwrap();
+        assert_eq!(filter.max_level(), LevelFilter::Off);
+
+        // The last directive on a target wins
+        let filter: LogFilter = "zenoh=info,zenoh=debug".parse().unwrap();
+        assert_eq!(filter.to_string(), "zenoh=debug");
+
+        assert!("zenoh=verbose".parse::<LogFilter>().is_err());
+        assert!("zenoh=info=debug".parse::<LogFilter>().is_err());
+        assert!("=info".parse::<LogFilter>().is_err());
+        assert!("zenoh[span]=info".parse::<LogFilter>().is_err());
+    }
+
+    #[test]
+    fn log_filter_reload() {
+        let applied = Arc::new(Mutex::new(None));
+        let handle = {
+            let applied = applied.clone();
+            move |f: &LogFilter| {
+                *zlock!(applied) = Some(f.to_string());
+                Ok(())
+            }
+        };
+        set_log_reload_handle(handle, "info".parse().unwrap());
+        assert_eq!(log_filter().unwrap().to_string(), "info");
+
+        set_log_filter("zenoh=trace").unwrap();
+        assert_eq!(zlock!(applied).as_deref(), Some("zenoh=trace"));
+        assert_eq!(log_filter().unwrap().to_string(), "zenoh=trace");
+
+        // Invalid filters are rejected and leave the current one untouched
+        assert!(set_log_filter("zenoh=loud").is_err());
+        assert_eq!(log_filter().unwrap().to_string(), "zenoh=trace");
+    }
+}
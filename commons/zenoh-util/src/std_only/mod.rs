@@ -1,8 +1,14 @@
+#[cfg(feature = "test")]
+pub mod alloc_counter;
+pub mod backoff;
 pub mod ffi;
 mod lib_loader;
 pub mod net;
 pub mod time_range;
 pub use lib_loader::*;
+pub mod clock;
+pub mod cooperate;
+pub mod logging;
 pub mod timer;
 pub use timer::*;
 /// The "ZENOH_HOME" environement variable name
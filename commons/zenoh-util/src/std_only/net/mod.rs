@@ -487,3 +487,43 @@ pub fn get_ipv6_ipaddrs() -> Vec<IpAddr> {
         .chain(priv_ipv4_addrs)
         .collect()
 }
+
+/// Whether a network interface is named `name`.
+pub fn is_interface(name: &str) -> bool {
+    #[cfg(unix)]
+    {
+        pnet_datalink::interfaces()
+            .iter()
+            .any(|iface| iface.name == name)
+    }
+
+    #[cfg(windows)]
+    {
+        get_unicast_addresses_of_interface(name).map_or(false, |addrs| !addrs.is_empty())
+    }
+}
+
+/// The addresses of the network interface `name` a listener can be bound to: its unicast
+/// addresses but the IPv6 link-local ones, which can't be bound without their scope.
+///
+/// Fails if the interface doesn't exist, isn't up, or has no such address.
+pub fn get_listen_addresses_of_interface(name: &str) -> ZResult<Vec<IpAddr>> {
+    const fn is_unicast_link_local(addr: &Ipv6Addr) -> bool {
+        (addr.segments()[0] & 0xffc0) == 0xfe80
+    }
+
+    if !is_interface(name) {
+        bail!("Interface {name} not found");
+    }
+    let addrs = get_unicast_addresses_of_interface(name)?
+        .into_iter()
+        .filter(|addr| match addr {
+            IpAddr::V4(_) => true,
+            IpAddr::V6(addr) => !is_unicast_link_local(addr),
+        })
+        .collect::<Vec<IpAddr>>();
+    if addrs.is_empty() {
+        bail!("Interface {name} has no address to listen on");
+    }
+    Ok(addrs)
+}
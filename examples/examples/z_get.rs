@@ -15,6 +15,7 @@ use clap::{App, Arg};
 use std::convert::TryFrom;
 use std::time::Duration;
 use zenoh::config::Config;
+use zenoh::prelude::json;
 use zenoh::prelude::r#async::*;
 
 #[async_std::main]
@@ -22,12 +23,16 @@ async fn main() {
     // initiate logging
     env_logger::init();
 
-    let (config, selector, value, target, timeout) = parse_args();
+    let (config, selector, value, target, timeout, json) = parse_args();
 
-    println!("Opening session...");
+    if !json {
+        println!("Opening session...");
+    }
     let session = zenoh::open(config).res().await.unwrap();
 
-    println!("Sending Query '{selector}'...");
+    if !json {
+        println!("Sending Query '{selector}'...");
+    }
     let replies = match value {
         Some(value) => session.get(&selector).with_value(value),
         None => session.get(&selector),
@@ -38,6 +43,13 @@ async fn main() {
     .await
     .unwrap();
     while let Ok(reply) = replies.recv_async().await {
+        if json {
+            println!(
+                "{}",
+                serde_json::to_string(&json::Reply::from(&reply)).unwrap()
+            );
+            continue;
+        }
         match reply.sample {
             Ok(sample) => println!(
                 ">> Received ('{}': '{}')",
@@ -49,7 +61,7 @@ async fn main() {
     }
 }
 
-fn parse_args() -> (Config, String, Option<String>, QueryTarget, Duration) {
+fn parse_args() -> (Config, String, Option<String>, QueryTarget, Duration, bool) {
     let args = App::new("zenoh query example")
         .arg(
             Arg::from_usage("-m, --mode=[MODE]  'The zenoh session mode (peer by default).")
@@ -83,6 +95,9 @@ fn parse_args() -> (Config, String, Option<String>, QueryTarget, Duration) {
         .arg(Arg::from_usage(
             "--no-multicast-scouting 'Disable the multicast-based scouting mechanism.'",
         ))
+        .arg(Arg::from_usage(
+            "--json 'Print one JSON object per line instead of human-readable lines.'",
+        ))
         .get_matches();
 
     let mut config = if let Some(conf_file) = args.value_of("config") {
@@ -116,5 +131,7 @@ fn parse_args() -> (Config, String, Option<String>, QueryTarget, Duration) {
 
     let timeout = Duration::from_millis(args.value_of("timeout").unwrap().parse::<u64>().unwrap());
 
-    (config, selector, value, target, timeout)
+    let json = args.is_present("json");
+
+    (config, selector, value, target, timeout, json)
 }
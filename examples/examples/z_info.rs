@@ -13,6 +13,7 @@
 //
 use clap::{App, Arg};
 use zenoh::config::Config;
+use zenoh::prelude::json;
 use zenoh::prelude::r#async::*;
 
 #[async_std::main]
@@ -20,12 +21,25 @@ async fn main() {
     // initiate logging
     env_logger::init();
 
-    let config = parse_args();
+    let (config, json) = parse_args();
 
-    println!("Opening session...");
+    if !json {
+        println!("Opening session...");
+    }
     let session = zenoh::open(config).res().await.unwrap();
 
     let info = session.info();
+    if json {
+        // One line per router or peer this session is connected to
+        let transports = info.routers().res().await.into_iter();
+        for transport in transports.chain(info.peers().res().await) {
+            println!(
+                "{}",
+                serde_json::to_string(&json::TransportInfo::from(&transport)).unwrap()
+            );
+        }
+        return;
+    }
     println!("zid: {}", info.zid().res().await);
     println!(
         "routers zid: {:?}",
@@ -35,9 +49,21 @@ async fn main() {
         "peers zid: {:?}",
         info.peers_zid().res().await.collect::<Vec<ZenohId>>()
     );
+    println!("routers:");
+    for router in info.routers().res().await {
+        println!(
+            "  {} ({}) via {:?}",
+            router.zid, router.whatami, router.locators
+        );
+    }
+    println!("peers:");
+    for peer in info.peers().res().await {
+        println!("  {} ({}) via {:?}", peer.zid, peer.whatami, peer.locators);
+    }
+    println!("locators: {:?}", info.locators().res().await);
 }
 
-fn parse_args() -> Config {
+fn parse_args() -> (Config, bool) {
     let args = App::new("zenoh info example")
         .arg(
             Arg::from_usage("-m, --mode=[MODE] 'The zenoh session mode (peer by default).")
@@ -55,6 +81,9 @@ fn parse_args() -> Config {
         .arg(Arg::from_usage(
             "--no-multicast-scouting 'Disable the multicast-based scouting mechanism.'",
         ))
+        .arg(Arg::from_usage(
+            "--json 'Print one JSON object per line instead of human-readable lines.'",
+        ))
         .get_matches();
 
     let mut config = if let Some(conf_file) = args.value_of("config") {
@@ -75,5 +104,7 @@ fn parse_args() -> Config {
         config.scouting.multicast.set_enabled(Some(false)).unwrap();
     }
 
-    config
+    let json = args.is_present("json");
+
+    (config, json)
 }
@@ -17,6 +17,7 @@ use clap::{App, Arg};
 use futures::prelude::*;
 use std::time::Duration;
 use zenoh::config::Config;
+use zenoh::prelude::json;
 use zenoh::prelude::r#async::*;
 
 #[async_std::main]
@@ -24,12 +25,16 @@ async fn main() {
     // initiate logging
     env_logger::init();
 
-    let (config, key_expr) = parse_args();
+    let (config, key_expr, json) = parse_args();
 
-    println!("Opening session...");
+    if !json {
+        println!("Opening session...");
+    }
     let session = zenoh::open(config).res().await.unwrap();
 
-    println!("Declaring Subscriber on '{key_expr}'...");
+    if !json {
+        println!("Declaring Subscriber on '{key_expr}'...");
+    }
 
     let subscriber = session
         .declare_subscriber(&key_expr)
@@ -38,17 +43,26 @@ async fn main() {
         .await
         .unwrap();
 
-    println!("Press <enter> to pull data...");
+    if !json {
+        println!("Press <enter> to pull data...");
+    }
 
     // Define the future to handle incoming samples of the subscription.
     let subs = async {
         while let Ok(sample) = subscriber.recv_async().await {
-            println!(
-                ">> [Subscriber] Received {} ('{}': '{}')",
-                sample.kind,
-                sample.key_expr.as_str(),
-                sample.value,
-            );
+            if json {
+                println!(
+                    "{}",
+                    serde_json::to_string(&json::Sample::from(&sample)).unwrap()
+                );
+            } else {
+                println!(
+                    ">> [Subscriber] Received {} ('{}': '{}')",
+                    sample.kind,
+                    sample.key_expr.as_str(),
+                    sample.value,
+                );
+            }
         }
     };
 
@@ -70,7 +84,7 @@ async fn main() {
     subs.race(keyb).await;
 }
 
-fn parse_args() -> (Config, String) {
+fn parse_args() -> (Config, String, bool) {
     let args = App::new("zenoh pull example")
         .arg(
             Arg::from_usage("-m, --mode=[MODE]  'The zenoh session mode (peer by default).")
@@ -92,6 +106,9 @@ fn parse_args() -> (Config, String) {
         .arg(Arg::from_usage(
             "--no-multicast-scouting 'Disable the multicast-based scouting mechanism.'",
         ))
+        .arg(Arg::from_usage(
+            "--json 'Print one JSON object per line instead of human-readable lines.'",
+        ))
         .get_matches();
 
     let mut config = if let Some(conf_file) = args.value_of("config") {
@@ -114,5 +131,7 @@ fn parse_args() -> (Config, String) {
 
     let key_expr = args.value_of("key").unwrap().to_string();
 
-    (config, key_expr)
+    let json = args.is_present("json");
+
+    (config, key_expr, json)
 }
@@ -12,7 +12,9 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 use async_std::prelude::FutureExt;
+use clap::{App, Arg};
 use zenoh::config::Config;
+use zenoh::prelude::json;
 use zenoh::prelude::r#async::*;
 use zenoh::scouting::WhatAmI;
 
@@ -21,7 +23,11 @@ async fn main() {
     // initiate logging
     env_logger::init();
 
-    println!("Scouting...");
+    let json = parse_args();
+
+    if !json {
+        println!("Scouting...");
+    }
     let receiver = zenoh::scout(WhatAmI::Peer | WhatAmI::Router, Config::default())
         .res()
         .await
@@ -29,7 +35,14 @@ async fn main() {
 
     let _ = async {
         while let Ok(hello) = receiver.recv_async().await {
-            println!("{hello}");
+            if json {
+                println!(
+                    "{}",
+                    serde_json::to_string(&json::Hello::from(&hello)).unwrap()
+                );
+            } else {
+                println!("{hello}");
+            }
         }
     }
     .timeout(std::time::Duration::from_secs(1))
@@ -38,3 +51,13 @@ async fn main() {
     // stop scouting
     drop(receiver);
 }
+
+fn parse_args() -> bool {
+    let args = App::new("zenoh scout example")
+        .arg(Arg::from_usage(
+            "--json 'Print one JSON object per line instead of human-readable lines.'",
+        ))
+        .get_matches();
+
+    args.is_present("json")
+}
@@ -18,6 +18,7 @@ use futures::select;
 use std::convert::TryFrom;
 use std::time::Duration;
 use zenoh::config::Config;
+use zenoh::prelude::json;
 use zenoh::prelude::r#async::*;
 
 #[async_std::main]
@@ -25,24 +26,34 @@ async fn main() {
     // Initiate logging
     env_logger::init();
 
-    let (config, key_expr) = parse_args();
+    let (config, key_expr, json) = parse_args();
 
-    println!("Opening session...");
+    if !json {
+        println!("Opening session...");
+    }
     let session = zenoh::open(config).res().await.unwrap();
 
-    println!("Declaring Subscriber on '{}'...", &key_expr);
+    if !json {
+        println!("Declaring Subscriber on '{}'...", &key_expr);
+    }
 
     let subscriber = session.declare_subscriber(&key_expr).res().await.unwrap();
 
-    println!("Enter 'q' to quit...");
+    if !json {
+        println!("Enter 'q' to quit...");
+    }
     let mut stdin = async_std::io::stdin();
     let mut input = [0_u8];
     loop {
         select!(
             sample = subscriber.recv_async() => {
                 let sample = sample.unwrap();
-                println!(">> [Subscriber] Received {} ('{}': '{}')",
-                    sample.kind, sample.key_expr.as_str(), sample.value);
+                if json {
+                    println!("{}", serde_json::to_string(&json::Sample::from(&sample)).unwrap());
+                } else {
+                    println!(">> [Subscriber] Received {} ('{}': '{}')",
+                        sample.kind, sample.key_expr.as_str(), sample.value);
+                }
             },
 
             _ = stdin.read_exact(&mut input).fuse() => {
@@ -56,7 +67,7 @@ async fn main() {
     }
 }
 
-fn parse_args() -> (Config, KeyExpr<'static>) {
+fn parse_args() -> (Config, KeyExpr<'static>, bool) {
     let args = App::new("zenoh sub example")
         .arg(
             Arg::from_usage("-m, --mode=[MODE]  'The zenoh session mode (peer by default).")
@@ -78,6 +89,9 @@ fn parse_args() -> (Config, KeyExpr<'static>) {
         .arg(Arg::from_usage(
             "--no-multicast-scouting 'Disable the multicast-based scouting mechanism.'",
         ))
+        .arg(Arg::from_usage(
+            "--json 'Print one JSON object per line instead of human-readable lines.'",
+        ))
         .get_matches();
 
     let mut config = if let Some(conf_file) = args.value_of("config") {
@@ -102,5 +116,7 @@ fn parse_args() -> (Config, KeyExpr<'static>) {
         .unwrap()
         .into_owned();
 
-    (config, key_expr)
+    let json = args.is_present("json");
+
+    (config, key_expr, json)
 }
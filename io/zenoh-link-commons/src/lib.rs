@@ -23,14 +23,14 @@ extern crate alloc;
 mod multicast;
 mod unicast;
 
-use alloc::{borrow::ToOwned, boxed::Box, string::String};
+use alloc::{borrow::ToOwned, boxed::Box, string::String, vec::Vec};
 use async_trait::async_trait;
 use core::{cmp::PartialEq, fmt, hash::Hash};
 pub use multicast::*;
 use serde::Serialize;
 pub use unicast::*;
-use zenoh_protocol::core::Locator;
-use zenoh_result::ZResult;
+use zenoh_protocol::core::{endpoint::Parameters, Locator};
+use zenoh_result::{bail, ZResult};
 
 /*************************************/
 /*            GENERAL                */
@@ -99,3 +99,61 @@ impl From<LinkMulticast> for Link {
         Link::from(&link)
     }
 }
+
+/*************************************/
+/*              CONFIG               */
+/*************************************/
+/// The prefix of the endpoint configuration keys passed through to the link without being
+/// checked, e.g. for the keys of an experimental protocol.
+pub const CONFIG_KEY_PASSTHROUGH_PREFIX: &str = "x-";
+
+/// Checks that all the keys of an endpoint configuration are supported by its protocol,
+/// the keys prefixed with [`CONFIG_KEY_PASSTHROUGH_PREFIX`] being always accepted.
+pub fn validate_config_keys(config: &str, supported: &[&str]) -> ZResult<()> {
+    for (key, _) in Parameters::iter(config) {
+        if key.starts_with(CONFIG_KEY_PASSTHROUGH_PREFIX) || supported.contains(&key) {
+            continue;
+        }
+        let closest = supported
+            .iter()
+            .map(|k| (edit_distance(key, k), k))
+            .filter(|(d, _)| *d <= key.len().max(3) / 3)
+            .min_by_key(|(d, _)| *d);
+        match closest {
+            Some((_, k)) => bail!(
+                "unsupported configuration key `{}`, did you mean `{}`? Supported keys are: {:?}",
+                key,
+                k,
+                supported
+            ),
+            None => bail!(
+                "unsupported configuration key `{}`. Supported keys are: {:?}. \
+                Prefix the key with `{}` to pass it through without checking it",
+                key,
+                supported,
+                CONFIG_KEY_PASSTHROUGH_PREFIX
+            ),
+        }
+    }
+    Ok(())
+}
+
+// The Levenshtein distance between two strings
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut prev = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let cur = row[j + 1];
+            row[j + 1] = if ca == *cb {
+                prev
+            } else {
+                1 + prev.min(row[j]).min(cur)
+            };
+            prev = cur;
+        }
+    }
+    row[b.len()]
+}
@@ -32,6 +32,8 @@ use zenoh_result::{zerror, ZResult};
 #[async_trait]
 pub trait LinkManagerMulticastTrait: Send + Sync {
     async fn new_link(&self, endpoint: &EndPoint) -> ZResult<LinkMulticast>;
+    /// The keys accepted in the configuration of this protocol's endpoints.
+    fn supported_config_keys(&self) -> &'static [&'static str];
 }
 
 pub type LinkManagerMulticast = Arc<dyn LinkManagerMulticastTrait>;
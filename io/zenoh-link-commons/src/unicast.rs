@@ -38,6 +38,8 @@ pub trait LinkManagerUnicastTrait: Send + Sync {
     async fn del_listener(&self, endpoint: &EndPoint) -> ZResult<()>;
     fn get_listeners(&self) -> Vec<EndPoint>;
     fn get_locators(&self) -> Vec<Locator>;
+    /// The keys accepted in the configuration of this protocol's endpoints.
+    fn supported_config_keys(&self) -> &'static [&'static str];
 }
 pub type NewLinkChannelSender = flume::Sender<LinkUnicast>;
 pub trait ConstructibleLinkManagerUnicast<T>: Sized {
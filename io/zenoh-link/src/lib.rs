@@ -20,7 +20,7 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 use zenoh_config::Config;
-use zenoh_result::{bail, ZResult};
+use zenoh_result::{zerror, ZResult};
 
 #[cfg(feature = "transport_tcp")]
 pub use zenoh_link_tcp as tcp;
@@ -95,6 +95,32 @@ pub const PROTOCOLS: &[&str] = &[
     unixpipe::UNIXPIPE_LOCATOR_PREFIX,
 ];
 
+/// All the protocols supported by zenoh, with the feature compiling each of them in.
+pub const PROTOCOL_FEATURES: &[(&str, &str)] = &[
+    ("quic", "transport_quic"),
+    ("tcp", "transport_tcp"),
+    ("tls", "transport_tls"),
+    ("udp", "transport_udp"),
+    ("ws", "transport_ws"),
+    ("unixsock-stream", "transport_unixsock-stream"),
+    ("serial", "transport_serial"),
+    ("unixpipe", "transport_unixpipe"),
+];
+
+/// The error for a `protocol` not part of [`PROTOCOLS`], naming the feature that compiles it in
+/// if there is any.
+fn unsupported(protocol: &str, what: &str) -> zenoh_result::Error {
+    match PROTOCOL_FEATURES.iter().find(|(p, _)| *p == protocol) {
+        Some((_, feature)) if !PROTOCOLS.contains(&protocol) => zerror!(
+            "Protocol {} not compiled in: enable the `{}` feature",
+            protocol,
+            feature
+        )
+        .into(),
+        _ => zerror!("{} not supported for {} protocol", what, protocol).into(),
+    }
+}
+
 #[derive(Default, Clone)]
 pub struct LocatorInspector {
     #[cfg(feature = "transport_quic")]
@@ -138,7 +164,7 @@ impl LocatorInspector {
             SERIAL_LOCATOR_PREFIX => self.serial_inspector.is_multicast(locator).await,
             #[cfg(feature = "transport_unixpipe")]
             UNIXPIPE_LOCATOR_PREFIX => self.unixpipe_inspector.is_multicast(locator).await,
-            _ => bail!("Unsupported protocol: {}.", protocol),
+            _ => Err(unsupported(protocol.as_str(), "Inspection")),
         }
     }
 }
@@ -223,7 +249,7 @@ impl LinkManagerBuilderUnicast {
             SERIAL_LOCATOR_PREFIX => Ok(Arc::new(LinkManagerUnicastSerial::new(_manager))),
             #[cfg(feature = "transport_unixpipe")]
             UNIXPIPE_LOCATOR_PREFIX => Ok(Arc::new(LinkManagerUnicastPipe::new(_manager))),
-            _ => bail!("Unicast not supported for {} protocol", protocol),
+            _ => Err(unsupported(protocol, "Unicast")),
         }
     }
 }
@@ -239,7 +265,30 @@ impl LinkManagerBuilderMulticast {
         match protocol {
             #[cfg(feature = "transport_udp")]
             UDP_LOCATOR_PREFIX => Ok(Arc::new(LinkManagerMulticastUdp)),
-            _ => bail!("Multicast not supported for {} protocol", protocol),
+            _ => Err(unsupported(protocol, "Multicast")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn protocols_not_compiled_in() {
+        for (protocol, feature) in PROTOCOL_FEATURES {
+            let e = unsupported(protocol, "Unicast").to_string();
+            if PROTOCOLS.contains(protocol) {
+                assert!(!e.contains("not compiled in"), "{e}");
+            } else {
+                assert!(e.contains(feature), "{e}");
+            }
         }
+        assert!(PROTOCOLS
+            .iter()
+            .all(|p| PROTOCOL_FEATURES.iter().any(|(q, _)| p == q)));
+        assert!(!unsupported("foo", "Unicast")
+            .to_string()
+            .contains("not compiled in"));
     }
 }
@@ -488,6 +488,18 @@ impl LinkManagerUnicastTrait for LinkManagerUnicastQuic {
 
         locators
     }
+
+    fn supported_config_keys(&self) -> &'static [&'static str] {
+        &[
+            TLS_ROOT_CA_CERTIFICATE_FILE,
+            TLS_ROOT_CA_CERTIFICATE_RAW,
+            TLS_SERVER_PRIVATE_KEY_FILE,
+            TLS_SERVER_PRIVATE_KEY_RAW,
+            TLS_SERVER_CERTIFICATE_FILE,
+            TLS_SERVER_CERTIFICATE_RAW,
+            TLS_SERVER_NAME_VERIFICATION,
+        ]
+    }
 }
 
 async fn accept_task(
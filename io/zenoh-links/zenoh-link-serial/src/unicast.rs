@@ -371,6 +371,13 @@ impl LinkManagerUnicastTrait for LinkManagerUnicastSerial {
             .map(|x| x.endpoint.to_locator())
             .collect()
     }
+
+    fn supported_config_keys(&self) -> &'static [&'static str] {
+        &[
+            crate::config::PORT_BAUD_RATE_RAW,
+            crate::config::PORT_EXCLUSIVE_RAW,
+        ]
+    }
 }
 
 async fn accept_read_task(
@@ -410,6 +410,10 @@ impl LinkManagerUnicastTrait for LinkManagerUnicastTcp {
 
         locators
     }
+
+    fn supported_config_keys(&self) -> &'static [&'static str] {
+        &[]
+    }
 }
 
 async fn accept_task(
@@ -437,6 +437,23 @@ impl LinkManagerUnicastTrait for LinkManagerUnicastTls {
 
         locators
     }
+
+    fn supported_config_keys(&self) -> &'static [&'static str] {
+        &[
+            TLS_ROOT_CA_CERTIFICATE_FILE,
+            TLS_ROOT_CA_CERTIFICATE_RAW,
+            TLS_SERVER_PRIVATE_KEY_FILE,
+            TLS_SERVER_PRIVATE_KEY_RAW,
+            TLS_SERVER_CERTIFICATE_FILE,
+            TLS_SERVER_CERTIFICATE_RAW,
+            TLS_CLIENT_PRIVATE_KEY_FILE,
+            TLS_CLIENT_PRIVATE_KEY_RAW,
+            TLS_CLIENT_CERTIFICATE_FILE,
+            TLS_CLIENT_CERTIFICATE_RAW,
+            TLS_CLIENT_AUTH,
+            TLS_SERVER_NAME_VERIFICATION,
+        ]
+    }
 }
 
 async fn accept_task(
@@ -516,12 +533,7 @@ struct TlsServerConfig {
 
 impl TlsServerConfig {
     pub async fn new(config: &Config<'_>) -> ZResult<TlsServerConfig> {
-        let tls_server_client_auth: bool = match config.get(TLS_CLIENT_AUTH) {
-            Some(s) => s
-                .parse()
-                .map_err(|_| zerror!("Unknown client auth argument: {}", s))?,
-            None => false,
-        };
+        let tls_server_client_auth = config.get_bool(TLS_CLIENT_AUTH)?.unwrap_or(false);
         let tls_server_private_key = TlsServerConfig::load_tls_private_key(config).await?;
         let tls_server_certificate = TlsServerConfig::load_tls_certificate(config).await?;
 
@@ -603,25 +615,14 @@ struct TlsClientConfig {
 
 impl TlsClientConfig {
     pub async fn new(config: &Config<'_>) -> ZResult<TlsClientConfig> {
-        let tls_client_server_auth: bool = match config.get(TLS_CLIENT_AUTH) {
-            Some(s) => s
-                .parse()
-                .map_err(|_| zerror!("Unknown client auth argument: {}", s))?,
-            None => false,
-        };
+        let tls_client_server_auth = config.get_bool(TLS_CLIENT_AUTH)?.unwrap_or(false);
 
-        let tls_server_name_verification: bool = match config.get(TLS_SERVER_NAME_VERIFICATION) {
-            Some(s) => {
-                let s: bool = s
-                    .parse()
-                    .map_err(|_| zerror!("Unknown server name verification argument: {}", s))?;
-                if s {
-                    log::warn!("Skipping name verification of servers");
-                }
-                s
-            }
-            None => false,
-        };
+        let tls_server_name_verification = config
+            .get_bool(TLS_SERVER_NAME_VERIFICATION)?
+            .unwrap_or(false);
+        if tls_server_name_verification {
+            log::warn!("Skipping name verification of servers");
+        }
 
         // Allows mixed user-generated CA and webPKI CA
         log::debug!("Loading default Web PKI certificates.");
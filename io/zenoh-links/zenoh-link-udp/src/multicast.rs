@@ -254,7 +254,6 @@ impl LinkManagerMulticastUdp {
             .map_err(|e| zerror!("{}: {}", mcast_addr, e))?;
 
         // Join the multicast group
-        let join = config.values(UDP_MULTICAST_JOIN);
         match mcast_addr.ip() {
             IpAddr::V4(dst_ip4) => match local_addr {
                 IpAddr::V4(src_ip4) => {
@@ -263,9 +262,7 @@ impl LinkManagerMulticastUdp {
                         .join_multicast_v4(&dst_ip4, &src_ip4)
                         .map_err(|e| zerror!("{}: {}", mcast_addr, e))?;
                     // Join any additional multicast group
-                    for g in join {
-                        let g: Ipv4Addr =
-                            g.parse().map_err(|e| zerror!("{}: {}", mcast_addr, e))?;
+                    for g in config.get_list::<Ipv4Addr>(UDP_MULTICAST_JOIN)? {
                         mcast_sock
                             .join_multicast_v4(&g, &src_ip4)
                             .map_err(|e| zerror!("{}: {}", mcast_addr, e))?;
@@ -279,8 +276,7 @@ impl LinkManagerMulticastUdp {
                     .join_multicast_v6(&dst_ip6, 0)
                     .map_err(|e| zerror!("{}: {}", mcast_addr, e))?;
                 // Join any additional multicast group
-                for g in join {
-                    let g: Ipv6Addr = g.parse().map_err(|e| zerror!("{}: {}", mcast_addr, e))?;
+                for g in config.get_list::<Ipv6Addr>(UDP_MULTICAST_JOIN)? {
                     mcast_sock
                         .join_multicast_v6(&g, 0)
                         .map_err(|e| zerror!("{}: {}", mcast_addr, e))?;
@@ -334,4 +330,8 @@ impl LinkManagerMulticastTrait for LinkManagerMulticastUdp {
             errs
         )
     }
+
+    fn supported_config_keys(&self) -> &'static [&'static str] {
+        &[UDP_MULTICAST_IFACE, UDP_MULTICAST_JOIN]
+    }
 }
@@ -514,6 +514,13 @@ impl LinkManagerUnicastTrait for LinkManagerUnicastUdp {
 
         locators
     }
+
+    fn supported_config_keys(&self) -> &'static [&'static str] {
+        &[
+            crate::config::UDP_MULTICAST_IFACE,
+            crate::config::UDP_MULTICAST_JOIN,
+        ]
+    }
 }
 
 async fn accept_read_task(
@@ -569,6 +569,10 @@ impl LinkManagerUnicastTrait for LinkManagerUnicastPipe {
             .map(|v| v.uplink_locator.clone())
             .collect()
     }
+
+    fn supported_config_keys(&self) -> &'static [&'static str] {
+        &[config::FILE_ACCESS_MASK]
+    }
 }
 
 fn parse_pipe_endpoint(endpoint: &EndPoint) -> (String, String, u32) {
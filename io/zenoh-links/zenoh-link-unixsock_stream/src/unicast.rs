@@ -426,6 +426,10 @@ impl LinkManagerUnicastTrait for LinkManagerUnicastUnixSocketStream {
             .map(|x| x.endpoint.to_locator())
             .collect()
     }
+
+    fn supported_config_keys(&self) -> &'static [&'static str] {
+        &[]
+    }
 }
 
 async fn accept_task(
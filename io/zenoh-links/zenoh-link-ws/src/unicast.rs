@@ -450,6 +450,10 @@ impl LinkManagerUnicastTrait for LinkManagerUnicastWs {
 
         locators
     }
+
+    fn supported_config_keys(&self) -> &'static [&'static str] {
+        &[]
+    }
 }
 
 async fn accept_task(
@@ -0,0 +1,123 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+//! Two processes exchanging raw payloads over the zenoh transport:
+//!
+//!     cargo run --example mesh -- -l tcp/127.0.0.1:7447
+//!     cargo run --example mesh -- -e tcp/127.0.0.1:7447
+//!
+//! Each process sends a payload every second to all the nodes it has a
+//! transport with, and prints the payloads it receives.
+use async_std::task;
+use clap::{App, Arg};
+use std::any::Any;
+use std::sync::Arc;
+use std::time::Duration;
+use zenoh_mesh::*;
+
+struct Handler;
+
+impl TransportEventHandler for Handler {
+    fn new_unicast(
+        &self,
+        peer: TransportPeer,
+        _transport: TransportUnicast,
+    ) -> ZResult<Arc<dyn TransportPeerEventHandler>> {
+        println!("Transport opened with {}", peer.zid);
+        Ok(Arc::new(PeerHandler { zid: peer.zid }))
+    }
+
+    fn new_multicast(
+        &self,
+        _transport: TransportMulticast,
+    ) -> ZResult<Arc<dyn TransportMulticastEventHandler>> {
+        Ok(Arc::new(DummyTransportMulticastEventHandler))
+    }
+}
+
+struct PeerHandler {
+    zid: ZenohId,
+}
+
+impl TransportPeerEventHandler for PeerHandler {
+    fn handle_message(&self, msg: NetworkMessage) -> ZResult<()> {
+        if let Some(payload) = payload(&msg) {
+            println!(
+                ">> [{}] {}",
+                self.zid,
+                String::from_utf8_lossy(&payload.contiguous())
+            );
+        }
+        Ok(())
+    }
+
+    fn new_link(&self, _link: Link) {}
+    fn del_link(&self, _link: Link) {}
+    fn closing(&self) {}
+
+    fn closed(&self) {
+        println!("Transport closed with {}", self.zid);
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[async_std::main]
+async fn main() {
+    // initiate logging
+    env_logger::init();
+
+    let (listen, connect) = parse_args();
+
+    let manager = TransportManager::builder()
+        .whatami(WhatAmI::Peer)
+        .build(Arc::new(Handler))
+        .unwrap();
+    println!("Node {}", manager.zid());
+    for endpoint in listen {
+        for locator in manager.add_listener(endpoint).await.unwrap() {
+            println!("Listening on {locator}");
+        }
+    }
+    for endpoint in connect {
+        manager.open_transport_unicast(endpoint).await.unwrap();
+    }
+
+    for idx in 0u32.. {
+        task::sleep(Duration::from_secs(1)).await;
+        let payload = format!("[{idx:4}] Hello from {}", manager.zid());
+        for transport in manager.get_transports_unicast().await {
+            let _ = transport.schedule(message(payload.clone().into_bytes()));
+        }
+    }
+}
+
+fn parse_args() -> (Vec<EndPoint>, Vec<EndPoint>) {
+    let args = App::new("zenoh mesh example")
+        .arg(Arg::from_usage(
+            "-e, --connect=[ENDPOINT]...   'Endpoints to connect to.'",
+        ))
+        .arg(Arg::from_usage(
+            "-l, --listen=[ENDPOINT]...   'Endpoints to listen on.'",
+        ))
+        .get_matches();
+
+    let endpoints = |name| {
+        args.values_of(name)
+            .map(|values| values.map(|v| v.parse().unwrap()).collect())
+            .unwrap_or_default()
+    };
+    (endpoints("listen"), endpoints("connect"))
+}
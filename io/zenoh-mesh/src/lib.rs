@@ -0,0 +1,105 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+//! Mesh messaging on top of the zenoh transport.
+//!
+//! This crate exposes the subset of `zenoh-transport` needed to exchange
+//! messages between nodes addressed by their [`ZenohId`], over one or more
+//! (optionally authenticated) links, without the routing, key expressions
+//! and sessions of `zenoh`. Only the link protocols selected with the
+//! `transport_*` features are compiled, `transport_tcp` being the default.
+//!
+//! Raw payloads are wrapped into messages with [`message`] and extracted from
+//! the received ones with [`payload`].
+use zenoh_protocol::{
+    core::{CongestionControl, Encoding, Priority, WireExpr},
+    network::{
+        push::ext::{NodeIdType, QoSType},
+        NetworkBody, Push,
+    },
+    zenoh::{PushBody, Put},
+};
+
+pub use zenoh_buffers::{SplitBuffer, ZBuf};
+pub use zenoh_link::Link;
+pub use zenoh_protocol::core::{EndPoint, Locator, Reliability, WhatAmI, ZenohId};
+pub use zenoh_protocol::network::NetworkMessage;
+pub use zenoh_result::{Error, ZResult};
+pub use zenoh_transport::{
+    DummyTransportEventHandler, DummyTransportMulticastEventHandler,
+    DummyTransportPeerEventHandler, TransportEventHandler, TransportManager,
+    TransportManagerBuilder, TransportManagerBuilderUnicast, TransportMulticast,
+    TransportMulticastEventHandler, TransportPeer, TransportPeerEventHandler, TransportUnicast,
+};
+
+/// The reasons given when closing a transport or one of its links.
+pub mod close {
+    pub use zenoh_protocol::transport::close::{reason, reason_to_str};
+}
+
+/// The authenticators checked when establishing a transport.
+#[cfg(feature = "transport_auth")]
+pub mod auth {
+    pub use zenoh_transport::unicast::establishment::ext::auth::Auth;
+    #[cfg(feature = "auth_pubkey")]
+    pub use zenoh_transport::unicast::establishment::ext::auth::AuthPubKey;
+    #[cfg(feature = "auth_usrpwd")]
+    pub use zenoh_transport::unicast::establishment::ext::auth::AuthUsrPwd;
+}
+
+/// Wraps a raw payload into a message to be scheduled on a [`TransportUnicast`].
+pub fn message<T: Into<ZBuf>>(payload: T) -> NetworkMessage {
+    Push {
+        wire_expr: WireExpr::empty(),
+        ext_qos: QoSType::new(Priority::default(), CongestionControl::Block, false),
+        ext_tstamp: None,
+        ext_nodeid: NodeIdType::default(),
+        payload: Put {
+            payload: payload.into(),
+            timestamp: None,
+            encoding: Encoding::default(),
+            ext_sinfo: None,
+            #[cfg(feature = "shared-memory")]
+            ext_shm: None,
+            ext_integrity: None,
+            ext_batch: None,
+            ext_unknown: vec![],
+        }
+        .into(),
+    }
+    .into()
+}
+
+/// Returns the raw payload carried by a message received from a [`TransportPeerEventHandler`],
+/// or `None` if it is not a message built with [`message`].
+pub fn payload(message: &NetworkMessage) -> Option<&ZBuf> {
+    match &message.body {
+        NetworkBody::Push(Push {
+            payload: PushBody::Put(put),
+            ..
+        }) => Some(&put.payload),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn payload_roundtrip() {
+        let msg = message(vec![1u8, 2, 3]);
+        assert_eq!(msg.reliability, Reliability::Reliable);
+        assert_eq!(payload(&msg).unwrap().contiguous().as_ref(), &[1u8, 2, 3]);
+    }
+}
@@ -0,0 +1,78 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use std::collections::HashSet;
+use std::process::Command;
+
+// The number of crates the minimal feature set may pull in (for all the targets),
+// to be raised deliberately when a new dependency is worth it.
+const MAX_DEPENDENCIES: usize = 250;
+
+// Crates that must only be compiled when explicitly selected.
+const FORBIDDEN: [&str; 14] = [
+    "zenoh",
+    "zenoh-ext",
+    "zenoh-shm",
+    "zenoh-link-quic",
+    "zenoh-link-serial",
+    "zenoh-link-tls",
+    "zenoh-link-udp",
+    "zenoh-link-unixpipe",
+    "zenoh-link-unixsock_stream",
+    "zenoh-link-ws",
+    "quinn",
+    "rustls",
+    "rsa",
+    "tokio-tungstenite",
+];
+
+fn dependencies(features: &str) -> HashSet<String> {
+    let output = Command::new(env!("CARGO"))
+        .args(["tree", "--manifest-path"])
+        .arg(concat!(env!("CARGO_MANIFEST_DIR"), "/Cargo.toml"))
+        .args(["--no-default-features", "--features", features])
+        .args(["--edges", "normal", "--prefix", "none", "--target", "all"])
+        .args(["--format", "{p}"])
+        .output()
+        .expect("failed to run cargo tree");
+    assert!(
+        output.status.success(),
+        "cargo tree failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.split_whitespace().next())
+        .map(String::from)
+        .collect()
+}
+
+#[test]
+fn minimal_dependency_tree() {
+    let dependencies = dependencies("transport_tcp");
+    println!("{} crates: {:?}", dependencies.len(), dependencies);
+
+    assert!(dependencies.contains("zenoh-transport"));
+    assert!(dependencies.contains("zenoh-link-tcp"));
+    for name in FORBIDDEN {
+        assert!(
+            !dependencies.contains(name),
+            "{name} is compiled with the minimal feature set"
+        );
+    }
+    assert!(
+        dependencies.len() <= MAX_DEPENDENCIES,
+        "{} crates are compiled with the minimal feature set, more than {MAX_DEPENDENCIES}",
+        dependencies.len()
+    );
+}
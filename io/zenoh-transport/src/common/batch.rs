@@ -20,7 +20,7 @@ use zenoh_buffers::{
 use zenoh_codec::{WCodec, Zenoh080};
 use zenoh_protocol::{
     core::Reliability,
-    network::NetworkMessage,
+    network::{declare::prefix, NetworkBody, NetworkMessage},
     transport::{
         fragment::FragmentHeader, frame::FrameHeader, BatchSize, TransportMessage, TransportSn,
     },
@@ -97,8 +97,12 @@ pub(crate) struct WBatch {
     buffer: BBuf,
     // It is a streamed batch
     is_streamed: bool,
+    // The declared key expressions are prefix-delta encoded in the frames
+    is_prefix_compression: bool,
     // The current frame being serialized: BestEffort/Reliable
     current_frame: CurrentFrame,
+    // The suffix previously declared in the current frame, if it is compressed
+    current_prefix: Option<String>,
     // The latest SN
     pub(crate) latest_sn: LatestSn,
     // Statistics related to this batch
@@ -107,11 +111,13 @@ pub(crate) struct WBatch {
 }
 
 impl WBatch {
-    pub(crate) fn new(size: BatchSize, is_streamed: bool) -> Self {
+    pub(crate) fn new(size: BatchSize, is_streamed: bool, is_prefix_compression: bool) -> Self {
         let mut batch = Self {
             buffer: BBuf::with_capacity(size as usize),
             is_streamed,
+            is_prefix_compression,
             current_frame: CurrentFrame::None,
+            current_prefix: None,
             latest_sn: LatestSn {
                 reliable: None,
                 best_effort: None,
@@ -150,11 +156,19 @@ impl WBatch {
         self.is_streamed
     }
 
+    /// Verify that the declared key expressions are prefix-delta encoded in the frames of the
+    /// [`SerializationBatch`][SerializationBatch], see [`FrameHeader::compressed`].
+    #[inline(always)]
+    pub(crate) fn is_prefix_compression(&self) -> bool {
+        self.is_prefix_compression
+    }
+
     /// Clear the [`SerializationBatch`][SerializationBatch] memory buffer and related internal state.
     #[inline(always)]
     pub(crate) fn clear(&mut self) {
         self.buffer.clear();
         self.current_frame = CurrentFrame::None;
+        self.current_prefix = None;
         self.latest_sn.clear();
         #[cfg(feature = "stats")]
         {
@@ -205,6 +219,7 @@ impl Encode<&TransportMessage> for &mut WBatch {
 
         // Reset the current frame value
         self.current_frame = CurrentFrame::None;
+        self.current_prefix = None;
         #[cfg(feature = "stats")]
         {
             self.stats.t_msgs += 1;
@@ -242,7 +257,11 @@ impl Encode<&NetworkMessage> for &mut WBatch {
         let mark = writer.mark();
 
         let codec = Zenoh080::new();
-        codec.write(&mut writer, message).map_err(|_| {
+        let res = match self.current_prefix.as_mut() {
+            Some(previous) => write_compressed(codec, &mut writer, message, previous),
+            None => codec.write(&mut writer, message),
+        };
+        res.map_err(|_| {
             // Revert the write operation
             writer.rewind(mark);
             WError::DidntWrite
@@ -250,6 +269,34 @@ impl Encode<&NetworkMessage> for &mut WBatch {
     }
 }
 
+// Writes a message in a compressed frame, prefix-delta encoding the key expression it declares
+// against the one previously declared in the frame, which is only updated once written.
+fn write_compressed<W: Writer>(
+    codec: Zenoh080,
+    writer: &mut W,
+    message: &NetworkMessage,
+    previous: &mut String,
+) -> Result<(), DidntWrite> {
+    let NetworkBody::Declare(declare) = &message.body else {
+        return codec.write(writer, message);
+    };
+    match declare.declared_wire_expr() {
+        Some(wire_expr) if !wire_expr.suffix.is_empty() => {
+            let mut message = message.clone();
+            let mut prefix = previous.clone();
+            if let NetworkBody::Declare(declare) = &mut message.body {
+                if let Some(wire_expr) = declare.declared_wire_expr_mut() {
+                    prefix::compress(&mut prefix, wire_expr);
+                }
+            }
+            codec.write(writer, &message)?;
+            *previous = prefix;
+            Ok(())
+        }
+        _ => codec.write(writer, message),
+    }
+}
+
 impl Encode<(&NetworkMessage, FrameHeader)> for &mut WBatch {
     type Output = Result<(), DidntWrite>;
 
@@ -273,12 +320,18 @@ impl Encode<(&NetworkMessage, FrameHeader)> for &mut WBatch {
             e
         })?;
         // Write the zenoh message
-        codec.write(&mut writer, message).map_err(|e| {
+        let mut prefix = frame.compressed.then(String::new);
+        let res = match prefix.as_mut() {
+            Some(previous) => write_compressed(codec, &mut writer, message, previous),
+            None => codec.write(&mut writer, message),
+        };
+        res.map_err(|e| {
             // Revert the write operation
             writer.rewind(mark);
             e
         })?;
         // Update the frame
+        self.current_prefix = prefix;
         self.current_frame = match frame.reliability {
             Reliability::Reliable => {
                 self.latest_sn.reliable = Some(frame.sn);
@@ -343,22 +396,23 @@ impl Encode<(&mut ZBufReader<'_>, FragmentHeader)> for &mut WBatch {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use zenoh_buffers::ZBuf;
+    use zenoh_buffers::{reader::HasReader, ZBuf};
+    use zenoh_codec::RCodec;
     use zenoh_protocol::{
         core::{CongestionControl, Encoding, Priority, Reliability, WireExpr},
-        network::{ext, Push},
+        network::{declare, ext, Declare, DeclareBody, Mapping, Push},
         transport::{
             frame::{self, FrameHeader},
-            KeepAlive, TransportMessage,
+            KeepAlive, TransportBody, TransportMessage,
         },
         zenoh::{PushBody, Put},
     };
 
     #[test]
     fn serialization_batch() {
-        let mut batch = WBatch::new(u16::MAX, true);
+        let mut batch = WBatch::new(u16::MAX, true, false);
 
-        let tmsg: TransportMessage = KeepAlive.into();
+        let tmsg: TransportMessage = KeepAlive::default().into();
         let nmsg: NetworkMessage = Push {
             wire_expr: WireExpr::empty(),
             ext_qos: ext::QoSType::new(Priority::default(), CongestionControl::Block, false),
@@ -370,6 +424,8 @@ mod tests {
                 ext_sinfo: None,
                 #[cfg(feature = "shared-memory")]
                 ext_shm: None,
+                ext_integrity: None,
+                ext_batch: None,
                 ext_unknown: vec![],
                 payload: ZBuf::from(vec![0u8; 8]),
             }),
@@ -388,6 +444,7 @@ mod tests {
             reliability: Reliability::Reliable,
             sn: 0,
             ext_qos: frame::ext::QoSType::default(),
+            compressed: false,
         };
 
         // Serialize with a frame
@@ -414,4 +471,78 @@ mod tests {
         assert_ne!(batch.len(), 0);
         nmsgs_in.push(nmsg.clone());
     }
+
+    #[test]
+    fn serialization_batch_compressed() {
+        // A burst of declarations of key expressions, subscribers and queryables
+        let mut nmsgs_in: Vec<NetworkMessage> = vec![];
+        for i in 0..8 {
+            let wire_expr = |kind: &str| WireExpr::from(format!("demo/example/{kind}/{i}/**"));
+            for body in [
+                DeclareBody::DeclareKeyExpr(declare::DeclareKeyExpr {
+                    id: i,
+                    // The declared key expressions are always decoded with the receiver mapping
+                    wire_expr: WireExpr {
+                        mapping: Mapping::default(),
+                        ..wire_expr("keyexpr")
+                    },
+                }),
+                DeclareBody::DeclareSubscriber(declare::DeclareSubscriber {
+                    id: i as u32,
+                    wire_expr: wire_expr("subscriber"),
+                    ext_info: declare::subscriber::ext::SubscriberInfo::default(),
+                    ext_filter: None,
+                }),
+                DeclareBody::DeclareQueryable(declare::DeclareQueryable {
+                    id: i as u32,
+                    wire_expr: wire_expr("queryable"),
+                    ext_info: declare::queryable::ext::QueryableInfo::default(),
+                }),
+                DeclareBody::UndeclareSubscriber(declare::UndeclareSubscriber {
+                    id: i as u32,
+                    ext_wire_expr: declare::common::ext::WireExprType::null(),
+                }),
+            ] {
+                nmsgs_in.push(
+                    Declare {
+                        ext_qos: declare::ext::QoSType::declare_default(),
+                        ext_tstamp: None,
+                        ext_nodeid: declare::ext::NodeIdType::default(),
+                        body,
+                    }
+                    .into(),
+                );
+            }
+        }
+
+        // Serializes the burst in a single frame, returns its length and the decoded messages
+        let burst = |compressed: bool| {
+            let mut batch = WBatch::new(u16::MAX, false, compressed);
+            let frame = FrameHeader {
+                reliability: Reliability::Reliable,
+                sn: 0,
+                ext_qos: frame::ext::QoSType::default(),
+                compressed: batch.is_prefix_compression(),
+            };
+            let mut nmsgs = nmsgs_in.iter();
+            batch.encode((nmsgs.next().unwrap(), frame)).unwrap();
+            for nmsg in nmsgs {
+                assert!(batch.encode(nmsg).is_ok());
+            }
+
+            let mut reader = batch.as_bytes().reader();
+            let tmsg: TransportMessage = Zenoh080::new().read(&mut reader).unwrap();
+            let TransportBody::Frame(frame) = tmsg.body else {
+                panic!("Expected a frame, got {:?}", tmsg.body);
+            };
+            (batch.len(), frame.payload)
+        };
+
+        let (len, nmsgs_out) = burst(false);
+        assert_eq!(nmsgs_out, nmsgs_in);
+        let (compressed_len, nmsgs_out) = burst(true);
+        assert_eq!(nmsgs_out, nmsgs_in);
+        println!("Declarations: {len} bytes, {compressed_len} bytes compressed");
+        assert!(compressed_len < len * 3 / 4);
+    }
 }
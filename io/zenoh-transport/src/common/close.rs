@@ -0,0 +1,87 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use zenoh_core::zlock;
+use zenoh_protocol::transport::{close::CloseReason, Close};
+
+/// The reason a transport was closed or a link was rejected: received in the Close message of the
+/// remote node, or decided by the local one.
+///
+/// It is the error returned when opening a transport the remote node rejects, and it is notified
+/// to [`TransportPeerEventHandler::close_reason`](crate::TransportPeerEventHandler::close_reason)
+/// before the transport is closed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransportCloseReason {
+    pub reason: CloseReason,
+    /// The human-readable detail of the reason, if any.
+    pub detail: Option<String>,
+    /// Whether the close was decided by the remote node.
+    pub remote: bool,
+}
+
+impl TransportCloseReason {
+    pub fn local(reason: CloseReason, detail: Option<String>) -> Self {
+        Self {
+            reason,
+            detail,
+            remote: false,
+        }
+    }
+
+    pub fn remote(close: &Close) -> Self {
+        Self {
+            reason: close.reason.into(),
+            detail: close.detail(),
+            remote: true,
+        }
+    }
+}
+
+impl fmt::Display for TransportCloseReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let side = if self.remote { "remote" } else { "local" };
+        write!(f, "{} close ({})", side, self.reason)?;
+        if let Some(detail) = self.detail.as_ref() {
+            write!(f, ": {detail}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for TransportCloseReason {}
+
+/// The reason a transport is being closed, the first one recorded winning over the following
+/// ones: the remote and the local closes may race, and the close of the last link only counts
+/// if no other reason was given before.
+#[derive(Clone, Default)]
+pub(crate) struct CloseReasonCell(Arc<Mutex<Option<TransportCloseReason>>>);
+
+impl CloseReasonCell {
+    pub(crate) fn set(&self, reason: TransportCloseReason) {
+        zlock!(self.0).get_or_insert(reason);
+    }
+
+    /// Records the reason in place of the one already recorded, if any.
+    pub(crate) fn replace(&self, reason: TransportCloseReason) {
+        *zlock!(self.0) = Some(reason);
+    }
+
+    /// Takes the recorded reason, a generic local close if none was recorded.
+    pub(crate) fn take(&self) -> TransportCloseReason {
+        zlock!(self.0)
+            .take()
+            .unwrap_or_else(|| TransportCloseReason::local(CloseReason::Generic, None))
+    }
+}
@@ -16,11 +16,22 @@ use zenoh_buffers::{reader::HasReader, SplitBuffer, ZBuf, ZSlice};
 use zenoh_codec::{RCodec, Zenoh080Reliability};
 use zenoh_protocol::{
     core::{Bits, Reliability},
-    network::NetworkMessage,
+    network::{NetworkMessage, UnknownKind},
     transport::TransportSn,
 };
 use zenoh_result::{bail, ZResult};
 
+/// The outcome of pushing a fragment in a [`DefragBuffer`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum DefragPush {
+    /// The fragment is added to the message being reassembled.
+    Added,
+    /// The message exceeds the capacity with this fragment: it is dropped, with its next fragments.
+    Oversized,
+    /// The fragment belongs to an oversized message being dropped.
+    Dropped,
+}
+
 #[derive(Debug)]
 pub(crate) struct DefragBuffer {
     reliability: Reliability,
@@ -28,6 +39,7 @@ pub(crate) struct DefragBuffer {
     buffer: ZBuf,
     capacity: usize,
     len: usize,
+    oversized: bool,
 }
 
 impl DefragBuffer {
@@ -42,19 +54,21 @@ impl DefragBuffer {
             buffer: ZBuf::empty(),
             capacity,
             len: 0,
+            oversized: false,
         };
         Ok(db)
     }
 
     #[inline(always)]
     pub(crate) fn is_empty(&self) -> bool {
-        self.buffer.is_empty()
+        self.buffer.is_empty() && !self.oversized
     }
 
     #[inline(always)]
     pub(crate) fn clear(&mut self) {
         self.buffer.clear();
         self.len = 0;
+        self.oversized = false;
     }
 
     #[inline(always)]
@@ -62,34 +76,38 @@ impl DefragBuffer {
         self.sn.set(sn)
     }
 
-    pub(crate) fn push(&mut self, sn: TransportSn, zslice: ZSlice) -> ZResult<()> {
+    pub(crate) fn push(&mut self, sn: TransportSn, zslice: ZSlice) -> ZResult<DefragPush> {
         if sn != self.sn.get() {
             self.clear();
             bail!("Expected SN {}, received {}", self.sn.get(), sn)
         }
+        self.sn.increment();
 
+        if self.oversized {
+            return Ok(DefragPush::Dropped);
+        }
+
+        // The fragments of an oversized message are dropped until its last one,
+        // without keeping them in memory
         let new_len = self.len + zslice.len();
         if new_len > self.capacity {
-            self.clear();
-            bail!(
-                "Defragmentation buffer full: {} bytes. Capacity: {}.",
-                new_len,
-                self.capacity
-            )
+            self.buffer.clear();
+            self.len = 0;
+            self.oversized = true;
+            return Ok(DefragPush::Oversized);
         }
 
-        self.sn.increment();
         self.buffer.push_zslice(zslice);
         self.len = new_len;
 
-        Ok(())
+        Ok(DefragPush::Added)
     }
 
     #[inline(always)]
-    pub(crate) fn defragment(&mut self) -> Option<NetworkMessage> {
+    pub(crate) fn defragment(&mut self) -> Option<Result<NetworkMessage, UnknownKind>> {
         let mut reader = self.buffer.reader();
         let rcodec = Zenoh080Reliability::new(self.reliability);
-        let res: Option<NetworkMessage> = rcodec.read(&mut reader).ok();
+        let res: Option<Result<NetworkMessage, UnknownKind>> = rcodec.read(&mut reader).ok();
         self.clear();
         res
     }
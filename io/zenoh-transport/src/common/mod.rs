@@ -12,9 +12,13 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 pub(crate) mod batch;
+pub(crate) mod close;
 pub(crate) mod defragmentation;
 pub(crate) mod pipeline;
 pub(crate) mod priority;
+pub(crate) mod rtt;
+pub(crate) mod scheduler;
 pub(crate) mod seq_num;
 #[cfg(feature = "stats")]
 pub mod stats;
+pub(crate) mod unknown;
@@ -28,14 +28,14 @@ use zenoh_buffers::{
 use zenoh_codec::{WCodec, Zenoh080};
 use zenoh_config::QueueSizeConf;
 use zenoh_core::zlock;
-use zenoh_protocol::core::Reliability;
 use zenoh_protocol::network::NetworkMessage;
 use zenoh_protocol::{
+    common::ZExtBody,
     core::Priority,
     transport::{
         fragment::FragmentHeader,
         frame::{self, FrameHeader},
-        BatchSize, TransportMessage,
+        oam, BatchSize, Oam, TransportMessage,
     },
 };
 
@@ -182,9 +182,10 @@ impl StageIn {
 
         // The Frame
         let frame = FrameHeader {
-            reliability: Reliability::Reliable, // TODO
+            reliability: msg.reliability,
             sn,
             ext_qos: frame::ext::QoSType::new(priority),
+            compressed: batch.is_prefix_compression(),
         };
 
         if let WError::NewFrame = e {
@@ -484,6 +485,7 @@ impl StageOut {
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub(crate) struct TransmissionPipelineConf {
     pub(crate) is_streamed: bool,
+    pub(crate) is_prefix_compression: bool,
     pub(crate) batch_size: BatchSize,
     pub(crate) queue_size: [usize; Priority::NUM],
     pub(crate) backoff: Duration,
@@ -493,6 +495,7 @@ impl Default for TransmissionPipelineConf {
     fn default() -> Self {
         Self {
             is_streamed: false,
+            is_prefix_compression: false,
             batch_size: BatchSize::MAX,
             queue_size: [1; Priority::NUM],
             backoff: Duration::from_micros(1),
@@ -531,7 +534,11 @@ impl TransmissionPipeline {
             // Fill the refill ring buffer with batches
             for _ in 0..*num {
                 assert!(s_ref_w
-                    .push(WBatch::new(config.batch_size, config.is_streamed))
+                    .push(WBatch::new(
+                        config.batch_size,
+                        config.is_streamed,
+                        config.is_prefix_compression,
+                    ))
                     .is_none());
             }
             // Create the channel for notifying that new batches are in the refill ring buffer
@@ -575,6 +582,7 @@ impl TransmissionPipeline {
         let producer = TransmissionPipelineProducer {
             stage_in: stage_in.into_boxed_slice().into(),
             active: active.clone(),
+            sealed: Arc::new(AtomicBool::new(false)),
         };
         let consumer = TransmissionPipelineConsumer {
             stage_out: stage_out.into_boxed_slice(),
@@ -591,11 +599,25 @@ pub(crate) struct TransmissionPipelineProducer {
     // Each priority queue has its own Mutex
     stage_in: Arc<[Mutex<StageIn>]>,
     active: Arc<AtomicBool>,
+    // Set once the network messages are refused, see seal()
+    sealed: Arc<AtomicBool>,
 }
 
 impl TransmissionPipelineProducer {
     #[inline]
-    pub(crate) fn push_network_message(&self, mut msg: NetworkMessage) -> bool {
+    pub(crate) fn push_network_message(&self, msg: NetworkMessage) -> bool {
+        self.try_push_network_message(msg, None).unwrap_or(false)
+    }
+
+    /// Pushes a message on the pipeline, followed by an [`oam::id::OAM_ACK_REQUEST`] with id
+    /// `ack` on the same priority, so that the peer acknowledges it once it received the message.
+    /// The message is given back if the pipeline is [sealed](Self::seal).
+    #[inline]
+    pub(crate) fn try_push_network_message(
+        &self,
+        mut msg: NetworkMessage,
+        ack: Option<u64>,
+    ) -> Result<bool, NetworkMessage> {
         // If the queue is not QoS, it means that we only have one priority with index 0.
         let (idx, priority) = if self.stage_in.len() > 1 {
             let priority = msg.priority();
@@ -605,7 +627,39 @@ impl TransmissionPipelineProducer {
         };
         // Lock the channel. We are the only one that will be writing on it.
         let mut queue = zlock!(self.stage_in[idx]);
-        queue.push_network_message(&mut msg, priority)
+        if self.sealed.load(Ordering::Acquire) {
+            return Err(msg);
+        }
+        if !queue.push_network_message(&mut msg, priority) {
+            return Ok(false);
+        }
+        Ok(match ack {
+            Some(id) => queue.push_transport_message(
+                Oam {
+                    id: oam::id::OAM_ACK_REQUEST,
+                    body: ZExtBody::Z64(id),
+                    ext_qos: oam::ext::QoSType::new(priority),
+                }
+                .into(),
+            ),
+            None => true,
+        })
+    }
+
+    /// Pushes a last transport message after the network messages being pushed, refusing the
+    /// network messages pushed afterwards. The lower the priority, the later the message is sent:
+    /// at the lowest priority, it is sent once all the network messages are sent.
+    pub(crate) fn seal(&self, msg: TransportMessage, priority: Priority) -> bool {
+        // Wait for the pushes in progress, using the same locking order as in disable
+        let mut in_guards: Vec<MutexGuard<'_, StageIn>> =
+            self.stage_in.iter().map(|x| zlock!(x)).collect();
+        self.sealed.store(true, Ordering::Release);
+        let idx = if in_guards.len() > 1 {
+            priority as usize
+        } else {
+            0
+        };
+        in_guards[idx].push_transport_message(msg)
     }
 
     #[inline]
@@ -672,6 +726,20 @@ impl TransmissionPipelineConsumer {
         None
     }
 
+    /// Pulls a batch ready to be sent without waiting, if any.
+    pub(crate) fn try_pull(&mut self) -> Option<(WBatch, usize)> {
+        if !self.active.load(Ordering::Relaxed) {
+            return None;
+        }
+        self.stage_out
+            .iter_mut()
+            .enumerate()
+            .find_map(|(prio, queue)| match queue.try_pull() {
+                Pull::Some(batch) => Some((batch, prio)),
+                _ => None,
+            })
+    }
+
     pub(crate) fn refill(&mut self, batch: WBatch, priority: usize) {
         self.stage_out[priority].refill(batch);
     }
@@ -730,6 +798,7 @@ mod tests {
 
     const CONFIG: TransmissionPipelineConf = TransmissionPipelineConf {
         is_streamed: true,
+        is_prefix_compression: false,
         batch_size: BatchSize::MAX,
         queue_size: [1; Priority::NUM],
         backoff: Duration::from_micros(1),
@@ -753,6 +822,8 @@ mod tests {
                     ext_sinfo: None,
                     #[cfg(feature = "shared-memory")]
                     ext_shm: None,
+                    ext_integrity: None,
+                    ext_batch: None,
                     ext_unknown: vec![],
                     payload,
                 }),
@@ -882,6 +953,8 @@ mod tests {
                     ext_sinfo: None,
                     #[cfg(feature = "shared-memory")]
                     ext_shm: None,
+                    ext_integrity: None,
+                    ext_batch: None,
                     ext_unknown: vec![],
                     payload,
                 }),
@@ -954,6 +1027,68 @@ mod tests {
         });
     }
 
+    #[test]
+    fn tx_pipeline_batch_size() {
+        const NUM_MSG: usize = 64;
+        const PAYLOAD_SIZE: usize = 100;
+
+        // Returns the number of batches written on the link and the size of the largest one
+        fn writes(batch_size: BatchSize) -> (usize, usize) {
+            let tct = TransportPriorityTx::make(Bits::from(TransportSn::MAX)).unwrap();
+            let priorities = vec![tct];
+            let config = TransmissionPipelineConf {
+                batch_size,
+                queue_size: [16; Priority::NUM],
+                ..CONFIG
+            };
+            let (producer, mut consumer) =
+                TransmissionPipeline::make(config, priorities.as_slice());
+
+            let message: NetworkMessage = Push {
+                wire_expr: "test".into(),
+                ext_qos: ext::QoSType::new(Priority::Control, CongestionControl::Block, false),
+                ext_tstamp: None,
+                ext_nodeid: ext::NodeIdType::default(),
+                payload: PushBody::Put(Put {
+                    timestamp: None,
+                    encoding: Encoding::default(),
+                    ext_sinfo: None,
+                    #[cfg(feature = "shared-memory")]
+                    ext_shm: None,
+                    ext_integrity: None,
+                    ext_batch: None,
+                    ext_unknown: vec![],
+                    payload: ZBuf::from(vec![0_u8; PAYLOAD_SIZE]),
+                }),
+            }
+            .into();
+            for _ in 0..NUM_MSG {
+                assert!(producer.push_network_message(message.clone()));
+            }
+
+            let batches = consumer.drain();
+            let largest = batches
+                .iter()
+                .map(|(batch, _)| batch.len() as usize)
+                .max()
+                .unwrap();
+            assert!(largest <= batch_size as usize);
+            println!(
+                "Pipeline Batch Size [{batch_size}]: {} writes, at most {largest} bytes",
+                batches.len()
+            );
+            (batches.len(), largest)
+        }
+
+        // A larger batch size produces fewer and larger writes for the same messages
+        let (small_writes, small_largest) = writes(1_024);
+        let (large_writes, large_largest) = writes(8_192);
+        assert!(small_writes > 1);
+        assert_eq!(large_writes, 1);
+        assert!(large_writes < small_writes);
+        assert!(large_largest > small_largest);
+    }
+
     #[test]
     #[ignore]
     fn tx_pipeline_thr() {
@@ -993,6 +1128,8 @@ mod tests {
                             ext_sinfo: None,
                             #[cfg(feature = "shared-memory")]
                             ext_shm: None,
+                            ext_integrity: None,
+                            ext_batch: None,
                             ext_unknown: vec![],
                             payload,
                         }),
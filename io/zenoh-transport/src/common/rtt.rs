@@ -0,0 +1,195 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use zenoh_core::zlock;
+use zenoh_util::clock::Clock;
+
+/// The round-trip time estimate of a link, computed from the echoes of its keep-alives.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct LinkRtt {
+    /// The smoothed round-trip time.
+    pub srtt: Duration,
+    /// The smoothed deviation of the round-trip time samples from `srtt`.
+    pub jitter: Duration,
+    /// The number of round-trip time samples.
+    pub samples: u64,
+}
+
+/// Estimates the round-trip time of a link the way TCP does (RFC 6298), the samples being the
+/// time between sending an echo request token and receiving its echo.
+pub(crate) struct RttEstimator {
+    clock: Arc<dyn Clock>,
+    epoch: Instant,
+    estimate: Mutex<Option<LinkRtt>>,
+}
+
+impl RttEstimator {
+    pub(crate) fn new(clock: Arc<dyn Clock>) -> Self {
+        let epoch = clock.now();
+        Self {
+            clock,
+            epoch,
+            estimate: Mutex::new(None),
+        }
+    }
+
+    /// The token of an echo request sent now: the nanoseconds elapsed since the creation of the estimator.
+    pub(crate) fn token(&self) -> u64 {
+        let elapsed = self.clock.now().saturating_duration_since(self.epoch);
+        u64::try_from(elapsed.as_nanos()).unwrap_or(u64::MAX)
+    }
+
+    /// Updates the estimate with the echo of a token, ignoring the tokens not issued yet.
+    pub(crate) fn echoed(&self, token: u64) {
+        let now = self.token();
+        if token > now {
+            log::trace!("Ignoring the echo of a token not issued yet: {}", token);
+            return;
+        }
+        let sample = Duration::from_nanos(now - token);
+
+        let mut guard = zlock!(self.estimate);
+        let estimate = match *guard {
+            None => LinkRtt {
+                srtt: sample,
+                jitter: sample / 2,
+                samples: 1,
+            },
+            Some(e) => {
+                let deviation = if e.srtt > sample {
+                    e.srtt - sample
+                } else {
+                    sample - e.srtt
+                };
+                LinkRtt {
+                    srtt: e.srtt - e.srtt / 8 + sample / 8,
+                    jitter: e.jitter - e.jitter / 4 + deviation / 4,
+                    samples: e.samples.saturating_add(1),
+                }
+            }
+        };
+        *guard = Some(estimate);
+    }
+
+    /// The current estimate, `None` until an echo is received.
+    pub(crate) fn get(&self) -> Option<LinkRtt> {
+        *zlock!(self.estimate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zenoh_util::clock::TestClock;
+
+    fn probe(estimator: &RttEstimator, clock: &TestClock, rtt: Duration) {
+        let token = estimator.token();
+        clock.advance(rtt);
+        estimator.echoed(token);
+    }
+
+    fn assert_close(actual: Duration, expected: Duration, tolerance: Duration) {
+        let diff = if actual > expected {
+            actual - expected
+        } else {
+            expected - actual
+        };
+        assert!(
+            diff <= tolerance,
+            "{actual:?} is not within {tolerance:?} of {expected:?}"
+        );
+    }
+
+    #[test]
+    fn rtt_no_echo() {
+        let clock = TestClock::new();
+        let estimator = RttEstimator::new(Arc::new(clock.clone()));
+        let _ = estimator.token();
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(estimator.get(), None);
+
+        // A token from the future is not a sample
+        estimator.echoed(estimator.token() + 1);
+        assert_eq!(estimator.get(), None);
+    }
+
+    #[test]
+    fn rtt_first_sample() {
+        let clock = TestClock::new();
+        let estimator = RttEstimator::new(Arc::new(clock.clone()));
+        probe(&estimator, &clock, Duration::from_millis(20));
+        assert_eq!(
+            estimator.get(),
+            Some(LinkRtt {
+                srtt: Duration::from_millis(20),
+                jitter: Duration::from_millis(10),
+                samples: 1,
+            })
+        );
+    }
+
+    #[test]
+    fn rtt_converges() {
+        let clock = TestClock::new();
+        let estimator = RttEstimator::new(Arc::new(clock.clone()));
+
+        // A steady delay of 10 ms
+        for _ in 0..50 {
+            probe(&estimator, &clock, Duration::from_millis(10));
+        }
+        let estimate = estimator.get().unwrap();
+        assert_close(
+            estimate.srtt,
+            Duration::from_millis(10),
+            Duration::from_micros(10),
+        );
+        assert_close(estimate.jitter, Duration::ZERO, Duration::from_micros(10));
+        assert_eq!(estimate.samples, 50);
+
+        // The delay increases and alternates between 90 and 110 ms
+        for i in 0..200 {
+            let rtt = if i % 2 == 0 { 90 } else { 110 };
+            probe(&estimator, &clock, Duration::from_millis(rtt));
+        }
+        let estimate = estimator.get().unwrap();
+        assert_close(
+            estimate.srtt,
+            Duration::from_millis(100),
+            Duration::from_millis(2),
+        );
+        assert_close(
+            estimate.jitter,
+            Duration::from_millis(10),
+            Duration::from_millis(2),
+        );
+        assert_eq!(estimate.samples, 250);
+    }
+
+    #[test]
+    fn rtt_late_echo() {
+        let clock = TestClock::new();
+        let estimator = RttEstimator::new(Arc::new(clock.clone()));
+
+        // The echo of an older token is a longer sample
+        let old = estimator.token();
+        clock.advance(Duration::from_millis(5));
+        probe(&estimator, &clock, Duration::from_millis(10));
+        estimator.echoed(old);
+        let estimate = estimator.get().unwrap();
+        assert_eq!(estimate.samples, 2);
+        assert!(estimate.srtt > Duration::from_millis(10));
+    }
+}
@@ -0,0 +1,242 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use std::collections::VecDeque;
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::Poll;
+use std::time::Duration;
+use zenoh_core::zlock;
+use zenoh_util::clock::Clock;
+
+/// The turns of the transports sharing the tx threads, in deficit round-robin.
+///
+/// A single transport has the turn at a time. The transports with something to write while
+/// another one has the turn wait in a ring, in order, and the turn passes to the next one when
+/// the transport having it used up its quantum or has nothing left to write.
+#[derive(Default)]
+pub(crate) struct TxScheduler {
+    next_id: AtomicUsize,
+    ring: Mutex<TxRing>,
+}
+
+#[derive(Default)]
+struct TxRing {
+    // The transport having the turn
+    turn: Option<usize>,
+    // The transports waiting for the turn, in order
+    waiting: VecDeque<(usize, flume::Sender<()>)>,
+}
+
+impl TxRing {
+    /// Passes the turn to the next transport still waiting, returning whether there was one.
+    fn pass(&mut self) -> bool {
+        self.turn = None;
+        while let Some((id, notify)) = self.waiting.pop_front() {
+            // A transport whose tx task stopped doesn't wait anymore
+            if notify.send(()).is_ok() {
+                self.turn = Some(id);
+                return true;
+            }
+        }
+        false
+    }
+}
+
+/// The share of the tx threads of a transport, scheduled in deficit round-robin with the other
+/// transports of its [`TxScheduler`].
+///
+/// Each time a transport gets the turn, its deficit is credited with `quantum` bytes, and it
+/// keeps the turn while its batches fit in the deficit. A batch larger than the deficit passes
+/// the turn to the next waiting transport, the deficit carrying over to the next turn, so a
+/// transport with large batches still writes its fair share over a few rounds. The deficit is
+/// reset when the transport has nothing left to write.
+///
+/// The deficit is shared by all the links of the transport.
+pub(crate) struct TxShare {
+    id: usize,
+    quantum: usize,
+    deficit: Mutex<usize>,
+    scheduler: Arc<TxScheduler>,
+}
+
+impl TxShare {
+    /// A share of `quantum` bytes per round per unit of `weight`, both at least 1.
+    pub(crate) fn new(scheduler: Arc<TxScheduler>, quantum: usize, weight: u32) -> Self {
+        Self {
+            id: scheduler.next_id.fetch_add(1, Ordering::Relaxed),
+            quantum: quantum.max(1).saturating_mul(weight.max(1) as usize),
+            deficit: Mutex::new(0),
+            scheduler,
+        }
+    }
+
+    /// Charges `bytes` to the deficit of the transport, taking the turn if free.
+    ///
+    /// Returns `None` when the batch can be written, or the receiver notified when the turn
+    /// passes to the transport. `granted` is whether the turn was just passed to it.
+    fn charge(&self, bytes: usize, granted: bool) -> Option<flume::Receiver<()>> {
+        let mut ring = zlock!(self.scheduler.ring);
+        let mut deficit = zlock!(self.deficit);
+        match ring.turn {
+            None => {
+                ring.turn = Some(self.id);
+                *deficit += self.quantum;
+            }
+            Some(id) if id == self.id => {
+                if granted {
+                    *deficit += self.quantum;
+                }
+            }
+            Some(_) => return Some(self.wait(&mut ring)),
+        }
+        while *deficit < bytes {
+            if ring.pass() {
+                return Some(self.wait(&mut ring));
+            }
+            // Nobody is waiting for the turn: the next round is its own
+            ring.turn = Some(self.id);
+            *deficit += self.quantum;
+        }
+        *deficit -= bytes;
+        None
+    }
+
+    fn wait(&self, ring: &mut TxRing) -> flume::Receiver<()> {
+        let (notify, turn) = flume::bounded(1);
+        ring.waiting.push_back((self.id, notify));
+        turn
+    }
+
+    /// Waits for the turn of a batch of `bytes`, returning the time it waited.
+    pub(crate) async fn schedule(&self, bytes: usize, clock: &dyn Clock) -> Duration {
+        let mut start = None;
+        let mut granted = false;
+        while let Some(turn) = self.charge(bytes, granted) {
+            start.get_or_insert_with(|| clock.now());
+            granted = turn.recv_async().await.is_ok();
+        }
+        start.map_or(Duration::ZERO, |start| {
+            clock.now().saturating_duration_since(start)
+        })
+    }
+
+    /// Writes a batch, giving the turn to the other transports while the link is not ready.
+    pub(crate) async fn write<F: Future>(&self, write: F) -> F::Output {
+        futures::pin_mut!(write);
+        match futures::poll!(write.as_mut()) {
+            Poll::Ready(res) => res,
+            Poll::Pending => {
+                self.release();
+                write.await
+            }
+        }
+    }
+
+    /// Gives the turn to the next waiting transport when the transport has nothing left to
+    /// write, resetting its deficit.
+    pub(crate) fn release(&self) {
+        let mut ring = zlock!(self.scheduler.ring);
+        if ring.turn == Some(self.id) {
+            *zlock!(self.deficit) = 0;
+            ring.pass();
+        }
+    }
+}
+
+/// Gives the turn of a transport back when the tx task of one of its links stops.
+pub(crate) struct TxTurnGuard<'a>(pub(crate) &'a TxShare);
+
+impl Drop for TxTurnGuard<'_> {
+    fn drop(&mut self) {
+        self.0.release();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+    use std::cell::RefCell;
+    use zenoh_util::clock::SystemClock;
+
+    // Gives the other tasks a chance to run
+    async fn yield_now() {
+        let mut yielded = false;
+        futures::future::poll_fn(|cx| {
+            if yielded {
+                Poll::Ready(())
+            } else {
+                yielded = true;
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+        })
+        .await
+    }
+
+    // Writes the batches of the transports concurrently on a single thread, returning the
+    // order in which they were written
+    fn write_in_turn(transports: &[(&'static str, u32, &[usize])]) -> Vec<&'static str> {
+        let scheduler = Arc::new(TxScheduler::default());
+        let order = RefCell::new(vec![]);
+        block_on(futures::future::join_all(transports.iter().map(
+            |(name, weight, batches)| {
+                let share = TxShare::new(scheduler.clone(), 100, *weight);
+                let order = &order;
+                async move {
+                    for bytes in batches.iter() {
+                        share.schedule(*bytes, &SystemClock).await;
+                        order.borrow_mut().push(*name);
+                        yield_now().await;
+                    }
+                    share.release();
+                }
+            },
+        )));
+        order.into_inner()
+    }
+
+    #[test]
+    fn tx_share_round_robin() {
+        // A bulk transfer gives the turn to a small transport once its quantum is used up
+        let order = write_in_turn(&[("bulk", 1, &[50; 6]), ("small", 1, &[10; 2])]);
+        assert_eq!(
+            order,
+            ["bulk", "bulk", "small", "small", "bulk", "bulk", "bulk", "bulk"]
+        );
+
+        // A batch larger than the quantum waits for as many turns as needed
+        let order = write_in_turn(&[("small", 1, &[100; 3]), ("large", 1, &[250])]);
+        assert_eq!(order, ["small", "small", "small", "large"]);
+
+        // The transports write in proportion to their weight
+        let order = write_in_turn(&[("light", 1, &[100; 3]), ("heavy", 2, &[100; 4])]);
+        assert_eq!(
+            order,
+            ["light", "heavy", "heavy", "light", "heavy", "heavy", "light"]
+        );
+    }
+
+    #[test]
+    fn tx_share_alone() {
+        // A transport alone never waits, whatever the size of its batches
+        let share = TxShare::new(Arc::new(TxScheduler::default()), 0, 0);
+        for bytes in [1, 1_000, 65_535] {
+            assert!(share.charge(bytes, false).is_none());
+        }
+        share.release();
+        assert_eq!(*share.deficit.lock().unwrap(), 0);
+    }
+}
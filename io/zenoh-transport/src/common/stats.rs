@@ -196,6 +196,22 @@ stats_struct! {
         # TYPE "counter"
         pub tx_n_dropped,
 
+        # HELP "Counter of reliable network messages sent on a best-effort link."
+        # TYPE "counter"
+        pub tx_n_downgraded,
+
+        # HELP "Counter of retransmitted transport messages (reserved, not counted yet)."
+        # TYPE "counter"
+        pub tx_retransmissions,
+
+        # HELP "Counter of sent bytes scheduled in turn with the other transports."
+        # TYPE "counter"
+        pub tx_sched_bytes,
+
+        # HELP "Counter of microseconds the sent batches waited for their turn."
+        # TYPE "counter"
+        pub tx_sched_wait_us,
+
         # HELP "Counter of sent zenoh put messages."
         # TYPE "counter"
         pub tx_z_put_msgs DiscriminatedStats,
@@ -240,6 +256,26 @@ stats_struct! {
         # TYPE "counter"
         pub rx_n_dropped,
 
+        # HELP "Counter of fragmented messages dropped for exceeding the defragmentation buffer size."
+        # TYPE "counter"
+        pub rx_n_oversized,
+
+        # HELP "Counter of skipped network messages of an unknown id."
+        # TYPE "counter"
+        pub rx_n_unknown_msgs,
+
+        # HELP "Counter of skipped declarations of an unknown kind."
+        # TYPE "counter"
+        pub rx_n_unknown_decls,
+
+        # HELP "Counter of received batches read into a recycled buffer."
+        # TYPE "counter"
+        pub rx_pool_hits,
+
+        # HELP "Counter of received batches read into a newly allocated buffer."
+        # TYPE "counter"
+        pub rx_pool_misses,
+
         # HELP "Counter of received zenoh put messages."
         # TYPE "counter"
         pub rx_z_put_msgs DiscriminatedStats,
@@ -269,3 +305,14 @@ stats_struct! {
         pub rx_z_reply_pl_bytes DiscriminatedStats,
     }
 }
+
+impl TransportStats {
+    /// Counts a batch read into a buffer of the rx pool, `recycled` or newly allocated.
+    pub(crate) fn inc_rx_pool(&self, recycled: bool) {
+        if recycled {
+            self.inc_rx_pool_hits(1);
+        } else {
+            self.inc_rx_pool_misses(1);
+        }
+    }
+}
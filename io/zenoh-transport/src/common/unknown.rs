@@ -0,0 +1,115 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use zenoh_core::zlock;
+use zenoh_protocol::{core::ZenohId, network::UnknownKind};
+use zenoh_util::clock::Clock;
+
+/// The minimum period between two warnings about the same unknown kind.
+const WARN_PERIOD: Duration = Duration::from_secs(10);
+
+#[derive(Default)]
+struct UnknownCount {
+    total: usize,
+    since_warn: usize,
+    warned: Option<Instant>,
+}
+
+/// Counts the network messages and declarations of unknown kinds skipped when received from
+/// newer nodes, warning about each kind at most once per [`WARN_PERIOD`].
+pub(crate) struct UnknownKinds {
+    clock: Arc<dyn Clock>,
+    counts: Mutex<HashMap<UnknownKind, UnknownCount>>,
+}
+
+impl UnknownKinds {
+    pub(crate) fn new(clock: Arc<dyn Clock>) -> Self {
+        Self {
+            clock,
+            counts: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Counts a skipped message of an unknown kind sent by `zid`, returns whether it was warned about.
+    pub(crate) fn skipped(&self, zid: &ZenohId, kind: UnknownKind) -> bool {
+        let now = self.clock.now();
+        let mut guard = zlock!(self.counts);
+        let count = guard.entry(kind).or_default();
+        count.total += 1;
+        count.since_warn += 1;
+        if count
+            .warned
+            .map_or(false, |w| now.saturating_duration_since(w) < WARN_PERIOD)
+        {
+            return false;
+        }
+
+        log::warn!(
+            "Skipped {} {} of unknown kind {} sent by {}, probably running a newer version ({} in total)",
+            count.since_warn,
+            if count.since_warn == 1 { "message" } else { "messages" },
+            kind,
+            zid,
+            count.total
+        );
+        count.since_warn = 0;
+        count.warned = Some(now);
+        true
+    }
+
+    /// The number of skipped messages of each unknown kind received so far.
+    pub(crate) fn counts(&self) -> Vec<(UnknownKind, usize)> {
+        zlock!(self.counts)
+            .iter()
+            .map(|(kind, count)| (*kind, count.total))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zenoh_util::clock::TestClock;
+
+    #[test]
+    fn unknown_kinds_rate_limited() {
+        let clock = TestClock::new();
+        let unknown = UnknownKinds::new(Arc::new(clock.clone()));
+        let zid = ZenohId::rand();
+        let message = UnknownKind::Message(0x10);
+        let declaration = UnknownKind::Declaration(0x1f);
+
+        // The first one of each kind is warned about, the next ones of the period are only counted
+        assert!(unknown.skipped(&zid, message));
+        assert!(unknown.skipped(&zid, declaration));
+        for _ in 0..10 {
+            assert!(!unknown.skipped(&zid, message));
+        }
+        clock.advance(WARN_PERIOD / 2);
+        assert!(!unknown.skipped(&zid, declaration));
+
+        // A new period starts with a warning
+        clock.advance(WARN_PERIOD / 2);
+        assert!(unknown.skipped(&zid, message));
+        assert!(unknown.skipped(&zid, declaration));
+        assert!(!unknown.skipped(&zid, declaration));
+
+        let counts: HashMap<UnknownKind, usize> = unknown.counts().into_iter().collect();
+        assert_eq!(counts.len(), 2);
+        assert_eq!(counts[&message], 12);
+        assert_eq!(counts[&declaration], 4);
+    }
+}
@@ -23,6 +23,8 @@ mod multicast;
 mod primitives;
 pub mod unicast;
 
+pub use common::close::TransportCloseReason;
+pub use common::rtt::LinkRtt;
 #[cfg(feature = "stats")]
 pub use common::stats;
 
@@ -36,7 +38,7 @@ use serde::Serialize;
 use std::any::Any;
 use std::sync::Arc;
 pub use unicast::*;
-use zenoh_link::Link;
+use zenoh_link::{Link, Locator};
 use zenoh_protocol::core::{WhatAmI, ZenohId};
 use zenoh_protocol::network::NetworkMessage;
 use zenoh_result::ZResult;
@@ -55,6 +57,9 @@ pub trait TransportEventHandler: Send + Sync {
         &self,
         _transport: TransportMulticast,
     ) -> ZResult<Arc<dyn TransportMulticastEventHandler>>;
+
+    /// Called when this node refuses to open a transport with a peer, with the reason sent to it.
+    fn rejected(&self, _zid: &ZenohId, _whatami: WhatAmI, _reason: &TransportCloseReason) {}
 }
 
 #[derive(Default)]
@@ -77,6 +82,23 @@ impl TransportEventHandler for DummyTransportEventHandler {
     }
 }
 
+/// A listener registered on the [`TransportManager`] with
+/// [`TransportManagerBuilder::transport_listener`], notified of the unicast transports being
+/// opened and closed.
+pub trait TransportUnicastEventListener: Send + Sync {
+    fn opened(&self, event: &TransportUnicastEvent);
+    fn closed(&self, event: &TransportUnicastEvent);
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TransportUnicastEvent {
+    pub zid: ZenohId,
+    pub whatami: WhatAmI,
+    /// The locator of the link that opened the transport, or of a link of the closed transport
+    /// if it had any left.
+    pub locator: Option<Locator>,
+}
+
 /*************************************/
 /*            MULTICAST              */
 /*************************************/
@@ -111,6 +133,9 @@ pub struct TransportPeer {
     pub zid: ZenohId,
     pub whatami: WhatAmI,
     pub is_qos: bool,
+    /// Whether the transport was opened by this node rather than accepted,
+    /// always `false` for the peers of a multicast transport.
+    pub is_initiator: bool,
     #[serde(skip)]
     pub links: Vec<Link>,
     #[cfg(feature = "shared-memory")]
@@ -122,6 +147,8 @@ pub trait TransportPeerEventHandler: Send + Sync {
     fn new_link(&self, src: Link);
     fn del_link(&self, link: Link);
     fn closing(&self);
+    /// Called with the reason of the close of the transport, before [`closed`](Self::closed).
+    fn close_reason(&self, _reason: &TransportCloseReason) {}
     fn closed(&self);
     fn as_any(&self) -> &dyn Any;
 }
@@ -14,7 +14,8 @@
 use super::unicast::manager::{
     TransportManagerBuilderUnicast, TransportManagerConfigUnicast, TransportManagerStateUnicast,
 };
-use super::TransportEventHandler;
+use super::{TransportEventHandler, TransportUnicastEventListener};
+use crate::common::scheduler::TxScheduler;
 use crate::multicast::manager::{
     TransportManagerBuilderMulticast, TransportManagerConfigMulticast,
     TransportManagerStateMulticast,
@@ -24,7 +25,7 @@ use rand::{RngCore, SeedableRng};
 use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Duration;
-use zenoh_config::{Config, LinkRxConf, QueueConf, QueueSizeConf};
+use zenoh_config::{Config, LinkRxConf, LinkTxConf, QueueConf, QueueSizeConf};
 use zenoh_crypto::{BlockCipher, PseudoRng};
 use zenoh_link::NewLinkChannelSender;
 use zenoh_protocol::{
@@ -32,7 +33,12 @@ use zenoh_protocol::{
     transport::BatchSize,
     VERSION,
 };
-use zenoh_result::{bail, ZResult};
+use zenoh_result::{bail, zerror, ZResult};
+use zenoh_sync::BufferPool;
+use zenoh_util::clock::{Clock, SystemClock};
+
+/// The size classes of the buffers the links read the batches into, fitting the MTU of the links.
+const RX_POOL_CLASSES: [usize; 4] = [1 << 10, 1 << 12, 1 << 14, 1 << 16];
 
 /// # Examples
 /// ```
@@ -71,7 +77,7 @@ use zenoh_result::{bail, ZResult};
 /// // Configure the unicast transports parameters
 /// let unicast = TransportManager::config_unicast()
 ///         .lease(Duration::from_secs(1))
-///         .keep_alive(4)      // Send a KeepAlive every 250 ms
+///         .keep_alive_every(Duration::from_millis(250)) // Send a KeepAlive every 250 ms
 ///         .accept_timeout(Duration::from_secs(1))
 ///         .accept_pending(10) // Set to 10 the number of simultanous pending incoming transports        
 ///         .max_sessions(5);   // Allow max 5 transports open
@@ -95,14 +101,22 @@ pub struct TransportManagerConfig {
     pub batch_size: u16,
     pub queue_size: [usize; Priority::NUM],
     pub queue_backoff: Duration,
+    pub tx_stall_timeout: Duration,
+    pub tx_quantum: Option<usize>,
+    pub tx_weights: HashMap<ZenohId, u32>,
+    pub rtt_probe: bool,
     pub defrag_buff_size: usize,
     pub link_rx_buffer_size: usize,
+    pub link_rx_queue_size: usize,
+    pub link_rx_pool_size: usize,
     pub unicast: TransportManagerConfigUnicast,
     pub multicast: TransportManagerConfigMulticast,
     pub endpoints: HashMap<String, String>, // (protocol, config)
     pub handler: Arc<dyn TransportEventHandler>,
+    pub listeners: Vec<Arc<dyn TransportUnicastEventListener>>,
     pub tx_threads: usize,
     pub protocols: Vec<String>,
+    pub clock: Arc<dyn Clock>,
 }
 
 pub struct TransportManagerState {
@@ -123,13 +137,23 @@ pub struct TransportManagerBuilder {
     batch_size: u16,
     queue_size: QueueSizeConf,
     queue_backoff: Duration,
+    tx_stall_timeout: Duration,
+    tx_quantum: Option<usize>,
+    tx_weights: HashMap<ZenohId, u32>,
+    rtt_probe: bool,
     defrag_buff_size: usize,
     link_rx_buffer_size: usize,
+    link_rx_queue_size: usize,
+    link_rx_pool_size: usize,
     unicast: TransportManagerBuilderUnicast,
     multicast: TransportManagerBuilderMulticast,
     endpoints: HashMap<String, String>, // (protocol, config)
+    listen: Vec<EndPoint>,
+    connect: Vec<EndPoint>,
+    listeners: Vec<Arc<dyn TransportUnicastEventListener>>,
     tx_threads: usize,
     protocols: Option<Vec<String>>,
+    clock: Arc<dyn Clock>,
 }
 
 impl TransportManagerBuilder {
@@ -148,11 +172,14 @@ impl TransportManagerBuilder {
         self
     }
 
+    /// The size of the batches of the tx pipeline of the links, clamped to the MTU of each link.
     pub fn batch_size(mut self, batch_size: u16) -> Self {
         self.batch_size = batch_size;
         self
     }
 
+    /// The number of batches of each priority queue of the tx pipeline of the links, between
+    /// [`QueueSizeConf::MIN`] and [`QueueSizeConf::MAX`].
     pub fn queue_size(mut self, queue_size: QueueSizeConf) -> Self {
         self.queue_size = queue_size;
         self
@@ -163,21 +190,86 @@ impl TransportManagerBuilder {
         self
     }
 
+    /// The time after which a link whose writes don't progress is closed, [`Duration::ZERO`]
+    /// disabling the watchdog.
+    pub fn tx_stall_timeout(mut self, tx_stall_timeout: Duration) -> Self {
+        self.tx_stall_timeout = tx_stall_timeout;
+        self
+    }
+
+    /// The number of bytes a transport writes per round on the tx threads per unit of weight,
+    /// `None` disabling the deficit round-robin scheduling of the transports.
+    pub fn tx_quantum(mut self, tx_quantum: Option<usize>) -> Self {
+        self.tx_quantum = tx_quantum;
+        self
+    }
+
+    /// The weights of the transports with some peers in the tx scheduling, the others having a
+    /// weight of 1.
+    pub fn tx_weights(mut self, tx_weights: HashMap<ZenohId, u32>) -> Self {
+        self.tx_weights = tx_weights;
+        self
+    }
+
+    /// Whether the keep-alives request an echo from the remote node, estimating the round-trip
+    /// time of the links.
+    pub fn rtt_probe(mut self, rtt_probe: bool) -> Self {
+        self.rtt_probe = rtt_probe;
+        self
+    }
+
+    /// The maximum size in bytes of a reassembled fragmented message, per channel of a peer.
+    /// The bigger messages are dropped without closing the transport.
     pub fn defrag_buff_size(mut self, defrag_buff_size: usize) -> Self {
         self.defrag_buff_size = defrag_buff_size;
         self
     }
 
+    /// The size in bytes of the buffer each link is read into.
     pub fn link_rx_buffer_size(mut self, link_rx_buffer_size: usize) -> Self {
         self.link_rx_buffer_size = link_rx_buffer_size;
         self
     }
 
+    /// The number of received messages that can wait to be handled, per transport. Once full,
+    /// the droppable messages are dropped and the others hold the reading of the link, which
+    /// is closed if the queue stays full for a whole lease.
+    pub fn link_rx_queue_size(mut self, link_rx_queue_size: usize) -> Self {
+        self.link_rx_queue_size = link_rx_queue_size;
+        self
+    }
+
+    /// The total size in bytes of the received batch buffers kept for recycling across all
+    /// the links, 0 disabling the recycling.
+    pub fn link_rx_pool_size(mut self, link_rx_pool_size: usize) -> Self {
+        self.link_rx_pool_size = link_rx_pool_size;
+        self
+    }
+
     pub fn endpoints(mut self, endpoints: HashMap<String, String>) -> Self {
         self.endpoints = endpoints;
         self
     }
 
+    /// The endpoints the node will listen on, checked when building the [`TransportManager`].
+    pub fn listen(mut self, listen: Vec<EndPoint>) -> Self {
+        self.listen = listen;
+        self
+    }
+
+    /// The endpoints the node will connect to, checked when building the [`TransportManager`].
+    pub fn connect(mut self, connect: Vec<EndPoint>) -> Self {
+        self.connect = connect;
+        self
+    }
+
+    /// Registers a listener notified of the unicast transports being opened and closed,
+    /// including the ones closed when the [`TransportManager`] is closed.
+    pub fn transport_listener(mut self, listener: Arc<dyn TransportUnicastEventListener>) -> Self {
+        self.listeners.push(listener);
+        self
+    }
+
     pub fn unicast(mut self, unicast: TransportManagerBuilderUnicast) -> Self {
         self.unicast = unicast;
         self
@@ -198,6 +290,12 @@ impl TransportManagerBuilder {
         self
     }
 
+    /// The [`Clock`] driving the lease and keep-alive timers, the real clocks by default.
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
     pub async fn from_config(mut self, config: &Config) -> ZResult<TransportManagerBuilder> {
         self = self.zid(*config.id());
         if let Some(v) = config.mode() {
@@ -211,7 +309,20 @@ impl TransportManagerBuilder {
         self = self.batch_size(*link.tx().batch_size());
         self = self.defrag_buff_size(*link.rx().max_message_size());
         self = self.link_rx_buffer_size(*link.rx().buffer_size());
+        self = self.link_rx_queue_size(*link.rx().queue_size());
+        self = self.link_rx_pool_size(*link.rx().pool_size());
         self = self.queue_size(link.tx().queue().size().clone());
+        self = self.tx_stall_timeout(Duration::from_millis(*link.tx().stall_timeout()));
+        let scheduling = link.tx().scheduling();
+        self = self.tx_quantum(scheduling.enabled().then_some(*scheduling.quantum()));
+        self = self.tx_weights(
+            scheduling
+                .weights()
+                .iter()
+                .flat_map(|w| w.zids.iter().map(|zid| (*zid, w.weight)))
+                .collect(),
+        );
+        self = self.rtt_probe(*link.rtt_probe());
         self = self.tx_threads(*link.tx().threads());
         self = self.protocols(link.protocols().clone());
 
@@ -227,6 +338,8 @@ impl TransportManagerBuilder {
             bail!("{}", formatter);
         }
         self = self.endpoints(c);
+        self = self.listen(config.listen().endpoints().clone());
+        self = self.connect(config.connect().endpoints().clone());
         self = self.unicast(
             TransportManagerBuilderUnicast::default()
                 .from_config(config)
@@ -241,7 +354,98 @@ impl TransportManagerBuilder {
         Ok(self)
     }
 
+    fn validate(&self) -> ZResult<()> {
+        if self.zid.to_le_bytes().iter().all(|b| *b == 0) {
+            bail!("Invalid zid {}: it can't be all zeros", self.zid);
+        }
+
+        if self.batch_size == 0 {
+            bail!("Invalid batch size: it can't be 0");
+        }
+        for (priority, size) in [
+            ("control", self.queue_size.control()),
+            ("real_time", self.queue_size.real_time()),
+            ("interactive_high", self.queue_size.interactive_high()),
+            ("interactive_low", self.queue_size.interactive_low()),
+            ("data_high", self.queue_size.data_high()),
+            ("data", self.queue_size.data()),
+            ("data_low", self.queue_size.data_low()),
+            ("background", self.queue_size.background()),
+        ] {
+            if !(QueueSizeConf::MIN..=QueueSizeConf::MAX).contains(size) {
+                bail!(
+                    "Invalid {} queue size {}: it must be between {} and {}",
+                    priority,
+                    size,
+                    QueueSizeConf::MIN,
+                    QueueSizeConf::MAX
+                );
+            }
+        }
+
+        if self.whatami == WhatAmI::Router && self.listen.is_empty() {
+            log::warn!(
+                "Router {} has no listen endpoint: other nodes won't be able to connect to it",
+                self.zid
+            );
+        }
+
+        let (sender, _) = flume::unbounded();
+        let supported_config_keys = |protocol: &str| -> Option<&'static [&'static str]> {
+            zenoh_link::LinkManagerBuilderUnicast::make(sender.clone(), protocol)
+                .ok()
+                .map(|manager| manager.supported_config_keys())
+        };
+
+        for endpoint in self.listen.iter().chain(self.connect.iter()) {
+            let protocol = endpoint.protocol();
+            let keys = match supported_config_keys(protocol.as_str()) {
+                Some(keys) => keys,
+                None => bail!(
+                    "Endpoint {}: unsupported protocol `{}`. Supported protocols are: {:?}",
+                    endpoint,
+                    protocol,
+                    zenoh_link::PROTOCOLS
+                ),
+            };
+            if let Some(protocols) = self.protocols.as_ref() {
+                if !protocols.iter().any(|p| p.as_str() == protocol.as_str()) {
+                    bail!(
+                        "Endpoint {}: protocol `{}` is not enabled. Enabled protocols are: {:?}",
+                        endpoint,
+                        protocol,
+                        protocols
+                    );
+                }
+            }
+            zenoh_link::validate_config_keys(endpoint.config().as_str(), keys)
+                .map_err(|e| zerror!("Endpoint {}: {}", endpoint, e))?;
+        }
+
+        for (protocol, config) in self.endpoints.iter() {
+            let keys = match supported_config_keys(protocol) {
+                Some(keys) => keys,
+                None => bail!(
+                    "Unsupported protocol `{}` in the endpoints default configuration. Supported protocols are: {:?}",
+                    protocol,
+                    zenoh_link::PROTOCOLS
+                ),
+            };
+            zenoh_link::validate_config_keys(config, keys).map_err(|e| {
+                zerror!(
+                    "Endpoints default configuration of protocol `{}`: {}",
+                    protocol,
+                    e
+                )
+            })?;
+        }
+
+        Ok(())
+    }
+
     pub fn build(self, handler: Arc<dyn TransportEventHandler>) -> ZResult<TransportManager> {
+        self.validate()?;
+
         // Initialize the PRNG and the Cipher
         let mut prng = PseudoRng::from_entropy();
 
@@ -266,12 +470,19 @@ impl TransportManagerBuilder {
             batch_size: self.batch_size,
             queue_size,
             queue_backoff: self.queue_backoff,
+            tx_stall_timeout: self.tx_stall_timeout,
+            tx_quantum: self.tx_quantum,
+            tx_weights: self.tx_weights,
+            rtt_probe: self.rtt_probe,
             defrag_buff_size: self.defrag_buff_size,
             link_rx_buffer_size: self.link_rx_buffer_size,
+            link_rx_queue_size: self.link_rx_queue_size,
+            link_rx_pool_size: self.link_rx_pool_size,
             unicast: unicast.config,
             multicast: multicast.config,
             endpoints: self.endpoints,
             handler,
+            listeners: self.listeners,
             tx_threads: self.tx_threads,
             protocols: self.protocols.unwrap_or_else(|| {
                 zenoh_link::PROTOCOLS
@@ -279,6 +490,7 @@ impl TransportManagerBuilder {
                     .map(|x| x.to_string())
                     .collect()
             }),
+            clock: self.clock,
         };
 
         let state = TransportManagerState {
@@ -295,6 +507,7 @@ impl TransportManagerBuilder {
 impl Default for TransportManagerBuilder {
     fn default() -> Self {
         let link_rx = LinkRxConf::default();
+        let link_tx = LinkTxConf::default();
         let queue = QueueConf::default();
         let backoff = *queue.backoff();
         Self {
@@ -305,13 +518,26 @@ impl Default for TransportManagerBuilder {
             batch_size: BatchSize::MAX,
             queue_size: queue.size,
             queue_backoff: Duration::from_nanos(backoff),
+            tx_stall_timeout: Duration::from_millis(*link_tx.stall_timeout()),
+            tx_quantum: link_tx
+                .scheduling()
+                .enabled()
+                .then_some(*link_tx.scheduling().quantum()),
+            tx_weights: HashMap::new(),
+            rtt_probe: false,
             defrag_buff_size: *link_rx.max_message_size(),
             link_rx_buffer_size: *link_rx.buffer_size(),
+            link_rx_queue_size: *link_rx.queue_size(),
+            link_rx_pool_size: *link_rx.pool_size(),
             endpoints: HashMap::new(),
+            listen: vec![],
+            connect: vec![],
+            listeners: vec![],
             unicast: TransportManagerBuilderUnicast::default(),
             multicast: TransportManagerBuilderMulticast::default(),
             tx_threads: 1,
             protocols: None,
+            clock: Arc::new(SystemClock),
         }
     }
 }
@@ -358,8 +584,14 @@ pub struct TransportManager {
     pub(crate) locator_inspector: zenoh_link::LocatorInspector,
     pub(crate) new_unicast_link_sender: NewLinkChannelSender,
     pub(crate) tx_executor: TransportExecutor,
+    // The turns of the transports on the tx threads, if they write in turn
+    pub(crate) tx_scheduler: Arc<TxScheduler>,
+    pub(crate) rx_pool: BufferPool,
     #[cfg(feature = "stats")]
     pub(crate) stats: Arc<crate::stats::TransportStats>,
+    // The statistics of the unicast transports, counted in the ones of the manager
+    #[cfg(feature = "stats")]
+    pub(crate) stats_unicast: Arc<crate::stats::TransportStats>,
 }
 
 impl TransportManager {
@@ -373,6 +605,9 @@ impl TransportManager {
         let (new_unicast_link_sender, new_unicast_link_receiver) = flume::unbounded();
 
         let tx_threads = params.config.tx_threads;
+        let rx_pool = BufferPool::new(&RX_POOL_CLASSES, params.config.link_rx_pool_size);
+        #[cfg(feature = "stats")]
+        let stats = Arc::new(crate::stats::TransportStats::default());
         let this = TransportManager {
             config: Arc::new(params.config),
             state: Arc::new(params.state),
@@ -381,8 +616,12 @@ impl TransportManager {
             locator_inspector: Default::default(),
             new_unicast_link_sender,
             tx_executor: TransportExecutor::new(tx_threads),
+            tx_scheduler: Arc::new(TxScheduler::default()),
+            rx_pool,
+            #[cfg(feature = "stats")]
+            stats_unicast: Arc::new(crate::stats::TransportStats::new(Some(stats.clone()))),
             #[cfg(feature = "stats")]
-            stats: std::sync::Arc::new(crate::stats::TransportStats::default()),
+            stats,
         };
 
         // @TODO: this should be moved into the unicast module
@@ -406,26 +645,40 @@ impl TransportManager {
         self.config.zid
     }
 
+    /// The pool of the buffers the links read the batches into.
+    pub fn rx_pool(&self) -> &BufferPool {
+        &self.rx_pool
+    }
+
     #[cfg(feature = "stats")]
     pub fn get_stats(&self) -> std::sync::Arc<crate::stats::TransportStats> {
         self.stats.clone()
     }
 
     pub async fn close(&self) {
-        self.close_unicast().await;
+        let report = self.close_unicast().await;
+        if !report.forced.is_empty() {
+            log::warn!(
+                "Transports with {:?} not closed in time, their links were dropped",
+                report.forced
+            );
+        }
+        log::debug!("Transports with {:?} closed", report.graceful);
         self.tx_executor.stop().await;
     }
 
     /*************************************/
     /*              LISTENER             */
     /*************************************/
-    pub async fn add_listener(&self, endpoint: EndPoint) -> ZResult<Locator> {
+    /// Adds a listener on `endpoint`, returning the locators it listens on, see
+    /// [`add_listener_unicast`](Self::add_listener_unicast).
+    pub async fn add_listener(&self, endpoint: EndPoint) -> ZResult<Vec<Locator>> {
         if self
             .locator_inspector
             .is_multicast(&endpoint.to_locator())
             .await?
         {
-            self.add_listener_multicast(endpoint).await
+            Ok(vec![self.add_listener_multicast(endpoint).await?])
         } else {
             self.add_listener_unicast(endpoint).await
         }
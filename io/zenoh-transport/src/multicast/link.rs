@@ -33,7 +33,8 @@ use zenoh_protocol::{
     transport::{BatchSize, Join, PrioritySn, TransportMessage, TransportSn},
 };
 use zenoh_result::{bail, zerror, ZResult};
-use zenoh_sync::{RecyclingObjectPool, Signal};
+use zenoh_sync::Signal;
+use zenoh_util::clock::{timeout, Clock};
 
 pub(super) struct TransportLinkMulticastConfig {
     pub(super) version: u8,
@@ -106,6 +107,7 @@ impl TransportLinkMulticast {
         if self.handle_tx.is_none() {
             let tpc = TransmissionPipelineConf {
                 is_streamed: false,
+                is_prefix_compression: false,
                 batch_size: config.batch_size,
                 queue_size: self.transport.manager.config.queue_size,
                 backoff: self.transport.manager.config.queue_backoff,
@@ -123,6 +125,7 @@ impl TransportLinkMulticast {
                     c_link.clone(),
                     config,
                     initial_sns,
+                    ctransport.manager.config.clock.clone(),
                     #[cfg(feature = "stats")]
                     ctransport.stats.clone(),
                 )
@@ -206,6 +209,7 @@ async fn tx_task(
     link: LinkMulticast,
     config: TransportLinkMulticastConfig,
     mut last_sns: Vec<PrioritySn>,
+    clock: Arc<dyn Clock>,
     #[cfg(feature = "stats")] stats: Arc<TransportStats>,
 ) -> ZResult<()> {
     enum Action {
@@ -221,20 +225,20 @@ async fn tx_task(
         }
     }
 
-    async fn join(last_join: Instant, join_interval: Duration) -> Action {
-        let now = Instant::now();
+    async fn join(clock: &dyn Clock, last_join: Instant, join_interval: Duration) -> Action {
+        let now = clock.now();
         let target = last_join + join_interval;
         if now < target {
             let left = target - now;
-            task::sleep(left).await;
+            clock.sleep(left).await;
         }
         Action::Join
     }
 
-    let mut last_join = Instant::now().checked_sub(config.join_interval).unwrap();
+    let mut last_join = clock.now().checked_sub(config.join_interval).unwrap();
     loop {
         match pull(&mut pipeline)
-            .race(join(last_join, config.join_interval))
+            .race(join(&*clock, last_join, config.join_interval))
             .await
         {
             Action::Pull((batch, priority)) => {
@@ -292,16 +296,15 @@ async fn tx_task(
                     stats.inc_tx_bytes(n);
                 }
 
-                last_join = Instant::now();
+                last_join = clock.now();
             }
             Action::Stop => {
                 // Drain the transmission pipeline and write remaining bytes on the wire
                 let mut batches = pipeline.drain();
                 for (b, _) in batches.drain(..) {
-                    link.write_all(b.as_bytes())
-                        .timeout(config.join_interval)
+                    timeout(&*clock, config.join_interval, link.write_all(b.as_bytes()))
                         .await
-                        .map_err(|_| {
+                        .ok_or_else(|| {
                             zerror!(
                                 "{}: flush failed after {} ms",
                                 link,
@@ -345,16 +348,19 @@ async fn rx_task(
         Ok(Action::Stop)
     }
 
-    // The pool of buffers
+    // The buffers are recycled by the pool shared by the links of the manager
     let mtu = link.get_mtu() as usize;
     let mut n = rx_buffer_size / mtu;
     if rx_buffer_size % mtu != 0 {
         n += 1;
     }
-    let pool = RecyclingObjectPool::new(n, || vec![0_u8; mtu].into_boxed_slice());
+    let pool = transport.manager.rx_pool.clone();
+    pool.prefill(mtu, n);
     while !signal.is_triggered() {
         // Retrieve one buffer
-        let mut buffer = pool.try_take().unwrap_or_else(|| pool.alloc());
+        let mut buffer = pool.take(mtu);
+        #[cfg(feature = "stats")]
+        transport.stats.inc_rx_pool(buffer.recycled());
         // Async read from the underlying link
         let action = read(&link, &mut buffer).race(stop(signal.clone())).await?;
         match action {
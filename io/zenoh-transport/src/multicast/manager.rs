@@ -250,6 +250,11 @@ impl TransportManager {
                 .config_mut()
                 .extend(endpoint::Parameters::iter(config))?;
         }
+        zenoh_link::validate_config_keys(
+            endpoint.config().as_str(),
+            manager.supported_config_keys(),
+        )
+        .map_err(|e| zerror!("Endpoint {}: {}", endpoint, e))?;
 
         // Open the link
         let link = manager.new_link(&endpoint).await?;
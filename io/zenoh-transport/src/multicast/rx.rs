@@ -12,6 +12,7 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 use super::transport::{TransportMulticastInner, TransportMulticastPeer};
+use crate::common::defragmentation::DefragPush;
 use crate::common::priority::TransportChannelRx;
 use std::sync::MutexGuard;
 use zenoh_buffers::reader::{HasReader, Reader};
@@ -23,7 +24,11 @@ use zenoh_protocol::core::{Priority, Reliability};
 use zenoh_protocol::transport::{
     BatchSize, Close, Fragment, Frame, Join, KeepAlive, TransportBody, TransportSn,
 };
-use zenoh_protocol::{core::Locator, network::NetworkMessage, transport::TransportMessage};
+use zenoh_protocol::{
+    core::Locator,
+    network::{NetworkMessage, UnknownKind},
+    transport::TransportMessage,
+};
 use zenoh_result::{bail, zerror, ZResult};
 
 /*************************************/
@@ -40,7 +45,11 @@ impl TransportMulticastInner {
         #[cfg(feature = "shared-memory")]
         {
             if self.manager.config.multicast.is_shm {
-                crate::shm::map_zmsg_to_shmbuf(&mut msg, &self.manager.state.multicast.shm.reader)?;
+                crate::shm::map_zmsg_to_shmbuf(
+                    &mut msg,
+                    &self.manager.state.multicast.shm.reader,
+                    false,
+                )?;
             }
         }
 
@@ -138,6 +147,7 @@ impl TransportMulticastInner {
             sn,
             ext_qos,
             mut payload,
+            skipped,
         } = frame;
 
         let priority = ext_qos.priority();
@@ -161,6 +171,9 @@ impl TransportMulticastInner {
 
         self.verify_sn(sn, &mut guard)?;
 
+        for kind in skipped {
+            self.skip_unknown(kind, peer);
+        }
         for msg in payload.drain(..) {
             self.trigger_callback(msg, peer)?;
         }
@@ -200,21 +213,51 @@ impl TransportMulticastInner {
         if guard.defrag.is_empty() {
             let _ = guard.defrag.sync(sn);
         }
-        guard.defrag.push(sn, payload)?;
-        if !more {
-            // When shared-memory feature is disabled, msg does not need to be mutable
-            let msg = guard.defrag.defragment().ok_or_else(|| {
-                zerror!(
-                    "Transport: {}. Peer: {}. Priority: {:?}. Defragmentation error.",
-                    self.manager.config.zid,
-                    peer.zid,
-                    priority
-                )
-            })?;
-            return self.trigger_callback(msg, peer);
+        let pushed = guard.defrag.push(sn, payload)?;
+        if pushed == DefragPush::Oversized {
+            log::debug!(
+                "Transport: {}. Peer: {}. Dropping a fragmented message exceeding the defragmentation buffer size of {} bytes.",
+                self.manager.config.zid,
+                peer.zid,
+                self.manager.config.defrag_buff_size
+            );
+            #[cfg(feature = "stats")]
+            self.stats.inc_rx_n_oversized(1);
+        }
+        if more {
+            return Ok(());
+        }
+        if pushed != DefragPush::Added {
+            // The last fragment of an oversized message, the next ones are processed
+            guard.defrag.clear();
+            return Ok(());
         }
 
-        Ok(())
+        // When shared-memory feature is disabled, msg does not need to be mutable
+        let msg = guard.defrag.defragment().ok_or_else(|| {
+            zerror!(
+                "Transport: {}. Peer: {}. Priority: {:?}. Defragmentation error.",
+                self.manager.config.zid,
+                peer.zid,
+                priority
+            )
+        })?;
+        match msg {
+            Ok(msg) => self.trigger_callback(msg, peer),
+            Err(kind) => {
+                self.skip_unknown(kind, peer);
+                Ok(())
+            }
+        }
+    }
+
+    fn skip_unknown(&self, kind: UnknownKind, peer: &TransportMulticastPeer) {
+        #[cfg(feature = "stats")]
+        match kind {
+            UnknownKind::Message(_) => self.stats.inc_rx_n_unknown_msgs(1),
+            UnknownKind::Declaration(_) => self.stats.inc_rx_n_unknown_decls(1),
+        }
+        self.unknown.skipped(&peer.zid, kind);
     }
 
     fn verify_sn(
@@ -13,6 +13,7 @@
 //
 use super::common::priority::{TransportPriorityRx, TransportPriorityTx};
 use super::link::{TransportLinkMulticast, TransportLinkMulticastConfig};
+use crate::common::unknown::UnknownKinds;
 #[cfg(feature = "stats")]
 use crate::stats::TransportStats;
 use crate::{
@@ -101,6 +102,8 @@ pub(crate) struct TransportMulticastInner {
     pub(super) callback: Arc<RwLock<Option<Arc<dyn TransportMulticastEventHandler>>>>,
     // The timer for peer leases
     pub(super) timer: Arc<Timer>,
+    // The counts of the received messages of unknown kinds
+    pub(super) unknown: Arc<UnknownKinds>,
     // Transport statistics
     #[cfg(feature = "stats")]
     pub(super) stats: Arc<TransportStats>,
@@ -125,6 +128,8 @@ impl TransportMulticastInner {
         #[cfg(feature = "stats")]
         let stats = Arc::new(TransportStats::new(Some(manager.get_stats().clone())));
 
+        let unknown = Arc::new(UnknownKinds::new(manager.config.clock.clone()));
+
         let ti = TransportMulticastInner {
             manager,
             priority_tx: priority_tx.into_boxed_slice().into(),
@@ -133,6 +138,7 @@ impl TransportMulticastInner {
             link: Arc::new(RwLock::new(None)),
             callback: Arc::new(RwLock::new(None)),
             timer: Arc::new(Timer::new(false)),
+            unknown,
             #[cfg(feature = "stats")]
             stats,
         };
@@ -220,6 +226,7 @@ impl TransportMulticastInner {
                     let msg: TransportMessage = Close {
                         reason,
                         session: false,
+                        ext_detail: None,
                     }
                     .into();
                     pipeline.push_transport_message(msg, Priority::Background);
@@ -339,6 +346,7 @@ impl TransportMulticastInner {
             zid: join.zid,
             whatami: join.whatami,
             is_qos: join.ext_qos.is_some(),
+            is_initiator: false,
             #[cfg(feature = "shared-memory")]
             is_shm,
             links: vec![link],
@@ -441,6 +449,7 @@ impl TransportMulticastInner {
                     zid: p.zid,
                     whatami: p.whatami,
                     is_qos: p.is_qos(),
+                    is_initiator: false,
                     #[cfg(feature = "shared-memory")]
                     is_shm: self.is_shm(),
                     links: vec![link],
@@ -53,10 +53,15 @@ impl TransportMulticastInner {
     pub(super) fn schedule(&self, mut msg: NetworkMessage) -> bool {
         #[cfg(feature = "shared-memory")]
         {
+            // The alignment of the buffers is not negotiated on multicast transports
             let res = if self.manager.config.multicast.is_shm {
-                crate::shm::map_zmsg_to_shminfo(&mut msg)
+                crate::shm::map_zmsg_to_shminfo(&mut msg, false)
             } else {
-                crate::shm::map_zmsg_to_shmbuf(&mut msg, &self.manager.state.multicast.shm.reader)
+                crate::shm::map_zmsg_to_shmbuf(
+                    &mut msg,
+                    &self.manager.state.multicast.shm.reader,
+                    false,
+                )
             };
             if let Err(e) = res {
                 log::trace!("Failed SHM conversion: {}", e);
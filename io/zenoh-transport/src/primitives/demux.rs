@@ -32,7 +32,7 @@ impl<P: 'static + Primitives> TransportPeerEventHandler for DeMux<P> {
     fn handle_message(&self, msg: NetworkMessage) -> ZResult<()> {
         match msg.body {
             NetworkBody::Declare(m) => self.primitives.send_declare(m),
-            NetworkBody::Push(m) => self.primitives.send_push(m),
+            NetworkBody::Push(m) => self.primitives.send_push(m, msg.reliability),
             NetworkBody::Request(m) => self.primitives.send_request(m),
             NetworkBody::Response(m) => self.primitives.send_response(m),
             NetworkBody::ResponseFinal(m) => self.primitives.send_response_final(m),
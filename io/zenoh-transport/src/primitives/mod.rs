@@ -16,12 +16,48 @@ mod mux;
 
 pub use demux::*;
 pub use mux::*;
-use zenoh_protocol::network::{Declare, Push, Request, Response, ResponseFinal};
+use zenoh_protocol::{
+    core::Reliability,
+    network::{Declare, Push, Request, Response, ResponseFinal},
+};
+
+/// The outcome of the scheduling of a [`Push`] message, see [`Primitives::send_push_reported`].
+#[derive(Debug, Default)]
+pub struct PushReport {
+    /// The number of destinations the message was scheduled on.
+    pub scheduled: usize,
+    /// The number of destinations that dropped the message, e.g. because of congestion.
+    pub dropped: usize,
+    /// The acknowledgements of the destinations that were asked for one. Each one is signaled
+    /// once the first hop acknowledges the message, and disconnected if the message or its
+    /// acknowledgement is lost.
+    pub acks: Vec<flume::Receiver<()>>,
+}
+
+impl PushReport {
+    pub fn merge(&mut self, other: PushReport) {
+        self.scheduled += other.scheduled;
+        self.dropped += other.dropped;
+        self.acks.extend(other.acks);
+    }
+}
 
 pub trait Primitives: Send + Sync {
     fn send_declare(&self, msg: Declare);
 
-    fn send_push(&self, msg: Push);
+    fn send_push(&self, msg: Push, reliability: Reliability);
+
+    /// Sends a [`Push`] message and reports the outcome of its scheduling. If `ack` is set, the
+    /// report contains an acknowledgement for each reliable destination able to provide one.
+    ///
+    /// The default implementation considers the message as scheduled.
+    fn send_push_reported(&self, msg: Push, reliability: Reliability, _ack: bool) -> PushReport {
+        self.send_push(msg, reliability);
+        PushReport {
+            scheduled: 1,
+            ..Default::default()
+        }
+    }
 
     fn send_request(&self, msg: Request);
 
@@ -44,7 +80,7 @@ impl DummyPrimitives {
 impl Primitives for DummyPrimitives {
     fn send_declare(&self, _msg: Declare) {}
 
-    fn send_push(&self, _msg: Push) {}
+    fn send_push(&self, _msg: Push, _reliability: Reliability) {}
 
     fn send_request(&self, _msg: Request) {}
 
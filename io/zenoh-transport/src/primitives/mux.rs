@@ -12,9 +12,10 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 use super::super::{TransportMulticast, TransportUnicast};
-use super::Primitives;
-use zenoh_protocol::network::{
-    Declare, NetworkBody, NetworkMessage, Push, Request, Response, ResponseFinal,
+use super::{Primitives, PushReport};
+use zenoh_protocol::{
+    core::Reliability,
+    network::{Declare, NetworkBody, NetworkMessage, Push, Request, Response, ResponseFinal},
 };
 
 pub struct Mux {
@@ -31,22 +32,51 @@ impl Primitives for Mux {
     fn send_declare(&self, msg: Declare) {
         let _ = self.handler.schedule(NetworkMessage {
             body: NetworkBody::Declare(msg),
+            reliability: Reliability::Reliable,
             #[cfg(feature = "stats")]
             size: None,
         });
     }
 
-    fn send_push(&self, msg: Push) {
+    fn send_push(&self, msg: Push, reliability: Reliability) {
         let _ = self.handler.schedule(NetworkMessage {
             body: NetworkBody::Push(msg),
+            reliability,
             #[cfg(feature = "stats")]
             size: None,
         });
     }
 
+    fn send_push_reported(&self, msg: Push, reliability: Reliability, ack: bool) -> PushReport {
+        let msg = NetworkMessage {
+            body: NetworkBody::Push(msg),
+            reliability,
+            #[cfg(feature = "stats")]
+            size: None,
+        };
+        // Only the reliable channels can acknowledge the messages
+        let res = if ack && reliability == Reliability::Reliable {
+            self.handler.schedule_with_ack(msg).map(Some)
+        } else {
+            self.handler.schedule(msg).map(|_| None)
+        };
+        match res {
+            Ok(ack) => PushReport {
+                scheduled: 1,
+                dropped: 0,
+                acks: ack.into_iter().collect(),
+            },
+            Err(_) => PushReport {
+                dropped: 1,
+                ..Default::default()
+            },
+        }
+    }
+
     fn send_request(&self, msg: Request) {
         let _ = self.handler.schedule(NetworkMessage {
             body: NetworkBody::Request(msg),
+            reliability: Reliability::Reliable,
             #[cfg(feature = "stats")]
             size: None,
         });
@@ -55,6 +85,7 @@ impl Primitives for Mux {
     fn send_response(&self, msg: Response) {
         let _ = self.handler.schedule(NetworkMessage {
             body: NetworkBody::Response(msg),
+            reliability: Reliability::Reliable,
             #[cfg(feature = "stats")]
             size: None,
         });
@@ -63,6 +94,7 @@ impl Primitives for Mux {
     fn send_response_final(&self, msg: ResponseFinal) {
         let _ = self.handler.schedule(NetworkMessage {
             body: NetworkBody::ResponseFinal(msg),
+            reliability: Reliability::Reliable,
             #[cfg(feature = "stats")]
             size: None,
         });
@@ -87,22 +119,44 @@ impl Primitives for McastMux {
     fn send_declare(&self, msg: Declare) {
         let _ = self.handler.handle_message(NetworkMessage {
             body: NetworkBody::Declare(msg),
+            reliability: Reliability::Reliable,
             #[cfg(feature = "stats")]
             size: None,
         });
     }
 
-    fn send_push(&self, msg: Push) {
+    fn send_push(&self, msg: Push, reliability: Reliability) {
         let _ = self.handler.handle_message(NetworkMessage {
             body: NetworkBody::Push(msg),
+            reliability,
+            #[cfg(feature = "stats")]
+            size: None,
+        });
+    }
+
+    fn send_push_reported(&self, msg: Push, reliability: Reliability, _ack: bool) -> PushReport {
+        let res = self.handler.handle_message(NetworkMessage {
+            body: NetworkBody::Push(msg),
+            reliability,
             #[cfg(feature = "stats")]
             size: None,
         });
+        match res {
+            Ok(_) => PushReport {
+                scheduled: 1,
+                ..Default::default()
+            },
+            Err(_) => PushReport {
+                dropped: 1,
+                ..Default::default()
+            },
+        }
     }
 
     fn send_request(&self, msg: Request) {
         let _ = self.handler.handle_message(NetworkMessage {
             body: NetworkBody::Request(msg),
+            reliability: Reliability::Reliable,
             #[cfg(feature = "stats")]
             size: None,
         });
@@ -111,6 +165,7 @@ impl Primitives for McastMux {
     fn send_response(&self, msg: Response) {
         let _ = self.handler.handle_message(NetworkMessage {
             body: NetworkBody::Response(msg),
+            reliability: Reliability::Reliable,
             #[cfg(feature = "stats")]
             size: None,
         });
@@ -119,6 +174,7 @@ impl Primitives for McastMux {
     fn send_response_final(&self, msg: ResponseFinal) {
         let _ = self.handler.handle_message(NetworkMessage {
             body: NetworkBody::ResponseFinal(msg),
+            reliability: Reliability::Reliable,
             #[cfg(feature = "stats")]
             size: None,
         });
@@ -29,13 +29,13 @@ use zenoh_shm::{SharedMemoryBuf, SharedMemoryBufInfo, SharedMemoryReader};
 
 // Traits
 trait MapShm {
-    fn map_to_shminfo(&mut self) -> ZResult<bool>;
-    fn map_to_shmbuf(&mut self, shmr: &RwLock<SharedMemoryReader>) -> ZResult<bool>;
+    fn map_to_shminfo(&mut self, aligned: bool) -> ZResult<bool>;
+    fn map_to_shmbuf(&mut self, shmr: &RwLock<SharedMemoryReader>, aligned: bool) -> ZResult<bool>;
 }
 
 macro_rules! map_to_shminfo {
-    ($zbuf:expr, $ext_shm:expr) => {{
-        let res = map_zbuf_to_shminfo($zbuf)?;
+    ($zbuf:expr, $ext_shm:expr, $aligned:expr) => {{
+        let res = map_zbuf_to_shminfo($zbuf, $aligned)?;
         if res {
             *$ext_shm = Some(ShmType::new());
         }
@@ -44,10 +44,10 @@ macro_rules! map_to_shminfo {
 }
 
 macro_rules! map_to_shmbuf {
-    ($zbuf:expr, $ext_shm:expr, $shmr:expr) => {{
+    ($zbuf:expr, $ext_shm:expr, $shmr:expr, $aligned:expr) => {{
         if $ext_shm.is_some() {
             *$ext_shm = None;
-            map_zbuf_to_shmbuf($zbuf, $shmr)
+            map_zbuf_to_shmbuf($zbuf, $shmr, $aligned)
         } else {
             Ok(false)
         }
@@ -56,24 +56,24 @@ macro_rules! map_to_shmbuf {
 
 // Impl - Put
 impl MapShm for Put {
-    fn map_to_shminfo(&mut self) -> ZResult<bool> {
+    fn map_to_shminfo(&mut self, aligned: bool) -> ZResult<bool> {
         let Self {
             payload, ext_shm, ..
         } = self;
-        map_to_shminfo!(payload, ext_shm)
+        map_to_shminfo!(payload, ext_shm, aligned)
     }
 
-    fn map_to_shmbuf(&mut self, shmr: &RwLock<SharedMemoryReader>) -> ZResult<bool> {
+    fn map_to_shmbuf(&mut self, shmr: &RwLock<SharedMemoryReader>, aligned: bool) -> ZResult<bool> {
         let Self {
             payload, ext_shm, ..
         } = self;
-        map_to_shmbuf!(payload, ext_shm, shmr)
+        map_to_shmbuf!(payload, ext_shm, shmr, aligned)
     }
 }
 
 // Impl - Query
 impl MapShm for Query {
-    fn map_to_shminfo(&mut self) -> ZResult<bool> {
+    fn map_to_shminfo(&mut self, aligned: bool) -> ZResult<bool> {
         if let Self {
             ext_body: Some(QueryBodyType {
                 payload, ext_shm, ..
@@ -81,13 +81,13 @@ impl MapShm for Query {
             ..
         } = self
         {
-            map_to_shminfo!(payload, ext_shm)
+            map_to_shminfo!(payload, ext_shm, aligned)
         } else {
             Ok(false)
         }
     }
 
-    fn map_to_shmbuf(&mut self, shmr: &RwLock<SharedMemoryReader>) -> ZResult<bool> {
+    fn map_to_shmbuf(&mut self, shmr: &RwLock<SharedMemoryReader>, aligned: bool) -> ZResult<bool> {
         if let Self {
             ext_body: Some(QueryBodyType {
                 payload, ext_shm, ..
@@ -95,7 +95,7 @@ impl MapShm for Query {
             ..
         } = self
         {
-            map_to_shmbuf!(payload, ext_shm, shmr)
+            map_to_shmbuf!(payload, ext_shm, shmr, aligned)
         } else {
             Ok(false)
         }
@@ -104,24 +104,24 @@ impl MapShm for Query {
 
 // Impl - Reply
 impl MapShm for Reply {
-    fn map_to_shminfo(&mut self) -> ZResult<bool> {
+    fn map_to_shminfo(&mut self, aligned: bool) -> ZResult<bool> {
         let Self {
             payload, ext_shm, ..
         } = self;
-        map_to_shminfo!(payload, ext_shm)
+        map_to_shminfo!(payload, ext_shm, aligned)
     }
 
-    fn map_to_shmbuf(&mut self, shmr: &RwLock<SharedMemoryReader>) -> ZResult<bool> {
+    fn map_to_shmbuf(&mut self, shmr: &RwLock<SharedMemoryReader>, aligned: bool) -> ZResult<bool> {
         let Self {
             payload, ext_shm, ..
         } = self;
-        map_to_shmbuf!(payload, ext_shm, shmr)
+        map_to_shmbuf!(payload, ext_shm, shmr, aligned)
     }
 }
 
 // Impl - Err
 impl MapShm for Err {
-    fn map_to_shminfo(&mut self) -> ZResult<bool> {
+    fn map_to_shminfo(&mut self, aligned: bool) -> ZResult<bool> {
         if let Self {
             ext_body: Some(ErrBodyType {
                 payload, ext_shm, ..
@@ -129,13 +129,13 @@ impl MapShm for Err {
             ..
         } = self
         {
-            map_to_shminfo!(payload, ext_shm)
+            map_to_shminfo!(payload, ext_shm, aligned)
         } else {
             Ok(false)
         }
     }
 
-    fn map_to_shmbuf(&mut self, shmr: &RwLock<SharedMemoryReader>) -> ZResult<bool> {
+    fn map_to_shmbuf(&mut self, shmr: &RwLock<SharedMemoryReader>, aligned: bool) -> ZResult<bool> {
         if let Self {
             ext_body: Some(ErrBodyType {
                 payload, ext_shm, ..
@@ -143,7 +143,7 @@ impl MapShm for Err {
             ..
         } = self
         {
-            map_to_shmbuf!(payload, ext_shm, shmr)
+            map_to_shmbuf!(payload, ext_shm, shmr, aligned)
         } else {
             Ok(false)
         }
@@ -151,21 +151,24 @@ impl MapShm for Err {
 }
 
 // ShmBuf -> ShmInfo
-pub fn map_zmsg_to_shminfo(msg: &mut NetworkMessage) -> ZResult<bool> {
+//
+// The alignment of the buffers is carried after their info only when it was negotiated with the
+// other node, see the shm extension of the transport establishment.
+pub fn map_zmsg_to_shminfo(msg: &mut NetworkMessage, aligned: bool) -> ZResult<bool> {
     match &mut msg.body {
         NetworkBody::Push(Push { payload, .. }) => match payload {
-            PushBody::Put(b) => b.map_to_shminfo(),
+            PushBody::Put(b) => b.map_to_shminfo(aligned),
             PushBody::Del(_) => Ok(false),
         },
         NetworkBody::Request(Request { payload, .. }) => match payload {
-            RequestBody::Query(b) => b.map_to_shminfo(),
-            RequestBody::Put(b) => b.map_to_shminfo(),
+            RequestBody::Query(b) => b.map_to_shminfo(aligned),
+            RequestBody::Put(b) => b.map_to_shminfo(aligned),
             RequestBody::Del(_) | RequestBody::Pull(_) => Ok(false),
         },
         NetworkBody::Response(Response { payload, .. }) => match payload {
-            ResponseBody::Reply(b) => b.map_to_shminfo(),
-            ResponseBody::Put(b) => b.map_to_shminfo(),
-            ResponseBody::Err(b) => b.map_to_shminfo(),
+            ResponseBody::Reply(b) => b.map_to_shminfo(aligned),
+            ResponseBody::Put(b) => b.map_to_shminfo(aligned),
+            ResponseBody::Err(b) => b.map_to_shminfo(aligned),
             ResponseBody::Ack(_) => Ok(false),
         },
         NetworkBody::ResponseFinal(_) | NetworkBody::Declare(_) | NetworkBody::OAM(_) => Ok(false),
@@ -173,11 +176,11 @@ pub fn map_zmsg_to_shminfo(msg: &mut NetworkMessage) -> ZResult<bool> {
 }
 
 // Mapping
-pub fn map_zbuf_to_shminfo(zbuf: &mut ZBuf) -> ZResult<bool> {
+pub fn map_zbuf_to_shminfo(zbuf: &mut ZBuf, aligned: bool) -> ZResult<bool> {
     let mut res = false;
     for zs in zbuf.zslices_mut() {
         if let Some(shmb) = zs.downcast_ref::<SharedMemoryBuf>() {
-            *zs = map_zslice_to_shminfo(shmb)?;
+            *zs = map_zslice_to_shminfo(shmb, aligned)?;
             res = true;
         }
     }
@@ -186,7 +189,7 @@ pub fn map_zbuf_to_shminfo(zbuf: &mut ZBuf) -> ZResult<bool> {
 
 #[cold]
 #[inline(never)]
-pub fn map_zslice_to_shminfo(shmb: &SharedMemoryBuf) -> ZResult<ZSlice> {
+pub fn map_zslice_to_shminfo(shmb: &SharedMemoryBuf, aligned: bool) -> ZResult<ZSlice> {
     // Serialize the shmb info
     let codec = Zenoh080::new();
     let mut info = vec![];
@@ -194,6 +197,11 @@ pub fn map_zslice_to_shminfo(shmb: &SharedMemoryBuf) -> ZResult<ZSlice> {
     codec
         .write(&mut writer, &shmb.info)
         .map_err(|e| zerror!("{:?}", e))?;
+    if aligned {
+        codec
+            .write(&mut writer, shmb.info.alignment)
+            .map_err(|e| zerror!("{:?}", e))?;
+    }
     // Increase the reference count so to keep the SharedMemoryBuf valid
     shmb.inc_ref_count();
     // Replace the content of the slice
@@ -206,21 +214,22 @@ pub fn map_zslice_to_shminfo(shmb: &SharedMemoryBuf) -> ZResult<ZSlice> {
 pub fn map_zmsg_to_shmbuf(
     msg: &mut NetworkMessage,
     shmr: &RwLock<SharedMemoryReader>,
+    aligned: bool,
 ) -> ZResult<bool> {
     match &mut msg.body {
         NetworkBody::Push(Push { payload, .. }) => match payload {
-            PushBody::Put(b) => b.map_to_shmbuf(shmr),
+            PushBody::Put(b) => b.map_to_shmbuf(shmr, aligned),
             PushBody::Del(_) => Ok(false),
         },
         NetworkBody::Request(Request { payload, .. }) => match payload {
-            RequestBody::Query(b) => b.map_to_shmbuf(shmr),
-            RequestBody::Put(b) => b.map_to_shmbuf(shmr),
+            RequestBody::Query(b) => b.map_to_shmbuf(shmr, aligned),
+            RequestBody::Put(b) => b.map_to_shmbuf(shmr, aligned),
             RequestBody::Del(_) | RequestBody::Pull(_) => Ok(false),
         },
         NetworkBody::Response(Response { payload, .. }) => match payload {
-            ResponseBody::Put(b) => b.map_to_shmbuf(shmr),
-            ResponseBody::Err(b) => b.map_to_shmbuf(shmr),
-            ResponseBody::Reply(b) => b.map_to_shmbuf(shmr),
+            ResponseBody::Put(b) => b.map_to_shmbuf(shmr, aligned),
+            ResponseBody::Err(b) => b.map_to_shmbuf(shmr, aligned),
+            ResponseBody::Reply(b) => b.map_to_shmbuf(shmr, aligned),
             ResponseBody::Ack(_) => Ok(false),
         },
         NetworkBody::ResponseFinal(_) | NetworkBody::Declare(_) | NetworkBody::OAM(_) => Ok(false),
@@ -228,10 +237,14 @@ pub fn map_zmsg_to_shmbuf(
 }
 
 // Mapping
-pub fn map_zbuf_to_shmbuf(zbuf: &mut ZBuf, shmr: &RwLock<SharedMemoryReader>) -> ZResult<bool> {
+pub fn map_zbuf_to_shmbuf(
+    zbuf: &mut ZBuf,
+    shmr: &RwLock<SharedMemoryReader>,
+    aligned: bool,
+) -> ZResult<bool> {
     let mut res = false;
     for zs in zbuf.zslices_mut().filter(|x| x.kind == ZSliceKind::ShmPtr) {
-        res |= map_zslice_to_shmbuf(zs, shmr)?;
+        res |= map_zslice_to_shmbuf(zs, shmr, aligned)?;
     }
     Ok(res)
 }
@@ -241,12 +254,18 @@ pub fn map_zbuf_to_shmbuf(zbuf: &mut ZBuf, shmr: &RwLock<SharedMemoryReader>) ->
 pub fn map_zslice_to_shmbuf(
     zslice: &mut ZSlice,
     shmr: &RwLock<SharedMemoryReader>,
+    aligned: bool,
 ) -> ZResult<bool> {
     // Deserialize the shmb info into shm buff
     let codec = Zenoh080::new();
     let mut reader = zslice.reader();
 
-    let shmbinfo: SharedMemoryBufInfo = codec.read(&mut reader).map_err(|e| zerror!("{:?}", e))?;
+    let mut shmbinfo: SharedMemoryBufInfo =
+        codec.read(&mut reader).map_err(|e| zerror!("{:?}", e))?;
+    if aligned {
+        let alignment: usize = codec.read(&mut reader).map_err(|e| zerror!("{:?}", e))?;
+        shmbinfo = shmbinfo.with_alignment(alignment);
+    }
 
     // First, try in read mode allowing concurrenct lookups
     let r_guard = task::block_on(async { zasyncread!(shmr) });
@@ -18,7 +18,7 @@ use crate::{
         close_link, compute_sn, ext, finalize_transport, AcceptFsm, Cookie, InputFinalize,
         Zenoh080Cookie,
     },
-    TransportConfigUnicast, TransportManager,
+    TransportCloseReason, TransportConfigUnicast, TransportManager,
 };
 use async_std::sync::Mutex;
 use async_trait::async_trait;
@@ -56,6 +56,9 @@ struct State {
     #[cfg(feature = "transport_auth")]
     ext_auth: ext::auth::StateAccept,
     ext_lowlatency: ext::lowlatency::StateAccept,
+    ext_resync: ext::resync::StateAccept,
+    ext_prefix: ext::prefix::StateAccept,
+    ext_integrity: ext::integrity::StateAccept,
 }
 
 // InitSyn
@@ -117,6 +120,9 @@ struct AcceptLink<'a> {
     #[cfg(feature = "transport_auth")]
     ext_auth: ext::auth::AuthFsm<'a>,
     ext_lowlatency: ext::lowlatency::LowLatencyFsm<'a>,
+    ext_resync: ext::resync::ResyncFsm<'a>,
+    ext_prefix: ext::prefix::PrefixFsm<'a>,
+    ext_integrity: ext::integrity::IntegrityFsm<'a>,
 }
 
 #[async_trait]
@@ -195,6 +201,24 @@ impl<'a> AcceptFsm for AcceptLink<'a> {
             .await
             .map_err(|e| (e, Some(close::reason::GENERIC)))?;
 
+        // Extension Resync
+        self.ext_resync
+            .recv_init_syn((&mut state.ext_resync, init_syn.ext_resync))
+            .await
+            .map_err(|e| (e, Some(close::reason::GENERIC)))?;
+
+        // Extension PrefixCompression
+        self.ext_prefix
+            .recv_init_syn((&mut state.ext_prefix, init_syn.ext_prefix))
+            .await
+            .map_err(|e| (e, Some(close::reason::GENERIC)))?;
+
+        // Extension Integrity
+        self.ext_integrity
+            .recv_init_syn((&mut state.ext_integrity, init_syn.ext_integrity))
+            .await
+            .map_err(|e| (e, Some(close::reason::GENERIC)))?;
+
         // Extension Shm
         #[cfg(feature = "shared-memory")]
         let ext_shm = self
@@ -208,7 +232,10 @@ impl<'a> AcceptFsm for AcceptLink<'a> {
         self.ext_auth
             .recv_init_syn((&mut state.ext_auth, init_syn.ext_auth))
             .await
-            .map_err(|e| (e, Some(close::reason::GENERIC)))?;
+            .map_err(|e| {
+                log::trace!("Rejecting link {}: {}", self.link, e);
+                (e, Some(close::reason::UNAUTHORIZED))
+            })?;
 
         // Extension MultiLink
         #[cfg(feature = "transport_multilink")]
@@ -249,6 +276,27 @@ impl<'a> AcceptFsm for AcceptLink<'a> {
             .await
             .map_err(|e| (e, Some(close::reason::GENERIC)))?;
 
+        // Extension Resync
+        let ext_resync = self
+            .ext_resync
+            .send_init_ack(&state.ext_resync)
+            .await
+            .map_err(|e| (e, Some(close::reason::GENERIC)))?;
+
+        // Extension PrefixCompression
+        let ext_prefix = self
+            .ext_prefix
+            .send_init_ack(&state.ext_prefix)
+            .await
+            .map_err(|e| (e, Some(close::reason::GENERIC)))?;
+
+        // Extension Integrity
+        let ext_integrity = self
+            .ext_integrity
+            .send_init_ack(&state.ext_integrity)
+            .await
+            .map_err(|e| (e, Some(close::reason::GENERIC)))?;
+
         // Extension Shm
         let ext_shm = zcondfeat!(
             "shared-memory",
@@ -295,6 +343,9 @@ impl<'a> AcceptFsm for AcceptLink<'a> {
             #[cfg(feature = "transport_auth")]
             ext_auth: state.ext_auth,
             ext_lowlatency: state.ext_lowlatency,
+            ext_resync: state.ext_resync,
+            ext_prefix: state.ext_prefix,
+            ext_integrity: state.ext_integrity,
         };
 
         let mut encrypted = vec![];
@@ -325,6 +376,9 @@ impl<'a> AcceptFsm for AcceptLink<'a> {
             ext_auth,
             ext_mlink,
             ext_lowlatency,
+            ext_resync,
+            ext_prefix,
+            ext_integrity,
         }
         .into();
 
@@ -412,6 +466,9 @@ impl<'a> AcceptFsm for AcceptLink<'a> {
             #[cfg(feature = "transport_auth")]
             ext_auth: cookie.ext_auth,
             ext_lowlatency: cookie.ext_lowlatency,
+            ext_resync: cookie.ext_resync,
+            ext_prefix: cookie.ext_prefix,
+            ext_integrity: cookie.ext_integrity,
         };
 
         // Extension QoS
@@ -438,7 +495,10 @@ impl<'a> AcceptFsm for AcceptLink<'a> {
         self.ext_auth
             .recv_open_syn((&mut state.ext_auth, open_syn.ext_auth))
             .await
-            .map_err(|e| (e, Some(close::reason::GENERIC)))?;
+            .map_err(|e| {
+                log::trace!("Rejecting link {}: {}", self.link, e);
+                (e, Some(close::reason::UNAUTHORIZED))
+            })?;
 
         // Extension MultiLink
         #[cfg(feature = "transport_multilink")]
@@ -540,6 +600,9 @@ pub(crate) async fn accept_link(link: &LinkUnicast, manager: &TransportManager)
         #[cfg(feature = "transport_auth")]
         ext_auth: manager.state.unicast.authenticator.fsm(&manager.prng),
         ext_lowlatency: ext::lowlatency::LowLatencyFsm::new(),
+        ext_resync: ext::resync::ResyncFsm::new(),
+        ext_prefix: ext::prefix::PrefixFsm::new(),
+        ext_integrity: ext::integrity::IntegrityFsm::new(),
     };
 
     // Init handshake
@@ -549,14 +612,14 @@ pub(crate) async fn accept_link(link: &LinkUnicast, manager: &TransportManager)
                 Ok(output) => output,
                 Err((e, reason)) => {
                     log::debug!("{}", e);
-                    close_link(link, reason).await;
+                    close_link(link, reason, &e).await;
                     return Err(e);
                 }
             }
         };
     }
 
-    let iack_out = {
+    let (iack_out, _pending) = {
         let mut state = State {
             zenoh: StateZenoh {
                 batch_size: manager.config.batch_size,
@@ -564,6 +627,9 @@ pub(crate) async fn accept_link(link: &LinkUnicast, manager: &TransportManager)
             },
             ext_qos: ext::qos::StateAccept::new(manager.config.unicast.is_qos),
             ext_lowlatency: ext::lowlatency::StateAccept::new(manager.config.unicast.is_lowlatency),
+            ext_resync: ext::resync::StateAccept::new(manager.config.unicast.is_resync),
+            ext_prefix: ext::prefix::StateAccept::new(manager.config.unicast.is_prefix_compression),
+            ext_integrity: ext::integrity::StateAccept::new(manager.config.unicast.is_integrity),
             #[cfg(feature = "transport_multilink")]
             ext_mlink: manager
                 .state
@@ -587,6 +653,9 @@ pub(crate) async fn accept_link(link: &LinkUnicast, manager: &TransportManager)
             mine_version: manager.config.version,
         };
         let isyn_out = step!(fsm.recv_init_syn((&mut state, isyn_in)).await);
+        // Track the link until the transport is initialized, for it to be resolved against the
+        // links opened at the same time to the same peer
+        let pending = manager.add_pending_unicast(isyn_out.other_zid, false);
 
         let iack_in = SendInitAckIn {
             mine_version: manager.config.version,
@@ -597,7 +666,7 @@ pub(crate) async fn accept_link(link: &LinkUnicast, manager: &TransportManager)
             #[cfg(feature = "shared-memory")]
             ext_shm: isyn_out.ext_shm,
         };
-        step!(fsm.send_init_ack((state, iack_in)).await)
+        (step!(fsm.send_init_ack((state, iack_in)).await), pending)
     };
 
     // Open handshake
@@ -619,20 +688,38 @@ pub(crate) async fn accept_link(link: &LinkUnicast, manager: &TransportManager)
         zid: osyn_out.other_zid,
         whatami: osyn_out.other_whatami,
         sn_resolution: state.zenoh.resolution.get(Field::FrameSN),
+        batch_size: state.zenoh.batch_size,
         tx_initial_sn: oack_out.open_ack.initial_sn,
         is_qos: state.ext_qos.is_qos(),
         #[cfg(feature = "transport_multilink")]
         multilink: state.ext_mlink.multilink(),
         #[cfg(feature = "shared-memory")]
         is_shm: state.ext_shm.is_shm(),
+        #[cfg(feature = "shared-memory")]
+        is_shm_aligned: state.ext_shm.is_shm_aligned(),
+        #[cfg(feature = "shared-memory")]
+        is_shm_downgraded: state.ext_shm.is_shm_downgraded(),
         is_lowlatency: state.ext_lowlatency.is_lowlatency(),
+        is_resync: state.ext_resync.is_resync(),
+        is_prefix_compression: state.ext_prefix.is_prefix_compression(),
+        is_integrity: state.ext_integrity.is_integrity(),
+        is_initiator: false,
+        auth_user: zcondfeat!("transport_auth", state.ext_auth.user(), None),
+        lease: osyn_out.other_lease,
     };
 
-    let transport = step!(
-        manager
-            .init_transport_unicast(config, link.clone(), LinkUnicastDirection::Inbound)
-            .await
-    );
+    let res = manager
+        .init_transport_unicast(config, link.clone(), LinkUnicastDirection::Inbound)
+        .await;
+    if let Err((e, _)) = res.as_ref() {
+        if let Some(reason) = e.downcast_ref::<TransportCloseReason>() {
+            manager
+                .config
+                .handler
+                .rejected(&osyn_out.other_zid, osyn_out.other_whatami, reason);
+        }
+    }
+    let transport = step!(res);
 
     // Send the open_ack on the link
     step!(link
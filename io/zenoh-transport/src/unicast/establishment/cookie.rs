@@ -38,6 +38,9 @@ pub(crate) struct Cookie {
     #[cfg(feature = "transport_auth")]
     pub(crate) ext_auth: ext::auth::StateAccept,
     pub(crate) ext_lowlatency: ext::lowlatency::StateAccept,
+    pub(crate) ext_resync: ext::resync::StateAccept,
+    pub(crate) ext_prefix: ext::prefix::StateAccept,
+    pub(crate) ext_integrity: ext::integrity::StateAccept,
 }
 
 impl<W> WCodec<&Cookie, &mut W> for Zenoh080
@@ -62,6 +65,9 @@ where
         #[cfg(feature = "transport_auth")]
         self.write(&mut *writer, &x.ext_auth)?;
         self.write(&mut *writer, &x.ext_lowlatency)?;
+        self.write(&mut *writer, &x.ext_resync)?;
+        self.write(&mut *writer, &x.ext_prefix)?;
+        self.write(&mut *writer, &x.ext_integrity)?;
 
         Ok(())
     }
@@ -90,6 +96,9 @@ where
         #[cfg(feature = "transport_auth")]
         let ext_auth: ext::auth::StateAccept = self.read(&mut *reader)?;
         let ext_lowlatency: ext::lowlatency::StateAccept = self.read(&mut *reader)?;
+        let ext_resync: ext::resync::StateAccept = self.read(&mut *reader)?;
+        let ext_prefix: ext::prefix::StateAccept = self.read(&mut *reader)?;
+        let ext_integrity: ext::integrity::StateAccept = self.read(&mut *reader)?;
 
         let cookie = Cookie {
             zid,
@@ -105,6 +114,9 @@ where
             #[cfg(feature = "transport_auth")]
             ext_auth,
             ext_lowlatency,
+            ext_resync,
+            ext_prefix,
+            ext_integrity,
         };
 
         Ok(cookie)
@@ -174,6 +186,9 @@ impl Cookie {
             #[cfg(feature = "transport_auth")]
             ext_auth: ext::auth::StateAccept::rand(),
             ext_lowlatency: ext::lowlatency::StateAccept::rand(),
+            ext_resync: ext::resync::StateAccept::rand(),
+            ext_prefix: ext::prefix::StateAccept::rand(),
+            ext_integrity: ext::integrity::StateAccept::rand(),
         }
     }
 }
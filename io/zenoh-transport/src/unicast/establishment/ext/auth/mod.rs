@@ -113,7 +113,7 @@ impl Auth {
     }
 }
 
-#[cfg(feature = "test")]
+// Used to configure the authenticators of a TransportManagerBuilderUnicast without a Config
 impl Auth {
     pub const fn empty() -> Self {
         Self {
@@ -170,6 +170,15 @@ pub(crate) struct StateAccept {
 }
 
 impl StateAccept {
+    /// The user authenticated during the establishment, if any.
+    pub(crate) fn user(&self) -> Option<String> {
+        #[cfg(feature = "auth_usrpwd")]
+        if let Some(user) = self.usrpwd.as_ref().and_then(|s| s.user()) {
+            return Some(String::from_utf8_lossy(user).into_owned());
+        }
+        None
+    }
+
     #[cfg(test)]
     pub(crate) fn rand() -> Self {
         let mut rng = rand::thread_rng();
@@ -94,6 +94,18 @@ impl AuthUsrPwd {
             }
             log::debug!("{S} User-password dictionary has been configured.");
         }
+        if let Some(dict) = config.dictionary() {
+            for (user, password) in dict.iter() {
+                if user.is_empty() {
+                    bail!("{S} Invalid user-password dictionary: empty user.")
+                }
+                if password.is_empty() {
+                    bail!("{S} Invalid user-password dictionary: empty password.")
+                }
+                lookup.insert(user.as_bytes().to_owned(), password.as_bytes().to_owned());
+            }
+            log::debug!("{S} User-password dictionary has been configured.");
+        }
 
         let mut credentials: Option<(User, Password)> = None;
         if let Some(user) = config.user() {
@@ -154,6 +166,8 @@ impl StateOpen {
 #[derive(Debug, PartialEq, Eq)]
 pub(crate) struct StateAccept {
     nonce: u64,
+    // The user authenticated by the OpenSyn, not part of the cookie
+    user: Option<User>,
 }
 
 impl StateAccept {
@@ -161,7 +175,14 @@ impl StateAccept {
     where
         R: Rng + CryptoRng,
     {
-        Self { nonce: prng.gen() }
+        Self {
+            nonce: prng.gen(),
+            user: None,
+        }
+    }
+
+    pub(crate) fn user(&self) -> Option<&[u8]> {
+        self.user.as_deref()
     }
 
     #[cfg(all(test, feature = "test"))]
@@ -191,7 +212,7 @@ where
 
     fn read(self, reader: &mut R) -> Result<StateAccept, Self::Error> {
         let nonce: u64 = self.read(&mut *reader)?;
-        Ok(StateAccept { nonce })
+        Ok(StateAccept { nonce, user: None })
     }
 }
 
@@ -429,6 +450,7 @@ impl<'a> AcceptFsm for AuthUsrPwdFsm<'a> {
         if hmac != open_syn.hmac {
             bail!("{S} Invalid password.");
         }
+        state.user = Some(open_syn.user);
 
         Ok(())
     }
@@ -498,6 +520,29 @@ mod tests {
             assert!(AuthUsrPwd::from_config(&config).await.is_err());
 
             let _ = std::fs::remove_file(f1);
+
+            // Inline dictionary
+            let mut config = UsrPwdConf::default();
+            config
+                .set_dictionary(Some(
+                    [("usr2".to_owned(), "pwd2".to_owned())]
+                        .into_iter()
+                        .collect(),
+                ))
+                .unwrap();
+            let auth = AuthUsrPwd::from_config(&config).await.unwrap().unwrap();
+            assert_eq!(
+                auth.lookup.get(b"usr2".as_slice()).map(Vec::as_slice),
+                Some(b"pwd2".as_slice())
+            );
+            assert!(auth.credentials.is_none());
+            // Empty password
+            config
+                .set_dictionary(Some(
+                    [("usr2".to_owned(), String::new())].into_iter().collect(),
+                ))
+                .unwrap();
+            assert!(AuthUsrPwd::from_config(&config).await.is_err());
         }
 
         async_std::task::block_on(async {
@@ -13,9 +13,12 @@
 //
 #[cfg(feature = "transport_auth")]
 pub mod auth;
+pub(crate) mod integrity;
 pub(crate) mod lowlatency;
 #[cfg(feature = "transport_multilink")]
 pub(crate) mod multilink;
+pub(crate) mod prefix;
 pub(crate) mod qos;
+pub(crate) mod resync;
 #[cfg(feature = "shared-memory")]
 pub(crate) mod shm;
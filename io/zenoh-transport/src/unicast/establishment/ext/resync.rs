@@ -0,0 +1,194 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use crate::unicast::establishment::{AcceptFsm, OpenFsm};
+use async_trait::async_trait;
+use core::marker::PhantomData;
+use zenoh_buffers::{
+    reader::{DidntRead, Reader},
+    writer::{DidntWrite, Writer},
+};
+use zenoh_codec::{RCodec, WCodec, Zenoh080};
+use zenoh_protocol::transport::init;
+use zenoh_result::Error as ZError;
+
+// Extension Fsm
+pub(crate) struct ResyncFsm<'a> {
+    _a: PhantomData<&'a ()>,
+}
+
+impl<'a> ResyncFsm<'a> {
+    pub(crate) const fn new() -> Self {
+        Self { _a: PhantomData }
+    }
+}
+
+/*************************************/
+/*              OPEN                 */
+/*************************************/
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct StateOpen {
+    is_resync: bool,
+}
+
+impl StateOpen {
+    pub(crate) const fn new(is_resync: bool) -> Self {
+        Self { is_resync }
+    }
+
+    pub(crate) const fn is_resync(&self) -> bool {
+        self.is_resync
+    }
+}
+
+#[async_trait]
+impl<'a> OpenFsm for ResyncFsm<'a> {
+    type Error = ZError;
+
+    type SendInitSynIn = &'a StateOpen;
+    type SendInitSynOut = Option<init::ext::Resync>;
+    async fn send_init_syn(
+        &self,
+        state: Self::SendInitSynIn,
+    ) -> Result<Self::SendInitSynOut, Self::Error> {
+        let output = state.is_resync.then_some(init::ext::Resync::new());
+        Ok(output)
+    }
+
+    type RecvInitAckIn = (&'a mut StateOpen, Option<init::ext::Resync>);
+    type RecvInitAckOut = ();
+    async fn recv_init_ack(
+        &self,
+        input: Self::RecvInitAckIn,
+    ) -> Result<Self::RecvInitAckOut, Self::Error> {
+        let (state, other_ext) = input;
+        state.is_resync &= other_ext.is_some();
+        Ok(())
+    }
+
+    // The negotiation is complete after the INIT exchange
+    type SendOpenSynIn = &'a StateOpen;
+    type SendOpenSynOut = ();
+    async fn send_open_syn(
+        &self,
+        _state: Self::SendOpenSynIn,
+    ) -> Result<Self::SendOpenSynOut, Self::Error> {
+        Ok(())
+    }
+
+    type RecvOpenAckIn = &'a mut StateOpen;
+    type RecvOpenAckOut = ();
+    async fn recv_open_ack(
+        &self,
+        _state: Self::RecvOpenAckIn,
+    ) -> Result<Self::RecvOpenAckOut, Self::Error> {
+        Ok(())
+    }
+}
+
+/*************************************/
+/*            ACCEPT                 */
+/*************************************/
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct StateAccept {
+    is_resync: bool,
+}
+
+impl StateAccept {
+    pub(crate) const fn new(is_resync: bool) -> Self {
+        Self { is_resync }
+    }
+
+    pub(crate) const fn is_resync(&self) -> bool {
+        self.is_resync
+    }
+
+    #[cfg(test)]
+    pub(crate) fn rand() -> Self {
+        use rand::Rng;
+        let mut rng = rand::thread_rng();
+        Self::new(rng.gen_bool(0.5))
+    }
+}
+
+// Codec
+impl<W> WCodec<&StateAccept, &mut W> for Zenoh080
+where
+    W: Writer,
+{
+    type Output = Result<(), DidntWrite>;
+
+    fn write(self, writer: &mut W, x: &StateAccept) -> Self::Output {
+        let is_resync = u8::from(x.is_resync);
+        self.write(&mut *writer, is_resync)?;
+        Ok(())
+    }
+}
+
+impl<R> RCodec<StateAccept, &mut R> for Zenoh080
+where
+    R: Reader,
+{
+    type Error = DidntRead;
+
+    fn read(self, reader: &mut R) -> Result<StateAccept, Self::Error> {
+        let is_resync: u8 = self.read(&mut *reader)?;
+        let is_resync = is_resync == 1;
+        Ok(StateAccept { is_resync })
+    }
+}
+
+#[async_trait]
+impl<'a> AcceptFsm for ResyncFsm<'a> {
+    type Error = ZError;
+
+    type RecvInitSynIn = (&'a mut StateAccept, Option<init::ext::Resync>);
+    type RecvInitSynOut = ();
+    async fn recv_init_syn(
+        &self,
+        input: Self::RecvInitSynIn,
+    ) -> Result<Self::RecvInitSynOut, Self::Error> {
+        let (state, other_ext) = input;
+        state.is_resync &= other_ext.is_some();
+        Ok(())
+    }
+
+    type SendInitAckIn = &'a StateAccept;
+    type SendInitAckOut = Option<init::ext::Resync>;
+    async fn send_init_ack(
+        &self,
+        state: Self::SendInitAckIn,
+    ) -> Result<Self::SendInitAckOut, Self::Error> {
+        let output = state.is_resync.then_some(init::ext::Resync::new());
+        Ok(output)
+    }
+
+    // The negotiation is complete after the INIT exchange
+    type RecvOpenSynIn = &'a mut StateAccept;
+    type RecvOpenSynOut = ();
+    async fn recv_open_syn(
+        &self,
+        _state: Self::RecvOpenSynIn,
+    ) -> Result<Self::RecvOpenSynOut, Self::Error> {
+        Ok(())
+    }
+
+    type SendOpenAckIn = &'a StateAccept;
+    type SendOpenAckOut = ();
+    async fn send_open_ack(
+        &self,
+        _state: Self::SendOpenAckIn,
+    ) -> Result<Self::SendOpenAckOut, Self::Error> {
+        Ok(())
+    }
+}
@@ -16,13 +16,11 @@ use crate::unicast::{
     shared_memory_unicast::{Challenge, SharedMemoryUnicast},
 };
 use async_trait::async_trait;
-use std::convert::TryInto;
 use zenoh_buffers::{
     reader::{DidntRead, HasReader, Reader},
     writer::{DidntWrite, HasWriter, Writer},
 };
 use zenoh_codec::{RCodec, WCodec, Zenoh080};
-use zenoh_core::zasyncwrite;
 use zenoh_protocol::transport::{init, open};
 use zenoh_result::{zerror, Error as ZError};
 use zenoh_shm::SharedMemoryBufInfo;
@@ -34,8 +32,11 @@ use zenoh_shm::SharedMemoryBufInfo;
 /// +-+-+-+-+-+-+-+-+
 /// ~ ShmMemBufInfo ~
 /// +---------------+
+/// %   alignment   % -- Optional, the nodes ignoring it don't carry the alignment of the buffers
+/// +---------------+
 pub(crate) struct InitSyn {
     pub(crate) alice_info: SharedMemoryBufInfo,
+    pub(crate) alignment: bool,
 }
 
 // Codec
@@ -47,6 +48,7 @@ where
 
     fn write(self, writer: &mut W, x: &InitSyn) -> Self::Output {
         self.write(&mut *writer, &x.alice_info)?;
+        self.write(&mut *writer, u8::from(x.alignment))?;
         Ok(())
     }
 }
@@ -59,7 +61,11 @@ where
 
     fn read(self, reader: &mut R) -> Result<InitSyn, Self::Error> {
         let alice_info: SharedMemoryBufInfo = self.read(&mut *reader)?;
-        Ok(InitSyn { alice_info })
+        let alignment = read_alignment(self, &mut *reader)?;
+        Ok(InitSyn {
+            alice_info,
+            alignment,
+        })
     }
 }
 
@@ -72,9 +78,12 @@ where
 /// +---------------+
 /// ~ ShmMemBufInfo ~
 /// +---------------+
+/// %   alignment   % -- Optional, set only if Alice offered it
+/// +---------------+
 struct InitAck {
     alice_challenge: u64,
     bob_info: SharedMemoryBufInfo,
+    alignment: bool,
 }
 
 impl<W> WCodec<&InitAck, &mut W> for Zenoh080
@@ -86,6 +95,7 @@ where
     fn write(self, writer: &mut W, x: &InitAck) -> Self::Output {
         self.write(&mut *writer, x.alice_challenge)?;
         self.write(&mut *writer, &x.bob_info)?;
+        self.write(&mut *writer, u8::from(x.alignment))?;
         Ok(())
     }
 }
@@ -99,13 +109,27 @@ where
     fn read(self, reader: &mut R) -> Result<InitAck, Self::Error> {
         let alice_challenge: u64 = self.read(&mut *reader)?;
         let bob_info: SharedMemoryBufInfo = self.read(&mut *reader)?;
+        let alignment = read_alignment(self, &mut *reader)?;
         Ok(InitAck {
             alice_challenge,
             bob_info,
+            alignment,
         })
     }
 }
 
+// The nodes not supporting the alignment of the buffers don't append it to the extension
+fn read_alignment<R>(codec: Zenoh080, reader: &mut R) -> Result<bool, DidntRead>
+where
+    R: Reader,
+{
+    if !reader.can_read() {
+        return Ok(false);
+    }
+    let alignment: u8 = codec.read(&mut *reader)?;
+    Ok(alignment == 1)
+}
+
 /*************************************/
 /*             OpenSyn               */
 /*************************************/
@@ -123,6 +147,11 @@ where
 /// +---------------+
 
 // Extension Fsm
+//
+// Each node writes a fresh nonce in its shared memory and sends the buffer to the other node,
+// which must read and echo it back. When the challenge can't be completed while both nodes enable
+// the shared memory, e.g. when they don't run on the same host, the transport is downgraded to
+// not use it instead of failing.
 pub(crate) struct ShmFsm<'a> {
     inner: &'a SharedMemoryUnicast,
 }
@@ -139,23 +168,48 @@ impl<'a> ShmFsm<'a> {
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub(crate) struct StateOpen {
     is_shm: bool,
+    is_shm_aligned: bool,
+    is_shm_downgraded: bool,
+    // The challenge sent to the other node
+    challenge: Challenge,
 }
 
 impl StateOpen {
     pub(crate) const fn new(is_shm: bool) -> Self {
-        Self { is_shm }
+        Self {
+            is_shm,
+            is_shm_aligned: false,
+            is_shm_downgraded: false,
+            challenge: 0,
+        }
     }
 
     pub(crate) const fn is_shm(&self) -> bool {
         self.is_shm
     }
+
+    /// Whether both nodes carry the alignment of the buffers in their descriptors.
+    pub(crate) const fn is_shm_aligned(&self) -> bool {
+        self.is_shm && self.is_shm_aligned
+    }
+
+    /// Whether the shared memory was offered but its challenge couldn't be completed.
+    pub(crate) const fn is_shm_downgraded(&self) -> bool {
+        self.is_shm_downgraded
+    }
+
+    fn downgrade(&mut self, reason: &str) {
+        log::debug!("Shm extension - Downgraded to not use the shared memory: {reason}");
+        self.is_shm = false;
+        self.is_shm_downgraded = true;
+    }
 }
 
 #[async_trait]
 impl<'a> OpenFsm for ShmFsm<'a> {
     type Error = ZError;
 
-    type SendInitSynIn = &'a StateOpen;
+    type SendInitSynIn = &'a mut StateOpen;
     type SendInitSynOut = Option<init::ext::Shm>;
     async fn send_init_syn(
         &self,
@@ -167,8 +221,21 @@ impl<'a> OpenFsm for ShmFsm<'a> {
             return Ok(None);
         }
 
+        // Not offering the shared memory is not a downgrade: the other node can't tell it apart
+        // from a node not enabling it
+        let (challenge, alice_info) = match self.inner.new_challenge() {
+            Ok(challenge) => challenge,
+            Err(e) => {
+                log::debug!("{} {}", S, e);
+                state.is_shm = false;
+                return Ok(None);
+            }
+        };
+        state.challenge = challenge;
+
         let init_syn = InitSyn {
-            alice_info: self.inner.challenge.info.clone(),
+            alice_info,
+            alignment: true,
         };
 
         let codec = Zenoh080::new();
@@ -194,8 +261,11 @@ impl<'a> OpenFsm for ShmFsm<'a> {
             return Ok(0);
         }
 
+        // Bob has read Alice challenge, or won't
+        self.inner.release(state.challenge);
+
         let Some(ext) = ext.take() else {
-            state.is_shm = false;
+            state.downgrade("no challenge answer");
             return Ok(0);
         };
 
@@ -204,53 +274,32 @@ impl<'a> OpenFsm for ShmFsm<'a> {
         let mut reader = ext.value.reader();
         let Ok(init_ack): Result<InitAck, _> = codec.read(&mut reader) else {
             log::trace!("{} Decoding error.", S);
-            state.is_shm = false;
+            state.downgrade("invalid challenge answer");
             return Ok(0);
         };
 
-        // Alice challenge as seen by Alice
-        let bytes: [u8; std::mem::size_of::<Challenge>()] = self
-            .inner
-            .challenge
-            .as_slice()
-            .try_into()
-            .map_err(|e| zerror!("{}", e))?;
-        let challenge = u64::from_le_bytes(bytes);
-
         // Verify that Bob has correctly read Alice challenge
-        if challenge != init_ack.alice_challenge {
+        if state.challenge != init_ack.alice_challenge {
             log::trace!(
                 "{} Challenge mismatch: {} != {}.",
                 S,
                 init_ack.alice_challenge,
-                challenge
+                state.challenge
             );
-            state.is_shm = false;
+            state.downgrade("challenge mismatch");
             return Ok(0);
         }
+        state.is_shm_aligned = init_ack.alignment;
 
-        // Read Bob's SharedMemoryBuf
-        let shm_buff = match zasyncwrite!(self.inner.reader).read_shmbuf(&init_ack.bob_info) {
-            Ok(buff) => buff,
+        // Bob challenge as seen by Alice
+        match self.inner.read_challenge(&init_ack.bob_info).await {
+            Ok(bob_challenge) => Ok(bob_challenge),
             Err(e) => {
                 log::trace!("{} {}", S, e);
-                state.is_shm = false;
-                return Ok(0);
+                state.downgrade("failed to read the challenge");
+                Ok(0)
             }
-        };
-
-        // Bob challenge as seen by Alice
-        let bytes: [u8; std::mem::size_of::<Challenge>()] = match shm_buff.as_slice().try_into() {
-            Ok(bytes) => bytes,
-            Err(_) => {
-                log::trace!("{} Failed to read remote Shm.", S);
-                state.is_shm = false;
-                return Ok(0);
-            }
-        };
-        let bob_challenge = u64::from_le_bytes(bytes);
-
-        Ok(bob_challenge)
+        }
     }
 
     type SendOpenSynIn = (&'a StateOpen, Self::RecvInitAckOut);
@@ -283,13 +332,13 @@ impl<'a> OpenFsm for ShmFsm<'a> {
         }
 
         let Some(ext) = ext.take() else {
-            state.is_shm = false;
+            state.downgrade("challenge not acknowledged");
             return Ok(());
         };
 
         if ext.value != 1 {
             log::trace!("{} Invalid value.", S);
-            state.is_shm = false;
+            state.downgrade("challenge not acknowledged");
             return Ok(());
         }
 
@@ -305,22 +354,52 @@ impl<'a> OpenFsm for ShmFsm<'a> {
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub(crate) struct StateAccept {
     is_shm: bool,
+    is_shm_aligned: bool,
+    is_shm_downgraded: bool,
+    // The challenge sent to the other node
+    challenge: Challenge,
 }
 
 impl StateAccept {
     pub(crate) const fn new(is_shm: bool) -> Self {
-        Self { is_shm }
+        Self {
+            is_shm,
+            is_shm_aligned: false,
+            is_shm_downgraded: false,
+            challenge: 0,
+        }
     }
 
     pub(crate) const fn is_shm(&self) -> bool {
         self.is_shm
     }
 
+    /// Whether both nodes carry the alignment of the buffers in their descriptors.
+    pub(crate) const fn is_shm_aligned(&self) -> bool {
+        self.is_shm && self.is_shm_aligned
+    }
+
+    /// Whether the shared memory was offered but its challenge couldn't be completed.
+    pub(crate) const fn is_shm_downgraded(&self) -> bool {
+        self.is_shm_downgraded
+    }
+
+    fn downgrade(&mut self, reason: &str) {
+        log::debug!("Shm extension - Downgraded to not use the shared memory: {reason}");
+        self.is_shm = false;
+        self.is_shm_downgraded = true;
+    }
+
     #[cfg(test)]
     pub(crate) fn rand() -> Self {
         use rand::Rng;
         let mut rng = rand::thread_rng();
-        Self::new(rng.gen_bool(0.5))
+        Self {
+            is_shm: rng.gen_bool(0.5),
+            is_shm_aligned: rng.gen_bool(0.5),
+            is_shm_downgraded: rng.gen_bool(0.5),
+            challenge: rng.gen(),
+        }
     }
 }
 
@@ -334,6 +413,11 @@ where
     fn write(self, writer: &mut W, x: &StateAccept) -> Self::Output {
         let is_shm = u8::from(x.is_shm);
         self.write(&mut *writer, is_shm)?;
+        let is_shm_aligned = u8::from(x.is_shm_aligned);
+        self.write(&mut *writer, is_shm_aligned)?;
+        let is_shm_downgraded = u8::from(x.is_shm_downgraded);
+        self.write(&mut *writer, is_shm_downgraded)?;
+        self.write(&mut *writer, x.challenge)?;
         Ok(())
     }
 }
@@ -347,7 +431,17 @@ where
     fn read(self, reader: &mut R) -> Result<StateAccept, Self::Error> {
         let is_shm: u8 = self.read(&mut *reader)?;
         let is_shm = is_shm == 1;
-        Ok(StateAccept { is_shm })
+        let is_shm_aligned: u8 = self.read(&mut *reader)?;
+        let is_shm_aligned = is_shm_aligned == 1;
+        let is_shm_downgraded: u8 = self.read(&mut *reader)?;
+        let is_shm_downgraded = is_shm_downgraded == 1;
+        let challenge: Challenge = self.read(&mut *reader)?;
+        Ok(StateAccept {
+            is_shm,
+            is_shm_aligned,
+            is_shm_downgraded,
+            challenge,
+        })
     }
 }
 
@@ -378,35 +472,23 @@ impl<'a> AcceptFsm for ShmFsm<'a> {
         let mut reader = ext.value.reader();
         let Ok(init_syn): Result<InitSyn, _> = codec.read(&mut reader) else {
             log::trace!("{} Decoding error.", S);
-            state.is_shm = false;
+            state.downgrade("invalid challenge");
             return Ok(0);
         };
+        state.is_shm_aligned = init_syn.alignment;
 
-        // Read Alice's SharedMemoryBuf
-        let shm_buff = match zasyncwrite!(self.inner.reader).read_shmbuf(&init_syn.alice_info) {
-            Ok(buff) => buff,
+        // Alice challenge as seen by Bob
+        match self.inner.read_challenge(&init_syn.alice_info).await {
+            Ok(alice_challenge) => Ok(alice_challenge),
             Err(e) => {
                 log::trace!("{} {}", S, e);
-                state.is_shm = false;
-                return Ok(0);
+                state.downgrade("failed to read the challenge");
+                Ok(0)
             }
-        };
-
-        // Alice challenge as seen by Bob
-        let bytes: [u8; std::mem::size_of::<Challenge>()] = match shm_buff.as_slice().try_into() {
-            Ok(bytes) => bytes,
-            Err(_) => {
-                log::trace!("{} Failed to read remote Shm.", S);
-                state.is_shm = false;
-                return Ok(0);
-            }
-        };
-        let alice_challenge = u64::from_le_bytes(bytes);
-
-        Ok(alice_challenge)
+        }
     }
 
-    type SendInitAckIn = (&'a StateAccept, Self::RecvInitSynOut);
+    type SendInitAckIn = (&'a mut StateAccept, Self::RecvInitSynOut);
     type SendInitAckOut = Option<init::ext::Shm>;
     async fn send_init_ack(
         &self,
@@ -419,9 +501,21 @@ impl<'a> AcceptFsm for ShmFsm<'a> {
             return Ok(None);
         }
 
+        // The challenge is carried by the cookie until the answer of Alice
+        let (challenge, bob_info) = match self.inner.new_challenge() {
+            Ok(challenge) => challenge,
+            Err(e) => {
+                log::debug!("{} {}", S, e);
+                state.downgrade("failed to create a challenge");
+                return Ok(None);
+            }
+        };
+        state.challenge = challenge;
+
         let init_syn = InitAck {
             alice_challenge,
-            bob_info: self.inner.challenge.info.clone(),
+            bob_info,
+            alignment: state.is_shm_aligned,
         };
 
         let codec = Zenoh080::new();
@@ -447,30 +541,24 @@ impl<'a> AcceptFsm for ShmFsm<'a> {
             return Ok(());
         }
 
+        // Alice has read Bob challenge, or won't
+        self.inner.release(state.challenge);
+
         let Some(ext) = ext.take() else {
-            state.is_shm = false;
+            state.downgrade("no challenge answer");
             return Ok(());
         };
 
-        // Bob challenge as seen by Bob
-        let bytes: [u8; std::mem::size_of::<Challenge>()] = self
-            .inner
-            .challenge
-            .as_slice()
-            .try_into()
-            .map_err(|e| zerror!("{}", e))?;
-        let challenge = u64::from_le_bytes(bytes);
-
         // Verify that Alice has correctly read Bob challenge
-        let bob_challnge = ext.value;
-        if challenge != bob_challnge {
+        let bob_challenge = ext.value;
+        if state.challenge != bob_challenge {
             log::trace!(
                 "{} Challenge mismatch: {} != {}.",
                 S,
-                bob_challnge,
-                challenge
+                bob_challenge,
+                state.challenge
             );
-            state.is_shm = false;
+            state.downgrade("challenge mismatch");
             return Ok(());
         }
 
@@ -493,3 +581,196 @@ impl<'a> AcceptFsm for ShmFsm<'a> {
         Ok(Some(open::ext::Shm::new(1)))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Mocks a link between two hosts: the buffers sent by the other node can't be read
+    fn on_remote_host(info: &mut SharedMemoryBufInfo) {
+        info.shm_manager = format!("{}.remote", info.shm_manager);
+    }
+
+    fn remote_init_syn(ext: Option<init::ext::Shm>) -> Option<init::ext::Shm> {
+        ext.map(|ext| {
+            let mut init_syn: InitSyn = Zenoh080::new().read(&mut ext.value.reader()).unwrap();
+            on_remote_host(&mut init_syn.alice_info);
+            let mut buff = vec![];
+            let mut writer = buff.writer();
+            Zenoh080::new().write(&mut writer, &init_syn).unwrap();
+            init::ext::Shm::new(buff.into())
+        })
+    }
+
+    fn remote_init_ack(ext: Option<init::ext::Shm>) -> Option<init::ext::Shm> {
+        ext.map(|ext| {
+            let mut init_ack: InitAck = Zenoh080::new().read(&mut ext.value.reader()).unwrap();
+            on_remote_host(&mut init_ack.bob_info);
+            let mut buff = vec![];
+            let mut writer = buff.writer();
+            Zenoh080::new().write(&mut writer, &init_ack).unwrap();
+            init::ext::Shm::new(buff.into())
+        })
+    }
+
+    // Mocks a node not supporting the alignment: the extension ends after the buffer info
+    fn legacy_init_syn(ext: Option<init::ext::Shm>) -> Option<init::ext::Shm> {
+        ext.map(|ext| {
+            let init_syn: InitSyn = Zenoh080::new().read(&mut ext.value.reader()).unwrap();
+            let mut buff = vec![];
+            let mut writer = buff.writer();
+            Zenoh080::new()
+                .write(&mut writer, &init_syn.alice_info)
+                .unwrap();
+            init::ext::Shm::new(buff.into())
+        })
+    }
+
+    fn legacy_init_ack(ext: Option<init::ext::Shm>) -> Option<init::ext::Shm> {
+        ext.map(|ext| {
+            let init_ack: InitAck = Zenoh080::new().read(&mut ext.value.reader()).unwrap();
+            let mut buff = vec![];
+            let mut writer = buff.writer();
+            Zenoh080::new()
+                .write(&mut writer, init_ack.alice_challenge)
+                .unwrap();
+            Zenoh080::new()
+                .write(&mut writer, &init_ack.bob_info)
+                .unwrap();
+            init::ext::Shm::new(buff.into())
+        })
+    }
+
+    // Alice opens a link to Bob, the messages of Alice or Bob being sent from another host
+    fn establish(
+        alice: &SharedMemoryUnicast,
+        bob: &SharedMemoryUnicast,
+        bob_is_shm: bool,
+        alice_remote: bool,
+        bob_remote: bool,
+    ) -> (StateOpen, StateAccept) {
+        establish_with(
+            alice,
+            bob,
+            bob_is_shm,
+            alice_remote,
+            bob_remote,
+            (false, false),
+        )
+    }
+
+    // Same as establish, Alice or Bob not supporting the alignment of the buffers
+    fn establish_with(
+        alice: &SharedMemoryUnicast,
+        bob: &SharedMemoryUnicast,
+        bob_is_shm: bool,
+        alice_remote: bool,
+        bob_remote: bool,
+        (alice_legacy, bob_legacy): (bool, bool),
+    ) -> (StateOpen, StateAccept) {
+        async_std::task::block_on(async {
+            let alice_fsm = ShmFsm::new(alice);
+            let bob_fsm = ShmFsm::new(bob);
+            let mut alice_state = StateOpen::new(true);
+            let mut bob_state = StateAccept::new(bob_is_shm);
+
+            let mut init_syn = alice_fsm.send_init_syn(&mut alice_state).await.unwrap();
+            if alice_remote {
+                init_syn = remote_init_syn(init_syn);
+            }
+            if alice_legacy {
+                init_syn = legacy_init_syn(init_syn);
+            }
+            let alice_challenge = bob_fsm
+                .recv_init_syn((&mut bob_state, init_syn))
+                .await
+                .unwrap();
+            let mut init_ack = bob_fsm
+                .send_init_ack((&mut bob_state, alice_challenge))
+                .await
+                .unwrap();
+            if bob_remote {
+                init_ack = remote_init_ack(init_ack);
+            }
+            if bob_legacy {
+                init_ack = legacy_init_ack(init_ack);
+            }
+            let bob_challenge = alice_fsm
+                .recv_init_ack((&mut alice_state, init_ack))
+                .await
+                .unwrap();
+            let open_syn = alice_fsm
+                .send_open_syn((&alice_state, bob_challenge))
+                .await
+                .unwrap();
+            bob_fsm
+                .recv_open_syn((&mut bob_state, open_syn))
+                .await
+                .unwrap();
+            let open_ack = bob_fsm.send_open_ack(&mut bob_state).await.unwrap();
+            alice_fsm
+                .recv_open_ack((&mut alice_state, open_ack))
+                .await
+                .unwrap();
+            (alice_state, bob_state)
+        })
+    }
+
+    #[test]
+    fn shm_same_host() {
+        let alice = SharedMemoryUnicast::make().unwrap();
+        let bob = SharedMemoryUnicast::make().unwrap();
+        let (alice_state, bob_state) = establish(&alice, &bob, true, false, false);
+        assert!(alice_state.is_shm() && !alice_state.is_shm_downgraded());
+        assert!(bob_state.is_shm() && !bob_state.is_shm_downgraded());
+        assert!(alice_state.is_shm_aligned() && bob_state.is_shm_aligned());
+        // The challenges are released once answered
+        assert_eq!(alice.pending(), 0);
+        assert_eq!(bob.pending(), 0);
+    }
+
+    #[test]
+    fn shm_remote_host() {
+        let alice = SharedMemoryUnicast::make().unwrap();
+        let bob = SharedMemoryUnicast::make().unwrap();
+
+        // Bob can't read the challenge of Alice, both nodes fall back to not using shared memory
+        for (alice_remote, bob_remote) in [(true, false), (false, true), (true, true)] {
+            let (alice_state, bob_state) = establish(&alice, &bob, true, alice_remote, bob_remote);
+            assert!(!alice_state.is_shm() && alice_state.is_shm_downgraded());
+            assert!(!bob_state.is_shm() && bob_state.is_shm_downgraded());
+        }
+        assert_eq!(alice.pending(), 0);
+        assert_eq!(bob.pending(), 0);
+
+        // A node not enabling shared memory isn't downgraded
+        let (alice_state, bob_state) = establish(&alice, &bob, false, false, false);
+        assert!(!alice_state.is_shm());
+        assert!(!bob_state.is_shm() && !bob_state.is_shm_downgraded());
+        assert_eq!(alice.pending(), 0);
+    }
+
+    #[test]
+    fn shm_alignment_negotiation() {
+        let alice = SharedMemoryUnicast::make().unwrap();
+        let bob = SharedMemoryUnicast::make().unwrap();
+
+        // The shared memory is still used with a node not supporting the alignment, but the
+        // descriptors of the buffers keep their original layout
+        let (alice_state, bob_state) =
+            establish_with(&alice, &bob, true, false, false, (true, false));
+        assert!(alice_state.is_shm() && bob_state.is_shm());
+        assert!(!bob_state.is_shm_aligned());
+        // Only the answer of Bob is mocked: Alice doesn't carry the alignment
+        let (alice_state, bob_state) =
+            establish_with(&alice, &bob, true, false, false, (false, true));
+        assert!(alice_state.is_shm() && bob_state.is_shm());
+        assert!(!alice_state.is_shm_aligned());
+
+        // The alignment is never carried without the shared memory
+        let (alice_state, bob_state) = establish(&alice, &bob, false, false, false);
+        assert!(!alice_state.is_shm_aligned() && !bob_state.is_shm_aligned());
+        assert_eq!(alice.pending(), 0);
+        assert_eq!(bob.pending(), 0);
+    }
+}
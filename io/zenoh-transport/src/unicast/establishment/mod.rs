@@ -17,7 +17,7 @@ pub mod ext;
 pub(crate) mod open;
 
 use super::{TransportPeer, TransportUnicast};
-use crate::{common::seq_num, TransportManager};
+use crate::{common::seq_num, TransportCloseReason, TransportManager};
 use async_trait::async_trait;
 use cookie::*;
 use sha3::{
@@ -30,7 +30,7 @@ use zenoh_protocol::{
     core::{Field, Resolution, ZenohId},
     transport::{BatchSize, Close, TransportMessage, TransportSn},
 };
-use zenoh_result::ZResult;
+use zenoh_result::{Error, ZResult};
 
 /*************************************/
 /*             TRAITS                */
@@ -116,14 +116,21 @@ pub(super) fn compute_sn(zid1: ZenohId, zid2: ZenohId, resolution: Resolution) -
     TransportSn::from_le_bytes(array) & seq_num::get_mask(resolution.get(Field::FrameSN))
 }
 
-pub(super) async fn close_link(link: &LinkUnicast, reason: Option<u8>) {
+pub(super) async fn close_link(link: &LinkUnicast, reason: Option<u8>, error: &Error) {
     if let Some(reason) = reason {
-        // Build the close message
-        let message: TransportMessage = Close {
+        // Build the close message, only the reasons given on purpose being detailed to the peer
+        let mut close = Close {
             reason,
             session: false,
+            ext_detail: None,
+        };
+        if let Some(detail) = error
+            .downcast_ref::<TransportCloseReason>()
+            .and_then(|r| r.detail.clone())
+        {
+            close = close.with_detail(detail);
         }
-        .into();
+        let message: TransportMessage = close.into();
         // Send the close message on the link
         let _ = link.send(&message).await;
     }
@@ -147,11 +154,10 @@ pub(super) async fn finalize_transport(
     let transport = input.transport.get_inner()?;
 
     // Start the TX loop
-    let keep_alive = manager.config.unicast.lease / manager.config.unicast.keep_alive as u32;
     transport.start_tx(
         link,
         &manager.tx_executor,
-        keep_alive,
+        manager.config.unicast.keep_alive,
         input.agreed_batch_size,
     )?;
 
@@ -164,6 +170,7 @@ pub(super) async fn finalize_transport(
             whatami: transport.get_whatami(),
             links: vec![Link::from(link)],
             is_qos: transport.is_qos(),
+            is_initiator: transport.get_config().is_initiator,
             #[cfg(feature = "shared-memory")]
             is_shm: transport.is_shm(),
         };
@@ -17,7 +17,7 @@ use crate::{
     unicast::establishment::{
         close_link, compute_sn, ext, finalize_transport, InputFinalize, OpenFsm,
     },
-    TransportConfigUnicast, TransportManager, TransportUnicast,
+    TransportCloseReason, TransportConfigUnicast, TransportManager, TransportUnicast,
 };
 use async_trait::async_trait;
 use std::time::Duration;
@@ -29,8 +29,9 @@ use zenoh_link::{LinkUnicast, LinkUnicastDirection};
 use zenoh_protocol::{
     core::{Field, Resolution, WhatAmI, ZenohId},
     transport::{
-        batch_size, close, BatchSize, Close, InitSyn, OpenSyn, TransportBody, TransportMessage,
-        TransportSn,
+        batch_size,
+        close::{self, CloseReason},
+        BatchSize, InitSyn, OpenSyn, TransportBody, TransportMessage, TransportSn,
     },
 };
 use zenoh_result::ZResult;
@@ -52,6 +53,9 @@ struct State {
     #[cfg(feature = "transport_auth")]
     ext_auth: ext::auth::StateOpen,
     ext_lowlatency: ext::lowlatency::StateOpen,
+    ext_resync: ext::resync::StateOpen,
+    ext_prefix: ext::prefix::StateOpen,
+    ext_integrity: ext::integrity::StateOpen,
 }
 
 // InitSyn
@@ -101,6 +105,9 @@ struct OpenLink<'a> {
     #[cfg(feature = "transport_auth")]
     ext_auth: ext::auth::AuthFsm<'a>,
     ext_lowlatency: ext::lowlatency::LowLatencyFsm<'a>,
+    ext_resync: ext::resync::ResyncFsm<'a>,
+    ext_prefix: ext::prefix::PrefixFsm<'a>,
+    ext_integrity: ext::integrity::IntegrityFsm<'a>,
 }
 
 #[async_trait]
@@ -129,11 +136,32 @@ impl<'a> OpenFsm for OpenLink<'a> {
             .await
             .map_err(|e| (e, Some(close::reason::GENERIC)))?;
 
+        // Extension Resync
+        let ext_resync = self
+            .ext_resync
+            .send_init_syn(&state.ext_resync)
+            .await
+            .map_err(|e| (e, Some(close::reason::GENERIC)))?;
+
+        // Extension PrefixCompression
+        let ext_prefix = self
+            .ext_prefix
+            .send_init_syn(&state.ext_prefix)
+            .await
+            .map_err(|e| (e, Some(close::reason::GENERIC)))?;
+
+        // Extension Integrity
+        let ext_integrity = self
+            .ext_integrity
+            .send_init_syn(&state.ext_integrity)
+            .await
+            .map_err(|e| (e, Some(close::reason::GENERIC)))?;
+
         // Extension Shm
         let ext_shm = zcondfeat!(
             "shared-memory",
             self.ext_shm
-                .send_init_syn(&state.ext_shm)
+                .send_init_syn(&mut state.ext_shm)
                 .await
                 .map_err(|e| (e, Some(close::reason::GENERIC)))?,
             None
@@ -170,6 +198,9 @@ impl<'a> OpenFsm for OpenLink<'a> {
             ext_auth,
             ext_mlink,
             ext_lowlatency,
+            ext_resync,
+            ext_prefix,
+            ext_integrity,
         }
         .into();
 
@@ -196,17 +227,19 @@ impl<'a> OpenFsm for OpenLink<'a> {
 
         let init_ack = match msg.body {
             TransportBody::InitAck(init_ack) => init_ack,
-            TransportBody::Close(Close { reason, .. }) => {
+            TransportBody::Close(rejection) => {
+                // The reason of the rejection is returned for the application to act on it
+                let reason = TransportCloseReason::remote(&rejection);
                 let e = zerror!(
-                    "Received a close message (reason {}) in response to an InitSyn on: {}",
-                    close::reason_to_str(reason),
+                    "Received a close message ({}) in response to an InitSyn on: {}",
+                    reason,
                     self.link,
                 );
-                match reason {
+                match rejection.reason {
                     close::reason::MAX_LINKS => log::debug!("{}", e),
                     _ => log::error!("{}", e),
                 }
-                return Err((e.into(), None));
+                return Err((reason.into(), None));
             }
             _ => {
                 let e = zerror!(
@@ -273,6 +306,24 @@ impl<'a> OpenFsm for OpenLink<'a> {
             .await
             .map_err(|e| (e, Some(close::reason::GENERIC)))?;
 
+        // Extension Resync
+        self.ext_resync
+            .recv_init_ack((&mut state.ext_resync, init_ack.ext_resync))
+            .await
+            .map_err(|e| (e, Some(close::reason::GENERIC)))?;
+
+        // Extension PrefixCompression
+        self.ext_prefix
+            .recv_init_ack((&mut state.ext_prefix, init_ack.ext_prefix))
+            .await
+            .map_err(|e| (e, Some(close::reason::GENERIC)))?;
+
+        // Extension Integrity
+        self.ext_integrity
+            .recv_init_ack((&mut state.ext_integrity, init_ack.ext_integrity))
+            .await
+            .map_err(|e| (e, Some(close::reason::GENERIC)))?;
+
         // Extension Shm
         #[cfg(feature = "shared-memory")]
         let shm_challenge = self
@@ -286,7 +337,7 @@ impl<'a> OpenFsm for OpenLink<'a> {
         self.ext_auth
             .recv_init_ack((&mut state.ext_auth, init_ack.ext_auth))
             .await
-            .map_err(|e| (e, Some(close::reason::GENERIC)))?;
+            .map_err(|e| (e, Some(close::reason::UNAUTHORIZED)))?;
 
         // Extension MultiLink
         #[cfg(feature = "transport_multilink")]
@@ -395,17 +446,19 @@ impl<'a> OpenFsm for OpenLink<'a> {
 
         let open_ack = match msg.body {
             TransportBody::OpenAck(open_ack) => open_ack,
-            TransportBody::Close(Close { reason, .. }) => {
+            TransportBody::Close(rejection) => {
+                // The reason of the rejection is returned for the application to act on it
+                let reason = TransportCloseReason::remote(&rejection);
                 let e = zerror!(
-                    "Received a close message (reason {}) in response to an OpenSyn on: {:?}",
-                    close::reason_to_str(reason),
+                    "Received a close message ({}) in response to an OpenSyn on: {:?}",
+                    reason,
                     self.link,
                 );
-                match reason {
+                match rejection.reason {
                     close::reason::MAX_LINKS => log::debug!("{}", e),
                     _ => log::error!("{}", e),
                 }
-                return Err((e.into(), None));
+                return Err((reason.into(), None));
             }
             _ => {
                 let e = zerror!(
@@ -462,6 +515,7 @@ impl<'a> OpenFsm for OpenLink<'a> {
 pub(crate) async fn open_link(
     link: &LinkUnicast,
     manager: &TransportManager,
+    peer: Option<&ZenohId>,
 ) -> ZResult<TransportUnicast> {
     let fsm = OpenLink {
         link,
@@ -473,6 +527,9 @@ pub(crate) async fn open_link(
         #[cfg(feature = "transport_auth")]
         ext_auth: manager.state.unicast.authenticator.fsm(&manager.prng),
         ext_lowlatency: ext::lowlatency::LowLatencyFsm::new(),
+        ext_resync: ext::resync::ResyncFsm::new(),
+        ext_prefix: ext::prefix::PrefixFsm::new(),
+        ext_integrity: ext::integrity::IntegrityFsm::new(),
     };
 
     let mut state = State {
@@ -496,6 +553,9 @@ pub(crate) async fn open_link(
             .authenticator
             .open(&mut *zasynclock!(manager.prng)),
         ext_lowlatency: ext::lowlatency::StateOpen::new(manager.config.unicast.is_lowlatency),
+        ext_resync: ext::resync::StateOpen::new(manager.config.unicast.is_resync),
+        ext_prefix: ext::prefix::StateOpen::new(manager.config.unicast.is_prefix_compression),
+        ext_integrity: ext::integrity::StateOpen::new(manager.config.unicast.is_integrity),
     };
 
     // Init handshake
@@ -504,7 +564,7 @@ pub(crate) async fn open_link(
             match $s {
                 Ok(output) => output,
                 Err((e, reason)) => {
-                    close_link(link, reason).await;
+                    close_link(link, reason, &e).await;
                     return Err(e);
                 }
             }
@@ -519,6 +579,34 @@ pub(crate) async fn open_link(
     step!(fsm.send_init_syn((&mut state, isyn_in)).await);
 
     let iack_out = step!(fsm.recv_init_ack(&mut state).await);
+    // A link added to the transport of a given peer must reach that peer, the link being
+    // rejected without retry otherwise
+    if let Some(peer) = peer {
+        if iack_out.other_zid != *peer {
+            let detail = format!("link expected to reach peer {}", peer);
+            let e = TransportCloseReason::local(CloseReason::Invalid, Some(detail)).into();
+            close_link(link, Some(close::reason::INVALID), &e).await;
+            return Err(e);
+        }
+    }
+    let _pending = manager.add_pending_unicast(iack_out.other_zid, true);
+
+    // The link may be discarded in favour of a link opened by the peer at the same time,
+    // in which case the transport established with the latter is returned
+    macro_rules! step_or_adopt {
+        ($s: expr) => {
+            match $s {
+                Ok(output) => output,
+                Err((e, reason)) => {
+                    close_link(link, reason, &e).await;
+                    return manager
+                        .adopt_transport_unicast(&iack_out.other_zid)
+                        .await
+                        .ok_or(e);
+                }
+            }
+        };
+    }
 
     // Open handshake
     let osyn_in = SendOpenSynIn {
@@ -529,25 +617,36 @@ pub(crate) async fn open_link(
         #[cfg(feature = "shared-memory")]
         ext_shm: iack_out.ext_shm,
     };
-    let osyn_out = step!(fsm.send_open_syn((&mut state, osyn_in)).await);
+    let osyn_out = step_or_adopt!(fsm.send_open_syn((&mut state, osyn_in)).await);
 
-    let oack_out = step!(fsm.recv_open_ack(&mut state).await);
+    let oack_out = step_or_adopt!(fsm.recv_open_ack(&mut state).await);
 
     // Initialize the transport
     let config = TransportConfigUnicast {
         zid: iack_out.other_zid,
         whatami: iack_out.other_whatami,
         sn_resolution: state.zenoh.resolution.get(Field::FrameSN),
+        batch_size: state.zenoh.batch_size,
         tx_initial_sn: osyn_out.mine_initial_sn,
         is_qos: state.ext_qos.is_qos(),
         #[cfg(feature = "transport_multilink")]
         multilink: state.ext_mlink.multilink(),
         #[cfg(feature = "shared-memory")]
         is_shm: state.ext_shm.is_shm(),
+        #[cfg(feature = "shared-memory")]
+        is_shm_aligned: state.ext_shm.is_shm_aligned(),
+        #[cfg(feature = "shared-memory")]
+        is_shm_downgraded: state.ext_shm.is_shm_downgraded(),
         is_lowlatency: state.ext_lowlatency.is_lowlatency(),
+        is_resync: state.ext_resync.is_resync(),
+        is_prefix_compression: state.ext_prefix.is_prefix_compression(),
+        is_integrity: state.ext_integrity.is_integrity(),
+        is_initiator: true,
+        auth_user: None,
+        lease: oack_out.other_lease,
     };
 
-    let transport = step!(
+    let transport = step_or_adopt!(
         manager
             .init_transport_unicast(config, link.clone(), LinkUnicastDirection::Outbound)
             .await
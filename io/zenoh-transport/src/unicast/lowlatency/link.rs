@@ -14,9 +14,9 @@
 use super::transport::TransportUnicastLowlatency;
 #[cfg(feature = "stats")]
 use crate::stats::TransportStats;
-use crate::TransportExecutor;
+use crate::{TransportCloseReason, TransportExecutor};
+use async_std::sync::RwLock;
 use async_std::task;
-use async_std::{prelude::FutureExt, sync::RwLock};
 use zenoh_codec::*;
 use zenoh_core::{zasyncread, zasyncwrite};
 
@@ -25,10 +25,10 @@ use std::time::Duration;
 use zenoh_buffers::{writer::HasWriter, ZSlice};
 use zenoh_link::LinkUnicast;
 use zenoh_protocol::transport::{
-    BatchSize, KeepAlive, TransportBodyLowLatency, TransportMessageLowLatency,
+    close::CloseReason, BatchSize, KeepAlive, TransportBodyLowLatency, TransportMessageLowLatency,
 };
 use zenoh_result::{zerror, ZResult};
-use zenoh_sync::RecyclingObjectPool;
+use zenoh_util::clock::{timeout, Clock};
 
 pub(crate) async fn send_with_link(
     link: &LinkUnicast,
@@ -97,6 +97,7 @@ impl TransportUnicastLowlatency {
             let res = keepalive_task(
                 c_transport.link.clone(),
                 keep_alive,
+                c_transport.manager.config.clock.clone(),
                 #[cfg(feature = "stats")]
                 c_transport.stats.clone(),
             )
@@ -139,6 +140,7 @@ impl TransportUnicastLowlatency {
             let link = guard.clone();
             drop(guard);
             let rx_buffer_size = c_transport.manager.config.link_rx_buffer_size;
+            let link_name = link.to_string();
 
             // Start the rx task
             let res = rx_task(link, c_transport.clone(), lease, batch_size, rx_buffer_size).await;
@@ -147,13 +149,24 @@ impl TransportUnicastLowlatency {
                 c_transport.manager.config.zid,
                 res
             );
-            if res.is_err() {
+            if let Err(e) = res {
                 log::debug!(
                     "[{}] <on rx exit> finalizing transport with peer: {}",
                     c_transport.manager.config.zid,
                     c_transport.config.zid
                 );
-                let _ = c_transport.finalize(0).await;
+                let reason = e
+                    .downcast_ref::<TransportCloseReason>()
+                    .cloned()
+                    .unwrap_or_else(|| {
+                        TransportCloseReason::local(
+                            CloseReason::Generic,
+                            Some(format!("{}: link lost", link_name)),
+                        )
+                    });
+                let code = reason.reason.code();
+                c_transport.close_reason.set(reason);
+                let _ = c_transport.finalize(code).await;
             }
         });
         *guard = Some(handle);
@@ -179,13 +192,14 @@ impl TransportUnicastLowlatency {
 async fn keepalive_task(
     link: Arc<RwLock<LinkUnicast>>,
     keep_alive: Duration,
+    clock: Arc<dyn Clock>,
     #[cfg(feature = "stats")] stats: Arc<TransportStats>,
 ) -> ZResult<()> {
     loop {
-        async_std::task::sleep(keep_alive).await;
+        clock.sleep(keep_alive).await;
 
         let keepailve = TransportMessageLowLatency {
-            body: TransportBodyLowLatency::KeepAlive(KeepAlive),
+            body: TransportBodyLowLatency::KeepAlive(KeepAlive::default()),
         };
 
         let guard = zasyncwrite!(link);
@@ -200,6 +214,18 @@ async fn keepalive_task(
     }
 }
 
+// The error of a link on which nothing was received for a whole lease
+fn expired(link: &LinkUnicast, lease: Duration) -> TransportCloseReason {
+    TransportCloseReason::local(
+        CloseReason::Expired,
+        Some(format!(
+            "{}: expired after {} milliseconds",
+            link,
+            lease.as_millis()
+        )),
+    )
+}
+
 async fn rx_task_stream(
     link: LinkUnicast,
     transport: TransportUnicastLowlatency,
@@ -217,23 +243,25 @@ async fn rx_task_stream(
         Ok(n)
     }
 
-    // The pool of buffers
+    // The buffers are recycled by the pool shared by the links of the manager
     let mtu = link.get_mtu().min(rx_batch_size) as usize;
     let mut n = rx_buffer_size / mtu;
     if rx_buffer_size % mtu != 0 {
         n += 1;
     }
-
-    let pool = RecyclingObjectPool::new(n, || vec![0_u8; mtu].into_boxed_slice());
+    let pool = transport.manager.rx_pool.clone();
+    pool.prefill(mtu, n);
+    let clock = transport.manager.config.clock.clone();
     loop {
         // Retrieve one buffer
-        let mut buffer = pool.try_take().unwrap_or_else(|| pool.alloc());
+        let mut buffer = pool.take(mtu);
+        #[cfg(feature = "stats")]
+        transport.stats.inc_rx_pool(buffer.recycled());
 
         // Async read from the underlying link
-        let bytes = read(&link, &mut buffer)
-            .timeout(lease)
+        let bytes = timeout(&*clock, lease, read(&link, &mut buffer))
             .await
-            .map_err(|_| zerror!("{}: expired after {} milliseconds", link, lease.as_millis()))??;
+            .ok_or_else(|| expired(&link, lease))??;
         #[cfg(feature = "stats")]
         transport.stats.inc_rx_bytes(2 + bytes); // Account for the batch len encoding (16 bits)
 
@@ -250,23 +278,25 @@ async fn rx_task_dgram(
     rx_batch_size: BatchSize,
     rx_buffer_size: usize,
 ) -> ZResult<()> {
-    // The pool of buffers
+    // The buffers are recycled by the pool shared by the links of the manager
     let mtu = link.get_mtu().min(rx_batch_size) as usize;
     let mut n = rx_buffer_size / mtu;
     if rx_buffer_size % mtu != 0 {
         n += 1;
     }
-
-    let pool = RecyclingObjectPool::new(n, || vec![0_u8; mtu].into_boxed_slice());
+    let pool = transport.manager.rx_pool.clone();
+    pool.prefill(mtu, n);
+    let clock = transport.manager.config.clock.clone();
     loop {
         // Retrieve one buffer
-        let mut buffer = pool.try_take().unwrap_or_else(|| pool.alloc());
+        let mut buffer = pool.take(mtu);
+        #[cfg(feature = "stats")]
+        transport.stats.inc_rx_pool(buffer.recycled());
 
         // Async read from the underlying link
-        let bytes =
-            link.read(&mut buffer).timeout(lease).await.map_err(|_| {
-                zerror!("{}: expired after {} milliseconds", link, lease.as_millis())
-            })??;
+        let bytes = timeout(&*clock, lease, link.read(&mut buffer))
+            .await
+            .ok_or_else(|| expired(&link, lease))??;
 
         #[cfg(feature = "stats")]
         transport.stats.inc_rx_bytes(bytes);
@@ -12,6 +12,7 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 use super::transport::TransportUnicastLowlatency;
+use crate::TransportCloseReason;
 use zenoh_buffers::{
     reader::{HasReader, Reader},
     ZSlice,
@@ -36,7 +37,11 @@ impl TransportUnicastLowlatency {
             #[cfg(feature = "shared-memory")]
             {
                 if self.config.is_shm {
-                    crate::shm::map_zmsg_to_shmbuf(&mut msg, &self.manager.shm().reader)?;
+                    crate::shm::map_zmsg_to_shmbuf(
+                        &mut msg,
+                        &self.manager.shm().reader,
+                        self.config.is_shm_aligned,
+                    )?;
                 }
             }
             callback.handle_message(msg)
@@ -70,11 +75,14 @@ impl TransportUnicastLowlatency {
             }
 
             match msg.body {
-                zenoh_protocol::transport::TransportBodyLowLatency::Close(_) => {
+                zenoh_protocol::transport::TransportBodyLowLatency::Close(close) => {
+                    self.close_reason.set(TransportCloseReason::remote(&close));
                     let _ = self.delete().await;
                 }
                 zenoh_protocol::transport::TransportBodyLowLatency::KeepAlive(_) => {}
                 zenoh_protocol::transport::TransportBodyLowLatency::Network(msg) => {
+                    #[cfg(feature = "stats")]
+                    self.stats.inc_rx_n_msgs(1);
                     let _ = self.trigger_callback(msg);
                 }
             }
@@ -13,11 +13,13 @@
 //
 #[cfg(feature = "transport_unixpipe")]
 use super::link::send_with_link;
+use crate::common::close::CloseReasonCell;
 #[cfg(feature = "stats")]
 use crate::stats::TransportStats;
 use crate::transport_unicast_inner::TransportUnicastTrait;
-use crate::TransportConfigUnicast;
+use crate::LinkRtt;
 use crate::TransportManager;
+use crate::{TransportCloseReason, TransportConfigUnicast};
 use crate::{TransportExecutor, TransportPeerEventHandler};
 use async_executor::Task;
 #[cfg(feature = "transport_unixpipe")]
@@ -36,10 +38,10 @@ use zenoh_link::unixpipe::UNIXPIPE_LOCATOR_PREFIX;
 use zenoh_link::Link;
 use zenoh_link::{LinkUnicast, LinkUnicastDirection};
 use zenoh_protocol::core::{WhatAmI, ZenohId};
-use zenoh_protocol::network::NetworkMessage;
+use zenoh_protocol::network::{NetworkMessage, UnknownKind};
 use zenoh_protocol::transport::TransportBodyLowLatency;
 use zenoh_protocol::transport::TransportMessageLowLatency;
-use zenoh_protocol::transport::{Close, TransportSn};
+use zenoh_protocol::transport::{close::CloseReason, Close, TransportSn};
 #[cfg(not(feature = "transport_unixpipe"))]
 use zenoh_result::bail;
 use zenoh_result::{zerror, ZResult};
@@ -59,6 +61,8 @@ pub(crate) struct TransportUnicastLowlatency {
     pub(super) callback: Arc<SyncRwLock<Option<Arc<dyn TransportPeerEventHandler>>>>,
     // Mutex for notification
     alive: Arc<AsyncMutex<bool>>,
+    // The reason of the close, notified to the callback once closed
+    pub(super) close_reason: CloseReasonCell,
     // Transport statistics
     #[cfg(feature = "stats")]
     pub(super) stats: Arc<TransportStats>,
@@ -75,13 +79,14 @@ impl TransportUnicastLowlatency {
         link: LinkUnicast,
     ) -> ZResult<TransportUnicastLowlatency> {
         #[cfg(feature = "stats")]
-        let stats = Arc::new(TransportStats::new(Some(manager.get_stats().clone())));
+        let stats = Arc::new(TransportStats::new(Some(manager.get_stats_unicast())));
         let t = TransportUnicastLowlatency {
             manager,
             config,
             link: Arc::new(RwLock::new(link)),
             callback: Arc::new(SyncRwLock::new(None)),
             alive: Arc::new(AsyncMutex::new(false)),
+            close_reason: CloseReasonCell::default(),
             #[cfg(feature = "stats")]
             stats,
             handle_keepalive: Arc::new(RwLock::new(None)),
@@ -100,12 +105,15 @@ impl TransportUnicastLowlatency {
             self.manager.config.zid,
             self.config.zid
         );
+        self.close_reason
+            .set(TransportCloseReason::local(CloseReason::from(reason), None));
 
         // Send close message on the link
         let close = TransportMessageLowLatency {
             body: TransportBodyLowLatency::Close(Close {
                 reason,
                 session: false,
+                ext_detail: None,
             }),
         };
         let _ = self.send_async(close).await;
@@ -140,7 +148,9 @@ impl TransportUnicastLowlatency {
         let _ = zasyncread!(self.link).close().await;
 
         // Notify the callback that we have closed the transport
+        let reason = self.close_reason.take();
         if let Some(cb) = callback.as_ref() {
+            cb.close_reason(&reason);
             cb.closed();
         }
 
@@ -167,6 +177,26 @@ impl TransportUnicastTrait for TransportUnicastLowlatency {
         [guard.clone()].to_vec()
     }
 
+    fn get_links_with_direction(&self, direction: &LinkUnicastDirection) -> Vec<LinkUnicast> {
+        // The single link has the direction of the transport
+        match (direction, self.config.is_initiator) {
+            (LinkUnicastDirection::Outbound, true) | (LinkUnicastDirection::Inbound, false) => {
+                self.get_links()
+            }
+            _ => vec![],
+        }
+    }
+
+    fn get_links_rtt(&self) -> Vec<(LinkUnicast, Option<LinkRtt>)> {
+        // The low latency transport doesn't echo the keep-alives
+        self.get_links().into_iter().map(|l| (l, None)).collect()
+    }
+
+    fn get_unknown_kinds(&self) -> Vec<(UnknownKind, usize)> {
+        // The low latency transport doesn't skip the messages of unknown kinds
+        vec![]
+    }
+
     fn get_zid(&self) -> ZenohId {
         self.config.zid
     }
@@ -204,6 +234,13 @@ impl TransportUnicastTrait for TransportUnicastLowlatency {
         self.internal_schedule(msg)
     }
 
+    fn schedule_with_ack(&self, msg: NetworkMessage) -> ZResult<flume::Receiver<()>> {
+        // The peer doesn't acknowledge the messages: the acknowledgement is disconnected
+        self.internal_schedule(msg)?;
+        let (_, ack_r) = flume::bounded(1);
+        Ok(ack_r)
+    }
+
     fn start_tx(
         &self,
         _link: &LinkUnicast,
@@ -255,6 +292,7 @@ impl TransportUnicastTrait for TransportUnicastLowlatency {
                             body: TransportBodyLowLatency::Close(Close {
                                 reason: 0,
                                 session: false,
+                                ext_detail: None,
                             }),
                         };
                         let _ = send_with_link(
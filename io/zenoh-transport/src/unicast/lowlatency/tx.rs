@@ -28,9 +28,9 @@ impl TransportUnicastLowlatency {
         #[cfg(feature = "shared-memory")]
         {
             let res = if self.config.is_shm {
-                crate::shm::map_zmsg_to_shminfo(&mut msg)
+                crate::shm::map_zmsg_to_shminfo(&mut msg, self.config.is_shm_aligned)
             } else {
-                crate::shm::map_zmsg_to_shmbuf(&mut msg, &self.manager.shm().reader)
+                crate::shm::map_zmsg_to_shmbuf(&mut msg, &self.manager.shm().reader, false)
             };
             if let Err(e) = res {
                 bail!("Failed SHM conversion: {}", e);
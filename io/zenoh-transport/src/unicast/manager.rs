@@ -13,6 +13,7 @@
 //
 #[cfg(feature = "shared-memory")]
 use super::shared_memory_unicast::SharedMemoryUnicast;
+use super::sources::{self, AcceptSources};
 #[cfg(feature = "transport_auth")]
 use crate::unicast::establishment::ext::auth::Auth;
 #[cfg(feature = "transport_multilink")]
@@ -22,35 +23,70 @@ use crate::{
     transport_unicast_inner::TransportUnicastTrait,
     unicast::{TransportConfigUnicast, TransportUnicast},
     universal::transport::TransportUnicastUniversal,
-    TransportManager,
+    TransportCloseReason, TransportManager, TransportUnicastEvent,
+};
+use async_std::{net::ToSocketAddrs, prelude::FutureExt, sync::Mutex, task};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    net::{IpAddr, SocketAddr},
+    sync::Arc,
+    time::Duration,
 };
-use async_std::{prelude::FutureExt, sync::Mutex, task};
-use std::{collections::HashMap, sync::Arc, time::Duration};
 #[cfg(feature = "shared-memory")]
 use zenoh_config::SharedMemoryConf;
-use zenoh_config::{Config, LinkTxConf, QoSConf, TransportUnicastConf};
-use zenoh_core::{zasynclock, zcondfeat};
+use zenoh_config::{
+    Config, LinkTxConf, QoSConf, TransportUnicastConf, TransportUnicastOpenRetryConf,
+};
+use zenoh_core::{zasynclock, zcondfeat, zlock};
 use zenoh_crypto::PseudoRng;
 use zenoh_link::*;
 use zenoh_protocol::{
-    core::{endpoint, ZenohId},
-    transport::close,
+    core::{endpoint, WhatAmIMatcher, ZenohId},
+    transport::close::{self, CloseReason},
 };
 use zenoh_result::{bail, zerror, Error, ZResult};
+use zenoh_sync::{Condition, Signal};
+use zenoh_util::clock::timeout;
+
+// The period at which the links being accepted from a peer are checked when adopting a transport,
+// and the transports being deleted when initializing a new one
+const PENDING_POLL_PERIOD: Duration = Duration::from_millis(10);
 
 /*************************************/
 /*         TRANSPORT CONFIG          */
 /*************************************/
 pub struct TransportManagerConfigUnicast {
     pub lease: Duration,
-    pub keep_alive: usize,
+    // The period of the keep-alive messages on an idle link
+    pub keep_alive: Duration,
     pub accept_timeout: Duration,
     pub accept_pending: usize,
+    pub accept_pending_per_source: usize,
+    pub accept_failures_per_source: Option<usize>,
+    pub accept_ban_period: Duration,
     pub max_sessions: usize,
+    // The limits overriding max_sessions for the sessions of some protocols
+    pub max_sessions_per_protocol: HashMap<String, usize>,
+    // The limits of the sessions with the nodes of some kinds, in addition to the other ones
+    pub max_sessions_by_whatami: HashMap<WhatAmIMatcher, usize>,
+    pub close_timeout: Duration,
+    pub shutdown_timeout: Duration,
     pub is_qos: bool,
     pub is_lowlatency: bool,
+    pub is_resync: bool,
+    pub is_prefix_compression: bool,
+    pub is_integrity: bool,
+    pub open_retry: Option<TransportOpenRetry>,
+    // If not empty, the only peers admitted to establish transports
+    pub allowed_peers: HashSet<ZenohId>,
+    // The peers never admitted to establish transports
+    pub denied_peers: HashSet<ZenohId>,
     #[cfg(feature = "transport_multilink")]
     pub max_links: usize,
+    // The limits overriding max_links for the links of some protocols
+    #[cfg(feature = "transport_multilink")]
+    pub max_links_per_protocol: HashMap<String, usize>,
     #[cfg(feature = "shared-memory")]
     pub is_shm: bool,
     #[cfg(all(feature = "unstable", feature = "transport_compression"))]
@@ -60,10 +96,18 @@ pub struct TransportManagerConfigUnicast {
 pub struct TransportManagerStateUnicast {
     // Incoming uninitialized transports
     pub(super) incoming: Arc<Mutex<usize>>,
+    // Notified when the last incoming transport is accepted or dropped
+    pub(super) incoming_drained: Arc<Condition>,
+    // Incoming uninitialized transports and failed accepts, per source address
+    pub(super) sources: Arc<std::sync::Mutex<AcceptSources>>,
     // Established listeners
     pub(super) protocols: Arc<Mutex<HashMap<String, LinkManagerUnicast>>>,
     // Established transports
     pub(super) transports: Arc<Mutex<HashMap<ZenohId, Arc<dyn TransportUnicastTrait>>>>,
+    // Transports being opened (true) or accepted (false), per peer
+    pub(super) pending: Arc<std::sync::Mutex<HashMap<(ZenohId, bool), usize>>>,
+    // Triggered when the manager is closed, interrupting the retries of the opens
+    pub(super) closing: Signal,
     // Multilink
     #[cfg(feature = "transport_multilink")]
     pub(super) multilink: Arc<MultiLink>,
@@ -75,6 +119,40 @@ pub struct TransportManagerStateUnicast {
     pub(super) shm: Arc<SharedMemoryUnicast>,
 }
 
+/// The retry of the opening of a unicast transport, when the link can't be opened or drops during
+/// the establishment.
+#[derive(Clone, Debug)]
+pub struct TransportOpenRetry {
+    /// The period before the first retry.
+    pub period_init: Duration,
+    /// The maximum period between two attempts.
+    pub period_max: Duration,
+    /// The factor the period is multiplied by after each attempt.
+    pub period_increase_factor: f64,
+    /// The maximum number of attempts, including the first one, unlimited if `None`.
+    pub max_attempts: Option<usize>,
+}
+
+impl TransportOpenRetry {
+    // The period following the given one. It is bounded before being converted back, as it
+    // overflows a Duration after enough attempts.
+    fn next_period(&self, period: Duration) -> Duration {
+        Duration::try_from_secs_f64(period.as_secs_f64() * self.period_increase_factor)
+            .map_or(self.period_max, |next| next.min(self.period_max))
+    }
+}
+
+impl From<&TransportUnicastOpenRetryConf> for TransportOpenRetry {
+    fn from(conf: &TransportUnicastOpenRetryConf) -> Self {
+        Self {
+            period_init: Duration::from_millis(*conf.period_init()),
+            period_max: Duration::from_millis(*conf.period_max()),
+            period_increase_factor: *conf.period_increase_factor(),
+            max_attempts: *conf.max_attempts(),
+        }
+    }
+}
+
 pub struct TransportManagerParamsUnicast {
     pub config: TransportManagerConfigUnicast,
     pub state: TransportManagerStateUnicast,
@@ -88,17 +166,33 @@ pub struct TransportManagerBuilderUnicast {
     //       target interval.
     pub(super) lease: Duration,
     pub(super) keep_alive: usize,
+    pub(super) keep_alive_period: Option<Duration>,
     pub(super) accept_timeout: Duration,
     pub(super) accept_pending: usize,
+    pub(super) accept_pending_per_source: usize,
+    pub(super) accept_failures_per_source: Option<usize>,
+    pub(super) accept_ban_period: Duration,
     pub(super) max_sessions: usize,
+    pub(super) max_sessions_per_protocol: HashMap<String, usize>,
+    pub(super) max_sessions_by_whatami: HashMap<WhatAmIMatcher, usize>,
+    pub(super) close_timeout: Duration,
+    pub(super) shutdown_timeout: Duration,
     pub(super) is_qos: bool,
     #[cfg(feature = "transport_multilink")]
     pub(super) max_links: usize,
+    #[cfg(feature = "transport_multilink")]
+    pub(super) max_links_per_protocol: HashMap<String, usize>,
     #[cfg(feature = "shared-memory")]
     pub(super) is_shm: bool,
     #[cfg(feature = "transport_auth")]
     pub(super) authenticator: Auth,
     pub(super) is_lowlatency: bool,
+    pub(super) is_resync: bool,
+    pub(super) is_prefix_compression: bool,
+    pub(super) is_integrity: bool,
+    pub(super) open_retry: Option<TransportOpenRetry>,
+    pub(super) allowed_peers: HashSet<ZenohId>,
+    pub(super) denied_peers: HashSet<ZenohId>,
 }
 
 impl TransportManagerBuilderUnicast {
@@ -107,11 +201,19 @@ impl TransportManagerBuilderUnicast {
         self
     }
 
+    /// The number of keep-alive messages sent in a lease on an idle link, unless a period is set
+    /// with [`keep_alive_every`](Self::keep_alive_every).
     pub fn keep_alive(mut self, keep_alive: usize) -> Self {
         self.keep_alive = keep_alive;
         self
     }
 
+    /// The period of the keep-alive messages on an idle link, at most half the lease.
+    pub fn keep_alive_every(mut self, keep_alive_period: Duration) -> Self {
+        self.keep_alive_period = Some(keep_alive_period);
+        self
+    }
+
     pub fn accept_timeout(mut self, accept_timeout: Duration) -> Self {
         self.accept_timeout = accept_timeout;
         self
@@ -122,27 +224,131 @@ impl TransportManagerBuilderUnicast {
         self
     }
 
+    /// The maximum number of links from the same source address pending in the accept phase,
+    /// within the limit of [`accept_pending`](Self::accept_pending).
+    pub fn accept_pending_per_source(mut self, accept_pending_per_source: usize) -> Self {
+        self.accept_pending_per_source = accept_pending_per_source;
+        self
+    }
+
+    /// The number of failed accepts from the same source address within the
+    /// [`accept_ban_period`](Self::accept_ban_period) after which the new links of the source are
+    /// closed right away during the period, `None` never banning a source.
+    pub fn accept_failures_per_source(mut self, accept_failures_per_source: Option<usize>) -> Self {
+        self.accept_failures_per_source = accept_failures_per_source;
+        self
+    }
+
+    /// The period during which the failed accepts of a source are counted, and the source banned.
+    pub fn accept_ban_period(mut self, accept_ban_period: Duration) -> Self {
+        self.accept_ban_period = accept_ban_period;
+        self
+    }
+
     pub fn max_sessions(mut self, max_sessions: usize) -> Self {
         self.max_sessions = max_sessions;
         self
     }
 
+    /// The maximum number of sessions per protocol, the protocols without limit being bounded by
+    /// [`max_sessions`](Self::max_sessions) only.
+    pub fn max_sessions_per_protocol(
+        mut self,
+        max_sessions_per_protocol: HashMap<String, usize>,
+    ) -> Self {
+        self.max_sessions_per_protocol = max_sessions_per_protocol;
+        self
+    }
+
+    /// The maximum number of sessions with the nodes matching each [`WhatAmIMatcher`], e.g. to
+    /// bound the clients of a router independently from the other routers. These limits apply in
+    /// addition to [`max_sessions`](Self::max_sessions).
+    pub fn max_sessions_by_whatami(
+        mut self,
+        max_sessions_by_whatami: HashMap<WhatAmIMatcher, usize>,
+    ) -> Self {
+        self.max_sessions_by_whatami = max_sessions_by_whatami;
+        self
+    }
+
+    pub fn close_timeout(mut self, close_timeout: Duration) -> Self {
+        self.close_timeout = close_timeout;
+        self
+    }
+
+    /// The timeout of the close of each transport by [`TransportManager::close_unicast`].
+    pub fn shutdown_timeout(mut self, shutdown_timeout: Duration) -> Self {
+        self.shutdown_timeout = shutdown_timeout;
+        self
+    }
+
     pub fn qos(mut self, is_qos: bool) -> Self {
         self.is_qos = is_qos;
         self
     }
 
+    /// Write each message directly on the link of the transports, without the priority queues
+    /// nor the batching. The lowlatency transports don't fragment the messages and have a single
+    /// link. The mode is negotiated with the other node, and is incompatible with QoS.
     pub fn lowlatency(mut self, is_lowlatency: bool) -> Self {
         self.is_lowlatency = is_lowlatency;
         self
     }
 
+    pub fn resync(mut self, is_resync: bool) -> Self {
+        self.is_resync = is_resync;
+        self
+    }
+
+    /// Prefix-compress the key expressions declared in bulk, on the transports with the other
+    /// nodes supporting it.
+    pub fn prefix_compression(mut self, is_prefix_compression: bool) -> Self {
+        self.is_prefix_compression = is_prefix_compression;
+        self
+    }
+
+    /// Forward the integrity checksums of the payloads of the publications, on the transports
+    /// with the other nodes supporting them. The checksums are stripped on the other transports.
+    pub fn integrity(mut self, is_integrity: bool) -> Self {
+        self.is_integrity = is_integrity;
+        self
+    }
+
+    /// The retry of the opening of the transports, `None` giving up at the first failure.
+    pub fn open_retry(mut self, open_retry: Option<TransportOpenRetry>) -> Self {
+        self.open_retry = open_retry;
+        self
+    }
+
+    /// The peers admitted to establish transports, all of them if empty.
+    pub fn allowed_peers(mut self, allowed_peers: Vec<ZenohId>) -> Self {
+        self.allowed_peers = allowed_peers.into_iter().collect();
+        self
+    }
+
+    /// The peers never admitted to establish transports, even if allowed.
+    pub fn denied_peers(mut self, denied_peers: Vec<ZenohId>) -> Self {
+        self.denied_peers = denied_peers.into_iter().collect();
+        self
+    }
+
     #[cfg(feature = "transport_multilink")]
     pub fn max_links(mut self, max_links: usize) -> Self {
         self.max_links = max_links;
         self
     }
 
+    /// The maximum number of incoming links per session for each protocol, the protocols without
+    /// limit being bounded by [`max_links`](Self::max_links).
+    #[cfg(feature = "transport_multilink")]
+    pub fn max_links_per_protocol(
+        mut self,
+        max_links_per_protocol: HashMap<String, usize>,
+    ) -> Self {
+        self.max_links_per_protocol = max_links_per_protocol;
+        self
+    }
+
     #[cfg(feature = "transport_auth")]
     pub fn authenticator(mut self, authenticator: Auth) -> Self {
         self.authenticator = authenticator;
@@ -166,17 +372,77 @@ impl TransportManagerBuilderUnicast {
             *config.transport().link().tx().lease(),
         ));
         self = self.keep_alive(*config.transport().link().tx().keep_alive());
+        if let Some(period) = config.transport().link().tx().keep_alive_period_ms() {
+            self = self.keep_alive_every(Duration::from_millis(*period));
+        }
         self = self.accept_timeout(Duration::from_millis(
             *config.transport().unicast().accept_timeout(),
         ));
         self = self.accept_pending(*config.transport().unicast().accept_pending());
+        self = self
+            .accept_pending_per_source(*config.transport().unicast().accept_pending_per_source());
+        self = self
+            .accept_failures_per_source(*config.transport().unicast().accept_failures_per_source());
+        self = self.accept_ban_period(Duration::from_millis(
+            *config.transport().unicast().accept_ban_period(),
+        ));
         self = self.max_sessions(*config.transport().unicast().max_sessions());
+        self = self.max_sessions_per_protocol(
+            config
+                .transport()
+                .unicast()
+                .limits()
+                .max_sessions()
+                .clone()
+                .unwrap_or_default(),
+        );
+        let mut max_sessions_by_whatami = HashMap::new();
+        for (whatami, limit) in config
+            .transport()
+            .unicast()
+            .limits()
+            .max_sessions_by_whatami()
+            .iter()
+            .flatten()
+        {
+            match whatami.parse::<WhatAmIMatcher>() {
+                Ok(matcher) if !matcher.is_empty() => {
+                    max_sessions_by_whatami.insert(matcher, *limit);
+                }
+                _ => bail!(
+                    "Invalid whatami '{}' in 'transport/unicast/limits/max_sessions_by_whatami'",
+                    whatami
+                ),
+            }
+        }
+        self = self.max_sessions_by_whatami(max_sessions_by_whatami);
+        self = self.close_timeout(Duration::from_millis(
+            *config.transport().unicast().close_timeout(),
+        ));
+        self = self.shutdown_timeout(Duration::from_millis(
+            *config.transport().unicast().shutdown_timeout(),
+        ));
         self = self.qos(*config.transport().qos().enabled());
         self = self.lowlatency(*config.transport().unicast().lowlatency());
+        self = self.resync(*config.transport().unicast().resync());
+        self = self.prefix_compression(*config.transport().unicast().prefix_compression());
+        let open_retry = config.transport().unicast().open_retry();
+        self = self.open_retry((*open_retry.enabled()).then(|| open_retry.into()));
+        self = self.allowed_peers(config.transport().auth().peers().allow().clone());
+        self = self.denied_peers(config.transport().auth().peers().deny().clone());
 
         #[cfg(feature = "transport_multilink")]
         {
             self = self.max_links(*config.transport().unicast().max_links());
+            self = self.max_links_per_protocol(
+                config
+                    .transport()
+                    .unicast()
+                    .limits()
+                    .max_links()
+                    .clone()
+                    .unwrap_or_default(),
+            );
         }
         #[cfg(feature = "shared-memory")]
         {
@@ -197,27 +463,71 @@ impl TransportManagerBuilderUnicast {
         if self.is_qos && self.is_lowlatency {
             bail!("'qos' and 'lowlatency' options are incompatible");
         }
+        let keep_alive = match self.keep_alive_period {
+            Some(period) => period,
+            None if self.keep_alive == 0 => bail!("'keep_alive' must be at least 1"),
+            None => self.lease / u32::try_from(self.keep_alive).unwrap_or(u32::MAX),
+        };
+        if keep_alive.is_zero() || keep_alive > self.lease / 2 {
+            bail!(
+                "The keep-alive period of {} ms must be positive and at most half the lease of {} ms",
+                keep_alive.as_millis(),
+                self.lease.as_millis()
+            );
+        }
+        if let Some(retry) = self.open_retry.as_ref() {
+            let factor = retry.period_increase_factor;
+            if !factor.is_finite() || factor < 1. {
+                bail!(
+                    "The period increase factor of the open retry must be a finite number of at least 1, got {}",
+                    factor
+                );
+            }
+        }
 
         let config = TransportManagerConfigUnicast {
             lease: self.lease,
-            keep_alive: self.keep_alive,
+            keep_alive,
             accept_timeout: self.accept_timeout,
             accept_pending: self.accept_pending,
+            accept_pending_per_source: self.accept_pending_per_source,
+            accept_failures_per_source: self.accept_failures_per_source,
+            accept_ban_period: self.accept_ban_period,
             max_sessions: self.max_sessions,
+            max_sessions_per_protocol: self.max_sessions_per_protocol,
+            max_sessions_by_whatami: self.max_sessions_by_whatami,
+            close_timeout: self.close_timeout,
+            shutdown_timeout: self.shutdown_timeout,
             is_qos: self.is_qos,
             #[cfg(feature = "transport_multilink")]
             max_links: self.max_links,
+            #[cfg(feature = "transport_multilink")]
+            max_links_per_protocol: self.max_links_per_protocol,
             #[cfg(feature = "shared-memory")]
             is_shm: self.is_shm,
             #[cfg(all(feature = "unstable", feature = "transport_compression"))]
             is_compressed: self.is_compressed,
             is_lowlatency: self.is_lowlatency,
+            is_resync: self.is_resync,
+            is_prefix_compression: self.is_prefix_compression,
+            is_integrity: self.is_integrity,
+            open_retry: self.open_retry,
+            allowed_peers: self.allowed_peers,
+            denied_peers: self.denied_peers,
         };
 
         let state = TransportManagerStateUnicast {
             incoming: Arc::new(Mutex::new(0)),
+            incoming_drained: Arc::new(Condition::new()),
+            sources: Arc::new(std::sync::Mutex::new(AcceptSources::new(
+                self.accept_pending_per_source,
+                self.accept_failures_per_source,
+                self.accept_ban_period,
+            ))),
             protocols: Arc::new(Mutex::new(HashMap::new())),
             transports: Arc::new(Mutex::new(HashMap::new())),
+            pending: Arc::new(std::sync::Mutex::new(HashMap::new())),
+            closing: Signal::new(),
             #[cfg(feature = "transport_multilink")]
             multilink: Arc::new(MultiLink::make(prng)?),
             #[cfg(feature = "shared-memory")]
@@ -243,21 +553,248 @@ impl Default for TransportManagerBuilderUnicast {
         Self {
             lease: Duration::from_millis(*link_tx.lease()),
             keep_alive: *link_tx.keep_alive(),
+            keep_alive_period: link_tx.keep_alive_period_ms().map(Duration::from_millis),
             accept_timeout: Duration::from_millis(*transport.accept_timeout()),
             accept_pending: *transport.accept_pending(),
+            accept_pending_per_source: *transport.accept_pending_per_source(),
+            accept_failures_per_source: *transport.accept_failures_per_source(),
+            accept_ban_period: Duration::from_millis(*transport.accept_ban_period()),
             max_sessions: *transport.max_sessions(),
+            max_sessions_per_protocol: HashMap::new(),
+            max_sessions_by_whatami: HashMap::new(),
+            close_timeout: Duration::from_millis(*transport.close_timeout()),
+            shutdown_timeout: Duration::from_millis(*transport.shutdown_timeout()),
             is_qos: *qos.enabled(),
             #[cfg(feature = "transport_multilink")]
             max_links: *transport.max_links(),
+            #[cfg(feature = "transport_multilink")]
+            max_links_per_protocol: HashMap::new(),
             #[cfg(feature = "shared-memory")]
             is_shm: *shm.enabled(),
             #[cfg(feature = "transport_auth")]
             authenticator: Auth::default(),
             is_lowlatency: *transport.lowlatency(),
+            is_resync: *transport.resync(),
+            is_prefix_compression: *transport.prefix_compression(),
+            is_integrity: true,
+            open_retry: None,
+            allowed_peers: HashSet::new(),
+            denied_peers: HashSet::new(),
+        }
+    }
+}
+
+/// The outcome of the close of the unicast transports of a [`TransportManager`].
+#[derive(Clone, Debug, Default)]
+pub struct TransportCloseReport {
+    /// The peers whose transport was closed within the timeout.
+    pub graceful: Vec<ZenohId>,
+    /// The peers whose transport wasn't closed within the timeout, their links being dropped.
+    pub forced: Vec<ZenohId>,
+}
+
+/// A transport being opened or accepted with a peer, until dropped.
+pub(super) struct TransportPendingUnicast {
+    pending: Arc<std::sync::Mutex<HashMap<(ZenohId, bool), usize>>>,
+    key: (ZenohId, bool),
+}
+
+impl Drop for TransportPendingUnicast {
+    fn drop(&mut self) {
+        let mut guard = zlock!(self.pending);
+        if let Some(count) = guard.get_mut(&self.key) {
+            *count -= 1;
+            if *count == 0 {
+                guard.remove(&self.key);
+            }
+        }
+    }
+}
+
+// Simultaneous opens are resolved only for the transports with a single link, the multilink ones
+// accepting the links opened by both nodes and the lowlatency ones not supporting to switch link.
+fn resolves_simultaneous_open(config: &TransportConfigUnicast) -> bool {
+    !config.is_lowlatency && zcondfeat!("transport_multilink", config.multilink.is_none(), true)
+}
+
+// The event notified to the transport listeners when the transport is closed
+fn closed_event(transport: &Arc<dyn TransportUnicastTrait>) -> TransportUnicastEvent {
+    let config = transport.get_config();
+    TransportUnicastEvent {
+        zid: config.zid,
+        whatami: config.whatami,
+        locator: transport.get_links().first().map(|l| l.get_dst().clone()),
+    }
+}
+
+/// The error of [`TransportManager::add_listener_unicast`] when the endpoint overlaps an existing
+/// listener, whose locator it carries.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AlreadyListening(pub Locator);
+
+impl fmt::Display for AlreadyListening {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Already listening on {}", self.0)
+    }
+}
+
+impl std::error::Error for AlreadyListening {}
+
+// How an endpoint relates to an existing listener
+enum Equivalence {
+    // The endpoint of the listener
+    Same,
+    // An endpoint whose socket overlaps the one of the listener
+    Overlapping,
+}
+
+// The canonical form of a listener endpoint: its socket address, if any, is normalized, e.g.
+// `[0:0:0:0:0:0:0:0]:7447` into `[::]:7447`. The metadata and config are sorted when parsed.
+fn canonicalize_listener(endpoint: &EndPoint) -> ZResult<EndPoint> {
+    match endpoint.address().as_str().parse::<SocketAddr>() {
+        Ok(addr) => EndPoint::new(
+            endpoint.protocol(),
+            addr.to_string(),
+            endpoint.metadata(),
+            endpoint.config(),
+        ),
+        Err(_) => Ok(endpoint.clone()),
+    }
+}
+
+/// The key of the metadata or of the config of an endpoint naming the network interface to listen
+/// on, e.g. `tcp/[::]:7447#iface=eth0`.
+pub const LISTEN_IFACE: &str = "iface";
+
+// The endpoints to listen on for an endpoint bound to a network interface, one per address of the
+// interface, or `None` if the endpoint isn't bound to an interface. The interface is named by the
+// `iface` key of the endpoint, its host being unspecified, or else by its host, e.g.
+// `tcp/eth0:7447`, a host naming an interface not being resolved as a host name.
+fn interface_listeners(endpoint: &EndPoint) -> ZResult<Option<Vec<EndPoint>>> {
+    if !matches!(endpoint.protocol().as_str(), "tcp" | "udp") {
+        return Ok(None);
+    }
+    let address = endpoint.address();
+    let (host, port) = match address.as_str().rsplit_once(':') {
+        Some((host, port)) => match port.parse::<u16>() {
+            Ok(port) => (host, port),
+            Err(_) => return Ok(None),
+        },
+        None => return Ok(None),
+    };
+    let host_ip = host
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .parse::<IpAddr>()
+        .ok();
+    let metadata = endpoint.metadata();
+    let config = endpoint.config();
+    let key = metadata
+        .get(LISTEN_IFACE)
+        .or_else(|| config.get(LISTEN_IFACE));
+    let iface = match (key, host_ip) {
+        (Some(iface), Some(ip)) if ip.is_unspecified() => iface,
+        (Some(iface), None) if iface == host => iface,
+        (Some(iface), _) => bail!(
+            "Endpoint {}: the address must be unspecified to listen on interface {}",
+            endpoint,
+            iface
+        ),
+        (None, Some(_)) => return Ok(None),
+        (None, None) if zenoh_util::net::is_interface(host) => host,
+        (None, None) => return Ok(None),
+    };
+    let addrs = zenoh_util::net::get_listen_addresses_of_interface(iface)
+        .map_err(|e| zerror!("Endpoint {}: {}", endpoint, e))?
+        .into_iter()
+        // An unspecified IPv4 address restricts the listeners to the IPv4 addresses
+        .filter(|addr| !matches!(host_ip, Some(IpAddr::V4(_))) || addr.is_ipv4())
+        .collect::<Vec<IpAddr>>();
+    if addrs.is_empty() {
+        bail!(
+            "Endpoint {}: interface {} has no IPv4 address to listen on",
+            endpoint,
+            iface
+        );
+    }
+    let mut endpoints = vec![];
+    for addr in addrs {
+        let mut listener = EndPoint::new(
+            endpoint.protocol(),
+            SocketAddr::new(addr, port).to_string(),
+            endpoint.metadata(),
+            endpoint.config(),
+        )?;
+        listener.metadata_mut().remove(LISTEN_IFACE)?;
+        listener.config_mut().remove(LISTEN_IFACE)?;
+        endpoints.push(listener);
+    }
+    Ok(Some(endpoints))
+}
+
+// The socket addresses of a listener endpoint, its host name being resolved, or none if its
+// address isn't a socket address, e.g. the path of a unix socket
+async fn listener_addrs(endpoint: &EndPoint) -> Vec<SocketAddr> {
+    let address = endpoint.address();
+    let address = address.as_str();
+    if let Ok(addr) = address.parse::<SocketAddr>() {
+        return vec![addr];
+    }
+    match address.rsplit_once(':') {
+        Some((host, port))
+            if !host.is_empty() && !host.contains('/') && port.parse::<u16>().is_ok() =>
+        {
+            match address.to_socket_addrs().await {
+                Ok(addrs) => addrs.collect(),
+                Err(_) => vec![],
+            }
         }
+        _ => vec![],
     }
 }
 
+// The listeners of the protocols binding the same kind of socket overlap on the same address
+fn socket_kind(protocol: &str) -> &str {
+    match protocol {
+        "tcp" | "tls" | "ws" => "tcp",
+        "udp" | "quic" => "udp",
+        protocol => protocol,
+    }
+}
+
+// Whether binding `a` fails when `b` is bound: the unspecified IPv6 address also covers IPv4.
+// The port 0 binds a new port.
+fn socket_addrs_overlap(a: &SocketAddr, b: &SocketAddr) -> bool {
+    let covers =
+        |x: &SocketAddr, y: &SocketAddr| x.ip().is_unspecified() && (x.is_ipv6() || y.is_ipv4());
+    a.port() != 0 && a.port() == b.port() && (a.ip() == b.ip() || covers(a, b) || covers(b, a))
+}
+
+fn listener_equivalence(
+    endpoint: &EndPoint,
+    addrs: &[SocketAddr],
+    listener: &EndPoint,
+) -> Option<Equivalence> {
+    if socket_kind(endpoint.protocol().as_str()) != socket_kind(listener.protocol().as_str()) {
+        return None;
+    }
+    let listener_addr = listener.address().as_str().parse::<SocketAddr>().ok();
+    let same_address = endpoint.address() == listener.address()
+        || listener_addr.map_or(false, |l| addrs.contains(&l) && l.port() != 0);
+    if same_address
+        && endpoint.protocol() == listener.protocol()
+        && endpoint.metadata() == listener.metadata()
+        && endpoint.config() == listener.config()
+    {
+        return Some(Equivalence::Same);
+    }
+    let overlapping = match listener_addr {
+        Some(l) => addrs.iter().any(|a| socket_addrs_overlap(a, &l)),
+        None => endpoint.address() == listener.address(),
+    };
+    overlapping.then_some(Equivalence::Overlapping)
+}
+
 /*************************************/
 /*         TRANSPORT MANAGER         */
 /*************************************/
@@ -271,8 +808,18 @@ impl TransportManager {
         &self.state.unicast.shm
     }
 
-    pub async fn close_unicast(&self) {
+    /// Closes the listeners and the transports, each transport being given the configured
+    /// [`shutdown_timeout`](TransportManagerBuilderUnicast::shutdown_timeout) to close.
+    pub async fn close_unicast(&self) -> TransportCloseReport {
+        self.close_unicast_timeout(self.config.unicast.shutdown_timeout)
+            .await
+    }
+
+    /// Closes the listeners and then all the transports concurrently. The links of the
+    /// transports not closed within `deadline` are dropped without waiting for their peer.
+    pub async fn close_unicast_timeout(&self, deadline: Duration) -> TransportCloseReport {
         log::trace!("TransportManagerUnicast::clear())");
+        self.state.unicast.closing.trigger();
 
         let mut pl_guard = zasynclock!(self.state.unicast.protocols)
             .drain()
@@ -285,13 +832,67 @@ impl TransportManager {
             }
         }
 
-        let mut tu_guard = zasynclock!(self.state.unicast.transports)
+        // The pending accepts are interrupted by the closing signal. Wait for them to release
+        // the manager, an accept about to complete adding its transport.
+        let drained = async {
+            loop {
+                let guard = zasynclock!(self.state.unicast.incoming);
+                if *guard == 0 {
+                    break;
+                }
+                self.state.unicast.incoming_drained.wait(guard).await;
+            }
+        };
+        if timeout(&*self.config.clock, deadline, drained)
+            .await
+            .is_none()
+        {
+            log::debug!(
+                "Pending accepts not drained within {} ms",
+                deadline.as_millis()
+            );
+        }
+
+        let transports = zasynclock!(self.state.unicast.transports)
             .drain()
             .map(|(_, v)| v)
             .collect::<Vec<Arc<dyn TransportUnicastTrait>>>();
-        for tu in tu_guard.drain(..) {
-            let _ = tu.close(close::reason::GENERIC).await;
+        // The drained transports can't be deleted from the manager anymore, the listeners are
+        // notified here once they are closed
+        let events = transports.iter().map(closed_event).collect::<Vec<_>>();
+        // Close the transports concurrently. Each close waits for the acknowledgment of the peer
+        // and then flushes the links, it goes on in the background once timed out.
+        let closes = transports.into_iter().map(|tu| {
+            let clock = self.config.clock.clone();
+            async move {
+                let c_tu = tu.clone();
+                let handle = task::spawn(async move { c_tu.close(close::reason::GENERIC).await });
+                let graceful = timeout(&*clock, deadline, handle).await.is_some();
+                if !graceful {
+                    log::debug!(
+                        "Closing transport with peer {} took more than {} ms, dropping its links",
+                        tu.get_zid(),
+                        deadline.as_millis()
+                    );
+                    for link in tu.get_links() {
+                        let _ = link.close().await;
+                    }
+                }
+                (tu.get_zid(), graceful)
+            }
+        });
+        let mut report = TransportCloseReport::default();
+        for (zid, graceful) in futures::future::join_all(closes).await {
+            if graceful {
+                report.graceful.push(zid);
+            } else {
+                report.forced.push(zid);
+            }
+        }
+        for event in events {
+            self.notify_closed_unicast(&event);
         }
+        report
     }
 
     /*************************************/
@@ -340,7 +941,18 @@ impl TransportManager {
     /*************************************/
     /*              LISTENER             */
     /*************************************/
-    pub async fn add_listener_unicast(&self, mut endpoint: EndPoint) -> ZResult<Locator> {
+    /// Adds a listener on `endpoint`, returning the locators it listens on.
+    ///
+    /// The endpoint is canonicalized: its socket address is normalized, and its metadata and
+    /// config are sorted, as reported by [`get_listeners_unicast`](Self::get_listeners_unicast).
+    /// An endpoint bound to a network interface, e.g. `tcp/eth0:7447` or
+    /// `tcp/[::]:7447#iface=eth0`, is resolved to the current addresses of the interface, one
+    /// listener being added per address. See [`LISTEN_IFACE`].
+    ///
+    /// Adding again the endpoint of a listener returns its locator, while adding an endpoint
+    /// overlapping it fails with [`AlreadyListening`], e.g. `tcp/0.0.0.0:7447` when listening on
+    /// `tcp/127.0.0.1:7447`.
+    pub async fn add_listener_unicast(&self, endpoint: EndPoint) -> ZResult<Vec<Locator>> {
         if self
             .locator_inspector
             .is_multicast(&endpoint.to_locator())
@@ -351,7 +963,41 @@ impl TransportManager {
                 endpoint
             )
         }
+        let endpoint = canonicalize_listener(&endpoint)?;
+        let endpoints = match interface_listeners(&endpoint)? {
+            Some(endpoints) => endpoints,
+            None => vec![endpoint],
+        };
+
+        // The listeners of an interface are added all or none
+        let mut locators = vec![];
+        let mut added = vec![];
+        for endpoint in endpoints {
+            match self.add_single_listener_unicast(endpoint).await {
+                Ok((locator, is_new)) => {
+                    if is_new {
+                        added.push(locator.clone());
+                    }
+                    locators.push(locator);
+                }
+                Err(e) => {
+                    for locator in added {
+                        if let Err(e) = self.del_listener_unicast(&locator.into()).await {
+                            log::warn!("Unable to remove listener: {}", e);
+                        }
+                    }
+                    return Err(e);
+                }
+            }
+        }
+        Ok(locators)
+    }
 
+    // Adds a listener on a single endpoint, returning its locator and whether it is a new one
+    async fn add_single_listener_unicast(
+        &self,
+        mut endpoint: EndPoint,
+    ) -> ZResult<(Locator, bool)> {
         let manager = self
             .new_link_manager_unicast(endpoint.protocol().as_str())
             .await?;
@@ -361,10 +1007,47 @@ impl TransportManager {
                 .config_mut()
                 .extend(endpoint::Parameters::iter(config))?;
         };
-        manager.new_listener(endpoint).await
+        zenoh_link::validate_config_keys(
+            endpoint.config().as_str(),
+            manager.supported_config_keys(),
+        )
+        .map_err(|e| zerror!("Endpoint {}: {}", endpoint, e))?;
+
+        // Refuse the endpoints equivalent to an existing listener of any protocol, the same
+        // endpoint being accepted again
+        let addrs = listener_addrs(&endpoint).await;
+        for listener in self.get_listeners_unicast().await {
+            match listener_equivalence(&endpoint, &addrs, &listener) {
+                Some(Equivalence::Same) => {
+                    log::debug!("Already listening on endpoint: {}", listener);
+                    return Ok((listener.to_locator(), false));
+                }
+                Some(Equivalence::Overlapping) => {
+                    return Err(AlreadyListening(listener.to_locator()).into());
+                }
+                None => {}
+            }
+        }
+        let locator = manager.new_listener(endpoint).await?;
+        Ok((locator, true))
     }
 
+    /// Removes the listener on `endpoint`, or the listeners on the current addresses of the
+    /// network interface it is bound to.
     pub async fn del_listener_unicast(&self, endpoint: &EndPoint) -> ZResult<()> {
+        let endpoint = canonicalize_listener(endpoint)?;
+        match interface_listeners(&endpoint)? {
+            Some(endpoints) => {
+                for endpoint in endpoints.iter() {
+                    self.del_single_listener_unicast(endpoint).await?;
+                }
+                Ok(())
+            }
+            None => self.del_single_listener_unicast(&endpoint).await,
+        }
+    }
+
+    async fn del_single_listener_unicast(&self, endpoint: &EndPoint) -> ZResult<()> {
         let lm = self
             .get_link_manager_unicast(endpoint.protocol().as_str())
             .await?;
@@ -401,15 +1084,107 @@ impl TransportManager {
         link: LinkUnicast,
         direction: LinkUnicastDirection,
     ) -> Result<TransportUnicast, (Error, Option<u8>)> {
-        let mut guard = zasynclock!(self.state.unicast.transports);
+        // Verify that the peer is admitted before anything else
+        let denied = self.config.unicast.denied_peers.contains(&config.zid);
+        let allowed = self.config.unicast.allowed_peers.is_empty()
+            || self.config.unicast.allowed_peers.contains(&config.zid);
+        if denied || !allowed {
+            log::trace!(
+                "Peer {} is {}. Denying transport on link: {}",
+                config.zid,
+                if denied { "denied" } else { "not allowed" },
+                link
+            );
+            let e = TransportCloseReason::local(
+                CloseReason::Invalid,
+                Some(format!("peer {} is not admitted", config.zid)),
+            );
+            return Err((e.into(), Some(close::reason::INVALID)));
+        }
+
+        // A transport left without links is being deleted: the link is added to a new one
+        let mut guard = loop {
+            let guard = zasynclock!(self.state.unicast.transports);
+            match guard.get(&config.zid) {
+                Some(transport) if transport.get_links().is_empty() => {
+                    drop(guard);
+                    task::sleep(PENDING_POLL_PERIOD).await;
+                }
+                _ => break guard,
+            }
+        };
+
+        // When both nodes open a link to the other at the same time, only the links opened by the
+        // node with the lowest id are kept
+        let is_simultaneous_open = resolves_simultaneous_open(&config);
+        let is_winner = (self.config.zid < config.zid) == config.is_initiator;
+        let opposite = match direction {
+            LinkUnicastDirection::Inbound => LinkUnicastDirection::Outbound,
+            LinkUnicastDirection::Outbound => LinkUnicastDirection::Inbound,
+        };
 
         // First verify if the transport already exists
         match guard.get(&config.zid) {
             Some(transport) => {
                 let existing_config = transport.get_config();
+                // A link presenting another public key than the transport's one doesn't come
+                // from the same peer, whatever the ZenohId it claims
+                #[cfg(feature = "transport_multilink")]
+                if existing_config.multilink.is_some()
+                    && existing_config.multilink != config.multilink
+                {
+                    let e = zerror!(
+                        "Transport with peer {} already exist. Mismatching public key on link: {}",
+                        config.zid,
+                        link
+                    );
+                    log::warn!("{}", e);
+                    return Err((e.into(), Some(close::reason::UNAUTHORIZED)));
+                }
+                // A lowlatency transport writes the messages directly on its single link: it
+                // never mixes with a batched one
+                if existing_config.is_lowlatency != config.is_lowlatency {
+                    let e = zerror!(
+                        "Transport with peer {} already exist. Mismatching lowlatency mode on link: {}. Expected: {}.",
+                        config.zid,
+                        link,
+                        existing_config.is_lowlatency
+                    );
+                    log::trace!("{}", e);
+                    return Err((e.into(), Some(close::reason::INVALID)));
+                }
                 // If it exists, verify that fundamental parameters like are correct.
-                // Ignore the non fundamental parameters like initial SN.
-                if *existing_config != config {
+                // Ignore the non fundamental parameters like initial SN, the lease and the
+                // direction of the transport, the links opened by both nodes belonging to it.
+                // The links opened by this node don't authenticate the other node.
+                // A link whose shared memory challenge couldn't be completed doesn't tell whether
+                // the peer is on the same host: it keeps the shared memory mode of the transport.
+                let expected_config = TransportConfigUnicast {
+                    is_initiator: config.is_initiator,
+                    lease: config.lease,
+                    auth_user: if config.is_initiator {
+                        config.auth_user.clone()
+                    } else {
+                        existing_config.auth_user.clone()
+                    },
+                    #[cfg(feature = "shared-memory")]
+                    is_shm: if config.is_shm_downgraded || existing_config.is_shm_downgraded {
+                        config.is_shm
+                    } else {
+                        existing_config.is_shm
+                    },
+                    #[cfg(feature = "shared-memory")]
+                    is_shm_aligned: if config.is_shm_downgraded || existing_config.is_shm_downgraded
+                    {
+                        config.is_shm_aligned
+                    } else {
+                        existing_config.is_shm_aligned
+                    },
+                    #[cfg(feature = "shared-memory")]
+                    is_shm_downgraded: config.is_shm_downgraded,
+                    ..existing_config.clone()
+                };
+                if expected_config != config {
                     let e = zerror!(
                         "Transport with peer {} already exist. Invalid config: {:?}. Expected: {:?}.",
                         config.zid,
@@ -420,24 +1195,141 @@ impl TransportManager {
                     return Err((e.into(), Some(close::reason::INVALID)));
                 }
 
+                let discarded = if is_simultaneous_open {
+                    transport.get_links_with_direction(&opposite)
+                } else {
+                    vec![]
+                };
+                if !is_winner && !discarded.is_empty() {
+                    let e = zerror!(
+                        "Simultaneous open with peer {}: discarding link {} in favour of {:?}",
+                        config.zid,
+                        link,
+                        discarded
+                    );
+                    log::debug!("{}", e);
+                    return Err((e.into(), Some(close::reason::MAX_LINKS)));
+                }
+
                 // Add the link to the transport
                 transport
-                    .add_link(link, direction)
+                    .add_link(link, direction.clone())
                     .await
                     .map_err(|e| (e, Some(close::reason::MAX_LINKS)))?;
 
+                // The links opened by the peer are closed by the node that opened the kept ones,
+                // after the peer added them to its transport
+                if direction == LinkUnicastDirection::Outbound {
+                    for link in discarded {
+                        log::debug!(
+                            "Simultaneous open with peer {}: closing link {}",
+                            config.zid,
+                            link
+                        );
+                        let c_transport = transport.clone();
+                        task::spawn(async move {
+                            let _ = c_transport
+                                .close_link(&link, close::reason::MAX_LINKS)
+                                .await;
+                        });
+                    }
+                }
+
                 Ok(TransportUnicast(Arc::downgrade(transport)))
             }
             None => {
-                // Then verify that we haven't reached the transport number limit
-                if guard.len() >= self.config.unicast.max_sessions {
+                let locator = link.get_dst().clone();
+
+                // Verify that we haven't reached the transport number limit of the kinds of
+                // node of the peer
+                for (matcher, limit) in self
+                    .config
+                    .unicast
+                    .max_sessions_by_whatami
+                    .iter()
+                    .filter(|(m, _)| m.matches(config.whatami))
+                {
+                    let count = guard
+                        .values()
+                        .filter(|t| matcher.matches(t.get_whatami()))
+                        .count();
+                    if count >= *limit {
+                        log::trace!(
+                            "Max {} transports reached ({}). Denying new transport with peer: {}",
+                            matcher,
+                            limit,
+                            config.zid
+                        );
+                        let e = TransportCloseReason::local(
+                            CloseReason::MaxSessions,
+                            Some(format!("max {matcher} sessions reached ({limit})")),
+                        );
+                        return Err((e.into(), Some(close::reason::MAX_SESSIONS)));
+                    }
+                }
+
+                // Then verify that we haven't reached the transport number limit, the one of
+                // the protocol of the link if any
+                let protocol = locator.protocol();
+                match self
+                    .config
+                    .unicast
+                    .max_sessions_per_protocol
+                    .get(protocol.as_str())
+                {
+                    Some(limit) => {
+                        let count = guard
+                            .values()
+                            .filter(|t| {
+                                t.get_links()
+                                    .iter()
+                                    .any(|l| l.get_dst().protocol().as_str() == protocol.as_str())
+                            })
+                            .count();
+                        if count >= *limit {
+                            log::trace!(
+                                "Max {} transports reached ({}). Denying new transport with peer: {}",
+                                protocol,
+                                limit,
+                                config.zid
+                            );
+                            let e = TransportCloseReason::local(
+                                CloseReason::MaxSessions,
+                                Some(format!("max {protocol} sessions reached ({limit})")),
+                            );
+                            return Err((e.into(), Some(close::reason::MAX_SESSIONS)));
+                        }
+                    }
+                    None => {
+                        let limit = self.config.unicast.max_sessions;
+                        if guard.len() >= limit {
+                            log::trace!(
+                                "Max transports reached ({}). Denying new transport with peer: {}",
+                                limit,
+                                config.zid
+                            );
+                            let e = TransportCloseReason::local(
+                                CloseReason::MaxSessions,
+                                Some(format!("max sessions reached ({limit})")),
+                            );
+                            return Err((e.into(), Some(close::reason::MAX_SESSIONS)));
+                        }
+                    }
+                }
+
+                // Don't create a transport that the link being opened in the other direction
+                // would take over
+                if is_simultaneous_open
+                    && !is_winner
+                    && self.is_pending_unicast(&config.zid, !config.is_initiator)
+                {
                     let e = zerror!(
-                        "Max transports reached ({}). Denying new transport with peer: {}",
-                        self.config.unicast.max_sessions,
-                        config.zid
+                        "Simultaneous open with peer {}: discarding link {} in favour of the pending one",
+                        config.zid,
+                        link
                     );
-                    log::trace!("{}", e);
-                    return Err((e.into(), Some(close::reason::INVALID)));
+                    log::debug!("{}", e);
+                    return Err((e.into(), Some(close::reason::MAX_LINKS)));
                 }
 
                 // Create the transport
@@ -468,6 +1360,7 @@ impl TransportManager {
                 // Add the transport transport to the list of active transports
                 let transport = TransportUnicast(Arc::downgrade(&a_t));
                 guard.insert(config.zid, a_t);
+                drop(guard);
 
                 zcondfeat!(
                     "shared-memory",
@@ -500,14 +1393,54 @@ impl TransportManager {
                     }
                 );
 
+                let event = TransportUnicastEvent {
+                    zid: config.zid,
+                    whatami: config.whatami,
+                    locator: Some(locator),
+                };
+                for listener in self.config.listeners.iter() {
+                    listener.opened(&event);
+                }
+
                 Ok(transport)
             }
         }
     }
 
-    pub async fn open_transport_unicast(
+    pub async fn open_transport_unicast(&self, endpoint: EndPoint) -> ZResult<TransportUnicast> {
+        self.open_unicast(endpoint, None).await
+    }
+
+    /// Opens an additional link to the peer of an established transport, e.g. a link over another
+    /// protocol.
+    ///
+    /// The link is added to the transport once the open handshake succeeds, the peer rejecting it
+    /// when its `max_links` would be exceeded. The transport must have negotiated multiple links.
+    pub async fn open_link_unicast(&self, peer: &ZenohId, endpoint: EndPoint) -> ZResult<()> {
+        let transport = self
+            .get_transport_unicast(peer)
+            .await
+            .ok_or_else(|| zerror!("No transport with peer {}", peer))?;
+        let is_multilink = zcondfeat!(
+            "transport_multilink",
+            transport.get_inner()?.get_config().multilink.is_some(),
+            false
+        );
+        if !is_multilink {
+            bail!(
+                "Can not add a link to the transport with peer {}: multiple links not negotiated",
+                peer
+            );
+        }
+        self.open_unicast(endpoint, Some(peer)).await?;
+        Ok(())
+    }
+
+    // Opens a link to the given endpoint, checking that it reaches the given peer if any
+    async fn open_unicast(
         &self,
         mut endpoint: EndPoint,
+        peer: Option<&ZenohId>,
     ) -> ZResult<TransportUnicast> {
         if self
             .locator_inspector
@@ -530,11 +1463,112 @@ impl TransportManager {
                 .config_mut()
                 .extend(endpoint::Parameters::iter(config))?;
         };
+        zenoh_link::validate_config_keys(
+            endpoint.config().as_str(),
+            manager.supported_config_keys(),
+        )
+        .map_err(|e| zerror!("Endpoint {}: {}", endpoint, e))?;
 
+        let retry = match self.config.unicast.open_retry.as_ref() {
+            Some(retry) => retry,
+            None => return self.try_open_unicast(&manager, endpoint, peer).await,
+        };
+        let mut period = retry.period_init;
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
+            let cause = match self
+                .try_open_unicast(&manager, endpoint.clone(), peer)
+                .await
+            {
+                Ok(transport) => return Ok(transport),
+                Err(e) => e,
+            };
+            // A transport rejected by the peer is not retried
+            if cause.downcast_ref::<TransportCloseReason>().is_some() {
+                return Err(cause);
+            }
+            if retry.max_attempts.map_or(false, |max| attempts >= max) {
+                let e = zerror!(
+                    "Unable to open a transport with {} after {} attempts",
+                    endpoint,
+                    attempts
+                );
+                return Err(e.set_source(cause).into());
+            }
+            log::debug!(
+                "Unable to open a transport with {}, retrying in {:?}: {}",
+                endpoint,
+                period,
+                cause
+            );
+            // The wait is interrupted by the close of the manager
+            let closed = async {
+                self.state.unicast.closing.wait().await;
+                true
+            };
+            let elapsed = async {
+                task::sleep(period).await;
+                false
+            };
+            if closed.race(elapsed).await {
+                let e = zerror!(
+                    "Unable to open a transport with {}: the manager is closed",
+                    endpoint
+                );
+                return Err(e.set_source(cause).into());
+            }
+            period = retry.next_period(period);
+        }
+    }
+
+    async fn try_open_unicast(
+        &self,
+        manager: &LinkManagerUnicast,
+        endpoint: EndPoint,
+        peer: Option<&ZenohId>,
+    ) -> ZResult<TransportUnicast> {
         // Create a new link associated by calling the Link Manager
         let link = manager.new_link(endpoint).await?;
         // Open the link
-        super::establishment::open::open_link(&link, self).await
+        super::establishment::open::open_link(&link, self, peer).await
+    }
+
+    pub(super) fn add_pending_unicast(
+        &self,
+        peer: ZenohId,
+        is_initiator: bool,
+    ) -> TransportPendingUnicast {
+        let key = (peer, is_initiator);
+        *zlock!(self.state.unicast.pending).entry(key).or_insert(0) += 1;
+        TransportPendingUnicast {
+            pending: self.state.unicast.pending.clone(),
+            key,
+        }
+    }
+
+    fn is_pending_unicast(&self, peer: &ZenohId, is_initiator: bool) -> bool {
+        zlock!(self.state.unicast.pending).contains_key(&(*peer, is_initiator))
+    }
+
+    /// Returns the transport established with the links opened by the given peer, when a link
+    /// opened by this node at the same time has been discarded in their favour.
+    pub(super) async fn adopt_transport_unicast(&self, peer: &ZenohId) -> Option<TransportUnicast> {
+        // Only the links opened by the node with the lowest id are kept
+        if self.config.zid < *peer {
+            return None;
+        }
+        // Wait for the links being accepted from the peer, bounded by the accept timeout
+        while self.is_pending_unicast(peer, false) {
+            task::sleep(PENDING_POLL_PERIOD).await;
+        }
+        let guard = zasynclock!(self.state.unicast.transports);
+        let transport = guard.get(peer)?;
+        let is_adopted = resolves_simultaneous_open(transport.get_config())
+            && !transport
+                .get_links_with_direction(&LinkUnicastDirection::Inbound)
+                .is_empty();
+        is_adopted.then(|| TransportUnicast(Arc::downgrade(transport)))
     }
 
     pub async fn get_transport_unicast(&self, peer: &ZenohId) -> Option<TransportUnicast> {
@@ -558,19 +1592,83 @@ impl TransportManager {
             .collect()
     }
 
-    pub(super) async fn del_transport_unicast(&self, peer: &ZenohId) -> ZResult<()> {
+    /// The unicast transports matching `predicate`.
+    ///
+    /// The transports are matched under the lock of the transports: the predicate sees a
+    /// consistent snapshot of them, no transport being opened or closed meanwhile.
+    pub async fn find_transports_unicast<F>(&self, mut predicate: F) -> Vec<TransportUnicast>
+    where
+        F: FnMut(&TransportUnicast) -> bool,
+    {
         zasynclock!(self.state.unicast.transports)
+            .values()
+            .map(|t| TransportUnicast(Arc::downgrade(t)))
+            .filter(|t| predicate(t))
+            .collect()
+    }
+
+    /// The unicast transport with a link to `locator`, e.g. the address of a peer as observed by
+    /// the diagnostic tools.
+    pub async fn get_transport_unicast_by_locator(
+        &self,
+        locator: &Locator,
+    ) -> Option<TransportUnicast> {
+        self.find_transports_unicast(|t| {
+            t.get_links()
+                .map_or(false, |links| links.iter().any(|l| l.dst == *locator))
+        })
+        .await
+        .pop()
+    }
+
+    /// The statistics of all the unicast transports of the manager, including the closed ones.
+    #[cfg(feature = "stats")]
+    pub fn get_stats_unicast(&self) -> Arc<crate::stats::TransportStats> {
+        self.stats_unicast.clone()
+    }
+
+    /// Closes the transport with the given peer, notifying it with the given [`close::reason`].
+    ///
+    /// The transport event handler is notified of the close before returning, so that the state
+    /// associated to the peer is cleaned without waiting for the lease to expire.
+    pub async fn close_transport_unicast(&self, peer: &ZenohId, reason: u8) -> ZResult<()> {
+        let transport = self
+            .get_transport_unicast(peer)
+            .await
+            .ok_or_else(|| zerror!("No transport with peer: {}", peer))?;
+        transport.close_with_reason(reason).await
+    }
+
+    pub(super) async fn del_transport_unicast(&self, peer: &ZenohId) -> ZResult<()> {
+        let transport = zasynclock!(self.state.unicast.transports)
             .remove(peer)
             .ok_or_else(|| {
                 let e = zerror!("Can not delete the transport of peer: {}", peer);
                 log::trace!("{}", e);
                 e
             })?;
+        self.notify_closed_unicast(&closed_event(&transport));
         Ok(())
     }
 
+    fn notify_closed_unicast(&self, event: &TransportUnicastEvent) {
+        for listener in self.config.listeners.iter() {
+            listener.closed(event);
+        }
+    }
+
+    /// Returns the number of incoming links currently pending in the accept phase.
+    pub async fn get_incoming_pending_unicast(&self) -> usize {
+        *zasynclock!(self.state.unicast.incoming)
+    }
+
     pub(crate) async fn handle_new_link_unicast(&self, link: LinkUnicast) {
         let mut guard = zasynclock!(self.state.unicast.incoming);
+        if self.state.unicast.closing.is_triggered() {
+            log::trace!("Closing link accepted while closing the manager: {}", link);
+            let _ = link.close().await;
+            return;
+        }
         if *guard >= self.config.unicast.accept_pending {
             // We reached the limit of concurrent incoming transport, this means two things:
             // - the values configured for ZN_OPEN_INCOMING_PENDING and ZN_OPEN_TIMEOUT
@@ -581,6 +1679,19 @@ impl TransportManager {
             let _ = link.close().await;
             return;
         }
+        // A single source can't take all the pending links
+        let source = sources::source_address(&link);
+        let admitted = zlock!(self.state.unicast.sources).admit(&source, self.config.clock.now());
+        if let Err(refusal) = admitted {
+            log::debug!(
+                "Closing link from {} for preventing potential DoS ({}): {}",
+                source,
+                refusal,
+                link
+            );
+            let _ = link.close().await;
+            return;
+        }
 
         // A new link is available
         log::trace!("New link waiting... {}", link);
@@ -590,15 +1701,42 @@ impl TransportManager {
         // Spawn a task to accept the link
         let c_manager = self.clone();
         task::spawn(async move {
-            if let Err(e) = super::establishment::accept::accept_link(&link, &c_manager)
-                .timeout(c_manager.config.unicast.accept_timeout)
-                .await
-            {
-                log::debug!("{}", e);
-                let _ = link.close().await;
-            }
+            // The accept is interrupted by the close of the manager
+            let closed = async {
+                c_manager.state.unicast.closing.wait().await;
+                None
+            };
+            let accepted = async {
+                Some(
+                    super::establishment::accept::accept_link(&link, &c_manager)
+                        .timeout(c_manager.config.unicast.accept_timeout)
+                        .await,
+                )
+            };
+            let failed = match accepted.race(closed).await {
+                // The link is closed by the accept on failure
+                Some(Ok(res)) => res.is_err(),
+                Some(Err(e)) => {
+                    log::debug!("{}", e);
+                    let _ = link.close().await;
+                    true
+                }
+                None => {
+                    log::debug!("Accept of {} interrupted by the close of the manager", link);
+                    let _ = link.close().await;
+                    false
+                }
+            };
+            zlock!(c_manager.state.unicast.sources).release(
+                &source,
+                failed,
+                c_manager.config.clock.now(),
+            );
             let mut guard = zasynclock!(c_manager.state.unicast.incoming);
             *guard -= 1;
+            if *guard == 0 {
+                c_manager.state.unicast.incoming_drained.notify_all();
+            }
         });
     }
 }
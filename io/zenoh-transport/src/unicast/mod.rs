@@ -14,6 +14,7 @@
 pub mod establishment;
 pub(crate) mod lowlatency;
 pub(crate) mod manager;
+pub(crate) mod sources;
 pub(crate) mod transport_unicast_inner;
 pub(crate) mod universal;
 
@@ -25,20 +26,23 @@ pub(crate) mod shared_memory_unicast;
 
 use self::transport_unicast_inner::TransportUnicastTrait;
 
-use super::{TransportPeer, TransportPeerEventHandler};
+use super::{LinkRtt, TransportPeer, TransportPeerEventHandler};
 #[cfg(feature = "transport_multilink")]
 use establishment::ext::auth::ZPublicKey;
 pub use manager::*;
+use serde::{Serialize, Serializer};
 use std::fmt;
 use std::sync::{Arc, Weak};
+use std::time::Duration;
 use zenoh_core::zcondfeat;
-use zenoh_link::Link;
-use zenoh_protocol::network::NetworkMessage;
+use zenoh_link::{Link, Locator};
+use zenoh_protocol::network::{DeclareBody, NetworkBody, NetworkMessage, Push, UnknownKind};
 use zenoh_protocol::{
     core::{Bits, WhatAmI, ZenohId},
-    transport::{close, TransportSn},
+    transport::{close, BatchSize, TransportSn},
+    zenoh::PushBody,
 };
-use zenoh_result::{zerror, ZResult};
+use zenoh_result::{bail, zerror, ZResult};
 
 /*************************************/
 /*        TRANSPORT UNICAST          */
@@ -49,12 +53,85 @@ pub(crate) struct TransportConfigUnicast {
     pub(crate) whatami: WhatAmI,
     pub(crate) sn_resolution: Bits,
     pub(crate) tx_initial_sn: TransportSn,
+    // The size of the batches agreed with the other node
+    pub(crate) batch_size: BatchSize,
     pub(crate) is_qos: bool,
     #[cfg(feature = "transport_multilink")]
     pub(crate) multilink: Option<ZPublicKey>,
     #[cfg(feature = "shared-memory")]
     pub(crate) is_shm: bool,
+    // Whether the alignment of the shared memory buffers is carried in their descriptors
+    #[cfg(feature = "shared-memory")]
+    pub(crate) is_shm_aligned: bool,
+    // Whether both nodes enable the shared memory but its challenge couldn't be completed
+    #[cfg(feature = "shared-memory")]
+    pub(crate) is_shm_downgraded: bool,
     pub(crate) is_lowlatency: bool,
+    // Whether the key expression mappings are resynchronized in bulk on reconnect
+    pub(crate) is_resync: bool,
+    // Whether the key expressions declared in bulk are prefix-compressed
+    pub(crate) is_prefix_compression: bool,
+    // Whether the integrity checksums of the payloads are forwarded to the other node
+    pub(crate) is_integrity: bool,
+    // Whether the transport was opened by this node (or accepted from the other node)
+    pub(crate) is_initiator: bool,
+    // The user the other node authenticated as when this node accepted the transport
+    pub(crate) auth_user: Option<String>,
+    // The lease advertised by the other node
+    pub(crate) lease: Duration,
+}
+
+/// The parameters negotiated with the other node of a [`TransportUnicast`] and its current links.
+#[derive(Clone, Debug, Serialize, PartialEq, Eq)]
+pub struct TransportUnicastInfo {
+    pub zid: ZenohId,
+    pub whatami: WhatAmI,
+    /// The resolution of the sequence numbers of the frames.
+    pub sn_resolution: Bits,
+    pub is_qos: bool,
+    /// Always `false` when zenoh is built without shared memory support.
+    pub is_shm: bool,
+    pub is_lowlatency: bool,
+    pub is_resync: bool,
+    pub is_prefix_compression: bool,
+    pub is_integrity: bool,
+    pub is_initiator: bool,
+    /// The lease advertised by the other node: this node closes the transport when it receives
+    /// nothing from the other node during the lease, and the other node expects to receive
+    /// something from this node at least once per lease.
+    #[serde(rename = "lease_ms", serialize_with = "serialize_millis")]
+    pub lease: Duration,
+    /// The links of the transport, with their locators and MTU.
+    pub links: Vec<Link>,
+}
+
+// Prefix-compresses the key expressions declared in bulk if the other node supports it, the ones
+// declared one by one being compressed in the frames by the transmission pipeline
+fn compress_declarations(transport: &Arc<dyn TransportUnicastTrait>, message: &mut NetworkMessage) {
+    if let NetworkBody::Declare(declare) = &mut message.body {
+        if let DeclareBody::DeclareKeyExprs(m) = &mut declare.body {
+            if transport.get_config().is_prefix_compression && !m.compressed {
+                m.compress();
+            }
+        }
+    }
+}
+
+// Strips the integrity checksums of the payloads if the other node doesn't support them
+fn strip_integrity(transport: &Arc<dyn TransportUnicastTrait>, message: &mut NetworkMessage) {
+    if let NetworkBody::Push(Push {
+        payload: PushBody::Put(put),
+        ..
+    }) = &mut message.body
+    {
+        if !transport.get_config().is_integrity {
+            put.ext_integrity = None;
+        }
+    }
+}
+
+fn serialize_millis<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_u128(duration.as_millis())
 }
 
 /// [`TransportUnicast`] is the transport handler returned
@@ -82,6 +159,13 @@ impl TransportUnicast {
         Ok(transport.get_whatami())
     }
 
+    /// Returns `true` if the transport negotiated QoS, i.e. has a queue per priority.
+    #[inline(always)]
+    pub fn is_qos(&self) -> ZResult<bool> {
+        let transport = self.get_inner()?;
+        Ok(transport.is_qos())
+    }
+
     #[cfg(feature = "shared-memory")]
     #[inline(always)]
     pub fn is_shm(&self) -> ZResult<bool> {
@@ -89,6 +173,85 @@ impl TransportUnicast {
         Ok(transport.is_shm())
     }
 
+    /// Returns the size of the batches agreed with the other node, bounding the size of the
+    /// messages sent without fragmentation.
+    #[inline(always)]
+    pub fn get_batch_size(&self) -> ZResult<BatchSize> {
+        let transport = self.get_inner()?;
+        Ok(transport.get_config().batch_size)
+    }
+
+    /// Returns `true` if the transport was opened by this node, `false` if it was accepted.
+    #[inline(always)]
+    pub fn is_initiator(&self) -> ZResult<bool> {
+        let transport = self.get_inner()?;
+        Ok(transport.get_config().is_initiator)
+    }
+
+    /// Returns `true` if the transport negotiated the lowlatency mode, i.e. writes each message
+    /// directly on its single link.
+    #[inline(always)]
+    pub fn is_lowlatency(&self) -> ZResult<bool> {
+        let transport = self.get_inner()?;
+        Ok(transport.get_config().is_lowlatency)
+    }
+
+    /// Returns `true` if the transport negotiated the bulk resynchronization of the key
+    /// expression mappings on reconnect.
+    #[inline(always)]
+    pub fn is_resync(&self) -> ZResult<bool> {
+        let transport = self.get_inner()?;
+        Ok(transport.get_config().is_resync)
+    }
+
+    /// Returns `true` if the transport negotiated the prefix compression of the key expressions
+    /// declared in bulk.
+    #[inline(always)]
+    pub fn is_prefix_compression(&self) -> ZResult<bool> {
+        let transport = self.get_inner()?;
+        Ok(transport.get_config().is_prefix_compression)
+    }
+
+    /// Returns `true` if the transport negotiated the integrity checksums of the payloads of
+    /// the publications, `false` if they are stripped from the messages sent to the other node.
+    #[inline(always)]
+    pub fn is_integrity(&self) -> ZResult<bool> {
+        let transport = self.get_inner()?;
+        Ok(transport.get_config().is_integrity)
+    }
+
+    /// Returns the user the other node authenticated as, if this node accepted the transport
+    /// with user-password authentication.
+    #[inline(always)]
+    pub fn get_auth_user(&self) -> ZResult<Option<String>> {
+        let transport = self.get_inner()?;
+        Ok(transport.get_config().auth_user.clone())
+    }
+
+    /// Returns the parameters negotiated with the other node and the current links of the transport.
+    pub fn info(&self) -> ZResult<TransportUnicastInfo> {
+        let transport = self.get_inner()?;
+        let config = transport.get_config();
+        Ok(TransportUnicastInfo {
+            zid: config.zid,
+            whatami: config.whatami,
+            sn_resolution: config.sn_resolution,
+            is_qos: config.is_qos,
+            is_shm: zcondfeat!("shared-memory", config.is_shm, false),
+            is_lowlatency: config.is_lowlatency,
+            is_resync: config.is_resync,
+            is_prefix_compression: config.is_prefix_compression,
+            is_integrity: config.is_integrity,
+            is_initiator: config.is_initiator,
+            lease: config.lease,
+            links: transport
+                .get_links()
+                .into_iter()
+                .map(|l| l.into())
+                .collect(),
+        })
+    }
+
     #[inline(always)]
     pub fn get_callback(&self) -> ZResult<Option<Arc<dyn TransportPeerEventHandler>>> {
         let transport = self.get_inner()?;
@@ -106,6 +269,7 @@ impl TransportUnicast {
                 .map(|l| l.into())
                 .collect(),
             is_qos: transport.is_qos(),
+            is_initiator: transport.get_config().is_initiator,
             #[cfg(feature = "shared-memory")]
             is_shm: transport.is_shm(),
         };
@@ -122,24 +286,82 @@ impl TransportUnicast {
             .collect())
     }
 
+    /// Returns the links of the transport with their round-trip time estimate, which stays `None`
+    /// unless the keep-alives request an echo and the remote node supports it.
+    #[inline(always)]
+    pub fn get_links_rtt(&self) -> ZResult<Vec<(Link, Option<LinkRtt>)>> {
+        let transport = self.get_inner()?;
+        Ok(transport
+            .get_links_rtt()
+            .into_iter()
+            .map(|(l, rtt)| (l.into(), rtt))
+            .collect())
+    }
+
+    /// Returns the number of messages and declarations of each unknown kind skipped on the
+    /// transport, sent by a remote node running a newer version.
+    #[inline(always)]
+    pub fn get_unknown_kinds(&self) -> ZResult<Vec<(UnknownKind, usize)>> {
+        let transport = self.get_inner()?;
+        Ok(transport.get_unknown_kinds())
+    }
+
     #[inline(always)]
-    pub fn schedule(&self, message: NetworkMessage) -> ZResult<()> {
+    pub fn schedule(&self, mut message: NetworkMessage) -> ZResult<()> {
         let transport = self.get_inner()?;
+        compress_declarations(&transport, &mut message);
+        strip_integrity(&transport, &mut message);
         transport.schedule(message)
     }
 
+    /// Schedules a message like [`schedule`](Self::schedule), returning an acknowledgement that
+    /// is signaled once the peer received the message, or disconnected if the message or the
+    /// acknowledgement of the peer is lost. The low latency transports don't support them.
     #[inline(always)]
-    pub async fn close_link(&self, link: &Link) -> ZResult<()> {
+    pub fn schedule_with_ack(&self, mut message: NetworkMessage) -> ZResult<flume::Receiver<()>> {
+        let transport = self.get_inner()?;
+        compress_declarations(&transport, &mut message);
+        strip_integrity(&transport, &mut message);
+        transport.schedule_with_ack(message)
+    }
+
+    /// Closes a link of the transport, notifying the remote node with the given [`close::reason`].
+    ///
+    /// The messages already scheduled on the link are sent, then the remote node stops scheduling
+    /// messages on the link and acknowledges the close, the transport staying up on its other
+    /// links. Closing the last link closes the transport.
+    #[inline(always)]
+    pub async fn close_link(&self, link: &Link, reason: u8) -> ZResult<()> {
         let transport = self.get_inner()?;
         let link = transport
             .get_links()
             .into_iter()
             .find(|l| l.get_src() == &link.src && l.get_dst() == &link.dst)
             .ok_or_else(|| zerror!("Invalid link"))?;
-        transport.close_link(&link, close::reason::GENERIC).await?;
+        transport.close_link(&link, reason).await?;
         Ok(())
     }
 
+    /// Removes the link of the transport with the given destination locator, the transport staying
+    /// up on its other links.
+    ///
+    /// The last link of the transport can't be removed, the transport has to be closed instead.
+    pub async fn del_link(&self, locator: &Locator) -> ZResult<()> {
+        let transport = self.get_inner()?;
+        let links = transport.get_links();
+        let link = links
+            .iter()
+            .find(|l| l.get_dst() == locator)
+            .ok_or_else(|| zerror!("No link to {} in the transport", locator))?;
+        if links.len() == 1 {
+            bail!(
+                "Can not remove {}: it is the last link of the transport",
+                link
+            );
+        }
+        transport.close_link(link, close::reason::GENERIC).await
+    }
+
     #[inline(always)]
     pub async fn close(&self) -> ZResult<()> {
         // Return Ok if the transport has already been closed
@@ -149,6 +371,16 @@ impl TransportUnicast {
         }
     }
 
+    /// Closes the transport, notifying the remote node with the given [`close::reason`].
+    #[inline(always)]
+    pub async fn close_with_reason(&self, reason: u8) -> ZResult<()> {
+        // Return Ok if the transport has already been closed
+        match self.get_inner() {
+            Ok(transport) => transport.close(reason).await,
+            Err(_) => Ok(()),
+        }
+    }
+
     #[cfg(feature = "stats")]
     pub fn get_stats(&self) -> ZResult<Arc<crate::stats::TransportStats>> {
         Ok(self.get_inner()?.stats())
@@ -182,6 +414,7 @@ impl fmt::Debug for TransportUnicast {
                             .field("whatami", &transport.get_whatami())
                             .field("is_qos", &transport.is_qos())
                             .field("is_shm", &is_shm)
+                            .field("is_initiator", &transport.get_config().is_initiator)
                             .field("links", &transport.get_links()),
                     )
                     .finish()
@@ -13,22 +13,36 @@
 //
 use async_std::sync::RwLock;
 use rand::{Rng, SeedableRng};
-use zenoh_core::zerror;
+use std::collections::HashMap;
+use std::convert::TryInto;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use zenoh_core::{zasyncwrite, zerror, zlock};
 use zenoh_crypto::PseudoRng;
 use zenoh_result::ZResult;
-use zenoh_shm::{SharedMemoryBuf, SharedMemoryManager, SharedMemoryReader};
+use zenoh_shm::{SharedMemoryBuf, SharedMemoryBufInfo, SharedMemoryManager, SharedMemoryReader};
 
 pub(crate) type Challenge = u64;
 const NAME: &str = "zshm";
+// The maximum number of challenges pending at once
+const CHALLENGES: usize = 256;
+// The room taken by a challenge in the shared memory, its chunk header and padding included
+const CHALLENGE_ROOM: usize = 64;
+// How long a challenge is kept for the other node to read it
+const CHALLENGE_LIFETIME: Duration = Duration::from_secs(10);
 
 /*************************************/
 /*          Authenticator            */
 /*************************************/
+/// Verifies that the other node of a transport is on the same host during the establishment:
+/// each node writes a fresh nonce in a temporary buffer of its shared memory, which the other
+/// node must read and echo back.
 pub(crate) struct SharedMemoryUnicast {
     // Rust guarantees that fields are dropped in the order of declaration.
-    // Buffer needs to be dropped before the manager.
-    pub(crate) challenge: SharedMemoryBuf,
-    pub(crate) _manager: SharedMemoryManager,
+    // Buffers need to be dropped before the manager.
+    challenges: Mutex<HashMap<Challenge, (SharedMemoryBuf, Instant)>>,
+    manager: Mutex<SharedMemoryManager>,
+    prng: Mutex<PseudoRng>,
     pub(crate) reader: RwLock<SharedMemoryReader>,
 }
 
@@ -36,22 +50,112 @@ unsafe impl Sync for SharedMemoryUnicast {}
 
 impl SharedMemoryUnicast {
     pub fn make() -> ZResult<SharedMemoryUnicast> {
-        // Create a challenge for session establishment
         let mut prng = PseudoRng::from_entropy();
-        let nonce = prng.gen::<Challenge>();
-        let size = std::mem::size_of::<Challenge>();
-
-        let mut _manager = SharedMemoryManager::make(format!("{NAME}.{nonce}"), size)?;
-
-        let mut challenge = _manager.alloc(size).map_err(|e| zerror!("{e}"))?;
-        let slice = unsafe { challenge.as_mut_slice() };
-        slice[0..size].copy_from_slice(&nonce.to_le_bytes());
+        let id = prng.gen::<u64>();
+        let manager =
+            SharedMemoryManager::make(format!("{NAME}.{id}"), CHALLENGES * CHALLENGE_ROOM)?;
 
         let shmauth = SharedMemoryUnicast {
-            challenge,
-            _manager,
+            challenges: Mutex::new(HashMap::new()),
+            manager: Mutex::new(manager),
+            prng: Mutex::new(prng),
             reader: RwLock::new(SharedMemoryReader::new()),
         };
         Ok(shmauth)
     }
+
+    /// Writes a fresh nonce in a temporary buffer of the shared memory, kept until released or
+    /// for [`CHALLENGE_LIFETIME`]. Returns the nonce and the buffer to send to the other node.
+    pub(crate) fn new_challenge(&self) -> ZResult<(Challenge, SharedMemoryBufInfo)> {
+        let now = Instant::now();
+        let mut challenges = zlock!(self.challenges);
+        challenges.retain(|_, (_, at)| now.duration_since(*at) < CHALLENGE_LIFETIME);
+
+        let size = std::mem::size_of::<Challenge>();
+        let mut manager = zlock!(self.manager);
+        let mut buffer = match manager.alloc(size) {
+            Ok(buffer) => buffer,
+            Err(_) => {
+                manager.defragment();
+                manager
+                    .alloc(size)
+                    .map_err(|e| zerror!("Too many pending challenges: {e}"))?
+            }
+        };
+        drop(manager);
+
+        let nonce = zlock!(self.prng).gen::<Challenge>();
+        let slice = unsafe { buffer.as_mut_slice() };
+        slice[0..size].copy_from_slice(&nonce.to_le_bytes());
+        let info = buffer.info.clone();
+        challenges.insert(nonce, (buffer, now));
+        Ok((nonce, info))
+    }
+
+    /// Releases the buffer of a challenge answered by the other node, or that can no longer be.
+    pub(crate) fn release(&self, challenge: Challenge) {
+        zlock!(self.challenges).remove(&challenge);
+    }
+
+    /// Reads the nonce written by the other node in its shared memory.
+    pub(crate) async fn read_challenge(&self, info: &SharedMemoryBufInfo) -> ZResult<Challenge> {
+        let buffer = zasyncwrite!(self.reader).read_shmbuf(info)?;
+        // The buffer is only read: the reference count decremented when it is dropped is the one
+        // of its owner, which releases it
+        buffer.inc_ref_count();
+        let bytes: [u8; std::mem::size_of::<Challenge>()] = buffer
+            .as_slice()
+            .try_into()
+            .map_err(|_| zerror!("Invalid challenge of {} bytes", buffer.len()))?;
+        Ok(Challenge::from_le_bytes(bytes))
+    }
+
+    #[cfg(test)]
+    pub(crate) fn pending(&self) -> usize {
+        zlock!(self.challenges).len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shm_challenges() {
+        let alice = SharedMemoryUnicast::make().unwrap();
+        let bob = SharedMemoryUnicast::make().unwrap();
+
+        // Each challenge is a fresh nonce, read as written by the other node
+        let (c1, info1) = alice.new_challenge().unwrap();
+        let (c2, info2) = alice.new_challenge().unwrap();
+        assert_ne!(c1, c2);
+        async_std::task::block_on(async {
+            assert_eq!(bob.read_challenge(&info1).await.unwrap(), c1);
+            assert_eq!(bob.read_challenge(&info2).await.unwrap(), c2);
+            // Reading a challenge again doesn't release it
+            assert_eq!(bob.read_challenge(&info1).await.unwrap(), c1);
+        });
+        assert_eq!(alice.pending(), 2);
+
+        // The released buffers are reused by the next challenges
+        alice.release(c1);
+        alice.release(c2);
+        assert_eq!(alice.pending(), 0);
+        for _ in 0..4 * CHALLENGES {
+            let (c, _) = alice.new_challenge().unwrap();
+            alice.release(c);
+        }
+
+        // The pending challenges are bounded
+        let mut pending = vec![];
+        while let Ok((c, _)) = alice.new_challenge() {
+            pending.push(c);
+            assert!(pending.len() <= CHALLENGES * CHALLENGE_ROOM);
+        }
+        assert!(pending.len() >= CHALLENGES);
+        for c in pending {
+            alice.release(c);
+        }
+        assert!(alice.new_challenge().is_ok());
+    }
 }
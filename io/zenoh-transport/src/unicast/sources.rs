@@ -0,0 +1,235 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::time::{Duration, Instant};
+use zenoh_link::LinkUnicast;
+
+/// The minimum number of sources before the sources without any pending link, recent failure
+/// or ban are forgotten.
+const MIN_SOURCES_CLEANUP: usize = 1024;
+
+/// The address of the source of an accepted link, without its port: all the links of a host share it.
+pub(crate) fn source_address(link: &LinkUnicast) -> String {
+    let address = link.get_dst().address();
+    match address.as_str().rsplit_once(':') {
+        Some((host, port)) if !port.is_empty() && port.bytes().all(|b| b.is_ascii_digit()) => {
+            host.to_string()
+        }
+        _ => address.to_string(),
+    }
+}
+
+/// Why the links of a source are refused.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum SourceRefusal {
+    /// The source has too many links pending in the accept phase.
+    Pending,
+    /// The source failed too many accepts recently.
+    Banned,
+}
+
+impl fmt::Display for SourceRefusal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SourceRefusal::Pending => write!(f, "too many links pending in the accept phase"),
+            SourceRefusal::Banned => write!(f, "too many failed accepts"),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Source {
+    pending: usize,
+    // The times of the failed accepts within the ban period, oldest first
+    failures: VecDeque<Instant>,
+    banned_until: Option<Instant>,
+}
+
+impl Source {
+    // Forgets the failures and the ban older than the ban period
+    fn expire(&mut self, now: Instant, ban_period: Duration) {
+        while self
+            .failures
+            .front()
+            .map_or(false, |t| now.saturating_duration_since(*t) > ban_period)
+        {
+            self.failures.pop_front();
+        }
+        if self.banned_until.map_or(false, |t| now >= t) {
+            self.banned_until = None;
+        }
+    }
+
+    fn is_idle(&self) -> bool {
+        self.pending == 0 && self.failures.is_empty() && self.banned_until.is_none()
+    }
+}
+
+/// The links pending in the accept phase and the recently failed accepts, per source address,
+/// so that a single source can't starve the others of the `accept_pending` links.
+pub(crate) struct AcceptSources {
+    pending_per_source: usize,
+    failures_per_source: Option<usize>,
+    ban_period: Duration,
+    sources: HashMap<String, Source>,
+    next_cleanup: usize,
+}
+
+impl AcceptSources {
+    pub(crate) fn new(
+        pending_per_source: usize,
+        failures_per_source: Option<usize>,
+        ban_period: Duration,
+    ) -> Self {
+        AcceptSources {
+            pending_per_source,
+            failures_per_source,
+            ban_period,
+            sources: HashMap::new(),
+            next_cleanup: MIN_SOURCES_CLEANUP,
+        }
+    }
+
+    /// Admits a new link from `source` in the accept phase, unless the source is over its quota
+    /// of pending links or banned. An admitted link must be released once accepted or failed.
+    pub(crate) fn admit(&mut self, source: &str, now: Instant) -> Result<(), SourceRefusal> {
+        if self.sources.len() >= self.next_cleanup {
+            let ban_period = self.ban_period;
+            self.sources.retain(|_, s| {
+                s.expire(now, ban_period);
+                !s.is_idle()
+            });
+            self.next_cleanup = MIN_SOURCES_CLEANUP.max(2 * self.sources.len());
+        }
+
+        let s = self.sources.entry(source.to_string()).or_default();
+        s.expire(now, self.ban_period);
+        if s.banned_until.is_some() {
+            return Err(SourceRefusal::Banned);
+        }
+        if s.pending >= self.pending_per_source {
+            return Err(SourceRefusal::Pending);
+        }
+        s.pending += 1;
+        Ok(())
+    }
+
+    /// Releases a link of `source` admitted in the accept phase, banning the source for the ban
+    /// period if the accept failed and too many accepts of the source failed within the period.
+    pub(crate) fn release(&mut self, source: &str, failed: bool, now: Instant) {
+        let Some(s) = self.sources.get_mut(source) else {
+            return;
+        };
+        s.pending = s.pending.saturating_sub(1);
+        s.expire(now, self.ban_period);
+        if failed {
+            s.failures.push_back(now);
+            if let Some(max) = self.failures_per_source {
+                if s.failures.len() >= max {
+                    s.failures.clear();
+                    s.banned_until = Some(now + self.ban_period);
+                }
+            }
+        }
+        if s.is_idle() {
+            self.sources.remove(source);
+        }
+    }
+
+    /// The number of links of `source` pending in the accept phase.
+    #[cfg(test)]
+    fn pending(&self, source: &str) -> usize {
+        self.sources.get(source).map_or(0, |s| s.pending)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BAN_PERIOD: Duration = Duration::from_secs(60);
+
+    #[test]
+    fn accept_sources_same_address() {
+        let mut sources = AcceptSources::new(3, Some(2), BAN_PERIOD);
+        let now = Instant::now();
+
+        // Many links from the same address: only the quota is admitted
+        for _ in 0..3 {
+            assert_eq!(sources.admit("10.0.0.1", now), Ok(()));
+        }
+        for _ in 0..10 {
+            assert_eq!(sources.admit("10.0.0.1", now), Err(SourceRefusal::Pending));
+        }
+        assert_eq!(sources.pending("10.0.0.1"), 3);
+
+        // An accepted link frees a slot
+        sources.release("10.0.0.1", false, now);
+        assert_eq!(sources.admit("10.0.0.1", now), Ok(()));
+
+        // Failed accepts ban the address for the ban period, even with free slots
+        sources.release("10.0.0.1", true, now);
+        sources.release("10.0.0.1", true, now);
+        assert_eq!(sources.pending("10.0.0.1"), 1);
+        assert_eq!(sources.admit("10.0.0.1", now), Err(SourceRefusal::Banned));
+        let later = now + BAN_PERIOD - Duration::from_secs(1);
+        assert_eq!(sources.admit("10.0.0.1", later), Err(SourceRefusal::Banned));
+        let after = now + BAN_PERIOD;
+        assert_eq!(sources.admit("10.0.0.1", after), Ok(()));
+
+        // Failures older than the ban period are forgotten
+        sources.release("10.0.0.1", true, after);
+        sources.release("10.0.0.1", true, after + BAN_PERIOD * 2);
+        assert_eq!(sources.admit("10.0.0.1", after + BAN_PERIOD * 2), Ok(()));
+    }
+
+    #[test]
+    fn accept_sources_distinct_addresses() {
+        let mut sources = AcceptSources::new(3, Some(2), BAN_PERIOD);
+        let now = Instant::now();
+
+        // Links from distinct addresses are admitted while one address is over its quota
+        for _ in 0..3 {
+            assert_eq!(sources.admit("10.0.0.1", now), Ok(()));
+        }
+        assert_eq!(sources.admit("10.0.0.1", now), Err(SourceRefusal::Pending));
+        for i in 2..100 {
+            assert_eq!(sources.admit(&format!("10.0.0.{i}"), now), Ok(()));
+        }
+
+        // A banned address doesn't affect the others
+        sources.release("10.0.0.1", true, now);
+        sources.release("10.0.0.1", true, now);
+        assert_eq!(sources.admit("10.0.0.1", now), Err(SourceRefusal::Banned));
+        sources.release("10.0.0.2", true, now);
+        assert_eq!(sources.admit("10.0.0.2", now), Ok(()));
+
+        // Idle addresses are forgotten
+        for i in 3..100 {
+            sources.release(&format!("10.0.0.{i}"), false, now);
+        }
+        assert_eq!(sources.sources.len(), 2);
+    }
+
+    #[test]
+    fn accept_sources_no_ban() {
+        let mut sources = AcceptSources::new(1, None, BAN_PERIOD);
+        let now = Instant::now();
+        for _ in 0..100 {
+            assert_eq!(sources.admit("10.0.0.1", now), Ok(()));
+            sources.release("10.0.0.1", true, now);
+        }
+    }
+}
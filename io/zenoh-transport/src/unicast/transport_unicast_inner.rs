@@ -19,12 +19,12 @@ use async_trait::async_trait;
 use zenoh_link::{LinkUnicast, LinkUnicastDirection};
 use zenoh_protocol::{
     core::{WhatAmI, ZenohId},
-    network::NetworkMessage,
+    network::{NetworkMessage, UnknownKind},
     transport::TransportSn,
 };
 use zenoh_result::ZResult;
 
-use crate::{TransportConfigUnicast, TransportExecutor, TransportPeerEventHandler};
+use crate::{LinkRtt, TransportConfigUnicast, TransportExecutor, TransportPeerEventHandler};
 
 /*************************************/
 /*      UNICAST TRANSPORT TRAIT      */
@@ -40,6 +40,9 @@ pub(crate) trait TransportUnicastTrait: Send + Sync {
     fn get_whatami(&self) -> WhatAmI;
     fn get_callback(&self) -> Option<Arc<dyn TransportPeerEventHandler>>;
     fn get_links(&self) -> Vec<LinkUnicast>;
+    fn get_links_with_direction(&self, direction: &LinkUnicastDirection) -> Vec<LinkUnicast>;
+    fn get_links_rtt(&self) -> Vec<(LinkUnicast, Option<LinkRtt>)>;
+    fn get_unknown_kinds(&self) -> Vec<(UnknownKind, usize)>;
     #[cfg(feature = "shared-memory")]
     fn is_shm(&self) -> bool;
     fn is_qos(&self) -> bool;
@@ -56,6 +59,7 @@ pub(crate) trait TransportUnicastTrait: Send + Sync {
     /*                TX                 */
     /*************************************/
     fn schedule(&self, msg: NetworkMessage) -> ZResult<()>;
+    fn schedule_with_ack(&self, msg: NetworkMessage) -> ZResult<flume::Receiver<()>>;
     fn start_tx(
         &self,
         link: &LinkUnicast,
@@ -17,22 +17,34 @@ use crate::common::pipeline::{
     TransmissionPipelineProducer,
 };
 use crate::common::priority::TransportPriorityTx;
+use crate::common::rtt::RttEstimator;
+use crate::common::scheduler::{TxShare, TxTurnGuard};
 #[cfg(feature = "stats")]
 use crate::common::stats::TransportStats;
-use crate::TransportExecutor;
+use crate::{TransportCloseReason, TransportExecutor};
 use async_std::prelude::FutureExt;
 use async_std::task;
 use async_std::task::JoinHandle;
 
 #[cfg(all(feature = "unstable", feature = "transport_compression"))]
 use std::convert::TryInto;
+use std::future::Future;
 use std::sync::Arc;
 use std::time::Duration;
 use zenoh_buffers::ZSlice;
 use zenoh_link::{LinkUnicast, LinkUnicastDirection};
-use zenoh_protocol::transport::{BatchSize, KeepAlive, TransportMessage};
+use zenoh_protocol::transport::{
+    close::CloseReason, keepalive, BatchSize, KeepAlive, TransportMessage,
+};
 use zenoh_result::{bail, zerror, ZResult};
-use zenoh_sync::{RecyclingObjectPool, Signal};
+use zenoh_sync::Signal;
+#[cfg(all(feature = "unstable", feature = "transport_compression"))]
+use zenoh_sync::{BufferPool, PooledBuffer};
+use zenoh_util::clock::{timeout, Clock};
+use zenoh_util::cooperate::Cooperate;
+
+// The number of batches read in a row before yielding, the reads of a busy link being always ready
+const RX_COOPERATE_BUDGET: usize = 64;
 
 #[cfg(all(feature = "unstable", feature = "transport_compression"))]
 const HEADER_BYTES_SIZE: usize = 2;
@@ -63,12 +75,19 @@ pub(super) struct TransportLinkUnicast {
     pub(super) link: LinkUnicast,
     // The transmission pipeline
     pub(super) pipeline: Option<TransmissionPipelineProducer>,
+    // The round-trip time estimate, from the echoes of the keep-alives
+    pub(super) rtt: Arc<RttEstimator>,
     // The transport this link is associated to
     transport: TransportUnicastUniversal,
     // The signals to stop TX/RX tasks
     handle_tx: Option<Arc<async_executor::Task<()>>>,
     signal_rx: Signal,
     handle_rx: Option<Arc<JoinHandle<()>>>,
+    // Set once the link is being closed, triggered when the close is acknowledged by the peer
+    closing: Option<Signal>,
+    // Set once the transport is being closed with the given reason, triggered when the peer
+    // echoes the Close
+    closing_transport: Option<(Signal, u8)>,
 }
 
 impl TransportLinkUnicast {
@@ -77,14 +96,18 @@ impl TransportLinkUnicast {
         link: LinkUnicast,
         direction: LinkUnicastDirection,
     ) -> TransportLinkUnicast {
+        let rtt = Arc::new(RttEstimator::new(transport.manager.config.clock.clone()));
         TransportLinkUnicast {
             direction,
             transport,
             link,
             pipeline: None,
+            rtt,
             handle_tx: None,
             signal_rx: Signal::new(),
             handle_rx: None,
+            closing: None,
+            closing_transport: None,
         }
     }
 }
@@ -100,6 +123,7 @@ impl TransportLinkUnicast {
         if self.handle_tx.is_none() {
             let config = TransmissionPipelineConf {
                 is_streamed: self.link.is_streamed(),
+                is_prefix_compression: self.transport.config.is_prefix_compression,
                 batch_size: batch_size.min(self.link.get_mtu()),
                 queue_size: self.transport.manager.config.queue_size,
                 backoff: self.transport.manager.config.queue_backoff,
@@ -115,11 +139,24 @@ impl TransportLinkUnicast {
             // Spawn the TX task
             let c_link = self.link.clone();
             let c_transport = self.transport.clone();
+            let c_clock = self.transport.manager.config.clock.clone();
+            let c_stall_timeout = self.transport.manager.config.tx_stall_timeout;
+            let c_rtt = self
+                .transport
+                .manager
+                .config
+                .rtt_probe
+                .then(|| self.rtt.clone());
+            let c_share = self.transport.tx_share.clone();
             let handle = executor.spawn(async move {
                 let res = tx_task(
                     consumer,
                     c_link.clone(),
                     keep_alive,
+                    c_stall_timeout,
+                    c_clock,
+                    c_rtt,
+                    c_share,
                     #[cfg(feature = "stats")]
                     c_transport.stats.clone(),
                     #[cfg(all(feature = "unstable", feature = "transport_compression"))]
@@ -150,6 +187,7 @@ impl TransportLinkUnicast {
             let c_transport = self.transport.clone();
             let c_signal = self.signal_rx.clone();
             let c_rx_buffer_size = self.transport.manager.config.link_rx_buffer_size;
+            let c_clock = self.transport.manager.config.clock.clone();
 
             let handle = task::spawn(async move {
                 // Start the consume task
@@ -160,11 +198,26 @@ impl TransportLinkUnicast {
                     c_signal.clone(),
                     batch_size,
                     c_rx_buffer_size,
+                    c_clock,
                 )
                 .await;
                 c_signal.trigger();
                 if let Err(e) = res {
                     log::debug!("{}", e);
+                    // The peer closed the link while it was closing: this acknowledges the close
+                    if c_transport.ack_close(&c_link) {
+                        return;
+                    }
+                    let reason = e
+                        .downcast_ref::<TransportCloseReason>()
+                        .cloned()
+                        .unwrap_or_else(|| {
+                            TransportCloseReason::local(
+                                CloseReason::Generic,
+                                Some(format!("{}: link lost", c_link)),
+                            )
+                        });
+                    c_transport.set_link_close_reason(&c_link, reason);
                     // Spawn a task to avoid a deadlock waiting for this same task
                     // to finish in the close() joining its handle
                     task::spawn(async move { c_transport.del_link(&c_link).await });
@@ -178,6 +231,34 @@ impl TransportLinkUnicast {
         self.signal_rx.trigger();
     }
 
+    /// Whether the link is being closed: no new message is scheduled on a closing link.
+    pub(super) fn is_closing(&self) -> bool {
+        self.closing.is_some() || self.closing_transport.is_some()
+    }
+
+    /// Marks the link as closing, returning the signal triggered on the acknowledgment of the close.
+    pub(super) fn start_closing(&mut self) -> Signal {
+        self.closing.get_or_insert_with(Signal::new).clone()
+    }
+
+    pub(super) fn closing(&self) -> Option<&Signal> {
+        self.closing.as_ref()
+    }
+
+    /// Marks the link as closing with its transport, returning the signal triggered when the peer
+    /// echoes the Close sent with `reason`.
+    pub(super) fn start_closing_transport(&mut self, reason: u8) -> Signal {
+        self.closing_transport
+            .get_or_insert_with(|| (Signal::new(), reason))
+            .0
+            .clone()
+    }
+
+    /// The signal and the reason of the close of the transport, if it is being closed.
+    pub(super) fn closing_transport(&self) -> Option<&(Signal, u8)> {
+        self.closing_transport.as_ref()
+    }
+
     pub(super) async fn close(mut self) -> ZResult<()> {
         log::trace!("{}: closing", self.link);
         self.stop_rx();
@@ -201,10 +282,40 @@ impl TransportLinkUnicast {
 /*************************************/
 /*              TASKS                */
 /*************************************/
+// Runs a write on the link, failing once it didn't complete within `stall_timeout`: the peer
+// may keep the lease alive while the link doesn't drain anymore.
+async fn watch_write<T>(
+    link: &LinkUnicast,
+    write: impl Future<Output = ZResult<T>>,
+    stall_timeout: Duration,
+    clock: &dyn Clock,
+) -> ZResult<T> {
+    if stall_timeout.is_zero() {
+        return write.await;
+    }
+    let start = clock.now();
+    match timeout(clock, stall_timeout, write).await {
+        Some(res) => res,
+        None => {
+            let stall = clock.now().saturating_duration_since(start);
+            log::error!(
+                "Alarm: closing link {}: a write waited {} ms to complete",
+                link,
+                stall.as_millis()
+            );
+            bail!("{}: tx stalled for {} ms", link, stall.as_millis())
+        }
+    }
+}
+
 async fn tx_task(
     mut pipeline: TransmissionPipelineConsumer,
     link: LinkUnicast,
     keep_alive: Duration,
+    stall_timeout: Duration,
+    clock: Arc<dyn Clock>,
+    rtt: Option<Arc<RttEstimator>>,
+    share: Option<Arc<TxShare>>,
     #[cfg(feature = "stats")] stats: Arc<TransportStats>,
     #[cfg(all(feature = "unstable", feature = "transport_compression"))] is_compressed: bool,
 ) -> ZResult<()> {
@@ -212,9 +323,20 @@ async fn tx_task(
     let mut compression_aux_buff: Box<[u8]> =
         vec![0; lz4_flex::block::get_maximum_output_size(MAX_BATCH_SIZE)].into_boxed_slice();
 
+    let _turn = share.as_deref().map(TxTurnGuard);
     loop {
-        match pipeline.pull().timeout(keep_alive).await {
-            Ok(res) => match res {
+        let pulled = match pipeline.try_pull() {
+            Some(batch) => Some(Some(batch)),
+            None => {
+                // Give the turn to the other transports while there is nothing to write
+                if let Some(share) = share.as_ref() {
+                    share.release();
+                }
+                timeout(&*clock, keep_alive, pipeline.pull()).await
+            }
+        };
+        match pulled {
+            Some(res) => match res {
                 Some((batch, priority)) => {
                     // Send the buffer on the link
                     #[allow(unused_mut)]
@@ -231,7 +353,24 @@ async fn tx_task(
                         bytes = &compression_aux_buff[..batch_size];
                     }
 
-                    link.write_all(bytes).await?;
+                    // Wait for the turn of the transport on the tx threads
+                    if let Some(share) = share.as_ref() {
+                        #[allow(unused_variables)] // Used when stats feature is enabled
+                        let wait = share.schedule(bytes.len(), &*clock).await;
+                        #[cfg(feature = "stats")]
+                        {
+                            stats.inc_tx_sched_bytes(bytes.len());
+                            stats.inc_tx_sched_wait_us(
+                                usize::try_from(wait.as_micros()).unwrap_or(usize::MAX),
+                            );
+                        }
+                    }
+
+                    let write = watch_write(&link, link.write_all(bytes), stall_timeout, &*clock);
+                    match share.as_ref() {
+                        Some(share) => share.write(write).await?,
+                        None => write.await?,
+                    }
 
                     #[cfg(feature = "stats")]
                     {
@@ -244,11 +383,18 @@ async fn tx_task(
                 }
                 None => break,
             },
-            Err(_) => {
-                let message: TransportMessage = KeepAlive.into();
+            None => {
+                // Request an echo to estimate the round-trip time, if enabled
+                let message: TransportMessage = KeepAlive {
+                    ext_echo_request: rtt
+                        .as_ref()
+                        .map(|rtt| keepalive::ext::EchoRequest::new(rtt.token())),
+                    ext_echo_reply: None,
+                }
+                .into();
 
                 #[allow(unused_variables)] // Used when stats feature is enabled
-                let n = link.send(&message).await?;
+                let n = watch_write(&link, link.send(&message), stall_timeout, &*clock).await?;
                 #[cfg(feature = "stats")]
                 {
                     stats.inc_tx_t_msgs(1);
@@ -261,10 +407,11 @@ async fn tx_task(
     // Drain the transmission pipeline and write remaining bytes on the wire
     let mut batches = pipeline.drain();
     for (b, _) in batches.drain(..) {
-        link.write_all(b.as_bytes())
-            .timeout(keep_alive)
+        timeout(&*clock, keep_alive, link.write_all(b.as_bytes()))
             .await
-            .map_err(|_| zerror!("{}: flush failed after {} ms", link, keep_alive.as_millis()))??;
+            .ok_or_else(|| {
+                zerror!("{}: flush failed after {} ms", link, keep_alive.as_millis())
+            })??;
 
         #[cfg(feature = "stats")]
         {
@@ -276,6 +423,18 @@ async fn tx_task(
     Ok(())
 }
 
+// The error of a link on which nothing was received for a whole lease
+fn expired(link: &LinkUnicast, lease: Duration) -> TransportCloseReason {
+    TransportCloseReason::local(
+        CloseReason::Expired,
+        Some(format!(
+            "{}: expired after {} milliseconds",
+            link,
+            lease.as_millis()
+        )),
+    )
+}
+
 async fn rx_task_stream(
     link: LinkUnicast,
     transport: TransportUnicastUniversal,
@@ -283,6 +442,7 @@ async fn rx_task_stream(
     signal: Signal,
     rx_batch_size: BatchSize,
     rx_buffer_size: usize,
+    clock: Arc<dyn Clock>,
 ) -> ZResult<()> {
     enum Action {
         Read(usize),
@@ -303,23 +463,28 @@ async fn rx_task_stream(
         Ok(Action::Stop)
     }
 
-    // The pool of buffers
+    // The buffers are recycled by the pool shared by the links of the manager
     let mtu = link.get_mtu().min(rx_batch_size) as usize;
     let mut n = rx_buffer_size / mtu;
     if rx_buffer_size % mtu != 0 {
         n += 1;
     }
-
-    let pool = RecyclingObjectPool::new(n, || vec![0_u8; mtu].into_boxed_slice());
+    let pool = transport.manager.rx_pool.clone();
+    pool.prefill(mtu, n);
+    let mut cooperate = Cooperate::new(RX_COOPERATE_BUDGET);
     while !signal.is_triggered() {
         // Retrieve one buffer
-        let mut buffer = pool.try_take().unwrap_or_else(|| pool.alloc());
+        let mut buffer = pool.take(mtu);
+        #[cfg(feature = "stats")]
+        transport.stats.inc_rx_pool(buffer.recycled());
         // Async read from the underlying link
-        let action = read(&link, &mut buffer)
-            .race(stop(signal.clone()))
-            .timeout(lease)
-            .await
-            .map_err(|_| zerror!("{}: expired after {} milliseconds", link, lease.as_millis()))??;
+        let action = timeout(
+            &*clock,
+            lease,
+            read(&link, &mut buffer).race(stop(signal.clone())),
+        )
+        .await
+        .ok_or_else(|| expired(&link, lease))??;
         match action {
             Action::Read(n) => {
                 #[cfg(feature = "stats")]
@@ -339,7 +504,8 @@ async fn rx_task_stream(
                 // Deserialize all the messages from the current ZBuf
                 let zslice = ZSlice::make(Arc::new(buffer), start_pos, end_pos)
                     .map_err(|_| zerror!("Read {} bytes but buffer is {} bytes", n, mtu))?;
-                transport.read_messages(zslice, &link)?;
+                transport.read_messages(zslice, &link).await?;
+                cooperate.step().await;
             }
             Action::Stop => break,
         }
@@ -354,6 +520,7 @@ async fn rx_task_dgram(
     signal: Signal,
     rx_batch_size: BatchSize,
     rx_buffer_size: usize,
+    clock: Arc<dyn Clock>,
 ) -> ZResult<()> {
     enum Action {
         Read(usize),
@@ -370,23 +537,29 @@ async fn rx_task_dgram(
         Ok(Action::Stop)
     }
 
-    // The pool of buffers
+    // The buffers are recycled by the pool shared by the links of the manager
     let mtu = link.get_mtu().min(rx_batch_size) as usize;
     let mut n = rx_buffer_size / mtu;
     if rx_buffer_size % mtu != 0 {
         n += 1;
     }
-    let pool = RecyclingObjectPool::new(n, || vec![0_u8; mtu].into_boxed_slice());
+    let pool = transport.manager.rx_pool.clone();
+    pool.prefill(mtu, n);
+    let mut cooperate = Cooperate::new(RX_COOPERATE_BUDGET);
 
     while !signal.is_triggered() {
         // Retrieve one buffer
-        let mut buffer = pool.try_take().unwrap_or_else(|| pool.alloc());
+        let mut buffer = pool.take(mtu);
+        #[cfg(feature = "stats")]
+        transport.stats.inc_rx_pool(buffer.recycled());
         // Async read from the underlying link
-        let action = read(&link, &mut buffer)
-            .race(stop(signal.clone()))
-            .timeout(lease)
-            .await
-            .map_err(|_| zerror!("{}: expired after {} milliseconds", link, lease.as_millis()))??;
+        let action = timeout(
+            &*clock,
+            lease,
+            read(&link, &mut buffer).race(stop(signal.clone())),
+        )
+        .await
+        .ok_or_else(|| expired(&link, lease))??;
         match action {
             Action::Read(n) => {
                 if n == 0 {
@@ -411,7 +584,8 @@ async fn rx_task_dgram(
                 // Deserialize all the messages from the current ZBuf
                 let zslice = ZSlice::make(Arc::new(buffer), start_pos, end_pos)
                     .map_err(|_| zerror!("Read {} bytes but buffer is {} bytes", n, mtu))?;
-                transport.read_messages(zslice, &link)?;
+                transport.read_messages(zslice, &link).await?;
+                cooperate.step().await;
             }
             Action::Stop => break,
         }
@@ -426,6 +600,7 @@ async fn rx_task(
     signal: Signal,
     rx_batch_size: u16,
     rx_buffer_size: usize,
+    clock: Arc<dyn Clock>,
 ) -> ZResult<()> {
     if link.is_streamed() {
         rx_task_stream(
@@ -435,6 +610,7 @@ async fn rx_task(
             signal,
             rx_batch_size,
             rx_buffer_size,
+            clock,
         )
         .await
     } else {
@@ -445,6 +621,7 @@ async fn rx_task(
             signal,
             rx_batch_size,
             rx_buffer_size,
+            clock,
         )
         .await
     }
@@ -453,15 +630,15 @@ async fn rx_task(
 #[cfg(all(feature = "unstable", feature = "transport_compression"))]
 /// Decompresses the received contents contained in the buffer.
 fn rx_decompress(
-    buffer: &mut zenoh_sync::RecyclingObject<Box<[u8]>>,
-    pool: &RecyclingObjectPool<Box<[u8]>, impl Fn() -> Box<[u8]>>,
+    buffer: &mut PooledBuffer,
+    pool: &BufferPool,
     read_bytes: usize,
     start_pos: &mut usize,
     end_pos: &mut usize,
 ) -> ZResult<()> {
     let is_compressed: bool = buffer[COMPRESSION_BYTE_INDEX] == COMPRESSION_ENABLED;
     if is_compressed {
-        let mut aux_buff = pool.try_take().unwrap_or_else(|| pool.alloc());
+        let mut aux_buff = pool.take(buffer.len());
         *end_pos = lz4_flex::block::decompress_into(
             &buffer[BATCH_PAYLOAD_START_INDEX..read_bytes],
             &mut aux_buff,
@@ -659,8 +836,8 @@ fn tx_compression_test() {
 #[cfg(all(feature = "transport_compression", feature = "unstable"))]
 #[test]
 fn rx_compression_test() {
-    let pool = RecyclingObjectPool::new(2, || vec![0_u8; MAX_BATCH_SIZE].into_boxed_slice());
-    let mut buffer = pool.try_take().unwrap_or_else(|| pool.alloc());
+    let pool = BufferPool::new(&[MAX_BATCH_SIZE], 2 * MAX_BATCH_SIZE);
+    let mut buffer = pool.take(MAX_BATCH_SIZE);
 
     // Compressed batch
     let payload: [u8; 8] = [1, 2, 3, 4, 5, 6, 7, 8];
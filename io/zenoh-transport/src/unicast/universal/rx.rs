@@ -14,27 +14,51 @@ use crate::transport_unicast_inner::TransportUnicastTrait;
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 use super::transport::TransportUnicastUniversal;
+use crate::common::defragmentation::DefragPush;
 use crate::common::priority::TransportChannelRx;
+use crate::{TransportCloseReason, TransportManager, TransportPeerEventHandler};
 use async_std::task;
-use std::sync::MutexGuard;
+use std::sync::{atomic::Ordering, Arc, MutexGuard, RwLock};
 use zenoh_buffers::{
     reader::{HasReader, Reader},
     ZSlice,
 };
 use zenoh_codec::{RCodec, Zenoh080};
-use zenoh_core::{zlock, zread};
+use zenoh_core::{zlock, zread, zwrite};
 use zenoh_link::LinkUnicast;
 use zenoh_protocol::{
-    core::{Priority, Reliability},
-    network::NetworkMessage,
-    transport::{Close, Fragment, Frame, KeepAlive, TransportBody, TransportMessage, TransportSn},
+    common::ZExtBody,
+    core::{Priority, Reliability, ZenohId},
+    network::{NetworkMessage, UnknownKind},
+    transport::{
+        close, keepalive, oam, Close, Fragment, Frame, KeepAlive, Oam, TransportBody,
+        TransportMessage, TransportSn,
+    },
 };
 use zenoh_result::{bail, zerror, ZResult};
+use zenoh_util::clock::timeout;
+use zenoh_util::cooperate::Cooperate;
+
+// The number of messages handled in a row before yielding, when the rx queue stays full
+const RX_COOPERATE_BUDGET: usize = 256;
 
 /*************************************/
-/*            TRANSPORT RX           */
+/*            MESSAGES RX            */
 /*************************************/
-impl TransportUnicastUniversal {
+// The messages decoded by the rx tasks of the links are handed off to a task of the transport
+// handling them, so that a slow callback doesn't delay the reading of the links.
+pub(super) struct TransportRxHandler {
+    pub(super) zid: ZenohId,
+    pub(super) callback: Arc<RwLock<Option<Arc<dyn TransportPeerEventHandler>>>>,
+    #[cfg(feature = "shared-memory")]
+    pub(super) is_shm: bool,
+    #[cfg(feature = "shared-memory")]
+    pub(super) is_shm_aligned: bool,
+    #[cfg_attr(not(feature = "shared-memory"), allow(dead_code))]
+    pub(super) manager: TransportManager,
+}
+
+impl TransportRxHandler {
     fn trigger_callback(
         &self,
         #[allow(unused_mut)] // shared-memory feature requires mut
@@ -44,10 +68,11 @@ impl TransportUnicastUniversal {
         if let Some(callback) = callback.as_ref() {
             #[cfg(feature = "shared-memory")]
             {
-                if self.config.is_shm {
+                if self.is_shm {
                     crate::shm::map_zmsg_to_shmbuf(
                         &mut msg,
                         &self.manager.state.unicast.shm.reader,
+                        self.is_shm_aligned,
                     )?;
                 }
             }
@@ -55,14 +80,130 @@ impl TransportUnicastUniversal {
         } else {
             log::debug!(
                 "Transport: {}. No callback available, dropping message: {}",
-                self.config.zid,
+                self.zid,
                 msg
             );
             Ok(())
         }
     }
 
-    fn handle_close(&self, link: &LinkUnicast, _reason: u8, session: bool) -> ZResult<()> {
+    /// Handles the messages until the transport is dropped.
+    pub(super) async fn run(self, receiver: flume::Receiver<NetworkMessage>) {
+        // Draining a full queue never suspends the task, which yields once out of budget
+        let mut cooperate = Cooperate::new(RX_COOPERATE_BUDGET);
+        loop {
+            let msg = match receiver.try_recv() {
+                Ok(msg) => {
+                    cooperate.step().await;
+                    msg
+                }
+                Err(flume::TryRecvError::Empty) => {
+                    cooperate.reset();
+                    match receiver.recv_async().await {
+                        Ok(msg) => msg,
+                        Err(_) => break,
+                    }
+                }
+                Err(flume::TryRecvError::Disconnected) => break,
+            };
+            if let Err(e) = self.trigger_callback(msg) {
+                log::error!("Transport: {}. Error handling a message: {}", self.zid, e);
+            }
+        }
+    }
+}
+
+/*************************************/
+/*            TRANSPORT RX           */
+/*************************************/
+impl TransportUnicastUniversal {
+    async fn handoff(&self, msg: NetworkMessage) -> ZResult<()> {
+        #[cfg(feature = "stats")]
+        self.stats.inc_rx_n_msgs(1);
+        if msg.is_droppable() {
+            match self.handoff.try_send(msg) {
+                Ok(()) => {}
+                Err(flume::TrySendError::Full(msg)) => {
+                    log::trace!(
+                        "Transport: {}. Rx queue full, dropping message: {}",
+                        self.config.zid,
+                        msg
+                    );
+                    #[cfg(feature = "stats")]
+                    self.stats.inc_rx_n_dropped(1);
+                }
+                Err(flume::TrySendError::Disconnected(_)) => {
+                    bail!("Transport: {}. Rx queue closed.", self.config.zid)
+                }
+            }
+        } else {
+            // The link is not read while waiting for some room, including the keep-alives of the
+            // peer: give up on the link once the queue stayed full for a whole lease
+            let lease = self.manager.config.unicast.lease;
+            match timeout(
+                &*self.manager.config.clock,
+                lease,
+                self.handoff.send_async(msg),
+            )
+            .await
+            {
+                Some(res) => {
+                    res.map_err(|_| zerror!("Transport: {}. Rx queue closed.", self.config.zid))?
+                }
+                None => bail!(
+                    "Transport: {}. Rx queue full for {} ms.",
+                    self.config.zid,
+                    lease.as_millis()
+                ),
+            }
+        }
+        Ok(())
+    }
+
+    fn handle_close(&self, link: &LinkUnicast, close: Close) -> ZResult<()> {
+        // The local close() waits for the echo of its Close, then tears the link down. A Close
+        // that isn't the echo was sent by the peer closing concurrently: its reason prevails.
+        let sent = zread!(self.links)
+            .iter()
+            .find(|tl| &tl.link == link)
+            .and_then(|tl| tl.closing_transport().map(|(_, reason)| *reason));
+        if let Some(sent) = sent {
+            if close.session || close.reason != sent {
+                self.close_reason
+                    .replace(TransportCloseReason::remote(&close));
+            }
+            self.ack_close(link);
+            return Ok(());
+        }
+
+        // The peer closes a link being closed by the local close_link()
+        if self.ack_close(link) {
+            return Ok(());
+        }
+
+        let remote = TransportCloseReason::remote(&close);
+        let session = close.session;
+        if session {
+            self.set_close_reason(remote);
+        } else {
+            self.set_link_close_reason(link, remote);
+        }
+
+        // Echo the Close to acknowledge it, the link flushes it when closing
+        if let Some(p) = zread!(self.links)
+            .iter()
+            .find(|tl| &tl.link == link)
+            .and_then(|tl| tl.pipeline.as_ref())
+        {
+            let msg: TransportMessage = Close {
+                reason: close.reason,
+                session,
+                ext_detail: None,
+            }
+            .into();
+            p.push_transport_message(msg, Priority::Background);
+        }
+
         // Stop now rx and tx tasks before doing the proper cleanup
         let _ = self.stop_rx(link);
         let _ = self.stop_tx(link);
@@ -83,12 +224,156 @@ impl TransportUnicastUniversal {
         Ok(())
     }
 
-    fn handle_frame(&self, frame: Frame) -> ZResult<()> {
+    fn handle_keep_alive(&self, link: &LinkUnicast, keep_alive: KeepAlive) {
+        // The peer sends keep-alives while idle: the acknowledgments still pending are lost
+        self.expire_acks();
+
+        let KeepAlive {
+            ext_echo_request,
+            ext_echo_reply,
+        } = keep_alive;
+        if ext_echo_request.is_none() && ext_echo_reply.is_none() {
+            return;
+        }
+
+        let guard = zread!(self.links);
+        let tl = match guard.iter().find(|tl| &tl.link == link) {
+            Some(tl) => tl,
+            None => return,
+        };
+        if let Some(reply) = ext_echo_reply {
+            tl.rtt.echoed(reply.value);
+        }
+        // Echo the token on the same link, ahead of the data
+        if let (Some(request), Some(pipeline)) = (ext_echo_request, tl.pipeline.as_ref()) {
+            let msg: TransportMessage = KeepAlive {
+                ext_echo_request: None,
+                ext_echo_reply: Some(keepalive::ext::EchoReply::new(request.value)),
+            }
+            .into();
+            pipeline.push_transport_message(msg, Priority::Control);
+        }
+    }
+
+    fn handle_oam(&self, link: &LinkUnicast, oam: Oam) -> ZResult<()> {
+        match oam.id {
+            oam::id::OAM_CLOSE_LINK => {
+                let reason = match oam.body {
+                    ZExtBody::Z64(reason) => u8::try_from(reason).unwrap_or(close::reason::GENERIC),
+                    _ => close::reason::GENERIC,
+                };
+                self.handle_close_link(link, reason)
+            }
+            oam::id::OAM_CLOSE_LINK_ACK => {
+                // Wake up the close_link() waiting for the acknowledgment
+                if let Some(closing) = zread!(self.links)
+                    .iter()
+                    .find(|tl| &tl.link == link)
+                    .and_then(|tl| tl.closing())
+                {
+                    closing.trigger();
+                }
+                Ok(())
+            }
+            oam::id::OAM_ACK_REQUEST => {
+                // Acknowledge the messages received before the request, on the same link
+                if let ZExtBody::Z64(id) = oam.body {
+                    let pipeline = zread!(self.links)
+                        .iter()
+                        .find(|tl| &tl.link == link)
+                        .and_then(|tl| tl.pipeline.clone());
+                    if let Some(p) = pipeline {
+                        let msg: TransportMessage = Oam {
+                            id: oam::id::OAM_ACK,
+                            body: ZExtBody::Z64(id),
+                            ext_qos: oam::ext::QoSType::new(Priority::Control),
+                        }
+                        .into();
+                        p.push_transport_message(msg, Priority::Control);
+                    }
+                }
+                Ok(())
+            }
+            oam::id::OAM_ACK => {
+                if let ZExtBody::Z64(id) = oam.body {
+                    self.ack(id);
+                }
+                Ok(())
+            }
+            id => {
+                log::debug!(
+                    "Transport: {}. Unknown OAM message: {}",
+                    self.config.zid,
+                    id
+                );
+                Ok(())
+            }
+        }
+    }
+
+    fn handle_close_link(&self, link: &LinkUnicast, reason: u8) -> ZResult<()> {
+        // Stop scheduling on the link, unless it is the last one
+        let closing = {
+            let mut guard = zwrite!(self.links);
+            let is_last = guard.iter().all(|tl| &tl.link == link || tl.is_closing());
+            match guard.iter_mut().find(|tl| &tl.link == link) {
+                Some(tl) if !is_last => {
+                    let is_closing = tl.is_closing();
+                    Some((tl.start_closing(), is_closing, tl.pipeline.clone()))
+                }
+                Some(_) => None,
+                None => bail!("Can not close Link {} with peer: {}", link, self.config.zid),
+            }
+        };
+
+        let (signal, is_closing, pipeline) = match closing {
+            Some(closing) => closing,
+            // The last link can not be migrated: close the whole transport
+            None => {
+                let close = Close {
+                    reason,
+                    session: true,
+                    ext_detail: None,
+                };
+                return self.handle_close(link, close);
+            }
+        };
+
+        // Acknowledge the close once the messages already scheduled on the link are sent
+        if let Some(p) = pipeline {
+            let msg: TransportMessage = Oam {
+                id: oam::id::OAM_CLOSE_LINK_ACK,
+                body: ZExtBody::Unit,
+                ext_qos: oam::ext::QoSType::default(),
+            }
+            .into();
+            p.seal(msg, Priority::Background);
+        }
+
+        if is_closing {
+            // The link is concurrently closed on both sides: the local close_link() removes it
+            signal.trigger();
+        } else {
+            // Removing the link flushes the acknowledgment before closing it.
+            // Spawn a task to avoid a deadlock waiting for this same task
+            // to finish in the link close() joining the rx handle
+            let c_transport = self.clone();
+            let c_link = link.clone();
+            task::spawn(async move {
+                let _ = c_transport.del_link(&c_link).await;
+            });
+        }
+
+        Ok(())
+    }
+
+    async fn handle_frame(&self, frame: Frame) -> ZResult<()> {
         let Frame {
             reliability,
             sn,
             ext_qos,
             mut payload,
+            skipped,
         } = frame;
 
         let priority = ext_qos.priority();
@@ -104,20 +389,25 @@ impl TransportUnicastUniversal {
             );
         };
 
-        let mut guard = match reliability {
-            Reliability::Reliable => zlock!(c.reliable),
-            Reliability::BestEffort => zlock!(c.best_effort),
-        };
+        {
+            let mut guard = match reliability {
+                Reliability::Reliable => zlock!(c.reliable),
+                Reliability::BestEffort => zlock!(c.best_effort),
+            };
 
-        self.verify_sn(sn, &mut guard)?;
+            self.verify_sn(sn, &mut guard)?;
+        }
 
+        for kind in skipped {
+            self.skip_unknown(kind);
+        }
         for msg in payload.drain(..) {
-            self.trigger_callback(msg)?;
+            self.handoff(msg).await?;
         }
         Ok(())
     }
 
-    fn handle_fragment(&self, fragment: Fragment) -> ZResult<()> {
+    async fn handle_fragment(&self, fragment: Fragment) -> ZResult<()> {
         let Fragment {
             reliability,
             more,
@@ -138,27 +428,64 @@ impl TransportUnicastUniversal {
             );
         };
 
-        let mut guard = match reliability {
-            Reliability::Reliable => zlock!(c.reliable),
-            Reliability::BestEffort => zlock!(c.best_effort),
-        };
+        let msg = {
+            let mut guard = match reliability {
+                Reliability::Reliable => zlock!(c.reliable),
+                Reliability::BestEffort => zlock!(c.best_effort),
+            };
 
-        self.verify_sn(sn, &mut guard)?;
+            self.verify_sn(sn, &mut guard)?;
 
-        if guard.defrag.is_empty() {
-            let _ = guard.defrag.sync(sn);
-        }
-        guard.defrag.push(sn, payload)?;
-        if !more {
-            // When shared-memory feature is disabled, msg does not need to be mutable
-            let msg = guard
+            if guard.defrag.is_empty() {
+                let _ = guard.defrag.sync(sn);
+            }
+            let pushed = guard.defrag.push(sn, payload)?;
+            if pushed == DefragPush::Oversized {
+                self.drop_oversized();
+            }
+            if more {
+                return Ok(());
+            }
+            if pushed != DefragPush::Added {
+                // The last fragment of an oversized message, the next ones are processed
+                guard.defrag.clear();
+                return Ok(());
+            }
+            guard
                 .defrag
                 .defragment()
-                .ok_or_else(|| zerror!("Transport: {}. Defragmentation error.", self.config.zid))?;
-            return self.trigger_callback(msg);
+                .ok_or_else(|| zerror!("Transport: {}. Defragmentation error.", self.config.zid))?
+        };
+
+        match msg {
+            Ok(msg) => self.handoff(msg).await,
+            Err(kind) => {
+                self.skip_unknown(kind);
+                Ok(())
+            }
         }
+    }
 
-        Ok(())
+    fn drop_oversized(&self) {
+        #[cfg(feature = "stats")]
+        self.stats.inc_rx_n_oversized(1);
+        if !self.oversized_logged.swap(true, Ordering::Relaxed) {
+            log::warn!(
+                "Transport: {}. Dropping a fragmented message exceeding the defragmentation buffer size of {} bytes. \
+                The next ones from this peer are dropped silently.",
+                self.config.zid,
+                self.manager.config.defrag_buff_size
+            );
+        }
+    }
+
+    fn skip_unknown(&self, kind: UnknownKind) {
+        #[cfg(feature = "stats")]
+        match kind {
+            UnknownKind::Message(_) => self.stats.inc_rx_n_unknown_msgs(1),
+            UnknownKind::Declaration(_) => self.stats.inc_rx_n_unknown_decls(1),
+        }
+        self.unknown.skipped(&self.config.zid, kind);
     }
 
     fn verify_sn(
@@ -189,7 +516,11 @@ impl TransportUnicastUniversal {
         Ok(())
     }
 
-    pub(super) fn read_messages(&self, mut zslice: ZSlice, link: &LinkUnicast) -> ZResult<()> {
+    pub(super) async fn read_messages(
+        &self,
+        mut zslice: ZSlice,
+        link: &LinkUnicast,
+    ) -> ZResult<()> {
         let codec = Zenoh080::new();
         let mut reader = zslice.reader();
         while reader.can_read() {
@@ -205,12 +536,11 @@ impl TransportUnicastUniversal {
             }
 
             match msg.body {
-                TransportBody::Frame(msg) => self.handle_frame(msg)?,
-                TransportBody::Fragment(fragment) => self.handle_fragment(fragment)?,
-                TransportBody::Close(Close { reason, session }) => {
-                    self.handle_close(link, reason, session)?
-                }
-                TransportBody::KeepAlive(KeepAlive { .. }) => {}
+                TransportBody::Frame(msg) => self.handle_frame(msg).await?,
+                TransportBody::Fragment(fragment) => self.handle_fragment(fragment).await?,
+                TransportBody::Close(close) => self.handle_close(link, close)?,
+                TransportBody::KeepAlive(keep_alive) => self.handle_keep_alive(link, keep_alive),
+                TransportBody::OAM(oam) => self.handle_oam(link, oam)?,
                 _ => {
                     log::debug!(
                         "Transport: {}. Message handling not implemented: {:?}",
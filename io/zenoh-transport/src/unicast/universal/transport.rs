@@ -11,26 +11,36 @@
 // Contributors:
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
+use crate::common::close::CloseReasonCell;
 use crate::common::priority::{TransportPriorityRx, TransportPriorityTx};
+use crate::common::scheduler::TxShare;
+use crate::common::unknown::UnknownKinds;
 #[cfg(feature = "stats")]
 use crate::stats::TransportStats;
 use crate::transport_unicast_inner::TransportUnicastTrait;
 use crate::unicast::universal::link::TransportLinkUnicast;
+use crate::unicast::universal::rx::TransportRxHandler;
 use crate::TransportConfigUnicast;
-use crate::{TransportExecutor, TransportManager, TransportPeerEventHandler};
+use crate::{
+    LinkRtt, TransportCloseReason, TransportExecutor, TransportManager, TransportPeerEventHandler,
+};
 use async_std::sync::{Mutex as AsyncMutex, MutexGuard as AsyncMutexGuard};
+use async_std::task;
 use async_trait::async_trait;
+use std::collections::HashMap;
 use std::fmt::DebugStruct;
-use std::sync::{Arc, RwLock};
-use std::time::Duration;
-use zenoh_core::{zasynclock, zcondfeat, zread, zwrite};
+use std::sync::{atomic::AtomicBool, Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+use zenoh_core::{zasynclock, zcondfeat, zlock, zread, zwrite};
 use zenoh_link::{Link, LinkUnicast, LinkUnicastDirection};
-use zenoh_protocol::network::NetworkMessage;
+use zenoh_protocol::network::{NetworkMessage, UnknownKind};
 use zenoh_protocol::{
+    common::ZExtBody,
     core::{Priority, WhatAmI, ZenohId},
-    transport::{Close, PrioritySn, TransportMessage, TransportSn},
+    transport::{close::CloseReason, oam, Close, Oam, PrioritySn, TransportMessage, TransportSn},
 };
 use zenoh_result::{bail, zerror, ZResult};
+use zenoh_util::clock::timeout;
 
 macro_rules! zlinkget {
     ($guard:expr, $link:expr) => {
@@ -50,6 +60,13 @@ macro_rules! zlinkindex {
     };
 }
 
+// The acknowledgments requested from the peer, see schedule_with_ack()
+#[derive(Default)]
+pub(super) struct PendingAcks {
+    next_id: u64,
+    acks: HashMap<u64, (Instant, flume::Sender<()>)>,
+}
+
 /*************************************/
 /*        UNIVERSAL TRANSPORT        */
 /*************************************/
@@ -69,6 +86,18 @@ pub(crate) struct TransportUnicastUniversal {
     pub(super) callback: Arc<RwLock<Option<Arc<dyn TransportPeerEventHandler>>>>,
     // Mutex for notification
     pub(super) alive: Arc<AsyncMutex<bool>>,
+    // The reason of the close, notified to the callback once closed
+    pub(super) close_reason: CloseReasonCell,
+    // The queue of the received messages waiting to be handled
+    pub(super) handoff: flume::Sender<NetworkMessage>,
+    // The share of the tx threads of the links, if the transports write in turn
+    pub(super) tx_share: Option<Arc<TxShare>>,
+    // The counts of the received messages of unknown kinds
+    pub(super) unknown: Arc<UnknownKinds>,
+    // Whether a fragmented message exceeding the defragmentation buffer was logged
+    pub(super) oversized_logged: Arc<AtomicBool>,
+    // The acknowledgments requested from the peer and not received yet
+    pub(super) pending_acks: Arc<Mutex<PendingAcks>>,
     // Transport statistics
     #[cfg(feature = "stats")]
     pub(super) stats: Arc<TransportStats>,
@@ -103,7 +132,32 @@ impl TransportUnicastUniversal {
         }
 
         #[cfg(feature = "stats")]
-        let stats = Arc::new(TransportStats::new(Some(manager.get_stats().clone())));
+        let stats = Arc::new(TransportStats::new(Some(manager.get_stats_unicast())));
+
+        // The handler stops once the transport and thus the sender are dropped
+        let (handoff, receiver) = flume::bounded(manager.config.link_rx_queue_size);
+        let callback = Arc::new(RwLock::new(None));
+        let handler = TransportRxHandler {
+            zid: config.zid,
+            callback: callback.clone(),
+            #[cfg(feature = "shared-memory")]
+            is_shm: config.is_shm,
+            #[cfg(feature = "shared-memory")]
+            is_shm_aligned: config.is_shm_aligned,
+            manager: manager.clone(),
+        };
+        task::spawn(handler.run(receiver));
+
+        let tx_share = manager.config.tx_quantum.map(|quantum| {
+            let weight = manager.config.tx_weights.get(&config.zid).copied();
+            Arc::new(TxShare::new(
+                manager.tx_scheduler.clone(),
+                quantum,
+                weight.unwrap_or(1),
+            ))
+        });
+
+        let unknown = Arc::new(UnknownKinds::new(manager.config.clock.clone()));
 
         let t = TransportUnicastUniversal {
             manager,
@@ -111,8 +165,14 @@ impl TransportUnicastUniversal {
             priority_tx: priority_tx.into_boxed_slice().into(),
             priority_rx: priority_rx.into_boxed_slice().into(),
             links: Arc::new(RwLock::new(vec![].into_boxed_slice())),
-            callback: Arc::new(RwLock::new(None)),
+            callback,
             alive: Arc::new(AsyncMutex::new(false)),
+            close_reason: CloseReasonCell::default(),
+            handoff,
+            tx_share,
+            unknown,
+            oversized_logged: Arc::new(AtomicBool::new(false)),
+            pending_acks: Arc::new(Mutex::new(PendingAcks::default())),
             #[cfg(feature = "stats")]
             stats,
         };
@@ -154,30 +214,70 @@ impl TransportUnicastUniversal {
             let _ = l.close().await;
         }
 
+        // The acknowledgments not received yet are disconnected
+        zlock!(self.pending_acks).acks.clear();
+
         // Notify the callback that we have closed the transport
+        let reason = self.close_reason.take();
         if let Some(cb) = callback.as_ref() {
+            cb.close_reason(&reason);
             cb.closed();
         }
 
         Ok(())
     }
 
+    /// Signals the acknowledgment `id` received from the peer.
+    pub(super) fn ack(&self, id: u64) {
+        if let Some((_, ack)) = zlock!(self.pending_acks).acks.remove(&id) {
+            let _ = ack.try_send(());
+        }
+    }
+
+    /// Disconnects the acknowledgments the peer didn't send within the lease, e.g. because the
+    /// link carrying the request was closed or the peer doesn't support them.
+    pub(super) fn expire_acks(&self) {
+        let now = self.manager.config.clock.now();
+        let lease = self.manager.config.unicast.lease;
+        zlock!(self.pending_acks)
+            .acks
+            .retain(|_, (since, _)| now.saturating_duration_since(*since) < lease);
+    }
+
+    /// Records the reason of the close of the transport.
+    pub(super) fn set_close_reason(&self, reason: TransportCloseReason) {
+        self.close_reason.set(reason);
+    }
+
+    /// Records the reason of the close of `link`, closing the transport if it is its last one.
+    pub(super) fn set_link_close_reason(&self, link: &LinkUnicast, reason: TransportCloseReason) {
+        let guard = zread!(self.links);
+        if guard.len() == 1 && zlinkget!(guard, link).is_some() {
+            self.close_reason.set(reason);
+        }
+    }
+
     pub(crate) async fn del_link(&self, link: &LinkUnicast) -> ZResult<()> {
         enum Target {
-            Transport,
+            Transport(Box<TransportLinkUnicast>),
             Link(Box<TransportLinkUnicast>),
         }
 
-        // Try to remove the link
+        // Try to remove the link, under the lock of the transports of the manager for a link
+        // being added to the transport at the same time not to be closed along with it
         let target = {
+            let _transports = zasynclock!(self.manager.state.unicast.transports);
             let mut guard = zwrite!(self.links);
 
             if let Some(index) = zlinkindex!(guard, link) {
                 let is_last = guard.len() == 1;
                 if is_last {
-                    // Close the whole transport
+                    // Close the whole transport, left without links for the new ones to wait
+                    // for it to be deleted
+                    let stl = guard.to_vec().remove(index);
+                    *guard = vec![].into_boxed_slice();
                     drop(guard);
-                    Target::Transport
+                    Target::Transport(stl.into())
                 } else {
                     // Remove the link
                     let mut links = guard.to_vec();
@@ -201,11 +301,34 @@ impl TransportUnicastUniversal {
         }
 
         match target {
-            Target::Transport => self.delete().await,
+            Target::Transport(stl) => {
+                let res = self.delete().await;
+                let _ = stl.close().await;
+                res
+            }
             Target::Link(stl) => stl.close().await,
         }
     }
 
+    /// Acknowledges the close of `link` if it is closing, returning whether it is.
+    pub(super) fn ack_close(&self, link: &LinkUnicast) -> bool {
+        let guard = zread!(self.links);
+        let tl = match guard.iter().find(|tl| &tl.link == link) {
+            Some(tl) => tl,
+            None => return false,
+        };
+        let signals = tl
+            .closing()
+            .into_iter()
+            .chain(tl.closing_transport().map(|(signal, _)| signal));
+        let mut acked = false;
+        for signal in signals {
+            signal.trigger();
+            acked = true;
+        }
+        acked
+    }
+
     pub(crate) fn stop_tx(&self, link: &LinkUnicast) -> ZResult<()> {
         let mut guard = zwrite!(self.links);
         match zlinkgetmut!(guard, link) {
@@ -252,26 +375,82 @@ impl TransportUnicastTrait for TransportUnicastUniversal {
 
         // Check if we can add more inbound links
         if let LinkUnicastDirection::Inbound = direction {
-            let count = guard.iter().filter(|l| l.direction == direction).count();
-
-            let limit = zcondfeat!(
+            // The links of a protocol with a limit of its own are only bounded by it
+            let protocol = link.get_dst().protocol();
+            let protocol_limit = zcondfeat!(
                 "transport_multilink",
-                match self.config.multilink {
-                    Some(_) => self.manager.config.unicast.max_links,
-                    None => 1,
-                },
-                1
+                self.config.multilink.as_ref().and_then(|_| {
+                    self.manager
+                        .config
+                        .unicast
+                        .max_links_per_protocol
+                        .get(protocol.as_str())
+                        .copied()
+                }),
+                None
             );
 
-            if count >= limit {
-                let e = zerror!(
-                    "Can not add Link {} with peer {}: max num of links reached {}/{}",
-                    link,
-                    self.config.zid,
-                    count,
-                    limit
-                );
-                return Err(e.into());
+            match protocol_limit {
+                Some(limit) => {
+                    let count = guard
+                        .iter()
+                        .filter(|l| {
+                            l.direction == direction
+                                && l.link.get_dst().protocol().as_str() == protocol.as_str()
+                        })
+                        .count();
+                    if count >= limit {
+                        let e = zerror!(
+                            "Can not add Link {} with peer {}: max num of {} links reached {}/{}",
+                            link,
+                            self.config.zid,
+                            protocol,
+                            count,
+                            limit
+                        );
+                        return Err(e.into());
+                    }
+                }
+                None => {
+                    // The links of the protocols with a limit of their own don't count against
+                    // the global one
+                    let count = guard
+                        .iter()
+                        .filter(|l| {
+                            l.direction == direction
+                                && zcondfeat!(
+                                    "transport_multilink",
+                                    !self
+                                        .manager
+                                        .config
+                                        .unicast
+                                        .max_links_per_protocol
+                                        .contains_key(l.link.get_dst().protocol().as_str()),
+                                    true
+                                )
+                        })
+                        .count();
+
+                    let limit = zcondfeat!(
+                        "transport_multilink",
+                        match self.config.multilink {
+                            Some(_) => self.manager.config.unicast.max_links,
+                            None => 1,
+                        },
+                        1
+                    );
+
+                    if count >= limit {
+                        let e = zerror!(
+                            "Can not add Link {} with peer {}: max num of links reached {}/{}",
+                            link,
+                            self.config.zid,
+                            count,
+                            limit
+                        );
+                        return Err(e.into());
+                    }
+                }
             }
         }
 
@@ -360,19 +539,58 @@ impl TransportUnicastTrait for TransportUnicastUniversal {
     async fn close_link(&self, link: &LinkUnicast, reason: u8) -> ZResult<()> {
         log::trace!("Closing link {} with peer: {}", link, self.config.zid);
 
-        let mut pipeline = zlinkget!(zread!(self.links), link)
-            .map(|l| l.pipeline.clone())
-            .ok_or_else(|| zerror!("Cannot close Link {:?}: not found", link))?;
+        // Stop scheduling on the link, unless it is the last one
+        let closing = {
+            let mut guard = zwrite!(self.links);
+            let is_last = guard.iter().all(|tl| &tl.link == link || tl.is_closing());
+            let tl = zlinkgetmut!(guard, link)
+                .ok_or_else(|| zerror!("Cannot close Link {:?}: not found", link))?;
+            if is_last {
+                None
+            } else {
+                Some((tl.start_closing(), tl.pipeline.clone()))
+            }
+        };
+
+        let (signal, mut pipeline) = match closing {
+            Some(closing) => closing,
+            // The last link can not be migrated: close the whole transport
+            None => return self.close(reason).await,
+        };
 
         if let Some(p) = pipeline.take() {
-            // Close message to be sent on the target link
-            let msg: TransportMessage = Close {
-                reason,
-                session: false,
+            // Ask the peer to close the link once the messages already scheduled on it are sent
+            let msg: TransportMessage = Oam {
+                id: oam::id::OAM_CLOSE_LINK,
+                body: ZExtBody::Z64(reason as u64),
+                ext_qos: oam::ext::QoSType::default(),
             }
             .into();
+            p.seal(msg, Priority::Background);
+
+            // Wait for the peer to acknowledge the close
+            let clock = self.manager.config.clock.clone();
+            let lease = self.manager.config.unicast.lease;
+            if timeout(&*clock, lease, signal.wait()).await.is_none() {
+                log::debug!(
+                    "Close of link {} not acknowledged by peer: {}",
+                    link,
+                    self.config.zid
+                );
+                // Close message to be sent on the target link
+                let msg: TransportMessage = Close {
+                    reason,
+                    session: false,
+                    ext_detail: None,
+                }
+                .into();
+                p.push_transport_message(msg, Priority::Background);
+            }
+        }
 
-            p.push_transport_message(msg, Priority::Background);
+        // The link may have already been removed on the close of the peer
+        if zlinkget!(zread!(self.links), link).is_none() {
+            return Ok(());
         }
 
         // Remove the link from the channel
@@ -381,24 +599,56 @@ impl TransportUnicastTrait for TransportUnicastUniversal {
 
     async fn close(&self, reason: u8) -> ZResult<()> {
         log::trace!("Closing transport with peer: {}", self.config.zid);
-
-        let mut pipelines = zread!(self.links)
-            .iter()
-            .filter_map(|sl| sl.pipeline.clone())
+        self.set_close_reason(TransportCloseReason::local(CloseReason::from(reason), None));
+
+        // Stop scheduling on all the links while waiting for the acknowledgment of the peer
+        let mut closings = zwrite!(self.links)
+            .iter_mut()
+            .filter_map(|sl| {
+                let signal = sl.start_closing_transport(reason);
+                sl.pipeline.clone().map(|p| (sl.link.clone(), p, signal))
+            })
             .collect::<Vec<_>>();
-        for p in pipelines.drain(..) {
-            // Close message to be sent on all the links
-            // session should always be true for user-triggered close. However, in case of
-            // multiple links, it is safer to close all the links first. When no links are left,
-            // the transport is then considered closed.
-            let msg: TransportMessage = Close {
-                reason,
-                session: false,
-            }
-            .into();
+        // Close message to be sent on all the links
+        // session should always be true for user-triggered close. However, in case of
+        // multiple links, it is safer to close all the links first. When no links are left,
+        // the transport is then considered closed.
+        let msg: TransportMessage = Close {
+            reason,
+            session: false,
+            ext_detail: None,
+        }
+        .into();
+        for (_, p, _) in closings.iter() {
+            p.push_transport_message(msg.clone(), Priority::Background);
+        }
 
-            p.push_transport_message(msg, Priority::Background);
+        // Wait for the peer to echo the Close or to close the link before tearing it down,
+        // otherwise a Close lost on the way lets the peer wait for the lease to expire.
+        // Datagrams may be lost: the Close is sent once more on the datagram links not yet
+        // acknowledged halfway through the timeout.
+        let clock = self.manager.config.clock.clone();
+        let close_timeout = self.manager.config.unicast.close_timeout;
+        let start = clock.now();
+        let remaining =
+            |until: Duration| until.saturating_sub(clock.now().saturating_duration_since(start));
+        for (_, _, signal) in closings.iter() {
+            let _ = timeout(&*clock, remaining(close_timeout / 2), signal.wait()).await;
+        }
+        for (link, p, signal) in closings.iter() {
+            if !link.is_streamed() && !signal.is_triggered() {
+                log::debug!(
+                    "Close not acknowledged on link {} by peer: {}. Sending it again",
+                    link,
+                    self.config.zid
+                );
+                p.push_transport_message(msg.clone(), Priority::Background);
+            }
+        }
+        for (_, _, signal) in closings.drain(..) {
+            let _ = timeout(&*clock, remaining(close_timeout), signal.wait()).await;
         }
+
         // Terminate and clean up the transport
         self.delete().await
     }
@@ -407,16 +657,56 @@ impl TransportUnicastTrait for TransportUnicastUniversal {
         zread!(self.links).iter().map(|l| l.link.clone()).collect()
     }
 
+    fn get_links_with_direction(&self, direction: &LinkUnicastDirection) -> Vec<LinkUnicast> {
+        zread!(self.links)
+            .iter()
+            .filter(|l| &l.direction == direction)
+            .map(|l| l.link.clone())
+            .collect()
+    }
+
+    fn get_links_rtt(&self) -> Vec<(LinkUnicast, Option<LinkRtt>)> {
+        zread!(self.links)
+            .iter()
+            .map(|l| (l.link.clone(), l.rtt.get()))
+            .collect()
+    }
+
+    fn get_unknown_kinds(&self) -> Vec<(UnknownKind, usize)> {
+        self.unknown.counts()
+    }
+
     /*************************************/
     /*                TX                 */
     /*************************************/
     fn schedule(&self, msg: NetworkMessage) -> ZResult<()> {
-        match self.internal_schedule(msg) {
+        match self.internal_schedule(msg, None) {
             true => Ok(()),
             false => bail!("error scheduling message!"),
         }
     }
 
+    fn schedule_with_ack(&self, msg: NetworkMessage) -> ZResult<flume::Receiver<()>> {
+        self.expire_acks();
+        let (ack, ack_r) = flume::bounded(1);
+        let id = {
+            let mut guard = zlock!(self.pending_acks);
+            let id = guard.next_id;
+            guard.next_id = guard.next_id.wrapping_add(1);
+            guard
+                .acks
+                .insert(id, (self.manager.config.clock.now(), ack));
+            id
+        };
+        match self.internal_schedule(msg, Some(id)) {
+            true => Ok(ack_r),
+            false => {
+                zlock!(self.pending_acks).acks.remove(&id);
+                bail!("error scheduling message!")
+            }
+        }
+    }
+
     fn start_tx(
         &self,
         link: &LinkUnicast,
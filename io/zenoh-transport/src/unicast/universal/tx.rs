@@ -16,59 +16,71 @@ use zenoh_core::zread;
 use zenoh_protocol::network::NetworkMessage;
 
 impl TransportUnicastUniversal {
-    fn schedule_on_link(&self, msg: NetworkMessage) -> bool {
-        macro_rules! zpush {
-            ($guard:expr, $pipeline:expr, $msg:expr) => {
+    fn schedule_on_link(&self, mut msg: NetworkMessage, ack: Option<u64>) -> bool {
+        loop {
+            let pipeline = {
+                let guard = zread!(self.links);
+                // Skip the links being closed
+                let links = || guard.iter().filter(|tl| !tl.is_closing());
+                // First try to find the best match between msg and link reliability
+                let best = links()
+                    .filter_map(|tl| {
+                        if msg.is_reliable() == tl.link.is_reliable() {
+                            tl.pipeline.as_ref()
+                        } else {
+                            None
+                        }
+                    })
+                    .next();
+                match best {
+                    Some(pl) => Some(pl.clone()),
+                    // No best match found, take the first available link
+                    None => {
+                        let pl = links().filter_map(|tl| tl.pipeline.as_ref()).next();
+                        if pl.is_some() && msg.is_reliable() {
+                            // The message is downgraded to best-effort since no reliable link is available
+                            log::debug!("Reliable message sent on a best-effort link: {}", msg);
+                            #[cfg(feature = "stats")]
+                            self.stats.inc_tx_n_downgraded(1);
+                        }
+                        pl.cloned()
+                    }
+                }
                 // Drop the guard before the push_zenoh_message since
                 // the link could be congested and this operation could
                 // block for fairly long time
-                let pl = $pipeline.clone();
-                drop($guard);
-                log::trace!("Scheduled: {:?}", $msg);
-                return pl.push_network_message($msg);
             };
-        }
 
-        let guard = zread!(self.links);
-        // First try to find the best match between msg and link reliability
-        if let Some(pl) = guard
-            .iter()
-            .filter_map(|tl| {
-                if msg.is_reliable() == tl.link.is_reliable() {
-                    tl.pipeline.as_ref()
-                } else {
-                    None
+            let pl = match pipeline {
+                Some(pl) => pl,
+                None => {
+                    // No Link found
+                    log::trace!(
+                        "Message dropped because the transport has no links: {}",
+                        msg
+                    );
+                    return false;
                 }
-            })
-            .next()
-        {
-            zpush!(guard, pl, msg);
-        }
-
-        // No best match found, take the first available link
-        if let Some(pl) = guard.iter().filter_map(|tl| tl.pipeline.as_ref()).next() {
-            zpush!(guard, pl, msg);
+            };
+            log::trace!("Scheduled: {:?}", msg);
+            match pl.try_push_network_message(msg, ack) {
+                Ok(res) => return res,
+                // The link started closing meanwhile: schedule the message on another link
+                Err(m) => msg = m,
+            }
         }
-
-        // No Link found
-        log::trace!(
-            "Message dropped because the transport has no links: {}",
-            msg
-        );
-
-        false
     }
 
     #[allow(unused_mut)] // When feature "shared-memory" is not enabled
     #[allow(clippy::let_and_return)] // When feature "stats" is not enabled
     #[inline(always)]
-    pub(crate) fn internal_schedule(&self, mut msg: NetworkMessage) -> bool {
+    pub(crate) fn internal_schedule(&self, mut msg: NetworkMessage, ack: Option<u64>) -> bool {
         #[cfg(feature = "shared-memory")]
         {
             let res = if self.config.is_shm {
-                crate::shm::map_zmsg_to_shminfo(&mut msg)
+                crate::shm::map_zmsg_to_shminfo(&mut msg, self.config.is_shm_aligned)
             } else {
-                crate::shm::map_zmsg_to_shmbuf(&mut msg, &self.manager.shm().reader)
+                crate::shm::map_zmsg_to_shmbuf(&mut msg, &self.manager.shm().reader, false)
             };
             if let Err(e) = res {
                 log::trace!("Failed SHM conversion: {}", e);
@@ -76,7 +88,7 @@ impl TransportUnicastUniversal {
             }
         }
 
-        let res = self.schedule_on_link(msg);
+        let res = self.schedule_on_link(msg, ack);
 
         #[cfg(feature = "stats")]
         if res {
@@ -14,15 +14,16 @@
 use async_std::{prelude::FutureExt, task};
 use std::{any::Any, convert::TryFrom, sync::Arc, time::Duration};
 use zenoh_core::zasync_executor_init;
-use zenoh_link::{EndPoint, Link};
+use zenoh_link::{EndPoint, Link, Locator};
 use zenoh_protocol::{
     core::{WhatAmI, ZenohId},
     network::NetworkMessage,
 };
 use zenoh_result::ZResult;
 use zenoh_transport::{
-    TransportEventHandler, TransportManager, TransportMulticast, TransportMulticastEventHandler,
-    TransportPeer, TransportPeerEventHandler, TransportUnicast,
+    AlreadyListening, TransportEventHandler, TransportManager, TransportMulticast,
+    TransportMulticastEventHandler, TransportPeer, TransportPeerEventHandler, TransportUnicast,
+    LISTEN_IFACE,
 };
 
 const TIMEOUT: Duration = Duration::from_secs(60);
@@ -457,3 +458,283 @@ AXVFFIgCSluyrolaD6CWD9MqOex4YOfJR2bNxI7lFvuK4AwjyUJzT1U1HXib17mM
     let endpoints = vec![endpoint];
     task::block_on(run(&endpoints));
 }
+
+#[cfg(feature = "transport_tcp")]
+#[test]
+fn endpoint_validation() {
+    let _ = env_logger::try_init();
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+
+    let build = |listen: &str| {
+        TransportManager::builder()
+            .whatami(WhatAmI::Router)
+            .zid(ZenohId::try_from([1]).unwrap())
+            .listen(vec![listen.parse().unwrap()])
+            .build(Arc::new(SH))
+    };
+
+    // A valid endpoint
+    assert!(build("tcp/127.0.0.1:17447").is_ok());
+
+    // A typo in the protocol
+    let err = build("tpc/127.0.0.1:17447").err().unwrap().to_string();
+    assert!(err.contains("tpc/127.0.0.1:17447"), "{err}");
+
+    // A configuration key not supported by the protocol
+    let err = build("tcp/127.0.0.1:17447#foo=bar")
+        .err()
+        .unwrap()
+        .to_string();
+    assert!(err.contains("foo"), "{err}");
+
+    // A protocol not enabled in the configuration
+    let res = TransportManager::builder()
+        .zid(ZenohId::try_from([1]).unwrap())
+        .protocols(Some(vec!["udp".to_string()]))
+        .connect(vec!["tcp/127.0.0.1:17447".parse().unwrap()])
+        .build(Arc::new(SH));
+    assert!(res.is_err());
+
+    // A configuration key not supported in the default configuration of a protocol
+    let res = TransportManager::builder()
+        .zid(ZenohId::try_from([1]).unwrap())
+        .endpoints([("tcp".to_string(), "foo=bar".to_string())].into())
+        .build(Arc::new(SH));
+    assert!(res.is_err());
+
+    // An all-zeros zid
+    if let Ok(zid) = ZenohId::try_from([0]) {
+        let res = TransportManager::builder().zid(zid).build(Arc::new(SH));
+        assert!(res.is_err());
+    }
+}
+
+#[cfg(all(feature = "transport_tcp", feature = "transport_udp"))]
+#[test]
+fn endpoint_config_keys() {
+    let _ = env_logger::try_init();
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+
+    let build = |listen: &str| {
+        TransportManager::builder()
+            .whatami(WhatAmI::Router)
+            .zid(ZenohId::try_from([1]).unwrap())
+            .listen(vec![listen.parse().unwrap()])
+            .build(Arc::new(SH))
+    };
+
+    // A typo in a configuration key is reported with the closest supported key
+    let err = build("udp/127.0.0.1:17448#ifase=lo")
+        .err()
+        .unwrap()
+        .to_string();
+    assert!(err.contains("ifase"), "{err}");
+    assert!(err.contains("did you mean `iface`"), "{err}");
+
+    // The keys with the pass-through prefix are not checked
+    assert!(build("tcp/127.0.0.1:17447#x-foo=bar").is_ok());
+
+    // The configuration keys are also checked when adding a listener
+    let manager = TransportManager::builder()
+        .whatami(WhatAmI::Router)
+        .zid(ZenohId::try_from([1]).unwrap())
+        .build(Arc::new(SH))
+        .unwrap();
+    task::block_on(async {
+        let endpoint: EndPoint = "tcp/127.0.0.1:17450#foo=bar".parse().unwrap();
+        let res = ztimeout!(manager.add_listener(endpoint));
+        assert!(res.is_err());
+
+        let endpoint: EndPoint = "tcp/127.0.0.1:17450#x-foo=bar".parse().unwrap();
+        let res = ztimeout!(manager.add_listener(endpoint));
+        assert!(res.is_ok(), "{res:?}");
+
+        ztimeout!(manager.close());
+    });
+}
+
+#[cfg(feature = "transport_tcp")]
+#[test]
+fn endpoint_duplicates_tcp() {
+    let _ = env_logger::try_init();
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+
+    let manager = TransportManager::builder()
+        .whatami(WhatAmI::Router)
+        .zid(ZenohId::try_from([1]).unwrap())
+        .build(Arc::new(SH))
+        .unwrap();
+    let is_already_listening = |res: &ZResult<Vec<Locator>>, locator: &Locator| {
+        res.as_ref()
+            .err()
+            .and_then(|e| e.downcast_ref::<AlreadyListening>())
+            .map_or(false, |e| e.0 == *locator)
+    };
+
+    task::block_on(async {
+        // Adding the same endpoint again returns the locator of the listener
+        println!("Endpoint duplicates [1a1]");
+        let endpoint: EndPoint = "tcp/127.0.0.1:14214#x-b=1;x-a=2".parse().unwrap();
+        let locator = ztimeout!(manager.add_listener(endpoint.clone())).unwrap()[0].clone();
+        let same: EndPoint = "tcp/127.0.0.1:14214#x-a=2;x-b=1".parse().unwrap();
+        let res = ztimeout!(manager.add_listener(same));
+        println!("Endpoint duplicates [1a2]: {res:?}");
+        assert_eq!(res.unwrap(), vec![locator.clone()]);
+        let listeners = ztimeout!(manager.get_listeners_unicast());
+        assert_eq!(listeners, vec![endpoint.clone()]);
+        assert_eq!(listeners[0].as_str(), "tcp/127.0.0.1:14214#x-a=2;x-b=1");
+
+        // The endpoints overlapping the listener are refused
+        println!("Endpoint duplicates [1b1]");
+        for overlapping in [
+            "tcp/0.0.0.0:14214",
+            "tcp/[::]:14214",
+            "tcp/127.0.0.1:14214#x-a=3",
+        ] {
+            let res = ztimeout!(manager.add_listener(overlapping.parse().unwrap()));
+            println!("Endpoint duplicates [1b2]: {overlapping}: {res:?}");
+            assert!(is_already_listening(&res, &locator));
+        }
+        assert_eq!(ztimeout!(manager.get_listeners_unicast()).len(), 1);
+
+        // The wildcard listeners cover the specific addresses
+        println!("Endpoint duplicates [1c1]");
+        let wildcard: EndPoint = "tcp/[0:0:0:0:0:0:0:0]:14215".parse().unwrap();
+        match ztimeout!(manager.add_listener(wildcard)) {
+            Ok(locators) => {
+                // The address is normalized
+                let wildcard = &locators[0];
+                assert_eq!(wildcard.address().as_str(), "[::]:14215");
+                let res = ztimeout!(manager.add_listener("tcp/127.0.0.1:14215".parse().unwrap()));
+                assert!(is_already_listening(&res, wildcard));
+                ztimeout!(manager.del_listener(&"tcp/[::]:14215".parse().unwrap())).unwrap();
+            }
+            // No IPv6 on this host
+            Err(e) => println!("Endpoint duplicates [1c2]: {e}"),
+        }
+
+        // The port 0 binds a new port each time
+        println!("Endpoint duplicates [1d1]");
+        let any: EndPoint = "tcp/127.0.0.1:0".parse().unwrap();
+        let first = ztimeout!(manager.add_listener(any.clone())).unwrap()[0].clone();
+        let second = ztimeout!(manager.add_listener(any)).unwrap()[0].clone();
+        println!("Endpoint duplicates [1d2]: {first} {second}");
+        assert_ne!(first, second);
+        assert_ne!(first.address().as_str(), "127.0.0.1:0");
+        let listeners = ztimeout!(manager.get_listeners_unicast());
+        assert_eq!(listeners.len(), 3);
+        for locator in [&first, &second] {
+            assert!(listeners.iter().any(|e| e.to_locator() == *locator));
+            // Adding the bound port again returns the listener
+            let bound = EndPoint::from(locator.clone());
+            assert_eq!(
+                ztimeout!(manager.add_listener(bound)).unwrap(),
+                vec![locator.clone()]
+            );
+        }
+
+        // A deleted listener can be added again, and an overlapping one replace it
+        println!("Endpoint duplicates [1e1]");
+        for _ in 0..RUNS {
+            ztimeout!(manager.del_listener(&endpoint)).unwrap();
+            assert_eq!(ztimeout!(manager.get_listeners_unicast()).len(), 2);
+            assert_eq!(
+                ztimeout!(manager.add_listener(endpoint.clone())).unwrap(),
+                vec![locator.clone()]
+            );
+        }
+        ztimeout!(manager.del_listener(&endpoint)).unwrap();
+        let wildcard: EndPoint = "tcp/0.0.0.0:14214".parse().unwrap();
+        let res = ztimeout!(manager.add_listener(wildcard.clone()));
+        println!("Endpoint duplicates [1e2]: {res:?}");
+        assert!(res.is_ok());
+        ztimeout!(manager.del_listener(&wildcard)).unwrap();
+
+        ztimeout!(manager.close());
+    });
+}
+
+#[cfg(feature = "transport_tcp")]
+#[test]
+fn endpoint_interface_tcp() {
+    let _ = env_logger::try_init();
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+
+    let iface = match ["lo", "lo0"]
+        .into_iter()
+        .find(|name| zenoh_util::net::is_interface(name))
+    {
+        Some(iface) => iface,
+        // No loopback interface with a known name on this host
+        None => return,
+    };
+    let addresses = zenoh_util::net::get_listen_addresses_of_interface(iface).unwrap();
+    let manager = TransportManager::builder()
+        .whatami(WhatAmI::Router)
+        .zid(ZenohId::try_from([1]).unwrap())
+        .build(Arc::new(SH))
+        .unwrap();
+
+    task::block_on(async {
+        // An interface name listens on each of its addresses
+        println!("Endpoint interface [1a1]");
+        let endpoint: EndPoint = format!("tcp/{iface}:14216").parse().unwrap();
+        let locators = ztimeout!(manager.add_listener(endpoint.clone())).unwrap();
+        println!("Endpoint interface [1a2]: {locators:?}");
+        assert_eq!(locators.len(), addresses.len());
+        for addr in addresses.iter() {
+            let address = std::net::SocketAddr::new(*addr, 14216).to_string();
+            assert!(locators.iter().any(|l| l.address().as_str() == address));
+        }
+        assert_eq!(
+            ztimeout!(manager.get_listeners_unicast()).len(),
+            addresses.len()
+        );
+        // Deleting the interface endpoint deletes all its listeners
+        ztimeout!(manager.del_listener(&endpoint)).unwrap();
+        assert!(ztimeout!(manager.get_listeners_unicast()).is_empty());
+
+        // The interface may be given in the metadata of an unspecified address
+        println!("Endpoint interface [1b1]");
+        let endpoint: EndPoint = format!("tcp/0.0.0.0:14217#{LISTEN_IFACE}={iface}")
+            .parse()
+            .unwrap();
+        let locators = ztimeout!(manager.add_listener(endpoint.clone())).unwrap();
+        println!("Endpoint interface [1b2]: {locators:?}");
+        assert!(!locators.is_empty());
+        for locator in locators.iter() {
+            // Only the IPv4 addresses, without the interface in the metadata
+            assert!(locator.address().as_str().ends_with(":14217"));
+            assert!(!locator.address().as_str().starts_with('['));
+            assert!(locator.metadata().get(LISTEN_IFACE).is_none());
+        }
+        ztimeout!(manager.del_listener(&endpoint)).unwrap();
+
+        // An unknown interface and a specific address are refused
+        println!("Endpoint interface [1c1]");
+        let res = ztimeout!(manager.add_listener(
+            format!("tcp/0.0.0.0:14218#{LISTEN_IFACE}=nonexistent0")
+                .parse()
+                .unwrap()
+        ));
+        println!("Endpoint interface [1c2]: {res:?}");
+        assert!(res.unwrap_err().to_string().contains("not found"));
+        let res = ztimeout!(manager.add_listener(
+            format!("tcp/127.0.0.1:14218#{LISTEN_IFACE}={iface}")
+                .parse()
+                .unwrap()
+        ));
+        assert!(res.is_err());
+        assert!(ztimeout!(manager.get_listeners_unicast()).is_empty());
+
+        ztimeout!(manager.close());
+    });
+}
@@ -273,6 +273,8 @@ mod tests {
                 ext_sinfo: None,
                 #[cfg(feature = "shared-memory")]
                 ext_shm: None,
+                ext_integrity: None,
+                ext_batch: None,
                 ext_unknown: vec![],
             }
             .into(),
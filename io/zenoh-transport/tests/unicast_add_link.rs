@@ -0,0 +1,257 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+#[cfg(feature = "transport_multilink")]
+mod tests {
+    use async_std::{prelude::FutureExt, task};
+    use std::any::Any;
+    use std::convert::TryFrom;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+    use zenoh_core::zasync_executor_init;
+    use zenoh_link::{EndPoint, Link};
+    use zenoh_protocol::{
+        core::{CongestionControl, Encoding, Priority, WhatAmI, ZenohId},
+        network::{
+            push::{
+                ext::{NodeIdType, QoSType},
+                Push,
+            },
+            NetworkBody, NetworkMessage,
+        },
+        zenoh::Put,
+    };
+    use zenoh_result::ZResult;
+    use zenoh_transport::{
+        DummyTransportEventHandler, TransportEventHandler, TransportManager, TransportMulticast,
+        TransportMulticastEventHandler, TransportPeer, TransportPeerEventHandler, TransportUnicast,
+    };
+
+    const TIMEOUT: Duration = Duration::from_secs(60);
+    const SLEEP: Duration = Duration::from_millis(100);
+    const MSG_COUNT: usize = 1_000;
+    const KEY_DURING: &str = "test/add_link/during";
+    const KEY_AFTER: &str = "test/add_link/after";
+
+    macro_rules! ztimeout {
+        ($f:expr) => {
+            $f.timeout(TIMEOUT).await.unwrap()
+        };
+    }
+
+    // Transport Handler for the router, counting the messages sent after the removal of the link
+    struct SHRouterCount {
+        count: Arc<AtomicUsize>,
+    }
+
+    impl TransportEventHandler for SHRouterCount {
+        fn new_unicast(
+            &self,
+            _peer: TransportPeer,
+            _transport: TransportUnicast,
+        ) -> ZResult<Arc<dyn TransportPeerEventHandler>> {
+            Ok(Arc::new(SCRouterCount {
+                count: self.count.clone(),
+            }))
+        }
+
+        fn new_multicast(
+            &self,
+            _transport: TransportMulticast,
+        ) -> ZResult<Arc<dyn TransportMulticastEventHandler>> {
+            panic!();
+        }
+    }
+
+    struct SCRouterCount {
+        count: Arc<AtomicUsize>,
+    }
+
+    impl TransportPeerEventHandler for SCRouterCount {
+        fn handle_message(&self, message: NetworkMessage) -> ZResult<()> {
+            if let NetworkBody::Push(push) = message.body {
+                if push.wire_expr.suffix == KEY_AFTER {
+                    self.count.fetch_add(1, Ordering::SeqCst);
+                }
+            }
+            Ok(())
+        }
+
+        fn new_link(&self, _link: Link) {}
+        fn del_link(&self, _link: Link) {}
+        fn closing(&self) {}
+        fn closed(&self) {}
+
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+    }
+
+    fn message(key: &'static str) -> NetworkMessage {
+        Push {
+            wire_expr: key.into(),
+            ext_qos: QoSType::new(Priority::default(), CongestionControl::Block, false),
+            ext_tstamp: None,
+            ext_nodeid: NodeIdType::default(),
+            payload: Put {
+                payload: vec![0u8; 8].into(),
+                timestamp: None,
+                encoding: Encoding::default(),
+                ext_sinfo: None,
+                #[cfg(feature = "shared-memory")]
+                ext_shm: None,
+                ext_integrity: None,
+                ext_batch: None,
+                ext_unknown: vec![],
+            }
+            .into(),
+        }
+        .into()
+    }
+
+    async fn wait_router_links(router_manager: &TransportManager, client_id: &ZenohId, n: usize) {
+        ztimeout!(async {
+            loop {
+                let transport = router_manager.get_transport_unicast(client_id).await;
+                let transport = transport.expect("The router closed the transport");
+                if transport.get_links().unwrap().len() == n {
+                    break;
+                }
+                task::sleep(SLEEP).await;
+            }
+        });
+    }
+
+    async fn add_del_link_transport(first: &EndPoint, second: &EndPoint) {
+        let count = Arc::new(AtomicUsize::new(0));
+
+        let router_id = ZenohId::try_from([1]).unwrap();
+        let router_manager = TransportManager::builder()
+            .whatami(WhatAmI::Router)
+            .zid(router_id)
+            .unicast(TransportManager::config_unicast().max_links(2))
+            .build(Arc::new(SHRouterCount {
+                count: count.clone(),
+            }))
+            .unwrap();
+
+        let client_id = ZenohId::try_from([2]).unwrap();
+        let client_manager = TransportManager::builder()
+            .whatami(WhatAmI::Client)
+            .zid(client_id)
+            .unicast(TransportManager::config_unicast().max_links(2))
+            .build(Arc::new(DummyTransportEventHandler))
+            .unwrap();
+
+        println!("Transport Add Link [1a1]");
+        for endpoint in [first, second] {
+            let res = ztimeout!(router_manager.add_listener(endpoint.clone()));
+            println!("Transport Add Link [1a2]: {res:?}");
+            assert!(res.is_ok());
+        }
+
+        println!("Transport Add Link [1b1]");
+        let res = ztimeout!(client_manager.open_transport_unicast(first.clone()));
+        println!("Transport Add Link [1b2]: {res:?}");
+        let client_transport = res.unwrap();
+
+        // A link over the other protocol is added to the transport
+        println!("Transport Add Link [2a1]");
+        let res = ztimeout!(client_manager.open_link_unicast(&router_id, second.clone()));
+        println!("Transport Add Link [2a2]: {res:?}");
+        assert!(res.is_ok());
+        assert_eq!(client_transport.get_links().unwrap().len(), 2);
+        wait_router_links(&router_manager, &client_id, 2).await;
+
+        // A third link exceeds the max links of the router
+        println!("Transport Add Link [2b1]");
+        let res = ztimeout!(client_manager.open_link_unicast(&router_id, first.clone()));
+        println!("Transport Add Link [2b2]: {res:?}");
+        assert!(res.is_err());
+        assert_eq!(client_transport.get_links().unwrap().len(), 2);
+
+        // A link can't be added to the transport of an unknown peer
+        println!("Transport Add Link [2c1]");
+        let unknown = ZenohId::try_from([3]).unwrap();
+        let res = ztimeout!(client_manager.open_link_unicast(&unknown, second.clone()));
+        println!("Transport Add Link [2c2]: {res:?}");
+        assert!(res.is_err());
+
+        // Keep sending while the added link is removed
+        println!("Transport Add Link [3a1]: sending {MSG_COUNT} messages");
+        let c_transport = client_transport.clone();
+        let sender = task::spawn(async move {
+            for _ in 0..MSG_COUNT {
+                c_transport.schedule(message(KEY_DURING)).unwrap();
+                task::yield_now().await;
+            }
+        });
+
+        let link = client_transport
+            .get_links()
+            .unwrap()
+            .into_iter()
+            .find(|l| l.dst.protocol() == second.protocol())
+            .unwrap();
+        println!("Transport Add Link [3b1]: {link}");
+        let res = ztimeout!(client_transport.del_link(&link.dst));
+        println!("Transport Add Link [3b2]: {res:?}");
+        assert!(res.is_ok());
+        ztimeout!(sender);
+
+        // The transport keeps working on the remaining link
+        let links = client_transport.get_links().unwrap();
+        println!("Transport Add Link [3c1]: {links:?}");
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0].dst.protocol(), first.protocol());
+        wait_router_links(&router_manager, &client_id, 1).await;
+
+        println!("Transport Add Link [3d1]: sending {MSG_COUNT} messages");
+        for _ in 0..MSG_COUNT {
+            client_transport.schedule(message(KEY_AFTER)).unwrap();
+        }
+        ztimeout!(async {
+            while count.load(Ordering::SeqCst) != MSG_COUNT {
+                task::sleep(SLEEP).await;
+            }
+        });
+        println!("Transport Add Link [3d2]: {MSG_COUNT} messages received");
+
+        // The last link can't be removed
+        println!("Transport Add Link [4a1]");
+        let res = ztimeout!(client_transport.del_link(&links[0].dst));
+        println!("Transport Add Link [4a2]: {res:?}");
+        assert!(res.is_err());
+        assert_eq!(client_transport.get_links().unwrap().len(), 1);
+
+        ztimeout!(router_manager.close());
+        ztimeout!(client_manager.close());
+
+        // Wait a little bit
+        task::sleep(SLEEP).await;
+    }
+
+    #[cfg(all(feature = "transport_tcp", feature = "transport_udp"))]
+    #[test]
+    fn add_del_link_tcp_udp() {
+        let _ = env_logger::try_init();
+        task::block_on(async {
+            zasync_executor_init!();
+        });
+
+        let first: EndPoint = format!("tcp/127.0.0.1:{}", 14197).parse().unwrap();
+        let second: EndPoint = format!("udp/127.0.0.1:{}", 14198).parse().unwrap();
+        task::block_on(add_del_link_transport(&first, &second));
+    }
+}
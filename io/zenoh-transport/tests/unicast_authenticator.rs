@@ -409,6 +409,124 @@ async fn auth_pubkey(endpoint: &EndPoint, lowlatency_transport: bool) {
     task::sleep(SLEEP).await;
 }
 
+#[cfg(feature = "auth_pubkey")]
+async fn auth_pubkey_mismatch(endpoint: &EndPoint) {
+    use rsa::{RsaPrivateKey, RsaPublicKey};
+    use zenoh_transport::test_helpers::make_transport_manager_builder;
+    use zenoh_transport::unicast::establishment::ext::auth::AuthPubKey;
+    use zenoh_transport::TransportManager;
+
+    let make_auth = |pri_key: &RsaPrivateKey| {
+        let mut auth = Auth::empty();
+        auth.set_pubkey(Some(AuthPubKey::new(
+            RsaPublicKey::from(pri_key).into(),
+            pri_key.clone().into(),
+        )));
+        auth
+    };
+    let make_manager = |zid: ZenohId, auth: Auth| {
+        let unicast = make_transport_manager_builder(
+            2,
+            #[cfg(feature = "shared-memory")]
+            false,
+            false,
+        )
+        .authenticator(auth);
+        TransportManager::builder()
+            .whatami(WhatAmI::Client)
+            .zid(zid)
+            .unicast(unicast)
+            .build(Arc::new(SHClientAuthenticator))
+            .unwrap()
+    };
+
+    let mut rng = rand::thread_rng();
+    let client_pri_key = RsaPrivateKey::new(&mut rng, 512).unwrap();
+    let other_pri_key = RsaPrivateKey::new(&mut rng, 512).unwrap();
+    let router_pri_key = RsaPrivateKey::new(&mut rng, 512).unwrap();
+
+    // The router only accepts the key of the client
+    let mut auth_pubkey = AuthPubKey::new(
+        RsaPublicKey::from(&router_pri_key).into(),
+        router_pri_key.into(),
+    );
+    auth_pubkey
+        .add_pubkey(RsaPublicKey::from(&client_pri_key).into())
+        .await
+        .unwrap();
+    let mut auth = Auth::empty();
+    auth.set_pubkey(Some(auth_pubkey));
+    let unicast = make_transport_manager_builder(
+        2,
+        #[cfg(feature = "shared-memory")]
+        false,
+        false,
+    )
+    .authenticator(auth);
+    let router_manager = TransportManager::builder()
+        .whatami(WhatAmI::Router)
+        .zid(ZenohId::try_from([1]).unwrap())
+        .unicast(unicast)
+        .build(Arc::new(SHRouterAuthenticator::new()))
+        .unwrap();
+
+    let client_id = ZenohId::try_from([2]).unwrap();
+    let client01_manager = make_manager(client_id, make_auth(&client_pri_key));
+    let client02_manager = make_manager(ZenohId::try_from([3]).unwrap(), make_auth(&other_pri_key));
+    // Both the client 01 and the client 03 hold the key accepted by the router but each of them
+    // presents its own multilink key for the links of its transport
+    let client03_manager = make_manager(client_id, make_auth(&client_pri_key));
+
+    /* [1] */
+    println!("\nTransport Authenticator PubKey Mismatch [1a1]");
+    ztimeout!(router_manager.add_listener(endpoint.clone())).unwrap();
+
+    /* [2] */
+    // Open a transport with the accepted key
+    // -> This should be accepted
+    println!("Transport Authenticator PubKey Mismatch [2a1]");
+    let c_ses1 = ztimeout!(client01_manager.open_transport_unicast(endpoint.clone())).unwrap();
+    assert_eq!(c_ses1.get_links().unwrap().len(), 1);
+
+    // Open a transport with a key unknown to the router
+    // -> This should be rejected as unauthorized
+    println!("Transport Authenticator PubKey Mismatch [2b1]");
+    let res = ztimeout!(client02_manager.open_transport_unicast(endpoint.clone()));
+    println!("Transport Authenticator PubKey Mismatch [2b2]: {res:?}");
+    assert!(res.unwrap_err().to_string().contains("UNAUTHORIZED"));
+
+    // Add a link to the transport of the client 01 claiming its ZenohId with another key
+    // -> This should be rejected as unauthorized
+    println!("Transport Authenticator PubKey Mismatch [2c1]");
+    let res = ztimeout!(client03_manager.open_transport_unicast(endpoint.clone()));
+    println!("Transport Authenticator PubKey Mismatch [2c2]: {res:?}");
+    assert!(res.unwrap_err().to_string().contains("UNAUTHORIZED"));
+    let transports = ztimeout!(router_manager.get_transports_unicast());
+    assert_eq!(transports.len(), 1);
+    assert_eq!(transports[0].get_links().unwrap().len(), 1);
+
+    /* [3] */
+    println!("Transport Authenticator PubKey Mismatch [3a1]");
+    let res = ztimeout!(c_ses1.close());
+    println!("Transport Authenticator PubKey Mismatch [3a2]: {res:?}");
+    assert!(res.is_ok());
+
+    ztimeout!(async {
+        while !router_manager.get_transports_unicast().await.is_empty() {
+            task::sleep(SLEEP).await;
+        }
+    });
+
+    ztimeout!(router_manager.del_listener(endpoint)).unwrap();
+    ztimeout!(client01_manager.close());
+    ztimeout!(client02_manager.close());
+    ztimeout!(client03_manager.close());
+    ztimeout!(router_manager.close());
+
+    // Wait a little bit
+    task::sleep(SLEEP).await;
+}
+
 #[cfg(feature = "auth_usrpwd")]
 async fn auth_usrpwd(endpoint: &EndPoint, lowlatency_transport: bool) {
     use zenoh_transport::test_helpers::make_basic_transport_manager_builder;
@@ -617,6 +735,113 @@ async fn auth_usrpwd(endpoint: &EndPoint, lowlatency_transport: bool) {
     task::sleep(SLEEP).await;
 }
 
+#[cfg(feature = "auth_usrpwd")]
+async fn auth_usrpwd_rejections(endpoint: &EndPoint) {
+    use zenoh_config::{Config, ValidatedMap};
+    use zenoh_transport::TransportManager;
+
+    // The managers are configured from the transport/auth/usrpwd section of their config
+    let make_manager = |zid: u8, whatami: WhatAmI, usrpwd: &str| {
+        let mut config = Config::default();
+        config.set_id(ZenohId::try_from([zid]).unwrap()).unwrap();
+        config.set_mode(Some(whatami)).unwrap();
+        config
+            .insert_json5("transport/auth/usrpwd", usrpwd)
+            .unwrap();
+        async move {
+            let handler: Arc<dyn TransportEventHandler> = match whatami {
+                WhatAmI::Router => Arc::new(SHRouterAuthenticator::new()),
+                _ => Arc::new(SHClientAuthenticator),
+            };
+            TransportManager::builder()
+                .from_config(&config)
+                .await
+                .unwrap()
+                .build(handler)
+                .unwrap()
+        }
+    };
+
+    let router_manager = make_manager(
+        1,
+        WhatAmI::Router,
+        r#"{ dictionary: { user01: "password01" } }"#,
+    )
+    .await;
+    let client01_manager = make_manager(
+        2,
+        WhatAmI::Client,
+        r#"{ user: "user01", password: "password01" }"#,
+    )
+    .await;
+    let client02_manager = make_manager(
+        3,
+        WhatAmI::Client,
+        r#"{ user: "user01", password: "wrong" }"#,
+    )
+    .await;
+    let client03_manager = make_manager(
+        4,
+        WhatAmI::Client,
+        r#"{ user: "unknown", password: "password01" }"#,
+    )
+    .await;
+    let client04_manager = make_manager(5, WhatAmI::Client, "{}").await;
+
+    println!("Transport Authenticator UserPassword Rejections [1a1]");
+    let res = ztimeout!(router_manager.add_listener(endpoint.clone()));
+    println!("Transport Authenticator UserPassword Rejections [1a1]: {res:?}");
+    assert!(res.is_ok());
+
+    // Wrong password
+    println!("Transport Authenticator UserPassword Rejections [2a1]");
+    let res = ztimeout!(client02_manager.open_transport_unicast(endpoint.clone()));
+    println!("Transport Authenticator UserPassword Rejections [2a1]: {res:?}");
+    assert!(res.is_err());
+
+    // Unknown user
+    println!("Transport Authenticator UserPassword Rejections [3a1]");
+    let res = ztimeout!(client03_manager.open_transport_unicast(endpoint.clone()));
+    println!("Transport Authenticator UserPassword Rejections [3a1]: {res:?}");
+    assert!(res.is_err());
+
+    // No credentials while the router requires them
+    println!("Transport Authenticator UserPassword Rejections [4a1]");
+    let res = ztimeout!(client04_manager.open_transport_unicast(endpoint.clone()));
+    println!("Transport Authenticator UserPassword Rejections [4a1]: {res:?}");
+    assert!(res.is_err());
+    assert!(router_manager.get_transports_unicast().await.is_empty());
+
+    // Valid credentials
+    println!("Transport Authenticator UserPassword Rejections [5a1]");
+    let res = ztimeout!(client01_manager.open_transport_unicast(endpoint.clone()));
+    println!("Transport Authenticator UserPassword Rejections [5a1]: {res:?}");
+    let c_ses1 = res.unwrap();
+    let res = ztimeout!(c_ses1.close());
+    println!("Transport Authenticator UserPassword Rejections [5a2]: {res:?}");
+    assert!(res.is_ok());
+
+    ztimeout!(async {
+        while !router_manager.get_transports_unicast().await.is_empty() {
+            task::sleep(SLEEP).await;
+        }
+    });
+
+    println!("Transport Authenticator UserPassword Rejections [6a1]");
+    let res = ztimeout!(router_manager.del_listener(endpoint));
+    println!("Transport Authenticator UserPassword Rejections [6a1]: {res:?}");
+    assert!(res.is_ok());
+
+    ztimeout!(router_manager.close());
+    ztimeout!(client01_manager.close());
+    ztimeout!(client02_manager.close());
+    ztimeout!(client03_manager.close());
+    ztimeout!(client04_manager.close());
+
+    // Wait a little bit
+    task::sleep(SLEEP).await;
+}
+
 async fn run(endpoint: &EndPoint, lowlatency_transport: bool) {
     #[cfg(feature = "auth_pubkey")]
     auth_pubkey(endpoint, lowlatency_transport).await;
@@ -656,6 +881,30 @@ fn authenticator_tcp_with_lowlatency_transport() {
     task::block_on(run_with_lowlatency_transport(&endpoint));
 }
 
+#[cfg(all(feature = "transport_tcp", feature = "auth_pubkey"))]
+#[test]
+fn authenticator_pubkey_mismatch_tcp() {
+    let _ = env_logger::try_init();
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+
+    let endpoint: EndPoint = format!("tcp/127.0.0.1:{}", 14150).parse().unwrap();
+    task::block_on(auth_pubkey_mismatch(&endpoint));
+}
+
+#[cfg(all(feature = "transport_tcp", feature = "auth_usrpwd"))]
+#[test]
+fn authenticator_usrpwd_rejections_tcp() {
+    let _ = env_logger::try_init();
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+
+    let endpoint: EndPoint = format!("tcp/127.0.0.1:{}", 14151).parse().unwrap();
+    task::block_on(auth_usrpwd_rejections(&endpoint));
+}
+
 #[cfg(feature = "transport_udp")]
 #[test]
 fn authenticator_udp() {
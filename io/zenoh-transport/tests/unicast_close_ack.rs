@@ -0,0 +1,233 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use async_std::{prelude::FutureExt, task};
+#[cfg(feature = "transport_udp")]
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::{
+    convert::TryFrom,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use zenoh_core::zasync_executor_init;
+use zenoh_link::EndPoint;
+use zenoh_protocol::core::{WhatAmI, ZenohId};
+use zenoh_result::ZResult;
+use zenoh_transport::{
+    test_helpers::make_transport_manager_builder, DummyTransportPeerEventHandler,
+    TransportEventHandler, TransportManager, TransportMulticast, TransportMulticastEventHandler,
+    TransportPeer, TransportPeerEventHandler, TransportUnicast,
+};
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_millis(10);
+// A lease way longer than the test: the transports can only be closed by a Close
+const LEASE: Duration = Duration::from_secs(1_000);
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(2);
+
+macro_rules! ztimeout {
+    ($f:expr) => {
+        $f.timeout(TIMEOUT).await.unwrap()
+    };
+}
+
+// Transport Handler
+#[derive(Default)]
+struct SHCloseAck;
+
+impl TransportEventHandler for SHCloseAck {
+    fn new_unicast(
+        &self,
+        _peer: TransportPeer,
+        _transport: TransportUnicast,
+    ) -> ZResult<Arc<dyn TransportPeerEventHandler>> {
+        Ok(Arc::new(DummyTransportPeerEventHandler))
+    }
+
+    fn new_multicast(
+        &self,
+        _transport: TransportMulticast,
+    ) -> ZResult<Arc<dyn TransportMulticastEventHandler>> {
+        panic!();
+    }
+}
+
+fn make_manager(zid: ZenohId, whatami: WhatAmI) -> TransportManager {
+    let unicast = make_transport_manager_builder(
+        #[cfg(feature = "transport_multilink")]
+        1,
+        #[cfg(feature = "shared-memory")]
+        false,
+        false,
+    )
+    .lease(LEASE)
+    .close_timeout(CLOSE_TIMEOUT);
+    TransportManager::builder()
+        .whatami(whatami)
+        .zid(zid)
+        .unicast(unicast)
+        .build(Arc::new(SHCloseAck))
+        .unwrap()
+}
+
+async fn open(
+    router_manager: &TransportManager,
+    client_manager: &TransportManager,
+    endpoint: &EndPoint,
+    client_endpoint: &EndPoint,
+) -> TransportUnicast {
+    println!("Transport Close Ack [1a1]");
+    let res = ztimeout!(router_manager.add_listener(endpoint.clone()));
+    println!("Transport Close Ack [1a2]: {res:?}");
+    assert!(res.is_ok());
+
+    println!("Transport Close Ack [1b1]");
+    let res = ztimeout!(client_manager.open_transport_unicast(client_endpoint.clone()));
+    println!("Transport Close Ack [1b2]: {res:?}");
+    res.unwrap()
+}
+
+async fn close(router_manager: &TransportManager, client_manager: &TransportManager) {
+    // Only a Close can remove the transport of the router before the lease expires
+    println!("Transport Close Ack [3a1]");
+    ztimeout!(async {
+        while !ztimeout!(router_manager.get_transports_unicast()).is_empty() {
+            task::sleep(SLEEP).await;
+        }
+    });
+
+    println!("Transport Close Ack [4a1]");
+    for endpoint in ztimeout!(router_manager.get_listeners_unicast()) {
+        let res = ztimeout!(router_manager.del_listener(&endpoint));
+        println!("Transport Close Ack [4a2]: {res:?}");
+        assert!(res.is_ok());
+    }
+
+    ztimeout!(router_manager.close());
+    ztimeout!(client_manager.close());
+}
+
+async fn close_ack_stream(endpoint: &EndPoint) {
+    let router_id = ZenohId::try_from([1]).unwrap();
+    let router_manager = make_manager(router_id, WhatAmI::Router);
+    let client_id = ZenohId::try_from([2]).unwrap();
+    let client_manager = make_manager(client_id, WhatAmI::Client);
+
+    let transport = open(&router_manager, &client_manager, endpoint, endpoint).await;
+
+    // The close completes as soon as the router echoes the Close
+    println!("Transport Close Ack [2a1]");
+    let now = Instant::now();
+    let res = ztimeout!(transport.close());
+    println!("Transport Close Ack [2a2]: {res:?} in {:?}", now.elapsed());
+    assert!(res.is_ok());
+    assert!(now.elapsed() < CLOSE_TIMEOUT);
+
+    close(&router_manager, &client_manager).await;
+}
+
+#[cfg(feature = "transport_udp")]
+struct Proxy {
+    // Drop the next datagram sent by the client
+    drop_next: AtomicBool,
+    // The datagrams of the client forwarded after the dropped one
+    forwarded: AtomicUsize,
+}
+
+// An UDP proxy forwarding the datagrams of a single client from `listen` to `server`,
+// which can drop the next datagram sent by the client.
+#[cfg(feature = "transport_udp")]
+async fn proxy(listen: &str, server: &str) -> Arc<Proxy> {
+    use async_std::net::UdpSocket;
+
+    let proxy = Arc::new(Proxy {
+        drop_next: AtomicBool::new(false),
+        forwarded: AtomicUsize::new(0),
+    });
+    let front = Arc::new(UdpSocket::bind(listen).await.unwrap());
+    let back = Arc::new(UdpSocket::bind("127.0.0.1:0").await.unwrap());
+    back.connect(server).await.unwrap();
+    let c_proxy = proxy.clone();
+    task::spawn(async move {
+        let mut buf = vec![0u8; 65_535];
+        let (n, client) = front.recv_from(&mut buf).await.unwrap();
+        back.send(&buf[..n]).await.unwrap();
+
+        let c_front = front.clone();
+        let c_back = back.clone();
+        task::spawn(async move {
+            let mut buf = vec![0u8; 65_535];
+            while let Ok(n) = c_back.recv(&mut buf).await {
+                let _ = c_front.send_to(&buf[..n], client).await;
+            }
+        });
+
+        while let Ok((n, _)) = front.recv_from(&mut buf).await {
+            if c_proxy.drop_next.swap(false, Ordering::SeqCst) {
+                continue;
+            }
+            c_proxy.forwarded.fetch_add(1, Ordering::SeqCst);
+            let _ = back.send(&buf[..n]).await;
+        }
+    });
+    proxy
+}
+
+#[cfg(feature = "transport_udp")]
+async fn close_ack_dgram(endpoint: &EndPoint, proxy_endpoint: &EndPoint) {
+    let router_id = ZenohId::try_from([1]).unwrap();
+    let router_manager = make_manager(router_id, WhatAmI::Router);
+    let client_id = ZenohId::try_from([2]).unwrap();
+    let client_manager = make_manager(client_id, WhatAmI::Client);
+
+    let address = |e: &EndPoint| e.address().as_str().to_string();
+    let proxy = ztimeout!(proxy(&address(proxy_endpoint), &address(endpoint)));
+    let transport = open(&router_manager, &client_manager, endpoint, proxy_endpoint).await;
+
+    // The first Close is lost: the client sends it again
+    println!("Transport Close Ack [2a1]");
+    proxy.forwarded.store(0, Ordering::SeqCst);
+    proxy.drop_next.store(true, Ordering::SeqCst);
+    let res = ztimeout!(transport.close());
+    println!("Transport Close Ack [2a2]: {res:?}");
+    assert!(res.is_ok());
+    assert!(!proxy.drop_next.load(Ordering::SeqCst));
+    assert!(proxy.forwarded.load(Ordering::SeqCst) > 0);
+
+    close(&router_manager, &client_manager).await;
+}
+
+#[cfg(feature = "transport_tcp")]
+#[test]
+fn close_ack_tcp_only() {
+    let _ = env_logger::try_init();
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+
+    let endpoint: EndPoint = format!("tcp/127.0.0.1:{}", 14140).parse().unwrap();
+    task::block_on(close_ack_stream(&endpoint));
+}
+
+#[cfg(feature = "transport_udp")]
+#[test]
+fn close_ack_udp_only() {
+    let _ = env_logger::try_init();
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+
+    let endpoint: EndPoint = format!("udp/127.0.0.1:{}", 14141).parse().unwrap();
+    let proxy_endpoint: EndPoint = format!("udp/127.0.0.1:{}", 14142).parse().unwrap();
+    task::block_on(close_ack_dgram(&endpoint, &proxy_endpoint));
+}
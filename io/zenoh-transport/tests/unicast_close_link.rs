@@ -0,0 +1,320 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use async_std::{prelude::FutureExt, task};
+use std::any::Any;
+use std::convert::TryFrom;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use zenoh_core::zasync_executor_init;
+use zenoh_link::{EndPoint, Link};
+use zenoh_protocol::{
+    core::{CongestionControl, Encoding, Priority, WhatAmI, ZenohId},
+    network::{
+        push::{
+            ext::{NodeIdType, QoSType},
+            Push,
+        },
+        NetworkBody, NetworkMessage,
+    },
+    transport::close,
+    zenoh::Put,
+};
+use zenoh_result::ZResult;
+use zenoh_transport::{
+    DummyTransportPeerEventHandler, TransportEventHandler, TransportManager, TransportMulticast,
+    TransportMulticastEventHandler, TransportPeer, TransportPeerEventHandler, TransportUnicast,
+};
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_millis(100);
+const MSG_COUNT: usize = 1_000;
+const KEY_DURING: &str = "test/close_link/during";
+const KEY_AFTER: &str = "test/close_link/after";
+
+macro_rules! ztimeout {
+    ($f:expr) => {
+        $f.timeout(TIMEOUT).await.unwrap()
+    };
+}
+
+// Transport Handler for the router, counting the messages sent after the close of the link.
+// The messages sent during the close may be reordered across the links, and thus dropped by the
+// sequence number check of the receiver.
+struct SHRouterCount {
+    count: Arc<AtomicUsize>,
+}
+
+impl TransportEventHandler for SHRouterCount {
+    fn new_unicast(
+        &self,
+        _peer: TransportPeer,
+        _transport: TransportUnicast,
+    ) -> ZResult<Arc<dyn TransportPeerEventHandler>> {
+        Ok(Arc::new(SCRouterCount {
+            count: self.count.clone(),
+        }))
+    }
+
+    fn new_multicast(
+        &self,
+        _transport: TransportMulticast,
+    ) -> ZResult<Arc<dyn TransportMulticastEventHandler>> {
+        panic!();
+    }
+}
+
+struct SCRouterCount {
+    count: Arc<AtomicUsize>,
+}
+
+impl TransportPeerEventHandler for SCRouterCount {
+    fn handle_message(&self, message: NetworkMessage) -> ZResult<()> {
+        if let NetworkBody::Push(push) = message.body {
+            if push.wire_expr.suffix == KEY_AFTER {
+                self.count.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+        Ok(())
+    }
+
+    fn new_link(&self, _link: Link) {}
+    fn del_link(&self, _link: Link) {}
+    fn closing(&self) {}
+    fn closed(&self) {}
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+// Transport Handler for the client
+#[derive(Default)]
+struct SHClient;
+
+impl TransportEventHandler for SHClient {
+    fn new_unicast(
+        &self,
+        _peer: TransportPeer,
+        _transport: TransportUnicast,
+    ) -> ZResult<Arc<dyn TransportPeerEventHandler>> {
+        Ok(Arc::new(DummyTransportPeerEventHandler))
+    }
+
+    fn new_multicast(
+        &self,
+        _transport: TransportMulticast,
+    ) -> ZResult<Arc<dyn TransportMulticastEventHandler>> {
+        panic!();
+    }
+}
+
+fn message(key: &'static str) -> NetworkMessage {
+    Push {
+        wire_expr: key.into(),
+        ext_qos: QoSType::new(Priority::default(), CongestionControl::Block, false),
+        ext_tstamp: None,
+        ext_nodeid: NodeIdType::default(),
+        payload: Put {
+            payload: vec![0u8; 8].into(),
+            timestamp: None,
+            encoding: Encoding::default(),
+            ext_sinfo: None,
+            #[cfg(feature = "shared-memory")]
+            ext_shm: None,
+            ext_integrity: None,
+            ext_batch: None,
+            ext_unknown: vec![],
+        }
+        .into(),
+    }
+    .into()
+}
+
+async fn open_transport(
+    endpoint: &EndPoint,
+    links: usize,
+) -> (
+    TransportManager,
+    ZenohId,
+    Arc<AtomicUsize>,
+    TransportManager,
+    TransportUnicast,
+) {
+    let count = Arc::new(AtomicUsize::new(0));
+
+    let router_id = ZenohId::try_from([1]).unwrap();
+    let router_manager = TransportManager::builder()
+        .whatami(WhatAmI::Router)
+        .zid(router_id)
+        .unicast(TransportManager::config_unicast().max_links(links))
+        .build(Arc::new(SHRouterCount {
+            count: count.clone(),
+        }))
+        .unwrap();
+
+    let client_id = ZenohId::try_from([2]).unwrap();
+    let client_manager = TransportManager::builder()
+        .whatami(WhatAmI::Client)
+        .zid(client_id)
+        .unicast(TransportManager::config_unicast().max_links(links))
+        .build(Arc::new(SHClient))
+        .unwrap();
+
+    println!("Transport Close Link [1a1]");
+    let res = ztimeout!(router_manager.add_listener(endpoint.clone()));
+    println!("Transport Close Link [1a2]: {res:?}");
+    assert!(res.is_ok());
+
+    // Every open adds a link to the same transport
+    let mut client_transport = None;
+    for _ in 0..links {
+        println!("Transport Close Link [1b1]");
+        let res = ztimeout!(client_manager.open_transport_unicast(endpoint.clone()));
+        println!("Transport Close Link [1b2]: {res:?}");
+        client_transport = Some(res.unwrap());
+    }
+    let client_transport = client_transport.unwrap();
+    assert_eq!(client_transport.get_links().unwrap().len(), links);
+
+    // Wait for the links to be added on the router
+    ztimeout!(async {
+        loop {
+            if let Some(t) = router_manager.get_transport_unicast(&client_id).await {
+                if t.get_links().unwrap().len() == links {
+                    break;
+                }
+            }
+            task::sleep(SLEEP).await;
+        }
+    });
+
+    (
+        router_manager,
+        client_id,
+        count,
+        client_manager,
+        client_transport,
+    )
+}
+
+async fn close_link_transport(endpoint: &EndPoint) {
+    let (router_manager, client_id, count, client_manager, client_transport) =
+        open_transport(endpoint, 2).await;
+
+    // Keep sending while the link is closed: the messages are scheduled on the other link
+    println!("Transport Close Link [2a1]: sending {MSG_COUNT} messages");
+    let c_transport = client_transport.clone();
+    let sender = task::spawn(async move {
+        for _ in 0..MSG_COUNT {
+            c_transport.schedule(message(KEY_DURING)).unwrap();
+            task::yield_now().await;
+        }
+    });
+
+    let link = client_transport.get_links().unwrap().remove(0);
+    println!("Transport Close Link [2b1]: {link}");
+    let res = ztimeout!(client_transport.close_link(&link, close::reason::GENERIC));
+    println!("Transport Close Link [2b2]: {res:?}");
+    assert!(res.is_ok());
+    ztimeout!(sender);
+
+    // The transport stays up on the remaining link on both sides
+    let links = client_transport.get_links().unwrap();
+    println!("Transport Close Link [2c1]: {links:?}");
+    assert_eq!(links.len(), 1);
+    assert_ne!(links[0], link);
+    ztimeout!(async {
+        loop {
+            let transport = router_manager.get_transport_unicast(&client_id).await;
+            let transport = transport.expect("The router closed the transport");
+            if transport.get_links().unwrap().len() == 1 {
+                break;
+            }
+            task::sleep(SLEEP).await;
+        }
+    });
+
+    // No message is lost on the remaining link
+    println!("Transport Close Link [2d1]: sending {MSG_COUNT} messages");
+    for _ in 0..MSG_COUNT {
+        client_transport.schedule(message(KEY_AFTER)).unwrap();
+    }
+    ztimeout!(async {
+        while count.load(Ordering::SeqCst) != MSG_COUNT {
+            task::sleep(SLEEP).await;
+        }
+    });
+    println!("Transport Close Link [2d2]: {MSG_COUNT} messages received");
+
+    ztimeout!(router_manager.close());
+    ztimeout!(client_manager.close());
+
+    // Wait a little bit
+    task::sleep(SLEEP).await;
+}
+
+async fn close_last_link_transport(endpoint: &EndPoint) {
+    let (router_manager, client_id, _count, client_manager, client_transport) =
+        open_transport(endpoint, 1).await;
+    let router_id = client_transport.get_zid().unwrap();
+
+    // Closing the last link closes the transport
+    let link = client_transport.get_links().unwrap().remove(0);
+    println!("Transport Close Link [3a1]: {link}");
+    let res = ztimeout!(client_transport.close_link(&link, close::reason::GENERIC));
+    println!("Transport Close Link [3a2]: {res:?}");
+    assert!(res.is_ok());
+
+    assert!(ztimeout!(client_manager.get_transport_unicast(&router_id)).is_none());
+    ztimeout!(async {
+        while router_manager
+            .get_transport_unicast(&client_id)
+            .await
+            .is_some()
+        {
+            task::sleep(SLEEP).await;
+        }
+    });
+
+    ztimeout!(router_manager.close());
+    ztimeout!(client_manager.close());
+
+    // Wait a little bit
+    task::sleep(SLEEP).await;
+}
+
+#[cfg(all(feature = "transport_tcp", feature = "transport_multilink"))]
+#[test]
+fn close_link_tcp() {
+    let _ = env_logger::try_init();
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+
+    let endpoint: EndPoint = format!("tcp/127.0.0.1:{}", 14120).parse().unwrap();
+    task::block_on(close_link_transport(&endpoint));
+}
+
+#[cfg(feature = "transport_tcp")]
+#[test]
+fn close_last_link_tcp() {
+    let _ = env_logger::try_init();
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+
+    let endpoint: EndPoint = format!("tcp/127.0.0.1:{}", 14121).parse().unwrap();
+    task::block_on(close_last_link_transport(&endpoint));
+}
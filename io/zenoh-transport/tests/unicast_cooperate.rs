@@ -0,0 +1,208 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use async_std::{prelude::FutureExt, task};
+use std::any::Any;
+use std::convert::TryFrom;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use zenoh_core::zasync_executor_init;
+use zenoh_link::{EndPoint, Link};
+use zenoh_protocol::{
+    core::{CongestionControl, Encoding, Priority, WhatAmI, ZenohId},
+    network::{
+        push::{
+            ext::{NodeIdType, QoSType},
+            Push,
+        },
+        NetworkMessage,
+    },
+    zenoh::Put,
+};
+use zenoh_result::ZResult;
+use zenoh_transport::{
+    DummyTransportPeerEventHandler, TransportEventHandler, TransportManager, TransportMulticast,
+    TransportMulticastEventHandler, TransportPeer, TransportPeerEventHandler, TransportUnicast,
+};
+use zenoh_util::cooperate::StallWatchdog;
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_millis(10);
+const MSG_COUNT: usize = 100_000;
+const MSG_SIZE: usize = 64;
+// The period of the concurrent timer and the delay of its wake-ups reported as a stall
+const TIMER_PERIOD: Duration = Duration::from_millis(5);
+const STALL_BUDGET: Duration = Duration::from_millis(100);
+
+macro_rules! ztimeout {
+    ($f:expr) => {
+        $f.timeout(TIMEOUT).await.unwrap()
+    };
+}
+
+// Transport Handler for the router
+struct SHRouter {
+    count: Arc<AtomicUsize>,
+}
+
+impl TransportEventHandler for SHRouter {
+    fn new_unicast(
+        &self,
+        _peer: TransportPeer,
+        _transport: TransportUnicast,
+    ) -> ZResult<Arc<dyn TransportPeerEventHandler>> {
+        Ok(Arc::new(SCRouter {
+            count: self.count.clone(),
+        }))
+    }
+
+    fn new_multicast(
+        &self,
+        _transport: TransportMulticast,
+    ) -> ZResult<Arc<dyn TransportMulticastEventHandler>> {
+        panic!();
+    }
+}
+
+struct SCRouter {
+    count: Arc<AtomicUsize>,
+}
+
+impl TransportPeerEventHandler for SCRouter {
+    fn handle_message(&self, _message: NetworkMessage) -> ZResult<()> {
+        self.count.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+
+    fn new_link(&self, _link: Link) {}
+    fn del_link(&self, _link: Link) {}
+    fn closing(&self) {}
+    fn closed(&self) {}
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+// Transport Handler for the client
+#[derive(Default)]
+struct SHClient;
+
+impl TransportEventHandler for SHClient {
+    fn new_unicast(
+        &self,
+        _peer: TransportPeer,
+        _transport: TransportUnicast,
+    ) -> ZResult<Arc<dyn TransportPeerEventHandler>> {
+        Ok(Arc::new(DummyTransportPeerEventHandler))
+    }
+
+    fn new_multicast(
+        &self,
+        _transport: TransportMulticast,
+    ) -> ZResult<Arc<dyn TransportMulticastEventHandler>> {
+        panic!();
+    }
+}
+
+fn message() -> NetworkMessage {
+    Push {
+        wire_expr: "test".into(),
+        ext_qos: QoSType::new(Priority::default(), CongestionControl::Block, false),
+        ext_tstamp: None,
+        ext_nodeid: NodeIdType::default(),
+        payload: Put {
+            payload: vec![0u8; MSG_SIZE].into(),
+            timestamp: None,
+            encoding: Encoding::default(),
+            ext_sinfo: None,
+            #[cfg(feature = "shared-memory")]
+            ext_shm: None,
+            ext_integrity: None,
+            ext_batch: None,
+            ext_unknown: vec![],
+        }
+        .into(),
+    }
+    .into()
+}
+
+async fn burst_transport(endpoint: &EndPoint) {
+    let count = Arc::new(AtomicUsize::new(0));
+
+    let router_id = ZenohId::try_from([1]).unwrap();
+    let router_manager = TransportManager::builder()
+        .whatami(WhatAmI::Router)
+        .zid(router_id)
+        .build(Arc::new(SHRouter {
+            count: count.clone(),
+        }))
+        .unwrap();
+
+    let client_id = ZenohId::try_from([2]).unwrap();
+    let client_manager = TransportManager::builder()
+        .whatami(WhatAmI::Client)
+        .zid(client_id)
+        .build(Arc::new(SHClient))
+        .unwrap();
+
+    println!("Transport Cooperate [1a1]");
+    let res = ztimeout!(router_manager.add_listener(endpoint.clone()));
+    println!("Transport Cooperate [1a2]: {res:?}");
+    assert!(res.is_ok());
+
+    println!("Transport Cooperate [1b1]");
+    let res = ztimeout!(client_manager.open_transport_unicast(endpoint.clone()));
+    println!("Transport Cooperate [1b2]: {res:?}");
+    let client_transport = res.unwrap();
+    task::sleep(SLEEP).await;
+
+    // The burst is delivered entirely, the stalls of the executor being only reported since they
+    // depend on the load of the machine running the test
+    println!("Transport Cooperate [2a1]: sending {MSG_COUNT} messages of {MSG_SIZE} bytes");
+    let watchdog = StallWatchdog::spawn(TIMER_PERIOD, STALL_BUDGET);
+    for _ in 0..MSG_COUNT {
+        client_transport.schedule(message()).unwrap();
+    }
+    ztimeout!(async {
+        while count.load(Ordering::SeqCst) < MSG_COUNT {
+            task::sleep(SLEEP).await;
+        }
+    });
+    let max_latency = watchdog.max_latency();
+    println!(
+        "Transport Cooperate [2a2]: timer woken up at most {} ms late, {} stalls",
+        max_latency.as_millis(),
+        watchdog.stalls()
+    );
+    assert_eq!(count.load(Ordering::SeqCst), MSG_COUNT);
+
+    ztimeout!(router_manager.close());
+    ztimeout!(client_manager.close());
+
+    // Wait a little bit
+    task::sleep(SLEEP).await;
+}
+
+#[cfg(feature = "transport_tcp")]
+#[test]
+fn cooperate_burst_tcp() {
+    let _ = env_logger::try_init();
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+
+    let endpoint: EndPoint = format!("tcp/127.0.0.1:{}", 14180).parse().unwrap();
+    task::block_on(burst_transport(&endpoint));
+}
@@ -12,8 +12,18 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 use async_std::{prelude::FutureExt, task};
-use std::{convert::TryFrom, sync::Arc, time::Duration};
+use std::{
+    any::Any,
+    convert::TryFrom,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use zenoh_buffers::SplitBuffer;
 use zenoh_core::zasync_executor_init;
+use zenoh_link::Link;
 use zenoh_protocol::{
     core::{
         Channel, CongestionControl, Encoding, EndPoint, Priority, Reliability, WhatAmI, ZenohId,
@@ -23,17 +33,24 @@ use zenoh_protocol::{
             ext::{NodeIdType, QoSType},
             Push,
         },
-        NetworkMessage,
+        NetworkBody, NetworkMessage,
     },
-    zenoh::Put,
+    zenoh::{PushBody, Put},
+};
+use zenoh_result::ZResult;
+use zenoh_transport::{
+    DummyTransportEventHandler, TransportEventHandler, TransportManager, TransportMulticast,
+    TransportMulticastEventHandler, TransportPeer, TransportPeerEventHandler, TransportUnicast,
 };
-use zenoh_transport::{DummyTransportEventHandler, TransportManager};
 
 const TIMEOUT: Duration = Duration::from_secs(60);
 const SLEEP: Duration = Duration::from_secs(1);
 
-const MSG_SIZE: usize = 131_072;
 const MSG_DEFRAG_BUF: usize = 128_000;
+// The size of the payloads of the messages just fitting and just exceeding the
+// defragmentation buffer, once serialized with their headers
+const MSG_SIZE_BELOW: usize = MSG_DEFRAG_BUF - 64;
+const MSG_SIZE_ABOVE: usize = MSG_DEFRAG_BUF + 1;
 
 macro_rules! ztimeout {
     ($f:expr) => {
@@ -41,17 +58,94 @@ macro_rules! ztimeout {
     };
 }
 
-async fn run(endpoint: &EndPoint, channel: Channel, msg_size: usize) {
+// Transport Handler for the router
+struct SHRouter {
+    received: Arc<AtomicUsize>,
+}
+
+impl TransportEventHandler for SHRouter {
+    fn new_unicast(
+        &self,
+        _peer: TransportPeer,
+        _transport: TransportUnicast,
+    ) -> ZResult<Arc<dyn TransportPeerEventHandler>> {
+        Ok(Arc::new(SCRouter {
+            received: self.received.clone(),
+        }))
+    }
+
+    fn new_multicast(
+        &self,
+        _transport: TransportMulticast,
+    ) -> ZResult<Arc<dyn TransportMulticastEventHandler>> {
+        panic!();
+    }
+}
+
+// Checks and counts the received messages, all of them fitting in the defragmentation buffer
+struct SCRouter {
+    received: Arc<AtomicUsize>,
+}
+
+impl TransportPeerEventHandler for SCRouter {
+    fn handle_message(&self, message: NetworkMessage) -> ZResult<()> {
+        match message.body {
+            NetworkBody::Push(Push {
+                payload: PushBody::Put(put),
+                ..
+            }) => assert_eq!(put.payload.len(), MSG_SIZE_BELOW),
+            body => panic!("Unexpected message: {body:?}"),
+        }
+        self.received.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+
+    fn new_link(&self, _link: Link) {}
+    fn del_link(&self, _link: Link) {}
+    fn closing(&self) {}
+    fn closed(&self) {}
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+fn message(channel: Channel, msg_size: usize) -> NetworkMessage {
+    Push {
+        wire_expr: "test".into(),
+        ext_qos: QoSType::new(channel.priority, CongestionControl::Block, false),
+        ext_tstamp: None,
+        ext_nodeid: NodeIdType::default(),
+        payload: Put {
+            payload: vec![0u8; msg_size].into(),
+            timestamp: None,
+            encoding: Encoding::default(),
+            ext_sinfo: None,
+            #[cfg(feature = "shared-memory")]
+            ext_shm: None,
+            ext_integrity: None,
+            ext_batch: None,
+            ext_unknown: vec![],
+        }
+        .into(),
+    }
+    .into()
+}
+
+async fn run(endpoint: &EndPoint, channel: Channel) {
     // Define client and router IDs
     let client_id = ZenohId::try_from([1]).unwrap();
     let router_id = ZenohId::try_from([2]).unwrap();
 
     // Create the router transport manager
+    let received = Arc::new(AtomicUsize::new(0));
     let router_manager = TransportManager::builder()
         .zid(router_id)
         .whatami(WhatAmI::Router)
         .defrag_buff_size(MSG_DEFRAG_BUF)
-        .build(Arc::new(DummyTransportEventHandler))
+        .build(Arc::new(SHRouter {
+            received: received.clone(),
+        }))
         .unwrap();
 
     // Create the client transport manager
@@ -76,43 +170,37 @@ async fn run(endpoint: &EndPoint, channel: Channel, msg_size: usize) {
         .await
         .unwrap();
 
-    // Create the message to send
-    let message: NetworkMessage = Push {
-        wire_expr: "test".into(),
-        ext_qos: QoSType::new(channel.priority, CongestionControl::Block, false),
-        ext_tstamp: None,
-        ext_nodeid: NodeIdType::default(),
-        payload: Put {
-            payload: vec![0u8; msg_size].into(),
-            timestamp: None,
-            encoding: Encoding::default(),
-            ext_sinfo: None,
-            #[cfg(feature = "shared-memory")]
-            ext_shm: None,
-            ext_unknown: vec![],
-        }
-        .into(),
-    }
-    .into();
-
+    // The message exceeding the defragmentation buffer is dropped, the ones around it are received
     println!(
-        "Sending message of {msg_size} bytes while defragmentation buffer size is {MSG_DEFRAG_BUF} bytes"
+        "Sending messages of {MSG_SIZE_BELOW}, {MSG_SIZE_ABOVE} and {MSG_SIZE_BELOW} bytes while defragmentation buffer size is {MSG_DEFRAG_BUF} bytes"
     );
-    client_transport.schedule(message.clone()).unwrap();
-
-    // Wait that the client transport has been closed
+    for msg_size in [MSG_SIZE_BELOW, MSG_SIZE_ABOVE, MSG_SIZE_BELOW] {
+        client_transport
+            .schedule(message(channel, msg_size))
+            .unwrap();
+    }
     ztimeout!(async {
-        while client_transport.get_zid().is_ok() {
+        while received.load(Ordering::SeqCst) != 2 {
             task::sleep(SLEEP).await;
         }
     });
 
-    // Wait on the router manager that the transport has been closed
-    ztimeout!(async {
-        while !router_manager.get_transports_unicast().await.is_empty() {
-            task::sleep(SLEEP).await;
-        }
-    });
+    // The transport stays open
+    task::sleep(SLEEP).await;
+    assert_eq!(received.load(Ordering::SeqCst), 2);
+    assert!(client_transport.get_zid().is_ok());
+    let router_transport = router_manager
+        .get_transport_unicast(&client_id)
+        .await
+        .unwrap();
+    #[cfg(feature = "stats")]
+    {
+        let report = router_transport.get_stats().unwrap().report();
+        println!("Router stats: {report:?}");
+        assert_eq!(report.rx_n_oversized, 1);
+    }
+
+    ztimeout!(router_transport.close()).unwrap();
 
     // Stop the locators on the manager
     println!("Del locator: {endpoint}");
@@ -166,7 +254,7 @@ fn transport_unicast_defragmentation_tcp_only() {
     // Run
     task::block_on(async {
         for ch in channel.iter() {
-            run(&endpoint, *ch, MSG_SIZE).await;
+            run(&endpoint, *ch).await;
         }
     });
 }
@@ -204,7 +292,7 @@ fn transport_unicast_defragmentation_ws_only() {
     // Run
     task::block_on(async {
         for ch in channel.iter() {
-            run(&endpoint, *ch, MSG_SIZE).await;
+            run(&endpoint, *ch).await;
         }
     });
 }
@@ -244,7 +332,7 @@ fn transport_unicast_defragmentation_unixpipe_only() {
     // Run
     task::block_on(async {
         for ch in channel.iter() {
-            run(&endpoint, *ch, MSG_SIZE).await;
+            run(&endpoint, *ch).await;
         }
     });
 }
@@ -0,0 +1,165 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use async_std::{prelude::FutureExt, task};
+use std::convert::TryFrom;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use zenoh_core::{zasync_executor_init, zlock};
+use zenoh_link::EndPoint;
+use zenoh_protocol::core::{WhatAmI, ZenohId};
+use zenoh_transport::{
+    DummyTransportEventHandler, TransportManager, TransportUnicastEvent,
+    TransportUnicastEventListener,
+};
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_millis(10);
+
+macro_rules! ztimeout {
+    ($f:expr) => {
+        $f.timeout(TIMEOUT).await.unwrap()
+    };
+}
+
+// Records the events of the transports
+#[derive(Default)]
+struct SLRecorder {
+    opened: Mutex<Vec<TransportUnicastEvent>>,
+    closed: Mutex<Vec<TransportUnicastEvent>>,
+}
+
+impl SLRecorder {
+    fn opened(&self) -> Vec<TransportUnicastEvent> {
+        zlock!(self.opened).clone()
+    }
+
+    fn closed(&self) -> Vec<TransportUnicastEvent> {
+        zlock!(self.closed).clone()
+    }
+
+    async fn wait_closed(&self, count: usize) {
+        ztimeout!(async {
+            while zlock!(self.closed).len() < count {
+                task::sleep(SLEEP).await;
+            }
+        });
+    }
+}
+
+impl TransportUnicastEventListener for SLRecorder {
+    fn opened(&self, event: &TransportUnicastEvent) {
+        zlock!(self.opened).push(event.clone());
+    }
+
+    fn closed(&self, event: &TransportUnicastEvent) {
+        zlock!(self.closed).push(event.clone());
+    }
+}
+
+async fn transport_events(endpoint: &EndPoint) {
+    let router_id = ZenohId::try_from([1]).unwrap();
+    let router_listener = Arc::new(SLRecorder::default());
+    let router_manager = TransportManager::builder()
+        .whatami(WhatAmI::Router)
+        .zid(router_id)
+        .transport_listener(router_listener.clone())
+        .build(Arc::new(DummyTransportEventHandler))
+        .unwrap();
+
+    let client_id = ZenohId::try_from([2]).unwrap();
+    let client_listener = Arc::new(SLRecorder::default());
+    let client_manager = TransportManager::builder()
+        .whatami(WhatAmI::Client)
+        .zid(client_id)
+        .transport_listener(client_listener.clone())
+        .build(Arc::new(DummyTransportEventHandler))
+        .unwrap();
+
+    println!("Transport Events [1a1]");
+    let res = ztimeout!(router_manager.add_listener(endpoint.clone()));
+    println!("Transport Events [1a2]: {res:?}");
+    assert!(res.is_ok());
+
+    // Opening a transport notifies both nodes
+    println!("Transport Events [2a1]");
+    let res = ztimeout!(client_manager.open_transport_unicast(endpoint.clone()));
+    println!("Transport Events [2a2]: {res:?}");
+    let client_transport = res.unwrap();
+    ztimeout!(async {
+        while router_listener.opened().is_empty() {
+            task::sleep(SLEEP).await;
+        }
+    });
+    let opened = client_listener.opened();
+    println!("Transport Events [2a3]: client {opened:?}");
+    assert_eq!(opened.len(), 1);
+    assert_eq!(opened[0].zid, router_id);
+    assert_eq!(opened[0].whatami, WhatAmI::Router);
+    assert_eq!(opened[0].locator.as_ref(), Some(&endpoint.to_locator()));
+    let opened = router_listener.opened();
+    println!("Transport Events [2a4]: router {opened:?}");
+    assert_eq!(opened.len(), 1);
+    assert_eq!(opened[0].zid, client_id);
+    assert_eq!(opened[0].whatami, WhatAmI::Client);
+    assert!(opened[0].locator.is_some());
+
+    // Closing a transport notifies both nodes
+    println!("Transport Events [3a1]");
+    let res = ztimeout!(client_transport.close());
+    println!("Transport Events [3a2]: {res:?}");
+    assert!(res.is_ok());
+    router_listener.wait_closed(1).await;
+    client_listener.wait_closed(1).await;
+    assert_eq!(client_listener.closed()[0].zid, router_id);
+    assert_eq!(router_listener.closed()[0].zid, client_id);
+
+    // Closing the managers notifies the transports drained at shutdown
+    println!("Transport Events [4a1]");
+    let res = ztimeout!(client_manager.open_transport_unicast(endpoint.clone()));
+    println!("Transport Events [4a2]: {res:?}");
+    assert!(res.is_ok());
+    ztimeout!(async {
+        while router_listener.opened().len() < 2 {
+            task::sleep(SLEEP).await;
+        }
+    });
+    ztimeout!(client_manager.close());
+    client_listener.wait_closed(2).await;
+    router_listener.wait_closed(2).await;
+    ztimeout!(router_manager.close());
+    let closed = client_listener.closed();
+    println!("Transport Events [4a3]: client {closed:?}");
+    assert_eq!(closed.len(), 2);
+    assert_eq!(closed[1].zid, router_id);
+    assert_eq!(closed[1].whatami, WhatAmI::Router);
+    let closed = router_listener.closed();
+    println!("Transport Events [4a4]: router {closed:?}");
+    assert_eq!(closed.len(), 2);
+    assert_eq!(closed[1].zid, client_id);
+
+    // Wait a little bit
+    task::sleep(SLEEP).await;
+}
+
+#[cfg(feature = "transport_tcp")]
+#[test]
+fn transport_events_tcp() {
+    let _ = env_logger::try_init();
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+
+    let endpoint: EndPoint = format!("tcp/127.0.0.1:{}", 14170).parse().unwrap();
+    task::block_on(transport_events(&endpoint));
+}
@@ -0,0 +1,180 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+#[cfg(feature = "transport_tcp")]
+mod tests {
+    use async_std::{prelude::FutureExt, task};
+    use std::convert::TryFrom;
+    use std::sync::Arc;
+    use std::time::Duration;
+    use zenoh_core::zasync_executor_init;
+    use zenoh_link::{EndPoint, Locator};
+    use zenoh_protocol::core::{Bits, WhatAmI, ZenohId};
+    use zenoh_transport::{DummyTransportEventHandler, TransportManager};
+
+    const TIMEOUT: Duration = Duration::from_secs(60);
+    const SLEEP: Duration = Duration::from_millis(100);
+
+    macro_rules! ztimeout {
+        ($f:expr) => {
+            $f.timeout(TIMEOUT).await.unwrap()
+        };
+    }
+
+    fn make_manager(id: u8, whatami: WhatAmI, lease: Duration) -> TransportManager {
+        TransportManager::builder()
+            .whatami(whatami)
+            .zid(ZenohId::try_from([id]).unwrap())
+            .unicast(
+                TransportManager::config_unicast()
+                    .lease(lease)
+                    .qos(true)
+                    .lowlatency(false),
+            )
+            .build(Arc::new(DummyTransportEventHandler))
+            .unwrap()
+    }
+
+    #[test]
+    fn transport_unicast_info() {
+        let _ = env_logger::try_init();
+        task::block_on(async {
+            zasync_executor_init!();
+
+            let endpoint: EndPoint = format!("tcp/127.0.0.1:{}", 14205).parse().unwrap();
+            let router_lease = Duration::from_secs(10);
+            let client_lease = Duration::from_secs(6);
+            let router_manager = make_manager(1, WhatAmI::Router, router_lease);
+            let client_manager = make_manager(2, WhatAmI::Client, client_lease);
+            let res = ztimeout!(router_manager.add_listener(endpoint.clone()));
+            assert!(res.is_ok());
+
+            println!("Transport Info [1a1]");
+            let client_transport =
+                ztimeout!(client_manager.open_transport_unicast(endpoint.clone())).unwrap();
+            let router_transport =
+                ztimeout!(router_manager.get_transport_unicast(&client_manager.zid())).unwrap();
+
+            // Each node sees what was negotiated with the other one, and its lease
+            let info = client_transport.info().unwrap();
+            println!("Transport Info [1a2]: {info:?}");
+            assert_eq!(info.zid, router_manager.zid());
+            assert_eq!(info.whatami, WhatAmI::Router);
+            assert!(info.is_qos);
+            assert!(!info.is_lowlatency);
+            assert!(info.is_initiator);
+            assert_eq!(info.lease, router_lease);
+            assert_eq!(info.links.len(), 1);
+            let link = &info.links[0];
+            assert_eq!(link.dst, endpoint.to_locator());
+            assert!(link.mtu > 0);
+
+            let info = router_transport.info().unwrap();
+            println!("Transport Info [1a3]: {info:?}");
+            assert_eq!(info.zid, client_manager.zid());
+            assert_eq!(info.whatami, WhatAmI::Client);
+            assert!(!info.is_initiator);
+            assert_eq!(info.lease, client_lease);
+            assert_eq!(info.links.len(), 1);
+            assert_eq!(info.links[0].src, endpoint.to_locator());
+            assert_eq!(
+                info.sn_resolution,
+                client_transport.info().unwrap().sn_resolution
+            );
+
+            // The info is serializable for the admin space
+            let json = serde_json::to_value(&info).unwrap();
+            println!("Transport Info [1a4]: {json}");
+            assert_eq!(json["lease_ms"], 6_000);
+            assert_eq!(json["sn_resolution"], Bits::U32.to_str());
+            assert_eq!(json["links"].as_array().unwrap().len(), 1);
+
+            // The info of a closed transport is not available
+            println!("Transport Info [1b1]");
+            ztimeout!(client_transport.close()).unwrap();
+            task::sleep(SLEEP).await;
+            assert!(client_transport.info().is_err());
+
+            ztimeout!(client_manager.close());
+            ztimeout!(router_manager.close());
+            // Wait a little bit
+            task::sleep(SLEEP).await;
+        });
+    }
+
+    #[test]
+    fn transport_unicast_by_locator() {
+        let _ = env_logger::try_init();
+        task::block_on(async {
+            zasync_executor_init!();
+
+            let endpoint: EndPoint = format!("tcp/127.0.0.1:{}", 14213).parse().unwrap();
+            let lease = Duration::from_secs(10);
+            let router_manager = make_manager(1, WhatAmI::Router, lease);
+            let client01_manager = make_manager(2, WhatAmI::Client, lease);
+            let client02_manager = make_manager(3, WhatAmI::Client, lease);
+            ztimeout!(router_manager.add_listener(endpoint.clone())).unwrap();
+
+            println!("Transport By Locator [1a1]");
+            let client01_transport =
+                ztimeout!(client01_manager.open_transport_unicast(endpoint.clone())).unwrap();
+            let client02_transport =
+                ztimeout!(client02_manager.open_transport_unicast(endpoint.clone())).unwrap();
+
+            // The router resolves each client from the address it observes for it
+            for (transport, manager) in [
+                (&client01_transport, &client01_manager),
+                (&client02_transport, &client02_manager),
+            ] {
+                let observed = transport.get_links().unwrap()[0].src.clone();
+                println!("Transport By Locator [1a2]: {observed}");
+                let found =
+                    ztimeout!(router_manager.get_transport_unicast_by_locator(&observed)).unwrap();
+                assert_eq!(found.get_zid().unwrap(), manager.zid());
+            }
+            // The client resolves the router from the locator it connected to
+            let found = ztimeout!(
+                client01_manager.get_transport_unicast_by_locator(&endpoint.to_locator())
+            )
+            .unwrap();
+            assert_eq!(found.get_zid().unwrap(), router_manager.zid());
+
+            println!("Transport By Locator [1b1]");
+            let unknown: Locator = "tcp/127.0.0.1:1".parse().unwrap();
+            assert!(ztimeout!(router_manager.get_transport_unicast_by_locator(&unknown)).is_none());
+            let found = ztimeout!(router_manager.find_transports_unicast(|t| t
+                .get_zid()
+                .map_or(false, |zid| zid == client02_manager.zid())));
+            assert_eq!(found.len(), 1);
+            let found = ztimeout!(router_manager.find_transports_unicast(|t| t
+                .get_whatami()
+                .map_or(false, |w| w == WhatAmI::Client)));
+            assert_eq!(found.len(), 2);
+
+            // A closed transport isn't found anymore
+            println!("Transport By Locator [1c1]");
+            let observed = client01_transport.get_links().unwrap()[0].src.clone();
+            ztimeout!(client01_transport.close()).unwrap();
+            task::sleep(SLEEP).await;
+            assert!(
+                ztimeout!(router_manager.get_transport_unicast_by_locator(&observed)).is_none()
+            );
+
+            ztimeout!(client01_manager.close());
+            ztimeout!(client02_manager.close());
+            ztimeout!(router_manager.close());
+            // Wait a little bit
+            task::sleep(SLEEP).await;
+        });
+    }
+}
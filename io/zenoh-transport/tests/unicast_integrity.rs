@@ -0,0 +1,188 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+#[cfg(feature = "transport_tcp")]
+mod tests {
+    use async_std::{prelude::FutureExt, task};
+    use std::{any::Any, convert::TryFrom, sync::Arc, time::Duration};
+    use zenoh_core::zasync_executor_init;
+    use zenoh_link::{EndPoint, Link};
+    use zenoh_protocol::{
+        core::{Encoding, WhatAmI, ZenohId},
+        network::{
+            push::ext::{NodeIdType, QoSType},
+            NetworkBody, NetworkMessage, Push,
+        },
+        zenoh::{put::ext::IntegrityType, PushBody, Put},
+    };
+    use zenoh_result::ZResult;
+    use zenoh_transport::{
+        DummyTransportEventHandler, TransportEventHandler, TransportManager, TransportMulticast,
+        TransportMulticastEventHandler, TransportPeer, TransportPeerEventHandler, TransportUnicast,
+    };
+
+    const TIMEOUT: Duration = Duration::from_secs(60);
+    const SLEEP: Duration = Duration::from_millis(100);
+
+    macro_rules! ztimeout {
+        ($f:expr) => {
+            $f.timeout(TIMEOUT).await.unwrap()
+        };
+    }
+
+    // Notifies the integrity checksums of the puts it receives
+    struct SHRouter {
+        checksums: flume::Sender<Option<IntegrityType>>,
+    }
+
+    impl TransportEventHandler for SHRouter {
+        fn new_unicast(
+            &self,
+            _peer: TransportPeer,
+            _transport: TransportUnicast,
+        ) -> ZResult<Arc<dyn TransportPeerEventHandler>> {
+            Ok(Arc::new(SCRouter {
+                checksums: self.checksums.clone(),
+            }))
+        }
+
+        fn new_multicast(
+            &self,
+            _transport: TransportMulticast,
+        ) -> ZResult<Arc<dyn TransportMulticastEventHandler>> {
+            panic!();
+        }
+    }
+
+    struct SCRouter {
+        checksums: flume::Sender<Option<IntegrityType>>,
+    }
+
+    impl TransportPeerEventHandler for SCRouter {
+        fn handle_message(&self, message: NetworkMessage) -> ZResult<()> {
+            if let NetworkBody::Push(Push {
+                payload: PushBody::Put(put),
+                ..
+            }) = message.body
+            {
+                let _ = self.checksums.send(put.ext_integrity);
+            }
+            Ok(())
+        }
+
+        fn new_link(&self, _link: Link) {}
+        fn del_link(&self, _link: Link) {}
+        fn closing(&self) {}
+        fn closed(&self) {}
+
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+    }
+
+    fn make_manager(
+        id: u8,
+        whatami: WhatAmI,
+        integrity: bool,
+        handler: Arc<dyn TransportEventHandler>,
+    ) -> TransportManager {
+        TransportManager::builder()
+            .whatami(whatami)
+            .zid(ZenohId::try_from([id]).unwrap())
+            .unicast(TransportManager::config_unicast().integrity(integrity))
+            .build(handler)
+            .unwrap()
+    }
+
+    async fn integrity_negotiation(endpoint: &EndPoint, client: bool, router: bool) {
+        let (checksums_tx, checksums_rx) = flume::unbounded();
+        let router_manager = make_manager(
+            1,
+            WhatAmI::Router,
+            router,
+            Arc::new(SHRouter {
+                checksums: checksums_tx,
+            }),
+        );
+        let client_manager = make_manager(
+            2,
+            WhatAmI::Client,
+            client,
+            Arc::new(DummyTransportEventHandler),
+        );
+
+        println!("Transport Integrity {client} {router} [1a1]");
+        ztimeout!(router_manager.add_listener(endpoint.clone())).unwrap();
+        let transport = ztimeout!(client_manager.open_transport_unicast(endpoint.clone())).unwrap();
+        let router_transport =
+            ztimeout!(router_manager.get_transport_unicast(&client_manager.zid())).unwrap();
+
+        // The checksums are negotiated only if both nodes support them
+        let negotiated = client && router;
+        assert_eq!(transport.is_integrity().unwrap(), negotiated);
+        assert_eq!(router_transport.is_integrity().unwrap(), negotiated);
+        assert_eq!(transport.info().unwrap().is_integrity, negotiated);
+
+        println!("Transport Integrity {client} {router} [2a1]");
+        let checksum = IntegrityType {
+            algorithm: IntegrityType::CRC32C,
+            checksum: 0x1234_5678,
+        };
+        let message: NetworkMessage = Push {
+            wire_expr: "test".into(),
+            ext_qos: QoSType::default(),
+            ext_tstamp: None,
+            ext_nodeid: NodeIdType::default(),
+            payload: Put {
+                payload: vec![0u8; 8].into(),
+                timestamp: None,
+                encoding: Encoding::default(),
+                ext_sinfo: None,
+                #[cfg(feature = "shared-memory")]
+                ext_shm: None,
+                ext_integrity: Some(checksum),
+                ext_batch: None,
+                ext_unknown: vec![],
+            }
+            .into(),
+        }
+        .into();
+        transport.schedule(message).unwrap();
+
+        // The checksum reaches the other node untouched, or is stripped if not negotiated
+        let received = ztimeout!(checksums_rx.recv_async()).unwrap();
+        println!("Transport Integrity {client} {router} [2a2]: {received:?}");
+        assert_eq!(received, negotiated.then_some(checksum));
+
+        println!("Transport Integrity {client} {router} [3a1]");
+        ztimeout!(transport.close()).unwrap();
+        ztimeout!(router_manager.del_listener(endpoint)).unwrap();
+        ztimeout!(client_manager.close());
+        ztimeout!(router_manager.close());
+        // Wait a little bit
+        task::sleep(SLEEP).await;
+    }
+
+    #[test]
+    fn transport_unicast_integrity_negotiation() {
+        let _ = env_logger::try_init();
+        task::block_on(async {
+            zasync_executor_init!();
+
+            let endpoint: EndPoint = format!("tcp/127.0.0.1:{}", 14252).parse().unwrap();
+            integrity_negotiation(&endpoint, true, true).await;
+            integrity_negotiation(&endpoint, true, false).await;
+            integrity_negotiation(&endpoint, false, true).await;
+        });
+    }
+}
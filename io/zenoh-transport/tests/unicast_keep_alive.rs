@@ -0,0 +1,160 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use std::sync::Arc;
+use std::time::Duration;
+use zenoh_transport::{DummyTransportEventHandler, TransportManager};
+
+const LEASE: Duration = Duration::from_secs(60);
+
+#[test]
+fn keep_alive_validation() {
+    let build = |unicast| {
+        TransportManager::builder()
+            .unicast(unicast)
+            .build(Arc::new(DummyTransportEventHandler))
+    };
+
+    // A period independent of the lease
+    let unicast = TransportManager::config_unicast()
+        .lease(LEASE)
+        .keep_alive_every(Duration::from_millis(100));
+    let manager = build(unicast).unwrap();
+    assert_eq!(
+        manager.config.unicast.keep_alive,
+        Duration::from_millis(100)
+    );
+
+    // The number of keep-alives per lease, when no period is set
+    let unicast = TransportManager::config_unicast()
+        .lease(LEASE)
+        .keep_alive(4);
+    let manager = build(unicast).unwrap();
+    assert_eq!(manager.config.unicast.keep_alive, LEASE / 4);
+
+    // The period overrides the number of keep-alives per lease
+    let unicast = TransportManager::config_unicast()
+        .lease(LEASE)
+        .keep_alive(4)
+        .keep_alive_every(LEASE / 2);
+    let manager = build(unicast).unwrap();
+    assert_eq!(manager.config.unicast.keep_alive, LEASE / 2);
+
+    // A keep-alive longer than half the lease, or zero, is rejected
+    let unicast = TransportManager::config_unicast()
+        .lease(LEASE)
+        .keep_alive_every(LEASE / 2 + Duration::from_millis(1));
+    assert!(build(unicast).is_err());
+    let unicast = TransportManager::config_unicast()
+        .lease(LEASE)
+        .keep_alive(1);
+    assert!(build(unicast).is_err());
+    let unicast = TransportManager::config_unicast()
+        .lease(LEASE)
+        .keep_alive(0);
+    assert!(build(unicast).is_err());
+    let unicast = TransportManager::config_unicast()
+        .lease(LEASE)
+        .keep_alive_every(Duration::ZERO);
+    assert!(build(unicast).is_err());
+}
+
+#[cfg(all(feature = "transport_tcp", feature = "stats"))]
+mod tests {
+    use super::LEASE;
+    use async_std::{prelude::FutureExt, task};
+    use std::convert::TryFrom;
+    use std::sync::Arc;
+    use std::time::Duration;
+    use zenoh_core::zasync_executor_init;
+    use zenoh_link::EndPoint;
+    use zenoh_protocol::core::{WhatAmI, ZenohId};
+    use zenoh_transport::{DummyTransportEventHandler, TransportManager};
+    use zenoh_util::clock::TestClock;
+
+    const TIMEOUT: Duration = Duration::from_secs(60);
+    const SLEEP: Duration = Duration::from_millis(10);
+    const KEEP_ALIVE: Duration = Duration::from_millis(100);
+
+    macro_rules! ztimeout {
+        ($f:expr) => {
+            $f.timeout(TIMEOUT).await.unwrap()
+        };
+    }
+
+    fn make_manager(id: u8, whatami: WhatAmI, clock: &TestClock) -> TransportManager {
+        TransportManager::builder()
+            .whatami(whatami)
+            .zid(ZenohId::try_from([id]).unwrap())
+            .unicast(
+                TransportManager::config_unicast()
+                    .lease(LEASE)
+                    .keep_alive_every(KEEP_ALIVE),
+            )
+            .clock(Arc::new(clock.clone()))
+            .build(Arc::new(DummyTransportEventHandler))
+            .unwrap()
+    }
+
+    #[test]
+    fn keep_alive_period_idle_transport() {
+        let _ = env_logger::try_init();
+        task::block_on(async {
+            zasync_executor_init!();
+
+            // Only the client's clock moves: only the client sends keep-alives
+            let router_clock = TestClock::new();
+            let client_clock = TestClock::new();
+            let endpoint: EndPoint = format!("tcp/127.0.0.1:{}", 14207).parse().unwrap();
+            let router_manager = make_manager(1, WhatAmI::Router, &router_clock);
+            let client_manager = make_manager(2, WhatAmI::Client, &client_clock);
+            let res = ztimeout!(router_manager.add_listener(endpoint.clone()));
+            assert!(res.is_ok());
+
+            println!("Transport Keep Alive [1a1]");
+            let client_transport =
+                ztimeout!(client_manager.open_transport_unicast(endpoint.clone())).unwrap();
+            task::sleep(SLEEP).await;
+            let stats = client_transport.get_stats().unwrap();
+            let initial = stats.get_tx_t_msgs();
+
+            // No keep-alive is sent before the period elapsed
+            println!("Transport Keep Alive [2a1]");
+            client_clock.advance(KEEP_ALIVE - Duration::from_millis(1));
+            task::sleep(SLEEP * 10).await;
+            assert_eq!(stats.get_tx_t_msgs(), initial);
+
+            // A keep-alive is sent at each period on the idle transport
+            for i in 1..=5 {
+                println!("Transport Keep Alive [2a2]: period {i}");
+                client_clock.advance(if i == 1 {
+                    Duration::from_millis(1)
+                } else {
+                    KEEP_ALIVE
+                });
+                ztimeout!(async {
+                    while stats.get_tx_t_msgs() < initial + i {
+                        task::sleep(SLEEP).await;
+                    }
+                });
+                task::sleep(SLEEP * 10).await;
+                assert_eq!(stats.get_tx_t_msgs(), initial + i);
+            }
+
+            ztimeout!(client_manager.close());
+            ztimeout!(router_manager.close());
+            // Wait a little bit
+            task::sleep(SLEEP).await;
+        });
+    }
+}
@@ -0,0 +1,281 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use async_std::{prelude::FutureExt, task};
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::sync::Arc;
+use std::time::Duration;
+use zenoh_core::zasync_executor_init;
+use zenoh_link::EndPoint;
+use zenoh_protocol::core::{WhatAmI, WhatAmIMatcher, ZenohId};
+use zenoh_protocol::transport::close::CloseReason;
+use zenoh_transport::{DummyTransportEventHandler, TransportCloseReason, TransportManager};
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_millis(10);
+
+macro_rules! ztimeout {
+    ($f:expr) => {
+        $f.timeout(TIMEOUT).await.unwrap()
+    };
+}
+
+fn client_manager(id: u8) -> TransportManager {
+    node_manager(id, WhatAmI::Client)
+}
+
+fn node_manager(id: u8, whatami: WhatAmI) -> TransportManager {
+    TransportManager::builder()
+        .whatami(whatami)
+        .zid(ZenohId::try_from([id]).unwrap())
+        .build(Arc::new(DummyTransportEventHandler))
+        .unwrap()
+}
+
+// The sessions of a protocol with a limit are bounded by it, the other ones by the global limit
+async fn sessions_per_protocol(limited: &EndPoint, unlimited: &EndPoint) {
+    let unicast = TransportManager::config_unicast()
+        .max_sessions(10)
+        .max_sessions_per_protocol(HashMap::from([(
+            limited.protocol().as_str().to_string(),
+            1,
+        )]));
+    let router_manager = TransportManager::builder()
+        .whatami(WhatAmI::Router)
+        .zid(ZenohId::try_from([1]).unwrap())
+        .unicast(unicast)
+        .build(Arc::new(DummyTransportEventHandler))
+        .unwrap();
+    let clients = (2..5).map(client_manager).collect::<Vec<_>>();
+
+    println!("Transport Limits [1a1]");
+    for endpoint in [limited, unlimited] {
+        let res = ztimeout!(router_manager.add_listener(endpoint.clone()));
+        println!("Transport Limits [1a2]: {res:?}");
+        assert!(res.is_ok());
+    }
+
+    // The first session over the limited protocol is accepted
+    println!("Transport Limits [2a1]");
+    let res = ztimeout!(clients[0].open_transport_unicast(limited.clone()));
+    println!("Transport Limits [2a2]: {res:?}");
+    assert!(res.is_ok());
+
+    // The second one is rejected
+    println!("Transport Limits [2b1]");
+    let res = ztimeout!(clients[1].open_transport_unicast(limited.clone()));
+    println!("Transport Limits [2b2]: {res:?}");
+    assert!(res.is_err());
+
+    // The sessions over the other protocol are bounded by the global limit only
+    println!("Transport Limits [2c1]");
+    for client in clients[1..].iter() {
+        let res = ztimeout!(client.open_transport_unicast(unlimited.clone()));
+        println!("Transport Limits [2c2]: {res:?}");
+        assert!(res.is_ok());
+    }
+    ztimeout!(async {
+        while router_manager.get_transports_unicast().await.len() != clients.len() {
+            task::sleep(SLEEP).await;
+        }
+    });
+
+    for client in clients.iter() {
+        ztimeout!(client.close());
+    }
+    ztimeout!(router_manager.close());
+
+    // Wait a little bit
+    task::sleep(SLEEP).await;
+}
+
+// The sessions of each kind of node are bounded by its own limit
+async fn sessions_by_whatami(endpoint: &EndPoint) {
+    const CLIENTS: usize = 3;
+    let unicast = TransportManager::config_unicast()
+        .max_sessions(10)
+        .max_sessions_by_whatami(HashMap::from([
+            (WhatAmIMatcher::empty().client(), CLIENTS),
+            (WhatAmIMatcher::empty().peer(), 2),
+        ]));
+    let router_manager = TransportManager::builder()
+        .whatami(WhatAmI::Router)
+        .zid(ZenohId::try_from([1]).unwrap())
+        .unicast(unicast)
+        .build(Arc::new(DummyTransportEventHandler))
+        .unwrap();
+    let clients = (2..3 + CLIENTS as u8)
+        .map(client_manager)
+        .collect::<Vec<_>>();
+    let peers = (10..13)
+        .map(|id| node_manager(id, WhatAmI::Peer))
+        .collect::<Vec<_>>();
+
+    println!("Transport Limits [4a1]");
+    let res = ztimeout!(router_manager.add_listener(endpoint.clone()));
+    println!("Transport Limits [4a2]: {res:?}");
+    assert!(res.is_ok());
+
+    // The clients and a peer open their transports concurrently
+    println!("Transport Limits [4b1]");
+    let tasks = clients[..CLIENTS]
+        .iter()
+        .chain(peers[..1].iter())
+        .map(|manager| {
+            let manager = manager.clone();
+            let endpoint = endpoint.clone();
+            task::spawn(async move { manager.open_transport_unicast(endpoint).await })
+        })
+        .collect::<Vec<_>>();
+    for task in tasks {
+        let res = ztimeout!(task);
+        println!("Transport Limits [4b2]: {res:?}");
+        assert!(res.is_ok());
+    }
+
+    // One more client is rejected, naming the kind of node at its limit
+    println!("Transport Limits [4c1]");
+    let res = ztimeout!(clients[CLIENTS].open_transport_unicast(endpoint.clone()));
+    println!("Transport Limits [4c2]: {res:?}");
+    let e = res.unwrap_err();
+    let reason = e.downcast_ref::<TransportCloseReason>().unwrap();
+    assert_eq!(reason.reason, CloseReason::MaxSessions);
+    assert_eq!(
+        reason.detail.as_deref(),
+        Some(format!("max client sessions reached ({CLIENTS})").as_str())
+    );
+
+    // A second peer is still accepted, a third one is rejected
+    println!("Transport Limits [4d1]");
+    let res = ztimeout!(peers[1].open_transport_unicast(endpoint.clone()));
+    println!("Transport Limits [4d2]: {res:?}");
+    assert!(res.is_ok());
+    let res = ztimeout!(peers[2].open_transport_unicast(endpoint.clone()));
+    println!("Transport Limits [4d3]: {res:?}");
+    assert!(res.is_err());
+    ztimeout!(async {
+        while router_manager.get_transports_unicast().await.len() != CLIENTS + 2 {
+            task::sleep(SLEEP).await;
+        }
+    });
+
+    for manager in clients.iter().chain(peers.iter()) {
+        ztimeout!(manager.close());
+    }
+    ztimeout!(router_manager.close());
+
+    // Wait a little bit
+    task::sleep(SLEEP).await;
+}
+
+// The links of a protocol with a limit are bounded by it, the other ones by the global limit
+#[cfg(feature = "transport_multilink")]
+async fn links_per_protocol(limited: &EndPoint, unlimited: &EndPoint) {
+    let unicast = TransportManager::config_unicast()
+        .max_links(2)
+        .max_links_per_protocol(HashMap::from([(
+            limited.protocol().as_str().to_string(),
+            1,
+        )]));
+    let router_manager = TransportManager::builder()
+        .whatami(WhatAmI::Router)
+        .zid(ZenohId::try_from([1]).unwrap())
+        .unicast(unicast)
+        .build(Arc::new(DummyTransportEventHandler))
+        .unwrap();
+    let client_manager = TransportManager::builder()
+        .whatami(WhatAmI::Client)
+        .zid(ZenohId::try_from([2]).unwrap())
+        .unicast(TransportManager::config_unicast().max_links(4))
+        .build(Arc::new(DummyTransportEventHandler))
+        .unwrap();
+
+    println!("Transport Limits [3a1]");
+    for endpoint in [limited, unlimited] {
+        let res = ztimeout!(router_manager.add_listener(endpoint.clone()));
+        println!("Transport Limits [3a2]: {res:?}");
+        assert!(res.is_ok());
+    }
+
+    println!("Transport Limits [3b1]");
+    let res = ztimeout!(client_manager.open_transport_unicast(limited.clone()));
+    println!("Transport Limits [3b2]: {res:?}");
+    let transport = res.unwrap();
+
+    // A second link over the limited protocol is rejected
+    println!("Transport Limits [3c1]");
+    let res = ztimeout!(client_manager.open_transport_unicast(limited.clone()));
+    println!("Transport Limits [3c2]: {res:?}");
+    assert!(res.is_err());
+
+    // Two links over the other protocol are accepted, the third one is rejected
+    println!("Transport Limits [3d1]");
+    for _ in 0..2 {
+        let res = ztimeout!(client_manager.open_transport_unicast(unlimited.clone()));
+        println!("Transport Limits [3d2]: {res:?}");
+        assert!(res.is_ok());
+    }
+    let res = ztimeout!(client_manager.open_transport_unicast(unlimited.clone()));
+    println!("Transport Limits [3d3]: {res:?}");
+    assert!(res.is_err());
+    assert_eq!(transport.get_links().unwrap().len(), 3);
+
+    ztimeout!(client_manager.close());
+    ztimeout!(router_manager.close());
+
+    // Wait a little bit
+    task::sleep(SLEEP).await;
+}
+
+#[cfg(all(feature = "transport_tcp", feature = "transport_udp"))]
+#[test]
+fn limits_sessions_per_protocol() {
+    let _ = env_logger::try_init();
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+
+    let limited: EndPoint = format!("tcp/127.0.0.1:{}", 14190).parse().unwrap();
+    let unlimited: EndPoint = format!("udp/127.0.0.1:{}", 14191).parse().unwrap();
+    task::block_on(sessions_per_protocol(&limited, &unlimited));
+}
+
+#[cfg(feature = "transport_tcp")]
+#[test]
+fn limits_sessions_by_whatami() {
+    let _ = env_logger::try_init();
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+
+    let endpoint: EndPoint = format!("tcp/127.0.0.1:{}", 14208).parse().unwrap();
+    task::block_on(sessions_by_whatami(&endpoint));
+}
+
+#[cfg(all(
+    feature = "transport_multilink",
+    feature = "transport_tcp",
+    feature = "transport_udp"
+))]
+#[test]
+fn limits_links_per_protocol() {
+    let _ = env_logger::try_init();
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+
+    let limited: EndPoint = format!("tcp/127.0.0.1:{}", 14192).parse().unwrap();
+    let unlimited: EndPoint = format!("udp/127.0.0.1:{}", 14193).parse().unwrap();
+    task::block_on(links_per_protocol(&limited, &unlimited));
+}
@@ -0,0 +1,307 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use async_std::{prelude::FutureExt, task};
+use std::{
+    any::Any,
+    convert::TryFrom,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use zenoh_core::zasync_executor_init;
+use zenoh_link::Link;
+use zenoh_protocol::{
+    core::{Encoding, EndPoint, WhatAmI, ZenohId},
+    network::{
+        push::ext::{NodeIdType, QoSType},
+        NetworkMessage, Push,
+    },
+    zenoh::Put,
+};
+use zenoh_result::ZResult;
+use zenoh_transport::{
+    test_helpers::make_transport_manager_builder, DummyTransportEventHandler,
+    TransportEventHandler, TransportManager, TransportMulticast, TransportMulticastEventHandler,
+    TransportPeer, TransportPeerEventHandler, TransportUnicast,
+};
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_millis(10);
+const PINGS: usize = 1_000;
+const WARMUP: usize = 100;
+const MSG_SIZE: usize = 8;
+
+macro_rules! ztimeout {
+    ($f:expr) => {
+        $f.timeout(TIMEOUT).await.unwrap()
+    };
+}
+
+// Sends back the messages it receives
+struct SHPong;
+
+impl TransportEventHandler for SHPong {
+    fn new_unicast(
+        &self,
+        _peer: TransportPeer,
+        transport: TransportUnicast,
+    ) -> ZResult<Arc<dyn TransportPeerEventHandler>> {
+        Ok(Arc::new(SCPong { transport }))
+    }
+
+    fn new_multicast(
+        &self,
+        _transport: TransportMulticast,
+    ) -> ZResult<Arc<dyn TransportMulticastEventHandler>> {
+        panic!();
+    }
+}
+
+struct SCPong {
+    transport: TransportUnicast,
+}
+
+impl TransportPeerEventHandler for SCPong {
+    fn handle_message(&self, message: NetworkMessage) -> ZResult<()> {
+        self.transport.schedule(message)
+    }
+
+    fn new_link(&self, _link: Link) {}
+    fn del_link(&self, _link: Link) {}
+    fn closing(&self) {}
+    fn closed(&self) {}
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+// Notifies the pongs it receives
+struct SHPing {
+    pongs: flume::Sender<()>,
+}
+
+impl TransportEventHandler for SHPing {
+    fn new_unicast(
+        &self,
+        _peer: TransportPeer,
+        _transport: TransportUnicast,
+    ) -> ZResult<Arc<dyn TransportPeerEventHandler>> {
+        Ok(Arc::new(SCPing {
+            pongs: self.pongs.clone(),
+        }))
+    }
+
+    fn new_multicast(
+        &self,
+        _transport: TransportMulticast,
+    ) -> ZResult<Arc<dyn TransportMulticastEventHandler>> {
+        panic!();
+    }
+}
+
+struct SCPing {
+    pongs: flume::Sender<()>,
+}
+
+impl TransportPeerEventHandler for SCPing {
+    fn handle_message(&self, _message: NetworkMessage) -> ZResult<()> {
+        let _ = self.pongs.send(());
+        Ok(())
+    }
+
+    fn new_link(&self, _link: Link) {}
+    fn del_link(&self, _link: Link) {}
+    fn closing(&self) {}
+    fn closed(&self) {}
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+fn make_manager(
+    zid: ZenohId,
+    whatami: WhatAmI,
+    lowlatency: bool,
+    handler: Arc<dyn TransportEventHandler>,
+) -> TransportManager {
+    let unicast = make_transport_manager_builder(
+        #[cfg(feature = "transport_multilink")]
+        1,
+        #[cfg(feature = "shared-memory")]
+        false,
+        lowlatency,
+    );
+    TransportManager::builder()
+        .whatami(whatami)
+        .zid(zid)
+        .unicast(unicast)
+        .build(handler)
+        .unwrap()
+}
+
+async fn get_transport(manager: &TransportManager) -> TransportUnicast {
+    loop {
+        if let Some(transport) = manager.get_transports_unicast().await.pop() {
+            return transport;
+        }
+        task::sleep(SLEEP).await;
+    }
+}
+
+// Returns the mean round-trip time of the pings
+async fn lowlatency_rtt(endpoint: &EndPoint, lowlatency: bool) -> Duration {
+    let router_id = ZenohId::try_from([1]).unwrap();
+    let router_manager = make_manager(router_id, WhatAmI::Router, lowlatency, Arc::new(SHPong));
+    let (pongs_tx, pongs_rx) = flume::unbounded();
+    let client_id = ZenohId::try_from([2]).unwrap();
+    let client_manager = make_manager(
+        client_id,
+        WhatAmI::Client,
+        lowlatency,
+        Arc::new(SHPing { pongs: pongs_tx }),
+    );
+
+    println!("Transport LowLatency {lowlatency} [1a1]");
+    let res = ztimeout!(router_manager.add_listener(endpoint.clone()));
+    println!("Transport LowLatency {lowlatency} [1a2]: {res:?}");
+    assert!(res.is_ok());
+    let transport = ztimeout!(client_manager.open_transport_unicast(endpoint.clone())).unwrap();
+    assert_eq!(transport.is_lowlatency().unwrap(), lowlatency);
+    let router_transport = ztimeout!(get_transport(&router_manager));
+    assert_eq!(router_transport.is_lowlatency().unwrap(), lowlatency);
+
+    let message: NetworkMessage = Push {
+        wire_expr: "test".into(),
+        ext_qos: QoSType::default(),
+        ext_tstamp: None,
+        ext_nodeid: NodeIdType::default(),
+        payload: Put {
+            payload: vec![0u8; MSG_SIZE].into(),
+            timestamp: None,
+            encoding: Encoding::default(),
+            ext_sinfo: None,
+            #[cfg(feature = "shared-memory")]
+            ext_shm: None,
+            ext_integrity: None,
+            ext_batch: None,
+            ext_unknown: vec![],
+        }
+        .into(),
+    }
+    .into();
+
+    // One ping at a time: each pong is received before sending the next ping
+    println!("Transport LowLatency {lowlatency} [2a1]");
+    let mut total = Duration::ZERO;
+    for i in 0..WARMUP + PINGS {
+        let start = Instant::now();
+        transport.schedule(message.clone()).unwrap();
+        ztimeout!(pongs_rx.recv_async()).unwrap();
+        if i >= WARMUP {
+            total += start.elapsed();
+        }
+    }
+    let rtt = total / PINGS as u32;
+    println!("Transport LowLatency {lowlatency} [2a2]: mean RTT {rtt:?}");
+
+    println!("Transport LowLatency {lowlatency} [3a1]");
+    ztimeout!(transport.close()).unwrap();
+    ztimeout!(router_manager.del_listener(endpoint)).unwrap();
+    ztimeout!(router_manager.close());
+    ztimeout!(client_manager.close());
+    // Wait a little bit
+    task::sleep(SLEEP).await;
+
+    rtt
+}
+
+async fn lowlatency_mismatch(endpoint: &EndPoint, first: bool) {
+    // Both clients have the same id: they are seen as the same peer by the router
+    let router_id = ZenohId::try_from([1]).unwrap();
+    let router_manager = make_manager(
+        router_id,
+        WhatAmI::Router,
+        true,
+        Arc::new(DummyTransportEventHandler),
+    );
+    let client_id = ZenohId::try_from([2]).unwrap();
+    let client01_manager = make_manager(
+        client_id,
+        WhatAmI::Client,
+        first,
+        Arc::new(DummyTransportEventHandler),
+    );
+    let client02_manager = make_manager(
+        client_id,
+        WhatAmI::Client,
+        !first,
+        Arc::new(DummyTransportEventHandler),
+    );
+
+    println!("Transport LowLatency mismatch {first} [1a1]");
+    ztimeout!(router_manager.add_listener(endpoint.clone())).unwrap();
+    let transport = ztimeout!(client01_manager.open_transport_unicast(endpoint.clone())).unwrap();
+    assert_eq!(transport.is_lowlatency().unwrap(), first);
+
+    // The router refuses a link in the other mode with the same peer
+    println!("Transport LowLatency mismatch {first} [2a1]");
+    let res = ztimeout!(client02_manager.open_transport_unicast(endpoint.clone()));
+    println!("Transport LowLatency mismatch {first} [2a2]: {res:?}");
+    assert!(res.is_err());
+    let transports = ztimeout!(router_manager.get_transports_unicast());
+    assert_eq!(transports.len(), 1);
+    assert_eq!(transports[0].is_lowlatency().unwrap(), first);
+    assert_eq!(transports[0].get_links().unwrap().len(), 1);
+
+    println!("Transport LowLatency mismatch {first} [3a1]");
+    ztimeout!(router_manager.del_listener(endpoint)).unwrap();
+    ztimeout!(router_manager.close());
+    ztimeout!(client01_manager.close());
+    ztimeout!(client02_manager.close());
+    // Wait a little bit
+    task::sleep(SLEEP).await;
+}
+
+#[cfg(feature = "transport_tcp")]
+#[test]
+fn lowlatency_rtt_tcp_only() {
+    let _ = env_logger::try_init();
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+
+    let endpoint: EndPoint = format!("tcp/127.0.0.1:{}", 14209).parse().unwrap();
+    let batched = task::block_on(lowlatency_rtt(&endpoint, false));
+    let endpoint: EndPoint = format!("tcp/127.0.0.1:{}", 14210).parse().unwrap();
+    let lowlatency = task::block_on(lowlatency_rtt(&endpoint, true));
+    // The RTTs depend on the host: they are compared without being asserted
+    println!(
+        "Transport LowLatency: mean RTT of {PINGS} pings of {MSG_SIZE} bytes: batched {batched:?}, lowlatency {lowlatency:?}"
+    );
+}
+
+#[cfg(feature = "transport_tcp")]
+#[test]
+fn lowlatency_mismatch_tcp_only() {
+    let _ = env_logger::try_init();
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+
+    let endpoint: EndPoint = format!("tcp/127.0.0.1:{}", 14211).parse().unwrap();
+    task::block_on(lowlatency_mismatch(&endpoint, true));
+    let endpoint: EndPoint = format!("tcp/127.0.0.1:{}", 14212).parse().unwrap();
+    task::block_on(lowlatency_mismatch(&endpoint, false));
+}
@@ -0,0 +1,178 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use async_std::{prelude::FutureExt, task};
+use std::convert::TryFrom;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use zenoh_core::zasync_executor_init;
+use zenoh_link::EndPoint;
+use zenoh_protocol::core::{WhatAmI, ZenohId};
+use zenoh_result::ZResult;
+use zenoh_transport::{DummyTransportEventHandler, TransportManager, TransportOpenRetry};
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_millis(100);
+const PERIOD: Duration = Duration::from_millis(200);
+
+macro_rules! ztimeout {
+    ($f:expr) => {
+        $f.timeout(TIMEOUT).await.unwrap()
+    };
+}
+
+fn client_manager(max_attempts: Option<usize>, period_init: Duration) -> TransportManager {
+    let open_retry = TransportOpenRetry {
+        period_init,
+        period_max: Duration::from_secs(1),
+        period_increase_factor: 2.,
+        max_attempts,
+    };
+    build_client_manager(open_retry).unwrap()
+}
+
+fn build_client_manager(open_retry: TransportOpenRetry) -> ZResult<TransportManager> {
+    TransportManager::builder()
+        .whatami(WhatAmI::Client)
+        .zid(ZenohId::try_from([2]).unwrap())
+        .unicast(TransportManager::config_unicast().open_retry(Some(open_retry)))
+        .build(Arc::new(DummyTransportEventHandler))
+}
+
+// The transport is opened once the listener is up, after two failed attempts
+async fn open_retry_late_listener(endpoint: &EndPoint) {
+    let router_manager = TransportManager::builder()
+        .whatami(WhatAmI::Router)
+        .zid(ZenohId::try_from([1]).unwrap())
+        .build(Arc::new(DummyTransportEventHandler))
+        .unwrap();
+    let client_manager = client_manager(Some(5), PERIOD);
+
+    // The attempts are made after 0, 200 and 600 ms
+    println!("Transport Open Retry [1a1]");
+    let start = Instant::now();
+    let c_client_manager = client_manager.clone();
+    let c_endpoint = endpoint.clone();
+    let open =
+        task::spawn(async move { c_client_manager.open_transport_unicast(c_endpoint).await });
+
+    // The listener comes up between the second and the third attempts
+    task::sleep(2 * PERIOD).await;
+    println!("Transport Open Retry [1a2]");
+    let res = ztimeout!(router_manager.add_listener(endpoint.clone()));
+    println!("Transport Open Retry [1a3]: {res:?}");
+    assert!(res.is_ok());
+
+    let res = ztimeout!(open);
+    println!("Transport Open Retry [1a4]: {res:?}");
+    assert!(res.is_ok());
+    assert!(start.elapsed() >= 3 * PERIOD);
+
+    ztimeout!(client_manager.close());
+    ztimeout!(router_manager.close());
+
+    // Wait a little bit
+    task::sleep(SLEEP).await;
+}
+
+// The open fails with the last underlying error once the attempts are exhausted
+async fn open_retry_max_attempts(endpoint: &EndPoint) {
+    let client_manager = client_manager(Some(2), PERIOD);
+
+    println!("Transport Open Retry [2a1]");
+    let res = ztimeout!(client_manager.open_transport_unicast(endpoint.clone()));
+    println!("Transport Open Retry [2a2]: {res:?}");
+    let e = res.unwrap_err();
+    assert!(e.to_string().contains("after 2 attempts"));
+    assert!(e.source().is_some());
+
+    ztimeout!(client_manager.close());
+
+    // Wait a little bit
+    task::sleep(SLEEP).await;
+}
+
+// The retries are interrupted by the close of the manager
+async fn open_retry_cancelled(endpoint: &EndPoint) {
+    let client_manager = client_manager(None, Duration::from_secs(10));
+
+    println!("Transport Open Retry [3a1]");
+    let c_client_manager = client_manager.clone();
+    let c_endpoint = endpoint.clone();
+    let open =
+        task::spawn(async move { c_client_manager.open_transport_unicast(c_endpoint).await });
+
+    task::sleep(SLEEP).await;
+    println!("Transport Open Retry [3a2]");
+    let start = Instant::now();
+    ztimeout!(client_manager.close());
+    let res = ztimeout!(open);
+    println!("Transport Open Retry [3a3]: {res:?}");
+    assert!(res.is_err());
+    assert!(start.elapsed() < Duration::from_secs(5));
+
+    // Wait a little bit
+    task::sleep(SLEEP).await;
+}
+
+// The period saturates at its maximum instead of overflowing
+async fn open_retry_period_overflow(endpoint: &EndPoint) {
+    let open_retry = TransportOpenRetry {
+        period_init: Duration::from_millis(10),
+        period_max: Duration::from_millis(50),
+        period_increase_factor: f64::MAX,
+        max_attempts: Some(3),
+    };
+    let client_manager = build_client_manager(open_retry).unwrap();
+
+    println!("Transport Open Retry [4a1]");
+    let res = ztimeout!(client_manager.open_transport_unicast(endpoint.clone()));
+    println!("Transport Open Retry [4a2]: {res:?}");
+    assert!(res.unwrap_err().to_string().contains("after 3 attempts"));
+
+    ztimeout!(client_manager.close());
+
+    // Wait a little bit
+    task::sleep(SLEEP).await;
+}
+
+#[test]
+fn open_retry_invalid_factor() {
+    for factor in [0.5, -1., f64::NAN, f64::INFINITY] {
+        let open_retry = TransportOpenRetry {
+            period_init: PERIOD,
+            period_max: Duration::from_secs(1),
+            period_increase_factor: factor,
+            max_attempts: None,
+        };
+        assert!(build_client_manager(open_retry).is_err());
+    }
+}
+
+#[cfg(feature = "transport_tcp")]
+#[test]
+fn open_retry_tcp() {
+    let _ = env_logger::try_init();
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+
+    let endpoint: EndPoint = format!("tcp/127.0.0.1:{}", 14194).parse().unwrap();
+    task::block_on(open_retry_late_listener(&endpoint));
+    let endpoint: EndPoint = format!("tcp/127.0.0.1:{}", 14195).parse().unwrap();
+    task::block_on(open_retry_max_attempts(&endpoint));
+    let endpoint: EndPoint = format!("tcp/127.0.0.1:{}", 14196).parse().unwrap();
+    task::block_on(open_retry_cancelled(&endpoint));
+    let endpoint: EndPoint = format!("tcp/127.0.0.1:{}", 14253).parse().unwrap();
+    task::block_on(open_retry_period_overflow(&endpoint));
+}
@@ -161,6 +161,9 @@ async fn openclose_transport(endpoint: &EndPoint, lowlatency_transport: bool) {
     println!("Transport Open Close [1d2]: {transports:?}");
     assert_eq!(transports.len(), 1);
     assert_eq!(c_ses1.get_zid().unwrap(), router_id);
+    // The transport has been opened by the client
+    assert!(c_ses1.is_initiator().unwrap());
+    assert!(c_ses1.get_peer().unwrap().is_initiator);
     println!("Transport Open Close [1e1]");
     let links = c_ses1.get_links().unwrap();
     println!("Transport Open Close [1e2]: {links:?}");
@@ -179,6 +182,9 @@ async fn openclose_transport(endpoint: &EndPoint, lowlatency_transport: bool) {
                 Some(s) => {
                     let links = s.get_links().unwrap();
                     assert_eq!(links.len(), links_num);
+                    // The transport has been accepted by the router
+                    assert!(!s.is_initiator().unwrap());
+                    assert!(!s.get_peer().unwrap().is_initiator);
                     break;
                 }
                 None => task::sleep(SLEEP).await,
@@ -0,0 +1,147 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+#[cfg(feature = "transport_tcp")]
+mod tests {
+    use async_std::{prelude::FutureExt, task};
+    use std::convert::TryFrom;
+    use std::sync::Arc;
+    use std::time::Duration;
+    use zenoh_core::zasync_executor_init;
+    use zenoh_link::EndPoint;
+    use zenoh_protocol::{
+        core::{WhatAmI, ZenohId},
+        transport::close::CloseReason,
+    };
+    use zenoh_transport::{DummyTransportEventHandler, TransportCloseReason, TransportManager};
+
+    const TIMEOUT: Duration = Duration::from_secs(60);
+    const SLEEP: Duration = Duration::from_millis(100);
+
+    macro_rules! ztimeout {
+        ($f:expr) => {
+            $f.timeout(TIMEOUT).await.unwrap()
+        };
+    }
+
+    fn zid(id: u8) -> ZenohId {
+        ZenohId::try_from([id]).unwrap()
+    }
+
+    fn make_manager(id: u8, whatami: WhatAmI, allowed: &[u8], denied: &[u8]) -> TransportManager {
+        TransportManager::builder()
+            .whatami(whatami)
+            .zid(zid(id))
+            .unicast(
+                TransportManager::config_unicast()
+                    .allowed_peers(allowed.iter().copied().map(zid).collect())
+                    .denied_peers(denied.iter().copied().map(zid).collect()),
+            )
+            .build(Arc::new(DummyTransportEventHandler))
+            .unwrap()
+    }
+
+    // Opens a transport from each client to the router, returning the ones that succeeded
+    async fn open_clients(router_manager: &TransportManager, endpoint: &EndPoint) -> Vec<u8> {
+        let mut opened = vec![];
+        for id in 2..5 {
+            let client_manager = make_manager(id, WhatAmI::Client, &[], &[]);
+            let res = ztimeout!(client_manager.open_transport_unicast(endpoint.clone()));
+            println!("Transport Peers [  ]: client {id}: {res:?}");
+            if res.is_ok() {
+                opened.push(id);
+                let transport = ztimeout!(router_manager.get_transport_unicast(&zid(id)));
+                assert!(transport.is_some());
+            }
+            ztimeout!(client_manager.close());
+        }
+        opened
+    }
+
+    async fn admitted_peers(endpoint: &EndPoint, allowed: &[u8], denied: &[u8]) -> Vec<u8> {
+        let router_manager = make_manager(1, WhatAmI::Router, allowed, denied);
+        let res = ztimeout!(router_manager.add_listener(endpoint.clone()));
+        assert!(res.is_ok());
+
+        let opened = open_clients(&router_manager, endpoint).await;
+
+        ztimeout!(router_manager.close());
+        // Wait a little bit
+        task::sleep(SLEEP).await;
+        opened
+    }
+
+    #[test]
+    fn peers_empty_lists() {
+        let _ = env_logger::try_init();
+        task::block_on(async {
+            zasync_executor_init!();
+        });
+
+        // All the peers are admitted
+        let endpoint: EndPoint = format!("tcp/127.0.0.1:{}", 14202).parse().unwrap();
+        println!("Transport Peers [1a1]");
+        let opened = task::block_on(admitted_peers(&endpoint, &[], &[]));
+        assert_eq!(opened, vec![2, 3, 4]);
+    }
+
+    #[test]
+    fn peers_allow_list() {
+        let _ = env_logger::try_init();
+        task::block_on(async {
+            zasync_executor_init!();
+        });
+
+        // Only the allowed peers are admitted
+        let endpoint: EndPoint = format!("tcp/127.0.0.1:{}", 14203).parse().unwrap();
+        println!("Transport Peers [2a1]");
+        let opened = task::block_on(admitted_peers(&endpoint, &[2, 4], &[]));
+        assert_eq!(opened, vec![2, 4]);
+
+        // The list also applies to the transports opened to a peer which isn't allowed
+        task::block_on(async {
+            let router_manager = make_manager(1, WhatAmI::Router, &[], &[]);
+            let res = ztimeout!(router_manager.add_listener(endpoint.clone()));
+            assert!(res.is_ok());
+
+            println!("Transport Peers [2b1]");
+            let client_manager = make_manager(2, WhatAmI::Client, &[3], &[]);
+            let res = ztimeout!(client_manager.open_transport_unicast(endpoint.clone()));
+            println!("Transport Peers [2b2]: {res:?}");
+            let e = res.unwrap_err();
+            let reason = e.downcast_ref::<TransportCloseReason>().unwrap();
+            assert_eq!(reason.reason, CloseReason::Invalid);
+            assert!(!reason.remote);
+            assert!(ztimeout!(client_manager.get_transports_unicast()).is_empty());
+
+            ztimeout!(client_manager.close());
+            ztimeout!(router_manager.close());
+            // Wait a little bit
+            task::sleep(SLEEP).await;
+        });
+    }
+
+    #[test]
+    fn peers_allow_deny_lists() {
+        let _ = env_logger::try_init();
+        task::block_on(async {
+            zasync_executor_init!();
+        });
+
+        // A peer both allowed and denied is not admitted
+        let endpoint: EndPoint = format!("tcp/127.0.0.1:{}", 14204).parse().unwrap();
+        println!("Transport Peers [3a1]");
+        let opened = task::block_on(admitted_peers(&endpoint, &[2, 3], &[3, 4]));
+        assert_eq!(opened, vec![2]);
+    }
+}
@@ -0,0 +1,125 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use async_std::{prelude::FutureExt, task};
+use std::{convert::TryFrom, sync::Arc, time::Duration};
+use zenoh_core::zasync_executor_init;
+use zenoh_link::EndPoint;
+use zenoh_protocol::core::{WhatAmI, ZenohId};
+use zenoh_transport::{
+    test_helpers::make_transport_manager_builder, DummyTransportEventHandler, TransportManager,
+    TransportUnicast,
+};
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_millis(10);
+// A keep-alive is sent every LEASE / 4 on an idle link
+const LEASE: Duration = Duration::from_millis(400);
+const SAMPLES: u64 = 3;
+
+macro_rules! ztimeout {
+    ($f:expr) => {
+        $f.timeout(TIMEOUT).await.unwrap()
+    };
+}
+
+fn make_manager(zid: ZenohId, whatami: WhatAmI, rtt_probe: bool) -> TransportManager {
+    let unicast = make_transport_manager_builder(
+        #[cfg(feature = "transport_multilink")]
+        1,
+        #[cfg(feature = "shared-memory")]
+        false,
+        false,
+    )
+    .lease(LEASE);
+    TransportManager::builder()
+        .whatami(whatami)
+        .zid(zid)
+        .unicast(unicast)
+        .rtt_probe(rtt_probe)
+        .build(Arc::new(DummyTransportEventHandler))
+        .unwrap()
+}
+
+async fn get_transport(manager: &TransportManager) -> TransportUnicast {
+    loop {
+        if let Some(transport) = manager.get_transports_unicast().await.pop() {
+            return transport;
+        }
+        task::sleep(SLEEP).await;
+    }
+}
+
+async fn rtt_transport(endpoint: &EndPoint) {
+    // Only the router requests the echo of its keep-alives
+    let router_id = ZenohId::try_from([1]).unwrap();
+    let router_manager = make_manager(router_id, WhatAmI::Router, true);
+    let client_id = ZenohId::try_from([2]).unwrap();
+    let client_manager = make_manager(client_id, WhatAmI::Client, false);
+
+    println!("Transport RTT [1a1]");
+    let res = ztimeout!(router_manager.add_listener(endpoint.clone()));
+    println!("Transport RTT [1a2]: {res:?}");
+    assert!(res.is_ok());
+
+    println!("Transport RTT [1b1]");
+    let res = ztimeout!(client_manager.open_transport_unicast(endpoint.clone()));
+    println!("Transport RTT [1b2]: {res:?}");
+    assert!(res.is_ok());
+
+    let router_transport = ztimeout!(get_transport(&router_manager));
+    let client_transport = ztimeout!(get_transport(&client_manager));
+
+    // The router estimates the round-trip time from the echoes of the client
+    println!("Transport RTT [2a1]");
+    let rtt = ztimeout!(async {
+        loop {
+            let links = router_transport.get_links_rtt().unwrap();
+            assert_eq!(links.len(), 1);
+            match links[0].1 {
+                Some(rtt) if rtt.samples >= SAMPLES => break rtt,
+                _ => task::sleep(SLEEP).await,
+            }
+        }
+    });
+    println!("Transport RTT [2a2]: {rtt:?}");
+    // The echo is sent as soon as the keep-alive is received, far before the next keep-alive
+    assert!(rtt.srtt < LEASE / 4);
+
+    // The client doesn't request any echo: it has no estimate
+    println!("Transport RTT [2b1]");
+    let links = client_transport.get_links_rtt().unwrap();
+    println!("Transport RTT [2b2]: {links:?}");
+    assert_eq!(links.len(), 1);
+    assert_eq!(links[0].1, None);
+
+    println!("Transport RTT [3a1]");
+    let res = ztimeout!(router_manager.del_listener(endpoint));
+    println!("Transport RTT [3a2]: {res:?}");
+    assert!(res.is_ok());
+
+    ztimeout!(router_manager.close());
+    ztimeout!(client_manager.close());
+}
+
+#[cfg(feature = "transport_tcp")]
+#[test]
+fn rtt_tcp_only() {
+    let _ = env_logger::try_init();
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+
+    let endpoint: EndPoint = format!("tcp/127.0.0.1:{}", 15050).parse().unwrap();
+    task::block_on(rtt_transport(&endpoint));
+}
@@ -0,0 +1,248 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use async_std::{prelude::FutureExt, task};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::any::Any;
+use std::convert::TryFrom;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use zenoh_core::zasync_executor_init;
+use zenoh_link::{EndPoint, Link};
+use zenoh_protocol::{
+    core::{CongestionControl, Encoding, Priority, WhatAmI, ZenohId},
+    network::{
+        push::{
+            ext::{NodeIdType, QoSType},
+            Push,
+        },
+        NetworkMessage,
+    },
+    zenoh::Put,
+};
+use zenoh_result::ZResult;
+use zenoh_transport::{
+    DummyTransportPeerEventHandler, TransportEventHandler, TransportManager, TransportMulticast,
+    TransportMulticastEventHandler, TransportPeer, TransportPeerEventHandler, TransportUnicast,
+};
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_millis(10);
+const MSG_COUNT: usize = 50_000;
+const MSG_SIZE: usize = 64;
+// The size class of the buffers the TCP links read the batches into
+const RX_BUFFER_SIZE: usize = 1 << 16;
+
+macro_rules! ztimeout {
+    ($f:expr) => {
+        $f.timeout(TIMEOUT).await.unwrap()
+    };
+}
+
+// Counts the allocations of rx buffers
+struct CountingAllocator;
+
+static RX_BUFFER_ALLOCS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        if layout.size() == RX_BUFFER_SIZE {
+            RX_BUFFER_ALLOCS.fetch_add(1, Ordering::Relaxed);
+        }
+        System.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        if layout.size() == RX_BUFFER_SIZE {
+            RX_BUFFER_ALLOCS.fetch_add(1, Ordering::Relaxed);
+        }
+        System.alloc_zeroed(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+// Transport Handler for the router
+struct SHRouter {
+    count: Arc<AtomicUsize>,
+}
+
+impl TransportEventHandler for SHRouter {
+    fn new_unicast(
+        &self,
+        _peer: TransportPeer,
+        _transport: TransportUnicast,
+    ) -> ZResult<Arc<dyn TransportPeerEventHandler>> {
+        Ok(Arc::new(SCRouter {
+            count: self.count.clone(),
+        }))
+    }
+
+    fn new_multicast(
+        &self,
+        _transport: TransportMulticast,
+    ) -> ZResult<Arc<dyn TransportMulticastEventHandler>> {
+        panic!();
+    }
+}
+
+struct SCRouter {
+    count: Arc<AtomicUsize>,
+}
+
+impl TransportPeerEventHandler for SCRouter {
+    fn handle_message(&self, _message: NetworkMessage) -> ZResult<()> {
+        self.count.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+
+    fn new_link(&self, _link: Link) {}
+    fn del_link(&self, _link: Link) {}
+    fn closing(&self) {}
+    fn closed(&self) {}
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+// Transport Handler for the client
+#[derive(Default)]
+struct SHClient;
+
+impl TransportEventHandler for SHClient {
+    fn new_unicast(
+        &self,
+        _peer: TransportPeer,
+        _transport: TransportUnicast,
+    ) -> ZResult<Arc<dyn TransportPeerEventHandler>> {
+        Ok(Arc::new(DummyTransportPeerEventHandler))
+    }
+
+    fn new_multicast(
+        &self,
+        _transport: TransportMulticast,
+    ) -> ZResult<Arc<dyn TransportMulticastEventHandler>> {
+        panic!();
+    }
+}
+
+fn message() -> NetworkMessage {
+    Push {
+        wire_expr: "test".into(),
+        ext_qos: QoSType::new(Priority::default(), CongestionControl::Block, false),
+        ext_tstamp: None,
+        ext_nodeid: NodeIdType::default(),
+        payload: Put {
+            payload: vec![0u8; MSG_SIZE].into(),
+            timestamp: None,
+            encoding: Encoding::default(),
+            ext_sinfo: None,
+            #[cfg(feature = "shared-memory")]
+            ext_shm: None,
+            ext_integrity: None,
+            ext_batch: None,
+            ext_unknown: vec![],
+        }
+        .into(),
+    }
+    .into()
+}
+
+// Returns the number of rx buffers allocated while receiving the messages
+async fn rx_pool_transport(endpoint: &EndPoint, pool_size: usize) -> usize {
+    let count = Arc::new(AtomicUsize::new(0));
+
+    let router_id = ZenohId::try_from([1]).unwrap();
+    let router_manager = TransportManager::builder()
+        .whatami(WhatAmI::Router)
+        .zid(router_id)
+        .link_rx_pool_size(pool_size)
+        .build(Arc::new(SHRouter {
+            count: count.clone(),
+        }))
+        .unwrap();
+
+    let client_id = ZenohId::try_from([2]).unwrap();
+    let client_manager = TransportManager::builder()
+        .whatami(WhatAmI::Client)
+        .zid(client_id)
+        .link_rx_pool_size(pool_size)
+        .build(Arc::new(SHClient))
+        .unwrap();
+
+    println!("Transport Rx Pool [1a1]");
+    let res = ztimeout!(router_manager.add_listener(endpoint.clone()));
+    println!("Transport Rx Pool [1a2]: {res:?}");
+    assert!(res.is_ok());
+
+    println!("Transport Rx Pool [1b1]");
+    let res = ztimeout!(client_manager.open_transport_unicast(endpoint.clone()));
+    println!("Transport Rx Pool [1b2]: {res:?}");
+    let client_transport = res.unwrap();
+    task::sleep(SLEEP).await;
+
+    println!("Transport Rx Pool [2a1]: sending {MSG_COUNT} messages of {MSG_SIZE} bytes");
+    let allocs = RX_BUFFER_ALLOCS.load(Ordering::SeqCst);
+    for _ in 0..MSG_COUNT {
+        client_transport.schedule(message()).unwrap();
+    }
+    ztimeout!(async {
+        while count.load(Ordering::SeqCst) < MSG_COUNT {
+            task::sleep(SLEEP).await;
+        }
+    });
+    let allocs = RX_BUFFER_ALLOCS.load(Ordering::SeqCst) - allocs;
+    let pool = router_manager.rx_pool();
+    println!(
+        "Transport Rx Pool [2a2]: {allocs} rx buffers allocated, {} hits, {} misses",
+        pool.hits(),
+        pool.misses()
+    );
+    if pool_size > 0 {
+        assert!(pool.hits() > 0);
+    } else {
+        assert_eq!(pool.hits(), 0);
+    }
+
+    ztimeout!(router_manager.close());
+    ztimeout!(client_manager.close());
+
+    // Wait a little bit
+    task::sleep(SLEEP).await;
+
+    allocs
+}
+
+#[cfg(feature = "transport_tcp")]
+#[test]
+fn rx_pool_allocations_tcp() {
+    let _ = env_logger::try_init();
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+
+    // Both runs are in the same test to count the allocations of a single transport at once
+    let endpoint: EndPoint = format!("tcp/127.0.0.1:{}", 14140).parse().unwrap();
+    let unpooled = task::block_on(rx_pool_transport(&endpoint, 0));
+    let endpoint: EndPoint = format!("tcp/127.0.0.1:{}", 14141).parse().unwrap();
+    let pooled = task::block_on(rx_pool_transport(&endpoint, 16 * RX_BUFFER_SIZE));
+    println!("Transport Rx Pool [3a1]: {unpooled} allocations without pool, {pooled} with");
+    assert!(pooled < unpooled);
+}
@@ -0,0 +1,312 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use async_std::{prelude::FutureExt, task};
+use std::any::Any;
+use std::convert::TryFrom;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use zenoh_core::zasync_executor_init;
+use zenoh_link::{EndPoint, Link};
+use zenoh_protocol::{
+    core::{CongestionControl, Encoding, Priority, WhatAmI, ZenohId},
+    network::{
+        push::{
+            ext::{NodeIdType, QoSType},
+            Push,
+        },
+        NetworkMessage,
+    },
+    zenoh::Put,
+};
+use zenoh_result::ZResult;
+use zenoh_transport::{
+    DummyTransportPeerEventHandler, TransportEventHandler, TransportManager, TransportMulticast,
+    TransportMulticastEventHandler, TransportPeer, TransportPeerEventHandler, TransportUnicast,
+};
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_millis(10);
+const LEASE: Duration = Duration::from_secs(1);
+// Handling all the messages takes several leases
+const CALLBACK_DURATION: Duration = Duration::from_millis(500);
+const MSG_COUNT: usize = 8;
+
+macro_rules! ztimeout {
+    ($f:expr) => {
+        $f.timeout(TIMEOUT).await.unwrap()
+    };
+}
+
+// Transport Handler for the router, with a slow callback
+struct SHRouterSlow {
+    count: Arc<AtomicUsize>,
+    duration: Duration,
+}
+
+impl TransportEventHandler for SHRouterSlow {
+    fn new_unicast(
+        &self,
+        _peer: TransportPeer,
+        _transport: TransportUnicast,
+    ) -> ZResult<Arc<dyn TransportPeerEventHandler>> {
+        Ok(Arc::new(SCRouterSlow {
+            count: self.count.clone(),
+            duration: self.duration,
+        }))
+    }
+
+    fn new_multicast(
+        &self,
+        _transport: TransportMulticast,
+    ) -> ZResult<Arc<dyn TransportMulticastEventHandler>> {
+        panic!();
+    }
+}
+
+struct SCRouterSlow {
+    count: Arc<AtomicUsize>,
+    duration: Duration,
+}
+
+impl TransportPeerEventHandler for SCRouterSlow {
+    fn handle_message(&self, _message: NetworkMessage) -> ZResult<()> {
+        std::thread::sleep(self.duration);
+        self.count.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+
+    fn new_link(&self, _link: Link) {}
+    fn del_link(&self, _link: Link) {}
+    fn closing(&self) {}
+    fn closed(&self) {}
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+// Transport Handler for the client
+#[derive(Default)]
+struct SHClient;
+
+impl TransportEventHandler for SHClient {
+    fn new_unicast(
+        &self,
+        _peer: TransportPeer,
+        _transport: TransportUnicast,
+    ) -> ZResult<Arc<dyn TransportPeerEventHandler>> {
+        Ok(Arc::new(DummyTransportPeerEventHandler))
+    }
+
+    fn new_multicast(
+        &self,
+        _transport: TransportMulticast,
+    ) -> ZResult<Arc<dyn TransportMulticastEventHandler>> {
+        panic!();
+    }
+}
+
+fn message(congestion_control: CongestionControl) -> NetworkMessage {
+    Push {
+        wire_expr: "test".into(),
+        ext_qos: QoSType::new(Priority::default(), congestion_control, false),
+        ext_tstamp: None,
+        ext_nodeid: NodeIdType::default(),
+        payload: Put {
+            payload: vec![0u8; 8].into(),
+            timestamp: None,
+            encoding: Encoding::default(),
+            ext_sinfo: None,
+            #[cfg(feature = "shared-memory")]
+            ext_shm: None,
+            ext_integrity: None,
+            ext_batch: None,
+            ext_unknown: vec![],
+        }
+        .into(),
+    }
+    .into()
+}
+
+async fn rx_queue_transport(
+    endpoint: &EndPoint,
+    queue_size: usize,
+    congestion_control: CongestionControl,
+) -> usize {
+    let count = Arc::new(AtomicUsize::new(0));
+
+    let router_id = ZenohId::try_from([1]).unwrap();
+    let router_manager = TransportManager::builder()
+        .whatami(WhatAmI::Router)
+        .zid(router_id)
+        .link_rx_queue_size(queue_size)
+        .unicast(TransportManager::config_unicast().lease(LEASE))
+        .build(Arc::new(SHRouterSlow {
+            count: count.clone(),
+            duration: CALLBACK_DURATION,
+        }))
+        .unwrap();
+
+    let client_id = ZenohId::try_from([2]).unwrap();
+    let client_manager = TransportManager::builder()
+        .whatami(WhatAmI::Client)
+        .zid(client_id)
+        .unicast(TransportManager::config_unicast().lease(LEASE))
+        .build(Arc::new(SHClient))
+        .unwrap();
+
+    println!("Transport Rx Queue [1a1]");
+    let res = ztimeout!(router_manager.add_listener(endpoint.clone()));
+    println!("Transport Rx Queue [1a2]: {res:?}");
+    assert!(res.is_ok());
+
+    println!("Transport Rx Queue [1b1]");
+    let res = ztimeout!(client_manager.open_transport_unicast(endpoint.clone()));
+    println!("Transport Rx Queue [1b2]: {res:?}");
+    let client_transport = res.unwrap();
+
+    println!("Transport Rx Queue [2a1]: sending {MSG_COUNT} messages");
+    for _ in 0..MSG_COUNT {
+        client_transport
+            .schedule(message(congestion_control))
+            .unwrap();
+    }
+
+    // The messages are handled for several leases: the transport must stay open meanwhile
+    let mut elapsed = Duration::ZERO;
+    while elapsed < 3 * LEASE {
+        task::sleep(LEASE / 4).await;
+        elapsed += LEASE / 4;
+        assert!(
+            ztimeout!(router_manager.get_transport_unicast(&client_id)).is_some(),
+            "Router transport expired after {elapsed:?}"
+        );
+        assert!(
+            ztimeout!(client_manager.get_transport_unicast(&router_id)).is_some(),
+            "Client transport expired after {elapsed:?}"
+        );
+    }
+
+    // Wait for all the queued messages to be handled
+    let handled = ztimeout!(async {
+        loop {
+            let handled = count.load(Ordering::SeqCst);
+            task::sleep(2 * CALLBACK_DURATION).await;
+            if count.load(Ordering::SeqCst) == handled {
+                break handled;
+            }
+        }
+    });
+    println!("Transport Rx Queue [2a2]: {handled} messages handled");
+
+    ztimeout!(router_manager.close());
+    ztimeout!(client_manager.close());
+
+    // Wait a little bit
+    task::sleep(SLEEP).await;
+
+    handled
+}
+
+#[cfg(feature = "transport_tcp")]
+#[test]
+fn rx_queue_slow_callback_tcp() {
+    let _ = env_logger::try_init();
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+
+    // All the messages fit in the queue: none is dropped
+    let endpoint: EndPoint = format!("tcp/127.0.0.1:{}", 14110).parse().unwrap();
+    let handled = task::block_on(rx_queue_transport(
+        &endpoint,
+        MSG_COUNT,
+        CongestionControl::Block,
+    ));
+    assert_eq!(handled, MSG_COUNT);
+}
+
+#[cfg(feature = "transport_tcp")]
+#[test]
+fn rx_queue_full_tcp() {
+    let _ = env_logger::try_init();
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+
+    // Once the queue is full the droppable messages are dropped
+    let endpoint: EndPoint = format!("tcp/127.0.0.1:{}", 14111).parse().unwrap();
+    let handled = task::block_on(rx_queue_transport(&endpoint, 1, CongestionControl::Drop));
+    assert!(handled > 0);
+    assert!(handled < MSG_COUNT);
+}
+
+#[cfg(feature = "transport_tcp")]
+#[test]
+fn rx_queue_stalled_tcp() {
+    let _ = env_logger::try_init();
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+
+    task::block_on(async {
+        let endpoint: EndPoint = format!("tcp/127.0.0.1:{}", 14112).parse().unwrap();
+        let router_id = ZenohId::try_from([1]).unwrap();
+        let router_manager = TransportManager::builder()
+            .whatami(WhatAmI::Router)
+            .zid(router_id)
+            .link_rx_queue_size(1)
+            .unicast(TransportManager::config_unicast().lease(LEASE))
+            .build(Arc::new(SHRouterSlow {
+                count: Arc::new(AtomicUsize::new(0)),
+                duration: 2 * LEASE,
+            }))
+            .unwrap();
+
+        let client_id = ZenohId::try_from([2]).unwrap();
+        let client_manager = TransportManager::builder()
+            .whatami(WhatAmI::Client)
+            .zid(client_id)
+            .unicast(TransportManager::config_unicast().lease(LEASE))
+            .build(Arc::new(SHClient))
+            .unwrap();
+
+        println!("Transport Rx Queue [3a1]");
+        ztimeout!(router_manager.add_listener(endpoint.clone())).unwrap();
+        let client_transport =
+            ztimeout!(client_manager.open_transport_unicast(endpoint.clone())).unwrap();
+
+        // The queue stays full longer than a lease: the link is closed
+        println!("Transport Rx Queue [3b1]: stalling the rx queue");
+        for _ in 0..3 {
+            client_transport
+                .schedule(message(CongestionControl::Block))
+                .unwrap();
+        }
+        ztimeout!(async {
+            while router_manager
+                .get_transport_unicast(&client_id)
+                .await
+                .is_some()
+            {
+                task::sleep(LEASE / 4).await;
+            }
+        });
+
+        ztimeout!(router_manager.close());
+        ztimeout!(client_manager.close());
+        task::sleep(SLEEP).await;
+    });
+}
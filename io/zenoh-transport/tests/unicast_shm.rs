@@ -266,6 +266,8 @@ mod tests {
                     encoding: Encoding::default(),
                     ext_sinfo: None,
                     ext_shm: None,
+                    ext_integrity: None,
+                    ext_batch: None,
                     ext_unknown: vec![],
                 }
                 .into(),
@@ -313,6 +315,8 @@ mod tests {
                     encoding: Encoding::default(),
                     ext_sinfo: None,
                     ext_shm: None,
+                    ext_integrity: None,
+                    ext_batch: None,
                     ext_unknown: vec![],
                 }
                 .into(),
@@ -0,0 +1,156 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+#[cfg(all(feature = "transport_tcp", feature = "transport_udp"))]
+mod tests {
+    use async_std::{net::UdpSocket, prelude::FutureExt, task};
+    use std::convert::TryFrom;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+    use zenoh_core::zasync_executor_init;
+    use zenoh_link::EndPoint;
+    use zenoh_protocol::core::{WhatAmI, ZenohId};
+    use zenoh_transport::{DummyTransportEventHandler, TransportManager};
+
+    const TIMEOUT: Duration = Duration::from_secs(60);
+    const SLEEP: Duration = Duration::from_millis(100);
+    // A lease and a close timeout way longer than the shutdown timeout
+    const LEASE: Duration = Duration::from_secs(1_000);
+    const CLOSE_TIMEOUT: Duration = Duration::from_secs(10);
+    const SHUTDOWN_TIMEOUT: Duration = Duration::from_millis(500);
+
+    macro_rules! ztimeout {
+        ($f:expr) => {
+            $f.timeout(TIMEOUT).await.unwrap()
+        };
+    }
+
+    // An UDP proxy forwarding the datagrams of a single client from `listen` to `server`,
+    // until it is silenced.
+    async fn proxy(listen: &str, server: &str) -> Arc<AtomicBool> {
+        let silent = Arc::new(AtomicBool::new(false));
+        let front = Arc::new(UdpSocket::bind(listen).await.unwrap());
+        let back = Arc::new(UdpSocket::bind("127.0.0.1:0").await.unwrap());
+        back.connect(server).await.unwrap();
+        let c_silent = silent.clone();
+        task::spawn(async move {
+            let mut buf = vec![0u8; 65_535];
+            let (n, client) = front.recv_from(&mut buf).await.unwrap();
+            back.send(&buf[..n]).await.unwrap();
+
+            let c_front = front.clone();
+            let c_back = back.clone();
+            let cc_silent = c_silent.clone();
+            task::spawn(async move {
+                let mut buf = vec![0u8; 65_535];
+                while let Ok(n) = c_back.recv(&mut buf).await {
+                    if !cc_silent.load(Ordering::SeqCst) {
+                        let _ = c_front.send_to(&buf[..n], client).await;
+                    }
+                }
+            });
+
+            while let Ok((n, _)) = front.recv_from(&mut buf).await {
+                if !c_silent.load(Ordering::SeqCst) {
+                    let _ = back.send(&buf[..n]).await;
+                }
+            }
+        });
+        silent
+    }
+
+    fn make_manager(zid: u8, whatami: WhatAmI) -> TransportManager {
+        TransportManager::builder()
+            .whatami(whatami)
+            .zid(ZenohId::try_from([zid]).unwrap())
+            .unicast(
+                TransportManager::config_unicast()
+                    .lease(LEASE)
+                    .close_timeout(CLOSE_TIMEOUT)
+                    .shutdown_timeout(SHUTDOWN_TIMEOUT),
+            )
+            .build(Arc::new(DummyTransportEventHandler))
+            .unwrap()
+    }
+
+    async fn shutdown_timeout(
+        endpoint01: &EndPoint,
+        endpoint02: &EndPoint,
+        proxy_endpoint02: &EndPoint,
+    ) {
+        let router01_manager = make_manager(1, WhatAmI::Router);
+        let router02_manager = make_manager(2, WhatAmI::Router);
+        let client_manager = make_manager(3, WhatAmI::Client);
+
+        // The client reaches the second router through the proxy
+        println!("Transport Shutdown [1a1]");
+        let res = ztimeout!(router01_manager.add_listener(endpoint01.clone()));
+        println!("Transport Shutdown [1a2]: {res:?}");
+        assert!(res.is_ok());
+        let res = ztimeout!(router02_manager.add_listener(endpoint02.clone()));
+        println!("Transport Shutdown [1a3]: {res:?}");
+        assert!(res.is_ok());
+        let address = |e: &EndPoint| e.address().as_str().to_string();
+        let silent = ztimeout!(proxy(&address(proxy_endpoint02), &address(endpoint02)));
+
+        println!("Transport Shutdown [1b1]");
+        let res = ztimeout!(client_manager.open_transport_unicast(endpoint01.clone()));
+        println!("Transport Shutdown [1b2]: {res:?}");
+        assert!(res.is_ok());
+        let res = ztimeout!(client_manager.open_transport_unicast(proxy_endpoint02.clone()));
+        println!("Transport Shutdown [1b3]: {res:?}");
+        assert!(res.is_ok());
+
+        // The second router stops acknowledging anything
+        silent.store(true, Ordering::SeqCst);
+
+        // The close of the first transport is acknowledged, the one of the second times out
+        println!("Transport Shutdown [2a1]");
+        let now = Instant::now();
+        let report = ztimeout!(client_manager.close_unicast());
+        println!(
+            "Transport Shutdown [2a2]: {report:?} in {:?}",
+            now.elapsed()
+        );
+        assert_eq!(report.graceful, vec![router01_manager.zid()]);
+        assert_eq!(report.forced, vec![router02_manager.zid()]);
+        assert!(now.elapsed() >= SHUTDOWN_TIMEOUT);
+        assert!(now.elapsed() < CLOSE_TIMEOUT);
+        assert!(ztimeout!(client_manager.get_transports_unicast()).is_empty());
+
+        ztimeout!(router01_manager.close());
+        ztimeout!(router02_manager.close());
+        ztimeout!(client_manager.close());
+
+        // Wait a little bit
+        task::sleep(SLEEP).await;
+    }
+
+    #[test]
+    fn shutdown_timeout_tcp_udp() {
+        let _ = env_logger::try_init();
+        task::block_on(async {
+            zasync_executor_init!();
+        });
+
+        let endpoint01: EndPoint = format!("tcp/127.0.0.1:{}", 14199).parse().unwrap();
+        let endpoint02: EndPoint = format!("udp/127.0.0.1:{}", 14200).parse().unwrap();
+        let proxy_endpoint02: EndPoint = format!("udp/127.0.0.1:{}", 14201).parse().unwrap();
+        task::block_on(shutdown_timeout(
+            &endpoint01,
+            &endpoint02,
+            &proxy_endpoint02,
+        ));
+    }
+}
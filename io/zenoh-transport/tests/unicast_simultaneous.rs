@@ -32,7 +32,7 @@ mod tests {
     };
     use zenoh_result::ZResult;
     use zenoh_transport::{
-        TransportEventHandler, TransportManager, TransportMulticast,
+        DummyTransportEventHandler, TransportEventHandler, TransportManager, TransportMulticast,
         TransportMulticastEventHandler, TransportPeer, TransportPeerEventHandler, TransportUnicast,
     };
 
@@ -42,6 +42,8 @@ mod tests {
     const MSG_COUNT: usize = 16;
     const MSG_SIZE: usize = 1_024;
 
+    const RUNS: usize = 100;
+
     macro_rules! ztimeout {
         ($f:expr) => {
             $f.timeout(TIMEOUT).await.unwrap()
@@ -86,6 +88,8 @@ mod tests {
                     ext_sinfo: None,
                     #[cfg(feature = "shared-memory")]
                     ext_shm: None,
+                    ext_integrity: None,
+                    ext_batch: None,
                     ext_unknown: vec![],
                 }
                 .into(),
@@ -303,6 +307,80 @@ mod tests {
         task::sleep(SLEEP).await;
     }
 
+    async fn transport_simultaneous_single_link(endpoint01: EndPoint, endpoint02: EndPoint) {
+        /* [Peers] */
+        let peer_id01 = ZenohId::try_from([2]).unwrap();
+        let peer_id02 = ZenohId::try_from([3]).unwrap();
+
+        let make_manager = |zid| {
+            let unicast = TransportManager::config_unicast().max_links(1);
+            TransportManager::builder()
+                .whatami(WhatAmI::Peer)
+                .zid(zid)
+                .unicast(unicast)
+                .build(Arc::new(DummyTransportEventHandler))
+                .unwrap()
+        };
+        let peer01_manager = make_manager(peer_id01);
+        let peer02_manager = make_manager(peer_id02);
+        ztimeout!(peer01_manager.add_listener(endpoint01.clone())).unwrap();
+        ztimeout!(peer02_manager.add_listener(endpoint02.clone())).unwrap();
+
+        for run in 0..RUNS {
+            println!("[Simultaneous Single Link {run}] => Opening transports...");
+            let (res01, res02) = ztimeout!(peer01_manager
+                .open_transport_unicast(endpoint02.clone())
+                .join(peer02_manager.open_transport_unicast(endpoint01.clone())));
+            let (tp02, tp01) = (res01.unwrap(), res02.unwrap());
+
+            // Only the link opened by the peer with the lowest id is kept, by both peers
+            ztimeout!(async {
+                loop {
+                    let links01 = tp02.get_links().unwrap();
+                    let links02 = tp01.get_links().unwrap();
+                    if links01.len() == 1 && links02.len() == 1 {
+                        assert_eq!(links01[0].src, links02[0].dst);
+                        assert_eq!(links01[0].dst, links02[0].src);
+                        assert_eq!(links01[0].dst, endpoint02.to_locator());
+                        break;
+                    }
+                    task::sleep(Duration::from_millis(10)).await;
+                }
+            });
+            assert_eq!(ztimeout!(peer01_manager.get_transports_unicast()).len(), 1);
+            assert_eq!(ztimeout!(peer02_manager.get_transports_unicast()).len(), 1);
+
+            println!("[Simultaneous Single Link {run}] => Closing transports...");
+            ztimeout!(tp02.close()).unwrap();
+            ztimeout!(async {
+                while !peer01_manager.get_transports_unicast().await.is_empty()
+                    || !peer02_manager.get_transports_unicast().await.is_empty()
+                {
+                    task::sleep(Duration::from_millis(10)).await;
+                }
+            });
+        }
+
+        ztimeout!(peer01_manager.close());
+        ztimeout!(peer02_manager.close());
+    }
+
+    #[cfg(feature = "transport_tcp")]
+    #[test]
+    fn transport_tcp_simultaneous_single_link() {
+        let _ = env_logger::try_init();
+        task::block_on(async {
+            zasync_executor_init!();
+        });
+
+        let endpoint01: EndPoint = format!("tcp/127.0.0.1:{}", 15040).parse().unwrap();
+        let endpoint02: EndPoint = format!("tcp/127.0.0.1:{}", 15041).parse().unwrap();
+
+        task::block_on(async {
+            transport_simultaneous_single_link(endpoint01, endpoint02).await;
+        });
+    }
+
     #[cfg(feature = "transport_tcp")]
     #[test]
     fn transport_tcp_simultaneous() {
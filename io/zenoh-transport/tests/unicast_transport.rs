@@ -475,6 +475,8 @@ async fn test_transport(
             ext_sinfo: None,
             #[cfg(feature = "shared-memory")]
             ext_shm: None,
+            ext_integrity: None,
+            ext_batch: None,
             ext_unknown: vec![],
         }
         .into(),
@@ -0,0 +1,216 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use async_std::io::{ReadExt, WriteExt};
+use async_std::net::{TcpListener, TcpStream};
+use async_std::{prelude::FutureExt, task};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::{convert::TryFrom, sync::Arc, time::Duration};
+use zenoh_core::zasync_executor_init;
+use zenoh_link::EndPoint;
+use zenoh_protocol::{
+    core::{CongestionControl, Encoding, Priority, WhatAmI, ZenohId},
+    network::{
+        push::{
+            ext::{NodeIdType, QoSType},
+            Push,
+        },
+        NetworkMessage,
+    },
+    zenoh::Put,
+};
+use zenoh_result::ZResult;
+use zenoh_transport::{
+    test_helpers::make_transport_manager_builder, DummyTransportPeerEventHandler,
+    TransportEventHandler, TransportManager, TransportMulticast, TransportMulticastEventHandler,
+    TransportPeer, TransportPeerEventHandler, TransportUnicast,
+};
+use zenoh_util::clock::{Clock, SystemClock, TestClock};
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_millis(10);
+// A lease long enough not to expire while the clock of the router moves forward
+const LEASE: Duration = Duration::from_secs(1_000);
+const STALL_TIMEOUT: Duration = Duration::from_secs(1);
+const MSG_SIZE: usize = 65_536;
+
+macro_rules! ztimeout {
+    ($f:expr) => {
+        $f.timeout(TIMEOUT).await.unwrap()
+    };
+}
+
+// Transport Handler
+#[derive(Default)]
+struct SHStall;
+
+impl TransportEventHandler for SHStall {
+    fn new_unicast(
+        &self,
+        _peer: TransportPeer,
+        _transport: TransportUnicast,
+    ) -> ZResult<Arc<dyn TransportPeerEventHandler>> {
+        Ok(Arc::new(DummyTransportPeerEventHandler))
+    }
+
+    fn new_multicast(
+        &self,
+        _transport: TransportMulticast,
+    ) -> ZResult<Arc<dyn TransportMulticastEventHandler>> {
+        panic!();
+    }
+}
+
+fn make_manager(zid: ZenohId, whatami: WhatAmI, clock: Arc<dyn Clock>) -> TransportManager {
+    let unicast = make_transport_manager_builder(
+        #[cfg(feature = "transport_multilink")]
+        1,
+        #[cfg(feature = "shared-memory")]
+        false,
+        false,
+    )
+    .lease(LEASE);
+    TransportManager::builder()
+        .whatami(whatami)
+        .zid(zid)
+        .unicast(unicast)
+        .tx_stall_timeout(STALL_TIMEOUT)
+        .clock(clock)
+        .build(Arc::new(SHStall))
+        .unwrap()
+}
+
+// Forward the bytes read from `from` to `to`, stopping to read once frozen.
+async fn forward(mut from: TcpStream, mut to: TcpStream, frozen: Arc<AtomicBool>) {
+    let mut buf = vec![0u8; 65_535];
+    loop {
+        while frozen.load(Ordering::SeqCst) {
+            task::sleep(SLEEP).await;
+        }
+        match from.read(&mut buf).await {
+            Ok(n) if n > 0 => {
+                if to.write_all(&buf[..n]).await.is_err() {
+                    break;
+                }
+            }
+            _ => break,
+        }
+    }
+}
+
+// A TCP proxy forwarding a single connection from `listen` to `server`, whose server to client
+// direction can be frozen to simulate a socket that doesn't drain anymore.
+async fn proxy(listen: &str, server: &str) -> Arc<AtomicBool> {
+    let frozen = Arc::new(AtomicBool::new(false));
+    let listener = TcpListener::bind(listen).await.unwrap();
+    let server = server.to_string();
+    let c_frozen = frozen.clone();
+    task::spawn(async move {
+        let (client, _) = listener.accept().await.unwrap();
+        let server = TcpStream::connect(server).await.unwrap();
+        task::spawn(forward(
+            client.clone(),
+            server.clone(),
+            Arc::new(AtomicBool::new(false)),
+        ));
+        forward(server, client, c_frozen).await;
+    });
+    frozen
+}
+
+async fn tx_stall(endpoint: &EndPoint, proxy_endpoint: &EndPoint) {
+    // Only the clock of the router moves: the client keeps the lease of the router alive
+    let router_clock = TestClock::new();
+    let router_id = ZenohId::try_from([1]).unwrap();
+    let router_manager = make_manager(router_id, WhatAmI::Router, Arc::new(router_clock.clone()));
+    let client_id = ZenohId::try_from([2]).unwrap();
+    let client_manager = make_manager(client_id, WhatAmI::Client, Arc::new(SystemClock));
+
+    println!("Transport Tx Stall [1a1]");
+    let res = ztimeout!(router_manager.add_listener(endpoint.clone()));
+    println!("Transport Tx Stall [1a2]: {res:?}");
+    assert!(res.is_ok());
+
+    let address = |e: &EndPoint| e.address().as_str().to_string();
+    let frozen = ztimeout!(proxy(&address(proxy_endpoint), &address(endpoint)));
+
+    println!("Transport Tx Stall [1b1]");
+    let res = ztimeout!(client_manager.open_transport_unicast(proxy_endpoint.clone()));
+    println!("Transport Tx Stall [1b2]: {res:?}");
+    assert!(res.is_ok());
+
+    let transport = ztimeout!(async {
+        loop {
+            if let Some(t) = router_manager.get_transport_unicast(&client_id).await {
+                break t;
+            }
+            task::sleep(SLEEP).await;
+        }
+    });
+
+    // The consumer stops reading: the writes of the router eventually block
+    println!("Transport Tx Stall [2a1]");
+    frozen.store(true, Ordering::SeqCst);
+    let message: NetworkMessage = Push {
+        wire_expr: "test".into(),
+        ext_qos: QoSType::new(Priority::Data, CongestionControl::Drop, false),
+        ext_tstamp: None,
+        ext_nodeid: NodeIdType::default(),
+        payload: Put {
+            payload: vec![0u8; MSG_SIZE].into(),
+            timestamp: None,
+            encoding: Encoding::default(),
+            ext_sinfo: None,
+            #[cfg(feature = "shared-memory")]
+            ext_shm: None,
+            ext_integrity: None,
+            ext_batch: None,
+            ext_unknown: vec![],
+        }
+        .into(),
+    }
+    .into();
+
+    // The watchdog closes the stalled link, and so the transport, way before the lease expires
+    println!("Transport Tx Stall [2a2]");
+    ztimeout!(async {
+        while !ztimeout!(router_manager.get_transports_unicast()).is_empty() {
+            let _ = transport.schedule(message.clone());
+            router_clock.advance(STALL_TIMEOUT);
+            task::sleep(SLEEP).await;
+        }
+    });
+    println!("Transport Tx Stall [2a3]: {:?}", router_clock.elapsed());
+    assert!(router_clock.elapsed() < LEASE);
+
+    println!("Transport Tx Stall [3a1]");
+    let res = ztimeout!(router_manager.del_listener(endpoint));
+    println!("Transport Tx Stall [3a2]: {res:?}");
+    assert!(res.is_ok());
+
+    ztimeout!(router_manager.close());
+    ztimeout!(client_manager.close());
+}
+
+#[cfg(feature = "transport_tcp")]
+#[test]
+fn tx_stall_tcp_only() {
+    let _ = env_logger::try_init();
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+
+    let endpoint: EndPoint = format!("tcp/127.0.0.1:{}", 14130).parse().unwrap();
+    let proxy_endpoint: EndPoint = format!("tcp/127.0.0.1:{}", 14131).parse().unwrap();
+    task::block_on(tx_stall(&endpoint, &proxy_endpoint));
+}
@@ -21,9 +21,10 @@ use std::sync::{
     atomic::{AtomicBool, Ordering::Relaxed},
     Arc, Mutex,
 };
-use zenoh::plugins::{Plugin, RunningPluginTrait, ValidationFunction, ZenohPlugin};
+use zenoh::plugins::{
+    Plugin, PluginApi, PluginContext, RunningPluginTrait, ValidationFunction, ZenohPlugin,
+};
 use zenoh::prelude::r#async::*;
-use zenoh::runtime::Runtime;
 use zenoh_core::zlock;
 use zenoh_result::{bail, ZResult};
 
@@ -39,16 +40,17 @@ const DEFAULT_SELECTOR: &str = "demo/example/**";
 
 impl ZenohPlugin for ExamplePlugin {}
 impl Plugin for ExamplePlugin {
-    type StartArgs = Runtime;
+    type StartArgs = PluginApi;
     type RunningPlugin = zenoh::plugins::RunningPlugin;
 
     // A mandatory const to define, in case of the plugin is built as a standalone executable
     const STATIC_NAME: &'static str = "example";
 
     // The first operation called by zenohd on the plugin
-    fn start(name: &str, runtime: &Self::StartArgs) -> ZResult<Self::RunningPlugin> {
-        let config = runtime.config.lock();
-        let self_cfg = config.plugin(name).unwrap().as_object().unwrap();
+    fn start(name: &str, api: &Self::StartArgs) -> ZResult<Self::RunningPlugin> {
+        let context = api.plugin(name);
+        let config = context.config().unwrap();
+        let self_cfg = config.as_object().unwrap();
         // get the plugin's config details from self_cfg Map (here the "storage-selector" property)
         let selector: KeyExpr = match self_cfg.get("storage-selector") {
             Some(serde_json::Value::String(s)) => KeyExpr::try_from(s)?,
@@ -59,18 +61,17 @@ impl Plugin for ExamplePlugin {
         }
         .clone()
         .into_owned();
-        std::mem::drop(config);
 
         // a flag to end the plugin's loop when the plugin is removed from the config
         let flag = Arc::new(AtomicBool::new(true));
         // spawn the task running the plugin's loop
-        async_std::task::spawn(run(runtime.clone(), selector, flag.clone()));
+        async_std::task::spawn(run(context.clone(), selector, flag.clone()));
         // return a RunningPlugin to zenohd
         Ok(Box::new(RunningPlugin(Arc::new(Mutex::new(
             RunningPluginInner {
                 flag,
                 name: name.into(),
-                runtime: runtime.clone(),
+                context,
             },
         )))))
     }
@@ -80,7 +81,7 @@ impl Plugin for ExamplePlugin {
 struct RunningPluginInner {
     flag: Arc<AtomicBool>,
     name: String,
-    runtime: Runtime,
+    context: PluginContext,
 }
 // The RunningPlugin struct implementing the RunningPluginTrait trait
 #[derive(Clone)]
@@ -107,7 +108,7 @@ impl RunningPluginTrait for RunningPlugin {
                             Err(e) => log::error!("{}", e),
                             Ok(selector) => {
                                 async_std::task::spawn(run(
-                                    guard.runtime.clone(),
+                                    guard.context.clone(),
                                     selector,
                                     guard.flag.clone(),
                                 ));
@@ -146,11 +147,11 @@ impl Drop for RunningPlugin {
     }
 }
 
-async fn run(runtime: Runtime, selector: KeyExpr<'_>, flag: Arc<AtomicBool>) {
+async fn run(context: PluginContext, selector: KeyExpr<'_>, flag: Arc<AtomicBool>) {
     env_logger::init();
 
     // create a zenoh Session that shares the same Runtime than zenohd
-    let session = zenoh::init(runtime).res().await.unwrap();
+    let session = context.open_session().res().await.unwrap();
 
     // the HasMap used as a storage by this example of storage plugin
     let mut stored: HashMap<String, Sample> = HashMap::new();
@@ -30,6 +30,8 @@ pub struct PluginConfig {
     pub volumes: Vec<VolumeConfig>,
     #[schemars(with = "Map<String, Value>")]
     pub storages: Vec<StorageConfig>,
+    #[schemars(skip)]
+    pub garbage_collection_config: GarbageCollectionConfig,
     #[as_ref]
     #[as_mut]
     #[schemars(skip)]
@@ -86,12 +88,14 @@ impl Default for ReplicaConfig {
 }
 
 // The configuration for periodic garbage collection of metadata in storage manager
+// The plugin-wide defaults are set with the `gc_period` and `tombstone_lifetime` fields of the plugin's
+// configuration, and can be overridden per storage with its `garbage_collection` field
 #[derive(JsonSchema, Debug, Clone, PartialEq, Eq)]
 pub struct GarbageCollectionConfig {
     // The duration between two garbage collection events
     // The garbage collection will be scheduled as a periodic event with this period
     pub period: Duration,
-    // The metadata older than this parameter will be garbage collected
+    // The metadata (tombstones and wildcard updates) older than this parameter will be garbage collected
     pub lifespan: Duration,
 }
 
@@ -161,6 +165,19 @@ impl<S: Into<String> + AsRef<str>, V: AsObject> TryFrom<(S, &V)> for PluginConfi
             Some(configs) => VolumeConfig::try_from(name.as_ref(), configs)?,
             None => Vec::new(),
         };
+        let mut garbage_collection_config = GarbageCollectionConfig::default();
+        if let Some(period) = value.get("gc_period") {
+            match period.as_u64() {
+                Some(period) => garbage_collection_config.period = Duration::from_secs(period),
+                None => bail!("Invalid type for field `gc_period` of `{}`'s configuration. Only integer values are accepted.", name.as_ref()),
+            }
+        }
+        if let Some(lifetime) = value.get("tombstone_lifetime") {
+            match lifetime.as_u64() {
+                Some(lifetime) => garbage_collection_config.lifespan = Duration::from_secs(lifetime),
+                None => bail!("Invalid type for field `tombstone_lifetime` of `{}`'s configuration. Only integer values are accepted.", name.as_ref()),
+            }
+        }
         let storages = match value.get("storages") {
             Some(serde_json::Value::Object(configs)) => {
                 let mut storages = Vec::with_capacity(configs.len());
@@ -169,6 +186,7 @@ impl<S: Into<String> + AsRef<str>, V: AsObject> TryFrom<(S, &V)> for PluginConfi
                         name.as_ref(),
                         storage_name,
                         config,
+                        &garbage_collection_config,
                     )?)
                 }
                 storages
@@ -185,11 +203,19 @@ impl<S: Into<String> + AsRef<str>, V: AsObject> TryFrom<(S, &V)> for PluginConfi
             backend_search_dirs,
             volumes,
             storages,
+            garbage_collection_config,
             rest: value
                 .into_iter()
                 .filter_map(|(k, v)| {
-                    (!["__required__", "backend_search_dirs", "volumes", "storages"]
-                        .contains(&k.as_str()))
+                    (![
+                        "__required__",
+                        "backend_search_dirs",
+                        "volumes",
+                        "storages",
+                        "gc_period",
+                        "tombstone_lifetime",
+                    ]
+                    .contains(&k.as_str()))
                     .then(|| (k.clone(), v.clone()))
                 })
                 .collect(),
@@ -332,7 +358,12 @@ impl StorageConfig {
         );
         Value::Object(result)
     }
-    fn try_from<V: AsObject>(plugin_name: &str, storage_name: &str, config: &V) -> ZResult<Self> {
+    fn try_from<V: AsObject>(
+        plugin_name: &str,
+        storage_name: &str,
+        config: &V,
+        default_gc_config: &GarbageCollectionConfig,
+    ) -> ZResult<Self> {
         let config = config.as_object().ok_or_else(|| {
             zerror!(
                 "`storages` field of `{}`'s configuration must be an array of objects",
@@ -363,7 +394,11 @@ impl StorageConfig {
         };
         let strip_prefix: Option<OwnedKeyExpr> = match config.get("strip_prefix") {
             Some(Value::String(s)) => {
-                if !key_expr.starts_with(s) {
+                let is_prefix = match key_expr.as_str().strip_prefix(s.as_str()) {
+                    Some(rest) => rest.is_empty() || rest.starts_with('/'),
+                    None => false,
+                };
+                if !is_prefix {
                     bail!(
                         r#"The specified "strip_prefix={}" is not a prefix of "key_expr={}""#,
                         s,
@@ -413,7 +448,7 @@ impl StorageConfig {
         };
         let garbage_collection_config = match config.get("garbage_collection") {
             Some(s) => {
-                let mut garbage_collection_config = GarbageCollectionConfig::default();
+                let mut garbage_collection_config = default_gc_config.clone();
                 if let Some(period) = s.get("period") {
                     let period = period.to_string().parse::<u64>();
                     if let Ok(period) = period {
@@ -432,7 +467,7 @@ impl StorageConfig {
                 }
                 garbage_collection_config
             }
-            None => GarbageCollectionConfig::default(),
+            None => default_gc_config.clone(),
         };
         let replica_config = match config.get("replica_config") {
             Some(s) => {
@@ -0,0 +1,246 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! The streaming of the bodies of the HTTP requests and responses, never materialized in a
+//! contiguous buffer.
+use futures::io::{AsyncRead, AsyncReadExt};
+use futures::{Stream, StreamExt, TryStreamExt};
+use std::fmt;
+use tide::Body;
+use zenoh::buffers::{ZBuf, ZSlice};
+use zenoh::prelude::r#async::*;
+
+/// The size of the chunks the request bodies are read by.
+pub(crate) const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Why a request body couldn't be read.
+#[derive(Debug)]
+pub(crate) enum BodyError {
+    /// The body is larger than the maximum size given.
+    TooLarge(usize),
+    Io(std::io::Error),
+}
+
+impl From<std::io::Error> for BodyError {
+    fn from(e: std::io::Error) -> Self {
+        BodyError::Io(e)
+    }
+}
+
+impl fmt::Display for BodyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BodyError::TooLarge(max) => write!(f, "The body is larger than {max} bytes"),
+            BodyError::Io(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+/// Reads `reader` to the end by chunks of [`CHUNK_SIZE`] bytes, each becoming a slice of the
+/// returned [`ZBuf`]. Fails as soon as more than `max_size` bytes are read.
+pub(crate) async fn read_body<R>(mut reader: R, max_size: Option<usize>) -> Result<ZBuf, BodyError>
+where
+    R: AsyncRead + Unpin,
+{
+    let mut zbuf = ZBuf::empty();
+    let mut size = 0;
+    loop {
+        let mut chunk = vec![0u8; CHUNK_SIZE];
+        let mut len = 0;
+        while len < CHUNK_SIZE {
+            match reader.read(&mut chunk[len..]).await? {
+                0 => break,
+                n => len += n,
+            }
+        }
+        size += len;
+        if let Some(max) = max_size {
+            if size > max {
+                return Err(BodyError::TooLarge(max));
+            }
+        }
+        if len == 0 {
+            return Ok(zbuf);
+        }
+        if len < CHUNK_SIZE {
+            // The last chunk of the body
+            chunk.truncate(len);
+            chunk.shrink_to_fit();
+            zbuf.push_zslice(chunk.into());
+            return Ok(zbuf);
+        }
+        zbuf.push_zslice(chunk.into());
+    }
+}
+
+/// A chunked body writing the slices of `payload` as they are.
+pub(crate) fn zbuf_body(payload: &ZBuf) -> Body {
+    let slices = payload.zslices().cloned().collect::<Vec<ZSlice>>();
+    let stream = futures::stream::iter(slices).map(Ok::<_, std::io::Error>);
+    Body::from_reader(stream.into_async_read(), None)
+}
+
+/// A chunked body writing the `results` formatted by `format` as they are received, separated
+/// by `separator` and enclosed in `prefix` and `suffix`.
+///
+/// The results are received as the body is written, a slow client slowing their reception.
+pub(crate) fn results_body<S>(
+    results: S,
+    format: fn(Result<Sample, Value>) -> String,
+    prefix: &'static str,
+    separator: &'static str,
+    suffix: &'static str,
+) -> Body
+where
+    S: Stream<Item = Result<Sample, Value>> + Send + 'static,
+{
+    let (sender, receiver) = async_std::channel::bounded::<String>(1);
+    async_std::task::spawn(async move {
+        let mut results = Box::pin(results);
+        let mut chunk = prefix.to_string();
+        let mut first = true;
+        while let Some(result) = results.next().await {
+            if !first {
+                chunk.push_str(separator);
+            }
+            first = false;
+            chunk.push_str(&format(result));
+            if sender.send(std::mem::take(&mut chunk)).await.is_err() {
+                // The client is gone
+                return;
+            }
+        }
+        chunk.push_str(suffix);
+        let _ = sender.send(chunk).await;
+    });
+    let stream = receiver.map(|chunk| Ok::<_, std::io::Error>(chunk.into_bytes()));
+    Body::from_reader(stream.into_async_read(), None)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::sync::{Mutex, MutexGuard};
+    use zenoh_util::alloc_counter::{measure, CountingAllocator};
+
+    #[global_allocator]
+    static ALLOCATOR: CountingAllocator = CountingAllocator;
+    // The allocations being counted for all the threads, the tests don't run in parallel
+    static SERIAL: Mutex<()> = Mutex::new(());
+
+    pub(crate) fn serial() -> MutexGuard<'static, ()> {
+        let guard = SERIAL.lock().unwrap_or_else(|e| e.into_inner());
+        // The runtime is started before the measures, its threads allocating on startup
+        async_std::task::block_on(async {});
+        guard
+    }
+
+    // Larger than the chunks, in many more pieces than chunks as with a chunked upload
+    const PAYLOAD_SIZE: usize = 128 * CHUNK_SIZE + 1000;
+    const PIECE_SIZE: usize = 1000;
+
+    fn payload() -> Vec<u8> {
+        (0..PAYLOAD_SIZE).map(|i| i as u8).collect()
+    }
+
+    fn upload(payload: &[u8]) -> impl AsyncRead + Unpin {
+        let pieces = payload
+            .chunks(PIECE_SIZE)
+            .map(|piece| Ok::<_, std::io::Error>(piece.to_vec()))
+            .collect::<Vec<_>>();
+        futures::stream::iter(pieces).into_async_read()
+    }
+
+    #[test]
+    fn read_body_chunked() {
+        let _serial = serial();
+        let payload = payload();
+        let reader = upload(&payload);
+        let (zbuf, allocations) =
+            measure(|| async_std::task::block_on(read_body(reader, None)).unwrap());
+        println!("Read {PAYLOAD_SIZE} bytes with {allocations:?}");
+        assert_eq!(zbuf.zslices().count(), PAYLOAD_SIZE / CHUNK_SIZE + 1);
+        let read = zbuf
+            .zslices()
+            .flat_map(|s| s.iter().copied())
+            .collect::<Vec<u8>>();
+        assert_eq!(read, payload);
+        // The body is read once, with no reallocation of a growing buffer
+        assert!(allocations.bytes < PAYLOAD_SIZE + 4 * CHUNK_SIZE);
+    }
+
+    #[test]
+    fn read_body_too_large() {
+        let _serial = serial();
+        let payload = payload();
+        let max = 16 * CHUNK_SIZE;
+        let reader = upload(&payload);
+        let (res, allocations) =
+            measure(|| async_std::task::block_on(read_body(reader, Some(max))));
+        assert!(matches!(res, Err(BodyError::TooLarge(m)) if m == max));
+        // The reading stops at the maximum size
+        assert!(allocations.bytes < max + 4 * CHUNK_SIZE);
+
+        let reader = upload(&payload[..max]);
+        let zbuf = async_std::task::block_on(read_body(reader, Some(max))).unwrap();
+        assert_eq!(zbuf.zslices().map(|s| s.len()).sum::<usize>(), max);
+    }
+
+    #[test]
+    fn zbuf_body_streamed() {
+        let _serial = serial();
+        let payload = payload();
+        let zbuf = async_std::task::block_on(read_body(upload(&payload), None)).unwrap();
+        let (read, allocations) = measure(|| {
+            async_std::task::block_on(async {
+                let mut body = zbuf_body(&zbuf);
+                let mut buf = [0u8; PIECE_SIZE];
+                let mut read = 0;
+                loop {
+                    match body.read(&mut buf).await.unwrap() {
+                        0 => return read,
+                        n => {
+                            assert_eq!(buf[..n], payload[read..read + n]);
+                            read += n;
+                        }
+                    }
+                }
+            })
+        });
+        println!("Wrote {PAYLOAD_SIZE} bytes with {allocations:?}");
+        assert_eq!(read, PAYLOAD_SIZE);
+        // The payload is written without being copied
+        assert!(allocations.bytes < CHUNK_SIZE);
+    }
+
+    #[test]
+    fn results_body_json() {
+        let _serial = serial();
+        let (sender, receiver) = flume::bounded(2);
+        for i in 0..2 {
+            let sample = Sample::new(KeyExpr::try_from("test/body").unwrap(), i as u64);
+            sender.send(Ok(sample)).unwrap();
+        }
+        drop(sender);
+        let format = |result: Result<Sample, Value>| result.unwrap().value.to_string();
+        let body = results_body(receiver.into_stream(), format, "[\n", ",\n", "\n]\n");
+        let json = async_std::task::block_on(body.into_string()).unwrap();
+        assert_eq!(json, "[\n0,\n1\n]\n");
+
+        let (_, receiver) = flume::bounded(1);
+        let body = results_body(receiver.into_stream(), format, "[\n", ",\n", "\n]\n");
+        let json = async_std::task::block_on(body.into_string()).unwrap();
+        assert_eq!(json, "[\n\n]\n");
+    }
+}
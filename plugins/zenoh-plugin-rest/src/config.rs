@@ -17,12 +17,15 @@ use serde::{de, Deserialize, Deserializer};
 use std::fmt;
 
 const DEFAULT_HTTP_INTERFACE: &str = "[::]";
+const DEFAULT_MAX_BODY_SIZE: usize = 100 * 1024 * 1024;
 
 #[derive(JsonSchema, Deserialize, serde::Serialize, Clone, Debug)]
 #[serde(deny_unknown_fields)]
 pub struct Config {
     #[serde(deserialize_with = "deserialize_http_port")]
     pub http_port: String,
+    #[serde(default = "default_max_body_size")]
+    pub max_body_size: usize,
     __path__: Option<String>,
     __required__: Option<bool>,
     __config__: Option<String>,
@@ -34,6 +37,10 @@ impl From<&Config> for serde_json::Value {
     }
 }
 
+fn default_max_body_size() -> usize {
+    DEFAULT_MAX_BODY_SIZE
+}
+
 fn deserialize_http_port<'de, D>(deserializer: D) -> Result<String, D::Error>
 where
     D: Deserializer<'de>,
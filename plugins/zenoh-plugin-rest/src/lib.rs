@@ -27,15 +27,16 @@ use std::sync::Arc;
 use tide::http::Mime;
 use tide::sse::Sender;
 use tide::{Request, Response, Server, StatusCode};
-use zenoh::plugins::{Plugin, RunningPluginTrait, ZenohPlugin};
+use zenoh::plugins::{Plugin, PluginApi, PluginContext, RunningPluginTrait, ZenohPlugin};
 use zenoh::prelude::r#async::*;
 use zenoh::properties::Properties;
 use zenoh::query::{QueryConsolidation, Reply};
-use zenoh::runtime::Runtime;
-use zenoh::selector::TIME_RANGE_KEY;
+use zenoh::runtime::HealthStatus;
+use zenoh::selector::ParameterError;
 use zenoh::Session;
 use zenoh_result::{bail, zerror, ZResult};
 
+mod body;
 mod config;
 pub use config::Config;
 
@@ -45,6 +46,9 @@ lazy_static::lazy_static! {
 }
 const RAW_KEY: &str = "_raw";
 
+// The session of the plugin, the id of its runtime and the maximum size of the request bodies
+type State = (Arc<Session>, String, usize);
+
 fn value_to_json(value: Value) -> String {
     // @TODO: transcode to JSON when implemented in Value
     match &value.encoding {
@@ -99,21 +103,12 @@ fn result_to_json(sample: Result<Sample, Value>) -> String {
     }
 }
 
-async fn to_json(results: flume::Receiver<Reply>) -> String {
-    let values = results
-        .stream()
-        .filter_map(move |reply| async move { Some(result_to_json(reply.sample)) })
-        .collect::<Vec<String>>()
-        .await
-        .join(",\n");
-    format!("[\n{values}\n]\n")
-}
-
-async fn to_json_response(results: flume::Receiver<Reply>) -> Response {
-    response(
+fn to_json_response(results: flume::Receiver<Reply>) -> Response {
+    let results = results.into_stream().map(|reply| reply.sample);
+    streaming_response(
         StatusCode::Ok,
         Mime::from_str("application/json").unwrap(),
-        &to_json(results).await,
+        body::results_body(results, result_to_json, "[\n", ",\n", "\n]\n"),
     )
 }
 
@@ -137,34 +132,28 @@ fn result_to_html(sample: Result<Sample, Value>) -> String {
     }
 }
 
-async fn to_html(results: flume::Receiver<Reply>) -> String {
-    let values = results
-        .stream()
-        .filter_map(move |reply| async move { Some(result_to_html(reply.sample)) })
-        .collect::<Vec<String>>()
-        .await
-        .join("\n");
-    format!("<dl>\n{values}\n</dl>\n")
-}
-
-async fn to_html_response(results: flume::Receiver<Reply>) -> Response {
-    response(StatusCode::Ok, "text/html", &to_html(results).await)
+fn to_html_response(results: flume::Receiver<Reply>) -> Response {
+    let results = results.into_stream().map(|reply| reply.sample);
+    streaming_response(
+        StatusCode::Ok,
+        "text/html",
+        body::results_body(results, result_to_html, "<dl>\n", "\n", "\n</dl>\n"),
+    )
 }
 
 async fn to_raw_response(results: flume::Receiver<Reply>) -> Response {
     match results.recv_async().await {
-        Ok(reply) => match reply.sample {
-            Ok(sample) => response(
-                StatusCode::Ok,
-                sample.value.encoding.to_string().as_ref(),
-                String::from_utf8_lossy(&sample.payload.contiguous()).as_ref(),
-            ),
-            Err(value) => response(
+        Ok(reply) => {
+            let value = match reply.sample {
+                Ok(sample) => sample.value,
+                Err(value) => value,
+            };
+            streaming_response(
                 StatusCode::Ok,
                 value.encoding.to_string().as_ref(),
-                String::from_utf8_lossy(&value.payload.contiguous()).as_ref(),
-            ),
-        },
+                body::zbuf_body(&value.payload),
+            )
+        }
         Err(_) => response(StatusCode::Ok, "", ""),
     }
 }
@@ -188,6 +177,21 @@ fn response(status: StatusCode, content_type: impl TryInto<Mime>, body: &str) ->
     builder.build()
 }
 
+// A response with a chunked body, written as it is streamed
+fn streaming_response(
+    status: StatusCode,
+    content_type: impl TryInto<Mime>,
+    body: tide::Body,
+) -> Response {
+    let mut builder = Response::builder(status)
+        .header("Access-Control-Allow-Origin", "*")
+        .body(body);
+    if let Ok(mime) = content_type.try_into() {
+        builder = builder.content_type(mime);
+    }
+    builder.build()
+}
+
 zenoh_plugin_trait::declare_plugin!(RestPlugin);
 pub struct RestPlugin {}
 #[derive(Clone, Copy, Debug)]
@@ -214,25 +218,25 @@ impl std::fmt::Display for StringError {
 impl ZenohPlugin for RestPlugin {}
 
 impl Plugin for RestPlugin {
-    type StartArgs = Runtime;
+    type StartArgs = PluginApi;
     type RunningPlugin = zenoh::plugins::RunningPlugin;
     const STATIC_NAME: &'static str = "rest";
 
-    fn start(name: &str, runtime: &Self::StartArgs) -> ZResult<zenoh::plugins::RunningPlugin> {
+    fn start(name: &str, api: &Self::StartArgs) -> ZResult<zenoh::plugins::RunningPlugin> {
         // Try to initiate login.
         // Required in case of dynamic lib, otherwise no logs.
         // But cannot be done twice in case of static link.
         let _ = env_logger::try_init();
         log::debug!("REST plugin {}", LONG_VERSION.as_str());
 
-        let runtime_conf = runtime.config.lock();
-        let plugin_conf = runtime_conf
-            .plugin(name)
+        let context = api.plugin(name);
+        let plugin_conf = context
+            .config()
             .ok_or_else(|| zerror!("Plugin `{}`: missing config", name))?;
 
-        let conf: Config = serde_json::from_value(plugin_conf.clone())
+        let conf: Config = serde_json::from_value(plugin_conf)
             .map_err(|e| zerror!("Plugin `{}` configuration error: {}", name, e))?;
-        let task = async_std::task::spawn(run(runtime.clone(), conf.clone()));
+        let task = async_std::task::spawn(run(context, conf.clone()));
         let task = async_std::task::block_on(task.timeout(std::time::Duration::from_millis(1)));
         if let Ok(Err(e)) = task {
             bail!("REST server failed within 1ms: {e}")
@@ -296,7 +300,7 @@ fn with_extended_string<R, F: FnMut(&mut String) -> R>(
     result
 }
 
-async fn query(req: Request<(Arc<Session>, String)>) -> tide::Result<Response> {
+async fn query(req: Request<State>) -> tide::Result<Response> {
     log::trace!("Incoming GET request: {:?}", req);
 
     let first_accept = match req.header("accept") {
@@ -314,7 +318,7 @@ async fn query(req: Request<(Arc<Session>, String)>) -> tide::Result<Response> {
     if first_accept == "text/event-stream" {
         Ok(tide::sse::upgrade(
             req,
-            move |req: Request<(Arc<Session>, String)>, sender: Sender| async move {
+            move |req: Request<State>, sender: Sender| async move {
                 let key_expr = match path_to_key_expr(req.url().path(), &req.state().1) {
                     Ok(ke) => ke.into_owned(),
                     Err(e) => {
@@ -386,17 +390,30 @@ async fn query(req: Request<(Arc<Session>, String)>) -> tide::Result<Response> {
             }
         };
         let query_part = url.query();
-        let selector = if let Some(q) = query_part {
-            Selector::from(key_expr).with_parameters(q)
+        let mut selector = if let Some(q) = query_part {
+            Selector::from(key_expr.clone()).with_parameters(q)
         } else {
-            key_expr.into()
+            key_expr.clone().into()
         };
-        let consolidation = if selector.decode().any(|(k, _)| k.as_ref() == TIME_RANGE_KEY) {
+        if let Err(e) = selector.validate(&[RAW_KEY]) {
+            let body = match e.downcast_ref::<ParameterError>() {
+                Some(e) => Value::from(e).to_string(),
+                None => e.to_string(),
+            };
+            return Ok(response(StatusCode::BadRequest, "application/json", &body));
+        }
+        let consolidation = if matches!(selector.time_range(), Ok(Some(_))) {
             QueryConsolidation::from(zenoh::query::ConsolidationMode::None)
         } else {
             QueryConsolidation::from(zenoh::query::ConsolidationMode::Latest)
         };
+        // `_raw` is an argument of the REST API, not forwarded to the queryables
         let raw = selector.decode().any(|(k, _)| k.as_ref() == RAW_KEY);
+        if raw {
+            let mut forwarded = Selector::from(key_expr);
+            forwarded.extend(selector.decode().filter(|(k, _)| k.as_ref() != RAW_KEY));
+            selector = forwarded;
+        }
         match req
             .state()
             .0
@@ -409,9 +426,9 @@ async fn query(req: Request<(Arc<Session>, String)>) -> tide::Result<Response> {
                 if raw {
                     Ok(to_raw_response(receiver).await)
                 } else if first_accept == "text/html" {
-                    Ok(to_html_response(receiver).await)
+                    Ok(to_html_response(receiver))
                 } else {
-                    Ok(to_json_response(receiver).await)
+                    Ok(to_json_response(receiver))
                 }
             }
             Err(e) => Ok(response(
@@ -423,61 +440,125 @@ async fn query(req: Request<(Arc<Session>, String)>) -> tide::Result<Response> {
     }
 }
 
-async fn write(mut req: Request<(Arc<Session>, String)>) -> tide::Result<Response> {
+async fn write(mut req: Request<State>) -> tide::Result<Response> {
     log::trace!("Incoming PUT request: {:?}", req);
-    match req.body_bytes().await {
-        Ok(bytes) => {
-            let key_expr = match path_to_key_expr(req.url().path(), &req.state().1) {
-                Ok(ke) => ke,
-                Err(e) => {
-                    return Ok(response(
-                        StatusCode::BadRequest,
-                        "text/plain",
-                        &e.to_string(),
-                    ))
-                }
-            };
-            let encoding: Encoding = req
-                .content_type()
-                .map(|m| m.essence().to_owned().into())
-                .unwrap_or_default();
-
-            // @TODO: Define the right congestion control value
-            match req
-                .state()
-                .0
-                .put(&key_expr, bytes)
-                .encoding(encoding)
-                .kind(method_to_kind(req.method()))
-                .res()
-                .await
-            {
-                Ok(_) => Ok(Response::new(StatusCode::Ok)),
-                Err(e) => Ok(response(
-                    StatusCode::InternalServerError,
-                    "text/plain",
-                    &e.to_string(),
-                )),
-            }
+    let key_expr = match path_to_key_expr(req.url().path(), &req.state().1) {
+        Ok(ke) => ke.into_owned(),
+        Err(e) => {
+            return Ok(response(
+                StatusCode::BadRequest,
+                "text/plain",
+                &e.to_string(),
+            ))
         }
+    };
+    // Follow the same rule as the API for updates on key expressions containing wildcards
+    if key_expr.is_wild()
+        && !req
+            .state()
+            .0
+            .config()
+            .lock()
+            .wildcard_updates()
+            .unwrap_or(false)
+    {
+        return Ok(response(
+            StatusCode::BadRequest,
+            "text/plain",
+            &format!(
+                "Updates on key expression '{key_expr}' containing wildcards are rejected: enable the `wildcard_updates` configuration to accept them"
+            ),
+        ));
+    }
+    // The bodies of a known length are refused before being read, the chunked ones as they are
+    let max_body_size = req.state().2;
+    if req.len().map_or(false, |len| len > max_body_size) {
+        return Ok(response(
+            StatusCode::PayloadTooLarge,
+            "text/plain",
+            &body::BodyError::TooLarge(max_body_size).to_string(),
+        ));
+    }
+    let payload = match body::read_body(req.take_body(), Some(max_body_size)).await {
+        Ok(payload) => payload,
+        Err(e @ body::BodyError::TooLarge(_)) => {
+            return Ok(response(
+                StatusCode::PayloadTooLarge,
+                "text/plain",
+                &e.to_string(),
+            ))
+        }
+        Err(e) => {
+            return Ok(response(
+                StatusCode::NoContent,
+                "text/plain",
+                &e.to_string(),
+            ))
+        }
+    };
+    let encoding: Encoding = req
+        .content_type()
+        .map(|m| m.essence().to_owned().into())
+        .unwrap_or_default();
+
+    // @TODO: Define the right congestion control value
+    match req
+        .state()
+        .0
+        .put(&key_expr, payload)
+        .encoding(encoding)
+        .kind(method_to_kind(req.method()))
+        .res()
+        .await
+    {
+        Ok(_) => Ok(Response::new(StatusCode::Ok)),
+        Err(e) => Ok(response(
+            StatusCode::InternalServerError,
+            "text/plain",
+            &e.to_string(),
+        )),
+    }
+}
+
+async fn health(context: PluginContext) -> tide::Result<Response> {
+    let report = context.health().await;
+    // Only a failing runtime fails the probes, a degraded one still serves requests
+    let status = match report.status {
+        HealthStatus::Ok | HealthStatus::Degraded => StatusCode::Ok,
+        HealthStatus::Failing => StatusCode::ServiceUnavailable,
+    };
+    match serde_json::to_string(&report) {
+        Ok(json) => Ok(response(status, "application/json", &json)),
         Err(e) => Ok(response(
-            StatusCode::NoContent,
+            StatusCode::InternalServerError,
             "text/plain",
             &e.to_string(),
         )),
     }
 }
 
-pub async fn run(runtime: Runtime, conf: Config) -> ZResult<()> {
+pub async fn run(context: PluginContext, conf: Config) -> ZResult<()> {
     // Try to initiate login.
     // Required in case of dynamic lib, otherwise no logs.
     // But cannot be done twice in case of static link.
     let _ = env_logger::try_init();
 
-    let zid = runtime.zid.to_string();
-    let session = zenoh::init(runtime).res().await.unwrap();
+    let zid = context.zid().to_string();
+    let session = context.open_session().res().await.unwrap();
+
+    let mut app = server((Arc::new(session), zid, conf.max_body_size));
+    app.at("/@health").get(move |_| health(context.clone()));
+
+    if let Err(e) = app.listen(conf.http_port).await {
+        log::error!("Unable to start http server for REST: {:?}", e);
+        return Err(e.into());
+    }
+    Ok(())
+}
 
-    let mut app = Server::with_state((Arc::new(session), zid));
+// The http server answering the requests on the key expressions
+fn server(state: State) -> Server<State> {
+    let mut app = Server::with_state(state);
     app.with(
         tide::security::CorsMiddleware::new()
             .allow_methods(
@@ -491,12 +572,7 @@ pub async fn run(runtime: Runtime, conf: Config) -> ZResult<()> {
 
     app.at("/").get(query).put(write).patch(write).delete(write);
     app.at("*").get(query).put(write).patch(write).delete(write);
-
-    if let Err(e) = app.listen(conf.http_port).await {
-        log::error!("Unable to start http server for REST: {:?}", e);
-        return Err(e.into());
-    }
-    Ok(())
+    app
 }
 
 fn path_to_key_expr<'a>(path: &'a str, zid: &str) -> ZResult<KeyExpr<'a>> {
@@ -509,3 +585,71 @@ fn path_to_key_expr<'a>(path: &'a str, zid: &str) -> ZResult<KeyExpr<'a>> {
         KeyExpr::try_from(path)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tide::http::{Body, Url};
+
+    const TIMEOUT: Duration = Duration::from_secs(60);
+    const MAX_BODY_SIZE: usize = 1024;
+
+    async fn open_server() -> (Arc<Session>, Server<State>) {
+        let mut config = zenoh::config::peer();
+        config.scouting.multicast.set_enabled(Some(false)).unwrap();
+        let session = Arc::new(zenoh::open(config).res().await.unwrap());
+        let zid = session.zid().to_string();
+        (session.clone(), server((session, zid, MAX_BODY_SIZE)))
+    }
+
+    fn put(body: Body) -> tide::http::Request {
+        let url = Url::parse("http://localhost/test/rest").unwrap();
+        let mut req = tide::http::Request::new(Method::Put, url);
+        req.set_body(body);
+        req
+    }
+
+    // A body of unknown length, sent in chunks
+    fn chunked(size: usize) -> Body {
+        Body::from_reader(futures::io::Cursor::new(vec![0u8; size]), None)
+    }
+
+    #[test]
+    fn put_too_large() {
+        let _serial = body::tests::serial();
+        async_std::task::block_on(async {
+            let (_session, app) = open_server().await;
+
+            // A body of a known length is refused before being read
+            let body = Body::from(vec![0u8; MAX_BODY_SIZE + 1]);
+            let res: tide::http::Response = app.respond(put(body)).await.unwrap();
+            assert_eq!(res.status(), StatusCode::PayloadTooLarge);
+
+            // A chunked body is refused once read beyond the maximum size
+            let res: tide::http::Response =
+                app.respond(put(chunked(MAX_BODY_SIZE + 1))).await.unwrap();
+            assert_eq!(res.status(), StatusCode::PayloadTooLarge);
+        });
+    }
+
+    #[test]
+    fn put_chunked() {
+        let _serial = body::tests::serial();
+        async_std::task::block_on(async {
+            let (session, app) = open_server().await;
+            let subscriber = session.declare_subscriber("test/rest").res().await.unwrap();
+
+            let res: tide::http::Response = app.respond(put(chunked(MAX_BODY_SIZE))).await.unwrap();
+            assert_eq!(res.status(), StatusCode::Ok);
+            let sample = subscriber
+                .recv_async()
+                .timeout(TIMEOUT)
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(sample.key_expr.as_str(), "test/rest");
+            assert_eq!(sample.payload.len(), MAX_BODY_SIZE);
+        });
+    }
+}
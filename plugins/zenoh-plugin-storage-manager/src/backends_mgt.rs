@@ -36,6 +36,8 @@ pub(crate) async fn create_and_start_storage(
     zenoh: Arc<Session>,
 ) -> ZResult<Sender<StorageMessage>> {
     log::trace!("Create storage {}", &admin_key);
+    // detect invalid key mappings before creating the storage in the backend
+    let key_mapping = KeyMapping::new(&config.key_expr, config.strip_prefix.clone())?;
     let capability = backend.get_capability();
     let storage = backend.create_storage(config.clone()).await?;
     let store_intercept = StoreIntercept {
@@ -45,5 +47,5 @@ pub(crate) async fn create_and_start_storage(
         out_interceptor,
     };
 
-    start_storage(store_intercept, config, admin_key, zenoh).await
+    start_storage(store_intercept, config, key_mapping, admin_key, zenoh).await
 }
@@ -0,0 +1,692 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! A durable volume storing each key in its own directory under a root directory.
+//!
+//! The chunks of a key are the path segments of its directory, escaped where they contain
+//! characters invalid in file names or that a case-insensitive filesystem would confuse.
+//! The directory of a key contains:
+//!  - `@meta`: the encoding and timestamp of the value, or the timestamp of the deletion of the key,
+//!  - `@value.<version>`: the payload of the value, the version being named by `@meta`.
+//!
+//! An update writes a new value file then commits it by renaming a new `@meta` over the previous
+//! one: a crash in between leaves the previous value in place, and the partially written files are
+//! removed when the storage is next created.
+//!
+//! The files are accessed on the blocking threads of async-std, not to block the storage tasks.
+use async_std::sync::{Mutex as AsyncMutex, MutexGuardArc};
+use async_std::task::spawn_blocking;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use zenoh::prelude::r#async::*;
+use zenoh::time::Timestamp;
+use zenoh_backend_traits::config::{StorageConfig, VolumeConfig};
+use zenoh_backend_traits::*;
+use zenoh_core::zlock;
+use zenoh_result::{bail, zerror, ZResult};
+use zenoh_util::zenoh_home;
+
+/// The option of the volume configuring the directory the storages are created under.
+const PROP_ROOT: &str = "root";
+/// The option of the storages configuring their directory, relative to the root of the volume.
+const PROP_DIR: &str = "dir";
+/// The default root of the volume, relative to the zenoh home.
+const DEFAULT_ROOT_DIR: &str = "zenoh_backend_fs";
+
+const META_FILENAME: &str = "@meta";
+const VALUE_FILENAME_PREFIX: &str = "@value.";
+const TMP_SUFFIX: &str = ".tmp";
+
+/// The minimum number of key locks before the unused ones are forgotten.
+const MIN_LOCKS_CLEANUP: usize = 1024;
+
+pub fn create_fs_backend(config: VolumeConfig) -> ZResult<Box<dyn Volume>> {
+    let root = match config.rest.get(PROP_ROOT) {
+        Some(serde_json::Value::String(root)) => PathBuf::from(root),
+        Some(v) => bail!(
+            "Invalid `{}` option for the filesystem volume: {}",
+            PROP_ROOT,
+            v
+        ),
+        None => zenoh_home().join(DEFAULT_ROOT_DIR),
+    };
+    Ok(Box::new(FsBackend { config, root }))
+}
+
+pub struct FsBackend {
+    config: VolumeConfig,
+    root: PathBuf,
+}
+
+#[async_trait]
+impl Volume for FsBackend {
+    fn get_admin_status(&self) -> serde_json::Value {
+        let mut status = self.config.to_json_value();
+        status["root"] = self.root.to_string_lossy().into();
+        status
+    }
+
+    fn get_capability(&self) -> Capability {
+        Capability {
+            persistence: Persistence::Durable,
+            history: History::Latest,
+            read_cost: 1,
+        }
+    }
+
+    async fn create_storage(&mut self, properties: StorageConfig) -> ZResult<Box<dyn Storage>> {
+        log::debug!(
+            "Create Filesystem Storage with configuration: {:?}",
+            properties
+        );
+        let dir = match properties.volume_cfg.get(PROP_DIR) {
+            Some(serde_json::Value::String(dir)) => PathBuf::from(dir),
+            _ => bail!(
+                "Storage {} requires a `{}` string option for the filesystem volume",
+                properties.name,
+                PROP_DIR
+            ),
+        };
+        // The storages stay confined under the root of the volume
+        if !dir
+            .components()
+            .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+        {
+            bail!(
+                "The `{}` of storage {} must be a relative path without `..`: {}",
+                PROP_DIR,
+                properties.name,
+                dir.display()
+            );
+        }
+        let dir = self.root.join(dir);
+        Ok(Box::new(
+            spawn_blocking(move || FsStorage::new(properties, dir)).await?,
+        ))
+    }
+
+    fn incoming_data_interceptor(&self) -> Option<Arc<dyn Fn(Sample) -> Sample + Send + Sync>> {
+        None
+    }
+
+    fn outgoing_data_interceptor(&self) -> Option<Arc<dyn Fn(Sample) -> Sample + Send + Sync>> {
+        None
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct Meta {
+    timestamp: Timestamp,
+    // The encoding and the value file of the value, `None` if the key is deleted
+    value: Option<(String, String)>,
+}
+
+// The directory, the key and the metadata of a key stored in a directory
+type StoredKey = (PathBuf, Option<OwnedKeyExpr>, Option<Meta>);
+
+/// Escapes a chunk of a key expression into a file name.
+///
+/// `%` escapes the characters invalid in file names, `%` itself, a leading `@` reserved to the
+/// files of the volume, and the `.` and `..` chunks, so that distinct chunks never collide. The
+/// upper case and non-ASCII characters are escaped too, for the distinct chunks not to collide on
+/// the case-insensitive or normalizing filesystems either.
+fn escape_chunk(chunk: &str) -> String {
+    let mut escaped = String::with_capacity(chunk.len());
+    for (i, c) in chunk.chars().enumerate() {
+        let escape = match c {
+            '%' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => true,
+            '@' | '.' if i == 0 => true,
+            c => c.is_control() || c.is_ascii_uppercase() || !c.is_ascii(),
+        };
+        if escape {
+            let mut buf = [0u8; 4];
+            for b in c.encode_utf8(&mut buf).bytes() {
+                escaped.push_str(&format!("%{b:02X}"));
+            }
+        } else {
+            escaped.push(c);
+        }
+    }
+    escaped
+}
+
+/// The chunk of a key expression escaped into `name` by [`escape_chunk`].
+fn unescape_chunk(name: &str) -> ZResult<String> {
+    let mut bytes = Vec::with_capacity(name.len());
+    let mut iter = name.bytes();
+    while let Some(b) = iter.next() {
+        if b == b'%' {
+            let hex = [
+                iter.next()
+                    .ok_or_else(|| zerror!("Truncated escape in {}", name))?,
+                iter.next()
+                    .ok_or_else(|| zerror!("Truncated escape in {}", name))?,
+            ];
+            let hex = std::str::from_utf8(&hex).map_err(|e| zerror!("{}: {}", name, e))?;
+            bytes.push(u8::from_str_radix(hex, 16).map_err(|e| zerror!("{}: {}", name, e))?);
+        } else {
+            bytes.push(b);
+        }
+    }
+    String::from_utf8(bytes).map_err(|e| zerror!("{}: {}", name, e).into())
+}
+
+/// The directory of `key` under `dir`.
+fn key_to_path(dir: &Path, key: Option<&keyexpr>) -> PathBuf {
+    let mut path = dir.to_path_buf();
+    if let Some(key) = key {
+        for chunk in key.split('/') {
+            path.push(escape_chunk(chunk));
+        }
+    }
+    path
+}
+
+/// Syncs the directory `dir`, for the creations, renamings and removals of its entries to be
+/// durable. The directories can't be synced on Windows, where they don't need to.
+fn sync_dir(dir: &Path) -> std::io::Result<()> {
+    #[cfg(unix)]
+    fs::File::open(dir)?.sync_all()?;
+    #[cfg(not(unix))]
+    let _ = dir;
+    Ok(())
+}
+
+/// Creates the directory `path` and its missing parents, syncing the directories they are
+/// created in.
+fn create_dir_synced(path: &Path) -> std::io::Result<()> {
+    if path.is_dir() {
+        return Ok(());
+    }
+    let parent = path.parent().filter(|p| !p.as_os_str().is_empty());
+    if let Some(parent) = parent {
+        create_dir_synced(parent)?;
+    }
+    match fs::create_dir(path) {
+        Ok(()) => parent.map_or(Ok(()), sync_dir),
+        Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => Ok(()),
+        Err(e) => Err(e),
+    }
+}
+
+/// Writes `contents` to `path` through a temporary file renamed over it once synced, so that
+/// `path` has either its previous or its new contents after a crash.
+fn write_atomic(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(TMP_SUFFIX);
+    let tmp = PathBuf::from(tmp);
+    let mut file = fs::File::create(&tmp)?;
+    file.write_all(contents)?;
+    file.sync_all()?;
+    fs::rename(&tmp, path)?;
+    // The renaming is only durable once the directory is synced
+    path.parent().map_or(Ok(()), sync_dir)
+}
+
+/// Locks on the keys of a storage, for the updates and reads of the files of a key to be atomic.
+#[derive(Default)]
+struct KeyLocks {
+    locks: Mutex<HashMap<PathBuf, Arc<AsyncMutex<()>>>>,
+}
+
+impl KeyLocks {
+    async fn lock(&self, path: &Path) -> MutexGuardArc<()> {
+        let lock = {
+            let mut locks = zlock!(self.locks);
+            if locks.len() >= MIN_LOCKS_CLEANUP {
+                locks.retain(|_, lock| Arc::strong_count(lock) > 1);
+            }
+            locks.entry(path.to_path_buf()).or_default().clone()
+        };
+        lock.lock_arc().await
+    }
+}
+
+struct FsStorage {
+    config: StorageConfig,
+    dir: PathBuf,
+    locks: KeyLocks,
+}
+
+impl FsStorage {
+    /// Creates the storage in `dir`, blocking on the filesystem.
+    fn new(config: StorageConfig, dir: PathBuf) -> ZResult<FsStorage> {
+        create_dir_synced(&dir)
+            .map_err(|e| zerror!("Failed to create directory {}: {}", dir.display(), e))?;
+        // Clean up after the updates interrupted by a crash
+        for (path, _, meta) in Self::walk(&dir)? {
+            if let Err(e) = Self::remove_stale_files(&path, &meta) {
+                log::warn!("Failed to clean up {}: {}", path.display(), e);
+            }
+        }
+        Ok(FsStorage {
+            config,
+            dir,
+            locks: KeyLocks::default(),
+        })
+    }
+
+    fn read_meta(path: &Path) -> ZResult<Option<Meta>> {
+        match fs::read(path.join(META_FILENAME)) {
+            Ok(bytes) => Ok(Some(serde_json::from_slice(&bytes).map_err(|e| {
+                zerror!("Corrupted metadata in {}: {}", path.display(), e)
+            })?)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => bail!("Failed to read metadata in {}: {}", path.display(), e),
+        }
+    }
+
+    fn read_data(path: &Path, meta: Meta) -> ZResult<Option<StoredData>> {
+        match meta.value {
+            Some((encoding, file)) => {
+                let payload = fs::read(path.join(&file))
+                    .map_err(|e| zerror!("Failed to read {}: {}", path.join(&file).display(), e))?;
+                Ok(Some(StoredData {
+                    value: Value::from(payload).encoding(Encoding::from(encoding)),
+                    timestamp: meta.timestamp,
+                }))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Reads the value of the key in `path`, if any.
+    fn read(path: &Path) -> ZResult<Option<StoredData>> {
+        match Self::read_meta(path)? {
+            Some(meta) => Self::read_data(path, meta),
+            None => Ok(None),
+        }
+    }
+
+    /// Removes the temporary files and the value files not referenced by `meta` in `path`.
+    fn remove_stale_files(path: &Path, meta: &Option<Meta>) -> std::io::Result<()> {
+        let current = meta.as_ref().and_then(|m| m.value.as_ref()).map(|(_, f)| f);
+        for entry in fs::read_dir(path)? {
+            let name = entry?.file_name();
+            let name = name.to_string_lossy();
+            let stale = name.ends_with(TMP_SUFFIX)
+                || (name.starts_with(VALUE_FILENAME_PREFIX)
+                    && Some(&*name) != current.map(|f| f.as_str()));
+            if stale {
+                fs::remove_file(path.join(&*name))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes `meta` for `key`, together with `payload` if any, committing the update.
+    fn write(path: &Path, meta: &Meta, payload: Option<&[u8]>) -> ZResult<()> {
+        create_dir_synced(path)
+            .map_err(|e| zerror!("Failed to create directory {}: {}", path.display(), e))?;
+        if let (Some(payload), Some((_, file))) = (payload, meta.value.as_ref()) {
+            write_atomic(&path.join(file), payload)
+                .map_err(|e| zerror!("Failed to write {}: {}", path.join(file).display(), e))?;
+        }
+        let bytes = serde_json::to_vec(meta).map_err(|e| zerror!("{}", e))?;
+        write_atomic(&path.join(META_FILENAME), &bytes)
+            .map_err(|e| zerror!("Failed to write metadata in {}: {}", path.display(), e))?;
+        if let Err(e) = Self::remove_stale_files(path, &Some(meta.clone())) {
+            log::warn!("Failed to clean up {}: {}", path.display(), e);
+        }
+        Ok(())
+    }
+
+    /// Walks the directory `dir` of a storage, returning the directory, the key and the metadata
+    /// of each key with a directory. The directories not named after a key are skipped.
+    fn walk(dir: &Path) -> ZResult<Vec<StoredKey>> {
+        let mut result = vec![];
+        let mut pending = vec![(dir.to_path_buf(), None)];
+        while let Some((path, key)) = pending.pop() {
+            let entries = fs::read_dir(&path)
+                .map_err(|e| zerror!("Failed to read directory {}: {}", path.display(), e))?;
+            for entry in entries {
+                let entry = entry.map_err(|e| zerror!("{}: {}", path.display(), e))?;
+                if entry.file_type().map(|t| t.is_dir()).unwrap_or(false) {
+                    let name = entry.file_name().to_string_lossy().into_owned();
+                    let child = unescape_chunk(&name).and_then(|chunk| {
+                        let child = match &key {
+                            Some(key) => format!("{key}/{chunk}"),
+                            None => chunk,
+                        };
+                        OwnedKeyExpr::try_from(child)
+                    });
+                    match child {
+                        Ok(child) => pending.push((entry.path(), Some(child))),
+                        Err(e) => log::warn!(
+                            "Skipping directory {} not named after a key: {}",
+                            entry.path().display(),
+                            e
+                        ),
+                    }
+                }
+            }
+            let meta = Self::read_meta(&path)?;
+            result.push((path, key, meta));
+        }
+        Ok(result)
+    }
+}
+
+#[async_trait]
+impl Storage for FsStorage {
+    fn get_admin_status(&self) -> serde_json::Value {
+        self.config.to_json_value()
+    }
+
+    async fn put(
+        &mut self,
+        key: Option<OwnedKeyExpr>,
+        value: Value,
+        timestamp: Timestamp,
+    ) -> ZResult<StorageInsertionResult> {
+        log::trace!("put for {:?}", key);
+        let path = key_to_path(&self.dir, key.as_deref());
+        let _guard = self.locks.lock(&path).await;
+        let meta = Meta {
+            timestamp,
+            value: Some((
+                value.encoding.to_string(),
+                format!(
+                    "{}{:x}",
+                    VALUE_FILENAME_PREFIX,
+                    timestamp.get_time().as_u64()
+                ),
+            )),
+        };
+        let payload = value.payload.contiguous().into_owned();
+        spawn_blocking(move || {
+            let result = match Self::read_meta(&path)? {
+                Some(previous) if previous.timestamp > timestamp => {
+                    return Ok(StorageInsertionResult::Outdated)
+                }
+                Some(Meta { value: Some(_), .. }) => StorageInsertionResult::Replaced,
+                _ => StorageInsertionResult::Inserted,
+            };
+            Self::write(&path, &meta, Some(payload.as_slice()))?;
+            Ok(result)
+        })
+        .await
+    }
+
+    async fn delete(
+        &mut self,
+        key: Option<OwnedKeyExpr>,
+        timestamp: Timestamp,
+    ) -> ZResult<StorageInsertionResult> {
+        log::trace!("delete for {:?}", key);
+        let path = key_to_path(&self.dir, key.as_deref());
+        let _guard = self.locks.lock(&path).await;
+        spawn_blocking(move || {
+            if let Some(meta) = Self::read_meta(&path)? {
+                if meta.timestamp > timestamp {
+                    return Ok(StorageInsertionResult::Outdated);
+                }
+            }
+            // The metadata without value is the tombstone of the key
+            Self::write(
+                &path,
+                &Meta {
+                    timestamp,
+                    value: None,
+                },
+                None,
+            )?;
+            Ok(StorageInsertionResult::Deleted)
+        })
+        .await
+    }
+
+    async fn get(
+        &mut self,
+        key: Option<OwnedKeyExpr>,
+        _parameters: &str,
+    ) -> ZResult<Vec<StoredData>> {
+        log::trace!("get for {:?}", key);
+        match key {
+            Some(key) if key.is_wild() => {
+                let dir = self.dir.clone();
+                let mut result = vec![];
+                for (path, k, meta) in spawn_blocking(move || Self::walk(&dir)).await? {
+                    if let (Some(k), Some(_)) = (k, meta) {
+                        if k.intersects(&key) {
+                            // The value is read again under the lock, it may have been updated
+                            let _guard = self.locks.lock(&path).await;
+                            result.extend(spawn_blocking(move || Self::read(&path)).await?);
+                        }
+                    }
+                }
+                Ok(result)
+            }
+            key => {
+                let path = key_to_path(&self.dir, key.as_deref());
+                let _guard = self.locks.lock(&path).await;
+                Ok(spawn_blocking(move || Self::read(&path))
+                    .await?
+                    .into_iter()
+                    .collect())
+            }
+        }
+    }
+
+    async fn get_all_entries(&self) -> ZResult<Vec<(Option<OwnedKeyExpr>, Timestamp)>> {
+        let dir = self.dir.clone();
+        Ok(spawn_blocking(move || Self::walk(&dir))
+            .await?
+            .into_iter()
+            .filter_map(|(_, key, meta)| meta.map(|m| (key, m.timestamp)))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::TryFrom;
+    use std::time::Duration;
+    use zenoh::time::{TimestampId, NTP64};
+
+    fn ts(secs: u64) -> Timestamp {
+        let time = Duration::from_secs(secs);
+        Timestamp::new(NTP64::from(time), TimestampId::try_from([1]).unwrap())
+    }
+
+    fn storage(name: &str) -> FsStorage {
+        let dir = std::env::temp_dir().join(format!("zenoh-test-fs-backend-{name}"));
+        let _ = fs::remove_dir_all(&dir);
+        let config = StorageConfig {
+            name: name.to_string(),
+            key_expr: OwnedKeyExpr::try_from("test/**").unwrap(),
+            complete: false,
+            strip_prefix: None,
+            volume_id: "filesystem".to_string(),
+            volume_cfg: serde_json::json!({ "dir": name }),
+            garbage_collection_config: Default::default(),
+            replica_config: None,
+        };
+        FsStorage::new(config, dir).unwrap()
+    }
+
+    fn ke(s: &str) -> Option<OwnedKeyExpr> {
+        Some(OwnedKeyExpr::try_from(s).unwrap())
+    }
+
+    async fn get(storage: &mut FsStorage, key: &str) -> Vec<String> {
+        storage
+            .get(ke(key), "")
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|d| d.value.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn escape_collisions() {
+        let keys = [
+            "a:b", "a%3Ab", "a%253Ab", "@a", "%40a", ".", "..", "a.b", "a\\b", "A", "a", "É", "é",
+        ];
+        let mut names = vec![];
+        for key in keys {
+            let name = escape_chunk(key);
+            assert!(!name.contains(|c: char| "\\:*?\"<>|".contains(c)));
+            assert!(name.is_ascii());
+            assert!(!name.starts_with(|c: char| c == '@' || c == '.'));
+            assert_eq!(unescape_chunk(&name).unwrap(), key);
+            // No collision on the case-insensitive filesystems either
+            names.push(name.to_ascii_lowercase());
+        }
+        names.sort();
+        names.dedup();
+        assert_eq!(names.len(), keys.len());
+
+        async_std::task::block_on(async {
+            let mut storage = storage("escape");
+            for (i, key) in keys.iter().enumerate() {
+                let key = format!("test/{key}");
+                storage
+                    .put(ke(&key), Value::from(i.to_string()), ts(1))
+                    .await
+                    .unwrap();
+            }
+            for (i, key) in keys.iter().enumerate() {
+                assert_eq!(
+                    get(&mut storage, &format!("test/{key}")).await,
+                    [i.to_string()]
+                );
+            }
+            let mut entries = storage.get_all_entries().await.unwrap();
+            entries.sort_by(|(a, _), (b, _)| {
+                let a = a.as_ref().map(|k| k.as_str());
+                a.cmp(&b.as_ref().map(|k| k.as_str()))
+            });
+            let mut expected: Vec<_> = keys.iter().map(|k| ke(&format!("test/{k}"))).collect();
+            expected.sort_by(|a, b| {
+                let a = a.as_ref().map(|k| k.as_str());
+                a.cmp(&b.as_ref().map(|k| k.as_str()))
+            });
+            assert_eq!(
+                entries.into_iter().map(|(k, _)| k).collect::<Vec<_>>(),
+                expected
+            );
+        });
+    }
+
+    #[test]
+    fn skip_undecodable_names() {
+        async_std::task::block_on(async {
+            let mut storage = storage("undecodable");
+            storage
+                .put(ke("test/a"), Value::from("1"), ts(1))
+                .await
+                .unwrap();
+            fs::create_dir_all(storage.dir.join("test").join("%ZZ")).unwrap();
+            fs::create_dir_all(storage.dir.join("%").join("b")).unwrap();
+
+            let storage = FsStorage::new(storage.config.clone(), storage.dir.clone()).unwrap();
+            let entries = storage.get_all_entries().await.unwrap();
+            assert_eq!(entries, [(ke("test/a"), ts(1))]);
+        });
+    }
+
+    #[test]
+    fn put_get_delete() {
+        async_std::task::block_on(async {
+            let mut storage = storage("operations");
+            storage
+                .put(ke("test/a"), Value::from("1"), ts(1))
+                .await
+                .unwrap();
+            storage
+                .put(ke("test/a/b"), Value::from("2"), ts(1))
+                .await
+                .unwrap();
+            storage.put(None, Value::from("0"), ts(1)).await.unwrap();
+            assert_eq!(get(&mut storage, "test/a").await, ["1"]);
+            assert_eq!(storage.get(None, "").await.unwrap().len(), 1);
+
+            let mut values = get(&mut storage, "test/**").await;
+            values.sort();
+            assert_eq!(values, ["1", "2"]);
+
+            // An older update is outdated
+            assert!(matches!(
+                storage
+                    .put(ke("test/a"), Value::from("old"), ts(0))
+                    .await
+                    .unwrap(),
+                StorageInsertionResult::Outdated
+            ));
+            assert_eq!(get(&mut storage, "test/a").await, ["1"]);
+
+            // The delete leaves a tombstone discarding the older puts
+            storage.delete(ke("test/a"), ts(2)).await.unwrap();
+            assert!(get(&mut storage, "test/a").await.is_empty());
+            assert!(matches!(
+                storage
+                    .put(ke("test/a"), Value::from("old"), ts(1))
+                    .await
+                    .unwrap(),
+                StorageInsertionResult::Outdated
+            ));
+            let entries = storage.get_all_entries().await.unwrap();
+            assert!(entries.contains(&(ke("test/a"), ts(2))));
+            assert_eq!(get(&mut storage, "test/a/b").await, ["2"]);
+
+            storage
+                .put(ke("test/a"), Value::from("3"), ts(3))
+                .await
+                .unwrap();
+            assert_eq!(get(&mut storage, "test/a").await, ["3"]);
+        });
+    }
+
+    #[test]
+    fn crash_during_update() {
+        async_std::task::block_on(async {
+            let mut storage = storage("crash");
+            storage
+                .put(ke("test/a"), Value::from("1"), ts(1))
+                .await
+                .unwrap();
+            let path = key_to_path(&storage.dir, ke("test/a").as_deref());
+
+            // A crash after writing the new value file, before committing the metadata
+            let meta = FsStorage::read_meta(&path).unwrap().unwrap();
+            fs::write(path.join(format!("{VALUE_FILENAME_PREFIX}ff")), "2").unwrap();
+            fs::write(
+                path.join(format!("{META_FILENAME}{TMP_SUFFIX}")),
+                "{\"trunc",
+            )
+            .unwrap();
+
+            // The previous value is still there, and the leftovers are removed on restart
+            let mut storage = FsStorage::new(storage.config.clone(), storage.dir.clone()).unwrap();
+            assert_eq!(get(&mut storage, "test/a").await, ["1"]);
+            assert_eq!(FsStorage::read_meta(&path).unwrap(), Some(meta));
+            let mut files: Vec<_> = fs::read_dir(&path)
+                .unwrap()
+                .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+                .collect();
+            files.sort();
+            assert_eq!(files.len(), 2);
+            assert_eq!(files[0], META_FILENAME);
+            assert!(files[1].starts_with(VALUE_FILENAME_PREFIX));
+        });
+    }
+}
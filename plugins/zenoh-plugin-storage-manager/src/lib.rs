@@ -21,6 +21,7 @@
 
 use async_std::task;
 use flume::Sender;
+use fs_backend::create_fs_backend;
 use libloading::Library;
 use memory_backend::create_memory_backend;
 use std::collections::HashMap;
@@ -30,9 +31,10 @@ use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
 use std::sync::Mutex;
 use storages_mgt::StorageMessage;
-use zenoh::plugins::{Plugin, RunningPluginTrait, ValidationFunction, ZenohPlugin};
+use zenoh::plugins::{
+    Plugin, PluginApi, PluginContext, RunningPluginTrait, ValidationFunction, ZenohPlugin,
+};
 use zenoh::prelude::sync::*;
-use zenoh::runtime::Runtime;
 use zenoh::Session;
 use zenoh_backend_traits::CreateVolume;
 use zenoh_backend_traits::CREATE_VOLUME_FN_NAME;
@@ -43,6 +45,7 @@ use zenoh_util::LibLoader;
 
 mod backends_mgt;
 use backends_mgt::*;
+mod fs_backend;
 mod memory_backend;
 mod replica;
 mod storages_mgt;
@@ -58,24 +61,23 @@ impl ZenohPlugin for StoragesPlugin {}
 impl Plugin for StoragesPlugin {
     const STATIC_NAME: &'static str = "storage_manager";
 
-    type StartArgs = Runtime;
+    type StartArgs = PluginApi;
     type RunningPlugin = zenoh::plugins::RunningPlugin;
 
-    fn start(name: &str, runtime: &Self::StartArgs) -> ZResult<Self::RunningPlugin> {
+    fn start(name: &str, api: &Self::StartArgs) -> ZResult<Self::RunningPlugin> {
         std::mem::drop(env_logger::try_init());
         log::debug!("StorageManager plugin {}", LONG_VERSION.as_str());
-        let config =
-            { PluginConfig::try_from((name, runtime.config.lock().plugin(name).unwrap())) }?;
+        let context = api.plugin(name);
+        let config = PluginConfig::try_from((name, &context.config().unwrap()))?;
         Ok(Box::new(StorageRuntime::from(StorageRuntimeInner::new(
-            runtime.clone(),
-            config,
+            context, config,
         )?)))
     }
 }
 struct StorageRuntime(Arc<Mutex<StorageRuntimeInner>>);
 struct StorageRuntimeInner {
     name: String,
-    runtime: Runtime,
+    context: PluginContext,
     session: Arc<Session>,
     lib_loader: LibLoader,
     volumes: HashMap<String, VolumeHandle>,
@@ -83,12 +85,9 @@ struct StorageRuntimeInner {
 }
 impl StorageRuntimeInner {
     fn status_key(&self) -> String {
-        format!(
-            "@/router/{}/status/plugins/{}",
-            &self.runtime.zid, &self.name
-        )
+        self.context.admin_prefix()
     }
-    fn new(runtime: Runtime, config: PluginConfig) -> ZResult<Self> {
+    fn new(context: PluginContext, config: PluginConfig) -> ZResult<Self> {
         // Try to initiate login.
         // Required in case of dynamic lib, otherwise no logs.
         // But cannot be done twice in case of static link.
@@ -104,10 +103,10 @@ impl StorageRuntimeInner {
             .map(|search_dirs| LibLoader::new(&search_dirs, false))
             .unwrap_or_default();
 
-        let session = Arc::new(zenoh::init(runtime.clone()).res_sync().unwrap());
+        let session = Arc::new(context.open_session().res_sync().unwrap());
         let mut new_self = StorageRuntimeInner {
             name,
-            runtime,
+            context,
             session,
             lib_loader,
             volumes: Default::default(),
@@ -120,6 +119,13 @@ impl StorageRuntimeInner {
             required: false,
             rest: Default::default(),
         })?;
+        new_self.spawn_volume(VolumeConfig {
+            name: FS_BACKEND_NAME.into(),
+            backend: None,
+            paths: None,
+            required: false,
+            rest: Default::default(),
+        })?;
         new_self.update(
             volumes
                 .into_iter()
@@ -163,6 +169,16 @@ impl StorageRuntimeInner {
                 }
                 Err(e) => bail!("{}", e),
             }
+        } else if volume_id == FS_BACKEND_NAME {
+            match create_fs_backend(config) {
+                Ok(backend) => {
+                    self.volumes.insert(
+                        volume_id,
+                        VolumeHandle::new(backend, None, "<static-filesystem>".into()),
+                    );
+                }
+                Err(e) => bail!("{}", e),
+            }
         } else {
             match config.backend_search_method() {
                 BackendSearchMethod::ByPaths(paths) => {
@@ -394,6 +410,7 @@ impl RunningPluginTrait for StorageRuntime {
 
 const BACKEND_LIB_PREFIX: &str = "zenoh_backend_";
 const MEMORY_BACKEND_NAME: &str = "memory";
+const FS_BACKEND_NAME: &str = "filesystem";
 
 fn with_extended_string<R, F: FnMut(&mut String) -> R>(
     prefix: &mut String,
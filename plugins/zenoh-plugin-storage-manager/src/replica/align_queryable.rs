@@ -13,6 +13,7 @@
 //
 use super::digest::*;
 use super::Snapshotter;
+use super::ALIGN_COOPERATE_BUDGET;
 use async_std::sync::Arc;
 use std::cmp::Ordering;
 use std::collections::{BTreeSet, HashMap, HashSet};
@@ -21,6 +22,7 @@ use std::str::FromStr;
 use zenoh::prelude::r#async::*;
 use zenoh::time::Timestamp;
 use zenoh::Session;
+use zenoh_util::cooperate::Cooperate;
 
 pub struct AlignQueryable {
     session: Arc<Session>,
@@ -91,7 +93,9 @@ impl AlignQueryable {
             if diff_required.is_some() {
                 let values = self.get_value(diff_required.unwrap()).await;
                 log::trace!("[ALIGN QUERYABLE] value for the query is {:?}", values);
+                let mut cooperate = Cooperate::new(ALIGN_COOPERATE_BUDGET);
                 for value in values {
+                    cooperate.step().await;
                     match value {
                         AlignData::Interval(i, c) => {
                             let sample = Sample::new(
@@ -136,9 +140,11 @@ impl AlignQueryable {
                 result
             }
             AlignComponent::Intervals(intervals) => {
+                let mut cooperate = Cooperate::new(ALIGN_COOPERATE_BUDGET);
                 let mut subintervals = HashMap::new();
                 for each in intervals {
                     subintervals.extend(self.get_subintervals(each).await);
+                    cooperate.step().await;
                 }
                 let mut result = Vec::new();
                 for (i, c) in subintervals {
@@ -147,9 +153,11 @@ impl AlignQueryable {
                 result
             }
             AlignComponent::Subintervals(subintervals) => {
+                let mut cooperate = Cooperate::new(ALIGN_COOPERATE_BUDGET);
                 let mut content = HashMap::new();
                 for each in subintervals {
                     content.extend(self.get_content(each).await);
+                    cooperate.step().await;
                 }
                 let mut result = Vec::new();
                 for (i, c) in content {
@@ -13,7 +13,7 @@
 //
 
 use super::{Digest, EraType, LogEntry, Snapshotter};
-use super::{CONTENTS, ERA, INTERVALS, SUBINTERVALS};
+use super::{ALIGN_COOPERATE_BUDGET, CONTENTS, ERA, INTERVALS, SUBINTERVALS};
 use async_std::sync::{Arc, RwLock};
 use flume::{Receiver, Sender};
 use std::collections::{HashMap, HashSet};
@@ -23,6 +23,7 @@ use zenoh::prelude::r#async::*;
 use zenoh::query::QueryConsolidation;
 use zenoh::time::Timestamp;
 use zenoh::Session;
+use zenoh_util::cooperate::Cooperate;
 
 pub struct Aligner {
     session: Arc<Session>,
@@ -100,7 +101,9 @@ impl Aligner {
             // Missing data might be empty since some samples in digest might be outdated
             log::trace!("[ALIGNER] Missing data is {:?}", missing_data);
 
+            let mut cooperate = Cooperate::new(ALIGN_COOPERATE_BUDGET);
             for (key, (ts, value)) in missing_data {
+                cooperate.step().await;
                 let sample = Sample::new(key, value).with_timestamp(ts);
                 log::debug!("[ALIGNER] Adding sample {:?} to storage", sample);
                 self.tx_sample.send_async(sample).await.unwrap_or_else(|e| {
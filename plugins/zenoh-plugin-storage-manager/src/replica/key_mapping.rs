@@ -0,0 +1,179 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use zenoh::Result as ZResult;
+use zenoh_keyexpr::key_expr::{keyexpr, OwnedKeyExpr};
+use zenoh_result::bail;
+
+/// The mapping between the keys a storage receives (and replies with) and the keys stored in its backend.
+///
+/// With a `strip_prefix`, the backend stores the keys stripped from that prefix, the prefix itself being
+/// stored as the empty key (`None`). Without it, the keys are stored as they are.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyMapping {
+    strip_prefix: Option<OwnedKeyExpr>,
+}
+
+impl KeyMapping {
+    /// Creates the mapping of a storage on `key_expr`, failing if `strip_prefix` contains wildcards
+    /// or doesn't prefix all the keys included by `key_expr`.
+    pub fn new(key_expr: &keyexpr, strip_prefix: Option<OwnedKeyExpr>) -> ZResult<Self> {
+        if let Some(prefix) = &strip_prefix {
+            if prefix.is_wild() {
+                bail!(
+                    r#"The specified "strip_prefix={}" contains wildcard characters (it shouldn't)"#,
+                    prefix
+                );
+            }
+            let included = match key_expr.as_str().strip_prefix(prefix.as_str()) {
+                Some(rest) => rest.is_empty() || rest.starts_with('/'),
+                None => false,
+            };
+            if !included {
+                bail!(
+                    r#"The specified "strip_prefix={}" is not a prefix of "key_expr={}""#,
+                    prefix,
+                    key_expr
+                );
+            }
+        }
+        Ok(KeyMapping { strip_prefix })
+    }
+
+    /// Maps the key of a received sample to the key to store, `None` standing for the prefix itself.
+    pub fn to_stored(&self, key_expr: &keyexpr) -> ZResult<Option<OwnedKeyExpr>> {
+        let prefix = match &self.strip_prefix {
+            Some(prefix) => prefix,
+            None => return Ok(Some(key_expr.into())),
+        };
+        if key_expr == &**prefix {
+            return Ok(None);
+        }
+        // A wildcard straddling the prefix may be stripped into a key that doesn't map back to `key_expr`
+        match key_expr.strip_prefix(prefix).as_slice() {
+            [stripped] if *prefix.join(stripped)? == *key_expr => Ok(Some((*stripped).into())),
+            _ => bail!(
+                "Keyexpr doesn't start with prefix '{}': '{}'",
+                prefix,
+                key_expr
+            ),
+        }
+    }
+
+    /// Maps a stored key back to the full key it was received on.
+    pub fn to_full(&self, stored: Option<&keyexpr>) -> ZResult<OwnedKeyExpr> {
+        match (&self.strip_prefix, stored) {
+            (Some(prefix), Some(stored)) => prefix.join(stored),
+            (Some(prefix), None) => Ok(prefix.clone()),
+            (None, Some(stored)) => Ok(stored.into()),
+            (None, None) => bail!("Empty key found in a storage without strip_prefix"),
+        }
+    }
+
+    /// Returns the full key of a stored key if it is matched by the key expression of a query's selector.
+    pub fn matching(&self, selector: &keyexpr, stored: Option<&keyexpr>) -> Option<OwnedKeyExpr> {
+        match self.to_full(stored) {
+            Ok(full) if selector.intersects(&full) => Some(full),
+            Ok(_) => None,
+            Err(e) => {
+                log::warn!("{}", e);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ke(s: &str) -> OwnedKeyExpr {
+        OwnedKeyExpr::new(s).unwrap()
+    }
+
+    fn mapping(key_expr: &str, strip_prefix: Option<&str>) -> ZResult<KeyMapping> {
+        KeyMapping::new(&ke(key_expr), strip_prefix.map(ke))
+    }
+
+    #[test]
+    fn key_mapping_config() {
+        assert!(mapping("demo/example/**", Some("demo/example")).is_ok());
+        assert!(mapping("demo/example", Some("demo/example")).is_ok());
+        assert!(mapping("demo/example/*/a", Some("demo/example")).is_ok());
+        assert!(mapping("demo/**", None).is_ok());
+        // not a chunk-wise prefix
+        assert!(mapping("demo/example/**", Some("demo/ex")).is_err());
+        // not including all the keys of the storage
+        assert!(mapping("demo/**", Some("demo/example")).is_err());
+        assert!(mapping("demo/*/test/**", Some("demo/example")).is_err());
+        assert!(mapping("other/**", Some("demo/example")).is_err());
+        // wild prefixes
+        assert!(mapping("demo/example/**", Some("demo/*")).is_err());
+        assert!(mapping("demo/example/**", Some("demo/**")).is_err());
+    }
+
+    #[test]
+    fn key_mapping_roundtrip() {
+        let mapping = mapping("demo/example/**", Some("demo/example")).unwrap();
+        for (full, stored) in [
+            ("demo/example", None),
+            ("demo/example/a", Some("a")),
+            ("demo/example/a/b/c", Some("a/b/c")),
+            ("demo/example/example", Some("example")),
+        ] {
+            let stored = stored.map(ke);
+            assert_eq!(mapping.to_stored(&ke(full)).unwrap(), stored);
+            assert_eq!(mapping.to_full(stored.as_deref()).unwrap().as_str(), full);
+        }
+        assert!(mapping.to_stored(&ke("demo/other/a")).is_err());
+        assert!(mapping.to_stored(&ke("demo")).is_err());
+        assert!(mapping.to_stored(&ke("demo/**")).is_err());
+        assert!(mapping.to_stored(&ke("**/a")).is_err());
+
+        let mapping = KeyMapping::new(&ke("demo/**"), None).unwrap();
+        assert_eq!(
+            mapping.to_stored(&ke("demo/a")).unwrap(),
+            Some(ke("demo/a"))
+        );
+        assert_eq!(mapping.to_full(Some(&ke("demo/a"))).unwrap(), ke("demo/a"));
+        assert!(mapping.to_full(None).is_err());
+    }
+
+    #[test]
+    fn key_mapping_queries() {
+        let mapping = mapping("demo/example/**", Some("demo/example")).unwrap();
+        let stored = [None, Some(ke("a")), Some(ke("a/b")), Some(ke("test/c"))];
+        let matching = |selector: &str| {
+            stored
+                .iter()
+                .filter_map(|s| mapping.matching(&ke(selector), s.as_deref()))
+                .map(|k| k.to_string())
+                .collect::<Vec<_>>()
+        };
+        let all = [
+            "demo/example",
+            "demo/example/a",
+            "demo/example/a/b",
+            "demo/example/test/c",
+        ];
+        assert_eq!(matching("**"), all);
+        assert_eq!(matching("demo/**"), all);
+        assert_eq!(matching("demo/example/**"), all);
+        assert_eq!(matching("*/example"), ["demo/example"]);
+        assert_eq!(matching("demo/*"), ["demo/example"]);
+        assert_eq!(matching("demo/*/a"), ["demo/example/a"]);
+        assert_eq!(matching("**/a/**"), ["demo/example/a", "demo/example/a/b"]);
+        assert_eq!(matching("**/test/*"), ["demo/example/test/c"]);
+        assert!(matching("demo/other/**").is_empty());
+    }
+}
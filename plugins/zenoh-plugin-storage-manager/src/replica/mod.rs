@@ -34,12 +34,14 @@ use zenoh_backend_traits::config::{ReplicaConfig, StorageConfig};
 pub mod align_queryable;
 pub mod aligner;
 pub mod digest;
+pub mod key_mapping;
 pub mod snapshotter;
 pub mod storage;
 
 pub use align_queryable::AlignQueryable;
 pub use aligner::Aligner;
 pub use digest::{Digest, DigestConfig, EraType, LogEntry};
+pub use key_mapping::KeyMapping;
 pub use snapshotter::{ReplicationInfo, Snapshotter};
 pub use storage::{ReplicationService, StorageService};
 
@@ -47,6 +49,9 @@ const ERA: &str = "era";
 const INTERVALS: &str = "intervals";
 const SUBINTERVALS: &str = "subintervals";
 const CONTENTS: &str = "contents";
+// The number of intervals, replies or samples processed in a row by the alignment before
+// yielding, a large alignment never suspending otherwise
+const ALIGN_COOPERATE_BUDGET: usize = 64;
 pub const EPOCH_START: SystemTime = SystemTime::UNIX_EPOCH;
 
 pub const ALIGN_PREFIX: &str = "@-digest";
@@ -78,27 +83,17 @@ impl Replica {
         store_intercept: StoreIntercept,
         storage_config: StorageConfig,
         name: &str,
+        key_mapping: KeyMapping,
         rx: Receiver<StorageMessage>,
     ) {
         log::trace!("[REPLICA] Opening session...");
         let startup_entries = match store_intercept.storage.get_all_entries().await {
             Ok(entries) => {
                 let mut result = Vec::new();
-                for entry in entries {
-                    if entry.0.is_none() {
-                        if let Some(prefix) = storage_config.clone().strip_prefix {
-                            result.push((prefix, entry.1));
-                        } else {
-                            log::error!("Empty key found with timestamp `{}`", entry.1);
-                        }
-                    } else {
-                        result.push((
-                            StorageService::get_prefixed(
-                                &storage_config.strip_prefix,
-                                &entry.0.unwrap().into(),
-                            ),
-                            entry.1,
-                        ));
+                for (key, timestamp) in entries {
+                    match key_mapping.to_full(key.as_deref()) {
+                        Ok(full_key) => result.push((full_key, timestamp)),
+                        Err(e) => log::error!("{} with timestamp `{}`", e, timestamp),
                     }
                 }
                 result
@@ -165,6 +160,7 @@ impl Replica {
             replica.session.clone(),
             storage_config,
             &replica.name,
+            key_mapping,
             store_intercept,
             rx,
             Some(replication),
@@ -11,6 +11,7 @@
 // Contributors:
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
+use super::KeyMapping;
 use crate::backends_mgt::StoreIntercept;
 use crate::storages_mgt::StorageMessage;
 use async_std::sync::Arc;
@@ -19,13 +20,14 @@ use async_trait::async_trait;
 use flume::{Receiver, Sender};
 use futures::select;
 use std::collections::{HashMap, HashSet};
-use std::str::{self, FromStr};
+use std::str;
 use std::time::{SystemTime, UNIX_EPOCH};
 use zenoh::buffers::ZBuf;
 use zenoh::prelude::r#async::*;
 use zenoh::query::ConsolidationMode;
-use zenoh::time::{Timestamp, NTP64};
-use zenoh::{Result as ZResult, Session};
+use zenoh::selector::ParameterError;
+use zenoh::time::{Timestamp, TimestampExt, NTP64};
+use zenoh::Session;
 use zenoh_backend_traits::config::{GarbageCollectionConfig, StorageConfig};
 use zenoh_backend_traits::{Capability, History, Persistence, StorageInsertionResult, StoredData};
 use zenoh_keyexpr::key_expr::OwnedKeyExpr;
@@ -34,7 +36,6 @@ use zenoh_keyexpr::keyexpr_tree::IKeyExprTreeMut;
 use zenoh_keyexpr::keyexpr_tree::{
     support::NonWild, support::UnknownWildness, IKeyExprTreeExt, IKeyExprTreeExtMut, KeBoxTree,
 };
-use zenoh_result::bail;
 use zenoh_util::{zenoh_home, Timed, TimedEvent, Timer};
 
 pub const WILDCARD_UPDATES_FILENAME: &str = "wildcard_updates";
@@ -57,11 +58,13 @@ pub struct StorageService {
     key_expr: OwnedKeyExpr,
     complete: bool,
     name: String,
-    strip_prefix: Option<OwnedKeyExpr>,
+    key_mapping: KeyMapping,
     storage: Mutex<Box<dyn zenoh_backend_traits::Storage>>,
     capability: Capability,
     tombstones: Arc<RwLock<KeBoxTree<Timestamp, NonWild, KeyedSetProvider>>>,
     wildcard_updates: Arc<RwLock<KeBoxTree<Update, UnknownWildness, KeyedSetProvider>>>,
+    // Whether updates on key expressions containing wildcards are applied (`wildcard_updates` config)
+    accept_wildcard_updates: bool,
     in_interceptor: Option<Arc<dyn Fn(Sample) -> Sample + Send + Sync>>,
     out_interceptor: Option<Arc<dyn Fn(Sample) -> Sample + Send + Sync>>,
     replication: Option<ReplicationService>,
@@ -72,21 +75,24 @@ impl StorageService {
         session: Arc<Session>,
         config: StorageConfig,
         name: &str,
+        key_mapping: KeyMapping,
         store_intercept: StoreIntercept,
         rx: Receiver<StorageMessage>,
         replication: Option<ReplicationService>,
     ) {
         // @TODO: optimization: if read_cost is high for the storage, initialize a cache for the latest value
+        let accept_wildcard_updates = session.config().lock().wildcard_updates().unwrap_or(false);
         let mut storage_service = StorageService {
             session,
             key_expr: config.key_expr,
             complete: config.complete,
             name: name.to_string(),
-            strip_prefix: config.strip_prefix,
+            key_mapping,
             storage: Mutex::new(store_intercept.storage),
             capability: store_intercept.capability,
             tombstones: Arc::new(RwLock::new(KeBoxTree::new())),
             wildcard_updates: Arc::new(RwLock::new(KeBoxTree::new())),
+            accept_wildcard_updates,
             in_interceptor: store_intercept.in_interceptor,
             out_interceptor: store_intercept.out_interceptor,
             replication,
@@ -274,6 +280,14 @@ impl StorageService {
 
         // if wildcard, update wildcard_updates
         if sample.key_expr.is_wild() {
+            if !self.accept_wildcard_updates {
+                log::warn!(
+                    "Storage {} ignored the update on key expression {} containing wildcards: `wildcard_updates` is disabled",
+                    self.name,
+                    sample.key_expr
+                );
+                return;
+            }
             self.register_wildcard_update(sample.clone()).await;
         }
 
@@ -323,7 +337,7 @@ impl StorageService {
                     }
                 };
 
-                let stripped_key = match self.strip_prefix(&sample_to_store.key_expr) {
+                let stripped_key = match self.key_mapping.to_stored(&sample_to_store.key_expr) {
                     Ok(stripped) => stripped,
                     Err(e) => {
                         log::error!("{}", e);
@@ -331,7 +345,7 @@ impl StorageService {
                     }
                 };
                 let mut storage = self.storage.lock().await;
-                let result = if sample.kind == SampleKind::Put {
+                let result = if sample_to_store.kind == SampleKind::Put {
                     storage
                         .put(
                             stripped_key,
@@ -339,7 +353,7 @@ impl StorageService {
                             sample_to_store.timestamp.unwrap(),
                         )
                         .await
-                } else if sample.kind == SampleKind::Delete {
+                } else if sample_to_store.kind == SampleKind::Delete {
                     // register a tombstone
                     self.mark_tombstone(&k, sample_to_store.timestamp.unwrap())
                         .await;
@@ -423,7 +437,20 @@ impl StorageService {
         // check tombstones to see if it is deleted in the future
         let tombstones = self.tombstones.read().await;
         let weight = tombstones.weight_at(key_expr);
-        weight.is_some() && weight.unwrap() > timestamp
+        if weight.is_some() && weight.unwrap() > timestamp {
+            return true;
+        }
+        drop(tombstones);
+        // check wild card deletions covering the key, it might have been deleted before being stored
+        let wildcards = self.wildcard_updates.read().await;
+        for node in wildcards.intersecting_keys(key_expr) {
+            if let Some(update) = wildcards.weight_at(&node) {
+                if update.kind == SampleKind::Delete && update.data.timestamp > *timestamp {
+                    return true;
+                }
+            }
+        }
+        false
     }
 
     async fn ovderriding_wild_update(
@@ -440,7 +467,7 @@ impl StorageService {
             if weight.is_some() && weight.unwrap().data.timestamp > *ts {
                 // if the key matches a wild card update, check whether it was saved in storage
                 // remember that wild card updates change only existing keys
-                let stripped_key = match self.strip_prefix(&key_expr.into()) {
+                let stripped_key = match self.key_mapping.to_stored(key_expr) {
                     Ok(stripped) => stripped,
                     Err(e) => {
                         log::error!("{}", e);
@@ -463,10 +490,10 @@ impl StorageService {
                             key_expr,
                             e
                         );
-                        ts = &weight.unwrap().data.timestamp;
-                        update = Some(weight.unwrap().clone());
                     }
                 }
+                ts = &weight.unwrap().data.timestamp;
+                update = Some(weight.unwrap().clone());
             }
         }
         update
@@ -475,7 +502,7 @@ impl StorageService {
     async fn is_latest(&self, key_expr: &OwnedKeyExpr, timestamp: &Timestamp) -> bool {
         // @TODO: if cache exists, read from there
         let mut storage = self.storage.lock().await;
-        let stripped_key = match self.strip_prefix(&key_expr.into()) {
+        let stripped_key = match self.key_mapping.to_stored(key_expr) {
             Ok(stripped) => stripped,
             Err(e) => {
                 log::error!("{}", e);
@@ -501,12 +528,48 @@ impl StorageService {
             }
         };
         log::trace!("[STORAGE] Processing query on key_expr: {}", q.key_expr());
+        // reject the misspelled reserved parameters and the malformed standardized ones
+        if let Err(e) = q.selector().validate(&[]) {
+            log::warn!(
+                "Storage {} received a query with invalid parameters: {}",
+                self.name,
+                e
+            );
+            let value = match e.downcast_ref::<ParameterError>() {
+                Some(e) => Value::from(e),
+                None => Value::from(e.to_string()),
+            };
+            if let Err(e) = q.reply(Err(value)).res().await {
+                log::warn!(
+                    "Storage {} raised an error replying a query: {}",
+                    self.name,
+                    e
+                )
+            }
+            return;
+        }
+        // filter out the entries that are outside of the `_time` range (if any)
+        let time_range = q
+            .selector()
+            .time_range()
+            .ok()
+            .flatten()
+            .map(|r| r.resolve());
+        let in_time_range = |entry: &StoredData| match &time_range {
+            Some(range) => entry.timestamp.is_in(range),
+            None => true,
+        };
+        // reply at most `_limit` samples (if any)
+        let mut remaining = q.selector().limit().ok().flatten().unwrap_or(usize::MAX);
         if q.key_expr().is_wild() {
             // resolve key expr into individual keys
             let matching_keys = self.get_matching_keys(q.key_expr()).await;
             let mut storage = self.storage.lock().await;
             for key in matching_keys {
-                let stripped_key = match self.strip_prefix(&key.clone().into()) {
+                if remaining == 0 {
+                    break;
+                }
+                let stripped_key = match self.key_mapping.to_stored(&key) {
                     Ok(k) => k,
                     Err(e) => {
                         log::error!("{}", e);
@@ -516,7 +579,9 @@ impl StorageService {
                 };
                 match storage.get(stripped_key, q.parameters()).await {
                     Ok(stored_data) => {
-                        for entry in stored_data {
+                        let entries = stored_data.into_iter().filter(in_time_range);
+                        for entry in entries.take(remaining) {
+                            remaining -= 1;
                             let sample = Sample::new(key.clone(), entry.value)
                                 .with_timestamp(entry.timestamp);
                             // apply outgoing interceptor on results
@@ -539,7 +604,7 @@ impl StorageService {
             }
             drop(storage);
         } else {
-            let stripped_key = match self.strip_prefix(q.key_expr()) {
+            let stripped_key = match self.key_mapping.to_stored(q.key_expr()) {
                 Ok(k) => k,
                 Err(e) => {
                     log::error!("{}", e);
@@ -562,7 +627,8 @@ impl StorageService {
                         }
                         return;
                     }
-                    for entry in stored_data {
+                    let entries = stored_data.into_iter().filter(in_time_range);
+                    for entry in entries.take(remaining) {
                         let sample = Sample::new(q.key_expr().clone(), entry.value)
                             .with_timestamp(entry.timestamp);
                         // apply outgoing interceptor on results
@@ -603,12 +669,7 @@ impl StorageService {
         match storage.get_all_entries().await {
             Ok(entries) => {
                 for (k, _ts) in entries {
-                    // @TODO: optimize adding back the prefix (possible inspiration from https://github.com/eclipse-zenoh/zenoh/blob/0.5.0-beta.9/backends/traits/src/utils.rs#L79)
-                    let full_key = match k {
-                        Some(key) => StorageService::get_prefixed(&self.strip_prefix, &key.into()),
-                        None => self.strip_prefix.clone().unwrap(),
-                    };
-                    if key_expr.intersects(&full_key.clone()) {
+                    if let Some(full_key) = self.key_mapping.matching(key_expr, k.as_deref()) {
                         result.push(full_key);
                     }
                 }
@@ -622,41 +683,6 @@ impl StorageService {
         result
     }
 
-    fn strip_prefix(&self, key_expr: &KeyExpr<'_>) -> ZResult<Option<OwnedKeyExpr>> {
-        let key = match &self.strip_prefix {
-            Some(prefix) => {
-                if key_expr.as_str().eq(prefix.as_str()) {
-                    ""
-                } else {
-                    match key_expr.strip_prefix(prefix).as_slice() {
-                        [ke] => ke.as_str(),
-                        _ => bail!(
-                            "Keyexpr doesn't start with prefix '{}': '{}'",
-                            prefix,
-                            key_expr
-                        ),
-                    }
-                }
-            }
-            None => key_expr.as_str(),
-        };
-        if key.is_empty() {
-            Ok(None)
-        } else {
-            Ok(Some(OwnedKeyExpr::new(key.to_string()).unwrap()))
-        }
-    }
-
-    pub fn get_prefixed(
-        strip_prefix: &Option<OwnedKeyExpr>,
-        key_expr: &KeyExpr<'_>,
-    ) -> OwnedKeyExpr {
-        match strip_prefix {
-            Some(prefix) => prefix.join(key_expr.as_keyexpr()).unwrap(),
-            None => OwnedKeyExpr::from(key_expr.as_keyexpr()),
-        }
-    }
-
     async fn initialize_if_empty(&mut self) {
         if self.replication.is_some() && self.replication.as_ref().unwrap().empty_start {
             // align with other storages, querying them on key_expr,
@@ -694,7 +720,7 @@ impl StorageService {
 fn serialize_update(update: &Update) -> String {
     let result = (
         update.kind.to_string(),
-        update.data.timestamp.to_string(),
+        update.data.timestamp.to_lossless_string(),
         update.data.value.encoding.to_string(),
         update.data.value.payload.slices().collect::<Vec<&[u8]>>(),
     );
@@ -710,7 +736,7 @@ fn construct_update(data: String) -> Update {
     let value = Value::new(payload).encoding(Encoding::from(result.2));
     let data = StoredData {
         value,
-        timestamp: Timestamp::from_str(&result.1).unwrap(),
+        timestamp: Timestamp::from_lossless_str(&result.1).unwrap(),
     };
     let kind = if result.0.eq(&(SampleKind::Put).to_string()) {
         SampleKind::Put
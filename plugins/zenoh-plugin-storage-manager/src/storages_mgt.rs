@@ -16,7 +16,7 @@ use zenoh::Session;
 use zenoh_backend_traits::config::StorageConfig;
 use zenoh_result::ZResult;
 
-pub use super::replica::{Replica, StorageService};
+pub use super::replica::{KeyMapping, Replica, StorageService};
 
 pub enum StorageMessage {
     Stop,
@@ -26,6 +26,7 @@ pub enum StorageMessage {
 pub(crate) async fn start_storage(
     store_intercept: super::StoreIntercept,
     config: StorageConfig,
+    key_mapping: KeyMapping,
     admin_key: String,
     zenoh: Arc<Session>,
 ) -> ZResult<flume::Sender<StorageMessage>> {
@@ -43,9 +44,26 @@ pub(crate) async fn start_storage(
         // If a configuration for replica is present, we initialize a replica, else only a storage service
         // A replica contains a storage service and all metadata required for anti-entropy
         if config.replica_config.is_some() {
-            Replica::start(zenoh.clone(), store_intercept, config, &name, rx).await;
+            Replica::start(
+                zenoh.clone(),
+                store_intercept,
+                config,
+                &name,
+                key_mapping,
+                rx,
+            )
+            .await;
         } else {
-            StorageService::start(zenoh.clone(), config, &name, store_intercept, rx, None).await;
+            StorageService::start(
+                zenoh.clone(),
+                config,
+                &name,
+                key_mapping,
+                store_intercept,
+                rx,
+                None,
+            )
+            .await;
         }
     });
 
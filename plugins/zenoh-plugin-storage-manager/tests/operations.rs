@@ -20,6 +20,7 @@ use std::str::FromStr;
 use std::thread::sleep;
 
 use async_std::task;
+use zenoh::plugins::PluginApi;
 use zenoh::prelude::r#async::*;
 use zenoh::query::Reply;
 use zenoh::{prelude::Config, time::Timestamp};
@@ -79,8 +80,11 @@ async fn test_updates_in_order() {
         .unwrap();
 
     let runtime = zenoh::runtime::Runtime::new(config).await.unwrap();
-    let storage =
-        zenoh_plugin_storage_manager::StoragesPlugin::start("storage-manager", &runtime).unwrap();
+    let storage = zenoh_plugin_storage_manager::StoragesPlugin::start(
+        "storage-manager",
+        &PluginApi::new(runtime.clone()),
+    )
+    .unwrap();
 
     let session = zenoh::init(runtime).res().await.unwrap();
 
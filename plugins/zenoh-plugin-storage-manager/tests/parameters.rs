@@ -0,0 +1,113 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+// Test the selector parameters of the queries on a storage -
+// 1. the misspelled reserved parameters are rejected with an error reply
+// 2. the number of replies is bounded by `_limit`
+
+use std::thread::sleep;
+
+use async_std::task;
+use zenoh::plugins::PluginApi;
+use zenoh::prelude::r#async::*;
+use zenoh::prelude::Config;
+use zenoh::query::Reply;
+use zenoh_core::zasync_executor_init;
+use zenoh_plugin_trait::Plugin;
+
+async fn get_replies(session: &zenoh::Session, selector: &str) -> Vec<Reply> {
+    let replies: Vec<Reply> = session
+        .get(selector)
+        .res()
+        .await
+        .unwrap()
+        .into_iter()
+        .collect();
+    println!("Getting replies on '{selector}': '{replies:?}'...");
+    replies
+}
+
+async fn test_query_parameters() {
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+    let mut config = Config::default();
+    config
+        .insert_json5(
+            "plugins/storage-manager",
+            r#"{
+                    storages: {
+                        parameters_test: {
+                            key_expr: "parameters/test/**",
+                            volume: {
+                                id: "memory"
+                            }
+                        }
+                    }
+                }"#,
+        )
+        .unwrap();
+
+    let runtime = zenoh::runtime::Runtime::new(config).await.unwrap();
+    let storage = zenoh_plugin_storage_manager::StoragesPlugin::start(
+        "storage-manager",
+        &PluginApi::new(runtime.clone()),
+    )
+    .unwrap();
+
+    let session = zenoh::init(runtime).res().await.unwrap();
+
+    sleep(std::time::Duration::from_secs(1));
+
+    for (key_expr, value) in [
+        ("parameters/test/a", "1"),
+        ("parameters/test/b", "2"),
+        ("parameters/test/c", "3"),
+    ] {
+        println!("Putting Data ('{key_expr}': '{value}')...");
+        session.put(key_expr, value).res().await.unwrap();
+    }
+
+    sleep(std::time::Duration::from_millis(10));
+
+    // expects all the samples
+    let replies = get_replies(&session, "parameters/test/**?_time=[..]").await;
+    assert_eq!(replies.len(), 3);
+    assert!(replies.iter().all(|r| r.sample.is_ok()));
+
+    // expects an error reply naming the misspelled parameter
+    let replies = get_replies(&session, "parameters/test/**?_tme=[..]").await;
+    assert_eq!(replies.len(), 1);
+    let error = replies[0].sample.as_ref().unwrap_err().to_string();
+    assert!(error.contains("\"unknown\""), "{error}");
+    assert!(error.contains("_tme"), "{error}");
+
+    // expects an error reply for a malformed limit
+    let replies = get_replies(&session, "parameters/test/a?_limit=none").await;
+    assert_eq!(replies.len(), 1);
+    let error = replies[0].sample.as_ref().unwrap_err().to_string();
+    assert!(error.contains("\"malformed\""), "{error}");
+
+    // expects at most `_limit` samples
+    let replies = get_replies(&session, "parameters/test/**?_limit=2").await;
+    assert_eq!(replies.len(), 2);
+    assert!(replies.iter().all(|r| r.sample.is_ok()));
+
+    drop(storage);
+}
+
+#[test]
+fn parameters_test() {
+    task::block_on(async { test_query_parameters().await });
+}
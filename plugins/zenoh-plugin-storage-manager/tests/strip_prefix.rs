@@ -0,0 +1,119 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+// Test the key mapping of storages with a strip_prefix -
+// 1. replies carry the full original keys, including for the prefix itself
+// 2. queries with wildcards straddling the prefix match the stored keys
+
+use std::thread::sleep;
+
+use async_std::task;
+use zenoh::plugins::PluginApi;
+use zenoh::prelude::r#async::*;
+use zenoh::prelude::Config;
+use zenoh::query::Reply;
+use zenoh_core::zasync_executor_init;
+use zenoh_plugin_trait::Plugin;
+
+async fn put_data(session: &zenoh::Session, key_expr: &str, value: &str) {
+    println!("Putting Data ('{key_expr}': '{value}')...");
+    session.put(key_expr, value).res().await.unwrap();
+}
+
+async fn get_keys(session: &zenoh::Session, key_expr: &str) -> Vec<String> {
+    let replies: Vec<Reply> = session
+        .get(key_expr)
+        .res()
+        .await
+        .unwrap()
+        .into_iter()
+        .collect();
+    println!("Getting replies on '{key_expr}': '{replies:?}'...");
+    let mut keys: Vec<String> = replies
+        .into_iter()
+        .filter_map(|reply| reply.sample.ok())
+        .map(|sample| sample.key_expr.to_string())
+        .collect();
+    keys.sort();
+    keys
+}
+
+async fn test_strip_prefix() {
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+    let mut config = Config::default();
+    config
+        .insert_json5(
+            "plugins/storage-manager",
+            r#"{
+                    storages: {
+                        strip_prefix_test: {
+                            key_expr: "demo/example/**",
+                            strip_prefix: "demo/example",
+                            volume: {
+                                id: "memory"
+                            }
+                        }
+                    }
+                }"#,
+        )
+        .unwrap();
+
+    let runtime = zenoh::runtime::Runtime::new(config).await.unwrap();
+    let storage = zenoh_plugin_storage_manager::StoragesPlugin::start(
+        "storage-manager",
+        &PluginApi::new(runtime.clone()),
+    )
+    .unwrap();
+
+    let session = zenoh::init(runtime).res().await.unwrap();
+
+    sleep(std::time::Duration::from_secs(1));
+
+    put_data(&session, "demo/example", "0").await;
+    put_data(&session, "demo/example/a", "1").await;
+    put_data(&session, "demo/example/a/b", "2").await;
+    put_data(&session, "demo/example/test/c", "3").await;
+
+    sleep(std::time::Duration::from_millis(10));
+
+    let all = [
+        "demo/example",
+        "demo/example/a",
+        "demo/example/a/b",
+        "demo/example/test/c",
+    ];
+    assert_eq!(get_keys(&session, "**").await, all);
+    assert_eq!(get_keys(&session, "demo/**").await, all);
+    assert_eq!(get_keys(&session, "demo/example/**").await, all);
+    assert_eq!(get_keys(&session, "demo/*").await, ["demo/example"]);
+    assert_eq!(get_keys(&session, "demo/*/a").await, ["demo/example/a"]);
+    assert_eq!(
+        get_keys(&session, "**/test/*").await,
+        ["demo/example/test/c"]
+    );
+    assert_eq!(
+        get_keys(&session, "demo/example/a").await,
+        ["demo/example/a"]
+    );
+    assert_eq!(get_keys(&session, "demo/example").await, ["demo/example"]);
+
+    drop(storage);
+}
+
+#[test]
+fn strip_prefix_test() {
+    task::block_on(async { test_strip_prefix().await });
+}
@@ -0,0 +1,179 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+// Test tombstones -
+// 1. a delete on a never stored key discards the older puts received afterwards
+// 2. a wild card delete discards the older puts on the keys it covers, even if they were never stored
+// 3. the tombstones are garbage collected after `tombstone_lifetime`
+
+use std::convert::TryFrom;
+use std::thread::sleep;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use async_std::task;
+use zenoh::plugins::PluginApi;
+use zenoh::prelude::r#async::*;
+use zenoh::query::Reply;
+use zenoh::time::{Timestamp, TimestampId};
+use zenoh::{prelude::Config, time::NTP64};
+use zenoh_core::zasync_executor_init;
+use zenoh_plugin_trait::Plugin;
+
+// A timestamp `secs` seconds in the past
+fn ts(secs: u64) -> Timestamp {
+    let time = SystemTime::now().duration_since(UNIX_EPOCH).unwrap() - Duration::from_secs(secs);
+    Timestamp::new(NTP64::from(time), TimestampId::try_from([1]).unwrap())
+}
+
+async fn put_data(session: &zenoh::Session, key_expr: &str, value: &str, timestamp: Timestamp) {
+    println!("Putting Data ('{key_expr}': '{value}', {timestamp})...");
+    session
+        .put(key_expr, value)
+        .timestamp(timestamp)
+        .res()
+        .await
+        .unwrap();
+}
+
+async fn delete_data(session: &zenoh::Session, key_expr: &str, timestamp: Timestamp) {
+    println!("Deleting Data '{key_expr}' ({timestamp})...");
+    session
+        .delete(key_expr)
+        .timestamp(timestamp)
+        .res()
+        .await
+        .unwrap();
+}
+
+async fn get_data(session: &zenoh::Session, key_expr: &str) -> Vec<Sample> {
+    let replies: Vec<Reply> = session
+        .get(key_expr)
+        .res()
+        .await
+        .unwrap()
+        .into_iter()
+        .collect();
+    println!("Getting replies on '{key_expr}': '{replies:?}'...");
+    let mut samples = Vec::new();
+    for reply in replies {
+        if let Ok(sample) = reply.sample {
+            samples.push(sample);
+        }
+    }
+    println!("Getting Data on '{key_expr}': '{samples:?}'...");
+    samples
+}
+
+async fn test_tombstones() {
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+    let mut config = Config::default();
+    config
+        .insert_json5(
+            "plugins/storage-manager",
+            r#"{
+                    gc_period: 1,
+                    tombstone_lifetime: 60,
+                    storages: {
+                        tombstone_test: {
+                            key_expr: "tombstone/test/**",
+                            volume: {
+                                id: "memory"
+                            }
+                        }
+                    }
+                }"#,
+        )
+        .unwrap();
+
+    config.set_wildcard_updates(Some(true)).unwrap();
+
+    let runtime = zenoh::runtime::Runtime::new(config).await.unwrap();
+    let storage = zenoh_plugin_storage_manager::StoragesPlugin::start(
+        "storage-manager",
+        &PluginApi::new(runtime.clone()),
+    )
+    .unwrap();
+
+    let session = zenoh::init(runtime).res().await.unwrap();
+
+    sleep(std::time::Duration::from_secs(1));
+
+    // delete a never stored key, then receive an older put (e.g. replayed by alignment)
+    delete_data(&session, "tombstone/test/a", ts(10)).await;
+    put_data(&session, "tombstone/test/a", "1", ts(20)).await;
+
+    sleep(std::time::Duration::from_millis(10));
+
+    // expects zero sample: the put must not resurrect the key
+    let data = get_data(&session, "tombstone/test/a").await;
+    assert_eq!(data.len(), 0);
+
+    put_data(&session, "tombstone/test/a", "2", ts(5)).await;
+
+    sleep(std::time::Duration::from_millis(10));
+
+    // expects exactly one sample: the put is newer than the delete
+    let data = get_data(&session, "tombstone/test/a").await;
+    assert_eq!(data.len(), 1);
+    assert_eq!(format!("{}", data[0].value), "2");
+
+    // wild card delete, then older puts on keys it covers
+    delete_data(&session, "tombstone/test/wild/*", ts(10)).await;
+    put_data(&session, "tombstone/test/wild/b", "3", ts(20)).await;
+    put_data(&session, "tombstone/test/other", "4", ts(20)).await;
+
+    sleep(std::time::Duration::from_millis(10));
+
+    // expects zero sample on the covered key
+    let data = get_data(&session, "tombstone/test/wild/b").await;
+    assert_eq!(data.len(), 0);
+
+    // expects exactly one sample on the key not covered by the delete
+    let data = get_data(&session, "tombstone/test/other").await;
+    assert_eq!(data.len(), 1);
+    assert_eq!(format!("{}", data[0].value), "4");
+
+    put_data(&session, "tombstone/test/wild/c", "5", ts(5)).await;
+
+    sleep(std::time::Duration::from_millis(10));
+
+    // expects exactly one sample: the put is newer than the wild card delete
+    let data = get_data(&session, "tombstone/test/wild/*").await;
+    assert_eq!(data.len(), 1);
+    assert_eq!(data[0].key_expr.as_str(), "tombstone/test/wild/c");
+    assert_eq!(format!("{}", data[0].value), "5");
+
+    // a delete older than `tombstone_lifetime` is forgotten after the next garbage collection
+    delete_data(&session, "tombstone/test/expired", ts(120)).await;
+
+    sleep(std::time::Duration::from_secs(2));
+
+    put_data(&session, "tombstone/test/expired", "6", ts(130)).await;
+
+    sleep(std::time::Duration::from_millis(10));
+
+    // expects exactly one sample
+    let data = get_data(&session, "tombstone/test/expired").await;
+    assert_eq!(data.len(), 1);
+    assert_eq!(format!("{}", data[0].value), "6");
+
+    drop(storage);
+}
+
+#[test]
+fn tombstones_test() {
+    task::block_on(async { test_tombstones().await });
+}
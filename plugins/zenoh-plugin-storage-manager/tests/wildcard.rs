@@ -15,12 +15,14 @@
 // Test wild card updates -
 // 1. normal case, just some wild card puts and deletes on existing keys and ensure it works
 // 2. check for dealing with out of order updates
+// 3. a wild card put is applied to a key created later with an older timestamp
 
 use std::str::FromStr;
 use std::thread::sleep;
 
 // use std::collections::HashMap;
 use async_std::task;
+use zenoh::plugins::PluginApi;
 use zenoh::prelude::r#async::*;
 use zenoh::query::Reply;
 use zenoh::{prelude::Config, time::Timestamp};
@@ -79,9 +81,14 @@ async fn test_wild_card_in_order() {
         )
         .unwrap();
 
+    config.set_wildcard_updates(Some(true)).unwrap();
+
     let runtime = zenoh::runtime::Runtime::new(config).await.unwrap();
-    let storage =
-        zenoh_plugin_storage_manager::StoragesPlugin::start("storage-manager", &runtime).unwrap();
+    let storage = zenoh_plugin_storage_manager::StoragesPlugin::start(
+        "storage-manager",
+        &PluginApi::new(runtime.clone()),
+    )
+    .unwrap();
 
     let session = zenoh::init(runtime).res().await.unwrap();
     sleep(std::time::Duration::from_secs(1));
@@ -174,6 +181,66 @@ async fn test_wild_card_in_order() {
     drop(storage);
 }
 
+async fn test_wild_card_later_key() {
+    let mut config = Config::default();
+    config
+        .insert_json5(
+            "plugins/storage-manager",
+            r#"{
+                    storages: {
+                        wild_later: {
+                            key_expr: "wild/later/**",
+                            volume: {
+                                id: "memory"
+                            }
+                        }
+                    }
+                }"#,
+        )
+        .unwrap();
+    config.set_wildcard_updates(Some(true)).unwrap();
+
+    let runtime = zenoh::runtime::Runtime::new(config).await.unwrap();
+    let storage = zenoh_plugin_storage_manager::StoragesPlugin::start(
+        "storage-manager",
+        &PluginApi::new(runtime.clone()),
+    )
+    .unwrap();
+
+    let session = zenoh::init(runtime).res().await.unwrap();
+    sleep(std::time::Duration::from_secs(1));
+
+    // put *, then an older put on a key that didn't exist yet
+    session
+        .put("wild/later/*", "wild")
+        .timestamp(
+            Timestamp::from_str("2022-01-17T10:43:10.418555997Z/BC779A06D7E049BD88C3FF3DB0C17FCC")
+                .unwrap(),
+        )
+        .res()
+        .await
+        .unwrap();
+    session
+        .put("wild/later/a", "older")
+        .timestamp(
+            Timestamp::from_str("2022-01-17T10:42:10.418555997Z/BC779A06D7E049BD88C3FF3DB0C17FCC")
+                .unwrap(),
+        )
+        .res()
+        .await
+        .unwrap();
+
+    sleep(std::time::Duration::from_millis(10));
+
+    // expected the wild card value on the new key
+    let data = get_data(&session, "wild/later/a").await;
+    assert_eq!(data.len(), 1);
+    assert_eq!(data[0].key_expr.as_str(), "wild/later/a");
+    assert_eq!(format!("{}", data[0].value), "wild");
+
+    drop(storage);
+}
+
 // fn test_wild_card_out_of_order() {
 //     assert_eq!(true, true);
 // }
@@ -181,5 +248,6 @@ async fn test_wild_card_in_order() {
 #[test]
 fn wildcard_test() {
     task::block_on(async { test_wild_card_in_order().await });
+    task::block_on(async { test_wild_card_later_key().await });
     // task::block_on(async { test_wild_card_out_of_order() });
 }
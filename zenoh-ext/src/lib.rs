@@ -15,12 +15,16 @@ pub mod group;
 mod publication_cache;
 mod querying_subscriber;
 mod session_ext;
+mod stream;
 mod subscriber_ext;
 pub use publication_cache::{PublicationCache, PublicationCacheBuilder};
 pub use querying_subscriber::{
     FetchingSubscriber, FetchingSubscriberBuilder, QueryingSubscriberBuilder,
 };
 pub use session_ext::SessionExt;
+pub use stream::{
+    StreamError, StreamReceiver, StreamReceiverBuilder, StreamSender, StreamSenderBuilder,
+};
 pub use subscriber_ext::SubscriberBuilderExt;
 pub use subscriber_ext::SubscriberForward;
 
@@ -0,0 +1,645 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! Reliable ordered streams of messages between a sender and a receiver, built atop pub/sub.
+//!
+//! The messages of a stream are published on its key expression with a sequence number.
+//! The receiver reorders them and requests the missing ones, through queries, to a
+//! retransmission cache kept by the sender. The control key expressions of a stream
+//! `<key>` are:
+//! - `@/stream/<key>/retransmit`: the queryable of the sender cache, the parameters `from` and `to`
+//!   giving the range of the requested sequence numbers;
+//! - `@/stream/<key>/closed`: the queryable of the receiver, replying once the receiver has received
+//!   the whole stream up to its close.
+use async_std::task;
+use futures::select;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use std::convert::TryInto;
+use std::fmt;
+use std::future::Ready;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use zenoh::prelude::r#async::*;
+use zenoh::publication::{CongestionControl, Publisher};
+use zenoh::query::{ConsolidationMode, Reply};
+use zenoh::queryable::{Query, Queryable};
+use zenoh::subscriber::FlumeSubscriber;
+use zenoh::Session;
+use zenoh_core::{zlock, AsyncResolve, Resolvable, SyncResolve};
+use zenoh_result::{bail, ZResult};
+use zenoh_util::core::ResolveFuture;
+
+const STREAM_PREFIX: &str = "@/stream";
+const RETRANSMIT_SUFFIX: &str = "retransmit";
+const CLOSED_SUFFIX: &str = "closed";
+const FROM_PARAM: &str = "from";
+const TO_PARAM: &str = "to";
+
+const DEFAULT_CACHE_DEPTH: usize = 1_024;
+const DEFAULT_CLOSE_TIMEOUT: Duration = Duration::from_secs(10);
+const DEFAULT_WINDOW: usize = 1_024;
+const DEFAULT_NACK_PERIOD: Duration = Duration::from_millis(100);
+// The maximum period of the retransmission requests while the receiver waits for messages
+const MAX_POLL_PERIOD: Duration = Duration::from_secs(1);
+
+fn control_key_expr(key_expr: &KeyExpr, suffix: &str) -> ZResult<KeyExpr<'static>> {
+    format!("{STREAM_PREFIX}/{key_expr}/{suffix}").try_into()
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+enum StreamMessage {
+    Data { sn: u64, payload: Vec<u8> },
+    Close { sn: u64 },
+}
+
+impl StreamMessage {
+    fn sn(&self) -> u64 {
+        match self {
+            StreamMessage::Data { sn, .. } | StreamMessage::Close { sn } => *sn,
+        }
+    }
+}
+
+/// The errors of a [`StreamReceiver`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StreamError {
+    /// The stream has been closed by the sender, all its messages having been received.
+    Closed,
+    /// The message with this sequence number has been lost: it was evicted from the
+    /// retransmission cache of the sender before being received.
+    Overflow { sn: u64 },
+}
+
+impl fmt::Display for StreamError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StreamError::Closed => write!(f, "stream closed"),
+            StreamError::Overflow { sn } => write!(
+                f,
+                "message {sn} evicted from the retransmission cache before being received"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for StreamError {}
+
+/*************************************/
+/*              SENDER               */
+/*************************************/
+/// The builder of [`StreamSender`], allowing to configure it.
+pub struct StreamSenderBuilder<'a, 'b> {
+    session: &'a Session,
+    key_expr: ZResult<KeyExpr<'b>>,
+    cache_depth: usize,
+    close_timeout: Duration,
+}
+
+impl<'a, 'b> StreamSenderBuilder<'a, 'b> {
+    pub(crate) fn new(
+        session: &'a Session,
+        key_expr: ZResult<KeyExpr<'b>>,
+    ) -> StreamSenderBuilder<'a, 'b> {
+        StreamSenderBuilder {
+            session,
+            key_expr,
+            cache_depth: DEFAULT_CACHE_DEPTH,
+            close_timeout: DEFAULT_CLOSE_TIMEOUT,
+        }
+    }
+
+    /// Change the number of messages kept for retransmission.
+    pub fn cache_depth(mut self, depth: usize) -> Self {
+        self.cache_depth = depth;
+        self
+    }
+
+    /// Change how long [`StreamSender::close`] waits for the receiver to acknowledge the close.
+    pub fn close_timeout(mut self, timeout: Duration) -> Self {
+        self.close_timeout = timeout;
+        self
+    }
+}
+
+impl<'a> Resolvable for StreamSenderBuilder<'a, '_> {
+    type To = ZResult<StreamSender<'a>>;
+}
+
+impl SyncResolve for StreamSenderBuilder<'_, '_> {
+    fn res_sync(self) -> <Self as Resolvable>::To {
+        StreamSender::new(self)
+    }
+}
+
+impl<'a> AsyncResolve for StreamSenderBuilder<'a, '_> {
+    type Future = Ready<Self::To>;
+
+    fn res_async(self) -> Self::Future {
+        std::future::ready(self.res_sync())
+    }
+}
+
+struct SenderCache {
+    next_sn: u64,
+    depth: usize,
+    messages: VecDeque<(u64, Vec<u8>)>,
+}
+
+impl SenderCache {
+    fn push(&mut self, message: StreamMessage) -> Vec<u8> {
+        let buf = bincode::serialize(&message).unwrap();
+        self.next_sn += 1;
+        if self.depth > 0 {
+            if self.messages.len() >= self.depth {
+                self.messages.pop_front();
+            }
+            self.messages.push_back((message.sn(), buf.clone()));
+        }
+        buf
+    }
+
+    // The oldest sequence number still available for retransmission
+    fn oldest(&self) -> u64 {
+        self.messages
+            .front()
+            .map(|(sn, _)| *sn)
+            .unwrap_or(self.next_sn)
+    }
+
+    fn retransmit(&self, query: &Query) {
+        let params = match query.selector().parameters_stringmap() {
+            Ok(params) => params,
+            Err(e) => {
+                log::warn!("Invalid retransmission request {}: {}", query, e);
+                return;
+            }
+        };
+        let param = |name: &str, default: u64| {
+            params
+                .get(name)
+                .and_then(|v| v.parse::<u64>().ok())
+                .unwrap_or(default)
+        };
+        let from = param(FROM_PARAM, 0);
+        let to = param(TO_PARAM, u64::MAX);
+
+        let oldest = self.oldest();
+        if from < oldest {
+            // Tell the receiver which messages are still available
+            let value = Value::from(bincode::serialize(&oldest).unwrap());
+            if let Err(e) = query.reply(Err(value)).res_sync() {
+                log::warn!("Error replying to retransmission request: {}", e);
+            }
+        }
+        for (_, buf) in self
+            .messages
+            .iter()
+            .filter(|(sn, _)| (from..to).contains(sn))
+        {
+            let sample = Sample::new(query.key_expr().clone(), buf.clone());
+            if let Err(e) = query.reply(Ok(sample)).res_sync() {
+                log::warn!("Error replying to retransmission request: {}", e);
+            }
+        }
+    }
+}
+
+/// The sending side of a reliable ordered stream.
+///
+/// The messages are kept in a retransmission cache of [`cache_depth`](StreamSenderBuilder::cache_depth)
+/// messages, the loss of an evicted message being reported by the receiver as a
+/// [`StreamError::Overflow`].
+pub struct StreamSender<'a> {
+    session: &'a Session,
+    publisher: Publisher<'a>,
+    cache: Arc<Mutex<SenderCache>>,
+    _queryable: Queryable<'a, ()>,
+    closed_key_expr: KeyExpr<'static>,
+    close_timeout: Duration,
+}
+
+impl<'a> StreamSender<'a> {
+    fn new(conf: StreamSenderBuilder<'a, '_>) -> ZResult<StreamSender<'a>> {
+        let key_expr = conf.key_expr?;
+        if key_expr.is_wild() {
+            bail!(
+                "Stream key expression can not contain wildcards: {}",
+                key_expr
+            );
+        }
+        log::debug!(
+            "Create StreamSender on {} with cache_depth={}",
+            key_expr,
+            conf.cache_depth
+        );
+
+        let cache = Arc::new(Mutex::new(SenderCache {
+            next_sn: 0,
+            depth: conf.cache_depth,
+            messages: VecDeque::new(),
+        }));
+
+        // declare the queryable answering the retransmission requests from the cache
+        let c_cache = cache.clone();
+        let queryable = conf
+            .session
+            .declare_queryable(control_key_expr(&key_expr, RETRANSMIT_SUFFIX)?)
+            .callback(move |query| zlock!(c_cache).retransmit(&query))
+            .res_sync()?;
+
+        let closed_key_expr = control_key_expr(&key_expr, CLOSED_SUFFIX)?;
+        let publisher = conf
+            .session
+            .declare_publisher(key_expr.into_owned())
+            .congestion_control(CongestionControl::Block)
+            .res_sync()?;
+
+        Ok(StreamSender {
+            session: conf.session,
+            publisher,
+            cache,
+            _queryable: queryable,
+            closed_key_expr,
+            close_timeout: conf.close_timeout,
+        })
+    }
+
+    /// Send a message on the stream.
+    pub fn send<IntoPayload>(&self, payload: IntoPayload) -> impl Resolve<ZResult<()>> + '_
+    where
+        IntoPayload: Into<Vec<u8>>,
+    {
+        let payload = payload.into();
+        ResolveFuture::new(async move {
+            let buf = {
+                let mut cache = zlock!(self.cache);
+                let sn = cache.next_sn;
+                cache.push(StreamMessage::Data { sn, payload })
+            };
+            self.publisher.put(buf).res_async().await
+        })
+    }
+
+    /// Close the stream, waiting for the receiver to have received all the messages.
+    ///
+    /// The retransmission cache is kept until the receiver acknowledges the close, or until the
+    /// [`close_timeout`](StreamSenderBuilder::close_timeout) expires, returning an error.
+    pub fn close(self) -> impl Resolve<ZResult<()>> + 'a {
+        ResolveFuture::new(async move {
+            let buf = {
+                let mut cache = zlock!(self.cache);
+                let sn = cache.next_sn;
+                cache.push(StreamMessage::Close { sn })
+            };
+            self.publisher.put(buf).res_async().await?;
+
+            let deadline = Instant::now() + self.close_timeout;
+            let acknowledged = loop {
+                let replies = self
+                    .session
+                    .get(&self.closed_key_expr)
+                    .consolidation(ConsolidationMode::None)
+                    .timeout(DEFAULT_NACK_PERIOD)
+                    .res_async()
+                    .await?;
+                let mut acknowledged = false;
+                while let Ok(reply) = replies.recv_async().await {
+                    acknowledged |= reply.sample.is_ok();
+                }
+                if acknowledged || Instant::now() >= deadline {
+                    break acknowledged;
+                }
+                task::sleep(DEFAULT_NACK_PERIOD).await;
+            };
+
+            let StreamSender {
+                publisher,
+                _queryable,
+                close_timeout,
+                ..
+            } = self;
+            _queryable.undeclare().res_async().await?;
+            publisher.undeclare().res_async().await?;
+            if !acknowledged {
+                bail!(
+                    "Close of the stream not acknowledged by the receiver after {:?}",
+                    close_timeout
+                );
+            }
+            Ok(())
+        })
+    }
+
+    pub fn key_expr(&self) -> &KeyExpr<'a> {
+        self.publisher.key_expr()
+    }
+}
+
+/*************************************/
+/*             RECEIVER              */
+/*************************************/
+/// The builder of [`StreamReceiver`], allowing to configure it.
+pub struct StreamReceiverBuilder<'a, 'b> {
+    session: &'a Session,
+    key_expr: ZResult<KeyExpr<'b>>,
+    window: usize,
+    nack_period: Duration,
+}
+
+impl<'a, 'b> StreamReceiverBuilder<'a, 'b> {
+    pub(crate) fn new(
+        session: &'a Session,
+        key_expr: ZResult<KeyExpr<'b>>,
+    ) -> StreamReceiverBuilder<'a, 'b> {
+        StreamReceiverBuilder {
+            session,
+            key_expr,
+            window: DEFAULT_WINDOW,
+            nack_period: DEFAULT_NACK_PERIOD,
+        }
+    }
+
+    /// Change the number of messages received out of order that are kept for reordering.
+    pub fn window(mut self, window: usize) -> Self {
+        self.window = window.max(1);
+        self
+    }
+
+    /// Change the minimum period between two retransmission requests.
+    pub fn nack_period(mut self, period: Duration) -> Self {
+        self.nack_period = period;
+        self
+    }
+}
+
+impl<'a> Resolvable for StreamReceiverBuilder<'a, '_> {
+    type To = ZResult<StreamReceiver<'a>>;
+}
+
+impl SyncResolve for StreamReceiverBuilder<'_, '_> {
+    fn res_sync(self) -> <Self as Resolvable>::To {
+        StreamReceiver::new(self)
+    }
+}
+
+impl<'a> AsyncResolve for StreamReceiverBuilder<'a, '_> {
+    type Future = Ready<Self::To>;
+
+    fn res_async(self) -> Self::Future {
+        std::future::ready(self.res_sync())
+    }
+}
+
+struct ReceiverState {
+    next_sn: u64,
+    pending: BTreeMap<u64, StreamMessage>,
+    error: Option<StreamError>,
+    last_nack: Option<Instant>,
+    poll_period: Duration,
+}
+
+enum ReceiverEvent {
+    Sample(Sample),
+    Reply(Reply),
+}
+
+/// The receiving side of a reliable ordered stream.
+///
+/// The messages are received in order and without loss from the first message of the stream,
+/// so the receiver must be declared before the sender starts sending. The missing messages are
+/// requested to the sender while waiting in [`recv_async`](StreamReceiver::recv_async).
+pub struct StreamReceiver<'a> {
+    session: &'a Session,
+    subscriber: FlumeSubscriber<'a>,
+    retransmit_key_expr: KeyExpr<'static>,
+    replies: (flume::Sender<Reply>, flume::Receiver<Reply>),
+    state: Mutex<ReceiverState>,
+    window: usize,
+    nack_period: Duration,
+    closed: Arc<AtomicBool>,
+    _queryable: Queryable<'a, ()>,
+}
+
+impl<'a> StreamReceiver<'a> {
+    fn new(conf: StreamReceiverBuilder<'a, '_>) -> ZResult<StreamReceiver<'a>> {
+        let key_expr = conf.key_expr?;
+        if key_expr.is_wild() {
+            bail!(
+                "Stream key expression can not contain wildcards: {}",
+                key_expr
+            );
+        }
+        log::debug!(
+            "Create StreamReceiver on {} with window={}",
+            key_expr,
+            conf.window
+        );
+
+        // declare the queryable acknowledging the close of the stream
+        let closed = Arc::new(AtomicBool::new(false));
+        let c_closed = closed.clone();
+        let queryable = conf
+            .session
+            .declare_queryable(control_key_expr(&key_expr, CLOSED_SUFFIX)?)
+            .callback(move |query| {
+                if c_closed.load(Ordering::Acquire) {
+                    let sample = Sample::new(query.key_expr().clone(), vec![]);
+                    if let Err(e) = query.reply(Ok(sample)).res_sync() {
+                        log::warn!("Error acknowledging the close of the stream: {}", e);
+                    }
+                }
+            })
+            .res_sync()?;
+
+        let retransmit_key_expr = control_key_expr(&key_expr, RETRANSMIT_SUFFIX)?;
+        let subscriber = conf
+            .session
+            .declare_subscriber(key_expr.into_owned())
+            .reliable()
+            .res_sync()?;
+
+        Ok(StreamReceiver {
+            session: conf.session,
+            subscriber,
+            retransmit_key_expr,
+            replies: flume::unbounded(),
+            state: Mutex::new(ReceiverState {
+                next_sn: 0,
+                pending: BTreeMap::new(),
+                error: None,
+                last_nack: None,
+                poll_period: conf.nack_period,
+            }),
+            window: conf.window,
+            nack_period: conf.nack_period,
+            closed,
+            _queryable: queryable,
+        })
+    }
+
+    /// Receive the next message of the stream, waiting for it if needed.
+    ///
+    /// Returns [`StreamError::Closed`] once the whole stream has been received, or
+    /// [`StreamError::Overflow`] if a message has been lost.
+    pub async fn recv_async(&self) -> Result<Vec<u8>, StreamError> {
+        loop {
+            // Deliver the next message if already received
+            let nack = {
+                let mut state = zlock!(self.state);
+                if let Some(e) = state.error.as_ref() {
+                    return Err(e.clone());
+                }
+                let next_sn = state.next_sn;
+                match state.pending.remove(&next_sn) {
+                    Some(StreamMessage::Data { payload, .. }) => {
+                        state.next_sn += 1;
+                        return Ok(payload);
+                    }
+                    Some(StreamMessage::Close { .. }) => {
+                        state.next_sn += 1;
+                        state.error = Some(StreamError::Closed);
+                        self.closed.store(true, Ordering::Release);
+                        return Err(StreamError::Closed);
+                    }
+                    // Some messages are missing
+                    None if !state.pending.is_empty() => state
+                        .last_nack
+                        .map_or(true, |t| t.elapsed() >= self.nack_period),
+                    None => false,
+                }
+            };
+            if nack {
+                self.nack().await;
+            }
+
+            let period = zlock!(self.state).poll_period;
+            let event = async_std::future::timeout(period, async {
+                select!(
+                    sample = self.subscriber.recv_async() => sample.ok().map(ReceiverEvent::Sample),
+                    reply = self.replies.1.recv_async() => reply.ok().map(ReceiverEvent::Reply),
+                )
+            })
+            .await;
+
+            match event {
+                Ok(Some(ReceiverEvent::Sample(sample))) => self.handle(&sample.value),
+                Ok(Some(ReceiverEvent::Reply(reply))) => match reply.sample {
+                    Ok(sample) => self.handle(&sample.value),
+                    Err(value) => self.handle_evicted(&value),
+                },
+                Ok(None) => {}
+                Err(_) => {
+                    // Nothing received for a while: request the messages possibly lost at the
+                    // tail of the stream, backing off while the stream is idle.
+                    {
+                        let mut state = zlock!(self.state);
+                        state.poll_period = (state.poll_period * 2).min(MAX_POLL_PERIOD);
+                    }
+                    self.nack().await;
+                }
+            }
+        }
+    }
+
+    /// Receive the next message of the stream, blocking until it is available.
+    pub fn recv(&self) -> Result<Vec<u8>, StreamError> {
+        task::block_on(self.recv_async())
+    }
+
+    fn handle(&self, value: &Value) {
+        let message = match bincode::deserialize::<StreamMessage>(&value.payload.contiguous()) {
+            Ok(message) => message,
+            Err(e) => {
+                log::warn!("Invalid stream message: {}", e);
+                return;
+            }
+        };
+
+        let mut state = zlock!(self.state);
+        state.poll_period = self.nack_period;
+        let sn = message.sn();
+        // Drop the duplicates and the messages beyond the window, to be retransmitted later
+        if sn >= state.next_sn && sn - state.next_sn < self.window as u64 {
+            state.pending.entry(sn).or_insert(message);
+        }
+    }
+
+    fn handle_evicted(&self, value: &Value) {
+        match bincode::deserialize::<u64>(&value.payload.contiguous()) {
+            Ok(oldest) => {
+                let mut state = zlock!(self.state);
+                if state.error.is_none() && state.next_sn < oldest {
+                    log::warn!(
+                        "Stream message {} evicted from the retransmission cache",
+                        state.next_sn
+                    );
+                    state.error = Some(StreamError::Overflow { sn: state.next_sn });
+                }
+            }
+            Err(e) => log::warn!("Invalid retransmission reply: {}", e),
+        }
+    }
+
+    // Request the retransmission of the missing messages within the window
+    async fn nack(&self) {
+        let from = {
+            let mut state = zlock!(self.state);
+            state.last_nack = Some(Instant::now());
+            state.next_sn
+        };
+        let to = from.saturating_add(self.window as u64);
+        let parameters = format!("{FROM_PARAM}={from}&{TO_PARAM}={to}");
+        let selector = self
+            .retransmit_key_expr
+            .clone()
+            .with_parameters(&parameters);
+        let sender = self.replies.0.clone();
+        let res = self
+            .session
+            .get(selector)
+            .consolidation(ConsolidationMode::None)
+            .callback(move |reply| {
+                let _ = sender.send(reply);
+            })
+            .res_async()
+            .await;
+        if let Err(e) = res {
+            log::warn!(
+                "Error requesting the retransmission of stream messages: {}",
+                e
+            );
+        }
+    }
+
+    pub fn key_expr(&self) -> &KeyExpr<'static> {
+        self.subscriber.key_expr()
+    }
+
+    /// Close this StreamReceiver
+    #[inline]
+    pub fn close(self) -> impl Resolve<ZResult<()>> + 'a {
+        ResolveFuture::new(async move {
+            let StreamReceiver {
+                subscriber,
+                _queryable,
+                ..
+            } = self;
+            _queryable.undeclare().res_async().await?;
+            subscriber.undeclare().res_async().await?;
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,216 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use async_std::net::UdpSocket;
+use async_std::prelude::FutureExt;
+use async_std::task;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use zenoh::prelude::r#async::*;
+use zenoh_core::zasync_executor_init;
+use zenoh_ext::{SessionExt, StreamError};
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_secs(1);
+const MSG_COUNT: usize = 1_000;
+// One datagram out of DROP_EVERY is dropped by the lossy link
+const DROP_EVERY: usize = 5;
+
+macro_rules! ztimeout {
+    ($f:expr) => {
+        $f.timeout(TIMEOUT).await.unwrap()
+    };
+}
+
+// A UDP proxy between a single client and a server, dropping one datagram out of DROP_EVERY in
+// both directions once lossy.
+struct LossyLink {
+    lossy: Arc<AtomicBool>,
+}
+
+impl LossyLink {
+    async fn new(listen: SocketAddr, server: SocketAddr) -> LossyLink {
+        let front = Arc::new(UdpSocket::bind(listen).await.unwrap());
+        let back = Arc::new(UdpSocket::bind("127.0.0.1:0").await.unwrap());
+        back.connect(server).await.unwrap();
+
+        let lossy = Arc::new(AtomicBool::new(false));
+        let count = Arc::new(AtomicUsize::new(0));
+        let dropped = {
+            let lossy = lossy.clone();
+            move || {
+                lossy.load(Ordering::SeqCst)
+                    && count.fetch_add(1, Ordering::SeqCst) % DROP_EVERY == DROP_EVERY - 1
+            }
+        };
+        let client = Arc::new(Mutex::new(None));
+
+        let (c_front, c_back, c_client, c_dropped) =
+            (front.clone(), back.clone(), client.clone(), dropped.clone());
+        task::spawn(async move {
+            let mut buf = vec![0u8; 65_535];
+            while let Ok((n, addr)) = c_front.recv_from(&mut buf).await {
+                *c_client.lock().unwrap() = Some(addr);
+                if !c_dropped() {
+                    let _ = c_back.send(&buf[..n]).await;
+                }
+            }
+        });
+        task::spawn(async move {
+            let mut buf = vec![0u8; 65_535];
+            while let Ok(n) = back.recv(&mut buf).await {
+                let addr = *client.lock().unwrap();
+                if let Some(addr) = addr {
+                    if !dropped() {
+                        let _ = front.send_to(&buf[..n], addr).await;
+                    }
+                }
+            }
+        });
+
+        LossyLink { lossy }
+    }
+
+    fn set_lossy(&self, lossy: bool) {
+        self.lossy.store(lossy, Ordering::SeqCst);
+    }
+}
+
+// Open a receiver session listening on `port` and a sender session connected to it through a
+// lossy link listening on `proxy_port`.
+async fn open_sessions(port: u16, proxy_port: u16) -> (Session, Session, LossyLink) {
+    let server: SocketAddr = format!("127.0.0.1:{port}").parse().unwrap();
+    let listen: SocketAddr = format!("127.0.0.1:{proxy_port}").parse().unwrap();
+    let link = LossyLink::new(listen, server).await;
+
+    let mut config = config::peer();
+    config.listen.endpoints = vec![format!("udp/{server}").parse().unwrap()];
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    println!("[  ][01a] Opening receiver session: {server}");
+    let receiver = ztimeout!(zenoh::open(config).res_async()).unwrap();
+
+    let mut config = config::peer();
+    config.connect.endpoints = vec![format!("udp/{listen}").parse().unwrap()];
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    println!("[  ][02a] Opening sender session: {listen}");
+    let sender = ztimeout!(zenoh::open(config).res_async()).unwrap();
+
+    (receiver, sender, link)
+}
+
+async fn close_sessions(receiver: Session, sender: Session) {
+    println!("[  ][01d] Closing sender session");
+    ztimeout!(sender.close().res_async()).unwrap();
+    println!("[  ][02d] Closing receiver session");
+    ztimeout!(receiver.close().res_async()).unwrap();
+}
+
+#[test]
+fn stream_lossless() {
+    task::block_on(async {
+        zasync_executor_init!();
+        let _ = env_logger::try_init();
+
+        let (r_session, s_session, link) = open_sessions(17461, 17460).await;
+        task::sleep(SLEEP).await;
+
+        let key_expr = "test/stream/lossless";
+        let receiver = ztimeout!(r_session.declare_stream_receiver(key_expr).res_async()).unwrap();
+        let sender = ztimeout!(s_session.declare_stream_sender(key_expr).res_async()).unwrap();
+        task::sleep(SLEEP).await;
+
+        // All the messages are received in order despite the losses
+        link.set_lossy(true);
+        let send = async {
+            for i in 0..MSG_COUNT {
+                sender
+                    .send((i as u64).to_le_bytes().to_vec())
+                    .res_async()
+                    .await
+                    .unwrap();
+            }
+            println!("[SR][01b] Sent {MSG_COUNT} messages");
+            sender.close().res_async().await.unwrap();
+            println!("[SR][02b] Stream closed");
+        };
+        let recv = async {
+            for i in 0..MSG_COUNT {
+                let payload = receiver.recv_async().await.unwrap();
+                assert_eq!(payload, (i as u64).to_le_bytes().to_vec());
+            }
+            println!("[SR][01c] Received {MSG_COUNT} messages");
+            assert_eq!(receiver.recv_async().await, Err(StreamError::Closed));
+        };
+        ztimeout!(futures::future::join(send, recv));
+        link.set_lossy(false);
+
+        ztimeout!(receiver.close().res_async()).unwrap();
+        close_sessions(r_session, s_session).await;
+    });
+}
+
+#[test]
+fn stream_overflow() {
+    task::block_on(async {
+        zasync_executor_init!();
+        let _ = env_logger::try_init();
+
+        let (r_session, s_session, link) = open_sessions(17463, 17462).await;
+        task::sleep(SLEEP).await;
+
+        let key_expr = "test/stream/overflow";
+        let receiver = ztimeout!(r_session.declare_stream_receiver(key_expr).res_async()).unwrap();
+        let sender = ztimeout!(s_session
+            .declare_stream_sender(key_expr)
+            .cache_depth(1)
+            .close_timeout(SLEEP)
+            .res_async())
+        .unwrap();
+        task::sleep(SLEEP).await;
+
+        // The lost messages are evicted from the cache before being retransmitted
+        link.set_lossy(true);
+        for i in 0..MSG_COUNT / 10 {
+            ztimeout!(sender.send((i as u64).to_le_bytes().to_vec()).res_async()).unwrap();
+            // Send every message in its own datagram
+            task::sleep(Duration::from_millis(1)).await;
+        }
+        println!("[SO][01b] Sent {} messages", MSG_COUNT / 10);
+
+        let mut received = 0u64;
+        let err = ztimeout!(async {
+            loop {
+                match receiver.recv_async().await {
+                    Ok(payload) => {
+                        assert_eq!(payload, received.to_le_bytes().to_vec());
+                        received += 1;
+                    }
+                    Err(e) => break e,
+                }
+            }
+        });
+        println!("[SO][01c] Received {received} messages: {err}");
+        assert_eq!(err, StreamError::Overflow { sn: received });
+        // The error is sticky
+        assert_eq!(receiver.recv_async().await, Err(err));
+
+        // The receiver never acknowledges the close
+        assert!(ztimeout!(sender.close().res_async()).is_err());
+        link.set_lossy(false);
+
+        ztimeout!(receiver.close().res_async()).unwrap();
+        close_sessions(r_session, s_session).await;
+    });
+}
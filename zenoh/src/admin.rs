@@ -24,7 +24,7 @@ use std::{
     hash::{Hash, Hasher},
     sync::Arc,
 };
-use zenoh_core::SyncResolve;
+use zenoh_core::{zread, SyncResolve};
 use zenoh_protocol::{
     core::{Encoding, KnownEncoding, WireExpr},
     network::NetworkMessage,
@@ -44,6 +44,8 @@ lazy_static::lazy_static!(
     static ref KE_PREFIX: &'static keyexpr = ke_for_sure!("@/session");
     static ref KE_TRANSPORT_UNICAST: &'static keyexpr = ke_for_sure!("transport/unicast");
     static ref KE_LINK: &'static keyexpr = ke_for_sure!("link");
+    static ref KE_SUBSCRIBER: &'static keyexpr = ke_for_sure!("subscriber");
+    static ref KE_QUERYABLE: &'static keyexpr = ke_for_sure!("queryable");
 );
 
 pub(crate) fn init(session: &Session) {
@@ -91,7 +93,48 @@ pub(crate) fn on_admin_query(session: &Session, query: Query) {
         }
     }
 
+    // The subscribers and queryables, with the number of panics of their callback, only listed
+    // for the queries on the admin space, not to reply to the queries on `**`
+    fn reply_entity(
+        own_zid: &keyexpr,
+        query: &Query,
+        kind: &keyexpr,
+        id: usize,
+        value: serde_json::Value,
+    ) {
+        if !query.key_expr().as_str().starts_with('@') {
+            return;
+        }
+        if let Ok(id) = keyexpr::new(&id.to_string()) {
+            let key_expr = *KE_PREFIX / own_zid / kind / id;
+            if query.key_expr().intersects(&key_expr) {
+                let _ = query.reply(Ok(Sample::new(key_expr, value))).res_sync();
+            }
+        }
+    }
+
     if let Ok(own_zid) = keyexpr::new(&session.zid().to_string()) {
+        let (subscribers, queryables) = {
+            let state = zread!(session.state);
+            (
+                state.subscribers.values().cloned().collect::<Vec<_>>(),
+                state.queryables.values().cloned().collect::<Vec<_>>(),
+            )
+        };
+        for sub in subscribers {
+            let value = serde_json::json!({
+                "key_expr": sub.key_expr.as_str(),
+                "panics": sub.panics.total(),
+            });
+            reply_entity(own_zid, &query, *KE_SUBSCRIBER, sub.id, value);
+        }
+        for qable in queryables {
+            let value = serde_json::json!({
+                "key_expr": qable.key_expr.to_string(),
+                "panics": qable.panics.total(),
+            });
+            reply_entity(own_zid, &query, *KE_QUERYABLE, qable.id, value);
+        }
         for transport in task::block_on(session.runtime.manager().get_transports_unicast()) {
             if let Ok(peer) = transport.get_peer() {
                 reply_peer(own_zid, &query, peer);
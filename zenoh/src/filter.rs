@@ -0,0 +1,523 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! Value filters of the subscribers.
+//!
+//! A [`Filter`] is carried with the declaration of a subscriber, and evaluated by the first node
+//! routing the samples to it (a router or the publishing session): the samples it doesn't match
+//! are never sent to the subscriber. The nodes that don't support filters forward all the
+//! samples, the subscriber evaluating its filter on reception.
+//!
+//! Filters are written in the following language:
+//! ```text
+//! filter := and ("or" and)*
+//! and    := atom ("and" atom)*
+//! atom   := "(" filter ")" | "exists" field | field op number
+//! field  := "payload"
+//! op     := "<" | "<=" | ">" | ">=" | "==" | "!="
+//! ```
+//!
+//! A comparison reads the payload as a number for the `application/integer`,
+//! `application/float`, `text/plain`, `application/json` and `text/json` encodings, and doesn't
+//! match the samples whose payload isn't one. `exists payload` matches the samples with a
+//! non-empty payload. Deletions match every filter.
+//!
+//! ```
+//! use zenoh::filter::Filter;
+//! use zenoh::prelude::sync::*;
+//!
+//! let filter: Filter = "payload > 10 and payload <= 20".parse().unwrap();
+//! assert!(filter.matches(&Sample::try_from("demo/example", 15).unwrap()));
+//! assert!(!filter.matches(&Sample::try_from("demo/example", 5).unwrap()));
+//! assert!(!filter.matches(&Sample::try_from("demo/example", "not a number").unwrap()));
+//! ```
+use crate::buffers::ZBuf;
+use crate::prelude::{Encoding, KnownEncoding, SampleKind, SplitBuffer};
+use crate::sample::Sample;
+use std::fmt;
+use std::str::FromStr;
+use zenoh_result::{bail, Error, ZResult};
+
+/// The maximum length of a filter expression.
+const MAX_LEN: usize = 1_024;
+/// The maximum nesting of the parentheses of a filter expression.
+const MAX_DEPTH: usize = 16;
+/// The maximum length of a payload read as a number.
+const MAX_NUMBER_LEN: usize = 64;
+
+/// A field of the samples a [`Filter`] can test.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Field {
+    Payload,
+}
+
+impl fmt::Display for Field {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Field::Payload => write!(f, "payload"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Op {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+}
+
+impl Op {
+    fn apply(self, lhs: f64, rhs: f64) -> bool {
+        match self {
+            Op::Lt => lhs < rhs,
+            Op::Le => lhs <= rhs,
+            Op::Gt => lhs > rhs,
+            Op::Ge => lhs >= rhs,
+            Op::Eq => lhs == rhs,
+            Op::Ne => lhs != rhs,
+        }
+    }
+}
+
+impl fmt::Display for Op {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let op = match self {
+            Op::Lt => "<",
+            Op::Le => "<=",
+            Op::Gt => ">",
+            Op::Ge => ">=",
+            Op::Eq => "==",
+            Op::Ne => "!=",
+        };
+        write!(f, "{op}")
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Expr {
+    Or(Vec<Expr>),
+    And(Vec<Expr>),
+    Exists(Field),
+    Compare(Field, Op, f64),
+}
+
+impl Expr {
+    fn eval(&self, sample: &Fields) -> bool {
+        match self {
+            Expr::Or(exprs) => exprs.iter().any(|e| e.eval(sample)),
+            Expr::And(exprs) => exprs.iter().all(|e| e.eval(sample)),
+            Expr::Exists(Field::Payload) => !sample.payload.is_empty(),
+            Expr::Compare(Field::Payload, op, rhs) => match sample.number() {
+                Some(lhs) => op.apply(lhs, *rhs),
+                None => false,
+            },
+        }
+    }
+
+    fn compares(&self) -> bool {
+        match self {
+            Expr::Or(exprs) | Expr::And(exprs) => exprs.iter().any(Expr::compares),
+            Expr::Exists(_) => false,
+            Expr::Compare(..) => true,
+        }
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let join = |f: &mut fmt::Formatter<'_>, exprs: &[Expr], sep: &str| {
+            for (i, e) in exprs.iter().enumerate() {
+                if i > 0 {
+                    write!(f, " {sep} ")?;
+                }
+                match e {
+                    Expr::Or(_) | Expr::And(_) => write!(f, "({e})")?,
+                    _ => write!(f, "{e}")?,
+                }
+            }
+            Ok(())
+        };
+        match self {
+            Expr::Or(exprs) => join(f, exprs, "or"),
+            Expr::And(exprs) => join(f, exprs, "and"),
+            Expr::Exists(field) => write!(f, "exists {field}"),
+            Expr::Compare(field, op, rhs) => write!(f, "{field} {op} {rhs}"),
+        }
+    }
+}
+
+/// The fields of a sample, read lazily by the evaluation of a filter.
+struct Fields<'a> {
+    encoding: &'a Encoding,
+    payload: &'a ZBuf,
+}
+
+impl Fields<'_> {
+    fn number(&self) -> Option<f64> {
+        match self.encoding.prefix() {
+            KnownEncoding::AppInteger
+            | KnownEncoding::AppFloat
+            | KnownEncoding::TextPlain
+            | KnownEncoding::AppJson
+            | KnownEncoding::TextJson => {}
+            _ => return None,
+        }
+        if self.payload.len() > MAX_NUMBER_LEN {
+            return None;
+        }
+        let bytes = self.payload.contiguous();
+        std::str::from_utf8(&bytes)
+            .ok()?
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .filter(|n| n.is_finite())
+    }
+}
+
+/// A predicate over the values of the samples, see the [module documentation](self).
+#[derive(Clone, Debug, PartialEq)]
+pub struct Filter {
+    expr: Expr,
+}
+
+impl Filter {
+    /// Returns `true` if the given sample matches this filter.
+    pub fn matches(&self, sample: &Sample) -> bool {
+        match sample.kind {
+            SampleKind::Put => self
+                .evaluate(&sample.value.encoding, &sample.value.payload)
+                .unwrap_or(false),
+            SampleKind::Delete => true,
+        }
+    }
+
+    /// Evaluates this filter on the value of a put.
+    ///
+    /// Returns `None` when the filter compares the payload which can't be read as a number, and
+    /// doesn't match otherwise.
+    pub(crate) fn evaluate(&self, encoding: &Encoding, payload: &ZBuf) -> Option<bool> {
+        let fields = Fields { encoding, payload };
+        if self.expr.eval(&fields) {
+            Some(true)
+        } else if self.expr.compares() && fields.number().is_none() {
+            None
+        } else {
+            Some(false)
+        }
+    }
+
+    /// Returns a filter matching the samples matched by this filter or by `other`.
+    pub(crate) fn or(&self, other: &Filter) -> Filter {
+        let mut exprs: Vec<Expr> = vec![];
+        for expr in [&self.expr, &other.expr] {
+            let alternatives = match expr {
+                Expr::Or(e) => e.as_slice(),
+                e => std::slice::from_ref(e),
+            };
+            for e in alternatives {
+                if !exprs.contains(e) {
+                    exprs.push(e.clone());
+                }
+            }
+        }
+        Filter {
+            expr: Expr::Or(exprs),
+        }
+    }
+}
+
+impl fmt::Display for Filter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.expr.fmt(f)
+    }
+}
+
+impl FromStr for Filter {
+    type Err = Error;
+
+    fn from_str(s: &str) -> ZResult<Self> {
+        if s.len() > MAX_LEN {
+            bail!("Filter longer than {} bytes", MAX_LEN);
+        }
+        let mut parser = Parser {
+            tokens: tokenize(s)?,
+            pos: 0,
+            depth: 0,
+        };
+        let expr = parser.or()?;
+        match parser.tokens.get(parser.pos) {
+            None => Ok(Filter { expr }),
+            Some(token) => bail!("Unexpected `{}` in filter `{}`", token, s),
+        }
+    }
+}
+
+impl TryFrom<&str> for Filter {
+    type Error = Error;
+
+    fn try_from(s: &str) -> ZResult<Self> {
+        s.parse()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Word(String),
+    Number(f64),
+    Op(Op),
+    Open,
+    Close,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Word(w) => write!(f, "{w}"),
+            Token::Number(n) => write!(f, "{n}"),
+            Token::Op(op) => write!(f, "{op}"),
+            Token::Open => write!(f, "("),
+            Token::Close => write!(f, ")"),
+        }
+    }
+}
+
+fn tokenize(s: &str) -> ZResult<Vec<Token>> {
+    let mut tokens = vec![];
+    let mut chars = s.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            '(' => Token::Open,
+            ')' => Token::Close,
+            '<' | '>' | '=' | '!' => {
+                let eq = chars.next_if(|(_, c)| *c == '=').is_some();
+                Token::Op(match (c, eq) {
+                    ('<', false) => Op::Lt,
+                    ('<', true) => Op::Le,
+                    ('>', false) => Op::Gt,
+                    ('>', true) => Op::Ge,
+                    ('=', true) => Op::Eq,
+                    ('!', true) => Op::Ne,
+                    _ => bail!("Invalid operator `{}` in filter `{}`", c, s),
+                })
+            }
+            c if c.is_ascii_alphabetic() => {
+                let mut end = start + c.len_utf8();
+                while let Some((i, c)) =
+                    chars.next_if(|(_, c)| c.is_ascii_alphanumeric() || *c == '_')
+                {
+                    end = i + c.len_utf8();
+                }
+                Token::Word(s[start..end].to_string())
+            }
+            c if c.is_ascii_digit() || c == '-' || c == '+' || c == '.' => {
+                let mut end = start + c.len_utf8();
+                let mut prev = c;
+                while let Some(&(i, c)) = chars.peek() {
+                    let exponent_sign = (c == '-' || c == '+') && (prev == 'e' || prev == 'E');
+                    if !(c.is_ascii_alphanumeric() || c == '.' || exponent_sign) {
+                        break;
+                    }
+                    chars.next();
+                    end = i + c.len_utf8();
+                    prev = c;
+                }
+                let number = &s[start..end];
+                match number.parse::<f64>() {
+                    Ok(n) if n.is_finite() => Token::Number(n),
+                    _ => bail!("Invalid number `{}` in filter `{}`", number, s),
+                }
+            }
+            c => bail!("Unexpected `{}` in filter `{}`", c, s),
+        };
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn next_if_word(&mut self, word: &str) -> bool {
+        match self.tokens.get(self.pos) {
+            Some(Token::Word(w)) if w == word => {
+                self.pos += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn or(&mut self) -> ZResult<Expr> {
+        let mut exprs = vec![self.and()?];
+        while self.next_if_word("or") {
+            exprs.push(self.and()?);
+        }
+        Ok(match exprs.len() {
+            1 => exprs.pop().unwrap(),
+            _ => Expr::Or(exprs),
+        })
+    }
+
+    fn and(&mut self) -> ZResult<Expr> {
+        let mut exprs = vec![self.atom()?];
+        while self.next_if_word("and") {
+            exprs.push(self.atom()?);
+        }
+        Ok(match exprs.len() {
+            1 => exprs.pop().unwrap(),
+            _ => Expr::And(exprs),
+        })
+    }
+
+    fn atom(&mut self) -> ZResult<Expr> {
+        match self.next() {
+            Some(Token::Open) => {
+                self.depth += 1;
+                if self.depth > MAX_DEPTH {
+                    bail!("Filter nested deeper than {} parentheses", MAX_DEPTH);
+                }
+                let expr = self.or()?;
+                match self.next() {
+                    Some(Token::Close) => {
+                        self.depth -= 1;
+                        Ok(expr)
+                    }
+                    _ => bail!("Missing `)` in filter"),
+                }
+            }
+            Some(Token::Word(w)) if w == "exists" => Ok(Expr::Exists(self.field()?)),
+            Some(Token::Word(w)) => {
+                self.pos -= 1;
+                let field = self.field()?;
+                let op = match self.next() {
+                    Some(Token::Op(op)) => op,
+                    Some(token) => bail!("Expected an operator after `{}`, found `{}`", w, token),
+                    None => bail!("Expected an operator after `{}`", w),
+                };
+                match self.next() {
+                    Some(Token::Number(n)) => Ok(Expr::Compare(field, op, n)),
+                    Some(token) => bail!("Expected a number after `{}`, found `{}`", op, token),
+                    None => bail!("Expected a number after `{}`", op),
+                }
+            }
+            Some(token) => bail!("Unexpected `{}` in filter", token),
+            None => bail!("Unexpected end of filter"),
+        }
+    }
+
+    fn field(&mut self) -> ZResult<Field> {
+        match self.next() {
+            Some(Token::Word(w)) if w == "payload" => Ok(Field::Payload),
+            Some(token) => bail!("Unknown field `{}` in filter", token),
+            None => bail!("Expected a field at the end of filter"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::Value;
+
+    fn eval(filter: &str, value: impl Into<Value>) -> Option<bool> {
+        let value: Value = value.into();
+        filter
+            .parse::<Filter>()
+            .unwrap()
+            .evaluate(&value.encoding, &value.payload)
+    }
+
+    #[test]
+    fn filter_parse() {
+        for (filter, display) in [
+            ("payload > 10", "payload > 10"),
+            ("payload>=-1.5e3", "payload >= -1500"),
+            (
+                "payload > 1 and payload < 3 or exists payload",
+                "(payload > 1 and payload < 3) or exists payload",
+            ),
+            (
+                "payload == 1 and (payload != 2 or payload <= 3)",
+                "payload == 1 and (payload != 2 or payload <= 3)",
+            ),
+        ] {
+            let parsed: Filter = filter.parse().unwrap();
+            assert_eq!(parsed.to_string(), display);
+            assert_eq!(display.parse::<Filter>().unwrap(), parsed);
+        }
+
+        for filter in [
+            "",
+            "payload",
+            "payload >",
+            "payload > a",
+            "payload = 1",
+            "value > 1",
+            "payload > 1 and",
+            "(payload > 1",
+            "payload > 1)",
+            "payload > 1e999",
+            "exists",
+        ] {
+            assert!(filter.parse::<Filter>().is_err(), "{filter}");
+        }
+        let nested = format!("{}payload > 1{}", "(".repeat(64), ")".repeat(64));
+        assert!(nested.parse::<Filter>().is_err());
+        let long = vec!["payload > 1"; 128].join(" or ");
+        assert!(long.parse::<Filter>().is_err());
+    }
+
+    #[test]
+    fn filter_evaluate() {
+        assert_eq!(eval("payload > 10", 11), Some(true));
+        assert_eq!(eval("payload > 10", 10), Some(false));
+        assert_eq!(eval("payload > 10", 10.5), Some(true));
+        assert_eq!(eval("payload < 0", " -3 "), Some(true));
+        assert_eq!(eval("payload > 10 or exists payload", "text"), Some(true));
+        assert_eq!(eval("exists payload", ""), Some(false));
+
+        // Payloads which can't be read as numbers don't match, and are reported
+        assert_eq!(eval("payload > 10", "text"), None);
+        assert_eq!(eval("payload > 10", vec![0xffu8, 0xfe]), None);
+        assert_eq!(eval("payload > 10", "1".repeat(128)), None);
+        assert_eq!(eval("payload > 10", "NaN"), None);
+        assert_eq!(eval("payload > 10 and exists payload", ""), None);
+
+        let filter: Filter = "payload > 10".parse().unwrap();
+        let or = filter.or(&"payload < 0".parse().unwrap());
+        assert_eq!(or.to_string(), "payload > 10 or payload < 0");
+        assert_eq!(or.or(&filter), or);
+        let v: Value = (-1).into();
+        assert_eq!(or.evaluate(&v.encoding, &v.payload), Some(true));
+        assert!(filter.matches(&Sample::try_from("test/filter", 11).unwrap()));
+        let mut delete = Sample::try_from("test/filter", 0).unwrap();
+        delete.kind = SampleKind::Delete;
+        assert!(filter.matches(&delete));
+    }
+}
@@ -86,3 +86,59 @@ pub fn locked<T>(fnmut: impl FnMut(T)) -> impl Fn(T) {
     let lock = std::sync::Mutex::new(fnmut);
     move |x| zlock!(lock)(x)
 }
+
+/// Calls `f`, containing its panic: the panic is logged for `entity` and `None` is returned.
+///
+/// The callers don't leave their own state inconsistent across the call, e.g. the session
+/// state lock is released, so that a panic can only leave the state of the callback itself
+/// inconsistent: the callbacks are asserted unwind safe. With `panic = "abort"` nothing is
+/// contained.
+pub(crate) fn contain<R>(entity: impl std::fmt::Display, f: impl FnOnce() -> R) -> Option<R> {
+    match std::panic::catch_unwind(std::panic::AssertUnwindSafe(f)) {
+        Ok(r) => Some(r),
+        Err(e) => {
+            match e
+                .downcast_ref::<String>()
+                .map(|s| s.as_str())
+                .or_else(|| e.downcast_ref::<&str>().copied())
+            {
+                Some(e) => log::error!("The callback of {} panicked: {}", entity, e),
+                None => log::error!(
+                    "The callback of {} panicked. The panic message couldn't be recovered.",
+                    entity
+                ),
+            }
+            None
+        }
+    }
+}
+
+/// The panics of the callback of a subscriber or a queryable, contained with [`contain`].
+#[derive(Debug, Default)]
+pub(crate) struct CallbackPanics {
+    total: std::sync::atomic::AtomicU64,
+    consecutive: std::sync::atomic::AtomicUsize,
+}
+
+impl CallbackPanics {
+    /// Calls `f`, containing and counting its panic. Returns the number of consecutive panics,
+    /// 0 if `f` returned.
+    pub(crate) fn call(&self, entity: impl std::fmt::Display, f: impl FnOnce()) -> usize {
+        use std::sync::atomic::Ordering;
+        match contain(entity, f) {
+            Some(()) => {
+                self.consecutive.store(0, Ordering::Relaxed);
+                0
+            }
+            None => {
+                self.total.fetch_add(1, Ordering::Relaxed);
+                self.consecutive.fetch_add(1, Ordering::Relaxed) + 1
+            }
+        }
+    }
+
+    /// The total number of panics.
+    pub(crate) fn total(&self) -> u64 {
+        self.total.load(std::sync::atomic::Ordering::Relaxed)
+    }
+}
@@ -13,11 +13,17 @@
 //
 
 //! Tools to access information about the current zenoh [`Session`](crate::Session).
+use crate::net::runtime::Runtime;
+pub use crate::net::runtime::{TopologyEvent, TopologySnapshot, TopologySubscriber};
 use crate::SessionRef;
 use async_std::task;
+use futures::future::BoxFuture;
 use std::future::Ready;
 use zenoh_core::{AsyncResolve, Resolvable, SyncResolve};
-use zenoh_protocol::core::{WhatAmI, ZenohId};
+use zenoh_protocol::core::{EndPoint, Locator, WhatAmI, ZenohId};
+pub use zenoh_protocol::transport::close::CloseReason;
+pub use zenoh_transport::TransportCloseReason;
+use zenoh_transport::TransportPeer;
 
 /// A builder retuned by [`SessionInfo::zid()`](SessionInfo::zid) that allows
 /// to access the [`ZenohId`] of the current zenoh [`Session`](crate::Session).
@@ -143,6 +149,193 @@ impl<'a> AsyncResolve for PeersZidBuilder<'a> {
     }
 }
 
+/// A zenoh node this process is currently connected to, as returned by
+/// [`SessionInfo::routers()`](SessionInfo::routers) and [`SessionInfo::peers()`](SessionInfo::peers).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransportInfo {
+    /// The [`ZenohId`] of the node.
+    pub zid: ZenohId,
+    /// The [`WhatAmI`] of the node.
+    pub whatami: WhatAmI,
+    /// The locators of the node the links of the transport are connected to.
+    pub locators: Vec<Locator>,
+}
+
+impl From<TransportPeer> for TransportInfo {
+    fn from(peer: TransportPeer) -> Self {
+        TransportInfo {
+            zid: peer.zid,
+            whatami: peer.whatami,
+            locators: peer.links.into_iter().map(|link| link.dst).collect(),
+        }
+    }
+}
+
+/// A change of the connectivity of the current zenoh [`Session`](crate::Session) with a zenoh
+/// node, see [`Session::on_connectivity_event()`](crate::Session::on_connectivity_event).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConnectivityEvent {
+    /// A transport was opened with the node.
+    Connected { zid: ZenohId, whatami: WhatAmI },
+    /// The transport with the node was closed.
+    Disconnected {
+        zid: ZenohId,
+        whatami: WhatAmI,
+        reason: TransportCloseReason,
+    },
+    /// The node listening on the endpoint rejected the transport opened to it.
+    Rejected {
+        endpoint: EndPoint,
+        reason: TransportCloseReason,
+    },
+}
+
+impl ConnectivityEvent {
+    /// The reason of the close or of the rejection of the transport, if any.
+    pub fn reason(&self) -> Option<&TransportCloseReason> {
+        match self {
+            ConnectivityEvent::Connected { .. } => None,
+            ConnectivityEvent::Disconnected { reason, .. }
+            | ConnectivityEvent::Rejected { reason, .. } => Some(reason),
+        }
+    }
+}
+
+async fn transports_info(runtime: Runtime, whatami: WhatAmI) -> Vec<TransportInfo> {
+    let manager = runtime.manager();
+    let unicast = manager
+        .get_transports_unicast()
+        .await
+        .into_iter()
+        .filter_map(|t| t.get_peer().ok());
+    let multicast = manager
+        .get_transports_multicast()
+        .await
+        .into_iter()
+        .filter_map(|t| t.get_peers().ok())
+        .flatten();
+    unicast
+        .chain(multicast)
+        .filter(|peer| peer.whatami == whatami)
+        .map(TransportInfo::from)
+        .collect()
+}
+
+/// A builder returned by [`SessionInfo::routers()`](SessionInfo::routers) that allows
+/// to access the [`TransportInfo`] of the zenoh routers this process is currently connected to.
+///
+/// # Examples
+/// ```
+/// # async_std::task::block_on(async {
+/// use zenoh::prelude::r#async::*;
+///
+/// let session = zenoh::open(config::peer()).res().await.unwrap();
+/// for router in session.info().routers().res().await {
+///     println!("{} {:?}", router.zid, router.locators);
+/// }
+/// # })
+/// ```
+pub struct RoutersBuilder<'a> {
+    pub(crate) session: SessionRef<'a>,
+}
+
+impl<'a> Resolvable for RoutersBuilder<'a> {
+    type To = Vec<TransportInfo>;
+}
+
+impl<'a> SyncResolve for RoutersBuilder<'a> {
+    fn res_sync(self) -> Self::To {
+        task::block_on(self.res_async())
+    }
+}
+
+impl<'a> AsyncResolve for RoutersBuilder<'a> {
+    type Future = BoxFuture<'static, Self::To>;
+
+    fn res_async(self) -> Self::Future {
+        Box::pin(transports_info(
+            self.session.runtime.clone(),
+            WhatAmI::Router,
+        ))
+    }
+}
+
+/// A builder returned by [`SessionInfo::peers()`](SessionInfo::peers) that allows
+/// to access the [`TransportInfo`] of the zenoh peers this process is currently connected to.
+///
+/// # Examples
+/// ```
+/// # async_std::task::block_on(async {
+/// use zenoh::prelude::r#async::*;
+///
+/// let session = zenoh::open(config::peer()).res().await.unwrap();
+/// for peer in session.info().peers().res().await {
+///     println!("{} {:?}", peer.zid, peer.locators);
+/// }
+/// # })
+/// ```
+pub struct PeersBuilder<'a> {
+    pub(crate) session: SessionRef<'a>,
+}
+
+impl<'a> Resolvable for PeersBuilder<'a> {
+    type To = Vec<TransportInfo>;
+}
+
+impl<'a> SyncResolve for PeersBuilder<'a> {
+    fn res_sync(self) -> Self::To {
+        task::block_on(self.res_async())
+    }
+}
+
+impl<'a> AsyncResolve for PeersBuilder<'a> {
+    type Future = BoxFuture<'static, Self::To>;
+
+    fn res_async(self) -> Self::Future {
+        Box::pin(transports_info(self.session.runtime.clone(), WhatAmI::Peer))
+    }
+}
+
+/// A builder returned by [`SessionInfo::locators()`](SessionInfo::locators) that allows
+/// to access the [`Locator`]s the current zenoh [`Session`](crate::Session) is listening on.
+///
+/// # Examples
+/// ```
+/// # async_std::task::block_on(async {
+/// use zenoh::prelude::r#async::*;
+///
+/// let session = zenoh::open(config::peer()).res().await.unwrap();
+/// let locators = session.info().locators().res().await;
+/// # })
+/// ```
+pub struct LocatorsBuilder<'a> {
+    pub(crate) session: SessionRef<'a>,
+}
+
+impl<'a> Resolvable for LocatorsBuilder<'a> {
+    type To = Vec<Locator>;
+}
+
+impl<'a> SyncResolve for LocatorsBuilder<'a> {
+    fn res_sync(self) -> Self::To {
+        task::block_on(self.res_async())
+    }
+}
+
+impl<'a> AsyncResolve for LocatorsBuilder<'a> {
+    type Future = BoxFuture<'static, Self::To>;
+
+    fn res_async(self) -> Self::Future {
+        let runtime = self.session.runtime.clone();
+        Box::pin(async move {
+            let manager = runtime.manager();
+            let mut locators = manager.get_locators_unicast().await;
+            locators.extend(manager.get_locators_multicast().await);
+            locators
+        })
+    }
+}
+
 /// Struct returned by [`Session::info()`](crate::Session::info) which allows
 /// to access informations about the current zenoh [`Session`](crate::Session).
 ///
@@ -214,4 +407,75 @@ impl SessionInfo<'_> {
             session: self.session.clone(),
         }
     }
+
+    /// Return the [`TransportInfo`] of the zenoh routers this process is currently connected to.
+    ///
+    /// # Examples
+    /// ```
+    /// # async_std::task::block_on(async {
+    /// use zenoh::prelude::r#async::*;
+    ///
+    /// let session = zenoh::open(config::peer()).res().await.unwrap();
+    /// let routers = session.info().routers().res().await;
+    /// # })
+    /// ```
+    pub fn routers(&self) -> RoutersBuilder<'_> {
+        RoutersBuilder {
+            session: self.session.clone(),
+        }
+    }
+
+    /// Return the [`TransportInfo`] of the zenoh peers this process is currently connected to.
+    ///
+    /// # Examples
+    /// ```
+    /// # async_std::task::block_on(async {
+    /// use zenoh::prelude::r#async::*;
+    ///
+    /// let session = zenoh::open(config::peer()).res().await.unwrap();
+    /// let peers = session.info().peers().res().await;
+    /// # })
+    /// ```
+    pub fn peers(&self) -> PeersBuilder<'_> {
+        PeersBuilder {
+            session: self.session.clone(),
+        }
+    }
+
+    /// Return the [`Locator`]s the current zenoh [`Session`](crate::Session) is listening on.
+    ///
+    /// The locators are the ones actually bound: the ports chosen by the system and the
+    /// addresses of the interfaces matched by unspecified addresses are reported.
+    ///
+    /// # Examples
+    /// ```
+    /// # async_std::task::block_on(async {
+    /// use zenoh::prelude::r#async::*;
+    ///
+    /// let session = zenoh::open(config::peer()).res().await.unwrap();
+    /// let locators = session.info().locators().res().await;
+    /// # })
+    /// ```
+    pub fn locators(&self) -> LocatorsBuilder<'_> {
+        LocatorsBuilder {
+            session: self.session.clone(),
+        }
+    }
+
+    /// Return the number of received samples whose payload didn't match their integrity checksum.
+    ///
+    /// The counter is shared by the sessions of the same runtime.
+    ///
+    /// # Examples
+    /// ```
+    /// # async_std::task::block_on(async {
+    /// use zenoh::prelude::r#async::*;
+    ///
+    /// let session = zenoh::open(config::peer()).res().await.unwrap();
+    /// let corrupted = session.info().corrupted_samples();
+    /// # })
+    /// ```
+    pub fn corrupted_samples(&self) -> u64 {
+        self.session.runtime.integrity.corrupted()
+    }
 }
@@ -0,0 +1,163 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use crate::config::{unwrap_or_default, Config};
+use crate::sample::SampleIntegrity;
+use crc::{Crc, CRC_32_ISCSI};
+use std::fmt;
+use std::hash::Hasher;
+use std::sync::atomic::{AtomicU64, Ordering};
+use twox_hash::XxHash64;
+use zenoh_buffers::ZBuf;
+use zenoh_config::{IntegrityAlgorithm, IntegrityMismatch};
+use zenoh_protocol::zenoh::put::ext::IntegrityType;
+
+const CRC32C: Crc<u32> = Crc::<u32>::new(&CRC_32_ISCSI);
+
+/// The end-to-end integrity checksums of the payloads, computed by the publishers and
+/// verified by the receiving sessions.
+pub(crate) struct Integrity {
+    // The algorithm of the checksums attached to the puts, None if disabled
+    algorithm: Option<IntegrityAlgorithm>,
+    on_mismatch: IntegrityMismatch,
+    corrupted: AtomicU64,
+}
+
+impl Integrity {
+    pub(crate) fn from_config(config: &Config) -> Self {
+        Integrity {
+            algorithm: unwrap_or_default!(config.integrity().enabled())
+                .then(|| unwrap_or_default!(config.integrity().algorithm())),
+            on_mismatch: unwrap_or_default!(config.integrity().on_mismatch()),
+            corrupted: AtomicU64::new(0),
+        }
+    }
+
+    /// The checksum to attach to a put of `payload`, if enabled.
+    pub(crate) fn checksum(&self, payload: &ZBuf) -> Option<IntegrityType> {
+        self.algorithm.map(|algorithm| match algorithm {
+            IntegrityAlgorithm::Crc32c => IntegrityType {
+                algorithm: IntegrityType::CRC32C,
+                checksum: crc32c(payload),
+            },
+            IntegrityAlgorithm::Xxhash64 => IntegrityType {
+                algorithm: IntegrityType::XXHASH64,
+                checksum: xxhash64(payload),
+            },
+        })
+    }
+
+    /// Verifies the checksum received with `payload`.
+    ///
+    /// Returns None if the sample must be dropped.
+    pub(crate) fn verify(
+        &self,
+        key: &dyn fmt::Display,
+        ext: Option<&IntegrityType>,
+        payload: &ZBuf,
+    ) -> Option<SampleIntegrity> {
+        let ext = match ext {
+            Some(ext) => ext,
+            None => return Some(SampleIntegrity::Unchecked),
+        };
+        let checksum = match ext.algorithm {
+            IntegrityType::CRC32C => crc32c(payload),
+            IntegrityType::XXHASH64 => xxhash64(payload),
+            algorithm => {
+                log::debug!("Unknown integrity algorithm {algorithm} for sample on {key}");
+                return Some(SampleIntegrity::Unchecked);
+            }
+        };
+        if checksum == ext.checksum {
+            return Some(SampleIntegrity::Verified);
+        }
+        let corrupted = self.corrupted.fetch_add(1, Ordering::Relaxed) + 1;
+        match self.on_mismatch {
+            IntegrityMismatch::Drop => {
+                log::warn!(
+                    "Alarm: dropped corrupted sample on {key} (checksum mismatch, {corrupted} corrupted samples so far)"
+                );
+                None
+            }
+            IntegrityMismatch::Flag => {
+                log::warn!(
+                    "Alarm: received corrupted sample on {key} (checksum mismatch, {corrupted} corrupted samples so far)"
+                );
+                Some(SampleIntegrity::Corrupted)
+            }
+        }
+    }
+
+    /// The number of received samples whose payload didn't match their checksum.
+    pub(crate) fn corrupted(&self) -> u64 {
+        self.corrupted.load(Ordering::Relaxed)
+    }
+}
+
+fn crc32c(payload: &ZBuf) -> u64 {
+    let mut digest = CRC32C.digest();
+    for slice in payload.zslices() {
+        digest.update(slice.as_slice());
+    }
+    digest.finalize() as u64
+}
+
+fn xxhash64(payload: &ZBuf) -> u64 {
+    let mut hasher = XxHash64::with_seed(0);
+    for slice in payload.zslices() {
+        hasher.write(slice.as_slice());
+    }
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn integrity_bit_flip() {
+        for (algorithm, on_mismatch) in [
+            (IntegrityAlgorithm::Crc32c, IntegrityMismatch::Drop),
+            (IntegrityAlgorithm::Xxhash64, IntegrityMismatch::Flag),
+        ] {
+            let integrity = Integrity {
+                algorithm: Some(algorithm),
+                on_mismatch,
+                corrupted: AtomicU64::new(0),
+            };
+            let payload = ZBuf::from(vec![0xa5u8; 1024]);
+            let ext = integrity.checksum(&payload);
+            assert!(ext.is_some());
+            assert_eq!(
+                integrity.verify(&"test", ext.as_ref(), &payload),
+                Some(SampleIntegrity::Verified)
+            );
+
+            let mut corrupted = vec![0xa5u8; 1024];
+            corrupted[512] ^= 0x01;
+            let expected = match on_mismatch {
+                IntegrityMismatch::Drop => None,
+                IntegrityMismatch::Flag => Some(SampleIntegrity::Corrupted),
+            };
+            assert_eq!(
+                integrity.verify(&"test", ext.as_ref(), &ZBuf::from(corrupted)),
+                expected
+            );
+            assert_eq!(integrity.corrupted(), 1);
+            assert_eq!(
+                integrity.verify(&"test", None, &payload),
+                Some(SampleIntegrity::Unchecked)
+            );
+        }
+    }
+}
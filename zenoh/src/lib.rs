@@ -86,6 +86,7 @@ use net::runtime::Runtime;
 use prelude::*;
 use scouting::ScoutBuilder;
 use std::future::Ready;
+use std::time::Duration;
 use zenoh_core::{AsyncResolve, Resolvable, SyncResolve};
 pub use zenoh_macros::{kedefine, keformat, kewrite};
 use zenoh_protocol::core::WhatAmIMatcher;
@@ -99,6 +100,7 @@ pub use zenoh_result::ZResult as Result;
 const GIT_VERSION: &str = git_version!(prefix = "v", cargo_prefix = "v");
 
 mod admin;
+mod integrity;
 #[macro_use]
 mod session;
 pub use session::*;
@@ -109,6 +111,7 @@ pub use net::runtime;
 pub mod selector;
 #[deprecated = "This module is now a separate crate. Use the crate directly for shorter compile-times"]
 pub use zenoh_config as config;
+pub mod filter;
 pub mod handlers;
 pub mod info;
 #[cfg(feature = "unstable")]
@@ -128,22 +131,10 @@ pub use zenoh_shm as shm;
 /// reading and writing data.
 pub use zenoh_buffers as buffers;
 
-/// Time related types and functions.
-pub mod time {
-    use std::convert::TryFrom;
+pub mod time;
 
-    pub use zenoh_protocol::core::{Timestamp, TimestampId, NTP64};
-
-    /// Generates a reception [`Timestamp`] with id=0x01.  
-    /// This operation should be called if a timestamp is required for an incoming [`zenoh::Sample`](crate::Sample)
-    /// that doesn't contain any timestamp.
-    pub fn new_reception_timestamp() -> Timestamp {
-        use std::time::{SystemTime, UNIX_EPOCH};
-
-        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
-        Timestamp::new(now.into(), TimestampId::try_from([1]).unwrap())
-    }
-}
+/// Runtime control of the log filter, exposed in the admin space under `@/router/<zid>/logger`.
+pub use zenoh_util::logging;
 
 /// A map of key/value (String,String) properties.
 pub mod properties {
@@ -239,7 +230,10 @@ where
     TryIntoConfig: std::convert::TryInto<crate::config::Config> + Send + 'static,
     <TryIntoConfig as std::convert::TryInto<crate::config::Config>>::Error: std::fmt::Debug,
 {
-    OpenBuilder { config }
+    OpenBuilder {
+        config,
+        timeout: None,
+    }
 }
 
 /// A builder returned by [`open`] used to open a zenoh [`Session`].
@@ -259,6 +253,35 @@ where
     <TryIntoConfig as std::convert::TryInto<crate::config::Config>>::Error: std::fmt::Debug,
 {
     config: TryIntoConfig,
+    timeout: Option<Duration>,
+}
+
+impl<TryIntoConfig> OpenBuilder<TryIntoConfig>
+where
+    TryIntoConfig: std::convert::TryInto<crate::config::Config> + Send + 'static,
+    <TryIntoConfig as std::convert::TryInto<crate::config::Config>>::Error: std::fmt::Debug,
+{
+    /// Fails to open the [`Session`] if it isn't open within `timeout`, e.g. while connecting to
+    /// the endpoints required by the `connect/required` configuration.
+    ///
+    /// # Examples
+    /// ```
+    /// # async_std::task::block_on(async {
+    /// use std::time::Duration;
+    /// use zenoh::prelude::r#async::*;
+    ///
+    /// let session = zenoh::open(config::peer())
+    ///     .timeout(Duration::from_secs(5))
+    ///     .res()
+    ///     .await
+    ///     .unwrap();
+    /// # })
+    /// ```
+    #[inline]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
 }
 
 impl<TryIntoConfig> Resolvable for OpenBuilder<TryIntoConfig>
@@ -279,7 +302,7 @@ where
             .config
             .try_into()
             .map_err(|e| zerror!("Invalid Zenoh configuration {:?}", &e))?;
-        Session::new(config).res_sync()
+        Session::new(config, self.timeout).res_sync()
     }
 }
 
@@ -304,6 +327,7 @@ pub fn init(runtime: Runtime) -> InitBuilder {
         runtime,
         aggregated_subscribers: vec![],
         aggregated_publishers: vec![],
+        namespace: None,
     }
 }
 
@@ -314,6 +338,7 @@ pub struct InitBuilder {
     runtime: Runtime,
     aggregated_subscribers: Vec<OwnedKeyExpr>,
     aggregated_publishers: Vec<OwnedKeyExpr>,
+    namespace: Option<OwnedKeyExpr>,
 }
 
 #[zenoh_macros::unstable]
@@ -329,6 +354,13 @@ impl InitBuilder {
         self.aggregated_publishers = exprs;
         self
     }
+
+    /// Confines the session to the given namespace.
+    #[inline]
+    pub fn namespace(mut self, namespace: OwnedKeyExpr) -> Self {
+        self.namespace = Some(namespace);
+        self
+    }
 }
 
 #[zenoh_macros::unstable]
@@ -343,6 +375,7 @@ impl SyncResolve for InitBuilder {
             self.runtime,
             self.aggregated_subscribers,
             self.aggregated_publishers,
+            self.namespace,
         )
         .res_sync())
     }
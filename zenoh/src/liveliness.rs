@@ -16,7 +16,7 @@
 //!
 //! see [`Liveliness`]
 
-use crate::query::Reply;
+use crate::query::{Reply, ReplyOrdering};
 
 #[zenoh_macros::unstable]
 use {
@@ -339,6 +339,14 @@ impl<'a> AsyncResolve for LivelinessTokenUndeclaration<'a> {
 
 #[zenoh_macros::unstable]
 impl<'a> LivelinessToken<'a> {
+    /// Returns the [`EntityGlobalId`] of this LivelinessToken.
+    pub fn id(&self) -> EntityGlobalId {
+        EntityGlobalId {
+            zid: self.session.zid(),
+            eid: self.state.id as EntityId,
+        }
+    }
+
     /// Undeclare a [`LivelinessToken`].
     ///
     /// LivelinessTokens are automatically closed when dropped,
@@ -545,6 +553,7 @@ where
                 Locality::default(),
                 callback,
                 &SubscriberInfo::default(),
+                None,
             )
             .map(|sub_state| Subscriber {
                 subscriber: SubscriberInner {
@@ -746,6 +755,8 @@ where
                 Locality::default(),
                 self.timeout,
                 None,
+                None,
+                ReplyOrdering::default(),
                 callback,
             )
             .map(|_| receiver)
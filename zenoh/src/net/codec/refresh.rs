@@ -0,0 +1,127 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use super::Zenoh080Routing;
+use crate::net::protocol::refresh::{DeclarationDigest, DeclarationRequest, DeclarationSet};
+use zenoh_buffers::{
+    reader::{DidntRead, Reader},
+    writer::{DidntWrite, Writer},
+};
+use zenoh_codec::{RCodec, WCodec, Zenoh080};
+use zenoh_protocol::core::ZenohId;
+
+// DeclarationDigest
+impl<W> WCodec<&DeclarationDigest, &mut W> for Zenoh080Routing
+where
+    W: Writer,
+{
+    type Output = Result<(), DidntWrite>;
+
+    fn write(self, writer: &mut W, x: &DeclarationDigest) -> Self::Output {
+        let codec = Zenoh080::new();
+
+        codec.write(&mut *writer, x.buckets.len())?;
+        for b in x.buckets.iter() {
+            codec.write(&mut *writer, *b)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<R> RCodec<DeclarationDigest, &mut R> for Zenoh080Routing
+where
+    R: Reader,
+{
+    type Error = DidntRead;
+
+    fn read(self, reader: &mut R) -> Result<DeclarationDigest, Self::Error> {
+        let codec = Zenoh080::new();
+
+        let len: usize = codec.read(&mut *reader)?;
+        // Each bucket takes at least a byte: don't trust the length for the allocation
+        let mut buckets = Vec::with_capacity(len.min(reader.remaining()));
+        for _ in 0..len {
+            let b: u64 = codec.read(&mut *reader)?;
+            buckets.push(b);
+        }
+
+        Ok(DeclarationDigest { buckets })
+    }
+}
+
+// DeclarationRequest
+impl<W> WCodec<&DeclarationRequest, &mut W> for Zenoh080Routing
+where
+    W: Writer,
+{
+    type Output = Result<(), DidntWrite>;
+
+    fn write(self, writer: &mut W, x: &DeclarationRequest) -> Self::Output {
+        let codec = Zenoh080::new();
+        codec.write(&mut *writer, &x.zid)
+    }
+}
+
+impl<R> RCodec<DeclarationRequest, &mut R> for Zenoh080Routing
+where
+    R: Reader,
+{
+    type Error = DidntRead;
+
+    fn read(self, reader: &mut R) -> Result<DeclarationRequest, Self::Error> {
+        let codec = Zenoh080::new();
+        let zid: ZenohId = codec.read(&mut *reader)?;
+        Ok(DeclarationRequest { zid })
+    }
+}
+
+// DeclarationSet
+impl<W> WCodec<&DeclarationSet, &mut W> for Zenoh080Routing
+where
+    W: Writer,
+{
+    type Output = Result<(), DidntWrite>;
+
+    fn write(self, writer: &mut W, x: &DeclarationSet) -> Self::Output {
+        let codec = Zenoh080::new();
+
+        codec.write(&mut *writer, x.subscribers.len())?;
+        for s in x.subscribers.iter() {
+            codec.write(&mut *writer, s)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<R> RCodec<DeclarationSet, &mut R> for Zenoh080Routing
+where
+    R: Reader,
+{
+    type Error = DidntRead;
+
+    fn read(self, reader: &mut R) -> Result<DeclarationSet, Self::Error> {
+        let codec = Zenoh080::new();
+
+        let len: usize = codec.read(&mut *reader)?;
+        // Each subscriber takes at least a byte: don't trust the length for the allocation
+        let mut subscribers = Vec::with_capacity(len.min(reader.remaining()));
+        for _ in 0..len {
+            let s: String = codec.read(&mut *reader)?;
+            subscribers.push(s);
+        }
+
+        Ok(DeclarationSet { subscribers })
+    }
+}
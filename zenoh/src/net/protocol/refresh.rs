@@ -0,0 +1,48 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use zenoh_protocol::core::ZenohId;
+
+// The digest of the declarations of a node, each key expression being hashed in one of
+// the buckets. Two sets of declarations differ if any of their buckets differ.
+//
+//  7 6 5 4 3 2 1 0
+// +-+-+-+-+-+-+-+-+
+// ~   [buckets]   ~
+// +---------------+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct DeclarationDigest {
+    pub(crate) buckets: Vec<u64>,
+}
+
+// The request of the full set of declarations of the given node.
+//
+//  7 6 5 4 3 2 1 0
+// +-+-+-+-+-+-+-+-+
+// ~      zid      ~
+// +---------------+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct DeclarationRequest {
+    pub(crate) zid: ZenohId,
+}
+
+// The full set of declarations of a node, replacing the ones previously received from it.
+//
+//  7 6 5 4 3 2 1 0
+// +-+-+-+-+-+-+-+-+
+// ~ [subscribers] ~
+// +---------------+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct DeclarationSet {
+    pub(crate) subscribers: Vec<String>,
+}
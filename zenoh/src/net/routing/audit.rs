@@ -0,0 +1,595 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use super::face::FaceState;
+use super::router::TablesLock;
+use serde_json::json;
+use std::collections::{HashMap, HashSet};
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use uhlc::HLC;
+use zenoh_core::{zlock, zread};
+use zenoh_protocol::{
+    core::{
+        key_expr::OwnedKeyExpr, ExprId, KnownEncoding, Reliability, WhatAmI, WireExpr, ZenohId,
+    },
+    network::{push, Mapping, Push},
+    zenoh::{PushBody, Put},
+};
+use zenoh_result::ZResult;
+use zenoh_transport::Primitives;
+use zenoh_util::clock::Clock;
+
+/// The number of keys a face published on beyond which they are forgotten, their next
+/// publication being recorded again.
+const MAX_PUBLISHED_KEYS: usize = 10000;
+
+/// The identity a face authenticated with.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AuthIdentity {
+    pub zid: ZenohId,
+    pub whatami: WhatAmI,
+    /// The user the transport of the face authenticated as, with user-password authentication.
+    pub user: Option<String>,
+}
+
+impl AuthIdentity {
+    pub fn to_json(&self) -> serde_json::Value {
+        json!({
+            "zid": self.zid.to_string(),
+            "whatami": self.whatami.to_str(),
+            "user": self.user,
+        })
+    }
+}
+
+/// An operation recorded in the audit log.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AuditOperation {
+    DeclareSubscriber,
+    DeclareQueryable,
+    /// The first publication of a face on a key expression.
+    Publish,
+    Query,
+}
+
+impl AuditOperation {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditOperation::DeclareSubscriber => "declare_subscriber",
+            AuditOperation::DeclareQueryable => "declare_queryable",
+            AuditOperation::Publish => "publish",
+            AuditOperation::Query => "query",
+        }
+    }
+}
+
+/// Whether a recorded operation was admitted by the router.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AuditOutcome {
+    Admitted,
+    /// The key expression is outside of the namespace of the face.
+    Denied,
+}
+
+impl AuditOutcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditOutcome::Admitted => "admitted",
+            AuditOutcome::Denied => "denied",
+        }
+    }
+}
+
+/// An operation of a face recorded in the audit log.
+#[derive(Clone, Debug)]
+pub struct AuditEvent {
+    pub timestamp: SystemTime,
+    pub identity: Arc<AuthIdentity>,
+    /// The id of the face in the routing tables, distinguishing the successive transports
+    /// of a same identity.
+    pub face: usize,
+    pub operation: AuditOperation,
+    pub key_expr: String,
+    pub outcome: AuditOutcome,
+    /// The number of events not recorded since the previous one, because of the sampling,
+    /// the rate limiting or the congestion of the sinks.
+    pub suppressed: u64,
+}
+
+impl AuditEvent {
+    pub fn to_json(&self) -> serde_json::Value {
+        let timestamp = self
+            .timestamp
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+        json!({
+            "timestamp": timestamp,
+            "identity": self.identity.to_json(),
+            "face": self.face,
+            "operation": self.operation.as_str(),
+            "key_expr": self.key_expr,
+            "outcome": self.outcome.as_str(),
+            "suppressed": self.suppressed,
+        })
+    }
+}
+
+/// A destination of the audit events, see [`crate::runtime::Runtime::add_audit_sink`].
+///
+/// The events are delivered in order, from a blocking thread of the runtime: a slow sink delays
+/// the next events, which are suppressed once the queue of the sinks is full.
+pub trait AuditSink: Send + Sync {
+    fn record(&self, event: &AuditEvent);
+}
+
+struct AuditFile {
+    file: File,
+    size: u64,
+}
+
+/// A sink writing the audit events as JSON lines to a file, rotated once larger than a
+/// maximum size: `<path>` is renamed `<path>.1`, `<path>.1` renamed `<path>.2`, and so on,
+/// the oldest of the `max_files` rotated files being deleted.
+pub struct FileAuditSink {
+    path: PathBuf,
+    max_size: u64,
+    max_files: usize,
+    file: Mutex<AuditFile>,
+}
+
+impl FileAuditSink {
+    pub fn new<P: AsRef<Path>>(path: P, max_size: u64, max_files: usize) -> ZResult<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let size = file.metadata()?.len();
+        Ok(FileAuditSink {
+            path,
+            max_size,
+            max_files,
+            file: Mutex::new(AuditFile { file, size }),
+        })
+    }
+
+    fn rotated(&self, index: usize) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(format!(".{index}"));
+        path.into()
+    }
+
+    fn rotate(&self, file: &mut AuditFile) -> std::io::Result<()> {
+        if self.max_files == 0 {
+            file.file.set_len(0)?;
+        } else {
+            for index in (1..self.max_files).rev() {
+                let from = self.rotated(index);
+                if from.exists() {
+                    std::fs::rename(from, self.rotated(index + 1))?;
+                }
+            }
+            std::fs::rename(&self.path, self.rotated(1))?;
+            file.file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)?;
+        }
+        file.size = 0;
+        Ok(())
+    }
+
+    fn write(&self, file: &mut AuditFile, event: &AuditEvent) -> std::io::Result<()> {
+        let mut line = event.to_json().to_string();
+        line.push('\n');
+        if file.size > 0 && file.size + line.len() as u64 > self.max_size {
+            self.rotate(file)?;
+        }
+        file.file.write_all(line.as_bytes())?;
+        file.size += line.len() as u64;
+        Ok(())
+    }
+}
+
+impl AuditSink for FileAuditSink {
+    fn record(&self, event: &AuditEvent) {
+        let file = &mut *zlock!(self.file);
+        if let Err(e) = self.write(file, event) {
+            log::error!("Error writing audit log {}: {}", self.path.display(), e);
+        }
+    }
+}
+
+/// A sink publishing the audit events as JSON onto a key expression, for their central collection.
+pub(crate) struct PublicationAuditSink {
+    primitives: Arc<dyn Primitives + Send + Sync>,
+    key_expr: OwnedKeyExpr,
+    hlc: Option<Arc<HLC>>,
+}
+
+impl PublicationAuditSink {
+    pub(crate) fn new(
+        primitives: Arc<dyn Primitives + Send + Sync>,
+        key_expr: OwnedKeyExpr,
+        hlc: Option<Arc<HLC>>,
+    ) -> Self {
+        PublicationAuditSink {
+            primitives,
+            key_expr,
+            hlc,
+        }
+    }
+}
+
+impl AuditSink for PublicationAuditSink {
+    fn record(&self, event: &AuditEvent) {
+        self.primitives.send_push(
+            Push {
+                wire_expr: self.key_expr.to_string().into(),
+                ext_qos: push::ext::QoSType::push_default(),
+                ext_tstamp: None,
+                ext_nodeid: push::ext::NodeIdType::default(),
+                payload: PushBody::Put(Put {
+                    timestamp: self.hlc.as_ref().map(|hlc| hlc.new_timestamp()),
+                    encoding: KnownEncoding::AppJson.into(),
+                    ext_sinfo: None,
+                    #[cfg(feature = "shared-memory")]
+                    ext_shm: None,
+                    ext_integrity: None,
+                    ext_batch: None,
+                    ext_unknown: vec![],
+                    payload: event.to_json().to_string().into_bytes().into(),
+                }),
+            },
+            Reliability::Reliable,
+        );
+    }
+}
+
+/// The sampling and rate limiting parameters of the audit log.
+pub(crate) struct AuditLimits {
+    /// The ratio of the events recorded, between 0 and 1.
+    pub(crate) sampling: f64,
+    /// The maximum number of events recorded per second, after a burst of as many.
+    pub(crate) rate: Option<u64>,
+    pub(crate) clock: Arc<dyn Clock>,
+}
+
+struct RateState {
+    tokens: f64,
+    last: Instant,
+}
+
+/// Samples and rate limits the audit events of all the faces, and queues them to the sinks.
+pub(crate) struct Auditor {
+    limits: AuditLimits,
+    rate: Mutex<RateState>,
+    suppressed: AtomicU64,
+    sender: flume::Sender<AuditEvent>,
+}
+
+impl Auditor {
+    pub(crate) fn new(limits: AuditLimits, sender: flume::Sender<AuditEvent>) -> Self {
+        let rate = RateState {
+            tokens: limits.rate.unwrap_or(0) as f64,
+            last: limits.clock.now(),
+        };
+        Auditor {
+            limits,
+            rate: Mutex::new(rate),
+            suppressed: AtomicU64::new(0),
+            sender,
+        }
+    }
+
+    fn admits(&self) -> bool {
+        if self.limits.sampling < 1.0 && rand::random::<f64>() >= self.limits.sampling {
+            return false;
+        }
+        match self.limits.rate {
+            Some(rate) => {
+                let now = self.limits.clock.now();
+                let state = &mut *zlock!(self.rate);
+                let elapsed = now.saturating_duration_since(state.last).as_secs_f64();
+                state.tokens = (state.tokens + elapsed * rate as f64).min(rate as f64);
+                state.last = now;
+                if state.tokens >= 1.0 {
+                    state.tokens -= 1.0;
+                    true
+                } else {
+                    false
+                }
+            }
+            None => true,
+        }
+    }
+
+    pub(crate) fn record(
+        &self,
+        identity: &Arc<AuthIdentity>,
+        face: usize,
+        operation: AuditOperation,
+        key_expr: String,
+        outcome: AuditOutcome,
+    ) {
+        if !self.admits() {
+            self.suppressed.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+        let event = AuditEvent {
+            timestamp: SystemTime::now(),
+            identity: identity.clone(),
+            face,
+            operation,
+            key_expr,
+            outcome,
+            suppressed: self.suppressed.swap(0, Ordering::Relaxed),
+        };
+        // The events are suppressed if the sinks can't record them fast enough
+        if let Err(e) = self.sender.try_send(event) {
+            let event = e.into_inner();
+            self.suppressed
+                .fetch_add(event.suppressed + 1, Ordering::Relaxed);
+        }
+    }
+}
+
+// The expressions a face published on, by scope and then suffix
+#[derive(Default)]
+struct Published {
+    suffixes: HashMap<(ExprId, Mapping), HashSet<String>>,
+    len: usize,
+}
+
+/// The audit of the operations of a face.
+pub(crate) struct FaceAudit {
+    auditor: Arc<Auditor>,
+    identity: Arc<AuthIdentity>,
+    // The expressions the face published on, only their first publication being recorded
+    published: Mutex<Published>,
+}
+
+impl FaceAudit {
+    pub(crate) fn new(auditor: Arc<Auditor>, identity: AuthIdentity) -> Self {
+        FaceAudit {
+            auditor,
+            identity: Arc::new(identity),
+            published: Mutex::new(Published::default()),
+        }
+    }
+
+    /// Forgets the publications on the expressions of the mapping `expr_id`, undeclared by the
+    /// face: a publication on its next declaration is recorded again.
+    pub(crate) fn forget_mapping(&self, expr_id: ExprId) {
+        let published = &mut *zlock!(self.published);
+        published.suffixes.retain(|(scope, _), suffixes| {
+            if *scope == expr_id {
+                published.len -= suffixes.len();
+            }
+            *scope != expr_id
+        });
+    }
+
+    // Returns `true` if `expr` is published on for the first time, without resolving it
+    fn first_publication(&self, expr: &WireExpr) -> bool {
+        let published = &mut *zlock!(self.published);
+        let scope = (expr.scope, expr.mapping);
+        if published
+            .suffixes
+            .get(&scope)
+            .map_or(false, |suffixes| suffixes.contains(expr.suffix.as_ref()))
+        {
+            return false;
+        }
+        if published.len >= MAX_PUBLISHED_KEYS {
+            published.suffixes.clear();
+            published.len = 0;
+        }
+        published
+            .suffixes
+            .entry(scope)
+            .or_default()
+            .insert(expr.suffix.to_string());
+        published.len += 1;
+        true
+    }
+
+    #[cold]
+    fn record(
+        &self,
+        tables: &TablesLock,
+        face: &FaceState,
+        operation: AuditOperation,
+        expr: &WireExpr,
+        outcome: AuditOutcome,
+    ) {
+        if operation == AuditOperation::Publish && !self.first_publication(expr) {
+            return;
+        }
+        let key_expr = match zread!(tables.tables).get_mapping(face, &expr.scope, expr.mapping) {
+            Some(prefix) => prefix.expr() + expr.suffix.as_ref(),
+            None => expr.suffix.to_string(),
+        };
+        self.auditor
+            .record(&self.identity, face.id, operation, key_expr, outcome);
+    }
+}
+
+/// Records the operation of `face` on `expr` in the audit log, if the face is audited.
+#[inline]
+pub(super) fn record(
+    tables: &TablesLock,
+    face: &FaceState,
+    operation: AuditOperation,
+    expr: &WireExpr,
+    outcome: AuditOutcome,
+) {
+    if let Some(audit) = face.audit.as_ref() {
+        audit.record(tables, face, operation, expr, outcome);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+    use std::time::Duration;
+    use zenoh_util::clock::TestClock;
+
+    fn identity() -> Arc<AuthIdentity> {
+        Arc::new(AuthIdentity {
+            zid: ZenohId::from_str("a1b2c3").unwrap(),
+            whatami: WhatAmI::Client,
+            user: Some("alice".to_string()),
+        })
+    }
+
+    fn event(key_expr: &str) -> AuditEvent {
+        AuditEvent {
+            timestamp: SystemTime::now(),
+            identity: identity(),
+            face: 1,
+            operation: AuditOperation::Publish,
+            key_expr: key_expr.to_string(),
+            outcome: AuditOutcome::Admitted,
+            suppressed: 0,
+        }
+    }
+
+    #[test]
+    fn audit_rate_limited() {
+        let clock = TestClock::new();
+        let (sender, receiver) = flume::unbounded();
+        let limits = AuditLimits {
+            sampling: 1.0,
+            rate: Some(2),
+            clock: Arc::new(clock.clone()),
+        };
+        let auditor = Auditor::new(limits, sender);
+        let identity = identity();
+        for i in 0..5 {
+            let key_expr = format!("a/{i}");
+            auditor.record(
+                &identity,
+                1,
+                AuditOperation::Query,
+                key_expr,
+                AuditOutcome::Admitted,
+            );
+        }
+        let events = receiver.drain().collect::<Vec<_>>();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[1].key_expr, "a/1");
+        assert_eq!(events[1].identity.user.as_deref(), Some("alice"));
+
+        // The next event counts the suppressed ones
+        clock.advance(Duration::from_secs(1));
+        auditor.record(
+            &identity,
+            1,
+            AuditOperation::Query,
+            "b".into(),
+            AuditOutcome::Denied,
+        );
+        let event = receiver.try_recv().unwrap();
+        assert_eq!(event.suppressed, 3);
+        assert_eq!(event.outcome, AuditOutcome::Denied);
+
+        // Nothing is recorded when sampling none of the events
+        let (sender, receiver) = flume::unbounded();
+        let limits = AuditLimits {
+            sampling: 0.0,
+            rate: None,
+            clock: Arc::new(clock),
+        };
+        let auditor = Auditor::new(limits, sender);
+        auditor.record(
+            &identity,
+            1,
+            AuditOperation::Query,
+            "c".into(),
+            AuditOutcome::Admitted,
+        );
+        assert!(receiver.try_recv().is_err());
+    }
+
+    #[test]
+    fn audit_first_publication() {
+        let (sender, _receiver) = flume::unbounded();
+        let limits = AuditLimits {
+            sampling: 1.0,
+            rate: None,
+            clock: Arc::new(TestClock::new()),
+        };
+        let audit = FaceAudit::new(
+            Arc::new(Auditor::new(limits, sender)),
+            (*identity()).clone(),
+        );
+        let expr = |scope: ExprId, suffix: &'static str| WireExpr {
+            scope,
+            suffix: suffix.into(),
+            mapping: Mapping::Receiver,
+        };
+        assert!(audit.first_publication(&expr(0, "a/b")));
+        assert!(!audit.first_publication(&expr(0, "a/b")));
+        assert!(audit.first_publication(&expr(1, "/b")));
+        assert!(!audit.first_publication(&expr(1, "/b")));
+        assert!(audit.first_publication(&expr(1, "/c")));
+
+        // The publications on an undeclared mapping are recorded again
+        audit.forget_mapping(1);
+        assert_eq!(zlock!(audit.published).len, 1);
+        assert!(audit.first_publication(&expr(1, "/b")));
+        assert!(!audit.first_publication(&expr(0, "a/b")));
+    }
+
+    #[test]
+    fn audit_file_rotated() {
+        let dir = std::env::temp_dir().join(format!("zenoh-test-audit-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("audit.jsonl");
+        let line_size = event("k/0").to_json().to_string().len() as u64 + 1;
+
+        // Two events per file, two rotated files kept
+        let sink = FileAuditSink::new(&path, 2 * line_size, 2).unwrap();
+        for i in 0..7 {
+            sink.record(&event(&format!("k/{i}")));
+        }
+        let keys = |path: &Path| {
+            std::fs::read_to_string(path)
+                .unwrap()
+                .lines()
+                .map(|line| {
+                    let event: serde_json::Value = serde_json::from_str(line).unwrap();
+                    assert_eq!(event["identity"]["user"], "alice");
+                    event["key_expr"].as_str().unwrap().to_string()
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(keys(&path), ["k/6"]);
+        assert_eq!(keys(&sink.rotated(1)), ["k/4", "k/5"]);
+        assert_eq!(keys(&sink.rotated(2)), ["k/2", "k/3"]);
+        assert!(!sink.rotated(3).exists());
+
+        // The file is appended to when reopened
+        drop(sink);
+        let sink = FileAuditSink::new(&path, 2 * line_size, 2).unwrap();
+        sink.record(&event("k/7"));
+        assert_eq!(keys(&path), ["k/6", "k/7"]);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,217 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use super::face::FaceState;
+use super::router::Tables;
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+use zenoh_buffers::SplitBuffer;
+use zenoh_core::{zlock, zread};
+use zenoh_protocol::{
+    core::{key_expr::keyexpr, WireExpr},
+    zenoh::PushBody,
+};
+use zenoh_util::clock::Clock;
+
+/// The minimum number of key expressions before the ones notified outside of the window are forgotten.
+const MIN_KEYS_CLEANUP: usize = 1024;
+
+/// The reason why a data message was dropped without reaching any destination.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DeadLetterReason {
+    /// No subscriber matches the key expression.
+    NoRoute,
+    /// The key expression is outside of the namespace of the face the message was received from.
+    Denied,
+    /// All the destinations dropped the message because of congestion.
+    Congestion,
+}
+
+impl DeadLetterReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DeadLetterReason::NoRoute => "no_route",
+            DeadLetterReason::Denied => "denied",
+            DeadLetterReason::Congestion => "congestion",
+        }
+    }
+}
+
+/// The notification of a data message dropped without reaching any destination.
+#[derive(Clone, Debug)]
+pub struct DeadLetter {
+    pub key_expr: String,
+    pub payload_size: usize,
+    pub reason: DeadLetterReason,
+    /// The number of messages dropped on the same key expression since the previous
+    /// notification, and not notified because of the rate limiting.
+    pub suppressed: u64,
+}
+
+impl DeadLetter {
+    pub fn to_json(&self) -> serde_json::Value {
+        json!({
+            "key_expr": self.key_expr,
+            "payload_size": self.payload_size,
+            "reason": self.reason.as_str(),
+            "suppressed": self.suppressed,
+        })
+    }
+}
+
+struct Notified {
+    // The time of the last notification and the number of drops suppressed since, per key
+    keys: HashMap<String, (Instant, u64)>,
+    next_cleanup: usize,
+}
+
+/// Notifies the data messages dropped without reaching any destination, at most once per key
+/// expression and window.
+pub(crate) struct DeadLetters {
+    window: Duration,
+    clock: Arc<dyn Clock>,
+    notified: Mutex<Notified>,
+    sender: flume::Sender<DeadLetter>,
+}
+
+impl DeadLetters {
+    pub(crate) fn new(
+        window: Duration,
+        clock: Arc<dyn Clock>,
+        sender: flume::Sender<DeadLetter>,
+    ) -> Self {
+        DeadLetters {
+            window,
+            clock,
+            notified: Mutex::new(Notified {
+                keys: HashMap::new(),
+                next_cleanup: MIN_KEYS_CLEANUP,
+            }),
+            sender,
+        }
+    }
+
+    pub(crate) fn report(&self, key_expr: &str, payload_size: usize, reason: DeadLetterReason) {
+        // The notifications are published in the admin space: don't notify their own drops
+        if key_expr.starts_with("@/") {
+            return;
+        }
+        let now = self.clock.now();
+        let window = self.window;
+        let notified = &mut *zlock!(self.notified);
+        if notified.keys.len() >= notified.next_cleanup {
+            notified
+                .keys
+                .retain(|_, (last, _)| now.saturating_duration_since(*last) < window);
+            notified.next_cleanup = (notified.keys.len() * 2).max(MIN_KEYS_CLEANUP);
+        }
+        let suppressed = match notified.keys.get_mut(key_expr) {
+            Some((last, suppressed)) if now.saturating_duration_since(*last) < window => {
+                *suppressed += 1;
+                return;
+            }
+            Some((last, suppressed)) => {
+                *last = now;
+                std::mem::take(suppressed)
+            }
+            None => {
+                notified.keys.insert(key_expr.to_string(), (now, 0));
+                0
+            }
+        };
+        log::trace!("Dead letter on {}: {}", key_expr, reason.as_str());
+        // The notifications are dropped if they can't be published fast enough
+        let _ = self.sender.try_send(DeadLetter {
+            key_expr: key_expr.to_string(),
+            payload_size,
+            reason,
+            suppressed,
+        });
+    }
+}
+
+pub(super) fn payload_size(payload: &PushBody) -> usize {
+    match payload {
+        PushBody::Put(put) => put.payload.len(),
+        PushBody::Del(_) => 0,
+    }
+}
+
+/// Whether `face` itself subscribed to `key_expr`: a session delivers its own data to its
+/// subscribers without routing it.
+pub(super) fn subscribed_by(face: &FaceState, key_expr: &str) -> bool {
+    match keyexpr::new(key_expr) {
+        Ok(key_expr) => face
+            .remote_subs
+            .iter()
+            .any(|sub| keyexpr::new(&sub.expr()).map_or(false, |sub| sub.intersects(key_expr))),
+        Err(_) => false,
+    }
+}
+
+/// Reports the drop of the data received from `face` on `expr`, if the dead letters are notified.
+pub(super) fn report(
+    tables_ref: &RwLock<Tables>,
+    face: &FaceState,
+    expr: &WireExpr,
+    payload: &PushBody,
+    reason: DeadLetterReason,
+) {
+    let tables = zread!(tables_ref);
+    if let Some(dead_letters) = tables.dead_letters.as_ref() {
+        let key_expr = match tables.get_mapping(face, &expr.scope, expr.mapping) {
+            Some(prefix) => prefix.expr() + expr.suffix.as_ref(),
+            None => expr.suffix.to_string(),
+        };
+        dead_letters.report(&key_expr, payload_size(payload), reason);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zenoh_util::clock::TestClock;
+
+    #[test]
+    fn dead_letters_rate_limited() {
+        let clock = TestClock::new();
+        let (sender, receiver) = flume::unbounded();
+        let dead_letters =
+            DeadLetters::new(Duration::from_secs(1), Arc::new(clock.clone()), sender);
+
+        // One notification per key and window
+        for _ in 0..3 {
+            dead_letters.report("a/b", 8, DeadLetterReason::NoRoute);
+        }
+        dead_letters.report("a/c", 8, DeadLetterReason::Congestion);
+        let notifications = receiver.drain().collect::<Vec<_>>();
+        assert_eq!(notifications.len(), 2);
+        assert_eq!(notifications[0].key_expr, "a/b");
+        assert_eq!(notifications[0].suppressed, 0);
+        assert_eq!(notifications[1].reason, DeadLetterReason::Congestion);
+
+        // The next notification counts the suppressed ones
+        clock.advance(Duration::from_secs(1));
+        dead_letters.report("a/b", 16, DeadLetterReason::NoRoute);
+        let notification = receiver.try_recv().unwrap();
+        assert_eq!(notification.payload_size, 16);
+        assert_eq!(notification.suppressed, 2);
+        assert!(receiver.try_recv().is_err());
+
+        // The drops of the notifications themselves are not notified
+        dead_letters.report("@/router/zid/deadletter", 8, DeadLetterReason::NoRoute);
+        assert!(receiver.try_recv().is_err());
+    }
+}
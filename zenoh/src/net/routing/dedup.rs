@@ -0,0 +1,200 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, VecDeque};
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use zenoh_config::{DeduplicationConf, DeduplicationMode};
+use zenoh_core::zlock;
+use zenoh_protocol::{
+    core::key_expr::{keyexpr, OwnedKeyExpr},
+    zenoh::PushBody,
+};
+use zenoh_util::clock::Clock;
+
+/// The maximum number of samples remembered per key: the oldest ones are forgotten first,
+/// even if they are still within the window.
+const MAX_SAMPLES_PER_KEY: usize = 1024;
+
+/// The minimum number of keys before the keys without any recent sample are forgotten.
+const MIN_KEYS_CLEANUP: usize = 1024;
+
+struct Seen {
+    // The hashes of the samples routed within the window, per key, oldest first
+    samples: HashMap<String, VecDeque<(Instant, u64)>>,
+    next_cleanup: usize,
+}
+
+struct DeduplicationRule {
+    keyexprs: Vec<OwnedKeyExpr>,
+    window: Duration,
+    mode: DeduplicationMode,
+    seen: Mutex<Seen>,
+}
+
+impl DeduplicationRule {
+    fn matches(&self, key: &keyexpr) -> bool {
+        self.keyexprs.iter().any(|ke| ke.includes(key))
+    }
+
+    fn hash(&self, payload: &PushBody) -> Option<u64> {
+        let mut hasher = DefaultHasher::new();
+        match (self.mode, payload) {
+            (DeduplicationMode::Content, PushBody::Put(put)) => {
+                0u8.hash(&mut hasher);
+                put.encoding.to_string().hash(&mut hasher);
+                for slice in put.payload.zslices() {
+                    hasher.write(slice.as_slice());
+                }
+            }
+            (DeduplicationMode::Content, PushBody::Del(_)) => 1u8.hash(&mut hasher),
+            (DeduplicationMode::SourceInfo, PushBody::Put(put)) => {
+                let sinfo = put.ext_sinfo.as_ref()?;
+                (sinfo.zid, sinfo.eid, sinfo.sn, put.timestamp?).hash(&mut hasher);
+            }
+            (DeduplicationMode::SourceInfo, PushBody::Del(del)) => {
+                let sinfo = del.ext_sinfo.as_ref()?;
+                (sinfo.zid, sinfo.eid, sinfo.sn, del.timestamp?).hash(&mut hasher);
+            }
+        }
+        Some(hasher.finish())
+    }
+
+    fn is_duplicate(&self, key: &str, hash: u64, now: Instant) -> bool {
+        let window = self.window;
+        let is_recent = |(t, _): &(Instant, u64)| now.saturating_duration_since(*t) <= window;
+
+        let seen = &mut *zlock!(self.seen);
+        if seen.samples.len() >= seen.next_cleanup {
+            seen.samples
+                .retain(|_, samples| samples.back().map_or(false, is_recent));
+            seen.next_cleanup = MIN_KEYS_CLEANUP.max(2 * seen.samples.len());
+        }
+
+        let samples = seen.samples.entry(key.to_string()).or_default();
+        while samples.front().map_or(false, |s| !is_recent(s)) {
+            samples.pop_front();
+        }
+        if samples.iter().any(|(_, h)| *h == hash) {
+            return true;
+        }
+        if samples.len() >= MAX_SAMPLES_PER_KEY {
+            samples.pop_front();
+        }
+        samples.push_back((now, hash));
+        false
+    }
+}
+
+/// Drops the samples that duplicate a sample recently routed on the same key.
+///
+/// Duplicates are identified by their content (or their source info), not by their publisher,
+/// so that when one of several redundant publishers stops the samples of the others flow
+/// without any delay.
+pub(crate) struct Deduplication {
+    rules: Vec<DeduplicationRule>,
+    clock: Arc<dyn Clock>,
+}
+
+impl Deduplication {
+    /// Returns `None` if there is no deduplication rule.
+    pub(crate) fn new(config: &[DeduplicationConf], clock: Arc<dyn Clock>) -> Option<Self> {
+        if config.is_empty() {
+            return None;
+        }
+        let rules = config
+            .iter()
+            .map(|c| DeduplicationRule {
+                keyexprs: c.keyexprs.clone(),
+                window: Duration::from_millis(c.window),
+                mode: c.mode,
+                seen: Mutex::new(Seen {
+                    samples: HashMap::new(),
+                    next_cleanup: MIN_KEYS_CLEANUP,
+                }),
+            })
+            .collect();
+        Some(Deduplication { rules, clock })
+    }
+
+    /// Returns `true` if `payload` duplicates a sample routed on `key` within the window of
+    /// the first rule matching `key`, and records it otherwise.
+    pub(crate) fn is_duplicate(&self, key: &str, payload: &PushBody) -> bool {
+        let Ok(ke) = keyexpr::new(key) else {
+            return false;
+        };
+        match self.rules.iter().find(|r| r.matches(ke)) {
+            Some(rule) => match rule.hash(payload) {
+                Some(hash) => rule.is_duplicate(key, hash, self.clock.now()),
+                None => false,
+            },
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::TryFrom;
+    use zenoh_protocol::zenoh::{Del, Put};
+    use zenoh_util::clock::TestClock;
+
+    fn put(payload: &[u8]) -> PushBody {
+        PushBody::Put(Put {
+            timestamp: None,
+            encoding: Default::default(),
+            ext_sinfo: None,
+            #[cfg(feature = "shared-memory")]
+            ext_shm: None,
+            ext_integrity: None,
+            ext_batch: None,
+            ext_unknown: vec![],
+            payload: payload.to_vec().into(),
+        })
+    }
+
+    #[test]
+    fn deduplication() {
+        let clock = TestClock::new();
+        let config = vec![DeduplicationConf {
+            keyexprs: vec![OwnedKeyExpr::try_from("test/dedup/**").unwrap()],
+            window: 1000,
+            mode: DeduplicationMode::Content,
+        }];
+        assert!(Deduplication::new(&[], Arc::new(clock.clone())).is_none());
+        let dedup = Deduplication::new(&config, Arc::new(clock.clone())).unwrap();
+
+        // Duplicates are dropped within the window, on the same key only
+        assert!(!dedup.is_duplicate("test/dedup/a", &put(b"1")));
+        assert!(dedup.is_duplicate("test/dedup/a", &put(b"1")));
+        assert!(!dedup.is_duplicate("test/dedup/a", &put(b"2")));
+        assert!(!dedup.is_duplicate("test/dedup/b", &put(b"1")));
+        assert!(!dedup.is_duplicate("test/other", &put(b"1")));
+        assert!(!dedup.is_duplicate("test/other", &put(b"1")));
+        let del = PushBody::Del(Del {
+            timestamp: None,
+            ext_sinfo: None,
+            ext_batch: None,
+            ext_unknown: vec![],
+        });
+        assert!(!dedup.is_duplicate("test/dedup/a", &del));
+
+        // Samples are forgotten after the window
+        clock.advance(Duration::from_millis(1001));
+        assert!(!dedup.is_duplicate("test/dedup/a", &put(b"1")));
+        assert!(dedup.is_duplicate("test/dedup/a", &put(b"1")));
+    }
+}
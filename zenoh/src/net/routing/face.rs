@@ -11,21 +11,28 @@
 // Contributors:
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
+use super::audit::{self, AuditOperation, AuditOutcome, FaceAudit};
+use super::deadletter::{self, DeadLetterReason};
+use super::mappings::{self, LocalMappingsLru};
+use super::namespace::{self, Namespace};
 use super::router::*;
+use crate::filter::Filter;
 use std::collections::{HashMap, HashSet};
 use std::fmt;
-use std::sync::Arc;
-use zenoh_protocol::zenoh::RequestBody;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Weak};
+use zenoh_protocol::zenoh::{PushBody, RequestBody};
 use zenoh_protocol::{
-    core::{ExprId, WhatAmI, ZenohId},
+    core::{key_expr::keyexpr, ExprId, Priority, Reliability, WhatAmI, WireExpr, ZenohId},
     network::{
-        declare::queryable::ext::QueryableInfo, Mapping, Push, Request, RequestId, Response,
-        ResponseFinal,
+        declare::{ext, queryable::ext::QueryableInfo, RejectKeyExprs},
+        response, Declare, DeclareBody, Mapping, Push, Request, RequestId, Response, ResponseFinal,
     },
+    transport::BatchSize,
 };
 #[cfg(feature = "stats")]
 use zenoh_transport::stats::TransportStats;
-use zenoh_transport::{Primitives, TransportMulticast};
+use zenoh_transport::{Primitives, PushReport, TransportMulticast};
 
 pub struct FaceState {
     pub(super) id: usize,
@@ -37,16 +44,42 @@ pub struct FaceState {
     pub(super) link_id: usize,
     pub(super) local_mappings: HashMap<ExprId, Arc<Resource>>,
     pub(super) remote_mappings: HashMap<ExprId, Arc<Resource>>,
+    // The last use of the local mappings, to evict the least recently used ones
+    pub(super) local_mappings_lru: LocalMappingsLru,
+    // Whether the face declared more remote mappings than the alarm threshold
+    pub(super) remote_mappings_alarm: AtomicBool,
     pub(super) local_subs: HashSet<Arc<Resource>>,
+    // The filters sent with the subscriptions declared to the face
+    pub(super) local_sub_filters: HashMap<Arc<Resource>, Arc<Filter>>,
     pub(super) remote_subs: HashSet<Arc<Resource>>,
     pub(super) local_qabls: HashMap<Arc<Resource>, QueryableInfo>,
     pub(super) remote_qabls: HashSet<Arc<Resource>>,
+    // The resources, by expression, whose cached routes went through the face when computed
+    pub(super) routed_resources: HashMap<String, Weak<Resource>>,
     pub(super) next_qid: RequestId,
     pub(super) pending_queries: HashMap<RequestId, Arc<Query>>,
+    // The queries received from the face and still pending, counted against the query limits
+    pub(super) pending_src_queries: AtomicUsize,
     pub(super) mcast_group: Option<TransportMulticast>,
+    // Whether the transport of the face has a queue per priority
+    pub(super) is_qos: bool,
+    // The data sent with a non default priority on the default queue of a transport without QoS
+    pub(super) priority_downgrades: AtomicUsize,
+    // The data dropped for this face because the filter of a subscription couldn't read its payload
+    pub(super) malformed_payloads: AtomicUsize,
+    // The samples sent to this face with a payload mutated by the mutation rules
+    pub(super) mutated_samples: AtomicUsize,
+    // The namespace the key expressions used by the face must be included by
+    pub(super) namespace: Option<Namespace>,
+    // The size of the batches of the transport of the face if the key expression mappings are
+    // resynchronized in bulk when the face reconnects, each batch resynchronizing some of them
+    pub(super) resync: Option<BatchSize>,
+    // The audit of the operations of the face, if they are audited
+    pub(super) audit: Option<FaceAudit>,
 }
 
 impl FaceState {
+    #[allow(clippy::too_many_arguments)]
     pub(super) fn new(
         id: usize,
         zid: ZenohId,
@@ -55,6 +88,10 @@ impl FaceState {
         primitives: Arc<dyn Primitives + Send + Sync>,
         link_id: usize,
         mcast_group: Option<TransportMulticast>,
+        is_qos: bool,
+        namespace: Option<Namespace>,
+        resync: Option<BatchSize>,
+        audit: Option<FaceAudit>,
     ) -> Arc<FaceState> {
         Arc::new(FaceState {
             id,
@@ -66,16 +103,50 @@ impl FaceState {
             link_id,
             local_mappings: HashMap::new(),
             remote_mappings: HashMap::new(),
+            local_mappings_lru: LocalMappingsLru::default(),
+            remote_mappings_alarm: AtomicBool::new(false),
             local_subs: HashSet::new(),
+            local_sub_filters: HashMap::new(),
             remote_subs: HashSet::new(),
             local_qabls: HashMap::new(),
             remote_qabls: HashSet::new(),
+            routed_resources: HashMap::new(),
             next_qid: 0,
             pending_queries: HashMap::new(),
+            pending_src_queries: AtomicUsize::new(0),
             mcast_group,
+            is_qos,
+            priority_downgrades: AtomicUsize::new(0),
+            malformed_payloads: AtomicUsize::new(0),
+            mutated_samples: AtomicUsize::new(0),
+            namespace,
+            resync,
+            audit,
         })
     }
 
+    /// Counts the data sent to this face with a non default priority when its transport has no QoS:
+    /// the data is then scheduled on the single queue of the transport.
+    #[inline]
+    pub(super) fn count_priority_downgrade(&self, priority: Priority) {
+        if !self.is_qos && priority != Priority::default() {
+            self.priority_downgrades.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Counts the data not sent to this face because the filter of a subscription compares a
+    /// payload which can't be read as a number.
+    #[inline]
+    pub(super) fn count_malformed_payload(&self) {
+        self.malformed_payloads.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Counts the samples sent to this face with a payload mutated by the mutation rules.
+    #[inline]
+    pub(super) fn count_mutated_sample(&self) {
+        self.mutated_samples.fetch_add(1, Ordering::Relaxed);
+    }
+
     #[inline]
     #[allow(clippy::trivially_copy_pass_by_ref)]
     pub(super) fn get_mapping(
@@ -89,6 +160,15 @@ impl FaceState {
         }
     }
 
+    /// Marks the mapping declared to this face which `expr` is sent with, if any, as the most
+    /// recently used one.
+    #[inline]
+    pub(super) fn touch_mapping(&self, expr: &WireExpr) {
+        if expr.mapping == Mapping::Sender && expr.scope != 0 {
+            self.local_mappings_lru.touch(expr.scope);
+        }
+    }
+
     pub(super) fn get_next_local_id(&self) -> ExprId {
         let mut id = 1;
         while self.local_mappings.get(&id).is_some() || self.remote_mappings.get(&id).is_some() {
@@ -148,12 +228,65 @@ impl fmt::Display for FaceState {
     }
 }
 
+// Whether the key expression declared by `face` is a valid key expression, in canon-form: the
+// routing tables index the declarations by key expression, the ones denoting the same set of keys
+// having to be equal.
+fn declares_canon(tables: &TablesLock, face: &FaceState, expr: &WireExpr) -> bool {
+    let rtables = zread!(tables.tables);
+    match rtables.get_mapping(face, &expr.scope, expr.mapping) {
+        Some(prefix) => {
+            let full = prefix.expr() + expr.suffix.as_ref();
+            match keyexpr::new(full.as_str()) {
+                Ok(_) => true,
+                Err(e) => {
+                    log::warn!("{} declared {:?}: dropped. {}", face, expr, e);
+                    false
+                }
+            }
+        }
+        // The unknown mappings are reported by the declarations
+        None => true,
+    }
+}
+
 #[derive(Clone)]
 pub struct Face {
     pub(crate) tables: Arc<TablesLock>,
     pub(crate) state: Arc<FaceState>,
 }
 
+impl Face {
+    // Whether the namespace of the face admits the data pushed on `wire_expr`, auditing the
+    // publication and reporting the denied data as a dead letter.
+    fn admits_push(&self, wire_expr: &WireExpr, payload: &PushBody) -> bool {
+        if !namespace::face_allows(&self.tables, &self.state, wire_expr) {
+            audit::record(
+                &self.tables,
+                &self.state,
+                AuditOperation::Publish,
+                wire_expr,
+                AuditOutcome::Denied,
+            );
+            deadletter::report(
+                &self.tables.tables,
+                &self.state,
+                wire_expr,
+                payload,
+                DeadLetterReason::Denied,
+            );
+            return false;
+        }
+        audit::record(
+            &self.tables,
+            &self.state,
+            AuditOperation::Publish,
+            wire_expr,
+            AuditOutcome::Admitted,
+        );
+        true
+    }
+}
+
 impl Primitives for Face {
     fn send_declare(&self, msg: zenoh_protocol::network::Declare) {
         let ctrl_lock = zlock!(self.tables.ctrl_lock);
@@ -164,7 +297,46 @@ impl Primitives for Face {
             zenoh_protocol::network::DeclareBody::UndeclareKeyExpr(m) => {
                 unregister_expr(&self.tables, &mut self.state.clone(), m.id);
             }
+            zenoh_protocol::network::DeclareBody::DeclareKeyExprs(m) => {
+                let rejected = register_exprs(&self.tables, &mut self.state.clone(), &m.mappings);
+                if !rejected.is_empty() {
+                    self.state.primitives.send_declare(Declare {
+                        ext_qos: ext::QoSType::declare_default(),
+                        ext_tstamp: None,
+                        ext_nodeid: ext::NodeIdType::default(),
+                        body: DeclareBody::RejectKeyExprs(RejectKeyExprs { ids: rejected }),
+                    });
+                }
+            }
+            zenoh_protocol::network::DeclareBody::RejectKeyExprs(m) => {
+                let mut wtables = zwrite!(self.tables.tables);
+                let mut face = self.state.clone();
+                for res in remap_exprs(&mut wtables, &mut face, &m.ids) {
+                    pubsub_remap_face(&mut face, &res);
+                    queries_remap_face(&mut face, &res);
+                }
+            }
+            zenoh_protocol::network::DeclareBody::DeclareSubscriber(m)
+                if !namespace::face_allows(&self.tables, &self.state, &m.wire_expr) =>
+            {
+                audit::record(
+                    &self.tables,
+                    &self.state,
+                    AuditOperation::DeclareSubscriber,
+                    &m.wire_expr,
+                    AuditOutcome::Denied,
+                );
+            }
+            zenoh_protocol::network::DeclareBody::DeclareSubscriber(m)
+                if !declares_canon(&self.tables, &self.state, &m.wire_expr) => {}
             zenoh_protocol::network::DeclareBody::DeclareSubscriber(m) => {
+                audit::record(
+                    &self.tables,
+                    &self.state,
+                    AuditOperation::DeclareSubscriber,
+                    &m.wire_expr,
+                    AuditOutcome::Admitted,
+                );
                 let rtables = zread!(self.tables.tables);
                 match (rtables.whatami, self.state.whatami) {
                     (WhatAmI::Router, WhatAmI::Router) => {
@@ -200,21 +372,25 @@ impl Primitives for Face {
                                 )
                             }
                         } else {
-                            declare_client_subscription(
+                            declare_client_subscription_filtered(
                                 &self.tables,
                                 rtables,
                                 &mut self.state.clone(),
                                 &m.wire_expr,
                                 &m.ext_info,
+                                m.id,
+                                parse_sub_filter(m.ext_filter.as_ref()),
                             )
                         }
                     }
-                    _ => declare_client_subscription(
+                    _ => declare_client_subscription_filtered(
                         &self.tables,
                         rtables,
                         &mut self.state.clone(),
                         &m.wire_expr,
                         &m.ext_info,
+                        m.id,
+                        parse_sub_filter(m.ext_filter.as_ref()),
                     ),
                 }
             }
@@ -268,7 +444,27 @@ impl Primitives for Face {
                     ),
                 }
             }
+            zenoh_protocol::network::DeclareBody::DeclareQueryable(m)
+                if !namespace::face_allows(&self.tables, &self.state, &m.wire_expr) =>
+            {
+                audit::record(
+                    &self.tables,
+                    &self.state,
+                    AuditOperation::DeclareQueryable,
+                    &m.wire_expr,
+                    AuditOutcome::Denied,
+                );
+            }
+            zenoh_protocol::network::DeclareBody::DeclareQueryable(m)
+                if !declares_canon(&self.tables, &self.state, &m.wire_expr) => {}
             zenoh_protocol::network::DeclareBody::DeclareQueryable(m) => {
+                audit::record(
+                    &self.tables,
+                    &self.state,
+                    AuditOperation::DeclareQueryable,
+                    &m.wire_expr,
+                    AuditOutcome::Admitted,
+                );
                 let rtables = zread!(self.tables.tables);
                 match (rtables.whatami, self.state.whatami) {
                     (WhatAmI::Router, WhatAmI::Router) => {
@@ -310,6 +506,7 @@ impl Primitives for Face {
                                 &mut self.state.clone(),
                                 &m.wire_expr,
                                 &m.ext_info,
+                                m.id,
                             )
                         }
                     }
@@ -319,6 +516,7 @@ impl Primitives for Face {
                         &mut self.state.clone(),
                         &m.wire_expr,
                         &m.ext_info,
+                        m.id,
                     ),
                 }
             }
@@ -378,10 +576,34 @@ impl Primitives for Face {
             zenoh_protocol::network::DeclareBody::FinalInterest(_m) => todo!(),
             zenoh_protocol::network::DeclareBody::UndeclareInterest(_m) => todo!(),
         }
+        mappings::limit_local_mappings(&self.tables);
         drop(ctrl_lock);
     }
 
-    fn send_push(&self, msg: Push) {
+    fn send_push(&self, mut msg: Push, _reliability: Reliability) {
+        if let Some(batch) = msg.payload.ext_batch_mut().take() {
+            let entries = std::iter::once((msg.wire_expr, msg.payload))
+                .chain(
+                    batch
+                        .entries
+                        .into_iter()
+                        .map(|entry| (entry.wire_expr, entry.payload)),
+                )
+                .filter(|(wire_expr, payload)| self.admits_push(wire_expr, payload))
+                .collect();
+            full_reentrant_route_data_batch(
+                &self.tables.tables,
+                &self.state,
+                msg.ext_qos,
+                msg.ext_nodeid.node_id as u64,
+                batch.id,
+                entries,
+            );
+            return;
+        }
+        if !self.admits_push(&msg.wire_expr, &msg.payload) {
+            return;
+        }
         full_reentrant_route_data(
             &self.tables.tables,
             &self.state,
@@ -392,9 +614,54 @@ impl Primitives for Face {
         );
     }
 
+    fn send_push_reported(&self, msg: Push, reliability: Reliability, ack: bool) -> PushReport {
+        // The batches are routed entry by entry, without report
+        if msg.payload.ext_batch().is_some() {
+            self.send_push(msg, reliability);
+            return PushReport::default();
+        }
+        if !self.admits_push(&msg.wire_expr, &msg.payload) {
+            return PushReport::default();
+        }
+        full_reentrant_route_data_reported(
+            &self.tables.tables,
+            &self.state,
+            &msg.wire_expr,
+            msg.ext_qos,
+            msg.payload,
+            msg.ext_nodeid.node_id as u64,
+            ack,
+        )
+    }
+
     fn send_request(&self, msg: Request) {
+        if !namespace::face_allows(&self.tables, &self.state, &msg.wire_expr) {
+            // Let the querier know that no reply will come
+            if let RequestBody::Query(_) = msg.payload {
+                audit::record(
+                    &self.tables,
+                    &self.state,
+                    AuditOperation::Query,
+                    &msg.wire_expr,
+                    AuditOutcome::Denied,
+                );
+                self.state.primitives.send_response_final(ResponseFinal {
+                    rid: msg.id,
+                    ext_qos: response::ext::QoSType::response_final_default(),
+                    ext_tstamp: None,
+                });
+            }
+            return;
+        }
         match msg.payload {
             RequestBody::Query(_) => {
+                audit::record(
+                    &self.tables,
+                    &self.state,
+                    AuditOperation::Query,
+                    &msg.wire_expr,
+                    AuditOutcome::Admitted,
+                );
                 route_query(
                     &self.tables,
                     &self.state,
@@ -405,6 +672,8 @@ impl Primitives for Face {
                     // consolidation,
                     msg.payload,
                     msg.ext_nodeid.node_id as u64,
+                    msg.ext_correlation,
+                    msg.ext_repliers,
                 );
             }
             RequestBody::Pull(_) => {
@@ -417,11 +686,15 @@ impl Primitives for Face {
     }
 
     fn send_response(&self, msg: Response) {
+        if !namespace::face_allows(&self.tables, &self.state, &msg.wire_expr) {
+            return;
+        }
         route_send_response(
             &self.tables,
             &mut self.state.clone(),
             msg.rid,
             msg.ext_respid,
+            msg.ext_correlation,
             msg.wire_expr,
             msg.payload,
         );
@@ -0,0 +1,293 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use super::face::FaceState;
+use super::resource::Resource;
+use super::router::{Tables, TablesLock};
+use crate::prelude::json;
+use std::collections::{HashMap, VecDeque};
+use std::mem::size_of;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use zenoh_core::{zread, zwrite};
+use zenoh_protocol::{
+    core::{ExprId, ZenohId},
+    network::declare::{ext, Declare, DeclareBody, UndeclareKeyExpr},
+};
+use zenoh_sync::get_mut_unchecked;
+use zenoh_util::clock::Clock;
+
+/// The limits on the key expression mappings of the faces, see `limits/keyexpr_mappings`.
+pub(crate) struct MappingLimits {
+    /// The maximum number of mappings declared to each face, unlimited if `None`.
+    pub(crate) max_local: Option<usize>,
+    /// The number of mappings declared by a face from which an alarm is logged.
+    pub(crate) remote_alarm: usize,
+    /// How long an evicted mapping stays declared, for the messages already sent with it.
+    pub(crate) grace_period: Duration,
+    pub(crate) clock: Arc<dyn Clock>,
+}
+
+/// The last use of the key expression mappings declared to a face, and the mappings evicted
+/// from it waiting for their grace period to elapse before being undeclared.
+///
+/// The uses are recorded with an atomic tick on every resolution of a route, without lock: the
+/// mappings are only inserted and removed under the tables write lock.
+#[derive(Default)]
+pub(crate) struct LocalMappingsLru {
+    next_tick: AtomicU64,
+    last_use: HashMap<ExprId, AtomicU64>,
+    evicted: VecDeque<(Instant, ExprId)>,
+    // Whether mappings were inserted since the limit was last enforced
+    inserted: bool,
+}
+
+impl LocalMappingsLru {
+    /// Marks the mapping as the most recently used one.
+    #[inline]
+    pub(crate) fn touch(&self, expr_id: ExprId) {
+        if let Some(last_use) = self.last_use.get(&expr_id) {
+            last_use.store(
+                self.next_tick.fetch_add(1, Ordering::Relaxed),
+                Ordering::Relaxed,
+            );
+        }
+    }
+
+    /// Tracks a mapping declared to the face, as the most recently used one.
+    pub(crate) fn insert(&mut self, expr_id: ExprId) {
+        let tick = self.next_tick.fetch_add(1, Ordering::Relaxed);
+        self.last_use.insert(expr_id, AtomicU64::new(tick));
+        self.inserted = true;
+    }
+
+    /// Forgets a mapping removed from the face.
+    pub(crate) fn remove(&mut self, expr_id: ExprId) {
+        self.last_use.remove(&expr_id);
+    }
+
+    /// Whether the mapping was evicted and is waiting to be undeclared.
+    pub(crate) fn is_evicted(&self, expr_id: ExprId) -> bool {
+        self.evicted.iter().any(|(_, id)| *id == expr_id)
+    }
+
+    /// Evicts the least recently used mappings beyond `max`, returning them.
+    fn evict(&mut self, max: usize, now: Instant) -> Vec<ExprId> {
+        let count = self.last_use.len().saturating_sub(max);
+        if count == 0 {
+            return vec![];
+        }
+        let mut by_use = self
+            .last_use
+            .iter()
+            .map(|(expr_id, last_use)| (last_use.load(Ordering::Relaxed), *expr_id))
+            .collect::<Vec<_>>();
+        by_use.select_nth_unstable(count - 1);
+        by_use.truncate(count);
+        by_use.sort_unstable();
+        by_use
+            .into_iter()
+            .map(|(_, expr_id)| {
+                self.last_use.remove(&expr_id);
+                self.evicted.push_back((now, expr_id));
+                expr_id
+            })
+            .collect()
+    }
+
+    /// Returns the evicted mappings whose grace period elapsed.
+    fn expired(&mut self, now: Instant, grace_period: Duration) -> Vec<ExprId> {
+        let mut expired = vec![];
+        while let Some((at, expr_id)) = self.evicted.front() {
+            if now.saturating_duration_since(*at) < grace_period {
+                break;
+            }
+            expired.push(*expr_id);
+            self.evicted.pop_front();
+        }
+        expired
+    }
+
+    fn memory(&self) -> usize {
+        self.last_use.capacity() * size_of::<(ExprId, AtomicU64)>()
+            + self.evicted.capacity() * size_of::<(Instant, ExprId)>()
+    }
+}
+
+/// Recomputes the cached routes of `res` and of its descendants, whose key expressions may
+/// have been sent with a mapping of `res` that changed.
+pub(super) fn recompute_routes(tables: &mut Tables, res: &Arc<Resource>) {
+    for mut res in Resource::get_resources(res) {
+        if res.context.is_some() {
+            tables.compute_routes(&mut res);
+        }
+    }
+}
+
+/// Evicts the least recently used mappings declared to the faces beyond
+/// `limits/keyexpr_mappings/max_local`, and undeclares the evicted mappings whose grace period
+/// elapsed.
+///
+/// An evicted mapping is no longer used by the routes, the key expressions it mapped being sent
+/// in full, but stays declared for the grace period: the messages already sent with it may
+/// reach the face after its undeclaration otherwise, e.g. on another priority.
+///
+/// The limit is only enforced when mappings were declared to the faces since it was last
+/// enforced, the evicted mappings being undeclared at the next declaration of a mapping after
+/// their grace period.
+pub(super) fn limit_local_mappings(tables: &TablesLock) {
+    {
+        let rtables = zread!(tables.tables);
+        let is_limited = matches!(
+            rtables.mapping_limits,
+            Some(MappingLimits {
+                max_local: Some(_),
+                ..
+            })
+        );
+        if !is_limited
+            || !rtables
+                .faces
+                .values()
+                .any(|face| face.local_mappings_lru.inserted)
+        {
+            return;
+        }
+    }
+    let mut wtables = zwrite!(tables.tables);
+    let (max_local, grace_period, now) = match wtables.mapping_limits.as_ref() {
+        Some(MappingLimits {
+            max_local: Some(max_local),
+            grace_period,
+            clock,
+            ..
+        }) => (*max_local, *grace_period, clock.now()),
+        _ => return,
+    };
+    let faces = wtables
+        .faces
+        .values()
+        .filter(|face| face.local_mappings_lru.inserted)
+        .cloned()
+        .collect::<Vec<_>>();
+    for mut face in faces {
+        let evicted = {
+            let lru = &mut get_mut_unchecked(&mut face).local_mappings_lru;
+            lru.inserted = false;
+            lru.evict(max_local, now)
+        };
+        for expr_id in evicted {
+            if let Some(mut res) = face.local_mappings.get(&expr_id).cloned() {
+                log::trace!(
+                    "Evict mapping {} of {} declared to {}",
+                    expr_id,
+                    res.expr(),
+                    face
+                );
+                if let Some(ctx) = get_mut_unchecked(&mut res).session_ctxs.get_mut(&face.id) {
+                    get_mut_unchecked(ctx).local_expr_id = None;
+                }
+                recompute_routes(&mut wtables, &res);
+            }
+        }
+
+        let expired = get_mut_unchecked(&mut face)
+            .local_mappings_lru
+            .expired(now, grace_period);
+        for expr_id in expired {
+            if let Some(mut res) = get_mut_unchecked(&mut face).local_mappings.remove(&expr_id) {
+                face.primitives.send_declare(Declare {
+                    ext_qos: ext::QoSType::declare_default(),
+                    ext_tstamp: None,
+                    ext_nodeid: ext::NodeIdType::default(),
+                    body: DeclareBody::UndeclareKeyExpr(UndeclareKeyExpr { id: expr_id }),
+                });
+                Resource::clean(&mut res);
+            }
+        }
+    }
+}
+
+/// Logs an alarm when the number of mappings declared by `face` exceeds
+/// `limits/keyexpr_mappings/remote_alarm`, once until it goes back under it.
+pub(super) fn check_remote_mappings(tables: &Tables, face: &FaceState) {
+    let remote_alarm = match tables.mapping_limits.as_ref() {
+        Some(limits) => limits.remote_alarm,
+        None => return,
+    };
+    let count = face.remote_mappings.len();
+    if count <= remote_alarm {
+        face.remote_mappings_alarm.store(false, Ordering::Relaxed);
+    } else if !face.remote_mappings_alarm.swap(true, Ordering::Relaxed) {
+        log::warn!(
+            "Alarm: {} declared {} key expression mappings, more than {} (limits/keyexpr_mappings/remote_alarm)",
+            face,
+            count,
+            remote_alarm
+        );
+    }
+}
+
+/// The key expression mappings of the faces of `zid` and an estimate of their memory.
+pub(crate) fn mappings_info(tables: &Tables, zid: &ZenohId) -> json::MappingsInfo {
+    let entry_size = size_of::<(ExprId, Arc<Resource>)>();
+    let mut info = json::MappingsInfo::default();
+    for face in tables.faces.values().filter(|face| face.zid == *zid) {
+        let lru = &face.local_mappings_lru;
+        let evicted = lru.evicted.len();
+        info.local += face.local_mappings.len().saturating_sub(evicted);
+        info.evicted += evicted;
+        info.remote += face.remote_mappings.len();
+        info.memory += (face.local_mappings.capacity() + face.remote_mappings.capacity())
+            * entry_size
+            + lru.memory();
+    }
+    info
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn local_mappings_lru() {
+        let now = Instant::now();
+        let grace_period = Duration::from_secs(1);
+        let mut lru = LocalMappingsLru::default();
+        for expr_id in 1..=4 {
+            lru.insert(expr_id);
+        }
+        // Using a mapping makes it the most recently used one
+        lru.touch(1);
+        lru.remove(3);
+
+        assert_eq!(lru.evict(2, now), vec![2]);
+        assert!(lru.is_evicted(2));
+        assert_eq!(lru.evict(0, now + grace_period / 2), vec![4, 1]);
+        assert!(lru.evict(0, now).is_empty());
+
+        // The evicted mappings are released in order once their grace period elapsed
+        assert!(lru.expired(now, grace_period).is_empty());
+        assert_eq!(lru.expired(now + grace_period, grace_period), vec![2]);
+        assert_eq!(
+            lru.expired(now + 2 * grace_period, grace_period),
+            vec![4, 1]
+        );
+        assert!(!lru.is_evicted(2));
+
+        // The mappings not tracked are not used
+        lru.touch(5);
+        assert!(lru.evict(0, now).is_empty());
+    }
+}
@@ -17,12 +17,23 @@
 //! This module is intended for Zenoh's internal use.
 //!
 //! [Click here for Zenoh's documentation](../zenoh/index.html)
+pub mod audit;
+pub mod deadletter;
+pub(crate) mod dedup;
 pub mod face;
+pub(crate) mod mappings;
+pub mod mutation;
+pub(crate) mod namespace;
 pub mod network;
 pub mod pubsub;
 pub mod queries;
+pub(crate) mod querylimit;
+pub(crate) mod querythrottle;
+pub(crate) mod ratelimit;
+pub(crate) mod refresh;
 pub mod resource;
 pub mod router;
+pub(crate) mod trace;
 
 use super::runtime;
 
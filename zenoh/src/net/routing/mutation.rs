@@ -0,0 +1,401 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use super::face::FaceState;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, RwLock};
+use zenoh_buffers::SplitBuffer;
+#[cfg(feature = "shared-memory")]
+use zenoh_buffers::{reader::HasReader, ZBuf, ZSliceKind};
+#[cfg(feature = "shared-memory")]
+use zenoh_codec::{RCodec, Zenoh080};
+use zenoh_config::{MutationConf, MutationKind};
+use zenoh_protocol::{
+    core::{
+        key_expr::{keyexpr, OwnedKeyExpr},
+        WhatAmIMatcher, ZenohId,
+    },
+    zenoh::{PushBody, Put},
+};
+#[cfg(feature = "shared-memory")]
+use zenoh_result::{zerror, ZResult};
+#[cfg(feature = "shared-memory")]
+use zenoh_shm::{SharedMemoryBufInfo, SharedMemoryReader};
+
+/// A hook mutating the payload of the samples sent to some faces, applied by the `mutations`
+/// rules referring to the name it is registered under on the [`Runtime`](crate::runtime::Runtime).
+pub trait SampleMutation: Send + Sync {
+    /// Returns the payload to send instead of `payload` for a sample on `key_expr`,
+    /// or `None` if the sample must not be sent.
+    fn mutate(&self, key_expr: &keyexpr, payload: &[u8]) -> Option<Vec<u8>>;
+}
+
+struct Truncate(usize);
+
+impl SampleMutation for Truncate {
+    fn mutate(&self, _key_expr: &keyexpr, payload: &[u8]) -> Option<Vec<u8>> {
+        Some(payload[..payload.len().min(self.0)].to_vec())
+    }
+}
+
+struct Replace(Vec<u8>);
+
+impl SampleMutation for Replace {
+    fn mutate(&self, _key_expr: &keyexpr, _payload: &[u8]) -> Option<Vec<u8>> {
+        Some(self.0.clone())
+    }
+}
+
+enum Mutator {
+    BuiltIn(Box<dyn SampleMutation>),
+    // The name of the hook registered on the runtime
+    Custom(String),
+}
+
+struct MutationRule {
+    keyexprs: Vec<OwnedKeyExpr>,
+    zids: HashSet<ZenohId>,
+    whatami: Option<WhatAmIMatcher>,
+    mutator: Mutator,
+}
+
+impl MutationRule {
+    fn matches(&self, key: &keyexpr, face: &FaceState) -> bool {
+        (self.zids.is_empty() || self.zids.contains(&face.zid))
+            && self.whatami.map_or(true, |w| w.matches(face.whatami))
+            && self.keyexprs.iter().any(|ke| ke.includes(key))
+    }
+}
+
+/// What becomes of a sample sent to a face.
+pub(crate) enum Egress {
+    /// The sample is sent as is.
+    Unchanged,
+    /// The sample is sent with a mutated payload.
+    Mutated(PushBody),
+    /// The sample is not sent.
+    Dropped,
+}
+
+/// Mutates the payload of the samples sent to some faces, e.g. to redact them before they leave
+/// a site. The samples sent to the local sessions are never mutated.
+pub(crate) struct Mutations {
+    zid: ZenohId,
+    rules: Vec<MutationRule>,
+    hooks: RwLock<HashMap<String, Arc<dyn SampleMutation>>>,
+    // The reader of the shared memory the payloads to mutate are copied out of
+    #[cfg(feature = "shared-memory")]
+    shm_reader: RwLock<SharedMemoryReader>,
+}
+
+impl Mutations {
+    /// Returns `None` if there is no mutation rule.
+    pub(crate) fn new(zid: ZenohId, config: &[MutationConf]) -> Option<Self> {
+        if config.is_empty() {
+            return None;
+        }
+        let rules = config
+            .iter()
+            .map(|c| MutationRule {
+                keyexprs: c.keyexprs.clone(),
+                zids: c.zids.iter().copied().collect(),
+                whatami: c.whatami,
+                mutator: match &c.mutation {
+                    MutationKind::Truncate(size) => Mutator::BuiltIn(Box::new(Truncate(*size))),
+                    MutationKind::Replace(value) => {
+                        Mutator::BuiltIn(Box::new(Replace(value.as_bytes().to_vec())))
+                    }
+                    MutationKind::Custom(name) => Mutator::Custom(name.clone()),
+                },
+            })
+            .collect();
+        Some(Mutations {
+            zid,
+            rules,
+            hooks: RwLock::new(HashMap::new()),
+            #[cfg(feature = "shared-memory")]
+            shm_reader: RwLock::new(SharedMemoryReader::new()),
+        })
+    }
+
+    /// Registers the hook applied by the rules with a `custom` mutation of the given name.
+    pub(crate) fn add_hook(&self, name: String, hook: Arc<dyn SampleMutation>) {
+        self.hooks.write().unwrap().insert(name, hook);
+    }
+
+    /// Applies the first rule matching `key` and `outface` to a sample sent to `outface`.
+    ///
+    /// The mutated payload is always a copy: a payload in shared memory is copied out of it to be
+    /// mutated, never in place, and the mutated sample is sent out of the shared memory.
+    pub(crate) fn mutate(&self, key: &str, outface: &FaceState, payload: &PushBody) -> Egress {
+        let PushBody::Put(put) = payload else {
+            return Egress::Unchanged;
+        };
+        if outface.zid == self.zid {
+            return Egress::Unchanged;
+        }
+        let Ok(ke) = keyexpr::new(key) else {
+            return Egress::Unchanged;
+        };
+        let Some(rule) = self.rules.iter().find(|r| r.matches(ke, outface)) else {
+            return Egress::Unchanged;
+        };
+
+        // The payload of a shared memory sample only references its value
+        #[cfg(feature = "shared-memory")]
+        let payload = match put.ext_shm {
+            Some(_) => match self.read_shm(&put.payload) {
+                Ok(payload) => Cow::Owned(payload),
+                Err(e) => {
+                    log::warn!(
+                        "Drop sample on {key}: its shared memory payload can't be read: {e}"
+                    );
+                    return Egress::Dropped;
+                }
+            },
+            None => put.payload.contiguous(),
+        };
+        #[cfg(not(feature = "shared-memory"))]
+        let payload: Cow<[u8]> = put.payload.contiguous();
+        let mutated = match &rule.mutator {
+            Mutator::BuiltIn(mutation) => mutation.mutate(ke, &payload),
+            Mutator::Custom(name) => match self.hooks.read().unwrap().get(name) {
+                Some(mutation) => mutation.mutate(ke, &payload),
+                None => {
+                    log::warn!("Drop sample on {key}: no sample mutation registered as `{name}`");
+                    None
+                }
+            },
+        };
+        match mutated {
+            Some(mutated) => {
+                outface.count_mutated_sample();
+                Egress::Mutated(PushBody::Put(Put {
+                    payload: mutated.into(),
+                    // The checksum of the publisher doesn't match the mutated payload
+                    ext_integrity: None,
+                    #[cfg(feature = "shared-memory")]
+                    ext_shm: None,
+                    ..put.clone()
+                }))
+            }
+            None => Egress::Dropped,
+        }
+    }
+
+    /// Copies the value of a payload referencing shared memory out of it.
+    #[cfg(feature = "shared-memory")]
+    fn read_shm(&self, payload: &ZBuf) -> ZResult<Vec<u8>> {
+        let codec = Zenoh080::new();
+        let mut value = Vec::with_capacity(payload.len());
+        for zslice in payload.zslices() {
+            if zslice.kind != ZSliceKind::ShmPtr {
+                value.extend_from_slice(zslice.as_slice());
+                continue;
+            }
+            let info: SharedMemoryBufInfo = codec
+                .read(&mut zslice.as_slice().reader())
+                .map_err(|e| zerror!("{:?}", e))?;
+            let shmb = self.shm_reader.write().unwrap().read_shmbuf(&info)?;
+            value.extend_from_slice(shmb.as_slice());
+            // The reference of the sample to the buffer is kept for the other faces
+            shmb.inc_ref_count();
+        }
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::TryFrom;
+    use std::sync::atomic::Ordering;
+    use zenoh_buffers::ZBuf;
+    use zenoh_protocol::core::WhatAmI;
+    use zenoh_protocol::zenoh::Del;
+    use zenoh_transport::DummyPrimitives;
+
+    fn face(zid: u8, whatami: WhatAmI) -> Arc<FaceState> {
+        FaceState::new(
+            zid as usize,
+            ZenohId::try_from([zid]).unwrap(),
+            whatami,
+            #[cfg(feature = "stats")]
+            None,
+            Arc::new(DummyPrimitives),
+            0,
+            None,
+            false,
+            None,
+            None,
+            None,
+        )
+    }
+
+    fn put(payload: &[u8]) -> PushBody {
+        PushBody::Put(Put {
+            timestamp: None,
+            encoding: Default::default(),
+            ext_sinfo: None,
+            #[cfg(feature = "shared-memory")]
+            ext_shm: None,
+            ext_integrity: None,
+            ext_batch: None,
+            ext_unknown: vec![],
+            payload: payload.to_vec().into(),
+        })
+    }
+
+    fn payload(egress: Egress) -> Option<Vec<u8>> {
+        match egress {
+            Egress::Mutated(PushBody::Put(put)) => Some(put.payload.contiguous().to_vec()),
+            Egress::Mutated(PushBody::Del(_)) => panic!("A delete was mutated"),
+            Egress::Unchanged => Some(b"unchanged".to_vec()),
+            Egress::Dropped => None,
+        }
+    }
+
+    struct Upper;
+
+    impl SampleMutation for Upper {
+        fn mutate(&self, _key_expr: &keyexpr, payload: &[u8]) -> Option<Vec<u8>> {
+            Some(payload.to_ascii_uppercase())
+        }
+    }
+
+    #[test]
+    fn mutations() {
+        let rule = |keyexpr: &str, zids: &[u8], whatami, mutation| MutationConf {
+            keyexprs: vec![OwnedKeyExpr::try_from(keyexpr).unwrap()],
+            zids: zids
+                .iter()
+                .map(|z| ZenohId::try_from([*z]).unwrap())
+                .collect(),
+            whatami,
+            mutation,
+        };
+        let config = vec![
+            rule(
+                "test/redact/**",
+                &[2],
+                None,
+                MutationKind::Replace("***".to_string()),
+            ),
+            rule(
+                "test/**",
+                &[],
+                Some(WhatAmIMatcher::empty().router()),
+                MutationKind::Truncate(2),
+            ),
+            rule(
+                "custom/**",
+                &[],
+                None,
+                MutationKind::Custom("upper".to_string()),
+            ),
+        ];
+        let local = ZenohId::try_from([1]).unwrap();
+        assert!(Mutations::new(local, &[]).is_none());
+        let mutations = Mutations::new(local, &config).unwrap();
+        let session = face(1, WhatAmI::Client);
+        let client = face(2, WhatAmI::Client);
+        let router = face(3, WhatAmI::Router);
+
+        // The first rule matching both the key and the face applies
+        let egress = mutations.mutate("test/redact/a", &client, &put(b"secret"));
+        assert_eq!(payload(egress), Some(b"***".to_vec()));
+        let egress = mutations.mutate("test/redact/a", &router, &put(b"secret"));
+        assert_eq!(payload(egress), Some(b"se".to_vec()));
+        let egress = mutations.mutate("test/other", &client, &put(b"secret"));
+        assert_eq!(payload(egress), Some(b"unchanged".to_vec()));
+        let egress = mutations.mutate("other", &router, &put(b"secret"));
+        assert_eq!(payload(egress), Some(b"unchanged".to_vec()));
+        assert_eq!(client.mutated_samples.load(Ordering::Relaxed), 1);
+        assert_eq!(router.mutated_samples.load(Ordering::Relaxed), 1);
+
+        // The local sessions and the deletes are never mutated
+        let egress = mutations.mutate("test/redact/a", &session, &put(b"secret"));
+        assert_eq!(payload(egress), Some(b"unchanged".to_vec()));
+        let del = PushBody::Del(Del {
+            timestamp: None,
+            ext_sinfo: None,
+            ext_batch: None,
+            ext_unknown: vec![],
+        });
+        let egress = mutations.mutate("test/redact/a", &client, &del);
+        assert!(matches!(egress, Egress::Unchanged));
+
+        // The samples of a custom mutation are dropped until its hook is registered
+        let egress = mutations.mutate("custom/a", &client, &put(b"secret"));
+        assert!(payload(egress).is_none());
+        mutations.add_hook("upper".to_string(), Arc::new(Upper));
+        let mut fragmented = ZBuf::from(b"sec".to_vec());
+        fragmented.push_zslice(b"ret".to_vec().into());
+        let PushBody::Put(mut sample) = put(b"") else {
+            unreachable!()
+        };
+        sample.payload = fragmented;
+        let egress = mutations.mutate("custom/a", &client, &PushBody::Put(sample));
+        assert_eq!(payload(egress), Some(b"SECRET".to_vec()));
+    }
+
+    #[cfg(feature = "shared-memory")]
+    #[test]
+    fn mutations_shm() {
+        use zenoh_buffers::{writer::HasWriter, ZSlice};
+        use zenoh_codec::WCodec;
+        use zenoh_protocol::zenoh::ext::ShmType;
+        use zenoh_shm::SharedMemoryManager;
+
+        let config = vec![MutationConf {
+            keyexprs: vec![OwnedKeyExpr::try_from("test/**").unwrap()],
+            zids: vec![],
+            whatami: None,
+            mutation: MutationKind::Truncate(2),
+        }];
+        let mutations = Mutations::new(ZenohId::try_from([1]).unwrap(), &config).unwrap();
+        let client = face(2, WhatAmI::Client);
+
+        // A sample referencing its value in shared memory, as received from another process
+        let mut manager =
+            SharedMemoryManager::make("routing_mutations_shm".to_string(), 64).unwrap();
+        let mut shmb = manager.alloc(6).unwrap();
+        unsafe { shmb.as_mut_slice() }.copy_from_slice(b"secret");
+        let mut info = vec![];
+        Zenoh080::new()
+            .write(&mut info.writer(), &shmb.info)
+            .unwrap();
+        shmb.inc_ref_count();
+        let mut zslice: ZSlice = info.into();
+        zslice.kind = ZSliceKind::ShmPtr;
+        let PushBody::Put(mut sample) = put(b"") else {
+            unreachable!()
+        };
+        sample.payload = zslice.into();
+        sample.ext_shm = Some(ShmType::new());
+        let ref_count = shmb.ref_count();
+
+        // The value is copied out of the shared memory to be mutated, once for each face
+        for _ in 0..2 {
+            let egress = mutations.mutate("test/a", &client, &PushBody::Put(sample.clone()));
+            let Egress::Mutated(PushBody::Put(mutated)) = egress else {
+                panic!("The shared memory sample was not mutated")
+            };
+            assert!(mutated.ext_shm.is_none());
+            assert_eq!(mutated.payload.contiguous().as_ref(), b"se");
+        }
+        // The sample still references the buffer
+        assert_eq!(shmb.ref_count(), ref_count);
+    }
+}
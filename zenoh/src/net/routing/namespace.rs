@@ -0,0 +1,354 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use super::face::FaceState;
+use super::router::TablesLock;
+use super::PREFIX_LIVELINESS;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use zenoh_config::NamespaceConf;
+use zenoh_protocol::{
+    core::{
+        key_expr::{keyexpr, OwnedKeyExpr},
+        ExprId, WireExpr,
+    },
+    network::{
+        declare::common::ext::WireExprType, Declare, DeclareBody, Mapping, Push, Request, Response,
+        ResponseFinal,
+    },
+};
+use zenoh_transport::{Primitives, PushReport};
+
+/// A namespace confining key expressions under a prefix.
+///
+/// The admin space keys (starting with `@/`) are left out of the namespaces, except the
+/// liveliness keys which are namespaced after `@/liveliness`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Namespace {
+    prefix: OwnedKeyExpr,
+    liveliness: OwnedKeyExpr,
+}
+
+impl Namespace {
+    pub(crate) fn new(prefix: OwnedKeyExpr) -> Self {
+        let liveliness = ke_liveliness() / &*prefix;
+        Namespace { prefix, liveliness }
+    }
+
+    /// Indexes the namespaces of the configured rules by user.
+    pub(crate) fn from_config(rules: &[NamespaceConf]) -> Vec<(String, Namespace)> {
+        rules
+            .iter()
+            .flat_map(|rule| {
+                let namespace = Namespace::new(rule.namespace.clone());
+                rule.users
+                    .iter()
+                    .map(move |user| (user.clone(), namespace.clone()))
+            })
+            .collect()
+    }
+
+    #[inline]
+    pub(crate) fn prefix(&self) -> &keyexpr {
+        &self.prefix
+    }
+
+    /// Returns the key expression used outside the namespace for `key_expr`.
+    pub(crate) fn add(&self, key_expr: &keyexpr) -> OwnedKeyExpr {
+        match liveliness_suffix(key_expr) {
+            Some(suffix) => &self.liveliness / suffix,
+            None if is_admin(key_expr) => key_expr.into(),
+            None => &self.prefix / key_expr,
+        }
+    }
+
+    /// Returns the key expression used inside the namespace for `key_expr`, or `None` if
+    /// `key_expr` is not included by the namespace.
+    pub(crate) fn strip(&self, key_expr: &keyexpr) -> Option<OwnedKeyExpr> {
+        let prefix = match liveliness_suffix(key_expr) {
+            Some(_) => &self.liveliness,
+            // The admin space is reachable as long as it doesn't expose the liveliness of other namespaces
+            None if is_admin(key_expr) => {
+                let liveliness = ke_liveliness() / unsafe { keyexpr::from_str_unchecked("**") };
+                return (!key_expr.intersects(&liveliness)).then(|| key_expr.into());
+            }
+            None => &self.prefix,
+        };
+        // A wildcard straddling the prefix may be stripped into a key that doesn't map back to `key_expr`
+        match key_expr.strip_prefix(prefix).as_slice() {
+            [stripped] if *prefix.join(stripped).ok()? == *key_expr => {
+                if prefix == &self.liveliness {
+                    Some(ke_liveliness() / *stripped)
+                } else {
+                    Some((*stripped).into())
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether `key_expr` may be used by a face confined to the namespace.
+    #[inline]
+    pub(crate) fn includes(&self, key_expr: &keyexpr) -> bool {
+        self.strip(key_expr).is_some()
+    }
+}
+
+fn ke_liveliness() -> &'static keyexpr {
+    unsafe { keyexpr::from_str_unchecked(PREFIX_LIVELINESS) }
+}
+
+fn is_admin(key_expr: &keyexpr) -> bool {
+    key_expr.as_str() == "@" || key_expr.as_str().starts_with("@/")
+}
+
+fn liveliness_suffix(key_expr: &keyexpr) -> Option<&keyexpr> {
+    key_expr
+        .as_str()
+        .strip_prefix(PREFIX_LIVELINESS)
+        .and_then(|s| s.strip_prefix('/'))
+        .map(|s| unsafe { keyexpr::from_str_unchecked(s) })
+}
+
+/// Whether the key expression received on `face` is included by its namespace, if any.
+pub(crate) fn face_allows(tables: &TablesLock, face: &FaceState, expr: &WireExpr) -> bool {
+    let namespace = match face.namespace.as_ref() {
+        Some(namespace) => namespace,
+        None => return true,
+    };
+    let rtables = zread!(tables.tables);
+    let allowed = match rtables.get_mapping(face, &expr.scope, expr.mapping) {
+        Some(prefix) => {
+            let full = prefix.expr() + expr.suffix.as_ref();
+            keyexpr::new(full.as_str())
+                .map(|ke| namespace.includes(ke))
+                .unwrap_or(false)
+        }
+        None => false,
+    };
+    if !allowed {
+        log::warn!(
+            "{} confined to namespace {} used key expression {:?}: dropped",
+            face,
+            namespace.prefix(),
+            expr
+        );
+    }
+    allowed
+}
+
+/// The direction in which [`NamespacePrimitives`] translates the key expressions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Direction {
+    /// From the session to the network: the namespace is added.
+    Egress,
+    /// From the network to the session: the namespace is stripped.
+    Ingress,
+}
+
+/// Primitives translating the key expressions of the messages between the view of a session
+/// confined to a namespace and the view of the network.
+///
+/// Only the non scoped key expressions are translated, the scoped ones refer to a key expression
+/// translated when it was declared. The key expressions declared from the network outside of the
+/// namespace, such as a prefix of the namespace, are not declared to the session but kept to
+/// resolve the scoped key expressions using them.
+pub(crate) struct NamespacePrimitives {
+    namespace: Namespace,
+    direction: Direction,
+    primitives: Arc<dyn Primitives + Send + Sync>,
+    outside: Mutex<HashMap<ExprId, String>>,
+}
+
+impl NamespacePrimitives {
+    pub(crate) fn new(
+        namespace: Namespace,
+        direction: Direction,
+        primitives: Arc<dyn Primitives + Send + Sync>,
+    ) -> Self {
+        NamespacePrimitives {
+            namespace,
+            direction,
+            primitives,
+            outside: Mutex::new(HashMap::new()),
+        }
+    }
+
+    // Resolves the scoped key expressions of the network using a key expression declared outside
+    // of the namespace into non scoped ones
+    fn resolve(&self, expr: &mut WireExpr<'static>) {
+        if self.direction != Direction::Ingress
+            || expr.scope == 0
+            || expr.mapping != Mapping::Sender
+        {
+            return;
+        }
+        if let Some(prefix) = zlock!(self.outside).get(&expr.scope) {
+            expr.suffix = Cow::Owned(prefix.clone() + expr.suffix.as_ref());
+            expr.scope = 0;
+        }
+    }
+
+    fn map(&self, expr: &mut WireExpr<'static>) -> bool {
+        self.resolve(expr);
+        if expr.scope != 0 {
+            return true;
+        }
+        let key_expr = match keyexpr::new(expr.suffix.as_ref()) {
+            Ok(key_expr) => key_expr,
+            Err(_) => return false,
+        };
+        let mapped = match self.direction {
+            Direction::Egress => Some(self.namespace.add(key_expr)),
+            Direction::Ingress => self.namespace.strip(key_expr),
+        };
+        match mapped {
+            Some(mapped) => {
+                expr.suffix = Cow::Owned(mapped.into());
+                true
+            }
+            None => {
+                log::debug!(
+                    "Key expression {} outside of namespace {}: dropped",
+                    key_expr,
+                    self.namespace.prefix()
+                );
+                false
+            }
+        }
+    }
+
+    fn map_ext(&self, ext: &mut WireExprType) -> bool {
+        ext.wire_expr.suffix.is_empty() || self.map(&mut ext.wire_expr)
+    }
+
+    fn map_declared(&self, id: ExprId, expr: &mut WireExpr<'static>) -> bool {
+        self.resolve(expr);
+        if self.direction == Direction::Ingress
+            && expr.scope == 0
+            && keyexpr::new(expr.suffix.as_ref()).map_or(false, |ke| !self.namespace.includes(ke))
+        {
+            zlock!(self.outside).insert(id, expr.suffix.to_string());
+            return false;
+        }
+        self.map(expr)
+    }
+}
+
+impl Primitives for NamespacePrimitives {
+    fn send_declare(&self, mut msg: Declare) {
+        let mapped = match &mut msg.body {
+            DeclareBody::DeclareKeyExpr(m) => self.map_declared(m.id, &mut m.wire_expr),
+            DeclareBody::DeclareSubscriber(m) => self.map(&mut m.wire_expr),
+            DeclareBody::UndeclareSubscriber(m) => self.map_ext(&mut m.ext_wire_expr),
+            DeclareBody::DeclareQueryable(m) => self.map(&mut m.wire_expr),
+            DeclareBody::UndeclareQueryable(m) => self.map_ext(&mut m.ext_wire_expr),
+            DeclareBody::DeclareToken(m) => self.map(&mut m.wire_expr),
+            DeclareBody::UndeclareToken(m) => self.map_ext(&mut m.ext_wire_expr),
+            DeclareBody::DeclareInterest(m) => self.map(&mut m.wire_expr),
+            DeclareBody::UndeclareInterest(m) => self.map_ext(&mut m.ext_wire_expr),
+            DeclareBody::DeclareKeyExprs(m) => {
+                m.mappings.retain_mut(|m| self.map(&mut m.wire_expr));
+                !m.mappings.is_empty()
+            }
+            DeclareBody::UndeclareKeyExpr(m) => {
+                if self.direction == Direction::Ingress {
+                    zlock!(self.outside).remove(&m.id);
+                }
+                true
+            }
+            DeclareBody::FinalInterest(_) | DeclareBody::RejectKeyExprs(_) => true,
+        };
+        if mapped {
+            self.primitives.send_declare(msg);
+        }
+    }
+
+    fn send_push(&self, mut msg: Push, reliability: zenoh_protocol::core::Reliability) {
+        if self.map(&mut msg.wire_expr) {
+            // The other messages of the batch outside of the namespace are dropped
+            if let Some(batch) = msg.payload.ext_batch_mut() {
+                batch.entries.retain_mut(|e| self.map(&mut e.wire_expr));
+            }
+            self.primitives.send_push(msg, reliability);
+        }
+    }
+
+    fn send_push_reported(
+        &self,
+        mut msg: Push,
+        reliability: zenoh_protocol::core::Reliability,
+        ack: bool,
+    ) -> PushReport {
+        if self.map(&mut msg.wire_expr) {
+            self.primitives.send_push_reported(msg, reliability, ack)
+        } else {
+            PushReport::default()
+        }
+    }
+
+    fn send_request(&self, mut msg: Request) {
+        if self.map(&mut msg.wire_expr) {
+            self.primitives.send_request(msg);
+        }
+    }
+
+    fn send_response(&self, mut msg: Response) {
+        if self.map(&mut msg.wire_expr) {
+            self.primitives.send_response(msg);
+        }
+    }
+
+    fn send_response_final(&self, msg: ResponseFinal) {
+        self.primitives.send_response_final(msg);
+    }
+
+    fn send_close(&self) {
+        self.primitives.send_close();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ke(s: &str) -> OwnedKeyExpr {
+        OwnedKeyExpr::new(s).unwrap()
+    }
+
+    #[test]
+    fn namespace_add_strip() {
+        let ns = Namespace::new(ke("tenants/app_a"));
+        assert_eq!(ns.add(&ke("demo/a")), ke("tenants/app_a/demo/a"));
+        assert_eq!(ns.add(&ke("**")), ke("tenants/app_a/**"));
+        assert_eq!(ns.add(&ke("@/session/x/**")), ke("@/session/x/**"));
+        assert_eq!(
+            ns.add(&ke("@/liveliness/demo/a")),
+            ke("@/liveliness/tenants/app_a/demo/a")
+        );
+
+        assert_eq!(ns.strip(&ke("tenants/app_a/demo/a")), Some(ke("demo/a")));
+        assert_eq!(ns.strip(&ke("tenants/app_a/**")), Some(ke("**")));
+        assert_eq!(
+            ns.strip(&ke("@/liveliness/tenants/app_a/demo/a")),
+            Some(ke("@/liveliness/demo/a"))
+        );
+        assert_eq!(ns.strip(&ke("@/router/x")), Some(ke("@/router/x")));
+        assert_eq!(ns.strip(&ke("tenants/app_b/demo/a")), None);
+        assert_eq!(ns.strip(&ke("tenants/*/demo/a")), None);
+        assert_eq!(ns.strip(&ke("**")), None);
+        assert_eq!(ns.strip(&ke("@/liveliness/tenants/app_b/a")), None);
+        assert_eq!(ns.strip(&ke("@/**")), None);
+    }
+}
@@ -179,6 +179,17 @@ impl Network {
             .find(|idx| self.graph[*idx].zid == *zid)
     }
 
+    /// The number of routes cached by the resources for the nodes of this network, which are
+    /// indexed by the indexes of the nodes.
+    #[inline]
+    pub(crate) fn routes_len(&self) -> usize {
+        self.graph
+            .node_indices()
+            .map(|idx| idx.index() + 1)
+            .max()
+            .unwrap_or(0)
+    }
+
     #[inline]
     pub(crate) fn get_link(&self, id: usize) -> Option<&Link> {
         self.links.get(id)
@@ -859,6 +870,36 @@ impl Network {
         }
     }
 
+    /// Sends the new locators of this node to the other nodes, when they are gossiped.
+    pub(crate) fn update_locators(&mut self) {
+        if !self.gossip {
+            return;
+        }
+        self.graph[self.idx].sn += 1;
+        self.send_on_links(
+            vec![(
+                self.idx,
+                Details {
+                    zid: false,
+                    locators: true,
+                    links: true,
+                },
+            )],
+            |_| true,
+        );
+    }
+
+    /// Notifies the neighbors of the nodes of the network to the topology subscribers.
+    pub(crate) fn notify_topology(&self) {
+        self.runtime.topology.linkstate(
+            self.graph
+                .node_weights()
+                .filter(|node| !node.links.is_empty())
+                .map(|node| (node.zid, node.links.clone()))
+                .collect(),
+        );
+    }
+
     fn remove_detached_nodes(&mut self) -> Vec<(NodeIndex, Node)> {
         let mut dfs_stack = vec![self.idx];
         let mut visit_map = self.graph.visit_map();
@@ -11,34 +11,36 @@
 // Contributors:
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
+use super::deadletter::{self, DeadLetterReason};
 use super::face::FaceState;
+use super::mutation::Egress;
 use super::network::Network;
 use super::resource::{
     DataRoutes, Direction, PullCaches, Resource, Route, RoutingContext, SessionContext,
 };
 use super::router::{RoutingExpr, Tables, TablesLock};
+use crate::filter::Filter;
 use petgraph::graph::NodeIndex;
 use std::borrow::Cow;
 use std::collections::{HashMap, HashSet};
-use std::convert::TryFrom;
 use std::sync::RwLock;
 use std::sync::{Arc, RwLockReadGuard};
 use zenoh_core::zread;
 use zenoh_protocol::{
-    core::{
-        key_expr::{keyexpr, OwnedKeyExpr},
-        Reliability, WhatAmI, WireExpr, ZenohId,
-    },
+    core::{key_expr::keyexpr, EntityId, Reliability, WhatAmI, WireExpr, ZenohId},
     network::{
         declare::{
-            common::ext::WireExprType, ext, subscriber::ext::SubscriberInfo, Declare, DeclareBody,
-            DeclareSubscriber, Mode, UndeclareSubscriber,
+            common::ext::WireExprType,
+            ext,
+            subscriber::ext::{FilterType, SubscriberInfo},
+            Declare, DeclareBody, DeclareSubscriber, Mode, UndeclareSubscriber,
         },
         Push,
     },
-    zenoh::PushBody,
+    zenoh::{ext::BatchEntry, put::ext::BatchType, PushBody},
 };
 use zenoh_sync::get_mut_unchecked;
+use zenoh_transport::PushReport;
 
 #[inline]
 fn send_sourced_subscription_to_net_childs(
@@ -69,6 +71,7 @@ fn send_sourced_subscription_to_net_childs(
                                 id: 0, // TODO
                                 wire_expr: key_expr,
                                 ext_info: *sub_info,
+                                ext_filter: None,
                             }),
                         });
                     }
@@ -88,8 +91,20 @@ fn propagate_simple_subscription_to(
     src_face: &mut Arc<FaceState>,
     full_peer_net: bool,
 ) {
+    // The sessions and the peers of a peer-to-peer network hand the filters of the subscriptions
+    // over to the next hop, which drops the samples they don't match
+    let filter = match tables.whatami {
+        WhatAmI::Router => None,
+        WhatAmI::Peer if full_peer_net => None,
+        _ => subs_filter(res, dst_face),
+    };
+    // A subscription declared with a filter is declared again when it gets less selective
+    let stale_filter = match dst_face.local_sub_filters.get(res) {
+        Some(sent) => filter.as_ref() != Some(sent),
+        None => false,
+    };
     if (src_face.id != dst_face.id || res.expr().starts_with(super::PREFIX_LIVELINESS))
-        && !dst_face.local_subs.contains(res)
+        && (!dst_face.local_subs.contains(res) || stale_filter)
         && match tables.whatami {
             WhatAmI::Router => {
                 if full_peer_net {
@@ -112,15 +127,24 @@ fn propagate_simple_subscription_to(
         }
     {
         get_mut_unchecked(dst_face).local_subs.insert(res.clone());
+        match &filter {
+            Some(filter) => get_mut_unchecked(dst_face)
+                .local_sub_filters
+                .insert(res.clone(), filter.clone()),
+            None => get_mut_unchecked(dst_face).local_sub_filters.remove(res),
+        };
         let key_expr = Resource::decl_key(res, dst_face);
         dst_face.primitives.send_declare(Declare {
             ext_qos: ext::QoSType::declare_default(),
             ext_tstamp: None,
             ext_nodeid: ext::NodeIdType::default(),
             body: DeclareBody::DeclareSubscriber(DeclareSubscriber {
-                id: 0, // TODO
+                id: res.propagated_sub_id(tables, src_face),
                 wire_expr: key_expr,
                 ext_info: *sub_info,
+                ext_filter: filter.map(|filter| FilterType {
+                    expr: filter.to_string(),
+                }),
             }),
         });
     }
@@ -195,7 +219,11 @@ fn register_router_subscription(
     sub_info: &SubscriberInfo,
     router: ZenohId,
 ) {
-    if !res.context().router_subs.contains(&router) {
+    // A subscription is (re)registered when it is new or when it is upgraded to reliable
+    if !res.context().router_subs.contains(&router)
+        || (sub_info.reliability == Reliability::Reliable
+            && !res.context().reliable_router_subs.contains(&router))
+    {
         // Register router subscription
         {
             log::debug!(
@@ -207,6 +235,12 @@ fn register_router_subscription(
                 .context_mut()
                 .router_subs
                 .insert(router);
+            if sub_info.reliability == Reliability::Reliable {
+                get_mut_unchecked(res)
+                    .context_mut()
+                    .reliable_router_subs
+                    .insert(router);
+            }
             tables.router_subs.insert(res.clone());
         }
 
@@ -265,9 +299,7 @@ pub fn declare_router_subscription(
 
             let wtables = zwrite!(tables.tables);
             for (mut res, data_routes) in matches_data_routes {
-                get_mut_unchecked(&mut res)
-                    .context_mut()
-                    .update_data_routes(data_routes);
+                Resource::update_data_routes(&mut res, data_routes);
             }
             drop(wtables);
         }
@@ -285,11 +317,21 @@ fn register_peer_subscription(
     sub_info: &SubscriberInfo,
     peer: ZenohId,
 ) {
-    if !res.context().peer_subs.contains(&peer) {
+    // A subscription is (re)registered when it is new or when it is upgraded to reliable
+    if !res.context().peer_subs.contains(&peer)
+        || (sub_info.reliability == Reliability::Reliable
+            && !res.context().reliable_peer_subs.contains(&peer))
+    {
         // Register peer subscription
         {
             log::debug!("Register peer subscription {} (peer: {})", res.expr(), peer);
             get_mut_unchecked(res).context_mut().peer_subs.insert(peer);
+            if sub_info.reliability == Reliability::Reliable {
+                get_mut_unchecked(res)
+                    .context_mut()
+                    .reliable_peer_subs
+                    .insert(peer);
+            }
             tables.peer_subs.insert(res.clone());
         }
 
@@ -352,9 +394,7 @@ pub fn declare_peer_subscription(
 
             let wtables = zwrite!(tables.tables);
             for (mut res, data_routes) in matches_data_routes {
-                get_mut_unchecked(&mut res)
-                    .context_mut()
-                    .update_data_routes(data_routes);
+                Resource::update_data_routes(&mut res, data_routes);
             }
             drop(wtables);
         }
@@ -370,6 +410,8 @@ fn register_client_subscription(
     face: &mut Arc<FaceState>,
     res: &mut Arc<Resource>,
     sub_info: &SubscriberInfo,
+    id: EntityId,
+    filter: Option<Arc<Filter>>,
 ) {
     // Register subscription
     {
@@ -378,12 +420,20 @@ fn register_client_subscription(
         match res.session_ctxs.get_mut(&face.id) {
             Some(ctx) => match &ctx.subs {
                 Some(info) => {
+                    let filter = merge_filters(&ctx.sub_filter, &filter);
                     if Mode::Pull == info.mode {
                         get_mut_unchecked(ctx).subs = Some(*sub_info);
+                    } else if sub_info.reliability == Reliability::Reliable {
+                        get_mut_unchecked(ctx).subs = Some(SubscriberInfo {
+                            reliability: Reliability::Reliable,
+                            mode: info.mode,
+                        });
                     }
+                    get_mut_unchecked(ctx).sub_filter = filter;
                 }
                 None => {
                     get_mut_unchecked(ctx).subs = Some(*sub_info);
+                    get_mut_unchecked(ctx).sub_filter = filter;
                 }
             },
             None => {
@@ -394,22 +444,35 @@ fn register_client_subscription(
                         local_expr_id: None,
                         remote_expr_id: None,
                         subs: Some(*sub_info),
+                        sub_id: None,
+                        sub_filter: filter,
                         qabl: None,
+                        qabl_id: None,
                         last_values: HashMap::new(),
                     }),
                 );
             }
         }
+        if id != 0 {
+            if let Some(ctx) = res.session_ctxs.get_mut(&face.id) {
+                get_mut_unchecked(ctx).sub_id = Some(id);
+            }
+        }
     }
     get_mut_unchecked(face).remote_subs.insert(res.clone());
 }
 
-pub fn declare_client_subscription(
+/// Declares a client subscription, the data routed to the face for it having to match the given
+/// `filter` if any.
+#[allow(clippy::too_many_arguments)]
+pub fn declare_client_subscription_filtered(
     tables: &TablesLock,
     rtables: RwLockReadGuard<Tables>,
     face: &mut Arc<FaceState>,
     expr: &WireExpr,
     sub_info: &SubscriberInfo,
+    id: EntityId,
+    filter: Option<Arc<Filter>>,
 ) {
     log::debug!("Register client subscription");
     match rtables
@@ -438,7 +501,7 @@ pub fn declare_client_subscription(
                     (res, wtables)
                 };
 
-            register_client_subscription(&mut wtables, face, &mut res, sub_info);
+            register_client_subscription(&mut wtables, face, &mut res, sub_info, id, filter);
             let mut propa_sub_info = *sub_info;
             propa_sub_info.mode = Mode::Push;
             match wtables.whatami {
@@ -467,7 +530,7 @@ pub fn declare_client_subscription(
                         // This introduced a buffer overflow on windows
                         // TODO: Let's deactivate this on windows until Fixed
                         #[cfg(not(windows))]
-                        for mcast_group in &wtables.mcast_groups {
+                        for mcast_group in wtables.mcast_groups.iter_mut() {
                             mcast_group.primitives.send_declare(Declare {
                                 ext_qos: ext::QoSType::declare_default(),
                                 ext_tstamp: None,
@@ -476,8 +539,12 @@ pub fn declare_client_subscription(
                                     id: 0, // TODO
                                     wire_expr: res.expr().into(),
                                     ext_info: *sub_info,
+                                    ext_filter: None,
                                 }),
-                            })
+                            });
+                            get_mut_unchecked(mcast_group)
+                                .local_subs
+                                .insert(res.clone());
                         }
                     }
                 }
@@ -486,7 +553,7 @@ pub fn declare_client_subscription(
                     // This introduced a buffer overflow on windows
                     // TODO: Let's deactivate this on windows until Fixed
                     #[cfg(not(windows))]
-                    for mcast_group in &wtables.mcast_groups {
+                    for mcast_group in wtables.mcast_groups.iter_mut() {
                         mcast_group.primitives.send_declare(Declare {
                             ext_qos: ext::QoSType::declare_default(),
                             ext_tstamp: None,
@@ -495,8 +562,12 @@ pub fn declare_client_subscription(
                                 id: 0, // TODO
                                 wire_expr: res.expr().into(),
                                 ext_info: *sub_info,
+                                ext_filter: None,
                             }),
-                        })
+                        });
+                        get_mut_unchecked(mcast_group)
+                            .local_subs
+                            .insert(res.clone());
                     }
                 }
             }
@@ -509,9 +580,7 @@ pub fn declare_client_subscription(
 
             let wtables = zwrite!(tables.tables);
             for (mut res, data_routes) in matches_data_routes {
-                get_mut_unchecked(&mut res)
-                    .context_mut()
-                    .update_data_routes(data_routes);
+                Resource::update_data_routes(&mut res, data_routes);
             }
             drop(wtables);
         }
@@ -519,6 +588,41 @@ pub fn declare_client_subscription(
     }
 }
 
+/// Parses the filter of a subscription declaration. The subscriptions whose filter isn't
+/// understood are registered without filter, their subscriber evaluating it.
+pub(crate) fn parse_sub_filter(ext: Option<&FilterType>) -> Option<Arc<Filter>> {
+    let ext = ext?;
+    match ext.expr.parse::<Filter>() {
+        Ok(filter) => Some(Arc::new(filter)),
+        Err(e) => {
+            log::debug!("Ignore subscription filter `{}`: {}", ext.expr, e);
+            None
+        }
+    }
+}
+
+/// Returns the filter matching the data matched by both filters, `None` matching all the data.
+#[inline]
+fn merge_filters(f1: &Option<Arc<Filter>>, f2: &Option<Arc<Filter>>) -> Option<Arc<Filter>> {
+    match (f1, f2) {
+        (Some(f1), Some(f2)) if f1 == f2 => Some(f1.clone()),
+        (Some(f1), Some(f2)) => Some(Arc::new(f1.or(f2))),
+        _ => None,
+    }
+}
+
+/// Returns the filter of the subscriptions of the faces other than `dst_face` on the resource.
+#[inline]
+fn subs_filter(res: &Arc<Resource>, dst_face: &FaceState) -> Option<Arc<Filter>> {
+    let mut filters = res
+        .session_ctxs
+        .values()
+        .filter(|ctx| ctx.subs.is_some() && ctx.face.id != dst_face.id)
+        .map(|ctx| &ctx.sub_filter);
+    let filter = filters.next()?.clone();
+    filters.fold(filter, |f1, f2| merge_filters(&f1, f2))
+}
+
 #[inline]
 fn remote_router_subs(tables: &Tables, res: &Arc<Resource>) -> bool {
     res.context.is_some()
@@ -539,6 +643,33 @@ fn remote_peer_subs(tables: &Tables, res: &Arc<Resource>) -> bool {
             .any(|peer| peer != &tables.zid)
 }
 
+/// Returns the strictest of the two given reliabilities.
+#[inline]
+fn strictest(r1: Reliability, r2: Reliability) -> Reliability {
+    if r1 == Reliability::Reliable || r2 == Reliability::Reliable {
+        Reliability::Reliable
+    } else {
+        Reliability::BestEffort
+    }
+}
+
+/// Returns the strictest reliability required by the subscriptions on the given resource.
+#[inline]
+fn subs_reliability(res: &Arc<Resource>) -> Reliability {
+    let reliable_net_subs = res.context.as_ref().map_or(false, |ctx| {
+        !ctx.reliable_router_subs.is_empty() || !ctx.reliable_peer_subs.is_empty()
+    });
+    let reliable_client_subs = res.session_ctxs.values().any(|ctx| {
+        ctx.subs
+            .map_or(false, |info| info.reliability == Reliability::Reliable)
+    });
+    if reliable_net_subs || reliable_client_subs {
+        Reliability::Reliable
+    } else {
+        Reliability::BestEffort
+    }
+}
+
 #[inline]
 fn client_subs(res: &Arc<Resource>) -> Vec<Arc<FaceState>> {
     res.session_ctxs
@@ -604,10 +735,19 @@ fn propagate_forget_simple_subscription(tables: &mut Tables, res: &Arc<Resource>
                 }),
             });
             get_mut_unchecked(face).local_subs.remove(res);
+            get_mut_unchecked(face).local_sub_filters.remove(res);
         }
     }
 }
 
+// The undeclarations are not sent to the multicast groups: the soft-state refresh of the
+// declarations forgets them
+fn forget_mcast_subscription(tables: &mut Tables, res: &Arc<Resource>) {
+    for mcast_group in tables.mcast_groups.iter_mut() {
+        get_mut_unchecked(mcast_group).local_subs.remove(res);
+    }
+}
+
 fn propagate_forget_simple_subscription_to_peers(tables: &mut Tables, res: &Arc<Resource>) {
     if !tables.full_net(WhatAmI::Peer)
         && res.context().router_subs.len() == 1
@@ -641,6 +781,7 @@ fn propagate_forget_simple_subscription_to_peers(tables: &mut Tables, res: &Arc<
                 });
 
                 get_mut_unchecked(&mut face).local_subs.remove(res);
+                get_mut_unchecked(&mut face).local_sub_filters.remove(res);
             }
         }
     }
@@ -692,6 +833,10 @@ fn unregister_router_subscription(tables: &mut Tables, res: &mut Arc<Resource>,
         .context_mut()
         .router_subs
         .retain(|sub| sub != router);
+    get_mut_unchecked(res)
+        .context_mut()
+        .reliable_router_subs
+        .retain(|sub| sub != router);
 
     if res.context().router_subs.is_empty() {
         tables.router_subs.retain(|sub| !Arc::ptr_eq(sub, res));
@@ -738,9 +883,7 @@ pub fn forget_router_subscription(
                 drop(rtables);
                 let wtables = zwrite!(tables.tables);
                 for (mut res, data_routes) in matches_data_routes {
-                    get_mut_unchecked(&mut res)
-                        .context_mut()
-                        .update_data_routes(data_routes);
+                    Resource::update_data_routes(&mut res, data_routes);
                 }
                 Resource::clean(&mut res);
                 drop(wtables);
@@ -761,6 +904,10 @@ fn unregister_peer_subscription(tables: &mut Tables, res: &mut Arc<Resource>, pe
         .context_mut()
         .peer_subs
         .retain(|sub| sub != peer);
+    get_mut_unchecked(res)
+        .context_mut()
+        .reliable_peer_subs
+        .retain(|sub| sub != peer);
 
     if res.context().peer_subs.is_empty() {
         tables.peer_subs.retain(|sub| !Arc::ptr_eq(sub, res));
@@ -812,9 +959,7 @@ pub fn forget_peer_subscription(
                 drop(rtables);
                 let wtables = zwrite!(tables.tables);
                 for (mut res, data_routes) in matches_data_routes {
-                    get_mut_unchecked(&mut res)
-                        .context_mut()
-                        .update_data_routes(data_routes);
+                    Resource::update_data_routes(&mut res, data_routes);
                 }
                 Resource::clean(&mut res);
                 drop(wtables);
@@ -833,6 +978,7 @@ pub(crate) fn undeclare_client_subscription(
     log::debug!("Unregister client subscription {} for {}", res.expr(), face);
     if let Some(ctx) = get_mut_unchecked(res).session_ctxs.get_mut(&face.id) {
         get_mut_unchecked(ctx).subs = None;
+        get_mut_unchecked(ctx).sub_id = None;
     }
     get_mut_unchecked(face).remote_subs.remove(res);
 
@@ -853,12 +999,14 @@ pub(crate) fn undeclare_client_subscription(
                     undeclare_peer_subscription(tables, None, res, &tables.zid.clone());
                 } else {
                     propagate_forget_simple_subscription(tables, res);
+                    forget_mcast_subscription(tables, res);
                 }
             }
         }
         _ => {
             if client_subs.is_empty() {
                 propagate_forget_simple_subscription(tables, res);
+                forget_mcast_subscription(tables, res);
             }
         }
     }
@@ -880,6 +1028,7 @@ pub(crate) fn undeclare_client_subscription(
             });
 
             get_mut_unchecked(face).local_subs.remove(res);
+            get_mut_unchecked(face).local_sub_filters.remove(res);
         }
     }
 }
@@ -905,9 +1054,7 @@ pub fn forget_client_subscription(
 
                 let wtables = zwrite!(tables.tables);
                 for (mut res, data_routes) in matches_data_routes {
-                    get_mut_unchecked(&mut res)
-                        .context_mut()
-                        .update_data_routes(data_routes);
+                    Resource::update_data_routes(&mut res, data_routes);
                 }
                 Resource::clean(&mut res);
                 drop(wtables);
@@ -918,9 +1065,43 @@ pub fn forget_client_subscription(
     }
 }
 
+/// Re-declares to `face` the subscriptions whose key expression used the mapping of `prefix`,
+/// after `face` rejected it and it got re-mapped.
+pub(crate) fn pubsub_remap_face(face: &mut Arc<FaceState>, prefix: &Arc<Resource>) {
+    let subs = face
+        .local_subs
+        .iter()
+        .filter(|sub| {
+            Resource::nonwild_prefix(sub)
+                .0
+                .map_or(false, |p| Arc::ptr_eq(&p, prefix))
+        })
+        .cloned()
+        .collect::<Vec<Arc<Resource>>>();
+    for sub in subs {
+        let key_expr = Resource::decl_key(&sub, face);
+        face.primitives.send_declare(Declare {
+            ext_qos: ext::QoSType::declare_default(),
+            ext_tstamp: None,
+            ext_nodeid: ext::NodeIdType::default(),
+            body: DeclareBody::DeclareSubscriber(DeclareSubscriber {
+                id: 0, // TODO
+                wire_expr: key_expr,
+                ext_info: SubscriberInfo {
+                    reliability: subs_reliability(&sub),
+                    mode: Mode::Push,
+                },
+                ext_filter: face.local_sub_filters.get(&sub).map(|filter| FilterType {
+                    expr: filter.to_string(),
+                }),
+            }),
+        });
+    }
+}
+
 pub(crate) fn pubsub_new_face(tables: &mut Tables, face: &mut Arc<FaceState>) {
-    let sub_info = SubscriberInfo {
-        reliability: Reliability::Reliable, // @TODO
+    let sub_info = |res: &Arc<Resource>| SubscriberInfo {
+        reliability: subs_reliability(res),
         mode: Mode::Push,
     };
     match tables.whatami {
@@ -936,7 +1117,8 @@ pub(crate) fn pubsub_new_face(tables: &mut Tables, face: &mut Arc<FaceState>) {
                         body: DeclareBody::DeclareSubscriber(DeclareSubscriber {
                             id: 0, // TODO
                             wire_expr: key_expr,
-                            ext_info: sub_info,
+                            ext_info: sub_info(sub),
+                            ext_filter: None,
                         }),
                     });
                 }
@@ -960,7 +1142,8 @@ pub(crate) fn pubsub_new_face(tables: &mut Tables, face: &mut Arc<FaceState>) {
                             body: DeclareBody::DeclareSubscriber(DeclareSubscriber {
                                 id: 0, // TODO
                                 wire_expr: key_expr,
-                                ext_info: sub_info,
+                                ext_info: sub_info(sub),
+                                ext_filter: None,
                             }),
                         });
                     }
@@ -980,7 +1163,8 @@ pub(crate) fn pubsub_new_face(tables: &mut Tables, face: &mut Arc<FaceState>) {
                             body: DeclareBody::DeclareSubscriber(DeclareSubscriber {
                                 id: 0, // TODO
                                 wire_expr: key_expr,
-                                ext_info: sub_info,
+                                ext_info: sub_info(sub),
+                                ext_filter: None,
                             }),
                         });
                     }
@@ -997,7 +1181,7 @@ pub(crate) fn pubsub_new_face(tables: &mut Tables, face: &mut Arc<FaceState>) {
                             tables,
                             face,
                             sub,
-                            &sub_info,
+                            &sub_info(sub),
                             &mut src_face.clone(),
                             false,
                         );
@@ -1017,7 +1201,7 @@ pub(crate) fn pubsub_new_face(tables: &mut Tables, face: &mut Arc<FaceState>) {
                         tables,
                         face,
                         sub,
-                        &sub_info,
+                        &sub_info(sub),
                         &mut src_face.clone(),
                         false,
                     );
@@ -1041,9 +1225,7 @@ pub(crate) fn pubsub_remove_node(tables: &mut Tables, node: &ZenohId, net_type:
 
                 let matches_data_routes = compute_matches_data_routes_(tables, &res);
                 for (mut res, data_routes) in matches_data_routes {
-                    get_mut_unchecked(&mut res)
-                        .context_mut()
-                        .update_data_routes(data_routes);
+                    Resource::update_data_routes(&mut res, data_routes);
                 }
                 Resource::clean(&mut res)
             }
@@ -1069,9 +1251,7 @@ pub(crate) fn pubsub_remove_node(tables: &mut Tables, node: &ZenohId, net_type:
                 // compute_matches_data_routes(tables, &mut res);
                 let matches_data_routes = compute_matches_data_routes_(tables, &res);
                 for (mut res, data_routes) in matches_data_routes {
-                    get_mut_unchecked(&mut res)
-                        .context_mut()
-                        .update_data_routes(data_routes);
+                    Resource::update_data_routes(&mut res, data_routes);
                 }
                 Resource::clean(&mut res)
             }
@@ -1080,11 +1260,13 @@ pub(crate) fn pubsub_remove_node(tables: &mut Tables, node: &ZenohId, net_type:
     }
 }
 
+/// Propagates the subscribers to the new childs of the trees, returning the resources whose data
+/// routes are to be recomputed.
 pub(crate) fn pubsub_tree_change(
     tables: &mut Tables,
     new_childs: &[Vec<NodeIndex>],
     net_type: WhatAmI,
-) {
+) -> Vec<Arc<Resource>> {
     // propagate subs to new childs
     for (tree_sid, tree_childs) in new_childs.iter().enumerate() {
         if !tree_childs.is_empty() {
@@ -1099,14 +1281,21 @@ pub(crate) fn pubsub_tree_change(
                 };
 
                 for res in subs_res {
-                    let subs = match net_type {
-                        WhatAmI::Router => &res.context().router_subs,
-                        _ => &res.context().peer_subs,
+                    let (subs, reliable_subs) = match net_type {
+                        WhatAmI::Router => (
+                            &res.context().router_subs,
+                            &res.context().reliable_router_subs,
+                        ),
+                        _ => (&res.context().peer_subs, &res.context().reliable_peer_subs),
                     };
                     for sub in subs {
                         if *sub == tree_id {
                             let sub_info = SubscriberInfo {
-                                reliability: Reliability::Reliable, // @TODO
+                                reliability: if reliable_subs.contains(sub) {
+                                    Reliability::Reliable
+                                } else {
+                                    Reliability::BestEffort
+                                },
                                 mode: Mode::Push,
                             };
                             send_sourced_subscription_to_net_childs(
@@ -1126,7 +1315,27 @@ pub(crate) fn pubsub_tree_change(
     }
 
     // recompute routes
-    compute_data_routes_from(tables, &mut tables.root_res.clone());
+    if tables.recompute_all_routes_on_tree_change() {
+        Resource::get_resources(&tables.root_res)
+    } else {
+        // Only the routes to the subscribers of the network depend on its trees
+        let subs_res = match net_type {
+            WhatAmI::Router => &tables.router_subs,
+            _ => &tables.peer_subs,
+        };
+        let mut affected = HashSet::new();
+        for res in subs_res {
+            for match_ in &res.context().matches {
+                affected.insert(match_.upgrade().unwrap());
+            }
+        }
+        // The routes from the new nodes are extended right away, the affected ones being
+        // recomputed afterwards
+        if let Some(len) = tables.get_net(net_type).map(Network::routes_len) {
+            extend_data_routes_from(&mut tables.root_res.clone(), net_type, len);
+        }
+        affected.into_iter().collect()
+    }
 }
 
 pub(crate) fn pubsub_linkstate_change(tables: &mut Tables, zid: &ZenohId, links: &[ZenohId]) {
@@ -1179,13 +1388,14 @@ pub(crate) fn pubsub_linkstate_change(tables: &mut Tables, zid: &ZenohId, links:
                                     });
 
                                     get_mut_unchecked(dst_face).local_subs.remove(res);
+                                    get_mut_unchecked(dst_face).local_sub_filters.remove(res);
                                 }
                             } else if Tables::failover_brokering_to(links, ctx.face.zid) {
                                 let dst_face = &mut get_mut_unchecked(ctx).face;
                                 get_mut_unchecked(dst_face).local_subs.insert(res.clone());
                                 let key_expr = Resource::decl_key(res, dst_face);
                                 let sub_info = SubscriberInfo {
-                                    reliability: Reliability::Reliable, // TODO
+                                    reliability: subs_reliability(res),
                                     mode: Mode::Push,
                                 };
                                 dst_face.primitives.send_declare(Declare {
@@ -1196,6 +1406,7 @@ pub(crate) fn pubsub_linkstate_change(tables: &mut Tables, zid: &ZenohId, links:
                                         id: 0, // TODO
                                         wire_expr: key_expr,
                                         ext_info: sub_info,
+                                        ext_filter: None,
                                     }),
                                 });
                             }
@@ -1215,6 +1426,7 @@ fn insert_faces_for_subs(
     net: &Network,
     source: usize,
     subs: &HashSet<ZenohId>,
+    reliable_subs: &HashSet<ZenohId>,
 ) {
     if net.trees.len() > source {
         for sub in subs {
@@ -1223,19 +1435,37 @@ fn insert_faces_for_subs(
                     if let Some(direction) = net.trees[source].directions[sub_idx.index()] {
                         if net.graph.contains_node(direction) {
                             if let Some(face) = tables.get_face(&net.graph[direction].zid) {
-                                route.entry(face.id).or_insert_with(|| {
-                                    let key_expr =
-                                        Resource::get_best_key(expr.prefix, expr.suffix, face.id);
-                                    (
-                                        face.clone(),
-                                        key_expr.to_owned(),
-                                        if source != 0 {
-                                            Some(source as u16)
-                                        } else {
-                                            None
-                                        },
-                                    )
-                                });
+                                let reliability = if reliable_subs.contains(sub) {
+                                    Reliability::Reliable
+                                } else {
+                                    Reliability::BestEffort
+                                };
+                                route
+                                    .entry(face.id)
+                                    .and_modify(|(_, r, filter)| {
+                                        *r = strictest(*r, reliability);
+                                        *filter = None;
+                                    })
+                                    .or_insert_with(|| {
+                                        let key_expr = Resource::get_best_key(
+                                            expr.prefix,
+                                            expr.suffix,
+                                            face.id,
+                                        );
+                                        (
+                                            (
+                                                face.clone(),
+                                                key_expr.to_owned(),
+                                                if source != 0 {
+                                                    Some(source as u16)
+                                                } else {
+                                                    None
+                                                },
+                                            ),
+                                            reliability,
+                                            None,
+                                        )
+                                    });
                             }
                         }
                     }
@@ -1254,6 +1484,7 @@ fn compute_data_route(
     source_type: WhatAmI,
 ) -> Arc<Route> {
     let mut route = HashMap::new();
+    let res = Resource::get_resource(expr.prefix, expr.suffix);
     let key_expr = expr.full_expr();
     if key_expr.ends_with('/') {
         return Arc::new(route);
@@ -1264,23 +1495,24 @@ fn compute_data_route(
         source,
         source_type
     );
-    let key_expr = match OwnedKeyExpr::try_from(key_expr) {
+    // Borrowed from the routing expression: the routes of the resources without a cached
+    // route are computed for each message
+    let key_expr = match keyexpr::new(key_expr) {
         Ok(ke) => ke,
         Err(e) => {
             log::warn!("Invalid KE reached the system: {}", e);
             return Arc::new(route);
         }
     };
-    let res = Resource::get_resource(expr.prefix, expr.suffix);
     let matches = res
         .as_ref()
         .and_then(|res| res.context.as_ref())
         .map(|ctx| Cow::from(&ctx.matches))
-        .unwrap_or_else(|| Cow::from(Resource::get_matches(tables, &key_expr)));
+        .unwrap_or_else(|| Cow::from(Resource::get_matches(tables, key_expr)));
 
     let master = tables.whatami != WhatAmI::Router
         || !tables.full_net(WhatAmI::Peer)
-        || *tables.elect_router(&key_expr, tables.shared_nodes.iter()) == tables.zid;
+        || *tables.elect_router(key_expr, tables.shared_nodes.iter()) == tables.zid;
 
     for mres in matches.iter() {
         let mres = mres.upgrade().unwrap();
@@ -1298,6 +1530,7 @@ fn compute_data_route(
                     net,
                     router_source,
                     &mres.context().router_subs,
+                    &mres.context().reliable_router_subs,
                 );
             }
 
@@ -1314,6 +1547,7 @@ fn compute_data_route(
                     net,
                     peer_source,
                     &mres.context().peer_subs,
+                    &mres.context().reliable_peer_subs,
                 );
             }
         }
@@ -1331,6 +1565,7 @@ fn compute_data_route(
                 net,
                 peer_source,
                 &mres.context().peer_subs,
+                &mres.context().reliable_peer_subs,
             );
         }
 
@@ -1345,10 +1580,21 @@ fn compute_data_route(
                         }
                     } && subinfo.mode == Mode::Push
                     {
-                        route.entry(*sid).or_insert_with(|| {
-                            let key_expr = Resource::get_best_key(expr.prefix, expr.suffix, *sid);
-                            (context.face.clone(), key_expr.to_owned(), None)
-                        });
+                        route
+                            .entry(*sid)
+                            .and_modify(|(_, r, filter)| {
+                                *r = strictest(*r, subinfo.reliability);
+                                *filter = merge_filters(filter, &context.sub_filter);
+                            })
+                            .or_insert_with(|| {
+                                let key_expr =
+                                    Resource::get_best_key(expr.prefix, expr.suffix, *sid);
+                                (
+                                    (context.face.clone(), key_expr.to_owned(), None),
+                                    subinfo.reliability,
+                                    context.sub_filter.clone(),
+                                )
+                            });
                     }
                 }
             }
@@ -1358,8 +1604,13 @@ fn compute_data_route(
         route.insert(
             mcast_group.id,
             (
-                mcast_group.clone(),
-                expr.full_expr().to_string().into(),
+                (
+                    mcast_group.clone(),
+                    expr.full_expr().to_string().into(),
+                    None,
+                ),
+                // Multicast transports can't provide reliability
+                Reliability::BestEffort,
                 None,
             ),
         );
@@ -1369,17 +1620,17 @@ fn compute_data_route(
 
 fn compute_matching_pulls(tables: &Tables, expr: &mut RoutingExpr) -> Arc<PullCaches> {
     let mut pull_caches = vec![];
-    let ke = if let Ok(ke) = OwnedKeyExpr::try_from(expr.full_expr()) {
+    let res = Resource::get_resource(expr.prefix, expr.suffix);
+    let ke = if let Ok(ke) = keyexpr::new(expr.full_expr()) {
         ke
     } else {
         return Arc::new(pull_caches);
     };
-    let res = Resource::get_resource(expr.prefix, expr.suffix);
     let matches = res
         .as_ref()
         .and_then(|res| res.context.as_ref())
         .map(|ctx| Cow::from(&ctx.matches))
-        .unwrap_or_else(|| Cow::from(Resource::get_matches(tables, &ke)));
+        .unwrap_or_else(|| Cow::from(Resource::get_matches(tables, ke)));
 
     for mres in matches.iter() {
         let mres = mres.upgrade().unwrap();
@@ -1513,6 +1764,7 @@ pub(crate) fn compute_data_routes(tables: &mut Tables, res: &mut Arc<Resource>)
                 Some(compute_data_route(tables, &mut expr, None, WhatAmI::Client));
         }
         res_mut.context_mut().matching_pulls = compute_matching_pulls(tables, &mut expr);
+        Resource::index_routes(res);
     }
 }
 
@@ -1524,6 +1776,88 @@ pub(super) fn compute_data_routes_from(tables: &mut Tables, res: &mut Arc<Resour
     }
 }
 
+// The routes of a resource without subscribers in the network are the same whatever the source
+// node: the routes from the new nodes of the network are copies of the route from this node.
+fn extend_data_routes_from(res: &mut Arc<Resource>, net_type: WhatAmI, len: usize) {
+    let res = get_mut_unchecked(res);
+    if let Some(ctx) = res.context.as_mut() {
+        let routes = match net_type {
+            WhatAmI::Router => &mut ctx.routers_data_routes,
+            _ => &mut ctx.peers_data_routes,
+        };
+        if let Some(route) = routes.first().cloned() {
+            if routes.len() < len {
+                routes.resize(len, route);
+            }
+        }
+    }
+    for child in res.childs.values_mut() {
+        extend_data_routes_from(child, net_type, len);
+    }
+}
+
+/// Asserts that the cached data routes of `res` are the ones a full recomputation gives.
+#[cfg(debug_assertions)]
+pub(super) fn check_data_routes(tables: &Tables, res: &Arc<Resource>) {
+    let summary = |route: Option<&Arc<Route>>| {
+        route
+            .map(|route| {
+                route
+                    .iter()
+                    .map(|(id, ((face, key_expr, context), reliability, filter))| {
+                        let entry = (face.id, key_expr.clone(), *context, *reliability);
+                        (*id, (entry, filter.clone()))
+                    })
+                    .collect::<HashMap<_, _>>()
+            })
+            .unwrap_or_default()
+    };
+    let ctx = res.context();
+    if !ctx.valid_data_routes {
+        return;
+    }
+    let expected = compute_data_routes_(tables, res);
+    for (net_type, cached_routes, expected_routes) in [
+        (
+            WhatAmI::Router,
+            &ctx.routers_data_routes,
+            &expected.routers_data_routes,
+        ),
+        (
+            WhatAmI::Peer,
+            &ctx.peers_data_routes,
+            &expected.peers_data_routes,
+        ),
+    ] {
+        if let Some(net) = tables.get_net(net_type) {
+            for idx in net.graph.node_indices().map(|idx| idx.index()) {
+                if idx < cached_routes.len() {
+                    assert_eq!(
+                        summary(cached_routes.get(idx)),
+                        summary(expected_routes.get(idx)),
+                        "Inconsistent {} data route {} of {}",
+                        net_type,
+                        idx,
+                        res.expr()
+                    );
+                }
+            }
+        }
+    }
+    assert_eq!(
+        summary(ctx.peer_data_route.as_ref()),
+        summary(expected.peer_data_route.as_ref()),
+        "Inconsistent peer data route of {}",
+        res.expr()
+    );
+    assert_eq!(
+        summary(ctx.client_data_route.as_ref()),
+        summary(expected.client_data_route.as_ref()),
+        "Inconsistent client data route of {}",
+        res.expr()
+    );
+}
+
 pub(super) fn compute_matches_data_routes_<'a>(
     tables: &'a Tables,
     res: &'a Arc<Resource>,
@@ -1556,8 +1890,9 @@ pub(super) fn disable_matches_data_routes(_tables: &mut Tables, res: &mut Arc<Re
     }
 }
 
+// Returns `$ret` from the caller if the data is dropped because of its timestamp
 macro_rules! treat_timestamp {
-    ($hlc:expr, $payload:expr, $drop:expr) => {
+    ($hlc:expr, $payload:expr, $drop:expr, $ret:expr) => {
         // if an HLC was configured (via Config.add_timestamp),
         // check DataInfo and add a timestamp if there isn't
         if let Some(hlc) = $hlc {
@@ -1572,7 +1907,7 @@ macro_rules! treat_timestamp {
                                     "Error treating timestamp for received Data ({}). Drop it!",
                                     e
                                 );
-                                return;
+                                return $ret;
                             } else {
                                 data.timestamp = Some(hlc.new_timestamp());
                                 log::error!(
@@ -1719,6 +2054,29 @@ fn should_route(
     false
 }
 
+/// Returns `true` if the data matches the filter of the route to `outface`, counting the data
+/// whose payload the filter can't read.
+#[inline]
+fn matches_filter(outface: &FaceState, filter: &Option<Arc<Filter>>, payload: &PushBody) -> bool {
+    match (filter, payload) {
+        (Some(filter), PushBody::Put(put)) => {
+            // The payload of a shared memory sample only references its value
+            #[cfg(feature = "shared-memory")]
+            if put.ext_shm.is_some() {
+                return true;
+            }
+            match filter.evaluate(&put.encoding, &put.payload) {
+                Some(matches) => matches,
+                None => {
+                    outface.count_malformed_payload();
+                    false
+                }
+            }
+        }
+        _ => true,
+    }
+}
+
 #[cfg(feature = "stats")]
 macro_rules! inc_stats {
     (
@@ -1750,9 +2108,118 @@ pub fn full_reentrant_route_data(
     face: &FaceState,
     expr: &WireExpr,
     ext_qos: ext::QoSType,
-    mut payload: PushBody,
+    payload: PushBody,
+    routing_context: u64,
+) {
+    full_reentrant_route_data_reported(
+        tables_ref,
+        face,
+        expr,
+        ext_qos,
+        payload,
+        routing_context,
+        false,
+    );
+}
+
+/// Routes the data like [`full_reentrant_route_data`], reporting the outcome of its scheduling on
+/// the outgoing faces. If `ack` is set, the outgoing faces are asked for an acknowledgement.
+#[allow(clippy::too_many_arguments)]
+pub fn full_reentrant_route_data_reported(
+    tables_ref: &RwLock<Tables>,
+    face: &FaceState,
+    expr: &WireExpr,
+    ext_qos: ext::QoSType,
+    payload: PushBody,
     routing_context: u64,
+    ack: bool,
+) -> PushReport {
+    route_data(
+        tables_ref,
+        face,
+        expr,
+        ext_qos,
+        payload,
+        routing_context,
+        |outface, push, reliability| {
+            outface
+                .primitives
+                .send_push_reported(push, reliability, ack)
+        },
+    )
+}
+
+/// Routes the messages of the batch `id` received from `face`, given in publication order.
+///
+/// Each message is routed on its own, the messages routed to a same face being sent to it in a
+/// single message, whose batch extension carries the messages after the first one.
+pub fn full_reentrant_route_data_batch(
+    tables_ref: &RwLock<Tables>,
+    face: &FaceState,
+    ext_qos: ext::QoSType,
+    routing_context: u64,
+    id: u64,
+    entries: Vec<(WireExpr<'static>, PushBody)>,
 ) {
+    let mut batches: Vec<(Arc<FaceState>, Reliability, Vec<Push>)> = vec![];
+    for (expr, payload) in entries {
+        route_data(
+            tables_ref,
+            face,
+            &expr,
+            ext_qos,
+            payload,
+            routing_context,
+            |outface, push, reliability| {
+                match batches.iter_mut().find(|(f, _, _)| f.id == outface.id) {
+                    Some((_, batch_reliability, pushes)) => {
+                        // The batch is reliable as soon as one of its messages is
+                        if reliability == Reliability::Reliable {
+                            *batch_reliability = Reliability::Reliable;
+                        }
+                        pushes.push(push);
+                    }
+                    None => batches.push((outface.clone(), reliability, vec![push])),
+                }
+                PushReport {
+                    scheduled: 1,
+                    ..Default::default()
+                }
+            },
+        );
+    }
+    for (outface, reliability, pushes) in batches {
+        let mut pushes = pushes.into_iter();
+        let mut push = match pushes.next() {
+            Some(push) => push,
+            None => continue,
+        };
+        let entries = pushes
+            .map(|p| BatchEntry {
+                wire_expr: p.wire_expr,
+                payload: p.payload,
+            })
+            .collect();
+        *push.payload.ext_batch_mut() = Some(BatchType { id, entries });
+        outface.primitives.send_push(push, reliability);
+    }
+}
+
+// Routes the data, handing each message to an outgoing face over to `egress`.
+#[allow(clippy::too_many_arguments)]
+fn route_data<E>(
+    tables_ref: &RwLock<Tables>,
+    face: &FaceState,
+    expr: &WireExpr,
+    ext_qos: ext::QoSType,
+    mut payload: PushBody,
+    routing_context: u64,
+    mut egress: E,
+) -> PushReport
+where
+    E: FnMut(&Arc<FaceState>, Push, Reliability) -> PushReport,
+{
+    let mut report = PushReport::default();
     let tables = zread!(tables_ref);
     match tables.get_mapping(face, &expr.scope, expr.mapping).cloned() {
         Some(prefix) => {
@@ -1780,15 +2247,40 @@ pub fn full_reentrant_route_data(
             {
                 let res = Resource::get_resource(&prefix, expr.suffix);
                 let route = get_data_route(&tables, face, &res, &mut expr, routing_context);
+                for ((outface, key_expr, _context), _reliability, _filter) in route.values() {
+                    outface.touch_mapping(key_expr);
+                }
                 let matching_pulls = get_matching_pulls(&tables, &res, &mut expr);
+                let dead_letters = tables
+                    .dead_letters
+                    .clone()
+                    .map(|dead_letters| (dead_letters, deadletter::payload_size(&payload)));
 
                 if !(route.is_empty() && matching_pulls.is_empty()) {
-                    treat_timestamp!(&tables.hlc, payload, tables.drop_future_timestamp);
+                    if let Some(deduplication) = tables.deduplication.as_ref() {
+                        if deduplication.is_duplicate(expr.full_expr(), &payload) {
+                            log::trace!("Drop duplicate data for res {}", expr.full_expr());
+                            return report;
+                        }
+                    }
+                    treat_timestamp!(&tables.hlc, payload, tables.drop_future_timestamp, report);
+                    let mutations = tables.mutations.clone();
 
                     if route.len() == 1 && matching_pulls.len() == 0 {
-                        let (outface, key_expr, context) = route.values().next().unwrap();
-                        if should_route(&tables, face, outface, &mut expr) {
+                        let ((outface, key_expr, context), reliability, filter) =
+                            route.values().next().unwrap();
+                        if should_route(&tables, face, outface, &mut expr)
+                            && matches_filter(outface, filter, &payload)
+                        {
                             drop(tables);
+                            let payload = match mutations
+                                .as_ref()
+                                .map(|m| m.mutate(expr.full_expr(), outface, &payload))
+                            {
+                                Some(Egress::Mutated(mutated)) => mutated,
+                                Some(Egress::Dropped) => return report,
+                                _ => payload,
+                            };
                             #[cfg(feature = "stats")]
                             if !admin {
                                 inc_stats!(face, tx, user, payload)
@@ -1796,15 +2288,20 @@ pub fn full_reentrant_route_data(
                                 inc_stats!(face, tx, admin, payload)
                             }
 
-                            outface.primitives.send_push(Push {
-                                wire_expr: key_expr.into(),
-                                ext_qos,
-                                ext_tstamp: None,
-                                ext_nodeid: ext::NodeIdType {
-                                    node_id: context.unwrap_or(0),
+                            outface.count_priority_downgrade(ext_qos.get_priority());
+                            report.merge(egress(
+                                outface,
+                                Push {
+                                    wire_expr: key_expr.into(),
+                                    ext_qos,
+                                    ext_tstamp: None,
+                                    ext_nodeid: ext::NodeIdType {
+                                        node_id: context.unwrap_or(0),
+                                    },
+                                    payload,
                                 },
-                                payload,
-                            })
+                                *reliability,
+                            ))
                         }
                     } else {
                         if !matching_pulls.is_empty() {
@@ -1816,14 +2313,25 @@ pub fn full_reentrant_route_data(
                         if tables.whatami == WhatAmI::Router {
                             let route = route
                                 .values()
-                                .filter(|(outface, _key_expr, _context)| {
+                                .filter(|((outface, _key_expr, _context), _reliability, filter)| {
                                     should_route(&tables, face, outface, &mut expr)
+                                        && matches_filter(outface, filter, &payload)
+                                })
+                                .map(|(direction, reliability, _filter)| {
+                                    (direction.clone(), *reliability)
                                 })
-                                .cloned()
-                                .collect::<Vec<Direction>>();
+                                .collect::<Vec<(Direction, Reliability)>>();
 
                             drop(tables);
-                            for (outface, key_expr, context) in route {
+                            for ((outface, key_expr, context), reliability) in route {
+                                let payload = match mutations
+                                    .as_ref()
+                                    .map(|m| m.mutate(expr.full_expr(), &outface, &payload))
+                                {
+                                    Some(Egress::Mutated(mutated)) => mutated,
+                                    Some(Egress::Dropped) => continue,
+                                    _ => payload.clone(),
+                                };
                                 #[cfg(feature = "stats")]
                                 if !admin {
                                     inc_stats!(face, tx, user, payload)
@@ -1831,19 +2339,26 @@ pub fn full_reentrant_route_data(
                                     inc_stats!(face, tx, admin, payload)
                                 }
 
-                                outface.primitives.send_push(Push {
-                                    wire_expr: key_expr,
-                                    ext_qos,
-                                    ext_tstamp: None,
-                                    ext_nodeid: ext::NodeIdType {
-                                        node_id: context.unwrap_or(0),
+                                outface.count_priority_downgrade(ext_qos.get_priority());
+                                report.merge(egress(
+                                    &outface,
+                                    Push {
+                                        wire_expr: key_expr,
+                                        ext_qos,
+                                        ext_tstamp: None,
+                                        ext_nodeid: ext::NodeIdType {
+                                            node_id: context.unwrap_or(0),
+                                        },
+                                        payload,
                                     },
-                                    payload: payload.clone(),
-                                })
+                                    reliability,
+                                ))
                             }
                         } else {
                             drop(tables);
-                            for (outface, key_expr, context) in route.values() {
+                            for ((outface, key_expr, context), reliability, filter) in
+                                route.values()
+                            {
                                 if face.id != outface.id
                                     && match (
                                         face.mcast_group.as_ref(),
@@ -1852,7 +2367,16 @@ pub fn full_reentrant_route_data(
                                         (Some(l), Some(r)) => l != r,
                                         _ => true,
                                     }
+                                    && matches_filter(outface, filter, &payload)
                                 {
+                                    let payload = match mutations
+                                        .as_ref()
+                                        .map(|m| m.mutate(expr.full_expr(), outface, &payload))
+                                    {
+                                        Some(Egress::Mutated(mutated)) => mutated,
+                                        Some(Egress::Dropped) => continue,
+                                        _ => payload.clone(),
+                                    };
                                     #[cfg(feature = "stats")]
                                     if !admin {
                                         inc_stats!(face, tx, user, payload)
@@ -1860,19 +2384,41 @@ pub fn full_reentrant_route_data(
                                         inc_stats!(face, tx, admin, payload)
                                     }
 
-                                    outface.primitives.send_push(Push {
-                                        wire_expr: key_expr.into(),
-                                        ext_qos,
-                                        ext_tstamp: None,
-                                        ext_nodeid: ext::NodeIdType {
-                                            node_id: context.unwrap_or(0),
+                                    outface.count_priority_downgrade(ext_qos.get_priority());
+                                    report.merge(egress(
+                                        outface,
+                                        Push {
+                                            wire_expr: key_expr.into(),
+                                            ext_qos,
+                                            ext_tstamp: None,
+                                            ext_nodeid: ext::NodeIdType {
+                                                node_id: context.unwrap_or(0),
+                                            },
+                                            payload,
                                         },
-                                        payload: payload.clone(),
-                                    })
+                                        *reliability,
+                                    ))
                                 }
                             }
                         }
                     }
+                    if report.scheduled == 0 && report.dropped > 0 {
+                        if let Some((dead_letters, payload_size)) = dead_letters {
+                            dead_letters.report(
+                                expr.full_expr(),
+                                payload_size,
+                                DeadLetterReason::Congestion,
+                            );
+                        }
+                    }
+                } else if let Some((dead_letters, payload_size)) = dead_letters {
+                    if !deadletter::subscribed_by(face, expr.full_expr()) {
+                        dead_letters.report(
+                            expr.full_expr(),
+                            payload_size,
+                            DeadLetterReason::NoRoute,
+                        );
+                    }
                 }
             }
         }
@@ -1880,6 +2426,7 @@ pub fn full_reentrant_route_data(
             log::error!("Route data with unknown scope {}!", expr.scope);
         }
     }
+    report
 }
 
 pub fn pull_data(tables_ref: &RwLock<Tables>, face: &Arc<FaceState>, expr: WireExpr) {
@@ -1890,30 +2437,42 @@ pub fn pull_data(tables_ref: &RwLock<Tables>, face: &Arc<FaceState>, expr: WireE
                 let res = get_mut_unchecked(&mut res);
                 match res.session_ctxs.get_mut(&face.id) {
                     Some(ctx) => match &ctx.subs {
-                        Some(_subinfo) => {
-                            // let reliability = subinfo.reliability;
+                        Some(subinfo) => {
+                            let reliability = subinfo.reliability;
                             let lock = zlock!(tables.pull_caches_lock);
                             let route = get_mut_unchecked(ctx)
                                 .last_values
                                 .drain()
-                                .map(|(name, sample)| {
-                                    (
+                                .filter_map(|(name, sample)| {
+                                    let sample = match tables
+                                        .mutations
+                                        .as_ref()
+                                        .map(|m| m.mutate(&name, face, &sample))
+                                    {
+                                        Some(Egress::Mutated(mutated)) => mutated,
+                                        Some(Egress::Dropped) => return None,
+                                        _ => sample,
+                                    };
+                                    Some((
                                         Resource::get_best_key(&tables.root_res, &name, face.id)
                                             .to_owned(),
                                         sample,
-                                    )
+                                    ))
                                 })
                                 .collect::<Vec<(WireExpr, PushBody)>>();
                             drop(lock);
                             drop(tables);
                             for (key_expr, payload) in route {
-                                face.primitives.send_push(Push {
-                                    wire_expr: key_expr,
-                                    ext_qos: ext::QoSType::push_default(),
-                                    ext_tstamp: None,
-                                    ext_nodeid: ext::NodeIdType::default(),
-                                    payload,
-                                });
+                                face.primitives.send_push(
+                                    Push {
+                                        wire_expr: key_expr,
+                                        ext_qos: ext::QoSType::push_default(),
+                                        ext_tstamp: None,
+                                        ext_nodeid: ext::NodeIdType::default(),
+                                        payload,
+                                    },
+                                    reliability,
+                                );
                             }
                         }
                         None => {
@@ -13,16 +13,19 @@
 //
 use super::face::FaceState;
 use super::network::Network;
+use super::querylimit::{QueryLimit, QueryLimitPolicy, QueryPermit};
+use super::querythrottle::Throttled;
 use super::resource::{
     QueryRoute, QueryRoutes, QueryTargetQabl, QueryTargetQablSet, Resource, RoutingContext,
     SessionContext,
 };
 use super::router::{RoutingExpr, Tables, TablesLock};
+use super::trace::{QueryTrace, QueryTracer};
 use async_trait::async_trait;
 use ordered_float::OrderedFloat;
 use petgraph::graph::NodeIndex;
 use std::borrow::Cow;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::convert::TryFrom;
 use std::sync::{Arc, RwLockReadGuard, Weak};
 use zenoh_buffers::ZBuf;
@@ -32,17 +35,20 @@ use zenoh_protocol::{
             include::{Includer, DEFAULT_INCLUDER},
             keyexpr, OwnedKeyExpr,
         },
-        Encoding, WhatAmI, WireExpr, ZenohId,
+        Encoding, EntityId, WhatAmI, WireExpr, ZenohId,
     },
     network::{
         declare::{
             common::ext::WireExprType, ext, queryable::ext::QueryableInfo, Declare, DeclareBody,
             DeclareQueryable, UndeclareQueryable,
         },
-        request::{ext::TargetType, Request, RequestId},
+        request::{
+            ext::{CorrelationType, RepliersType, TargetType},
+            Request, RequestId,
+        },
         response::{self, ext::ResponderIdType, Response, ResponseFinal},
     },
-    zenoh::{reply::ext::ConsolidationType, Reply, RequestBody, ResponseBody},
+    zenoh::{err, reply::ext::ConsolidationType, Reply, RequestBody, ResponseBody},
 };
 use zenoh_sync::get_mut_unchecked;
 use zenoh_util::Timed;
@@ -50,6 +56,22 @@ use zenoh_util::Timed;
 pub(crate) struct Query {
     src_face: Arc<FaceState>,
     src_qid: RequestId,
+    correlation: Option<CorrelationType>,
+    // The tracer of the query and the sequence number of its trace, if traced
+    trace: Option<(Arc<QueryTracer>, u64)>,
+    // The admission of the query by the query limits, if any
+    permit: Option<QueryPermit>,
+}
+
+impl Query {
+    fn response_correlation(&self) -> Option<response::ext::CorrelationType> {
+        self.correlation
+            .as_ref()
+            .map(|c| response::ext::CorrelationType {
+                zid: c.zid,
+                eid: c.eid,
+            })
+    }
 }
 
 #[cfg(feature = "complete_n")]
@@ -285,12 +307,16 @@ fn propagate_simple_queryable(
                 .local_qabls
                 .insert(res.clone(), info);
             let key_expr = Resource::decl_key(res, &mut dst_face);
+            let id = src_face
+                .as_ref()
+                .map(|src_face| res.propagated_qabl_id(tables, src_face))
+                .unwrap_or(0);
             dst_face.primitives.send_declare(Declare {
                 ext_qos: ext::QoSType::declare_default(),
                 ext_tstamp: None,
                 ext_nodeid: ext::NodeIdType::default(),
                 body: DeclareBody::DeclareQueryable(DeclareQueryable {
-                    id: 0, // TODO
+                    id,
                     wire_expr: key_expr,
                     ext_info: info,
                 }),
@@ -427,9 +453,7 @@ pub fn declare_router_queryable(
 
             let wtables = zwrite!(tables.tables);
             for (mut res, query_routes) in matches_query_routes {
-                get_mut_unchecked(&mut res)
-                    .context_mut()
-                    .update_query_routes(query_routes);
+                Resource::update_query_routes(&mut res, query_routes);
             }
             drop(wtables);
         }
@@ -523,9 +547,7 @@ pub fn declare_peer_queryable(
 
             let wtables = zwrite!(tables.tables);
             for (mut res, query_routes) in matches_query_routes {
-                get_mut_unchecked(&mut res)
-                    .context_mut()
-                    .update_query_routes(query_routes);
+                Resource::update_query_routes(&mut res, query_routes);
             }
             drop(wtables);
         }
@@ -538,22 +560,29 @@ fn register_client_queryable(
     face: &mut Arc<FaceState>,
     res: &mut Arc<Resource>,
     qabl_info: &QueryableInfo,
+    id: EntityId,
 ) {
     // Register queryable
     {
         let res = get_mut_unchecked(res);
         log::debug!("Register queryable {} (face: {})", res.expr(), face,);
-        get_mut_unchecked(res.session_ctxs.entry(face.id).or_insert_with(|| {
+        let ctx = get_mut_unchecked(res.session_ctxs.entry(face.id).or_insert_with(|| {
             Arc::new(SessionContext {
                 face: face.clone(),
                 local_expr_id: None,
                 remote_expr_id: None,
                 subs: None,
+                sub_id: None,
+                sub_filter: None,
                 qabl: None,
+                qabl_id: None,
                 last_values: HashMap::new(),
             })
-        }))
-        .qabl = Some(*qabl_info);
+        }));
+        ctx.qabl = Some(*qabl_info);
+        if id != 0 {
+            ctx.qabl_id = Some(id);
+        }
     }
     get_mut_unchecked(face).remote_qabls.insert(res.clone());
 }
@@ -564,6 +593,7 @@ pub fn declare_client_queryable(
     face: &mut Arc<FaceState>,
     expr: &WireExpr,
     qabl_info: &QueryableInfo,
+    id: EntityId,
 ) {
     match rtables
         .get_mapping(face, &expr.scope, expr.mapping)
@@ -592,7 +622,7 @@ pub fn declare_client_queryable(
                     (res, wtables)
                 };
 
-            register_client_queryable(&mut wtables, face, &mut res, qabl_info);
+            register_client_queryable(&mut wtables, face, &mut res, qabl_info, id);
 
             match wtables.whatami {
                 WhatAmI::Router => {
@@ -634,9 +664,7 @@ pub fn declare_client_queryable(
 
             let wtables = zwrite!(tables.tables);
             for (mut res, query_routes) in matches_query_routes {
-                get_mut_unchecked(&mut res)
-                    .context_mut()
-                    .update_query_routes(query_routes);
+                Resource::update_query_routes(&mut res, query_routes);
             }
             drop(wtables);
         }
@@ -865,9 +893,7 @@ pub fn forget_router_queryable(
 
                 let wtables = zwrite!(tables.tables);
                 for (mut res, query_routes) in matches_query_routes {
-                    get_mut_unchecked(&mut res)
-                        .context_mut()
-                        .update_query_routes(query_routes);
+                    Resource::update_query_routes(&mut res, query_routes);
                 }
                 Resource::clean(&mut res);
                 drop(wtables);
@@ -936,9 +962,7 @@ pub fn forget_peer_queryable(
 
                 let wtables = zwrite!(tables.tables);
                 for (mut res, query_routes) in matches_query_routes {
-                    get_mut_unchecked(&mut res)
-                        .context_mut()
-                        .update_query_routes(query_routes);
+                    Resource::update_query_routes(&mut res, query_routes);
                 }
                 Resource::clean(&mut res);
                 drop(wtables);
@@ -957,6 +981,7 @@ pub(crate) fn undeclare_client_queryable(
     log::debug!("Unregister client queryable {} for {}", res.expr(), face);
     if let Some(ctx) = get_mut_unchecked(res).session_ctxs.get_mut(&face.id) {
         get_mut_unchecked(ctx).qabl = None;
+        get_mut_unchecked(ctx).qabl_id = None;
         if ctx.qabl.is_none() {
             get_mut_unchecked(face).remote_qabls.remove(res);
         }
@@ -1039,9 +1064,7 @@ pub fn forget_client_queryable(
 
                 let wtables = zwrite!(tables.tables);
                 for (mut res, query_routes) in matches_query_routes {
-                    get_mut_unchecked(&mut res)
-                        .context_mut()
-                        .update_query_routes(query_routes);
+                    Resource::update_query_routes(&mut res, query_routes);
                 }
                 Resource::clean(&mut res);
                 drop(wtables);
@@ -1052,6 +1075,34 @@ pub fn forget_client_queryable(
     }
 }
 
+/// Re-declares to `face` the queryables whose key expression used the mapping of `prefix`,
+/// after `face` rejected it and it got re-mapped.
+pub(crate) fn queries_remap_face(face: &mut Arc<FaceState>, prefix: &Arc<Resource>) {
+    let qabls = face
+        .local_qabls
+        .iter()
+        .filter(|(qabl, _)| {
+            Resource::nonwild_prefix(qabl)
+                .0
+                .map_or(false, |p| Arc::ptr_eq(&p, prefix))
+        })
+        .map(|(qabl, info)| (qabl.clone(), *info))
+        .collect::<Vec<(Arc<Resource>, QueryableInfo)>>();
+    for (qabl, info) in qabls {
+        let key_expr = Resource::decl_key(&qabl, face);
+        face.primitives.send_declare(Declare {
+            ext_qos: ext::QoSType::declare_default(),
+            ext_tstamp: None,
+            ext_nodeid: ext::NodeIdType::default(),
+            body: DeclareBody::DeclareQueryable(DeclareQueryable {
+                id: 0, // TODO
+                wire_expr: key_expr,
+                ext_info: info,
+            }),
+        });
+    }
+}
+
 pub(crate) fn queries_new_face(tables: &mut Tables, face: &mut Arc<FaceState>) {
     match tables.whatami {
         WhatAmI::Router => {
@@ -1172,9 +1223,7 @@ pub(crate) fn queries_remove_node(tables: &mut Tables, node: &ZenohId, net_type:
 
                 let matches_query_routes = compute_matches_query_routes_(tables, &res);
                 for (mut res, query_routes) in matches_query_routes {
-                    get_mut_unchecked(&mut res)
-                        .context_mut()
-                        .update_query_routes(query_routes);
+                    Resource::update_query_routes(&mut res, query_routes);
                 }
                 Resource::clean(&mut res);
             }
@@ -1204,9 +1253,7 @@ pub(crate) fn queries_remove_node(tables: &mut Tables, node: &ZenohId, net_type:
 
                 let matches_query_routes = compute_matches_query_routes_(tables, &res);
                 for (mut res, query_routes) in matches_query_routes {
-                    get_mut_unchecked(&mut res)
-                        .context_mut()
-                        .update_query_routes(query_routes);
+                    Resource::update_query_routes(&mut res, query_routes);
                 }
                 Resource::clean(&mut res)
             }
@@ -1290,11 +1337,13 @@ pub(crate) fn queries_linkstate_change(tables: &mut Tables, zid: &ZenohId, links
     }
 }
 
+/// Propagates the queryables to the new childs of the trees, returning the resources whose query
+/// routes are to be recomputed.
 pub(crate) fn queries_tree_change(
     tables: &mut Tables,
     new_childs: &[Vec<NodeIndex>],
     net_type: WhatAmI,
-) {
+) -> Vec<Arc<Resource>> {
     // propagate qabls to new childs
     for (tree_sid, tree_childs) in new_childs.iter().enumerate() {
         if !tree_childs.is_empty() {
@@ -1330,7 +1379,27 @@ pub(crate) fn queries_tree_change(
     }
 
     // recompute routes
-    compute_query_routes_from(tables, &mut tables.root_res.clone());
+    if tables.recompute_all_routes_on_tree_change() {
+        Resource::get_resources(&tables.root_res)
+    } else {
+        // Only the routes to the queryables of the network depend on its trees
+        let qabls_res = match net_type {
+            WhatAmI::Router => &tables.router_qabls,
+            _ => &tables.peer_qabls,
+        };
+        let mut affected = HashSet::new();
+        for res in qabls_res {
+            for match_ in &res.context().matches {
+                affected.insert(match_.upgrade().unwrap());
+            }
+        }
+        // The routes from the new nodes are extended right away, the affected ones being
+        // recomputed afterwards
+        if let Some(len) = tables.get_net(net_type).map(Network::routes_len) {
+            extend_query_routes_from(&mut tables.root_res.clone(), net_type, len);
+        }
+        affected.into_iter().collect()
+    }
 }
 
 #[inline]
@@ -1637,10 +1706,11 @@ pub(crate) fn compute_query_routes(tables: &mut Tables, res: &mut Arc<Resource>)
                 WhatAmI::Client,
             ));
         }
+        Resource::index_routes(res);
     }
 }
 
-fn compute_query_routes_from(tables: &mut Tables, res: &mut Arc<Resource>) {
+pub(super) fn compute_query_routes_from(tables: &mut Tables, res: &mut Arc<Resource>) {
     compute_query_routes(tables, res);
     let res = get_mut_unchecked(res);
     for child in res.childs.values_mut() {
@@ -1648,6 +1718,91 @@ fn compute_query_routes_from(tables: &mut Tables, res: &mut Arc<Resource>) {
     }
 }
 
+// The routes of a resource without queryables in the network are the same whatever the source
+// node: the routes from the new nodes of the network are copies of the route from this node.
+fn extend_query_routes_from(res: &mut Arc<Resource>, net_type: WhatAmI, len: usize) {
+    let res = get_mut_unchecked(res);
+    if let Some(ctx) = res.context.as_mut() {
+        let routes = match net_type {
+            WhatAmI::Router => &mut ctx.routers_query_routes,
+            _ => &mut ctx.peers_query_routes,
+        };
+        if let Some(route) = routes.first().cloned() {
+            if routes.len() < len {
+                routes.resize(len, route);
+            }
+        }
+    }
+    for child in res.childs.values_mut() {
+        extend_query_routes_from(child, net_type, len);
+    }
+}
+
+/// Asserts that the cached query routes of `res` are the ones a full recomputation gives.
+#[cfg(debug_assertions)]
+pub(super) fn check_query_routes(tables: &Tables, res: &Arc<Resource>) {
+    let summary = |route: Option<&Arc<QueryTargetQablSet>>| {
+        let mut summary = route
+            .map(|route| {
+                route
+                    .iter()
+                    .map(|qabl| {
+                        let (face, key_expr, context) = &qabl.direction;
+                        let target = (qabl.complete, OrderedFloat(qabl.distance));
+                        (face.id, key_expr.clone(), *context, target)
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        summary.sort_by_key(|(id, _, context, target)| (*id, *context, *target));
+        summary
+    };
+    let ctx = res.context();
+    if !ctx.valid_query_routes {
+        return;
+    }
+    let expected = compute_query_routes_(tables, res);
+    for (net_type, cached_routes, expected_routes) in [
+        (
+            WhatAmI::Router,
+            &ctx.routers_query_routes,
+            &expected.routers_query_routes,
+        ),
+        (
+            WhatAmI::Peer,
+            &ctx.peers_query_routes,
+            &expected.peers_query_routes,
+        ),
+    ] {
+        if let Some(net) = tables.get_net(net_type) {
+            for idx in net.graph.node_indices().map(|idx| idx.index()) {
+                if idx < cached_routes.len() {
+                    assert_eq!(
+                        summary(cached_routes.get(idx)),
+                        summary(expected_routes.get(idx)),
+                        "Inconsistent {} query route {} of {}",
+                        net_type,
+                        idx,
+                        res.expr()
+                    );
+                }
+            }
+        }
+    }
+    assert_eq!(
+        summary(ctx.peer_query_route.as_ref()),
+        summary(expected.peer_query_route.as_ref()),
+        "Inconsistent peer query route of {}",
+        res.expr()
+    );
+    assert_eq!(
+        summary(ctx.client_query_route.as_ref()),
+        summary(expected.client_query_route.as_ref()),
+        "Inconsistent client query route of {}",
+        res.expr()
+    );
+}
+
 pub(super) fn compute_matches_query_routes_(
     tables: &Tables,
     res: &Arc<Resource>,
@@ -1675,6 +1830,170 @@ fn insert_pending_query(outface: &mut Arc<FaceState>, query: Arc<Query>) -> Requ
     qid
 }
 
+/// The admission of a query under the query limits, with the query evicted to admit it if any.
+type QueryAdmission = (
+    Result<Option<QueryPermit>, QueryLimit>,
+    Option<(Query, QueryLimit)>,
+);
+
+/// Admits a new query `qid` received from the given face under the query limits, if any.
+///
+/// With the `evict_oldest` policy, the oldest pending query under the reached limit is evicted
+/// to admit the new one. The evicted query is returned, to be notified once the tables are
+/// released, see [`evict_query`].
+fn admit_query(tables: &Tables, face: &Arc<FaceState>, qid: RequestId) -> QueryAdmission {
+    let limits = match tables.query_limits.as_ref() {
+        Some(limits) => limits,
+        None => return (Ok(None), None),
+    };
+    match limits.admit(face) {
+        Ok(permit) => (Ok(Some(permit)), None),
+        Err(limit) if limits.policy == QueryLimitPolicy::EvictOldest => {
+            match evict_oldest_query(tables, face, qid, limit) {
+                Some(evicted) => (limits.admit(face).map(Some), Some((evicted, limit))),
+                None => (Err(limit), None),
+            }
+        }
+        Err(limit) => (Err(limit), None),
+    }
+}
+
+/// Removes the oldest pending query under the given limit from the faces it was routed to,
+/// releasing its admission. The query `qid` of the given face being admitted is never evicted:
+/// a query sent again with the same id while still pending is rejected instead.
+fn evict_oldest_query(
+    tables: &Tables,
+    face: &Arc<FaceState>,
+    qid: RequestId,
+    limit: QueryLimit,
+) -> Option<Query> {
+    let outfaces = || tables.faces.values().chain(tables.mcast_groups.iter());
+    let oldest = outfaces()
+        .flat_map(|outface| outface.pending_queries.values())
+        .filter(|query| !(Arc::ptr_eq(&query.src_face, face) && query.src_qid == qid))
+        .filter(|query| limit == QueryLimit::Total || Arc::ptr_eq(&query.src_face, face))
+        .filter_map(|query| query.permit.as_ref().map(|permit| (permit.seq, query)))
+        .min_by_key(|(seq, _)| *seq)
+        .map(|(_, query)| query.clone())?;
+    let mut removed = vec![];
+    for outface in outfaces() {
+        let qids = outface
+            .pending_queries
+            .iter()
+            .filter(|(_, query)| Arc::ptr_eq(query, &oldest))
+            .map(|(qid, _)| *qid)
+            .collect::<Vec<RequestId>>();
+        for qid in qids {
+            removed.extend(
+                get_mut_unchecked(&mut outface.clone())
+                    .pending_queries
+                    .remove(&qid),
+            );
+        }
+    }
+    drop(removed);
+    let mut query = Arc::into_inner(oldest)?;
+    query.permit = None;
+    Some(query)
+}
+
+/// Notifies the querier of a query evicted by the query limits.
+fn evict_query((query, limit): (Query, QueryLimit), zid: ZenohId) {
+    log::warn!(
+        "Alarm: evicted query {}:{}: {}",
+        query.src_face,
+        query.src_qid,
+        limit
+    );
+    reject_query(
+        &query.src_face,
+        query.src_qid,
+        zid,
+        query.response_correlation(),
+        limit,
+    );
+}
+
+/// Replies to a query rejected by the query limits with an error, followed by the final reply,
+/// so that the querier fails fast rather than waiting for the timeout of the query.
+fn reject_query(
+    face: &Arc<FaceState>,
+    qid: RequestId,
+    zid: ZenohId,
+    correlation: Option<response::ext::CorrelationType>,
+    limit: QueryLimit,
+) {
+    let payload = format!("Query rejected: {limit}");
+    reply_error(
+        face,
+        qid,
+        zid,
+        correlation,
+        0,
+        Encoding::TEXT_PLAIN,
+        payload,
+    );
+}
+
+/// Replies to a query throttled by the rate limit of its querier with a `THROTTLED` error
+/// telling when it may be retried, followed by the final reply.
+fn throttle_query(
+    face: &Arc<FaceState>,
+    qid: RequestId,
+    zid: ZenohId,
+    correlation: Option<response::ext::CorrelationType>,
+    throttled: Throttled,
+) {
+    let code = err::code::THROTTLED;
+    let payload = format!("retry_after_ms={}", throttled.retry_after.as_millis());
+    reply_error(
+        face,
+        qid,
+        zid,
+        correlation,
+        code,
+        Encoding::APP_PROPERTIES,
+        payload,
+    );
+}
+
+fn reply_error(
+    face: &Arc<FaceState>,
+    qid: RequestId,
+    zid: ZenohId,
+    correlation: Option<response::ext::CorrelationType>,
+    code: u16,
+    encoding: Encoding,
+    payload: String,
+) {
+    face.primitives.clone().send_response(Response {
+        rid: qid,
+        wire_expr: WireExpr::empty(),
+        payload: ResponseBody::Err(err::Err {
+            code,
+            is_infrastructure: true,
+            timestamp: None,
+            ext_sinfo: None,
+            ext_body: Some(err::ext::ErrBodyType {
+                #[cfg(feature = "shared-memory")]
+                ext_shm: None,
+                encoding,
+                payload: payload.into_bytes().into(),
+            }),
+            ext_unknown: vec![],
+        }),
+        ext_qos: response::ext::QoSType::response_default(),
+        ext_tstamp: None,
+        ext_respid: Some(ResponderIdType { zid, eid: 0 }),
+        ext_correlation: correlation,
+    });
+    face.primitives.clone().send_response_final(ResponseFinal {
+        rid: qid,
+        ext_qos: response::ext::QoSType::response_final_default(),
+        ext_tstamp: None,
+    });
+}
+
 #[inline]
 fn should_route(
     tables: &Tables,
@@ -2063,14 +2382,17 @@ pub fn route_query(
     target: TargetType,
     body: RequestBody,
     routing_context: u64,
+    correlation: Option<CorrelationType>,
+    repliers: Option<RepliersType>,
 ) {
     let rtables = zread!(tables_ref.tables);
     match rtables.get_mapping(face, &expr.scope, expr.mapping) {
         Some(prefix) => {
             log::debug!(
-                "Route query {}:{} for res {}{}",
+                "Route query {}:{} ({:?}) for res {}{}",
                 face,
                 qid,
+                correlation,
                 prefix.expr(),
                 expr.suffix.as_ref(),
             );
@@ -2086,6 +2408,33 @@ pub fn route_query(
                 inc_req_stats!(face, rx, admin, body)
             }
 
+            // The queries of the clients are rate limited by the router they are connected to
+            if let Some(throttle) = rtables
+                .query_throttle
+                .as_ref()
+                .filter(|_| rtables.whatami == WhatAmI::Router && face.whatami == WhatAmI::Client)
+            {
+                if let Ok(key_expr) = keyexpr::new(expr.full_expr()) {
+                    if let Err(throttled) = throttle.admit(&face.zid, key_expr) {
+                        log::debug!(
+                            "Throttled query {}:{} on {}: {}",
+                            face,
+                            qid,
+                            key_expr,
+                            throttled
+                        );
+                        let zid = rtables.zid;
+                        drop(rtables);
+                        let correlation = correlation.map(|c| response::ext::CorrelationType {
+                            zid: c.zid,
+                            eid: c.eid,
+                        });
+                        throttle_query(face, qid, zid, correlation, throttled);
+                        return;
+                    }
+                }
+            }
+
             if rtables.whatami != WhatAmI::Router
                 || face.whatami != WhatAmI::Peer
                 || rtables.peers_net.is_none()
@@ -2093,21 +2442,90 @@ pub fn route_query(
                     == *rtables.elect_router(expr.full_expr(), rtables.get_router_links(face.zid))
             {
                 let res = Resource::get_resource(&prefix, expr.suffix);
-                let route = get_query_route(&rtables, face, &res, &mut expr, routing_context);
+                let mut qabls = get_query_route(&rtables, face, &res, &mut expr, routing_context);
+                for qabl in qabls.iter() {
+                    qabl.direction.0.touch_mapping(&qabl.direction.1);
+                }
+                // The clients only hold their own queryables: the ones of the clients whose
+                // replies are not accepted by the querier are skipped
+                if let Some(repliers) = repliers.as_ref() {
+                    qabls = Arc::new(
+                        qabls
+                            .iter()
+                            .filter(|qabl| {
+                                qabl.direction.0.whatami != WhatAmI::Client
+                                    || repliers.contains(&qabl.direction.0.zid)
+                            })
+                            .cloned()
+                            .collect(),
+                    );
+                }
 
+                let trace = rtables
+                    .query_tracer
+                    .as_ref()
+                    .map(|tracer| (tracer.clone(), tracer.next_seq()));
+                let queries_lock = zwrite!(tables_ref.queries_lock);
+                let (permit, evicted) = admit_query(&rtables, face, qid);
+                let zid = rtables.zid;
+                let permit = match permit {
+                    Ok(permit) => permit,
+                    Err(limit) => {
+                        let full_expr = expr.full_expr().to_string();
+                        drop(queries_lock);
+                        drop(rtables);
+                        log::warn!(
+                            "Alarm: rejected query {}:{} on {}: {}",
+                            face,
+                            qid,
+                            full_expr,
+                            limit
+                        );
+                        if let Some(evicted) = evicted {
+                            evict_query(evicted, zid);
+                        }
+                        let correlation = correlation.map(|c| response::ext::CorrelationType {
+                            zid: c.zid,
+                            eid: c.eid,
+                        });
+                        reject_query(face, qid, zid, correlation, limit);
+                        return;
+                    }
+                };
                 let query = Arc::new(Query {
                     src_face: face.clone(),
                     src_qid: qid,
+                    correlation: correlation.clone(),
+                    trace: trace.clone(),
+                    permit,
                 });
-
-                let queries_lock = zwrite!(tables_ref.queries_lock);
-                let route = compute_final_route(&rtables, &route, face, &mut expr, &target, query);
-                let local_replies = compute_local_replies(&rtables, &prefix, expr.suffix, face);
-                let zid = rtables.zid;
+                let response_correlation = query.response_correlation();
+
+                let route = compute_final_route(&rtables, &qabls, face, &mut expr, &target, query);
+                if let Some((tracer, seq)) = trace {
+                    let trace = QueryTrace::new(
+                        &rtables,
+                        correlation.clone(),
+                        expr.full_expr(),
+                        face,
+                        &qabls,
+                        &route,
+                    );
+                    tracer.record(seq, trace);
+                }
+                let local_replies = if repliers.as_ref().map_or(true, |r| r.contains(&zid)) {
+                    compute_local_replies(&rtables, &prefix, expr.suffix, face)
+                } else {
+                    vec![]
+                };
 
                 drop(queries_lock);
                 drop(rtables);
 
+                if let Some(evicted) = evicted {
+                    evict_query(evicted, zid);
+                }
+
                 for (expr, payload) in local_replies {
                     let payload = ResponseBody::Reply(Reply {
                         timestamp: None,
@@ -2136,6 +2554,7 @@ pub fn route_query(
                             zid,
                             eid: 0, // TODO
                         }),
+                        ext_correlation: response_correlation.clone(),
                     });
                 }
 
@@ -2183,6 +2602,8 @@ pub fn route_query(
                                 ext_target: *t,
                                 ext_budget: None,
                                 ext_timeout: None,
+                                ext_correlation: correlation.clone(),
+                                ext_repliers: repliers.clone(),
                                 payload: body.clone(),
                             });
                         }
@@ -2218,6 +2639,8 @@ pub fn route_query(
                                 ext_target: target,
                                 ext_budget: None,
                                 ext_timeout: None,
+                                ext_correlation: correlation.clone(),
+                                ext_repliers: repliers.clone(),
                                 payload: body.clone(),
                             });
                         }
@@ -2254,6 +2677,7 @@ pub(crate) fn route_send_response(
     face: &mut Arc<FaceState>,
     qid: RequestId,
     ext_respid: Option<ResponderIdType>,
+    ext_correlation: Option<response::ext::CorrelationType>,
     key_expr: WireExpr,
     body: ResponseBody,
 ) {
@@ -2278,6 +2702,9 @@ pub(crate) fn route_send_response(
                 inc_res_stats!(query.src_face, tx, admin, body)
             }
 
+            if let Some((tracer, seq)) = &query.trace {
+                tracer.reply(*seq, face);
+            }
             query.src_face.primitives.clone().send_response(Response {
                 rid: query.src_qid,
                 wire_expr: key_expr.to_owned(),
@@ -2285,6 +2712,7 @@ pub(crate) fn route_send_response(
                 ext_qos: response::ext::QoSType::response_default(),
                 ext_tstamp: None,
                 ext_respid,
+                ext_correlation: query.response_correlation().or(ext_correlation),
             });
         }
         None => log::warn!(
@@ -0,0 +1,167 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use super::face::FaceState;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use zenoh_result::{bail, ZResult};
+
+/// What to do with a new query once a limit on the pending queries is reached.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum QueryLimitPolicy {
+    /// The new query is rejected.
+    Reject,
+    /// The oldest pending query under the same limit is evicted to admit the new one.
+    EvictOldest,
+}
+
+impl QueryLimitPolicy {
+    pub(crate) fn from_config(policy: &str) -> ZResult<Self> {
+        match policy {
+            "reject" => Ok(QueryLimitPolicy::Reject),
+            "evict_oldest" => Ok(QueryLimitPolicy::EvictOldest),
+            _ => bail!(
+                "Unknown query limits policy `{}`: expected `reject` or `evict_oldest`",
+                policy
+            ),
+        }
+    }
+}
+
+/// The limit reached by a query.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum QueryLimit {
+    /// The limit of pending queries of the face the query was received from.
+    Face,
+    /// The limit of pending queries of this node.
+    Total,
+}
+
+impl fmt::Display for QueryLimit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QueryLimit::Face => write!(f, "too many pending queries from the face"),
+            QueryLimit::Total => write!(f, "too many pending queries"),
+        }
+    }
+}
+
+/// The limits on the queries routed by this node and waiting for their final replies.
+pub(crate) struct QueryLimits {
+    pub(crate) per_face: usize,
+    pub(crate) total: usize,
+    pub(crate) policy: QueryLimitPolicy,
+    pending: AtomicUsize,
+    next_seq: AtomicU64,
+}
+
+impl QueryLimits {
+    pub(crate) fn new(per_face: usize, total: usize, policy: QueryLimitPolicy) -> Self {
+        QueryLimits {
+            per_face,
+            total,
+            policy,
+            pending: AtomicUsize::new(0),
+            next_seq: AtomicU64::new(0),
+        }
+    }
+
+    /// Admits a new query received from the given face, returning the limit it reached if any.
+    /// The query is pending until the returned permit is dropped.
+    pub(crate) fn admit(
+        self: &Arc<Self>,
+        face: &Arc<FaceState>,
+    ) -> Result<QueryPermit, QueryLimit> {
+        if face.pending_src_queries.fetch_add(1, Ordering::AcqRel) >= self.per_face {
+            face.pending_src_queries.fetch_sub(1, Ordering::AcqRel);
+            return Err(QueryLimit::Face);
+        }
+        if self.pending.fetch_add(1, Ordering::AcqRel) >= self.total {
+            self.pending.fetch_sub(1, Ordering::AcqRel);
+            face.pending_src_queries.fetch_sub(1, Ordering::AcqRel);
+            return Err(QueryLimit::Total);
+        }
+        Ok(QueryPermit {
+            limits: self.clone(),
+            face: face.clone(),
+            seq: self.next_seq.fetch_add(1, Ordering::Relaxed),
+        })
+    }
+
+    /// The number of queries currently pending.
+    #[cfg(test)]
+    pub(crate) fn pending(&self) -> usize {
+        self.pending.load(Ordering::Acquire)
+    }
+}
+
+/// A query admitted by the [`QueryLimits`], released when dropped.
+pub(crate) struct QueryPermit {
+    limits: Arc<QueryLimits>,
+    face: Arc<FaceState>,
+    // The admission order of the query, the oldest query having the lowest
+    pub(crate) seq: u64,
+}
+
+impl Drop for QueryPermit {
+    fn drop(&mut self) {
+        self.face.pending_src_queries.fetch_sub(1, Ordering::AcqRel);
+        self.limits.pending.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zenoh_protocol::core::{WhatAmI, ZenohId};
+    use zenoh_transport::DummyPrimitives;
+
+    fn face(id: usize) -> Arc<FaceState> {
+        FaceState::new(
+            id,
+            ZenohId::rand(),
+            WhatAmI::Client,
+            #[cfg(feature = "stats")]
+            None,
+            Arc::new(DummyPrimitives),
+            0,
+            None,
+            false,
+            None,
+            None,
+            None,
+        )
+    }
+
+    #[test]
+    fn query_limits() {
+        let limits = Arc::new(QueryLimits::new(2, 3, QueryLimitPolicy::Reject));
+        let face1 = face(1);
+        let face2 = face(2);
+
+        let p1 = limits.admit(&face1).unwrap();
+        let _p2 = limits.admit(&face1).unwrap();
+        assert_eq!(limits.admit(&face1).err(), Some(QueryLimit::Face));
+        let p3 = limits.admit(&face2).unwrap();
+        assert_eq!(limits.admit(&face2).err(), Some(QueryLimit::Total));
+        assert_eq!(limits.pending(), 3);
+
+        // Releasing a query of the first face admits a new one of the second face
+        drop(p1);
+        assert_eq!(limits.pending(), 2);
+        let p4 = limits.admit(&face2).unwrap();
+        assert!(p4.seq > p3.seq);
+        assert_eq!(limits.admit(&face1).err(), Some(QueryLimit::Total));
+    }
+}
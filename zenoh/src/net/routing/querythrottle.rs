@@ -0,0 +1,300 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use crate::prelude::json;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use zenoh_config::QueryThrottleRuleConf;
+use zenoh_core::zlock;
+use zenoh_protocol::core::{
+    key_expr::{keyexpr, OwnedKeyExpr},
+    ZenohId,
+};
+use zenoh_result::{bail, ZResult};
+use zenoh_util::clock::Clock;
+
+/// A rate limit on the queries of each client on some key expressions.
+struct QueryThrottleRule {
+    keyexprs: Vec<OwnedKeyExpr>,
+    zids: Vec<ZenohId>,
+    // Queries per second
+    rate: u64,
+    burst: u64,
+}
+
+impl QueryThrottleRule {
+    fn applies(&self, zid: &ZenohId, key_expr: &keyexpr) -> bool {
+        (self.zids.is_empty() || self.zids.contains(zid))
+            && self.keyexprs.iter().any(|ke| ke.intersects(key_expr))
+    }
+}
+
+/// A query beyond the rate limit of its querier.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct Throttled {
+    /// The time after which a query of the querier would be admitted.
+    pub(crate) retry_after: Duration,
+}
+
+impl fmt::Display for Throttled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "too many queries, retry after {}ms",
+            self.retry_after.as_millis()
+        )
+    }
+}
+
+struct Bucket {
+    tokens: f64,
+    last: Instant,
+}
+
+/// The token buckets and the counters of a querier.
+struct Subject {
+    // The last use of the subject, for the LRU
+    tick: u64,
+    // By index of rule
+    buckets: HashMap<usize, Bucket>,
+    admitted: u64,
+    throttled: u64,
+}
+
+#[derive(Default)]
+struct ThrottleState {
+    next_tick: u64,
+    subjects: HashMap<ZenohId, Subject>,
+    by_use: BTreeMap<u64, ZenohId>,
+}
+
+impl ThrottleState {
+    /// Returns the subject of `zid`, marked as the most recently used one, forgetting the least
+    /// recently used subjects beyond `max_subjects`.
+    fn subject(&mut self, zid: ZenohId, max_subjects: usize) -> &mut Subject {
+        let tick = self.next_tick;
+        self.next_tick += 1;
+        match self.subjects.get_mut(&zid) {
+            Some(subject) => {
+                self.by_use.remove(&subject.tick);
+                subject.tick = tick;
+            }
+            None => {
+                while self.subjects.len() >= max_subjects {
+                    match self.by_use.pop_first() {
+                        Some((_, lru)) => self.subjects.remove(&lru),
+                        None => break,
+                    };
+                }
+                self.subjects.insert(
+                    zid,
+                    Subject {
+                        tick,
+                        buckets: HashMap::new(),
+                        admitted: 0,
+                        throttled: 0,
+                    },
+                );
+            }
+        }
+        self.by_use.insert(tick, zid);
+        self.subjects.get_mut(&zid).unwrap()
+    }
+}
+
+/// The rate limits on the queries of the clients, see `routing/query/throttle`.
+///
+/// Each client has a token bucket per rule, the first rule matching a query applying to it. The
+/// buckets of the least recently active clients are forgotten beyond `max_subjects`.
+pub(crate) struct QueryThrottle {
+    rules: Vec<QueryThrottleRule>,
+    max_subjects: usize,
+    clock: Arc<dyn Clock>,
+    state: Mutex<ThrottleState>,
+}
+
+impl QueryThrottle {
+    pub(crate) fn new(
+        rules: &[QueryThrottleRuleConf],
+        max_subjects: usize,
+        clock: Arc<dyn Clock>,
+    ) -> ZResult<Self> {
+        let mut throttle_rules = Vec::with_capacity(rules.len());
+        for rule in rules {
+            if rule.rate == 0 {
+                bail!(
+                    "Invalid query throttle rule on {:?}: the rate must be positive",
+                    rule.keyexprs
+                );
+            }
+            throttle_rules.push(QueryThrottleRule {
+                keyexprs: rule.keyexprs.clone(),
+                zids: rule.zids.clone(),
+                rate: rule.rate,
+                burst: rule.burst.max(1),
+            });
+        }
+        Ok(QueryThrottle {
+            rules: throttle_rules,
+            max_subjects: max_subjects.max(1),
+            clock,
+            state: Mutex::new(ThrottleState::default()),
+        })
+    }
+
+    /// Admits a query of `zid` on `key_expr`, or returns the time after which it may be retried.
+    pub(crate) fn admit(&self, zid: &ZenohId, key_expr: &keyexpr) -> Result<(), Throttled> {
+        let (index, rule) = match self
+            .rules
+            .iter()
+            .enumerate()
+            .find(|(_, rule)| rule.applies(zid, key_expr))
+        {
+            Some(rule) => rule,
+            None => return Ok(()),
+        };
+        let now = self.clock.now();
+        let state = &mut *zlock!(self.state);
+        let subject = state.subject(*zid, self.max_subjects);
+        let bucket = subject.buckets.entry(index).or_insert(Bucket {
+            tokens: rule.burst as f64,
+            last: now,
+        });
+        let elapsed = now.saturating_duration_since(bucket.last);
+        bucket.tokens =
+            (bucket.tokens + elapsed.as_secs_f64() * rule.rate as f64).min(rule.burst as f64);
+        bucket.last = now;
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            subject.admitted += 1;
+            Ok(())
+        } else {
+            subject.throttled += 1;
+            // Rounded up to the millisecond, not to retry too early
+            let wait = (1.0 - bucket.tokens) / rule.rate as f64;
+            Err(Throttled {
+                retry_after: Duration::from_millis((wait * 1000.0).ceil() as u64),
+            })
+        }
+    }
+
+    /// The counters of the queries of `zid`, if it is tracked.
+    pub(crate) fn info(&self, zid: &ZenohId) -> Option<json::QueryThrottleInfo> {
+        zlock!(self.state)
+            .subjects
+            .get(zid)
+            .map(|subject| json::QueryThrottleInfo {
+                admitted: subject.admitted,
+                throttled: subject.throttled,
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::TryFrom;
+    use zenoh_util::clock::TestClock;
+
+    fn rule(keyexpr: &str, zids: Vec<ZenohId>, rate: u64, burst: u64) -> QueryThrottleRuleConf {
+        QueryThrottleRuleConf {
+            keyexprs: vec![OwnedKeyExpr::try_from(keyexpr).unwrap()],
+            zids,
+            rate,
+            burst,
+        }
+    }
+
+    #[test]
+    fn query_throttle() {
+        let clock = TestClock::new();
+        let zid1 = ZenohId::try_from([1]).unwrap();
+        let zid2 = ZenohId::try_from([2]).unwrap();
+        let throttle = QueryThrottle::new(
+            &[
+                rule("storage/vip/**", vec![zid2], 1000, 1000),
+                rule("storage/**", vec![], 10, 2),
+            ],
+            10,
+            Arc::new(clock.clone()),
+        )
+        .unwrap();
+        let storage = keyexpr::new("storage/a").unwrap();
+
+        // The burst is admitted right away, the next query waiting for a token
+        assert_eq!(throttle.admit(&zid1, storage), Ok(()));
+        assert_eq!(throttle.admit(&zid1, storage), Ok(()));
+        let retry_after = throttle.admit(&zid1, storage).unwrap_err().retry_after;
+        assert_eq!(retry_after, Duration::from_millis(100));
+        clock.advance(Duration::from_millis(50));
+        let retry_after = throttle.admit(&zid1, storage).unwrap_err().retry_after;
+        assert!(retry_after > Duration::ZERO && retry_after <= Duration::from_millis(51));
+        clock.advance(Duration::from_millis(60));
+        assert_eq!(throttle.admit(&zid1, storage), Ok(()));
+
+        // Each querier has its own bucket, and the queries outside of the rules are admitted
+        assert_eq!(throttle.admit(&zid2, storage), Ok(()));
+        assert_eq!(throttle.admit(&zid2, storage), Ok(()));
+        assert!(throttle.admit(&zid2, storage).is_err());
+        assert_eq!(
+            throttle.admit(&zid1, keyexpr::new("other").unwrap()),
+            Ok(())
+        );
+        // The first matching rule applies, a wildcard query matching the rules it intersects
+        assert_eq!(
+            throttle.admit(&zid2, keyexpr::new("storage/vip/a").unwrap()),
+            Ok(())
+        );
+        assert!(throttle.admit(&zid1, keyexpr::new("**").unwrap()).is_err());
+
+        assert_eq!(
+            throttle.info(&zid1),
+            Some(json::QueryThrottleInfo {
+                admitted: 3,
+                throttled: 3,
+            })
+        );
+        assert_eq!(
+            throttle.info(&zid2),
+            Some(json::QueryThrottleInfo {
+                admitted: 3,
+                throttled: 1,
+            })
+        );
+    }
+
+    #[test]
+    fn query_throttle_lru() {
+        let clock = TestClock::new();
+        let throttle =
+            QueryThrottle::new(&[rule("**", vec![], 1, 1)], 2, Arc::new(clock.clone())).unwrap();
+        let key_expr = keyexpr::new("a").unwrap();
+        let zids = (1..=3)
+            .map(|id| ZenohId::try_from([id]).unwrap())
+            .collect::<Vec<_>>();
+
+        assert_eq!(throttle.admit(&zids[0], key_expr), Ok(()));
+        assert_eq!(throttle.admit(&zids[1], key_expr), Ok(()));
+        assert!(throttle.admit(&zids[0], key_expr).is_err());
+        // The least recently active querier is forgotten beyond the maximum, with its bucket
+        assert_eq!(throttle.admit(&zids[2], key_expr), Ok(()));
+        assert!(throttle.info(&zids[1]).is_none());
+        assert!(throttle.info(&zids[0]).is_some());
+        assert_eq!(throttle.admit(&zids[1], key_expr), Ok(()));
+        assert!(throttle.info(&zids[0]).is_none());
+
+        assert!(QueryThrottle::new(&[rule("**", vec![], 0, 1)], 2, Arc::new(clock)).is_err());
+    }
+}
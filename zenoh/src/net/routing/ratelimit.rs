@@ -0,0 +1,222 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use zenoh_core::zlock;
+use zenoh_util::clock::Clock;
+
+/// The declaration rate limiting parameters, shared by all the faces.
+#[derive(Clone)]
+pub(crate) struct DeclarationRate {
+    // Declarations per second
+    pub(crate) rate: u64,
+    pub(crate) burst: u64,
+    pub(crate) queue: usize,
+    pub(crate) clock: Arc<dyn Clock>,
+}
+
+/// What to do with a declaration submitted to a [`DeclarationLimiter`].
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum Admission<T> {
+    /// The declaration can be processed right away.
+    Process(T),
+    /// The declaration has been queued. If `drain` is `true`, the caller must
+    /// start draining the queue with [`DeclarationLimiter::next`].
+    Queued { drain: bool },
+    /// The queue is full: the declaration has been dropped and the face must be closed.
+    Overflow,
+    /// The limiter has been closed: the declaration has been dropped.
+    Dropped,
+}
+
+struct LimiterState<T> {
+    tokens: f64,
+    last: Instant,
+    queue: VecDeque<T>,
+    draining: bool,
+    closed: bool,
+}
+
+/// A token bucket limiting the rate of the declarations received on a face.
+///
+/// The declarations exceeding the rate are queued, in order, and released at the rate.
+pub(crate) struct DeclarationLimiter<T> {
+    rate: DeclarationRate,
+    state: Mutex<LimiterState<T>>,
+}
+
+impl<T> DeclarationLimiter<T> {
+    pub(crate) fn new(rate: DeclarationRate) -> Self {
+        let state = LimiterState {
+            tokens: rate.burst as f64,
+            last: rate.clock.now(),
+            queue: VecDeque::new(),
+            draining: false,
+            closed: false,
+        };
+        DeclarationLimiter {
+            rate,
+            state: Mutex::new(state),
+        }
+    }
+
+    pub(crate) fn clock(&self) -> &Arc<dyn Clock> {
+        &self.rate.clock
+    }
+
+    fn refill(&self, state: &mut LimiterState<T>) {
+        let now = self.rate.clock.now();
+        let elapsed = now.saturating_duration_since(state.last);
+        state.tokens = (state.tokens + elapsed.as_secs_f64() * self.rate.rate as f64)
+            .min(self.rate.burst as f64);
+        state.last = now;
+    }
+
+    pub(crate) fn submit(&self, declaration: T) -> Admission<T> {
+        let state = &mut *zlock!(self.state);
+        if state.closed {
+            return Admission::Dropped;
+        }
+        self.refill(state);
+        // Queued declarations go first to preserve the declarations order,
+        // including the last one popped and still being processed by the draining
+        if !state.draining && state.tokens >= 1.0 {
+            state.tokens -= 1.0;
+            return Admission::Process(declaration);
+        }
+        if state.queue.len() >= self.rate.queue {
+            state.closed = true;
+            state.queue.clear();
+            return Admission::Overflow;
+        }
+        state.queue.push_back(declaration);
+        let drain = !state.draining;
+        state.draining = true;
+        Admission::Queued { drain }
+    }
+
+    /// Admits a declaration to be processed right away whatever the rate, the messages received
+    /// after it depending on it: the declarations submitted afterwards are delayed accordingly.
+    /// The debt is bounded by the size of the queue.
+    pub(crate) fn charge(&self, declaration: T) -> Admission<T> {
+        let state = &mut *zlock!(self.state);
+        if state.closed {
+            return Admission::Dropped;
+        }
+        self.refill(state);
+        if state.tokens < -(self.rate.queue as f64) {
+            state.closed = true;
+            state.queue.clear();
+            return Admission::Overflow;
+        }
+        state.tokens -= 1.0;
+        Admission::Process(declaration)
+    }
+
+    /// Returns the next queued declaration to process, or the time to wait before it can be
+    /// processed. Returns `None` once the queue is empty, ending the draining.
+    pub(crate) fn next(&self) -> Option<Result<T, Duration>> {
+        let state = &mut *zlock!(self.state);
+        if state.queue.is_empty() {
+            state.draining = false;
+            return None;
+        }
+        self.refill(state);
+        if state.tokens >= 1.0 {
+            state.tokens -= 1.0;
+            state.queue.pop_front().map(Ok)
+        } else {
+            let wait = (1.0 - state.tokens) / self.rate.rate.max(1) as f64;
+            Some(Err(Duration::from_secs_f64(wait)))
+        }
+    }
+
+    /// Drops the queued declarations and all the declarations submitted afterwards.
+    pub(crate) fn close(&self) {
+        let state = &mut *zlock!(self.state);
+        state.closed = true;
+        state.queue.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zenoh_util::clock::TestClock;
+
+    #[test]
+    fn declaration_limiter() {
+        let clock = TestClock::new();
+        let limiter = DeclarationLimiter::new(DeclarationRate {
+            rate: 10,
+            burst: 2,
+            queue: 2,
+            clock: Arc::new(clock.clone()),
+        });
+
+        // The burst is processed right away
+        assert_eq!(limiter.submit(1), Admission::Process(1));
+        assert_eq!(limiter.submit(2), Admission::Process(2));
+        assert_eq!(limiter.next(), None);
+
+        // Beyond the burst, the declarations are queued and released at the rate
+        assert_eq!(limiter.submit(3), Admission::Queued { drain: true });
+        assert_eq!(limiter.submit(4), Admission::Queued { drain: false });
+        assert!(matches!(limiter.next(), Some(Err(_))));
+        clock.advance(Duration::from_millis(100));
+        assert_eq!(limiter.next(), Some(Ok(3)));
+        // The queue goes first even if a token is available
+        clock.advance(Duration::from_millis(200));
+        assert_eq!(limiter.submit(5), Admission::Queued { drain: false });
+        assert_eq!(limiter.next(), Some(Ok(4)));
+        assert_eq!(limiter.next(), Some(Ok(5)));
+        assert_eq!(limiter.next(), None);
+
+        // Overflowing the queue closes the limiter
+        assert_eq!(limiter.submit(6), Admission::Queued { drain: true });
+        assert_eq!(limiter.submit(7), Admission::Queued { drain: false });
+        assert_eq!(limiter.submit(8), Admission::Overflow);
+        assert_eq!(limiter.submit(9), Admission::Dropped);
+        assert_eq!(limiter.next(), None);
+    }
+
+    #[test]
+    fn declaration_limiter_charge() {
+        let clock = TestClock::new();
+        let limiter = DeclarationLimiter::new(DeclarationRate {
+            rate: 10,
+            burst: 1,
+            queue: 2,
+            clock: Arc::new(clock.clone()),
+        });
+
+        // The charged declarations are processed right away, delaying the submitted ones
+        assert_eq!(limiter.charge(1), Admission::Process(1));
+        assert_eq!(limiter.charge(2), Admission::Process(2));
+        assert_eq!(limiter.submit(3), Admission::Queued { drain: true });
+        clock.advance(Duration::from_millis(100));
+        assert!(matches!(limiter.next(), Some(Err(_))));
+        clock.advance(Duration::from_millis(100));
+        assert_eq!(limiter.next(), Some(Ok(3)));
+        assert_eq!(limiter.next(), None);
+
+        // Beyond the size of the queue, the debt closes the limiter
+        assert_eq!(limiter.charge(4), Admission::Process(4));
+        assert_eq!(limiter.charge(5), Admission::Process(5));
+        assert_eq!(limiter.charge(6), Admission::Process(6));
+        assert_eq!(limiter.charge(7), Admission::Overflow);
+        assert_eq!(limiter.submit(8), Admission::Dropped);
+    }
+}
@@ -0,0 +1,236 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+//! Soft-state refresh of the declarations sent over multicast groups.
+//!
+//! The declarations sent over a multicast transport are best effort: a lost declaration is
+//! never repaired. Each node periodically sends a digest of the subscriptions it declared to
+//! the group, hashed with a fixed hash function so that all the nodes compute the same digest. A node whose view of the subscriptions of the sender doesn't match the digest
+//! requests the full set, which then replaces its view.
+use super::face::{Face, FaceState};
+use super::router::{Tables, TablesLock};
+use crate::net::codec::Zenoh080Routing;
+use crate::net::protocol::refresh::{DeclarationDigest, DeclarationRequest, DeclarationSet};
+use std::collections::HashSet;
+use std::hash::Hasher;
+use std::sync::Arc;
+use std::time::Duration;
+use twox_hash::XxHash64;
+use zenoh_buffers::{reader::HasReader, writer::HasWriter, ZBuf};
+use zenoh_codec::{RCodec, WCodec};
+use zenoh_core::zread;
+use zenoh_protocol::{
+    common::ZExtBody,
+    core::{WhatAmI, WireExpr},
+    network::{
+        declare::{self, common::ext::WireExprType, ext, subscriber::ext::SubscriberInfo},
+        oam::{
+            self,
+            id::{OAM_DECLARATION_DIGEST, OAM_DECLARATION_REQUEST, OAM_DECLARATION_SET},
+            OamId,
+        },
+        Declare, DeclareBody, NetworkBody, NetworkMessage, Oam,
+    },
+};
+use zenoh_result::ZResult;
+use zenoh_transport::Primitives;
+
+/// The configuration of the soft-state refresh of the declarations sent over multicast groups.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct DeclarationRefresh {
+    /// The period of the digests.
+    pub(crate) period: Duration,
+    /// The number of buckets of the digests.
+    pub(crate) buckets: usize,
+}
+
+/// The subscriptions this node declared to the given multicast group, sorted. The routers and
+/// the peers of a linkstate network don't declare their subscriptions to the multicast groups.
+pub(crate) fn local_subscriptions(tables: &Tables, group: &FaceState) -> Option<Vec<String>> {
+    if tables.whatami == WhatAmI::Router || tables.full_net(WhatAmI::Peer) {
+        return None;
+    }
+    let mut subs = group
+        .local_subs
+        .iter()
+        .map(|res| res.expr())
+        .collect::<Vec<String>>();
+    subs.sort();
+    Some(subs)
+}
+
+/// The subscriptions received from the given face.
+fn remote_subscriptions(face: &FaceState) -> HashSet<String> {
+    face.remote_subs.iter().map(|res| res.expr()).collect()
+}
+
+/// Computes the digest of the given subscriptions: each key expression is hashed in one of the
+/// buckets, the hashes of a bucket being combined independently of their order.
+pub(crate) fn digest<'a>(
+    subs: impl Iterator<Item = &'a String>,
+    buckets: usize,
+) -> DeclarationDigest {
+    let mut digest = vec![0u64; buckets.max(1)];
+    for sub in subs {
+        let mut hasher = XxHash64::with_seed(0);
+        hasher.write(sub.as_bytes());
+        let hash = hasher.finish();
+        let n = digest.len();
+        digest[(hash % n as u64) as usize] ^= hash;
+    }
+    DeclarationDigest { buckets: digest }
+}
+
+fn make_msg(id: OamId, buf: ZBuf) -> NetworkMessage {
+    NetworkBody::OAM(Oam {
+        id,
+        body: ZExtBody::ZBuf(buf),
+        ext_qos: oam::ext::QoSType::oam_default(),
+        ext_tstamp: None,
+    })
+    .into()
+}
+
+fn digest_msg(tables: &Tables, group: &FaceState, buckets: usize) -> Option<NetworkMessage> {
+    let subs = local_subscriptions(tables, group)?;
+    let mut buf = ZBuf::empty();
+    Zenoh080Routing::new()
+        .write(&mut buf.writer(), &digest(subs.iter(), buckets))
+        .ok()?;
+    Some(make_msg(OAM_DECLARATION_DIGEST, buf))
+}
+
+/// Periodically sends the digest of the subscriptions declared to the multicast group with
+/// `send`, until it fails.
+pub(crate) async fn refresh_task<F>(
+    tables: &TablesLock,
+    group: Arc<FaceState>,
+    refresh: DeclarationRefresh,
+    send: F,
+) where
+    F: Fn(NetworkMessage) -> ZResult<()>,
+{
+    loop {
+        async_std::task::sleep(refresh.period).await;
+        let msg = digest_msg(&zread!(tables.tables), &group, refresh.buckets);
+        if let Some(msg) = msg {
+            log::trace!("Send declaration digest {:?}", msg);
+            if let Err(e) = send(msg) {
+                log::debug!("Stop declaration refresh: {}", e);
+                break;
+            }
+        }
+    }
+}
+
+/// Handles a refresh message received from the node of the given face, returning the message
+/// to send back to the multicast group, if any.
+pub(crate) fn handle_oam(face: &Face, oam: Oam) -> Option<NetworkMessage> {
+    let buf = match oam.body {
+        ZExtBody::ZBuf(buf) => buf,
+        _ => return None,
+    };
+    let codec = Zenoh080Routing::new();
+    let mut reader = buf.reader();
+    match oam.id {
+        OAM_DECLARATION_DIGEST => {
+            let received: DeclarationDigest = codec.read(&mut reader).ok()?;
+            let known = {
+                let _rtables = zread!(face.tables.tables);
+                remote_subscriptions(&face.state)
+            };
+            if digest(known.iter(), received.buckets.len()) == received {
+                return None;
+            }
+            log::debug!(
+                "Declarations of {} out of sync, request them",
+                face.state.zid
+            );
+            let mut buf = ZBuf::empty();
+            codec
+                .write(
+                    &mut buf.writer(),
+                    &DeclarationRequest {
+                        zid: face.state.zid,
+                    },
+                )
+                .ok()?;
+            Some(make_msg(OAM_DECLARATION_REQUEST, buf))
+        }
+        OAM_DECLARATION_REQUEST => {
+            let request: DeclarationRequest = codec.read(&mut reader).ok()?;
+            let subscribers = {
+                let rtables = zread!(face.tables.tables);
+                if request.zid != rtables.zid {
+                    return None;
+                }
+                // The group the request was received from
+                let group = rtables
+                    .mcast_groups
+                    .iter()
+                    .find(|group| group.mcast_group == face.state.mcast_group)?;
+                local_subscriptions(&rtables, group)?
+            };
+            let mut buf = ZBuf::empty();
+            codec
+                .write(&mut buf.writer(), &DeclarationSet { subscribers })
+                .ok()?;
+            Some(make_msg(OAM_DECLARATION_SET, buf))
+        }
+        OAM_DECLARATION_SET => {
+            let set: DeclarationSet = codec.read(&mut reader).ok()?;
+            apply_set(face, set);
+            None
+        }
+        _ => None,
+    }
+}
+
+/// Replaces the subscriptions received from the node of the given face by the given set.
+fn apply_set(face: &Face, set: DeclarationSet) {
+    let mut known = {
+        let _rtables = zread!(face.tables.tables);
+        remote_subscriptions(&face.state)
+    };
+    for sub in set.subscribers {
+        if !known.remove(&sub) {
+            log::debug!("Refresh subscription {} of {}", sub, face.state.zid);
+            face.send_declare(Declare {
+                ext_qos: ext::QoSType::declare_default(),
+                ext_tstamp: None,
+                ext_nodeid: ext::NodeIdType::default(),
+                body: DeclareBody::DeclareSubscriber(declare::DeclareSubscriber {
+                    id: 0,
+                    wire_expr: WireExpr::from(sub),
+                    ext_info: SubscriberInfo::default(),
+                    ext_filter: None,
+                }),
+            });
+        }
+    }
+    // The subscriptions left are not declared by the node anymore
+    for sub in known {
+        log::debug!("Forget stale subscription {} of {}", sub, face.state.zid);
+        face.send_declare(Declare {
+            ext_qos: ext::QoSType::declare_default(),
+            ext_tstamp: None,
+            ext_nodeid: ext::NodeIdType::default(),
+            body: DeclareBody::UndeclareSubscriber(declare::UndeclareSubscriber {
+                id: 0,
+                ext_wire_expr: WireExprType {
+                    wire_expr: WireExpr::from(sub),
+                },
+            }),
+        });
+    }
+}
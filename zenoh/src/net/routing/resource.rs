@@ -12,8 +12,10 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 use super::face::FaceState;
+use super::mappings;
 use super::router::{Tables, TablesLock};
-use std::collections::{HashMap, HashSet};
+use crate::filter::Filter;
+use std::collections::{hash_map::Entry, HashMap, HashSet};
 use std::convert::TryInto;
 use std::hash::{Hash, Hasher};
 use std::sync::{Arc, Weak};
@@ -22,11 +24,14 @@ use zenoh_protocol::network::request::ext::TargetType;
 use zenoh_protocol::network::RequestId;
 use zenoh_protocol::zenoh::PushBody;
 use zenoh_protocol::{
-    core::{key_expr::keyexpr, ExprId, WireExpr, ZenohId},
+    core::{
+        key_expr::keyexpr, EntityGlobalId, EntityId, ExprId, Reliability, WhatAmI, WireExpr,
+        ZenohId, EMPTY_EXPR_ID,
+    },
     network::{
         declare::{
             ext, queryable::ext::QueryableInfo, subscriber::ext::SubscriberInfo, Declare,
-            DeclareBody, DeclareKeyExpr,
+            DeclareBody, DeclareKeyExpr, DeclareKeyExprs,
         },
         Mapping,
     },
@@ -36,11 +41,13 @@ use zenoh_sync::get_mut_unchecked;
 pub(super) type RoutingContext = u16;
 
 pub(super) type Direction = (Arc<FaceState>, WireExpr<'static>, Option<RoutingContext>);
-pub(super) type Route = HashMap<usize, Direction>;
+// The filter of a route is the one the samples must match to be sent on its face, if any
+pub(super) type Route = HashMap<usize, (Direction, Reliability, Option<Arc<Filter>>)>;
 #[cfg(feature = "complete_n")]
 pub(super) type QueryRoute = HashMap<usize, (Direction, RequestId, TargetType)>;
 #[cfg(not(feature = "complete_n"))]
 pub(super) type QueryRoute = HashMap<usize, (Direction, RequestId)>;
+#[derive(Clone)]
 pub(super) struct QueryTargetQabl {
     pub(super) direction: Direction,
     pub(super) complete: u64,
@@ -54,7 +61,13 @@ pub(super) struct SessionContext {
     pub(super) local_expr_id: Option<ExprId>,
     pub(super) remote_expr_id: Option<ExprId>,
     pub(super) subs: Option<SubscriberInfo>,
+    // The id of the subscriber declared by the face, when the declaration carried one
+    pub(super) sub_id: Option<EntityId>,
+    // The filter of the subscriptions declared by the face, none of them being unfiltered
+    pub(super) sub_filter: Option<Arc<Filter>>,
     pub(super) qabl: Option<QueryableInfo>,
+    // The id of the queryable declared by the face, when the declaration carried one
+    pub(super) qabl_id: Option<EntityId>,
     pub(super) last_values: HashMap<String, PushBody>,
 }
 
@@ -76,6 +89,8 @@ pub(super) struct QueryRoutes {
 pub(super) struct ResourceContext {
     pub(super) router_subs: HashSet<ZenohId>,
     pub(super) peer_subs: HashSet<ZenohId>,
+    pub(super) reliable_router_subs: HashSet<ZenohId>,
+    pub(super) reliable_peer_subs: HashSet<ZenohId>,
     pub(super) router_qabls: HashMap<ZenohId, QueryableInfo>,
     pub(super) peer_qabls: HashMap<ZenohId, QueryableInfo>,
     pub(super) matches: Vec<Weak<Resource>>,
@@ -90,6 +105,8 @@ pub(super) struct ResourceContext {
     pub(super) peers_query_routes: Vec<Arc<QueryTargetQablSet>>,
     pub(super) peer_query_route: Option<Arc<QueryTargetQablSet>>,
     pub(super) client_query_route: Option<Arc<QueryTargetQablSet>>,
+    // The faces indexing the resource in their routed resources, by id
+    pub(super) routed_faces: HashMap<usize, Weak<FaceState>>,
 }
 
 impl ResourceContext {
@@ -97,6 +114,8 @@ impl ResourceContext {
         ResourceContext {
             router_subs: HashSet::new(),
             peer_subs: HashSet::new(),
+            reliable_router_subs: HashSet::new(),
+            reliable_peer_subs: HashSet::new(),
             router_qabls: HashMap::new(),
             peer_qabls: HashMap::new(),
             matches: Vec::new(),
@@ -111,10 +130,11 @@ impl ResourceContext {
             peers_query_routes: Vec::new(),
             peer_query_route: None,
             client_query_route: None,
+            routed_faces: HashMap::new(),
         }
     }
 
-    pub(super) fn update_data_routes(&mut self, data_routes: DataRoutes) {
+    fn update_data_routes(&mut self, data_routes: DataRoutes) {
         self.valid_data_routes = true;
         if let Some(matching_pulls) = data_routes.matching_pulls {
             self.matching_pulls = matching_pulls;
@@ -125,7 +145,7 @@ impl ResourceContext {
         self.client_data_route = data_routes.client_data_route;
     }
 
-    pub(super) fn update_query_routes(&mut self, query_routes: QueryRoutes) {
+    fn update_query_routes(&mut self, query_routes: QueryRoutes) {
         self.valid_query_routes = true;
         self.routers_query_routes = query_routes.routers_query_routes;
         self.peers_query_routes = query_routes.peers_query_routes;
@@ -186,6 +206,58 @@ impl Resource {
         }
     }
 
+    /// Returns the global ids of the subscribers declared on this resource by the faces
+    /// whose declarations carried an id.
+    pub(crate) fn subscriber_ids(&self) -> Vec<EntityGlobalId> {
+        self.session_ctxs
+            .values()
+            .filter(|ctx| ctx.subs.is_some())
+            .filter_map(|ctx| {
+                ctx.sub_id.map(|eid| EntityGlobalId {
+                    zid: ctx.face.zid,
+                    eid,
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the global ids of the queryables declared on this resource by the faces
+    /// whose declarations carried an id.
+    pub(crate) fn queryable_ids(&self) -> Vec<EntityGlobalId> {
+        self.session_ctxs
+            .values()
+            .filter(|ctx| ctx.qabl.is_some())
+            .filter_map(|ctx| {
+                ctx.qabl_id.map(|eid| EntityGlobalId {
+                    zid: ctx.face.zid,
+                    eid,
+                })
+            })
+            .collect()
+    }
+
+    // The ids of the entities are only meaningful along with the zid of the face that declared
+    // them: only the declarations of the local sessions are propagated with their id.
+    pub(super) fn propagated_sub_id(&self, tables: &Tables, face: &FaceState) -> EntityId {
+        if face.zid != tables.zid {
+            return 0;
+        }
+        self.session_ctxs
+            .get(&face.id)
+            .and_then(|ctx| ctx.sub_id)
+            .unwrap_or(0)
+    }
+
+    pub(super) fn propagated_qabl_id(&self, tables: &Tables, face: &FaceState) -> EntityId {
+        if face.zid != tables.zid {
+            return 0;
+        }
+        self.session_ctxs
+            .get(&face.id)
+            .and_then(|ctx| ctx.qabl_id)
+            .unwrap_or(0)
+    }
+
     #[inline(always)]
     pub(super) fn context(&self) -> &ResourceContext {
         self.context.as_ref().unwrap()
@@ -354,6 +426,22 @@ impl Resource {
                             }
                         }
                     }
+                    // Unindexes the resource from the faces, for them not to keep a dead entry
+                    let mut expr = None;
+                    for mut face in context
+                        .routed_faces
+                        .drain()
+                        .filter_map(|(_, f)| f.upgrade())
+                    {
+                        let expr = expr.get_or_insert_with(|| res.expr());
+                        let routed = &mut get_mut_unchecked(&mut face).routed_resources;
+                        if routed
+                            .get(expr.as_str())
+                            .map_or(false, |r| r.as_ptr() == Arc::as_ptr(res))
+                        {
+                            routed.remove(expr.as_str());
+                        }
+                    }
                 }
                 {
                     get_mut_unchecked(parent).childs.remove(&res.suffix);
@@ -363,13 +451,65 @@ impl Resource {
         }
     }
 
-    pub fn print_tree(from: &Arc<Resource>) -> String {
-        let mut result = from.expr();
-        result.push('\n');
-        for child in from.childs.values() {
-            result.push_str(&Resource::print_tree(child));
+    /// Replaces the cached data routes of `res` and indexes it in the faces they go through.
+    pub(super) fn update_data_routes(res: &mut Arc<Resource>, data_routes: DataRoutes) {
+        get_mut_unchecked(res)
+            .context_mut()
+            .update_data_routes(data_routes);
+        Resource::index_routes(res);
+    }
+
+    /// Replaces the cached query routes of `res` and indexes it in the faces they go through.
+    pub(super) fn update_query_routes(res: &mut Arc<Resource>, query_routes: QueryRoutes) {
+        get_mut_unchecked(res)
+            .context_mut()
+            .update_query_routes(query_routes);
+        Resource::index_routes(res);
+    }
+
+    /// Indexes `res` in the faces its cached routes go through, so that only the routes going
+    /// through a face are repaired when it closes.
+    ///
+    /// The index isn't cleaned when a route stops going through a face: it may list a few
+    /// resources whose routes don't need a repair, which are then recomputed for nothing. The
+    /// resource is unindexed from all the faces having indexed it once cleaned.
+    pub(super) fn index_routes(res: &Arc<Resource>) {
+        if let Some(ctx) = res.context.as_ref() {
+            let data_faces = ctx
+                .routers_data_routes
+                .iter()
+                .chain(ctx.peers_data_routes.iter())
+                .chain(ctx.peer_data_route.iter())
+                .chain(ctx.client_data_route.iter())
+                .flat_map(|route| route.values().map(|((face, _, _), _, _)| face));
+            let query_faces = ctx
+                .routers_query_routes
+                .iter()
+                .chain(ctx.peers_query_routes.iter())
+                .chain(ctx.peer_query_route.iter())
+                .chain(ctx.client_query_route.iter())
+                .flat_map(|route| route.iter().map(|qabl| &qabl.direction.0));
+
+            let mut indexed = HashMap::new();
+            let mut expr = None;
+            // The routes of all the resources go through the multicast groups
+            for face in data_faces
+                .chain(query_faces)
+                .filter(|face| face.mcast_group.is_none())
+            {
+                if let Entry::Vacant(entry) = indexed.entry(face.id) {
+                    entry.insert(Arc::downgrade(face));
+                    let expr = expr.get_or_insert_with(|| res.expr()).clone();
+                    get_mut_unchecked(&mut face.clone())
+                        .routed_resources
+                        .insert(expr, Arc::downgrade(res));
+                }
+            }
+            get_mut_unchecked(&mut res.clone())
+                .context_mut()
+                .routed_faces
+                .extend(indexed);
         }
-        result
     }
 
     pub fn make_resource(
@@ -492,7 +632,10 @@ impl Resource {
                             local_expr_id: None,
                             remote_expr_id: None,
                             subs: None,
+                            sub_id: None,
+                            sub_filter: None,
                             qabl: None,
+                            qabl_id: None,
                             last_values: HashMap::new(),
                         })
                     });
@@ -504,6 +647,7 @@ impl Resource {
                         mapping: Mapping::Receiver,
                     }
                 } else if let Some(expr_id) = ctx.local_expr_id {
+                    face.local_mappings_lru.touch(expr_id);
                     WireExpr {
                         scope: expr_id,
                         suffix: wildsuffix.into(),
@@ -515,6 +659,7 @@ impl Resource {
                     get_mut_unchecked(face)
                         .local_mappings
                         .insert(expr_id, nonwild_prefix.clone());
+                    get_mut_unchecked(face).local_mappings_lru.insert(expr_id);
                     face.primitives.send_declare(Declare {
                         ext_qos: ext::QoSType::declare_default(),
                         ext_tstamp: None,
@@ -557,6 +702,7 @@ impl Resource {
                         mapping: Mapping::Receiver,
                     };
                 } else if let Some(expr_id) = ctx.local_expr_id {
+                    ctx.face.local_mappings_lru.touch(expr_id);
                     return WireExpr {
                         scope: expr_id,
                         suffix: suffix.into(),
@@ -574,6 +720,19 @@ impl Resource {
         get_best_key_(prefix, suffix, sid, true)
     }
 
+    /// The given resource and all its descendants.
+    pub(crate) fn get_resources(res: &Arc<Resource>) -> Vec<Arc<Resource>> {
+        fn collect(res: &Arc<Resource>, resources: &mut Vec<Arc<Resource>>) {
+            resources.push(res.clone());
+            for child in res.childs.values() {
+                collect(child, resources);
+            }
+        }
+        let mut resources = vec![];
+        collect(res, &mut resources);
+        resources
+    }
+
     pub fn get_matches(tables: &Tables, key_expr: &keyexpr) -> Vec<Weak<Resource>> {
         fn recursive_push(from: &Arc<Resource>, matches: &mut Vec<Weak<Resource>>) {
             if from.context.is_some() {
@@ -733,7 +892,10 @@ pub fn register_expr(
                             local_expr_id: None,
                             remote_expr_id: Some(expr_id),
                             subs: None,
+                            sub_id: None,
+                            sub_filter: None,
                             qabl: None,
+                            qabl_id: None,
                             last_values: HashMap::new(),
                         })
                     });
@@ -741,6 +903,7 @@ pub fn register_expr(
                 get_mut_unchecked(face)
                     .remote_mappings
                     .insert(expr_id, res.clone());
+                mappings::check_remote_mappings(&wtables, face);
                 wtables.compute_matches_routes(&mut res);
                 drop(wtables);
             }
@@ -750,10 +913,244 @@ pub fn register_expr(
 }
 
 pub fn unregister_expr(tables: &TablesLock, face: &mut Arc<FaceState>, expr_id: ExprId) {
-    let wtables = zwrite!(tables.tables);
+    if let Some(audit) = face.audit.as_ref() {
+        audit.forget_mapping(expr_id);
+    }
+    let mut wtables = zwrite!(tables.tables);
     match get_mut_unchecked(face).remote_mappings.remove(&expr_id) {
-        Some(mut res) => Resource::clean(&mut res),
+        Some(mut res) => {
+            // The routes to the face no longer use the undeclared mapping
+            if let Some(ctx) = get_mut_unchecked(&mut res).session_ctxs.get_mut(&face.id) {
+                if ctx.remote_expr_id == Some(expr_id) {
+                    get_mut_unchecked(ctx).remote_expr_id = None;
+                    mappings::recompute_routes(&mut wtables, &res);
+                }
+            }
+            mappings::check_remote_mappings(&wtables, face);
+            Resource::clean(&mut res)
+        }
         None => log::error!("Undeclare unknown resource!"),
     }
     drop(wtables);
 }
+
+/// Registers at once the key expression mappings declared in bulk by `face`.
+///
+/// Returns the ids of the mappings which were rejected, e.g. because they collide with a
+/// mapping of another key expression or have an unknown scope.
+pub fn register_exprs(
+    tables: &TablesLock,
+    face: &mut Arc<FaceState>,
+    mappings: &[DeclareKeyExpr],
+) -> Vec<ExprId> {
+    let mut wtables = zwrite!(tables.tables);
+    let mut rejected = vec![];
+    for mapping in mappings {
+        if mapping.id == EMPTY_EXPR_ID {
+            log::debug!("Reject mapping of {} with reserved id", face);
+            rejected.push(mapping.id);
+            continue;
+        }
+        // The scopes of the mappings declared in bulk are always the ones of the sender
+        let expr = &mapping.wire_expr;
+        let mut prefix = match wtables
+            .get_mapping(face, &expr.scope, Mapping::Sender)
+            .cloned()
+        {
+            Some(prefix) => prefix,
+            None => {
+                log::debug!(
+                    "Reject mapping {} of {} with unknown scope {}",
+                    mapping.id,
+                    face,
+                    expr.scope
+                );
+                rejected.push(mapping.id);
+                continue;
+            }
+        };
+        let mut fullexpr = prefix.expr();
+        fullexpr.push_str(expr.suffix.as_ref());
+        if let Some(res) = face.remote_mappings.get(&mapping.id) {
+            if res.expr() != fullexpr {
+                log::debug!(
+                    "Reject mapping {} of {} to {}: already mapped to {}",
+                    mapping.id,
+                    face,
+                    fullexpr,
+                    res.expr()
+                );
+                rejected.push(mapping.id);
+            }
+            continue;
+        }
+        let key_expr = match keyexpr::new(fullexpr.as_str()) {
+            Ok(key_expr) => key_expr,
+            Err(e) => {
+                log::debug!("Reject mapping {} of {}: {}", mapping.id, face, e);
+                rejected.push(mapping.id);
+                continue;
+            }
+        };
+
+        let mut res = match Resource::get_resource(&prefix, &expr.suffix) {
+            Some(res) if res.context.is_some() => res,
+            _ => {
+                let mut matches = Resource::get_matches(&wtables, key_expr);
+                let mut res =
+                    Resource::make_resource(&mut wtables, &mut prefix, expr.suffix.as_ref());
+                matches.push(Arc::downgrade(&res));
+                Resource::match_resource(&wtables, &mut res, matches);
+                res
+            }
+        };
+        get_mut_unchecked(&mut res)
+            .session_ctxs
+            .entry(face.id)
+            .or_insert_with(|| {
+                Arc::new(SessionContext {
+                    face: face.clone(),
+                    local_expr_id: None,
+                    remote_expr_id: Some(mapping.id),
+                    subs: None,
+                    sub_id: None,
+                    sub_filter: None,
+                    qabl: None,
+                    qabl_id: None,
+                    last_values: HashMap::new(),
+                })
+            });
+        get_mut_unchecked(face)
+            .remote_mappings
+            .insert(mapping.id, res.clone());
+        wtables.compute_matches_routes(&mut res);
+    }
+    mappings::check_remote_mappings(&wtables, face);
+    rejected
+}
+
+// The bytes of a batch taken by the headers of the frame and of a resync declaration
+const RESYNC_HEADERS: usize = 64;
+// The bytes of a resync declaration taken by a mapping, besides its key expression
+const RESYNC_MAPPING_HEADER: usize = 8;
+
+/// Keeps the key expression mappings declared to a closing face, to re-declare them in bulk
+/// when the same node reconnects.
+///
+/// Only the mappings declared to routers are kept, the nodes a session reconnects to.
+pub(super) fn persist_exprs(tables: &mut Tables, face: &FaceState) {
+    if face.resync.is_some() && face.whatami == WhatAmI::Router && !face.local_mappings.is_empty() {
+        // The evicted mappings are no longer used
+        let lru = &face.local_mappings_lru;
+        let mappings = face
+            .local_mappings
+            .iter()
+            .filter(|(expr_id, _)| !lru.is_evicted(**expr_id))
+            .map(|(expr_id, res)| (*expr_id, res.expr()))
+            .collect();
+        tables.resync_mappings.insert(face.zid, mappings);
+    }
+}
+
+/// Re-declares at once to a new face the key expression mappings declared to the previous
+/// face of the same node, keeping their ids, instead of one by one as the resources get used.
+pub(super) fn resync_exprs(tables: &mut Tables, face: &mut Arc<FaceState>) {
+    let (mappings, batch_size) = match (tables.resync_mappings.remove(&face.zid), face.resync) {
+        (Some(mappings), Some(batch_size)) => (mappings, batch_size as usize),
+        _ => return,
+    };
+    let mut declared = vec![];
+    let mut size = 0;
+    let mut count = 0;
+    for (expr_id, expr) in mappings {
+        // The resources released since the disconnection don't need a mapping anymore
+        let mut res = match Resource::get_resource(&tables.root_res, &expr) {
+            Some(res) => res,
+            None => continue,
+        };
+        let ctx = get_mut_unchecked(&mut res)
+            .session_ctxs
+            .entry(face.id)
+            .or_insert_with(|| {
+                Arc::new(SessionContext {
+                    face: face.clone(),
+                    local_expr_id: None,
+                    remote_expr_id: None,
+                    subs: None,
+                    sub_id: None,
+                    sub_filter: None,
+                    qabl: None,
+                    qabl_id: None,
+                    last_values: HashMap::new(),
+                })
+            });
+        get_mut_unchecked(ctx).local_expr_id = Some(expr_id);
+        get_mut_unchecked(face)
+            .local_mappings
+            .insert(expr_id, res.clone());
+        get_mut_unchecked(face).local_mappings_lru.insert(expr_id);
+        // Each declaration fits a batch
+        let mapping_size = expr.len() + RESYNC_MAPPING_HEADER;
+        if !declared.is_empty() && size + mapping_size > batch_size.saturating_sub(RESYNC_HEADERS) {
+            send_resync(face, std::mem::take(&mut declared));
+            size = 0;
+        }
+        size += mapping_size;
+        count += 1;
+        declared.push(DeclareKeyExpr {
+            id: expr_id,
+            wire_expr: expr.into(),
+        });
+    }
+    if !declared.is_empty() {
+        send_resync(face, declared);
+    }
+    if count > 0 {
+        log::debug!("Resync {} key expression mappings to {}", count, face);
+    }
+}
+
+fn send_resync(face: &FaceState, mappings: Vec<DeclareKeyExpr>) {
+    face.primitives.send_declare(Declare {
+        ext_qos: ext::QoSType::declare_default(),
+        ext_tstamp: None,
+        ext_nodeid: ext::NodeIdType::default(),
+        body: DeclareBody::DeclareKeyExprs(DeclareKeyExprs {
+            mappings,
+            compressed: false,
+        }),
+    });
+}
+
+/// Re-declares one by one, with new ids, the key expression mappings `face` rejected.
+///
+/// Returns the resources of the re-declared mappings.
+pub fn remap_exprs(
+    tables: &mut Tables,
+    face: &mut Arc<FaceState>,
+    expr_ids: &[ExprId],
+) -> Vec<Arc<Resource>> {
+    let mut remapped = vec![];
+    for expr_id in expr_ids {
+        let mut res = match face.local_mappings.get(expr_id) {
+            Some(res) => res.clone(),
+            None => {
+                log::debug!("Rejected unknown mapping {} by {}", expr_id, face);
+                continue;
+            }
+        };
+        if let Some(ctx) = get_mut_unchecked(&mut res).session_ctxs.get_mut(&face.id) {
+            get_mut_unchecked(ctx).local_expr_id = None;
+        }
+        Resource::decl_key(&res, face);
+        tables.compute_matches_routes(&mut res);
+        remapped.push(res);
+    }
+    // The rejected ids remain reserved until all the new ones are allocated, so that none of
+    // them is reused for another mapping
+    for expr_id in expr_ids {
+        get_mut_unchecked(face).local_mappings.remove(expr_id);
+        get_mut_unchecked(face).local_mappings_lru.remove(*expr_id);
+    }
+    remapped
+}
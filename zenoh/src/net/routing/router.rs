@@ -11,12 +11,23 @@
 // Contributors:
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
+use super::audit::{Auditor, AuthIdentity, FaceAudit};
+use super::deadletter::DeadLetters;
+use super::dedup::Deduplication;
 use super::face::{Face, FaceState};
+use super::mappings::MappingLimits;
+use super::mutation::Mutations;
+use super::namespace::Namespace;
 use super::network::{shared_nodes, Network};
 pub use super::pubsub::*;
 pub use super::queries::*;
+use super::querylimit::QueryLimits;
+use super::querythrottle::QueryThrottle;
+use super::ratelimit::{Admission, DeclarationLimiter, DeclarationRate};
+use super::refresh::{self, DeclarationRefresh};
 pub use super::resource::*;
 use super::runtime::Runtime;
+use super::trace::QueryTracer;
 use crate::net::codec::Zenoh080Routing;
 use crate::net::protocol::linkstate::LinkStateList;
 use async_std::task::JoinHandle;
@@ -25,6 +36,7 @@ use std::collections::hash_map::DefaultHasher;
 use std::collections::{HashMap, HashSet};
 use std::hash::Hasher;
 use std::str::FromStr;
+use std::sync::atomic::Ordering;
 use std::sync::{Arc, Weak};
 use std::sync::{Mutex, RwLock};
 use std::time::Duration;
@@ -32,8 +44,11 @@ use uhlc::HLC;
 use zenoh_link::Link;
 use zenoh_protocol::common::ZExtBody;
 use zenoh_protocol::core::{ExprId, WhatAmI, WhatAmIMatcher, ZenohId};
-use zenoh_protocol::network::oam::id::OAM_LINKSTATE;
-use zenoh_protocol::network::{Mapping, NetworkBody, NetworkMessage};
+use zenoh_protocol::network::oam::id::{
+    OAM_DECLARATION_DIGEST, OAM_DECLARATION_REQUEST, OAM_DECLARATION_SET, OAM_LINKSTATE,
+};
+use zenoh_protocol::network::{Declare, DeclareBody, Mapping, NetworkBody, NetworkMessage};
+use zenoh_protocol::transport::{close, BatchSize};
 #[cfg(feature = "stats")]
 use zenoh_transport::stats::TransportStats;
 use zenoh_transport::{
@@ -44,11 +59,20 @@ use zenoh_transport::{
 use zenoh_core::zconfigurable;
 use zenoh_result::ZResult;
 use zenoh_sync::get_mut_unchecked;
+use zenoh_util::cooperate::Cooperate;
 
 zconfigurable! {
     static ref TREES_COMPUTATION_DELAY: u64 = 100;
 }
 
+// The number of routes computed in a row after a change of the trees, before releasing the
+// tables and yielding
+const ROUTES_COOPERATE_BUDGET: usize = 1_000;
+
+// The number of resources whose routes are checked after an incremental repair in debug builds
+#[cfg(debug_assertions)]
+const ROUTES_CHECK_SAMPLE: usize = 16;
+
 pub(crate) struct RoutingExpr<'a> {
     pub(crate) prefix: &'a Arc<Resource>,
     pub(crate) suffix: &'a str,
@@ -82,6 +106,8 @@ pub struct Tables {
     pub(crate) hlc: Option<Arc<HLC>>,
     pub(crate) drop_future_timestamp: bool,
     pub(crate) router_peers_failover_brokering: bool,
+    // Whether all the routes are recomputed on every change instead of only the affected ones
+    pub(crate) full_routes_recomputation: bool,
     // pub(crate) timer: Timer,
     // pub(crate) queries_default_timeout: Duration,
     pub(crate) root_res: Arc<Resource>,
@@ -98,6 +124,28 @@ pub struct Tables {
     pub(crate) shared_nodes: Vec<ZenohId>,
     pub(crate) routers_trees_task: Option<JoinHandle<()>>,
     pub(crate) peers_trees_task: Option<JoinHandle<()>>,
+    // The number of route recomputations in progress after the computation of the trees
+    pub(crate) routes_tasks: usize,
+    pub(crate) deduplication: Option<Deduplication>,
+    // The mutations of the samples sent to some faces
+    pub(crate) mutations: Option<Arc<Mutations>>,
+    pub(crate) declaration_rate: Option<DeclarationRate>,
+    // The soft-state refresh of the declarations sent over the multicast groups
+    pub(crate) declaration_refresh: Option<DeclarationRefresh>,
+    // The namespaces of the faces, by authenticated user
+    pub(crate) namespaces: HashMap<String, Namespace>,
+    pub(crate) query_tracer: Option<Arc<QueryTracer>>,
+    // The limits on the pending queries
+    pub(crate) query_limits: Option<Arc<QueryLimits>>,
+    // The rate limits on the queries of the clients
+    pub(crate) query_throttle: Option<QueryThrottle>,
+    pub(crate) dead_letters: Option<Arc<DeadLetters>>,
+    // The audit of the operations of the faces of the transports, if enabled
+    pub(crate) auditor: Option<Arc<Auditor>>,
+    // The key expression mappings declared to the routers this node got disconnected from, by zid
+    pub(crate) resync_mappings: HashMap<ZenohId, Vec<(ExprId, String)>>,
+    // The limits on the key expression mappings of the faces
+    pub(crate) mapping_limits: Option<MappingLimits>,
 }
 
 impl Tables {
@@ -116,6 +164,7 @@ impl Tables {
             hlc,
             drop_future_timestamp,
             router_peers_failover_brokering,
+            full_routes_recomputation: false,
             // timer: Timer::new(true),
             // queries_default_timeout,
             root_res: Resource::root(),
@@ -132,6 +181,19 @@ impl Tables {
             shared_nodes: vec![],
             routers_trees_task: None,
             peers_trees_task: None,
+            routes_tasks: 0,
+            deduplication: None,
+            mutations: None,
+            declaration_rate: None,
+            declaration_refresh: None,
+            namespaces: HashMap::new(),
+            query_tracer: None,
+            query_limits: None,
+            query_throttle: None,
+            dead_letters: None,
+            auditor: None,
+            resync_mappings: HashMap::new(),
+            mapping_limits: None,
         }
     }
 
@@ -140,10 +202,6 @@ impl Tables {
         &self.root_res
     }
 
-    pub fn print(&self) -> String {
-        Resource::print_tree(&self.root_res)
-    }
-
     #[inline]
     #[allow(clippy::trivially_copy_pass_by_ref)]
     pub(crate) fn get_mapping<'a>(
@@ -254,16 +312,59 @@ impl Tables {
                 .unwrap_or(false)
     }
 
-    fn open_net_face(
+    /// The data sent with a non default priority to the faces of `zid` whose transport has no QoS.
+    #[inline]
+    pub(crate) fn priority_downgrades(&self, zid: &ZenohId) -> usize {
+        self.faces
+            .values()
+            .filter(|face| face.zid == *zid)
+            .map(|face| face.priority_downgrades.load(Ordering::Relaxed))
+            .sum()
+    }
+
+    /// The data not sent to the faces of `zid` because a subscription filter couldn't read it.
+    #[inline]
+    pub(crate) fn malformed_payloads(&self, zid: &ZenohId) -> usize {
+        self.faces
+            .values()
+            .filter(|face| face.zid == *zid)
+            .map(|face| face.malformed_payloads.load(Ordering::Relaxed))
+            .sum()
+    }
+
+    /// The samples sent to the faces of `zid` with a payload mutated by the mutation rules.
+    #[inline]
+    pub(crate) fn mutated_samples(&self, zid: &ZenohId) -> usize {
+        self.faces
+            .values()
+            .filter(|face| face.zid == *zid)
+            .map(|face| face.mutated_samples.load(Ordering::Relaxed))
+            .sum()
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn open_net_face(
         &mut self,
         zid: ZenohId,
         whatami: WhatAmI,
         #[cfg(feature = "stats")] stats: Arc<TransportStats>,
         primitives: Arc<dyn Primitives + Send + Sync>,
         link_id: usize,
+        is_qos: bool,
+        namespace: Option<Namespace>,
+        resync: Option<BatchSize>,
+        auth_user: Option<String>,
     ) -> Weak<FaceState> {
         let fid = self.face_counter;
         self.face_counter += 1;
+        let audit = self.auditor.as_ref().map(|auditor| {
+            let identity = AuthIdentity {
+                zid,
+                whatami,
+                user: auth_user,
+            };
+            FaceAudit::new(auditor.clone(), identity)
+        });
         let mut newface = self
             .faces
             .entry(fid)
@@ -277,11 +378,17 @@ impl Tables {
                     primitives.clone(),
                     link_id,
                     None,
+                    is_qos,
+                    namespace,
+                    resync,
+                    audit,
                 )
             })
             .clone();
         log::debug!("New {}", newface);
 
+        // The mappings are resynchronized before the declarations using them
+        resync_exprs(self, &mut newface);
         pubsub_new_face(self, &mut newface);
         queries_new_face(self, &mut newface);
 
@@ -309,6 +416,10 @@ impl Tables {
                     primitives.clone(),
                     0,
                     None,
+                    true,
+                    None,
+                    None,
+                    None,
                 )
             })
             .clone();
@@ -320,7 +431,33 @@ impl Tables {
         Arc::downgrade(&newface)
     }
 
-    fn compute_routes(&mut self, res: &mut Arc<Resource>) {
+    pub(crate) fn open_mcast_group(
+        &mut self,
+        primitives: Arc<dyn Primitives + Send + Sync>,
+        mcast_group: Option<TransportMulticast>,
+        is_qos: bool,
+    ) -> Arc<FaceState> {
+        let fid = self.face_counter;
+        self.face_counter += 1;
+        let group = FaceState::new(
+            fid,
+            ZenohId::from_str("1").unwrap(),
+            WhatAmI::Peer,
+            #[cfg(feature = "stats")]
+            None,
+            primitives,
+            0,
+            mcast_group,
+            is_qos,
+            None,
+            None,
+            None,
+        );
+        self.mcast_groups.push(group.clone());
+        group
+    }
+
+    pub(crate) fn compute_routes(&mut self, res: &mut Arc<Resource>) {
         compute_data_routes(self, res);
         compute_query_routes(self, res);
     }
@@ -339,6 +476,39 @@ impl Tables {
         }
     }
 
+    // Whether a change of the trees requires recomputing all the routes: either it is configured
+    // so, or the routers elect the one forwarding each key among the nodes of both networks.
+    pub(crate) fn recompute_all_routes_on_tree_change(&self) -> bool {
+        self.full_routes_recomputation
+            || (self.whatami == WhatAmI::Router && self.full_net(WhatAmI::Peer))
+    }
+
+    /// Asserts that the cached routes of a random sample of the resources are the ones a full
+    /// recomputation gives, after an incremental repair of the routes.
+    ///
+    /// Nothing is checked while the trees of a network are to be computed: the routes going
+    /// through the network are only repaired once they are.
+    #[cfg(debug_assertions)]
+    pub(crate) fn check_routes_sample(&self) {
+        use rand::seq::IteratorRandom;
+
+        if self.routers_trees_task.is_some()
+            || self.peers_trees_task.is_some()
+            || self.routes_tasks > 0
+        {
+            return;
+        }
+
+        for res in Resource::get_resources(&self.root_res)
+            .into_iter()
+            .filter(|res| res.context.is_some())
+            .choose_multiple(&mut rand::thread_rng(), ROUTES_CHECK_SAMPLE)
+        {
+            check_data_routes(self, &res);
+            check_query_routes(self, &res);
+        }
+    }
+
     pub(crate) fn schedule_compute_trees(
         &mut self,
         tables_ref: Arc<TablesLock>,
@@ -351,23 +521,69 @@ impl Tables {
             let task = Some(async_std::task::spawn(async move {
                 async_std::task::sleep(std::time::Duration::from_millis(*TREES_COMPUTATION_DELAY))
                     .await;
-                let mut tables = zwrite!(tables_ref.tables);
-
-                log::trace!("Compute trees");
-                let new_childs = match net_type {
-                    WhatAmI::Router => tables.routers_net.as_mut().unwrap().compute_trees(),
-                    _ => tables.peers_net.as_mut().unwrap().compute_trees(),
+                let mut cooperate = Cooperate::new(ROUTES_COOPERATE_BUDGET);
+                let mut pending = {
+                    let mut tables = zwrite!(tables_ref.tables);
+
+                    log::trace!("Compute trees");
+                    let new_childs = match net_type {
+                        WhatAmI::Router => tables.routers_net.as_mut().unwrap().compute_trees(),
+                        _ => tables.peers_net.as_mut().unwrap().compute_trees(),
+                    };
+
+                    log::trace!("Compute routes");
+                    let mut pending = pubsub_tree_change(&mut tables, &new_childs, net_type)
+                        .into_iter()
+                        .map(|res| (res, true))
+                        .chain(
+                            queries_tree_change(&mut tables, &new_childs, net_type)
+                                .into_iter()
+                                .map(|res| (res, false)),
+                        )
+                        .collect::<Vec<_>>();
+                    match net_type {
+                        WhatAmI::Router => tables.routers_trees_task = None,
+                        _ => tables.peers_trees_task = None,
+                    };
+                    // The cached routes predate the new trees: they are invalidated so that the
+                    // messages routed while the tables are released compute theirs on the fly
+                    for (res, is_data) in pending.iter_mut() {
+                        if res.context.is_some() {
+                            let ctx = get_mut_unchecked(res).context_mut();
+                            if *is_data {
+                                ctx.valid_data_routes = false;
+                            } else {
+                                ctx.valid_query_routes = false;
+                            }
+                        }
+                    }
+                    if compute_pending_routes(&mut tables, &mut pending, &mut cooperate) {
+                        log::trace!("Computations completed");
+                        #[cfg(debug_assertions)]
+                        if !tables.recompute_all_routes_on_tree_change() {
+                            tables.check_routes_sample();
+                        }
+                        return;
+                    }
+                    tables.routes_tasks += 1;
+                    pending
                 };
 
-                log::trace!("Compute routes");
-                pubsub_tree_change(&mut tables, &new_childs, net_type);
-                queries_tree_change(&mut tables, &new_childs, net_type);
-
-                log::trace!("Computations completed");
-                match net_type {
-                    WhatAmI::Router => tables.routers_trees_task = None,
-                    _ => tables.peers_trees_task = None,
-                };
+                // Too many routes to compute them at once: the tables are released in between
+                // the chunks of routes, the routes not computed yet staying invalid
+                loop {
+                    async_std::task::yield_now().await;
+                    let mut tables = zwrite!(tables_ref.tables);
+                    if compute_pending_routes(&mut tables, &mut pending, &mut cooperate) {
+                        tables.routes_tasks -= 1;
+                        log::trace!("Computations completed");
+                        #[cfg(debug_assertions)]
+                        if !tables.recompute_all_routes_on_tree_change() {
+                            tables.check_routes_sample();
+                        }
+                        break;
+                    }
+                }
             }));
             match net_type {
                 WhatAmI::Router => self.routers_trees_task = task,
@@ -377,6 +593,34 @@ impl Tables {
     }
 }
 
+// Computes the data (true) or query (false) routes of the pending resources until out of budget,
+// validating them, and returns whether all of them are computed
+fn compute_pending_routes(
+    tables: &mut Tables,
+    pending: &mut Vec<(Arc<Resource>, bool)>,
+    cooperate: &mut Cooperate,
+) -> bool {
+    while let Some((mut res, is_data)) = pending.pop() {
+        if is_data {
+            compute_data_routes(tables, &mut res);
+        } else {
+            compute_query_routes(tables, &mut res);
+        }
+        if res.context.is_some() {
+            let ctx = get_mut_unchecked(&mut res).context_mut();
+            if is_data {
+                ctx.valid_data_routes = true;
+            } else {
+                ctx.valid_query_routes = true;
+            }
+        }
+        if cooperate.tick() {
+            break;
+        }
+    }
+    pending.is_empty()
+}
+
 pub fn close_face(tables: &TablesLock, face: &Weak<FaceState>) {
     match face.upgrade() {
         Some(mut face) => {
@@ -392,6 +636,7 @@ pub fn close_face(tables: &TablesLock, face: &Weak<FaceState>) {
                 Resource::clean(res);
             }
             face.remote_mappings.clear();
+            persist_exprs(&mut wtables, face);
             for res in face.local_mappings.values_mut() {
                 get_mut_unchecked(res).session_ctxs.remove(&face.id);
                 Resource::clean(res);
@@ -437,6 +682,29 @@ pub fn close_face(tables: &TablesLock, face: &Weak<FaceState>) {
                     qabls_matches.push(res);
                 }
             }
+
+            // The routes of the other resources don't go through the face, they don't change
+            wtables.faces.remove(&face.id);
+            for mut res in face
+                .routed_resources
+                .drain()
+                .filter_map(|(_, res)| res.upgrade())
+            {
+                if res.context.is_some() {
+                    let ctx = get_mut_unchecked(&mut res).context_mut();
+                    ctx.routed_faces.remove(&face.id);
+                    ctx.valid_data_routes = false;
+                    ctx.valid_query_routes = false;
+                    subs_matches.push(res.clone());
+                    qabls_matches.push(res);
+                }
+            }
+            let dedup = |resources: &mut Vec<Arc<Resource>>| {
+                let mut seen = HashSet::new();
+                resources.retain(|res| seen.insert(Arc::as_ptr(res)));
+            };
+            dedup(&mut subs_matches);
+            dedup(&mut qabls_matches);
             drop(wtables);
 
             let mut matches_data_routes = vec![];
@@ -453,18 +721,21 @@ pub fn close_face(tables: &TablesLock, face: &Weak<FaceState>) {
 
             let mut wtables = zwrite!(tables.tables);
             for (mut res, data_routes) in matches_data_routes {
-                get_mut_unchecked(&mut res)
-                    .context_mut()
-                    .update_data_routes(data_routes);
+                Resource::update_data_routes(&mut res, data_routes);
                 Resource::clean(&mut res);
             }
             for (mut res, query_routes) in matches_query_routes {
-                get_mut_unchecked(&mut res)
-                    .context_mut()
-                    .update_query_routes(query_routes);
+                Resource::update_query_routes(&mut res, query_routes);
                 Resource::clean(&mut res);
             }
-            wtables.faces.remove(&face.id);
+            if wtables.full_routes_recomputation {
+                let mut root_res = wtables.root_res.clone();
+                compute_data_routes_from(&mut wtables, &mut root_res);
+                compute_query_routes_from(&mut wtables, &mut root_res);
+            } else {
+                #[cfg(debug_assertions)]
+                wtables.check_routes_sample();
+            }
             drop(wtables);
             drop(ctrl_lock);
         }
@@ -580,11 +851,12 @@ impl Router {
         let whatami = transport.get_whatami()?;
 
         let link_id = match (self.whatami, whatami) {
-            (WhatAmI::Router, WhatAmI::Router) => tables
-                .routers_net
-                .as_mut()
-                .unwrap()
-                .add_link(transport.clone()),
+            (WhatAmI::Router, WhatAmI::Router) => {
+                let net = tables.routers_net.as_mut().unwrap();
+                let link_id = net.add_link(transport.clone());
+                net.notify_topology();
+                link_id
+            }
             (WhatAmI::Router, WhatAmI::Peer)
             | (WhatAmI::Peer, WhatAmI::Router)
             | (WhatAmI::Peer, WhatAmI::Peer) => {
@@ -604,9 +876,26 @@ impl Router {
             );
         }
 
+        // The declarations of the routers are not limited, they may legitimately
+        // forward the declarations of whole subsystems
+        let limiter = match whatami {
+            WhatAmI::Router => None,
+            _ => tables
+                .declaration_rate
+                .clone()
+                .map(|rate| Arc::new(DeclarationLimiter::new(rate))),
+        };
+
+        // The faces authenticated as a user of a namespace rule are confined to its namespace
+        let auth_user = transport.get_auth_user()?;
+        let namespace = auth_user
+            .as_ref()
+            .and_then(|user| tables.namespaces.get(user).cloned());
+
         let handler = Arc::new(LinkStateInterceptor::new(
             transport.clone(),
             self.tables.clone(),
+            limiter,
             Face {
                 tables: self.tables.clone(),
                 state: tables
@@ -615,8 +904,14 @@ impl Router {
                         whatami,
                         #[cfg(feature = "stats")]
                         transport.get_stats().unwrap(),
-                        Arc::new(Mux::new(transport)),
+                        Arc::new(Mux::new(transport.clone())),
                         link_id,
+                        transport.is_qos()?,
+                        namespace,
+                        transport
+                            .is_resync()?
+                            .then_some(transport.get_batch_size()?),
+                        auth_user,
                     )
                     .upgrade()
                     .unwrap(),
@@ -643,22 +938,25 @@ impl Router {
 
     pub fn new_transport_multicast(&self, transport: TransportMulticast) -> ZResult<()> {
         let mut tables = zwrite!(self.tables.tables);
-        let fid = tables.face_counter;
-        tables.face_counter += 1;
-        tables.mcast_groups.push(FaceState::new(
-            fid,
-            ZenohId::from_str("1").unwrap(),
-            WhatAmI::Peer,
-            #[cfg(feature = "stats")]
-            None,
+        let group = tables.open_mcast_group(
             Arc::new(McastMux::new(transport.clone())),
-            0,
-            Some(transport),
-        ));
+            Some(transport.clone()),
+            transport.is_qos()?,
+        );
 
         // recompute routes
         let mut root_res = tables.root_res.clone();
         compute_data_routes_from(&mut tables, &mut root_res);
+
+        if let Some(declaration_refresh) = tables.declaration_refresh {
+            let c_tables = self.tables.clone();
+            async_std::task::spawn(async move {
+                refresh::refresh_task(&c_tables, group, declaration_refresh, |msg| {
+                    transport.handle_message(msg)
+                })
+                .await
+            });
+        }
         Ok(())
     }
 
@@ -666,7 +964,7 @@ impl Router {
         &self,
         transport: TransportMulticast,
         peer: TransportPeer,
-    ) -> ZResult<Arc<DeMux<Face>>> {
+    ) -> ZResult<Arc<MulticastPeerInterceptor>> {
         let mut tables = zwrite!(self.tables.tables);
         let fid = tables.face_counter;
         tables.face_counter += 1;
@@ -678,17 +976,83 @@ impl Router {
             Some(transport.get_stats().unwrap()),
             Arc::new(DummyPrimitives),
             0,
-            Some(transport),
+            Some(transport.clone()),
+            transport.is_qos()?,
+            None,
+            None,
+            None,
         );
         tables.mcast_faces.push(face_state.clone());
 
-        // recompute routes
-        let mut root_res = tables.root_res.clone();
-        compute_data_routes_from(&mut tables, &mut root_res);
-        Ok(Arc::new(DeMux::new(Face {
-            tables: self.tables.clone(),
-            state: face_state,
-        })))
+        // The routes only go through the multicast groups, not through their faces
+        if tables.full_routes_recomputation {
+            let mut root_res = tables.root_res.clone();
+            compute_data_routes_from(&mut tables, &mut root_res);
+        }
+        Ok(Arc::new(MulticastPeerInterceptor::new(
+            transport,
+            Face {
+                tables: self.tables.clone(),
+                state: face_state,
+            },
+        )))
+    }
+}
+
+/// The handler of the messages received from a peer of a multicast group, answering the
+/// soft-state refresh of the declarations.
+pub struct MulticastPeerInterceptor {
+    pub(crate) transport: TransportMulticast,
+    pub(crate) face: Face,
+    pub(crate) demux: DeMux<Face>,
+}
+
+impl MulticastPeerInterceptor {
+    fn new(transport: TransportMulticast, face: Face) -> Self {
+        MulticastPeerInterceptor {
+            transport,
+            face: face.clone(),
+            demux: DeMux::new(face),
+        }
+    }
+}
+
+impl TransportPeerEventHandler for MulticastPeerInterceptor {
+    fn handle_message(&self, msg: NetworkMessage) -> ZResult<()> {
+        match msg.body {
+            NetworkBody::OAM(oam)
+                if matches!(
+                    oam.id,
+                    OAM_DECLARATION_DIGEST | OAM_DECLARATION_REQUEST | OAM_DECLARATION_SET
+                ) =>
+            {
+                match refresh::handle_oam(&self.face, oam) {
+                    Some(reply) => self.transport.handle_message(reply),
+                    None => Ok(()),
+                }
+            }
+            _ => self.demux.handle_message(msg),
+        }
+    }
+
+    fn new_link(&self, link: Link) {
+        self.demux.new_link(link)
+    }
+
+    fn del_link(&self, link: Link) {
+        self.demux.del_link(link)
+    }
+
+    fn closing(&self) {
+        self.demux.closing()
+    }
+
+    fn closed(&self) {
+        self.demux.closed()
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
     }
 }
 
@@ -697,15 +1061,59 @@ pub struct LinkStateInterceptor {
     pub(crate) tables: Arc<TablesLock>,
     pub(crate) face: Face,
     pub(crate) demux: DeMux<Face>,
+    pub(crate) limiter: Option<Arc<DeclarationLimiter<Declare>>>,
 }
 
 impl LinkStateInterceptor {
-    fn new(transport: TransportUnicast, tables: Arc<TablesLock>, face: Face) -> Self {
+    fn new(
+        transport: TransportUnicast,
+        tables: Arc<TablesLock>,
+        limiter: Option<Arc<DeclarationLimiter<Declare>>>,
+        face: Face,
+    ) -> Self {
         LinkStateInterceptor {
             transport,
             tables,
             face: face.clone(),
             demux: DeMux::new(face),
+            limiter,
+        }
+    }
+
+    fn limit_declare(&self, limiter: &Arc<DeclarationLimiter<Declare>>, declare: Declare) {
+        // The key expressions are never queued: the pushes and queries using them are not
+        let admission = match declare.body {
+            DeclareBody::DeclareKeyExpr(_) => limiter.charge(declare),
+            _ => limiter.submit(declare),
+        };
+        match admission {
+            Admission::Process(declare) => self.face.send_declare(declare),
+            Admission::Queued { drain: true } => {
+                let limiter = limiter.clone();
+                let face = self.face.clone();
+                async_std::task::spawn(async move {
+                    while let Some(next) = limiter.next() {
+                        match next {
+                            Ok(declare) => face.send_declare(declare),
+                            Err(wait) => limiter.clock().sleep(wait).await,
+                        }
+                    }
+                });
+            }
+            Admission::Queued { drain: false } | Admission::Dropped => (),
+            Admission::Overflow => {
+                log::error!(
+                    "Alarm: closing face {} ({}): declarations exceeded the rate limit and its queue",
+                    self.face.state.id,
+                    self.face.state.zid
+                );
+                let transport = self.transport.clone();
+                async_std::task::spawn(async move {
+                    if let Err(e) = transport.close_with_reason(close::reason::ABUSE).await {
+                        log::error!("Error closing transport: {}", e);
+                    }
+                });
+            }
         }
     }
 }
@@ -729,13 +1137,10 @@ impl TransportPeerEventHandler for LinkStateInterceptor {
                             let whatami = self.transport.get_whatami()?;
                             match (tables.whatami, whatami) {
                                 (WhatAmI::Router, WhatAmI::Router) => {
-                                    for (_, removed_node) in tables
-                                        .routers_net
-                                        .as_mut()
-                                        .unwrap()
-                                        .link_states(list.link_states, zid)
-                                        .removed_nodes
-                                    {
+                                    let net = tables.routers_net.as_mut().unwrap();
+                                    let changes = net.link_states(list.link_states, zid);
+                                    net.notify_topology();
+                                    for (_, removed_node) in changes.removed_nodes {
                                         pubsub_remove_node(
                                             &mut tables,
                                             &removed_node.zid,
@@ -816,6 +1221,10 @@ impl TransportPeerEventHandler for LinkStateInterceptor {
 
                 Ok(())
             }
+            NetworkBody::Declare(declare) if self.limiter.is_some() => {
+                self.limit_declare(self.limiter.as_ref().unwrap(), declare);
+                Ok(())
+            }
             _ => self.demux.handle_message(msg),
         }
     }
@@ -825,6 +1234,9 @@ impl TransportPeerEventHandler for LinkStateInterceptor {
     fn del_link(&self, _link: Link) {}
 
     fn closing(&self) {
+        if let Some(limiter) = self.limiter.as_ref() {
+            limiter.close();
+        }
         self.demux.closing();
         let tables_ref = self.tables.clone();
         match (self.transport.get_zid(), self.transport.get_whatami()) {
@@ -833,9 +1245,10 @@ impl TransportPeerEventHandler for LinkStateInterceptor {
                 let mut tables = zwrite!(tables_ref.tables);
                 match (tables.whatami, whatami) {
                     (WhatAmI::Router, WhatAmI::Router) => {
-                        for (_, removed_node) in
-                            tables.routers_net.as_mut().unwrap().remove_link(&zid)
-                        {
+                        let net = tables.routers_net.as_mut().unwrap();
+                        let removed_nodes = net.remove_link(&zid);
+                        net.notify_topology();
+                        for (_, removed_node) in removed_nodes {
                             pubsub_remove_node(&mut tables, &removed_node.zid, WhatAmI::Router);
                             queries_remove_node(&mut tables, &removed_node.zid, WhatAmI::Router);
                         }
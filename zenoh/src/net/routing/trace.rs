@@ -0,0 +1,217 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use super::face::FaceState;
+use super::resource::{QueryRoute, QueryTargetQablSet};
+use super::router::Tables;
+use serde_json::json;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use zenoh_core::zlock;
+use zenoh_protocol::{
+    core::{key_expr::keyexpr, WhatAmI, ZenohId},
+    network::request::ext::CorrelationType,
+};
+
+/// The reason why a face was not targeted by a query.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Exclusion {
+    /// The face the query was received from.
+    Source,
+    /// The face declared no queryable intersecting the query.
+    NoIntersection,
+    /// The face is confined to a namespace that doesn't include the query.
+    Namespace,
+    /// The face declared intersecting queryables, but the query target or the routing
+    /// strategy didn't select them.
+    TargetMismatch,
+}
+
+impl Exclusion {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Exclusion::Source => "source",
+            Exclusion::NoIntersection => "no_intersection",
+            Exclusion::Namespace => "namespace",
+            Exclusion::TargetMismatch => "target_mismatch",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct TracedFace {
+    id: usize,
+    zid: ZenohId,
+    whatami: WhatAmI,
+}
+
+impl From<&FaceState> for TracedFace {
+    fn from(face: &FaceState) -> Self {
+        TracedFace {
+            id: face.id,
+            zid: face.zid,
+            whatami: face.whatami,
+        }
+    }
+}
+
+impl TracedFace {
+    fn to_json(&self) -> serde_json::Value {
+        json!({
+            "face": self.id,
+            "zid": self.zid.to_string(),
+            "whatami": self.whatami.to_str(),
+        })
+    }
+}
+
+/// The routing decision taken for a query, and the replies routed back for it.
+#[derive(Debug)]
+pub(crate) struct QueryTrace {
+    seq: u64,
+    correlation: Option<CorrelationType>,
+    key_expr: String,
+    source: TracedFace,
+    targets: Vec<TracedFace>,
+    excluded: Vec<(TracedFace, Exclusion)>,
+    replies: Vec<(TracedFace, usize)>,
+}
+
+impl QueryTrace {
+    pub(super) fn new(
+        tables: &Tables,
+        correlation: Option<CorrelationType>,
+        key_expr: &str,
+        src_face: &FaceState,
+        qabls: &QueryTargetQablSet,
+        route: &QueryRoute,
+    ) -> Self {
+        let mut targets = vec![];
+        let mut excluded: Vec<(TracedFace, Exclusion)> = vec![];
+        for face in tables.faces.values() {
+            let exclusion = if face.id == src_face.id {
+                Exclusion::Source
+            } else if route.contains_key(&face.id) {
+                targets.push(face.as_ref().into());
+                continue;
+            } else if face.namespace.as_ref().map_or(false, |namespace| {
+                keyexpr::new(key_expr).map_or(true, |ke| !namespace.includes(ke))
+            }) {
+                Exclusion::Namespace
+            } else if qabls.iter().any(|qabl| qabl.direction.0.id == face.id) {
+                Exclusion::TargetMismatch
+            } else {
+                Exclusion::NoIntersection
+            };
+            excluded.push((face.as_ref().into(), exclusion));
+        }
+        targets.sort_by_key(|face: &TracedFace| face.id);
+        excluded.sort_by_key(|(face, _)| face.id);
+        QueryTrace {
+            seq: 0,
+            correlation,
+            key_expr: key_expr.to_string(),
+            source: src_face.into(),
+            targets,
+            excluded,
+            replies: vec![],
+        }
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        json!({
+            "correlation": self.correlation.as_ref().map(|c| json!({
+                "zid": c.zid.to_string(),
+                "eid": c.eid,
+            })),
+            "key_expr": self.key_expr,
+            "source": self.source.to_json(),
+            "targets": self.targets.iter().map(TracedFace::to_json).collect::<Vec<_>>(),
+            "excluded": self.excluded.iter().map(|(face, exclusion)| {
+                let mut json = face.to_json();
+                json["reason"] = exclusion.as_str().into();
+                json
+            }).collect::<Vec<_>>(),
+            "replies": self.replies.iter().map(|(face, count)| {
+                let mut json = face.to_json();
+                json["count"] = (*count).into();
+                json
+            }).collect::<Vec<_>>(),
+        })
+    }
+}
+
+/// Keeps the traces of the last queries routed by a router.
+pub(crate) struct QueryTracer {
+    size: usize,
+    next_seq: AtomicU64,
+    traces: Mutex<VecDeque<QueryTrace>>,
+}
+
+impl QueryTracer {
+    pub(crate) fn new(size: usize) -> Self {
+        QueryTracer {
+            size,
+            next_seq: AtomicU64::new(0),
+            traces: Mutex::new(VecDeque::with_capacity(size)),
+        }
+    }
+
+    /// The sequence number identifying the trace of a new query, to attribute its replies to it.
+    pub(crate) fn next_seq(&self) -> u64 {
+        self.next_seq.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// Records the `trace` of the query identified by `seq`, forgetting the oldest one if needed.
+    pub(crate) fn record(&self, seq: u64, mut trace: QueryTrace) {
+        log::trace!(
+            "Query {:?} on {} from {}: targets {:?}, excluded {:?}",
+            trace.correlation,
+            trace.key_expr,
+            trace.source.zid,
+            trace.targets.iter().map(|f| f.zid).collect::<Vec<_>>(),
+            trace
+                .excluded
+                .iter()
+                .map(|(f, e)| (f.zid, e.as_str()))
+                .collect::<Vec<_>>(),
+        );
+        if self.size == 0 {
+            return;
+        }
+        trace.seq = seq;
+        let mut traces = zlock!(self.traces);
+        if traces.len() >= self.size {
+            traces.pop_front();
+        }
+        traces.push_back(trace);
+    }
+
+    /// Attributes a reply received on `face` to the query traced with `seq`.
+    pub(crate) fn reply(&self, seq: u64, face: &FaceState) {
+        let mut traces = zlock!(self.traces);
+        if let Some(trace) = traces.iter_mut().rev().find(|t| t.seq == seq) {
+            match trace.replies.iter_mut().find(|(f, _)| f.id == face.id) {
+                Some((_, count)) => *count += 1,
+                None => trace.replies.push((face.into(), 1)),
+            }
+        }
+    }
+
+    /// The recorded traces, oldest first.
+    pub(crate) fn to_json(&self) -> serde_json::Value {
+        let traces = zlock!(self.traces);
+        serde_json::Value::Array(traces.iter().map(QueryTrace::to_json).collect())
+    }
+}
@@ -0,0 +1,304 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use super::Runtime;
+use async_std::prelude::FutureExt;
+use std::collections::HashSet;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+use zenoh_link::EndPoint;
+use zenoh_result::ZResult;
+
+/// Provides the addresses of the local network interfaces watched by a [`Runtime`],
+/// see [`Runtime::set_address_provider`].
+pub trait AddressProvider: Send + Sync {
+    fn addresses(&self) -> ZResult<Vec<IpAddr>>;
+
+    /// Subscribes to the changes of the addresses, notified on the returned channel, if the
+    /// provider supports it. The addresses are polled otherwise.
+    fn changes(&self) -> Option<flume::Receiver<()>> {
+        None
+    }
+}
+
+/// The [`AddressProvider`] reading the addresses of the network interfaces of the system,
+/// notifying their changes through netlink on Linux.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemAddresses;
+
+impl AddressProvider for SystemAddresses {
+    fn addresses(&self) -> ZResult<Vec<IpAddr>> {
+        zenoh_util::net::get_local_addresses()
+    }
+
+    #[cfg(target_os = "linux")]
+    fn changes(&self) -> Option<flume::Receiver<()>> {
+        netlink::subscribe()
+            .map_err(|e| log::debug!("Polling the addresses of the interfaces: {}", e))
+            .ok()
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod netlink {
+    use std::cmp::Ordering;
+    use std::io::Error;
+    use std::mem;
+    use std::time::Duration;
+    use zenoh_result::{bail, ZResult};
+
+    // The period at which the reading thread checks that the changes are still watched
+    const RECV_TIMEOUT: Duration = Duration::from_secs(1);
+
+    // Subscribes to the changes of the IPv4 and IPv6 addresses through a NETLINK_ROUTE socket,
+    // read by a dedicated thread until the returned channel is dropped.
+    pub(super) fn subscribe() -> ZResult<flume::Receiver<()>> {
+        // SAFETY: the socket is closed on every path, and the structures passed are initialized
+        unsafe {
+            let fd = libc::socket(
+                libc::AF_NETLINK,
+                libc::SOCK_RAW | libc::SOCK_CLOEXEC,
+                libc::NETLINK_ROUTE,
+            );
+            if fd < 0 {
+                bail!(
+                    "Unable to open a netlink socket: {}",
+                    Error::last_os_error()
+                );
+            }
+
+            let mut addr: libc::sockaddr_nl = mem::zeroed();
+            addr.nl_family = libc::AF_NETLINK as libc::sa_family_t;
+            addr.nl_groups = (libc::RTMGRP_IPV4_IFADDR | libc::RTMGRP_IPV6_IFADDR) as u32;
+            let timeout = libc::timeval {
+                tv_sec: RECV_TIMEOUT.as_secs() as libc::time_t,
+                tv_usec: 0,
+            };
+            if libc::bind(
+                fd,
+                &addr as *const libc::sockaddr_nl as *const libc::sockaddr,
+                mem::size_of::<libc::sockaddr_nl>() as libc::socklen_t,
+            ) < 0
+                || libc::setsockopt(
+                    fd,
+                    libc::SOL_SOCKET,
+                    libc::SO_RCVTIMEO,
+                    &timeout as *const libc::timeval as *const libc::c_void,
+                    mem::size_of::<libc::timeval>() as libc::socklen_t,
+                ) < 0
+            {
+                let e = Error::last_os_error();
+                libc::close(fd);
+                bail!("Unable to subscribe to the address changes: {}", e);
+            }
+
+            let (sender, receiver) = flume::bounded(1);
+            let res = std::thread::Builder::new()
+                .name("zenoh-netlink".to_string())
+                .spawn(move || {
+                    // Only the notifications matter: the addresses are read again on a change
+                    let mut buf = [0u8; 4096];
+                    while !sender.is_disconnected() {
+                        let n = libc::recv(fd, buf.as_mut_ptr() as *mut libc::c_void, buf.len(), 0);
+                        match n.cmp(&0) {
+                            Ordering::Greater => {
+                                let _ = sender.try_send(());
+                            }
+                            Ordering::Less => {
+                                let e = Error::last_os_error();
+                                match e.raw_os_error() {
+                                    Some(libc::EAGAIN | libc::EINTR) => {}
+                                    // The buffer of the socket overflowed: some changes were lost
+                                    Some(libc::ENOBUFS) => {
+                                        let _ = sender.try_send(());
+                                    }
+                                    _ => {
+                                        log::debug!("Stopped watching the address changes: {}", e);
+                                        break;
+                                    }
+                                }
+                            }
+                            Ordering::Equal => {}
+                        }
+                    }
+                    libc::close(fd);
+                });
+            if let Err(e) = res {
+                libc::close(fd);
+                bail!("Unable to watch the address changes: {}", e);
+            }
+            Ok(receiver)
+        }
+    }
+}
+
+// The IP address a listener is bound to, if it's bound to an IP address at all.
+fn bound_address(endpoint: &EndPoint) -> Option<IpAddr> {
+    endpoint
+        .address()
+        .as_str()
+        .parse::<SocketAddr>()
+        .ok()
+        .map(|addr| addr.ip())
+}
+
+// Whether `ip` is among the addresses of the interfaces: the loopback interface lists a single
+// address of its range while any address of the range can be bound.
+fn is_present(ip: &IpAddr, addresses: &HashSet<IpAddr>) -> bool {
+    addresses.contains(ip)
+        || (ip.is_loopback()
+            && addresses
+                .iter()
+                .any(|a| a.is_loopback() && a.is_ipv4() == ip.is_ipv4()))
+}
+
+// A watched address provider, with its notifications of the changes if it supports them
+type WatchedProvider = (Arc<dyn AddressProvider>, Option<flume::Receiver<()>>);
+
+impl Runtime {
+    /// Watches the addresses of the local network interfaces, removing the listeners whose
+    /// address disappeared and binding them again once it reappears. The addresses are read
+    /// again on the changes notified by the [`AddressProvider`], or every `period` if it
+    /// doesn't notify them.
+    pub(super) async fn watch_addresses(self, period: Duration) {
+        // The removed listeners, waiting for their address
+        let mut lost: Vec<(EndPoint, IpAddr)> = vec![];
+        // The provider watched, with its notifications of the changes if it supports them
+        let mut watched: Option<WatchedProvider> = None;
+        loop {
+            let provider = zread!(self.address_provider).clone();
+            let mut changes = match watched.take() {
+                Some((p, changes)) if Arc::ptr_eq(&p, &provider) => changes,
+                _ => provider.changes(),
+            };
+            let changed = match changes.as_ref() {
+                // Still waking up every period, to follow the replacement of the provider and
+                // to retry binding the listeners which failed to
+                Some(receiver) => {
+                    let notified = async { Some(receiver.recv_async().await.is_ok()) }
+                        .race(async {
+                            self.clock.sleep(period).await;
+                            None
+                        })
+                        .await;
+                    match notified {
+                        Some(true) => true,
+                        // The notifications stopped, the addresses are polled from now on
+                        Some(false) => {
+                            changes = None;
+                            true
+                        }
+                        None => !lost.is_empty(),
+                    }
+                }
+                None => {
+                    self.clock.sleep(period).await;
+                    true
+                }
+            };
+            let replaced = !Arc::ptr_eq(&provider, &zread!(self.address_provider));
+            if changed || replaced {
+                self.check_addresses(&mut lost).await;
+            }
+            watched = Some((provider, changes));
+        }
+    }
+
+    async fn check_addresses(&self, lost: &mut Vec<(EndPoint, IpAddr)>) {
+        let listeners = self.manager().get_listeners();
+        if listeners.is_empty() && lost.is_empty() {
+            return;
+        }
+        let provider = zread!(self.address_provider).clone();
+        let addresses = match provider.addresses() {
+            Ok(addresses) => addresses.into_iter().collect::<HashSet<IpAddr>>(),
+            Err(e) => {
+                log::debug!("Unable to read the addresses of the interfaces: {}", e);
+                return;
+            }
+        };
+
+        for endpoint in listeners {
+            // The listeners bound to a wildcard address follow the interfaces by themselves
+            let ip = match bound_address(&endpoint) {
+                Some(ip) if !ip.is_unspecified() && !is_present(&ip, &addresses) => ip,
+                _ => continue,
+            };
+            match self.manager().del_listener(&endpoint).await {
+                Ok(()) => {
+                    log::warn!(
+                        "Alarm: removed listener {}: address {} disappeared",
+                        endpoint,
+                        ip
+                    );
+                    lost.push((endpoint, ip));
+                }
+                Err(e) => log::error!("Unable to remove listener {}: {}", endpoint, e),
+            }
+        }
+
+        let mut still_lost = vec![];
+        for (endpoint, ip) in lost.drain(..) {
+            if is_present(&ip, &addresses) {
+                match self.manager().add_listener(endpoint.clone()).await {
+                    Ok(_) => {
+                        log::warn!(
+                            "Alarm: bound listener {} again: address {} reappeared",
+                            endpoint,
+                            ip
+                        );
+                        continue;
+                    }
+                    Err(e) => log::debug!("Unable to bind listener {} again: {}", endpoint, e),
+                }
+            }
+            still_lost.push((endpoint, ip));
+        }
+        *lost = still_lost;
+
+        self.update_locators();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bound_addresses() {
+        let bound = |endpoint: &str| bound_address(&endpoint.parse().unwrap());
+        assert_eq!(bound("tcp/192.168.1.2:7447"), Some([192, 168, 1, 2].into()));
+        assert_eq!(
+            bound("udp/[::1]:7447"),
+            Some(std::net::Ipv6Addr::LOCALHOST.into())
+        );
+        assert!(bound("tcp/[::]:7447").unwrap().is_unspecified());
+        assert_eq!(bound("tls/localhost:7447"), None);
+        assert_eq!(bound("unixsock-stream/tmp/zenoh.sock"), None);
+    }
+
+    #[test]
+    fn present_addresses() {
+        let addresses: HashSet<IpAddr> = [[127, 0, 0, 1].into(), [192, 168, 1, 2].into()].into();
+        assert!(is_present(&[192, 168, 1, 2].into(), &addresses));
+        assert!(!is_present(&[192, 168, 1, 3].into(), &addresses));
+        // Any address of the loopback range, as long as the loopback interface is up
+        assert!(is_present(&[127, 0, 0, 2].into(), &addresses));
+        assert!(!is_present(
+            &std::net::Ipv6Addr::LOCALHOST.into(),
+            &addresses
+        ));
+        assert!(!is_present(&[127, 0, 0, 2].into(), &HashSet::new()));
+    }
+}
@@ -11,9 +11,12 @@
 // Contributors:
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 use super::routing::face::Face;
+use super::routing::mappings;
 use super::Runtime;
 use crate::key_expr::KeyExpr;
+use crate::plugins::api;
 use crate::plugins::sealed as plugins;
+use crate::prelude::json;
 use crate::prelude::sync::{Sample, SyncResolve};
 use crate::queryable::Query;
 use crate::queryable::QueryInner;
@@ -21,28 +24,35 @@ use crate::value::Value;
 use async_std::task;
 use log::{error, trace};
 use serde_json::json;
+use std::borrow::Cow;
 use std::collections::HashMap;
 use std::convert::TryFrom;
 use std::convert::TryInto;
+use std::str::FromStr;
 use std::sync::Arc;
 use std::sync::Mutex;
 use zenoh_buffers::SplitBuffer;
 use zenoh_config::ValidatedMap;
 use zenoh_protocol::{
-    core::{key_expr::OwnedKeyExpr, ExprId, KnownEncoding, WireExpr, ZenohId, EMPTY_EXPR_ID},
+    core::{
+        key_expr::OwnedKeyExpr, EndPoint, ExprId, KnownEncoding, Reliability, WhatAmI, WireExpr,
+        ZenohId, EMPTY_EXPR_ID,
+    },
     network::{
         declare::{queryable::ext::QueryableInfo, subscriber::ext::SubscriberInfo},
         ext, Declare, DeclareBody, DeclareQueryable, DeclareSubscriber, Push, Request, Response,
         ResponseFinal,
     },
+    transport::close,
     zenoh::{PushBody, RequestBody},
 };
 use zenoh_result::ZResult;
-use zenoh_transport::{Primitives, TransportUnicast};
+use zenoh_transport::{Primitives, TransportPeer, TransportUnicast};
 
 pub struct AdminContext {
     runtime: Runtime,
     plugins_mgr: Mutex<plugins::PluginsManager>,
+    plugins_api: plugins::PluginApi,
     zid_str: String,
     version: String,
     metadata: serde_json::Value,
@@ -100,6 +110,22 @@ impl AdminSpace {
                 .unwrap(),
             Arc::new(queryables_data),
         );
+        handlers.insert(
+            format!("@/router/{zid_str}/transport/unicast/**")
+                .try_into()
+                .unwrap(),
+            Arc::new(transports_data),
+        );
+        handlers.insert(
+            format!("@/router/{zid_str}/debug/queries")
+                .try_into()
+                .unwrap(),
+            Arc::new(queries_trace_data),
+        );
+        handlers.insert(
+            format!("@/router/{zid_str}/logger").try_into().unwrap(),
+            Arc::new(logger_data),
+        );
         handlers.insert(
             format!("@/router/{zid_str}/status/plugins/**")
                 .try_into()
@@ -112,10 +138,12 @@ impl AdminSpace {
             .into_iter()
             .map(|(a, b)| (a.to_string(), b.to_string()))
             .collect::<HashMap<_, _>>();
+        *zwrite!(runtime.running_plugins) = Some(active_plugins.keys().cloned().collect());
 
         let context = Arc::new(AdminContext {
             runtime: runtime.clone(),
             plugins_mgr: Mutex::new(plugins_mgr),
+            plugins_api: plugins::PluginApi::new(runtime.clone()),
             zid_str,
             version,
             metadata,
@@ -191,7 +219,7 @@ impl AdminSpace {
                                     Ok(path) => {
                                         let name = &plugin.name;
                                         log::info!("Loaded plugin `{}` from {}", name, &path);
-                                        match plugins_mgr.start(name, &admin.context.runtime) {
+                                        match plugins_mgr.start(name, &admin.context.plugins_api) {
                                             Ok(Some((path, plugin))) => {
                                                 active_plugins.insert(name.into(), path.into());
                                                 let mut cfg_guard =
@@ -216,6 +244,8 @@ impl AdminSpace {
                             }
                         }
                     }
+                    *zwrite!(admin.context.runtime.running_plugins) =
+                        Some(active_plugins.keys().cloned().collect());
                     log::info!("Running plugins: {:?}", &active_plugins)
                 }
             }
@@ -246,6 +276,43 @@ impl AdminSpace {
                 id: 0, // TODO
                 wire_expr: [&root_key, "/config/**"].concat().into(),
                 ext_info: SubscriberInfo::default(),
+                ext_filter: None,
+            }),
+        });
+
+        primitives.send_declare(Declare {
+            ext_qos: ext::QoSType::declare_default(),
+            ext_tstamp: None,
+            ext_nodeid: ext::NodeIdType::default(),
+            body: DeclareBody::DeclareSubscriber(DeclareSubscriber {
+                id: 0, // TODO
+                wire_expr: [&root_key, "/logger"].concat().into(),
+                ext_info: SubscriberInfo::default(),
+                ext_filter: None,
+            }),
+        });
+
+        primitives.send_declare(Declare {
+            ext_qos: ext::QoSType::declare_default(),
+            ext_tstamp: None,
+            ext_nodeid: ext::NodeIdType::default(),
+            body: DeclareBody::DeclareSubscriber(DeclareSubscriber {
+                id: 0, // TODO
+                wire_expr: [&root_key, "/transport/unicast/*/expire"].concat().into(),
+                ext_info: SubscriberInfo::default(),
+                ext_filter: None,
+            }),
+        });
+
+        primitives.send_declare(Declare {
+            ext_qos: ext::QoSType::declare_default(),
+            ext_tstamp: None,
+            ext_nodeid: ext::NodeIdType::default(),
+            body: DeclareBody::DeclareSubscriber(DeclareSubscriber {
+                id: 0, // TODO
+                wire_expr: [&root_key, "/listeners/*"].concat().into(),
+                ext_info: SubscriberInfo::default(),
+                ext_filter: None,
             }),
         });
     }
@@ -280,7 +347,7 @@ impl Primitives for AdminSpace {
         }
     }
 
-    fn send_push(&self, msg: Push) {
+    fn send_push(&self, msg: Push, _reliability: Reliability) {
         trace!("recv Push {:?}", msg);
         {
             let conf = self.context.runtime.config.lock();
@@ -330,6 +397,44 @@ impl Primitives for AdminSpace {
                     }
                 }
             }
+        } else if msg.wire_expr.as_str() == format!("@/router/{}/logger", &self.context.zid_str) {
+            match msg.payload {
+                PushBody::Put(put) => match std::str::from_utf8(&put.payload.contiguous()) {
+                    Ok(filter) => match crate::logging::set_log_filter(filter) {
+                        Ok(filter) => log::info!("Log filter set to '{}'", filter),
+                        Err(e) => error!("Error setting log filter '{}' : {}", filter, e),
+                    },
+                    Err(e) => error!("Received non utf8 log filter on {} : {}", msg.wire_expr, e),
+                },
+                PushBody::Del(_) => {
+                    error!("The log filter can't be deleted, put a new one instead")
+                }
+            }
+        } else if let Some(peer) = msg
+            .wire_expr
+            .as_str()
+            .strip_prefix(&format!(
+                "@/router/{}/transport/unicast/",
+                &self.context.zid_str
+            ))
+            .and_then(|s| s.strip_suffix("/expire"))
+        {
+            match msg.payload {
+                PushBody::Put(_) => expire_transport(&self.context, peer),
+                PushBody::Del(_) => error!("Received DELETE on {}", msg.wire_expr),
+            }
+        } else if let Some(op) = msg
+            .wire_expr
+            .as_str()
+            .strip_prefix(&format!("@/router/{}/listeners/", &self.context.zid_str))
+        {
+            match msg.payload {
+                PushBody::Put(put) => match std::str::from_utf8(&put.payload.contiguous()) {
+                    Ok(endpoint) => update_listener(&self.context, op, endpoint),
+                    Err(e) => error!("Received non utf8 endpoint on {} : {}", msg.wire_expr, e),
+                },
+                PushBody::Del(_) => error!("Received DELETE on {}", msg.wire_expr),
+            }
         }
     }
 
@@ -377,8 +482,10 @@ impl Primitives for AdminSpace {
                         .map(|b| Value::from(b.payload).encoding(b.encoding)),
                     qid: msg.id,
                     zid,
+                    correlation: msg.ext_correlation,
                     primitives,
                 }),
+                eid: 0,
             };
 
             for (key, handler) in &self.handlers {
@@ -417,42 +524,51 @@ fn router_data(context: &AdminContext, query: Query) {
     };
 
     // locators info
-    let locators: Vec<serde_json::Value> = transport_mgr
+    let locators: Vec<serde_json::Value> = context
+        .runtime
         .get_locators()
         .iter()
         .map(|locator| json!(locator.as_str()))
         .collect();
 
     // transports info
-    let transport_to_json = |transport: &TransportUnicast| {
-        #[allow(unused_mut)]
-        let mut json = json!({
-            "peer": transport.get_zid().map_or_else(|_| "unknown".to_string(), |p| p.to_string()),
-            "whatami": transport.get_whatami().map_or_else(|_| "unknown".to_string(), |p| p.to_string()),
-            "links": transport.get_links().map_or_else(
-                |_| Vec::new(),
-                |links| links.iter().map(|link| link.dst.to_string()).collect()
-            ),
+    #[cfg(feature = "stats")]
+    let stats = crate::prelude::Parameters::decode(&query.selector())
+        .any(|(k, v)| k.as_ref() == "_stats" && v != "false");
+    let transport_info = |transport: &TransportUnicast| {
+        let peer = transport.get_peer().ok()?;
+        let mut info = json::TransportInfo::from(&peer);
+        let tables = zread!(context.runtime.router.tables.tables);
+        info.priority_downgrades = Some(tables.priority_downgrades(&peer.zid));
+        info.malformed_payloads = Some(tables.malformed_payloads(&peer.zid));
+        info.mutated_samples = Some(tables.mutated_samples(&peer.zid));
+        info.keyexpr_mappings = Some(mappings::mappings_info(&tables, &peer.zid));
+        info.query_throttle = tables
+            .query_throttle
+            .as_ref()
+            .and_then(|throttle| throttle.info(&peer.zid));
+        drop(tables);
+        info.unknown_kinds = transport.get_unknown_kinds().ok().map(|kinds| {
+            kinds
+                .into_iter()
+                .map(|(kind, count)| (kind.to_string(), count))
+                .collect()
         });
+        info.links = transport
+            .get_links_rtt()
+            .ok()
+            .map(|links| links.iter().map(json::LinkInfo::from).collect());
         #[cfg(feature = "stats")]
-        {
-            let stats = crate::prelude::Parameters::decode(&query.selector())
-                .any(|(k, v)| k.as_ref() == "_stats" && v != "false");
-            if stats {
-                json.as_object_mut().unwrap().insert(
-                    "stats".to_string(),
-                    transport
-                        .get_stats()
-                        .map_or_else(|_| json!({}), |p| json!(p.report())),
-                );
-            }
+        if stats {
+            info.stats = transport.get_stats().ok().map(|s| json!(s.report()));
         }
-        json
+        Some(info)
     };
-    let transports: Vec<serde_json::Value> = task::block_on(transport_mgr.get_transports_unicast())
-        .iter()
-        .map(transport_to_json)
-        .collect();
+    let transports: Vec<json::TransportInfo> =
+        task::block_on(transport_mgr.get_transports_unicast())
+            .iter()
+            .filter_map(transport_info)
+            .collect();
 
     #[allow(unused_mut)]
     let mut json = json!({
@@ -465,15 +581,11 @@ fn router_data(context: &AdminContext, query: Query) {
     });
 
     #[cfg(feature = "stats")]
-    {
-        let stats = crate::prelude::Parameters::decode(&query.selector())
-            .any(|(k, v)| k.as_ref() == "_stats" && v != "false");
-        if stats {
-            json.as_object_mut().unwrap().insert(
-                "stats".to_string(),
-                json!(transport_mgr.get_stats().report()),
-            );
-        }
+    if stats {
+        json.as_object_mut().unwrap().insert(
+            "stats".to_string(),
+            json!(transport_mgr.get_stats().report()),
+        );
     }
 
     log::trace!("AdminSpace router_data: {:?}", json);
@@ -587,7 +699,15 @@ fn subscribers_data(context: &AdminContext, query: Query) {
         ))
         .unwrap();
         if query.key_expr().intersects(&key) {
-            if let Err(e) = query.reply(Ok(Sample::new(key, Value::empty()))).res() {
+            let json = json!(sub.subscriber_ids());
+            if let Err(e) = query
+                .reply(Ok(Sample::new(
+                    key,
+                    Value::from(json.to_string().as_bytes().to_vec())
+                        .encoding(KnownEncoding::AppJson.into()),
+                )))
+                .res()
+            {
                 log::error!("Error sending AdminSpace reply: {:?}", e);
             }
         }
@@ -604,10 +724,370 @@ fn queryables_data(context: &AdminContext, query: Query) {
         ))
         .unwrap();
         if query.key_expr().intersects(&key) {
-            if let Err(e) = query.reply(Ok(Sample::new(key, Value::empty()))).res() {
+            let json = json!(qabl.queryable_ids());
+            if let Err(e) = query
+                .reply(Ok(Sample::new(
+                    key,
+                    Value::from(json.to_string().as_bytes().to_vec())
+                        .encoding(KnownEncoding::AppJson.into()),
+                )))
+                .res()
+            {
+                log::error!("Error sending AdminSpace reply: {:?}", e);
+            }
+        }
+    }
+}
+
+/// Selector parameters accepted by the `@/router/<zid>/transport/unicast/**` handler.
+/// Parameters starting with `_` are reserved by zenoh and are ignored by the filter.
+const TRANSPORT_FILTER_PARAMS: [&str; 6] = [
+    "whatami",
+    "link_proto",
+    "min_rx_bytes",
+    "limit",
+    "offset",
+    "fields",
+];
+
+/// Fields that can be selected through the `fields` parameter.
+const TRANSPORT_FIELDS: [&str; 7] = [
+    "zid",
+    "whatami",
+    "locators",
+    "is_qos",
+    "is_initiator",
+    "stats",
+    "closed",
+];
+
+/// Server-side filtering, pagination and projection of the transports reported in the admin space.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct TransportFilter {
+    whatami: Option<WhatAmI>,
+    link_proto: Option<String>,
+    min_rx_bytes: Option<usize>,
+    limit: Option<usize>,
+    offset: usize,
+    fields: Option<Vec<String>>,
+}
+
+impl TransportFilter {
+    fn from_parameters(parameters: &str) -> ZResult<Self> {
+        use crate::prelude::Parameters;
+
+        let mut filter = TransportFilter::default();
+        let map: HashMap<Cow<str>, Cow<str>> = parameters.decode_into_map()?;
+        for (name, value) in map.iter() {
+            match name.as_ref() {
+                "whatami" => {
+                    filter.whatami =
+                        Some(value.parse().map_err(|_| {
+                            zerror!("Invalid value for parameter `whatami`: {}", value)
+                        })?)
+                }
+                "link_proto" => filter.link_proto = Some(value.to_string()),
+                "min_rx_bytes" => {
+                    if cfg!(not(feature = "stats")) {
+                        bail!("Parameter `min_rx_bytes` requires zenoh to be built with the `stats` feature");
+                    }
+                    filter.min_rx_bytes = Some(value.parse().map_err(|_| {
+                        zerror!("Invalid value for parameter `min_rx_bytes`: {}", value)
+                    })?)
+                }
+                "limit" => {
+                    filter.limit =
+                        Some(value.parse().map_err(|_| {
+                            zerror!("Invalid value for parameter `limit`: {}", value)
+                        })?)
+                }
+                "offset" => {
+                    filter.offset = value
+                        .parse()
+                        .map_err(|_| zerror!("Invalid value for parameter `offset`: {}", value))?
+                }
+                "fields" => {
+                    let mut fields = vec![];
+                    for field in value.split(',').filter(|f| !f.is_empty()) {
+                        if !TRANSPORT_FIELDS.contains(&field) {
+                            bail!(
+                                "Unknown field `{}` in parameter `fields`. Accepted fields are: {:?}",
+                                field,
+                                TRANSPORT_FIELDS
+                            );
+                        }
+                        fields.push(field.to_string());
+                    }
+                    filter.fields = Some(fields);
+                }
+                name if name.starts_with('_') => {}
+                name => bail!(
+                    "Unknown parameter `{}`. Accepted parameters are: {:?}",
+                    name,
+                    TRANSPORT_FILTER_PARAMS
+                ),
+            }
+        }
+        Ok(filter)
+    }
+
+    fn matches(&self, peer: &TransportPeer, rx_bytes: Option<usize>) -> bool {
+        if let Some(whatami) = self.whatami {
+            if peer.whatami != whatami {
+                return false;
+            }
+        }
+        if let Some(proto) = self.link_proto.as_ref() {
+            if !peer
+                .links
+                .iter()
+                .any(|l| l.dst.protocol().as_str() == proto.as_str())
+            {
+                return false;
+            }
+        }
+        if let Some(min) = self.min_rx_bytes {
+            if rx_bytes.map_or(true, |rx| rx < min) {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Whether a transport closed recently matches the filter: only its kind of node is known.
+    fn matches_closed(&self, whatami: WhatAmI) -> bool {
+        self.whatami.map_or(true, |w| w == whatami)
+            && self.link_proto.is_none()
+            && self.min_rx_bytes.is_none()
+    }
+
+    fn project(&self, mut json: serde_json::Value) -> serde_json::Value {
+        if let (Some(fields), Some(object)) = (self.fields.as_ref(), json.as_object_mut()) {
+            object.retain(|k, _| fields.iter().any(|f| f == k));
+        }
+        json
+    }
+}
+
+fn transports_data(context: &AdminContext, query: Query) {
+    let filter = match TransportFilter::from_parameters(query.parameters()) {
+        Ok(filter) => filter,
+        Err(e) => {
+            if let Err(e) = query.reply(Err(e.to_string().into())).res() {
                 log::error!("Error sending AdminSpace reply: {:?}", e);
             }
+            return;
+        }
+    };
+
+    let transports = task::block_on(context.runtime.manager().get_transports_unicast())
+        .into_iter()
+        .filter_map(|t| t.get_peer().ok().map(|peer| (peer, t)))
+        .collect::<Vec<_>>();
+    let transport_key = |zid: &ZenohId| {
+        KeyExpr::try_from(format!(
+            "@/router/{}/transport/unicast/{}",
+            context.zid_str, zid
+        ))
+        .ok()
+        .filter(|key| query.key_expr().intersects(key))
+    };
+
+    let mut replies = transports
+        .iter()
+        .filter_map(|(peer, transport)| {
+            let key = transport_key(&peer.zid)?;
+            let mut info = json::TransportInfo::from(peer);
+            info.links = transport
+                .get_links_rtt()
+                .ok()
+                .map(|links| links.iter().map(json::LinkInfo::from).collect());
+            #[allow(unused_mut)]
+            let mut rx_bytes = None;
+            #[cfg(feature = "stats")]
+            {
+                if let Ok(stats) = transport.get_stats() {
+                    let report = stats.report();
+                    rx_bytes = Some(report.rx_bytes);
+                    info.stats = Some(json!(report));
+                }
+            }
+            let json = serde_json::to_value(info).ok()?;
+            filter
+                .matches(peer, rx_bytes)
+                .then(|| (peer.zid, key, filter.project(json)))
+        })
+        .collect::<Vec<_>>();
+    // The transports closed recently are reported with their reason, unless reopened since
+    let closed = context.runtime.closed_transports();
+    replies.extend(closed.iter().filter_map(|(zid, closed)| {
+        if !filter.matches_closed(closed.whatami) || transports.iter().any(|(p, _)| p.zid == *zid) {
+            return None;
+        }
+        let key = transport_key(zid)?;
+        let info = json::TransportInfo {
+            zid: *zid,
+            whatami: closed.whatami.to_string(),
+            locators: vec![],
+            is_qos: None,
+            is_initiator: None,
+            priority_downgrades: None,
+            malformed_payloads: None,
+            mutated_samples: None,
+            keyexpr_mappings: None,
+            query_throttle: None,
+            unknown_kinds: None,
+            stats: None,
+            links: None,
+            closed: Some(json::CloseInfo::from(&closed.reason)),
+        };
+        let json = serde_json::to_value(info).ok()?;
+        Some((*zid, key, filter.project(json)))
+    }));
+    // Sort the transports to provide a stable order for pagination
+    replies.sort_by_key(|(zid, _, _)| *zid);
+
+    for (_, key, json) in replies
+        .into_iter()
+        .skip(filter.offset)
+        .take(filter.limit.unwrap_or(usize::MAX))
+    {
+        if let Err(e) = query
+            .reply(Ok(Sample::new(
+                key,
+                Value::from(json.to_string().as_bytes().to_vec())
+                    .encoding(KnownEncoding::AppJson.into()),
+            )))
+            .res()
+        {
+            log::error!("Error sending AdminSpace reply: {:?}", e);
+        }
+    }
+}
+
+/// Closes the transport with the given peer without waiting for its lease to expire.
+///
+/// The transport is closed by a task: the push is handled on the rx task of the link, which the
+/// close waits for. Once closed, the face of the peer is removed from the routing tables, so that
+/// the following queries no longer target its queryables.
+fn expire_transport(context: &AdminContext, peer: &str) {
+    let zid = match ZenohId::from_str(peer) {
+        Ok(zid) => zid,
+        Err(e) => {
+            error!("Invalid zid {} in transport expire: {}", peer, e);
+            return;
+        }
+    };
+    log::info!("Expire transport with peer {}", zid);
+    let runtime = context.runtime.clone();
+    context.runtime.spawn(async move {
+        if let Err(e) = runtime
+            .manager()
+            .close_transport_unicast(&zid, close::reason::EXPIRED)
+            .await
+        {
+            error!("Error expiring transport with peer {}: {}", zid, e);
+        }
+    });
+}
+
+/// Adds (`op` is `add`) or removes (`op` is `remove`) the listener on the given endpoint, in a
+/// task not to block the handling of the push. The result is logged.
+fn update_listener(context: &AdminContext, op: &str, endpoint: &str) {
+    let endpoint = match EndPoint::from_str(endpoint.trim()) {
+        Ok(endpoint) => endpoint,
+        Err(e) => {
+            error!("Invalid endpoint {} in listener {}: {}", endpoint, op, e);
+            return;
+        }
+    };
+    let runtime = context.runtime.clone();
+    match op {
+        "add" => {
+            context.runtime.spawn(async move {
+                match runtime.add_listener(endpoint.clone()).await {
+                    Ok(locators) => log::info!("Added listener {}: {:?}", endpoint, locators),
+                    Err(e) => error!("Error adding listener {}: {}", endpoint, e),
+                }
+            });
+        }
+        "remove" => {
+            context.runtime.spawn(async move {
+                match runtime.remove_listener(&endpoint).await {
+                    Ok(()) => log::info!("Removed listener {}", endpoint),
+                    Err(e) => error!("Error removing listener {}: {}", endpoint, e),
+                }
+            });
+        }
+        _ => error!("Unknown listener operation {}: expected add or remove", op),
+    }
+}
+
+fn queries_trace_data(context: &AdminContext, query: Query) {
+    let reply_key: OwnedKeyExpr = format!("@/router/{}/debug/queries", context.zid_str)
+        .try_into()
+        .unwrap();
+
+    let tracer = zread!(context.runtime.router.tables.tables)
+        .query_tracer
+        .clone();
+    let reply = match tracer {
+        Some(tracer) => Ok(Sample::new(
+            reply_key,
+            Value::from(tracer.to_json().to_string().as_bytes().to_vec())
+                .encoding(KnownEncoding::AppJson.into()),
+        )),
+        None => Err("Query tracing is disabled: see routing.query_tracing in configuration".into()),
+    };
+    if let Err(e) = query.reply(reply).res() {
+        log::error!("Error sending AdminSpace reply: {:?}", e);
+    }
+}
+
+fn logger_data(context: &AdminContext, query: Query) {
+    use crate::logging;
+
+    let reply_key: OwnedKeyExpr = format!("@/router/{}/logger", context.zid_str)
+        .try_into()
+        .unwrap();
+
+    // A query carrying a filter sets it, so that errors can be replied
+    let filter = match query.value() {
+        Some(_) if !context.runtime.config.lock().adminspace.permissions().write => Err(zerror!(
+            "Can't set the log filter: adminspace.permissions.write=false in configuration"
+        )
+        .into()),
+        Some(value) => match std::str::from_utf8(&value.payload.contiguous()) {
+            Ok(filter) => logging::set_log_filter(filter),
+            Err(e) => Err(zerror!("Received non utf8 log filter: {}", e).into()),
+        },
+        None => {
+            logging::log_filter().ok_or_else(|| zerror!("No reloadable logger installed").into())
+        }
+    };
+    let reply = match filter {
+        Ok(filter) => {
+            let suppressed = logging::suppressed_records();
+            let json = json!({
+                "filter": filter.to_string(),
+                "suppressed": {
+                    "error": suppressed.error,
+                    "warn": suppressed.warn,
+                    "info": suppressed.info,
+                    "debug": suppressed.debug,
+                    "trace": suppressed.trace,
+                },
+            });
+            Ok(Sample::new(
+                reply_key,
+                Value::from(json.to_string().as_bytes().to_vec())
+                    .encoding(KnownEncoding::AppJson.into()),
+            ))
         }
+        Err(e) => Err(e.to_string().into()),
+    };
+    if let Err(e) = query.reply(reply).res() {
+        log::error!("Error sending AdminSpace reply: {:?}", e);
     }
 }
 
@@ -635,6 +1115,23 @@ fn plugins_status(context: &AdminContext, query: Query) {
                     log::error!("Error: invalid plugin path key {}", plugin_path_key);
                 }
             });
+            with_extended_string(plugin_key, &["/status"], |plugin_status_key| {
+                if let Ok(key_expr) = KeyExpr::try_from(plugin_status_key.clone()) {
+                    if query.key_expr().intersects(&key_expr) {
+                        if let Some(status) = api::status(&context.runtime, name) {
+                            if let Err(e) = query
+                                .reply(Ok(Sample::new(
+                                    key_expr,
+                                    Value::from(status).encoding(KnownEncoding::AppJson.into()),
+                                )))
+                                .res()
+                            {
+                                log::error!("Error sending AdminSpace reply: {:?}", e);
+                            }
+                        }
+                    }
+                }
+            });
             let matches_plugin = |plugin_status_space: &mut String| {
                 query
                     .key_expr()
@@ -691,3 +1188,94 @@ fn with_extended_string<R, F: FnMut(&mut String) -> R>(
     prefix.truncate(prefix_len);
     result
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zenoh_link::Link;
+
+    fn peer(whatami: WhatAmI, protos: &[&str]) -> TransportPeer {
+        TransportPeer {
+            zid: ZenohId::rand(),
+            whatami,
+            is_qos: true,
+            is_initiator: false,
+            links: protos
+                .iter()
+                .map(|p| Link {
+                    src: format!("{p}/127.0.0.1:7447").parse().unwrap(),
+                    dst: format!("{p}/127.0.0.1:17447").parse().unwrap(),
+                    group: None,
+                    mtu: u16::MAX,
+                    is_reliable: true,
+                    is_streamed: true,
+                })
+                .collect(),
+            #[cfg(feature = "shared-memory")]
+            is_shm: false,
+        }
+    }
+
+    #[test]
+    fn transport_filter_parameters() {
+        let filter = TransportFilter::from_parameters("").unwrap();
+        assert_eq!(filter, TransportFilter::default());
+
+        let filter =
+            TransportFilter::from_parameters("whatami=client&link_proto=tls&limit=10&offset=5")
+                .unwrap();
+        assert_eq!(filter.whatami, Some(WhatAmI::Client));
+        assert_eq!(filter.link_proto.as_deref(), Some("tls"));
+        assert_eq!(filter.limit, Some(10));
+        assert_eq!(filter.offset, 5);
+
+        // Reserved parameters are ignored
+        assert!(TransportFilter::from_parameters("_stats&whatami=peer").is_ok());
+        // Unknown parameters, invalid values and duplicates are rejected
+        assert!(TransportFilter::from_parameters("wahtami=peer").is_err());
+        assert!(TransportFilter::from_parameters("whatami=robot").is_err());
+        assert!(TransportFilter::from_parameters("limit=-1").is_err());
+        assert!(TransportFilter::from_parameters("fields=zid,password").is_err());
+        assert!(TransportFilter::from_parameters("limit=1&limit=2").is_err());
+        #[cfg(not(feature = "stats"))]
+        assert!(TransportFilter::from_parameters("min_rx_bytes=10").is_err());
+    }
+
+    #[test]
+    fn transport_filter_matches() {
+        let client_tcp = peer(WhatAmI::Client, &["tcp"]);
+        let peer_tls = peer(WhatAmI::Peer, &["tcp", "tls"]);
+
+        let filter = TransportFilter::from_parameters("whatami=client").unwrap();
+        assert!(filter.matches(&client_tcp, None));
+        assert!(!filter.matches(&peer_tls, None));
+
+        let filter = TransportFilter::from_parameters("link_proto=tls").unwrap();
+        assert!(!filter.matches(&client_tcp, None));
+        assert!(filter.matches(&peer_tls, None));
+
+        let filter = TransportFilter {
+            min_rx_bytes: Some(100),
+            ..Default::default()
+        };
+        assert!(!filter.matches(&client_tcp, None));
+        assert!(!filter.matches(&client_tcp, Some(99)));
+        assert!(filter.matches(&client_tcp, Some(100)));
+    }
+
+    #[test]
+    fn transport_filter_projection() {
+        let peer = peer(WhatAmI::Router, &["udp"]);
+        let json = serde_json::to_value(json::TransportInfo::from(&peer)).unwrap();
+
+        let filter = TransportFilter::default();
+        assert_eq!(filter.project(json.clone()), json);
+
+        let filter = TransportFilter::from_parameters("fields=zid,locators").unwrap();
+        let projected = filter.project(json);
+        let object = projected.as_object().unwrap();
+        assert_eq!(object.len(), 2);
+        assert_eq!(object["zid"], json!(peer.zid.to_string()));
+        assert_eq!(object["locators"], json!(["udp/127.0.0.1:17447"]));
+    }
+}
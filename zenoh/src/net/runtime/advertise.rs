@@ -0,0 +1,124 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use zenoh_config::LocatorRewriteConf;
+use zenoh_protocol::core::Locator;
+
+/// A hook rewriting the locators a [`Runtime`](super::Runtime) advertises in the scouting
+/// Hellos, the gossip and the admin space, e.g. with addresses discovered with STUN.
+///
+/// The rewriting doesn't change the endpoints the runtime actually listens on.
+pub trait LocatorsRewriter: Send + Sync {
+    /// Returns the locators to advertise instead of `locators`.
+    fn rewrite(&self, locators: Vec<Locator>) -> Vec<Locator>;
+}
+
+/// The static rewriting rules of the `advertise/rewrite` configuration.
+pub(crate) struct RewriteRules {
+    rules: Vec<LocatorRewriteConf>,
+}
+
+impl RewriteRules {
+    pub(crate) fn new(rules: &[LocatorRewriteConf]) -> Self {
+        RewriteRules {
+            rules: rules.to_vec(),
+        }
+    }
+}
+
+impl LocatorsRewriter for RewriteRules {
+    fn rewrite(&self, locators: Vec<Locator>) -> Vec<Locator> {
+        let mut rewritten: Vec<Locator> = Vec::with_capacity(locators.len());
+        for locator in locators {
+            let locator = match self
+                .rules
+                .iter()
+                .find(|rule| glob_match(&rule.r#match, locator.as_str()))
+            {
+                Some(rule) => rule.replace.clone(),
+                None => locator,
+            };
+            // Several locators may be rewritten into the same one
+            if !rewritten.contains(&locator) {
+                rewritten.push(locator);
+            }
+        }
+        rewritten
+    }
+}
+
+// Whether `s` matches `pattern`, where `*` matches any sequence of characters.
+fn glob_match(pattern: &str, s: &str) -> bool {
+    let mut chunks = pattern.split('*');
+    // The first chunk is anchored at the start of `s`
+    let first = chunks.next().unwrap_or_default();
+    let mut rest = match s.strip_prefix(first) {
+        Some(rest) => rest,
+        None => return false,
+    };
+    let chunks: Vec<&str> = chunks.collect();
+    let Some((last, middle)) = chunks.split_last() else {
+        return rest.is_empty();
+    };
+    for chunk in middle {
+        match rest.find(chunk) {
+            Some(i) => rest = &rest[i + chunk.len()..],
+            None => return false,
+        }
+    }
+    // The last chunk is anchored at the end of `s`
+    rest.ends_with(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn locator_glob_match() {
+        assert!(glob_match("tcp/10.0.*", "tcp/10.0.1.2:7447"));
+        assert!(!glob_match("tcp/10.0.*", "udp/10.0.1.2:7447"));
+        assert!(glob_match("*/10.0.*:7447", "tcp/10.0.1.2:7447"));
+        assert!(!glob_match("*/10.0.*:7447", "tcp/10.0.1.2:7448"));
+        assert!(glob_match("tcp/127.0.0.1:7447", "tcp/127.0.0.1:7447"));
+        assert!(!glob_match("tcp/127.0.0.1:7447", "tcp/127.0.0.1:74470"));
+        assert!(glob_match("*", "tcp/127.0.0.1:7447"));
+    }
+
+    #[test]
+    fn locator_rewrite_rules() {
+        let rules = RewriteRules::new(&[
+            LocatorRewriteConf {
+                r#match: "tcp/10.0.*".to_string(),
+                replace: "tcp/public.example.com:7447".parse().unwrap(),
+            },
+            LocatorRewriteConf {
+                r#match: "tcp/*".to_string(),
+                replace: "tcp/fallback.example.com:7447".parse().unwrap(),
+            },
+        ]);
+        let locators = [
+            "tcp/10.0.1.2:7447",
+            "tcp/10.0.1.3:7447",
+            "udp/10.0.1.2:7447",
+        ]
+        .iter()
+        .map(|l| l.parse().unwrap())
+        .collect();
+        let expected: Vec<Locator> = ["tcp/public.example.com:7447", "udp/10.0.1.2:7447"]
+            .iter()
+            .map(|l| l.parse().unwrap())
+            .collect();
+        assert_eq!(rules.rewrite(locators), expected);
+    }
+}
@@ -0,0 +1,203 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use super::Runtime;
+use crate::config::Config;
+use async_std::net::ToSocketAddrs;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::Arc;
+use zenoh_link::{EndPoint, LocatorInspector};
+use zenoh_protocol::core::endpoint;
+use zenoh_result::{bail, ZResult};
+use zenoh_util::clock::SystemClock;
+
+/// The protocols whose address is a `host:port` pair resolved when opening the links.
+const RESOLVED_PROTOCOLS: &[&str] = &["tcp", "udp", "tls", "quic", "ws"];
+
+/// The part of the configuration a [`ValidationProblem`] was found in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ValidationKind {
+    /// The runtime could not be built from the configuration.
+    Config,
+    /// An endpoint of the `listen` configuration can not be listened on.
+    Listen,
+    /// An endpoint of the `connect` configuration can not be connected to.
+    Connect,
+}
+
+/// A problem found when validating a configuration, see [`Runtime::check`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValidationProblem {
+    pub kind: ValidationKind,
+    /// The endpoint the problem was found on, if any.
+    pub endpoint: Option<EndPoint>,
+    pub error: String,
+}
+
+impl fmt::Display for ValidationProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.endpoint {
+            Some(endpoint) => write!(f, "{:?} {}: {}", self.kind, endpoint, self.error),
+            None => write!(f, "{:?}: {}", self.kind, self.error),
+        }
+    }
+}
+
+/// All the problems found when validating a configuration, see [`Runtime::check`].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValidationReport {
+    pub problems: Vec<ValidationProblem>,
+}
+
+impl ValidationReport {
+    /// Whether no problem was found.
+    pub fn is_ok(&self) -> bool {
+        self.problems.is_empty()
+    }
+
+    fn push(
+        &mut self,
+        kind: ValidationKind,
+        endpoint: Option<&EndPoint>,
+        error: impl fmt::Display,
+    ) {
+        self.problems.push(ValidationProblem {
+            kind,
+            endpoint: endpoint.cloned(),
+            error: error.to_string(),
+        });
+    }
+}
+
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, problem) in self.problems.iter().enumerate() {
+            if i > 0 {
+                write!(f, "; ")?;
+            }
+            write!(f, "{problem}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Runtime {
+    /// Runs the build phase of a [`Runtime`] with the given configuration, without starting it,
+    /// and reports all the problems found rather than stopping at the first one.
+    ///
+    /// The listeners are bound then closed right away, loading their TLS material, and the
+    /// addresses of the connect endpoints are resolved.
+    pub async fn check(config: Config) -> ZResult<ValidationReport> {
+        let runtime = match Runtime::init(config, Arc::new(SystemClock)).await {
+            Ok(runtime) => runtime,
+            Err(e) => {
+                let mut report = ValidationReport::default();
+                report.push(ValidationKind::Config, None, e);
+                return Ok(report);
+            }
+        };
+        let report = runtime.validate().await;
+        runtime.close().await?;
+        Ok(report)
+    }
+
+    /// Validates the endpoints of the configuration of a runtime which is not started yet.
+    pub(crate) async fn validate(&self) -> ValidationReport {
+        let mut report = ValidationReport::default();
+        let (listeners, peers) = {
+            let guard = self.config.lock();
+            (self.listeners(&guard), guard.connect().endpoints().clone())
+        };
+        let inspector = LocatorInspector::default();
+
+        for endpoint in &listeners {
+            match inspector.is_multicast(&endpoint.to_locator()).await {
+                // The multicast groups are joined when starting the runtime
+                Ok(true) => continue,
+                Ok(false) => {}
+                Err(e) => {
+                    report.push(ValidationKind::Listen, Some(endpoint), e);
+                    continue;
+                }
+            }
+            match self.manager().add_listener_unicast(endpoint.clone()).await {
+                Ok(locators) => {
+                    for locator in locators {
+                        if let Err(e) = self
+                            .manager()
+                            .del_listener_unicast(&EndPoint::from(locator))
+                            .await
+                        {
+                            log::warn!("Unable to close the probed listener {}: {}", endpoint, e);
+                        }
+                    }
+                }
+                Err(e) => report.push(ValidationKind::Listen, Some(endpoint), e),
+            }
+        }
+
+        for endpoint in &peers {
+            if let Err(e) = self.check_files(endpoint).await {
+                report.push(ValidationKind::Connect, Some(endpoint), e);
+            }
+            let protocol = endpoint.protocol();
+            if RESOLVED_PROTOCOLS.contains(&protocol.as_str()) {
+                let address = endpoint.address();
+                match address.as_str().to_socket_addrs().await {
+                    Ok(mut addrs) => {
+                        if addrs.next().is_none() {
+                            report.push(
+                                ValidationKind::Connect,
+                                Some(endpoint),
+                                format!("{address} resolves to no address"),
+                            );
+                        }
+                    }
+                    Err(e) => report.push(
+                        ValidationKind::Connect,
+                        Some(endpoint),
+                        format!("Unable to resolve {address}: {e}"),
+                    ),
+                }
+            }
+        }
+
+        report
+    }
+
+    /// Checks that the files referenced by the configuration of the given endpoint, e.g. its
+    /// TLS certificates, can be read.
+    async fn check_files(&self, endpoint: &EndPoint) -> ZResult<()> {
+        let protocol = endpoint.protocol();
+        let defaults = self
+            .manager()
+            .config
+            .endpoints
+            .get(protocol.as_str())
+            .map(|c| c.as_str())
+            .unwrap_or_default();
+        let config = endpoint.config();
+        let files = config
+            .iter()
+            .chain(endpoint::Parameters::iter(defaults))
+            .filter(|(k, _)| k.ends_with("_file"));
+        for (key, path) in files {
+            if let Err(e) = async_std::fs::metadata(path).await {
+                bail!("Unable to read {} `{}`: {}", key, path, e);
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,152 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use zenoh_core::zlock;
+use zenoh_protocol::core::{WhatAmI, ZenohId};
+use zenoh_transport::TransportCloseReason;
+
+/// How long the reason of a closed or rejected transport is reported in the admin space.
+const CLOSED_TRANSPORTS_GRACE: Duration = Duration::from_secs(60);
+
+/// The maximum number of closed or rejected transports reported in the admin space, above which
+/// the oldest ones are forgotten.
+const CLOSED_TRANSPORTS_MAX: usize = 256;
+
+/// A transport closed or rejected by the runtime, see [`ClosedTransports`].
+#[derive(Clone)]
+pub(crate) struct ClosedTransport {
+    pub(crate) whatami: WhatAmI,
+    pub(crate) reason: TransportCloseReason,
+    at: Instant,
+}
+
+/// The transports closed or rejected recently, reported in the admin space until
+/// [`CLOSED_TRANSPORTS_GRACE`] elapsed or a new transport is opened with the peer.
+///
+/// At most [`CLOSED_TRANSPORTS_MAX`] transports are kept, so that a storm of rejected peers
+/// doesn't grow the list without bound.
+pub(crate) struct ClosedTransports {
+    entries: Mutex<HashMap<ZenohId, ClosedTransport>>,
+}
+
+impl ClosedTransports {
+    pub(crate) fn new() -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Records the reason of a transport closed or rejected at `now`.
+    pub(crate) fn record(
+        &self,
+        zid: ZenohId,
+        whatami: WhatAmI,
+        reason: &TransportCloseReason,
+        now: Instant,
+    ) {
+        let mut entries = zlock!(self.entries);
+        Self::expire(&mut entries, now);
+        entries.insert(
+            zid,
+            ClosedTransport {
+                whatami,
+                reason: reason.clone(),
+                at: now,
+            },
+        );
+        while entries.len() > CLOSED_TRANSPORTS_MAX {
+            let oldest = entries
+                .iter()
+                .min_by_key(|(_, c)| c.at)
+                .map(|(zid, _)| *zid);
+            match oldest {
+                Some(oldest) => entries.remove(&oldest),
+                None => break,
+            };
+        }
+    }
+
+    /// Forgets the transport closed with a peer, once a new transport is opened with it.
+    pub(crate) fn reopened(&self, zid: &ZenohId) {
+        zlock!(self.entries).remove(zid);
+    }
+
+    /// The transports closed or rejected for less than [`CLOSED_TRANSPORTS_GRACE`] at `now`.
+    pub(crate) fn get(&self, now: Instant) -> Vec<(ZenohId, ClosedTransport)> {
+        let mut entries = zlock!(self.entries);
+        Self::expire(&mut entries, now);
+        entries.iter().map(|(zid, c)| (*zid, c.clone())).collect()
+    }
+
+    fn expire(entries: &mut HashMap<ZenohId, ClosedTransport>, now: Instant) {
+        entries.retain(|_, c| now.saturating_duration_since(c.at) < CLOSED_TRANSPORTS_GRACE);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zenoh_protocol::transport::close::CloseReason;
+
+    fn zid(id: u16) -> ZenohId {
+        let [lo, hi] = id.to_le_bytes();
+        ZenohId::try_from([lo, hi, 1]).unwrap()
+    }
+
+    #[test]
+    fn closed_transports_expire() {
+        let closed = ClosedTransports::new();
+        let reason = TransportCloseReason::local(CloseReason::Expired, None);
+        let now = Instant::now();
+
+        closed.record(zid(1), WhatAmI::Peer, &reason, now);
+        closed.record(
+            zid(2),
+            WhatAmI::Client,
+            &reason,
+            now + Duration::from_secs(30),
+        );
+        assert_eq!(closed.get(now + Duration::from_secs(30)).len(), 2);
+
+        // The reopened transports and the expired ones are forgotten
+        closed.reopened(&zid(2));
+        assert_eq!(closed.get(now + Duration::from_secs(30)).len(), 1);
+        assert!(closed.get(now + CLOSED_TRANSPORTS_GRACE).is_empty());
+        assert!(zlock!(closed.entries).is_empty());
+    }
+
+    #[test]
+    fn closed_transports_bounded() {
+        let closed = ClosedTransports::new();
+        let reason = TransportCloseReason::local(CloseReason::MaxSessions, None);
+        let now = Instant::now();
+
+        let count = CLOSED_TRANSPORTS_MAX as u16 + 10;
+        for id in 1..=count {
+            let at = now + Duration::from_millis(id.into());
+            closed.record(zid(id), WhatAmI::Client, &reason, at);
+        }
+
+        // The oldest transports are forgotten first
+        let at = now + Duration::from_millis(count.into());
+        let mut zids: Vec<ZenohId> = closed.get(at).into_iter().map(|(zid, _)| zid).collect();
+        assert_eq!(zids.len(), CLOSED_TRANSPORTS_MAX);
+        zids.sort();
+        let mut expected: Vec<ZenohId> = (11..=count).map(zid).collect();
+        expected.sort();
+        assert_eq!(zids, expected);
+    }
+}
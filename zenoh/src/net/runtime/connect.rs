@@ -0,0 +1,219 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use zenoh_link::EndPoint;
+use zenoh_result::ZResult;
+
+struct Preference {
+    endpoint: EndPoint,
+    priority: u8,
+    exclusive: bool,
+}
+
+/// The order in which the configured connect endpoints are connected to, given by their
+/// `priority` and `exclusive` metadata, see [`EndPoint::connect_priority`] and
+/// [`EndPoint::is_connect_exclusive`].
+///
+/// The endpoints are ranked by priority then by their position in the configuration.
+pub(super) struct ConnectPlan {
+    preferences: Vec<Preference>,
+}
+
+impl ConnectPlan {
+    pub(super) fn new(endpoints: &[EndPoint]) -> ZResult<Self> {
+        let mut preferences = endpoints
+            .iter()
+            .map(|endpoint| {
+                Ok(Preference {
+                    endpoint: endpoint.clone(),
+                    priority: endpoint.connect_priority()?,
+                    exclusive: endpoint.is_connect_exclusive()?,
+                })
+            })
+            .collect::<ZResult<Vec<Preference>>>()?;
+        // The sort is stable, keeping the configuration order within a priority
+        preferences.sort_by_key(|p| p.priority);
+        Ok(Self { preferences })
+    }
+
+    /// Whether some endpoints are preferred to others, or exclusive. Otherwise all the endpoints
+    /// are connected to at once, as equals.
+    pub(super) fn is_prioritized(&self) -> bool {
+        self.preferences.iter().any(|p| p.exclusive)
+            || self
+                .preferences
+                .windows(2)
+                .any(|w| w[0].priority != w[1].priority)
+    }
+
+    /// The endpoints grouped by priority, the preferred ones first.
+    pub(super) fn groups(&self) -> Vec<Vec<EndPoint>> {
+        self.grouped(|_| true)
+    }
+
+    fn grouped<F: Fn(usize) -> bool>(&self, filter: F) -> Vec<Vec<EndPoint>> {
+        let mut groups: Vec<(u8, Vec<EndPoint>)> = vec![];
+        for (rank, p) in self.preferences.iter().enumerate() {
+            if !filter(rank) {
+                continue;
+            }
+            match groups.last_mut() {
+                Some((priority, group)) if *priority == p.priority => {
+                    group.push(p.endpoint.clone())
+                }
+                _ => groups.push((p.priority, vec![p.endpoint.clone()])),
+            }
+        }
+        groups.into_iter().map(|(_, group)| group).collect()
+    }
+
+    fn rank(&self, endpoint: &EndPoint) -> Option<usize> {
+        self.preferences
+            .iter()
+            .position(|p| p.endpoint == *endpoint)
+    }
+
+    // The rank of the most preferred connected exclusive endpoint
+    fn exclusive_rank(&self, connected: &[EndPoint]) -> Option<usize> {
+        connected
+            .iter()
+            .filter_map(|e| self.rank(e))
+            .filter(|rank| self.preferences[*rank].exclusive)
+            .min()
+    }
+
+    /// The endpoints worth connecting to given the `connected` ones, grouped by priority, the
+    /// preferred ones first: the endpoints that aren't less preferred than the connected ones,
+    /// without the exclusive endpoints less preferred than a connected exclusive one.
+    pub(super) fn candidates(&self, connected: &[EndPoint]) -> Vec<Vec<EndPoint>> {
+        let best = connected
+            .iter()
+            .filter_map(|e| self.rank(e))
+            .map(|rank| self.preferences[rank].priority)
+            .min()
+            .unwrap_or(u8::MAX);
+        let exclusive = self.exclusive_rank(connected).unwrap_or(usize::MAX);
+        self.grouped(|rank| {
+            let p = &self.preferences[rank];
+            p.priority <= best
+                && !(p.exclusive && rank >= exclusive)
+                && !connected.contains(&p.endpoint)
+        })
+    }
+
+    /// The `connected` endpoints whose transport has to be closed: all the exclusive ones but the
+    /// most preferred one.
+    pub(super) fn to_close(&self, connected: &[EndPoint]) -> Vec<EndPoint> {
+        let exclusive = match self.exclusive_rank(connected) {
+            Some(rank) => rank,
+            None => return vec![],
+        };
+        connected
+            .iter()
+            .filter(|e| match self.rank(e) {
+                Some(rank) => self.preferences[rank].exclusive && rank != exclusive,
+                None => false,
+            })
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn endpoints(endpoints: &[&str]) -> Vec<EndPoint> {
+        endpoints.iter().map(|e| e.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn connect_plan_groups() {
+        let plan = ConnectPlan::new(&endpoints(&["tcp/127.0.0.1:1", "tcp/127.0.0.1:2"])).unwrap();
+        assert!(!plan.is_prioritized());
+        assert_eq!(plan.groups().len(), 1);
+
+        let [a, b, c, d]: [EndPoint; 4] = endpoints(&[
+            "tcp/127.0.0.1:1?priority=3",
+            "tcp/127.0.0.1:2",
+            "tcp/127.0.0.1:3?priority=2",
+            "tcp/127.0.0.1:4?priority=3",
+        ])
+        .try_into()
+        .unwrap();
+        let plan = ConnectPlan::new(&[a.clone(), b.clone(), c.clone(), d.clone()]).unwrap();
+        assert!(plan.is_prioritized());
+        assert_eq!(plan.groups(), vec![vec![b], vec![c], vec![a, d]]);
+
+        assert!(ConnectPlan::new(&endpoints(&["tcp/127.0.0.1:1?priority=0"])).is_err());
+    }
+
+    #[test]
+    fn connect_plan_fallback() {
+        let [a, b, c]: [EndPoint; 3] = endpoints(&[
+            "tcp/127.0.0.1:1",
+            "tcp/127.0.0.1:2",
+            "tcp/127.0.0.1:3?priority=2",
+        ])
+        .try_into()
+        .unwrap();
+        let plan = ConnectPlan::new(&[a.clone(), b.clone(), c.clone()]).unwrap();
+
+        // Nothing connected: all the groups, in order
+        assert_eq!(
+            plan.candidates(&[]),
+            vec![vec![a.clone(), b.clone()], vec![c.clone()]]
+        );
+        // Connected to the fallback: the preferred ones
+        assert_eq!(
+            plan.candidates(&[c.clone()]),
+            vec![vec![a.clone(), b.clone()]]
+        );
+        // Connected to a preferred one: the other preferred ones, the fallback being kept
+        assert_eq!(plan.candidates(&[a.clone(), c.clone()]), vec![vec![b]]);
+        assert!(plan.to_close(&[a, c]).is_empty());
+    }
+
+    #[test]
+    fn connect_plan_exclusive() {
+        let [a, b, c, d]: [EndPoint; 4] = endpoints(&[
+            "tcp/127.0.0.1:1?exclusive=true",
+            "tcp/127.0.0.1:2?exclusive=true",
+            "tcp/127.0.0.1:3?priority=2;exclusive=true",
+            "tcp/127.0.0.1:4?priority=2",
+        ])
+        .try_into()
+        .unwrap();
+        let plan = ConnectPlan::new(&[a.clone(), b.clone(), c.clone(), d.clone()]).unwrap();
+
+        // Connected to the exclusive fallback: the preferred ones, and the other fallback
+        assert_eq!(
+            plan.candidates(&[c.clone()]),
+            vec![vec![a.clone(), b.clone()], vec![d.clone()]]
+        );
+        // Connected to the second exclusive one: the first one, the fallback not being needed
+        assert_eq!(plan.candidates(&[b.clone()]), vec![vec![a.clone()]]);
+        assert!(plan.candidates(&[a.clone()]).is_empty());
+
+        // Only the most preferred exclusive transport is kept
+        assert_eq!(
+            plan.to_close(&[c.clone(), d.clone(), b.clone()]),
+            vec![c.clone()]
+        );
+        assert_eq!(
+            plan.to_close(&[b.clone(), a.clone(), c.clone()]),
+            vec![b, c]
+        );
+        assert!(plan.to_close(&[a, d]).is_empty());
+    }
+}
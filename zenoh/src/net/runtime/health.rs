@@ -0,0 +1,233 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use super::{Runtime, RuntimeSession};
+use crate::config::unwrap_or_default;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::Ordering;
+use std::time::SystemTime;
+use zenoh_link::EndPoint;
+use zenoh_protocol::core::WhatAmI;
+
+/// The overall health of a [`Runtime`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum HealthStatus {
+    /// All the indicators are below their thresholds.
+    Ok,
+    /// The runtime is operational but some indicators reached their degraded threshold.
+    Degraded,
+    /// The runtime can not be expected to be operational.
+    Failing,
+}
+
+/// The listeners of a [`Runtime`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ListenersHealth {
+    /// The endpoints the runtime should listen on.
+    pub configured: Vec<EndPoint>,
+    /// The endpoints the runtime actually listens on.
+    pub bound: Vec<EndPoint>,
+}
+
+/// The state of an endpoint of the `connect` configuration.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConnectHealth {
+    pub endpoint: EndPoint,
+    /// Whether a transport opened on this endpoint is currently alive.
+    pub established: bool,
+}
+
+/// The state of a plugin of the `plugins` configuration.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PluginHealth {
+    pub name: String,
+    pub running: bool,
+}
+
+/// The pressure on the accept queue of the incoming unicast links.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AcceptHealth {
+    /// The number of incoming links currently in the accept phase.
+    pub pending: usize,
+    /// The maximum number of incoming links in the accept phase (`transport/unicast/accept_pending`).
+    pub limit: usize,
+}
+
+/// A summary of the health of a [`Runtime`], as returned by [`Runtime::health`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HealthReport {
+    pub status: HealthStatus,
+    /// The reasons why the status is not [`HealthStatus::Ok`].
+    pub reasons: Vec<String>,
+    pub listeners: ListenersHealth,
+    pub connect: Vec<ConnectHealth>,
+    /// The plugins requested in the configuration, only reported when the plugins are managed
+    /// by the admin space of the runtime.
+    pub plugins: Vec<PluginHealth>,
+    pub accept: AcceptHealth,
+    /// The time of the last timestamp issued by [`Runtime::new_timestamp`].
+    pub last_timestamp: Option<SystemTime>,
+}
+
+impl HealthReport {
+    fn degrade(&mut self, status: HealthStatus, reason: String) {
+        self.status = self.status.max(status);
+        self.reasons.push(reason);
+    }
+}
+
+impl Runtime {
+    /// Returns a summary of the health of the runtime, classified against the thresholds
+    /// of the `health` configuration.
+    ///
+    /// The report only takes short-lived locks and is cheap enough to back liveness
+    /// and readiness probes.
+    pub async fn health(&self) -> HealthReport {
+        let (
+            configured,
+            endpoints,
+            requested_plugins,
+            pressure_degraded,
+            pressure_failing,
+            failures_degraded,
+        ) = {
+            let guard = self.config.lock();
+            (
+                self.listeners(&guard),
+                guard.connect().endpoints().clone(),
+                guard
+                    .plugins()
+                    .load_requests()
+                    .map(|request| request.name)
+                    .collect::<Vec<_>>(),
+                unwrap_or_default!(guard.health().accept_pressure_degraded()),
+                unwrap_or_default!(guard.health().accept_pressure_failing()),
+                unwrap_or_default!(guard.health().connect_failures_degraded()),
+            )
+        };
+
+        let mut bound = self.manager().get_listeners_unicast().await;
+        bound.extend(self.manager().get_listeners_multicast().await);
+
+        let transports = self.manager().get_transports_unicast().await;
+        let established = transports
+            .iter()
+            .filter_map(|transport| {
+                let callback = transport.get_callback().ok()??;
+                callback
+                    .as_any()
+                    .downcast_ref::<RuntimeSession>()
+                    .and_then(|session| zread!(session.endpoint).clone())
+            })
+            .collect::<Vec<_>>();
+        let connect = endpoints
+            .into_iter()
+            .map(|endpoint| ConnectHealth {
+                established: established.contains(&endpoint),
+                endpoint,
+            })
+            .collect::<Vec<_>>();
+
+        let plugins = match &*zread!(self.running_plugins) {
+            Some(running) => requested_plugins
+                .into_iter()
+                .map(|name| PluginHealth {
+                    running: running.contains(&name),
+                    name,
+                })
+                .collect(),
+            None => vec![],
+        };
+
+        let accept = AcceptHealth {
+            pending: self.manager().get_incoming_pending_unicast().await,
+            limit: self.manager().config.unicast.accept_pending,
+        };
+
+        let last_timestamp = match self.last_timestamp.load(Ordering::Relaxed) {
+            0 => None,
+            time => Some(uhlc::NTP64(time).to_system_time()),
+        };
+
+        let mut report = HealthReport {
+            status: HealthStatus::Ok,
+            reasons: vec![],
+            listeners: ListenersHealth { configured, bound },
+            connect,
+            plugins,
+            accept,
+            last_timestamp,
+        };
+
+        if report.listeners.bound.len() < report.listeners.configured.len() {
+            let status = if report.listeners.bound.is_empty() {
+                HealthStatus::Failing
+            } else {
+                HealthStatus::Degraded
+            };
+            let reason = format!(
+                "{} of the {} configured listeners are not bound",
+                report.listeners.configured.len() - report.listeners.bound.len(),
+                report.listeners.configured.len()
+            );
+            report.degrade(status, reason);
+        }
+
+        let failures = report.connect.iter().filter(|c| !c.established).count();
+        if self.whatami == WhatAmI::Client {
+            // A client only connects to one of its configured endpoints
+            if transports.is_empty() {
+                report.degrade(
+                    HealthStatus::Failing,
+                    "no connection to any router or peer".to_string(),
+                );
+            }
+        } else if failures_degraded > 0 && failures >= failures_degraded {
+            let reason = format!(
+                "{} of the {} configured connect endpoints are not established",
+                failures,
+                report.connect.len()
+            );
+            report.degrade(HealthStatus::Degraded, reason);
+        }
+
+        for plugin in report.plugins.clone() {
+            if !plugin.running {
+                report.degrade(
+                    HealthStatus::Degraded,
+                    format!("plugin `{}` is not running", plugin.name),
+                );
+            }
+        }
+
+        if report.accept.pending > 0 && report.accept.limit > 0 {
+            let pressure = report.accept.pending * 100 / report.accept.limit;
+            let status = if pressure >= pressure_failing as usize {
+                Some(HealthStatus::Failing)
+            } else if pressure >= pressure_degraded as usize {
+                Some(HealthStatus::Degraded)
+            } else {
+                None
+            };
+            if let Some(status) = status {
+                let reason = format!(
+                    "{} of the {} accept slots are pending",
+                    report.accept.pending, report.accept.limit
+                );
+                report.degrade(status, reason);
+            }
+        }
+
+        report
+    }
+}
@@ -17,48 +17,145 @@
 //! This module is intended for Zenoh's internal use.
 //!
 //! [Click here for Zenoh's documentation](../zenoh/index.html)
+mod addresses;
 mod adminspace;
+mod advertise;
+mod check;
+mod closed;
+mod connect;
+mod health;
 pub mod orchestrator;
+mod reconnect;
+mod topology;
 
 use super::routing;
-use super::routing::face::Face;
-use super::routing::pubsub::full_reentrant_route_data;
-use super::routing::router::{LinkStateInterceptor, Router};
+pub use super::routing::audit::{
+    AuditEvent, AuditOperation, AuditOutcome, AuditSink, AuthIdentity, FileAuditSink,
+};
+use super::routing::audit::{AuditLimits, Auditor, PublicationAuditSink};
+use super::routing::deadletter::{DeadLetter, DeadLetters};
+use super::routing::dedup::Deduplication;
+use super::routing::mappings::MappingLimits;
+use super::routing::mutation::Mutations;
+pub use super::routing::mutation::SampleMutation;
+use super::routing::namespace::Namespace;
+use super::routing::querylimit::{QueryLimitPolicy, QueryLimits};
+use super::routing::querythrottle::QueryThrottle;
+use super::routing::ratelimit::DeclarationRate;
+use super::routing::refresh::DeclarationRefresh;
+use super::routing::router::{LinkStateInterceptor, MulticastPeerInterceptor, Router};
+use super::routing::trace::QueryTracer;
 use crate::config::{unwrap_or_default, Config, ModeDependent, Notifier};
+use crate::handlers::contain;
+use crate::info::ConnectivityEvent;
+use crate::integrity::Integrity;
+use crate::plugins::api::StatusCallback;
 use crate::GIT_VERSION;
+pub use addresses::{AddressProvider, SystemAddresses};
 pub use adminspace::AdminSpace;
+pub use advertise::LocatorsRewriter;
+use advertise::RewriteRules;
 use async_std::task::JoinHandle;
+pub use check::{ValidationKind, ValidationProblem, ValidationReport};
+use closed::{ClosedTransport, ClosedTransports};
 use futures::stream::StreamExt;
 use futures::Future;
+pub use health::{
+    AcceptHealth, ConnectHealth, HealthReport, HealthStatus, ListenersHealth, PluginHealth,
+};
+use reconnect::Reconnection;
 use std::any::Any;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 use stop_token::future::FutureExt;
 use stop_token::{StopSource, TimedOutError};
+use topology::Topology;
+pub use topology::{TopologyEvent, TopologySnapshot, TopologySubscriber};
 use uhlc::{HLCBuilder, HLC};
 use zenoh_link::{EndPoint, Link};
-use zenoh_protocol::core::{whatami::WhatAmIMatcher, Locator, WhatAmI, ZenohId};
-use zenoh_protocol::network::{NetworkBody, NetworkMessage};
+use zenoh_protocol::core::{
+    key_expr::OwnedKeyExpr, whatami::WhatAmIMatcher, EntityId, Locator, WhatAmI, ZenohId,
+};
+use zenoh_protocol::core::{CongestionControl, KnownEncoding, Reliability};
+use zenoh_protocol::network::{push, NetworkBody, NetworkMessage, Push};
+use zenoh_protocol::transport::close::CloseReason;
+use zenoh_protocol::zenoh::{PushBody, Put};
 use zenoh_result::{bail, ZResult};
 use zenoh_sync::get_mut_unchecked;
 use zenoh_transport::{
-    DeMux, TransportEventHandler, TransportManager, TransportMulticast,
-    TransportMulticastEventHandler, TransportPeer, TransportPeerEventHandler, TransportUnicast,
+    DummyPrimitives, Primitives, TransportCloseReason, TransportEventHandler, TransportManager,
+    TransportMulticast, TransportMulticastEventHandler, TransportPeer, TransportPeerEventHandler,
+    TransportUnicast,
 };
+use zenoh_util::clock::{Clock, SystemClock};
+#[cfg(debug_assertions)]
+use zenoh_util::cooperate::StallWatchdog;
 
 pub struct RuntimeState {
     pub zid: ZenohId,
     pub whatami: WhatAmI,
-    pub metadata: serde_json::Value,
-    pub router: Arc<Router>,
+    pub(crate) metadata: serde_json::Value,
+    pub(crate) router: Arc<Router>,
     pub config: Notifier<Config>,
-    pub manager: TransportManager,
-    pub transport_handlers: std::sync::RwLock<Vec<Arc<dyn TransportEventHandler>>>,
+    pub(crate) manager: TransportManager,
+    pub(crate) transport_handlers: std::sync::RwLock<Vec<Arc<dyn TransportEventHandler>>>,
     pub(crate) locators: std::sync::RwLock<Vec<Locator>>,
-    pub hlc: Option<Arc<HLC>>,
+    /// The rewriting of the advertised locators, applied in order.
+    pub(crate) locators_rewriters: std::sync::RwLock<Vec<Arc<dyn LocatorsRewriter>>>,
+    /// The provider of the addresses of the local network interfaces, watched by the runtime.
+    pub(crate) address_provider: std::sync::RwLock<Arc<dyn AddressProvider>>,
+    pub(crate) hlc: Option<Arc<HLC>>,
+    pub(crate) wildcard_updates: bool,
+    pub(crate) local_routing: bool,
+    /// The integrity checksums of the payloads published and received by the sessions.
+    pub(crate) integrity: Integrity,
+    /// The plugins started by the admin space, if it manages the plugins of the runtime.
+    pub(crate) running_plugins: std::sync::RwLock<Option<Vec<String>>>,
+    /// The status callbacks set by the plugins, see [`crate::plugins::api::PluginContext::set_status`].
+    pub(crate) plugins_status: std::sync::RwLock<HashMap<String, StatusCallback>>,
+    pub(crate) last_timestamp: AtomicU64,
+    /// The clock driving the timers of the runtime and of its sessions.
+    pub(crate) clock: Arc<dyn Clock>,
+    dead_letter_handlers: std::sync::RwLock<Vec<DeadLetterHandler>>,
+    /// The sinks recording the audit events, if the `routing/audit` configuration enables them.
+    audit_sinks: std::sync::RwLock<Vec<Arc<dyn AuditSink>>>,
+    connectivity_handlers: std::sync::RwLock<Vec<(EntityId, ConnectivityHandler)>>,
+    /// The transports closed or rejected recently, reported in the admin space.
+    closed_transports: ClosedTransports,
+    /// The routers and peers connected to the runtime, notified to its topology subscribers.
+    pub(crate) topology: Topology,
+    /// The reconnection of a client runtime that lost its transports to the routers and peers.
+    pub(crate) reconnection: Reconnection,
+    next_id: AtomicU32,
     pub(crate) stop_source: std::sync::RwLock<Option<StopSource>>,
+    /// The detector of the executor stalls in debug builds, stopped when closing the runtime.
+    #[cfg(debug_assertions)]
+    stall_watchdog: std::sync::Mutex<Option<StallWatchdog>>,
 }
 
+/// A callback notified of the data messages dropped without reaching any destination,
+/// see [`Runtime::on_dead_letter`].
+pub type DeadLetterHandler = Arc<dyn Fn(&DeadLetter) + Send + Sync>;
+
+/// A callback notified of the transports opened, closed and rejected by the remote nodes,
+/// see [`Runtime::on_connectivity_event`].
+pub type ConnectivityHandler = Arc<dyn Fn(&ConnectivityEvent) + Send + Sync>;
+
+/// The maximum number of dead letter notifications waiting to be published.
+const DEAD_LETTERS_QUEUE: usize = 1024;
+
+/// The maximum number of audit events waiting to be recorded by the sinks.
+const AUDIT_QUEUE: usize = 4096;
+
+/// The period of the timer measuring the scheduling latency of the executor in debug builds.
+#[cfg(debug_assertions)]
+const STALL_WATCHDOG_PERIOD: Duration = Duration::from_millis(10);
+/// The scheduling latency above which the executor is reported as stalled in debug builds.
+#[cfg(debug_assertions)]
+const STALL_WATCHDOG_THRESHOLD: Duration = Duration::from_millis(50);
+
 #[derive(Clone)]
 pub struct Runtime {
     state: Arc<RuntimeState>,
@@ -74,14 +171,33 @@ impl std::ops::Deref for Runtime {
 
 impl Runtime {
     pub async fn new(config: Config) -> ZResult<Runtime> {
-        let mut runtime = Runtime::init(config).await?;
+        Runtime::new_with_clock(config, Arc::new(SystemClock)).await
+    }
+
+    /// Creates a [`Runtime`] whose transport timers and HLC are driven by the given [`Clock`].
+    pub async fn new_with_clock(config: Config, clock: Arc<dyn Clock>) -> ZResult<Runtime> {
+        let mut runtime = Runtime::init(config, clock).await?;
         match runtime.start().await {
             Ok(()) => Ok(runtime),
             Err(err) => Err(err),
         }
     }
 
-    pub(crate) async fn init(config: Config) -> ZResult<Runtime> {
+    /// Builds a [`Runtime`] without starting it: its listeners are probed, its TLS material is
+    /// loaded and the addresses of its connect endpoints are resolved, failing with all the
+    /// problems found, see [`Runtime::check`]. The runtime begins listening and connecting
+    /// when calling [`Runtime::start`].
+    pub async fn build(config: Config) -> ZResult<Runtime> {
+        let runtime = Runtime::init(config, Arc::new(SystemClock)).await?;
+        let report = runtime.validate().await;
+        if !report.is_ok() {
+            let _ = runtime.close().await;
+            bail!("Invalid configuration: {}", report);
+        }
+        Ok(runtime)
+    }
+
+    pub(crate) async fn init(config: Config, clock: Arc<dyn Clock>) -> ZResult<Runtime> {
         log::debug!("Zenoh Rust API {}", GIT_VERSION);
         // Make sure to have have enough threads spawned in the async futures executor
         zasync_executor_init!();
@@ -92,8 +208,14 @@ impl Runtime {
 
         let whatami = unwrap_or_default!(config.mode());
         let metadata = config.metadata().clone();
-        let hlc = (*unwrap_or_default!(config.timestamping().enabled().get(whatami)))
-            .then(|| Arc::new(HLCBuilder::new().with_id(uhlc::ID::from(&zid)).build()));
+        let hlc = (*unwrap_or_default!(config.timestamping().enabled().get(whatami))).then(|| {
+            Arc::new(
+                HLCBuilder::new()
+                    .with_id(uhlc::ID::from(&zid))
+                    .with_clock(clock.hlc_clock())
+                    .build(),
+            )
+        });
         let drop_future_timestamp =
             unwrap_or_default!(config.timestamping().drop_future_timestamp());
 
@@ -112,6 +234,14 @@ impl Runtime {
             unwrap_or_default!(config.routing().router().peers_failover_brokering());
         let queries_default_timeout =
             Duration::from_millis(unwrap_or_default!(config.queries_default_timeout()));
+        let wildcard_updates = unwrap_or_default!(config.wildcard_updates());
+        let local_routing = unwrap_or_default!(config.local_routing());
+        let integrity = Integrity::from_config(&config);
+        let address_watch = unwrap_or_default!(config.listen().address_watch().enabled());
+        let address_watch_period =
+            Duration::from_millis(unwrap_or_default!(config.listen().address_watch().period()));
+        let rewrite_rules: Arc<dyn LocatorsRewriter> =
+            Arc::new(RewriteRules::new(config.advertise().rewrite()));
 
         let router = Arc::new(Router::new(
             zid,
@@ -122,6 +252,108 @@ impl Runtime {
             queries_default_timeout,
         ));
 
+        zwrite!(router.tables.tables).full_routes_recomputation =
+            unwrap_or_default!(config.routing().full_recomputation());
+        zwrite!(router.tables.tables).namespaces = Namespace::from_config(config.namespaces())
+            .into_iter()
+            .collect();
+        zwrite!(router.tables.tables).deduplication =
+            Deduplication::new(config.deduplication(), clock.clone());
+        zwrite!(router.tables.tables).mutations =
+            Mutations::new(zid, config.mutations()).map(Arc::new);
+        if unwrap_or_default!(config.routing().face().declaration_rate().enabled()) {
+            zwrite!(router.tables.tables).declaration_rate = Some(DeclarationRate {
+                rate: unwrap_or_default!(config.routing().face().declaration_rate().rate()),
+                burst: unwrap_or_default!(config.routing().face().declaration_rate().burst()),
+                queue: unwrap_or_default!(config.routing().face().declaration_rate().queue()),
+                clock: clock.clone(),
+            });
+        }
+        zwrite!(router.tables.tables).mapping_limits = Some(MappingLimits {
+            max_local: *config.limits().keyexpr_mappings().max_local(),
+            remote_alarm: unwrap_or_default!(config.limits().keyexpr_mappings().remote_alarm()),
+            grace_period: Duration::from_millis(unwrap_or_default!(config
+                .limits()
+                .keyexpr_mappings()
+                .grace_period())),
+            clock: clock.clone(),
+        });
+        if unwrap_or_default!(config.routing().declaration_refresh().enabled()) {
+            zwrite!(router.tables.tables).declaration_refresh = Some(DeclarationRefresh {
+                period: Duration::from_millis(unwrap_or_default!(config
+                    .routing()
+                    .declaration_refresh()
+                    .period())),
+                buckets: unwrap_or_default!(config.routing().declaration_refresh().buckets()),
+            });
+        }
+        if unwrap_or_default!(config.routing().query().limits().enabled()) {
+            let policy: String = unwrap_or_default!(config.routing().query().limits().policy());
+            zwrite!(router.tables.tables).query_limits = Some(Arc::new(QueryLimits::new(
+                unwrap_or_default!(config.routing().query().limits().per_face()),
+                unwrap_or_default!(config.routing().query().limits().total()),
+                QueryLimitPolicy::from_config(&policy)?,
+            )));
+        }
+        if unwrap_or_default!(config.routing().query().throttle().enabled()) {
+            zwrite!(router.tables.tables).query_throttle = Some(QueryThrottle::new(
+                config.routing().query().throttle().rules(),
+                unwrap_or_default!(config.routing().query().throttle().max_subjects()),
+                clock.clone(),
+            )?);
+        }
+        if unwrap_or_default!(config.routing().query_tracing().enabled()) {
+            zwrite!(router.tables.tables).query_tracer =
+                Some(Arc::new(QueryTracer::new(unwrap_or_default!(config
+                    .routing()
+                    .query_tracing()
+                    .size()))));
+        }
+
+        let dead_letters =
+            unwrap_or_default!(config.routing().dead_letter().enabled()).then(|| {
+                let (sender, receiver) = flume::bounded(DEAD_LETTERS_QUEUE);
+                zwrite!(router.tables.tables).dead_letters = Some(Arc::new(DeadLetters::new(
+                    Duration::from_millis(unwrap_or_default!(config
+                        .routing()
+                        .dead_letter()
+                        .window())),
+                    clock.clone(),
+                    sender,
+                )));
+                receiver
+            });
+
+        let mut audit_sinks: Vec<Arc<dyn AuditSink>> = vec![];
+        let audit = if unwrap_or_default!(config.routing().audit().enabled()) {
+            let sampling: f64 = unwrap_or_default!(config.routing().audit().sampling());
+            if !(0.0..=1.0).contains(&sampling) {
+                bail!(
+                    "Invalid routing/audit/sampling {}: expected a ratio between 0 and 1",
+                    sampling
+                );
+            }
+            if let Some(path) = config.routing().audit().file().path() {
+                audit_sinks.push(Arc::new(FileAuditSink::new(
+                    path,
+                    unwrap_or_default!(config.routing().audit().file().max_size()),
+                    unwrap_or_default!(config.routing().audit().file().max_files()),
+                )?));
+            }
+            let (sender, receiver) = flume::bounded(AUDIT_QUEUE);
+            zwrite!(router.tables.tables).auditor = Some(Arc::new(Auditor::new(
+                AuditLimits {
+                    sampling,
+                    rate: *config.routing().audit().rate(),
+                    clock: clock.clone(),
+                },
+                sender,
+            )));
+            Some((receiver, config.routing().audit().key_expr().clone()))
+        } else {
+            None
+        };
+
         let handler = Arc::new(RuntimeTransportEventHandler {
             runtime: std::sync::RwLock::new(None),
         });
@@ -131,6 +363,7 @@ impl Runtime {
             .await?
             .whatami(whatami)
             .zid(zid)
+            .clock(clock.clone())
             .build(handler.clone())?;
 
         let config = Notifier::new(config);
@@ -145,8 +378,30 @@ impl Runtime {
                 manager: transport_manager,
                 transport_handlers: std::sync::RwLock::new(vec![]),
                 locators: std::sync::RwLock::new(vec![]),
+                locators_rewriters: std::sync::RwLock::new(vec![rewrite_rules]),
+                address_provider: std::sync::RwLock::new(Arc::new(SystemAddresses)),
                 hlc,
+                wildcard_updates,
+                local_routing,
+                integrity,
+                running_plugins: std::sync::RwLock::new(None),
+                plugins_status: std::sync::RwLock::new(HashMap::new()),
+                last_timestamp: AtomicU64::new(0),
+                clock,
+                dead_letter_handlers: std::sync::RwLock::new(vec![]),
+                audit_sinks: std::sync::RwLock::new(audit_sinks),
+                connectivity_handlers: std::sync::RwLock::new(vec![]),
+                closed_transports: ClosedTransports::new(),
+                topology: Topology::new(),
+                reconnection: Reconnection::default(),
+                // Note: start at 1 because 0 is reserved for the declarations without entity
+                next_id: AtomicU32::new(1),
                 stop_source: std::sync::RwLock::new(Some(StopSource::new())),
+                #[cfg(debug_assertions)]
+                stall_watchdog: std::sync::Mutex::new(Some(StallWatchdog::spawn(
+                    STALL_WATCHDOG_PERIOD,
+                    STALL_WATCHDOG_THRESHOLD,
+                ))),
             }),
         };
         *handler.runtime.write().unwrap() = Some(runtime.clone());
@@ -174,10 +429,150 @@ impl Runtime {
                 }
             }
         });
+        if address_watch {
+            runtime.spawn(runtime.clone().watch_addresses(address_watch_period));
+        }
+        if let Some(receiver) = dead_letters {
+            runtime.spawn(runtime.clone().notify_dead_letters(receiver));
+        }
+        if let Some((receiver, key_expr)) = audit {
+            runtime.spawn(runtime.clone().record_audit_events(receiver, key_expr));
+        }
 
         Ok(runtime)
     }
 
+    // Notifies the dead letters to the handlers and publishes them under `@/router/<zid>/deadletter`.
+    async fn notify_dead_letters(self, receiver: flume::Receiver<DeadLetter>) {
+        let key_expr = format!("@/router/{}/deadletter", self.zid);
+        let face = self.router.new_primitives(Arc::new(DummyPrimitives));
+        while let Ok(dead_letter) = receiver.recv_async().await {
+            let handlers = zread!(self.dead_letter_handlers).clone();
+            for handler in handlers {
+                contain("a dead letter handler", || handler(&dead_letter));
+            }
+            face.send_push(
+                Push {
+                    wire_expr: key_expr.clone().into(),
+                    ext_qos: push::ext::QoSType::push_default(),
+                    ext_tstamp: None,
+                    ext_nodeid: push::ext::NodeIdType::default(),
+                    payload: PushBody::Put(Put {
+                        timestamp: self.new_timestamp(),
+                        encoding: KnownEncoding::AppJson.into(),
+                        ext_sinfo: None,
+                        #[cfg(feature = "shared-memory")]
+                        ext_shm: None,
+                        ext_integrity: None,
+                        ext_batch: None,
+                        ext_unknown: vec![],
+                        payload: dead_letter.to_json().to_string().into_bytes().into(),
+                    }),
+                },
+                Reliability::Reliable,
+            );
+        }
+    }
+
+    // Delivers the audit events to the sinks in order, publishing them onto `key_expr` if any.
+    async fn record_audit_events(
+        self,
+        receiver: flume::Receiver<AuditEvent>,
+        key_expr: Option<OwnedKeyExpr>,
+    ) {
+        if let Some(key_expr) = key_expr {
+            let face = self.router.new_primitives(Arc::new(DummyPrimitives));
+            let sink = PublicationAuditSink::new(face, key_expr, self.hlc.clone());
+            zwrite!(self.audit_sinks).push(Arc::new(sink));
+        }
+        while let Ok(event) = receiver.recv_async().await {
+            // The sinks may block, e.g. writing to a file: the pending events are delivered from
+            // a blocking thread, awaited to keep them in order
+            let events: Vec<AuditEvent> = std::iter::once(event).chain(receiver.drain()).collect();
+            let sinks = zread!(self.audit_sinks).clone();
+            async_std::task::spawn_blocking(move || {
+                for event in &events {
+                    for sink in &sinks {
+                        contain("an audit sink", || sink.record(event));
+                    }
+                }
+            })
+            .await;
+        }
+    }
+
+    /// Adds a sink recording the audit events, if the `routing/audit` configuration enables
+    /// the audit of the operations.
+    pub fn add_audit_sink(&self, sink: Arc<dyn AuditSink>) {
+        zwrite!(self.audit_sinks).push(sink);
+    }
+
+    /// Adds a callback notified of the data messages dropped without reaching any destination,
+    /// if the `routing/dead_letter` configuration enables their notification.
+    pub fn on_dead_letter(&self, handler: DeadLetterHandler) {
+        zwrite!(self.dead_letter_handlers).push(handler);
+    }
+
+    /// Registers a handler notified of the transports opened, closed and rejected by the
+    /// remote nodes, returning the id to unregister it with
+    /// [`Runtime::remove_connectivity_handler`].
+    pub fn on_connectivity_event(&self, handler: ConnectivityHandler) -> EntityId {
+        let id = self.next_id();
+        zwrite!(self.connectivity_handlers).push((id, handler));
+        id
+    }
+
+    /// Unregisters a handler registered with [`Runtime::on_connectivity_event`].
+    pub fn remove_connectivity_handler(&self, id: EntityId) {
+        zwrite!(self.connectivity_handlers).retain(|(i, _)| *i != id);
+    }
+
+    /// Returns a stream of the changes of the topology seen by the runtime, starting from its
+    /// current topology.
+    pub fn topology(&self) -> TopologySubscriber {
+        self.topology.subscribe()
+    }
+
+    pub(crate) fn notify_connectivity(&self, event: ConnectivityEvent) {
+        if let Some(reason) = event.reason().filter(|r| r.reason != CloseReason::Generic) {
+            match &event {
+                ConnectivityEvent::Rejected { endpoint, .. } => {
+                    log::warn!("Alarm: transport to {} rejected: {}", endpoint, reason)
+                }
+                ConnectivityEvent::Disconnected { zid, .. } => {
+                    log::warn!("Alarm: transport with {} closed: {}", zid, reason)
+                }
+                ConnectivityEvent::Connected { .. } => {}
+            }
+        }
+        let handlers = zread!(self.connectivity_handlers).clone();
+        for (_, handler) in handlers.iter() {
+            contain("a connectivity handler", || handler(&event));
+        }
+    }
+
+    /// Notifies the rejection of a transport opened to `endpoint`, if the error of the opening
+    /// is the close of the link by the remote node.
+    pub(crate) fn notify_rejected(&self, endpoint: &EndPoint, error: &zenoh_result::Error) {
+        if let Some(reason) = error.downcast_ref::<TransportCloseReason>() {
+            self.notify_connectivity(ConnectivityEvent::Rejected {
+                endpoint: endpoint.clone(),
+                reason: reason.clone(),
+            });
+        }
+    }
+
+    /// Records the reason of a transport closed or rejected, see [`ClosedTransports`].
+    fn record_closed(&self, zid: ZenohId, whatami: WhatAmI, reason: &TransportCloseReason) {
+        self.closed_transports
+            .record(zid, whatami, reason, self.clock.now());
+    }
+
+    /// The transports closed or rejected recently, with no transport opened with the peer since.
+    pub(crate) fn closed_transports(&self) -> Vec<(ZenohId, ClosedTransport)> {
+        self.closed_transports.get(self.clock.now())
+    }
+
     #[inline(always)]
     pub fn manager(&self) -> &TransportManager {
         &self.manager
@@ -190,16 +585,92 @@ impl Runtime {
     pub async fn close(&self) -> ZResult<()> {
         log::trace!("Runtime::close())");
         drop(self.stop_source.write().unwrap().take());
+        self.reconnection.close();
         self.manager().close().await;
+        self.topology.close();
+        #[cfg(debug_assertions)]
+        drop(self.stall_watchdog.lock().unwrap().take());
         Ok(())
     }
 
+    /// Returns true once the runtime has been closed.
+    pub fn is_closed(&self) -> bool {
+        self.stop_source.read().unwrap().is_none()
+    }
+
+    /// Holds a publication while the client runtime is disconnected from the routers and peers,
+    /// according to its `congestion_control`: blocks until the runtime is reconnected or closed
+    /// with [`CongestionControl::Block`], returns whether the publication has to be dropped with
+    /// [`CongestionControl::Drop`].
+    pub(crate) fn hold_publication(&self, congestion_control: CongestionControl) -> bool {
+        match congestion_control {
+            CongestionControl::Block => {
+                self.reconnection.wait();
+                false
+            }
+            CongestionControl::Drop => self.reconnection.is_disconnected(),
+        }
+    }
+
+    /// Holds a publication like [`hold_publication`](Self::hold_publication), waiting for the
+    /// reconnection without blocking the executor.
+    pub(crate) async fn hold_publication_async(
+        &self,
+        congestion_control: CongestionControl,
+    ) -> bool {
+        match congestion_control {
+            CongestionControl::Block => {
+                self.reconnection.wait_async().await;
+                false
+            }
+            CongestionControl::Drop => self.reconnection.is_disconnected(),
+        }
+    }
+
     pub fn new_timestamp(&self) -> Option<uhlc::Timestamp> {
-        self.hlc.as_ref().map(|hlc| hlc.new_timestamp())
+        self.hlc.as_ref().map(|hlc| {
+            let timestamp = hlc.new_timestamp();
+            self.last_timestamp
+                .store(timestamp.get_time().as_u64(), Ordering::Relaxed);
+            timestamp
+        })
+    }
+
+    /// Returns a new [`EntityId`], unique among the entities declared by the sessions of this runtime.
+    pub fn next_id(&self) -> EntityId {
+        self.next_id.fetch_add(1, Ordering::SeqCst)
     }
 
+    /// Returns the locators advertised by this runtime: the locators it listens on, rewritten by
+    /// the `advertise/rewrite` configuration and the [`LocatorsRewriter`]s of the runtime.
     pub fn get_locators(&self) -> Vec<Locator> {
-        self.locators.read().unwrap().clone()
+        let locators = self.locators.read().unwrap().clone();
+        self.locators_rewriters
+            .read()
+            .unwrap()
+            .iter()
+            .fold(locators, |locators, rewriter| rewriter.rewrite(locators))
+    }
+
+    /// Replaces the [`AddressProvider`] watched to remove and bind again the listeners when the
+    /// addresses of the local network interfaces change, see the `listen/address_watch` configuration.
+    pub fn set_address_provider(&self, provider: Arc<dyn AddressProvider>) {
+        *zwrite!(self.address_provider) = provider;
+    }
+
+    /// Adds a [`LocatorsRewriter`], applied after the `advertise/rewrite` configuration and
+    /// the previously added ones.
+    pub fn add_locators_rewriter(&self, rewriter: Arc<dyn LocatorsRewriter>) {
+        self.locators_rewriters.write().unwrap().push(rewriter);
+    }
+
+    /// Registers the [`SampleMutation`] applied by the `mutations` rules with a `custom` mutation
+    /// of the given name. Until it is registered, the samples these rules match are not sent.
+    pub fn add_sample_mutation(&self, name: &str, mutation: Arc<dyn SampleMutation>) {
+        match zread!(self.router.tables.tables).mutations.as_ref() {
+            Some(mutations) => mutations.add_hook(name.to_string(), mutation),
+            None => log::warn!("Sample mutation `{name}` registered without any mutation rule"),
+        }
     }
 
     pub(crate) fn spawn<F, T>(&self, future: F) -> Option<JoinHandle<Result<T, TimedOutError>>>
@@ -234,9 +705,25 @@ impl TransportEventHandler for RuntimeTransportEventHandler {
                             handler.new_unicast(peer.clone(), transport.clone()).ok()
                         })
                         .collect();
+                runtime.closed_transports.reopened(&peer.zid);
+                runtime.notify_connectivity(ConnectivityEvent::Connected {
+                    zid: peer.zid,
+                    whatami: peer.whatami,
+                });
+                runtime.topology.added(
+                    peer.zid,
+                    peer.whatami,
+                    peer.links.iter().map(|link| link.dst.clone()).collect(),
+                );
+                if runtime.whatami == WhatAmI::Client && runtime.reconnection.opened() {
+                    log::info!("Reconnected to {} {}", peer.whatami, peer.zid);
+                }
                 Ok(Arc::new(RuntimeSession {
                     runtime: runtime.clone(),
+                    zid: peer.zid,
+                    whatami: peer.whatami,
                     endpoint: std::sync::RwLock::new(None),
+                    is_initiator: peer.is_initiator,
                     main_handler: runtime.router.new_transport_unicast(transport).unwrap(),
                     slave_handlers,
                 }))
@@ -266,29 +753,33 @@ impl TransportEventHandler for RuntimeTransportEventHandler {
             None => bail!("Runtime not yet ready!"),
         }
     }
+
+    fn rejected(&self, zid: &ZenohId, whatami: WhatAmI, reason: &TransportCloseReason) {
+        if let Some(runtime) = zread!(self.runtime).as_ref() {
+            log::debug!("Rejected transport with {} ({}): {}", zid, whatami, reason);
+            for handler in zread!(runtime.transport_handlers).iter() {
+                handler.rejected(zid, whatami, reason);
+            }
+            runtime.record_closed(*zid, whatami, reason);
+        }
+    }
 }
 
 pub(super) struct RuntimeSession {
     pub(super) runtime: Runtime,
+    pub(super) zid: ZenohId,
+    pub(super) whatami: WhatAmI,
     pub(super) endpoint: std::sync::RwLock<Option<EndPoint>>,
+    pub(super) is_initiator: bool,
     pub(super) main_handler: Arc<LinkStateInterceptor>,
     pub(super) slave_handlers: Vec<Arc<dyn TransportPeerEventHandler>>,
 }
 
 impl TransportPeerEventHandler for RuntimeSession {
     fn handle_message(&self, msg: NetworkMessage) -> ZResult<()> {
-        // critical path shortcut
+        // critical path shortcut, the face still confining and auditing the data
         if let NetworkBody::Push(data) = msg.body {
-            let face = &self.main_handler.face.state;
-
-            full_reentrant_route_data(
-                &self.main_handler.tables.tables,
-                face,
-                &data.wire_expr,
-                data.ext_qos,
-                data.payload,
-                data.ext_nodeid.node_id.into(),
-            );
+            self.main_handler.face.send_push(data, msg.reliability);
             return Ok(());
         }
 
@@ -310,6 +801,12 @@ impl TransportPeerEventHandler for RuntimeSession {
     }
 
     fn closing(&self) {
+        self.runtime.topology.removed(self.zid, self.whatami);
+        if self.runtime.whatami == WhatAmI::Client && self.runtime.reconnection.closed() {
+            log::warn!(
+                "Disconnected from the routers and peers, holding the publications until reconnected"
+            );
+        }
         self.main_handler.closing();
         Runtime::closing_session(self);
         for handler in &self.slave_handlers {
@@ -317,6 +814,19 @@ impl TransportPeerEventHandler for RuntimeSession {
         }
     }
 
+    fn close_reason(&self, reason: &TransportCloseReason) {
+        for handler in &self.slave_handlers {
+            handler.close_reason(reason);
+        }
+        self.runtime.record_closed(self.zid, self.whatami, reason);
+        self.runtime
+            .notify_connectivity(ConnectivityEvent::Disconnected {
+                zid: self.zid,
+                whatami: self.whatami,
+                reason: reason.clone(),
+            });
+    }
+
     fn closed(&self) {
         self.main_handler.closed();
         for handler in &self.slave_handlers {
@@ -369,7 +879,7 @@ impl TransportMulticastEventHandler for RuntimeMuticastGroup {
 }
 
 pub(super) struct RuntimeMuticastSession {
-    pub(super) main_handler: Arc<DeMux<Face>>,
+    pub(super) main_handler: Arc<MulticastPeerInterceptor>,
     pub(super) slave_handlers: Vec<Arc<dyn TransportPeerEventHandler>>,
 }
 
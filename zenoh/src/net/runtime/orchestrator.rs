@@ -11,32 +11,33 @@
 // Contributors:
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
+use super::connect::ConnectPlan;
 use super::{Runtime, RuntimeSession};
 use async_std::net::UdpSocket;
 use async_std::prelude::FutureExt;
 use futures::prelude::*;
+use futures::stream::BoxStream;
 use socket2::{Domain, Socket, Type};
 use std::net::{IpAddr, Ipv6Addr, SocketAddr};
 use std::time::Duration;
 use zenoh_buffers::reader::DidntRead;
 use zenoh_buffers::{reader::HasReader, writer::HasWriter};
 use zenoh_codec::{RCodec, WCodec, Zenoh080};
-use zenoh_config::{unwrap_or_default, ModeDependent};
+use zenoh_config::{unwrap_or_default, Config, ModeDependent};
 use zenoh_link::{Locator, LocatorInspector};
 use zenoh_protocol::{
     core::{whatami::WhatAmIMatcher, EndPoint, WhatAmI, ZenohId},
     scouting::{Hello, Scout, ScoutingBody, ScoutingMessage},
 };
 use zenoh_result::{bail, zerror, ZResult};
+use zenoh_transport::TransportUnicast;
+use zenoh_util::backoff::{retry, Backoff};
 
 const RCV_BUF_SIZE: usize = u16::MAX as usize;
 const SCOUT_INITIAL_PERIOD: Duration = Duration::from_millis(1_000);
 const SCOUT_MAX_PERIOD: Duration = Duration::from_millis(8_000);
-const SCOUT_PERIOD_INCREASE_FACTOR: u32 = 2;
+const SCOUT_PERIOD_INCREASE_FACTOR: f64 = 2.0;
 const CONNECTION_TIMEOUT: Duration = Duration::from_millis(10_000);
-const CONNECTION_RETRY_INITIAL_PERIOD: Duration = Duration::from_millis(1_000);
-const CONNECTION_RETRY_MAX_PERIOD: Duration = Duration::from_millis(4_000);
-const CONNECTION_RETRY_PERIOD_INCREASE_FACTOR: u32 = 2;
 const ROUTER_DEFAULT_LISTENER: &str = "tcp/[::]:7447";
 const PEER_DEFAULT_LISTENER: &str = "tcp/[::]:0";
 
@@ -46,7 +47,9 @@ pub enum Loop {
 }
 
 impl Runtime {
-    pub(crate) async fn start(&mut self) -> ZResult<()> {
+    /// Starts a built [`Runtime`]: binds its listeners, connects to its peers and starts
+    /// the scouting, see [`Runtime::build`].
+    pub async fn start(&mut self) -> ZResult<()> {
         match self.whatami {
             WhatAmI::Client => self.start_client().await,
             WhatAmI::Peer => self.start_peer().await,
@@ -55,10 +58,12 @@ impl Runtime {
     }
 
     async fn start_client(&self) -> ZResult<()> {
-        let (peers, scouting, addr, ifaces, timeout) = {
+        let (peers, required, parallelism, scouting, addr, ifaces, timeout) = {
             let guard = self.config.lock();
             (
                 guard.connect().endpoints().clone(),
+                *unwrap_or_default!(guard.connect().required().client()),
+                unwrap_or_default!(guard.connect().parallelism()),
                 unwrap_or_default!(guard.scouting().multicast().enabled()),
                 unwrap_or_default!(guard.scouting().multicast().address()),
                 unwrap_or_default!(guard.scouting().multicast().interface()),
@@ -88,49 +93,29 @@ impl Runtime {
                     bail!("No peer specified and multicast scouting desactivated!")
                 }
             }
-            _ => {
-                for locator in &peers {
-                    match self
-                        .manager()
-                        .open_transport_unicast(locator.clone())
-                        .timeout(CONNECTION_TIMEOUT)
-                        .await
-                    {
-                        Ok(Ok(_)) => return Ok(()),
-                        Ok(Err(e)) => log::warn!("Unable to connect to {}! {}", locator, e),
-                        Err(e) => log::warn!("Unable to connect to {}! {}", locator, e),
-                    }
-                }
-                let e = zerror!("Unable to connect to any of {:?}! ", peers);
-                log::error!("{}", &e);
-                Err(e.into())
-            }
+            _ => self.connect_peers(&peers, required, parallelism).await,
         }
     }
 
     async fn start_peer(&self) -> ZResult<()> {
-        let (listeners, peers, scouting, listen, autoconnect, addr, ifaces, delay) = {
+        let (
+            listeners,
+            peers,
+            required,
+            parallelism,
+            scouting,
+            listen,
+            autoconnect,
+            addr,
+            ifaces,
+            delay,
+        ) = {
             let guard = &self.config.lock();
-            let listeners = if guard.listen().endpoints().is_empty() {
-                let endpoint: EndPoint = PEER_DEFAULT_LISTENER.parse().unwrap();
-                let protocol = endpoint.protocol();
-                let mut listeners = vec![];
-                if self
-                    .manager
-                    .config
-                    .protocols
-                    .iter()
-                    .any(|p| p.as_str() == protocol.as_str())
-                {
-                    listeners.push(endpoint)
-                }
-                listeners
-            } else {
-                guard.listen().endpoints().clone()
-            };
             (
-                listeners,
+                self.listeners(guard),
                 guard.connect().endpoints().clone(),
+                *unwrap_or_default!(guard.connect().required().peer()),
+                unwrap_or_default!(guard.connect().parallelism()),
                 unwrap_or_default!(guard.scouting().multicast().enabled()),
                 *unwrap_or_default!(guard.scouting().multicast().listen().peer()),
                 *unwrap_or_default!(guard.scouting().multicast().autoconnect().peer()),
@@ -142,9 +127,7 @@ impl Runtime {
 
         self.bind_listeners(&listeners).await?;
 
-        for peer in peers {
-            self.spawn_peer_connector(peer).await?;
-        }
+        self.connect_peers(&peers, required, parallelism).await?;
 
         if scouting {
             self.start_scout(listen, autoconnect, addr, ifaces).await?;
@@ -154,28 +137,13 @@ impl Runtime {
     }
 
     async fn start_router(&self) -> ZResult<()> {
-        let (listeners, peers, scouting, listen, autoconnect, addr, ifaces) = {
+        let (listeners, peers, required, parallelism, scouting, listen, autoconnect, addr, ifaces) = {
             let guard = self.config.lock();
-            let listeners = if guard.listen().endpoints().is_empty() {
-                let endpoint: EndPoint = ROUTER_DEFAULT_LISTENER.parse().unwrap();
-                let protocol = endpoint.protocol();
-                let mut listeners = vec![];
-                if self
-                    .manager
-                    .config
-                    .protocols
-                    .iter()
-                    .any(|p| p.as_str() == protocol.as_str())
-                {
-                    listeners.push(endpoint)
-                }
-                listeners
-            } else {
-                guard.listen().endpoints().clone()
-            };
             (
-                listeners,
+                self.listeners(&guard),
                 guard.connect().endpoints().clone(),
+                *unwrap_or_default!(guard.connect().required().router()),
+                unwrap_or_default!(guard.connect().parallelism()),
                 unwrap_or_default!(guard.scouting().multicast().enabled()),
                 *unwrap_or_default!(guard.scouting().multicast().listen().router()),
                 *unwrap_or_default!(guard.scouting().multicast().autoconnect().router()),
@@ -186,9 +154,7 @@ impl Runtime {
 
         self.bind_listeners(&listeners).await?;
 
-        for peer in peers {
-            self.spawn_peer_connector(peer).await?;
-        }
+        self.connect_peers(&peers, required, parallelism).await?;
 
         if scouting {
             self.start_scout(listen, autoconnect, addr, ifaces).await?;
@@ -197,6 +163,43 @@ impl Runtime {
         Ok(())
     }
 
+    /// Returns the endpoints the runtime listens on: the configured ones if any,
+    /// the default listener of its mode otherwise.
+    pub(crate) fn listeners(&self, config: &Config) -> Vec<EndPoint> {
+        if !config.listen().endpoints().is_empty() {
+            return config.listen().endpoints().clone();
+        }
+        let endpoint: EndPoint = match self.whatami {
+            WhatAmI::Router => ROUTER_DEFAULT_LISTENER.parse().unwrap(),
+            WhatAmI::Peer => PEER_DEFAULT_LISTENER.parse().unwrap(),
+            WhatAmI::Client => return vec![],
+        };
+        let protocol = endpoint.protocol();
+        let mut listeners = vec![];
+        if self
+            .manager
+            .config
+            .protocols
+            .iter()
+            .any(|p| p.as_str() == protocol.as_str())
+        {
+            listeners.push(endpoint)
+        }
+        listeners
+    }
+
+    /// Records the configured endpoint on which a transport was opened.
+    fn set_endpoint(transport: &TransportUnicast, endpoint: EndPoint) {
+        if let Ok(Some(orch_transport)) = transport.get_callback() {
+            if let Some(orch_transport) = orch_transport
+                .as_any()
+                .downcast_ref::<super::RuntimeSession>()
+            {
+                *zwrite!(orch_transport.endpoint) = Some(endpoint);
+            }
+        }
+    }
+
     async fn start_scout(
         &self,
         listen: bool,
@@ -293,7 +296,7 @@ impl Runtime {
         for listener in listeners {
             let endpoint = listener.clone();
             match self.manager().add_listener(endpoint).await {
-                Ok(listener) => log::debug!("Listener added: {}", listener),
+                Ok(locators) => log::debug!("Listener added: {:?}", locators),
                 Err(err) => {
                     log::error!("Unable to open listener {}: {}", listener, err);
                     return Err(err);
@@ -309,6 +312,50 @@ impl Runtime {
         Ok(())
     }
 
+    /// Adds a listener on `endpoint` to the running runtime, returning the locators it listens on.
+    ///
+    /// The new locators are advertised right away, in the scouting and to the other routers.
+    pub async fn add_listener(&self, endpoint: EndPoint) -> ZResult<Vec<Locator>> {
+        let locators = self.manager().add_listener(endpoint).await?;
+        log::debug!("Listener added: {:?}", locators);
+        self.update_locators();
+        Ok(locators)
+    }
+
+    /// Removes the listener on `endpoint` from the running runtime.
+    ///
+    /// The removed locators aren't advertised anymore, in the scouting and to the other routers.
+    pub async fn remove_listener(&self, endpoint: &EndPoint) -> ZResult<()> {
+        self.manager().del_listener(endpoint).await?;
+        log::debug!("Listener removed: {}", endpoint);
+        self.update_locators();
+        Ok(())
+    }
+
+    /// Updates the advertised locators from the listeners, gossiping them to the other nodes
+    /// when they changed.
+    pub(super) fn update_locators(&self) {
+        let locators = self.manager().get_locators();
+        {
+            let mut guard = self.locators.write().unwrap();
+            if *guard == locators {
+                return;
+            }
+            log::info!("Zenoh can now be reached at: {:?}", locators);
+            *guard = locators;
+        }
+        let ctrl_lock = zlock!(self.router.tables.ctrl_lock);
+        let mut tables = zwrite!(self.router.tables.tables);
+        if let Some(net) = tables.routers_net.as_mut() {
+            net.update_locators();
+        }
+        if let Some(net) = tables.peers_net.as_mut() {
+            net.update_locators();
+        }
+        drop(tables);
+        drop(ctrl_lock);
+    }
+
     pub fn get_interfaces(names: &str) -> Vec<IpAddr> {
         if names == "auto" {
             let ifaces = zenoh_util::net::get_multicast_interfaces();
@@ -457,52 +504,270 @@ impl Runtime {
         }
     }
 
-    async fn peer_connector(&self, peer: EndPoint) {
-        let mut delay = CONNECTION_RETRY_INITIAL_PERIOD;
-        loop {
-            log::trace!("Trying to connect to configured peer {}", peer);
-            let endpoint = peer.clone();
-            match self
-                .manager()
-                .open_transport_unicast(endpoint)
-                .timeout(CONNECTION_TIMEOUT)
-                .await
-            {
-                Ok(Ok(transport)) => {
+    // The periods between the attempts to connect to the configured peers, see the
+    // `connect/retry` configuration.
+    fn connection_retry_policy(&self) -> Backoff {
+        let guard = self.config.lock();
+        Backoff::new(
+            Duration::from_millis(unwrap_or_default!(guard.connect().retry().period_init())),
+            Duration::from_millis(unwrap_or_default!(guard.connect().retry().period_max())),
+        )
+        .multiplier(unwrap_or_default!(guard
+            .connect()
+            .retry()
+            .period_increase_factor()))
+    }
+
+    fn connection_retry_period_max(&self) -> Duration {
+        let guard = self.config.lock();
+        Duration::from_millis(unwrap_or_default!(guard.connect().retry().period_max()))
+    }
+
+    /// Opens the transports to the configured `peers` concurrently, at most `parallelism` at a
+    /// time, returning once `required` of them are established. The other peers keep being
+    /// connected to in the background, retried with the connection retry policy.
+    ///
+    /// When the peers have priorities or are exclusive, see [`ConnectPlan`], the peers of a
+    /// priority are connected to only if fewer than `required` peers of the preferred priorities
+    /// could be connected to, and the connections are then supervised in the background.
+    async fn connect_peers(
+        &self,
+        peers: &[EndPoint],
+        required: usize,
+        parallelism: usize,
+    ) -> ZResult<()> {
+        let inspector = LocatorInspector::default();
+        for peer in peers {
+            if inspector.is_multicast(&peer.to_locator()).await? {
+                bail!("Forbidden multicast endpoint in connect list!")
+            }
+        }
+        let plan = ConnectPlan::new(peers)?;
+        let prioritized = plan.is_prioritized();
+
+        let mut connected = 0;
+        let mut failed = vec![];
+        let mut last_error = None;
+        let mut pending = None;
+        for group in plan.groups() {
+            if connected >= required {
+                break;
+            }
+            let mut attempts = self.connect_attempts(group, parallelism);
+            while connected < required {
+                match attempts.next().await {
+                    Some((peer, Ok(transport))) => {
+                        log::debug!("Successfully connected to configured peer {}", peer);
+                        Runtime::set_endpoint(&transport, peer);
+                        connected += 1;
+                    }
+                    Some((peer, Err(e))) => {
+                        log::warn!("Unable to connect to {}! {}", peer, e);
+                        failed.push(peer);
+                        last_error = Some(e);
+                    }
+                    None => break,
+                }
+            }
+            pending = Some(attempts);
+        }
+        if connected < required {
+            // The last error is kept as the source, e.g. the reason of a rejection
+            let mut e = if connected == 0 {
+                zerror!("Unable to connect to any of {:?}! ", peers)
+            } else {
+                zerror!(
+                    "Unable to connect to {} of {:?}, connected to {}! ",
+                    required,
+                    peers,
+                    connected
+                )
+            };
+            if let Some(source) = last_error {
+                e = e.set_source(source);
+            }
+            log::error!("{}", &e);
+            return Err(e.into());
+        }
+
+        if prioritized {
+            let this = self.clone();
+            self.spawn(async move { this.supervise_peers(plan, pending, parallelism).await });
+            return Ok(());
+        }
+
+        let mut attempts = match pending {
+            Some(attempts) => attempts,
+            None => self.connect_attempts(peers.to_vec(), parallelism),
+        };
+        for peer in failed {
+            self.spawn_peer_connector(peer).await?;
+        }
+        let this = self.clone();
+        self.spawn(async move {
+            while let Some((peer, res)) = attempts.next().await {
+                match res {
+                    Ok(transport) => {
+                        log::debug!("Successfully connected to configured peer {}", peer);
+                        Runtime::set_endpoint(&transport, peer);
+                    }
+                    Err(e) => {
+                        log::debug!("Unable to connect to configured peer {}! {}", peer, e);
+                        let _ = this.spawn_peer_connector(peer).await;
+                    }
+                }
+            }
+        });
+        Ok(())
+    }
+
+    /// Tries once to open a transport to each of the `peers`, at most `parallelism` at a time.
+    fn connect_attempts(
+        &self,
+        peers: Vec<EndPoint>,
+        parallelism: usize,
+    ) -> BoxStream<'static, (EndPoint, ZResult<TransportUnicast>)> {
+        let this = self.clone();
+        futures::stream::iter(peers)
+            .map(move |peer| {
+                let this = this.clone();
+                async move {
+                    let res = this.connect_endpoint(&peer).await;
+                    (peer, res)
+                }
+            })
+            .buffer_unordered(parallelism.max(1))
+            .boxed()
+    }
+
+    /// Keeps the transports to the peers of the `plan` in its order: reconnects to the preferred
+    /// peers after a drop, falling back to the less preferred ones, regularly tries to return to
+    /// the preferred peers, and keeps at most one transport to the exclusive peers.
+    async fn supervise_peers(
+        &self,
+        plan: ConnectPlan,
+        pending: Option<BoxStream<'static, (EndPoint, ZResult<TransportUnicast>)>>,
+        parallelism: usize,
+    ) {
+        // The attempts left by the start are awaited first, not to connect twice to a peer
+        if let Some(mut attempts) = pending {
+            while let Some((peer, res)) = attempts.next().await {
+                if let Ok(transport) = res {
                     log::debug!("Successfully connected to configured peer {}", peer);
-                    if let Ok(Some(orch_transport)) = transport.get_callback() {
-                        if let Some(orch_transport) = orch_transport
-                            .as_any()
-                            .downcast_ref::<super::RuntimeSession>()
-                        {
-                            *zwrite!(orch_transport.endpoint) = Some(peer);
+                    Runtime::set_endpoint(&transport, peer);
+                }
+            }
+        }
+
+        let mut backoff = self.connection_retry_policy();
+        loop {
+            let connected = self.close_excluded_peers(&plan).await;
+            let mut attempted = false;
+            for group in plan.candidates(&connected) {
+                attempted = true;
+                let mut attempts = self.connect_attempts(group, parallelism);
+                let mut success = false;
+                while let Some((peer, res)) = attempts.next().await {
+                    match res {
+                        Ok(transport) => {
+                            log::debug!("Successfully connected to configured peer {}", peer);
+                            Runtime::set_endpoint(&transport, peer);
+                            success = true;
+                        }
+                        Err(e) => {
+                            log::debug!("Unable to connect to configured peer {}! {}", peer, e)
                         }
                     }
-                    break;
                 }
-                Ok(Err(e)) => {
-                    log::debug!(
-                        "Unable to connect to configured peer {}! {}. Retry in {:?}.",
-                        peer,
-                        e,
-                        delay
-                    );
+                // The less preferred peers are only a fallback
+                if success {
+                    self.close_excluded_peers(&plan).await;
+                    backoff = self.connection_retry_policy();
+                    break;
                 }
-                Err(e) => {
-                    log::debug!(
-                        "Unable to connect to configured peer {}! {}. Retry in {:?}.",
-                        peer,
-                        e,
-                        delay
-                    );
+            }
+            let period = if attempted {
+                backoff
+                    .next()
+                    .unwrap_or_else(|| self.connection_retry_period_max())
+            } else {
+                self.connection_retry_period_max()
+            };
+            self.clock.sleep(period).await;
+        }
+    }
+
+    /// Closes the transports to the exclusive peers of the `plan` that aren't kept, returning the
+    /// peers of the `plan` still connected.
+    async fn close_excluded_peers(&self, plan: &ConnectPlan) -> Vec<EndPoint> {
+        let mut transports = vec![];
+        for transport in self.manager().get_transports_unicast().await {
+            if let Ok(Some(orch_transport)) = transport.get_callback() {
+                if let Some(orch_transport) = orch_transport
+                    .as_any()
+                    .downcast_ref::<super::RuntimeSession>()
+                {
+                    if let Some(endpoint) = &*zread!(orch_transport.endpoint) {
+                        transports.push((endpoint.clone(), transport.clone()));
+                    }
                 }
             }
-            async_std::task::sleep(delay).await;
-            delay *= CONNECTION_RETRY_PERIOD_INCREASE_FACTOR;
-            if delay > CONNECTION_RETRY_MAX_PERIOD {
-                delay = CONNECTION_RETRY_MAX_PERIOD;
+        }
+        let connected = transports
+            .iter()
+            .map(|(endpoint, _)| endpoint.clone())
+            .collect::<Vec<EndPoint>>();
+        let to_close = plan.to_close(&connected);
+        for (endpoint, transport) in transports.iter() {
+            if to_close.contains(endpoint) {
+                log::debug!(
+                    "Closing transport to less preferred exclusive peer {}",
+                    endpoint
+                );
+                if let Err(e) = transport.close().await {
+                    log::debug!("Unable to close transport to {}! {}", endpoint, e);
+                }
             }
         }
+        connected
+            .into_iter()
+            .filter(|endpoint| !to_close.contains(endpoint))
+            .collect()
+    }
+
+    /// Tries once to open a transport to the configured `peer`.
+    async fn connect_endpoint(&self, peer: &EndPoint) -> ZResult<TransportUnicast> {
+        log::trace!("Trying to connect to configured peer {}", peer);
+        let res = self
+            .manager()
+            .open_transport_unicast(peer.clone())
+            .timeout(CONNECTION_TIMEOUT)
+            .await
+            .unwrap_or_else(|e| Err(zerror!("{}", e).into()));
+        if let Err(e) = &res {
+            self.notify_rejected(peer, e);
+        }
+        res
+    }
+
+    async fn peer_connector(&self, peer: EndPoint) {
+        let res = retry(
+            self.connection_retry_policy(),
+            |e| {
+                log::debug!(
+                    "Unable to connect to configured peer {}! {}. Retry.",
+                    peer,
+                    e
+                );
+                true
+            },
+            || self.connect_endpoint(&peer),
+        )
+        .await;
+        if let Ok(transport) = res {
+            log::debug!("Successfully connected to configured peer {}", peer);
+            Runtime::set_endpoint(&transport, peer);
+        }
     }
 
     pub async fn scout<Fut, F>(
@@ -529,6 +794,7 @@ impl Runtime {
             let codec = Zenoh080::new();
             codec.write(&mut writer, &scout).unwrap();
 
+            // The scouting never stops, the period being capped once increased to its maximum
             loop {
                 for socket in sockets {
                     log::trace!(
@@ -555,9 +821,9 @@ impl Runtime {
                     }
                 }
                 async_std::task::sleep(delay).await;
-                if delay * SCOUT_PERIOD_INCREASE_FACTOR <= SCOUT_MAX_PERIOD {
-                    delay *= SCOUT_PERIOD_INCREASE_FACTOR;
-                }
+                delay = delay
+                    .mul_f64(SCOUT_PERIOD_INCREASE_FACTOR)
+                    .min(SCOUT_MAX_PERIOD);
             }
         };
         let recvs = futures::future::select_all(sockets.iter().map(move |socket| {
@@ -632,7 +898,7 @@ impl Runtime {
                 }
             } else {
                 match manager
-                    .open_transport_unicast(endpoint)
+                    .open_transport_unicast(endpoint.clone())
                     .timeout(CONNECTION_TIMEOUT)
                     .await
                 {
@@ -643,7 +909,10 @@ impl Runtime {
                         );
                         return true;
                     }
-                    Ok(Err(e)) => log::trace!("{} {} on {}: {}", ERR, zid, locator, e),
+                    Ok(Err(e)) => {
+                        log::trace!("{} {} on {}: {}", ERR, zid, locator, e);
+                        self.notify_rejected(&endpoint, &e);
+                    }
                     Err(e) => log::trace!("{} {} on {}: {}", ERR, zid, locator, e),
                 }
             }
@@ -728,6 +997,16 @@ impl Runtime {
         .await
     }
 
+    /// The Hello answering the scouts matching this runtime, advertising its locators.
+    pub fn hello(&self) -> Hello {
+        Hello {
+            version: zenoh_protocol::VERSION,
+            whatami: self.whatami,
+            zid: self.manager().zid(),
+            locators: self.get_locators(),
+        }
+    }
+
     async fn responder(&self, mcast_socket: &UdpSocket, ucast_sockets: &[UdpSocket]) {
         fn get_best_match<'a>(addr: &IpAddr, sockets: &'a [UdpSocket]) -> Option<&'a UdpSocket> {
             fn octets(addr: &IpAddr) -> Vec<u8> {
@@ -776,14 +1055,7 @@ impl Runtime {
                         let mut writer = wbuf.writer();
                         let codec = Zenoh080::new();
 
-                        let zid = self.manager().zid();
-                        let hello: ScoutingMessage = Hello {
-                            version: zenoh_protocol::VERSION,
-                            whatami: self.whatami,
-                            zid,
-                            locators: self.get_locators(),
-                        }
-                        .into();
+                        let hello: ScoutingMessage = self.hello().into();
                         let socket = get_best_match(&peer.ip(), ucast_sockets).unwrap();
                         log::trace!(
                             "Send {:?} to {} on interface {}",
@@ -811,23 +1083,30 @@ impl Runtime {
     }
 
     pub(super) fn closing_session(session: &RuntimeSession) {
+        // Only the transports opened by this node are reopened,
+        // the accepted ones are up to the remote node
+        if !session.is_initiator {
+            return;
+        }
+        // The prioritized peers are reconnected to in their order by their supervisor
+        let peers = { session.runtime.config.lock().connect().endpoints().clone() };
+        if ConnectPlan::new(&peers).map_or(false, |plan| plan.is_prioritized()) {
+            return;
+        }
         match session.runtime.whatami {
             WhatAmI::Client => {
                 let runtime = session.runtime.clone();
                 session.runtime.spawn(async move {
-                    let mut delay = CONNECTION_RETRY_INITIAL_PERIOD;
-                    while runtime.start_client().await.is_err() {
-                        async_std::task::sleep(delay).await;
-                        delay *= CONNECTION_RETRY_PERIOD_INCREASE_FACTOR;
-                        if delay > CONNECTION_RETRY_MAX_PERIOD {
-                            delay = CONNECTION_RETRY_MAX_PERIOD;
-                        }
-                    }
+                    let _ = retry(
+                        runtime.connection_retry_policy(),
+                        |_| true,
+                        || runtime.start_client(),
+                    )
+                    .await;
                 });
             }
             _ => {
                 if let Some(endpoint) = &*zread!(session.endpoint) {
-                    let peers = { session.runtime.config.lock().connect().endpoints().clone() };
                     if peers.contains(endpoint) {
                         let endpoint = endpoint.clone();
                         let runtime = session.runtime.clone();
@@ -0,0 +1,140 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use event_listener::Event;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use zenoh_core::zlock;
+
+/// The transports of a client runtime to the routers and peers, tracked to hold the
+/// publications of its sessions from the loss of the last one until the reconnection.
+#[derive(Default)]
+pub(crate) struct Reconnection {
+    state: Mutex<ReconnectionState>,
+    // Mirrors the state, updated under its lock, so that the publications don't take it
+    disconnected: AtomicBool,
+    reconnected: Event,
+}
+
+#[derive(Default)]
+struct ReconnectionState {
+    transports: usize,
+    closed: bool,
+}
+
+impl Reconnection {
+    /// Notifies a transport opened, returning whether it ends a disconnection.
+    pub(crate) fn opened(&self) -> bool {
+        let mut state = zlock!(self.state);
+        state.transports += 1;
+        if self.disconnected.swap(false, Ordering::AcqRel) {
+            self.reconnected.notify(usize::MAX);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Notifies a transport closed, returning whether it was the last one.
+    pub(crate) fn closed(&self) -> bool {
+        let mut state = zlock!(self.state);
+        state.transports = state.transports.saturating_sub(1);
+        let disconnected = state.transports == 0 && !state.closed;
+        self.disconnected.store(disconnected, Ordering::Release);
+        disconnected
+    }
+
+    /// Ends the disconnection for good, the runtime being closed.
+    pub(crate) fn close(&self) {
+        let mut state = zlock!(self.state);
+        state.closed = true;
+        self.disconnected.store(false, Ordering::Release);
+        self.reconnected.notify(usize::MAX);
+    }
+
+    pub(crate) fn is_disconnected(&self) -> bool {
+        self.disconnected.load(Ordering::Acquire)
+    }
+
+    /// Blocks until the end of the disconnection, if any.
+    pub(crate) fn wait(&self) {
+        while self.is_disconnected() {
+            let listener = self.reconnected.listen();
+            if !self.is_disconnected() {
+                return;
+            }
+            listener.wait();
+        }
+    }
+
+    /// Waits for the end of the disconnection, if any, without blocking the executor.
+    pub(crate) async fn wait_async(&self) {
+        while self.is_disconnected() {
+            let listener = self.reconnected.listen();
+            if !self.is_disconnected() {
+                return;
+            }
+            listener.await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[test]
+    fn reconnection_wait() {
+        let reconnection = Arc::new(Reconnection::default());
+        // Not connected yet is not a disconnection
+        assert!(!reconnection.is_disconnected());
+
+        assert!(!reconnection.opened());
+        assert!(!reconnection.opened());
+        assert!(!reconnection.closed());
+        assert!(reconnection.closed());
+        assert!(reconnection.is_disconnected());
+
+        let waiter = {
+            let reconnection = reconnection.clone();
+            std::thread::spawn(move || reconnection.wait())
+        };
+        std::thread::sleep(Duration::from_millis(100));
+        assert!(!waiter.is_finished());
+        assert!(reconnection.opened());
+        waiter.join().unwrap();
+
+        // The transports closed with the runtime are no disconnection
+        reconnection.close();
+        assert!(!reconnection.closed());
+        reconnection.wait();
+    }
+
+    #[test]
+    fn reconnection_wait_async() {
+        let reconnection = Arc::new(Reconnection::default());
+        assert!(!reconnection.opened());
+        assert!(reconnection.closed());
+
+        let waiter = {
+            let reconnection = reconnection.clone();
+            async_std::task::spawn(async move { reconnection.wait_async().await })
+        };
+        std::thread::sleep(Duration::from_millis(100));
+        assert!(reconnection.opened());
+        async_std::task::block_on(waiter);
+        async_std::task::block_on(reconnection.wait_async());
+    }
+}
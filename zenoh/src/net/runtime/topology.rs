@@ -0,0 +1,375 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use zenoh_core::zlock;
+use zenoh_protocol::core::{Locator, WhatAmI, ZenohId};
+
+/// The number of topology events waiting to be received by a [`TopologySubscriber`] above which
+/// the pending ones are coalesced.
+const TOPOLOGY_QUEUE: usize = 256;
+
+/// A change of the topology seen by a runtime.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TopologyEvent {
+    /// A transport was opened with a router.
+    RouterAdded {
+        zid: ZenohId,
+        locators: Vec<Locator>,
+    },
+    /// The transport with a router was closed.
+    RouterRemoved { zid: ZenohId },
+    /// A transport was opened with a peer.
+    PeerAdded {
+        zid: ZenohId,
+        locators: Vec<Locator>,
+    },
+    /// The transport with a peer was closed.
+    PeerRemoved { zid: ZenohId },
+    /// The neighbors of a router changed in the linkstate database of the routers, only
+    /// notified by the routers. A router without neighbors left the database.
+    LinkstateNeighborChanged {
+        zid: ZenohId,
+        neighbors: Vec<ZenohId>,
+    },
+}
+
+impl TopologyEvent {
+    /// The events superseding each other: the latest one gives the state of the key.
+    fn key(&self) -> (u8, ZenohId) {
+        match self {
+            TopologyEvent::RouterAdded { zid, .. } | TopologyEvent::RouterRemoved { zid } => {
+                (0, *zid)
+            }
+            TopologyEvent::PeerAdded { zid, .. } | TopologyEvent::PeerRemoved { zid } => (1, *zid),
+            TopologyEvent::LinkstateNeighborChanged { zid, .. } => (2, *zid),
+        }
+    }
+}
+
+/// The topology seen by a runtime: the routers and peers it has a transport with, and for the
+/// routers the neighbors of each router of the linkstate database.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TopologySnapshot {
+    pub routers: HashMap<ZenohId, Vec<Locator>>,
+    pub peers: HashMap<ZenohId, Vec<Locator>>,
+    pub neighbors: HashMap<ZenohId, Vec<ZenohId>>,
+}
+
+impl TopologySnapshot {
+    /// Applies `event` to the snapshot, returning whether it changed it.
+    fn apply(&mut self, event: &TopologyEvent) -> bool {
+        fn insert<V: PartialEq>(map: &mut HashMap<ZenohId, V>, zid: &ZenohId, value: V) -> bool {
+            map.insert(*zid, value)
+                .map_or(true, |previous| previous != *map.get(zid).unwrap())
+        }
+
+        match event {
+            TopologyEvent::RouterAdded { zid, locators } => {
+                insert(&mut self.routers, zid, locators.clone())
+            }
+            TopologyEvent::RouterRemoved { zid } => self.routers.remove(zid).is_some(),
+            TopologyEvent::PeerAdded { zid, locators } => {
+                insert(&mut self.peers, zid, locators.clone())
+            }
+            TopologyEvent::PeerRemoved { zid } => self.peers.remove(zid).is_some(),
+            TopologyEvent::LinkstateNeighborChanged { zid, neighbors } if neighbors.is_empty() => {
+                self.neighbors.remove(zid).is_some()
+            }
+            TopologyEvent::LinkstateNeighborChanged { zid, neighbors } => {
+                insert(&mut self.neighbors, zid, neighbors.clone())
+            }
+        }
+    }
+}
+
+/// The events not yet received by a subscriber, and its view of the topology.
+struct SubscriberState {
+    pending: VecDeque<TopologyEvent>,
+    view: TopologySnapshot,
+    capacity: usize,
+}
+
+impl SubscriberState {
+    fn push(&mut self, event: TopologyEvent) {
+        if self.pending.len() >= self.capacity {
+            self.coalesce();
+        }
+        self.pending.push_back(event);
+    }
+
+    /// Keeps the latest pending event of each key, in the order of these latest events.
+    fn coalesce(&mut self) {
+        let mut latest = HashMap::new();
+        for (i, event) in self.pending.iter().enumerate() {
+            latest.insert(event.key(), i);
+        }
+        let mut i = 0;
+        self.pending.retain(|event| {
+            i += 1;
+            latest[&event.key()] == i - 1
+        });
+    }
+
+    /// Pops the next event changing the view, skipping the ones the view already reflects.
+    fn pop(&mut self) -> Option<TopologyEvent> {
+        while let Some(event) = self.pending.pop_front() {
+            if self.view.apply(&event) {
+                return Some(event);
+            }
+        }
+        None
+    }
+}
+
+/// A stream of the [`TopologyEvent`]s of a runtime, see [`crate::runtime::Runtime::topology`].
+///
+/// The events are delivered in order, without duplicates. When the subscriber doesn't keep up,
+/// the pending events of the same router or peer are coalesced: only its latest state is
+/// delivered. The pending events are then bounded by the number of routers and peers.
+pub struct TopologySubscriber {
+    state: Arc<Mutex<SubscriberState>>,
+    signal: flume::Receiver<()>,
+}
+
+impl TopologySubscriber {
+    /// Returns the next event if any, without waiting.
+    pub fn try_recv(&self) -> Option<TopologyEvent> {
+        zlock!(self.state).pop()
+    }
+
+    /// Waits for the next event, returning `None` once the runtime is closed.
+    pub async fn recv_async(&self) -> Option<TopologyEvent> {
+        loop {
+            if let Some(event) = self.try_recv() {
+                return Some(event);
+            }
+            self.signal.recv_async().await.ok()?;
+        }
+    }
+
+    /// Waits for the next event, returning `None` once the runtime is closed.
+    pub fn recv(&self) -> Option<TopologyEvent> {
+        loop {
+            if let Some(event) = self.try_recv() {
+                return Some(event);
+            }
+            self.signal.recv().ok()?;
+        }
+    }
+
+    /// The topology resulting from the events already received.
+    pub fn snapshot(&self) -> TopologySnapshot {
+        zlock!(self.state).view.clone()
+    }
+}
+
+/// The topology of a runtime, notified to its subscribers.
+pub(crate) struct Topology {
+    state: Mutex<TopologyState>,
+}
+
+struct TopologyState {
+    current: TopologySnapshot,
+    subscribers: Vec<(Arc<Mutex<SubscriberState>>, flume::Sender<()>)>,
+}
+
+impl Topology {
+    pub(crate) fn new() -> Self {
+        Topology {
+            state: Mutex::new(TopologyState {
+                current: TopologySnapshot::default(),
+                subscribers: vec![],
+            }),
+        }
+    }
+
+    pub(crate) fn subscribe(&self) -> TopologySubscriber {
+        self.subscribe_with_capacity(TOPOLOGY_QUEUE)
+    }
+
+    fn subscribe_with_capacity(&self, capacity: usize) -> TopologySubscriber {
+        let mut state = zlock!(self.state);
+        let (sender, receiver) = flume::bounded(1);
+        let subscriber = Arc::new(Mutex::new(SubscriberState {
+            pending: VecDeque::new(),
+            view: state.current.clone(),
+            capacity: capacity.max(1),
+        }));
+        state.subscribers.push((subscriber.clone(), sender));
+        TopologySubscriber {
+            state: subscriber,
+            signal: receiver,
+        }
+    }
+
+    fn notify(&self, event: TopologyEvent) {
+        let mut state = zlock!(self.state);
+        if !state.current.apply(&event) {
+            return;
+        }
+        state
+            .subscribers
+            .retain(|(_, signal)| !signal.is_disconnected());
+        for (subscriber, signal) in &state.subscribers {
+            zlock!(subscriber).push(event.clone());
+            let _ = signal.try_send(());
+        }
+    }
+
+    /// Ends the streams of the subscribers, once they received the pending events.
+    pub(crate) fn close(&self) {
+        zlock!(self.state).subscribers.clear();
+    }
+
+    /// Notifies the transport opened with a router or a peer.
+    pub(crate) fn added(&self, zid: ZenohId, whatami: WhatAmI, locators: Vec<Locator>) {
+        match whatami {
+            WhatAmI::Router => self.notify(TopologyEvent::RouterAdded { zid, locators }),
+            WhatAmI::Peer => self.notify(TopologyEvent::PeerAdded { zid, locators }),
+            WhatAmI::Client => (),
+        }
+    }
+
+    /// Notifies the transport closed with a router or a peer.
+    pub(crate) fn removed(&self, zid: ZenohId, whatami: WhatAmI) {
+        match whatami {
+            WhatAmI::Router => self.notify(TopologyEvent::RouterRemoved { zid }),
+            WhatAmI::Peer => self.notify(TopologyEvent::PeerRemoved { zid }),
+            WhatAmI::Client => (),
+        }
+    }
+
+    /// Notifies the changes of the neighbors of the routers of the linkstate database, given
+    /// the neighbors of all its routers.
+    pub(crate) fn linkstate(&self, mut neighbors: HashMap<ZenohId, Vec<ZenohId>>) {
+        let previous = zlock!(self.state).current.neighbors.clone();
+        for zid in previous.keys() {
+            if !neighbors.contains_key(zid) {
+                self.notify(TopologyEvent::LinkstateNeighborChanged {
+                    zid: *zid,
+                    neighbors: vec![],
+                });
+            }
+        }
+        let mut zids = neighbors.keys().copied().collect::<Vec<_>>();
+        zids.sort();
+        for zid in zids {
+            let mut neighbors = neighbors.remove(&zid).unwrap();
+            neighbors.sort();
+            neighbors.dedup();
+            self.notify(TopologyEvent::LinkstateNeighborChanged { zid, neighbors });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn zid(id: u8) -> ZenohId {
+        ZenohId::try_from([id]).unwrap()
+    }
+
+    #[test]
+    fn topology_deduplicated() {
+        let topology = Topology::new();
+        let subscriber = topology.subscribe();
+
+        topology.added(zid(1), WhatAmI::Router, vec![]);
+        topology.added(zid(1), WhatAmI::Router, vec![]);
+        topology.added(zid(2), WhatAmI::Client, vec![]);
+        topology.removed(zid(3), WhatAmI::Peer);
+        assert_eq!(
+            subscriber.try_recv(),
+            Some(TopologyEvent::RouterAdded {
+                zid: zid(1),
+                locators: vec![]
+            })
+        );
+        assert_eq!(subscriber.try_recv(), None);
+
+        // The neighbors are notified when they change, and sorted
+        let neighbors = HashMap::from([(zid(1), vec![zid(3), zid(2)])]);
+        topology.linkstate(neighbors.clone());
+        topology.linkstate(neighbors);
+        topology.linkstate(HashMap::new());
+        assert_eq!(
+            subscriber.try_recv(),
+            Some(TopologyEvent::LinkstateNeighborChanged {
+                zid: zid(1),
+                neighbors: vec![zid(2), zid(3)]
+            })
+        );
+        assert_eq!(subscriber.snapshot().neighbors.len(), 1);
+        assert_eq!(
+            subscriber.try_recv(),
+            Some(TopologyEvent::LinkstateNeighborChanged {
+                zid: zid(1),
+                neighbors: vec![]
+            })
+        );
+        assert_eq!(subscriber.try_recv(), None);
+
+        // A new subscriber starts from the current topology
+        let late = topology.subscribe();
+        assert_eq!(late.try_recv(), None);
+        assert_eq!(late.snapshot(), subscriber.snapshot());
+        assert!(late.snapshot().routers.contains_key(&zid(1)));
+    }
+
+    #[test]
+    fn topology_coalesced() {
+        let topology = Topology::new();
+        let subscriber = topology.subscribe_with_capacity(2);
+
+        // A flapping peer only delivers its latest state once the queue overflows
+        for _ in 0..4 {
+            topology.added(zid(1), WhatAmI::Peer, vec![]);
+            topology.removed(zid(1), WhatAmI::Peer);
+        }
+        topology.added(zid(2), WhatAmI::Router, vec![]);
+        topology.added(zid(1), WhatAmI::Peer, vec![]);
+        let events = std::iter::from_fn(|| subscriber.try_recv()).collect::<Vec<_>>();
+        assert_eq!(
+            events,
+            vec![
+                TopologyEvent::RouterAdded {
+                    zid: zid(2),
+                    locators: vec![]
+                },
+                TopologyEvent::PeerAdded {
+                    zid: zid(1),
+                    locators: vec![]
+                },
+            ]
+        );
+
+        // The nodes added and removed while the queue overflows are not all delivered
+        for id in 3..10 {
+            topology.added(zid(id), WhatAmI::Peer, vec![]);
+        }
+        for id in 3..10 {
+            topology.removed(zid(id), WhatAmI::Peer);
+        }
+        let events = std::iter::from_fn(|| subscriber.try_recv()).collect::<Vec<_>>();
+        assert!(events.len() < 14);
+
+        // The view of the subscriber is the current topology once all the events are received
+        let mut snapshot = TopologySnapshot::default();
+        snapshot.routers.insert(zid(2), vec![]);
+        snapshot.peers.insert(zid(1), vec![]);
+        assert_eq!(subscriber.snapshot(), snapshot);
+    }
+}
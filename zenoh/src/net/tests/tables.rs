@@ -11,23 +11,39 @@
 // Contributors:
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
+use crate::net::routing::face::{Face, FaceState};
+use crate::net::routing::mappings::{self, MappingLimits};
+use crate::net::routing::querylimit::{QueryLimitPolicy, QueryLimits};
+use crate::net::routing::refresh::{self, DeclarationRefresh};
 use crate::net::routing::router::{self, *};
+use std::collections::HashMap;
 use std::convert::{TryFrom, TryInto};
-use std::sync::{Arc, Mutex, RwLock};
-use std::time::Duration;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, RwLock, Weak};
+use std::time::{Duration, Instant};
 use uhlc::HLC;
 use zenoh_buffers::ZBuf;
 use zenoh_config::defaults::queries_default_timeout;
 use zenoh_core::zlock;
 use zenoh_protocol::core::Encoding;
 use zenoh_protocol::core::{
-    key_expr::keyexpr, ExprId, Reliability, WhatAmI, WireExpr, ZenohId, EMPTY_EXPR_ID,
+    key_expr::keyexpr, CongestionControl, ExprId, Priority, Reliability, WhatAmI, WireExpr,
+    ZenohId, EMPTY_EXPR_ID,
 };
+use zenoh_protocol::network::declare::common::ext::WireExprType;
+use zenoh_protocol::network::declare::queryable::ext::QueryableInfo;
 use zenoh_protocol::network::declare::subscriber::ext::SubscriberInfo;
 use zenoh_protocol::network::declare::Mode;
-use zenoh_protocol::network::{ext, Declare, DeclareBody, DeclareKeyExpr};
-use zenoh_protocol::zenoh::{PushBody, Put};
+use zenoh_protocol::network::oam::id::OAM_DECLARATION_REQUEST;
+use zenoh_protocol::network::{
+    ext, request::ext::TargetType, Declare, DeclareBody, DeclareKeyExpr, DeclareSubscriber,
+    NetworkBody, NetworkMessage, RejectKeyExprs, UndeclareSubscriber,
+};
+use zenoh_protocol::transport::{batch_size, BatchSize};
+use zenoh_protocol::zenoh::{PushBody, Put, Query, RequestBody};
+use zenoh_result::{bail, ZResult};
 use zenoh_transport::{DummyPrimitives, Primitives};
+use zenoh_util::clock::TestClock;
 
 #[test]
 fn base_test() {
@@ -67,15 +83,15 @@ fn base_test() {
         reliability: Reliability::Reliable,
         mode: Mode::Push,
     };
-    declare_client_subscription(
+    declare_client_subscription_filtered(
         &tables,
         zread!(tables.tables),
         &mut face.upgrade().unwrap(),
         &WireExpr::from(1).with_suffix("four/five"),
         &sub_info,
+        0,
+        None,
     );
-
-    Tables::print(&zread!(tables.tables));
 }
 
 #[test]
@@ -254,12 +270,14 @@ fn clean_test() {
         mode: Mode::Push,
     };
 
-    declare_client_subscription(
+    declare_client_subscription_filtered(
         &tables,
         zread!(tables.tables),
         &mut face0.upgrade().unwrap(),
         &"todrop1/todrop11".into(),
         &sub_info,
+        0,
+        None,
     );
     let optres2 = Resource::get_resource(zread!(tables.tables)._get_root(), "todrop1/todrop11")
         .map(|res| Arc::downgrade(&res));
@@ -267,12 +285,14 @@ fn clean_test() {
     let res2 = optres2.unwrap();
     assert!(res2.upgrade().is_some());
 
-    declare_client_subscription(
+    declare_client_subscription_filtered(
         &tables,
         zread!(tables.tables),
         &mut face0.upgrade().unwrap(),
         &WireExpr::from(1).with_suffix("/todrop12"),
         &sub_info,
+        0,
+        None,
     );
     let optres3 = Resource::get_resource(zread!(tables.tables)._get_root(), "todrop1/todrop12")
         .map(|res| Arc::downgrade(&res));
@@ -307,12 +327,14 @@ fn clean_test() {
 
     // --------------
     register_expr(&tables, &mut face0.upgrade().unwrap(), 2, &"todrop3".into());
-    declare_client_subscription(
+    declare_client_subscription_filtered(
         &tables,
         zread!(tables.tables),
         &mut face0.upgrade().unwrap(),
         &"todrop3".into(),
         &sub_info,
+        0,
+        None,
     );
     let optres1 = Resource::get_resource(zread!(tables.tables)._get_root(), "todrop3")
         .map(|res| Arc::downgrade(&res));
@@ -334,19 +356,23 @@ fn clean_test() {
     // --------------
     register_expr(&tables, &mut face0.upgrade().unwrap(), 3, &"todrop4".into());
     register_expr(&tables, &mut face0.upgrade().unwrap(), 4, &"todrop5".into());
-    declare_client_subscription(
+    declare_client_subscription_filtered(
         &tables,
         zread!(tables.tables),
         &mut face0.upgrade().unwrap(),
         &"todrop5".into(),
         &sub_info,
+        0,
+        None,
     );
-    declare_client_subscription(
+    declare_client_subscription_filtered(
         &tables,
         zread!(tables.tables),
         &mut face0.upgrade().unwrap(),
         &"todrop6".into(),
         &sub_info,
+        0,
+        None,
     );
 
     let optres1 = Resource::get_resource(zread!(tables.tables)._get_root(), "todrop4")
@@ -375,6 +401,7 @@ fn clean_test() {
 
 pub struct ClientPrimitives {
     data: std::sync::Mutex<Option<WireExpr<'static>>>,
+    reliability: std::sync::Mutex<Option<Reliability>>,
     mapping: std::sync::Mutex<std::collections::HashMap<ExprId, String>>,
 }
 
@@ -382,12 +409,14 @@ impl ClientPrimitives {
     pub fn new() -> ClientPrimitives {
         ClientPrimitives {
             data: std::sync::Mutex::new(None),
+            reliability: std::sync::Mutex::new(None),
             mapping: std::sync::Mutex::new(std::collections::HashMap::new()),
         }
     }
 
     pub fn clear_data(&self) {
         *self.data.lock().unwrap() = None;
+        *self.reliability.lock().unwrap() = None;
     }
 }
 
@@ -422,6 +451,10 @@ impl ClientPrimitives {
     fn get_last_key(&self) -> Option<WireExpr> {
         self.data.lock().unwrap().as_ref().cloned()
     }
+
+    fn get_last_reliability(&self) -> Option<Reliability> {
+        *self.reliability.lock().unwrap()
+    }
 }
 
 impl Primitives for ClientPrimitives {
@@ -438,8 +471,9 @@ impl Primitives for ClientPrimitives {
         }
     }
 
-    fn send_push(&self, msg: zenoh_protocol::network::Push) {
+    fn send_push(&self, msg: zenoh_protocol::network::Push, reliability: Reliability) {
         *zlock!(self.data) = Some(msg.wire_expr.to_owned());
+        *zlock!(self.reliability) = Some(reliability);
     }
 
     fn send_request(&self, _msg: zenoh_protocol::network::Request) {}
@@ -493,12 +527,14 @@ fn client_test() {
             wire_expr: "test/client".into(),
         }),
     });
-    declare_client_subscription(
+    declare_client_subscription_filtered(
         &tables,
         zread!(tables.tables),
         &mut face0.upgrade().unwrap(),
         &WireExpr::from(11).with_suffix("/**"),
         &sub_info,
+        0,
+        None,
     );
     register_expr(
         &tables,
@@ -537,12 +573,14 @@ fn client_test() {
             wire_expr: "test/client".into(),
         }),
     });
-    declare_client_subscription(
+    declare_client_subscription_filtered(
         &tables,
         zread!(tables.tables),
         &mut face1.upgrade().unwrap(),
         &WireExpr::from(21).with_suffix("/**"),
         &sub_info,
+        0,
+        None,
     );
     register_expr(
         &tables,
@@ -581,12 +619,14 @@ fn client_test() {
             wire_expr: "test/client".into(),
         }),
     });
-    declare_client_subscription(
+    declare_client_subscription_filtered(
         &tables,
         zread!(tables.tables),
         &mut face2.upgrade().unwrap(),
         &WireExpr::from(31).with_suffix("/**"),
         &sub_info,
+        0,
+        None,
     );
 
     primitives0.clear_data();
@@ -604,6 +644,8 @@ fn client_test() {
             ext_sinfo: None,
             #[cfg(feature = "shared-memory")]
             ext_shm: None,
+            ext_integrity: None,
+            ext_batch: None,
             ext_unknown: vec![],
             payload: ZBuf::empty(),
         }),
@@ -636,6 +678,8 @@ fn client_test() {
             ext_sinfo: None,
             #[cfg(feature = "shared-memory")]
             ext_shm: None,
+            ext_integrity: None,
+            ext_batch: None,
             ext_unknown: vec![],
             payload: ZBuf::empty(),
         }),
@@ -668,6 +712,8 @@ fn client_test() {
             ext_sinfo: None,
             #[cfg(feature = "shared-memory")]
             ext_shm: None,
+            ext_integrity: None,
+            ext_batch: None,
             ext_unknown: vec![],
             payload: ZBuf::empty(),
         }),
@@ -700,6 +746,8 @@ fn client_test() {
             ext_sinfo: None,
             #[cfg(feature = "shared-memory")]
             ext_shm: None,
+            ext_integrity: None,
+            ext_batch: None,
             ext_unknown: vec![],
             payload: ZBuf::empty(),
         }),
@@ -732,6 +780,8 @@ fn client_test() {
             ext_sinfo: None,
             #[cfg(feature = "shared-memory")]
             ext_shm: None,
+            ext_integrity: None,
+            ext_batch: None,
             ext_unknown: vec![],
             payload: ZBuf::empty(),
         }),
@@ -750,3 +800,1070 @@ fn client_test() {
     // mapping strategy check
     // assert_eq!(primitives2.get_last_key().unwrap(), KeyExpr::IdWithSuffix(31, "/z2_pub1".to_string()));
 }
+
+#[test]
+fn reliability_test() {
+    let tables = TablesLock {
+        tables: RwLock::new(Tables::new(
+            ZenohId::try_from([1]).unwrap(),
+            WhatAmI::Client,
+            Some(Arc::new(HLC::default())),
+            false,
+            true,
+            Duration::from_millis(queries_default_timeout),
+        )),
+        ctrl_lock: Mutex::new(()),
+        queries_lock: RwLock::new(()),
+    };
+
+    let reliable = SubscriberInfo {
+        reliability: Reliability::Reliable,
+        mode: Mode::Push,
+    };
+    let best_effort = SubscriberInfo {
+        reliability: Reliability::BestEffort,
+        mode: Mode::Push,
+    };
+
+    let primitives0 = Arc::new(ClientPrimitives::new());
+    let face0 = zwrite!(tables.tables).open_face(
+        ZenohId::try_from([1]).unwrap(),
+        WhatAmI::Client,
+        primitives0.clone(),
+    );
+
+    let primitives1 = Arc::new(ClientPrimitives::new());
+    let face1 = zwrite!(tables.tables).open_face(
+        ZenohId::try_from([1]).unwrap(),
+        WhatAmI::Client,
+        primitives1.clone(),
+    );
+    declare_client_subscription_filtered(
+        &tables,
+        zread!(tables.tables),
+        &mut face1.upgrade().unwrap(),
+        &"test/reliability/**".into(),
+        &reliable,
+        0,
+        None,
+    );
+
+    let primitives2 = Arc::new(ClientPrimitives::new());
+    let face2 = zwrite!(tables.tables).open_face(
+        ZenohId::try_from([1]).unwrap(),
+        WhatAmI::Client,
+        primitives2.clone(),
+    );
+    declare_client_subscription_filtered(
+        &tables,
+        zread!(tables.tables),
+        &mut face2.upgrade().unwrap(),
+        &"test/reliability/**".into(),
+        &best_effort,
+        0,
+        None,
+    );
+
+    let route = |primitives: &[&Arc<ClientPrimitives>]| {
+        for p in primitives {
+            p.clear_data();
+        }
+        full_reentrant_route_data(
+            &tables.tables,
+            &face0.upgrade().unwrap(),
+            &"test/reliability/a".into(),
+            ext::QoSType::default(),
+            PushBody::Put(Put {
+                timestamp: None,
+                encoding: Encoding::default(),
+                ext_sinfo: None,
+                #[cfg(feature = "shared-memory")]
+                ext_shm: None,
+                ext_integrity: None,
+                ext_batch: None,
+                ext_unknown: vec![],
+                payload: ZBuf::empty(),
+            }),
+            0,
+        );
+    };
+
+    // Each leg of the route honors the reliability declared by its subscriber
+    route(&[&primitives1, &primitives2]);
+    assert_eq!(
+        primitives1.get_last_reliability(),
+        Some(Reliability::Reliable)
+    );
+    assert_eq!(
+        primitives2.get_last_reliability(),
+        Some(Reliability::BestEffort)
+    );
+
+    // Upgrading a best-effort subscription to reliable upgrades its leg
+    declare_client_subscription_filtered(
+        &tables,
+        zread!(tables.tables),
+        &mut face2.upgrade().unwrap(),
+        &"test/reliability/**".into(),
+        &reliable,
+        0,
+        None,
+    );
+    route(&[&primitives1, &primitives2]);
+    assert_eq!(
+        primitives1.get_last_reliability(),
+        Some(Reliability::Reliable)
+    );
+    assert_eq!(
+        primitives2.get_last_reliability(),
+        Some(Reliability::Reliable)
+    );
+}
+
+#[test]
+fn filter_test() {
+    let tables = TablesLock {
+        tables: RwLock::new(Tables::new(
+            ZenohId::try_from([1]).unwrap(),
+            WhatAmI::Client,
+            Some(Arc::new(HLC::default())),
+            false,
+            true,
+            Duration::from_millis(queries_default_timeout),
+        )),
+        ctrl_lock: Mutex::new(()),
+        queries_lock: RwLock::new(()),
+    };
+
+    let primitives0 = Arc::new(ClientPrimitives::new());
+    let face0 = zwrite!(tables.tables).open_face(
+        ZenohId::try_from([1]).unwrap(),
+        WhatAmI::Client,
+        primitives0.clone(),
+    );
+
+    let primitives1 = Arc::new(ClientPrimitives::new());
+    let face1 = zwrite!(tables.tables).open_face(
+        ZenohId::try_from([2]).unwrap(),
+        WhatAmI::Client,
+        primitives1.clone(),
+    );
+    declare_client_subscription_filtered(
+        &tables,
+        zread!(tables.tables),
+        &mut face1.upgrade().unwrap(),
+        &"test/filter/**".into(),
+        &SubscriberInfo::default(),
+        0,
+        Some(Arc::new("payload > 10".parse().unwrap())),
+    );
+
+    let route = |encoding: Encoding, payload: &str| {
+        primitives1.clear_data();
+        full_reentrant_route_data(
+            &tables.tables,
+            &face0.upgrade().unwrap(),
+            &"test/filter/a".into(),
+            ext::QoSType::default(),
+            PushBody::Put(Put {
+                timestamp: None,
+                encoding,
+                ext_sinfo: None,
+                #[cfg(feature = "shared-memory")]
+                ext_shm: None,
+                ext_integrity: None,
+                ext_batch: None,
+                ext_unknown: vec![],
+                payload: payload.as_bytes().to_vec().into(),
+            }),
+            0,
+        );
+        primitives1.get_last_name().is_some()
+    };
+
+    // Only the data matching the filter of the subscription is routed to its face
+    assert!(route(Encoding::APP_INTEGER, "15"));
+    assert!(!route(Encoding::APP_INTEGER, "5"));
+    assert!(!route(Encoding::TEXT_PLAIN, "text"));
+    assert_eq!(
+        zread!(tables.tables).malformed_payloads(&ZenohId::try_from([2]).unwrap()),
+        1
+    );
+
+    // An unfiltered subscription of the same face receives all the data
+    declare_client_subscription_filtered(
+        &tables,
+        zread!(tables.tables),
+        &mut face1.upgrade().unwrap(),
+        &"test/filter/**".into(),
+        &SubscriberInfo::default(),
+        0,
+        None,
+    );
+    assert!(route(Encoding::APP_INTEGER, "5"));
+}
+
+#[test]
+fn priority_downgrades_test() {
+    let tables = TablesLock {
+        tables: RwLock::new(Tables::new(
+            ZenohId::try_from([1]).unwrap(),
+            WhatAmI::Client,
+            Some(Arc::new(HLC::default())),
+            false,
+            true,
+            Duration::from_millis(queries_default_timeout),
+        )),
+        ctrl_lock: Mutex::new(()),
+        queries_lock: RwLock::new(()),
+    };
+
+    let primitives0 = Arc::new(ClientPrimitives::new());
+    let face0 = zwrite!(tables.tables).open_face(
+        ZenohId::try_from([1]).unwrap(),
+        WhatAmI::Client,
+        primitives0.clone(),
+    );
+
+    // The subscribers are reached through a transport without QoS and one with QoS
+    let mut subscribers = vec![];
+    for (zid, is_qos) in [(2, false), (3, true)] {
+        let primitives = Arc::new(ClientPrimitives::new());
+        let face = zwrite!(tables.tables).open_net_face(
+            ZenohId::try_from([zid]).unwrap(),
+            WhatAmI::Client,
+            #[cfg(feature = "stats")]
+            Arc::new(zenoh_transport::stats::TransportStats::default()),
+            primitives.clone(),
+            0,
+            is_qos,
+            None,
+            None,
+            None,
+        );
+        declare_client_subscription_filtered(
+            &tables,
+            zread!(tables.tables),
+            &mut face.upgrade().unwrap(),
+            &"test/priority/**".into(),
+            &SubscriberInfo::default(),
+            0,
+            None,
+        );
+        subscribers.push(primitives);
+    }
+
+    let route = |priority: Priority| {
+        for primitives in &subscribers {
+            primitives.clear_data();
+        }
+        full_reentrant_route_data(
+            &tables.tables,
+            &face0.upgrade().unwrap(),
+            &"test/priority/a".into(),
+            ext::QoSType::new(priority, CongestionControl::Drop, false),
+            PushBody::Put(Put {
+                timestamp: None,
+                encoding: Encoding::default(),
+                ext_sinfo: None,
+                #[cfg(feature = "shared-memory")]
+                ext_shm: None,
+                ext_integrity: None,
+                ext_batch: None,
+                ext_unknown: vec![],
+                payload: ZBuf::empty(),
+            }),
+            0,
+        );
+        assert!(subscribers.iter().all(|p| p.get_last_name().is_some()));
+    };
+    let downgrades =
+        |zid: u8| zread!(tables.tables).priority_downgrades(&ZenohId::try_from([zid]).unwrap());
+
+    // Only the data with a non default priority sent on the transport without QoS is counted
+    route(Priority::default());
+    assert_eq!(downgrades(2), 0);
+    route(Priority::RealTime);
+    route(Priority::Background);
+    assert_eq!(downgrades(2), 2);
+    assert_eq!(downgrades(3), 0);
+}
+
+#[test]
+fn routes_repair_test() {
+    let tables = TablesLock {
+        tables: RwLock::new(Tables::new(
+            ZenohId::try_from([1]).unwrap(),
+            WhatAmI::Client,
+            Some(Arc::new(HLC::default())),
+            false,
+            true,
+            Duration::from_millis(queries_default_timeout),
+        )),
+        ctrl_lock: Mutex::new(()),
+        queries_lock: RwLock::new(()),
+    };
+
+    let face1 = zwrite!(tables.tables).open_face(
+        ZenohId::try_from([1]).unwrap(),
+        WhatAmI::Client,
+        Arc::new(DummyPrimitives::new()),
+    );
+    let face2 = zwrite!(tables.tables).open_face(
+        ZenohId::try_from([2]).unwrap(),
+        WhatAmI::Client,
+        Arc::new(DummyPrimitives::new()),
+    );
+    for (face, expr) in [
+        (&face1, "test/repair/a"),
+        (&face2, "test/repair/a"),
+        (&face2, "test/repair/b"),
+    ] {
+        declare_client_subscription_filtered(
+            &tables,
+            zread!(tables.tables),
+            &mut face.upgrade().unwrap(),
+            &expr.into(),
+            &SubscriberInfo::default(),
+            0,
+            None,
+        );
+    }
+
+    let route = |expr: &str| {
+        Resource::get_resource(zread!(tables.tables)._get_root(), expr)
+            .and_then(|res| res.client_data_route())
+            .unwrap()
+    };
+    let route_b = route("test/repair/b");
+    assert_eq!(route("test/repair/a").len(), 2);
+
+    // Only the routes going through the closed face are recomputed
+    router::close_face(&tables, &face1);
+    assert_eq!(route("test/repair/a").len(), 1);
+    assert!(Arc::ptr_eq(&route("test/repair/b"), &route_b));
+}
+
+// Compares the repair of the routes when a client face flaps among 50k resources, recomputing
+// only the routes going through the face or all of them.
+// Run with `cargo test --release -p zenoh flapping_face_bench -- --ignored --nocapture`.
+#[test]
+#[ignore]
+fn flapping_face_bench() {
+    const RESOURCES: usize = 50_000;
+    const FLAPS: u32 = 20;
+
+    for full_recomputation in [false, true] {
+        let tables = TablesLock {
+            tables: RwLock::new(Tables::new(
+                ZenohId::try_from([1]).unwrap(),
+                WhatAmI::Client,
+                Some(Arc::new(HLC::default())),
+                false,
+                true,
+                Duration::from_millis(queries_default_timeout),
+            )),
+            ctrl_lock: Mutex::new(()),
+            queries_lock: RwLock::new(()),
+        };
+        zwrite!(tables.tables).full_routes_recomputation = full_recomputation;
+
+        // The resources are mapped by a face, not to propagate their declarations to each new face
+        let face0 = zwrite!(tables.tables).open_face(
+            ZenohId::try_from([1]).unwrap(),
+            WhatAmI::Client,
+            Arc::new(DummyPrimitives::new()),
+        );
+        for i in 0..RESOURCES {
+            register_expr(
+                &tables,
+                &mut face0.upgrade().unwrap(),
+                i as ExprId + 1,
+                &format!("test/bench/{i}").into(),
+            );
+        }
+
+        let start = Instant::now();
+        for _ in 0..FLAPS {
+            let face1 = zwrite!(tables.tables).open_face(
+                ZenohId::try_from([2]).unwrap(),
+                WhatAmI::Client,
+                Arc::new(DummyPrimitives::new()),
+            );
+            declare_client_subscription_filtered(
+                &tables,
+                zread!(tables.tables),
+                &mut face1.upgrade().unwrap(),
+                &"test/bench/0".into(),
+                &SubscriberInfo::default(),
+                0,
+                None,
+            );
+            router::close_face(&tables, &face1);
+            assert!(face1.upgrade().is_none());
+        }
+        println!(
+            "{} recomputation: {:?} per flap of a client face among {} resources",
+            if full_recomputation {
+                "Full"
+            } else {
+                "Incremental"
+            },
+            start.elapsed() / FLAPS,
+            RESOURCES
+        );
+    }
+}
+
+// Records the key expression mappings and counts the declarations sent to a face
+#[derive(Default)]
+struct DeclarePrimitives {
+    mappings: Mutex<HashMap<ExprId, String>>,
+    keyexpr_decls: AtomicUsize,
+    bulk_decls: AtomicUsize,
+    // The size of the key expressions of the largest bulk declaration
+    max_bulk_size: AtomicUsize,
+    sub_decls: AtomicUsize,
+}
+
+impl DeclarePrimitives {
+    fn counts(&self) -> (usize, usize, usize) {
+        (
+            self.keyexpr_decls.load(Ordering::Relaxed),
+            self.bulk_decls.load(Ordering::Relaxed),
+            self.sub_decls.load(Ordering::Relaxed),
+        )
+    }
+}
+
+impl Primitives for DeclarePrimitives {
+    fn send_declare(&self, msg: zenoh_protocol::network::Declare) {
+        match msg.body {
+            DeclareBody::DeclareKeyExpr(d) => {
+                self.keyexpr_decls.fetch_add(1, Ordering::Relaxed);
+                zlock!(self.mappings).insert(d.id, d.wire_expr.suffix.to_string());
+            }
+            DeclareBody::DeclareKeyExprs(d) => {
+                self.bulk_decls.fetch_add(1, Ordering::Relaxed);
+                let size = d.mappings.iter().map(|m| m.wire_expr.suffix.len()).sum();
+                self.max_bulk_size.fetch_max(size, Ordering::Relaxed);
+                let mut mappings = zlock!(self.mappings);
+                for d in d.mappings {
+                    mappings.insert(d.id, d.wire_expr.suffix.to_string());
+                }
+            }
+            DeclareBody::DeclareSubscriber(d) => {
+                // The subscriptions are declared with a mapping declared beforehand
+                assert!(zlock!(self.mappings).contains_key(&d.wire_expr.scope));
+                self.sub_decls.fetch_add(1, Ordering::Relaxed);
+            }
+            _ => (),
+        }
+    }
+
+    fn send_push(&self, _msg: zenoh_protocol::network::Push, _reliability: Reliability) {}
+
+    fn send_request(&self, _msg: zenoh_protocol::network::Request) {}
+
+    fn send_response(&self, _msg: zenoh_protocol::network::Response) {}
+
+    fn send_response_final(&self, _msg: zenoh_protocol::network::ResponseFinal) {}
+
+    fn send_close(&self) {}
+}
+
+// Opens the face of a client's transport to a router, the same router on each call
+fn open_router_face(
+    tables: &TablesLock,
+    primitives: Arc<DeclarePrimitives>,
+    resync: Option<BatchSize>,
+) -> Weak<FaceState> {
+    zwrite!(tables.tables).open_net_face(
+        ZenohId::try_from([2]).unwrap(),
+        WhatAmI::Router,
+        #[cfg(feature = "stats")]
+        Arc::new(zenoh_transport::stats::TransportStats::default()),
+        primitives,
+        0,
+        true,
+        None,
+        resync,
+        None,
+    )
+}
+
+// Declares a subscription on `resources` key expressions from a client session
+fn client_tables(resources: usize) -> Arc<TablesLock> {
+    let tables = Arc::new(TablesLock {
+        tables: RwLock::new(Tables::new(
+            ZenohId::try_from([1]).unwrap(),
+            WhatAmI::Client,
+            Some(Arc::new(HLC::default())),
+            false,
+            true,
+            Duration::from_millis(queries_default_timeout),
+        )),
+        ctrl_lock: Mutex::new(()),
+        queries_lock: RwLock::new(()),
+    });
+    let face0 = zwrite!(tables.tables).open_face(
+        ZenohId::try_from([1]).unwrap(),
+        WhatAmI::Client,
+        Arc::new(DummyPrimitives::new()),
+    );
+    for i in 0..resources {
+        declare_client_subscription_filtered(
+            &tables,
+            zread!(tables.tables),
+            &mut face0.upgrade().unwrap(),
+            &format!("test/resync/{i}").into(),
+            &SubscriberInfo::default(),
+            0,
+            None,
+        );
+    }
+    tables
+}
+
+#[test]
+fn resync_test() {
+    const RESOURCES: usize = 1_000;
+    let tables = client_tables(RESOURCES);
+
+    // Each mapping is declared on its own on the first connection
+    let primitives1 = Arc::new(DeclarePrimitives::default());
+    let face1 = open_router_face(&tables, primitives1.clone(), Some(batch_size::UNICAST));
+    assert_eq!(primitives1.counts(), (RESOURCES, 0, RESOURCES));
+    router::close_face(&tables, &face1);
+
+    // The mappings are re-declared at once, with the same ids, on reconnect
+    let primitives2 = Arc::new(DeclarePrimitives::default());
+    let face2 = open_router_face(&tables, primitives2.clone(), Some(batch_size::UNICAST));
+    assert_eq!(primitives2.counts(), (0, 1, RESOURCES));
+    assert_eq!(*zlock!(primitives2.mappings), *zlock!(primitives1.mappings));
+
+    // The mappings rejected by the router are re-declared on their own with new ids, along with
+    // the subscriptions using them
+    let rejected: Vec<ExprId> = zlock!(primitives2.mappings)
+        .keys()
+        .take(10)
+        .copied()
+        .collect();
+    let face = Face {
+        tables: tables.clone(),
+        state: face2.upgrade().unwrap(),
+    };
+    face.send_declare(Declare {
+        ext_qos: ext::QoSType::declare_default(),
+        ext_tstamp: None,
+        ext_nodeid: ext::NodeIdType::default(),
+        body: DeclareBody::RejectKeyExprs(RejectKeyExprs {
+            ids: rejected.clone(),
+        }),
+    });
+    assert_eq!(
+        primitives2.counts(),
+        (rejected.len(), 1, RESOURCES + rejected.len())
+    );
+    assert_eq!(
+        zlock!(primitives2.mappings).len(),
+        RESOURCES + rejected.len()
+    );
+    drop(face);
+    router::close_face(&tables, &face2);
+
+    // Without resync the mappings are declared one by one again
+    let primitives3 = Arc::new(DeclarePrimitives::default());
+    let face3 = open_router_face(&tables, primitives3.clone(), None);
+    assert_eq!(primitives3.counts(), (RESOURCES, 0, RESOURCES));
+    router::close_face(&tables, &face3);
+
+    // The mappings are re-declared in as many declarations as needed to fit the batches
+    const BATCH_SIZE: BatchSize = 1_024;
+    let primitives4 = Arc::new(DeclarePrimitives::default());
+    let face4 = open_router_face(&tables, primitives4.clone(), Some(BATCH_SIZE));
+    router::close_face(&tables, &face4);
+    let primitives5 = Arc::new(DeclarePrimitives::default());
+    let face5 = open_router_face(&tables, primitives5.clone(), Some(BATCH_SIZE));
+    let (keyexpr_decls, bulk_decls, sub_decls) = primitives5.counts();
+    assert_eq!((keyexpr_decls, sub_decls), (0, RESOURCES));
+    assert!(bulk_decls > 1);
+    assert!(primitives5.max_bulk_size.load(Ordering::Relaxed) < BATCH_SIZE as usize);
+    assert_eq!(*zlock!(primitives5.mappings), *zlock!(primitives4.mappings));
+    router::close_face(&tables, &face5);
+}
+
+#[test]
+fn register_exprs_test() {
+    let tables = TablesLock {
+        tables: RwLock::new(Tables::new(
+            ZenohId::try_from([1]).unwrap(),
+            WhatAmI::Client,
+            Some(Arc::new(HLC::default())),
+            false,
+            true,
+            Duration::from_millis(queries_default_timeout),
+        )),
+        ctrl_lock: Mutex::new(()),
+        queries_lock: RwLock::new(()),
+    };
+    let face = zwrite!(tables.tables).open_face(
+        ZenohId::try_from([2]).unwrap(),
+        WhatAmI::Client,
+        Arc::new(DummyPrimitives::new()),
+    );
+    register_expr(
+        &tables,
+        &mut face.upgrade().unwrap(),
+        1,
+        &"test/bulk/a".into(),
+    );
+
+    let mapping = |id: ExprId, wire_expr: WireExpr<'static>| DeclareKeyExpr { id, wire_expr };
+    let rejected = register_exprs(
+        &tables,
+        &mut face.upgrade().unwrap(),
+        &[
+            // Already mapped to the same key expression
+            mapping(1, "test/bulk/a".into()),
+            mapping(2, "test/bulk/b".into()),
+            // Scoped by a mapping of the same bulk
+            mapping(3, WireExpr::from(2).with_suffix("/c")),
+            // Already mapped to another key expression
+            mapping(1, "test/bulk/x".into()),
+            // Unknown scope
+            mapping(4, WireExpr::from(99).with_suffix("/d")),
+            // Reserved id
+            mapping(EMPTY_EXPR_ID, "test/bulk/e".into()),
+        ],
+    );
+    assert_eq!(rejected, vec![1, 4, EMPTY_EXPR_ID]);
+
+    let root = zread!(tables.tables)._get_root().clone();
+    assert!(Resource::get_resource(&root, "test/bulk/b").is_some());
+    assert!(Resource::get_resource(&root, "test/bulk/b/c").is_some());
+    assert!(Resource::get_resource(&root, "test/bulk/x").is_none());
+}
+
+// Compares the declarations sent and the time taken to restore 10k mappings when a client
+// reconnects to a router, with and without resync.
+// Run with `cargo test --release -p zenoh resync_bench -- --ignored --nocapture`.
+#[test]
+#[ignore]
+fn resync_bench() {
+    const RESOURCES: usize = 10_000;
+
+    for is_resync in [false, true] {
+        let tables = client_tables(RESOURCES);
+        let resync = is_resync.then_some(batch_size::UNICAST);
+        let face = open_router_face(&tables, Arc::new(DeclarePrimitives::default()), resync);
+        router::close_face(&tables, &face);
+
+        let primitives = Arc::new(DeclarePrimitives::default());
+        let start = Instant::now();
+        let face = open_router_face(&tables, primitives.clone(), resync);
+        let elapsed = start.elapsed();
+        let (keyexpr_decls, bulk_decls, _) = primitives.counts();
+        println!(
+            "{}: {:?} to reconnect with {} mappings, {} DeclareKeyExpr and {} DeclareKeyExprs sent",
+            if is_resync { "Resync" } else { "No resync" },
+            elapsed,
+            RESOURCES,
+            keyexpr_decls,
+            bulk_decls
+        );
+        router::close_face(&tables, &face);
+    }
+}
+
+// Counts the subscriptions declared and not undeclared to a face
+#[derive(Default)]
+struct SubPrimitives {
+    subs: AtomicUsize,
+}
+
+impl Primitives for SubPrimitives {
+    fn send_declare(&self, msg: zenoh_protocol::network::Declare) {
+        match msg.body {
+            DeclareBody::DeclareSubscriber(_) => {
+                self.subs.fetch_add(1, Ordering::SeqCst);
+            }
+            DeclareBody::UndeclareSubscriber(_) => {
+                self.subs.fetch_sub(1, Ordering::SeqCst);
+            }
+            _ => (),
+        }
+    }
+
+    fn send_push(&self, _msg: zenoh_protocol::network::Push, _reliability: Reliability) {}
+
+    fn send_request(&self, _msg: zenoh_protocol::network::Request) {}
+
+    fn send_response(&self, _msg: zenoh_protocol::network::Response) {}
+
+    fn send_response_final(&self, _msg: zenoh_protocol::network::ResponseFinal) {}
+
+    fn send_close(&self) {}
+}
+
+// A multicast-like link delivering the declarations sent to a group to the face of the sender
+// in a remote node, dropping the first ones
+struct LossyMcastLink {
+    remote: Face,
+    drops: AtomicUsize,
+}
+
+impl Primitives for LossyMcastLink {
+    fn send_declare(&self, msg: zenoh_protocol::network::Declare) {
+        let drop = self
+            .drops
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+            .is_ok();
+        if !drop {
+            self.remote.send_declare(msg);
+        }
+    }
+
+    fn send_push(&self, _msg: zenoh_protocol::network::Push, _reliability: Reliability) {}
+
+    fn send_request(&self, _msg: zenoh_protocol::network::Request) {}
+
+    fn send_response(&self, _msg: zenoh_protocol::network::Response) {}
+
+    fn send_response_final(&self, _msg: zenoh_protocol::network::ResponseFinal) {}
+
+    fn send_close(&self) {}
+}
+
+fn peer_tables(zid: u8) -> Arc<TablesLock> {
+    Arc::new(TablesLock {
+        tables: RwLock::new(Tables::new(
+            ZenohId::try_from([zid]).unwrap(),
+            WhatAmI::Peer,
+            Some(Arc::new(HLC::default())),
+            false,
+            true,
+            Duration::from_millis(queries_default_timeout),
+        )),
+        ctrl_lock: Mutex::new(()),
+        queries_lock: RwLock::new(()),
+    })
+}
+
+fn open_peer_face(
+    tables: &Arc<TablesLock>,
+    zid: u8,
+    primitives: Arc<dyn Primitives + Send + Sync>,
+) -> Face {
+    let state = zwrite!(tables.tables)
+        .open_face(
+            ZenohId::try_from([zid]).unwrap(),
+            WhatAmI::Client,
+            primitives,
+        )
+        .upgrade()
+        .unwrap();
+    Face {
+        tables: tables.clone(),
+        state,
+    }
+}
+
+#[test]
+fn declaration_refresh_test() {
+    const PERIOD: Duration = Duration::from_millis(200);
+    let refresh = DeclarationRefresh {
+        period: PERIOD,
+        buckets: 4,
+    };
+
+    // The peer 1 declares a subscription to a multicast group the peer 2 is part of
+    let tables1 = peer_tables(1);
+    let tables2 = peer_tables(2);
+    let face2_1 = open_peer_face(&tables2, 1, Arc::new(DummyPrimitives::new()));
+    let observer = Arc::new(SubPrimitives::default());
+    let _face2_3 = open_peer_face(&tables2, 3, observer.clone());
+    let face1_2 = open_peer_face(&tables1, 2, Arc::new(DummyPrimitives::new()));
+    let group = zwrite!(tables1.tables).open_mcast_group(
+        Arc::new(LossyMcastLink {
+            remote: face2_1.clone(),
+            drops: AtomicUsize::new(1),
+        }),
+        None,
+        false,
+    );
+    let session1 = open_peer_face(&tables1, 1, Arc::new(DummyPrimitives::new()));
+
+    // The declaration is lost
+    declare_client_subscription_filtered(
+        &tables1,
+        zread!(tables1.tables),
+        &mut session1.state.clone(),
+        &"test/refresh".into(),
+        &SubscriberInfo::default(),
+        0,
+        None,
+    );
+    assert_eq!(observer.subs.load(Ordering::SeqCst), 0);
+
+    // The refresh messages are delivered in turn to each peer until no answer is sent back
+    let requests = Arc::new(AtomicUsize::new(0));
+    let stop = Arc::new(AtomicBool::new(false));
+    let c_requests = requests.clone();
+    let c_stop = stop.clone();
+    let send = move |msg: NetworkMessage| -> ZResult<()> {
+        if c_stop.load(Ordering::SeqCst) {
+            bail!("Stopped");
+        }
+        let mut msg = Some(msg);
+        let mut faces = [&face2_1, &face1_2].into_iter().cycle();
+        while let Some(NetworkMessage {
+            body: NetworkBody::OAM(oam),
+            ..
+        }) = msg.take()
+        {
+            if oam.id == OAM_DECLARATION_REQUEST {
+                c_requests.fetch_add(1, Ordering::SeqCst);
+            }
+            msg = refresh::handle_oam(faces.next().unwrap(), oam);
+        }
+        Ok(())
+    };
+    let c_tables1 = tables1.clone();
+    let task = async_std::task::spawn(async move {
+        refresh::refresh_task(&c_tables1, group, refresh, send).await
+    });
+
+    let wait_subs = |n: usize| {
+        let start = Instant::now();
+        while observer.subs.load(Ordering::SeqCst) != n {
+            assert!(start.elapsed() < 2 * PERIOD);
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    };
+
+    // The subscription is repaired by the refresh
+    wait_subs(1);
+    assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+    // The digests match from then on
+    std::thread::sleep(2 * PERIOD);
+    assert_eq!(observer.subs.load(Ordering::SeqCst), 1);
+    assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+    // The undeclarations are not sent to the multicast groups: the stale subscription is
+    // forgotten by the refresh
+    forget_client_subscription(
+        &tables1,
+        zread!(tables1.tables),
+        &mut session1.state.clone(),
+        &"test/refresh".into(),
+    );
+    wait_subs(0);
+    assert_eq!(requests.load(Ordering::SeqCst), 2);
+
+    stop.store(true, Ordering::SeqCst);
+    async_std::task::block_on(task);
+}
+
+#[test]
+fn keyexpr_mappings_lru_test() {
+    const MAX_LOCAL: usize = 1_000;
+    const CHURN: usize = 20_000;
+    const GRACE_PERIOD: Duration = Duration::from_secs(1);
+    let clock = TestClock::new();
+    let tables = client_tables(0);
+    zwrite!(tables.tables).mapping_limits = Some(MappingLimits {
+        max_local: Some(MAX_LOCAL),
+        remote_alarm: usize::MAX,
+        grace_period: GRACE_PERIOD,
+        clock: Arc::new(clock.clone()),
+    });
+    let face0 = Face {
+        tables: tables.clone(),
+        state: zread!(tables.tables).faces.values().next().unwrap().clone(),
+    };
+
+    // The router subscribes to all the churned key expressions
+    let primitives = Arc::new(ClientPrimitives::new());
+    let router_face = Face {
+        tables: tables.clone(),
+        state: zwrite!(tables.tables)
+            .open_net_face(
+                ZenohId::try_from([2]).unwrap(),
+                WhatAmI::Router,
+                #[cfg(feature = "stats")]
+                Arc::new(zenoh_transport::stats::TransportStats::default()),
+                primitives.clone(),
+                0,
+                true,
+                None,
+                None,
+                None,
+            )
+            .upgrade()
+            .unwrap(),
+    };
+    router_face.send_declare(Declare {
+        ext_qos: ext::QoSType::declare_default(),
+        ext_tstamp: None,
+        ext_nodeid: ext::NodeIdType::default(),
+        body: DeclareBody::DeclareSubscriber(DeclareSubscriber {
+            id: 0,
+            wire_expr: "test/**".into(),
+            ext_info: SubscriberInfo::default(),
+            ext_filter: None,
+        }),
+    });
+
+    for i in 0..CHURN {
+        let key_expr = format!("test/churn/{i}");
+        face0.send_declare(Declare {
+            ext_qos: ext::QoSType::declare_default(),
+            ext_tstamp: None,
+            ext_nodeid: ext::NodeIdType::default(),
+            body: DeclareBody::DeclareSubscriber(DeclareSubscriber {
+                id: i as u32,
+                wire_expr: key_expr.clone().into(),
+                ext_info: SubscriberInfo::default(),
+                ext_filter: None,
+            }),
+        });
+        full_reentrant_route_data(
+            &tables.tables,
+            &face0.state,
+            &key_expr.clone().into(),
+            ext::QoSType::default(),
+            PushBody::Put(Put {
+                timestamp: None,
+                encoding: Encoding::default(),
+                ext_sinfo: None,
+                #[cfg(feature = "shared-memory")]
+                ext_shm: None,
+                ext_integrity: None,
+                ext_batch: None,
+                ext_unknown: vec![],
+                payload: ZBuf::empty(),
+            }),
+            0,
+        );
+        // The data is sent with a mapping still declared to the router
+        assert_eq!(primitives.get_last_name().unwrap(), key_expr);
+        face0.send_declare(Declare {
+            ext_qos: ext::QoSType::declare_default(),
+            ext_tstamp: None,
+            ext_nodeid: ext::NodeIdType::default(),
+            body: DeclareBody::UndeclareSubscriber(UndeclareSubscriber {
+                id: i as u32,
+                ext_wire_expr: WireExprType {
+                    wire_expr: key_expr.into(),
+                },
+            }),
+        });
+        if i % MAX_LOCAL == 0 {
+            clock.advance(GRACE_PERIOD);
+        }
+    }
+
+    // The mappings, and the resources they retained, are bounded by the limit
+    let rtables = zread!(tables.tables);
+    assert!(zlock!(primitives.mapping).len() <= 2 * MAX_LOCAL);
+    assert!(Resource::get_resources(rtables._get_root()).len() <= 2 * MAX_LOCAL + 10);
+    let info = mappings::mappings_info(&rtables, &ZenohId::try_from([2]).unwrap());
+    assert!(info.local <= MAX_LOCAL);
+    assert!(info.local + info.evicted <= 2 * MAX_LOCAL);
+    assert!(info.memory < 1 << 20);
+}
+
+// Counts the requests routed to a face
+#[derive(Default)]
+struct RequestPrimitives {
+    requests: AtomicUsize,
+}
+
+impl Primitives for RequestPrimitives {
+    fn send_declare(&self, _msg: zenoh_protocol::network::Declare) {}
+
+    fn send_push(&self, _msg: zenoh_protocol::network::Push, _reliability: Reliability) {}
+
+    fn send_request(&self, _msg: zenoh_protocol::network::Request) {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn send_response(&self, _msg: zenoh_protocol::network::Response) {}
+
+    fn send_response_final(&self, _msg: zenoh_protocol::network::ResponseFinal) {}
+
+    fn send_close(&self) {}
+}
+
+#[test]
+fn query_limits_evict_test() {
+    let tables = Arc::new(TablesLock {
+        tables: RwLock::new(Tables::new(
+            ZenohId::try_from([1]).unwrap(),
+            WhatAmI::Client,
+            Some(Arc::new(HLC::default())),
+            false,
+            true,
+            Duration::from_millis(queries_default_timeout),
+        )),
+        ctrl_lock: Mutex::new(()),
+        queries_lock: RwLock::new(()),
+    });
+    let limits = Arc::new(QueryLimits::new(1, 10, QueryLimitPolicy::EvictOldest));
+    zwrite!(tables.tables).query_limits = Some(limits.clone());
+
+    let querier = zwrite!(tables.tables).open_face(
+        ZenohId::try_from([2]).unwrap(),
+        WhatAmI::Client,
+        Arc::new(DummyPrimitives::new()),
+    );
+    let primitives = Arc::new(RequestPrimitives::default());
+    let queryable = zwrite!(tables.tables).open_face(
+        ZenohId::try_from([3]).unwrap(),
+        WhatAmI::Client,
+        primitives.clone(),
+    );
+    declare_client_queryable(
+        &tables,
+        zread!(tables.tables),
+        &mut queryable.upgrade().unwrap(),
+        &"test/limits/**".into(),
+        &QueryableInfo {
+            complete: 0,
+            distance: 0,
+        },
+        0,
+    );
+
+    let query = |qid| {
+        route_query(
+            &tables,
+            &querier.upgrade().unwrap(),
+            &"test/limits/a".into(),
+            qid,
+            TargetType::default(),
+            RequestBody::Query(Query {
+                parameters: String::new(),
+                ext_sinfo: None,
+                ext_consolidation: Default::default(),
+                ext_body: None,
+                ext_unknown: vec![],
+            }),
+            0,
+            None,
+            None,
+        );
+        primitives.requests.load(Ordering::Relaxed)
+    };
+
+    // The second query evicts the first one, reaching the limit of the face
+    assert_eq!(query(1), 1);
+    assert_eq!(query(2), 2);
+    assert_eq!(limits.pending(), 1);
+
+    // A query sent again while pending doesn't evict itself: it is rejected
+    assert_eq!(query(2), 2);
+    assert_eq!(limits.pending(), 1);
+}
@@ -0,0 +1,152 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! The services of a runtime handed to the plugins when started.
+//!
+//! A plugin only interacts with the runtime hosting it through a [`PluginApi`]: it opens sessions
+//! bound to the runtime, reads its own configuration subtree and publishes its status in the admin
+//! space, under the prefix reserved to it.
+use crate::net::runtime::{HealthReport, Runtime};
+use crate::prelude::sync::*;
+use crate::Session;
+use std::sync::Arc;
+use zenoh_core::{zread, zwrite};
+use zenoh_protocol::core::ZenohId;
+use zenoh_result::ZResult;
+
+/// A callback returning the status of a plugin, see [`PluginContext::set_status`].
+pub type StatusCallback = Arc<dyn Fn() -> serde_json::Value + Send + Sync>;
+
+/// The services of the runtime hosting the plugins, handed to [`Plugin::start`](super::Plugin::start).
+#[derive(Clone)]
+pub struct PluginApi {
+    runtime: Runtime,
+}
+
+impl PluginApi {
+    pub fn new(runtime: Runtime) -> Self {
+        PluginApi { runtime }
+    }
+
+    /// The [`ZenohId`] of the runtime hosting the plugins.
+    pub fn zid(&self) -> ZenohId {
+        self.runtime.zid
+    }
+
+    /// The services of the runtime for the plugin named `name`, to be called when the plugin
+    /// starts: it opens the session through which the plugin publishes in the admin space.
+    pub fn plugin(&self, name: &str) -> PluginContext {
+        let admin_session = Session::init(self.runtime.clone(), vec![], vec![], None).res_sync();
+        PluginContext {
+            runtime: self.runtime.clone(),
+            name: name.to_string(),
+            admin_session: Arc::new(admin_session),
+        }
+    }
+}
+
+/// The services of the runtime for a given plugin, see [`PluginApi::plugin`].
+#[derive(Clone)]
+pub struct PluginContext {
+    runtime: Runtime,
+    name: String,
+    // The session publishing in the admin space
+    admin_session: Arc<Session>,
+}
+
+impl PluginContext {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The [`ZenohId`] of the runtime hosting the plugin.
+    pub fn zid(&self) -> ZenohId {
+        self.runtime.zid
+    }
+
+    /// Opens a session bound to the runtime hosting the plugin, to declare its entities.
+    #[zenoh_macros::unstable]
+    pub fn open_session(&self) -> crate::InitBuilder {
+        crate::init(self.runtime.clone())
+    }
+
+    /// The configuration subtree of the plugin, under `plugins/<name>`.
+    pub fn config(&self) -> Option<serde_json::Value> {
+        self.runtime.config.lock().plugin(&self.name).cloned()
+    }
+
+    /// Calls `callback` with the new configuration subtree of the plugin each time it changes.
+    ///
+    /// The callback is called until the runtime is closed.
+    pub fn on_config_change<F>(&self, callback: F)
+    where
+        F: Fn(serde_json::Value) + Send + Sync + 'static,
+    {
+        let receiver = self.runtime.config.subscribe();
+        let context = self.clone();
+        let prefix = format!("plugins/{}", self.name);
+        self.runtime.spawn(async move {
+            while let Ok(change) = receiver.recv_async().await {
+                let change = change.strip_prefix('/').unwrap_or(&change);
+                let matches = change
+                    .strip_prefix(prefix.as_str())
+                    .map_or(false, |rest| rest.is_empty() || rest.starts_with('/'));
+                if !matches {
+                    continue;
+                }
+                if let Some(config) = context.config() {
+                    callback(config);
+                }
+            }
+        });
+    }
+
+    /// The admin space prefix reserved to the plugin: `@/router/<zid>/status/plugins/<name>`.
+    pub fn admin_prefix(&self) -> String {
+        format!("@/router/{}/status/plugins/{}", self.runtime.zid, self.name)
+    }
+
+    /// Publishes `value` on `<admin prefix>/<suffix>`.
+    pub fn publish_admin(&self, suffix: &str, value: serde_json::Value) -> ZResult<()> {
+        let key_expr = KeyExpr::try_from(format!("{}/{}", self.admin_prefix(), suffix))?;
+        self.admin_session
+            .put(key_expr, value.to_string())
+            .encoding(KnownEncoding::AppJson)
+            .res_sync()
+    }
+
+    /// Sets the callback returning the status of the plugin, replied by the admin space
+    /// on `<admin prefix>/status`.
+    pub fn set_status<F>(&self, callback: F)
+    where
+        F: Fn() -> serde_json::Value + Send + Sync + 'static,
+    {
+        zwrite!(self.runtime.plugins_status).insert(self.name.clone(), Arc::new(callback));
+    }
+
+    /// Removes the callback set by [`PluginContext::set_status`].
+    pub fn clear_status(&self) {
+        zwrite!(self.runtime.plugins_status).remove(&self.name);
+    }
+
+    /// Returns a summary of the health of the runtime hosting the plugin.
+    pub async fn health(&self) -> HealthReport {
+        self.runtime.health().await
+    }
+}
+
+pub(crate) fn status(runtime: &Runtime, name: &str) -> Option<serde_json::Value> {
+    let callback = zread!(runtime.plugins_status).get(name).cloned();
+    callback.map(|callback| callback())
+}
@@ -16,7 +16,10 @@
 //!
 //! This module is intended for Zenoh's internal use.
 //!
-//! [Click here for Zenoh's documentation](../../zenoh/index.html)
+#[cfg(feature = "unstable")]
+pub mod api;
+#[cfg(not(feature = "unstable"))]
+pub(crate) mod api;
 pub(crate) mod sealed;
 
 #[zenoh_macros::unstable]
@@ -14,8 +14,8 @@
 
 //! `zenohd`'s plugin system. For more details, consult the [detailed documentation](https://github.com/eclipse-zenoh/roadmap/blob/main/rfcs/ALL/Plugins/Zenoh%20Plugins.md).
 
+pub use super::api::{PluginApi, PluginContext};
 use crate::prelude::Selector;
-pub use crate::runtime::Runtime;
 pub use crate::Result as ZResult;
 use zenoh_core::zconfigurable;
 
@@ -27,7 +27,7 @@ zconfigurable! {
 pub trait ZenohPlugin: Plugin<StartArgs = StartArgs, RunningPlugin = RunningPlugin> {}
 
 /// A zenoh plugin receives a reference to a value of this type when started.
-pub type StartArgs = Runtime;
+pub type StartArgs = PluginApi;
 /// A zenoh plugin, when started, must return this type.
 pub type RunningPlugin = Box<dyn RunningPluginTrait + 'static>;
 
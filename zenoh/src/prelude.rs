@@ -59,8 +59,13 @@ pub(crate) mod common {
     pub use zenoh_protocol::core::Locator;
     /// The global unique id of a zenoh peer.
     pub use zenoh_protocol::core::ZenohId;
+    /// The id of an entity declared by a zenoh peer, and its global unique id.
+    #[zenoh_macros::unstable]
+    pub use zenoh_protocol::core::{EntityGlobalId, EntityId};
 }
 
+pub mod json;
+
 /// Prelude to import when using Zenoh's sync API.
 pub mod sync {
     pub use super::common::*;
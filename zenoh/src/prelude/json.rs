@@ -0,0 +1,428 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! Serializable mirrors of the zenoh types, for machine-readable outputs.
+//!
+//! The admin space replies with these types, and the examples print them one per line when
+//! started with `--json`. Their field names are stable, and deserializing their serialization
+//! gives back the same value.
+//!
+//! ```
+//! use zenoh::prelude::json;
+//! use zenoh::prelude::sync::*;
+//!
+//! let sample = Sample::try_from("demo/example", "value").unwrap();
+//! let json = serde_json::to_string(&json::Sample::from(&sample)).unwrap();
+//! assert_eq!(
+//!     serde_json::from_str::<json::Sample>(&json).unwrap(),
+//!     json::Sample::from(&sample)
+//! );
+//! ```
+use crate::buffers::ZBuf;
+use crate::info;
+use crate::prelude::{CongestionControl, SplitBuffer};
+use crate::query;
+use crate::sample;
+use crate::value;
+use base64::{engine::general_purpose::STANDARD as b64_std_engine, Engine};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use zenoh_link::Link;
+use zenoh_protocol::core::{Locator, ZenohId};
+use zenoh_protocol::scouting;
+use zenoh_transport::{LinkRtt, TransportCloseReason, TransportPeer};
+
+pub use crate::net::runtime::{
+    AcceptHealth, ConnectHealth, HealthReport, HealthStatus, ListenersHealth, PluginHealth,
+};
+
+/// A payload, as text when it is valid UTF-8, encoded in base64 otherwise.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Payload {
+    Text(String),
+    Base64(String),
+}
+
+impl From<&ZBuf> for Payload {
+    fn from(payload: &ZBuf) -> Self {
+        let bytes = payload.contiguous();
+        match std::str::from_utf8(&bytes) {
+            Ok(text) => Payload::Text(text.to_string()),
+            Err(_) => Payload::Base64(b64_std_engine.encode(&bytes)),
+        }
+    }
+}
+
+/// A [`Value`](value::Value).
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Value {
+    /// The encoding of the payload, e.g. `text/plain`.
+    pub encoding: String,
+    pub payload: Payload,
+}
+
+impl From<&value::Value> for Value {
+    fn from(value: &value::Value) -> Self {
+        Value {
+            encoding: value.encoding.to_string(),
+            payload: (&value.payload).into(),
+        }
+    }
+}
+
+/// The QoS of a [`Sample`](sample::Sample).
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QoS {
+    /// The priority, from 1 (`RealTime`) to 7 (`Background`).
+    pub priority: u8,
+    /// `block` or `drop`.
+    pub congestion_control: String,
+    pub express: bool,
+}
+
+impl From<&sample::QoS> for QoS {
+    fn from(qos: &sample::QoS) -> Self {
+        QoS {
+            priority: qos.priority() as u8,
+            congestion_control: match qos.congestion_control() {
+                CongestionControl::Block => "block",
+                CongestionControl::Drop => "drop",
+            }
+            .to_string(),
+            express: qos.express(),
+        }
+    }
+}
+
+/// A [`Sample`](sample::Sample).
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Sample {
+    pub key_expr: String,
+    /// `PUT` or `DELETE`.
+    pub kind: String,
+    /// The encoding of the payload, e.g. `text/plain`.
+    pub encoding: String,
+    pub payload: Payload,
+    /// The timestamp of the sample, formatted as `<time>/<id>`.
+    pub timestamp: Option<String>,
+    pub qos: QoS,
+}
+
+impl From<&sample::Sample> for Sample {
+    fn from(sample: &sample::Sample) -> Self {
+        Sample {
+            key_expr: sample.key_expr.to_string(),
+            kind: sample.kind.to_string(),
+            encoding: sample.value.encoding.to_string(),
+            payload: (&sample.value.payload).into(),
+            timestamp: sample.timestamp.as_ref().map(|t| t.to_string()),
+            qos: sample.qos().into(),
+        }
+    }
+}
+
+/// A [`Reply`](query::Reply): either a `sample` or an `error`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Reply {
+    pub replier_id: ZenohId,
+    pub sample: Option<Sample>,
+    pub error: Option<Value>,
+}
+
+impl From<&query::Reply> for Reply {
+    fn from(reply: &query::Reply) -> Self {
+        let (sample, error) = match &reply.sample {
+            Ok(sample) => (Some(sample.into()), None),
+            Err(value) => (None, Some(value.into())),
+        };
+        Reply {
+            replier_id: reply.replier_id,
+            sample,
+            error,
+        }
+    }
+}
+
+/// A [`Hello`](scouting::Hello) received while scouting.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Hello {
+    pub zid: ZenohId,
+    /// `router`, `peer` or `client`.
+    pub whatami: String,
+    pub locators: Vec<Locator>,
+}
+
+impl From<&scouting::Hello> for Hello {
+    fn from(hello: &scouting::Hello) -> Self {
+        Hello {
+            zid: hello.zid,
+            whatami: hello.whatami.to_string(),
+            locators: hello.locators.clone(),
+        }
+    }
+}
+
+/// A transport with a remote node, as returned by [`SessionInfo`](crate::info::SessionInfo)
+/// or reported in the admin space.
+///
+/// The optional fields are only known for the transports reported in the admin space.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransportInfo {
+    pub zid: ZenohId,
+    /// `router`, `peer` or `client`.
+    pub whatami: String,
+    /// The locators of the remote node the links of the transport are connected to.
+    pub locators: Vec<Locator>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub is_qos: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub is_initiator: Option<bool>,
+    /// The data of a non default priority sent on the transport while it has no QoS.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority_downgrades: Option<usize>,
+    /// The data not sent on the transport because a subscription filter couldn't read its payload.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub malformed_payloads: Option<usize>,
+    /// The samples sent on the transport with a payload mutated by the mutation rules.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mutated_samples: Option<usize>,
+    /// The key expression mappings declared to and by the remote node.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keyexpr_mappings: Option<MappingsInfo>,
+    /// The queries of the remote node admitted and throttled by `routing/query/throttle`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query_throttle: Option<QueryThrottleInfo>,
+    /// The messages and declarations of unknown kinds skipped on the transport, sent by a remote
+    /// node running a newer version, counted by kind.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unknown_kinds: Option<BTreeMap<String, usize>>,
+    /// The statistics of the transport, when zenoh is built with the `stats` feature.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stats: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub links: Option<Vec<LinkInfo>>,
+    /// Why the transport was closed or rejected, for the transports closed recently.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub closed: Option<CloseInfo>,
+}
+
+impl From<&info::TransportInfo> for TransportInfo {
+    fn from(info: &info::TransportInfo) -> Self {
+        TransportInfo {
+            zid: info.zid,
+            whatami: info.whatami.to_string(),
+            locators: info.locators.clone(),
+            is_qos: None,
+            is_initiator: None,
+            priority_downgrades: None,
+            malformed_payloads: None,
+            mutated_samples: None,
+            keyexpr_mappings: None,
+            query_throttle: None,
+            unknown_kinds: None,
+            stats: None,
+            links: None,
+            closed: None,
+        }
+    }
+}
+
+impl From<&TransportPeer> for TransportInfo {
+    fn from(peer: &TransportPeer) -> Self {
+        TransportInfo {
+            zid: peer.zid,
+            whatami: peer.whatami.to_string(),
+            locators: peer.links.iter().map(|link| link.dst.clone()).collect(),
+            is_qos: Some(peer.is_qos),
+            is_initiator: Some(peer.is_initiator),
+            priority_downgrades: None,
+            malformed_payloads: None,
+            mutated_samples: None,
+            keyexpr_mappings: None,
+            query_throttle: None,
+            unknown_kinds: None,
+            stats: None,
+            links: None,
+            closed: None,
+        }
+    }
+}
+
+/// The key expression mappings of a transport, reported in the admin space.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MappingsInfo {
+    /// The mappings declared to the remote node and in use.
+    pub local: usize,
+    /// The mappings declared to the remote node, evicted by `limits/keyexpr_mappings/max_local`
+    /// and waiting for their grace period to be undeclared.
+    pub evicted: usize,
+    /// The mappings declared by the remote node.
+    pub remote: usize,
+    /// An estimate of the memory used by the mappings, in bytes.
+    pub memory: usize,
+}
+
+/// The queries of a transport rate limited by the router, reported in the admin space.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QueryThrottleInfo {
+    /// The queries admitted by the rate limits.
+    pub admitted: u64,
+    /// The queries throttled by the rate limits.
+    pub throttled: u64,
+}
+
+/// The reason a transport was closed or rejected, reported in the admin space.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CloseInfo {
+    /// The reason, e.g. `GENERIC`, `EXPIRED` or `MAX_SESSIONS`.
+    pub reason: String,
+    /// Whether the close was decided by the remote node.
+    pub remote: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+impl From<&TransportCloseReason> for CloseInfo {
+    fn from(reason: &TransportCloseReason) -> Self {
+        CloseInfo {
+            reason: reason.reason.to_string(),
+            remote: reason.remote,
+            detail: reason.detail.clone(),
+        }
+    }
+}
+
+/// A link of a transport, reported in the admin space.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LinkInfo {
+    pub src: Locator,
+    pub dst: Locator,
+    /// The smoothed round-trip time of the link in microseconds, estimated when the keep-alives
+    /// request an echo (`transport/link/rtt_probe`) and the remote node supports it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rtt_us: Option<u64>,
+    /// The jitter of the round-trip time of the link in microseconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rtt_jitter_us: Option<u64>,
+}
+
+impl From<&(Link, Option<LinkRtt>)> for LinkInfo {
+    fn from((link, rtt): &(Link, Option<LinkRtt>)) -> Self {
+        let micros = |d: std::time::Duration| u64::try_from(d.as_micros()).unwrap_or(u64::MAX);
+        LinkInfo {
+            src: link.src.clone(),
+            dst: link.dst.clone(),
+            rtt_us: rtt.map(|rtt| micros(rtt.srtt)),
+            rtt_jitter_us: rtt.map(|rtt| micros(rtt.jitter)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::de::DeserializeOwned;
+
+    fn round_trip<T: Serialize + DeserializeOwned + PartialEq + std::fmt::Debug>(value: T) {
+        let json = serde_json::to_string(&value).unwrap();
+        assert_eq!(serde_json::from_str::<T>(&json).unwrap(), value, "{json}");
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let zid = ZenohId::rand();
+        let locators: Vec<Locator> = vec!["tcp/127.0.0.1:7447".parse().unwrap()];
+
+        let text = sample::Sample::try_from("demo/example/text", "value").unwrap();
+        assert_eq!(
+            Sample::from(&text).payload,
+            Payload::Text("value".to_string())
+        );
+        round_trip(Sample::from(&text));
+        let binary = sample::Sample::try_from("demo/example/binary", vec![0xffu8, 0xfe]).unwrap();
+        assert_eq!(
+            Sample::from(&binary).payload,
+            Payload::Base64("//4=".to_string())
+        );
+        round_trip(Sample::from(&binary));
+
+        round_trip(Reply::from(&query::Reply {
+            sample: Ok(text),
+            replier_id: zid,
+            replier_eid: Some(1),
+            retry_after: None,
+        }));
+        round_trip(Reply::from(&query::Reply {
+            sample: Err("error".into()),
+            replier_id: zid,
+            replier_eid: None,
+            retry_after: None,
+        }));
+
+        round_trip(Hello::from(&scouting::Hello {
+            version: 0,
+            whatami: zenoh_protocol::core::WhatAmI::Router,
+            zid,
+            locators: locators.clone(),
+        }));
+
+        let mut transport = TransportInfo::from(&info::TransportInfo {
+            zid,
+            whatami: zenoh_protocol::core::WhatAmI::Peer,
+            locators,
+        });
+        round_trip(transport.clone());
+        transport.is_qos = Some(true);
+        transport.priority_downgrades = Some(3);
+        transport.malformed_payloads = Some(1);
+        transport.mutated_samples = Some(2);
+        transport.unknown_kinds = Some(BTreeMap::from([("network message 0x10".into(), 4)]));
+        transport.stats = Some(serde_json::json!({ "rx_bytes": 42 }));
+        transport.links = Some(vec![
+            LinkInfo {
+                src: "tcp/127.0.0.1:7447".parse().unwrap(),
+                dst: "tcp/127.0.0.1:7448".parse().unwrap(),
+                rtt_us: Some(1_250),
+                rtt_jitter_us: Some(125),
+            },
+            LinkInfo {
+                src: "udp/127.0.0.1:7447".parse().unwrap(),
+                dst: "udp/127.0.0.1:7448".parse().unwrap(),
+                rtt_us: None,
+                rtt_jitter_us: None,
+            },
+        ]);
+        round_trip(transport);
+
+        round_trip(HealthReport {
+            status: HealthStatus::Degraded,
+            reasons: vec!["reason".to_string()],
+            listeners: ListenersHealth {
+                configured: vec!["tcp/127.0.0.1:7447".parse().unwrap()],
+                bound: vec![],
+            },
+            connect: vec![],
+            plugins: vec![PluginHealth {
+                name: "rest".to_string(),
+                running: true,
+            }],
+            accept: AcceptHealth {
+                pending: 1,
+                limit: 100,
+            },
+            last_timestamp: Some(std::time::SystemTime::UNIX_EPOCH),
+        });
+    }
+}
@@ -14,17 +14,32 @@
 
 //! Publishing primitives.
 
-use crate::net::transport::Primitives;
+use crate::net::transport::PushReport;
 use crate::prelude::*;
-use crate::sample::DataInfo;
+use crate::sample::{DataInfo, SampleIntegrity, SourceSn};
+use crate::time::Timestamp;
 use crate::Encoding;
 use crate::SessionRef;
 use crate::Undeclarable;
+use async_std::prelude::FutureExt as _;
+use event_listener::Event;
+use futures::future::BoxFuture;
+use std::fmt;
 use std::future::Ready;
-use zenoh_core::{zread, AsyncResolve, Resolvable, Resolve, SyncResolve};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use stop_token::future::FutureExt as _;
+use stop_token::StopSource;
+use zenoh_core::{zlock, zread, AsyncResolve, Resolvable, Resolve, SyncResolve};
+#[zenoh_macros::unstable]
+use zenoh_protocol::core::EntityGlobalId;
+use zenoh_protocol::core::EntityId;
 use zenoh_protocol::network::push::ext;
 use zenoh_protocol::network::Mapping;
 use zenoh_protocol::network::Push;
+use zenoh_protocol::zenoh::ext::BatchEntry;
+use zenoh_protocol::zenoh::put;
 use zenoh_protocol::zenoh::Del;
 use zenoh_protocol::zenoh::PushBody;
 use zenoh_protocol::zenoh::Put;
@@ -74,6 +89,7 @@ pub struct PutBuilder<'a, 'b> {
     pub(crate) publisher: PublisherBuilder<'a, 'b>,
     pub(crate) value: Value,
     pub(crate) kind: SampleKind,
+    pub(crate) timestamp: Option<Timestamp>,
 }
 
 impl PutBuilder<'_, '_> {
@@ -109,88 +125,448 @@ impl PutBuilder<'_, '_> {
         self
     }
 
+    /// Fail when no remote route exists for the published data while its destination is
+    /// restricted to [`Locality::Remote`](crate::prelude::Locality::Remote).
+    #[zenoh_macros::unstable]
+    #[inline]
+    pub fn strict_destination(mut self, strict: bool) -> Self {
+        self.publisher = self.publisher.strict_destination(strict);
+        self
+    }
+
+    /// Fail when the data is dropped while being scheduled on a transport, e.g. under
+    /// [`CongestionControl::Drop`].
+    #[inline]
+    pub fn report_drops(mut self, report_drops: bool) -> Self {
+        self.publisher = self.publisher.report_drops(report_drops);
+        self
+    }
+
+    /// Complete the resolution only once the first hops acknowledged the data, see
+    /// [`PublisherBuilder::wait_for_ack`].
+    #[zenoh_macros::unstable]
+    #[inline]
+    pub fn wait_for_ack(mut self) -> Self {
+        self.publisher = self.publisher.wait_for_ack();
+        self
+    }
+
+    /// Don't deliver the published data to the subscribers of the same session, see
+    /// [`PublisherBuilder::no_local`].
+    #[inline]
+    pub fn no_local(mut self, no_local: bool) -> Self {
+        self.publisher = self.publisher.no_local(no_local);
+        self
+    }
+
     pub fn kind(mut self, kind: SampleKind) -> Self {
         self.kind = kind;
         self
     }
+
+    /// Set the [`Timestamp`] of the written data, instead of generating a new one from the HLC
+    /// of the session.
+    ///
+    /// Meant to replay or replicate data with its original timestamp, e.g. to test the storages
+    /// receiving data out of order: the timestamp is not checked against the HLC, and a
+    /// timestamp older than the one of the data a storage holds for the key gets discarded.
+    #[zenoh_macros::unstable]
+    #[inline]
+    pub fn timestamp(mut self, timestamp: Timestamp) -> Self {
+        self.timestamp = Some(timestamp);
+        self
+    }
 }
 
 impl Resolvable for PutBuilder<'_, '_> {
     type To = ZResult<()>;
 }
 
-impl SyncResolve for PutBuilder<'_, '_> {
-    #[inline]
-    fn res_sync(self) -> <Self as Resolvable>::To {
+impl PutBuilder<'_, '_> {
+    // Schedules the publication, returning the acknowledgements to wait for. `dropped` tells
+    // whether the publication is dropped while the client runtime is reconnecting.
+    fn publish(self, dropped: bool) -> ZResult<Vec<flume::Receiver<()>>> {
         let PutBuilder {
             publisher,
             value,
             kind,
+            timestamp,
         } = self;
+        let local = delivers_locally(
+            &publisher.session,
+            publisher.destination,
+            publisher.no_local,
+        );
+        let qos = publisher.qos();
         let key_expr = publisher.key_expr?;
+        publisher.session.check_alive()?;
+        publisher.session.check_update_key_expr(&key_expr)?;
         log::trace!("write({:?}, [...])", &key_expr);
         let primitives = zread!(publisher.session.state)
             .primitives
             .as_ref()
             .unwrap()
             .clone();
-        let timestamp = publisher.session.runtime.new_timestamp();
-
-        if publisher.destination != Locality::SessionLocal {
-            primitives.send_push(Push {
-                wire_expr: key_expr.to_wire(&publisher.session).to_owned(),
-                ext_qos: ext::QoSType::new(
-                    publisher.priority.into(),
-                    publisher.congestion_control,
-                    false,
-                ),
-                ext_tstamp: None,
-                ext_nodeid: ext::NodeIdType::default(),
-                payload: match kind {
+        let timestamp = timestamp.or_else(|| publisher.session.runtime.new_timestamp());
+
+        let report = if publisher.destination == Locality::SessionLocal {
+            PushReport::default()
+        } else if dropped {
+            // Dropped while the client is reconnecting
+            PushReport {
+                dropped: 1,
+                ..Default::default()
+            }
+        } else {
+            primitives.send_push_reported(
+                Push {
+                    wire_expr: key_expr.to_wire(&publisher.session).to_owned(),
+                    ext_qos: qos,
+                    ext_tstamp: None,
+                    ext_nodeid: ext::NodeIdType::default(),
+                    payload: match kind {
+                        SampleKind::Put => PushBody::Put(Put {
+                            timestamp,
+                            encoding: value.encoding.clone(),
+                            ext_sinfo: None,
+                            #[cfg(feature = "shared-memory")]
+                            ext_shm: None,
+                            ext_integrity: publisher
+                                .session
+                                .runtime
+                                .integrity
+                                .checksum(&value.payload),
+                            ext_batch: None,
+                            ext_unknown: vec![],
+                            payload: value.payload.clone(),
+                        }),
+                        SampleKind::Delete => PushBody::Del(Del {
+                            timestamp,
+                            ext_sinfo: None,
+                            ext_batch: None,
+                            ext_unknown: vec![],
+                        }),
+                    },
+                },
+                Reliability::Reliable,
+                publisher.wait_for_ack,
+            )
+        };
+        if local {
+            let data_info = DataInfo {
+                kind,
+                encoding: Some(value.encoding),
+                timestamp,
+                qos: qos.into(),
+                ..Default::default()
+            };
+
+            publisher.session.handle_data(
+                true,
+                &key_expr.to_wire(&publisher.session),
+                Some(data_info),
+                value.payload,
+            );
+        }
+        resolve_push_report(
+            report,
+            publisher.destination,
+            publisher.strict_destination,
+            publisher.report_drops,
+            publisher.wait_for_ack,
+        )
+    }
+}
+
+impl SyncResolve for PutBuilder<'_, '_> {
+    #[inline]
+    fn res_sync(self) -> <Self as Resolvable>::To {
+        let publisher = &self.publisher;
+        let dropped = publisher.destination != Locality::SessionLocal
+            && publisher
+                .session
+                .runtime
+                .hold_publication(publisher.congestion_control);
+        wait_acks(self.publish(dropped)?)
+    }
+}
+
+impl<'a, 'b: 'a> AsyncResolve for PutBuilder<'a, 'b> {
+    type Future = BoxFuture<'a, Self::To>;
+
+    fn res_async(self) -> Self::Future {
+        Box::pin(async move {
+            let publisher = &self.publisher;
+            let dropped = publisher.destination != Locality::SessionLocal
+                && publisher
+                    .session
+                    .runtime
+                    .hold_publication_async(publisher.congestion_control)
+                    .await;
+            wait_acks_async(self.publish(dropped)?).await
+        })
+    }
+}
+
+/// A builder for initializing a [`put_batch`](crate::Session::put_batch) operation.
+///
+/// The entries of a batch share the same timestamp, priority and congestion control, and are
+/// published in a single message. The routers forward to each destination the entries it
+/// subscribed to, again in a single message. The subscribers declared with
+/// [`batched`](crate::subscriber::SubscriberBuilder::batched) receive them together, while the
+/// other subscribers receive them as individual samples.
+///
+/// # Examples
+/// ```
+/// # async_std::task::block_on(async {
+/// use zenoh::prelude::r#async::*;
+///
+/// let session = zenoh::open(config::peer()).res().await.unwrap();
+/// session
+///     .put_batch()
+///     .put("pose/x", 1.0)
+///     .put("pose/y", 2.0)
+///     .delete("pose/z")
+///     .res()
+///     .await
+///     .unwrap();
+/// # })
+/// ```
+#[derive(Debug, Clone)]
+pub struct PutBatchBuilder<'a, 'b> {
+    pub(crate) session: &'a crate::Session,
+    pub(crate) entries: Vec<PutBuilder<'a, 'b>>,
+    pub(crate) congestion_control: CongestionControl,
+    pub(crate) priority: Priority,
+}
+
+impl<'a, 'b: 'a> PutBatchBuilder<'a, 'b> {
+    /// Add the put of `value` on `key_expr` to the batch.
+    #[inline]
+    pub fn put<TryIntoKeyExpr, IntoValue>(self, key_expr: TryIntoKeyExpr, value: IntoValue) -> Self
+    where
+        TryIntoKeyExpr: TryInto<KeyExpr<'b>>,
+        <TryIntoKeyExpr as TryInto<KeyExpr<'b>>>::Error: Into<zenoh_result::Error>,
+        IntoValue: Into<Value>,
+    {
+        self.entry(key_expr, value, SampleKind::Put)
+    }
+
+    /// Add the delete of `key_expr` to the batch.
+    #[inline]
+    pub fn delete<TryIntoKeyExpr>(self, key_expr: TryIntoKeyExpr) -> Self
+    where
+        TryIntoKeyExpr: TryInto<KeyExpr<'b>>,
+        <TryIntoKeyExpr as TryInto<KeyExpr<'b>>>::Error: Into<zenoh_result::Error>,
+    {
+        self.entry(key_expr, Value::empty(), SampleKind::Delete)
+    }
+
+    /// Add a sample of the given `kind` on `key_expr` to the batch.
+    #[inline]
+    pub fn entry<TryIntoKeyExpr, IntoValue>(
+        mut self,
+        key_expr: TryIntoKeyExpr,
+        value: IntoValue,
+        kind: SampleKind,
+    ) -> Self
+    where
+        TryIntoKeyExpr: TryInto<KeyExpr<'b>>,
+        <TryIntoKeyExpr as TryInto<KeyExpr<'b>>>::Error: Into<zenoh_result::Error>,
+        IntoValue: Into<Value>,
+    {
+        let entry = self.session.put(key_expr, value).kind(kind);
+        self.entries.push(entry);
+        self
+    }
+
+    /// Change the `congestion_control` to apply when routing the batch.
+    #[inline]
+    pub fn congestion_control(mut self, congestion_control: CongestionControl) -> Self {
+        self.congestion_control = congestion_control;
+        self
+    }
+
+    /// Change the priority of the batch.
+    #[inline]
+    pub fn priority(mut self, priority: Priority) -> Self {
+        self.priority = priority;
+        self
+    }
+}
+
+impl Resolvable for PutBatchBuilder<'_, '_> {
+    type To = ZResult<()>;
+}
+
+impl PutBatchBuilder<'_, '_> {
+    // Publishes the batch in a single message, whose batch extension carries the entries after
+    // the first one. The batch is only delivered locally when `dropped`.
+    fn publish(self, dropped: bool) -> ZResult<()> {
+        let PutBatchBuilder {
+            session,
+            entries,
+            congestion_control,
+            priority,
+        } = self;
+        // Nothing is published when one of the key expressions is invalid
+        let mut resolved = Vec::with_capacity(entries.len());
+        for entry in entries {
+            match entry.publisher.key_expr {
+                Ok(key_expr) => resolved.push((key_expr, entry.value, entry.kind)),
+                Err(e) => bail!("Invalid key expression in batch: {}", e),
+            }
+        }
+        if resolved.is_empty() {
+            return Ok(());
+        }
+        session.check_alive()?;
+        for (key_expr, _, _) in &resolved {
+            session.check_update_key_expr(key_expr)?;
+        }
+        let qos = ext::QoSType::new(priority.into(), congestion_control, false);
+        let timestamp = Some(
+            session
+                .runtime
+                .new_timestamp()
+                .unwrap_or_else(crate::time::new_reception_timestamp),
+        );
+        let id: u64 = rand::random();
+
+        if !dropped {
+            let mut bodies = resolved.iter().map(|(key_expr, value, kind)| {
+                let payload = match kind {
                     SampleKind::Put => PushBody::Put(Put {
                         timestamp,
                         encoding: value.encoding.clone(),
                         ext_sinfo: None,
                         #[cfg(feature = "shared-memory")]
                         ext_shm: None,
+                        ext_integrity: session.runtime.integrity.checksum(&value.payload),
+                        ext_batch: None,
                         ext_unknown: vec![],
                         payload: value.payload.clone(),
                     }),
                     SampleKind::Delete => PushBody::Del(Del {
                         timestamp,
                         ext_sinfo: None,
+                        ext_batch: None,
                         ext_unknown: vec![],
                     }),
-                },
+                };
+                (key_expr.to_wire(session).to_owned(), payload)
             });
-        }
-        if publisher.destination != Locality::Remote {
-            let data_info = DataInfo {
-                kind,
-                encoding: Some(value.encoding),
-                timestamp,
-                ..Default::default()
-            };
-
-            publisher.session.handle_data(
-                true,
-                &key_expr.to_wire(&publisher.session),
-                Some(data_info),
-                value.payload,
+            // The batch isn't empty
+            let (wire_expr, mut payload) = bodies.next().unwrap();
+            let entries = bodies
+                .map(|(wire_expr, payload)| BatchEntry { wire_expr, payload })
+                .collect();
+            *payload.ext_batch_mut() = Some(put::ext::BatchType { id, entries });
+            let primitives = zread!(session.state).primitives.as_ref().unwrap().clone();
+            primitives.send_push(
+                Push {
+                    wire_expr,
+                    ext_qos: qos,
+                    ext_tstamp: None,
+                    ext_nodeid: ext::NodeIdType::default(),
+                    payload,
+                },
+                Reliability::Reliable,
             );
         }
+        if session.runtime.local_routing {
+            let entries = resolved
+                .into_iter()
+                .map(|(key_expr, value, kind)| {
+                    let info = DataInfo {
+                        kind,
+                        encoding: Some(value.encoding),
+                        timestamp,
+                        qos: qos.into(),
+                        ..Default::default()
+                    };
+                    (key_expr.to_wire(session).to_owned(), info, value.payload)
+                })
+                .collect();
+            session.handle_batch(true, id, entries);
+        }
         Ok(())
     }
 }
 
-impl AsyncResolve for PutBuilder<'_, '_> {
-    type Future = Ready<Self::To>;
+impl SyncResolve for PutBatchBuilder<'_, '_> {
+    fn res_sync(self) -> <Self as Resolvable>::To {
+        let dropped = self
+            .session
+            .runtime
+            .hold_publication(self.congestion_control);
+        self.publish(dropped)
+    }
+}
+
+impl<'a, 'b: 'a> AsyncResolve for PutBatchBuilder<'a, 'b> {
+    type Future = BoxFuture<'a, Self::To>;
 
     fn res_async(self) -> Self::Future {
-        std::future::ready(self.res_sync())
+        Box::pin(async move {
+            let dropped = self
+                .session
+                .runtime
+                .hold_publication_async(self.congestion_control)
+                .await;
+            self.publish(dropped)
+        })
     }
 }
 
+// Whether the data published with the given destination is delivered to the subscribers of the
+// same session.
+fn delivers_locally(session: &Session, destination: Locality, no_local: bool) -> bool {
+    destination != Locality::Remote && !no_local && session.runtime.local_routing
+}
+
+// Turns the report of the scheduling of a publication into the result of its resolution,
+// returning the acknowledgements it contains.
+fn resolve_push_report(
+    report: PushReport,
+    destination: Locality,
+    strict_destination: bool,
+    report_drops: bool,
+    wait_for_ack: bool,
+) -> ZResult<Vec<flume::Receiver<()>>> {
+    if strict_destination
+        && destination == Locality::Remote
+        && report.scheduled == 0
+        && report.dropped == 0
+    {
+        bail!("No route for the publication to a remote destination");
+    }
+    // A dropped publication is never acknowledged
+    if (report_drops || wait_for_ack) && report.dropped > 0 {
+        bail!("Publication dropped by {} transport(s)", report.dropped);
+    }
+    Ok(report.acks)
+}
+
+// Waits for the acknowledgements of the first hops of a publication.
+fn wait_acks(acks: Vec<flume::Receiver<()>>) -> ZResult<()> {
+    for ack in acks {
+        if ack.recv().is_err() {
+            bail!("Publication lost before being acknowledged by the first hop");
+        }
+    }
+    Ok(())
+}
+
+async fn wait_acks_async(acks: Vec<flume::Receiver<()>>) -> ZResult<()> {
+    for ack in acks {
+        if ack.recv_async().await.is_err() {
+            bail!("Publication lost before being acknowledged by the first hop");
+        }
+    }
+    Ok(())
+}
+
 use futures::Sink;
 use std::convert::TryFrom;
 use std::convert::TryInto;
@@ -230,13 +606,32 @@ use zenoh_result::Error;
 #[derive(Debug, Clone)]
 pub struct Publisher<'a> {
     pub(crate) session: SessionRef<'a>,
+    pub(crate) id: Id,
+    pub(crate) sn: Arc<AtomicU32>,
     pub(crate) key_expr: KeyExpr<'a>,
     pub(crate) congestion_control: CongestionControl,
     pub(crate) priority: Priority,
     pub(crate) destination: Locality,
+    pub(crate) strict_destination: bool,
+    pub(crate) report_drops: bool,
+    pub(crate) wait_for_ack: bool,
+    pub(crate) no_local: bool,
+    pub(crate) source_info: bool,
 }
 
 impl<'a> Publisher<'a> {
+    /// Returns the [`EntityGlobalId`] of this Publisher.
+    ///
+    /// It is reported in the [`SourceInfo`](crate::sample::SourceInfo) of the samples it publishes
+    /// when built with [`source_info`](PublisherBuilder::source_info).
+    #[zenoh_macros::unstable]
+    pub fn id(&self) -> EntityGlobalId {
+        EntityGlobalId {
+            zid: self.session.zid(),
+            eid: self.id as EntityId,
+        }
+    }
+
     pub fn key_expr(&self) -> &KeyExpr<'a> {
         &self.key_expr
     }
@@ -264,6 +659,45 @@ impl<'a> Publisher<'a> {
         self
     }
 
+    /// Fail when no remote route exists for the published data while its destination is
+    /// restricted to [`Locality::Remote`](crate::prelude::Locality::Remote).
+    #[zenoh_macros::unstable]
+    #[inline]
+    pub fn strict_destination(mut self, strict: bool) -> Self {
+        self.strict_destination = strict;
+        self
+    }
+
+    /// Fail when the data is dropped while being scheduled on a transport, e.g. under
+    /// [`CongestionControl::Drop`].
+    #[inline]
+    pub fn report_drops(mut self, report_drops: bool) -> Self {
+        self.report_drops = report_drops;
+        self
+    }
+
+    /// Complete the resolution only once the first hops acknowledged the data, see
+    /// [`PublisherBuilder::wait_for_ack`].
+    #[zenoh_macros::unstable]
+    #[inline]
+    pub fn wait_for_ack(mut self) -> Self {
+        self.wait_for_ack = true;
+        self
+    }
+
+    /// Don't deliver the published data to the subscribers of the same session, see
+    /// [`PublisherBuilder::no_local`].
+    #[inline]
+    pub fn no_local(mut self, no_local: bool) -> Self {
+        self.no_local = no_local;
+        self
+    }
+
+    // The QoS of the published data.
+    fn qos(&self) -> ext::QoSType {
+        ext::QoSType::new(self.priority.into(), self.congestion_control, false)
+    }
+
     fn _write(&self, kind: SampleKind, value: Value) -> Publication {
         Publication {
             publisher: self,
@@ -342,6 +776,186 @@ impl<'a> Publisher<'a> {
     pub fn undeclare(self) -> impl Resolve<ZResult<()>> + 'a {
         Undeclarable::undeclare_inner(self, ())
     }
+
+    /// Publish the values returned by `supplier` every `period`, until the returned
+    /// [`PeriodicPublication`] is dropped.
+    ///
+    /// The timer is driven by the session's runtime, and the values are published with the QoS
+    /// of this publisher. The first value is published one `period` after this call. A tick
+    /// occurring while the previous publication is still blocked, e.g. by
+    /// [`CongestionControl::Block`] on a congested link, is skipped rather than delayed, so that
+    /// the publications never burst: see [`PeriodicPublication::skipped`].
+    ///
+    /// # Panics
+    /// Panics if `period` is zero.
+    ///
+    /// # Examples
+    /// ```
+    /// # async_std::task::block_on(async {
+    /// use std::time::Duration;
+    /// use zenoh::prelude::r#async::*;
+    ///
+    /// let session = zenoh::open(config::peer()).res().await.unwrap();
+    /// let publisher = session.declare_publisher("key/expression").res().await.unwrap();
+    /// let heartbeat = publisher.publish_periodically(Duration::from_secs(1), || "alive".into());
+    /// heartbeat.set_period(Duration::from_millis(500));
+    /// # })
+    /// ```
+    pub fn publish_periodically<F>(&self, period: Duration, supplier: F) -> PeriodicPublication
+    where
+        F: FnMut() -> Value + Send + 'static,
+    {
+        assert!(
+            !period.is_zero(),
+            "The period of a publication can't be zero"
+        );
+        let publisher = Arc::new(Publisher {
+            session: SessionRef::Shared(Arc::new(Session::clone(&self.session))),
+            id: self.id,
+            sn: self.sn.clone(),
+            key_expr: self.key_expr.clone().into_owned(),
+            congestion_control: self.congestion_control,
+            priority: self.priority,
+            destination: self.destination,
+            strict_destination: self.strict_destination,
+            report_drops: self.report_drops,
+            wait_for_ack: self.wait_for_ack,
+            no_local: self.no_local,
+            source_info: self.source_info,
+        });
+        let state = Arc::new(PeriodicState {
+            period: Mutex::new(period),
+            period_changed: Event::new(),
+            busy: AtomicBool::new(false),
+            published: AtomicU64::new(0),
+            skipped: AtomicU64::new(0),
+        });
+        let stop_source = StopSource::new();
+        let start = self.session.runtime.clock.now();
+        self.session.runtime.spawn(
+            publish_periodically(publisher, state.clone(), start, supplier)
+                .timeout_at(stop_source.token()),
+        );
+        PeriodicPublication {
+            state,
+            _stop_source: stop_source,
+        }
+    }
+}
+
+struct PeriodicState {
+    period: Mutex<Duration>,
+    period_changed: Event,
+    // Whether a publication is in progress
+    busy: AtomicBool,
+    published: AtomicU64,
+    skipped: AtomicU64,
+}
+
+async fn publish_periodically<F>(
+    publisher: Arc<Publisher<'static>>,
+    state: Arc<PeriodicState>,
+    mut last: Instant,
+    mut supplier: F,
+) where
+    F: FnMut() -> Value + Send + 'static,
+{
+    let clock = publisher.session.runtime.clock.clone();
+    loop {
+        // Listen before reading the period, not to miss a change in between
+        let listener = state.period_changed.listen();
+        let period = *zlock!(state.period);
+        let deadline = last + period;
+        let now = clock.now();
+        if now < deadline {
+            clock.sleep(deadline - now).race(listener).await;
+            continue;
+        }
+
+        // Don't catch up with the ticks missed while late, and stay in phase with the deadlines
+        let late = now - deadline;
+        let missed = late.as_nanos() / period.as_nanos();
+        last = now - Duration::from_nanos((late.as_nanos() % period.as_nanos()) as u64);
+        state.skipped.fetch_add(missed as u64, Ordering::Relaxed);
+
+        if state.busy.swap(true, Ordering::AcqRel) {
+            log::trace!(
+                "Skipping a periodic publication on {}: the previous one is blocked",
+                publisher.key_expr
+            );
+            state.skipped.fetch_add(1, Ordering::Relaxed);
+            continue;
+        }
+        let value = supplier();
+        let publisher = publisher.clone();
+        let state = state.clone();
+        // The publication may block: don't delay the next ticks with it
+        async_std::task::spawn_blocking(move || {
+            let res = publisher.put(value).res_sync();
+            state.busy.store(false, Ordering::Release);
+            match res {
+                Ok(()) => {
+                    state.published.fetch_add(1, Ordering::Relaxed);
+                }
+                Err(e) => log::warn!(
+                    "Periodic publication on {} failed: {}",
+                    publisher.key_expr,
+                    e
+                ),
+            }
+        });
+    }
+}
+
+/// A periodic publication returned by [`Publisher::publish_periodically`].
+///
+/// The publications stop when it is dropped.
+pub struct PeriodicPublication {
+    state: Arc<PeriodicState>,
+    _stop_source: StopSource,
+}
+
+impl PeriodicPublication {
+    /// The current period of the publications.
+    pub fn period(&self) -> Duration {
+        *zlock!(self.state.period)
+    }
+
+    /// Change the period of the publications.
+    ///
+    /// The next value is published one `period` after the last tick.
+    ///
+    /// # Panics
+    /// Panics if `period` is zero.
+    pub fn set_period(&self, period: Duration) {
+        assert!(
+            !period.is_zero(),
+            "The period of a publication can't be zero"
+        );
+        *zlock!(self.state.period) = period;
+        self.state.period_changed.notify(usize::MAX);
+    }
+
+    /// The number of values published so far.
+    pub fn published(&self) -> u64 {
+        self.state.published.load(Ordering::Relaxed)
+    }
+
+    /// The number of ticks skipped so far, because the previous publication was still blocked
+    /// or because the timer was late.
+    pub fn skipped(&self) -> u64 {
+        self.state.skipped.load(Ordering::Relaxed)
+    }
+}
+
+impl fmt::Debug for PeriodicPublication {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PeriodicPublication")
+            .field("period", &self.period())
+            .field("published", &self.published())
+            .field("skipped", &self.skipped())
+            .finish()
+    }
 }
 
 impl<'a> Undeclarable<(), PublisherUndeclaration<'a>> for Publisher<'a> {
@@ -414,47 +1028,76 @@ impl Resolvable for Publication<'_> {
     type To = ZResult<()>;
 }
 
-impl SyncResolve for Publication<'_> {
-    fn res_sync(self) -> <Self as Resolvable>::To {
+impl Publication<'_> {
+    // Schedules the publication, returning the acknowledgements to wait for. `dropped` tells
+    // whether the publication is dropped while the client runtime is reconnecting.
+    fn publish(self, dropped: bool) -> ZResult<Vec<flume::Receiver<()>>> {
         let Publication {
             publisher,
             value,
             kind,
         } = self;
+        publisher.session.check_alive()?;
+        publisher
+            .session
+            .check_update_key_expr(&publisher.key_expr)?;
         log::trace!("write({:?}, [...])", publisher.key_expr);
         let primitives = zread!(publisher.session.state)
             .primitives
             .as_ref()
             .unwrap()
             .clone();
+        let source_info = publisher.source_info.then(|| put::ext::SourceInfoType {
+            zid: publisher.session.zid(),
+            eid: publisher.id as EntityId,
+            sn: publisher.sn.fetch_add(1, Ordering::Relaxed),
+        });
 
-        if publisher.destination != Locality::SessionLocal {
-            primitives.send_push(Push {
-                wire_expr: publisher.key_expr.to_wire(&publisher.session).to_owned(),
-                ext_qos: ext::QoSType::new(
-                    publisher.priority.into(),
-                    publisher.congestion_control,
-                    false,
-                ),
-                ext_tstamp: None,
-                ext_nodeid: ext::NodeIdType::default(),
-                payload: PushBody::Put(Put {
-                    timestamp: publisher.session.runtime.new_timestamp(),
-                    encoding: value.encoding.clone(),
-                    ext_sinfo: None,
-                    #[cfg(feature = "shared-memory")]
-                    ext_shm: None,
-                    ext_unknown: vec![],
-                    payload: value.payload.clone(),
-                }),
-            });
-        }
-        if publisher.destination != Locality::Remote {
+        let report = if publisher.destination == Locality::SessionLocal {
+            PushReport::default()
+        } else if dropped {
+            // Dropped while the client is reconnecting
+            PushReport {
+                dropped: 1,
+                ..Default::default()
+            }
+        } else {
+            primitives.send_push_reported(
+                Push {
+                    wire_expr: publisher.key_expr.to_wire(&publisher.session).to_owned(),
+                    ext_qos: publisher.qos(),
+                    ext_tstamp: None,
+                    ext_nodeid: ext::NodeIdType::default(),
+                    payload: PushBody::Put(Put {
+                        timestamp: publisher.session.runtime.new_timestamp(),
+                        encoding: value.encoding.clone(),
+                        ext_sinfo: source_info.clone(),
+                        #[cfg(feature = "shared-memory")]
+                        ext_shm: None,
+                        ext_integrity: publisher.session.runtime.integrity.checksum(&value.payload),
+                        ext_batch: None,
+                        ext_unknown: vec![],
+                        payload: value.payload.clone(),
+                    }),
+                },
+                Reliability::Reliable,
+                publisher.wait_for_ack,
+            )
+        };
+        if delivers_locally(
+            &publisher.session,
+            publisher.destination,
+            publisher.no_local,
+        ) {
             let data_info = DataInfo {
                 kind,
                 encoding: Some(value.encoding),
                 timestamp: publisher.session.runtime.new_timestamp(),
-                ..Default::default()
+                source_id: source_info.as_ref().map(|i| i.zid),
+                source_eid: source_info.as_ref().map(|i| i.eid),
+                source_sn: source_info.as_ref().map(|i| i.sn as SourceSn),
+                qos: publisher.qos().into(),
+                integrity: SampleIntegrity::Unchecked,
             };
             publisher.session.handle_data(
                 true,
@@ -463,15 +1106,42 @@ impl SyncResolve for Publication<'_> {
                 value.payload,
             );
         }
-        Ok(())
+        resolve_push_report(
+            report,
+            publisher.destination,
+            publisher.strict_destination,
+            publisher.report_drops,
+            publisher.wait_for_ack,
+        )
     }
 }
 
-impl AsyncResolve for Publication<'_> {
-    type Future = Ready<Self::To>;
+impl SyncResolve for Publication<'_> {
+    fn res_sync(self) -> <Self as Resolvable>::To {
+        let publisher = self.publisher;
+        let dropped = publisher.destination != Locality::SessionLocal
+            && publisher
+                .session
+                .runtime
+                .hold_publication(publisher.congestion_control);
+        wait_acks(self.publish(dropped)?)
+    }
+}
+
+impl<'a> AsyncResolve for Publication<'a> {
+    type Future = BoxFuture<'a, Self::To>;
 
     fn res_async(self) -> Self::Future {
-        std::future::ready(self.res_sync())
+        Box::pin(async move {
+            let publisher = self.publisher;
+            let dropped = publisher.destination != Locality::SessionLocal
+                && publisher
+                    .session
+                    .runtime
+                    .hold_publication_async(publisher.congestion_control)
+                    .await;
+            wait_acks_async(self.publish(dropped)?).await
+        })
     }
 }
 
@@ -526,6 +1196,11 @@ pub struct PublisherBuilder<'a, 'b: 'a> {
     pub(crate) congestion_control: CongestionControl,
     pub(crate) priority: Priority,
     pub(crate) destination: Locality,
+    pub(crate) strict_destination: bool,
+    pub(crate) report_drops: bool,
+    pub(crate) wait_for_ack: bool,
+    pub(crate) no_local: bool,
+    pub(crate) source_info: bool,
 }
 
 impl<'a, 'b> Clone for PublisherBuilder<'a, 'b> {
@@ -539,6 +1214,11 @@ impl<'a, 'b> Clone for PublisherBuilder<'a, 'b> {
             congestion_control: self.congestion_control,
             priority: self.priority,
             destination: self.destination,
+            strict_destination: self.strict_destination,
+            report_drops: self.report_drops,
+            wait_for_ack: self.wait_for_ack,
+            no_local: self.no_local,
+            source_info: self.source_info,
         }
     }
 }
@@ -566,6 +1246,69 @@ impl<'a, 'b> PublisherBuilder<'a, 'b> {
         self.destination = destination;
         self
     }
+
+    /// Fail when no remote route exists for the published data while its destination is
+    /// restricted to [`Locality::Remote`](crate::prelude::Locality::Remote).
+    #[zenoh_macros::unstable]
+    #[inline]
+    pub fn strict_destination(mut self, strict: bool) -> Self {
+        self.strict_destination = strict;
+        self
+    }
+
+    /// Fail when the data is dropped while being scheduled on a transport, e.g. under
+    /// [`CongestionControl::Drop`]. A transport that closed without being removed from the
+    /// routing yet also drops the data.
+    #[inline]
+    pub fn report_drops(mut self, report_drops: bool) -> Self {
+        self.report_drops = report_drops;
+        self
+    }
+
+    /// Complete the resolution only once the first hops acknowledged the data: the resolution
+    /// fails if the data is lost before being acknowledged.
+    ///
+    /// The data is acknowledged once the first hop received it, and only by the unicast
+    /// transports routing it reliably, except the low latency ones which fail the resolution;
+    /// the other destinations don't delay the resolution. An acknowledgement not received
+    /// within the lease of the transport is considered lost.
+    ///
+    /// This has a significant performance cost: the resolution waits for a round trip with the
+    /// first hop, after the messages queued before the data. The synchronous resolution blocks
+    /// the calling thread meanwhile, while the asynchronous one awaits it.
+    #[zenoh_macros::unstable]
+    #[inline]
+    pub fn wait_for_ack(mut self) -> Self {
+        self.wait_for_ack = true;
+        self
+    }
+
+    /// Don't deliver the published data to the subscribers of the same session, while still
+    /// sending it to the network.
+    ///
+    /// This applies whatever the `local_routing` configuration, which disables the local
+    /// delivery for all the publications of the session.
+    #[inline]
+    pub fn no_local(mut self, no_local: bool) -> Self {
+        self.no_local = no_local;
+        self
+    }
+
+    /// Attach the [`SourceInfo`](crate::sample::SourceInfo) of the publisher to the published
+    /// data: the id of the publisher and the sequence number of the publication.
+    ///
+    /// This is disabled by default since it grows every message sent on the network.
+    #[zenoh_macros::unstable]
+    #[inline]
+    pub fn source_info(mut self, source_info: bool) -> Self {
+        self.source_info = source_info;
+        self
+    }
+
+    // The QoS of the published data.
+    fn qos(&self) -> ext::QoSType {
+        ext::QoSType::new(self.priority.into(), self.congestion_control, false)
+    }
 }
 
 impl<'a, 'b> Resolvable for PublisherBuilder<'a, 'b> {
@@ -609,11 +1352,18 @@ impl<'a, 'b> SyncResolve for PublisherBuilder<'a, 'b> {
             .declare_publication_intent(key_expr.clone())
             .res_sync()?;
         let publisher = Publisher {
+            id: self.session.runtime.next_id() as Id,
+            sn: Arc::new(AtomicU32::new(0)),
             session: self.session,
             key_expr,
             congestion_control: self.congestion_control,
             priority: self.priority,
             destination: self.destination,
+            strict_destination: self.strict_destination,
+            report_drops: self.report_drops,
+            wait_for_ack: self.wait_for_ack,
+            no_local: self.no_local,
+            source_info: self.source_info,
         };
         log::trace!("publish({:?})", publisher.key_expr);
         Ok(publisher)
@@ -16,11 +16,16 @@
 
 use crate::handlers::{locked, Callback, DefaultHandler};
 use crate::prelude::*;
+use crate::time::Timestamp;
 use crate::Session;
 use std::collections::HashMap;
 use std::future::Ready;
 use std::time::Duration;
 use zenoh_core::{AsyncResolve, Resolvable, SyncResolve};
+#[zenoh_macros::unstable]
+use zenoh_protocol::core::EntityGlobalId;
+use zenoh_protocol::core::EntityId;
+use zenoh_protocol::network::RequestId;
 use zenoh_result::ZResult;
 
 /// The [`Queryable`](crate::queryable::Queryable)s that should be target of a [`get`](Session::get).
@@ -82,6 +87,161 @@ pub struct Reply {
     pub sample: Result<Sample, Value>,
     /// The id of the zenoh instance that answered this Reply.
     pub replier_id: ZenohId,
+    // The id of the queryable that answered this Reply, if known
+    #[cfg_attr(not(feature = "unstable"), allow(dead_code))]
+    pub(crate) replier_eid: Option<EntityId>,
+    // The time after which the query may be retried, if it was throttled
+    pub(crate) retry_after: Option<Duration>,
+}
+
+impl Reply {
+    /// The id of the zenoh instance that answered this Reply.
+    #[inline]
+    pub fn replier_id(&self) -> ZenohId {
+        self.replier_id
+    }
+
+    /// The [`EntityGlobalId`] of the queryable that answered this Reply, if it was given by the
+    /// replier.
+    #[zenoh_macros::unstable]
+    #[inline]
+    pub fn replier_global_id(&self) -> Option<EntityGlobalId> {
+        self.replier_eid.map(|eid| EntityGlobalId {
+            zid: self.replier_id,
+            eid,
+        })
+    }
+
+    /// Whether the query was throttled by the rate limit of this session on the router it is
+    /// connected to (see `routing/query/throttle`), rather than replied with an error by a
+    /// queryable.
+    #[inline]
+    pub fn is_throttled(&self) -> bool {
+        self.retry_after.is_some()
+    }
+
+    /// The time after which the query may be retried, if it was throttled.
+    #[inline]
+    pub fn retry_after(&self) -> Option<Duration> {
+        self.retry_after
+    }
+}
+
+/// The order in which the replies to a [`get`](Session::get) are delivered.
+///
+/// The ordering is applied by the querying session on the replies left by the consolidation,
+/// the routers being unaffected.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ReplyOrdering {
+    /// The replies are delivered as they arrive.
+    #[default]
+    Arrival,
+    /// The replies are delivered as they arrive, except the ones with an older timestamp than a
+    /// reply already delivered for the same key expression, which are dropped.
+    MonotonicPerKey,
+    /// The replies are held until the end of the query, then delivered sorted by key expression
+    /// then by timestamp.
+    ///
+    /// At most [`MAX_SORTED_REPLIES`] replies are held: the following ones are dropped, and
+    /// reported by an error reply delivered after the sorted ones.
+    SortedAtEnd,
+}
+
+/// The maximum number of replies held by a query with [`ReplyOrdering::SortedAtEnd`].
+pub const MAX_SORTED_REPLIES: usize = 65_536;
+
+// Applies a `ReplyOrdering` to the replies of a query
+pub(crate) struct ReplyOrderer {
+    ordering: ReplyOrdering,
+    // The timestamp of the last reply delivered for each key expression
+    latest: HashMap<OwnedKeyExpr, Timestamp>,
+    held: Vec<Reply>,
+    // The maximum number of replies held until the end of the query
+    max_held: usize,
+    // The number of replies dropped for being older than a delivered one
+    pub(crate) regressions: usize,
+    // The number of replies dropped for exceeding `max_held`
+    pub(crate) overflows: usize,
+}
+
+impl ReplyOrderer {
+    pub(crate) fn new(ordering: ReplyOrdering) -> Self {
+        Self {
+            ordering,
+            latest: HashMap::new(),
+            held: vec![],
+            max_held: MAX_SORTED_REPLIES,
+            regressions: 0,
+            overflows: 0,
+        }
+    }
+
+    /// Returns the reply if it is to be delivered right away.
+    pub(crate) fn push(&mut self, reply: Reply) -> Option<Reply> {
+        // The errors are delivered as they arrive whatever the ordering
+        let sample = match &reply.sample {
+            Ok(sample) => sample,
+            Err(_) => return Some(reply),
+        };
+        match self.ordering {
+            ReplyOrdering::Arrival => Some(reply),
+            ReplyOrdering::MonotonicPerKey => {
+                let timestamp = match sample.timestamp {
+                    Some(timestamp) => timestamp,
+                    None => return Some(reply),
+                };
+                match self.latest.get_mut(sample.key_expr.as_keyexpr()) {
+                    Some(latest) if timestamp < *latest => {
+                        self.regressions += 1;
+                        None
+                    }
+                    Some(latest) => {
+                        *latest = timestamp;
+                        Some(reply)
+                    }
+                    None => {
+                        self.latest
+                            .insert(sample.key_expr.clone().into(), timestamp);
+                        Some(reply)
+                    }
+                }
+            }
+            ReplyOrdering::SortedAtEnd => {
+                if self.held.len() < self.max_held {
+                    self.held.push(reply);
+                } else {
+                    self.overflows += 1;
+                }
+                None
+            }
+        }
+    }
+
+    /// Returns the replies held until the end of the query, sorted by key expression then by
+    /// timestamp, followed by an error reply of `replier_id` if some replies were dropped for
+    /// exceeding the bound of the held ones.
+    pub(crate) fn finish(&mut self, replier_id: ZenohId) -> Vec<Reply> {
+        let mut held = std::mem::take(&mut self.held);
+        held.sort_by(|a, b| match (&a.sample, &b.sample) {
+            (Ok(a), Ok(b)) => {
+                (a.key_expr.as_str(), a.timestamp).cmp(&(b.key_expr.as_str(), b.timestamp))
+            }
+            _ => std::cmp::Ordering::Equal,
+        });
+        if self.overflows > 0 {
+            held.push(Reply {
+                sample: Err(format!(
+                    "Dropped {} replies exceeding the {} sorted ones",
+                    self.overflows, self.max_held
+                )
+                .into()),
+                replier_id,
+                replier_eid: None,
+                retry_after: None,
+            });
+        }
+        held
+    }
 }
 
 pub(crate) struct QueryState {
@@ -90,9 +250,36 @@ pub(crate) struct QueryState {
     pub(crate) scope: Option<KeyExpr<'static>>,
     pub(crate) reception_mode: ConsolidationMode,
     pub(crate) replies: Option<HashMap<OwnedKeyExpr, Reply>>,
+    // The zenoh instances whose replies are accepted, all of them if `None`
+    pub(crate) repliers: Option<Vec<ZenohId>>,
+    pub(crate) ordering: ReplyOrderer,
     pub(crate) callback: Callback<'static, Reply>,
 }
 
+impl QueryState {
+    /// Delivers the replies held until the end of the query: the consolidated ones, then the
+    /// sorted ones. The errors raised by the querying session are replied by `zid`.
+    pub(crate) fn finish(mut self, qid: RequestId, zid: ZenohId) {
+        if self.reception_mode == ConsolidationMode::Latest {
+            for (_, reply) in self.replies.take().unwrap() {
+                if let Some(reply) = self.ordering.push(reply) {
+                    (self.callback)(reply);
+                }
+            }
+        }
+        for reply in self.ordering.finish(zid) {
+            (self.callback)(reply);
+        }
+        if self.ordering.regressions > 0 {
+            log::debug!(
+                "Dropped {} replies older than the ones delivered for query {}",
+                self.ordering.regressions,
+                qid
+            );
+        }
+    }
+}
+
 /// A builder for initializing a `query`.
 ///
 /// # Examples
@@ -125,6 +312,8 @@ pub struct GetBuilder<'a, 'b, Handler> {
     pub(crate) timeout: Duration,
     pub(crate) handler: Handler,
     pub(crate) value: Option<Value>,
+    pub(crate) repliers: Option<Vec<ZenohId>>,
+    pub(crate) ordering: ReplyOrdering,
 }
 
 impl<'a, 'b> GetBuilder<'a, 'b, DefaultHandler> {
@@ -158,6 +347,8 @@ impl<'a, 'b> GetBuilder<'a, 'b, DefaultHandler> {
             destination,
             timeout,
             value,
+            repliers,
+            ordering,
             handler: _,
         } = self;
         GetBuilder {
@@ -169,6 +360,8 @@ impl<'a, 'b> GetBuilder<'a, 'b, DefaultHandler> {
             destination,
             timeout,
             value,
+            repliers,
+            ordering,
             handler: callback,
         }
     }
@@ -237,6 +430,8 @@ impl<'a, 'b> GetBuilder<'a, 'b, DefaultHandler> {
             destination,
             timeout,
             value,
+            repliers,
+            ordering,
             handler: _,
         } = self;
         GetBuilder {
@@ -248,6 +443,8 @@ impl<'a, 'b> GetBuilder<'a, 'b, DefaultHandler> {
             destination,
             timeout,
             value,
+            repliers,
+            ordering,
             handler,
         }
     }
@@ -309,6 +506,8 @@ impl<'a, 'b, Handler> GetBuilder<'a, 'b, Handler> {
             destination,
             timeout,
             value,
+            repliers,
+            ordering,
             handler,
         } = self;
         Self {
@@ -320,9 +519,67 @@ impl<'a, 'b, Handler> GetBuilder<'a, 'b, Handler> {
             destination,
             timeout,
             value,
+            repliers,
+            ordering,
             handler,
         }
     }
+
+    /// Only accept the replies of the given zenoh instances, the replies of the other ones being
+    /// dropped.
+    ///
+    /// The accepted repliers are carried by the query, the routers skipping the queryables of the
+    /// other clients.
+    ///
+    /// # Examples
+    /// ```
+    /// # async_std::task::block_on(async {
+    /// use zenoh::prelude::r#async::*;
+    ///
+    /// let session = zenoh::open(config::peer()).res().await.unwrap();
+    /// let replier: ZenohId = "1a2b3c".parse().unwrap();
+    /// let replies = session
+    ///     .get("key/expression")
+    ///     .accept_repliers(&[replier])
+    ///     .res()
+    ///     .await
+    ///     .unwrap();
+    /// while let Ok(reply) = replies.recv_async().await {
+    ///     assert_eq!(reply.replier_id(), replier);
+    /// }
+    /// # })
+    /// ```
+    #[inline]
+    pub fn accept_repliers(mut self, repliers: &[ZenohId]) -> Self {
+        self.repliers = Some(repliers.to_vec());
+        self
+    }
+
+    /// Change the order in which the replies are delivered, as they arrive by default.
+    ///
+    /// # Examples
+    /// ```
+    /// # async_std::task::block_on(async {
+    /// use zenoh::prelude::r#async::*;
+    /// use zenoh::query::ReplyOrdering;
+    ///
+    /// let session = zenoh::open(config::peer()).res().await.unwrap();
+    /// let replies = session
+    ///     .get("key/expression/**")
+    ///     .ordering(ReplyOrdering::SortedAtEnd)
+    ///     .res()
+    ///     .await
+    ///     .unwrap();
+    /// while let Ok(reply) = replies.recv_async().await {
+    ///     println!("Received {:?}", reply.sample)
+    /// }
+    /// # })
+    /// ```
+    #[inline]
+    pub fn ordering(mut self, ordering: ReplyOrdering) -> Self {
+        self.ordering = ordering;
+        self
+    }
 }
 
 pub(crate) const _REPLY_KEY_EXPR_ANY_SEL_PARAM: &str = "_anyke";
@@ -368,6 +625,8 @@ where
                 self.destination,
                 self.timeout,
                 self.value,
+                self.repliers,
+                self.ordering,
                 callback,
             )
             .map(|_| receiver)
@@ -385,3 +644,141 @@ where
         std::future::ready(self.res_sync())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time::{TimestampId, NTP64};
+    use std::convert::TryFrom;
+
+    fn reply(key_expr: &str, time: u64) -> Reply {
+        let timestamp = Timestamp::new(NTP64(time), TimestampId::try_from([1]).unwrap());
+        Reply {
+            sample: Ok(
+                Sample::new(KeyExpr::try_from(key_expr.to_string()).unwrap(), time)
+                    .with_timestamp(timestamp),
+            ),
+            replier_id: ZenohId::default(),
+            replier_eid: None,
+            retry_after: None,
+        }
+    }
+
+    // Shuffled replies of a storage, as the network may deliver them
+    fn shuffled() -> Vec<Reply> {
+        vec![
+            reply("test/b", 2),
+            reply("test/a", 3),
+            reply("test/b", 1),
+            Reply {
+                sample: Err("error".into()),
+                replier_id: ZenohId::default(),
+                replier_eid: None,
+                retry_after: None,
+            },
+            reply("test/a", 1),
+            reply("test/b", 3),
+            reply("test/a", 2),
+        ]
+    }
+
+    fn deliver(ordering: ReplyOrdering) -> (Vec<Option<(String, u64)>>, usize) {
+        let mut orderer = ReplyOrderer::new(ordering);
+        let mut delivered: Vec<Reply> = shuffled()
+            .into_iter()
+            .filter_map(|reply| orderer.push(reply))
+            .collect();
+        delivered.extend(orderer.finish(ZenohId::default()));
+        let delivered = delivered
+            .into_iter()
+            .map(|reply| {
+                reply.sample.ok().map(|sample| {
+                    (
+                        sample.key_expr.to_string(),
+                        sample.timestamp.unwrap().get_time().as_u64(),
+                    )
+                })
+            })
+            .collect();
+        (delivered, orderer.regressions)
+    }
+
+    fn expected(replies: &[(&str, u64)]) -> Vec<Option<(String, u64)>> {
+        replies
+            .iter()
+            .map(|(key_expr, time)| Some((key_expr.to_string(), *time)))
+            .collect()
+    }
+
+    #[test]
+    fn reply_ordering_arrival() {
+        let (delivered, regressions) = deliver(ReplyOrdering::Arrival);
+        let mut all = expected(&[("test/b", 2), ("test/a", 3), ("test/b", 1)]);
+        all.push(None);
+        all.extend(expected(&[("test/a", 1), ("test/b", 3), ("test/a", 2)]));
+        assert_eq!(delivered, all);
+        assert_eq!(regressions, 0);
+    }
+
+    #[test]
+    fn reply_ordering_monotonic_per_key() {
+        // The replies older than a delivered one of the same key are dropped
+        let (delivered, regressions) = deliver(ReplyOrdering::MonotonicPerKey);
+        let mut monotonic = expected(&[("test/b", 2), ("test/a", 3)]);
+        monotonic.push(None);
+        monotonic.extend(expected(&[("test/b", 3)]));
+        assert_eq!(delivered, monotonic);
+        assert_eq!(regressions, 3);
+    }
+
+    #[test]
+    fn reply_ordering_sorted_at_end() {
+        // The errors are delivered right away, the samples once sorted at the end
+        let (delivered, regressions) = deliver(ReplyOrdering::SortedAtEnd);
+        let mut sorted = vec![None];
+        sorted.extend(expected(&[
+            ("test/a", 1),
+            ("test/a", 2),
+            ("test/a", 3),
+            ("test/b", 1),
+            ("test/b", 2),
+            ("test/b", 3),
+        ]));
+        assert_eq!(delivered, sorted);
+        assert_eq!(regressions, 0);
+    }
+
+    #[test]
+    fn reply_ordering_sorted_at_end_bounded() {
+        // The replies exceeding the bound are dropped and reported after the sorted ones
+        let mut orderer = ReplyOrderer::new(ReplyOrdering::SortedAtEnd);
+        orderer.max_held = 3;
+        let errors: Vec<Reply> = shuffled()
+            .into_iter()
+            .filter_map(|reply| orderer.push(reply))
+            .collect();
+        assert_eq!(errors.len(), 1);
+        let delivered = orderer.finish(ZenohId::default());
+        assert_eq!(orderer.overflows, 3);
+        assert_eq!(delivered.len(), 4);
+        let sorted: Vec<(String, u64)> = delivered[..3]
+            .iter()
+            .map(|reply| {
+                let sample = reply.sample.as_ref().unwrap();
+                (
+                    sample.key_expr.to_string(),
+                    sample.timestamp.unwrap().get_time().as_u64(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            sorted,
+            vec![
+                ("test/a".to_string(), 3),
+                ("test/b".to_string(), 1),
+                ("test/b".to_string(), 2),
+            ]
+        );
+        assert!(delivered[3].sample.is_err());
+    }
+}
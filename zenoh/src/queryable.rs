@@ -14,7 +14,7 @@
 
 //! Queryable primitives.
 
-use crate::handlers::{locked, DefaultHandler};
+use crate::handlers::{locked, CallbackPanics, DefaultHandler};
 use crate::prelude::*;
 #[zenoh_macros::unstable]
 use crate::query::ReplyKeyExpr;
@@ -24,10 +24,11 @@ use crate::Undeclarable;
 use std::fmt;
 use std::future::Ready;
 use std::ops::Deref;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use zenoh_core::{AsyncResolve, Resolvable, SyncResolve};
-use zenoh_protocol::core::WireExpr;
-use zenoh_protocol::network::{response, Mapping, RequestId, Response, ResponseFinal};
+use zenoh_protocol::core::{EntityId, WireExpr};
+use zenoh_protocol::network::{request, response, Mapping, RequestId, Response, ResponseFinal};
 use zenoh_protocol::zenoh::reply::ext::ConsolidationType;
 use zenoh_protocol::zenoh::{self, ResponseBody};
 use zenoh_result::ZResult;
@@ -43,6 +44,7 @@ pub(crate) struct QueryInner {
 
     pub(crate) qid: RequestId,
     pub(crate) zid: ZenohId,
+    pub(crate) correlation: Option<request::ext::CorrelationType>,
     pub(crate) primitives: Arc<dyn Primitives>,
 }
 
@@ -60,6 +62,8 @@ impl Drop for QueryInner {
 #[derive(Clone)]
 pub struct Query {
     pub(crate) inner: Arc<QueryInner>,
+    // The id of the queryable the query is given to
+    pub(crate) eid: EntityId,
 }
 
 impl Query {
@@ -155,7 +159,7 @@ impl<'a> Resolvable for ReplyBuilder<'a> {
 
 impl SyncResolve for ReplyBuilder<'_> {
     fn res_sync(self) -> <Self as Resolvable>::To {
-        match self.result {
+        let (key_expr, payload) = match self.result {
             Ok(sample) => {
                 if !self.query._accepts_any_replies().unwrap_or(false)
                     && !self.query.key_expr().intersects(&sample.key_expr)
@@ -163,43 +167,66 @@ impl SyncResolve for ReplyBuilder<'_> {
                     bail!("Attempted to reply on `{}`, which does not intersect with query `{}`, despite query only allowing replies on matching key expressions", sample.key_expr, self.query.key_expr())
                 }
                 let (key_expr, payload, data_info) = sample.split();
-                self.query.inner.primitives.send_response(Response {
-                    rid: self.query.inner.qid,
-                    wire_expr: WireExpr {
-                        scope: 0,
-                        suffix: std::borrow::Cow::Owned(key_expr.into()),
-                        mapping: Mapping::Sender,
+                let body = ResponseBody::Reply(zenoh::Reply {
+                    timestamp: data_info.timestamp,
+                    encoding: data_info.encoding.unwrap_or_default(),
+                    ext_sinfo: if data_info.source_id.is_some() || data_info.source_sn.is_some() {
+                        Some(zenoh::reply::ext::SourceInfoType {
+                            zid: data_info.source_id.unwrap_or_default(),
+                            eid: data_info.source_eid.unwrap_or_default(),
+                            sn: data_info.source_sn.unwrap_or_default() as u32,
+                        })
+                    } else {
+                        None
                     },
-                    payload: ResponseBody::Reply(zenoh::Reply {
-                        timestamp: data_info.timestamp,
-                        encoding: data_info.encoding.unwrap_or_default(),
-                        ext_sinfo: if data_info.source_id.is_some() || data_info.source_sn.is_some()
-                        {
-                            Some(zenoh::reply::ext::SourceInfoType {
-                                zid: data_info.source_id.unwrap_or_default(),
-                                eid: 0, // TODO
-                                sn: data_info.source_sn.unwrap_or_default() as u32,
-                            })
-                        } else {
-                            None
-                        },
-                        ext_consolidation: ConsolidationType::default(),
+                    ext_consolidation: ConsolidationType::default(),
+                    #[cfg(feature = "shared-memory")]
+                    ext_shm: None,
+                    ext_unknown: vec![],
+                    payload,
+                });
+                (key_expr.into(), body)
+            }
+            // The errors of the application are replied on the key expression of the query
+            Err(value) => {
+                let body = ResponseBody::Err(zenoh::Err {
+                    code: 0,
+                    is_infrastructure: false,
+                    timestamp: None,
+                    ext_sinfo: None,
+                    ext_body: Some(zenoh::err::ext::ErrBodyType {
                         #[cfg(feature = "shared-memory")]
                         ext_shm: None,
-                        ext_unknown: vec![],
-                        payload,
-                    }),
-                    ext_qos: response::ext::QoSType::response_default(),
-                    ext_tstamp: None,
-                    ext_respid: Some(response::ext::ResponderIdType {
-                        zid: self.query.inner.zid,
-                        eid: 0, // TODO
+                        encoding: value.encoding,
+                        payload: value.payload,
                     }),
+                    ext_unknown: vec![],
                 });
-                Ok(())
+                (self.query.key_expr().as_str().to_owned(), body)
             }
-            Err(_) => Err(zerror!("Replying errors is not yet supported!").into()),
-        }
+        };
+        self.query.inner.primitives.send_response(Response {
+            rid: self.query.inner.qid,
+            wire_expr: WireExpr {
+                scope: 0,
+                suffix: std::borrow::Cow::Owned(key_expr),
+                mapping: Mapping::Sender,
+            },
+            payload,
+            ext_qos: response::ext::QoSType::response_default(),
+            ext_tstamp: None,
+            ext_respid: Some(response::ext::ResponderIdType {
+                zid: self.query.inner.zid,
+                eid: self.query.eid,
+            }),
+            ext_correlation: self.query.inner.correlation.as_ref().map(|c| {
+                response::ext::CorrelationType {
+                    zid: c.zid,
+                    eid: c.eid,
+                }
+            }),
+        });
+        Ok(())
     }
 }
 
@@ -217,6 +244,18 @@ pub(crate) struct QueryableState {
     pub(crate) complete: bool,
     pub(crate) origin: Locality,
     pub(crate) callback: Arc<dyn Fn(Query) + Send + Sync>,
+    pub(crate) panics: CallbackPanics,
+    // Whether the queryable was undeclared, by the application or after the panics of its
+    // callback
+    pub(crate) undeclared: AtomicBool,
+}
+
+impl QueryableState {
+    /// Marks the queryable as undeclared. Returns `false` if it already was, in which case it
+    /// must not be undeclared again.
+    pub(crate) fn mark_undeclared(&self) -> bool {
+        !self.undeclared.swap(true, Ordering::AcqRel)
+    }
 }
 
 impl fmt::Debug for QueryableState {
@@ -291,6 +330,9 @@ impl Resolvable for QueryableUndeclaration<'_> {
 impl SyncResolve for QueryableUndeclaration<'_> {
     fn res_sync(mut self) -> <Self as Resolvable>::To {
         self.queryable.alive = false;
+        if !self.queryable.state.mark_undeclared() {
+            return Ok(());
+        }
         self.queryable
             .session
             .close_queryable(self.queryable.state.id)
@@ -307,7 +349,7 @@ impl<'a> AsyncResolve for QueryableUndeclaration<'a> {
 
 impl Drop for CallbackQueryable<'_> {
     fn drop(&mut self) {
-        if self.alive {
+        if self.alive && self.state.mark_undeclared() {
             let _ = self.session.close_queryable(self.state.id);
         }
     }
@@ -500,6 +542,18 @@ pub struct Queryable<'a, Receiver> {
 }
 
 impl<'a, Receiver> Queryable<'a, Receiver> {
+    /// Returns the [`EntityGlobalId`] of this Queryable.
+    ///
+    /// It is carried in the declaration of the Queryable and is reported for it
+    /// in the admin space of the routers.
+    #[zenoh_macros::unstable]
+    pub fn id(&self) -> EntityGlobalId {
+        EntityGlobalId {
+            zid: self.queryable.session.zid(),
+            eid: self.queryable.state.id as EntityId,
+        }
+    }
+
     #[inline]
     pub fn undeclare(self) -> impl Resolve<ZResult<()>> + 'a {
         Undeclarable::undeclare_inner(self, ())
@@ -16,12 +16,16 @@
 use crate::buffers::ZBuf;
 use crate::prelude::ZenohId;
 use crate::prelude::{KeyExpr, SampleKind, Value};
+use crate::publication::Priority;
 use crate::query::Reply;
 use crate::time::{new_reception_timestamp, Timestamp};
 #[zenoh_macros::unstable]
 use serde::Serialize;
 use std::convert::{TryFrom, TryInto};
-use zenoh_protocol::core::Encoding;
+#[zenoh_macros::unstable]
+use zenoh_protocol::core::EntityGlobalId;
+use zenoh_protocol::core::{CongestionControl, Encoding, EntityId};
+use zenoh_protocol::network::push::ext::QoSType;
 
 pub type SourceSn = u64;
 
@@ -49,7 +53,46 @@ pub(crate) struct DataInfo {
     pub encoding: Option<Encoding>,
     pub timestamp: Option<Timestamp>,
     pub source_id: Option<ZenohId>,
+    pub source_eid: Option<EntityId>,
     pub source_sn: Option<SourceSn>,
+    pub qos: QoS,
+    pub integrity: SampleIntegrity,
+}
+
+/// The position of a [`Sample`] in a batch published with
+/// [`put_batch`](crate::Session::put_batch).
+///
+/// A subscriber receives the samples of a batch matching its key expression: the index and the
+/// count are those of the samples it receives.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SampleBatch {
+    /// The identifier of the batch, chosen by its publisher.
+    pub id: u64,
+    /// The index of the sample among the samples of the batch received by the subscriber.
+    pub index: u32,
+    /// The number of samples of the batch received by the subscriber.
+    pub count: u32,
+}
+
+impl SampleBatch {
+    /// Whether the sample is the last one of its batch.
+    #[inline]
+    pub fn is_last(&self) -> bool {
+        self.index + 1 >= self.count
+    }
+}
+
+/// The outcome of the verification of the integrity checksum of a [`Sample`]'s payload,
+/// see the `integrity` section of the configuration.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SampleIntegrity {
+    /// The sample was published without checksum, or with a checksum of an unknown algorithm.
+    #[default]
+    Unchecked,
+    /// The payload matches its checksum.
+    Verified,
+    /// The payload doesn't match its checksum: it was corrupted on its way.
+    Corrupted,
 }
 
 /// Informations on the source of a zenoh [`Sample`].
@@ -58,6 +101,8 @@ pub(crate) struct DataInfo {
 pub struct SourceInfo {
     /// The [`ZenohId`] of the zenoh instance that published the concerned [`Sample`].
     pub source_id: Option<ZenohId>,
+    /// The [`EntityId`] of the publisher of the concerned [`Sample`] within its zenoh instance.
+    pub source_eid: Option<EntityId>,
     /// The sequence number of the [`Sample`] from the source.
     pub source_sn: Option<SourceSn>,
 }
@@ -65,7 +110,7 @@ pub struct SourceInfo {
 #[test]
 #[cfg(feature = "unstable")]
 fn source_info_stack_size() {
-    assert_eq!(std::mem::size_of::<SourceInfo>(), 16 * 2);
+    assert_eq!(std::mem::size_of::<SourceInfo>(), 16 * 2 + 8);
 }
 
 #[zenoh_macros::unstable]
@@ -73,9 +118,18 @@ impl SourceInfo {
     pub(crate) fn empty() -> Self {
         SourceInfo {
             source_id: None,
+            source_eid: None,
             source_sn: None,
         }
     }
+
+    /// The [`EntityGlobalId`] of the publisher of the concerned [`Sample`].
+    pub fn source_global_id(&self) -> Option<EntityGlobalId> {
+        Some(EntityGlobalId {
+            zid: self.source_id?,
+            eid: self.source_eid?,
+        })
+    }
 }
 
 #[zenoh_macros::unstable]
@@ -83,6 +137,7 @@ impl From<DataInfo> for SourceInfo {
     fn from(data_info: DataInfo) -> Self {
         SourceInfo {
             source_id: data_info.source_id,
+            source_eid: data_info.source_eid,
             source_sn: data_info.source_sn,
         }
     }
@@ -110,6 +165,12 @@ pub struct Sample {
     pub kind: SampleKind,
     /// The [`Timestamp`] of this Sample.
     pub timestamp: Option<Timestamp>,
+    pub(crate) qos: QoS,
+    /// The outcome of the verification of the integrity checksum of the payload.
+    pub integrity: SampleIntegrity,
+    /// The position of this Sample in its batch, if it was published with
+    /// [`put_batch`](crate::Session::put_batch).
+    pub batch: Option<SampleBatch>,
 
     #[cfg(feature = "unstable")]
     /// <div class="stab unstable">
@@ -135,6 +196,9 @@ impl Sample {
             value: value.into(),
             kind: SampleKind::default(),
             timestamp: None,
+            qos: QoS::default(),
+            integrity: SampleIntegrity::default(),
+            batch: None,
             #[cfg(feature = "unstable")]
             source_info: SourceInfo::empty(),
         }
@@ -155,6 +219,9 @@ impl Sample {
             value: value.into(),
             kind: SampleKind::default(),
             timestamp: None,
+            qos: QoS::default(),
+            integrity: SampleIntegrity::default(),
+            batch: None,
             #[cfg(feature = "unstable")]
             source_info: SourceInfo::empty(),
         })
@@ -177,6 +244,9 @@ impl Sample {
                 value,
                 kind: data_info.kind,
                 timestamp: data_info.timestamp,
+                qos: data_info.qos,
+                integrity: data_info.integrity,
+                batch: None,
                 #[cfg(feature = "unstable")]
                 source_info: data_info.into(),
             }
@@ -186,6 +256,9 @@ impl Sample {
                 value,
                 kind: SampleKind::default(),
                 timestamp: None,
+                qos: QoS::default(),
+                integrity: SampleIntegrity::default(),
+                batch: None,
                 #[cfg(feature = "unstable")]
                 source_info: SourceInfo::empty(),
             }
@@ -203,9 +276,15 @@ impl Sample {
             #[cfg(not(feature = "unstable"))]
             source_id: None,
             #[cfg(feature = "unstable")]
+            source_eid: self.source_info.source_eid,
+            #[cfg(not(feature = "unstable"))]
+            source_eid: None,
+            #[cfg(feature = "unstable")]
             source_sn: self.source_info.source_sn,
             #[cfg(not(feature = "unstable"))]
             source_sn: None,
+            qos: self.qos,
+            integrity: self.integrity,
         };
         (self.key_expr, self.value.payload, info)
     }
@@ -216,6 +295,12 @@ impl Sample {
         self.timestamp.as_ref()
     }
 
+    /// Gets the quality of service settings this Sample was published with.
+    #[inline]
+    pub fn qos(&self) -> &QoS {
+        &self.qos
+    }
+
     /// Sets the timestamp of this Sample.
     #[inline]
     pub fn with_timestamp(mut self, timestamp: Timestamp) -> Self {
@@ -246,6 +331,35 @@ impl Sample {
     }
 }
 
+/// The quality of service settings of a [`Sample`].
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct QoS {
+    inner: QoSType,
+}
+
+impl QoS {
+    /// Gets the priority of the message.
+    pub fn priority(&self) -> Priority {
+        Priority::try_from(self.inner.get_priority() as u8).unwrap_or_default()
+    }
+
+    /// Gets the congestion control of the message.
+    pub fn congestion_control(&self) -> CongestionControl {
+        self.inner.get_congestion_control()
+    }
+
+    /// Whether the message was sent without being batched.
+    pub fn express(&self) -> bool {
+        self.inner.is_express()
+    }
+}
+
+impl From<QoSType> for QoS {
+    fn from(inner: QoSType) -> Self {
+        QoS { inner }
+    }
+}
+
 impl std::ops::Deref for Sample {
     type Target = Value;
 
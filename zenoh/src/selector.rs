@@ -18,12 +18,13 @@ use zenoh_protocol::core::key_expr::{keyexpr, OwnedKeyExpr};
 use zenoh_result::ZResult;
 pub use zenoh_util::time_range::{TimeBound, TimeExpr, TimeRange};
 
-use crate::{prelude::KeyExpr, queryable::Query};
+use crate::{prelude::KeyExpr, queryable::Query, value::Value};
 
 use std::{
     borrow::{Borrow, Cow},
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     convert::TryFrom,
+    fmt,
     hash::Hash,
 };
 
@@ -58,6 +59,7 @@ use std::{
 /// Here are the currently standardized parameters for Zenoh (check the specification page for the exhaustive list):
 /// - `_time`: used to express interest in only values dated within a certain time range, values for
 ///   this parameter must be readable by the [Zenoh Time DSL](zenoh_util::time_range::TimeRange) for the value to be considered valid.
+/// - `_limit`: used to bound the number of replies of a queryable, its value being a non-negative integer.
 /// - **`[unstable]`** `_anyke`: used in queries to express interest in replies coming from any key expression. By default, only replies
 ///   whose key expression match query's key expression are accepted. `_anyke` disables the query-reply key expression matching check.
 #[non_exhaustive]
@@ -70,6 +72,110 @@ pub struct Selector<'a> {
 }
 
 pub const TIME_RANGE_KEY: &str = "_time";
+pub const LIMIT_KEY: &str = "_limit";
+
+/// The check of the values of a standardized parameter.
+type ParameterCheck = fn(&str) -> ZResult<()>;
+
+/// The standardized parameters, with the check of their values.
+const RESERVED_PARAMETERS: [(&str, ParameterCheck); 3] = [
+    (TIME_RANGE_KEY, check_time_range),
+    (LIMIT_KEY, check_limit),
+    (crate::query::_REPLY_KEY_EXPR_ANY_SEL_PARAM, check_flag),
+];
+
+fn check_time_range(value: &str) -> ZResult<()> {
+    value.parse::<TimeRange>()?;
+    Ok(())
+}
+
+fn check_limit(value: &str) -> ZResult<()> {
+    parse_limit(value).map(|_| ())
+}
+
+fn check_flag(value: &str) -> ZResult<()> {
+    parse_flag(value).map(|_| ())
+}
+
+fn parse_limit(value: &str) -> ZResult<usize> {
+    value
+        .parse()
+        .map_err(|e| zerror!("`{}` is not a number of replies: {}", value, e).into())
+}
+
+// A flag is set by its name alone or with an explicit boolean value
+fn parse_flag(value: &str) -> ZResult<bool> {
+    match value {
+        "" | "true" => Ok(true),
+        "false" => Ok(false),
+        _ => bail!("`{}` is neither `true` nor `false`", value),
+    }
+}
+
+/// Whether a parameter name is in the namespace reserved by Zenoh: the names starting with `_`.
+pub fn is_reserved(name: &str) -> bool {
+    name.starts_with('_')
+}
+
+/// Why the parameters of a selector were rejected by [`Parameters::validate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParameterError {
+    /// The parameter appears more than once.
+    Duplicated(String),
+    /// The parameter name is reserved, but neither standardized nor accepted by the queryable.
+    Unknown(String),
+    /// The value of a standardized parameter is not of its type.
+    Malformed {
+        name: String,
+        value: String,
+        reason: String,
+    },
+}
+
+impl ParameterError {
+    /// The name of the rejected parameter.
+    pub fn name(&self) -> &str {
+        match self {
+            ParameterError::Duplicated(name) | ParameterError::Unknown(name) => name,
+            ParameterError::Malformed { name, .. } => name,
+        }
+    }
+}
+
+impl fmt::Display for ParameterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParameterError::Duplicated(name) => write!(f, "Duplicated parameter `{name}`"),
+            ParameterError::Unknown(name) => write!(f, "Unknown reserved parameter `{name}`"),
+            ParameterError::Malformed {
+                name,
+                value,
+                reason,
+            } => write!(f, "Malformed parameter `{name}={value}`: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for ParameterError {}
+
+/// The error replied to a query rejected for its parameters, as a JSON object with the `error`
+/// kind (`duplicated`, `unknown` or `malformed`), the `parameter` name and a `message`.
+impl From<&ParameterError> for Value {
+    fn from(e: &ParameterError) -> Self {
+        let error = match e {
+            ParameterError::Duplicated(_) => "duplicated",
+            ParameterError::Unknown(_) => "unknown",
+            ParameterError::Malformed { .. } => "malformed",
+        };
+        serde_json::json!({
+            "error": error,
+            "parameter": e.name(),
+            "message": e.to_string(),
+        })
+        .into()
+    }
+}
+
 impl<'a> Selector<'a> {
     /// Gets the parameters as a raw string.
     pub fn parameters(&self) -> &str {
@@ -280,6 +386,70 @@ fn selector_accessors() {
         assert_eq!(selector.to_string(), without_any + "&other");
     }
 }
+
+#[test]
+fn selector_validation() {
+    let validate = |selector: &str, accepted: &[&str]| {
+        let selector = Selector::try_from(selector).unwrap();
+        selector
+            .validate(accepted)
+            .map_err(|e| e.downcast_ref::<ParameterError>().unwrap().clone())
+    };
+
+    // The standardized parameters, and the arguments of the queryable
+    assert!(validate("a/b?_time=[now(-2s)..]&_limit=3&_anyke&arg=x", &[]).is_ok());
+    assert!(validate("a/b?_anyke=false", &[]).is_ok());
+    assert!(validate("a/b", &[]).is_ok());
+
+    // The unknown reserved names, unless accepted by the queryable
+    assert_eq!(
+        validate("a/b?_tme=[now(-2s)..]", &[]),
+        Err(ParameterError::Unknown("_tme".to_string()))
+    );
+    assert!(validate("a/b?_raw&_time=[..]", &["_raw"]).is_ok());
+
+    // The duplicated names, whatever their values
+    assert_eq!(
+        validate("a/b?arg=1&arg=1", &[]),
+        Err(ParameterError::Duplicated("arg".to_string()))
+    );
+    assert_eq!(
+        validate("a/b?_limit=1&_limit=2", &[]),
+        Err(ParameterError::Duplicated("_limit".to_string()))
+    );
+
+    // The malformed values of the standardized parameters
+    for selector in ["a/b?_limit=-1", "a/b?_time=yesterday", "a/b?_anyke=yes"] {
+        let e = validate(selector, &[]).unwrap_err();
+        assert!(matches!(e, ParameterError::Malformed { .. }), "{e}");
+    }
+
+    // The names and values are compared once percent-decoded
+    assert!(validate("a/b?%5Ftime=%5Bnow(-2s)..%5D&_limit=%33", &[]).is_ok());
+    assert_eq!(
+        validate("a/b?%5Ftme", &[]),
+        Err(ParameterError::Unknown("_tme".to_string()))
+    );
+    assert_eq!(
+        validate("a/b?_limit=1&%5Flimit=1", &[]),
+        Err(ParameterError::Duplicated("_limit".to_string()))
+    );
+
+    // The typed accessors
+    let selector = Selector::try_from("a/b?_limit=%33&_anyke").unwrap();
+    assert_eq!(selector.limit().unwrap(), Some(3));
+    assert!(selector.any_key_expr().unwrap());
+    let selector = Selector::try_from("a/b?arg").unwrap();
+    assert_eq!(selector.limit().unwrap(), None);
+    assert!(!selector.any_key_expr().unwrap());
+    assert!(Selector::try_from("a/b?_limit=x").unwrap().limit().is_err());
+
+    // The error replied to the rejected queries
+    let value = Value::from(&ParameterError::Unknown("_tme".to_string()));
+    let json = serde_json::Value::try_from(&value).unwrap();
+    assert_eq!(json["error"], "unknown");
+    assert_eq!(json["parameter"], "_tme");
+}
 pub trait Parameter: Sized {
     type Name: AsRef<str> + Sized;
     type Value: AsRef<str> + Sized;
@@ -404,6 +574,65 @@ pub trait Parameters<'a> {
             None => None,
         })
     }
+
+    /// Extracts the standardized `_limit` argument from the selector parameters.
+    fn limit(&'a self) -> ZResult<Option<usize>>
+    where
+        <Self::Decoder as Iterator>::Item: Parameter,
+    {
+        Ok(match &self.get_parameters([LIMIT_KEY])?[0] {
+            Some(s) => Some(parse_limit(s.as_ref())?),
+            None => None,
+        })
+    }
+
+    /// Extracts the standardized `_anyke` argument from the selector parameters, `false` if absent.
+    fn any_key_expr(&'a self) -> ZResult<bool>
+    where
+        <Self::Decoder as Iterator>::Item: Parameter,
+    {
+        match &self.get_parameters([crate::query::_REPLY_KEY_EXPR_ANY_SEL_PARAM])?[0] {
+            Some(s) => parse_flag(s.as_ref()),
+            None => Ok(false),
+        }
+    }
+
+    /// Checks the selector parameters, so that a queryable rejects a query it would misread.
+    ///
+    /// The parameters are rejected with a [`ParameterError`] if a name appears more than once,
+    /// if the value of a standardized parameter is not of its type, or if a [reserved](is_reserved)
+    /// name is neither standardized nor in `accepted`, e.g. a misspelled `_tme`. The other names
+    /// are the arguments of the queryable, left to it. The names are compared once percent-decoded.
+    fn validate(&'a self, accepted: &[&str]) -> ZResult<()>
+    where
+        <Self::Decoder as Iterator>::Item: Parameter,
+    {
+        let mut names = HashSet::new();
+        for pair in self.decode() {
+            let name = pair.name().as_ref();
+            if !names.insert(name.to_string()) {
+                return Err(ParameterError::Duplicated(name.to_string()).into());
+            }
+            match RESERVED_PARAMETERS.iter().find(|(n, _)| *n == name) {
+                Some((_, parse)) => {
+                    let value = pair.value().as_ref();
+                    if let Err(e) = parse(value) {
+                        return Err(ParameterError::Malformed {
+                            name: name.to_string(),
+                            value: value.to_string(),
+                            reason: e.to_string(),
+                        }
+                        .into());
+                    }
+                }
+                None if is_reserved(name) && !accepted.contains(&name) => {
+                    return Err(ParameterError::Unknown(name.to_string()).into());
+                }
+                None => {}
+            }
+        }
+        Ok(())
+    }
 }
 impl<'a> Parameters<'a> for Selector<'a> {
     type Decoder = <str as Parameters<'a>>::Decoder;
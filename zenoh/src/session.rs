@@ -15,12 +15,13 @@
 use crate::admin;
 use crate::config::Config;
 use crate::config::Notifier;
-use crate::handlers::{Callback, DefaultHandler};
+use crate::filter::Filter;
+use crate::handlers::{Callback, CallbackPanics, DefaultHandler};
 use crate::info::*;
 use crate::key_expr::KeyExprInner;
 #[zenoh_macros::unstable]
 use crate::liveliness::{Liveliness, LivelinessTokenState};
-use crate::net::routing::face::Face;
+use crate::net::routing::namespace::{Direction, Namespace, NamespacePrimitives};
 use crate::net::runtime::Runtime;
 use crate::net::transport::Primitives;
 use crate::prelude::Locality;
@@ -28,7 +29,7 @@ use crate::prelude::{KeyExpr, Parameters};
 use crate::publication::*;
 use crate::query::*;
 use crate::queryable::*;
-use crate::sample::DataInfo;
+use crate::sample::{DataInfo, QoS, SampleBatch, SampleIntegrity};
 use crate::selector::TIME_RANGE_KEY;
 use crate::subscriber::*;
 use crate::Id;
@@ -37,6 +38,7 @@ use crate::Sample;
 use crate::SampleKind;
 use crate::Selector;
 use crate::Value;
+use async_std::prelude::FutureExt;
 use async_std::task;
 use log::{error, trace, warn};
 use std::collections::HashMap;
@@ -44,21 +46,22 @@ use std::convert::TryFrom;
 use std::convert::TryInto;
 use std::fmt;
 use std::ops::Deref;
-use std::sync::atomic::{AtomicU16, AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU16, Ordering};
 use std::sync::Arc;
 use std::sync::RwLock;
 use std::time::Duration;
 use uhlc::HLC;
-use zenoh_buffers::ZBuf;
-use zenoh_collections::SingleOrVec;
+use zenoh_buffers::{SplitBuffer, ZBuf};
+use zenoh_collections::{Properties, SingleOrVec};
 use zenoh_config::unwrap_or_default;
 use zenoh_core::{zconfigurable, zread, Resolve, ResolveClosure, ResolveFuture, SyncResolve};
 use zenoh_protocol::network::AtomicRequestId;
 use zenoh_protocol::network::RequestId;
 use zenoh_protocol::{
     core::{
-        key_expr::{keyexpr, OwnedKeyExpr},
-        AtomicExprId, CongestionControl, ExprId, WireExpr, ZenohId, EMPTY_EXPR_ID,
+        key_expr::{keyexpr, KeMap, OwnedKeyExpr},
+        AtomicExprId, CongestionControl, EndPoint, EntityId, ExprId, Locator, WireExpr, ZenohId,
+        EMPTY_EXPR_ID,
     },
     network::{
         declare::{
@@ -67,10 +70,15 @@ use zenoh_protocol::{
             DeclareQueryable, DeclareSubscriber, UndeclareQueryable, UndeclareSubscriber,
         },
         ext,
-        request::{self, ext::TargetType, Request},
+        request::{
+            self,
+            ext::{RepliersType, TargetType},
+            Request,
+        },
         Mapping, Push, Response, ResponseFinal,
     },
     zenoh::{
+        err,
         query::{
             self,
             ext::{ConsolidationType, QueryBodyType},
@@ -79,6 +87,7 @@ use zenoh_protocol::{
     },
 };
 use zenoh_result::ZResult;
+use zenoh_util::clock::SystemClock;
 use zenoh_util::core::AsyncResolve;
 
 zconfigurable! {
@@ -90,20 +99,23 @@ zconfigurable! {
 }
 
 pub(crate) struct SessionState {
-    pub(crate) primitives: Option<Arc<Face>>, // @TODO replace with MaybeUninit ??
-    pub(crate) expr_id_counter: AtomicExprId, // @TODO: manage rollover and uniqueness
+    pub(crate) primitives: Option<Arc<dyn Primitives>>, // @TODO replace with MaybeUninit ??
+    pub(crate) expr_id_counter: AtomicExprId,           // @TODO: manage rollover and uniqueness
     pub(crate) qid_counter: AtomicRequestId,
-    pub(crate) decl_id_counter: AtomicUsize,
     pub(crate) local_resources: HashMap<ExprId, Resource>,
     pub(crate) remote_resources: HashMap<ExprId, Resource>,
     //pub(crate) publications: Vec<OwnedKeyExpr>,
     pub(crate) subscribers: HashMap<Id, Arc<SubscriberState>>,
+    // The subscribers indexed by key expression, for the local routing of the publications
+    pub(crate) subscribers_by_key_expr: KeMap<Vec<Arc<SubscriberState>>>,
     pub(crate) queryables: HashMap<Id, Arc<QueryableState>>,
     #[cfg(feature = "unstable")]
     pub(crate) tokens: HashMap<Id, Arc<LivelinessTokenState>>,
     pub(crate) queries: HashMap<RequestId, QueryState>,
     pub(crate) aggregated_subscribers: Vec<OwnedKeyExpr>,
     //pub(crate) aggregated_publishers: Vec<OwnedKeyExpr>,
+    // The connectivity handlers registered on the runtime, unregistered when closing
+    pub(crate) connectivity_handlers: Vec<EntityId>,
 }
 
 impl SessionState {
@@ -115,22 +127,55 @@ impl SessionState {
             primitives: None,
             expr_id_counter: AtomicExprId::new(1), // Note: start at 1 because 0 is reserved for NO_RESOURCE
             qid_counter: AtomicRequestId::new(0),
-            decl_id_counter: AtomicUsize::new(0),
             local_resources: HashMap::new(),
             remote_resources: HashMap::new(),
             //publications: Vec::new(),
             subscribers: HashMap::new(),
+            subscribers_by_key_expr: KeMap::new(),
             queryables: HashMap::new(),
             #[cfg(feature = "unstable")]
             tokens: HashMap::new(),
             queries: HashMap::new(),
             aggregated_subscribers,
             //aggregated_publishers,
+            connectivity_handlers: Vec::new(),
         }
     }
 }
 
 impl SessionState {
+    fn add_subscriber(&mut self, sub_state: Arc<SubscriberState>) {
+        match self.subscribers_by_key_expr.get_mut(&sub_state.key_expr) {
+            Some(subs) => subs.push(sub_state.clone()),
+            None => {
+                self.subscribers_by_key_expr
+                    .insert((&*sub_state.key_expr).into(), vec![sub_state.clone()]);
+            }
+        }
+        self.subscribers.insert(sub_state.id, sub_state);
+    }
+
+    fn remove_subscriber(&mut self, id: Id) -> Option<Arc<SubscriberState>> {
+        let sub_state = self.subscribers.remove(&id)?;
+        if let Some(subs) = self.subscribers_by_key_expr.get_mut(&sub_state.key_expr) {
+            subs.retain(|s| s.id != id);
+            if subs.is_empty() {
+                self.subscribers_by_key_expr.remove(&sub_state.key_expr);
+            }
+        }
+        Some(sub_state)
+    }
+
+    /// Iterates over the subscribers whose key expression intersects with `key_expr`.
+    fn subscribers_intersecting<'a>(
+        &'a self,
+        key_expr: &'a keyexpr,
+    ) -> impl Iterator<Item = &'a Arc<SubscriberState>> {
+        self.subscribers_by_key_expr
+            .values_intersecting(key_expr)
+            .flatten()
+    }
+
     #[inline]
     fn get_local_res(&self, id: &ExprId) -> Option<&Resource> {
         self.local_resources.get(id)
@@ -331,6 +376,7 @@ impl Session {
         runtime: Runtime,
         aggregated_subscribers: Vec<OwnedKeyExpr>,
         aggregated_publishers: Vec<OwnedKeyExpr>,
+        namespace: Option<OwnedKeyExpr>,
     ) -> impl Resolve<Session> {
         ResolveClosure::new(move || {
             let router = runtime.router.clone();
@@ -347,8 +393,20 @@ impl Session {
 
             runtime.new_handler(Arc::new(admin::Handler::new(session.clone())));
 
-            let primitives = Some(router.new_primitives(Arc::new(session.clone())));
-            zwrite!(state).primitives = primitives;
+            // A session confined to a namespace sees the key expressions stripped from it
+            let primitives: Arc<dyn Primitives> = match namespace.map(Namespace::new) {
+                Some(namespace) => Arc::new(NamespacePrimitives::new(
+                    namespace.clone(),
+                    Direction::Egress,
+                    router.new_primitives(Arc::new(NamespacePrimitives::new(
+                        namespace,
+                        Direction::Ingress,
+                        Arc::new(session.clone()),
+                    ))),
+                )),
+                None => router.new_primitives(Arc::new(session.clone())),
+            };
+            zwrite!(state).primitives = Some(primitives);
 
             admin::init(&session);
 
@@ -446,6 +504,10 @@ impl Session {
     pub fn close(self) -> impl Resolve<ZResult<()>> {
         ResolveFuture::new(async move {
             trace!("close()");
+            let handlers = std::mem::take(&mut zwrite!(self.state).connectivity_handlers);
+            for id in handlers {
+                self.runtime.remove_connectivity_handler(id);
+            }
             self.runtime.close().await?;
 
             let primitives = zwrite!(self.state).primitives.as_ref().unwrap().clone();
@@ -511,6 +573,98 @@ impl Session {
         }
     }
 
+    /// Adds a listener on `endpoint` to the running [`Session`](Session), returning the locators it
+    /// listens on.
+    ///
+    /// The new locators are returned by [`SessionInfo::locators()`](crate::info::SessionInfo::locators)
+    /// and advertised to the other zenoh nodes right away.
+    ///
+    /// # Examples
+    /// ```
+    /// # async_std::task::block_on(async {
+    /// use zenoh::prelude::r#async::*;
+    ///
+    /// let session = zenoh::open(config::peer()).res().await.unwrap();
+    /// let endpoint: EndPoint = "tcp/127.0.0.1:0".parse().unwrap();
+    /// let locators = session.add_listener(endpoint).res().await.unwrap();
+    /// # })
+    /// ```
+    pub fn add_listener(&self, endpoint: EndPoint) -> impl Resolve<ZResult<Vec<Locator>>> {
+        let runtime = self.runtime.clone();
+        ResolveFuture::new(async move { runtime.add_listener(endpoint).await })
+    }
+
+    /// Removes the listener on `endpoint` from the running [`Session`](Session).
+    ///
+    /// The link manager of its protocol is closed with the last listener using it.
+    ///
+    /// # Examples
+    /// ```
+    /// # async_std::task::block_on(async {
+    /// use zenoh::prelude::r#async::*;
+    ///
+    /// let session = zenoh::open(config::peer()).res().await.unwrap();
+    /// let endpoint: EndPoint = "tcp/127.0.0.1:7448".parse().unwrap();
+    /// session.add_listener(endpoint.clone()).res().await.unwrap();
+    /// session.remove_listener(&endpoint).res().await.unwrap();
+    /// # })
+    /// ```
+    pub fn remove_listener(&self, endpoint: &EndPoint) -> impl Resolve<ZResult<()>> {
+        let runtime = self.runtime.clone();
+        let endpoint = endpoint.clone();
+        ResolveFuture::new(async move { runtime.remove_listener(&endpoint).await })
+    }
+
+    /// Registers a callback notified of the [`ConnectivityEvent`]s of the session: the transports
+    /// opened with the other zenoh nodes, closed, or rejected by them, with the reason of the
+    /// close or of the rejection. The callback is unregistered when the session is closed.
+    ///
+    /// # Examples
+    /// ```
+    /// # async_std::task::block_on(async {
+    /// use zenoh::prelude::r#async::*;
+    /// use zenoh::info::ConnectivityEvent;
+    ///
+    /// let session = zenoh::open(config::peer()).res().await.unwrap();
+    /// session.on_connectivity_event(|event| {
+    ///     if let ConnectivityEvent::Rejected { endpoint, reason } = event {
+    ///         println!("Rejected by {endpoint}: {reason}");
+    ///     }
+    /// });
+    /// # })
+    /// ```
+    pub fn on_connectivity_event<F>(&self, callback: F)
+    where
+        F: Fn(&ConnectivityEvent) + Send + Sync + 'static,
+    {
+        let id = self.runtime.on_connectivity_event(Arc::new(callback));
+        zwrite!(self.state).connectivity_handlers.push(id);
+    }
+
+    /// Returns a stream of the [`TopologyEvent`](crate::info::TopologyEvent)s of the session: the
+    /// routers and peers it connects to or disconnects from and, for a router, the changes of
+    /// the neighbors of the routers of its linkstate database.
+    ///
+    /// The stream starts from the current topology, given by its
+    /// [`snapshot`](crate::info::TopologySubscriber::snapshot).
+    ///
+    /// # Examples
+    /// ```
+    /// # async_std::task::block_on(async {
+    /// use zenoh::prelude::r#async::*;
+    ///
+    /// let session = zenoh::open(config::peer()).res().await.unwrap();
+    /// let topology = session.topology();
+    /// let routers = topology.snapshot().routers;
+    /// while let Some(event) = topology.try_recv() {
+    ///     println!("{event:?}");
+    /// }
+    /// # })
+    /// ```
+    pub fn topology(&self) -> TopologySubscriber {
+        self.runtime.topology()
+    }
+
     /// Create a [`Subscriber`](Subscriber) for the given key expression.
     ///
     /// # Arguments
@@ -543,6 +697,7 @@ impl Session {
             reliability: Reliability::default(),
             mode: PushMode,
             origin: Locality::default(),
+            filter: None,
             handler: DefaultHandler,
         }
     }
@@ -619,6 +774,11 @@ impl Session {
             congestion_control: CongestionControl::default(),
             priority: Priority::default(),
             destination: Locality::default(),
+            strict_destination: false,
+            report_drops: false,
+            wait_for_ack: false,
+            no_local: false,
+            source_info: false,
         }
     }
 
@@ -717,6 +877,7 @@ impl Session {
             publisher: self.declare_publisher(key_expr),
             value: value.into(),
             kind: SampleKind::Put,
+            timestamp: None,
         }
     }
 
@@ -748,6 +909,39 @@ impl Session {
             publisher: self.declare_publisher(key_expr),
             value: Value::empty(),
             kind: SampleKind::Delete,
+            timestamp: None,
+        }
+    }
+
+    /// Publish several samples atomically.
+    ///
+    /// The entries added to the returned [`PutBatchBuilder`] share the same timestamp, and are
+    /// received together by the subscribers declared with
+    /// [`batched`](crate::subscriber::SubscriberBuilder::batched).
+    ///
+    /// # Examples
+    /// ```
+    /// # async_std::task::block_on(async {
+    /// use zenoh::prelude::r#async::*;
+    ///
+    /// let session = zenoh::open(config::peer()).res().await.unwrap();
+    /// session
+    ///     .put_batch()
+    ///     .put("pose/x", 1.0)
+    ///     .put("pose/y", 2.0)
+    ///     .put("pose/z", 3.0)
+    ///     .res()
+    ///     .await
+    ///     .unwrap();
+    /// # })
+    /// ```
+    #[inline]
+    pub fn put_batch<'a, 'b: 'a>(&'a self) -> PutBatchBuilder<'a, 'b> {
+        PutBatchBuilder {
+            session: self,
+            entries: vec![],
+            congestion_control: CongestionControl::default(),
+            priority: Priority::default(),
         }
     }
     /// Query data from the matching queryables in the system.
@@ -790,6 +984,8 @@ impl Session {
             destination: Locality::default(),
             timeout: Duration::from_millis(unwrap_or_default!(conf.queries_default_timeout())),
             value: None,
+            repliers: None,
+            ordering: ReplyOrdering::default(),
             handler: DefaultHandler,
         }
     }
@@ -829,21 +1025,35 @@ impl Session {
     }
 
     #[allow(clippy::new_ret_no_self)]
-    pub(super) fn new(config: Config) -> impl Resolve<ZResult<Session>> + Send {
+    pub(super) fn new(
+        config: Config,
+        timeout: Option<Duration>,
+    ) -> impl Resolve<ZResult<Session>> + Send {
         ResolveFuture::new(async move {
             log::debug!("Config: {:?}", &config);
             let aggregated_subscribers = config.aggregation().subscribers().clone();
             let aggregated_publishers = config.aggregation().publishers().clone();
-            match Runtime::init(config).await {
+            let namespace = config.namespace().clone();
+            match Runtime::init(config, Arc::new(SystemClock)).await {
                 Ok(mut runtime) => {
                     let session = Self::init(
                         runtime.clone(),
                         aggregated_subscribers,
                         aggregated_publishers,
+                        namespace,
                     )
                     .res_async()
                     .await;
-                    match runtime.start().await {
+                    let started = match timeout {
+                        Some(timeout) => {
+                            runtime.start().timeout(timeout).await.unwrap_or_else(|_| {
+                                Err(zerror!("Unable to open the session within {:?}", timeout)
+                                    .into())
+                            })
+                        }
+                        None => runtime.start().await,
+                    };
+                    match started {
                         Ok(()) => {
                             // Workaround for the declare_and_shoot problem
                             task::sleep(Duration::from_millis(*API_OPEN_SESSION_DELAY)).await;
@@ -879,11 +1089,7 @@ impl Session {
                         ..
                     }) = &mut res
                     {
-                        for sub in state.subscribers.values() {
-                            if key_expr.intersects(&sub.key_expr) {
-                                subscribers.push(sub.clone());
-                            }
-                        }
+                        subscribers.extend(state.subscribers_intersecting(key_expr).cloned());
                     }
                     state.local_resources.insert(expr_id, res);
                     let primitives = state.primitives.as_ref().unwrap().clone();
@@ -994,21 +1200,34 @@ impl Session {
         origin: Locality,
         callback: Callback<'static, Sample>,
         info: &SubscriberInfo,
+        filter: Option<Filter>,
     ) -> ZResult<Arc<SubscriberState>> {
         let mut state = zwrite!(self.state);
         log::trace!("subscribe({:?})", key_expr);
-        let id = state.decl_id_counter.fetch_add(1, Ordering::SeqCst);
+        let id = self.runtime.next_id() as Id;
         let key_expr = match scope {
             Some(scope) => scope / key_expr,
             None => key_expr.clone(),
         };
 
+        // The filter is also evaluated on reception, for the samples routed by nodes ignoring it
+        let callback: Callback<'static, Sample> = match filter.clone() {
+            Some(filter) => Arc::new(move |sample: Sample| {
+                if filter.matches(&sample) {
+                    callback(sample)
+                }
+            }),
+            None => callback,
+        };
         let sub_state = Arc::new(SubscriberState {
             id,
             key_expr: key_expr.clone().into_owned(),
             scope: scope.clone().map(|e| e.into_owned()),
             origin,
+            filter,
             callback,
+            panics: CallbackPanics::default(),
+            undeclared: AtomicBool::new(false),
         });
 
         #[cfg(not(feature = "unstable"))]
@@ -1030,20 +1249,22 @@ impl Session {
                         let joined_sub = state.subscribers.values().any(|s| {
                             s.origin != Locality::SessionLocal && join_sub.includes(&s.key_expr)
                         });
-                        (!joined_sub).then(|| join_sub.clone().into())
+                        (!joined_sub).then(|| (join_sub.clone().into(), None))
                     }
                     None => {
-                        let twin_sub = state
-                            .subscribers
-                            .values()
-                            .any(|s| s.origin != Locality::SessionLocal && s.key_expr == key_expr);
-                        (!twin_sub).then(|| key_expr.clone())
+                        // Only an unfiltered twin receives all the samples of the subscriber
+                        let twin_sub = state.subscribers.values().any(|s| {
+                            s.origin != Locality::SessionLocal
+                                && s.key_expr == key_expr
+                                && s.filter.is_none()
+                        });
+                        (!twin_sub).then(|| (key_expr.clone(), sub_state.filter.as_ref()))
                     }
                 }
             })
             .flatten();
 
-        state.subscribers.insert(sub_state.id, sub_state.clone());
+        state.add_subscriber(sub_state.clone());
         for res in state
             .local_resources
             .values_mut()
@@ -1063,7 +1284,10 @@ impl Session {
             }
         }
 
-        if let Some(key_expr) = declared_sub {
+        if let Some((key_expr, filter)) = declared_sub {
+            let ext_filter = filter.map(|filter| declare::subscriber::ext::FilterType {
+                expr: filter.to_string(),
+            });
             let primitives = state.primitives.as_ref().unwrap().clone();
             drop(state);
             // If key_expr is a pure Expr, remap it to optimal Rid or RidWithSuffix
@@ -1097,6 +1321,7 @@ impl Session {
                     id: id as u32,
                     wire_expr: key_expr.to_wire(self).to_owned(),
                     ext_info: *info,
+                    ext_filter,
                 }),
             });
         }
@@ -1106,7 +1331,7 @@ impl Session {
 
     pub(crate) fn unsubscribe(&self, sid: usize) -> ZResult<()> {
         let mut state = zwrite!(self.state);
-        if let Some(sub_state) = state.subscribers.remove(&sid) {
+        if let Some(sub_state) = state.remove_subscriber(sid) {
             trace!("unsubscribe({:?})", sub_state);
             for res in state
                 .local_resources
@@ -1197,13 +1422,15 @@ impl Session {
     ) -> ZResult<Arc<QueryableState>> {
         let mut state = zwrite!(self.state);
         log::trace!("queryable({:?})", key_expr);
-        let id = state.decl_id_counter.fetch_add(1, Ordering::SeqCst);
+        let id = self.runtime.next_id() as Id;
         let qable_state = Arc::new(QueryableState {
             id,
             key_expr: key_expr.to_owned(),
             complete,
             origin,
             callback,
+            panics: CallbackPanics::default(),
+            undeclared: AtomicBool::new(false),
         });
         #[cfg(feature = "complete_n")]
         {
@@ -1371,7 +1598,7 @@ impl Session {
     ) -> ZResult<Arc<LivelinessTokenState>> {
         let mut state = zwrite!(self.state);
         log::trace!("declare_liveliness({:?})", key_expr);
-        let id = state.decl_id_counter.fetch_add(1, Ordering::SeqCst);
+        let id = self.runtime.next_id() as Id;
         let key_expr = KeyExpr::from(*crate::liveliness::KE_PREFIX_LIVELINESS / key_expr);
         let tok_state = Arc::new(LivelinessTokenState {
             id,
@@ -1389,6 +1616,7 @@ impl Session {
                 id: id as u32,
                 wire_expr: key_expr.to_wire(self).to_owned(),
                 ext_info: SubscriberInfo::default(),
+                ext_filter: None,
             }),
         });
         Ok(tok_state)
@@ -1423,6 +1651,26 @@ impl Session {
         }
     }
 
+    /// Fails if the session was closed, e.g. by the closing of its runtime.
+    pub(crate) fn check_alive(&self) -> ZResult<()> {
+        if self.runtime.is_closed() {
+            bail!("Session closed");
+        }
+        Ok(())
+    }
+
+    /// Checks that `key_expr` can be the target of a put or a delete: updates on key
+    /// expressions containing wildcards are only accepted if `wildcard_updates` is enabled.
+    pub(crate) fn check_update_key_expr(&self, key_expr: &KeyExpr) -> ZResult<()> {
+        if key_expr.is_wild() && !self.runtime.wildcard_updates {
+            bail!(
+                "Updates on key expression '{}' containing wildcards are rejected: enable the `wildcard_updates` configuration to accept them",
+                key_expr
+            );
+        }
+        Ok(())
+    }
+
     pub(crate) fn handle_data(
         &self,
         local: bool,
@@ -1430,112 +1678,135 @@ impl Session {
         info: Option<DataInfo>,
         payload: ZBuf,
     ) {
-        let mut callbacks = SingleOrVec::default();
         let state = zread!(self.state);
-        if key_expr.suffix.is_empty() {
-            match state.get_res(&key_expr.scope, key_expr.mapping, local) {
-                Some(Resource::Node(res)) => {
-                    for sub in &res.subscribers {
-                        if sub.origin == Locality::Any
-                            || (local == (sub.origin == Locality::SessionLocal))
-                        {
-                            match &sub.scope {
-                                Some(scope) => {
-                                    if !res.key_expr.starts_with(&***scope) {
-                                        log::warn!(
-                                            "Received Data for `{}`, which didn't start with scope `{}`: don't deliver to scoped Subscriber.",
-                                            res.key_expr,
-                                            scope,
-                                        );
-                                    } else {
-                                        match KeyExpr::try_from(&res.key_expr[(scope.len() + 1)..])
-                                        {
-                                            Ok(key_expr) => callbacks.push((
-                                                sub.callback.clone(),
-                                                key_expr.into_owned(),
-                                            )),
-                                            Err(e) => {
-                                                log::warn!(
-                                                    "Error unscoping received Data for `{}`: {}",
-                                                    res.key_expr,
-                                                    e,
-                                                );
-                                            }
-                                        }
-                                    }
-                                }
-                                None => callbacks
-                                    .push((sub.callback.clone(), res.key_expr.clone().into())),
-                            };
-                        }
-                    }
-                }
-                Some(Resource::Prefix { prefix }) => {
-                    log::error!(
-                        "Received Data for `{}`, which isn't a key expression",
-                        prefix
-                    );
-                    return;
-                }
-                None => {
-                    log::error!("Received Data for unknown expr_id: {}", key_expr.scope);
-                    return;
-                }
-            }
-        } else {
-            match state.wireexpr_to_keyexpr(key_expr, local) {
-                Ok(key_expr) => {
-                    for sub in state.subscribers.values() {
-                        if (sub.origin == Locality::Any
-                            || (local == (sub.origin == Locality::SessionLocal)))
-                            && key_expr.intersects(&sub.key_expr)
-                        {
-                            match &sub.scope {
-                                Some(scope) => {
-                                    if !key_expr.starts_with(&***scope) {
-                                        log::warn!(
-                                            "Received Data for `{}`, which didn't start with scope `{}`: don't deliver to scoped Subscriber.",
-                                            key_expr,
-                                            scope,
-                                        );
-                                    } else {
-                                        match KeyExpr::try_from(&key_expr[(scope.len() + 1)..]) {
-                                            Ok(key_expr) => callbacks.push((
-                                                sub.callback.clone(),
-                                                key_expr.into_owned(),
-                                            )),
-                                            Err(e) => {
-                                                log::warn!(
-                                                    "Error unscoping received Data for `{}`: {}",
-                                                    key_expr,
-                                                    e,
-                                                );
-                                            }
-                                        }
-                                    }
-                                }
-                                None => callbacks
-                                    .push((sub.callback.clone(), key_expr.clone().into_owned())),
-                            };
-                        }
-                    }
-                }
-                Err(err) => {
-                    log::error!("Received Data for unkown key_expr: {}", err);
-                    return;
-                }
-            }
+        let callbacks = match matching_subscribers(&state, key_expr, local) {
+            Some(callbacks) => callbacks,
+            None => return,
         };
         drop(state);
         let zenoh_collections::single_or_vec::IntoIter { drain, last } = callbacks.into_iter();
-        for (cb, key_expr) in drain {
-            cb(Sample::with_info(key_expr, payload.clone(), info.clone()));
+        for (sub, key_expr) in drain {
+            self.deliver(
+                &sub,
+                Sample::with_info(key_expr, payload.clone(), info.clone()),
+            );
         }
-        if let Some((cb, key_expr)) = last {
-            cb(Sample::with_info(key_expr, payload, info));
+        if let Some((sub, key_expr)) = last {
+            self.deliver(&sub, Sample::with_info(key_expr, payload, info));
         }
     }
 
+    /// Delivers the samples of the batch `id`, given in publication order.
+    ///
+    /// The samples matching a subscriber are delivered to it one after the other, numbered
+    /// within the part of the batch it receives.
+    pub(crate) fn handle_batch(
+        &self,
+        local: bool,
+        id: u64,
+        entries: Vec<(WireExpr<'static>, DataInfo, ZBuf)>,
+    ) {
+        let mut deliveries: Vec<(Arc<SubscriberState>, Vec<Sample>)> = vec![];
+        let state = zread!(self.state);
+        for (key_expr, info, payload) in entries {
+            let callbacks = match matching_subscribers(&state, &key_expr, local) {
+                Some(callbacks) => callbacks,
+                None => continue,
+            };
+            for (sub, key_expr) in callbacks {
+                let sample = Sample::with_info(key_expr, payload.clone(), Some(info.clone()));
+                match deliveries.iter_mut().find(|(s, _)| s.id == sub.id) {
+                    Some((_, samples)) => samples.push(sample),
+                    None => deliveries.push((sub, vec![sample])),
+                }
+            }
+        }
+        drop(state);
+        for (sub, samples) in deliveries {
+            let count = samples.len() as u32;
+            for (index, mut sample) in samples.into_iter().enumerate() {
+                sample.batch = Some(SampleBatch {
+                    id,
+                    index: index as u32,
+                    count,
+                });
+                self.deliver(&sub, sample);
+            }
+        }
+    }
+
+    // The data pushed on `key_expr`, None if its payload fails the verification of its integrity.
+    fn push_data(
+        &self,
+        key_expr: WireExpr<'static>,
+        qos: QoS,
+        payload: PushBody,
+    ) -> Option<(WireExpr<'static>, DataInfo, ZBuf)> {
+        match payload {
+            PushBody::Put(m) => {
+                let integrity = self.runtime.integrity.verify(
+                    &key_expr,
+                    m.ext_integrity.as_ref(),
+                    &m.payload,
+                )?;
+                let info = DataInfo {
+                    kind: SampleKind::Put,
+                    encoding: Some(m.encoding),
+                    timestamp: m.timestamp,
+                    source_id: m.ext_sinfo.as_ref().map(|i| i.zid),
+                    source_eid: m.ext_sinfo.as_ref().map(|i| i.eid),
+                    source_sn: m.ext_sinfo.as_ref().map(|i| i.sn as u64),
+                    qos,
+                    integrity,
+                };
+                Some((key_expr, info, m.payload))
+            }
+            PushBody::Del(m) => {
+                let info = DataInfo {
+                    kind: SampleKind::Delete,
+                    encoding: None,
+                    timestamp: m.timestamp,
+                    source_id: m.ext_sinfo.as_ref().map(|i| i.zid),
+                    source_eid: m.ext_sinfo.as_ref().map(|i| i.eid),
+                    source_sn: m.ext_sinfo.as_ref().map(|i| i.sn as u64),
+                    qos,
+                    integrity: SampleIntegrity::Unchecked,
+                };
+                Some((key_expr, info, ZBuf::empty()))
+            }
+        }
+    }
+
+    // Calls the callback of the subscriber, containing its panic.
+    fn deliver(&self, sub: &SubscriberState, sample: Sample) {
+        let panics = sub.panics.call(
+            format_args!("subscriber {} on {}", sub.id, sub.key_expr),
+            || (sub.callback)(sample),
+        );
+        if self.undeclares_after(panics) && sub.mark_undeclared() {
+            log::error!(
+                "Undeclaring subscriber {} on {} after {} consecutive panics",
+                sub.id,
+                sub.key_expr,
+                panics
+            );
+            let _ = self.unsubscribe(sub.id);
+        }
+    }
+
+    // Whether a subscriber or a queryable is undeclared after `panics` consecutive panics of
+    // its callback, see the `callbacks/undeclare_after_panics` configuration.
+    fn undeclares_after(&self, panics: usize) -> bool {
+        panics > 0
+            && self
+                .runtime
+                .config
+                .lock()
+                .callbacks()
+                .undeclare_after_panics()
+                .map_or(false, |max| panics >= max)
+    }
+
     pub(crate) fn pull<'a>(&'a self, key_expr: &'a KeyExpr) -> impl Resolve<ZResult<()>> + 'a {
         ResolveClosure::new(move || {
             trace!("pull({:?})", key_expr);
@@ -1551,6 +1822,8 @@ impl Session {
                 ext_target: request::ext::TargetType::default(),
                 ext_budget: None,
                 ext_timeout: None,
+                ext_correlation: None,
+                ext_repliers: None,
                 payload: RequestBody::Pull(Pull {
                     ext_unknown: vec![],
                 }),
@@ -1569,6 +1842,8 @@ impl Session {
         destination: Locality,
         timeout: Duration,
         value: Option<Value>,
+        repliers: Option<Vec<ZenohId>>,
+        ordering: ReplyOrdering,
         callback: Callback<'static, Reply>,
     ) -> ZResult<()> {
         log::trace!("get({}, {:?}, {:?})", selector, target, consolidation);
@@ -1597,14 +1872,13 @@ impl Session {
                 if let Some(query) = state.queries.remove(&qid) {
                     std::mem::drop(state);
                     log::debug!("Timeout on query {}! Send error and close.", qid);
-                    if query.reception_mode == ConsolidationMode::Latest {
-                        for (_, reply) in query.replies.unwrap().into_iter() {
-                            (query.callback)(reply);
-                        }
-                    }
-                    (query.callback)(Reply {
+                    let callback = query.callback.clone();
+                    query.finish(qid, zid);
+                    callback(Reply {
                         sample: Err("Timeout".into()),
                         replier_id: zid,
+                        replier_eid: None,
+                        retry_after: None,
                     });
                 }
             }
@@ -1628,11 +1902,18 @@ impl Session {
                 scope: scope.clone().map(|e| e.into_owned()),
                 reception_mode: consolidation,
                 replies: (consolidation != ConsolidationMode::None).then(HashMap::new),
+                repliers: repliers.clone(),
+                ordering: ReplyOrderer::new(ordering),
                 callback,
             },
         );
 
         let primitives = state.primitives.as_ref().unwrap().clone();
+        // Identifies the query and its replies on every hop
+        let correlation = request::ext::CorrelationType {
+            zid: self.runtime.zid,
+            eid: qid,
+        };
 
         drop(state);
         if destination != Locality::SessionLocal {
@@ -1645,6 +1926,8 @@ impl Session {
                 ext_target: target,
                 ext_budget: None,
                 ext_timeout: Some(timeout),
+                ext_correlation: Some(correlation.clone()),
+                ext_repliers: repliers.map(|zids| RepliersType { zids }),
                 payload: RequestBody::Query(zenoh_protocol::zenoh::Query {
                     parameters: selector.parameters().to_string(),
                     ext_sinfo: None,
@@ -1667,6 +1950,7 @@ impl Session {
                 qid,
                 target,
                 consolidation.into(),
+                Some(correlation),
                 value.as_ref().map(|v| query::ext::QueryBodyType {
                     #[cfg(feature = "shared-memory")]
                     ext_shm: None,
@@ -1687,6 +1971,7 @@ impl Session {
         qid: RequestId,
         _target: TargetType,
         _consolidation: ConsolidationType,
+        correlation: Option<request::ext::CorrelationType>,
         body: Option<QueryBodyType>,
     ) {
         let (primitives, key_expr, callbacks) = {
@@ -1714,8 +1999,8 @@ impl Session {
                                     }
                                 }
                         )
-                        .map(|qable| qable.callback.clone())
-                        .collect::<Vec<Arc<dyn Fn(Query) + Send + Sync>>>();
+                        .cloned()
+                        .collect::<Vec<Arc<QueryableState>>>();
                     (
                         state.primitives.as_ref().unwrap().clone(),
                         key_expr.into_owned(),
@@ -1733,25 +2018,41 @@ impl Session {
 
         let zid = self.runtime.zid; // @TODO build/use prebuilt specific zid
 
-        let query = Query {
-            inner: Arc::new(QueryInner {
-                key_expr,
-                parameters,
-                value: body.map(|b| Value {
-                    payload: b.payload,
-                    encoding: b.encoding,
-                }),
-                qid,
-                zid,
-                primitives: if local {
-                    Arc::new(self.clone())
-                } else {
-                    primitives
-                },
+        let inner = Arc::new(QueryInner {
+            key_expr,
+            parameters,
+            value: body.map(|b| Value {
+                payload: b.payload,
+                encoding: b.encoding,
             }),
-        };
-        for callback in callbacks.iter() {
-            callback(query.clone());
+            qid,
+            zid,
+            correlation,
+            primitives: if local {
+                Arc::new(self.clone())
+            } else {
+                primitives
+            },
+        });
+        // Each queryable replies with its own id
+        for qable in callbacks.iter() {
+            let query = Query {
+                inner: inner.clone(),
+                eid: qable.id as EntityId,
+            };
+            let panics = qable.panics.call(
+                format_args!("queryable {} on {}", qable.id, qable.key_expr),
+                || (qable.callback)(query),
+            );
+            if self.undeclares_after(panics) && qable.mark_undeclared() {
+                log::error!(
+                    "Undeclaring queryable {} on {} after {} consecutive panics",
+                    qable.id,
+                    qable.key_expr,
+                    panics
+                );
+                let _ = self.close_queryable(qable.id);
+            }
         }
     }
 }
@@ -1794,6 +2095,7 @@ impl SessionDeclarations for Arc<Session> {
             reliability: Reliability::default(),
             mode: PushMode,
             origin: Locality::default(),
+            filter: None,
             handler: DefaultHandler,
         }
     }
@@ -1875,6 +2177,11 @@ impl SessionDeclarations for Arc<Session> {
             congestion_control: CongestionControl::default(),
             priority: Priority::default(),
             destination: Locality::default(),
+            strict_destination: false,
+            report_drops: false,
+            wait_for_ack: false,
+            no_local: false,
+            source_info: false,
         }
     }
 
@@ -1902,6 +2209,112 @@ impl SessionDeclarations for Arc<Session> {
     }
 }
 
+// The subscribers matching the data published on `key_expr`, with the key expression of the
+// data as seen by each of them.
+fn matching_subscribers(
+    state: &SessionState,
+    key_expr: &WireExpr,
+    local: bool,
+) -> Option<SingleOrVec<(Arc<SubscriberState>, KeyExpr<'static>)>> {
+    let mut callbacks = SingleOrVec::default();
+    if key_expr.suffix.is_empty() {
+        match state.get_res(&key_expr.scope, key_expr.mapping, local) {
+            Some(Resource::Node(res)) => {
+                for sub in &res.subscribers {
+                    if sub.origin == Locality::Any
+                        || (local == (sub.origin == Locality::SessionLocal))
+                    {
+                        match &sub.scope {
+                            Some(scope) => {
+                                if !res.key_expr.starts_with(&***scope) {
+                                    log::warn!(
+                                        "Received Data for `{}`, which didn't start with scope `{}`: don't deliver to scoped Subscriber.",
+                                        res.key_expr,
+                                        scope,
+                                    );
+                                } else {
+                                    match KeyExpr::try_from(&res.key_expr[(scope.len() + 1)..]) {
+                                        Ok(key_expr) => {
+                                            callbacks.push((sub.clone(), key_expr.into_owned()))
+                                        }
+                                        Err(e) => {
+                                            log::warn!(
+                                                "Error unscoping received Data for `{}`: {}",
+                                                res.key_expr,
+                                                e,
+                                            );
+                                        }
+                                    }
+                                }
+                            }
+                            None => callbacks.push((sub.clone(), res.key_expr.clone().into())),
+                        };
+                    }
+                }
+            }
+            Some(Resource::Prefix { prefix }) => {
+                log::error!(
+                    "Received Data for `{}`, which isn't a key expression",
+                    prefix
+                );
+                return None;
+            }
+            None => {
+                log::error!("Received Data for unknown expr_id: {}", key_expr.scope);
+                return None;
+            }
+        }
+    } else {
+        match state.wireexpr_to_keyexpr(key_expr, local) {
+            Ok(key_expr) => {
+                // Owned once for all the subscribers, instead of once per subscriber
+                let mut owned_key_expr: Option<KeyExpr<'static>> = None;
+                for sub in state.subscribers_intersecting(&key_expr) {
+                    if sub.origin == Locality::Any
+                        || (local == (sub.origin == Locality::SessionLocal))
+                    {
+                        match &sub.scope {
+                            Some(scope) => {
+                                if !key_expr.starts_with(&***scope) {
+                                    log::warn!(
+                                        "Received Data for `{}`, which didn't start with scope `{}`: don't deliver to scoped Subscriber.",
+                                        key_expr,
+                                        scope,
+                                    );
+                                } else {
+                                    match KeyExpr::try_from(&key_expr[(scope.len() + 1)..]) {
+                                        Ok(key_expr) => {
+                                            callbacks.push((sub.clone(), key_expr.into_owned()))
+                                        }
+                                        Err(e) => {
+                                            log::warn!(
+                                                "Error unscoping received Data for `{}`: {}",
+                                                key_expr,
+                                                e,
+                                            );
+                                        }
+                                    }
+                                }
+                            }
+                            None => callbacks.push((
+                                sub.clone(),
+                                owned_key_expr
+                                    .get_or_insert_with(|| key_expr.clone().into_owned())
+                                    .clone(),
+                            )),
+                        };
+                    }
+                }
+            }
+            Err(err) => {
+                log::error!("Received Data for unkown key_expr: {}", err);
+                return None;
+            }
+        }
+    }
+    Some(callbacks)
+}
+
 impl Primitives for Session {
     fn send_declare(&self, msg: zenoh_protocol::network::Declare) {
         match msg.body {
@@ -1910,12 +2323,7 @@ impl Primitives for Session {
                 let state = &mut zwrite!(self.state);
                 match state.remote_key_to_expr(&m.wire_expr) {
                     Ok(key_expr) => {
-                        let mut subs = Vec::new();
-                        for sub in state.subscribers.values() {
-                            if key_expr.intersects(&sub.key_expr) {
-                                subs.push(sub.clone());
-                            }
-                        }
+                        let subs = state.subscribers_intersecting(&key_expr).cloned().collect();
                         let res = Resource::Node(ResourceNode {
                             key_expr: key_expr.into(),
                             subscribers: subs,
@@ -1931,6 +2339,14 @@ impl Primitives for Session {
             }
             zenoh_protocol::network::DeclareBody::UndeclareKeyExpr(m) => {
                 trace!("recv UndeclareKeyExpr {}", m.id);
+                zwrite!(self.state).remote_resources.remove(&m.id);
+            }
+            // The mappings are only resynchronized in bulk between the routing tables
+            zenoh_protocol::network::DeclareBody::DeclareKeyExprs(m) => {
+                trace!("recv DeclareKeyExprs {}", m.mappings.len());
+            }
+            zenoh_protocol::network::DeclareBody::RejectKeyExprs(m) => {
+                trace!("recv RejectKeyExprs {:?}", m.ids);
             }
             zenoh_protocol::network::DeclareBody::DeclareSubscriber(m) => {
                 trace!("recv DeclareSubscriber {} {:?}", m.id, m.wire_expr);
@@ -1997,28 +2413,24 @@ impl Primitives for Session {
         }
     }
 
-    fn send_push(&self, msg: Push) {
+    fn send_push(&self, mut msg: Push, _reliability: Reliability) {
         trace!("recv Push {:?}", msg);
-        match msg.payload {
-            PushBody::Put(m) => {
-                let info = DataInfo {
-                    kind: SampleKind::Put,
-                    encoding: Some(m.encoding),
-                    timestamp: m.timestamp,
-                    source_id: m.ext_sinfo.as_ref().map(|i| i.zid),
-                    source_sn: m.ext_sinfo.as_ref().map(|i| i.sn as u64),
-                };
-                self.handle_data(false, &msg.wire_expr, Some(info), m.payload)
+        let qos: QoS = msg.ext_qos.into();
+        match msg.payload.ext_batch_mut().take() {
+            Some(batch) => {
+                let mut entries = Vec::with_capacity(batch.entries.len() + 1);
+                entries.extend(self.push_data(msg.wire_expr, qos, msg.payload));
+                for entry in batch.entries {
+                    entries.extend(self.push_data(entry.wire_expr, qos, entry.payload));
+                }
+                self.handle_batch(false, batch.id, entries);
             }
-            PushBody::Del(m) => {
-                let info = DataInfo {
-                    kind: SampleKind::Delete,
-                    encoding: None,
-                    timestamp: m.timestamp,
-                    source_id: m.ext_sinfo.as_ref().map(|i| i.zid),
-                    source_sn: m.ext_sinfo.as_ref().map(|i| i.sn as u64),
-                };
-                self.handle_data(false, &msg.wire_expr, Some(info), ZBuf::empty())
+            None => {
+                if let Some((key_expr, info, payload)) =
+                    self.push_data(msg.wire_expr, qos, msg.payload)
+                {
+                    self.handle_data(false, &key_expr, Some(info), payload)
+                }
             }
         }
     }
@@ -2033,6 +2445,7 @@ impl Primitives for Session {
                 msg.id,
                 msg.ext_target,
                 m.ext_consolidation,
+                msg.ext_correlation,
                 m.ext_body,
             ),
             RequestBody::Put(_) => (),
@@ -2043,6 +2456,43 @@ impl Primitives for Session {
 
     fn send_response(&self, msg: Response) {
         trace!("recv Response {:?}", msg);
+        if let ResponseBody::Err(e) = msg.payload {
+            let state = zread!(self.state);
+            match state.queries.get(&msg.rid) {
+                Some(query) => {
+                    let value = match e.ext_body {
+                        Some(body) => Value::new(body.payload).encoding(body.encoding),
+                        None => Value::empty(),
+                    };
+                    // A query throttled by the router carries the time after which to retry it
+                    let retry_after =
+                        (e.is_infrastructure && e.code == err::code::THROTTLED).then(|| {
+                            let properties = Properties::from(
+                                String::from_utf8_lossy(&value.payload.contiguous()).as_ref(),
+                            );
+                            properties
+                                .get("retry_after_ms")
+                                .and_then(|ms| ms.parse().ok())
+                                .map(Duration::from_millis)
+                                .unwrap_or_default()
+                        });
+                    let replier = msg.ext_respid.as_ref().map(|r| (r.zid, r.eid));
+                    let reply = Reply {
+                        sample: Err(value),
+                        replier_id: replier.map(|(zid, _)| zid).unwrap_or_default(),
+                        replier_eid: replier.map(|(_, eid)| eid),
+                        retry_after,
+                    };
+                    let callback = query.callback.clone();
+                    std::mem::drop(state);
+                    callback(reply);
+                }
+                None => {
+                    log::warn!("Received ReplyErr for unkown Query: {}", msg.rid);
+                }
+            }
+            return;
+        }
         if let ResponseBody::Reply(m) = msg.payload {
             let mut state = zwrite!(self.state);
             let key_expr = match state.remote_key_to_expr(&msg.wire_expr) {
@@ -2054,6 +2504,17 @@ impl Primitives for Session {
             };
             match state.queries.get_mut(&msg.rid) {
                 Some(query) => {
+                    let replier = msg.ext_respid.as_ref().map(|r| (r.zid, r.eid));
+                    if let Some(repliers) = query.repliers.as_ref() {
+                        if !replier.map_or(false, |(zid, _)| repliers.contains(&zid)) {
+                            trace!(
+                                "Received Reply for `{}` from `{:?}`, which is not an accepted replier: dropping Reply.",
+                                key_expr,
+                                msg.ext_respid,
+                            );
+                            return;
+                        }
+                    }
                     if !matches!(
                         query
                             .selector
@@ -2101,7 +2562,10 @@ impl Primitives for Session {
                         encoding: Some(m.encoding),
                         timestamp: m.timestamp,
                         source_id: m.ext_sinfo.as_ref().map(|i| i.zid),
+                        source_eid: m.ext_sinfo.as_ref().map(|i| i.eid),
                         source_sn: m.ext_sinfo.as_ref().map(|i| i.sn as u64),
+                        qos: QoS::default(),
+                        integrity: SampleIntegrity::Unchecked,
                     };
                     let new_reply = Reply {
                         sample: Ok(Sample::with_info(
@@ -2109,7 +2573,9 @@ impl Primitives for Session {
                             m.payload,
                             Some(info),
                         )),
-                        replier_id: ZenohId::rand(), // TOTO
+                        replier_id: replier.map(|(zid, _)| zid).unwrap_or_default(),
+                        replier_eid: replier.map(|(_, eid)| eid),
+                        retry_after: None,
                     };
                     let callback = match query.reception_mode {
                         ConsolidationMode::None => Some((query.callback.clone(), new_reply)),
@@ -2181,6 +2647,12 @@ impl Primitives for Session {
                             None
                         }
                     };
+                    let callback = callback.and_then(|(callback, new_reply)| {
+                        query
+                            .ordering
+                            .push(new_reply)
+                            .map(|reply| (callback, reply))
+                    });
                     std::mem::drop(state);
                     if let Some((callback, new_reply)) = callback {
                         callback(new_reply);
@@ -2202,11 +2674,7 @@ impl Primitives for Session {
                 if query.nb_final == 0 {
                     let query = state.queries.remove(&msg.rid).unwrap();
                     std::mem::drop(state);
-                    if query.reception_mode == ConsolidationMode::Latest {
-                        for (_, reply) in query.replies.unwrap().into_iter() {
-                            (query.callback)(reply);
-                        }
-                    }
+                    query.finish(msg.rid, self.runtime.zid);
                     trace!("Close query {}", msg.rid);
                 }
             }
@@ -13,16 +13,21 @@
 //
 
 //! Subscribing primitives.
-use crate::handlers::{locked, Callback, DefaultHandler};
+use crate::filter::Filter;
+use crate::handlers::{locked, Callback, CallbackPanics, DefaultHandler};
 use crate::prelude::Locality;
 use crate::prelude::{Id, IntoCallbackReceiverPair, KeyExpr, Sample};
 use crate::Undeclarable;
 use crate::{Result as ZResult, SessionRef};
+use std::collections::HashMap;
 use std::fmt;
 use std::future::Ready;
 use std::ops::{Deref, DerefMut};
-use std::sync::Arc;
-use zenoh_core::{AsyncResolve, Resolvable, Resolve, SyncResolve};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use zenoh_core::{zlock, AsyncResolve, Resolvable, Resolve, SyncResolve};
+#[zenoh_macros::unstable]
+use zenoh_protocol::core::{EntityGlobalId, EntityId};
 use zenoh_protocol::network::declare::{subscriber::ext::SubscriberInfo, Mode};
 
 /// The subscription mode.
@@ -36,7 +41,20 @@ pub(crate) struct SubscriberState {
     pub(crate) key_expr: KeyExpr<'static>,
     pub(crate) scope: Option<KeyExpr<'static>>,
     pub(crate) origin: Locality,
+    pub(crate) filter: Option<Filter>,
     pub(crate) callback: Callback<'static, Sample>,
+    pub(crate) panics: CallbackPanics,
+    // Whether the subscriber was undeclared, by the application or after the panics of its
+    // callback
+    pub(crate) undeclared: AtomicBool,
+}
+
+impl SubscriberState {
+    /// Marks the subscriber as undeclared. Returns `false` if it already was, in which case it
+    /// must not be undeclared again.
+    pub(crate) fn mark_undeclared(&self) -> bool {
+        !self.undeclared.swap(true, Ordering::AcqRel)
+    }
 }
 
 impl fmt::Debug for SubscriberState {
@@ -164,6 +182,14 @@ impl<'a> PullSubscriberInner<'a> {
 }
 
 impl<'a> SubscriberInner<'a> {
+    #[zenoh_macros::unstable]
+    pub(crate) fn id(&self) -> EntityGlobalId {
+        EntityGlobalId {
+            zid: self.session.zid(),
+            eid: self.state.id as EntityId,
+        }
+    }
+
     /// Close a [`CallbackSubscriber`](CallbackSubscriber).
     ///
     /// `CallbackSubscribers` are automatically closed when dropped, but you may want to use this function to handle errors or
@@ -224,6 +250,9 @@ impl Resolvable for SubscriberUndeclaration<'_> {
 impl SyncResolve for SubscriberUndeclaration<'_> {
     fn res_sync(mut self) -> <Self as Resolvable>::To {
         self.subscriber.alive = false;
+        if !self.subscriber.state.mark_undeclared() {
+            return Ok(());
+        }
         self.subscriber
             .session
             .unsubscribe(self.subscriber.state.id)
@@ -240,7 +269,7 @@ impl AsyncResolve for SubscriberUndeclaration<'_> {
 
 impl Drop for SubscriberInner<'_> {
     fn drop(&mut self) {
-        if self.alive {
+        if self.alive && self.state.mark_undeclared() {
             let _ = self.session.unsubscribe(self.state.id);
         }
     }
@@ -325,6 +354,11 @@ pub struct SubscriberBuilder<'a, 'b, Mode, Handler> {
     #[cfg(not(feature = "unstable"))]
     pub(crate) origin: Locality,
 
+    #[cfg(feature = "unstable")]
+    pub filter: Option<Filter>,
+    #[cfg(not(feature = "unstable"))]
+    pub(crate) filter: Option<Filter>,
+
     #[cfg(feature = "unstable")]
     pub handler: Handler,
     #[cfg(not(feature = "unstable"))]
@@ -359,6 +393,7 @@ impl<'a, 'b, Mode> SubscriberBuilder<'a, 'b, Mode, DefaultHandler> {
             reliability,
             mode,
             origin,
+            filter,
             handler: _,
         } = self;
         SubscriberBuilder {
@@ -367,6 +402,7 @@ impl<'a, 'b, Mode> SubscriberBuilder<'a, 'b, Mode, DefaultHandler> {
             reliability,
             mode,
             origin,
+            filter,
             handler: callback,
         }
     }
@@ -432,6 +468,7 @@ impl<'a, 'b, Mode> SubscriberBuilder<'a, 'b, Mode, DefaultHandler> {
             reliability,
             mode,
             origin,
+            filter,
             handler: _,
         } = self;
         SubscriberBuilder {
@@ -440,6 +477,7 @@ impl<'a, 'b, Mode> SubscriberBuilder<'a, 'b, Mode, DefaultHandler> {
             reliability,
             mode,
             origin,
+            filter,
             handler,
         }
     }
@@ -475,6 +513,31 @@ impl<'a, 'b, Mode, Handler> SubscriberBuilder<'a, 'b, Mode, Handler> {
         self
     }
 
+    /// Only receive the samples matching the given [`Filter`].
+    ///
+    /// The filter is sent with the declaration of the subscriber, so that the samples it doesn't
+    /// match are dropped before reaching the subscriber's session.
+    ///
+    /// # Examples
+    /// ```
+    /// # async_std::task::block_on(async {
+    /// use zenoh::prelude::r#async::*;
+    ///
+    /// let session = zenoh::open(config::peer()).res().await.unwrap();
+    /// let subscriber = session
+    ///     .declare_subscriber("key/expression")
+    ///     .filter("payload > 10".parse().unwrap())
+    ///     .res()
+    ///     .await
+    ///     .unwrap();
+    /// # })
+    /// ```
+    #[inline]
+    pub fn filter(mut self, filter: Filter) -> Self {
+        self.filter = Some(filter);
+        self
+    }
+
     /// Change the subscription mode to Pull.
     #[inline]
     pub fn pull_mode(self) -> SubscriberBuilder<'a, 'b, PullMode, Handler> {
@@ -484,6 +547,7 @@ impl<'a, 'b, Mode, Handler> SubscriberBuilder<'a, 'b, Mode, Handler> {
             reliability,
             mode: _,
             origin,
+            filter,
             handler,
         } = self;
         SubscriberBuilder {
@@ -492,6 +556,7 @@ impl<'a, 'b, Mode, Handler> SubscriberBuilder<'a, 'b, Mode, Handler> {
             reliability,
             mode: PullMode,
             origin,
+            filter,
             handler,
         }
     }
@@ -505,6 +570,7 @@ impl<'a, 'b, Mode, Handler> SubscriberBuilder<'a, 'b, Mode, Handler> {
             reliability,
             mode: _,
             origin,
+            filter,
             handler,
         } = self;
         SubscriberBuilder {
@@ -513,6 +579,7 @@ impl<'a, 'b, Mode, Handler> SubscriberBuilder<'a, 'b, Mode, Handler> {
             reliability,
             mode: PushMode,
             origin,
+            filter,
             handler,
         }
     }
@@ -546,6 +613,7 @@ where
                     reliability: self.reliability,
                     mode: self.mode.into(),
                 },
+                self.filter,
             )
             .map(|sub_state| Subscriber {
                 subscriber: SubscriberInner {
@@ -598,6 +666,7 @@ where
                     reliability: self.reliability,
                     mode: self.mode.into(),
                 },
+                self.filter,
             )
             .map(|sub_state| PullSubscriber {
                 subscriber: PullSubscriberInner {
@@ -624,6 +693,179 @@ where
     }
 }
 
+impl<'a, 'b> SubscriberBuilder<'a, 'b, PushMode, DefaultHandler> {
+    /// Receive the samples of this subscription grouped by batch, see
+    /// [`put_batch`](crate::Session::put_batch).
+    ///
+    /// The samples of a same batch are delivered together, in their publication order. The other
+    /// samples are delivered alone. If `batched` is `false`, every sample is delivered alone.
+    ///
+    /// The options of the subscription must be set before calling this function.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # async_std::task::block_on(async {
+    /// use zenoh::prelude::r#async::*;
+    ///
+    /// let session = zenoh::open(config::peer()).res().await.unwrap();
+    /// let subscriber = session
+    ///     .declare_subscriber("pose/*")
+    ///     .batched(true)
+    ///     .res()
+    ///     .await
+    ///     .unwrap();
+    /// while let Ok(samples) = subscriber.recv_async().await {
+    ///     println!("Received {} samples", samples.len());
+    /// }
+    /// # })
+    /// ```
+    #[inline]
+    pub fn batched(self, batched: bool) -> BatchedSubscriberBuilder<'a, 'b, DefaultHandler> {
+        BatchedSubscriberBuilder {
+            builder: self,
+            batched,
+            handler: DefaultHandler,
+        }
+    }
+}
+
+/// A builder for initializing a [`Subscriber`] receiving the samples grouped by batch.
+///
+/// Created with [`SubscriberBuilder::batched`].
+#[must_use = "Resolvables do nothing unless you resolve them using the `res` method from either `SyncResolve` or `AsyncResolve`"]
+pub struct BatchedSubscriberBuilder<'a, 'b, Handler> {
+    pub(crate) builder: SubscriberBuilder<'a, 'b, PushMode, DefaultHandler>,
+    pub(crate) batched: bool,
+    pub(crate) handler: Handler,
+}
+
+impl<'a, 'b> BatchedSubscriberBuilder<'a, 'b, DefaultHandler> {
+    /// Receive the batches of samples for this subscription with a callback.
+    #[inline]
+    pub fn callback<Callback>(
+        self,
+        callback: Callback,
+    ) -> BatchedSubscriberBuilder<'a, 'b, Callback>
+    where
+        Callback: Fn(Vec<Sample>) + Send + Sync + 'static,
+    {
+        self.with(callback)
+    }
+
+    /// Receive the batches of samples for this subscription with a mutable callback.
+    #[inline]
+    pub fn callback_mut<CallbackMut>(
+        self,
+        callback: CallbackMut,
+    ) -> BatchedSubscriberBuilder<'a, 'b, impl Fn(Vec<Sample>) + Send + Sync + 'static>
+    where
+        CallbackMut: FnMut(Vec<Sample>) + Send + Sync + 'static,
+    {
+        self.callback(locked(callback))
+    }
+
+    /// Receive the batches of samples for this subscription with a [`Handler`](crate::prelude::IntoCallbackReceiverPair).
+    #[inline]
+    pub fn with<Handler>(self, handler: Handler) -> BatchedSubscriberBuilder<'a, 'b, Handler>
+    where
+        Handler: IntoCallbackReceiverPair<'static, Vec<Sample>>,
+    {
+        BatchedSubscriberBuilder {
+            builder: self.builder,
+            batched: self.batched,
+            handler,
+        }
+    }
+}
+
+impl<'a, Handler> Resolvable for BatchedSubscriberBuilder<'a, '_, Handler>
+where
+    Handler: IntoCallbackReceiverPair<'static, Vec<Sample>> + Send,
+    Handler::Receiver: Send,
+{
+    type To = ZResult<Subscriber<'a, Handler::Receiver>>;
+}
+
+impl<'a, Handler> SyncResolve for BatchedSubscriberBuilder<'a, '_, Handler>
+where
+    Handler: IntoCallbackReceiverPair<'static, Vec<Sample>> + Send,
+    Handler::Receiver: Send,
+{
+    fn res_sync(self) -> <Self as Resolvable>::To {
+        let (callback, receiver) = self.handler.into_cb_receiver_pair();
+        let collector = BatchCollector {
+            callback,
+            batched: self.batched,
+            pending: Mutex::new(HashMap::new()),
+        };
+        let SubscriberBuilder {
+            session,
+            key_expr,
+            reliability,
+            mode,
+            origin,
+            filter,
+            handler: _,
+        } = self.builder;
+        let subscriber = SubscriberBuilder {
+            session,
+            key_expr,
+            reliability,
+            mode,
+            origin,
+            filter,
+            handler: move |sample| collector.push(sample),
+        }
+        .res_sync()?;
+        Ok(Subscriber {
+            subscriber: subscriber.subscriber,
+            receiver,
+        })
+    }
+}
+
+impl<'a, Handler> AsyncResolve for BatchedSubscriberBuilder<'a, '_, Handler>
+where
+    Handler: IntoCallbackReceiverPair<'static, Vec<Sample>> + Send,
+    Handler::Receiver: Send,
+{
+    type Future = Ready<Self::To>;
+
+    fn res_async(self) -> Self::Future {
+        std::future::ready(self.res_sync())
+    }
+}
+
+// Groups the samples received by a batched subscriber. The session delivers the samples of a
+// batch one after the other, the batch being complete once its count is reached.
+struct BatchCollector {
+    callback: Callback<'static, Vec<Sample>>,
+    batched: bool,
+    pending: Mutex<HashMap<u64, Vec<Sample>>>,
+}
+
+impl BatchCollector {
+    fn push(&self, sample: Sample) {
+        let batch = match sample.batch {
+            Some(batch) if self.batched => batch,
+            _ => return (self.callback)(vec![sample]),
+        };
+        let mut pending = zlock!(self.pending);
+        let samples = pending.entry(batch.id).or_default();
+        samples.push(sample);
+        if samples.len() >= batch.count as usize {
+            let samples = pending.remove(&batch.id).unwrap_or_default();
+            drop(pending);
+            self.deliver(samples);
+        }
+    }
+
+    fn deliver(&self, mut samples: Vec<Sample>) {
+        samples.sort_by_key(|s| s.batch.map(|b| b.index));
+        (self.callback)(samples);
+    }
+}
+
 /// A subscriber that provides data through a [`Handler`](crate::prelude::IntoCallbackReceiverPair).
 ///
 /// Subscribers can be created from a zenoh [`Session`](crate::Session)
@@ -705,6 +947,12 @@ impl<'a, Receiver> DerefMut for PullSubscriber<'a, Receiver> {
 }
 
 impl<'a, Receiver> PullSubscriber<'a, Receiver> {
+    /// Returns the [`EntityGlobalId`] of this PullSubscriber.
+    #[zenoh_macros::unstable]
+    pub fn id(&self) -> EntityGlobalId {
+        self.subscriber.inner.id()
+    }
+
     /// Pull available data for a [`PullSubscriber`].
     ///
     /// # Examples
@@ -755,6 +1003,15 @@ impl<'a, Receiver> PullSubscriber<'a, Receiver> {
 }
 
 impl<'a, Receiver> Subscriber<'a, Receiver> {
+    /// Returns the [`EntityGlobalId`] of this Subscriber.
+    ///
+    /// It is carried in the declaration of the Subscriber and is reported for it
+    /// in the admin space of the routers.
+    #[zenoh_macros::unstable]
+    pub fn id(&self) -> EntityGlobalId {
+        self.subscriber.id()
+    }
+
     /// Returns the [`KeyExpr`] this Subscriber subscribes to.
     pub fn key_expr(&self) -> &KeyExpr<'static> {
         &self.subscriber.state.key_expr
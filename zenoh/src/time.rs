@@ -0,0 +1,187 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! Time related types and functions.
+use std::convert::TryFrom;
+use std::str::FromStr;
+use std::time::SystemTime;
+use zenoh_result::ZResult;
+use zenoh_util::time_range::TimeRange;
+
+pub use uhlc::HLC;
+pub use zenoh_protocol::core::{Timestamp, TimestampId, NTP64};
+pub use zenoh_util::clock::{Clock, SystemClock, TestClock};
+
+/// The separator introducing the sub-nanosecond part of a [`Timestamp`] in its lossless string form.
+const SUBNANOS_SEPARATOR: char = '#';
+
+/// Generates a reception [`Timestamp`] with id=0x01.
+/// This operation should be called if a timestamp is required for an incoming [`zenoh::Sample`](crate::Sample)
+/// that doesn't contain any timestamp.
+pub fn new_reception_timestamp() -> Timestamp {
+    use std::time::UNIX_EPOCH;
+
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+    Timestamp::new(now.into(), TimestampId::try_from([1]).unwrap())
+}
+
+/// Helpers on [`Timestamp`].
+///
+/// [`Timestamp`]s are ordered as the [`HLC`] orders them: first by their time (whose lowest bits hold the
+/// HLC counter), then by their [`TimestampId`]. Two timestamps generated at the same wall-clock time are thus
+/// ordered by their counter, and by their id when counters are equal too.
+///
+/// The default `Display` representation of a [`Timestamp`] (`<RFC3339 time>/<id>`) is truncated to the
+/// nanosecond and drops part of the HLC counter. [`TimestampExt::to_lossless_string`] appends the truncated
+/// part (`<RFC3339 time>#<sub-nanos>/<id>`) so that [`TimestampExt::from_lossless_str`] gives back the exact
+/// same [`Timestamp`]. The latter also accepts the default representation.
+pub trait TimestampExt: Sized {
+    /// Generates a new [`Timestamp`] from the given [`HLC`].
+    fn now_with(hlc: &HLC) -> Self;
+
+    /// Returns the wall-clock time of this [`Timestamp`].
+    fn to_system_time(&self) -> SystemTime;
+
+    /// Returns `true` if this [`Timestamp`] belongs to the given `_time` range.
+    fn is_in(&self, range: &TimeRange<SystemTime>) -> bool;
+
+    /// Formats this [`Timestamp`] without losing the HLC counter.
+    fn to_lossless_string(&self) -> String;
+
+    /// Parses a [`Timestamp`] formatted by [`TimestampExt::to_lossless_string`] or by `Display`.
+    fn from_lossless_str(s: &str) -> ZResult<Self>;
+}
+
+impl TimestampExt for Timestamp {
+    fn now_with(hlc: &HLC) -> Self {
+        hlc.new_timestamp()
+    }
+
+    fn to_system_time(&self) -> SystemTime {
+        self.get_time().to_system_time()
+    }
+
+    fn is_in(&self, range: &TimeRange<SystemTime>) -> bool {
+        range.contains(self.to_system_time())
+    }
+
+    fn to_lossless_string(&self) -> String {
+        let s = self.to_string();
+        let subnanos = match Timestamp::from_str(&s) {
+            Ok(truncated) => self
+                .get_time()
+                .as_u64()
+                .wrapping_sub(truncated.get_time().as_u64()),
+            Err(_) => 0,
+        };
+        match (subnanos, s.find('/')) {
+            (0, _) | (_, None) => s,
+            (subnanos, Some(i)) => format!("{}{SUBNANOS_SEPARATOR}{subnanos}{}", &s[..i], &s[i..]),
+        }
+    }
+
+    fn from_lossless_str(s: &str) -> ZResult<Self> {
+        let (time, id) = match s.find('/') {
+            Some(i) => s.split_at(i),
+            None => bail!("Invalid timestamp '{}': expected '<time>/<id>'", s),
+        };
+        let (time, subnanos) = match time.split_once(SUBNANOS_SEPARATOR) {
+            Some((time, subnanos)) => (
+                time,
+                subnanos
+                    .parse::<u64>()
+                    .map_err(|e| zerror!("Invalid timestamp '{}': {}", s, e))?,
+            ),
+            None => (time, 0),
+        };
+        let truncated = Timestamp::from_str(&format!("{time}{id}"))
+            .map_err(|e| zerror!("Invalid timestamp '{}': {:?}", s, e))?;
+        Ok(Timestamp::new(
+            NTP64(truncated.get_time().as_u64().wrapping_add(subnanos)),
+            *truncated.get_id(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn id(b: u8) -> TimestampId {
+        TimestampId::try_from([b]).unwrap()
+    }
+
+    #[test]
+    fn timestamp_ordering() {
+        let base = Timestamp::from_str("2022-12-21T15:00:00.000000000Z/1")
+            .unwrap()
+            .get_time()
+            .as_u64();
+
+        // Same wall-clock time, different counters
+        let t0 = Timestamp::new(NTP64(base), id(2));
+        let t1 = Timestamp::new(NTP64(base + 1), id(1));
+        assert_eq!(t0.to_system_time(), t1.to_system_time());
+        assert!(t0 < t1);
+
+        // Same wall-clock time and counter, different ids
+        let t2 = Timestamp::new(NTP64(base + 1), id(2));
+        assert!(t1 < t2);
+        assert!(t0 < t2);
+
+        let hlc = HLC::default();
+        let a = Timestamp::now_with(&hlc);
+        let b = Timestamp::now_with(&hlc);
+        assert!(a < b);
+        assert_eq!(a.get_id(), b.get_id());
+    }
+
+    #[test]
+    fn timestamp_lossless_string() {
+        let legacy = "2022-12-21T15:00:00.000000000Z/1";
+        let base = Timestamp::from_str(legacy).unwrap();
+        assert_eq!(Timestamp::from_lossless_str(legacy).unwrap(), base);
+        assert_eq!(base.to_lossless_string(), base.to_string());
+
+        for counter in 1..16 {
+            let ts = Timestamp::new(NTP64(base.get_time().as_u64() + counter), id(1));
+            let s = ts.to_lossless_string();
+            assert_eq!(Timestamp::from_lossless_str(&s).unwrap(), ts);
+        }
+
+        let hlc = HLC::default();
+        let ts = Timestamp::now_with(&hlc);
+        assert_eq!(
+            Timestamp::from_lossless_str(&ts.to_lossless_string()).unwrap(),
+            ts
+        );
+
+        assert!(Timestamp::from_lossless_str("2022-12-21T15:00:00.000000000Z").is_err());
+        assert!(Timestamp::from_lossless_str("2022-12-21T15:00:00.000000000Z#x/1").is_err());
+        assert!(Timestamp::from_lossless_str("not a time/1").is_err());
+    }
+
+    #[test]
+    fn timestamp_time_range() {
+        let ts = Timestamp::from_str("2022-12-21T15:00:00.000000000Z/1").unwrap();
+        let range: TimeRange = "[2022-12-21T14:00:00Z..2022-12-21T15:00:00Z]"
+            .parse()
+            .unwrap();
+        assert!(ts.is_in(&range.resolve()));
+        let range: TimeRange = "[2022-12-21T14:00:00Z..2022-12-21T15:00:00Z["
+            .parse()
+            .unwrap();
+        assert!(!ts.is_in(&range.resolve()));
+    }
+}
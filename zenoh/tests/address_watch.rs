@@ -0,0 +1,94 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use async_std::prelude::FutureExt;
+use async_std::task;
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use zenoh::prelude::r#async::*;
+use zenoh::runtime::{AddressProvider, Runtime};
+use zenoh_core::zasync_executor_init;
+use zenoh_result::ZResult;
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+
+macro_rules! ztimeout {
+    ($f:expr) => {
+        $f.timeout(TIMEOUT).await.unwrap()
+    };
+}
+
+// An address provider whose addresses are set by the test
+struct MockAddresses(Mutex<Vec<IpAddr>>);
+
+impl AddressProvider for MockAddresses {
+    fn addresses(&self) -> ZResult<Vec<IpAddr>> {
+        Ok(self.0.lock().unwrap().clone())
+    }
+}
+
+// Polls `condition` until it holds.
+async fn wait_until(condition: impl Fn() -> bool) {
+    while !condition() {
+        task::sleep(Duration::from_millis(10)).await;
+    }
+}
+
+#[test]
+fn address_watch_rebind() {
+    task::block_on(async {
+        zasync_executor_init!();
+        let _ = env_logger::try_init();
+
+        let specific: EndPoint = "tcp/127.0.0.1:17534".parse().unwrap();
+        let wildcard: EndPoint = "tcp/0.0.0.0:17535".parse().unwrap();
+        let mut config = config::peer();
+        config.listen.endpoints = vec![specific.clone(), wildcard.clone()];
+        config.scouting.multicast.set_enabled(Some(false)).unwrap();
+        config.listen.address_watch.set_enabled(Some(true)).unwrap();
+        config.listen.address_watch.set_period(Some(100)).unwrap();
+        println!("[  ][01a] Opening peer runtime: {specific} {wildcard}");
+        let runtime = ztimeout!(Runtime::new(config)).unwrap();
+        let addresses = Arc::new(MockAddresses(Mutex::new(vec![[127, 0, 0, 1].into()])));
+        runtime.set_address_provider(addresses.clone());
+        let specific_locator = specific.to_locator();
+        let advertised = {
+            let runtime = runtime.clone();
+            move || runtime.get_locators()
+        };
+        assert!(advertised().contains(&specific_locator));
+
+        // The listener is removed with its locator when its address disappears
+        println!("[  ][02a] Removing the address of the listener");
+        addresses.0.lock().unwrap().clear();
+        ztimeout!(wait_until(|| !advertised().contains(&specific_locator)));
+        let listeners = runtime.manager().get_listeners();
+        assert!(!listeners.contains(&specific));
+        // The wildcard listener stays
+        assert!(listeners.contains(&wildcard));
+
+        // The listener is bound again once its address reappears
+        println!("[  ][02b] Restoring the address of the listener");
+        addresses.0.lock().unwrap().push([127, 0, 0, 1].into());
+        ztimeout!(wait_until(|| advertised().contains(&specific_locator)));
+        assert!(runtime.manager().get_listeners().contains(&specific));
+
+        println!("[  ][02c] Connecting to the listener bound again");
+        let session =
+            ztimeout!(zenoh::open(config::client([specific.clone()])).res_async()).unwrap();
+        ztimeout!(session.close().res_async()).unwrap();
+
+        ztimeout!(runtime.close()).unwrap();
+    });
+}
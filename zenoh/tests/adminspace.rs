@@ -0,0 +1,313 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use async_std::io::{ReadExt, WriteExt};
+use async_std::net::{TcpListener, TcpStream};
+use async_std::prelude::FutureExt;
+use async_std::task;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use zenoh::plugins::PluginsManager;
+use zenoh::prelude::r#async::*;
+use zenoh::prelude::sync::SyncResolve;
+use zenoh::runtime::{AdminSpace, Runtime};
+use zenoh_core::zasync_executor_init;
+use zenoh_protocol::core::WhatAmI;
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_secs(1);
+const QUERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+macro_rules! ztimeout {
+    ($f:expr) => {
+        $f.timeout(TIMEOUT).await.unwrap()
+    };
+}
+
+// Forward the bytes read from `from` to `to` until frozen, simulating a node crashing hard.
+async fn forward(mut from: TcpStream, mut to: TcpStream, frozen: Arc<AtomicBool>) {
+    let mut buf = vec![0u8; 65_535];
+    while let Ok(n) = from.read(&mut buf).await {
+        if n == 0 {
+            break;
+        }
+        if !frozen.load(Ordering::SeqCst) && to.write_all(&buf[..n]).await.is_err() {
+            break;
+        }
+    }
+}
+
+// A TCP proxy forwarding a single connection from `listen` to `server`.
+async fn proxy(listen: &str, server: &str) -> Arc<AtomicBool> {
+    let frozen = Arc::new(AtomicBool::new(false));
+    let listener = TcpListener::bind(listen).await.unwrap();
+    let server = server.to_string();
+    let c_frozen = frozen.clone();
+    task::spawn(async move {
+        let (client, _) = listener.accept().await.unwrap();
+        let server = TcpStream::connect(server).await.unwrap();
+        task::spawn(forward(client.clone(), server.clone(), c_frozen.clone()));
+        forward(server, client, c_frozen).await;
+    });
+    frozen
+}
+
+async fn open_client(endpoint: &str) -> Session {
+    let mut config = config::client([endpoint.parse::<EndPoint>().unwrap()]);
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    println!("[  ][01a] Opening client session: {endpoint}");
+    ztimeout!(zenoh::open(config).res_async()).unwrap()
+}
+
+#[test]
+fn adminspace_expire_transport() {
+    task::block_on(async {
+        zasync_executor_init!();
+
+        // Start a router with a writable admin space
+        let mut config = config::default();
+        config.set_mode(Some(WhatAmI::Router)).unwrap();
+        config.listen.endpoints = vec!["tcp/127.0.0.1:17470".parse().unwrap()];
+        config.scouting.multicast.set_enabled(Some(false)).unwrap();
+        config.adminspace.permissions.write = true;
+        let router = ztimeout!(Runtime::new(config)).unwrap();
+        AdminSpace::start(
+            &router,
+            PluginsManager::static_plugins_only(),
+            String::from("test"),
+        )
+        .await;
+
+        // The queryable client is connected through a proxy that will stop forwarding
+        let frozen = ztimeout!(proxy("127.0.0.1:17471", "127.0.0.1:17470"));
+        let dead = open_client("tcp/127.0.0.1:17471").await;
+        let dead_zid = dead.zid();
+        let key_expr = "test/adminspace/expire";
+        let queryable = ztimeout!(dead
+            .declare_queryable(key_expr)
+            .callback(|query| {
+                let sample = Sample::new(query.key_expr().clone(), "reply");
+                query.reply(Ok(sample)).res_sync().unwrap();
+            })
+            .res_async())
+        .unwrap();
+
+        let client = open_client("tcp/127.0.0.1:17470").await;
+        task::sleep(SLEEP).await;
+
+        let replies = ztimeout!(client.get(key_expr).res_async()).unwrap();
+        assert!(ztimeout!(replies.recv_async()).unwrap().sample.is_ok());
+
+        // The crashed client keeps its declarations in the router until its lease expires
+        println!("[  ][02a] Freezing the queryable client");
+        frozen.store(true, Ordering::SeqCst);
+
+        println!("[  ][03a] Expiring the transport of {dead_zid}");
+        let expire = format!(
+            "@/router/{}/transport/unicast/{}/expire",
+            router.zid, dead_zid
+        );
+        ztimeout!(client.put(expire, "").res_async()).unwrap();
+        ztimeout!(async {
+            while router
+                .manager()
+                .get_transport_unicast(&dead_zid)
+                .await
+                .is_some()
+            {
+                task::sleep(Duration::from_millis(10)).await;
+            }
+        });
+
+        // The following get no longer waits for the dead queryable
+        let start = Instant::now();
+        let replies = ztimeout!(client.get(key_expr).timeout(QUERY_TIMEOUT).res_async()).unwrap();
+        while ztimeout!(replies.recv_async()).is_ok() {}
+        println!("[  ][03b] Get completed in {:?}", start.elapsed());
+        assert!(start.elapsed() < QUERY_TIMEOUT / 2);
+
+        drop(queryable);
+        ztimeout!(client.close().res_async()).unwrap();
+        drop(dead);
+        ztimeout!(router.close()).unwrap();
+    });
+}
+
+#[cfg(feature = "unstable")]
+#[test]
+fn adminspace_entity_ids() {
+    task::block_on(async {
+        zasync_executor_init!();
+
+        let mut config = config::default();
+        config.set_mode(Some(WhatAmI::Router)).unwrap();
+        config.listen.endpoints = vec!["tcp/127.0.0.1:17513".parse().unwrap()];
+        config.scouting.multicast.set_enabled(Some(false)).unwrap();
+        let router = ztimeout!(Runtime::new(config)).unwrap();
+        AdminSpace::start(
+            &router,
+            PluginsManager::static_plugins_only(),
+            String::from("test"),
+        )
+        .await;
+
+        let declarer = open_client("tcp/127.0.0.1:17513").await;
+        let client = open_client("tcp/127.0.0.1:17513").await;
+        let key_expr = "test/adminspace/ids";
+        let subscriber = ztimeout!(declarer
+            .declare_subscriber(key_expr)
+            .callback(|_| {})
+            .res_async())
+        .unwrap();
+        let queryable = ztimeout!(declarer
+            .declare_queryable(key_expr)
+            .callback(|_| {})
+            .res_async())
+        .unwrap();
+        assert_eq!(subscriber.id().zid, declarer.zid());
+        assert_ne!(subscriber.id(), queryable.id());
+        task::sleep(SLEEP).await;
+
+        // The router reports the ids carried by the declarations of the client
+        for (kind, id) in [
+            ("subscriber", subscriber.id()),
+            ("queryable", queryable.id()),
+        ] {
+            println!("[  ][02a] Querying the {kind} ids of the router");
+            let selector = format!("@/router/{}/{kind}/{key_expr}", router.zid);
+            let replies = ztimeout!(client.get(selector).res_async()).unwrap();
+            let sample = ztimeout!(replies.recv_async()).unwrap().sample.unwrap();
+            let ids = serde_json::Value::try_from(&sample.value).unwrap();
+            let expected = serde_json::json!([{ "zid": id.zid.to_string(), "eid": id.eid }]);
+            assert_eq!(ids, expected);
+        }
+
+        // The samples of a publisher carry its id when asked to
+        println!("[  ][03a] Publishing with a declared publisher");
+        let receiver = ztimeout!(client.declare_subscriber(key_expr).res_async()).unwrap();
+        let publisher = ztimeout!(declarer
+            .declare_publisher(key_expr)
+            .source_info(true)
+            .res_async())
+        .unwrap();
+        task::sleep(SLEEP).await;
+        ztimeout!(publisher.put("value").res_async()).unwrap();
+        let sample = ztimeout!(receiver.recv_async()).unwrap();
+        assert_eq!(sample.source_info.source_global_id(), Some(publisher.id()));
+
+        // Otherwise they don't carry any source info
+        println!("[  ][03b] Publishing without source info");
+        let anonymous = ztimeout!(declarer.declare_publisher(key_expr).res_async()).unwrap();
+        ztimeout!(anonymous.put("value").res_async()).unwrap();
+        let sample = ztimeout!(receiver.recv_async()).unwrap();
+        assert_eq!(sample.source_info.source_global_id(), None);
+
+        drop(anonymous);
+        drop(publisher);
+        ztimeout!(receiver.undeclare().res_async()).unwrap();
+        ztimeout!(queryable.undeclare().res_async()).unwrap();
+        ztimeout!(subscriber.undeclare().res_async()).unwrap();
+        ztimeout!(client.close().res_async()).unwrap();
+        ztimeout!(declarer.close().res_async()).unwrap();
+        ztimeout!(router.close()).unwrap();
+    });
+}
+
+#[test]
+fn adminspace_transports() {
+    task::block_on(async {
+        zasync_executor_init!();
+
+        let mut config = config::default();
+        config.set_mode(Some(WhatAmI::Router)).unwrap();
+        config.listen.endpoints = vec!["tcp/127.0.0.1:17523".parse().unwrap()];
+        config.scouting.multicast.set_enabled(Some(false)).unwrap();
+        let router = ztimeout!(Runtime::new(config)).unwrap();
+        AdminSpace::start(
+            &router,
+            PluginsManager::static_plugins_only(),
+            String::from("test"),
+        )
+        .await;
+
+        let other = open_client("tcp/127.0.0.1:17523").await;
+        let client = open_client("tcp/127.0.0.1:17523").await;
+        task::sleep(SLEEP).await;
+
+        // Collects the replies of the transport listing, unconsolidated for them to keep the order
+        // of the router, sorted by zid
+        let transports = |parameters: &str| {
+            let selector = format!("@/router/{}/transport/unicast/**?{parameters}", router.zid);
+            let client = &client;
+            async move {
+                let replies = ztimeout!(client
+                    .get(selector)
+                    .consolidation(ConsolidationMode::None)
+                    .res_async())
+                .unwrap();
+                let mut values = vec![];
+                while let Ok(reply) = ztimeout!(replies.recv_async()) {
+                    values.push(
+                        reply
+                            .sample
+                            .map(|sample| serde_json::Value::try_from(&sample.value).unwrap()),
+                    );
+                }
+                values
+            }
+        };
+
+        println!("[  ][02a] Listing the transports of the router");
+        // The transports are sorted by their ids, not by the strings of their ids
+        let mut expected = vec![client.zid(), other.zid()];
+        expected.sort();
+        let expected = expected
+            .iter()
+            .map(|zid| zid.to_string())
+            .collect::<Vec<_>>();
+        let listed = transports("")
+            .await
+            .into_iter()
+            .map(|value| {
+                let value = value.unwrap();
+                assert_eq!(value["whatami"], "client");
+                value["zid"].as_str().unwrap().to_string()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(listed, expected);
+
+        println!("[  ][02b] Filtering and paginating the transports");
+        assert!(transports("whatami=peer").await.is_empty());
+        let page = transports("whatami=client&limit=1&offset=1").await;
+        assert_eq!(page.len(), 1);
+        assert_eq!(page[0].as_ref().unwrap()["zid"], expected[1].as_str());
+
+        println!("[  ][02c] Projecting the fields of the transports");
+        for value in transports("fields=zid,whatami").await {
+            let value = value.unwrap();
+            let mut fields = value.as_object().unwrap().keys().collect::<Vec<_>>();
+            fields.sort();
+            assert_eq!(fields, ["whatami", "zid"]);
+        }
+
+        println!("[  ][02d] Querying with an unknown parameter");
+        let replies = transports("unknown=1").await;
+        assert_eq!(replies.len(), 1);
+        assert!(replies[0].is_err());
+
+        ztimeout!(client.close().res_async()).unwrap();
+        ztimeout!(other.close().res_async()).unwrap();
+        ztimeout!(router.close()).unwrap();
+    });
+}
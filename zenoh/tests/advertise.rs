@@ -0,0 +1,73 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use async_std::prelude::FutureExt;
+use async_std::task;
+use std::sync::Arc;
+use std::time::Duration;
+use zenoh::prelude::r#async::*;
+use zenoh::runtime::{LocatorsRewriter, Runtime};
+use zenoh_core::zasync_executor_init;
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+
+macro_rules! ztimeout {
+    ($f:expr) => {
+        $f.timeout(TIMEOUT).await.unwrap()
+    };
+}
+
+// A rewriter advertising an address discovered at runtime, e.g. with STUN
+struct Discovered(Locator);
+
+impl LocatorsRewriter for Discovered {
+    fn rewrite(&self, mut locators: Vec<Locator>) -> Vec<Locator> {
+        locators.push(self.0.clone());
+        locators
+    }
+}
+
+#[test]
+fn advertise_rewrite() {
+    task::block_on(async {
+        zasync_executor_init!();
+        let _ = env_logger::try_init();
+
+        let listener: Locator = "tcp/127.0.0.1:17516".parse().unwrap();
+        let public: Locator = "tcp/public.example.com:7447".parse().unwrap();
+        let mut config = config::peer();
+        config.listen.endpoints = vec![listener.clone().into()];
+        config.scouting.multicast.set_enabled(Some(false)).unwrap();
+        config
+            .insert_json5(
+                "advertise/rewrite",
+                r#"[{ match: "tcp/127.0.0.1:*", replace: "tcp/public.example.com:7447" }]"#,
+            )
+            .unwrap();
+        println!("[  ][01a] Opening peer runtime: {listener}");
+        let runtime = ztimeout!(Runtime::new(config)).unwrap();
+
+        // The Hello advertises the rewritten locator, the runtime still listens on its endpoint
+        println!("[  ][02a] Checking the advertised locators");
+        assert_eq!(runtime.hello().locators, vec![public.clone()]);
+        assert_eq!(runtime.manager().get_locators(), vec![listener.clone()]);
+
+        println!("[  ][02b] Adding a locators rewriter");
+        let discovered: Locator = "udp/203.0.113.1:7447".parse().unwrap();
+        runtime.add_locators_rewriter(Arc::new(Discovered(discovered.clone())));
+        assert_eq!(runtime.hello().locators, vec![public, discovered]);
+        assert_eq!(runtime.manager().get_locators(), vec![listener]);
+
+        ztimeout!(runtime.close()).unwrap();
+    });
+}
@@ -0,0 +1,235 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+// The allocation budgets of the hot paths: the allocations are counted by the global allocator
+// of this binary, for all its threads, and divided by the number of messages.
+//
+// The logger isn't initialized: the formatting of the logs would be counted.
+use async_std::task;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use zenoh::prelude::sync::*;
+use zenoh_core::zasync_executor_init;
+use zenoh_util::alloc_counter::{measure, Allocations, CountingAllocator};
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_secs(1);
+const WARMUP: usize = 100;
+const MSG_COUNT: usize = 1_000;
+const MSG_SIZE: usize = 64;
+
+// The local delivery of a put of a declared publisher to a callback subscriber of the same
+// session. The allocations left are the ones of the routing of the put to the remote faces, of
+// which there is none, and of the sample delivered to the subscriber.
+const LOCAL_PUBSUB_BUDGET: usize = 8;
+// The put of a declared publisher over a link to a callback subscriber of another session.
+// There is no in-memory link: the sessions are connected over tcp on the loopback, and the
+// budget includes the wakeups of the tx and rx tasks of the transports.
+const LINK_PUT_BUDGET: usize = 24;
+// A get and its reply over a link, the queryable replying from its callback and the reply being
+// delivered to a callback. The query state, the query and the reply are allocated on each round
+// trip, as well as the timer of the timeout of the query.
+const QUERY_REPLY_BUDGET: usize = 96;
+
+// The tests are serialized: the allocations of all the threads are counted
+static SERIAL: Mutex<()> = Mutex::new(());
+
+fn open_session(listen: &[&str], connect: &[&str]) -> Session {
+    let mut config = config::peer();
+    config.listen.endpoints = listen.iter().map(|e| e.parse().unwrap()).collect();
+    config.connect.endpoints = connect.iter().map(|e| e.parse().unwrap()).collect();
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    zenoh::open(config).res().unwrap()
+}
+
+// Waits for `count` messages without allocating, the waiting being measured
+fn wait_for(received: &AtomicUsize, count: usize) {
+    let start = Instant::now();
+    while received.load(Ordering::Acquire) < count {
+        assert!(
+            start.elapsed() < TIMEOUT,
+            "Timeout waiting for {count} messages"
+        );
+        std::thread::sleep(Duration::from_millis(1));
+    }
+}
+
+fn check_budget(path: &str, allocations: Allocations, budget: usize) {
+    let per_message = allocations.per_message(MSG_COUNT);
+    println!(
+        "[AL][{path}] {} allocations ({} bytes) for {MSG_COUNT} messages: {} allocations ({} bytes) per message, budget {budget}",
+        allocations.count, allocations.bytes, per_message.count, per_message.bytes
+    );
+    assert!(
+        per_message.count <= budget,
+        "{path}: {} allocations per message over the budget of {budget}",
+        per_message.count
+    );
+}
+
+#[test]
+fn allocations_local_pubsub() {
+    let _serial = SERIAL.lock().unwrap_or_else(|e| e.into_inner());
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+    let key_expr = "test/allocations/local";
+
+    println!("[AL][01a] Opening the session");
+    let session = open_session(&[], &[]);
+    let received = Arc::new(AtomicUsize::new(0));
+    let c_received = received.clone();
+    let subscriber = session
+        .declare_subscriber(key_expr)
+        .callback(move |_| {
+            c_received.fetch_add(1, Ordering::Release);
+        })
+        .res()
+        .unwrap();
+    let publisher = session.declare_publisher(key_expr).res().unwrap();
+    let value = Value::from(vec![0u8; MSG_SIZE]);
+
+    println!("[AL][02a] Putting {WARMUP} messages to warm up");
+    for _ in 0..WARMUP {
+        publisher.put(value.clone()).res().unwrap();
+    }
+    wait_for(&received, WARMUP);
+
+    println!("[AL][03a] Putting {MSG_COUNT} messages");
+    let (_, allocations) = measure(|| {
+        for _ in 0..MSG_COUNT {
+            publisher.put(value.clone()).res().unwrap();
+        }
+        wait_for(&received, WARMUP + MSG_COUNT);
+    });
+    check_budget("local pub/sub", allocations, LOCAL_PUBSUB_BUDGET);
+
+    println!("[AL][04a] Closing the session");
+    publisher.undeclare().res().unwrap();
+    subscriber.undeclare().res().unwrap();
+    session.close().res().unwrap();
+}
+
+#[test]
+fn allocations_link_put() {
+    let _serial = SERIAL.lock().unwrap_or_else(|e| e.into_inner());
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+    let endpoint = "tcp/127.0.0.1:18449";
+    let key_expr = "test/allocations/link";
+
+    println!("[AL][01b] Opening the sessions");
+    let peer01 = open_session(&[endpoint], &[]);
+    let peer02 = open_session(&[], &[endpoint]);
+    let received = Arc::new(AtomicUsize::new(0));
+    let c_received = received.clone();
+    let subscriber = peer01
+        .declare_subscriber(key_expr)
+        .callback(move |_| {
+            c_received.fetch_add(1, Ordering::Release);
+        })
+        .res()
+        .unwrap();
+    let publisher = peer02
+        .declare_publisher(key_expr)
+        .congestion_control(CongestionControl::Block)
+        .res()
+        .unwrap();
+    let value = Value::from(vec![0u8; MSG_SIZE]);
+    // Wait for the declarations to propagate
+    std::thread::sleep(SLEEP);
+
+    println!("[AL][02b] Putting {WARMUP} messages to warm up");
+    for _ in 0..WARMUP {
+        publisher.put(value.clone()).res().unwrap();
+    }
+    wait_for(&received, WARMUP);
+
+    println!("[AL][03b] Putting {MSG_COUNT} messages");
+    let (_, allocations) = measure(|| {
+        for _ in 0..MSG_COUNT {
+            publisher.put(value.clone()).res().unwrap();
+        }
+        wait_for(&received, WARMUP + MSG_COUNT);
+    });
+    check_budget("link put", allocations, LINK_PUT_BUDGET);
+
+    println!("[AL][04b] Closing the sessions");
+    publisher.undeclare().res().unwrap();
+    subscriber.undeclare().res().unwrap();
+    peer02.close().res().unwrap();
+    peer01.close().res().unwrap();
+}
+
+#[test]
+fn allocations_query_reply() {
+    let _serial = SERIAL.lock().unwrap_or_else(|e| e.into_inner());
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+    let endpoint = "tcp/127.0.0.1:18450";
+    let key_expr = "test/allocations/query";
+
+    println!("[AL][01c] Opening the sessions");
+    let peer01 = open_session(&[endpoint], &[]);
+    let peer02 = open_session(&[], &[endpoint]);
+    let value = Value::from(vec![0u8; MSG_SIZE]);
+    let queryable = peer01
+        .declare_queryable(key_expr)
+        .callback(move |query| {
+            let sample = Sample::new(query.key_expr().clone(), value.clone());
+            query.reply(Ok(sample)).res().unwrap();
+        })
+        .res()
+        .unwrap();
+    // Wait for the declaration to propagate
+    std::thread::sleep(SLEEP);
+
+    let received = Arc::new(AtomicUsize::new(0));
+    let get = |count: usize| {
+        for _ in 0..count {
+            let c_received = received.clone();
+            peer02
+                .get(key_expr)
+                .consolidation(ConsolidationMode::None)
+                .callback(move |reply| {
+                    assert!(reply.sample.is_ok());
+                    c_received.fetch_add(1, Ordering::Release);
+                })
+                .res()
+                .unwrap();
+        }
+    };
+
+    println!("[AL][02c] Querying {WARMUP} times to warm up");
+    get(WARMUP);
+    wait_for(&received, WARMUP);
+
+    println!("[AL][03c] Querying {MSG_COUNT} times");
+    let (_, allocations) = measure(|| {
+        get(MSG_COUNT);
+        wait_for(&received, WARMUP + MSG_COUNT);
+    });
+    check_budget("query/reply", allocations, QUERY_REPLY_BUDGET);
+
+    println!("[AL][04c] Closing the sessions");
+    queryable.undeclare().res().unwrap();
+    peer02.close().res().unwrap();
+    peer01.close().res().unwrap();
+}
@@ -0,0 +1,217 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+#![cfg(feature = "auth_usrpwd")]
+use async_std::prelude::FutureExt;
+use async_std::task;
+use std::path::Path;
+use std::time::Duration;
+use zenoh::config::NamespaceConf;
+use zenoh::prelude::r#async::*;
+use zenoh::runtime::Runtime;
+use zenoh_core::zasync_executor_init;
+use zenoh_protocol::core::WhatAmI;
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_millis(500);
+
+macro_rules! ztimeout {
+    ($f:expr) => {
+        $f.timeout(TIMEOUT).await.unwrap()
+    };
+}
+
+async fn open_client(endpoint: &str, user: &str) -> Session {
+    let mut config = config::client([endpoint.parse::<EndPoint>().unwrap()]);
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    config
+        .transport
+        .auth
+        .usrpwd
+        .set_user(Some(user.to_string()))
+        .unwrap();
+    config
+        .transport
+        .auth
+        .usrpwd
+        .set_password(Some(format!("{user}_pwd")))
+        .unwrap();
+    println!("[AU][01b] Opening client session {user}: {endpoint}");
+    ztimeout!(zenoh::open(config).res_async()).unwrap()
+}
+
+// The events written to the audit log, leaving out the ones on the admin space
+fn audit_events(path: &Path) -> Vec<serde_json::Value> {
+    std::fs::read_to_string(path)
+        .unwrap_or_default()
+        .lines()
+        .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+        .filter(|event| !event["key_expr"].as_str().unwrap().starts_with('@'))
+        .collect()
+}
+
+#[test]
+fn audit_usrpwd() {
+    task::block_on(async {
+        zasync_executor_init!();
+        let _ = env_logger::try_init();
+
+        let endpoint = "tcp/127.0.0.1:17700";
+        let dir = std::env::temp_dir().join(format!("zenoh-test-audit-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let dictionary = dir.join("usrpwd.txt");
+        std::fs::write(
+            &dictionary,
+            "alice:alice_pwd\nbob:bob_pwd\ncarol:carol_pwd\n",
+        )
+        .unwrap();
+        let audit = dir.join("audit.jsonl");
+
+        let mut config = config::default();
+        config.set_mode(Some(WhatAmI::Router)).unwrap();
+        config.listen.endpoints = vec![endpoint.parse().unwrap()];
+        config.scouting.multicast.set_enabled(Some(false)).unwrap();
+        config
+            .transport
+            .auth
+            .usrpwd
+            .set_dictionary_file(Some(dictionary.to_string_lossy().to_string()))
+            .unwrap();
+        config
+            .set_namespaces(vec![NamespaceConf {
+                users: vec!["bob".to_string()],
+                namespace: "tenants/bob".try_into().unwrap(),
+            }])
+            .unwrap();
+        config
+            .insert_json5(
+                "routing/audit",
+                &format!(
+                    r#"{{ enabled: true, file: {{ path: {:?} }}, key_expr: "@/audit/test" }}"#,
+                    audit.to_string_lossy()
+                ),
+            )
+            .unwrap();
+        println!("[AU][01a] Opening router runtime auditing into {audit:?}");
+        let router = ztimeout!(Runtime::new(config)).unwrap();
+
+        let alice = open_client(endpoint, "alice").await;
+        let bob = open_client(endpoint, "bob").await;
+        let carol = open_client(endpoint, "carol").await;
+        let collector = ztimeout!(alice.declare_subscriber("@/audit/test").res_async()).unwrap();
+
+        // The clients only send the publications and queries the router declared a destination for
+        println!("[AU][02a] Declaring the subscribers and queryables");
+        let all = ztimeout!(carol.declare_subscriber("**").res_async()).unwrap();
+        let all_qabl =
+            ztimeout!(carol.declare_queryable("**").callback(|_| {}).res_async()).unwrap();
+        task::sleep(SLEEP).await;
+        let sub = ztimeout!(alice.declare_subscriber("demo/**").res_async()).unwrap();
+        let qabl = ztimeout!(alice
+            .declare_queryable("demo/q")
+            .callback(|_| {})
+            .res_async())
+        .unwrap();
+        task::sleep(SLEEP).await;
+        let denied = ztimeout!(bob.declare_subscriber("tenants/**").res_async()).unwrap();
+        task::sleep(SLEEP).await;
+
+        // Only the first publication per key and face is recorded
+        println!("[AU][02b] Publishing inside and outside of the namespace");
+        for _ in 0..3 {
+            ztimeout!(alice.put("demo/a", "a").res_async()).unwrap();
+        }
+        task::sleep(SLEEP).await;
+        ztimeout!(bob.put("tenants/alice/x", "escaped").res_async()).unwrap();
+        ztimeout!(bob.put("tenants/bob/x", "confined").res_async()).unwrap();
+        ztimeout!(bob.put("tenants/bob/x", "confined").res_async()).unwrap();
+        task::sleep(SLEEP).await;
+
+        println!("[AU][02c] Querying inside and outside of the namespace");
+        let replies = ztimeout!(bob.get("tenants/bob/q").res_async()).unwrap();
+        while ztimeout!(replies.recv_async()).is_ok() {}
+        let replies = ztimeout!(bob.get("demo/q").res_async()).unwrap();
+        while ztimeout!(replies.recv_async()).is_ok() {}
+
+        let expected = [
+            ("carol", "declare_subscriber", "**", "admitted"),
+            ("carol", "declare_queryable", "**", "admitted"),
+            ("alice", "declare_subscriber", "demo/**", "admitted"),
+            ("alice", "declare_queryable", "demo/q", "admitted"),
+            ("bob", "declare_subscriber", "tenants/**", "denied"),
+            ("alice", "publish", "demo/a", "admitted"),
+            ("bob", "publish", "tenants/alice/x", "denied"),
+            ("bob", "publish", "tenants/bob/x", "admitted"),
+            ("bob", "query", "tenants/bob/q", "admitted"),
+            ("bob", "query", "demo/q", "denied"),
+        ];
+        println!("[AU][03a] Reading the audit log");
+        let events = ztimeout!(async {
+            loop {
+                let events = audit_events(&audit);
+                if events.len() >= expected.len() {
+                    return events;
+                }
+                task::sleep(Duration::from_millis(100)).await;
+            }
+        });
+        let recorded = events
+            .iter()
+            .map(|event| {
+                (
+                    event["identity"]["user"].as_str().unwrap(),
+                    event["operation"].as_str().unwrap(),
+                    event["key_expr"].as_str().unwrap(),
+                    event["outcome"].as_str().unwrap(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(recorded, expected);
+
+        // Each event identifies the transport of the user
+        for event in events.iter() {
+            let zid = match event["identity"]["user"].as_str().unwrap() {
+                "alice" => alice.zid(),
+                "bob" => bob.zid(),
+                _ => carol.zid(),
+            };
+            assert_eq!(event["identity"]["zid"], zid.to_string());
+            assert_eq!(event["identity"]["whatami"], "client");
+            assert_eq!(event["suppressed"], 0);
+        }
+        assert_eq!(events[0]["face"], events[1]["face"]);
+        assert_ne!(events[1]["face"], events[2]["face"]);
+
+        // The events are also published onto the admin key
+        println!("[AU][03b] Collecting the published events");
+        ztimeout!(async {
+            loop {
+                let sample = collector.recv_async().await.unwrap();
+                let event: serde_json::Value =
+                    serde_json::from_str(&sample.value.to_string()).unwrap();
+                if event["key_expr"] == "tenants/bob/x" {
+                    assert_eq!(event["identity"]["user"], "bob");
+                    break;
+                }
+            }
+        });
+
+        drop((collector, all, all_qabl, sub, qabl, denied));
+        ztimeout!(alice.close().res_async()).unwrap();
+        ztimeout!(bob.close().res_async()).unwrap();
+        ztimeout!(carol.close().res_async()).unwrap();
+        ztimeout!(router.close()).unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+    });
+}
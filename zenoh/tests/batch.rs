@@ -0,0 +1,164 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use async_std::prelude::FutureExt;
+use async_std::task;
+use std::time::Duration;
+use zenoh::prelude::r#async::*;
+use zenoh_core::zasync_executor_init;
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_secs(1);
+
+macro_rules! ztimeout {
+    ($f:expr) => {
+        $f.timeout(TIMEOUT).await.unwrap()
+    };
+}
+
+async fn open_client(endpoint: &str) -> Session {
+    let mut config = config::client([endpoint.parse::<EndPoint>().unwrap()]);
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    ztimeout!(zenoh::open(config).res_async()).unwrap()
+}
+
+fn check_batch(samples: &[Sample]) {
+    let keys = samples
+        .iter()
+        .map(|s| s.key_expr.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(keys, ["pose/x", "pose/y", "pose/z"]);
+    assert_eq!(samples[2].kind, SampleKind::Delete);
+    // The samples of a batch share the same timestamp
+    let timestamp = samples[0].timestamp.unwrap();
+    assert!(samples.iter().all(|s| s.timestamp == Some(timestamp)));
+    let id = samples[0].batch.unwrap().id;
+    for (index, sample) in samples.iter().enumerate() {
+        let batch = sample.batch.unwrap();
+        assert_eq!((batch.id, batch.index, batch.count), (id, index as u32, 3));
+    }
+}
+
+#[test]
+fn batch_grouped_delivery() {
+    task::block_on(async {
+        zasync_executor_init!();
+        let _ = env_logger::try_init();
+
+        let endpoint = "tcp/127.0.0.1:17561";
+
+        println!("[BT][01a] Opening router session");
+        let mut config = config::peer();
+        config.set_mode(Some(WhatAmI::Router)).unwrap();
+        config.listen.endpoints = vec![endpoint.parse().unwrap()];
+        config.scouting.multicast.set_enabled(Some(false)).unwrap();
+        let router = ztimeout!(zenoh::open(config).res_async()).unwrap();
+
+        println!("[BT][01b] Opening client sessions");
+        let publisher = open_client(endpoint).await;
+        let subscriber = open_client(endpoint).await;
+        let batched = ztimeout!(subscriber
+            .declare_subscriber("pose/*")
+            .batched(true)
+            .res_async())
+        .unwrap();
+        let unbatched = ztimeout!(subscriber
+            .declare_subscriber("pose/*")
+            .batched(false)
+            .res_async())
+        .unwrap();
+        let plain = ztimeout!(subscriber.declare_subscriber("pose/*").res_async()).unwrap();
+        let partial = ztimeout!(subscriber
+            .declare_subscriber("pose/y")
+            .batched(true)
+            .res_async())
+        .unwrap();
+        let local = ztimeout!(publisher
+            .declare_subscriber("pose/*")
+            .batched(true)
+            .res_async())
+        .unwrap();
+        task::sleep(SLEEP).await;
+
+        println!("[BT][02a] Publishing a batch");
+        ztimeout!(publisher
+            .put_batch()
+            .put("pose/x", "1.0")
+            .put("pose/y", "2.0")
+            .delete("pose/z")
+            .res_async())
+        .unwrap();
+        println!("[BT][02b] Publishing a single sample");
+        ztimeout!(publisher.put("pose/x", "3.0").res_async()).unwrap();
+
+        // The batched subscribers receive the batch at once, then the single sample alone
+        println!("[BT][03a] Receiving the grouped samples");
+        for receiver in [&batched, &local] {
+            let samples = ztimeout!(receiver.recv_async()).unwrap();
+            check_batch(&samples);
+            let samples = ztimeout!(receiver.recv_async()).unwrap();
+            assert_eq!(samples.len(), 1);
+            assert_eq!(samples[0].value.to_string(), "3.0");
+            assert!(samples[0].batch.is_none());
+        }
+
+        // A subscriber to a part of the batch receives that part, numbered on its own
+        println!("[BT][03b] Receiving a part of the batch");
+        let samples = ztimeout!(partial.recv_async()).unwrap();
+        assert_eq!(samples.len(), 1);
+        assert_eq!(samples[0].key_expr.as_str(), "pose/y");
+        let batch = samples[0].batch.unwrap();
+        assert_eq!((batch.index, batch.count), (0, 1));
+
+        // The other subscribers receive the samples one by one
+        println!("[BT][03c] Receiving the individual samples");
+        let mut samples = vec![];
+        for _ in 0..3 {
+            let mut group = ztimeout!(unbatched.recv_async()).unwrap();
+            assert_eq!(group.len(), 1);
+            samples.push(group.pop().unwrap());
+        }
+        check_batch(&samples);
+        let mut samples = vec![];
+        for _ in 0..3 {
+            samples.push(ztimeout!(plain.recv_async()).unwrap());
+        }
+        check_batch(&samples);
+        let sample = ztimeout!(plain.recv_async()).unwrap();
+        assert_eq!(sample.value.to_string(), "3.0");
+
+        // A batch with an invalid key expression isn't published
+        println!("[BT][04a] Publishing an invalid batch");
+        assert!(publisher
+            .put_batch()
+            .put("pose/x", "4.0")
+            .put("pose//invalid", "5.0")
+            .res_async()
+            .await
+            .is_err());
+        task::sleep(SLEEP).await;
+        assert!(batched.try_recv().is_err());
+        assert!(plain.try_recv().is_err());
+        assert!(partial.try_recv().is_err());
+
+        println!("[BT][05a] Closing sessions");
+        ztimeout!(batched.undeclare().res_async()).unwrap();
+        ztimeout!(unbatched.undeclare().res_async()).unwrap();
+        ztimeout!(plain.undeclare().res_async()).unwrap();
+        ztimeout!(partial.undeclare().res_async()).unwrap();
+        ztimeout!(local.undeclare().res_async()).unwrap();
+        ztimeout!(publisher.close().res_async()).unwrap();
+        ztimeout!(subscriber.close().res_async()).unwrap();
+        ztimeout!(router.close().res_async()).unwrap();
+    });
+}
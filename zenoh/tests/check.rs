@@ -0,0 +1,100 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+#![cfg(feature = "transport_tls")]
+use async_std::prelude::FutureExt;
+use async_std::task;
+use std::time::Duration;
+use zenoh::prelude::r#async::*;
+use zenoh::runtime::{Runtime, ValidationKind, ValidationReport};
+use zenoh_core::zasync_executor_init;
+use zenoh_link::EndPoint;
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+
+macro_rules! ztimeout {
+    ($f:expr) => {
+        $f.timeout(TIMEOUT).await.unwrap()
+    };
+}
+
+fn peer_config(listen: &[&str]) -> Config {
+    let mut config = config::peer();
+    config.listen.endpoints = listen.iter().map(|e| e.parse().unwrap()).collect();
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    config
+}
+
+#[test]
+fn check_reports_all_problems() {
+    task::block_on(async {
+        zasync_executor_init!();
+        let _ = env_logger::try_init();
+
+        let valid = "tcp/127.0.0.1:17552";
+        let bad_cert = "tls/127.0.0.1:17553#server_certificate_file=/nonexistent/cert.pem;server_private_key_file=/nonexistent/key.pem";
+        let unbindable = "tcp/127.0.0.1:17554";
+
+        // The port is already bound by someone else
+        let _socket = std::net::TcpListener::bind("127.0.0.1:17554").unwrap();
+
+        println!("[  ][01a] Checking a valid configuration");
+        let report = ztimeout!(Runtime::check(peer_config(&[valid]))).unwrap();
+        println!("[  ][01b] {report:?}");
+        assert!(report.is_ok(), "{report:?}");
+
+        println!("[  ][02a] Checking a configuration with a bad certificate and a bound port");
+        let report =
+            ztimeout!(Runtime::check(peer_config(&[valid, bad_cert, unbindable]))).unwrap();
+        println!("[  ][02b] {report}");
+        assert_eq!(report.problems.len(), 2, "{report:?}");
+        let endpoints = report
+            .problems
+            .iter()
+            .map(|p| {
+                assert_eq!(p.kind, ValidationKind::Listen);
+                p.endpoint.as_ref().unwrap().to_string()
+            })
+            .collect::<Vec<_>>();
+        let expected = [bad_cert, unbindable]
+            .iter()
+            .map(|e| e.parse::<EndPoint>().unwrap().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(endpoints, expected);
+
+        // The report is serializable for tooling
+        let json = serde_json::to_string(&report).unwrap();
+        println!("[  ][02c] {json}");
+        let parsed: ValidationReport = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, report);
+
+        // The probed listeners are closed after the check
+        println!("[  ][03a] Binding the port of the probed listener");
+        assert!(std::net::TcpListener::bind("127.0.0.1:17552").is_ok());
+
+        // Building the runtime fails with all the problems found
+        println!("[  ][04a] Building the runtime");
+        let res = ztimeout!(Runtime::build(peer_config(&[valid, bad_cert, unbindable])));
+        let e = res.err().unwrap().to_string();
+        println!("[  ][04b] {e}");
+        assert!(e.contains("17553") && e.contains("17554"), "{e}");
+
+        // A valid runtime is only listening once started
+        println!("[  ][05a] Building then starting a valid runtime");
+        let mut runtime = ztimeout!(Runtime::build(peer_config(&[valid]))).unwrap();
+        assert!(runtime.manager().get_listeners().is_empty());
+        ztimeout!(runtime.start()).unwrap();
+        assert_eq!(runtime.manager().get_listeners().len(), 1);
+        ztimeout!(runtime.close()).unwrap();
+    });
+}
@@ -0,0 +1,151 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use async_std::net::TcpListener;
+use async_std::prelude::FutureExt;
+use async_std::task;
+use std::time::{Duration, Instant};
+use zenoh::prelude::r#async::*;
+use zenoh_core::zasync_executor_init;
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+// Well below the timeout of a connection to a blackholed endpoint
+const PROMPTLY: Duration = Duration::from_secs(5);
+
+macro_rules! ztimeout {
+    ($f:expr) => {
+        $f.timeout(TIMEOUT).await.unwrap()
+    };
+}
+
+async fn open_router(endpoint: &str) -> Session {
+    let mut config = config::default();
+    config.set_mode(Some(WhatAmI::Router)).unwrap();
+    config.listen.endpoints = vec![endpoint.parse().unwrap()];
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    ztimeout!(zenoh::open(config).res_async()).unwrap()
+}
+
+fn client_config(endpoints: &[&str]) -> Config {
+    let mut config = config::client(endpoints.iter().map(|e| e.parse::<EndPoint>().unwrap()));
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    config
+}
+
+#[test]
+fn connect_parallel() {
+    task::block_on(async {
+        zasync_executor_init!();
+        let _ = env_logger::try_init();
+
+        // The blackholed endpoint accepts the TCP connections but never answers the handshake
+        let _blackhole = TcpListener::bind("127.0.0.1:17590").await.unwrap();
+        let blackholed = "tcp/127.0.0.1:17590";
+        let live = "tcp/127.0.0.1:17591";
+        let refused = "tcp/127.0.0.1:17592";
+        println!("[CO][01a] Opening router on {live}");
+        let router = open_router(live).await;
+
+        // The session is open as soon as the live endpoint is connected
+        println!("[CO][02a] Opening client on {blackholed} and {live}");
+        let start = Instant::now();
+        let client =
+            ztimeout!(zenoh::open(client_config(&[blackholed, live])).res_async()).unwrap();
+        println!("[CO][02b] Client open in {:?}", start.elapsed());
+        assert!(start.elapsed() < PROMPTLY);
+        let routers = ztimeout!(client.info().routers_zid().res_async()).collect::<Vec<_>>();
+        assert_eq!(routers, vec![router.zid()]);
+        ztimeout!(client.close().res_async()).unwrap();
+
+        // The session isn't open when fewer endpoints than required are connected
+        println!("[CO][03a] Opening client requiring 2 of {refused} and {live}");
+        let mut config = client_config(&[refused, live]);
+        config.insert_json5("connect/required", "2").unwrap();
+        let start = Instant::now();
+        let e = ztimeout!(zenoh::open(config).res_async()).unwrap_err();
+        println!("[CO][03b] Client not open in {:?}: {e}", start.elapsed());
+        assert!(start.elapsed() < PROMPTLY);
+        assert!(e.to_string().contains("Unable to connect to 2 of"));
+
+        // The overall timeout bounds the wait for the required endpoints
+        println!("[CO][04a] Opening client requiring 2 of {blackholed} and {live}");
+        let mut config = client_config(&[blackholed, live]);
+        config.insert_json5("connect/required", "2").unwrap();
+        let start = Instant::now();
+        let res = ztimeout!(zenoh::open(config)
+            .timeout(Duration::from_secs(1))
+            .res_async());
+        println!("[CO][04b] Client not open in {:?}", start.elapsed());
+        assert!(res.is_err());
+        assert!(start.elapsed() < PROMPTLY);
+
+        // With a parallelism of 1 the endpoints are connected to in order
+        println!("[CO][05a] Opening client on {refused} and {live} one at a time");
+        let mut config = client_config(&[refused, live]);
+        config.insert_json5("connect/parallelism", "1").unwrap();
+        let client = ztimeout!(zenoh::open(config).res_async()).unwrap();
+        let routers = ztimeout!(client.info().routers_zid().res_async()).collect::<Vec<_>>();
+        assert_eq!(routers, vec![router.zid()]);
+
+        ztimeout!(client.close().res_async()).unwrap();
+        ztimeout!(router.close().res_async()).unwrap();
+    });
+}
+
+async fn routers(session: &Session) -> Vec<ZenohId> {
+    session.info().routers_zid().res_async().await.collect()
+}
+
+#[test]
+fn connect_priority() {
+    task::block_on(async {
+        zasync_executor_init!();
+        let _ = env_logger::try_init();
+
+        let preferred = "tcp/127.0.0.1:17593";
+        let fallback = "tcp/127.0.0.1:17594";
+        println!("[CO][01a] Opening fallback router on {fallback}");
+        let fallback_router = open_router(fallback).await;
+
+        // The fallback is connected to while the preferred endpoint is down
+        println!("[CO][02a] Opening client preferring {preferred} to {fallback}");
+        let client = ztimeout!(zenoh::open(client_config(&[
+            &format!("{fallback}?priority=2;exclusive=true"),
+            &format!("{preferred}?exclusive=true"),
+        ]))
+        .res_async())
+        .unwrap();
+        assert_eq!(routers(&client).await, vec![fallback_router.zid()]);
+
+        // The client returns to the preferred endpoint once it is up, closing the fallback
+        println!("[CO][03a] Opening preferred router on {preferred}");
+        let preferred_router = open_router(preferred).await;
+        ztimeout!(async {
+            while routers(&client).await != vec![preferred_router.zid()] {
+                task::sleep(Duration::from_millis(100)).await;
+            }
+        });
+
+        // The client falls back again once the preferred endpoint is down
+        println!("[CO][04a] Closing preferred router");
+        ztimeout!(preferred_router.close().res_async()).unwrap();
+        ztimeout!(async {
+            while routers(&client).await != vec![fallback_router.zid()] {
+                task::sleep(Duration::from_millis(100)).await;
+            }
+        });
+
+        ztimeout!(client.close().res_async()).unwrap();
+        ztimeout!(fallback_router.close().res_async()).unwrap();
+    });
+}
@@ -0,0 +1,213 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use async_std::prelude::FutureExt;
+use async_std::task;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use zenoh::info::{CloseReason, ConnectivityEvent};
+use zenoh::plugins::PluginsManager;
+use zenoh::prelude::r#async::*;
+use zenoh::runtime::{AdminSpace, Runtime};
+use zenoh_core::{zasync_executor_init, zlock};
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_millis(500);
+
+macro_rules! ztimeout {
+    ($f:expr) => {
+        $f.timeout(TIMEOUT).await.unwrap()
+    };
+}
+
+async fn open_router(endpoint: &str) -> Session {
+    let mut config = config::peer();
+    config.set_mode(Some(WhatAmI::Router)).unwrap();
+    config.listen.endpoints = vec![endpoint.parse().unwrap()];
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    config.transport.unicast.set_max_sessions(1).unwrap();
+    ztimeout!(zenoh::open(config).res_async()).unwrap()
+}
+
+async fn open_client(endpoint: &str) -> Session {
+    let mut config = config::peer();
+    config.set_mode(Some(WhatAmI::Client)).unwrap();
+    config.connect.endpoints = vec![endpoint.parse().unwrap()];
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    // Leave the time to restart a router before retrying to connect to it
+    config
+        .insert_json5("connect/retry/period_init", "3000")
+        .unwrap();
+    ztimeout!(zenoh::open(config).res_async()).unwrap()
+}
+
+async fn wait_event<F>(events: &Mutex<Vec<ConnectivityEvent>>, f: F) -> ConnectivityEvent
+where
+    F: Fn(&ConnectivityEvent) -> bool,
+{
+    ztimeout!(async {
+        loop {
+            if let Some(event) = zlock!(events).iter().find(|e| f(e)) {
+                return event.clone();
+            }
+            task::sleep(Duration::from_millis(10)).await;
+        }
+    })
+}
+
+// Returns the admin space entry of the transport of the router with `zid`, if any.
+async fn transport_entry(
+    session: &Session,
+    router: ZenohId,
+    zid: ZenohId,
+) -> Option<serde_json::Value> {
+    let selector = format!("@/router/{router}/transport/unicast/{zid}");
+    let replies = ztimeout!(session.get(selector).res_async()).unwrap();
+    let reply = replies.recv_async().await.ok()?;
+    serde_json::Value::try_from(&reply.sample.ok()?.value).ok()
+}
+
+// Waits for the admin space entry of the transport of the router with `zid` to report its close.
+async fn wait_closed(session: &Session, router: ZenohId, zid: ZenohId) -> serde_json::Value {
+    ztimeout!(async {
+        loop {
+            if let Some(entry) = transport_entry(session, router, zid).await {
+                if !entry["closed"].is_null() {
+                    return entry["closed"].clone();
+                }
+            }
+            task::sleep(Duration::from_millis(10)).await;
+        }
+    })
+}
+
+#[test]
+fn connectivity_rejected_max_sessions() {
+    task::block_on(async {
+        zasync_executor_init!();
+        let _ = env_logger::try_init();
+
+        let endpoint = "tcp/127.0.0.1:17550";
+
+        // The router accepts a single session
+        println!("[CE][01a] Opening router session");
+        let router = open_router(endpoint).await;
+        println!("[CE][01b] Opening client01 session");
+        let client01 = open_client(endpoint).await;
+        let events = Arc::new(Mutex::new(vec![]));
+        client01.on_connectivity_event({
+            let events = events.clone();
+            move |event: &ConnectivityEvent| zlock!(events).push(event.clone())
+        });
+
+        // The client loses its router and retries to connect to it
+        println!("[CE][02a] Closing router session");
+        ztimeout!(router.close().res_async()).unwrap();
+        let event = wait_event(&events, |e| {
+            matches!(e, ConnectivityEvent::Disconnected { .. })
+        })
+        .await;
+        println!("[CE][02b] client01 event: {event:?}");
+        // Let the immediate reconnection fail, the next one is only tried 3 seconds later
+        task::sleep(SLEEP).await;
+
+        // Another client takes the only session of the restarted router
+        println!("[CE][03a] Opening router session again");
+        let router = open_router(endpoint).await;
+        println!("[CE][03b] Opening client02 session");
+        let client02 = open_client(endpoint).await;
+
+        // The reconnection of the first client is rejected with the reason of the router
+        println!("[CE][04a] Waiting for the rejection of client01");
+        let event = wait_event(&events, |e| matches!(e, ConnectivityEvent::Rejected { .. })).await;
+        println!("[CE][04b] client01 event: {event:?}");
+        match event {
+            ConnectivityEvent::Rejected {
+                endpoint: rejected,
+                reason,
+            } => {
+                assert_eq!(rejected, endpoint.parse().unwrap());
+                assert_eq!(reason.reason, CloseReason::MaxSessions);
+                assert!(reason.remote);
+                assert_eq!(reason.detail.as_deref(), Some("max sessions reached (1)"));
+            }
+            _ => unreachable!(),
+        }
+
+        println!("[CE][05a] Closing sessions");
+        ztimeout!(client01.close().res_async()).unwrap();
+        ztimeout!(client02.close().res_async()).unwrap();
+        ztimeout!(router.close().res_async()).unwrap();
+    });
+}
+
+#[test]
+fn connectivity_close_reasons_in_adminspace() {
+    task::block_on(async {
+        zasync_executor_init!();
+        let _ = env_logger::try_init();
+
+        let endpoint = "tcp/127.0.0.1:17562";
+
+        // The router accepts two sessions: the observer querying its admin space, and client01
+        println!("[CR][01a] Opening router runtime");
+        let mut config = config::default();
+        config.set_mode(Some(WhatAmI::Router)).unwrap();
+        config.listen.endpoints = vec![endpoint.parse().unwrap()];
+        config.scouting.multicast.set_enabled(Some(false)).unwrap();
+        config.transport.unicast.set_max_sessions(2).unwrap();
+        let router = ztimeout!(Runtime::new(config)).unwrap();
+        AdminSpace::start(
+            &router,
+            PluginsManager::static_plugins_only(),
+            String::from("test"),
+        )
+        .await;
+        println!("[CR][01b] Opening observer and client01 sessions");
+        let observer = open_client(endpoint).await;
+        let client01 = open_client(endpoint).await;
+        let client01_zid = client01.zid();
+        task::sleep(SLEEP).await;
+        let entry = transport_entry(&observer, router.zid, client01_zid)
+            .await
+            .unwrap();
+        assert!(entry["closed"].is_null());
+
+        // The router rejects client02 and reports the reason it sent to it
+        println!("[CR][02a] Opening client02 session");
+        let client02_zid = ZenohId::rand();
+        let mut config = config::peer();
+        config.set_mode(Some(WhatAmI::Client)).unwrap();
+        config.set_id(client02_zid).unwrap();
+        config.connect.endpoints = vec![endpoint.parse().unwrap()];
+        config.scouting.multicast.set_enabled(Some(false)).unwrap();
+        assert!(ztimeout!(zenoh::open(config).res_async()).is_err());
+        let closed = wait_closed(&observer, router.zid, client02_zid).await;
+        println!("[CR][02b] client02 closed: {closed}");
+        assert_eq!(closed["reason"], "MAX_SESSIONS");
+        assert_eq!(closed["remote"], false);
+        assert_eq!(closed["detail"], "max sessions reached (2)");
+
+        // The router reports the orderly close of client01
+        println!("[CR][03a] Closing client01 session");
+        ztimeout!(client01.close().res_async()).unwrap();
+        let closed = wait_closed(&observer, router.zid, client01_zid).await;
+        println!("[CR][03b] client01 closed: {closed}");
+        assert_eq!(closed["reason"], "GENERIC");
+        assert_eq!(closed["remote"], true);
+
+        println!("[CR][04a] Closing sessions");
+        ztimeout!(observer.close().res_async()).unwrap();
+        ztimeout!(router.close()).unwrap();
+    });
+}
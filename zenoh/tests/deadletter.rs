@@ -0,0 +1,104 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+#![cfg(feature = "unstable")]
+use async_std::prelude::FutureExt;
+use async_std::task;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use zenoh::prelude::r#async::*;
+use zenoh::runtime::Runtime;
+use zenoh_core::zasync_executor_init;
+use zenoh_util::clock::TestClock;
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_secs(1);
+const WINDOW: Duration = Duration::from_secs(1);
+
+macro_rules! ztimeout {
+    ($f:expr) => {
+        $f.timeout(TIMEOUT).await.unwrap()
+    };
+}
+
+#[test]
+fn dead_letter_no_route() {
+    task::block_on(async {
+        zasync_executor_init!();
+        let _ = env_logger::try_init();
+
+        let clock = TestClock::new();
+        let mut config = config::peer();
+        config.scouting.multicast.set_enabled(Some(false)).unwrap();
+        config.routing.dead_letter.set_enabled(Some(true)).unwrap();
+        config
+            .routing
+            .dead_letter
+            .set_window(Some(WINDOW.as_millis() as u64))
+            .unwrap();
+        println!("[  ][01a] Opening peer session notifying the dead letters");
+        let runtime = ztimeout!(Runtime::new_with_clock(config, Arc::new(clock.clone()))).unwrap();
+        let handled = Arc::new(AtomicUsize::new(0));
+        let c_handled = handled.clone();
+        runtime.on_dead_letter(Arc::new(move |_| {
+            c_handled.fetch_add(1, Ordering::Relaxed);
+        }));
+        let session = ztimeout!(zenoh::init(runtime).res_async()).unwrap();
+        let notifications = ztimeout!(session
+            .declare_subscriber(format!("@/router/{}/deadletter", session.zid()))
+            .res_async())
+        .unwrap();
+
+        // Only the first publication of the window is notified
+        println!("[  ][02a] Publishing without route");
+        let key_expr = "test/deadletter/no_route";
+        for _ in 0..3 {
+            ztimeout!(session.put(key_expr, "value").res_async()).unwrap();
+        }
+        let sample = ztimeout!(notifications.recv_async()).unwrap();
+        let dead_letter = serde_json::Value::try_from(&sample.value).unwrap();
+        assert_eq!(dead_letter["key_expr"], key_expr);
+        assert_eq!(dead_letter["reason"], "no_route");
+        assert_eq!(dead_letter["payload_size"], 5);
+        assert_eq!(dead_letter["suppressed"], 0);
+        task::sleep(SLEEP).await;
+        assert!(notifications.try_recv().is_err());
+        assert_eq!(handled.load(Ordering::Relaxed), 1);
+
+        // The next window notifies again, counting the suppressed publications
+        println!("[  ][02b] Publishing without route in the next window");
+        clock.advance(WINDOW);
+        ztimeout!(session.put(key_expr, "value").res_async()).unwrap();
+        let sample = ztimeout!(notifications.recv_async()).unwrap();
+        let dead_letter = serde_json::Value::try_from(&sample.value).unwrap();
+        assert_eq!(dead_letter["suppressed"], 2);
+        task::sleep(SLEEP).await;
+        assert!(notifications.try_recv().is_err());
+        assert_eq!(handled.load(Ordering::Relaxed), 2);
+
+        // The routed publications are not notified
+        println!("[  ][02c] Publishing with a route");
+        let key_expr = "test/deadletter/routed";
+        let subscriber = ztimeout!(session.declare_subscriber(key_expr).res_async()).unwrap();
+        ztimeout!(session.put(key_expr, "value").res_async()).unwrap();
+        ztimeout!(subscriber.recv_async()).unwrap();
+        task::sleep(SLEEP).await;
+        assert!(notifications.try_recv().is_err());
+        assert_eq!(handled.load(Ordering::Relaxed), 2);
+
+        ztimeout!(subscriber.undeclare().res_async()).unwrap();
+        ztimeout!(notifications.undeclare().res_async()).unwrap();
+        ztimeout!(session.close().res_async()).unwrap();
+    });
+}
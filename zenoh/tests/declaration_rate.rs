@@ -0,0 +1,133 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use async_std::prelude::FutureExt;
+use async_std::task;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use zenoh::prelude::r#async::*;
+use zenoh_core::zasync_executor_init;
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_secs(1);
+
+const ENDPOINT: &str = "tcp/127.0.0.1:17481";
+const KEY_EXPR: &str = "test/ratelimit/data";
+const MSG_COUNT: usize = 200;
+const MSG_PERIOD: Duration = Duration::from_millis(10);
+// The flooding client declares and undeclares DECLARE_BATCH subscribers every MSG_PERIOD,
+// i.e. 10k declarations per second
+const DECLARE_BATCH: usize = 50;
+const MAX_LATENCY: Duration = Duration::from_millis(500);
+
+macro_rules! ztimeout {
+    ($f:expr) => {
+        $f.timeout(TIMEOUT).await.unwrap()
+    };
+}
+
+async fn open_client() -> Session {
+    let mut config = config::client([ENDPOINT.parse::<EndPoint>().unwrap()]);
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    ztimeout!(zenoh::open(config).res_async()).unwrap()
+}
+
+#[test]
+fn declaration_rate_flood() {
+    task::block_on(async {
+        zasync_executor_init!();
+        let _ = env_logger::try_init();
+
+        let mut config = config::peer();
+        config.set_mode(Some(WhatAmI::Router)).unwrap();
+        config.listen.endpoints = vec![ENDPOINT.parse().unwrap()];
+        config.scouting.multicast.set_enabled(Some(false)).unwrap();
+        config
+            .insert_json5(
+                "routing/face/declaration_rate",
+                r#"{ enabled: true, rate: 100, burst: 100, queue: 1000 }"#,
+            )
+            .unwrap();
+        println!("[DR][01a] Opening router session");
+        let router = ztimeout!(zenoh::open(config).res_async()).unwrap();
+
+        println!("[DR][01b] Opening client sessions");
+        let subscriber = open_client().await;
+        let publisher = open_client().await;
+        let flooder = open_client().await;
+
+        let latencies = Arc::new(Mutex::new(vec![]));
+        let sent = Arc::new(Mutex::new(vec![]));
+        let c_latencies = latencies.clone();
+        let c_sent = sent.clone();
+        let sub = ztimeout!(subscriber
+            .declare_subscriber(KEY_EXPR)
+            .callback(move |sample| {
+                let i = sample.value.to_string().parse::<usize>().unwrap();
+                let sent_at = c_sent.lock().unwrap()[i];
+                c_latencies.lock().unwrap().push(Instant::now() - sent_at);
+            })
+            .res_async())
+        .unwrap();
+
+        // Wait for the declaration to propagate
+        task::sleep(SLEEP).await;
+
+        println!("[DR][02a] Flooding the router with declarations");
+        let flooding = Arc::new(AtomicBool::new(true));
+        let c_flooding = flooding.clone();
+        let flood = task::spawn(async move {
+            let mut n = 0;
+            while c_flooding.load(Ordering::Relaxed) {
+                for _ in 0..DECLARE_BATCH {
+                    let sub = flooder
+                        .declare_subscriber(format!("test/ratelimit/flood/{n}"))
+                        .res_async()
+                        .await
+                        .unwrap();
+                    sub.undeclare().res_async().await.unwrap();
+                    n += 1;
+                }
+                task::sleep(MSG_PERIOD).await;
+            }
+            println!("[DR][02b] Issued {} declarations", 2 * n);
+            flooder
+        });
+
+        println!("[DR][03a] Publishing while the router is flooded");
+        for i in 0..MSG_COUNT {
+            sent.lock().unwrap().push(Instant::now());
+            ztimeout!(publisher.put(KEY_EXPR, i.to_string()).res_async()).unwrap();
+            task::sleep(MSG_PERIOD).await;
+        }
+        flooding.store(false, Ordering::Relaxed);
+        let flooder = ztimeout!(flood);
+
+        ztimeout!(async {
+            while latencies.lock().unwrap().len() < MSG_COUNT {
+                task::sleep(MSG_PERIOD).await;
+            }
+        });
+
+        let max = *latencies.lock().unwrap().iter().max().unwrap();
+        println!("[DR][03b] Maximum data-plane latency: {max:?}");
+        assert!(max < MAX_LATENCY);
+
+        ztimeout!(flooder.close().res_async()).unwrap();
+        ztimeout!(publisher.close().res_async()).unwrap();
+        ztimeout!(sub.undeclare().res_async()).unwrap();
+        ztimeout!(subscriber.close().res_async()).unwrap();
+        ztimeout!(router.close().res_async()).unwrap();
+    });
+}
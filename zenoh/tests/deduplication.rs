@@ -0,0 +1,112 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use async_std::prelude::FutureExt;
+use async_std::task;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use zenoh::prelude::r#async::*;
+use zenoh_core::zasync_executor_init;
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_secs(1);
+
+const ENDPOINT: &str = "tcp/127.0.0.1:17480";
+const KEY_EXPR: &str = "test/dedup/telemetry";
+const MSG_COUNT: usize = 100;
+
+macro_rules! ztimeout {
+    ($f:expr) => {
+        $f.timeout(TIMEOUT).await.unwrap()
+    };
+}
+
+async fn open_client() -> Session {
+    let mut config = config::client([ENDPOINT.parse::<EndPoint>().unwrap()]);
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    ztimeout!(zenoh::open(config).res_async()).unwrap()
+}
+
+#[test]
+fn deduplication_redundant_publishers() {
+    task::block_on(async {
+        zasync_executor_init!();
+        let _ = env_logger::try_init();
+
+        let mut config = config::peer();
+        config.set_mode(Some(WhatAmI::Router)).unwrap();
+        config.listen.endpoints = vec![ENDPOINT.parse().unwrap()];
+        config.scouting.multicast.set_enabled(Some(false)).unwrap();
+        config
+            .insert_json5(
+                "deduplication",
+                r#"[{ keyexprs: ["test/dedup/**"], window: 10000, mode: "content" }]"#,
+            )
+            .unwrap();
+        println!("[DD][01a] Opening router session");
+        let router = ztimeout!(zenoh::open(config).res_async()).unwrap();
+
+        println!("[DD][01b] Opening subscriber and publishers sessions");
+        let subscriber = open_client().await;
+        let primary = open_client().await;
+        let secondary = open_client().await;
+
+        let received = Arc::new(Mutex::new(vec![]));
+        let c_received = received.clone();
+        let sub = ztimeout!(subscriber
+            .declare_subscriber(KEY_EXPR)
+            .callback(move |sample| {
+                c_received.lock().unwrap().push(sample.value.to_string());
+            })
+            .res_async())
+        .unwrap();
+
+        // Wait for the declaration to propagate
+        task::sleep(SLEEP).await;
+
+        // Both publishers publish the same samples
+        println!("[DD][02a] Publishing from both publishers");
+        for i in 0..MSG_COUNT {
+            ztimeout!(primary.put(KEY_EXPR, i.to_string()).res_async()).unwrap();
+            ztimeout!(secondary.put(KEY_EXPR, i.to_string()).res_async()).unwrap();
+        }
+
+        // The primary stops, the samples of the secondary flow right away
+        println!("[DD][02b] Closing the primary publisher");
+        ztimeout!(primary.close().res_async()).unwrap();
+        for i in MSG_COUNT..2 * MSG_COUNT {
+            ztimeout!(secondary.put(KEY_EXPR, i.to_string()).res_async()).unwrap();
+        }
+
+        ztimeout!(async {
+            while received.lock().unwrap().len() < 2 * MSG_COUNT {
+                task::sleep(Duration::from_millis(10)).await;
+            }
+        });
+        // Leave some time for possible duplicates to arrive
+        task::sleep(SLEEP).await;
+
+        let mut received = received.lock().unwrap().clone();
+        println!("[DD][03a] Received {} samples", received.len());
+        received.sort_by_key(|s| s.parse::<usize>().unwrap());
+        let expected = (0..2 * MSG_COUNT)
+            .map(|i| i.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(received, expected);
+
+        ztimeout!(secondary.close().res_async()).unwrap();
+        ztimeout!(sub.undeclare().res_async()).unwrap();
+        ztimeout!(subscriber.close().res_async()).unwrap();
+        ztimeout!(router.close().res_async()).unwrap();
+    });
+}
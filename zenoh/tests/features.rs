@@ -0,0 +1,73 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use std::process::Command;
+
+// The packages of the normal dependencies of zenoh compiled with the given features only.
+fn dependencies(features: &[&str]) -> Vec<String> {
+    let mut command = Command::new(env!("CARGO"));
+    command
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .args(["tree", "--locked", "-p", "zenoh", "--no-default-features"])
+        .args(["-e", "normal", "--prefix", "none", "--format", "{p}"]);
+    if !features.is_empty() {
+        command.args(["--features", &features.join(",")]);
+    }
+    let output = command.output().unwrap();
+    assert!(
+        output.status.success(),
+        "cargo tree failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .filter_map(|line| line.split_whitespace().next())
+        .map(str::to_string)
+        .collect()
+}
+
+#[test]
+fn features_tcp_only() {
+    let deps = dependencies(&["transport_tcp"]);
+    println!(
+        "[  ][01a] {} dependencies with transport_tcp only",
+        deps.len()
+    );
+    assert!(deps.iter().any(|d| d == "zenoh-link-tcp"));
+    for excluded in [
+        "zenoh-link-quic",
+        "zenoh-link-tls",
+        "zenoh-link-ws",
+        "quinn",
+        "rustls",
+        "async-rustls",
+        "rustls-webpki",
+        "webpki-roots",
+        "tokio-tungstenite",
+    ] {
+        assert!(
+            !deps.iter().any(|d| d == excluded),
+            "{excluded} is compiled in with transport_tcp only"
+        );
+    }
+}
+
+#[test]
+fn features_no_transport() {
+    let deps = dependencies(&[]);
+    println!("[  ][01a] {} dependencies without transport", deps.len());
+    assert!(!deps
+        .iter()
+        .any(|d| d.starts_with("zenoh-link-") && d != "zenoh-link-commons"));
+}
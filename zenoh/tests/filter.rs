@@ -0,0 +1,115 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use async_std::prelude::FutureExt;
+use async_std::task;
+use std::time::Duration;
+use zenoh::plugins::PluginsManager;
+use zenoh::prelude::r#async::*;
+use zenoh::runtime::{AdminSpace, Runtime};
+use zenoh_core::zasync_executor_init;
+use zenoh_protocol::core::WhatAmI;
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_secs(1);
+
+macro_rules! ztimeout {
+    ($f:expr) => {
+        $f.timeout(TIMEOUT).await.unwrap()
+    };
+}
+
+async fn open_client(endpoint: &str) -> Session {
+    let mut config = config::client([endpoint.parse::<EndPoint>().unwrap()]);
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    println!("[  ][01b] Opening client session: {endpoint}");
+    ztimeout!(zenoh::open(config).res_async()).unwrap()
+}
+
+#[test]
+fn filter_evaluated_by_router() {
+    task::block_on(async {
+        zasync_executor_init!();
+        let _ = env_logger::try_init();
+
+        let endpoint = "tcp/127.0.0.1:17518";
+        let key_expr = "test/filter/value";
+
+        let mut config = config::default();
+        config.set_mode(Some(WhatAmI::Router)).unwrap();
+        config.listen.endpoints = vec![endpoint.parse().unwrap()];
+        config.scouting.multicast.set_enabled(Some(false)).unwrap();
+        println!("[  ][01a] Opening router runtime");
+        let router = ztimeout!(Runtime::new(config)).unwrap();
+        AdminSpace::start(
+            &router,
+            PluginsManager::static_plugins_only(),
+            String::from("test"),
+        )
+        .await;
+
+        let subscriber_session = open_client(endpoint).await;
+        let publisher_session = open_client(endpoint).await;
+        let subscriber = ztimeout!(subscriber_session
+            .declare_subscriber(key_expr)
+            .filter("payload > 10".parse().unwrap())
+            .res_async())
+        .unwrap();
+        task::sleep(SLEEP).await;
+
+        println!("[  ][02a] Publishing matching and non matching values");
+        for value in [Value::from(5), Value::from(15), Value::from("text")] {
+            ztimeout!(publisher_session.put(key_expr, value).res_async()).unwrap();
+        }
+        ztimeout!(publisher_session.put(key_expr, 20).res_async()).unwrap();
+
+        // Only the matching values reach the subscriber, in order
+        for expected in ["15", "20"] {
+            let sample = ztimeout!(subscriber.recv_async()).unwrap();
+            println!("[  ][02b] Received {sample}");
+            assert_eq!(sample.value.to_string(), expected);
+        }
+        task::sleep(SLEEP).await;
+        assert!(subscriber.try_recv().is_err());
+
+        // The router dropped the non matching values itself, reporting the unreadable one
+        println!("[  ][03a] Querying the sessions of the router");
+        let selector = format!("@/router/{}?_stats=true", router.zid);
+        let replies = ztimeout!(publisher_session.get(selector).res_async()).unwrap();
+        let sample = ztimeout!(replies.recv_async()).unwrap().sample.unwrap();
+        let report = serde_json::Value::try_from(&sample.value).unwrap();
+        let session = |zid: ZenohId| {
+            report["sessions"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .find(|s| s["zid"] == zid.to_string())
+                .unwrap()
+                .clone()
+        };
+        assert_eq!(session(subscriber_session.zid())["malformed_payloads"], 1);
+        assert_eq!(session(publisher_session.zid())["malformed_payloads"], 0);
+
+        #[cfg(feature = "stats")]
+        {
+            let publisher = session(publisher_session.zid());
+            assert_eq!(publisher["stats"]["rx_z_put_msgs"]["user"], 4);
+            assert_eq!(publisher["stats"]["tx_z_put_msgs"]["user"], 2);
+        }
+
+        ztimeout!(subscriber.undeclare().res_async()).unwrap();
+        ztimeout!(publisher_session.close().res_async()).unwrap();
+        ztimeout!(subscriber_session.close().res_async()).unwrap();
+        ztimeout!(router.close()).unwrap();
+    });
+}
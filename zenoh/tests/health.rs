@@ -0,0 +1,102 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use async_std::prelude::FutureExt;
+use async_std::task;
+use std::time::Duration;
+use zenoh::prelude::r#async::*;
+use zenoh::runtime::{HealthStatus, Runtime};
+use zenoh_core::zasync_executor_init;
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_secs(1);
+
+macro_rules! ztimeout {
+    ($f:expr) => {
+        $f.timeout(TIMEOUT).await.unwrap()
+    };
+}
+
+async fn open_runtime(listen: &[&str], connect: &[&str]) -> Runtime {
+    let mut config = config::peer();
+    config.listen.endpoints = listen.iter().map(|e| e.parse().unwrap()).collect();
+    config.connect.endpoints = connect.iter().map(|e| e.parse().unwrap()).collect();
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    println!("[  ][01a] Opening peer runtime: {listen:?} {connect:?}");
+    ztimeout!(Runtime::new(config)).unwrap()
+}
+
+#[test]
+fn health_connect_failures() {
+    task::block_on(async {
+        zasync_executor_init!();
+        let _ = env_logger::try_init();
+
+        let reachable = "tcp/127.0.0.1:17510";
+        let unreachable = "tcp/127.0.0.1:17511";
+        let server = open_runtime(&[reachable], &[]).await;
+        let report = ztimeout!(server.health());
+        assert_eq!(report.status, HealthStatus::Ok, "{report:?}");
+        assert_eq!(report.listeners.bound.len(), 1);
+
+        // One of the configured connect endpoints can't be established
+        println!("[  ][02a] Connecting to a failing endpoint");
+        let client = open_runtime(&[], &[reachable, unreachable]).await;
+        task::sleep(SLEEP).await;
+        let report = ztimeout!(client.health());
+        assert_eq!(report.status, HealthStatus::Degraded, "{report:?}");
+        assert_eq!(report.reasons.len(), 1);
+        for connect in &report.connect {
+            let established = connect.endpoint.to_string() == reachable;
+            assert_eq!(connect.established, established, "{report:?}");
+        }
+
+        // The failing endpoint is removed from the configuration
+        println!("[  ][02b] Removing the failing endpoint");
+        (&client.config)
+            .insert_json5("connect/endpoints", &format!("[\"{reachable}\"]"))
+            .unwrap();
+        task::sleep(SLEEP).await;
+        let report = ztimeout!(client.health());
+        assert_eq!(report.status, HealthStatus::Ok, "{report:?}");
+
+        ztimeout!(client.close()).unwrap();
+        ztimeout!(server.close()).unwrap();
+    });
+}
+
+#[test]
+fn health_connect_failures_threshold() {
+    task::block_on(async {
+        zasync_executor_init!();
+        let _ = env_logger::try_init();
+
+        // A failing endpoint below the threshold keeps the runtime healthy
+        let mut config = config::peer();
+        config.connect.endpoints = vec!["tcp/127.0.0.1:17512".parse().unwrap()];
+        config.scouting.multicast.set_enabled(Some(false)).unwrap();
+        config
+            .health
+            .set_connect_failures_degraded(Some(2))
+            .unwrap();
+        let runtime = ztimeout!(Runtime::new(config)).unwrap();
+        task::sleep(SLEEP).await;
+
+        println!("[  ][02a] Connecting to a failing endpoint below the threshold");
+        let report = ztimeout!(runtime.health());
+        assert_eq!(report.status, HealthStatus::Ok, "{report:?}");
+        assert!(!report.connect[0].established);
+
+        ztimeout!(runtime.close()).unwrap();
+    });
+}
@@ -0,0 +1,128 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use async_std::prelude::FutureExt;
+use async_std::task;
+use std::time::Duration;
+use zenoh::prelude::r#async::*;
+use zenoh::prelude::sync::SyncResolve;
+use zenoh_core::zasync_executor_init;
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_millis(100);
+
+macro_rules! ztimeout {
+    ($f:expr) => {
+        $f.timeout(TIMEOUT).await.unwrap()
+    };
+}
+
+async fn open_node(mode: WhatAmI, listen: &[&str], connect: &[&str]) -> Session {
+    let mut config = config::peer();
+    config.set_mode(Some(mode)).unwrap();
+    config.listen.endpoints = listen.iter().map(|e| e.parse().unwrap()).collect();
+    config.connect.endpoints = connect.iter().map(|e| e.parse().unwrap()).collect();
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    ztimeout!(zenoh::open(config).res_async()).unwrap()
+}
+
+#[test]
+fn info_router_client() {
+    task::block_on(async {
+        zasync_executor_init!();
+        let _ = env_logger::try_init();
+
+        // The router listens on a port chosen by the system
+        println!("[IN][01a] Opening router session");
+        let router = open_node(WhatAmI::Router, &["tcp/127.0.0.1:0"], &[]).await;
+        let locators = router.info().locators().res_async().await;
+        println!("[IN][01b] Router locators: {locators:?}");
+        assert_eq!(locators.len(), 1);
+        let locator = locators[0].clone();
+        assert_eq!(locator.protocol().as_str(), "tcp");
+        assert!(!locator.address().as_str().ends_with(":0"));
+        assert_eq!(router.info().locators().res_sync(), locators);
+
+        println!("[IN][02a] Opening client session on {locator}");
+        let client = open_node(WhatAmI::Client, &[], &[locator.as_str()]).await;
+        assert!(client.info().locators().res_async().await.is_empty());
+
+        let routers = client.info().routers().res_async().await;
+        println!("[IN][02b] Client routers: {routers:?}");
+        assert_eq!(routers.len(), 1);
+        assert_eq!(routers[0].zid, router.zid());
+        assert_eq!(routers[0].whatami, WhatAmI::Router);
+        assert_eq!(routers[0].locators, vec![locator]);
+        assert_eq!(client.info().routers().res_sync(), routers);
+        assert!(client.info().peers().res_async().await.is_empty());
+
+        // The client is neither a router nor a peer of the router
+        assert!(router.info().routers().res_async().await.is_empty());
+        assert!(router.info().peers().res_async().await.is_empty());
+
+        println!("[IN][03a] Closing router session");
+        ztimeout!(router.close().res_async()).unwrap();
+        ztimeout!(async {
+            while !client.info().routers().res_async().await.is_empty() {
+                task::sleep(SLEEP).await;
+            }
+        });
+
+        ztimeout!(client.close().res_async()).unwrap();
+    });
+}
+
+#[test]
+fn info_peers() {
+    task::block_on(async {
+        zasync_executor_init!();
+        let _ = env_logger::try_init();
+
+        println!("[IN][01a] Opening peer sessions");
+        let peer01 = open_node(WhatAmI::Peer, &["tcp/127.0.0.1:17482"], &[]).await;
+        let peer02 = open_node(WhatAmI::Peer, &[], &["tcp/127.0.0.1:17482"]).await;
+
+        let peers = ztimeout!(async {
+            loop {
+                let peers = peer01.info().peers().res_async().await;
+                if !peers.is_empty() {
+                    break peers;
+                }
+                task::sleep(SLEEP).await;
+            }
+        });
+        println!("[IN][01b] Peer01 peers: {peers:?}");
+        assert_eq!(peers.len(), 1);
+        assert_eq!(peers[0].zid, peer02.zid());
+        assert_eq!(peers[0].whatami, WhatAmI::Peer);
+        let peers = peer02.info().peers().res_sync();
+        println!("[IN][01c] Peer02 peers: {peers:?}");
+        assert_eq!(peers.len(), 1);
+        assert_eq!(peers[0].zid, peer01.zid());
+        assert_eq!(
+            peers[0].locators,
+            vec!["tcp/127.0.0.1:17482".parse::<Locator>().unwrap()]
+        );
+        assert!(peer01.info().routers().res_async().await.is_empty());
+
+        println!("[IN][02a] Closing peer02 session");
+        ztimeout!(peer02.close().res_async()).unwrap();
+        ztimeout!(async {
+            while !peer01.info().peers().res_async().await.is_empty() {
+                task::sleep(SLEEP).await;
+            }
+        });
+
+        ztimeout!(peer01.close().res_async()).unwrap();
+    });
+}
@@ -0,0 +1,157 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use async_std::io::{ReadExt, WriteExt};
+use async_std::net::{TcpListener, TcpStream};
+use async_std::prelude::FutureExt;
+use async_std::task;
+use std::time::Duration;
+use zenoh::prelude::r#async::*;
+use zenoh::sample::SampleIntegrity;
+use zenoh_core::zasync_executor_init;
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_secs(1);
+
+// The payloads containing the marker are corrupted by the middlebox
+const MARKER: &[u8] = b"corrupt-me";
+
+macro_rules! ztimeout {
+    ($f:expr) => {
+        $f.timeout(TIMEOUT).await.unwrap()
+    };
+}
+
+// Forward the bytes read from `from` to `to`, flipping a bit of the byte following each marker.
+async fn forward(mut from: TcpStream, mut to: TcpStream, flip: bool) {
+    let mut buf = vec![0u8; 65_535];
+    loop {
+        match from.read(&mut buf).await {
+            Ok(n) if n > 0 => {
+                if flip {
+                    let mut i = 0;
+                    while i + MARKER.len() < n {
+                        if &buf[i..i + MARKER.len()] == MARKER {
+                            buf[i + MARKER.len()] ^= 0x01;
+                            i += MARKER.len();
+                        }
+                        i += 1;
+                    }
+                }
+                if to.write_all(&buf[..n]).await.is_err() {
+                    break;
+                }
+            }
+            _ => break,
+        }
+    }
+}
+
+// A TCP middlebox forwarding a single connection from `listen` to `server`, corrupting the
+// payloads sent from the client to the server.
+async fn middlebox(listen: &str, server: &str) {
+    let listener = TcpListener::bind(listen).await.unwrap();
+    let server = server.to_string();
+    task::spawn(async move {
+        let (client, _) = listener.accept().await.unwrap();
+        let server = TcpStream::connect(server).await.unwrap();
+        task::spawn(forward(client.clone(), server.clone(), true));
+        forward(server, client, false).await;
+    });
+}
+
+async fn open_peer(listen: &[&str], connect: &[&str], integrity: &str) -> Session {
+    let mut config = config::peer();
+    config.listen.endpoints = listen.iter().map(|e| e.parse().unwrap()).collect();
+    config.connect.endpoints = connect.iter().map(|e| e.parse().unwrap()).collect();
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    config.insert_json5("integrity", integrity).unwrap();
+    println!("[  ][01a] Opening peer session: {listen:?} {connect:?} {integrity}");
+    ztimeout!(zenoh::open(config).res_async()).unwrap()
+}
+
+async fn integrity_middlebox(ports: [u16; 2], algorithm: &str, on_mismatch: &str) {
+    let key_expr = "test/integrity";
+    let listen = format!("tcp/127.0.0.1:{}", ports[0]);
+    let subscriber_session = open_peer(
+        &[&listen],
+        &[],
+        &format!("{{ on_mismatch: \"{on_mismatch}\" }}"),
+    )
+    .await;
+    let subscriber =
+        ztimeout!(subscriber_session.declare_subscriber(key_expr).res_async()).unwrap();
+    ztimeout!(middlebox(
+        &format!("127.0.0.1:{}", ports[1]),
+        &format!("127.0.0.1:{}", ports[0])
+    ));
+    let connect = format!("tcp/127.0.0.1:{}", ports[1]);
+    let publisher_session = open_peer(
+        &[],
+        &[&connect],
+        &format!("{{ enabled: true, algorithm: \"{algorithm}\" }}"),
+    )
+    .await;
+    task::sleep(SLEEP).await;
+
+    println!("[  ][02a] Publishing an intact payload");
+    ztimeout!(publisher_session.put(key_expr, "intact").res_async()).unwrap();
+    let sample = ztimeout!(subscriber.recv_async()).unwrap();
+    assert_eq!(sample.value.to_string(), "intact");
+    assert_eq!(sample.integrity, SampleIntegrity::Verified);
+
+    println!("[  ][02b] Publishing a payload corrupted by the middlebox");
+    let mut payload = MARKER.to_vec();
+    payload.extend_from_slice(b"-payload");
+    ztimeout!(publisher_session.put(key_expr, payload.clone()).res_async()).unwrap();
+    ztimeout!(publisher_session.put(key_expr, "intact").res_async()).unwrap();
+    let sample = ztimeout!(subscriber.recv_async()).unwrap();
+    match on_mismatch {
+        "drop" => {
+            assert_eq!(sample.value.to_string(), "intact");
+            assert_eq!(sample.integrity, SampleIntegrity::Verified);
+        }
+        _ => {
+            assert_eq!(sample.integrity, SampleIntegrity::Corrupted);
+            assert_ne!(sample.value.payload.contiguous().as_ref(), &payload[..]);
+            let sample = ztimeout!(subscriber.recv_async()).unwrap();
+            assert_eq!(sample.integrity, SampleIntegrity::Verified);
+        }
+    }
+    assert_eq!(subscriber_session.info().corrupted_samples(), 1);
+    assert_eq!(publisher_session.info().corrupted_samples(), 0);
+
+    ztimeout!(subscriber.undeclare().res_async()).unwrap();
+    ztimeout!(publisher_session.close().res_async()).unwrap();
+    ztimeout!(subscriber_session.close().res_async()).unwrap();
+}
+
+#[test]
+fn integrity_middlebox_drop() {
+    task::block_on(async {
+        zasync_executor_init!();
+        let _ = env_logger::try_init();
+
+        integrity_middlebox([17540, 17541], "crc32c", "drop").await;
+    });
+}
+
+#[test]
+fn integrity_middlebox_flag() {
+    task::block_on(async {
+        zasync_executor_init!();
+        let _ = env_logger::try_init();
+
+        integrity_middlebox([17542, 17543], "xxhash64", "flag").await;
+    });
+}
@@ -0,0 +1,158 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use async_std::prelude::FutureExt;
+use async_std::task;
+use std::time::Duration;
+use zenoh::plugins::PluginsManager;
+use zenoh::prelude::r#async::*;
+use zenoh::runtime::{AdminSpace, Runtime};
+use zenoh_core::zasync_executor_init;
+use zenoh_protocol::core::WhatAmI;
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_secs(1);
+
+macro_rules! ztimeout {
+    ($f:expr) => {
+        $f.timeout(TIMEOUT).await.unwrap()
+    };
+}
+
+async fn open_peer(listen: &[&str], connect: &[&str]) -> Session {
+    let mut config = config::peer();
+    config.listen.endpoints = listen.iter().map(|e| e.parse().unwrap()).collect();
+    config.connect.endpoints = connect.iter().map(|e| e.parse().unwrap()).collect();
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    ztimeout!(zenoh::open(config).res_async()).unwrap()
+}
+
+async fn open_client(endpoint: &str) -> Session {
+    let mut config = config::client([endpoint.parse::<EndPoint>().unwrap()]);
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    ztimeout!(zenoh::open(config).res_async()).unwrap()
+}
+
+async fn is_connected(session: &Session, zid: ZenohId) -> bool {
+    session
+        .info()
+        .peers_zid()
+        .res_async()
+        .await
+        .any(|peer| peer == zid)
+}
+
+#[test]
+fn listeners_add_remove() {
+    task::block_on(async {
+        zasync_executor_init!();
+        let _ = env_logger::try_init();
+
+        let initial: EndPoint = "tcp/127.0.0.1:17580".parse().unwrap();
+        let added: EndPoint = "tcp/127.0.0.1:17581".parse().unwrap();
+
+        println!("[LS][01a] Opening peer A on {initial}");
+        let a = open_peer(&[initial.as_str()], &[]).await;
+        let locators = ztimeout!(a.info().locators().res_async());
+        assert_eq!(locators, vec![initial.to_locator()]);
+
+        // The added listener is reported right away, and accepts the transports
+        println!("[LS][02a] Adding the listener {added}");
+        let res = ztimeout!(a.add_listener(added.clone()).res_async()).unwrap();
+        assert_eq!(res, vec![added.to_locator()]);
+        let locators = ztimeout!(a.info().locators().res_async());
+        assert!(locators.contains(&initial.to_locator()));
+        assert!(locators.contains(&added.to_locator()));
+
+        println!("[LS][02b] Opening peer B on {added}");
+        let b = open_peer(&["tcp/127.0.0.1:17582"], &[added.as_str()]).await;
+        task::sleep(SLEEP).await;
+        assert!(is_connected(&b, a.zid()).await);
+
+        // The removed listener isn't reported anymore, the transports it accepted staying open
+        println!("[LS][03a] Removing the listener {initial}");
+        ztimeout!(a.remove_listener(&initial).res_async()).unwrap();
+        let locators = ztimeout!(a.info().locators().res_async());
+        assert_eq!(locators, vec![added.to_locator()]);
+        assert!(ztimeout!(a.remove_listener(&initial).res_async()).is_err());
+        assert!(is_connected(&b, a.zid()).await);
+
+        // A peer only connected to B learns the new locators of A from the gossip, and
+        // connects to A with them
+        println!("[LS][04a] Opening peer C connected to B");
+        let c = open_peer(&["tcp/127.0.0.1:17583"], &["tcp/127.0.0.1:17582"]).await;
+        ztimeout!(async {
+            while !is_connected(&c, a.zid()).await {
+                task::sleep(Duration::from_millis(100)).await;
+            }
+        });
+
+        // Removing the last listener closes the link manager of its protocol
+        println!("[LS][05a] Removing the listener {added}");
+        ztimeout!(a.remove_listener(&added).res_async()).unwrap();
+        assert!(ztimeout!(a.info().locators().res_async()).is_empty());
+        let res = ztimeout!(a.add_listener(initial.clone()).res_async()).unwrap();
+        assert_eq!(res, vec![initial.to_locator()]);
+
+        println!("[LS][06a] Closing the sessions");
+        ztimeout!(c.close().res_async()).unwrap();
+        ztimeout!(b.close().res_async()).unwrap();
+        ztimeout!(a.close().res_async()).unwrap();
+    });
+}
+
+#[test]
+fn listeners_adminspace() {
+    task::block_on(async {
+        zasync_executor_init!();
+        let _ = env_logger::try_init();
+
+        let endpoint: EndPoint = "tcp/127.0.0.1:17584".parse().unwrap();
+        let mut config = config::default();
+        config.set_mode(Some(WhatAmI::Router)).unwrap();
+        config.listen.endpoints = vec!["tcp/127.0.0.1:17585".parse().unwrap()];
+        config.scouting.multicast.set_enabled(Some(false)).unwrap();
+        config.adminspace.permissions.write = true;
+        println!("[LS][01a] Opening router runtime");
+        let router = ztimeout!(Runtime::new(config)).unwrap();
+        AdminSpace::start(
+            &router,
+            PluginsManager::static_plugins_only(),
+            String::from("test"),
+        )
+        .await;
+        let client = open_client("tcp/127.0.0.1:17585").await;
+        let add = format!("@/router/{}/listeners/add", router.zid);
+        let remove = format!("@/router/{}/listeners/remove", router.zid);
+
+        // The operators add and remove the listeners with a PUT of the endpoint
+        println!("[LS][02a] Adding the listener {endpoint}");
+        ztimeout!(client.put(&add, endpoint.as_str()).res_async()).unwrap();
+        task::sleep(SLEEP).await;
+        assert!(router.get_locators().contains(&endpoint.to_locator()));
+
+        println!("[LS][02b] Removing the listener {endpoint}");
+        ztimeout!(client.put(&remove, endpoint.as_str()).res_async()).unwrap();
+        task::sleep(SLEEP).await;
+        assert!(!router.get_locators().contains(&endpoint.to_locator()));
+
+        // An invalid endpoint is ignored
+        println!("[LS][03a] Adding an invalid listener");
+        ztimeout!(client.put(&add, "not an endpoint").res_async()).unwrap();
+        task::sleep(SLEEP).await;
+        assert_eq!(router.get_locators().len(), 1);
+
+        ztimeout!(client.close().res_async()).unwrap();
+        ztimeout!(router.close()).unwrap();
+    });
+}
@@ -0,0 +1,167 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use async_std::prelude::FutureExt;
+use async_std::task;
+use std::sync::Arc;
+use std::time::Duration;
+use zenoh::plugins::PluginsManager;
+use zenoh::prelude::r#async::*;
+use zenoh::runtime::{AdminSpace, Runtime, SampleMutation};
+use zenoh_core::zasync_executor_init;
+use zenoh_protocol::core::WhatAmI;
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_secs(1);
+const REDACTED_ZID: &str = "a1";
+
+macro_rules! ztimeout {
+    ($f:expr) => {
+        $f.timeout(TIMEOUT).await.unwrap()
+    };
+}
+
+struct Upper;
+
+impl SampleMutation for Upper {
+    fn mutate(&self, _key_expr: &keyexpr, payload: &[u8]) -> Option<Vec<u8>> {
+        Some(payload.to_ascii_uppercase())
+    }
+}
+
+async fn open_client(endpoint: &str, id: Option<&str>) -> Session {
+    let mut config = config::client([endpoint.parse::<EndPoint>().unwrap()]);
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    if let Some(id) = id {
+        config.insert_json5("id", &format!("\"{id}\"")).unwrap();
+    }
+    println!("[  ][01b] Opening client session: {endpoint}");
+    ztimeout!(zenoh::open(config).res_async()).unwrap()
+}
+
+async fn recv_values(subscriber: &flume::Receiver<Sample>, count: usize) -> Vec<String> {
+    let mut values = vec![];
+    for _ in 0..count {
+        let sample = ztimeout!(subscriber.recv_async()).unwrap();
+        println!("[  ][  ] Received {sample}");
+        values.push(sample.value.to_string());
+    }
+    values
+}
+
+#[test]
+fn mutations_redacting_face() {
+    task::block_on(async {
+        zasync_executor_init!();
+        let _ = env_logger::try_init();
+
+        let endpoint = "tcp/127.0.0.1:17557";
+
+        let mut config = config::default();
+        config.set_mode(Some(WhatAmI::Router)).unwrap();
+        config.listen.endpoints = vec![endpoint.parse().unwrap()];
+        config.scouting.multicast.set_enabled(Some(false)).unwrap();
+        config
+            .insert_json5(
+                "mutations",
+                &format!(
+                    r#"[
+                        {{ keyexprs: ["test/mutations/secret/**"], zids: ["{REDACTED_ZID}"], mutation: {{ replace: "redacted" }} }},
+                        {{ keyexprs: ["test/mutations/custom/**"], zids: ["{REDACTED_ZID}"], mutation: {{ custom: "upper" }} }},
+                        {{ keyexprs: ["test/mutations/**"], zids: ["{REDACTED_ZID}"], mutation: {{ truncate: 4 }} }},
+                    ]"#
+                ),
+            )
+            .unwrap();
+        println!("[  ][01a] Opening router runtime");
+        let router = ztimeout!(Runtime::new(config)).unwrap();
+        router.add_sample_mutation("upper", Arc::new(Upper));
+        AdminSpace::start(
+            &router,
+            PluginsManager::static_plugins_only(),
+            String::from("test"),
+        )
+        .await;
+        let local_session = ztimeout!(zenoh::init(router.clone()).res_async()).unwrap();
+
+        let redacted_session = open_client(endpoint, Some(REDACTED_ZID)).await;
+        let publisher_session = open_client(endpoint, None).await;
+        let redacted = ztimeout!(redacted_session
+            .declare_subscriber("test/mutations/**")
+            .res_async())
+        .unwrap();
+        let local = ztimeout!(local_session
+            .declare_subscriber("test/mutations/**")
+            .res_async())
+        .unwrap();
+        task::sleep(SLEEP).await;
+
+        println!("[  ][02a] Publishing on the mutated keys");
+        let values = [
+            ("test/mutations/secret/password", "hunter2"),
+            ("test/mutations/custom/name", "alice"),
+            ("test/mutations/log", "connected"),
+        ];
+        for (key_expr, value) in values {
+            ztimeout!(publisher_session.put(key_expr, value).res_async()).unwrap();
+        }
+
+        // The subscriber behind the redacting face receives the mutated payloads
+        println!("[  ][02b] Receiving the mutated values");
+        let received = recv_values(&redacted, values.len()).await;
+        assert_eq!(received, ["redacted", "ALICE", "conn"]);
+
+        // The local subscriber receives the original payloads
+        println!("[  ][02c] Receiving the original values");
+        let received = recv_values(&local, values.len()).await;
+        let expected = values.iter().map(|(_, v)| *v).collect::<Vec<_>>();
+        assert_eq!(received, expected);
+
+        // The mutations are reported by the router
+        println!("[  ][03a] Querying the sessions of the router");
+        let selector = format!("@/router/{}?_stats=true", router.zid);
+        let replies = ztimeout!(publisher_session.get(selector).res_async()).unwrap();
+        let sample = ztimeout!(replies.recv_async()).unwrap().sample.unwrap();
+        let report = serde_json::Value::try_from(&sample.value).unwrap();
+        let session = |zid: ZenohId| {
+            report["sessions"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .find(|s| s["zid"] == zid.to_string())
+                .unwrap()
+                .clone()
+        };
+        assert_eq!(session(redacted_session.zid())["mutated_samples"], 3);
+        assert_eq!(session(publisher_session.zid())["mutated_samples"], 0);
+
+        // The egress statistics count the mutated payloads
+        #[cfg(feature = "stats")]
+        {
+            let sent = "redacted".len() + "ALICE".len() + "conn".len();
+            let original: usize = values.iter().map(|(_, v)| v.len()).sum();
+            let publisher = session(publisher_session.zid());
+            assert_eq!(
+                publisher["stats"]["tx_z_put_pl_bytes"]["user"],
+                sent + original
+            );
+        }
+
+        ztimeout!(redacted.undeclare().res_async()).unwrap();
+        ztimeout!(local.undeclare().res_async()).unwrap();
+        ztimeout!(publisher_session.close().res_async()).unwrap();
+        ztimeout!(redacted_session.close().res_async()).unwrap();
+        ztimeout!(local_session.close().res_async()).unwrap();
+        ztimeout!(router.close()).unwrap();
+    });
+}
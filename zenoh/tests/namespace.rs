@@ -0,0 +1,226 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+#![cfg(feature = "auth_usrpwd")]
+use async_std::prelude::FutureExt;
+use async_std::task;
+use std::time::Duration;
+use zenoh::config::NamespaceConf;
+use zenoh::prelude::r#async::*;
+use zenoh::prelude::sync::SyncResolve;
+use zenoh::runtime::Runtime;
+use zenoh_core::zasync_executor_init;
+use zenoh_protocol::core::WhatAmI;
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_secs(1);
+
+macro_rules! ztimeout {
+    ($f:expr) => {
+        $f.timeout(TIMEOUT).await.unwrap()
+    };
+}
+
+async fn open_client(endpoint: &str, user: &str, namespace: Option<&str>) -> Session {
+    let mut config = config::client([endpoint.parse::<EndPoint>().unwrap()]);
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    config
+        .transport
+        .auth
+        .usrpwd
+        .set_user(Some(user.to_string()))
+        .unwrap();
+    config
+        .transport
+        .auth
+        .usrpwd
+        .set_password(Some(format!("{user}_pwd")))
+        .unwrap();
+    config
+        .set_namespace(namespace.map(|ns| ns.try_into().unwrap()))
+        .unwrap();
+    println!("[  ][01b] Opening client session {user} in {namespace:?}: {endpoint}");
+    ztimeout!(zenoh::open(config).res_async()).unwrap()
+}
+
+#[test]
+fn namespace_isolation() {
+    task::block_on(async {
+        zasync_executor_init!();
+        let _ = env_logger::try_init();
+
+        let endpoint = "tcp/127.0.0.1:17521";
+        let dictionary = std::env::temp_dir().join("zenoh-test-namespace-usrpwd.txt");
+        std::fs::write(
+            &dictionary,
+            "app_a:app_a_pwd\napp_b:app_b_pwd\nobserver:observer_pwd\n",
+        )
+        .unwrap();
+
+        let mut config = config::default();
+        config.set_mode(Some(WhatAmI::Router)).unwrap();
+        config.listen.endpoints = vec![endpoint.parse().unwrap()];
+        config.scouting.multicast.set_enabled(Some(false)).unwrap();
+        config
+            .transport
+            .auth
+            .usrpwd
+            .set_dictionary_file(Some(dictionary.to_string_lossy().to_string()))
+            .unwrap();
+        config
+            .set_namespaces(vec![
+                NamespaceConf {
+                    users: vec!["app_a".to_string()],
+                    namespace: "tenants/app_a".try_into().unwrap(),
+                },
+                NamespaceConf {
+                    users: vec!["app_b".to_string()],
+                    namespace: "tenants/app_b".try_into().unwrap(),
+                },
+            ])
+            .unwrap();
+        println!("[  ][01a] Opening router runtime");
+        let router = ztimeout!(Runtime::new(config)).unwrap();
+
+        let app_a = open_client(endpoint, "app_a", Some("tenants/app_a")).await;
+        let app_b = open_client(endpoint, "app_b", Some("tenants/app_b")).await;
+        let observer = open_client(endpoint, "observer", None).await;
+
+        let sub_a = ztimeout!(app_a.declare_subscriber("**").res_async()).unwrap();
+        let sub_b = ztimeout!(app_b.declare_subscriber("**").res_async()).unwrap();
+        let sub_observer =
+            ztimeout!(observer.declare_subscriber("tenants/**").res_async()).unwrap();
+        task::sleep(SLEEP).await;
+
+        println!("[  ][02a] Publishing from the namespaced sessions");
+        ztimeout!(app_a.put("demo/a", "from a").res_async()).unwrap();
+        ztimeout!(app_b.put("demo/b", "from b").res_async()).unwrap();
+
+        // Each namespaced session only sees its own namespace, without its prefix
+        let sample = ztimeout!(sub_a.recv_async()).unwrap();
+        assert_eq!(sample.key_expr.as_str(), "demo/a");
+        let sample = ztimeout!(sub_b.recv_async()).unwrap();
+        assert_eq!(sample.key_expr.as_str(), "demo/b");
+
+        // The other sessions see the full keys
+        let mut keys = vec![];
+        for _ in 0..2 {
+            let sample = ztimeout!(sub_observer.recv_async()).unwrap();
+            keys.push(sample.key_expr.as_str().to_string());
+        }
+        keys.sort();
+        assert_eq!(keys, ["tenants/app_a/demo/a", "tenants/app_b/demo/b"]);
+
+        println!("[  ][02b] Publishing into a namespace from outside");
+        ztimeout!(observer.put("tenants/app_b/demo/x", "to b").res_async()).unwrap();
+        let sample = ztimeout!(sub_b.recv_async()).unwrap();
+        assert_eq!(sample.key_expr.as_str(), "demo/x");
+        task::sleep(SLEEP).await;
+        assert!(sub_a.try_recv().is_err());
+
+        println!("[  ][03a] Querying across the namespaces");
+        let qabl_a = ztimeout!(app_a
+            .declare_queryable("demo/q")
+            .callback(|query| {
+                let sample = Sample::new(query.key_expr().clone(), "reply from a");
+                query.reply(Ok(sample)).res_sync().unwrap();
+            })
+            .res_async())
+        .unwrap();
+        task::sleep(SLEEP).await;
+
+        let replies = ztimeout!(app_a.get("demo/q").res_async()).unwrap();
+        let reply = ztimeout!(replies.recv_async()).unwrap();
+        assert_eq!(reply.sample.unwrap().key_expr.as_str(), "demo/q");
+
+        let replies = ztimeout!(observer.get("tenants/app_a/demo/q").res_async()).unwrap();
+        let reply = ztimeout!(replies.recv_async()).unwrap();
+        assert_eq!(
+            reply.sample.unwrap().key_expr.as_str(),
+            "tenants/app_a/demo/q"
+        );
+
+        // app_b's view of the same key is its own namespace, where no queryable is
+        let replies = ztimeout!(app_b.get("demo/q").res_async()).unwrap();
+        assert!(ztimeout!(replies.recv_async()).is_err());
+
+        ztimeout!(sub_a.undeclare().res_async()).unwrap();
+        ztimeout!(sub_b.undeclare().res_async()).unwrap();
+        ztimeout!(sub_observer.undeclare().res_async()).unwrap();
+        ztimeout!(qabl_a.undeclare().res_async()).unwrap();
+        ztimeout!(app_a.close().res_async()).unwrap();
+        ztimeout!(app_b.close().res_async()).unwrap();
+        ztimeout!(observer.close().res_async()).unwrap();
+        ztimeout!(router.close()).unwrap();
+        let _ = std::fs::remove_file(dictionary);
+    });
+}
+
+#[test]
+fn namespace_enforced_by_router() {
+    task::block_on(async {
+        zasync_executor_init!();
+        let _ = env_logger::try_init();
+
+        let endpoint = "tcp/127.0.0.1:17522";
+        let dictionary = std::env::temp_dir().join("zenoh-test-namespace-enforced-usrpwd.txt");
+        std::fs::write(&dictionary, "app_a:app_a_pwd\nobserver:observer_pwd\n").unwrap();
+
+        let mut config = config::default();
+        config.set_mode(Some(WhatAmI::Router)).unwrap();
+        config.listen.endpoints = vec![endpoint.parse().unwrap()];
+        config.scouting.multicast.set_enabled(Some(false)).unwrap();
+        config
+            .transport
+            .auth
+            .usrpwd
+            .set_dictionary_file(Some(dictionary.to_string_lossy().to_string()))
+            .unwrap();
+        config
+            .set_namespaces(vec![NamespaceConf {
+                users: vec!["app_a".to_string()],
+                namespace: "tenants/app_a".try_into().unwrap(),
+            }])
+            .unwrap();
+        println!("[  ][01a] Opening router runtime");
+        let router = ztimeout!(Runtime::new(config)).unwrap();
+
+        // app_a authenticates as a tenant but doesn't confine itself
+        let app_a = open_client(endpoint, "app_a", None).await;
+        let observer = open_client(endpoint, "observer", None).await;
+
+        let sub_observer = ztimeout!(observer.declare_subscriber("**").res_async()).unwrap();
+        let sub_a = ztimeout!(app_a.declare_subscriber("tenants/**").res_async()).unwrap();
+        task::sleep(SLEEP).await;
+
+        println!("[  ][02a] Publishing outside and inside the namespace");
+        ztimeout!(app_a.put("tenants/app_b/x", "escaped").res_async()).unwrap();
+        ztimeout!(app_a.put("tenants/app_a/x", "confined").res_async()).unwrap();
+        let sample = ztimeout!(sub_observer.recv_async()).unwrap();
+        assert_eq!(sample.key_expr.as_str(), "tenants/app_a/x");
+
+        // The subscription outside of the namespace was not declared to the router
+        ztimeout!(observer.put("tenants/app_b/y", "hidden").res_async()).unwrap();
+        task::sleep(SLEEP).await;
+        while let Ok(sample) = sub_a.try_recv() {
+            assert_ne!(sample.key_expr.as_str(), "tenants/app_b/y");
+        }
+
+        ztimeout!(sub_a.undeclare().res_async()).unwrap();
+        ztimeout!(sub_observer.undeclare().res_async()).unwrap();
+        ztimeout!(app_a.close().res_async()).unwrap();
+        ztimeout!(observer.close().res_async()).unwrap();
+        ztimeout!(router.close()).unwrap();
+        let _ = std::fs::remove_file(dictionary);
+    });
+}
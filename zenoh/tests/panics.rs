@@ -0,0 +1,152 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use async_std::prelude::FutureExt;
+use async_std::task;
+use std::time::Duration;
+use zenoh::prelude::r#async::*;
+use zenoh::prelude::sync::SyncResolve;
+use zenoh_core::zasync_executor_init;
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const MSG_COUNT: usize = 5;
+
+macro_rules! ztimeout {
+    ($f:expr) => {
+        $f.timeout(TIMEOUT).await.unwrap()
+    };
+}
+
+async fn open_peer(undeclare_after_panics: Option<usize>) -> Session {
+    let mut config = config::peer();
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    if let Some(max) = undeclare_after_panics {
+        config
+            .insert_json5("callbacks/undeclare_after_panics", &max.to_string())
+            .unwrap();
+    }
+    ztimeout!(zenoh::open(config).res_async()).unwrap()
+}
+
+// The panics of the subscribers or queryables of the session, reported in its admin space
+async fn panics(session: &Session, kind: &str) -> Vec<u64> {
+    let replies = ztimeout!(session
+        .get(format!("@/session/{}/{kind}/*", session.zid()))
+        .res_async())
+    .unwrap();
+    let mut panics = vec![];
+    while let Ok(reply) = ztimeout!(replies.recv_async()) {
+        let value: serde_json::Value =
+            serde_json::from_str(&reply.sample.unwrap().value.to_string()).unwrap();
+        panics.push(value["panics"].as_u64().unwrap());
+    }
+    panics.sort();
+    panics
+}
+
+#[test]
+fn panics_subscriber() {
+    task::block_on(async {
+        zasync_executor_init!();
+        let _ = env_logger::try_init();
+
+        let key_expr = "test/panics/subscriber";
+        println!("[PA][01a] Opening peer");
+        let session = open_peer(None).await;
+        let panicking = ztimeout!(session
+            .declare_subscriber(key_expr)
+            .callback(|_| panic!("Panicking subscriber"))
+            .res_async())
+        .unwrap();
+        let healthy = ztimeout!(session.declare_subscriber(key_expr).res_async()).unwrap();
+
+        // The healthy subscriber keeps receiving, the panics being counted
+        println!("[PA][02a] Publishing {MSG_COUNT} samples");
+        for i in 0..MSG_COUNT {
+            ztimeout!(session.put(key_expr, i as u64).res_async()).unwrap();
+        }
+        for i in 0..MSG_COUNT {
+            let sample = ztimeout!(healthy.recv_async()).unwrap();
+            assert_eq!(sample.value.to_string(), i.to_string());
+        }
+        assert_eq!(
+            panics(&session, "subscriber").await,
+            vec![0, MSG_COUNT as u64]
+        );
+
+        ztimeout!(panicking.undeclare().res_async()).unwrap();
+        ztimeout!(healthy.undeclare().res_async()).unwrap();
+        ztimeout!(session.close().res_async()).unwrap();
+    });
+}
+
+#[test]
+fn panics_undeclare() {
+    task::block_on(async {
+        zasync_executor_init!();
+        let _ = env_logger::try_init();
+
+        let key_expr = "test/panics/undeclare";
+        println!("[PA][01a] Opening peer undeclaring after 3 panics");
+        let session = open_peer(Some(3)).await;
+        let panicking = ztimeout!(session
+            .declare_subscriber(key_expr)
+            .callback(|_| panic!("Panicking subscriber"))
+            .res_async())
+        .unwrap();
+        let healthy = ztimeout!(session.declare_subscriber(key_expr).res_async()).unwrap();
+
+        // The panicking subscriber is undeclared after 3 consecutive panics
+        println!("[PA][02a] Publishing {MSG_COUNT} samples");
+        for i in 0..MSG_COUNT {
+            ztimeout!(session.put(key_expr, i as u64).res_async()).unwrap();
+        }
+        for _ in 0..MSG_COUNT {
+            ztimeout!(healthy.recv_async()).unwrap();
+        }
+        assert_eq!(panics(&session, "subscriber").await, vec![0]);
+        // Undeclaring the subscriber again is a no-op
+        ztimeout!(panicking.undeclare().res_async()).unwrap();
+        assert_eq!(panics(&session, "subscriber").await, vec![0]);
+
+        // The queries are answered by the healthy queryables
+        println!("[PA][03a] Querying a panicking and a healthy queryable");
+        let panicking_qabl = ztimeout!(session
+            .declare_queryable(key_expr)
+            .callback(|_| panic!("Panicking queryable"))
+            .res_async())
+        .unwrap();
+        let healthy_qabl = ztimeout!(session
+            .declare_queryable(key_expr)
+            .callback(|query| {
+                query
+                    .reply(Ok(Sample::new(query.key_expr().clone(), "healthy")))
+                    .res_sync()
+                    .unwrap();
+            })
+            .res_async())
+        .unwrap();
+        let replies = ztimeout!(session.get(key_expr).res_async()).unwrap();
+        let mut values = vec![];
+        while let Ok(reply) = ztimeout!(replies.recv_async()) {
+            values.push(reply.sample.unwrap().value.to_string());
+        }
+        assert_eq!(values, vec!["healthy".to_string()]);
+        assert_eq!(panics(&session, "queryable").await, vec![0, 0, 1]);
+
+        ztimeout!(panicking_qabl.undeclare().res_async()).unwrap();
+        ztimeout!(healthy_qabl.undeclare().res_async()).unwrap();
+        ztimeout!(healthy.undeclare().res_async()).unwrap();
+        ztimeout!(session.close().res_async()).unwrap();
+    });
+}
@@ -0,0 +1,135 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+#![cfg(feature = "unstable")]
+use async_std::prelude::FutureExt;
+use async_std::task;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use zenoh::plugins::{
+    Plugin, PluginApi, PluginsManager, Response, RunningPlugin, RunningPluginTrait,
+    ValidationFunction, ZResult,
+};
+use zenoh::prelude::r#async::*;
+use zenoh::runtime::{AdminSpace, Runtime};
+use zenoh_core::{zasync_executor_init, zlock};
+use zenoh_protocol::core::WhatAmI;
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_millis(100);
+
+macro_rules! ztimeout {
+    ($f:expr) => {
+        $f.timeout(TIMEOUT).await.unwrap()
+    };
+}
+
+lazy_static::lazy_static! {
+    // The configurations notified to the probe plugin
+    static ref CHANGES: Mutex<Vec<serde_json::Value>> = Mutex::new(vec![]);
+}
+
+struct ProbePlugin;
+
+impl Plugin for ProbePlugin {
+    type StartArgs = PluginApi;
+    type RunningPlugin = RunningPlugin;
+    const STATIC_NAME: &'static str = "probe";
+
+    fn start(name: &str, api: &PluginApi) -> ZResult<RunningPlugin> {
+        let context = api.plugin(name);
+        let level = context.config().unwrap()["level"].clone();
+        context.set_status(move || serde_json::json!({ "level": level }));
+        context.on_config_change(|config| zlock!(CHANGES).push(config));
+        Ok(Box::new(RunningProbe))
+    }
+}
+
+struct RunningProbe;
+
+impl RunningPluginTrait for RunningProbe {
+    fn config_checker(&self) -> ValidationFunction {
+        Arc::new(|_, _, _| Ok(None))
+    }
+
+    fn adminspace_getter<'a>(
+        &'a self,
+        _selector: &'a Selector<'a>,
+        _plugin_status_key: &str,
+    ) -> ZResult<Vec<Response>> {
+        Ok(vec![])
+    }
+}
+
+#[test]
+fn plugins_api_status_and_config() {
+    task::block_on(async {
+        zasync_executor_init!();
+
+        let mut config = config::peer();
+        config.set_mode(Some(WhatAmI::Router)).unwrap();
+        config.scouting.multicast.set_enabled(Some(false)).unwrap();
+        config
+            .insert_json5("plugins/probe", "{ level: 1 }")
+            .unwrap();
+        let runtime = ztimeout!(Runtime::new(config)).unwrap();
+
+        println!("[  ][01a] Starting the probe plugin");
+        let mut plugins = PluginsManager::static_plugins_only().add_static::<ProbePlugin>();
+        let api = PluginApi::new(runtime.clone());
+        for (name, _, result) in plugins.start_all(&api) {
+            assert!(result.unwrap().is_some(), "{name} failed to start");
+        }
+        AdminSpace::start(&runtime, plugins, String::from("test")).await;
+
+        println!("[  ][02a] Reading the status of the plugin");
+        let session = ztimeout!(zenoh::init(runtime.clone()).res_async()).unwrap();
+        let context = api.plugin("probe");
+        let status_key = format!("{}/status", context.admin_prefix());
+        let replies = ztimeout!(session.get(&status_key).res_async()).unwrap();
+        let sample = ztimeout!(replies.recv_async()).unwrap().sample.unwrap();
+        assert_eq!(sample.key_expr.as_str(), status_key);
+        let status = serde_json::Value::try_from(&sample.value).unwrap();
+        assert_eq!(status["level"], 1);
+
+        println!("[  ][03a] Changing the configuration of the plugin");
+        (&runtime.config)
+            .insert_json5("plugins/probe/level", "2")
+            .unwrap();
+        ztimeout!(async {
+            while zlock!(CHANGES).is_empty() {
+                task::sleep(SLEEP).await;
+            }
+        });
+        assert_eq!(zlock!(CHANGES)[0]["level"], 2);
+        assert_eq!(context.config().unwrap()["level"], 2);
+
+        println!("[  ][04a] Publishing under the prefix of the plugin");
+        let subscriber = ztimeout!(session
+            .declare_subscriber(format!("{}/**", context.admin_prefix()))
+            .res_async())
+        .unwrap();
+        context
+            .publish_admin("event", serde_json::json!({ "kind": "test" }))
+            .unwrap();
+        let sample = ztimeout!(subscriber.recv_async()).unwrap();
+        assert!(sample
+            .key_expr
+            .as_str()
+            .ends_with("/status/plugins/probe/event"));
+
+        ztimeout!(subscriber.undeclare().res_async()).unwrap();
+        ztimeout!(session.close().res_async()).unwrap();
+        ztimeout!(runtime.close()).unwrap();
+    });
+}
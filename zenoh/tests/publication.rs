@@ -0,0 +1,427 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use async_std::io::{ReadExt, WriteExt};
+use async_std::net::{TcpListener, TcpStream};
+use async_std::prelude::FutureExt;
+use async_std::task;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use zenoh::prelude::r#async::*;
+use zenoh::publication::CongestionControl;
+use zenoh::subscriber::Subscriber;
+use zenoh_core::zasync_executor_init;
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_secs(1);
+
+const MSG_SIZE: usize = 32_768;
+const MAX_PUTS: usize = 100_000;
+
+macro_rules! ztimeout {
+    ($f:expr) => {
+        $f.timeout(TIMEOUT).await.unwrap()
+    };
+}
+
+// Forward the bytes read from `from` to `to`, pausing the reads while stalled so that the
+// writes of the sender eventually block.
+async fn forward(mut from: TcpStream, mut to: TcpStream, stalled: Arc<AtomicBool>) {
+    let mut buf = vec![0u8; 65_535];
+    loop {
+        while stalled.load(Ordering::SeqCst) {
+            task::sleep(Duration::from_millis(10)).await;
+        }
+        match from.read(&mut buf).await {
+            Ok(n) if n > 0 => {
+                if to.write_all(&buf[..n]).await.is_err() {
+                    break;
+                }
+            }
+            _ => break,
+        }
+    }
+}
+
+// A TCP proxy forwarding a single connection from `listen` to `server`, whose client to server
+// direction can be stalled.
+async fn proxy(listen: &str, server: &str) -> Arc<AtomicBool> {
+    let stalled = Arc::new(AtomicBool::new(false));
+    let listener = TcpListener::bind(listen).await.unwrap();
+    let server = server.to_string();
+    let c_stalled = stalled.clone();
+    task::spawn(async move {
+        let (client, _) = listener.accept().await.unwrap();
+        let server = TcpStream::connect(server).await.unwrap();
+        task::spawn(forward(client.clone(), server.clone(), c_stalled));
+        forward(server, client, Arc::new(AtomicBool::new(false))).await;
+    });
+    stalled
+}
+
+async fn open_peer(listen: &[&str], connect: &[&str]) -> Session {
+    let mut config = config::peer();
+    config.listen.endpoints = listen.iter().map(|e| e.parse().unwrap()).collect();
+    config.connect.endpoints = connect.iter().map(|e| e.parse().unwrap()).collect();
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    println!("[  ][01a] Opening peer session: {listen:?} {connect:?}");
+    ztimeout!(zenoh::open(config).res_async()).unwrap()
+}
+
+#[test]
+fn publication_report_drops() {
+    task::block_on(async {
+        zasync_executor_init!();
+        let _ = env_logger::try_init();
+
+        // The publisher reaches the subscriber through a proxy
+        let key_expr = "test/publication/drops";
+        let subscriber_session = open_peer(&["tcp/127.0.0.1:17490"], &[]).await;
+        let msgs = Arc::new(AtomicUsize::new(0));
+        let c_msgs = msgs.clone();
+        let subscriber = ztimeout!(subscriber_session
+            .declare_subscriber(key_expr)
+            .reliable()
+            .callback(move |_| {
+                c_msgs.fetch_add(1, Ordering::Relaxed);
+            })
+            .res_async())
+        .unwrap();
+        let stalled = ztimeout!(proxy("127.0.0.1:17491", "127.0.0.1:17490"));
+        let publisher_session = open_peer(&[], &["tcp/127.0.0.1:17491"]).await;
+        task::sleep(SLEEP).await;
+
+        let publisher = ztimeout!(publisher_session
+            .declare_publisher(key_expr)
+            .congestion_control(CongestionControl::Drop)
+            .report_drops(true)
+            .res_async())
+        .unwrap();
+        let payload = vec![0u8; MSG_SIZE];
+        println!("[  ][02a] Publishing on a free link");
+        ztimeout!(publisher.put(payload.clone()).res_async()).unwrap();
+        task::sleep(SLEEP).await;
+        assert_eq!(msgs.load(Ordering::Relaxed), 1);
+
+        // Once the link is congested, the dropped publications are reported
+        println!("[  ][02b] Publishing on a congested link");
+        stalled.store(true, Ordering::SeqCst);
+        let mut dropped = false;
+        for _ in 0..MAX_PUTS {
+            if ztimeout!(publisher.put(payload.clone()).res_async()).is_err() {
+                dropped = true;
+                break;
+            }
+        }
+        assert!(dropped);
+
+        // A dropped publication is never acknowledged
+        #[cfg(feature = "unstable")]
+        {
+            println!("[  ][02c] Waiting for the acknowledgement on a congested link");
+            let res = ztimeout!(publisher_session
+                .put(key_expr, payload.clone())
+                .congestion_control(CongestionControl::Drop)
+                .wait_for_ack()
+                .res_async());
+            assert!(res.is_err());
+        }
+
+        stalled.store(false, Ordering::SeqCst);
+        drop(publisher);
+        ztimeout!(subscriber.undeclare().res_async()).unwrap();
+        ztimeout!(publisher_session.close().res_async()).unwrap();
+        ztimeout!(subscriber_session.close().res_async()).unwrap();
+    });
+}
+
+#[cfg(feature = "unstable")]
+#[test]
+fn publication_strict_destination() {
+    task::block_on(async {
+        zasync_executor_init!();
+        let _ = env_logger::try_init();
+
+        let key_expr = "test/publication/strict";
+        let publisher_session = open_peer(&["tcp/127.0.0.1:17492"], &[]).await;
+
+        // No route to a remote subscriber
+        println!("[  ][02a] Publishing without remote route");
+        let put = || {
+            publisher_session
+                .put(key_expr, "value")
+                .allowed_destination(Locality::Remote)
+        };
+        assert!(ztimeout!(put().res_async()).is_ok());
+        assert!(ztimeout!(put().strict_destination(true).res_async()).is_err());
+
+        // A local subscriber doesn't provide a remote route
+        let local = ztimeout!(publisher_session
+            .declare_subscriber(key_expr)
+            .callback(|_| {})
+            .res_async())
+        .unwrap();
+        assert!(ztimeout!(put().strict_destination(true).res_async()).is_err());
+
+        println!("[  ][02b] Publishing with a remote route");
+        let subscriber_session = open_peer(&[], &["tcp/127.0.0.1:17492"]).await;
+        let subscriber = ztimeout!(subscriber_session
+            .declare_subscriber(key_expr)
+            .reliable()
+            .callback(|_| {})
+            .res_async())
+        .unwrap();
+        task::sleep(SLEEP).await;
+        ztimeout!(put().strict_destination(true).res_async()).unwrap();
+
+        // The acknowledgement of the first hop completes the resolution
+        println!("[  ][02c] Waiting for the acknowledgement of the first hop");
+        ztimeout!(put().wait_for_ack().res_async()).unwrap();
+
+        ztimeout!(local.undeclare().res_async()).unwrap();
+        ztimeout!(subscriber.undeclare().res_async()).unwrap();
+        ztimeout!(subscriber_session.close().res_async()).unwrap();
+        ztimeout!(publisher_session.close().res_async()).unwrap();
+    });
+}
+
+#[cfg(feature = "unstable")]
+#[test]
+fn publication_session_closed() {
+    use zenoh::runtime::Runtime;
+
+    task::block_on(async {
+        zasync_executor_init!();
+        let _ = env_logger::try_init();
+
+        let mut config = config::peer();
+        config.scouting.multicast.set_enabled(Some(false)).unwrap();
+        let runtime = ztimeout!(Runtime::new(config)).unwrap();
+        let session = ztimeout!(zenoh::init(runtime.clone()).res_async()).unwrap();
+        let key_expr = "test/publication/closed";
+        let publisher = ztimeout!(session.declare_publisher(key_expr).res_async()).unwrap();
+        ztimeout!(publisher.put("value").res_async()).unwrap();
+
+        println!("[  ][02a] Publishing on a closed session");
+        ztimeout!(runtime.close()).unwrap();
+        assert!(ztimeout!(session.put(key_expr, "value").res_async()).is_err());
+        assert!(ztimeout!(publisher.put("value").res_async()).is_err());
+    });
+}
+
+async fn declare_counter<'a>(
+    session: &'a Session,
+    key_expr: &str,
+) -> (Subscriber<'a, ()>, Arc<AtomicUsize>) {
+    let msgs = Arc::new(AtomicUsize::new(0));
+    let c_msgs = msgs.clone();
+    let subscriber = ztimeout!(session
+        .declare_subscriber(key_expr)
+        .callback(move |_| {
+            c_msgs.fetch_add(1, Ordering::Relaxed);
+        })
+        .res_async())
+    .unwrap();
+    (subscriber, msgs)
+}
+
+#[test]
+fn publication_no_local() {
+    task::block_on(async {
+        zasync_executor_init!();
+        let _ = env_logger::try_init();
+
+        let key_expr = "test/publication/no_local";
+        let session = open_peer(&["tcp/127.0.0.1:17514"], &[]).await;
+        let remote_session = open_peer(&[], &["tcp/127.0.0.1:17514"]).await;
+        let (local, local_msgs) = declare_counter(&session, key_expr).await;
+        let (remote, remote_msgs) = declare_counter(&remote_session, key_expr).await;
+        task::sleep(SLEEP).await;
+
+        println!("[  ][02a] Publishing without no_local");
+        let publisher = ztimeout!(session.declare_publisher(key_expr).res_async()).unwrap();
+        ztimeout!(publisher.put("value").res_async()).unwrap();
+        task::sleep(SLEEP).await;
+        assert_eq!(local_msgs.load(Ordering::Relaxed), 1);
+        assert_eq!(remote_msgs.load(Ordering::Relaxed), 1);
+
+        // The subscribers of the same session skip the publications
+        println!("[  ][02b] Publishing with no_local");
+        let publisher = publisher.no_local(true);
+        ztimeout!(publisher.put("value").res_async()).unwrap();
+        ztimeout!(session.put(key_expr, "value").no_local(true).res_async()).unwrap();
+        task::sleep(SLEEP).await;
+        assert_eq!(local_msgs.load(Ordering::Relaxed), 1);
+        assert_eq!(remote_msgs.load(Ordering::Relaxed), 3);
+
+        drop(publisher);
+        ztimeout!(remote.undeclare().res_async()).unwrap();
+        ztimeout!(local.undeclare().res_async()).unwrap();
+        ztimeout!(remote_session.close().res_async()).unwrap();
+        ztimeout!(session.close().res_async()).unwrap();
+    });
+}
+
+#[test]
+fn publication_local_routing() {
+    task::block_on(async {
+        zasync_executor_init!();
+        let _ = env_logger::try_init();
+
+        let key_expr = "test/publication/local_routing";
+        let mut config = config::peer();
+        config.listen.endpoints = vec!["tcp/127.0.0.1:17515".parse().unwrap()];
+        config.scouting.multicast.set_enabled(Some(false)).unwrap();
+        config.set_local_routing(Some(false)).unwrap();
+        let session = ztimeout!(zenoh::open(config).res_async()).unwrap();
+        let remote_session = open_peer(&[], &["tcp/127.0.0.1:17515"]).await;
+        let (local, local_msgs) = declare_counter(&session, key_expr).await;
+        let (remote, remote_msgs) = declare_counter(&remote_session, key_expr).await;
+        task::sleep(SLEEP).await;
+
+        // The publications are only sent to the network
+        println!("[  ][02a] Publishing without local routing");
+        let publisher = ztimeout!(session.declare_publisher(key_expr).res_async()).unwrap();
+        ztimeout!(publisher.put("value").res_async()).unwrap();
+        ztimeout!(session.put(key_expr, "value").res_async()).unwrap();
+        task::sleep(SLEEP).await;
+        assert_eq!(local_msgs.load(Ordering::Relaxed), 0);
+        assert_eq!(remote_msgs.load(Ordering::Relaxed), 2);
+
+        drop(publisher);
+        ztimeout!(remote.undeclare().res_async()).unwrap();
+        ztimeout!(local.undeclare().res_async()).unwrap();
+        ztimeout!(remote_session.close().res_async()).unwrap();
+        ztimeout!(session.close().res_async()).unwrap();
+    });
+}
+
+// Polls `condition` until it holds.
+#[cfg(feature = "unstable")]
+async fn wait_until(condition: impl Fn() -> bool) {
+    while !condition() {
+        task::sleep(Duration::from_millis(10)).await;
+    }
+}
+
+#[cfg(feature = "unstable")]
+#[test]
+fn publication_periodic() {
+    use zenoh::runtime::Runtime;
+    use zenoh_util::clock::TestClock;
+
+    const PERIOD: Duration = Duration::from_secs(1);
+    const PAYLOAD_SIZE: usize = 4 * 1024 * 1024;
+    const MAX_TICKS: usize = 20;
+
+    task::block_on(async {
+        zasync_executor_init!();
+        let _ = env_logger::try_init();
+
+        // The periodic publisher reaches the subscriber through a proxy
+        let key_expr = "test/publication/periodic";
+        let subscriber_session = open_peer(&["tcp/127.0.0.1:17532"], &[]).await;
+        let (subscriber, msgs) = declare_counter(&subscriber_session, key_expr).await;
+        let stalled = ztimeout!(proxy("127.0.0.1:17533", "127.0.0.1:17532"));
+
+        let clock = TestClock::new();
+        let mut config = config::peer();
+        config.connect.endpoints = vec!["tcp/127.0.0.1:17533".parse().unwrap()];
+        config.scouting.multicast.set_enabled(Some(false)).unwrap();
+        println!("[  ][01a] Opening peer session driven by a test clock");
+        let runtime = ztimeout!(Runtime::new_with_clock(config, Arc::new(clock.clone()))).unwrap();
+        let session = ztimeout!(zenoh::init(runtime).res_async()).unwrap();
+        task::sleep(SLEEP).await;
+
+        let publisher = ztimeout!(session
+            .declare_publisher(key_expr)
+            .congestion_control(CongestionControl::Block)
+            .res_async())
+        .unwrap();
+        let periodic = publisher.publish_periodically(PERIOD, || vec![0u8; PAYLOAD_SIZE].into());
+
+        println!("[  ][02a] Publishing at each tick");
+        for i in 1..=3 {
+            clock.advance(PERIOD);
+            ztimeout!(wait_until(|| periodic.published() == i));
+        }
+        task::sleep(SLEEP).await;
+        assert_eq!(msgs.load(Ordering::Relaxed), 3);
+        assert_eq!(periodic.skipped(), 0);
+
+        println!("[  ][02b] Changing the period");
+        periodic.set_period(2 * PERIOD);
+        clock.advance(PERIOD);
+        task::sleep(SLEEP).await;
+        assert_eq!(periodic.published(), 3);
+        clock.advance(PERIOD);
+        ztimeout!(wait_until(|| periodic.published() == 4));
+
+        // A late timer doesn't burst to catch up with the missed ticks
+        println!("[  ][02c] Advancing the clock by several periods");
+        clock.advance(6 * PERIOD);
+        ztimeout!(wait_until(|| periodic.published() == 5));
+        task::sleep(SLEEP).await;
+        assert_eq!(periodic.published(), 5);
+        assert_eq!(periodic.skipped(), 2);
+
+        // Once the link is congested, a publication blocks and the next ticks are skipped
+        println!("[  ][03a] Publishing on a congested link");
+        periodic.set_period(PERIOD);
+        stalled.store(true, Ordering::SeqCst);
+        let mut blocked = false;
+        for _ in 0..MAX_TICKS {
+            let published = periodic.published();
+            clock.advance(PERIOD);
+            let ticked = wait_until(|| periodic.published() > published)
+                .timeout(Duration::from_millis(500))
+                .await;
+            if ticked.is_err() {
+                blocked = true;
+                break;
+            }
+        }
+        assert!(blocked);
+        let published = periodic.published();
+        let skipped = periodic.skipped();
+        for i in 1..=2 {
+            clock.advance(PERIOD);
+            ztimeout!(wait_until(|| periodic.skipped() == skipped + i));
+        }
+        assert_eq!(periodic.published(), published);
+
+        // The blocked publication completes once the link is free, without burst
+        println!("[  ][03b] Freeing the link");
+        stalled.store(false, Ordering::SeqCst);
+        ztimeout!(wait_until(|| periodic.published() == published + 1));
+        clock.advance(PERIOD);
+        ztimeout!(wait_until(|| periodic.published() == published + 2));
+        assert_eq!(periodic.skipped(), skipped + 2);
+
+        // Dropping the periodic publication stops it
+        println!("[  ][04a] Dropping the periodic publication");
+        drop(periodic);
+        clock.advance(PERIOD);
+        task::sleep(SLEEP).await;
+        let received = msgs.load(Ordering::Relaxed);
+        clock.advance(PERIOD);
+        task::sleep(SLEEP).await;
+        assert_eq!(msgs.load(Ordering::Relaxed), received);
+
+        drop(publisher);
+        ztimeout!(subscriber.undeclare().res_async()).unwrap();
+        ztimeout!(session.close().res_async()).unwrap();
+        ztimeout!(subscriber_session.close().res_async()).unwrap();
+    });
+}
@@ -0,0 +1,80 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use async_std::prelude::FutureExt;
+use async_std::task;
+use std::time::Duration;
+use zenoh::prelude::r#async::*;
+use zenoh::publication::{CongestionControl, Priority};
+use zenoh_core::zasync_executor_init;
+use zenoh_protocol::core::WhatAmI;
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_secs(1);
+
+macro_rules! ztimeout {
+    ($f:expr) => {
+        $f.timeout(TIMEOUT).await.unwrap()
+    };
+}
+
+#[test]
+fn qos_forwarded_priority() {
+    task::block_on(async {
+        zasync_executor_init!();
+        let _ = env_logger::try_init();
+
+        let endpoint = "tcp/127.0.0.1:17517";
+        let key_expr = "test/qos/priority";
+
+        // The publisher and the subscriber reach each other through a router, QoS being
+        // enabled on both transports
+        let mut config = config::default();
+        config.set_mode(Some(WhatAmI::Router)).unwrap();
+        config.listen.endpoints = vec![endpoint.parse().unwrap()];
+        config.scouting.multicast.set_enabled(Some(false)).unwrap();
+        println!("[  ][01a] Opening router session");
+        let router = ztimeout!(zenoh::open(config).res_async()).unwrap();
+
+        let client = || {
+            let mut config = config::client([endpoint.parse::<EndPoint>().unwrap()]);
+            config.scouting.multicast.set_enabled(Some(false)).unwrap();
+            config
+        };
+        println!("[  ][01b] Opening client sessions");
+        let subscriber_session = ztimeout!(zenoh::open(client()).res_async()).unwrap();
+        let publisher_session = ztimeout!(zenoh::open(client()).res_async()).unwrap();
+
+        let subscriber =
+            ztimeout!(subscriber_session.declare_subscriber(key_expr).res_async()).unwrap();
+        task::sleep(SLEEP).await;
+
+        println!("[  ][02a] Publishing with a RealTime priority");
+        ztimeout!(publisher_session
+            .put(key_expr, "data")
+            .priority(Priority::RealTime)
+            .congestion_control(CongestionControl::Block)
+            .res_async())
+        .unwrap();
+
+        let sample = ztimeout!(subscriber.recv_async()).unwrap();
+        println!("[  ][02b] Received {} with {:?}", sample, sample.qos());
+        assert_eq!(sample.qos().priority(), Priority::RealTime);
+        assert_eq!(sample.qos().congestion_control(), CongestionControl::Block);
+
+        ztimeout!(subscriber.undeclare().res_async()).unwrap();
+        ztimeout!(publisher_session.close().res_async()).unwrap();
+        ztimeout!(subscriber_session.close().res_async()).unwrap();
+        ztimeout!(router.close().res_async()).unwrap();
+    });
+}
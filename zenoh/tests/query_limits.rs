@@ -0,0 +1,189 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use async_std::prelude::FutureExt;
+use async_std::task;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use zenoh::prelude::r#async::*;
+use zenoh::prelude::sync::SyncResolve;
+use zenoh::query::Reply;
+use zenoh::queryable::{Query, Queryable};
+use zenoh_core::zasync_executor_init;
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_secs(1);
+const QUERY_TIMEOUT: Duration = Duration::from_secs(30);
+const PER_FACE: usize = 10;
+const KEY_EXPR: &str = "test/query_limits/**";
+const SLOW: &str = "test/query_limits/slow";
+const FAST: &str = "test/query_limits/fast";
+
+macro_rules! ztimeout {
+    ($f:expr) => {
+        $f.timeout(TIMEOUT).await.unwrap()
+    };
+}
+
+fn make_config(mode: WhatAmI, listen: &[&str], connect: &[&str]) -> Config {
+    let mut config = config::peer();
+    config.set_mode(Some(mode)).unwrap();
+    config.listen.endpoints = listen.iter().map(|e| e.parse().unwrap()).collect();
+    config.connect.endpoints = connect.iter().map(|e| e.parse().unwrap()).collect();
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    config
+}
+
+async fn open_router(endpoint: &str, policy: &str) -> Session {
+    let mut config = make_config(WhatAmI::Router, &[endpoint], &[]);
+    let limits = &mut config.routing.query.limits;
+    limits.set_per_face(Some(PER_FACE)).unwrap();
+    limits.set_total(Some(10 * PER_FACE)).unwrap();
+    limits.set_policy(Some(policy.to_string())).unwrap();
+    ztimeout!(zenoh::open(config).res_async()).unwrap()
+}
+
+async fn open_client(endpoint: &str) -> Session {
+    let config = make_config(WhatAmI::Client, &[], &[endpoint]);
+    ztimeout!(zenoh::open(config).res_async()).unwrap()
+}
+
+// A storage holding the queries on `SLOW` until they are released, replying right away to the others
+async fn open_storage(session: &Session, held: Arc<Mutex<Vec<Query>>>) -> Queryable<'_, ()> {
+    ztimeout!(session
+        .declare_queryable(KEY_EXPR)
+        .callback(move |query| {
+            if query.key_expr().as_str() == SLOW {
+                held.lock().unwrap().push(query);
+            } else {
+                let sample = Sample::new(query.key_expr().clone(), "fast");
+                query.reply(Ok(sample)).res_sync().unwrap();
+            }
+        })
+        .res_async())
+    .unwrap()
+}
+
+async fn flood(session: &Session, count: usize) -> Vec<flume::Receiver<Reply>> {
+    let mut receivers = vec![];
+    for _ in 0..count {
+        let receiver = ztimeout!(session
+            .get(SLOW)
+            .consolidation(ConsolidationMode::None)
+            .timeout(QUERY_TIMEOUT)
+            .res_async())
+        .unwrap();
+        receivers.push(receiver);
+    }
+    receivers
+}
+
+// Asserts that the query of the receiver failed fast with an error reply from the router
+async fn assert_rejected(receiver: &flume::Receiver<Reply>, router: &Session) {
+    let now = Instant::now();
+    let reply = ztimeout!(receiver.recv_async()).unwrap();
+    println!("[QL][  ] Rejected: {reply:?}");
+    assert!(reply.sample.is_err());
+    assert_eq!(reply.replier_id(), router.zid());
+    assert!(ztimeout!(receiver.recv_async()).is_err());
+    assert!(now.elapsed() < QUERY_TIMEOUT);
+}
+
+// Asserts that the query of another face still succeeds
+async fn assert_fast_query(session: &Session) {
+    let replies = ztimeout!(session
+        .get(FAST)
+        .consolidation(ConsolidationMode::None)
+        .timeout(QUERY_TIMEOUT)
+        .res_async())
+    .unwrap();
+    let reply = ztimeout!(replies.recv_async()).unwrap();
+    println!("[QL][  ] Fast reply: {reply:?}");
+    assert_eq!(reply.sample.unwrap().value.to_string(), "fast");
+    assert!(ztimeout!(replies.recv_async()).is_err());
+}
+
+async fn query_limits(endpoint: &str, policy: &str) {
+    println!("[QL][01a] Opening the router with the {policy} policy");
+    let router = open_router(endpoint, policy).await;
+    println!("[QL][01b] Opening the client sessions");
+    let storage = open_client(endpoint).await;
+    let flooder = open_client(endpoint).await;
+    let querier = open_client(endpoint).await;
+
+    let held = Arc::new(Mutex::new(vec![]));
+    let queryable = open_storage(&storage, held.clone()).await;
+    task::sleep(SLEEP).await;
+
+    // One face floods the router with queries which are never answered
+    println!("[QL][02a] Flooding {} queries", PER_FACE + 5);
+    let receivers = flood(&flooder, PER_FACE + 5).await;
+    task::sleep(SLEEP).await;
+
+    let (failed, pending, routed) = match policy {
+        // The queries beyond the limit are rejected
+        "reject" => (&receivers[PER_FACE..], &receivers[..PER_FACE], PER_FACE),
+        // The oldest queries are evicted by the new ones
+        _ => (&receivers[..5], &receivers[5..], PER_FACE + 5),
+    };
+    println!("[QL][02b] Checking the failed queries");
+    for receiver in failed {
+        assert_rejected(receiver, &router).await;
+    }
+    println!("[QL][02c] Checking the pending queries");
+    for receiver in pending {
+        assert!(receiver.try_recv().is_err());
+        assert!(!receiver.is_disconnected());
+    }
+    assert_eq!(held.lock().unwrap().len(), routed);
+
+    // The queries of another face still succeed
+    println!("[QL][03a] Querying from another face");
+    assert_fast_query(&querier).await;
+
+    // The pending queries are answered once released
+    println!("[QL][04a] Releasing the held queries");
+    held.lock().unwrap().clear();
+    for receiver in pending {
+        assert!(ztimeout!(receiver.recv_async()).is_err());
+    }
+
+    // The flooding face can query again
+    println!("[QL][04b] Querying from the flooding face");
+    assert_fast_query(&flooder).await;
+
+    println!("[QL][05a] Closing sessions");
+    drop(queryable);
+    ztimeout!(querier.close().res_async()).unwrap();
+    ztimeout!(flooder.close().res_async()).unwrap();
+    ztimeout!(storage.close().res_async()).unwrap();
+    ztimeout!(router.close().res_async()).unwrap();
+}
+
+#[test]
+fn query_limits_reject() {
+    task::block_on(async {
+        zasync_executor_init!();
+        let _ = env_logger::try_init();
+        query_limits("tcp/127.0.0.1:17555", "reject").await;
+    });
+}
+
+#[test]
+fn query_limits_evict_oldest() {
+    task::block_on(async {
+        zasync_executor_init!();
+        let _ = env_logger::try_init();
+        query_limits("tcp/127.0.0.1:17556", "evict_oldest").await;
+    });
+}
@@ -0,0 +1,203 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use async_std::prelude::FutureExt;
+use async_std::task;
+use std::sync::Arc;
+use std::time::Duration;
+use zenoh::plugins::PluginsManager;
+use zenoh::prelude::r#async::*;
+use zenoh::prelude::sync::SyncResolve;
+use zenoh::query::Reply;
+use zenoh::runtime::{AdminSpace, Runtime};
+use zenoh_core::zasync_executor_init;
+use zenoh_protocol::core::WhatAmI;
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_secs(1);
+const RATE: u64 = 5;
+const FLOOD: usize = 30;
+const POLITE: usize = 5;
+const POLITE_PERIOD: Duration = Duration::from_millis(300);
+const STORAGE: &str = "test/query_throttle/storage";
+const ERROR: &str = "test/query_throttle/error";
+
+macro_rules! ztimeout {
+    ($f:expr) => {
+        $f.timeout(TIMEOUT).await.unwrap()
+    };
+}
+
+async fn open_client(endpoint: &str) -> Session {
+    let mut config = config::client([endpoint.parse::<EndPoint>().unwrap()]);
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    ztimeout!(zenoh::open(config).res_async()).unwrap()
+}
+
+// Returns all the replies of a query on `key_expr`
+async fn get(session: &Session, key_expr: &str) -> Vec<Reply> {
+    let receiver = ztimeout!(session.get(key_expr).res_async()).unwrap();
+    let mut replies = vec![];
+    while let Ok(reply) = ztimeout!(receiver.recv_async()) {
+        replies.push(reply);
+    }
+    replies
+}
+
+#[test]
+fn query_throttle_subjects() {
+    task::block_on(async {
+        zasync_executor_init!();
+        let _ = env_logger::try_init();
+
+        let endpoint = "tcp/127.0.0.1:17563";
+
+        let mut config = config::default();
+        config.set_mode(Some(WhatAmI::Router)).unwrap();
+        config.listen.endpoints = vec![endpoint.parse().unwrap()];
+        config.scouting.multicast.set_enabled(Some(false)).unwrap();
+        config
+            .insert_json5(
+                "routing/query/throttle",
+                &format!(
+                    r#"{{
+                        enabled: true,
+                        rules: [ {{ keyexprs: ["test/query_throttle/**"], rate: {RATE}, burst: {RATE} }} ],
+                    }}"#
+                ),
+            )
+            .unwrap();
+        println!("[QT][01a] Opening router runtime");
+        let router = ztimeout!(Runtime::new(config)).unwrap();
+        AdminSpace::start(
+            &router,
+            PluginsManager::static_plugins_only(),
+            String::from("test"),
+        )
+        .await;
+        let storage_session = ztimeout!(zenoh::init(router.clone()).res_async()).unwrap();
+        let storage = ztimeout!(storage_session
+            .declare_queryable("test/query_throttle/**")
+            .callback(|query| {
+                let reply = if query.key_expr().as_str() == ERROR {
+                    Err("application error".into())
+                } else {
+                    Ok(Sample::new(query.key_expr().clone(), "stored"))
+                };
+                query.reply(reply).res_sync().unwrap();
+            })
+            .res_async())
+        .unwrap();
+
+        println!("[QT][01b] Opening the client sessions");
+        let flooder = Arc::new(open_client(endpoint).await);
+        let polite = Arc::new(open_client(endpoint).await);
+        task::sleep(SLEEP).await;
+
+        // The polite client queries under the rate while the flooder queries beyond it
+        println!("[QT][02a] Querying concurrently");
+        let polite_task = task::spawn({
+            let polite = polite.clone();
+            async move {
+                let mut replies = vec![];
+                for _ in 0..POLITE {
+                    replies.extend(get(&polite, STORAGE).await);
+                    task::sleep(POLITE_PERIOD).await;
+                }
+                replies
+            }
+        });
+        let mut receivers = vec![];
+        for _ in 0..FLOOD {
+            receivers.push(ztimeout!(flooder.get(STORAGE).res_async()).unwrap());
+        }
+        let mut stored = 0;
+        let mut throttled = 0;
+        for receiver in receivers {
+            while let Ok(reply) = ztimeout!(receiver.recv_async()) {
+                if reply.is_throttled() {
+                    // The error comes from the router, with a hint not beyond a token
+                    assert_eq!(reply.replier_id(), router.zid);
+                    let retry_after = reply.retry_after().unwrap();
+                    assert!(retry_after <= Duration::from_millis(1000 / RATE));
+                    assert!(reply.sample.is_err());
+                    throttled += 1;
+                } else {
+                    assert_eq!(reply.sample.unwrap().value.to_string(), "stored");
+                    stored += 1;
+                }
+            }
+        }
+        println!("[QT][02b] Flooder: {stored} stored and {throttled} throttled replies");
+        assert_eq!(stored + throttled, FLOOD);
+        assert!(stored >= RATE as usize);
+        assert!(throttled >= FLOOD / 2);
+
+        println!("[QT][02c] Polite client");
+        let replies = ztimeout!(polite_task);
+        assert_eq!(replies.len(), POLITE);
+        for reply in replies {
+            assert!(!reply.is_throttled());
+            assert_eq!(reply.sample.unwrap().value.to_string(), "stored");
+        }
+
+        // An error of the application isn't mistaken for a throttling
+        println!("[QT][03a] Application error");
+        let replies = get(&polite, ERROR).await;
+        assert_eq!(replies.len(), 1);
+        assert!(!replies[0].is_throttled());
+        assert!(replies[0].retry_after().is_none());
+        assert_eq!(
+            replies[0].sample.as_ref().unwrap_err().to_string(),
+            "application error"
+        );
+
+        // The flooder is admitted again once it waited
+        println!("[QT][03b] Retrying after the hint");
+        task::sleep(SLEEP).await;
+        let replies = get(&flooder, STORAGE).await;
+        assert_eq!(replies.len(), 1);
+        assert!(!replies[0].is_throttled());
+        assert!(replies[0].sample.is_ok());
+
+        // The counters of each client are reported by the router
+        println!("[QT][04a] Querying the sessions of the router");
+        let selector = format!("@/router/{}", router.zid);
+        let replies = get(&polite, &selector).await;
+        let report =
+            serde_json::Value::try_from(&replies[0].sample.as_ref().unwrap().value).unwrap();
+        let counters = |zid: ZenohId| {
+            report["sessions"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .find(|s| s["zid"] == zid.to_string())
+                .unwrap()["query_throttle"]
+                .clone()
+        };
+        println!("[QT][04b] Report: {report}");
+        let flooder_counters = counters(flooder.zid());
+        assert_eq!(flooder_counters["admitted"], stored + 1);
+        assert_eq!(flooder_counters["throttled"], throttled);
+        let polite_counters = counters(polite.zid());
+        assert_eq!(polite_counters["admitted"], POLITE + 1);
+        assert_eq!(polite_counters["throttled"], 0);
+
+        println!("[QT][05a] Closing the sessions");
+        ztimeout!(storage.undeclare().res_async()).unwrap();
+        ztimeout!(Arc::try_unwrap(flooder).unwrap().close().res_async()).unwrap();
+        ztimeout!(Arc::try_unwrap(polite).unwrap().close().res_async()).unwrap();
+        ztimeout!(storage_session.close().res_async()).unwrap();
+        ztimeout!(router.close()).unwrap();
+    });
+}
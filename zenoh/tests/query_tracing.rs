@@ -0,0 +1,126 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use async_std::prelude::FutureExt;
+use async_std::task;
+use std::time::Duration;
+use zenoh::plugins::PluginsManager;
+use zenoh::prelude::r#async::*;
+use zenoh::prelude::sync::SyncResolve;
+use zenoh::runtime::{AdminSpace, Runtime};
+use zenoh_core::zasync_executor_init;
+use zenoh_protocol::core::WhatAmI;
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_secs(1);
+
+macro_rules! ztimeout {
+    ($f:expr) => {
+        $f.timeout(TIMEOUT).await.unwrap()
+    };
+}
+
+async fn open_router(listen: &[&str], connect: &[&str], tracing: bool) -> Session {
+    let mut config = config::default();
+    config.set_mode(Some(WhatAmI::Router)).unwrap();
+    config.listen.endpoints = listen.iter().map(|e| e.parse().unwrap()).collect();
+    config.connect.endpoints = connect.iter().map(|e| e.parse().unwrap()).collect();
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    config
+        .routing
+        .query_tracing
+        .set_enabled(Some(tracing))
+        .unwrap();
+    println!("[  ][01a] Opening router session: {listen:?} {connect:?}");
+    // The traces are read from the admin space of the router
+    let runtime = ztimeout!(Runtime::new(config)).unwrap();
+    AdminSpace::start(
+        &runtime,
+        PluginsManager::static_plugins_only(),
+        String::from("test"),
+    )
+    .await;
+    ztimeout!(zenoh::init(runtime).res_async()).unwrap()
+}
+
+fn zids(faces: &serde_json::Value) -> Vec<&str> {
+    faces
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|face| face["zid"].as_str().unwrap())
+        .collect()
+}
+
+#[test]
+fn query_tracing_across_routers() {
+    task::block_on(async {
+        zasync_executor_init!();
+        let _ = env_logger::try_init();
+
+        let endpoint = "tcp/127.0.0.1:17531";
+        let middle = open_router(&[endpoint], &[], true).await;
+        // The edge routers listen on their own ports, not on the default one
+        let edge_a = open_router(&["tcp/127.0.0.1:17536"], &[endpoint], false).await;
+        let edge_b = open_router(&["tcp/127.0.0.1:17537"], &[endpoint], false).await;
+
+        let key_expr = "test/tracing/q";
+        let queryable = ztimeout!(edge_b
+            .declare_queryable(key_expr)
+            .callback(|query| {
+                let sample = Sample::new(query.key_expr().clone(), "reply");
+                query.reply(Ok(sample)).res_sync().unwrap();
+            })
+            .res_async())
+        .unwrap();
+        task::sleep(SLEEP).await;
+
+        println!("[  ][02a] Querying across the middle router");
+        let replies = ztimeout!(edge_a.get(key_expr).res_async()).unwrap();
+        assert!(ztimeout!(replies.recv_async()).unwrap().sample.is_ok());
+        assert!(ztimeout!(replies.recv_async()).is_err());
+
+        println!("[  ][03a] Reading the traces of the middle router");
+        let traces_key = format!("@/router/{}/debug/queries", middle.zid());
+        let replies = ztimeout!(middle.get(traces_key).res_async()).unwrap();
+        let sample = ztimeout!(replies.recv_async()).unwrap().sample.unwrap();
+        let traces = serde_json::Value::try_from(&sample.value).unwrap();
+        let trace = traces
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|t| t["key_expr"] == key_expr)
+            .unwrap();
+
+        let zid_a = edge_a.zid().to_string();
+        let zid_b = edge_b.zid().to_string();
+        // The correlation id is the one assigned by the querier
+        assert_eq!(trace["correlation"]["zid"], zid_a.as_str());
+        assert_eq!(trace["source"]["zid"], zid_a.as_str());
+        assert_eq!(zids(&trace["targets"]), [zid_b.as_str()]);
+        assert_eq!(zids(&trace["replies"]), [zid_b.as_str()]);
+        assert_eq!(trace["replies"][0]["count"], 1);
+        for excluded in trace["excluded"].as_array().unwrap() {
+            if excluded["zid"] == zid_a.as_str() {
+                assert_eq!(excluded["reason"], "source");
+            } else {
+                assert_eq!(excluded["reason"], "no_intersection");
+            }
+        }
+
+        ztimeout!(queryable.undeclare().res_async()).unwrap();
+        ztimeout!(edge_a.close().res_async()).unwrap();
+        ztimeout!(edge_b.close().res_async()).unwrap();
+        ztimeout!(middle.close().res_async()).unwrap();
+    });
+}
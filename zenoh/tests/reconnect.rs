@@ -0,0 +1,141 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use async_std::prelude::FutureExt;
+use async_std::task;
+use std::time::Duration;
+use zenoh::prelude::r#async::*;
+use zenoh::prelude::sync::SyncResolve;
+use zenoh::publication::CongestionControl;
+use zenoh_core::zasync_executor_init;
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_millis(500);
+
+macro_rules! ztimeout {
+    ($f:expr) => {
+        $f.timeout(TIMEOUT).await.unwrap()
+    };
+}
+
+async fn open_router(endpoint: &str) -> Session {
+    let mut config = config::default();
+    config.set_mode(Some(WhatAmI::Router)).unwrap();
+    config.listen.endpoints = vec![endpoint.parse().unwrap()];
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    ztimeout!(zenoh::open(config).res_async()).unwrap()
+}
+
+async fn open_client(endpoint: &str) -> Session {
+    let mut config = config::client([endpoint.parse::<EndPoint>().unwrap()]);
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    config
+        .insert_json5("connect/retry/period_init", "100")
+        .unwrap();
+    config
+        .insert_json5("connect/retry/period_max", "500")
+        .unwrap();
+    ztimeout!(zenoh::open(config).res_async()).unwrap()
+}
+
+async fn routers(session: &Session) -> Vec<ZenohId> {
+    session.info().routers_zid().res_async().await.collect()
+}
+
+#[test]
+fn reconnect_client() {
+    task::block_on(async {
+        zasync_executor_init!();
+        let _ = env_logger::try_init();
+
+        let endpoint = "tcp/127.0.0.1:17600";
+        let key_expr = "test/reconnect";
+        println!("[RC][01a] Opening router on {endpoint}");
+        let router = open_router(endpoint).await;
+
+        println!("[RC][01b] Opening the subscriber and publisher clients");
+        let subscriber = open_client(endpoint).await.into_arc();
+        let sub = ztimeout!(subscriber.declare_subscriber(key_expr).res_async()).unwrap();
+        let publisher = open_client(endpoint).await;
+        task::sleep(SLEEP).await;
+        ztimeout!(publisher.put(key_expr, "before").res_async()).unwrap();
+        let sample = ztimeout!(sub.recv_async()).unwrap();
+        assert_eq!(sample.value.to_string(), "before");
+
+        println!("[RC][02a] Closing router");
+        ztimeout!(router.close().res_async()).unwrap();
+        ztimeout!(async {
+            while !routers(&subscriber).await.is_empty() {
+                task::sleep(Duration::from_millis(100)).await;
+            }
+        });
+
+        // While disconnected the publications are dropped or blocked by the congestion control
+        println!("[RC][02b] Publishing while disconnected");
+        ztimeout!(subscriber
+            .put(key_expr, "dropped")
+            .congestion_control(CongestionControl::Drop)
+            .res_async())
+        .unwrap();
+        assert!(ztimeout!(subscriber
+            .put(key_expr, "dropped")
+            .congestion_control(CongestionControl::Drop)
+            .report_drops(true)
+            .res_async())
+        .is_err());
+        let blocked = {
+            let subscriber = subscriber.clone();
+            std::thread::spawn(move || {
+                subscriber
+                    .put(key_expr, "blocked")
+                    .congestion_control(CongestionControl::Block)
+                    .res_sync()
+            })
+        };
+        task::sleep(SLEEP).await;
+        assert!(!blocked.is_finished());
+
+        // The clients reconnect to the restarted router, declaring again the subscriber
+        println!("[RC][03a] Reopening router on {endpoint}");
+        let router = open_router(endpoint).await;
+        ztimeout!(async {
+            while routers(&subscriber).await != vec![router.zid()]
+                || routers(&publisher).await != vec![router.zid()]
+            {
+                task::sleep(Duration::from_millis(100)).await;
+            }
+        });
+        ztimeout!(async {
+            while !blocked.is_finished() {
+                task::sleep(Duration::from_millis(100)).await;
+            }
+        });
+        blocked.join().unwrap().unwrap();
+
+        println!("[RC][03b] Publishing after the reconnection");
+        ztimeout!(async {
+            loop {
+                publisher.put(key_expr, "after").res_async().await.unwrap();
+                match sub.recv_async().timeout(SLEEP).await {
+                    Ok(Ok(sample)) if sample.value.to_string() == "after" => break,
+                    _ => continue,
+                }
+            }
+        });
+
+        println!("[RC][04a] Closing the sessions");
+        drop(sub);
+        ztimeout!(publisher.close().res_async()).unwrap();
+        ztimeout!(router.close().res_async()).unwrap();
+    });
+}
@@ -0,0 +1,129 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use async_std::prelude::FutureExt;
+use async_std::task;
+use std::time::Duration;
+use zenoh::prelude::r#async::*;
+use zenoh::prelude::sync::SyncResolve;
+use zenoh::query::Reply;
+use zenoh_core::zasync_executor_init;
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_secs(1);
+
+macro_rules! ztimeout {
+    ($f:expr) => {
+        $f.timeout(TIMEOUT).await.unwrap()
+    };
+}
+
+async fn open_session(mode: WhatAmI, listen: &[&str], connect: &[&str]) -> Session {
+    let mut config = config::peer();
+    config.set_mode(Some(mode)).unwrap();
+    config.listen.endpoints = listen.iter().map(|e| e.parse().unwrap()).collect();
+    config.connect.endpoints = connect.iter().map(|e| e.parse().unwrap()).collect();
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    ztimeout!(zenoh::open(config).res_async()).unwrap()
+}
+
+async fn get(session: &Session, key_expr: &str, repliers: Option<&[ZenohId]>) -> Vec<Reply> {
+    let mut builder = session
+        .get(key_expr)
+        .target(QueryTarget::All)
+        .consolidation(ConsolidationMode::None);
+    if let Some(repliers) = repliers {
+        builder = builder.accept_repliers(repliers);
+    }
+    let replies = ztimeout!(builder.res_async()).unwrap();
+    let mut received = vec![];
+    while let Ok(reply) = ztimeout!(replies.recv_async()) {
+        received.push(reply);
+    }
+    received
+}
+
+#[test]
+fn repliers_attribution_and_filtering() {
+    task::block_on(async {
+        zasync_executor_init!();
+        let _ = env_logger::try_init();
+
+        let endpoint = "tcp/127.0.0.1:17551";
+        let key_expr = "test/repliers";
+
+        println!("[RP][01a] Opening router session");
+        let router = open_session(WhatAmI::Router, &[endpoint], &[]).await;
+        println!("[RP][01b] Opening the storage sessions");
+        let storage01 = open_session(WhatAmI::Client, &[], &[endpoint]).await;
+        let storage02 = open_session(WhatAmI::Client, &[], &[endpoint]).await;
+        println!("[RP][01c] Opening the querier session");
+        let querier = open_session(WhatAmI::Client, &[], &[endpoint]).await;
+
+        // Each storage replies with its own zid
+        println!("[RP][02a] Declaring the queryables");
+        let mut queryables = vec![];
+        for storage in [&storage01, &storage02] {
+            let zid = storage.zid();
+            let queryable = ztimeout!(storage
+                .declare_queryable(key_expr)
+                .callback(move |query| {
+                    let sample = Sample::new(query.key_expr().clone(), zid.to_string());
+                    query.reply(Ok(sample)).res_sync().unwrap();
+                })
+                .res_async())
+            .unwrap();
+            queryables.push(queryable);
+        }
+        task::sleep(SLEEP).await;
+
+        // The replies are attributed to the storage that sent them
+        println!("[RP][03a] Querying all the storages");
+        let replies = get(&querier, key_expr, None).await;
+        println!("[RP][03b] Replies: {replies:?}");
+        assert_eq!(replies.len(), 2);
+        for reply in replies.iter() {
+            let sample = reply.sample.as_ref().unwrap();
+            assert_eq!(sample.value.to_string(), reply.replier_id().to_string());
+            let global_id = reply.replier_global_id().unwrap();
+            assert_eq!(global_id.zid, reply.replier_id());
+            #[cfg(feature = "unstable")]
+            assert!(queryables.iter().any(|q| q.id() == global_id));
+        }
+        let mut zids = replies.iter().map(|r| r.replier_id()).collect::<Vec<_>>();
+        zids.sort();
+        let mut expected = vec![storage01.zid(), storage02.zid()];
+        expected.sort();
+        assert_eq!(zids, expected);
+
+        // Only the replies of the accepted storage are received
+        println!("[RP][04a] Querying the first storage only");
+        let replies = get(&querier, key_expr, Some(&[storage01.zid()])).await;
+        println!("[RP][04b] Replies: {replies:?}");
+        assert_eq!(replies.len(), 1);
+        assert_eq!(replies[0].replier_id(), storage01.zid());
+
+        // No reply is received when no storage is accepted
+        println!("[RP][05a] Querying no storage");
+        let replies = get(&querier, key_expr, Some(&[])).await;
+        println!("[RP][05b] Replies: {replies:?}");
+        assert!(replies.is_empty());
+
+        println!("[RP][06a] Closing sessions");
+        drop(queryables);
+        ztimeout!(querier.close().res_async()).unwrap();
+        ztimeout!(storage02.close().res_async()).unwrap();
+        ztimeout!(storage01.close().res_async()).unwrap();
+        ztimeout!(router.close().res_async()).unwrap();
+    });
+}
@@ -208,3 +208,49 @@ fn zenoh_session_multicast() {
         close_session(peer01, peer02).await;
     });
 }
+
+async fn open_session_wildcard_updates(wildcard_updates: Option<bool>) -> Session {
+    let mut config = config::peer();
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    config.set_wildcard_updates(wildcard_updates).unwrap();
+    println!("[  ][01a] Opening session with wildcard updates: {wildcard_updates:?}");
+    ztimeout!(zenoh::open(config).res_async()).unwrap()
+}
+
+#[test]
+fn zenoh_session_wildcard_updates() {
+    task::block_on(async {
+        zasync_executor_init!();
+        let _ = env_logger::try_init();
+
+        // Updates on key expressions containing wildcards are rejected by default
+        let session = open_session_wildcard_updates(None).await;
+        println!("[  ][02a] Putting and deleting on wild key expressions");
+        assert!(ztimeout!(session.put("test/wild/*", "value").res_async()).is_err());
+        assert!(ztimeout!(session.delete("test/wild/**").res_async()).is_err());
+        let publisher = ztimeout!(session.declare_publisher("test/wild/*").res_async()).unwrap();
+        assert!(ztimeout!(publisher.put("value").res_async()).is_err());
+        assert!(ztimeout!(publisher.delete().res_async()).is_err());
+        drop(publisher);
+        assert!(ztimeout!(session.put("test/wild/a", "value").res_async()).is_ok());
+        ztimeout!(session.close().res_async()).unwrap();
+
+        // They are accepted and routed once enabled
+        let session = open_session_wildcard_updates(Some(true)).await;
+        let msgs = Arc::new(AtomicUsize::new(0));
+        let c_msgs = msgs.clone();
+        let subscriber = ztimeout!(session
+            .declare_subscriber("test/wild/a")
+            .callback(move |_| {
+                c_msgs.fetch_add(1, Ordering::Relaxed);
+            })
+            .res_async())
+        .unwrap();
+        println!("[  ][02b] Putting and deleting on wild key expressions");
+        ztimeout!(session.put("test/wild/*", "value").res_async()).unwrap();
+        ztimeout!(session.delete("test/wild/**").res_async()).unwrap();
+        assert_eq!(msgs.load(Ordering::Relaxed), 2);
+        ztimeout!(subscriber.undeclare().res_async()).unwrap();
+        ztimeout!(session.close().res_async()).unwrap();
+    });
+}
@@ -0,0 +1,157 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use async_std::prelude::FutureExt;
+use async_std::task;
+use std::collections::HashMap;
+use std::time::Duration;
+use zenoh::info::{TopologyEvent, TopologySnapshot, TopologySubscriber};
+use zenoh::prelude::r#async::*;
+use zenoh_core::zasync_executor_init;
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+
+macro_rules! ztimeout {
+    ($f:expr) => {
+        $f.timeout(TIMEOUT).await.unwrap()
+    };
+}
+
+async fn open_router(listen: &str, connect: Option<&str>) -> Session {
+    let mut config = config::peer();
+    config.set_mode(Some(WhatAmI::Router)).unwrap();
+    config.listen.endpoints = vec![listen.parse().unwrap()];
+    config.connect.endpoints = connect.iter().map(|e| e.parse().unwrap()).collect();
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    ztimeout!(zenoh::open(config).res_async()).unwrap()
+}
+
+// Receives the events until the snapshot of the subscriber matches `f`
+async fn recv_until<F>(topology: &TopologySubscriber, events: &mut Vec<TopologyEvent>, f: F)
+where
+    F: Fn(&TopologySnapshot) -> bool,
+{
+    ztimeout!(async {
+        while !f(&topology.snapshot()) {
+            events.push(topology.recv_async().await.unwrap());
+        }
+    })
+}
+
+#[test]
+fn topology_three_routers() {
+    task::block_on(async {
+        zasync_executor_init!();
+        let _ = env_logger::try_init();
+
+        let (endpoint01, endpoint02) = ("tcp/127.0.0.1:17558", "tcp/127.0.0.1:17559");
+        let mut events = vec![];
+
+        // The first router only sees itself
+        println!("[TP][01a] Opening router01");
+        let router01 = open_router(endpoint01, None).await;
+        let topology = router01.topology();
+        assert_eq!(topology.snapshot(), TopologySnapshot::default());
+        let zid01 = router01.zid();
+
+        // The second router connects to the first one
+        println!("[TP][02a] Opening router02");
+        let router02 = open_router(endpoint02, Some(endpoint01)).await;
+        let zid02 = router02.zid();
+        recv_until(&topology, &mut events, |s| {
+            s.routers.contains_key(&zid02) && s.neighbors.get(&zid02) == Some(&vec![zid01])
+        })
+        .await;
+        println!("[TP][02b] router01 events: {events:?}");
+        match &events[0] {
+            TopologyEvent::RouterAdded { zid, locators } => {
+                assert_eq!(*zid, zid02);
+                assert_eq!(locators.len(), 1);
+            }
+            event => panic!("Unexpected event: {event:?}"),
+        }
+        assert_eq!(
+            topology.snapshot().neighbors,
+            HashMap::from([(zid01, vec![zid02]), (zid02, vec![zid01])])
+        );
+
+        // The third router connects to the second one, it is only seen in the linkstate
+        println!("[TP][03a] Opening router03");
+        let router03 = open_router("tcp/127.0.0.1:17560", Some(endpoint02)).await;
+        let zid03 = router03.zid();
+        let mut zids = vec![zid01, zid03];
+        zids.sort();
+        recv_until(&topology, &mut events, |s| {
+            s.neighbors.get(&zid02) == Some(&zids) && s.neighbors.get(&zid03) == Some(&vec![zid02])
+        })
+        .await;
+        println!("[TP][03b] router01 events: {events:?}");
+        assert!(!topology.snapshot().routers.contains_key(&zid03));
+
+        // The third router leaves the linkstate
+        println!("[TP][04a] Closing router03");
+        ztimeout!(router03.close().res_async()).unwrap();
+        recv_until(&topology, &mut events, |s| {
+            !s.neighbors.contains_key(&zid03) && s.neighbors.get(&zid02) == Some(&vec![zid01])
+        })
+        .await;
+        println!("[TP][04b] router01 events: {events:?}");
+
+        // The second router leaves
+        println!("[TP][05a] Closing router02");
+        ztimeout!(router02.close().res_async()).unwrap();
+        recv_until(&topology, &mut events, |s| {
+            *s == TopologySnapshot::default()
+        })
+        .await;
+        println!("[TP][05b] router01 events: {events:?}");
+
+        // The second router was added and removed once, the third one never connected
+        let membership = events
+            .iter()
+            .filter(|e| !matches!(e, TopologyEvent::LinkstateNeighborChanged { .. }))
+            .cloned()
+            .collect::<Vec<_>>();
+        assert_eq!(membership.len(), 2);
+        assert_eq!(membership[1], TopologyEvent::RouterRemoved { zid: zid02 });
+
+        // Each event changed the topology: replaying them on an empty one never repeats a state
+        let mut replay = TopologySnapshot::default();
+        for event in &events {
+            let previous = replay.clone();
+            match event {
+                TopologyEvent::RouterAdded { zid, locators } => {
+                    replay.routers.insert(*zid, locators.clone());
+                }
+                TopologyEvent::RouterRemoved { zid } => {
+                    replay.routers.remove(zid);
+                }
+                TopologyEvent::LinkstateNeighborChanged { zid, neighbors } => {
+                    if neighbors.is_empty() {
+                        replay.neighbors.remove(zid);
+                    } else {
+                        replay.neighbors.insert(*zid, neighbors.clone());
+                    }
+                }
+                event => panic!("Unexpected event: {event:?}"),
+            }
+            assert_ne!(replay, previous, "Duplicated event: {event:?}");
+        }
+        assert_eq!(replay, topology.snapshot());
+
+        // The stream ends with the runtime
+        println!("[TP][06a] Closing router01");
+        ztimeout!(router01.close().res_async()).unwrap();
+        assert!(ztimeout!(topology.recv_async()).is_none());
+    });
+}
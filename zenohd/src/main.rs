@@ -17,7 +17,7 @@ use futures::future;
 use git_version::git_version;
 use std::collections::HashSet;
 use zenoh::config::{Config, ModeDependentValue, PermissionsConf, PluginLoad, ValidatedMap};
-use zenoh::plugins::PluginsManager;
+use zenoh::plugins::{PluginApi, PluginsManager};
 use zenoh::prelude::{EndPoint, WhatAmI};
 use zenoh::runtime::{AdminSpace, Runtime};
 
@@ -28,15 +28,11 @@ lazy_static::lazy_static!(
 );
 
 const DEFAULT_LISTENER: &str = "tcp/[::]:7447";
+const DEFAULT_LOG_FILTER: &str = "z=info";
 
 fn main() {
     task::block_on(async {
-        let mut log_builder =
-            env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("z=info"));
-        #[cfg(feature = "stats")]
-        log_builder.format_timestamp_millis().init();
-        #[cfg(not(feature = "stats"))]
-        log_builder.init();
+        init_logger();
 
         log::info!("zenohd {}", *LONG_VERSION);
 
@@ -112,7 +108,8 @@ clap::Arg::new("adminspace-permissions").long("adminspace-permissions").value_na
             }
         };
 
-        for (name, path, start_result) in plugins.start_all(&runtime) {
+        let plugins_api = PluginApi::new(runtime.clone());
+        for (name, path, start_result) in plugins.start_all(&plugins_api) {
             let required = required_plugins.contains(name);
             log::info!(
                 "Starting {req} plugin \"{name}\"",
@@ -150,6 +147,25 @@ clap::Arg::new("adminspace-permissions").long("adminspace-permissions").value_na
     });
 }
 
+fn init_logger() {
+    use zenoh::logging;
+
+    // Install a reloadable filter so that it can be changed through the admin space
+    let filter = logging::env_filter(DEFAULT_LOG_FILTER);
+    let logger = || {
+        // The records are filtered by the reloadable filter only
+        let mut builder = env_logger::Builder::new();
+        builder.filter_level(log::LevelFilter::Trace);
+        #[cfg(feature = "stats")]
+        builder.format_timestamp_millis();
+        Box::new(builder.build())
+    };
+    if let Err(e) = logging::init_reloadable_logger(logger(), &filter) {
+        eprintln!("Invalid log filter '{filter}' ({e}), falling back to '{DEFAULT_LOG_FILTER}'");
+        logging::init_reloadable_logger(logger(), DEFAULT_LOG_FILTER).unwrap();
+    }
+}
+
 fn config_from_args(args: &ArgMatches) -> Config {
     let mut config = args
         .value_of("config")